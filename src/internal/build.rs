@@ -1,8 +1,26 @@
 use std::process::Command as ProcessCommand;
 
 use lazy_static::lazy_static;
+use semver::Prerelease;
+use semver::Version;
 
 lazy_static! {
+    static ref CURRENT_VERSION: Version = {
+        let mut version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+        if !version.pre.is_empty() {
+            // Check if it starts with `rc` or `beta` or `alpha`, in which case
+            // we wanna keep them, otherwise we consider we're at the version,
+            // as otherwise semver would consider `1.0.0-5-xxxx` < `1.0.0`
+            if !(version.pre.starts_with("rc")
+                || version.pre.starts_with("beta")
+                || version.pre.starts_with("alpha"))
+            {
+                // Clear prerelease
+                version.pre = Prerelease::EMPTY;
+            }
+        }
+        version
+    };
     static ref RELEASE_ARCH: String = {
         let arch = match std::env::consts::ARCH {
             "aarch64" => "arm64",
@@ -20,6 +38,12 @@ lazy_static! {
     static ref ROSETTA_AVAILABLE: bool = compute_check_rosetta_available();
 }
 
+/// Returns the version of the running omni, as it should be
+/// compared to other versions
+pub fn current_omni_version() -> &'static Version {
+    &CURRENT_VERSION
+}
+
 pub fn current_os() -> String {
     (*RELEASE_OS).clone()
 }
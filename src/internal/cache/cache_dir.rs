@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::internal::env::omni_tmpdir;
+use crate::internal::user_interface::StringColor;
+use crate::internal::utils::base62_encode;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+use crate::omni_warning;
+
+/// The resolved cache directories, so that the location written to
+/// stays the same for the whole run, and the user is only warned once
+static RESOLVED_CACHE_DIRS: Lazy<Mutex<HashMap<PathBuf, CacheDir>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A cache directory, along with the location where the cache can be
+/// written to.
+///
+/// When the cache directory is not writable (e.g. read-only home or
+/// shared filesystem), writes go to a per-user fallback location in the
+/// temporary directory, while existing entries can still be read from
+/// the cache directory. If the fallback location is not writable either,
+/// the cache is read-only for the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheDir {
+    primary: PathBuf,
+    writable: Option<PathBuf>,
+}
+
+impl CacheDir {
+    /// Resolves where the cache can be written to for the given cache
+    /// directory; the resolution is done once per run, and a warning is
+    /// shown if the cache directory cannot be written to.
+    pub fn get(primary: impl Into<PathBuf>) -> Self {
+        let primary = primary.into();
+
+        let mut resolved = match RESOLVED_CACHE_DIRS.lock() {
+            Ok(resolved) => resolved,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(cache_dir) = resolved.get(&primary) {
+            return cache_dir.clone();
+        }
+
+        let fallback_root = PathBuf::from(omni_tmpdir()).join("cache");
+        let cache_dir = Self::resolve(primary.clone(), &fallback_root);
+        cache_dir.warn_if_degraded();
+
+        resolved.insert(primary, cache_dir.clone());
+        cache_dir
+    }
+
+    /// Resolves where the cache can be written to for the given cache
+    /// directory, using the given root for the fallback locations
+    pub(crate) fn resolve(primary: PathBuf, fallback_root: &Path) -> Self {
+        if is_writable_dir(&primary) {
+            return Self {
+                writable: Some(primary.clone()),
+                primary,
+            };
+        }
+
+        // Use a different fallback directory for each cache directory,
+        // so that unrelated caches do not overwrite each other
+        let hash = blake3::hash(primary.to_string_lossy().as_bytes());
+        let fallback = fallback_root.join(&base62_encode(hash.as_bytes())[..16]);
+
+        // The fallback location is in a shared temporary directory,
+        // so make sure it is only accessible to the current user
+        let _ = create_dir_all(&fallback, PathKind::Private);
+
+        Self {
+            writable: is_writable_dir(&fallback).then_some(fallback),
+            primary,
+        }
+    }
+
+    fn warn_if_degraded(&self) {
+        match &self.writable {
+            Some(writable) if *writable == self.primary => {}
+            Some(writable) => {
+                omni_warning!(format!(
+                    "cache directory {} is not writable, using {} instead",
+                    self.primary.display().to_string().light_yellow(),
+                    writable.display().to_string().light_yellow(),
+                ));
+            }
+            None => {
+                omni_warning!(format!(
+                    "cache directory {} is not writable, cache will be read-only",
+                    self.primary.display().to_string().light_yellow(),
+                ));
+            }
+        }
+    }
+
+    /// Returns the cache directory, as configured
+    pub fn primary(&self) -> &Path {
+        &self.primary
+    }
+
+    /// Returns the directory where the cache can be written to, if any
+    pub fn writable(&self) -> Option<&Path> {
+        self.writable.as_deref()
+    }
+
+    /// Whether the cache is written to a fallback location instead of
+    /// the cache directory
+    pub fn is_fallback(&self) -> bool {
+        matches!(&self.writable, Some(writable) if *writable != self.primary)
+    }
+
+    /// Returns a cache directory for the given subdirectory, resolved
+    /// the same way as this one
+    pub fn join(&self, name: impl AsRef<Path>) -> Self {
+        Self {
+            primary: self.primary.join(name.as_ref()),
+            writable: self
+                .writable
+                .as_ref()
+                .map(|writable| writable.join(name.as_ref())),
+        }
+    }
+
+    /// Returns the path to read the given entry from: the writable
+    /// location if the entry exists there, the cache directory otherwise
+    pub fn read_path(&self, name: impl AsRef<Path>) -> PathBuf {
+        if self.is_fallback() {
+            if let Some(writable) = &self.writable {
+                let path = writable.join(name.as_ref());
+                if path.exists() {
+                    return path;
+                }
+            }
+        }
+
+        self.primary.join(name.as_ref())
+    }
+
+    /// Returns the path to write the given entry to, if the cache can
+    /// be written to
+    pub fn write_path(&self, name: impl AsRef<Path>) -> Option<PathBuf> {
+        self.writable
+            .as_ref()
+            .map(|writable| writable.join(name.as_ref()))
+    }
+}
+
+/// Whether the directory can be written to, creating it if needed;
+/// directories without write permission bits are considered read-only
+/// even if the current user could bypass the permissions.
+///
+/// This cannot depend on the configuration, as cache directories are
+/// resolved while loading it, so the directory is created with the
+/// default permissions.
+fn is_writable_dir(dir: &Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+
+    match std::fs::metadata(dir) {
+        Ok(metadata) if metadata.is_dir() && !metadata.permissions().readonly() => {}
+        _ => return false,
+    }
+
+    tempfile::tempfile_in(dir).is_ok()
+}
+
+#[cfg(test)]
+#[path = "cache_dir_test.rs"]
+mod tests;
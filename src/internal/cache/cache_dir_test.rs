@@ -0,0 +1,105 @@
+use super::*;
+
+use std::os::unix::fs::PermissionsExt;
+
+fn set_mode(path: &Path, mode: u32) {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+        .expect("failed to set permissions");
+}
+
+#[test]
+fn writable_cache_dir_is_used() {
+    let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+    let primary = tmpdir.path().join("cache");
+
+    let cache_dir = CacheDir::resolve(primary.clone(), &tmpdir.path().join("fallback"));
+
+    assert!(primary.is_dir());
+    assert!(!cache_dir.is_fallback());
+    assert_eq!(cache_dir.writable(), Some(primary.as_path()));
+    assert_eq!(cache_dir.write_path("entry"), Some(primary.join("entry")));
+    assert_eq!(cache_dir.read_path("entry"), primary.join("entry"));
+}
+
+#[test]
+fn read_only_cache_dir_falls_back() {
+    let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+    let primary = tmpdir.path().join("cache");
+    let fallback_root = tmpdir.path().join("fallback");
+
+    std::fs::create_dir(&primary).expect("failed to create cache directory");
+    std::fs::write(primary.join("existing"), "primary").expect("failed to write entry");
+    set_mode(&primary, 0o555);
+
+    let cache_dir = CacheDir::resolve(primary.clone(), &fallback_root);
+
+    assert!(cache_dir.is_fallback());
+    let writable = cache_dir
+        .writable()
+        .expect("should be writable")
+        .to_path_buf();
+    assert!(writable.starts_with(&fallback_root));
+    assert!(writable.is_dir());
+
+    // The fallback location is the same every time
+    assert_eq!(
+        CacheDir::resolve(primary.clone(), &fallback_root),
+        cache_dir
+    );
+
+    // Entries only in the cache directory are read from there
+    assert_eq!(cache_dir.read_path("existing"), primary.join("existing"));
+
+    // Entries written to the fallback location are read from there
+    let written = cache_dir
+        .write_path("existing")
+        .expect("should be writable");
+    assert_eq!(written, writable.join("existing"));
+    std::fs::write(&written, "fallback").expect("failed to write entry");
+    assert_eq!(cache_dir.read_path("existing"), written);
+
+    // Subdirectories follow the same resolution
+    let subdir = cache_dir.join("sub");
+    assert_eq!(subdir.primary(), primary.join("sub"));
+    assert_eq!(subdir.writable(), Some(writable.join("sub").as_path()));
+
+    set_mode(&primary, 0o755);
+}
+
+#[test]
+fn read_only_without_fallback() {
+    let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+    let primary = tmpdir.path().join("cache");
+    let fallback_root = tmpdir.path().join("fallback");
+
+    std::fs::create_dir(&primary).expect("failed to create cache directory");
+    set_mode(&primary, 0o555);
+
+    // A file where the fallback location should be prevents creating it
+    std::fs::write(&fallback_root, "").expect("failed to write file");
+
+    let cache_dir = CacheDir::resolve(primary.clone(), &fallback_root);
+
+    assert!(!cache_dir.is_fallback());
+    assert_eq!(cache_dir.writable(), None);
+    assert_eq!(cache_dir.write_path("entry"), None);
+    assert_eq!(cache_dir.read_path("entry"), primary.join("entry"));
+
+    set_mode(&primary, 0o755);
+}
+
+#[test]
+fn resolution_is_kept_for_the_run() {
+    let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+    let primary = tmpdir.path().join("cache");
+
+    let cache_dir = CacheDir::get(&primary);
+    assert!(!cache_dir.is_fallback());
+
+    // Even if the cache directory becomes read-only, the location
+    // resolved at the start of the run is kept
+    set_mode(&primary, 0o555);
+    assert_eq!(CacheDir::get(&primary), cache_dir);
+
+    set_mode(&primary, 0o755);
+}
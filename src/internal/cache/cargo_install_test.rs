@@ -1,6 +1,8 @@
 use super::*;
 
 use crate::internal::cache::database::get_conn;
+use crate::internal::cache::CachePinKind;
+use crate::internal::cache::CachePinsCache;
 use crate::internal::testutils::run_with_env;
 
 mod cargo_install_operation_cache {
@@ -275,6 +277,66 @@ mod cargo_install_operation_cache {
         });
     }
 
+    #[test]
+    fn test_cleanup_skips_pinned() {
+        run_with_env(&[], || {
+            let cache = CargoInstallOperationCache::get();
+
+            // Add three versions, all old enough to be cleaned up
+            for version in ["v1.0.0", "v1.1.0", "v2.0.0"] {
+                cache
+                    .add_installed("test-crate", version)
+                    .expect("Failed to add installation");
+            }
+            let other = "critical-crate";
+            cache
+                .add_installed(other, "v1.0.0")
+                .expect("Failed to add installation");
+
+            let conn = get_conn();
+            conn.execute(
+                "UPDATE cargo_installed SET last_required_at = '1970-01-01T00:00:00.000Z'",
+                params![],
+            )
+            .expect("Failed to update last_required_at");
+
+            // Pin a single version of the tool, and all the versions of the other
+            let pins = CachePinsCache::get();
+            pins.pin(CachePinKind::CargoInstall, "test-crate", Some("v1.1.0"))
+                .expect("Failed to pin version");
+            pins.pin(CachePinKind::CargoInstall, other, None)
+                .expect("Failed to pin all versions");
+
+            // Run cleanup
+            cache.cleanup().expect("Failed to cleanup");
+
+            // Verify that only the pinned versions were kept
+            let mut kept = cache
+                .list_installed()
+                .expect("Failed to list installed")
+                .into_iter()
+                .map(|installed| (installed.crate_name, installed.version))
+                .collect::<Vec<_>>();
+            kept.sort();
+            assert_eq!(
+                kept,
+                vec![
+                    (other.to_string(), "v1.0.0".to_string()),
+                    ("test-crate".to_string(), "v1.1.0".to_string()),
+                ]
+            );
+
+            // Unpinning makes the version eligible for cleanup again
+            pins.unpin(CachePinKind::CargoInstall, "test-crate", Some("v1.1.0"))
+                .expect("Failed to unpin");
+            cache.cleanup().expect("Failed to cleanup");
+
+            let installed = cache.list_installed().expect("Failed to list installed");
+            assert_eq!(installed.len(), 1);
+            assert_eq!(installed[0].crate_name, other);
+        });
+    }
+
     #[test]
     fn test_cleanup_cascade() {
         run_with_env(&[], || {
@@ -0,0 +1,83 @@
+use std::io::Write;
+
+use crate::internal::cache::CacheDir;
+use crate::internal::env::cache_home;
+use crate::internal::utils::base62_encode;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+
+/// On-disk cache of the generated completion scripts, so that the
+/// scripts loaded by the shell integration each time the current
+/// directory changes do not require loading all the commands again.
+///
+/// There is one entry per location, e.g. per work directory, holding
+/// the script along with the key of the inputs it was generated from;
+/// an entry is only used if it was generated for the expected key, and
+/// is replaced when a script is generated for a new key.
+#[derive(Debug)]
+pub struct CompletionsCache {
+    dir: CacheDir,
+}
+
+impl CompletionsCache {
+    pub fn get() -> Self {
+        Self::new(CacheDir::get(cache_home()).join("completions"))
+    }
+
+    fn new(dir: CacheDir) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the cached script for the given location, if it was
+    /// generated for the given key
+    pub fn read(&self, location: &str, key: &str) -> Option<String> {
+        let contents =
+            std::fs::read_to_string(self.dir.read_path(self.entry_name(location))).ok()?;
+        let (header, script) = contents.split_once('\n')?;
+        if header != Self::header(key) {
+            return None;
+        }
+
+        Some(script.to_string())
+    }
+
+    /// Stores the script generated for the given location and key; any
+    /// failure is ignored, the script being generated again next time
+    pub fn write(&self, location: &str, key: &str, script: &str) {
+        // Nothing to do if the cache is read-only
+        let dir = match self.dir.writable() {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        if create_dir_all(dir, PathKind::Private).is_err() {
+            return;
+        }
+
+        // Write to a temporary file first and then move it in place,
+        // so that concurrent readers never see a partial entry
+        let mut tmp_file = match tempfile::NamedTempFile::new_in(dir) {
+            Ok(tmp_file) => tmp_file,
+            Err(_) => return,
+        };
+        if writeln!(tmp_file, "{}", Self::header(key)).is_err()
+            || tmp_file.write_all(script.as_bytes()).is_err()
+        {
+            return;
+        }
+        let _ = tmp_file.persist(dir.join(self.entry_name(location)));
+    }
+
+    fn header(key: &str) -> String {
+        format!("# omni completions cache key: {key}")
+    }
+
+    fn entry_name(&self, location: &str) -> String {
+        let hash = blake3::hash(location.as_bytes());
+        base62_encode(hash.as_bytes())[..20].to_string()
+    }
+}
+
+#[cfg(test)]
+#[path = "completions_test.rs"]
+mod tests;
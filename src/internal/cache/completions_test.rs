@@ -0,0 +1,54 @@
+use super::*;
+
+fn cache(tmpdir: &tempfile::TempDir) -> CompletionsCache {
+    CompletionsCache::new(CacheDir::resolve(
+        tmpdir.path().join("cache"),
+        &tmpdir.path().join("fallback"),
+    ))
+}
+
+mod completions_cache {
+    use super::*;
+
+    #[test]
+    fn reads_the_script_written_for_the_key() {
+        let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+        let cache = cache(&tmpdir);
+
+        assert_eq!(cache.read("/workdir", "key"), None);
+
+        cache.write("/workdir", "key", "complete -c omni\n");
+        assert_eq!(
+            cache.read("/workdir", "key"),
+            Some("complete -c omni\n".to_string())
+        );
+    }
+
+    #[test]
+    fn ignores_the_script_written_for_another_key() {
+        let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+        let cache = cache(&tmpdir);
+
+        cache.write("/workdir", "old-key", "complete -c omni\n");
+        assert_eq!(cache.read("/workdir", "new-key"), None);
+
+        cache.write("/workdir", "new-key", "complete -c omni -f\n");
+        assert_eq!(cache.read("/workdir", "old-key"), None);
+        assert_eq!(
+            cache.read("/workdir", "new-key"),
+            Some("complete -c omni -f\n".to_string())
+        );
+    }
+
+    #[test]
+    fn entries_are_per_location() {
+        let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+        let cache = cache(&tmpdir);
+
+        cache.write("/workdir", "key", "first\n");
+        cache.write("/other", "key", "second\n");
+
+        assert_eq!(cache.read("/workdir", "key"), Some("first\n".to_string()));
+        assert_eq!(cache.read("/other", "key"), Some("second\n".to_string()));
+    }
+}
@@ -0,0 +1,364 @@
+//! Batching of the writes to the cache database
+//!
+//! While a batch is open, the writes of all the cache managers of the
+//! process are buffered in memory instead of being committed one by one,
+//! and are written to the database in a single short transaction at
+//! checkpoints. No transaction is kept open between checkpoints, so
+//! other processes are never blocked by a batch for longer than the
+//! time needed to write the buffered writes.
+//!
+//! The reads of the process see the buffered writes: the first write
+//! buffered since the last checkpoint copies the database to a private
+//! in-memory database, to which each buffered write is then applied,
+//! and from which the reads of the process are served until the next
+//! checkpoint. The database is thus only read while a batch is open,
+//! and never locked for writing outside of checkpoints. A write returns
+//! the number of rows it changed in that copy. Copying the database
+//! once per checkpoint is only worth it while the database is small:
+//! above `MAX_OVERLAY_SIZE`, no copy is made, and the writes are
+//! committed directly, as they would be outside of a batch.
+//!
+//! At a checkpoint, the buffered writes are run again on the database.
+//! The current time they use is pinned to the time they were buffered,
+//! so that they write the same values, and change the same rows unless
+//! other processes changed those in the meantime. Since a cache
+//! transaction can read and write in any order, it cannot be buffered,
+//! and is instead committed right away with the buffered writes.
+//!
+//! The durability is at the checkpoint: if the process stops without
+//! reaching a checkpoint, e.g. if it crashes or gets killed, the writes
+//! since the last checkpoint are lost and the database stays as it was
+//! at that checkpoint. Other processes only see the writes once they
+//! are committed at a checkpoint.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+use rusqlite::backup::Backup;
+use rusqlite::types::ToSqlOutput;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use rusqlite::ToSql;
+
+use crate::internal::cache::database::get_conn;
+use crate::internal::cache::CacheManagerError;
+use crate::internal::user_interface::StringColor;
+use crate::omni_warning;
+
+/// The batch currently open for the process, if any
+static BATCH: Mutex<Option<BatchState>> = Mutex::new(None);
+
+/// The number of commits of writes to the database, whether they
+/// happened at a checkpoint of a batch or outside of any batch
+static COMMITS: AtomicUsize = AtomicUsize::new(0);
+
+/// The size of the database, in bytes, above which the writes of a
+/// batch are committed directly instead of being buffered, since the
+/// database would have to be copied in memory after each checkpoint
+const MAX_OVERLAY_SIZE: i64 = 16 * 1024 * 1024;
+
+/// A write buffered in a batch, with its parameters
+struct PendingWrite {
+    query: String,
+    params: Vec<Value>,
+}
+
+struct BatchState {
+    /// The number of guards currently holding the batch open
+    depth: usize,
+    /// The writes buffered since the last checkpoint
+    pending: Vec<PendingWrite>,
+    /// The in-memory copy of the database to which the buffered writes
+    /// are applied, if any write was buffered since the last checkpoint
+    overlay: Option<Connection>,
+    /// The size of the database above which no copy is made
+    max_overlay_size: i64,
+}
+
+impl BatchState {
+    /// Writes the buffered writes to the database in a single
+    /// transaction; if that fails, they stay buffered
+    fn flush(&mut self) -> Result<(), CacheManagerError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        self.commit_with(|_| Ok(()))
+    }
+
+    /// Writes the buffered writes to the database, followed by the
+    /// writes of the given function, in a single transaction; the
+    /// buffered writes are only discarded once committed
+    fn commit_with<T, F>(&mut self, f: F) -> Result<T, CacheManagerError>
+    where
+        F: FnOnce(&Connection) -> Result<T, CacheManagerError>,
+    {
+        let conn = get_conn();
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+
+        let result = apply_pending(&conn, &self.pending)
+            .and_then(|_| f(&conn))
+            .and_then(|result| {
+                conn.execute_batch("COMMIT")?;
+                Ok(result)
+            });
+
+        match result {
+            Ok(result) => {
+                self.pending.clear();
+                self.overlay = None;
+                record_commit();
+                Ok(result)
+            }
+            Err(err) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns the in-memory copy of the database with the buffered
+    /// writes applied, copying the database if no write was buffered
+    /// since the last checkpoint; returns `None` if the database is too
+    /// large to be copied
+    fn overlay(&mut self) -> Result<Option<&Connection>, CacheManagerError> {
+        if self.overlay.is_none() {
+            let conn = get_conn();
+            let size: i64 = conn.query_row(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                [],
+                |row| row.get(0),
+            )?;
+            if size > self.max_overlay_size {
+                return Ok(None);
+            }
+
+            let mut overlay = Connection::open_in_memory()?;
+            Backup::new(&conn, &mut overlay)?.run_to_completion(
+                1024,
+                Duration::from_millis(10),
+                None,
+            )?;
+            self.overlay = Some(overlay);
+        }
+
+        Ok(self.overlay.as_ref())
+    }
+}
+
+/// A guard over the batch of the process; the buffered writes are
+/// committed and the batch closed when the last guard is dropped
+#[must_use = "the batch is closed when the guard is dropped"]
+#[derive(Debug)]
+pub struct CacheBatch(());
+
+impl CacheBatch {
+    /// Opens a batch for the process, or joins the one that is already
+    /// open
+    pub fn begin() -> Self {
+        let mut batch = lock_batch();
+        match batch.as_mut() {
+            Some(state) => state.depth += 1,
+            None => {
+                *batch = Some(BatchState {
+                    depth: 1,
+                    pending: vec![],
+                    overlay: None,
+                    max_overlay_size: MAX_OVERLAY_SIZE,
+                })
+            }
+        }
+
+        Self(())
+    }
+
+    /// Commits the writes buffered in the batch of the process, if any,
+    /// and keeps the batch open for the following writes
+    pub fn checkpoint() -> Result<(), CacheManagerError> {
+        match lock_batch().as_mut() {
+            Some(state) => state.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Discards the writes since the last checkpoint and closes the
+    /// batch, as if the process stopped before the next checkpoint
+    #[cfg(test)]
+    pub(crate) fn abort() {
+        lock_batch().take();
+    }
+
+    /// Sets the size of the database above which the writes of the
+    /// batch of the process are committed directly
+    #[cfg(test)]
+    pub(crate) fn set_max_overlay_size(size: i64) {
+        if let Some(state) = lock_batch().as_mut() {
+            state.max_overlay_size = size;
+        }
+    }
+
+    /// Returns the number of commits of writes to the database since
+    /// the start of the process
+    #[cfg(test)]
+    pub(crate) fn commits() -> usize {
+        COMMITS.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for CacheBatch {
+    fn drop(&mut self) {
+        let mut batch = lock_batch();
+        let state = match batch.as_mut() {
+            Some(state) => state,
+            None => return,
+        };
+
+        state.depth = state.depth.saturating_sub(1);
+        if state.depth > 0 {
+            return;
+        }
+
+        if let Some(mut state) = batch.take() {
+            if let Err(err) = state.flush() {
+                omni_warning!(format!("failed to update cache: {}", err));
+            }
+        }
+    }
+}
+
+/// Returns whether a batch is currently open for the process
+pub(super) fn is_batching() -> bool {
+    lock_batch().is_some()
+}
+
+/// Runs the given read function on the database as it would be with
+/// the writes buffered in the batch of the process; if no write was
+/// buffered, or if the batch got closed in the meantime, the database
+/// is read directly
+pub(super) fn read<T, F>(f: F) -> Result<T, CacheManagerError>
+where
+    F: FnOnce(&Connection) -> Result<T, CacheManagerError>,
+{
+    let batch = lock_batch();
+    match batch.as_ref().and_then(|state| state.overlay.as_ref()) {
+        Some(overlay) => f(overlay),
+        None => {
+            drop(batch);
+            f(&get_conn())
+        }
+    }
+}
+
+/// Buffers the given write in the batch of the process, returning the
+/// number of rows it changes; if the batch got closed in the meantime,
+/// or if the database is too large to be copied, the write is committed
+/// directly
+pub(super) fn execute(query: &str, params: &[&dyn ToSql]) -> Result<usize, CacheManagerError> {
+    let mut batch = lock_batch();
+    let state = match batch.as_mut() {
+        Some(state) => state,
+        None => {
+            drop(batch);
+            let changed = get_conn().execute(query, params)?;
+            record_commit();
+            return Ok(changed);
+        }
+    };
+
+    // No write is buffered when there is no copy of the database, so
+    // committing the write directly keeps the writes in order
+    let overlay = match state.overlay()? {
+        Some(overlay) => overlay,
+        None => {
+            let changed = get_conn().execute(query, params)?;
+            record_commit();
+            return Ok(changed);
+        }
+    };
+
+    let params = owned_params(params)?;
+    let query = pin_current_time(overlay, query)?;
+    let changed = overlay.execute(&query, rusqlite::params_from_iter(params.iter()))?;
+    state.pending.push(PendingWrite { query, params });
+
+    Ok(changed)
+}
+
+/// Runs the given function in a transaction, committed right away with
+/// the writes buffered in the batch of the process
+pub(super) fn transaction<T, F>(f: F) -> Result<T, CacheManagerError>
+where
+    F: FnOnce(&Connection) -> Result<T, CacheManagerError>,
+{
+    let mut batch = lock_batch();
+    match batch.as_mut() {
+        Some(state) => state.commit_with(f),
+        None => {
+            drop(batch);
+            let mut conn = get_conn();
+            let tx = conn.transaction()?;
+            let result = f(&tx)?;
+            tx.commit()?;
+            record_commit();
+            Ok(result)
+        }
+    }
+}
+
+/// Applies the buffered writes on the given connection
+fn apply_pending(conn: &Connection, pending: &[PendingWrite]) -> Result<(), CacheManagerError> {
+    for write in pending {
+        conn.execute(
+            &write.query,
+            rusqlite::params_from_iter(write.params.iter()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the given query with its uses of the current time replaced
+/// by the current time of the given connection, so that running it
+/// again at a checkpoint writes the same values
+fn pin_current_time(conn: &Connection, query: &str) -> Result<String, CacheManagerError> {
+    if !query.contains("'now'") {
+        return Ok(query.to_string());
+    }
+
+    let now: String = conn.query_row("SELECT strftime('%Y-%m-%d %H:%M:%f', 'now')", [], |row| {
+        row.get(0)
+    })?;
+
+    Ok(query.replace("'now'", &format!("'{now}'")))
+}
+
+/// Copies the parameters of a write, so that it can be buffered
+fn owned_params(params: &[&dyn ToSql]) -> Result<Vec<Value>, CacheManagerError> {
+    params
+        .iter()
+        .map(|param| match param.to_sql()? {
+            ToSqlOutput::Borrowed(value) => Ok(value.into()),
+            ToSqlOutput::Owned(value) => Ok(value),
+            _ => Err(CacheManagerError::Other(
+                "unsupported parameter for a batched write".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// Records a commit of writes to the database
+pub(super) fn record_commit() {
+    COMMITS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Locks the batch of the process; the state of the batch stays
+/// consistent even if a thread panicked while holding the lock
+fn lock_batch() -> MutexGuard<'static, Option<BatchState>> {
+    BATCH.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+#[path = "batch_test.rs"]
+mod tests;
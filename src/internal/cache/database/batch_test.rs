@@ -0,0 +1,276 @@
+use super::*;
+
+use rusqlite::params;
+
+use crate::internal::cache::CacheManager;
+use crate::internal::testutils::run_with_env;
+
+fn trust(workdir_id: &str) {
+    CacheManager::get()
+        .execute(
+            "INSERT INTO workdir_trusted (workdir_id) VALUES (?1)",
+            params![workdir_id],
+        )
+        .expect("failed to insert workdir");
+}
+
+fn trusted() -> Vec<String> {
+    let cache = CacheManager::get();
+    let count: usize = cache
+        .query_row("SELECT COUNT(*) FROM workdir_trusted", &[], |row| {
+            row.get(0)
+        })
+        .expect("failed to count workdirs");
+
+    (0..count)
+        .map(|idx| {
+            cache
+                .query_row(
+                    "SELECT workdir_id FROM workdir_trusted ORDER BY workdir_id LIMIT 1 OFFSET ?1",
+                    params![idx as i64],
+                    |row| row.get(0),
+                )
+                .expect("failed to read workdir")
+        })
+        .collect()
+}
+
+mod cache_batch {
+    use super::*;
+
+    #[test]
+    fn test_without_batch_commits_each_write() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            for idx in 0..6 {
+                trust(&format!("workdir{idx}"));
+            }
+
+            assert_eq!(CacheBatch::commits() - before, 6);
+        });
+    }
+
+    #[test]
+    fn test_batch_commits_at_checkpoints() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            let batch = CacheBatch::begin();
+            for group in 0..3 {
+                trust(&format!("workdir{group}a"));
+                trust(&format!("workdir{group}b"));
+                CacheBatch::checkpoint().expect("failed to checkpoint");
+            }
+            drop(batch);
+
+            // The last checkpoint left nothing to commit when closing
+            assert_eq!(CacheBatch::commits() - before, 3);
+            assert_eq!(trusted().len(), 6);
+        });
+    }
+
+    #[test]
+    fn test_batch_commits_pending_writes_when_closed() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            let batch = CacheBatch::begin();
+            trust("workdir1");
+            trust("workdir2");
+            drop(batch);
+
+            assert_eq!(CacheBatch::commits() - before, 1);
+            assert_eq!(trusted(), vec!["workdir1", "workdir2"]);
+        });
+    }
+
+    #[test]
+    fn test_checkpoint_without_writes_does_not_commit() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            let batch = CacheBatch::begin();
+            CacheBatch::checkpoint().expect("failed to checkpoint");
+            CacheBatch::checkpoint().expect("failed to checkpoint");
+            drop(batch);
+
+            assert_eq!(CacheBatch::commits() - before, 0);
+        });
+    }
+
+    #[test]
+    fn test_reads_see_pending_writes() {
+        run_with_env(&[], || {
+            let _batch = CacheBatch::begin();
+            trust("workdir1");
+
+            assert_eq!(trusted(), vec!["workdir1"]);
+        });
+    }
+
+    #[test]
+    fn test_abort_keeps_completed_checkpoints_only() {
+        run_with_env(&[], || {
+            let batch = CacheBatch::begin();
+            trust("workdir1");
+            CacheBatch::checkpoint().expect("failed to checkpoint");
+            trust("workdir2");
+            CacheBatch::checkpoint().expect("failed to checkpoint");
+            trust("workdir3");
+
+            // Simulate the process stopping before the next checkpoint
+            CacheBatch::abort();
+            drop(batch);
+
+            assert!(!is_batching());
+            assert_eq!(trusted(), vec!["workdir1", "workdir2"]);
+        });
+    }
+
+    #[test]
+    fn test_nested_batches_close_with_the_outermost() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            let outer = CacheBatch::begin();
+            let inner = CacheBatch::begin();
+            trust("workdir1");
+            drop(inner);
+
+            assert!(is_batching());
+            assert_eq!(CacheBatch::commits() - before, 0);
+
+            drop(outer);
+
+            assert!(!is_batching());
+            assert_eq!(CacheBatch::commits() - before, 1);
+        });
+    }
+
+    #[test]
+    fn test_failed_transaction_in_batch_is_rolled_back() {
+        run_with_env(&[], || {
+            let _batch = CacheBatch::begin();
+            trust("workdir1");
+
+            let result: Result<(), CacheManagerError> = CacheManager::get().transaction(|conn| {
+                conn.execute(
+                    "INSERT INTO workdir_trusted (workdir_id) VALUES (?1)",
+                    params!["workdir2"],
+                )?;
+                Err(CacheManagerError::Other("failed".to_string()))
+            });
+
+            assert!(result.is_err());
+            assert_eq!(trusted(), vec!["workdir1"]);
+        });
+    }
+
+    #[test]
+    fn test_transaction_in_batch_commits_pending_writes() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            let batch = CacheBatch::begin();
+            trust("workdir1");
+
+            CacheManager::get()
+                .transaction(|conn| {
+                    conn.execute(
+                        "INSERT INTO workdir_trusted (workdir_id) VALUES (?1)",
+                        params!["workdir2"],
+                    )?;
+                    Ok(())
+                })
+                .expect("failed to run transaction");
+            assert_eq!(CacheBatch::commits() - before, 1);
+
+            // Both writes were committed, and are kept even if the
+            // process stops before the next checkpoint
+            CacheBatch::abort();
+            drop(batch);
+
+            assert_eq!(trusted(), vec!["workdir1", "workdir2"]);
+        });
+    }
+
+    #[test]
+    fn test_writes_in_batch_return_changed_rows() {
+        run_with_env(&[], || {
+            let _batch = CacheBatch::begin();
+            trust("workdir1");
+
+            let removed = CacheManager::get()
+                .execute(
+                    "DELETE FROM workdir_trusted WHERE workdir_id = ?1",
+                    params!["workdir1"],
+                )
+                .expect("failed to delete workdir");
+
+            assert_eq!(removed, 1);
+            assert!(trusted().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_other_connections_can_write_while_batch_is_open() {
+        run_with_env(&[], || {
+            let batch = CacheBatch::begin();
+            trust("workdir1");
+
+            // Reading with buffered writes does not lock the database
+            // for the other connections, even during the read
+            read(|_| {
+                get_conn().execute(
+                    "INSERT INTO workdir_trusted (workdir_id) VALUES (?1)",
+                    params!["workdir2"],
+                )?;
+                Ok(())
+            })
+            .expect("failed to write from another connection");
+            assert_eq!(trusted(), vec!["workdir1"]);
+
+            trust("workdir3");
+            drop(batch);
+
+            assert_eq!(trusted(), vec!["workdir1", "workdir2", "workdir3"]);
+        });
+    }
+
+    #[test]
+    fn test_large_database_writes_are_committed_directly() {
+        run_with_env(&[], || {
+            let before = CacheBatch::commits();
+            let batch = CacheBatch::begin();
+            CacheBatch::set_max_overlay_size(0);
+            trust("workdir1");
+            trust("workdir2");
+
+            assert_eq!(CacheBatch::commits() - before, 2);
+            assert!(lock_batch()
+                .as_ref()
+                .is_some_and(|state| state.overlay.is_none()));
+
+            // The writes were committed, and are kept even if the
+            // process stops before the next checkpoint
+            CacheBatch::abort();
+            drop(batch);
+
+            assert_eq!(trusted(), vec!["workdir1", "workdir2"]);
+        });
+    }
+
+    #[test]
+    fn test_writes_keep_the_time_they_were_buffered() {
+        run_with_env(&[], || {
+            let batch = CacheBatch::begin();
+            CacheManager::get()
+                .execute(
+                    "INSERT INTO workdir_trusted (workdir_id) VALUES (strftime('%Y-%m-%d %H:%M:%f', 'now'))",
+                    &[],
+                )
+                .expect("failed to insert workdir");
+            let buffered = trusted();
+
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            drop(batch);
+
+            assert_eq!(trusted(), buffered);
+        });
+    }
+}
@@ -2,6 +2,7 @@ use rusqlite::Connection;
 use rusqlite::Result as SqliteResult;
 use rusqlite::Row;
 
+use crate::internal::cache::database::batch;
 use crate::internal::cache::database::get_conn;
 use crate::internal::cache::database::FromRow;
 use crate::internal::cache::database::RowExt;
@@ -11,7 +12,16 @@ use crate::internal::cache::CacheManagerError;
 /// The cache manager
 #[derive(Debug)]
 pub struct CacheManager {
-    pub conn: SqliteConnection,
+    conn: ManagerConnection,
+}
+
+/// The connection used by a cache manager
+#[derive(Debug)]
+enum ManagerConnection {
+    /// A connection of its own from the pool
+    Pooled(SqliteConnection),
+    /// The connection of the batch open for the process
+    Batched,
 }
 
 impl CacheManager {
@@ -20,21 +30,42 @@ impl CacheManager {
     }
 
     fn new() -> Result<Self, CacheManagerError> {
-        let conn = get_conn();
+        let conn = if batch::is_batching() {
+            ManagerConnection::Batched
+        } else {
+            ManagerConnection::Pooled(get_conn())
+        };
         let manager = CacheManager { conn };
 
         Ok(manager)
     }
 
+    /// Runs the given read function with the connection of the manager
+    fn with_conn<T, F>(&self, f: F) -> Result<T, CacheManagerError>
+    where
+        F: FnOnce(&Connection) -> Result<T, CacheManagerError>,
+    {
+        match &self.conn {
+            ManagerConnection::Pooled(conn) => f(conn),
+            ManagerConnection::Batched => batch::read(f),
+        }
+    }
+
     pub fn transaction<F, T>(&mut self, f: F) -> Result<T, CacheManagerError>
     where
         F: FnOnce(&Connection) -> Result<T, CacheManagerError>,
     {
-        let tx = self.conn.transaction()?;
+        let conn = match &mut self.conn {
+            ManagerConnection::Pooled(conn) => conn,
+            ManagerConnection::Batched => return batch::transaction(f),
+        };
+
+        let tx = conn.transaction()?;
         let result = f(&tx);
         match result {
             Ok(result) => {
                 tx.commit()?;
+                batch::record_commit();
                 Ok(result)
             }
             Err(e) => {
@@ -53,9 +84,10 @@ impl CacheManager {
     where
         F: FnOnce(&Row) -> SqliteResult<T>,
     {
-        self.conn
-            .query_row(query, params, f)
-            .map_err(CacheManagerError::from)
+        self.with_conn(|conn| {
+            conn.query_row(query, params, f)
+                .map_err(CacheManagerError::from)
+        })
     }
 
     pub fn execute(
@@ -63,9 +95,14 @@ impl CacheManager {
         query: &str,
         params: &[&dyn rusqlite::ToSql],
     ) -> Result<usize, CacheManagerError> {
-        self.conn
-            .execute(query, params)
-            .map_err(CacheManagerError::from)
+        match &self.conn {
+            ManagerConnection::Pooled(conn) => {
+                let changed = conn.execute(query, params)?;
+                batch::record_commit();
+                Ok(changed)
+            }
+            ManagerConnection::Batched => batch::execute(query, params),
+        }
     }
 }
 
@@ -75,7 +112,7 @@ impl RowExt for CacheManager {
         query: &str,
         params: &[&dyn rusqlite::ToSql],
     ) -> Result<Vec<T>, CacheManagerError> {
-        self.conn.query_as(query, params)
+        self.with_conn(|conn| conn.query_as(query, params))
     }
 
     fn query_one<T: FromRow>(
@@ -83,7 +120,7 @@ impl RowExt for CacheManager {
         query: &str,
         params: &[&dyn rusqlite::ToSql],
     ) -> Result<T, CacheManagerError> {
-        self.conn.query_one(query, params)
+        self.with_conn(|conn| conn.query_one(query, params))
     }
 
     fn query_one_optional<T: FromRow>(
@@ -91,6 +128,6 @@ impl RowExt for CacheManager {
         query: &str,
         params: &[&dyn rusqlite::ToSql],
     ) -> Result<Option<T>, CacheManagerError> {
-        self.conn.query_one_optional(query, params)
+        self.with_conn(|conn| conn.query_one_optional(query, params))
     }
 }
@@ -1,3 +1,4 @@
+mod batch;
 mod errors;
 mod from_row;
 mod manager;
@@ -11,6 +12,7 @@ use upgrade::upgrade_database;
 pub(super) use from_row::FromRow;
 pub(super) use row_ext::RowExt;
 
+pub(crate) use batch::CacheBatch;
 pub(crate) use errors::CacheManagerError;
 pub(crate) use manager::CacheManager;
 
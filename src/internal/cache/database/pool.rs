@@ -16,6 +16,8 @@ cfg_if::cfg_if! {
         use std::collections::HashMap;
         use std::sync::Mutex;
 
+        use crate::internal::cache::database::CacheBatch;
+
         lazy_static! {
             // Map of test IDs to their respective pools
             static ref TEST_POOLS: Mutex<HashMap<String, SqlitePool>> = Mutex::new(HashMap::new());
@@ -57,29 +59,42 @@ cfg_if::cfg_if! {
 
         // Helper to clean up a test's pool
         pub(crate) fn cleanup_test_pool() {
+            // Do not leave a batch open on the pool for the next tests
+            CacheBatch::abort();
+
             if let Ok(test_id) = std::env::var("TEST_POOL_ID") {
                 let mut pools = TEST_POOLS.lock().unwrap();
                 pools.remove(&test_id);
             }
         }
     } else {
-        use std::path::PathBuf;
+        use rusqlite::OpenFlags;
 
+        use crate::internal::cache::CacheDir;
         use crate::internal::config::global_config;
+        use crate::internal::user_interface::StringColor;
+        use crate::internal::utils::PathKind;
+        use crate::internal::utils::PathPermissions;
+        use crate::omni_warning;
 
         lazy_static! {
             static ref SQLITE_POOL: SqlitePool = {
-                let cache_dir_path = PathBuf::from(global_config().cache.path.clone());
-                let db_path = cache_dir_path.join("cache.db");
-
-                // If the database file does not exist, make sure its parent directory does
-                if !db_path.exists() {
-                    if let Some(parent) = db_path.parent() {
-                        std::fs::create_dir_all(parent).expect("Failed to create cache directory");
-                    }
+                let cache_dir = CacheDir::get(global_config().cache.path.clone());
+                let primary_db_path = cache_dir.primary().join("cache.db");
+
+                let db_path = match cache_dir.write_path("cache.db") {
+                    Some(db_path) => db_path,
+                    None => return read_only_sqlite_pool(&primary_db_path),
+                };
+
+                // When writing to the fallback location, start from the
+                // contents of the existing database so they are still
+                // available for the run
+                if cache_dir.is_fallback() && !db_path.exists() && primary_db_path.exists() {
+                    let _ = std::fs::copy(&primary_db_path, &db_path);
                 }
 
-                let manager = SqliteConnectionManager::file(db_path);
+                let manager = SqliteConnectionManager::file(&db_path);
                 let pool = R2d2Pool::builder()
                     .max_size(10)
                     .build(manager)
@@ -89,10 +104,50 @@ cfg_if::cfg_if! {
                 let conn = pool.get().expect("Couldn't get connection from pool");
                 upgrade_database(&conn).expect("Failed to upgrade database");
 
+                // The database holds credential-adjacent data, such as the
+                // environment of the work directories, so keep it private
+                if let Ok(db_file) = std::fs::File::open(&db_path) {
+                    let _ = PathPermissions::for_kind(PathKind::Private).apply_to_file(&db_file);
+                }
+
                 pool
             };
         }
 
+        /// Returns a pool for when the cache cannot be written to: the
+        /// existing database is opened read-only, so writes fail without
+        /// changing it, or an in-memory database is used for the run if
+        /// there is no existing database.
+        fn read_only_sqlite_pool(db_path: &std::path::Path) -> SqlitePool {
+            if db_path.exists() {
+                let manager = SqliteConnectionManager::file(db_path).with_flags(
+                    OpenFlags::SQLITE_OPEN_READ_ONLY
+                        | OpenFlags::SQLITE_OPEN_URI
+                        | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                );
+                if let Ok(pool) = R2d2Pool::builder().max_size(10).build(manager) {
+                    return pool;
+                }
+            }
+
+            omni_warning!(format!(
+                "cannot open cache database {}, using an in-memory database for this run",
+                db_path.display().to_string().light_yellow(),
+            ));
+
+            // A single connection, as each in-memory connection has its own database
+            let manager = SqliteConnectionManager::memory();
+            let pool = R2d2Pool::builder()
+                .max_size(1)
+                .build(manager)
+                .expect("Failed to create pool");
+
+            let conn = pool.get().expect("Couldn't get connection from pool");
+            upgrade_database(&conn).expect("Failed to upgrade database");
+
+            pool
+        }
+
         /// Get a pooled SQLite connection
         pub(crate) fn get_conn() -> SqliteConnection {
             SQLITE_POOL
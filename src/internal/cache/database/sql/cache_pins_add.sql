@@ -0,0 +1,15 @@
+-- Pin a cached tool so it does not get removed by the cleanup
+-- :param: ?1 - the kind of the cached tool
+-- :param: ?2 - the name of the tool
+-- :param: ?3 - the version of the tool, or NULL for all the versions
+INSERT INTO cache_pins (
+    kind,
+    name,
+    version,
+    pinned_at
+) VALUES (
+    ?1,
+    ?2,
+    COALESCE(?3, '__NULL__'),
+    strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+) ON CONFLICT(kind, name, version) DO NOTHING;
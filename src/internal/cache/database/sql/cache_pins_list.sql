@@ -0,0 +1,44 @@
+-- List the pinned cached tools, and whether they are still cached
+SELECT
+    cp.kind,
+    cp.name,
+    CASE
+        WHEN cp.version = '__NULL__' THEN NULL
+        ELSE cp.version
+    END AS version,
+    cp.pinned_at,
+    CASE cp.kind
+        WHEN 'cargo-install' THEN EXISTS (
+            SELECT 1
+            FROM cargo_installed AS ci
+            WHERE ci.crate = cp.name
+                  AND cp.version IN ('__NULL__', ci.version)
+        )
+        WHEN 'github-release' THEN EXISTS (
+            SELECT 1
+            FROM github_release_installed AS gri
+            WHERE gri.repository = cp.name
+                  AND cp.version IN ('__NULL__', gri.version)
+        )
+        WHEN 'go-install' THEN EXISTS (
+            SELECT 1
+            FROM go_installed AS gi
+            WHERE gi.import_path = cp.name
+                  AND cp.version IN ('__NULL__', gi.version)
+        )
+        WHEN 'homebrew' THEN EXISTS (
+            SELECT 1
+            FROM homebrew_install AS hi
+            WHERE hi.name = cp.name
+                  AND cp.version IN ('__NULL__', hi.version)
+        )
+        WHEN 'mise' THEN EXISTS (
+            SELECT 1
+            FROM mise_installed AS mi
+            WHERE cp.name IN (mi.tool, mi.plugin_name, mi.normalized_name)
+                  AND cp.version IN ('__NULL__', mi.version)
+        )
+        ELSE 0
+    END AS cached
+FROM cache_pins AS cp
+ORDER BY cp.kind, cp.name, cp.version;
@@ -0,0 +1,10 @@
+-- Unpin a cached tool
+-- :param: ?1 - the kind of the cached tool
+-- :param: ?2 - the name of the tool
+-- :param: ?3 - the version of the tool, or NULL for all the versions
+DELETE FROM
+    cache_pins
+WHERE
+    kind = ?1
+    AND name = ?2
+    AND version = COALESCE(?3, '__NULL__');
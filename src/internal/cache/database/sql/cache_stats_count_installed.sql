@@ -0,0 +1,10 @@
+-- Count the installed tools for each kind of cached tool
+SELECT 'cargo-install' AS kind, COUNT(*) AS installed FROM cargo_installed
+UNION ALL
+SELECT 'github-release', COUNT(*) FROM github_release_installed
+UNION ALL
+SELECT 'go-install', COUNT(*) FROM go_installed
+UNION ALL
+SELECT 'homebrew', COUNT(*) FROM homebrew_install WHERE installed
+UNION ALL
+SELECT 'mise', COUNT(*) FROM mise_installed;
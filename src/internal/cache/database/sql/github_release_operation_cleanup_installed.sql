@@ -1,4 +1,5 @@
 -- Delete the github releases that are not required by any workdir
+-- nor pinned
 -- :param1: number of seconds of the grace period before a release can be removed
 DELETE FROM github_release_installed AS gri
 WHERE NOT EXISTS (
@@ -10,4 +11,11 @@ WHERE NOT EXISTS (
 AND (
     CAST(strftime('%s', 'now') AS INTEGER) >
     (CAST(strftime('%s', last_required_at) AS INTEGER) + ?1)
+)
+AND NOT EXISTS (
+    SELECT 1
+    FROM cache_pins AS cp
+    WHERE cp.kind = 'github-release'
+          AND cp.name = gri.repository
+          AND cp.version IN ('__NULL__', gri.version)
 );
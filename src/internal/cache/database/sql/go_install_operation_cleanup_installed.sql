@@ -1,4 +1,5 @@
 -- Delete the go install versions that are not required by any workdir
+-- nor pinned
 -- :param1: number of seconds of the grace period before a version can be removed
 DELETE FROM go_installed AS gi
 WHERE NOT EXISTS (
@@ -10,4 +11,11 @@ WHERE NOT EXISTS (
 AND (
     CAST(strftime('%s', 'now') AS INTEGER) >
     (CAST(strftime('%s', last_required_at) AS INTEGER) + ?1)
+)
+AND NOT EXISTS (
+    SELECT 1
+    FROM cache_pins AS cp
+    WHERE cp.kind = 'go-install'
+          AND cp.name = gi.import_path
+          AND cp.version IN ('__NULL__', gi.version)
 );
@@ -19,4 +19,11 @@ WHERE NOT EXISTS (
 AND (
     CAST(strftime('%s', 'now') AS INTEGER) >
     (CAST(strftime('%s', last_required_at) AS INTEGER) + ?1)
+)
+AND NOT EXISTS (
+    SELECT 1
+    FROM cache_pins AS cp
+    WHERE cp.kind = 'homebrew'
+          AND cp.name = hi.name
+          AND cp.version IN ('__NULL__', hi.version)
 );
@@ -0,0 +1,5 @@
+-- Get the version of the mise binary installed and managed by omni
+-- :return - the installed version of mise, if any
+SELECT value
+FROM metadata
+WHERE key = 'mise.managed_version';
@@ -13,4 +13,11 @@ WHERE NOT EXISTS (
 AND (
     CAST(strftime('%s', 'now') AS INTEGER) >
     (CAST(strftime('%s', last_required_at) AS INTEGER) + ?1)
+)
+AND NOT EXISTS (
+    SELECT 1
+    FROM cache_pins AS cp
+    WHERE cp.kind = 'mise'
+          AND cp.name IN (mi.tool, mi.plugin_name, mi.normalized_name)
+          AND cp.version IN ('__NULL__', mi.version)
 );
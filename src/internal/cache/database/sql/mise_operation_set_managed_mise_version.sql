@@ -0,0 +1,10 @@
+-- Record the version of the mise binary installed and managed by omni
+-- :param ?1 - the installed version of mise
+INSERT INTO metadata (
+    key,
+    value
+)
+VALUES ('mise.managed_version', ?1)
+ON CONFLICT(key) DO UPDATE SET
+    value = ?1
+WHERE key = 'mise.managed_version';
@@ -0,0 +1,19 @@
+-- Add a new pipx-installed tool
+-- :param: ?1 package - the name of the installed package
+-- :param: ?2 install_id - the id of the installation of the package
+INSERT INTO pipx_installed (
+    package,
+    install_id,
+    last_required_at
+)
+VALUES (
+    ?1,
+    ?2,
+    strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+)
+ON CONFLICT (package, install_id) DO UPDATE
+SET
+    last_required_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+WHERE
+    package = ?1
+    AND install_id = ?2;
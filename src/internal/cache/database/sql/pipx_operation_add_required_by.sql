@@ -0,0 +1,15 @@
+-- Add required_by relationship for pipx-installed tool
+-- :param: ?1 package - the name of the installed package
+-- :param: ?2 install_id - the id of the installation of the package
+-- :param: ?3 env_version_id - the id of the environment version that is requiring the tool
+INSERT INTO pipx_install_required_by (
+    package,
+    install_id,
+    env_version_id
+)
+VALUES (
+    ?1,
+    ?2,
+    ?3
+)
+ON CONFLICT (package, install_id, env_version_id) DO NOTHING;
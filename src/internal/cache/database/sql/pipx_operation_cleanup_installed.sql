@@ -0,0 +1,13 @@
+-- Delete the pipx installations that are not required by any workdir
+-- :param1: number of seconds of the grace period before an installation can be removed
+DELETE FROM pipx_installed AS pi
+WHERE NOT EXISTS (
+    SELECT 1
+    FROM pipx_install_required_by AS pirb
+    WHERE pirb.package = pi.package
+          AND pirb.install_id = pi.install_id
+)
+AND (
+    CAST(strftime('%s', 'now') AS INTEGER) >
+    (CAST(strftime('%s', last_required_at) AS INTEGER) + ?1)
+);
@@ -0,0 +1,6 @@
+-- List all the pipx-installed tools
+SELECT
+    package,
+    install_id
+FROM
+    pipx_installed;
@@ -0,0 +1,24 @@
+-- Record the result of a repository lookup
+-- :param: ?1 - the fragment that was looked up
+-- :param: ?2 - whether the lookup was limited to the worktrees
+-- :param: ?3 - whether the lookup was limited to the packages
+-- :param: ?4 - the fingerprint the lookup was computed for
+-- :param: ?5 - the path of the repository found, or NULL if none
+INSERT INTO repo_lookups (
+    fragment,
+    only_worktree,
+    only_packages,
+    fingerprint,
+    path,
+    looked_up_at
+) VALUES (
+    ?1,
+    ?2,
+    ?3,
+    ?4,
+    ?5,
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+) ON CONFLICT(fragment, only_worktree, only_packages) DO UPDATE SET
+    fingerprint = excluded.fingerprint,
+    path = excluded.path,
+    looked_up_at = excluded.looked_up_at;
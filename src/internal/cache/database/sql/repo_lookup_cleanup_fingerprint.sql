@@ -0,0 +1,4 @@
+-- Remove the lookups computed for another fingerprint than the current
+-- one (?1), as they cannot be used anymore
+DELETE FROM repo_lookups
+WHERE fingerprint != ?1;
@@ -0,0 +1,2 @@
+-- Remove all the recorded lookups
+DELETE FROM repo_lookups;
@@ -0,0 +1,12 @@
+-- Get the result of a repository lookup, if computed for the fingerprint
+-- :param: ?1 - the fragment that is looked up
+-- :param: ?2 - whether the lookup is limited to the worktrees
+-- :param: ?3 - whether the lookup is limited to the packages
+-- :param: ?4 - the current fingerprint
+SELECT
+    path
+FROM repo_lookups
+WHERE fragment = ?1
+  AND only_worktree = ?2
+  AND only_packages = ?3
+  AND fingerprint = ?4;
@@ -0,0 +1,14 @@
+-- Record that a repository was chosen for a fragment
+-- :param: ?1 - the fragment that was looked up
+-- :param: ?2 - the path of the chosen repository
+INSERT INTO repo_match_choices (
+    fragment,
+    path,
+    last_chosen_at
+) VALUES (
+    ?1,
+    ?2,
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+) ON CONFLICT(fragment, path) DO UPDATE SET
+    times_chosen = times_chosen + 1,
+    last_chosen_at = excluded.last_chosen_at;
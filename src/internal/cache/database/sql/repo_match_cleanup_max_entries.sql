@@ -0,0 +1,13 @@
+-- Keep only the max number of choices (?1), the most recent ones
+DELETE FROM repo_match_choices
+WHERE rowid IN (
+    SELECT rowid FROM (
+        SELECT
+            rowid,
+            ROW_NUMBER() OVER (
+                ORDER BY last_chosen_at DESC, rowid DESC
+            ) as rn
+        FROM repo_match_choices
+    ) ranked
+    WHERE rn > ?1
+);
@@ -0,0 +1,4 @@
+-- Remove the choices older than the retention period (?1 in seconds)
+DELETE FROM repo_match_choices
+WHERE CAST(strftime('%s', 'now') AS INTEGER) >
+      (CAST(strftime('%s', last_chosen_at) AS INTEGER) + ?1);
@@ -0,0 +1,2 @@
+-- Remove all the recorded choices
+DELETE FROM repo_match_choices;
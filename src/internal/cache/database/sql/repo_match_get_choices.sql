@@ -0,0 +1,7 @@
+-- Get the repositories previously chosen for a fragment
+-- :param: ?1 - the fragment that is looked up
+SELECT
+    path,
+    times_chosen
+FROM repo_match_choices
+WHERE fragment = ?1;
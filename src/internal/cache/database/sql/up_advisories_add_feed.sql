@@ -0,0 +1,19 @@
+-- Cache the advisories of a given feed
+-- :param: ?1 url - the url of the feed
+-- :param: ?2 advisories - JSON array of UpAdvisory
+INSERT INTO up_advisory_feeds (
+    url,
+    advisories,
+    fetched_at
+)
+VALUES (
+    ?1,
+    ?2,
+    strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+)
+ON CONFLICT (url) DO UPDATE
+SET
+    advisories = ?2,
+    fetched_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+WHERE
+    url = ?1;
@@ -0,0 +1,9 @@
+-- Get the advisories cached for a given feed
+-- :param: ?1 url - the url of the feed
+SELECT
+    advisories,
+    fetched_at
+FROM
+    up_advisory_feeds
+WHERE
+    url = ?1;
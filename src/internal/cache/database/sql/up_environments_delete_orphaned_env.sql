@@ -1,4 +1,8 @@
--- Delete orphaned environment versions
+-- Delete orphaned environment versions; the environments that are not
+-- assigned to any work directory are kept during the retention period
+-- since they were last used
+-- :param1: the pattern matching the ids of those environments
+-- :param2: the retention period in seconds, or 0 to keep them forever
 DELETE FROM env_versions AS ev
 WHERE NOT EXISTS (
     SELECT 1 FROM (
@@ -7,4 +11,11 @@ WHERE NOT EXISTS (
         SELECT env_version_id FROM env_history
     ) AS combined
     WHERE combined.env_version_id = ev.env_version_id
+)
+AND NOT (
+    ev.env_version_id GLOB ?1
+    AND (
+        ?2 = 0
+        OR strftime('%s', ev.last_assigned_at) >= strftime('%s', 'now') - ?2
+    )
 );
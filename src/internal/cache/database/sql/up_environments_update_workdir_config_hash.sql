@@ -0,0 +1,10 @@
+-- Update the config hash of the environment assigned to a workdir
+-- ?1: workdir_id
+-- ?2: config_hash
+UPDATE env_versions
+SET config_hash = ?2
+WHERE env_version_id = (
+    SELECT env_version_id
+    FROM workdir_env
+    WHERE workdir_id = ?1
+);
@@ -0,0 +1,29 @@
+-- Add an entry to the up history of a workdir
+-- :param: ?1 - the workdir id
+-- :param: ?2 - the omni version
+-- :param: ?3 - the config hash
+-- :param: ?4 - the user
+-- :param: ?5 - the hostname
+-- :param: ?6 - the operations, as a JSON array
+-- :return: the id of the added entry
+INSERT INTO up_history (
+    workdir_id,
+    started_at,
+    omni_version,
+    config_hash,
+    user,
+    hostname,
+    outcome,
+    operations
+)
+VALUES (
+    ?1,
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+    ?2,
+    ?3,
+    ?4,
+    ?5,
+    'interrupted',
+    ?6
+)
+RETURNING up_history_id;
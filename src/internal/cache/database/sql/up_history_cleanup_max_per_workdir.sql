@@ -0,0 +1,14 @@
+-- Keep only max per workdir (?1)
+DELETE FROM up_history
+WHERE up_history_id IN (
+    SELECT up_history_id FROM (
+        SELECT
+            up_history_id,
+            ROW_NUMBER() OVER (
+                PARTITION BY workdir_id
+                ORDER BY started_at DESC, up_history_id DESC
+            ) as rn
+        FROM up_history
+    ) ranked
+    WHERE rn > ?1
+);
@@ -0,0 +1,3 @@
+-- Apply retention period (?1 seconds)
+DELETE FROM up_history
+WHERE CAST(strftime('%s', started_at) AS INTEGER) < CAST(strftime('%s', 'now') AS INTEGER) - ?1;
@@ -0,0 +1,11 @@
+-- Update an entry of the up history once the run is finished
+-- :param: ?1 - the id of the entry
+-- :param: ?2 - the duration of the run, in milliseconds
+-- :param: ?3 - the outcome of the run
+-- :param: ?4 - the operations, as a JSON array
+UPDATE up_history
+SET
+    duration_ms = ?2,
+    outcome = ?3,
+    operations = ?4
+WHERE up_history_id = ?1;
@@ -0,0 +1,8 @@
+-- Get the start time of the last successful up run for a workdir
+-- :param: ?1 - the workdir id
+SELECT started_at
+FROM up_history
+WHERE workdir_id = ?1
+AND outcome = 'success'
+ORDER BY started_at DESC, up_history_id DESC
+LIMIT 1;
@@ -0,0 +1,16 @@
+-- List the up history of a workdir, most recent first
+-- :param: ?1 - the workdir id
+SELECT
+    up_history_id,
+    workdir_id,
+    started_at,
+    duration_ms,
+    omni_version,
+    config_hash,
+    user,
+    hostname,
+    outcome,
+    operations
+FROM up_history
+WHERE workdir_id = ?1
+ORDER BY started_at DESC, up_history_id DESC;
@@ -0,0 +1,22 @@
+-- Upgrade from version 10 to version 11
+BEGIN TRANSACTION;
+
+-- Table containing the results of the repository lookups that had to
+-- scan the worktrees, along with the fingerprint of the state of the
+-- worktrees and of the configuration that they were computed for, so
+-- that the same lookup does not need to scan again while it is unchanged
+CREATE TABLE IF NOT EXISTS repo_lookups (
+    fragment TEXT NOT NULL,
+    only_worktree INTEGER NOT NULL,
+    only_packages INTEGER NOT NULL,
+    fingerprint TEXT NOT NULL,
+    path TEXT,  -- NULL when no repository was found
+    looked_up_at TEXT NOT NULL,
+    PRIMARY KEY (fragment, only_worktree, only_packages)
+);
+
+-- Update the user_version to 11
+PRAGMA user_version = 11;
+
+-- Commit the transaction
+COMMIT;
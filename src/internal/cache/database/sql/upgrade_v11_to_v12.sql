@@ -0,0 +1,33 @@
+-- Upgrade from version 11 to version 12
+BEGIN TRANSACTION;
+
+-- Table containing the python tools that were installed in isolated
+-- environments by pipx operations; an installation is identified by
+-- the package and the installation id, which covers the version, the
+-- extras, the source and the interpreter used for the installation
+CREATE TABLE IF NOT EXISTS pipx_installed (
+    package TEXT NOT NULL COLLATE NOCASE,
+    install_id TEXT NOT NULL,
+    last_required_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00.000Z',
+    PRIMARY KEY (package, install_id)
+);
+
+-- Table containing the information of which workdir is
+-- requiring a given pipx installation
+CREATE TABLE IF NOT EXISTS pipx_install_required_by (
+    package TEXT NOT NULL COLLATE NOCASE,
+    install_id TEXT NOT NULL,
+    env_version_id TEXT NOT NULL,
+    PRIMARY KEY (package, install_id, env_version_id),
+    FOREIGN KEY(package, install_id) REFERENCES pipx_installed(package, install_id) ON DELETE CASCADE,
+    FOREIGN KEY(env_version_id) REFERENCES env_versions(env_version_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pipx_install_required_by ON pipx_install_required_by(package, install_id);
+CREATE INDEX IF NOT EXISTS idx_pipx_installed_package ON pipx_installed(package);
+
+-- Update the user_version to 12
+PRAGMA user_version = 12;
+
+-- Commit the transaction
+COMMIT;
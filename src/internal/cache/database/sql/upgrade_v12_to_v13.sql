@@ -0,0 +1,16 @@
+-- Upgrade from version 12 to version 13
+BEGIN TRANSACTION;
+
+-- Table containing the sequence of work directories entered by the
+-- user, so that omni cd can navigate back to the previous ones
+CREATE TABLE IF NOT EXISTS workdir_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL,
+    entered_at TEXT NOT NULL
+);
+
+-- Update the user_version to 13
+PRAGMA user_version = 13;
+
+-- Commit the transaction
+COMMIT;
@@ -0,0 +1,27 @@
+-- Upgrade from version 5 to version 6
+BEGIN TRANSACTION;
+
+-- Table containing the history of the omni up runs for each workdir;
+-- entries are added with an 'interrupted' outcome when the run starts,
+-- and updated when the run finishes, so that a run that did not get
+-- to finish is still recorded
+CREATE TABLE IF NOT EXISTS up_history (
+    up_history_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workdir_id TEXT NOT NULL COLLATE NOCASE,
+    started_at TEXT NOT NULL,
+    duration_ms INTEGER,
+    omni_version TEXT NOT NULL,
+    config_hash TEXT NOT NULL,
+    user TEXT NOT NULL,
+    hostname TEXT NOT NULL,
+    outcome TEXT NOT NULL DEFAULT 'interrupted',
+    operations TEXT NOT NULL DEFAULT '[]'
+);
+
+CREATE INDEX IF NOT EXISTS idx_up_history_workdir ON up_history(workdir_id, started_at);
+
+-- Update the user_version to 6
+PRAGMA user_version = 6;
+
+-- Commit the transaction
+COMMIT;
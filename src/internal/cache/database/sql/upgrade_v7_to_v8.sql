@@ -0,0 +1,19 @@
+-- Upgrade from version 7 to version 8
+BEGIN TRANSACTION;
+
+-- Table containing the cached tools pinned by the user, which are
+-- never removed by the cleanup of the unused installed tools; the
+-- version is '__NULL__' when all the versions of the tool are pinned
+CREATE TABLE IF NOT EXISTS cache_pins (
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    version TEXT NOT NULL DEFAULT '__NULL__' COLLATE NOCASE,
+    pinned_at TEXT NOT NULL,
+    PRIMARY KEY (kind, name, version)
+);
+
+-- Update the user_version to 8
+PRAGMA user_version = 8;
+
+-- Commit the transaction
+COMMIT;
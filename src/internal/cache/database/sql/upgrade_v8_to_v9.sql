@@ -0,0 +1,21 @@
+-- Upgrade from version 8 to version 9
+BEGIN TRANSACTION;
+
+-- Table containing the repositories chosen by the user when a repository
+-- fragment matched multiple repositories, so that the same repository
+-- can be preferred the next time the same fragment is looked up
+CREATE TABLE IF NOT EXISTS repo_match_choices (
+    fragment TEXT NOT NULL COLLATE NOCASE,
+    path TEXT NOT NULL,
+    times_chosen INTEGER NOT NULL DEFAULT 1,
+    last_chosen_at TEXT NOT NULL,
+    PRIMARY KEY (fragment, path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_repo_match_choices_last_chosen_at ON repo_match_choices(last_chosen_at);
+
+-- Update the user_version to 9
+PRAGMA user_version = 9;
+
+-- Commit the transaction
+COMMIT;
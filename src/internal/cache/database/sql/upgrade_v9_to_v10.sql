@@ -0,0 +1,16 @@
+-- Upgrade from version 9 to version 10
+BEGIN TRANSACTION;
+
+-- Table containing the cache of the advisory feeds used to check the
+-- tool versions resolved by `omni up`
+CREATE TABLE IF NOT EXISTS up_advisory_feeds (
+    url TEXT PRIMARY KEY,
+    advisories TEXT NOT NULL,  -- JSON array of UpAdvisory
+    fetched_at TEXT NOT NULL
+);
+
+-- Update the user_version to 10
+PRAGMA user_version = 10;
+
+-- Commit the transaction
+COMMIT;
@@ -0,0 +1,16 @@
+-- Record that a work directory was entered, unless it is already the
+-- last entry of the history, so that consecutive entries collapse
+-- :param: ?1 - the path of the root of the work directory
+INSERT INTO workdir_history (
+    path,
+    entered_at
+)
+SELECT
+    ?1,
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+WHERE ?1 IS NOT (
+    SELECT path
+    FROM workdir_history
+    ORDER BY id DESC
+    LIMIT 1
+);
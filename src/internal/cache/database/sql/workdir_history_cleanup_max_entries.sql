@@ -0,0 +1,8 @@
+-- Keep only the max number of entries (?1), the most recent ones
+DELETE FROM workdir_history
+WHERE id NOT IN (
+    SELECT id
+    FROM workdir_history
+    ORDER BY id DESC
+    LIMIT ?1
+);
@@ -0,0 +1,5 @@
+-- Get the last entry of the history
+SELECT path
+FROM workdir_history
+ORDER BY id DESC
+LIMIT 1;
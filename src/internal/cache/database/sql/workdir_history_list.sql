@@ -0,0 +1,4 @@
+-- Get the entries of the history, the most recent first
+SELECT path
+FROM workdir_history
+ORDER BY id DESC;
@@ -0,0 +1,11 @@
+-- Get the ids of all the work directories known to the cache
+SELECT workdir_id FROM workdir_env
+UNION
+SELECT workdir_id FROM env_history
+UNION
+SELECT workdir_id FROM up_history
+UNION
+SELECT workdir_id FROM workdir_trusted
+UNION
+SELECT workdir_id FROM workdir_fingerprints
+ORDER BY workdir_id;
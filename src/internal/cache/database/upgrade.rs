@@ -1,6 +1,7 @@
 use rusqlite::Connection;
 
 use crate::internal::cache::migration::convert_cache;
+use crate::internal::cache::migration::merge_physical_duplicate_env_versions;
 use crate::internal::cache::migration::migrate_json_to_database;
 use crate::internal::cache::CacheManagerError;
 
@@ -45,5 +46,42 @@ pub fn upgrade_database(conn: &Connection) -> Result<(), CacheManagerError> {
         conn.execute_batch(include_str!("sql/upgrade_v4_to_v5.sql"))?;
     }
 
+    if current_version < 6 {
+        conn.execute_batch(include_str!("sql/upgrade_v5_to_v6.sql"))?;
+    }
+
+    if current_version < 7 {
+        // Merge the environment versions that were recorded multiple
+        // times for the same physical paths
+        let tx = conn.unchecked_transaction()?;
+        merge_physical_duplicate_env_versions(&tx)?;
+        tx.execute_batch("PRAGMA user_version = 7;")?;
+        tx.commit()?;
+    }
+
+    if current_version < 8 {
+        conn.execute_batch(include_str!("sql/upgrade_v7_to_v8.sql"))?;
+    }
+
+    if current_version < 9 {
+        conn.execute_batch(include_str!("sql/upgrade_v8_to_v9.sql"))?;
+    }
+
+    if current_version < 10 {
+        conn.execute_batch(include_str!("sql/upgrade_v9_to_v10.sql"))?;
+    }
+
+    if current_version < 11 {
+        conn.execute_batch(include_str!("sql/upgrade_v10_to_v11.sql"))?;
+    }
+
+    if current_version < 12 {
+        conn.execute_batch(include_str!("sql/upgrade_v11_to_v12.sql"))?;
+    }
+
+    if current_version < 13 {
+        conn.execute_batch(include_str!("sql/upgrade_v12_to_v13.sql"))?;
+    }
+
     Ok(())
 }
@@ -1,5 +1,7 @@
 use super::*;
 use crate::internal::cache::database::get_conn;
+use crate::internal::cache::CachePinKind;
+use crate::internal::cache::CachePinsCache;
 use crate::internal::testutils::run_with_env;
 
 mod github_release_operation_cache {
@@ -367,6 +369,66 @@ mod github_release_operation_cache {
         });
     }
 
+    #[test]
+    fn test_cleanup_skips_pinned() {
+        run_with_env(&[], || {
+            let cache = GithubReleaseOperationCache::get();
+
+            // Add three versions, all old enough to be cleaned up
+            for version in ["v1.0.0", "v1.1.0", "v2.0.0"] {
+                cache
+                    .add_installed("test/repo", version, false, false)
+                    .expect("Failed to add installation");
+            }
+            let other = "test/critical";
+            cache
+                .add_installed(other, "v1.0.0", false, false)
+                .expect("Failed to add installation");
+
+            let conn = get_conn();
+            conn.execute(
+                "UPDATE github_release_installed SET last_required_at = '1970-01-01T00:00:00.000Z'",
+                params![],
+            )
+            .expect("Failed to update last_required_at");
+
+            // Pin a single version of the tool, and all the versions of the other
+            let pins = CachePinsCache::get();
+            pins.pin(CachePinKind::GithubRelease, "test/repo", Some("v1.1.0"))
+                .expect("Failed to pin version");
+            pins.pin(CachePinKind::GithubRelease, other, None)
+                .expect("Failed to pin all versions");
+
+            // Run cleanup
+            cache.cleanup().expect("Failed to cleanup");
+
+            // Verify that only the pinned versions were kept
+            let mut kept = cache
+                .list_installed()
+                .expect("Failed to list installed")
+                .into_iter()
+                .map(|installed| (installed.repository, installed.version))
+                .collect::<Vec<_>>();
+            kept.sort();
+            assert_eq!(
+                kept,
+                vec![
+                    (other.to_string(), "v1.0.0".to_string()),
+                    ("test/repo".to_string(), "v1.1.0".to_string()),
+                ]
+            );
+
+            // Unpinning makes the version eligible for cleanup again
+            pins.unpin(CachePinKind::GithubRelease, "test/repo", Some("v1.1.0"))
+                .expect("Failed to unpin");
+            cache.cleanup().expect("Failed to cleanup");
+
+            let installed = cache.list_installed().expect("Failed to list installed");
+            assert_eq!(installed.len(), 1);
+            assert_eq!(installed[0].repository, other);
+        });
+    }
+
     #[test]
     fn test_cleanup_cascade() {
         run_with_env(&[], || {
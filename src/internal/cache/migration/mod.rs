@@ -6,3 +6,6 @@ mod pre0029;
 
 mod predatabase;
 pub(crate) use predatabase::migrate_json_to_database;
+
+mod physical_paths;
+pub(crate) use physical_paths::merge_physical_duplicate_env_versions;
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use rusqlite::params;
+use rusqlite::Connection;
+
+use crate::internal::cache::database::CacheManagerError;
+
+/// The fields identifying an environment version, with its paths resolved
+/// to their physical location
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct PhysicalEnvVersion {
+    workdir_id: String,
+    versions: String,
+    paths: Vec<PathBuf>,
+    env_vars: String,
+    config_modtimes: String,
+    config_hash: String,
+}
+
+/// Merges the environment versions of a same work directory that only
+/// differ by the paths used to reach the same physical locations, e.g. when
+/// the work directory was reached once through a symlink and once through
+/// its resolved path; the references to the merged versions are moved to
+/// the version that is kept.
+pub(crate) fn merge_physical_duplicate_env_versions(
+    conn: &Connection,
+) -> Result<(), CacheManagerError> {
+    // Keep in priority the versions currently assigned to a work directory,
+    // then the most recently assigned ones
+    let mut stmt = conn.prepare(concat!(
+        "SELECT env_version_id, versions, paths, env_vars, config_modtimes, config_hash ",
+        "FROM env_versions ",
+        "ORDER BY env_version_id IN (SELECT env_version_id FROM workdir_env) DESC, ",
+        "last_assigned_at DESC, env_version_id",
+    ))?;
+    let env_versions = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut kept: HashMap<PhysicalEnvVersion, String> = HashMap::new();
+    let mut merges: Vec<(String, String)> = vec![];
+    for (env_version_id, versions, paths, env_vars, config_modtimes, config_hash) in env_versions {
+        // Leave alone the entries we cannot read
+        let paths: Vec<PathBuf> = match serde_json::from_str(&paths) {
+            Ok(paths) => paths,
+            Err(_) => continue,
+        };
+
+        let workdir_id = match env_version_id.rsplit_once('%') {
+            Some((workdir_id, _)) => workdir_id.to_string(),
+            None => env_version_id.clone(),
+        };
+
+        let key = PhysicalEnvVersion {
+            workdir_id,
+            versions,
+            paths: paths
+                .iter()
+                .map(|path| std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+                .collect(),
+            env_vars,
+            config_modtimes,
+            config_hash,
+        };
+
+        match kept.get(&key) {
+            Some(kept_id) => merges.push((env_version_id, kept_id.clone())),
+            None => {
+                kept.insert(key, env_version_id);
+            }
+        }
+    }
+
+    if merges.is_empty() {
+        return Ok(());
+    }
+
+    // List the tables referencing the environment versions
+    let mut stmt = conn.prepare(concat!(
+        "SELECT m.name FROM sqlite_master m, pragma_table_info(m.name) p ",
+        "WHERE m.type = 'table' AND m.name != 'env_versions' AND p.name = 'env_version_id'",
+    ))?;
+    let referencing_tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (duplicate_id, kept_id) in merges {
+        for table in &referencing_tables {
+            // Entries that would conflict with an entry already referencing
+            // the kept version are redundant, and are removed
+            conn.execute(
+                &format!(
+                    "UPDATE OR IGNORE {table} SET env_version_id = ?1 WHERE env_version_id = ?2"
+                ),
+                params![&kept_id, &duplicate_id],
+            )?;
+            conn.execute(
+                &format!("DELETE FROM {table} WHERE env_version_id = ?1"),
+                params![&duplicate_id],
+            )?;
+        }
+
+        conn.execute(
+            concat!(
+                "UPDATE env_versions SET last_assigned_at = MAX(last_assigned_at, ",
+                "(SELECT last_assigned_at FROM env_versions WHERE env_version_id = ?2)) ",
+                "WHERE env_version_id = ?1",
+            ),
+            params![&kept_id, &duplicate_id],
+        )?;
+        conn.execute(
+            "DELETE FROM env_versions WHERE env_version_id = ?1",
+            params![&duplicate_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "physical_paths_test.rs"]
+mod tests;
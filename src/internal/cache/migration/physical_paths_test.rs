@@ -0,0 +1,164 @@
+use super::*;
+
+use std::os::unix::fs::symlink;
+
+fn get_conn() -> Connection {
+    let conn = Connection::open_in_memory().expect("Failed to open in-memory database");
+    conn.execute_batch(include_str!("../database/sql/create_tables.sql"))
+        .expect("Failed to create tables");
+    conn
+}
+
+fn add_env_version(conn: &Connection, env_version_id: &str, paths: &[PathBuf], assigned: &str) {
+    conn.execute(
+        concat!(
+            "INSERT INTO env_versions ",
+            "(env_version_id, versions, paths, env_vars, config_modtimes, config_hash, last_assigned_at) ",
+            "VALUES (?1, '[]', ?2, '[]', '{}', 'hash', ?3)",
+        ),
+        params![
+            env_version_id,
+            serde_json::to_string(paths).unwrap(),
+            assigned
+        ],
+    )
+    .expect("Failed to add env version");
+}
+
+fn add_required_by(conn: &Connection, env_version_id: &str) {
+    conn.execute(
+        "INSERT OR IGNORE INTO asdf_installed (tool, version) VALUES ('python', '3.12')",
+        [],
+    )
+    .expect("Failed to add installed tool");
+    conn.execute(
+        "INSERT INTO asdf_installed_required_by (tool, version, env_version_id) VALUES ('python', '3.12', ?1)",
+        params![env_version_id],
+    )
+    .expect("Failed to add required by");
+}
+
+fn query_strings(conn: &Connection, query: &str) -> Vec<String> {
+    let mut stmt = conn.prepare(query).expect("Failed to prepare query");
+    stmt.query_map([], |row| row.get::<_, String>(0))
+        .expect("Failed to run query")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Failed to read rows")
+}
+
+#[test]
+fn merges_env_versions_with_same_physical_paths() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+    let base = std::fs::canonicalize(tempdir.path()).expect("failed to canonicalize");
+    std::fs::create_dir_all(base.join("real/bin")).expect("failed to create dir");
+    symlink(base.join("real"), base.join("link")).expect("failed to create symlink");
+
+    let conn = get_conn();
+
+    // The same environment, recorded through the symlink and resolved
+    add_env_version(
+        &conn,
+        "wd%logical",
+        &[base.join("link/bin")],
+        "2024-01-02T00:00:00.000Z",
+    );
+    add_env_version(
+        &conn,
+        "wd%physical",
+        &[base.join("real/bin")],
+        "2024-01-03T00:00:00.000Z",
+    );
+    // Not duplicates: different paths, or a different workdir
+    add_env_version(
+        &conn,
+        "wd%other",
+        &[base.join("other")],
+        "2024-01-01T00:00:00.000Z",
+    );
+    add_env_version(
+        &conn,
+        "wd2%logical",
+        &[base.join("link/bin")],
+        "2024-01-01T00:00:00.000Z",
+    );
+
+    conn.execute(
+        "INSERT INTO workdir_env (workdir_id, env_version_id) VALUES ('wd', 'wd%logical')",
+        [],
+    )
+    .unwrap();
+    for env_version_id in ["wd%logical", "wd%physical"] {
+        conn.execute(
+            concat!(
+                "INSERT INTO env_history (workdir_id, env_version_id, used_from_date) ",
+                "VALUES ('wd', ?1, '2024-01-01T00:00:00.000Z')",
+            ),
+            params![env_version_id],
+        )
+        .unwrap();
+        add_required_by(&conn, env_version_id);
+    }
+
+    merge_physical_duplicate_env_versions(&conn).expect("Migration failed");
+
+    // The version assigned to the workdir is the one kept, with the
+    // latest assignment date of the merged versions
+    assert_eq!(
+        query_strings(
+            &conn,
+            "SELECT env_version_id FROM env_versions ORDER BY env_version_id"
+        ),
+        vec!["wd%logical", "wd%other", "wd2%logical"]
+    );
+    assert_eq!(
+        query_strings(
+            &conn,
+            "SELECT last_assigned_at FROM env_versions WHERE env_version_id = 'wd%logical'"
+        ),
+        vec!["2024-01-03T00:00:00.000Z"]
+    );
+
+    // The references are moved to the kept version
+    assert_eq!(
+        query_strings(&conn, "SELECT env_version_id FROM workdir_env"),
+        vec!["wd%logical"]
+    );
+    assert_eq!(
+        query_strings(&conn, "SELECT env_version_id FROM env_history"),
+        vec!["wd%logical", "wd%logical"]
+    );
+    assert_eq!(
+        query_strings(
+            &conn,
+            "SELECT env_version_id FROM asdf_installed_required_by"
+        ),
+        vec!["wd%logical"]
+    );
+}
+
+#[test]
+fn keeps_env_versions_without_duplicates() {
+    let conn = get_conn();
+    add_env_version(
+        &conn,
+        "wd%first",
+        &[PathBuf::from("/missing/first")],
+        "2024-01-01T00:00:00.000Z",
+    );
+    add_env_version(
+        &conn,
+        "wd%second",
+        &[PathBuf::from("/missing/second")],
+        "2024-01-01T00:00:00.000Z",
+    );
+
+    merge_physical_duplicate_env_versions(&conn).expect("Migration failed");
+
+    assert_eq!(
+        query_strings(
+            &conn,
+            "SELECT env_version_id FROM env_versions ORDER BY env_version_id"
+        ),
+        vec!["wd%first", "wd%second"]
+    );
+}
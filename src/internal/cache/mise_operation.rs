@@ -30,6 +30,27 @@ impl MiseOperationCache {
         Ok(updated > 0)
     }
 
+    /// Records the version of the mise binary that omni installed in its
+    /// own directory, so that the managed binary is only updated as
+    /// requested by the configuration
+    pub fn set_managed_mise_version(&self, version: &str) -> Result<bool, CacheManagerError> {
+        let db = CacheManager::get();
+        let updated = db.execute(
+            include_str!("database/sql/mise_operation_set_managed_mise_version.sql"),
+            params![version],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn managed_mise_version(&self) -> Option<String> {
+        let db = CacheManager::get();
+        db.query_one_optional(
+            include_str!("database/sql/mise_operation_get_managed_mise_version.sql"),
+            params![],
+        )
+        .unwrap_or_default()
+    }
+
     pub fn updated_mise_plugin(&self, plugin: &str) -> Result<bool, CacheManagerError> {
         let db = CacheManager::get();
         let updated = db.execute(
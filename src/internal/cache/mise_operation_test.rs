@@ -1,6 +1,8 @@
 use super::*;
 
 use crate::internal::cache::database::get_conn;
+use crate::internal::cache::CachePinKind;
+use crate::internal::cache::CachePinsCache;
 use crate::internal::testutils::run_with_env;
 
 mod mise_operation_cache {
@@ -22,6 +24,25 @@ mod mise_operation_cache {
         });
     }
 
+    #[test]
+    fn test_managed_mise_version() {
+        run_with_env(&[], || {
+            let cache = MiseOperationCache::get();
+
+            assert_eq!(cache.managed_mise_version(), None);
+
+            cache
+                .set_managed_mise_version("2025.1.0")
+                .expect("Failed to set managed mise version");
+            assert_eq!(cache.managed_mise_version(), Some("2025.1.0".to_string()));
+
+            cache
+                .set_managed_mise_version("2025.2.1")
+                .expect("Failed to set managed mise version");
+            assert_eq!(cache.managed_mise_version(), Some("2025.2.1".to_string()));
+        });
+    }
+
     #[test]
     fn test_should_update_mise_plugin() {
         run_with_env(&[], || {
@@ -187,6 +208,55 @@ mod mise_operation_cache {
             assert_eq!(tool_in_db, 0);
         });
     }
+
+    #[test]
+    fn test_cleanup_skips_pinned() {
+        run_with_env(&[], || {
+            let conn = get_conn();
+
+            let mut installed_stmt = conn
+                .prepare("INSERT INTO mise_installed (tool, plugin_name, normalized_name, version, bin_paths, last_required_at) VALUES (?, ?, ?, ?, '[]', ?)")
+                .expect("Failed to prepare statement");
+            for version in ["1.0.0", "1.1.0"] {
+                installed_stmt
+                    .execute(params![
+                        "test-tool",
+                        "aqua:test/tool",
+                        "aqua-test-tool",
+                        version,
+                        "1970-01-01T00:00:00Z"
+                    ])
+                    .expect("Failed to insert test tool");
+            }
+
+            // Pins can use the tool name, plugin name or normalized name
+            CachePinsCache::get()
+                .pin(CachePinKind::Mise, "aqua:test/tool", Some("1.1.0"))
+                .expect("Failed to pin");
+
+            let mut deleted_tools = Vec::new();
+            MiseOperationCache::get()
+                .cleanup(|tool: &str, version: &str| {
+                    deleted_tools.push((tool.to_string(), version.to_string()));
+                    Ok(())
+                })
+                .expect("Failed to cleanup");
+
+            assert_eq!(
+                deleted_tools,
+                vec![("aqua-test-tool".to_string(), "1.0.0".to_string())]
+            );
+
+            let kept: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM mise_installed WHERE version = '1.1.0'",
+                    params![],
+                    |row| row.get(0),
+                )
+                .expect("Failed to query tool in database");
+            assert_eq!(kept, 1);
+        });
+    }
 }
 
 mod mise_plugin_versions {
@@ -2,7 +2,14 @@ pub(crate) mod cargo_install;
 pub(crate) use cargo_install::CargoInstallOperationCache;
 pub(crate) use cargo_install::CargoInstallVersions;
 
+pub(crate) mod cache_dir;
+pub(crate) use cache_dir::CacheDir;
+
+pub(crate) mod completions;
+pub(crate) use completions::CompletionsCache;
+
 pub(crate) mod database;
+pub(crate) use database::CacheBatch;
 pub(crate) use database::CacheManager;
 pub(crate) use database::CacheManagerError;
 
@@ -26,15 +33,41 @@ pub(crate) mod offsetdatetime_hashmap;
 pub(crate) mod omnipath;
 pub(crate) use omnipath::OmniPathCache;
 
+pub(crate) mod parsed_config;
+pub(crate) use parsed_config::ParsedConfigCache;
+
+pub(crate) mod pipx;
+pub(crate) use pipx::PipxOperationCache;
+
+pub(crate) mod pins;
+pub(crate) use pins::CachePin;
+pub(crate) use pins::CachePinKind;
+pub(crate) use pins::CachePinsCache;
+
 pub(crate) mod prompts;
 pub(crate) use prompts::PromptsCache;
 
+pub(crate) mod repo_lookup;
+pub(crate) use repo_lookup::RepoLookupCache;
+
+pub(crate) mod repo_match;
+pub(crate) use repo_match::RepoMatchCache;
+
+pub(crate) mod workdir_history;
+pub(crate) use workdir_history::WorkdirHistoryCache;
+
 pub(crate) mod workdirs;
 pub(crate) use workdirs::WorkdirsCache;
 
 mod migration;
 
+pub(crate) mod up_advisories;
+pub(crate) use up_advisories::UpAdvisoriesCache;
+
 pub(crate) mod up_environments;
 pub(crate) use up_environments::UpEnvironmentsCache;
 
+pub(crate) mod up_history;
+pub(crate) use up_history::UpHistoryCache;
+
 pub(crate) mod utils;
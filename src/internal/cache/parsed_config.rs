@@ -0,0 +1,203 @@
+use std::fs::Metadata;
+use std::io::Write;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::CacheDir;
+use crate::internal::env::cache_home;
+use crate::internal::utils::base62_encode;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+
+/// The version of the format of the cache entries; this should be
+/// increased when a change is not backwards-compatible, so that
+/// existing entries are ignored
+const PARSED_CONFIG_CACHE_FORMAT: u32 = 1;
+
+/// On-disk cache of the parsed configuration files, so that files that
+/// did not change since the last time they were parsed do not need to
+/// go through the YAML parser again.
+///
+/// The cache cannot depend on the configuration, or on the cache
+/// database which location depends on the configuration, since it is
+/// used while loading that configuration; it is thus stored as one
+/// file per configuration file in the cache directory.
+///
+/// If the cache directory is not writable, entries are written to the
+/// fallback location of the cache directory, and existing entries are
+/// still read from the cache directory.
+///
+/// Configuration files are parsed as-is, without any include or
+/// interpolation of environment variables, so the parsed value only
+/// depends on the contents of the file.
+#[derive(Debug)]
+pub struct ParsedConfigCache {
+    dir: CacheDir,
+    #[cfg(test)]
+    parse_count: std::sync::atomic::AtomicUsize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct ParsedConfigCacheEntry {
+    format: u32,
+    path: String,
+    size: u64,
+    mtime: u128,
+    content_hash: String,
+    value: serde_yaml::Value,
+}
+
+impl ParsedConfigCache {
+    pub fn get() -> Self {
+        Self::new(CacheDir::get(cache_home()).join("parsed_config"))
+    }
+
+    fn new(dir: CacheDir) -> Self {
+        Self {
+            dir,
+            #[cfg(test)]
+            parse_count: std::sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Parse the contents of the given configuration file, reusing the
+    /// cached parsed value if the file did not change since it was cached.
+    /// Any issue with the cache (missing, corrupt or stale entry, failure
+    /// to write) silently falls back to parsing the contents.
+    pub fn parse(
+        &self,
+        path: &str,
+        contents: &str,
+        metadata: Option<&Metadata>,
+    ) -> Result<serde_yaml::Value, serde_yaml::Error> {
+        let key = metadata.and_then(|metadata| CacheKey::new(contents, metadata));
+
+        if let Some(key) = &key {
+            if let Some(value) = self.read(path, key) {
+                return Ok(value);
+            }
+        }
+
+        #[cfg(test)]
+        self.parse_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let value = serde_yaml::from_str::<serde_yaml::Value>(contents)?;
+
+        if let Some(key) = key {
+            self.write(path, key, &value);
+        }
+
+        Ok(value)
+    }
+
+    fn entry_name(&self, path: &str) -> String {
+        let hash = blake3::hash(path.as_bytes());
+        let hash_b62 = base62_encode(hash.as_bytes())[..20].to_string();
+        format!("{hash_b62}.json")
+    }
+
+    fn read(&self, path: &str, key: &CacheKey) -> Option<serde_yaml::Value> {
+        let contents = std::fs::read(self.dir.read_path(self.entry_name(path))).ok()?;
+        let entry: ParsedConfigCacheEntry = serde_json::from_slice(&contents).ok()?;
+
+        if entry.format != PARSED_CONFIG_CACHE_FORMAT
+            || entry.path != path
+            || entry.size != key.size
+            || entry.mtime != key.mtime
+            || entry.content_hash != key.content_hash
+        {
+            return None;
+        }
+
+        Some(entry.value)
+    }
+
+    fn write(&self, path: &str, key: CacheKey, value: &serde_yaml::Value) {
+        // Values that cannot go through JSON without changing
+        // are not cached, and will be parsed every time
+        if !is_cacheable(value) {
+            return;
+        }
+
+        let entry = ParsedConfigCacheEntry {
+            format: PARSED_CONFIG_CACHE_FORMAT,
+            path: path.to_string(),
+            size: key.size,
+            mtime: key.mtime,
+            content_hash: key.content_hash,
+            value: value.clone(),
+        };
+
+        let serialized = match serde_json::to_vec(&entry) {
+            Ok(serialized) => serialized,
+            Err(_) => return,
+        };
+
+        // Nothing to do if the cache is read-only
+        let dir = match self.dir.writable() {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        if create_dir_all(dir, PathKind::Private).is_err() {
+            return;
+        }
+
+        // Write to a temporary file first and then move it in place,
+        // so that concurrent readers never see a partial entry
+        let mut tmp_file = match tempfile::NamedTempFile::new_in(dir) {
+            Ok(tmp_file) => tmp_file,
+            Err(_) => return,
+        };
+        if tmp_file.write_all(&serialized).is_err() {
+            return;
+        }
+        let _ = tmp_file.persist(dir.join(self.entry_name(path)));
+    }
+}
+
+#[derive(Debug)]
+struct CacheKey {
+    size: u64,
+    mtime: u128,
+    content_hash: String,
+}
+
+impl CacheKey {
+    fn new(contents: &str, metadata: &Metadata) -> Option<Self> {
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+
+        Some(Self {
+            size: metadata.len(),
+            mtime,
+            content_hash: blake3::hash(contents.as_bytes()).to_hex().to_string(),
+        })
+    }
+}
+
+/// Whether the value can be stored as JSON and read back to the exact
+/// same value; this is not the case for tagged values and non-string
+/// mapping keys, which JSON cannot represent.
+fn is_cacheable(value: &serde_yaml::Value) -> bool {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => mapping
+            .iter()
+            .all(|(key, value)| key.is_string() && is_cacheable(value)),
+        serde_yaml::Value::Sequence(sequence) => sequence.iter().all(is_cacheable),
+        serde_yaml::Value::Tagged(_) => false,
+        serde_yaml::Value::Number(number) => number.as_f64().is_none_or(f64::is_finite),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+#[path = "parsed_config_test.rs"]
+mod tests;
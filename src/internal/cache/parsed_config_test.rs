@@ -0,0 +1,218 @@
+use super::*;
+
+use std::fs::File;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+struct Fixture {
+    _tmpdir: tempfile::TempDir,
+    cache: ParsedConfigCache,
+    config_file: PathBuf,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+        let cache = ParsedConfigCache::new(CacheDir::resolve(
+            tmpdir.path().join("cache"),
+            &tmpdir.path().join("fallback"),
+        ));
+        let config_file = tmpdir.path().join("config.yaml");
+
+        Self {
+            _tmpdir: tmpdir,
+            cache,
+            config_file,
+        }
+    }
+
+    fn write(&self, contents: &str, mtime: Option<SystemTime>) {
+        std::fs::write(&self.config_file, contents).expect("failed to write config file");
+        if let Some(mtime) = mtime {
+            File::options()
+                .write(true)
+                .open(&self.config_file)
+                .expect("failed to open config file")
+                .set_modified(mtime)
+                .expect("failed to set mtime");
+        }
+    }
+
+    fn load(&self) -> serde_yaml::Value {
+        let path = self.config_file.to_string_lossy().to_string();
+        let contents = std::fs::read_to_string(&path).expect("failed to read config file");
+        let metadata = std::fs::metadata(&path).expect("failed to get metadata");
+        self.cache
+            .parse(&path, &contents, Some(&metadata))
+            .expect("failed to parse config file")
+    }
+
+    fn parse_count(&self) -> usize {
+        self.cache.parse_count.load(Ordering::Relaxed)
+    }
+}
+
+fn large_config() -> String {
+    let mut contents = String::from("commands:\n");
+    for idx in 0..2000 {
+        contents.push_str(&format!(
+            concat!(
+                "  command{idx}:\n",
+                "    desc: \"Command number {idx}\"\n",
+                "    run: echo {idx}\n",
+                "    aliases: [cmd{idx}, c{idx}]\n",
+                "    syntax:\n",
+                "      parameters:\n",
+                "        - name: --value\n",
+                "          type: int\n",
+                "          default: {idx}\n",
+            ),
+            idx = idx,
+        ));
+    }
+    contents
+}
+
+#[test]
+fn large_config_is_only_parsed_once() {
+    let fixture = Fixture::new();
+    fixture.write(&large_config(), None);
+
+    let start = Instant::now();
+    let parsed = fixture.load();
+    let uncached = start.elapsed();
+    assert_eq!(fixture.parse_count(), 1);
+
+    let start = Instant::now();
+    for _ in 0..5 {
+        assert_eq!(fixture.load(), parsed);
+    }
+    let cached = start.elapsed() / 5;
+    assert_eq!(fixture.parse_count(), 1);
+
+    eprintln!("large config: uncached {uncached:?}, cached {cached:?}");
+    assert!(cached < uncached);
+}
+
+#[test]
+fn invalidated_on_mtime_change() {
+    let fixture = Fixture::new();
+    let mtime = SystemTime::now() - Duration::from_secs(3600);
+
+    fixture.write("key: value\n", Some(mtime));
+    fixture.load();
+    fixture.load();
+    assert_eq!(fixture.parse_count(), 1);
+
+    fixture.write("key: value\n", Some(mtime + Duration::from_secs(10)));
+    fixture.load();
+    assert_eq!(fixture.parse_count(), 2);
+}
+
+#[test]
+fn invalidated_on_hash_change_with_equal_mtime() {
+    let fixture = Fixture::new();
+    let mtime = SystemTime::now() - Duration::from_secs(3600);
+
+    fixture.write("key: value1\n", Some(mtime));
+    assert_eq!(fixture.load()["key"], "value1");
+
+    // Same size and same mtime, only the contents changed
+    fixture.write("key: value2\n", Some(mtime));
+    assert_eq!(fixture.load()["key"], "value2");
+    assert_eq!(fixture.parse_count(), 2);
+}
+
+#[test]
+fn corrupt_entry_falls_back_to_parse() {
+    let fixture = Fixture::new();
+    fixture.write("key: value\n", None);
+    fixture.load();
+
+    let path = fixture.config_file.to_string_lossy().to_string();
+    let entry_path = fixture.cache.dir.read_path(fixture.cache.entry_name(&path));
+    std::fs::write(entry_path, "{not json").expect("failed to corrupt");
+
+    assert_eq!(fixture.load()["key"], "value");
+    assert_eq!(fixture.parse_count(), 2);
+
+    // The entry has been rewritten and can be used again
+    fixture.load();
+    assert_eq!(fixture.parse_count(), 2);
+}
+
+#[test]
+fn read_only_cache_dir_writes_to_fallback() {
+    let fixture = Fixture::new();
+    fixture.write("key: value\n", None);
+    fixture.load();
+    assert_eq!(fixture.parse_count(), 1);
+
+    let primary = fixture.cache.dir.primary().to_path_buf();
+    let fallback_root = fixture._tmpdir.path().join("fallback");
+    std::fs::set_permissions(&primary, std::fs::Permissions::from_mode(0o555))
+        .expect("failed to make cache directory read-only");
+
+    let cache = ParsedConfigCache::new(CacheDir::resolve(primary.clone(), &fallback_root));
+    assert!(cache.dir.is_fallback());
+
+    // Existing entries are still read from the cache directory
+    let path = fixture.config_file.to_string_lossy().to_string();
+    let load = |cache: &ParsedConfigCache| {
+        let contents = std::fs::read_to_string(&path).expect("failed to read config file");
+        let metadata = std::fs::metadata(&path).expect("failed to get metadata");
+        cache
+            .parse(&path, &contents, Some(&metadata))
+            .expect("failed to parse config file")
+    };
+    assert_eq!(load(&cache)["key"], "value");
+    assert_eq!(cache.parse_count.load(Ordering::Relaxed), 0);
+
+    // New entries are written to the fallback location
+    fixture.write("key: value2\n", None);
+    assert_eq!(load(&cache)["key"], "value2");
+    assert_eq!(load(&cache)["key"], "value2");
+    assert_eq!(cache.parse_count.load(Ordering::Relaxed), 1);
+
+    let entry_name = cache.entry_name(&path);
+    let fallback_entry = cache
+        .dir
+        .writable()
+        .expect("should be writable")
+        .join(&entry_name);
+    assert!(fallback_entry.exists());
+    let primary_entry =
+        std::fs::read_to_string(primary.join(&entry_name)).expect("failed to read primary entry");
+    assert!(!primary_entry.contains("value2"));
+
+    std::fs::set_permissions(&primary, std::fs::Permissions::from_mode(0o755))
+        .expect("failed to restore permissions");
+}
+
+#[test]
+fn tagged_values_are_not_cached() {
+    let fixture = Fixture::new();
+    fixture.write("key: !custom value\n", None);
+
+    let parsed = fixture.load();
+    assert_eq!(fixture.load(), parsed);
+    assert_eq!(fixture.parse_count(), 2);
+}
+
+#[test]
+fn parse_errors_are_returned() {
+    let fixture = Fixture::new();
+    fixture.write("key: [value\n", None);
+
+    let path = fixture.config_file.to_string_lossy().to_string();
+    let contents = std::fs::read_to_string(&path).expect("failed to read config file");
+    let metadata = std::fs::metadata(&path).expect("failed to get metadata");
+    assert!(fixture
+        .cache
+        .parse(&path, &contents, Some(&metadata))
+        .is_err());
+}
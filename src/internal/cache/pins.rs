@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use rusqlite::params;
+use serde::Deserialize;
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::internal::cache::database::FromRow;
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+
+/// The kinds of cached tools that can be pinned, which are the ones
+/// removed by the cleanup once they are not required anymore
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CachePinKind {
+    CargoInstall,
+    GithubRelease,
+    GoInstall,
+    Homebrew,
+    Mise,
+}
+
+impl CachePinKind {
+    pub const ALL: [Self; 5] = [
+        Self::CargoInstall,
+        Self::GithubRelease,
+        Self::GoInstall,
+        Self::Homebrew,
+        Self::Mise,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CargoInstall => "cargo-install",
+            Self::GithubRelease => "github-release",
+            Self::GoInstall => "go-install",
+            Self::Homebrew => "homebrew",
+            Self::Mise => "mise",
+        }
+    }
+}
+
+impl FromStr for CachePinKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or_else(|| format!("unknown cache kind: {value}"))
+    }
+}
+
+impl std::fmt::Display for CachePinKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachePinsCache {}
+
+impl CachePinsCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    /// Pins the given tool, or only the given version of the tool, so that
+    /// it is never removed by the cleanup; returns false if already pinned
+    pub fn pin(
+        &self,
+        kind: CachePinKind,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<bool, CacheManagerError> {
+        let db = CacheManager::get();
+        let inserted = db.execute(
+            include_str!("database/sql/cache_pins_add.sql"),
+            params![kind.as_str(), name, version],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Unpins the given tool, or the given version of the tool; returns
+    /// false if there was no such pin
+    pub fn unpin(
+        &self,
+        kind: CachePinKind,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<bool, CacheManagerError> {
+        let db = CacheManager::get();
+        let removed = db.execute(
+            include_str!("database/sql/cache_pins_remove.sql"),
+            params![kind.as_str(), name, version],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Lists the pins, along with whether they still match cached tools
+    pub fn list(&self) -> Result<Vec<CachePin>, CacheManagerError> {
+        let db = CacheManager::get();
+        let pins: Vec<CachePin> =
+            db.query_as(include_str!("database/sql/cache_pins_list.sql"), params![])?;
+        Ok(pins)
+    }
+
+    /// Counts the installed tools for each kind of cached tool
+    pub fn count_installed(&self) -> Result<BTreeMap<CachePinKind, usize>, CacheManagerError> {
+        let db = CacheManager::get();
+        let counts: Vec<(String, i64)> = db.query_as(
+            include_str!("database/sql/cache_stats_count_installed.sql"),
+            params![],
+        )?;
+
+        Ok(counts
+            .into_iter()
+            .filter_map(|(kind, count)| {
+                let kind = CachePinKind::from_str(&kind).ok()?;
+                Some((kind, usize::try_from(count).unwrap_or(0)))
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePin {
+    pub kind: CachePinKind,
+    pub name: String,
+    /// The pinned version, or `None` if all the versions are pinned
+    pub version: Option<String>,
+    pub pinned_at: OffsetDateTime,
+    /// Whether the pin matches at least one cached tool; a pin that does
+    /// not is stale, but is kept in case the tool gets installed again
+    pub cached: bool,
+}
+
+impl CachePin {
+    pub fn is_stale(&self) -> bool {
+        !self.cached
+    }
+}
+
+impl FromRow for CachePin {
+    fn from_row(row: &rusqlite::Row) -> Result<Self, CacheManagerError> {
+        let kind: String = row.get(0)?;
+        let kind = CachePinKind::from_str(&kind).map_err(CacheManagerError::Other)?;
+
+        let pinned_at: String = row.get(3)?;
+        let pinned_at = OffsetDateTime::parse(&pinned_at, &Rfc3339)?;
+
+        Ok(Self {
+            kind,
+            name: row.get(1)?,
+            version: row.get(2)?,
+            pinned_at,
+            cached: row.get(4)?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "pins_test.rs"]
+mod tests;
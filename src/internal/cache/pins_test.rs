@@ -0,0 +1,113 @@
+use super::*;
+
+use crate::internal::cache::GithubReleaseOperationCache;
+use crate::internal::testutils::run_with_env;
+
+mod cache_pin_kind {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        for kind in CachePinKind::ALL {
+            assert_eq!(CachePinKind::from_str(kind.as_str()), Ok(kind));
+        }
+        assert!(CachePinKind::from_str("asdf").is_err());
+    }
+}
+
+mod cache_pins_cache {
+    use super::*;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        run_with_env(&[], || {
+            let cache = CachePinsCache::get();
+
+            assert!(cache
+                .pin(CachePinKind::GithubRelease, "test/repo", Some("v1.0.0"))
+                .expect("Failed to pin"));
+            assert!(
+                !cache
+                    .pin(CachePinKind::GithubRelease, "test/repo", Some("v1.0.0"))
+                    .expect("Failed to pin again"),
+                "Pinning again should not add a new pin"
+            );
+            assert!(cache
+                .pin(CachePinKind::GithubRelease, "test/repo", None)
+                .expect("Failed to pin all versions"));
+
+            let pins = cache.list().expect("Failed to list pins");
+            assert_eq!(pins.len(), 2);
+            assert_eq!(pins[0].version, None);
+            assert_eq!(pins[1].version, Some("v1.0.0".to_string()));
+
+            assert!(cache
+                .unpin(CachePinKind::GithubRelease, "TEST/REPO", None)
+                .expect("Failed to unpin"));
+            assert!(!cache
+                .unpin(CachePinKind::GithubRelease, "test/repo", None)
+                .expect("Failed to unpin again"));
+
+            let pins = cache.list().expect("Failed to list pins");
+            assert_eq!(pins.len(), 1);
+            assert_eq!(pins[0].version, Some("v1.0.0".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_stale_pins() {
+        run_with_env(&[], || {
+            let cache = CachePinsCache::get();
+            GithubReleaseOperationCache::get()
+                .add_installed("test/repo", "v1.0.0", false, false)
+                .expect("Failed to add installation");
+
+            cache
+                .pin(CachePinKind::GithubRelease, "test/repo", None)
+                .expect("Failed to pin");
+            cache
+                .pin(CachePinKind::GithubRelease, "test/repo", Some("v1.0.0"))
+                .expect("Failed to pin");
+            cache
+                .pin(CachePinKind::GithubRelease, "test/repo", Some("v2.0.0"))
+                .expect("Failed to pin");
+            cache
+                .pin(CachePinKind::CargoInstall, "test/repo", None)
+                .expect("Failed to pin");
+
+            let stale = cache
+                .list()
+                .expect("Failed to list pins")
+                .into_iter()
+                .filter(CachePin::is_stale)
+                .map(|pin| (pin.kind, pin.version))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                stale,
+                vec![
+                    (CachePinKind::CargoInstall, None),
+                    (CachePinKind::GithubRelease, Some("v2.0.0".to_string())),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_count_installed() {
+        run_with_env(&[], || {
+            let cache = CachePinsCache::get();
+            let github_release = GithubReleaseOperationCache::get();
+            github_release
+                .add_installed("test/repo", "v1.0.0", false, false)
+                .expect("Failed to add installation");
+            github_release
+                .add_installed("test/repo", "v2.0.0", false, false)
+                .expect("Failed to add installation");
+
+            let counts = cache.count_installed().expect("Failed to count installed");
+            assert_eq!(counts.len(), CachePinKind::ALL.len());
+            assert_eq!(counts.get(&CachePinKind::GithubRelease), Some(&2));
+            assert_eq!(counts.get(&CachePinKind::Mise), Some(&0));
+        });
+    }
+}
@@ -0,0 +1,86 @@
+use rusqlite::params;
+use rusqlite::Row;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::database::FromRow;
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+use crate::internal::config::global_config;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipxOperationCache {}
+
+impl PipxOperationCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    pub fn add_installed(
+        &self,
+        package: &str,
+        install_id: &str,
+    ) -> Result<bool, CacheManagerError> {
+        let db = CacheManager::get();
+        let inserted = db.execute(
+            include_str!("database/sql/pipx_operation_add.sql"),
+            params![package, install_id],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn add_required_by(
+        &self,
+        env_version_id: &str,
+        package: &str,
+        install_id: &str,
+    ) -> Result<bool, CacheManagerError> {
+        let db = CacheManager::get();
+        let inserted = db.execute(
+            include_str!("database/sql/pipx_operation_add_required_by.sql"),
+            params![package, install_id, env_version_id],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn list_installed(&self) -> Result<Vec<PipxInstalled>, CacheManagerError> {
+        let db = CacheManager::get();
+        let installed: Vec<PipxInstalled> = db.query_as(
+            include_str!("database/sql/pipx_operation_list_installed.sql"),
+            params![],
+        )?;
+        Ok(installed)
+    }
+
+    pub fn cleanup(&self) -> Result<(), CacheManagerError> {
+        let config = global_config();
+        let db = CacheManager::get();
+
+        db.execute(
+            include_str!("database/sql/pipx_operation_cleanup_installed.sql"),
+            params![&config.cache.pipx.cleanup_after],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipxInstalled {
+    pub package: String,
+    pub install_id: String,
+}
+
+impl FromRow for PipxInstalled {
+    fn from_row(row: &Row) -> Result<Self, CacheManagerError> {
+        Ok(Self {
+            package: row.get("package")?,
+            install_id: row.get("install_id")?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "pipx_test.rs"]
+mod tests;
@@ -0,0 +1,125 @@
+use super::*;
+
+use crate::internal::cache::database::get_conn;
+use crate::internal::testutils::run_with_env;
+
+mod pipx_operation_cache {
+    use super::*;
+
+    #[test]
+    fn test_add_and_list_installed() {
+        run_with_env(&[], || {
+            let cache = PipxOperationCache::get();
+            let package = "black";
+            let install_id = "24.1.0-abcdef";
+
+            // Test adding installed package
+            assert!(cache
+                .add_installed(package, install_id)
+                .expect("Failed to add installed package"));
+
+            // Test listing installed packages
+            let installed = cache.list_installed().expect("Failed to list installed");
+            assert_eq!(installed.len(), 1);
+            assert_eq!(installed[0].package, package);
+            assert_eq!(installed[0].install_id, install_id);
+
+            // Test adding duplicate installed package
+            assert!(cache
+                .add_installed(package, install_id)
+                .expect("Failed to add duplicate installed package"));
+
+            // Verify no duplicates in list
+            let installed = cache.list_installed().expect("Failed to list installed");
+            assert_eq!(installed.len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_add_required_by() {
+        run_with_env(&[], || {
+            let cache = PipxOperationCache::get();
+            let package = "black";
+            let install_id = "24.1.0-abcdef";
+            let env_version_id = "test-env-id";
+
+            // Add environment version first for foreign key constraint
+            let conn = get_conn();
+            conn.execute(
+                include_str!("database/sql/up_environments_insert_env_version.sql"),
+                params![env_version_id, "{}", "[]", "[]", "{}", "hash"],
+            )
+            .expect("Failed to add environment version");
+
+            // Try adding required_by without installed - should fail
+            let result = cache.add_required_by(env_version_id, package, install_id);
+            assert!(result.is_err(), "Should fail without installed package");
+
+            // Add installed package
+            cache
+                .add_installed(package, install_id)
+                .expect("Failed to add installed package");
+
+            // Now add required_by - should succeed
+            assert!(cache
+                .add_required_by(env_version_id, package, install_id)
+                .expect("Failed to add required by relationship"));
+
+            // Verify the relationship exists
+            let required_exists: bool = conn
+                .query_row(
+                    concat!(
+                        "SELECT EXISTS(",
+                        "  SELECT 1 FROM pipx_install_required_by ",
+                        "  WHERE package = ?1 AND install_id = ?2 AND env_version_id = ?3",
+                        ")",
+                    ),
+                    params![package, install_id, env_version_id],
+                    |row| row.get(0),
+                )
+                .expect("Failed to query required by relationship");
+            assert!(required_exists);
+        });
+    }
+
+    #[test]
+    fn test_cleanup() {
+        run_with_env(&[], || {
+            let cache = PipxOperationCache::get();
+
+            // Add two packages
+            let pkg1 = "black";
+            let pkg2 = "ruff";
+            let install_id = "latest-abcdef";
+
+            // Add installations
+            cache
+                .add_installed(pkg1, install_id)
+                .expect("Failed to add pkg1 installation");
+            cache
+                .add_installed(pkg2, install_id)
+                .expect("Failed to add pkg2 installation");
+
+            let conn = get_conn();
+
+            // Set pkg1's last_required_at to old date (should be cleaned up)
+            conn.execute(
+                concat!(
+                    "UPDATE pipx_installed ",
+                    "SET last_required_at = '1970-01-01T00:00:00.000Z' ",
+                    "WHERE package = ?1",
+                ),
+                params![pkg1],
+            )
+            .expect("Failed to update last_required_at for pkg1");
+
+            // Run cleanup
+            cache.cleanup().expect("Failed to cleanup");
+
+            // Verify through list_installed that only pkg2 is left
+            let installed = cache.list_installed().expect("Failed to list installed");
+            assert_eq!(installed.len(), 1);
+            assert_eq!(installed[0].package, pkg2);
+        });
+    }
+}
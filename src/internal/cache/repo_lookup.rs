@@ -0,0 +1,85 @@
+use rusqlite::params;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+
+/// The results of the repository lookups that had to scan the worktrees,
+/// including the lookups that did not find any repository; each result
+/// is only valid for the fingerprint it was recorded with
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepoLookupCache {}
+
+impl RepoLookupCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    /// Returns the result recorded for the lookup if it was recorded for
+    /// the given fingerprint: `Some(None)` if no repository was found,
+    /// `Some(Some(path))` if one was, and `None` if there is no result
+    pub fn lookup(
+        &self,
+        fragment: &str,
+        only_worktree: bool,
+        only_packages: bool,
+        fingerprint: &str,
+    ) -> Option<Option<String>> {
+        let result: Option<(Option<String>,)> = CacheManager::get()
+            .query_one_optional(
+                include_str!("database/sql/repo_lookup_get.sql"),
+                params![&fragment, &only_worktree, &only_packages, &fingerprint],
+            )
+            .ok()?;
+
+        result.map(|(path,)| path)
+    }
+
+    /// Records the result of the lookup for the given fingerprint, and
+    /// removes the results recorded for other fingerprints since they
+    /// cannot be used anymore
+    pub fn add(
+        &self,
+        fragment: &str,
+        only_worktree: bool,
+        only_packages: bool,
+        fingerprint: &str,
+        path: Option<&str>,
+    ) -> Result<(), CacheManagerError> {
+        let mut db = CacheManager::get();
+        db.transaction(|tx| {
+            tx.execute(
+                include_str!("database/sql/repo_lookup_cleanup_fingerprint.sql"),
+                params![&fingerprint],
+            )?;
+
+            tx.execute(
+                include_str!("database/sql/repo_lookup_add.sql"),
+                params![
+                    &fragment,
+                    &only_worktree,
+                    &only_packages,
+                    &fingerprint,
+                    &path
+                ],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// Forgets all the recorded results, e.g. when a repository was added
+    /// to a worktree; returns the number of results that were removed
+    pub fn clear(&self) -> Result<usize, CacheManagerError> {
+        CacheManager::get().execute(
+            include_str!("database/sql/repo_lookup_clear.sql"),
+            params![],
+        )
+    }
+}
+
+#[cfg(test)]
+#[path = "repo_lookup_test.rs"]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+
+use crate::internal::testutils::run_with_env;
+
+mod repo_lookup_cache {
+    use super::*;
+
+    #[test]
+    fn records_misses_and_hits() {
+        run_with_env(&[], || {
+            let cache = RepoLookupCache::get();
+
+            assert_eq!(cache.lookup("apii", false, false, "fp1"), None);
+
+            cache.add("apii", false, false, "fp1", None).unwrap();
+            cache
+                .add("api", false, false, "fp1", Some("/wt/org1/api"))
+                .unwrap();
+
+            assert_eq!(cache.lookup("apii", false, false, "fp1"), Some(None));
+            assert_eq!(
+                cache.lookup("api", false, false, "fp1"),
+                Some(Some("/wt/org1/api".to_string()))
+            );
+
+            // The scope of the lookup is part of what was looked up
+            assert_eq!(cache.lookup("apii", true, false, "fp1"), None);
+            assert_eq!(cache.lookup("apii", false, true, "fp1"), None);
+        });
+    }
+
+    #[test]
+    fn results_expire_with_the_fingerprint() {
+        run_with_env(&[], || {
+            let cache = RepoLookupCache::get();
+
+            cache.add("apii", false, false, "fp1", None).unwrap();
+            cache.add("webb", false, false, "fp1", None).unwrap();
+            assert_eq!(cache.lookup("apii", false, false, "fp2"), None);
+
+            // Recording a result for a new fingerprint removes the
+            // results of the previous ones
+            cache
+                .add("apii", false, false, "fp2", Some("/wt/org1/apii"))
+                .unwrap();
+            assert_eq!(
+                cache.lookup("apii", false, false, "fp2"),
+                Some(Some("/wt/org1/apii".to_string()))
+            );
+            assert_eq!(cache.lookup("apii", false, false, "fp1"), None);
+            assert_eq!(cache.lookup("webb", false, false, "fp1"), None);
+        });
+    }
+
+    #[test]
+    fn clear_forgets_all_results() {
+        run_with_env(&[], || {
+            let cache = RepoLookupCache::get();
+
+            cache.add("apii", false, false, "fp1", None).unwrap();
+            cache
+                .add("api", false, false, "fp1", Some("/wt/org1/api"))
+                .unwrap();
+
+            assert_eq!(cache.clear().unwrap(), 2);
+            assert_eq!(cache.lookup("apii", false, false, "fp1"), None);
+            assert_eq!(cache.lookup("api", false, false, "fp1"), None);
+        });
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use rusqlite::params;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+use crate::internal::config::global_config;
+
+/// The memory of the repositories chosen by the user when looking up a
+/// repository fragment that matched multiple repositories
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepoMatchCache {}
+
+impl RepoMatchCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    /// Records that the repository at the given path was chosen for the
+    /// fragment, and trims the memory to its configured bounds
+    pub fn add_choice(&self, fragment: &str, path: &str) -> Result<(), CacheManagerError> {
+        let cache_config = global_config().cache.repo_match;
+
+        let mut db = CacheManager::get();
+        db.transaction(|tx| {
+            tx.execute(
+                include_str!("database/sql/repo_match_add_choice.sql"),
+                params![&fragment, &path],
+            )?;
+
+            // Only apply retention cleanup if retention > 0
+            if cache_config.retention > 0 {
+                tx.execute(
+                    include_str!("database/sql/repo_match_cleanup_retention.sql"),
+                    params![&cache_config.retention],
+                )?;
+            }
+
+            tx.execute(
+                include_str!("database/sql/repo_match_cleanup_max_entries.sql"),
+                params![&cache_config.max_entries],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// Returns the paths of the repositories chosen for the fragment,
+    /// along with the number of times they were chosen
+    pub fn choices(&self, fragment: &str) -> HashMap<String, u32> {
+        let choices: Vec<(String, i64)> = CacheManager::get()
+            .query_as(
+                include_str!("database/sql/repo_match_get_choices.sql"),
+                params![&fragment],
+            )
+            .unwrap_or_default();
+
+        choices
+            .into_iter()
+            .map(|(path, times_chosen)| (path, u32::try_from(times_chosen).unwrap_or(0)))
+            .collect()
+    }
+
+    /// Forgets all the recorded choices; returns the number of choices
+    /// that were removed
+    pub fn clear(&self) -> Result<usize, CacheManagerError> {
+        CacheManager::get().execute(include_str!("database/sql/repo_match_clear.sql"), params![])
+    }
+}
+
+#[cfg(test)]
+#[path = "repo_match_test.rs"]
+mod tests;
@@ -0,0 +1,110 @@
+use super::*;
+
+use crate::internal::testutils::run_with_env;
+use crate::internal::ConfigLoader;
+use crate::internal::ConfigValue;
+
+/// Moves the last time the choice was made back in time
+fn backdate(fragment: &str, path: &str, days: i64) {
+    CacheManager::get()
+        .execute(
+            "UPDATE repo_match_choices SET last_chosen_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?3) WHERE fragment = ?1 AND path = ?2",
+            params![&fragment, &path, &format!("-{days} days")],
+        )
+        .expect("failed to backdate choice");
+}
+
+fn set_cache_config(yaml: &str) {
+    if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
+        *config_value = ConfigValue::from_str(yaml).expect("Failed to create config value");
+        true
+    }) {
+        panic!("Failed to edit main user config file: {err}");
+    }
+}
+
+mod repo_match_cache {
+    use super::*;
+
+    #[test]
+    fn records_choices() {
+        run_with_env(&[], || {
+            let cache = RepoMatchCache::get();
+
+            cache.add_choice("api", "/wt/org2/api").unwrap();
+            cache.add_choice("api", "/wt/org2/api").unwrap();
+            cache.add_choice("API", "/wt/org1/api").unwrap();
+            cache.add_choice("web", "/wt/org1/web").unwrap();
+
+            let choices = cache.choices("api");
+            assert_eq!(choices.len(), 2);
+            assert_eq!(choices.get("/wt/org2/api"), Some(&2));
+            assert_eq!(choices.get("/wt/org1/api"), Some(&1));
+
+            assert!(cache.choices("unknown").is_empty());
+        });
+    }
+
+    #[test]
+    fn clear_forgets_all_choices() {
+        run_with_env(&[], || {
+            let cache = RepoMatchCache::get();
+
+            cache.add_choice("api", "/wt/org2/api").unwrap();
+            cache.add_choice("web", "/wt/org1/web").unwrap();
+
+            assert_eq!(cache.clear().unwrap(), 2);
+            assert!(cache.choices("api").is_empty());
+            assert!(cache.choices("web").is_empty());
+        });
+    }
+
+    #[test]
+    fn max_entries_trims_oldest_choices() {
+        run_with_env(&[], || {
+            set_cache_config("cache:\n  repo_match:\n    max_entries: 2");
+            assert_eq!(global_config().cache.repo_match.max_entries, 2);
+
+            let cache = RepoMatchCache::get();
+            cache.add_choice("api", "/wt/org1/api").unwrap();
+            backdate("api", "/wt/org1/api", 3);
+            cache.add_choice("web", "/wt/org1/web").unwrap();
+            backdate("web", "/wt/org1/web", 2);
+            cache.add_choice("cli", "/wt/org1/cli").unwrap();
+
+            assert!(cache.choices("api").is_empty());
+            assert_eq!(cache.choices("web").len(), 1);
+            assert_eq!(cache.choices("cli").len(), 1);
+
+            // Choosing again refreshes the choice, which is then kept
+            backdate("cli", "/wt/org1/cli", 1);
+            cache.add_choice("web", "/wt/org1/web").unwrap();
+            cache.add_choice("api", "/wt/org1/api").unwrap();
+
+            assert!(cache.choices("cli").is_empty());
+            assert_eq!(cache.choices("web").get("/wt/org1/web"), Some(&2));
+            assert_eq!(cache.choices("api").get("/wt/org1/api"), Some(&1));
+        });
+    }
+
+    #[test]
+    fn retention_trims_old_choices() {
+        run_with_env(&[], || {
+            set_cache_config("cache:\n  repo_match:\n    retention: 30d");
+            assert_eq!(global_config().cache.repo_match.retention, 30 * 86400);
+
+            let cache = RepoMatchCache::get();
+            cache.add_choice("api", "/wt/org1/api").unwrap();
+            backdate("api", "/wt/org1/api", 31);
+            cache.add_choice("web", "/wt/org1/web").unwrap();
+            backdate("web", "/wt/org1/web", 29);
+
+            // Cleanup happens when a new choice is recorded
+            cache.add_choice("cli", "/wt/org1/cli").unwrap();
+
+            assert!(cache.choices("api").is_empty());
+            assert_eq!(cache.choices("web").len(), 1);
+            assert_eq!(cache.choices("cli").len(), 1);
+        });
+    }
+}
@@ -0,0 +1,78 @@
+use rusqlite::params;
+use rusqlite::Row;
+use serde::Deserialize;
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::internal::cache::database::FromRow;
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+use crate::internal::config::up::advisories::UpAdvisory;
+
+/// The cache of the advisory feeds fetched to check the tool versions
+/// resolved by `omni up`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpAdvisoriesCache {}
+
+impl UpAdvisoriesCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    pub fn add_feed(
+        &self,
+        url: &str,
+        advisories: &[UpAdvisory],
+    ) -> Result<bool, CacheManagerError> {
+        let db = CacheManager::get();
+        let inserted = db.execute(
+            include_str!("database/sql/up_advisories_add_feed.sql"),
+            params![url, serde_json::to_string(advisories)?],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn get_feed(&self, url: &str) -> Option<UpAdvisoryFeed> {
+        let db = CacheManager::get();
+        db.query_one(
+            include_str!("database/sql/up_advisories_get_feed.sql"),
+            params![url],
+        )
+        .ok()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpAdvisoryFeed {
+    pub advisories: Vec<UpAdvisory>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub fetched_at: OffsetDateTime,
+}
+
+impl UpAdvisoryFeed {
+    pub fn is_stale(&self, ttl: u64) -> bool {
+        let duration = time::Duration::seconds(ttl as i64);
+        self.fetched_at + duration < OffsetDateTime::now_utc()
+    }
+}
+
+impl FromRow for UpAdvisoryFeed {
+    fn from_row(row: &Row) -> Result<Self, CacheManagerError> {
+        let advisories_str: String = row.get("advisories")?;
+        let advisories: Vec<UpAdvisory> = serde_json::from_str(&advisories_str)?;
+
+        let fetched_at_str: String = row.get("fetched_at")?;
+        let fetched_at: OffsetDateTime = OffsetDateTime::parse(&fetched_at_str, &Rfc3339)?;
+
+        Ok(Self {
+            advisories,
+            fetched_at,
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "up_advisories_test.rs"]
+mod tests;
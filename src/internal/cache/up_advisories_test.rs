@@ -0,0 +1,40 @@
+use super::*;
+
+use crate::internal::config::up::advisories::UpAdvisoryKind;
+use crate::internal::testutils::run_with_env;
+
+mod up_advisories_cache {
+    use super::*;
+
+    #[test]
+    fn add_and_get_feed() {
+        run_with_env(&[], || {
+            let cache = UpAdvisoriesCache::get();
+            let url = "https://advisories.example.com/feed.json";
+
+            let advisories = vec![UpAdvisory {
+                tool: "python".to_string(),
+                version: "<3.9".to_string(),
+                kind: UpAdvisoryKind::Eol,
+                id: None,
+                message: Some("upgrade to a supported version".to_string()),
+                url: None,
+            }];
+
+            assert!(cache
+                .add_feed(url, &advisories)
+                .expect("failed to add feed"));
+
+            let feed = cache.get_feed(url).expect("failed to get feed");
+            assert_eq!(feed.advisories, advisories);
+            assert!(!feed.is_stale(3600));
+
+            // Caching the feed again replaces the advisories
+            assert!(cache.add_feed(url, &[]).expect("failed to add feed"));
+            let feed = cache.get_feed(url).expect("failed to get feed");
+            assert!(feed.advisories.is_empty());
+
+            assert!(cache.get_feed("https://unknown.example.com").is_none());
+        });
+    }
+}
@@ -4,6 +4,7 @@ use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::path::Path;
 use std::path::PathBuf;
 
 use rusqlite::params;
@@ -11,12 +12,17 @@ use rusqlite::Row;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::internal::commands::utils::abs_path_from_path;
+use crate::internal::commands::utils::canonicalize_path;
 use crate::internal::config;
 use crate::internal::config::global_config;
+use crate::internal::config::parser::EnvCommandWhen;
 use crate::internal::config::parser::EnvConfig;
 use crate::internal::config::parser::EnvOperationConfig;
 use crate::internal::config::parser::EnvOperationEnum;
+use crate::internal::config::parser::UpAmbiguousVersionsPolicy;
 use crate::internal::config::up::utils::get_config_mod_times;
+use crate::internal::config::up::utils::VersionParser;
 use crate::internal::env::data_home;
 use crate::internal::env::workdir;
 use crate::internal::git::package_path_from_handle;
@@ -43,6 +49,10 @@ pub struct UpVersionParams<'a> {
 pub struct UpEnvironmentsCache {}
 
 impl UpEnvironmentsCache {
+    /// The prefix of the ids of the environments that are not assigned
+    /// to any work directory
+    pub const EPHEMERAL_ENV_PREFIX: &'static str = "ephemeral";
+
     pub fn get() -> Self {
         Self {}
     }
@@ -57,6 +67,39 @@ impl UpEnvironmentsCache {
         Some(env)
     }
 
+    /// Check if the config hash of the environment assigned to the workdir
+    /// matches the provided hash. If it matches the legacy hash instead, the
+    /// stored hash is replaced by the provided one, so that the legacy hash
+    /// is only accepted once.
+    pub fn check_config_hash(
+        &self,
+        workdir_id: &str,
+        config_hash: &str,
+        legacy_config_hash: &str,
+    ) -> bool {
+        let env = match self.get_env(workdir_id) {
+            Some(env) => env,
+            None => return false,
+        };
+
+        if env.config_hash == config_hash {
+            return true;
+        }
+
+        if env.config_hash != legacy_config_hash {
+            return false;
+        }
+
+        // If we fail to update the hash, the legacy hash will simply be
+        // accepted again on the next check
+        let _ = CacheManager::get().execute(
+            include_str!("database/sql/up_environments_update_workdir_config_hash.sql"),
+            &[&workdir_id, &config_hash],
+        );
+
+        true
+    }
+
     pub fn clear(&self, workdir_id: &str) -> Result<bool, CacheManagerError> {
         let mut cleared = false;
 
@@ -127,6 +170,53 @@ impl UpEnvironmentsCache {
         Ok(())
     }
 
+    /// Records an environment that is not assigned to any work directory,
+    /// such as the one set up by `omni with`, so that the tools it uses
+    /// can be marked as required by it; such environments are kept for
+    /// the retention period of the environments history since they were
+    /// last used. Returns whether the environment is new, and its id.
+    pub fn register_ephemeral_environment(
+        &self,
+        environment: &UpEnvironment,
+    ) -> Result<(bool, String), CacheManagerError> {
+        let env_version_id = format!(
+            "{}%{}",
+            Self::EPHEMERAL_ENV_PREFIX,
+            environment.hash_string()
+        );
+
+        let mut new_env = true;
+        let mut db = CacheManager::get();
+        db.transaction(|tx| {
+            new_env = match tx.query_one::<bool>(
+                include_str!("database/sql/up_environments_check_env_version_exists.sql"),
+                params![&env_version_id],
+            ) {
+                Ok(found) => !found,
+                Err(CacheManagerError::SqlError(rusqlite::Error::QueryReturnedNoRows)) => true,
+                Err(err) => return Err(err),
+            };
+
+            // Insert the environment version, or refresh when it was last
+            // used if it already exists
+            tx.execute(
+                include_str!("database/sql/up_environments_insert_env_version.sql"),
+                params![
+                    &env_version_id,
+                    serde_json::to_string(&environment.versions)?,
+                    serde_json::to_string(&environment.paths)?,
+                    serde_json::to_string(&environment.env_vars)?,
+                    serde_json::to_string(&environment.config_modtimes)?,
+                    environment.config_hash,
+                ],
+            )?;
+
+            Ok(())
+        })?;
+
+        Ok((new_env, env_version_id))
+    }
+
     pub fn assign_environment(
         &self,
         workdir_id: &str,
@@ -239,7 +329,10 @@ impl UpEnvironmentsCache {
             )?;
             tx.execute(
                 include_str!("database/sql/up_environments_delete_orphaned_env.sql"),
-                [],
+                params![
+                    format!("{}%*", Self::EPHEMERAL_ENV_PREFIX),
+                    &cache_env_config.retention
+                ],
             )?;
 
             // Update last_seen_at for this workdir to track that we just ran omni up
@@ -442,6 +535,70 @@ impl UpEnvironment {
         versions.values().cloned().collect()
     }
 
+    /// Returns the tools that have different versions scoped to different
+    /// subdirectories of the requested dir, without any version covering
+    /// the requested dir itself
+    pub fn ambiguous_versions_for_dir(&self, dir: &str) -> Vec<UpAmbiguousVersions> {
+        let covered = self
+            .versions_for_dir(dir)
+            .into_iter()
+            .map(|version| version.tool)
+            .collect::<HashSet<_>>();
+
+        let mut scoped: BTreeMap<String, Vec<UpVersion>> = BTreeMap::new();
+        for version in self.versions.iter() {
+            if covered.contains(&version.tool) || version.dir.is_empty() {
+                continue;
+            }
+
+            // Only consider the versions scoped to a subdirectory of the
+            // requested dir, the other ones are unrelated to it
+            if !dir.is_empty() && !version.dir.starts_with(format!("{dir}/").as_str()) {
+                continue;
+            }
+
+            scoped
+                .entry(version.tool.clone())
+                .or_default()
+                .push(version.clone());
+        }
+
+        scoped
+            .into_iter()
+            .filter_map(|(tool, mut versions)| {
+                versions.sort_by(|a, b| {
+                    VersionParser::compare(&a.version, &b.version).then(a.dir.cmp(&b.dir))
+                });
+
+                let first = &versions[0].version;
+                if versions.iter().all(|version| &version.version == first) {
+                    return None;
+                }
+
+                Some(UpAmbiguousVersions { tool, versions })
+            })
+            .collect()
+    }
+
+    /// Returns the versions to use for the requested dir, resolving the
+    /// tools with ambiguous versions according to the given policy; the
+    /// tools with ambiguous versions are returned alongside
+    pub fn resolve_versions_for_dir(
+        &self,
+        dir: &str,
+        policy: UpAmbiguousVersionsPolicy,
+    ) -> (Vec<UpVersion>, Vec<UpAmbiguousVersions>) {
+        let mut versions = self.versions_for_dir(dir);
+        let ambiguous = self.ambiguous_versions_for_dir(dir);
+
+        if policy == UpAmbiguousVersionsPolicy::Newest {
+            versions.extend(ambiguous.iter().map(|tool| tool.newest().clone()));
+            versions.sort_by(|a, b| a.tool.cmp(&b.tool));
+        }
+
+        (versions, ambiguous)
+    }
+
     pub fn add_env_var<T>(&mut self, key: T, value: T) -> bool
     where
         T: AsRef<str>,
@@ -462,6 +619,7 @@ impl UpEnvironment {
             name: key.as_ref().to_string(),
             value: Some(value.as_ref().to_string()),
             operation,
+            command: None,
         };
 
         self.env_vars.push(up_env_var);
@@ -475,10 +633,12 @@ impl UpEnvironment {
     }
 
     pub fn add_path(&mut self, path: PathBuf) -> bool {
+        let policy = global_config().paths.canonicalize;
+        let path = canonicalize_path(path, policy);
         self.paths.retain(|p| p != &path);
 
         // Prepend anything that starts with the data_home()
-        if path.starts_with(data_home()) {
+        if path.starts_with(canonicalize_path(data_home(), policy)) {
             self.paths.insert(0, path);
         } else {
             self.paths.push(path);
@@ -552,6 +712,52 @@ impl UpEnvironment {
 
         false
     }
+
+    /// Merges the contributions of a previous environment that this one
+    /// does not provide; this is used when only a subset of the operations
+    /// was run, so that the contributions of the other operations are kept
+    /// instead of being dropped. The tool versions, paths and environment
+    /// variables provided by this environment take precedence.
+    pub fn merge_untouched(&mut self, previous: &UpEnvironment) {
+        for version in previous.versions.iter() {
+            if !self.versions.iter().any(|exists| {
+                exists.backend == version.backend
+                    && exists.normalized_name == version.normalized_name
+                    && exists.dir == version.dir
+            }) {
+                self.versions.push(version.clone());
+            }
+        }
+
+        for path in previous.paths.iter() {
+            if !self.paths.contains(path) {
+                self.paths.push(path.clone());
+            }
+        }
+
+        let env_var_names = self
+            .env_vars
+            .iter()
+            .map(|env_var| env_var.name.clone())
+            .collect::<HashSet<_>>();
+        self.env_vars.extend(
+            previous
+                .env_vars
+                .iter()
+                .filter(|env_var| !env_var_names.contains(&env_var.name))
+                .cloned(),
+        );
+    }
+
+    /// Keeps the configuration hash of the previous environment, if any,
+    /// and clears it otherwise; this is used when only a subset of the
+    /// operations was run, so that the work directory is not considered
+    /// up to date with a configuration that was only partially applied
+    pub fn keep_config_hash(&mut self, previous: Option<&UpEnvironment>) {
+        self.config_hash = previous
+            .map(|previous| previous.config_hash.clone())
+            .unwrap_or_default();
+    }
 }
 
 // TODO: deprecated, remove after leaving time to migrate to the new UpVersion
@@ -605,6 +811,31 @@ impl From<OldUpVersion> for UpVersion {
     }
 }
 
+/// A tool with different versions scoped to different subdirectories,
+/// sorted from the oldest to the newest version
+#[derive(Debug, Clone)]
+pub struct UpAmbiguousVersions {
+    pub tool: String,
+    pub versions: Vec<UpVersion>,
+}
+
+impl UpAmbiguousVersions {
+    pub fn newest(&self) -> &UpVersion {
+        self.versions
+            .last()
+            .expect("ambiguous versions cannot be empty")
+    }
+
+    /// Describes the versions and the subdirectories they are scoped to
+    pub fn describe(&self) -> String {
+        self.versions
+            .iter()
+            .map(|version| format!("{} in {}", version.version, version.dir))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Hash)]
 pub struct UpEnvVar {
     #[serde(
@@ -628,6 +859,32 @@ pub struct UpEnvVar {
         skip_serializing_if = "EnvOperationEnum::is_default"
     )]
     pub operation: EnvOperationEnum,
+    /// The command to run when applying the environment to get the
+    /// value of the variable, instead of using a stored value
+    #[serde(
+        rename = "c",
+        alias = "command",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub command: Option<String>,
+}
+
+impl UpEnvVar {
+    /// Resolves the value of path operations relative to the given root,
+    /// expanding `~` and normalizing the path so that the same directory
+    /// is always recorded the same way; other operations are unchanged
+    pub fn resolve_path(mut self, root: impl AsRef<Path>) -> Self {
+        if self.operation.is_path() {
+            self.value = self.value.map(|value| {
+                abs_path_from_path(Path::new(&value), Some(root.as_ref()))
+                    .to_string_lossy()
+                    .to_string()
+            });
+        }
+
+        self
+    }
 }
 
 impl From<EnvOperationConfig> for UpEnvVar {
@@ -636,6 +893,12 @@ impl From<EnvOperationConfig> for UpEnvVar {
             name: env_op.name,
             value: env_op.value,
             operation: env_op.operation,
+            // Only the commands to run when applying the environment are
+            // kept, the others should have been run during `omni up`
+            command: env_op
+                .command
+                .filter(|command| command.when == EnvCommandWhen::Apply)
+                .map(|command| command.command),
         }
     }
 }
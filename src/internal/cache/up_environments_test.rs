@@ -1,5 +1,7 @@
 use super::*;
 
+use crate::internal::config::parser::env_command_output;
+use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::testutils::run_with_env;
 use crate::internal::ConfigLoader;
 use crate::internal::ConfigValue;
@@ -105,6 +107,47 @@ mod up_environments_cache {
         });
     }
 
+    #[test]
+    fn test_register_ephemeral_environment() {
+        run_with_env(&[], || {
+            let cache = UpEnvironmentsCache::get();
+            let env = UpEnvironment::new().init();
+
+            let (is_new, env_id) = cache
+                .register_ephemeral_environment(&env)
+                .expect("Failed to register environment");
+            assert!(is_new);
+            assert!(env_id.starts_with("ephemeral%"));
+
+            let (is_new, same_env_id) = cache
+                .register_ephemeral_environment(&env)
+                .expect("Failed to register environment");
+            assert!(!is_new);
+            assert_eq!(same_env_id, env_id);
+        });
+    }
+
+    #[test]
+    fn test_ephemeral_environment_is_not_orphaned() {
+        run_with_env(&[], || {
+            let cache = UpEnvironmentsCache::get();
+            let env = UpEnvironment::new().init();
+
+            let (_is_new, env_id) = cache
+                .register_ephemeral_environment(&env)
+                .expect("Failed to register environment");
+
+            // Assigning an environment to a work directory triggers the
+            // cleanup of the orphaned environments
+            let mut workdir_env = UpEnvironment::new().init();
+            cache
+                .assign_environment("test-workdir", None, &mut workdir_env)
+                .expect("Failed to assign environment");
+
+            assert!(cache.environment_ids().contains(&env_id));
+        });
+    }
+
     #[test]
     fn test_assign_environment_with_different_sha() {
         run_with_env(&[], || {
@@ -618,6 +661,78 @@ mod up_environments_cache {
     }
 }
 
+mod check_config_hash {
+    use super::*;
+
+    #[test]
+    fn test_no_environment() {
+        run_with_env(&[], || {
+            let cache = UpEnvironmentsCache::get();
+
+            assert!(!cache.check_config_hash("test-workdir", "new-hash", "legacy-hash"));
+        });
+    }
+
+    #[test]
+    fn test_matching_hash() {
+        run_with_env(&[], || {
+            let cache = UpEnvironmentsCache::get();
+            let workdir_id = "test-workdir";
+            let mut env = UpEnvironment::new();
+            env.config_hash = "new-hash".to_string();
+            cache
+                .assign_environment(workdir_id, None, &mut env)
+                .expect("Failed to assign environment");
+
+            assert!(cache.check_config_hash(workdir_id, "new-hash", "legacy-hash"));
+            assert!(!cache.check_config_hash(workdir_id, "other-hash", "legacy-hash"));
+        });
+    }
+
+    #[test]
+    fn test_legacy_hash_accepted_once() {
+        run_with_env(&[], || {
+            let cache = UpEnvironmentsCache::get();
+            let workdir_id = "test-workdir";
+            let mut env = UpEnvironment::new();
+            env.config_hash = "legacy-hash".to_string();
+            cache
+                .assign_environment(workdir_id, None, &mut env)
+                .expect("Failed to assign environment");
+
+            // The legacy hash is accepted, and the new hash is stored
+            assert!(cache.check_config_hash(workdir_id, "new-hash", "legacy-hash"));
+            let retrieved = cache
+                .get_env(workdir_id)
+                .expect("Failed to get environment");
+            assert_eq!(retrieved.config_hash, "new-hash");
+
+            // The new hash is now the one being checked
+            assert!(cache.check_config_hash(workdir_id, "new-hash", "legacy-hash"));
+
+            // If the configuration changes, the legacy hash is not
+            // accepted anymore
+            assert!(!cache.check_config_hash(workdir_id, "changed-hash", "legacy-hash"));
+        });
+    }
+
+    #[test]
+    fn test_partial_environment_is_not_current() {
+        run_with_env(&[], || {
+            let cache = UpEnvironmentsCache::get();
+            let workdir_id = "test-workdir";
+            let mut env = UpEnvironment::new();
+            env.config_hash = "new-hash".to_string();
+            env.keep_config_hash(None);
+            cache
+                .assign_environment(workdir_id, None, &mut env)
+                .expect("Failed to assign environment");
+
+            assert!(!cache.check_config_hash(workdir_id, "new-hash", "legacy-hash"));
+        });
+    }
+}
+
 mod up_environment {
     use super::*;
 
@@ -684,6 +799,87 @@ mod up_environment {
         assert_eq!(dir2_versions[0].tool, "tool3");
     }
 
+    #[test]
+    fn test_ambiguous_versions_for_dir() {
+        let mut env = UpEnvironment::new();
+
+        for (tool, version, dir) in [
+            ("go", "1.22.0", "services/b"),
+            ("go", "1.21.0", "services/a"),
+            ("node", "20.0.0", "services/a"),
+            ("node", "20.0.0", "services/b"),
+            ("python", "3.12.0", "services/a"),
+            ("ruby", "3.2.0", "tools/a"),
+            ("ruby", "3.3.0", "tools/b"),
+        ] {
+            env.add_version(UpVersionParams {
+                backend: "",
+                tool,
+                plugin_name: tool,
+                normalized_name: tool,
+                version,
+                bin_path: "bin",
+                dirs: BTreeSet::from([dir.to_string()]),
+                env_vars: Vec::new(),
+            });
+        }
+
+        // Only the tools with different versions are ambiguous at the root
+        let root = env.ambiguous_versions_for_dir("");
+        assert_eq!(
+            root.iter()
+                .map(|tool| tool.tool.as_str())
+                .collect::<Vec<_>>(),
+            vec!["go", "ruby"]
+        );
+        assert_eq!(root[0].newest().version, "1.22.0");
+        assert_eq!(
+            root[0].describe(),
+            "1.21.0 in services/a, 1.22.0 in services/b"
+        );
+
+        // Only the versions scoped under the requested dir are considered
+        let services = env.ambiguous_versions_for_dir("services");
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].tool, "go");
+
+        // A directory covered by a scope is not ambiguous
+        assert!(env.ambiguous_versions_for_dir("services/a").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_versions_for_dir_with_root_pin() {
+        let mut env = UpEnvironment::new();
+
+        for (version, dir) in [
+            ("1.20.0", ""),
+            ("1.21.0", "services/a"),
+            ("1.22.0", "services/b"),
+        ] {
+            env.add_version(UpVersionParams {
+                backend: "",
+                tool: "go",
+                plugin_name: "go",
+                normalized_name: "go",
+                version,
+                bin_path: "bin",
+                dirs: BTreeSet::from([dir.to_string()]),
+                env_vars: Vec::new(),
+            });
+        }
+
+        // A root-level version takes precedence over the subdirectories
+        for policy in [
+            UpAmbiguousVersionsPolicy::Newest,
+            UpAmbiguousVersionsPolicy::Error,
+        ] {
+            let (versions, ambiguous) = env.resolve_versions_for_dir("", policy);
+            assert_eq!(versions.len(), 1);
+            assert_eq!(versions[0].version, "1.20.0");
+            assert!(ambiguous.is_empty());
+        }
+    }
+
     #[test]
     fn test_env_vars() {
         let mut env = UpEnvironment::new();
@@ -703,6 +899,7 @@ mod up_environment {
             name: "KEY3".to_string(),
             value: Some("value3".to_string()),
             operation: EnvOperationEnum::Set,
+            command: None,
         }];
         assert!(env.add_raw_env_vars(raw_vars));
         assert_eq!(env.env_vars.len(), 3);
@@ -729,6 +926,38 @@ mod up_environment {
         });
     }
 
+    #[test]
+    fn test_paths_through_symlink() {
+        run_with_env(&[], || {
+            let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+            let base = std::fs::canonicalize(tempdir.path()).expect("failed to canonicalize");
+            std::fs::create_dir_all(base.join("real/bin")).expect("failed to create dir");
+            std::os::unix::fs::symlink(base.join("real"), base.join("link"))
+                .expect("failed to create symlink");
+
+            // By default, the path is recorded as its physical location,
+            // so that it is not duplicated when reached through the symlink
+            let mut env = UpEnvironment::new();
+            assert!(env.add_path(base.join("link/bin")));
+            assert!(env.add_path(base.join("real/bin")));
+            assert_eq!(env.paths, vec![base.join("real/bin")]);
+
+            // With the logical policy, the path is kept as provided
+            if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
+                *config_value = ConfigValue::from_str("paths:\n  canonicalize: logical")
+                    .expect("Failed to create config value");
+                true
+            }) {
+                panic!("Failed to edit main user config file: {err}");
+            }
+            config::flush_config("/");
+
+            let mut env = UpEnvironment::new();
+            assert!(env.add_path(base.join("link/bin")));
+            assert_eq!(env.paths, vec![base.join("link/bin")]);
+        });
+    }
+
     #[test]
     fn test_version_management() {
         let mut env = UpEnvironment::new();
@@ -763,6 +992,106 @@ mod up_environment {
         assert!(env.add_version_data_path("plugin-1", "1.0.0", "dir1", "/data/path"));
         assert_eq!(env.versions[0].data_path, Some("/data/path".to_string()));
     }
+
+    fn add_tool_version(env: &mut UpEnvironment, backend: &str, tool: &str, version: &str) {
+        env.add_version(UpVersionParams {
+            backend,
+            tool,
+            plugin_name: tool,
+            normalized_name: tool,
+            version,
+            bin_path: "bin",
+            dirs: BTreeSet::new(),
+            env_vars: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_merge_untouched_keeps_previous_contributions() {
+        let mut previous = UpEnvironment::new();
+        add_tool_version(&mut previous, "ghrelease", "owner/tool", "1.0.0");
+        add_tool_version(&mut previous, "", "python", "3.11.9");
+        previous.paths = vec![PathBuf::from("/previous/bin")];
+        previous.add_env_var("PREVIOUS", "kept");
+        previous.add_env_var("SHARED", "old");
+
+        let mut env = UpEnvironment::new();
+        add_tool_version(&mut env, "ghrelease", "owner/tool", "2.0.0");
+        env.paths = vec![PathBuf::from("/current/bin")];
+        env.add_env_var("SHARED", "new");
+
+        env.merge_untouched(&previous);
+
+        let versions = env
+            .versions
+            .iter()
+            .map(|version| (version.tool.as_str(), version.version.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            versions,
+            vec![("owner/tool", "2.0.0"), ("python", "3.11.9")]
+        );
+
+        assert_eq!(
+            env.paths,
+            vec![
+                PathBuf::from("/current/bin"),
+                PathBuf::from("/previous/bin")
+            ]
+        );
+
+        let env_vars = env
+            .env_vars
+            .iter()
+            .map(|env_var| (env_var.name.as_str(), env_var.value.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            env_vars,
+            vec![("SHARED", Some("new")), ("PREVIOUS", Some("kept"))]
+        );
+    }
+
+    #[test]
+    fn test_merge_untouched_keeps_versions_scoped_to_other_dirs() {
+        let mut previous = UpEnvironment::new();
+        previous.add_version(UpVersionParams {
+            backend: "",
+            tool: "node",
+            plugin_name: "node",
+            normalized_name: "node",
+            version: "18.0.0",
+            bin_path: "bin",
+            dirs: BTreeSet::from(["".to_string(), "legacy".to_string()]),
+            env_vars: Vec::new(),
+        });
+
+        let mut env = UpEnvironment::new();
+        add_tool_version(&mut env, "", "node", "20.0.0");
+
+        env.merge_untouched(&previous);
+
+        let versions = env
+            .versions
+            .iter()
+            .map(|version| (version.version.as_str(), version.dir.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec![("20.0.0", ""), ("18.0.0", "legacy")]);
+    }
+
+    #[test]
+    fn test_keep_config_hash() {
+        let mut previous = UpEnvironment::new();
+        previous.config_hash = "previous-hash".to_string();
+
+        let mut env = UpEnvironment::new();
+        env.config_hash = "current-hash".to_string();
+        env.keep_config_hash(Some(&previous));
+        assert_eq!(env.config_hash, "previous-hash");
+
+        env.config_hash = "current-hash".to_string();
+        env.keep_config_hash(None);
+        assert_eq!(env.config_hash, "");
+    }
 }
 
 mod up_version {
@@ -802,6 +1131,7 @@ mod up_env_var {
             name: "TEST_VAR".to_string(),
             value: Some("test_value".to_string()),
             operation: EnvOperationEnum::Set,
+            command: None,
         };
 
         let env_var: UpEnvVar = config.into();
@@ -818,11 +1148,13 @@ mod up_env_var {
                     name: "VAR1".to_string(),
                     value: Some("value1".to_string()),
                     operation: EnvOperationEnum::Set,
+                    command: None,
                 },
                 EnvOperationConfig {
                     name: "VAR2".to_string(),
                     value: Some("value2".to_string()),
                     operation: EnvOperationEnum::Append,
+                    command: None,
                 },
             ],
         };
@@ -832,4 +1164,122 @@ mod up_env_var {
         assert_eq!(env_vars[0].name, "VAR1");
         assert_eq!(env_vars[1].name, "VAR2");
     }
+    fn env_config(yaml: &str) -> EnvConfig {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        EnvConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop())
+    }
+
+    fn env_vars_hash(env_vars: Vec<UpEnvVar>) -> String {
+        let mut environment = UpEnvironment::new();
+        environment.env_vars = env_vars;
+        environment.hash_string()
+    }
+
+    #[test]
+    fn test_command_when_apply_is_kept() {
+        let config = env_config(concat!(
+            "GIT_COMMIT_SHA:\n",
+            "  set:\n",
+            "    command: git rev-parse HEAD\n",
+            "    when: apply\n",
+        ));
+
+        let env_vars: Vec<UpEnvVar> = config.into();
+        assert_eq!(env_vars.len(), 1);
+        assert_eq!(env_vars[0].name, "GIT_COMMIT_SHA");
+        assert_eq!(env_vars[0].operation, EnvOperationEnum::Set);
+        assert_eq!(env_vars[0].value, None);
+        assert_eq!(env_vars[0].command.as_deref(), Some("git rev-parse HEAD"));
+    }
+
+    #[test]
+    fn test_command_when_up_is_run_and_baked() {
+        let mut config = env_config(concat!(
+            "GIT_COMMIT_SHA:\n",
+            "  command: printf 'abc\\n\\n'\n",
+        ));
+        assert_eq!(config.operations.len(), 1);
+
+        let cwd = std::env::current_dir().expect("failed to get current dir");
+        for env_op in config.operations.iter_mut() {
+            env_op.run_up_command(&cwd).expect("command should succeed");
+        }
+
+        let env_vars: Vec<UpEnvVar> = config.into();
+        assert_eq!(env_vars[0].value.as_deref(), Some("abc"));
+        assert_eq!(env_vars[0].command, None);
+    }
+
+    #[test]
+    fn test_command_when_up_failure_unsets() {
+        let mut config = env_config("GIT_COMMIT_SHA:\n  command: exit 1\n");
+
+        let cwd = std::env::current_dir().expect("failed to get current dir");
+        let err = config.operations[0]
+            .run_up_command(&cwd)
+            .expect_err("command should fail");
+        assert_eq!(err, "exited with code 1");
+
+        let env_vars: Vec<UpEnvVar> = config.into();
+        assert_eq!(env_vars[0].operation, EnvOperationEnum::Set);
+        assert_eq!(env_vars[0].value, None);
+        assert_eq!(env_vars[0].command, None);
+    }
+
+    #[test]
+    fn test_invalid_command_when() {
+        let config_value = ConfigValue::from_str(concat!(
+            "GIT_COMMIT_SHA:\n",
+            "  command: git rev-parse HEAD\n",
+            "  when: later\n",
+        ))
+        .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::new().with_file("test.yaml");
+
+        let config = EnvConfig::from_config_value(Some(config_value), &error_handler);
+
+        assert!(config.operations.is_empty());
+        assert_eq!(error_handler.errors().len(), 1);
+    }
+
+    #[test]
+    fn test_hash_includes_baked_value_and_command() {
+        let baked = |value: &str| UpEnvVar {
+            name: "GIT_COMMIT_SHA".to_string(),
+            value: Some(value.to_string()),
+            operation: EnvOperationEnum::Set,
+            command: None,
+        };
+        let applied = |command: &str| UpEnvVar {
+            name: "GIT_COMMIT_SHA".to_string(),
+            value: None,
+            operation: EnvOperationEnum::Set,
+            command: Some(command.to_string()),
+        };
+
+        assert_ne!(
+            env_vars_hash(vec![baked("abc")]),
+            env_vars_hash(vec![baked("def")])
+        );
+        assert_eq!(
+            env_vars_hash(vec![applied("git rev-parse HEAD")]),
+            env_vars_hash(vec![applied("git rev-parse HEAD")])
+        );
+        assert_ne!(
+            env_vars_hash(vec![applied("git rev-parse HEAD")]),
+            env_vars_hash(vec![applied("git describe")])
+        );
+    }
+
+    #[test]
+    fn test_command_output_timeout() {
+        let cwd = std::env::current_dir().expect("failed to get current dir");
+
+        let started = std::time::Instant::now();
+        let err = env_command_output("sleep 5", &cwd, std::time::Duration::from_millis(100))
+            .expect_err("command should time out");
+
+        assert!(err.starts_with("timed out"), "unexpected error: {err}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
 }
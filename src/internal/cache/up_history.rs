@@ -0,0 +1,334 @@
+use std::panic::catch_unwind;
+use std::time::Duration;
+
+use gethostname::gethostname;
+use rusqlite::params;
+use rusqlite::Row;
+use serde::Deserialize;
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::internal::build::current_omni_version;
+use crate::internal::cache::database::FromRow;
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+use crate::internal::config::global_config;
+
+/// The outcome of an `omni up` run, or of one of its operations
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpHistoryOutcome {
+    Success,
+    Failed,
+    Skipped,
+    Interrupted,
+}
+
+impl UpHistoryOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    fn from_str(value: &str) -> Self {
+        match value {
+            "success" => Self::Success,
+            "failed" => Self::Failed,
+            "skipped" => Self::Skipped,
+            _ => Self::Interrupted,
+        }
+    }
+}
+
+impl std::fmt::Display for UpHistoryOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An operation of an `omni up` run, along with its outcome; operations
+/// are skipped until they are run
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UpHistoryOperation {
+    pub name: String,
+    pub outcome: UpHistoryOutcome,
+}
+
+impl UpHistoryOperation {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            outcome: UpHistoryOutcome::Skipped,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpHistoryCache {}
+
+impl UpHistoryCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    /// Records the start of an `omni up` run for the workdir, returning
+    /// the id of the history entry; the entry is considered interrupted
+    /// until `finish` is called for it.
+    pub fn start(
+        &self,
+        workdir_id: &str,
+        config_hash: &str,
+        operations: &[UpHistoryOperation],
+    ) -> Result<i64, CacheManagerError> {
+        let user = whoami::username().unwrap_or_else(|_| "unknown".to_string());
+        let hostname = match catch_unwind(gethostname) {
+            Ok(hostname) => hostname.to_string_lossy().to_string(),
+            Err(_) => "unknown".to_string(),
+        };
+        let operations = serde_json::to_string(operations)?;
+        let cache_config = global_config().cache.up_history;
+
+        let mut db = CacheManager::get();
+        db.transaction(|tx| {
+            let up_history_id: i64 = tx.query_one(
+                include_str!("database/sql/up_history_add.sql"),
+                params![
+                    &workdir_id,
+                    &current_omni_version().to_string(),
+                    &config_hash,
+                    &user,
+                    &hostname,
+                    &operations,
+                ],
+            )?;
+
+            // Only apply retention cleanup if retention > 0
+            if cache_config.retention > 0 {
+                tx.execute(
+                    include_str!("database/sql/up_history_cleanup_retention.sql"),
+                    params![&cache_config.retention],
+                )?;
+            }
+
+            tx.execute(
+                include_str!("database/sql/up_history_cleanup_max_per_workdir.sql"),
+                params![&cache_config.max_per_workdir],
+            )?;
+
+            Ok(up_history_id)
+        })
+    }
+
+    /// Records the end of an `omni up` run
+    pub fn finish(
+        &self,
+        up_history_id: i64,
+        duration: Duration,
+        outcome: UpHistoryOutcome,
+        operations: &[UpHistoryOperation],
+    ) -> Result<(), CacheManagerError> {
+        let duration_ms = duration.as_millis() as i64;
+        let operations = serde_json::to_string(operations)?;
+
+        CacheManager::get().execute(
+            include_str!("database/sql/up_history_finish.sql"),
+            params![&up_history_id, &duration_ms, outcome.as_str(), &operations],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the history of the `omni up` runs for the workdir, with
+    /// the most recent run first
+    pub fn list(&self, workdir_id: &str) -> Vec<UpHistoryEntry> {
+        CacheManager::get()
+            .query_as(
+                include_str!("database/sql/up_history_list.sql"),
+                params![&workdir_id],
+            )
+            .unwrap_or_default()
+    }
+
+    /// Returns when the last successful `omni up` run for the workdir
+    /// started, if any
+    pub fn last_success(&self, workdir_id: &str) -> Option<OffsetDateTime> {
+        let started_at: String = CacheManager::get()
+            .query_one_optional(
+                include_str!("database/sql/up_history_get_last_success.sql"),
+                params![&workdir_id],
+            )
+            .ok()??;
+
+        OffsetDateTime::parse(&started_at, &Rfc3339).ok()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpHistoryEntry {
+    pub id: i64,
+    pub workdir_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    pub duration: Option<Duration>,
+    pub omni_version: String,
+    pub config_hash: String,
+    pub user: String,
+    pub hostname: String,
+    pub outcome: UpHistoryOutcome,
+    pub operations: Vec<UpHistoryOperation>,
+}
+
+impl FromRow for UpHistoryEntry {
+    fn from_row(row: &Row) -> Result<Self, CacheManagerError> {
+        let started_at_str: String = row.get("started_at")?;
+        let started_at = OffsetDateTime::parse(&started_at_str, &Rfc3339)?;
+
+        let duration_ms: Option<i64> = row.get("duration_ms")?;
+        let duration = duration_ms.map(|ms| Duration::from_millis(ms.max(0) as u64));
+
+        let outcome: String = row.get("outcome")?;
+
+        let operations_json: String = row.get("operations")?;
+        let operations: Vec<UpHistoryOperation> = serde_json::from_str(&operations_json)?;
+
+        Ok(Self {
+            id: row.get("up_history_id")?,
+            workdir_id: row.get("workdir_id")?,
+            started_at,
+            duration,
+            omni_version: row.get("omni_version")?,
+            config_hash: row.get("config_hash")?,
+            user: row.get("user")?,
+            hostname: row.get("hostname")?,
+            outcome: UpHistoryOutcome::from_str(&outcome),
+            operations,
+        })
+    }
+}
+
+impl UpHistoryEntry {
+    /// Returns the details of the entry, one field per line
+    pub fn render_details(&self) -> String {
+        let mut lines = vec![
+            format!("started:    {}", format_timestamp(&self.started_at)),
+            format!("outcome:    {}", self.outcome),
+            format!("duration:   {}", format_run_duration(self.duration)),
+            format!("omni:       {}", self.omni_version),
+            format!("config:     {}", self.config_hash),
+            format!("user:       {}@{}", self.user, self.hostname),
+        ];
+
+        if self.operations.is_empty() {
+            lines.push("operations: none".to_string());
+        } else {
+            lines.push("operations:".to_string());
+            for operation in &self.operations {
+                lines.push(format!("  - {} ({})", operation.name, operation.outcome));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Renders the given history entries as a table, one entry per row
+pub fn render_up_history_table(entries: &[UpHistoryEntry]) -> String {
+    let headers = ["STARTED", "OUTCOME", "DURATION", "OMNI", "CONFIG", "USER"];
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            [
+                format_timestamp(&entry.started_at),
+                entry.outcome.to_string(),
+                format_run_duration(entry.duration),
+                entry.omni_version.clone(),
+                entry.config_hash.clone(),
+                format!("{}@{}", entry.user, entry.hostname),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(idx, header)| {
+            rows.iter()
+                .map(|row| row[idx].len())
+                .chain(std::iter::once(header.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let render_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    std::iter::once(render_row(headers.to_vec()))
+        .chain(
+            rows.iter()
+                .map(|row| render_row(row.iter().map(|cell| cell.as_str()).collect())),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formats the time elapsed since the given timestamp, in a human
+/// readable way (e.g. "12 days ago")
+pub fn format_time_ago(timestamp: &OffsetDateTime) -> String {
+    let elapsed = (OffsetDateTime::now_utc() - *timestamp)
+        .whole_seconds()
+        .max(0);
+
+    let (value, unit) = match elapsed {
+        0..60 => return "just now".to_string(),
+        60..3600 => (elapsed / 60, "minute"),
+        3600..86400 => (elapsed / 3600, "hour"),
+        _ => (elapsed / 86400, "day"),
+    };
+
+    format!("{} {}{} ago", value, unit, if value > 1 { "s" } else { "" })
+}
+
+fn format_timestamp(timestamp: &OffsetDateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        timestamp.year(),
+        timestamp.month() as u8,
+        timestamp.day(),
+        timestamp.hour(),
+        timestamp.minute(),
+        timestamp.second(),
+    )
+}
+
+fn format_run_duration(duration: Option<Duration>) -> String {
+    let duration = match duration {
+        Some(duration) => duration,
+        None => return "-".to_string(),
+    };
+
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+#[path = "up_history_test.rs"]
+mod tests;
@@ -0,0 +1,301 @@
+use super::*;
+
+use crate::internal::testutils::run_with_env;
+use crate::internal::ConfigLoader;
+use crate::internal::ConfigValue;
+
+fn operations(names: &[(&str, UpHistoryOutcome)]) -> Vec<UpHistoryOperation> {
+    names
+        .iter()
+        .map(|(name, outcome)| UpHistoryOperation {
+            name: name.to_string(),
+            outcome: *outcome,
+        })
+        .collect()
+}
+
+/// Moves the start of the given history entry back in time
+fn backdate(up_history_id: i64, days: i64) {
+    CacheManager::get()
+        .execute(
+            "UPDATE up_history SET started_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2) WHERE up_history_id = ?1",
+            params![&up_history_id, &format!("-{days} days")],
+        )
+        .expect("failed to backdate entry");
+}
+
+fn set_cache_config(yaml: &str) {
+    if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
+        *config_value = ConfigValue::from_str(yaml).expect("Failed to create config value");
+        true
+    }) {
+        panic!("Failed to edit main user config file: {err}");
+    }
+}
+
+mod up_history_cache {
+    use super::*;
+
+    #[test]
+    fn records_successful_run() {
+        run_with_env(&[], || {
+            let cache = UpHistoryCache::get();
+            let workdir_id = "test-workdir";
+
+            let mut ops = operations(&[("custom", UpHistoryOutcome::Skipped)]);
+            let id = cache
+                .start(workdir_id, "abcdef", &ops)
+                .expect("Failed to start history entry");
+
+            ops[0].outcome = UpHistoryOutcome::Success;
+            cache
+                .finish(
+                    id,
+                    Duration::from_millis(1500),
+                    UpHistoryOutcome::Success,
+                    &ops,
+                )
+                .expect("Failed to finish history entry");
+
+            let history = cache.list(workdir_id);
+            assert_eq!(history.len(), 1);
+
+            let entry = &history[0];
+            assert_eq!(entry.id, id);
+            assert_eq!(entry.workdir_id, workdir_id);
+            assert_eq!(entry.config_hash, "abcdef");
+            assert_eq!(entry.outcome, UpHistoryOutcome::Success);
+            assert_eq!(entry.duration, Some(Duration::from_millis(1500)));
+            assert_eq!(entry.omni_version, current_omni_version().to_string());
+            assert!(!entry.user.is_empty());
+            assert!(!entry.hostname.is_empty());
+            assert_eq!(entry.operations, ops);
+
+            assert!(cache.last_success(workdir_id).is_some());
+        });
+    }
+
+    #[test]
+    fn records_failed_run() {
+        run_with_env(&[], || {
+            let cache = UpHistoryCache::get();
+            let workdir_id = "test-workdir";
+
+            let ops = operations(&[
+                ("bootstrap", UpHistoryOutcome::Failed),
+                ("custom", UpHistoryOutcome::Skipped),
+            ]);
+            let id = cache
+                .start(workdir_id, "abcdef", &ops)
+                .expect("Failed to start history entry");
+            cache
+                .finish(id, Duration::from_secs(2), UpHistoryOutcome::Failed, &ops)
+                .expect("Failed to finish history entry");
+
+            let history = cache.list(workdir_id);
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].outcome, UpHistoryOutcome::Failed);
+            assert_eq!(history[0].operations, ops);
+
+            assert!(cache.last_success(workdir_id).is_none());
+        });
+    }
+
+    #[test]
+    fn unfinished_run_is_interrupted() {
+        run_with_env(&[], || {
+            let cache = UpHistoryCache::get();
+            let workdir_id = "test-workdir";
+
+            let ops = operations(&[("custom", UpHistoryOutcome::Skipped)]);
+            cache
+                .start(workdir_id, "abcdef", &ops)
+                .expect("Failed to start history entry");
+
+            let history = cache.list(workdir_id);
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].outcome, UpHistoryOutcome::Interrupted);
+            assert_eq!(history[0].duration, None);
+        });
+    }
+
+    #[test]
+    fn last_success_ignores_later_failures() {
+        run_with_env(&[], || {
+            let cache = UpHistoryCache::get();
+            let workdir_id = "test-workdir";
+
+            let success_id = cache.start(workdir_id, "abcdef", &[]).unwrap();
+            cache
+                .finish(success_id, Duration::ZERO, UpHistoryOutcome::Success, &[])
+                .unwrap();
+            backdate(success_id, 12);
+
+            let failed_id = cache.start(workdir_id, "abcdef", &[]).unwrap();
+            cache
+                .finish(failed_id, Duration::ZERO, UpHistoryOutcome::Failed, &[])
+                .unwrap();
+
+            let last_success = cache
+                .last_success(workdir_id)
+                .expect("should have a successful run");
+            assert_eq!(format_time_ago(&last_success), "12 days ago");
+
+            // Other workdirs are not affected
+            assert!(cache.last_success("other-workdir").is_none());
+        });
+    }
+
+    #[test]
+    fn retention_trims_old_entries() {
+        run_with_env(&[], || {
+            set_cache_config("cache:\n  up_history:\n    retention: 30d");
+            assert_eq!(global_config().cache.up_history.retention, 30 * 86400);
+
+            let cache = UpHistoryCache::get();
+            let workdir_id = "test-workdir";
+
+            let old_id = cache.start(workdir_id, "old", &[]).unwrap();
+            backdate(old_id, 31);
+            let recent_id = cache.start(workdir_id, "recent", &[]).unwrap();
+            backdate(recent_id, 29);
+
+            // Cleanup happens when a new run is recorded
+            cache.start(workdir_id, "new", &[]).unwrap();
+
+            let hashes = cache
+                .list(workdir_id)
+                .into_iter()
+                .map(|entry| entry.config_hash)
+                .collect::<Vec<_>>();
+            assert_eq!(hashes, vec!["new", "recent"]);
+        });
+    }
+
+    #[test]
+    fn max_per_workdir_trims_oldest_entries() {
+        run_with_env(&[], || {
+            set_cache_config("cache:\n  up_history:\n    max_per_workdir: 2");
+            assert_eq!(global_config().cache.up_history.max_per_workdir, 2);
+
+            let cache = UpHistoryCache::get();
+            for hash in ["first", "second", "third"] {
+                cache.start("test-workdir", hash, &[]).unwrap();
+            }
+            cache.start("other-workdir", "other", &[]).unwrap();
+
+            let hashes = cache
+                .list("test-workdir")
+                .into_iter()
+                .map(|entry| entry.config_hash)
+                .collect::<Vec<_>>();
+            assert_eq!(hashes, vec!["third", "second"]);
+            assert_eq!(cache.list("other-workdir").len(), 1);
+        });
+    }
+}
+
+mod render {
+    use super::*;
+
+    use time::macros::datetime;
+
+    fn entry(
+        started_at: OffsetDateTime,
+        duration: Option<Duration>,
+        outcome: UpHistoryOutcome,
+    ) -> UpHistoryEntry {
+        UpHistoryEntry {
+            id: 1,
+            workdir_id: "test-workdir".to_string(),
+            started_at,
+            duration,
+            omni_version: "1.2.3".to_string(),
+            config_hash: "0123456789abcdef".to_string(),
+            user: "jdoe".to_string(),
+            hostname: "laptop".to_string(),
+            outcome,
+            operations: operations(&[
+                ("bootstrap", UpHistoryOutcome::Success),
+                ("custom", UpHistoryOutcome::Failed),
+                ("go", UpHistoryOutcome::Skipped),
+            ]),
+        }
+    }
+
+    #[test]
+    fn table() {
+        let entries = vec![
+            entry(
+                datetime!(2026-10-16 09:30:05 UTC),
+                Some(Duration::from_millis(2500)),
+                UpHistoryOutcome::Failed,
+            ),
+            entry(
+                datetime!(2026-10-04 17:02:45 UTC),
+                Some(Duration::from_secs(125)),
+                UpHistoryOutcome::Success,
+            ),
+            entry(
+                datetime!(2026-10-01 08:00:00 UTC),
+                None,
+                UpHistoryOutcome::Interrupted,
+            ),
+        ];
+
+        assert_eq!(
+            render_up_history_table(&entries),
+            [
+                "STARTED              OUTCOME      DURATION  OMNI   CONFIG            USER",
+                "2026-10-16 09:30:05  failed       2.5s      1.2.3  0123456789abcdef  jdoe@laptop",
+                "2026-10-04 17:02:45  success      2m05s     1.2.3  0123456789abcdef  jdoe@laptop",
+                "2026-10-01 08:00:00  interrupted  -         1.2.3  0123456789abcdef  jdoe@laptop",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn details() {
+        let entry = entry(
+            datetime!(2026-10-16 09:30:05 UTC),
+            Some(Duration::from_millis(2500)),
+            UpHistoryOutcome::Failed,
+        );
+
+        assert_eq!(
+            entry.render_details(),
+            [
+                "started:    2026-10-16 09:30:05",
+                "outcome:    failed",
+                "duration:   2.5s",
+                "omni:       1.2.3",
+                "config:     0123456789abcdef",
+                "user:       jdoe@laptop",
+                "operations:",
+                "  - bootstrap (success)",
+                "  - custom (failed)",
+                "  - go (skipped)",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn time_ago() {
+        let now = OffsetDateTime::now_utc();
+        assert_eq!(format_time_ago(&now), "just now");
+        assert_eq!(
+            format_time_ago(&(now - time::Duration::minutes(1))),
+            "1 minute ago"
+        );
+        assert_eq!(
+            format_time_ago(&(now - time::Duration::hours(5))),
+            "5 hours ago"
+        );
+        assert_eq!(
+            format_time_ago(&(now - time::Duration::days(12))),
+            "12 days ago"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use rusqlite::params;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::database::RowExt;
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::CacheManagerError;
+use crate::internal::config::global_config;
+use crate::internal::workdir;
+
+/// The sequence of the work directories entered by the user, through
+/// the omni cd and clone commands or the prompt hook, which allows to
+/// navigate back to the previous ones
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkdirHistoryCache {}
+
+impl WorkdirHistoryCache {
+    pub fn get() -> Self {
+        Self {}
+    }
+
+    /// Records that the work directory containing the given path was
+    /// entered, and trims the history to its configured bounds; returns
+    /// whether an entry was added, which is not the case if the path is
+    /// not in a work directory, or if that work directory is already the
+    /// last entry of the history
+    pub fn enter(&self, path: &str) -> Result<bool, CacheManagerError> {
+        let wd = workdir(path);
+        let wd_root = match wd.root() {
+            Some(wd_root) => wd_root.to_string(),
+            None => return Ok(false),
+        };
+
+        self.add(&wd_root)
+    }
+
+    /// Records the work directory being left, which is the current one,
+    /// and the work directory containing the given path being entered,
+    /// so that it is possible to navigate back to either of them
+    pub fn navigate(&self, path: &str) -> Result<(), CacheManagerError> {
+        self.enter(".")?;
+        self.enter(path)?;
+        Ok(())
+    }
+
+    /// Records the given path as entered, unless it is already the last
+    /// entry of the history
+    fn add(&self, path: &str) -> Result<bool, CacheManagerError> {
+        let mut db = CacheManager::get();
+
+        // The prompt hook records the current work directory each time
+        // it runs, so check first without writing, to avoid waiting for
+        // the database when another process is writing to it
+        let last: Vec<String> = db.query_as(
+            include_str!("database/sql/workdir_history_get_last.sql"),
+            params![],
+        )?;
+        if last.first().is_some_and(|last| last == path) {
+            return Ok(false);
+        }
+
+        let cache_config = global_config().cache.workdir_history;
+        db.transaction(|tx| {
+            let added = tx.execute(
+                include_str!("database/sql/workdir_history_add.sql"),
+                params![&path],
+            )?;
+
+            if added == 0 {
+                return Ok(false);
+            }
+
+            tx.execute(
+                include_str!("database/sql/workdir_history_cleanup_max_entries.sql"),
+                params![&cache_config.max_entries],
+            )?;
+
+            Ok(true)
+        })
+    }
+
+    /// Returns the entries of the history, the most recent first
+    pub fn entries(&self) -> Vec<String> {
+        CacheManager::get()
+            .query_as(
+                include_str!("database/sql/workdir_history_list.sql"),
+                params![],
+            )
+            .unwrap_or_default()
+    }
+
+    /// Returns the work directories that can be navigated back to, the
+    /// most recent first, without the given current work directory
+    pub fn previous(&self, current: Option<&str>) -> PreviousWorkdirs {
+        let mut previous = PreviousWorkdirs::default();
+
+        for path in self.entries() {
+            if Some(path.as_str()) == current
+                || previous.workdirs.contains(&path)
+                || previous.missing.contains(&path)
+            {
+                continue;
+            }
+
+            if Path::new(&path).is_dir() {
+                previous.workdirs.push(path);
+            } else {
+                previous.missing.push(path);
+            }
+        }
+
+        previous
+    }
+}
+
+/// The work directories of the history that can be navigated back to
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PreviousWorkdirs {
+    /// The work directories that still exist, the most recent first
+    pub workdirs: Vec<String>,
+    /// The work directories that were skipped since their directory
+    /// does not exist anymore
+    pub missing: Vec<String>,
+}
+
+impl PreviousWorkdirs {
+    /// Returns the work directory at the given position, where `1` is
+    /// the previous work directory, `2` the one before, and so on
+    pub fn nth(&self, index: usize) -> Option<&str> {
+        index
+            .checked_sub(1)
+            .and_then(|index| self.workdirs.get(index))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+#[path = "workdir_history_test.rs"]
+mod tests;
@@ -0,0 +1,177 @@
+use super::*;
+
+use crate::internal::testutils::run_with_env;
+use crate::internal::ConfigLoader;
+use crate::internal::ConfigValue;
+
+fn set_cache_config(yaml: &str) {
+    if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
+        *config_value = ConfigValue::from_str(yaml).expect("Failed to create config value");
+        true
+    }) {
+        panic!("Failed to edit main user config file: {err}");
+    }
+}
+
+/// Creates the given directories under a temporary directory, returning
+/// that temporary directory and the paths of the created directories
+fn dirs(names: &[&str]) -> (tempfile::TempDir, Vec<String>) {
+    let tmp = tempfile::tempdir().expect("failed to create temp dir");
+    let paths = names
+        .iter()
+        .map(|name| {
+            let path = tmp.path().join(name);
+            std::fs::create_dir_all(&path).expect("failed to create dir");
+            path.to_string_lossy().to_string()
+        })
+        .collect();
+
+    (tmp, paths)
+}
+
+mod workdir_history_cache {
+    use super::*;
+
+    #[test]
+    fn records_entries() {
+        run_with_env(&[], || {
+            let cache = WorkdirHistoryCache::get();
+
+            assert!(cache.add("/wt/org/api").unwrap());
+            assert!(cache.add("/wt/org/web").unwrap());
+            assert!(cache.add("/wt/org/api").unwrap());
+
+            assert_eq!(
+                cache.entries(),
+                vec!["/wt/org/api", "/wt/org/web", "/wt/org/api"]
+            );
+        });
+    }
+
+    #[test]
+    fn consecutive_entries_collapse() {
+        run_with_env(&[], || {
+            let cache = WorkdirHistoryCache::get();
+
+            assert!(cache.add("/wt/org/api").unwrap());
+            assert!(!cache.add("/wt/org/api").unwrap());
+            assert!(cache.add("/wt/org/web").unwrap());
+            assert!(!cache.add("/wt/org/web").unwrap());
+            assert!(!cache.add("/wt/org/web").unwrap());
+
+            assert_eq!(cache.entries(), vec!["/wt/org/web", "/wt/org/api"]);
+        });
+    }
+
+    #[test]
+    fn max_entries_trims_oldest_entries() {
+        run_with_env(&[], || {
+            set_cache_config("cache:\n  workdir_history:\n    max_entries: 2");
+            assert_eq!(global_config().cache.workdir_history.max_entries, 2);
+
+            let cache = WorkdirHistoryCache::get();
+            cache.add("/wt/org/api").unwrap();
+            cache.add("/wt/org/web").unwrap();
+            cache.add("/wt/org/cli").unwrap();
+
+            assert_eq!(cache.entries(), vec!["/wt/org/cli", "/wt/org/web"]);
+        });
+    }
+
+    #[test]
+    fn enter_records_the_workdir_root() {
+        run_with_env(&[], || {
+            let (_tmp, paths) = dirs(&["repo/sub/dir"]);
+            let root = paths[0].trim_end_matches("/sub/dir").to_string();
+            git2::Repository::init(&root).expect("failed to init repository");
+
+            let cache = WorkdirHistoryCache::get();
+            assert!(cache.enter(&paths[0]).unwrap());
+            assert!(!cache.enter(&root).unwrap());
+
+            let entries = cache.entries();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(
+                std::fs::canonicalize(&entries[0]).unwrap(),
+                std::fs::canonicalize(&root).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn enter_ignores_paths_outside_workdirs() {
+        run_with_env(&[], || {
+            let (_tmp, paths) = dirs(&["not-a-workdir"]);
+
+            let cache = WorkdirHistoryCache::get();
+            assert!(!cache.enter(&paths[0]).unwrap());
+            assert!(cache.entries().is_empty());
+        });
+    }
+}
+
+mod previous_workdirs {
+    use super::*;
+
+    #[test]
+    fn skips_the_current_workdir_and_duplicates() {
+        run_with_env(&[], || {
+            let (_tmp, paths) = dirs(&["api", "web", "cli"]);
+            let (api, web, cli) = (&paths[0], &paths[1], &paths[2]);
+
+            let cache = WorkdirHistoryCache::get();
+            for path in [cli, api, web, api, web] {
+                cache.add(path).unwrap();
+            }
+
+            let previous = cache.previous(Some(web));
+            assert_eq!(previous.workdirs, vec![api.clone(), cli.clone()]);
+            assert!(previous.missing.is_empty());
+        });
+    }
+
+    #[test]
+    fn resolves_indexes() {
+        run_with_env(&[], || {
+            let (_tmp, paths) = dirs(&["api", "web", "cli"]);
+            let (api, web, cli) = (&paths[0], &paths[1], &paths[2]);
+
+            let cache = WorkdirHistoryCache::get();
+            for path in [cli, web, api] {
+                cache.add(path).unwrap();
+            }
+
+            let previous = cache.previous(Some(api));
+            assert_eq!(previous.nth(1), Some(web.as_str()));
+            assert_eq!(previous.nth(2), Some(cli.as_str()));
+            assert_eq!(previous.nth(3), None);
+            assert_eq!(previous.nth(0), None);
+
+            // Outside of the history, the last entry is the previous one
+            let previous = cache.previous(None);
+            assert_eq!(previous.nth(1), Some(api.as_str()));
+        });
+    }
+
+    #[test]
+    fn skips_missing_directories() {
+        run_with_env(&[], || {
+            let (_tmp, paths) = dirs(&["api", "web", "cli"]);
+            let (api, web, cli) = (&paths[0], &paths[1], &paths[2]);
+
+            let cache = WorkdirHistoryCache::get();
+            for path in [cli, web, api] {
+                cache.add(path).unwrap();
+            }
+            std::fs::remove_dir(web).expect("failed to remove dir");
+
+            let previous = cache.previous(Some(api));
+            assert_eq!(previous.workdirs, vec![cli.clone()]);
+            assert_eq!(previous.missing, vec![web.clone()]);
+            assert_eq!(previous.nth(1), Some(cli.as_str()));
+
+            // The missing directories stay in the history
+            assert_eq!(cache.entries().len(), 3);
+        });
+    }
+}
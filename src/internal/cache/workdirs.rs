@@ -43,6 +43,17 @@ impl WorkdirsCache {
         Ok(removed > 0)
     }
 
+    /// Returns the ids of all the work directories known to the cache,
+    /// whether they have an environment, a history, or are trusted
+    pub fn known_ids(&self) -> Vec<String> {
+        CacheManager::get()
+            .query_as(
+                include_str!("database/sql/workdir_known_ids.sql"),
+                params![],
+            )
+            .unwrap_or_default()
+    }
+
     pub fn check_fingerprint(
         &self,
         workdir: &str,
@@ -279,4 +279,24 @@ mod workdirs_cache {
             );
         });
     }
+
+    #[test]
+    fn test_known_ids() {
+        run_with_env(&[], || {
+            let mut cache = WorkdirsCache::get();
+            assert!(cache.known_ids().is_empty());
+
+            cache
+                .add_trusted("trusted")
+                .expect("Failed to add trusted workdir");
+            cache
+                .update_fingerprint("fingerprinted", "head_commit", 42)
+                .expect("Failed to update fingerprint");
+            cache
+                .update_fingerprint("trusted", "head_commit", 42)
+                .expect("Failed to update fingerprint");
+
+            assert_eq!(cache.known_ids(), vec!["fingerprinted", "trusted"]);
+        });
+    }
 }
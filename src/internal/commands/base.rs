@@ -1,23 +1,24 @@
 use std::collections::BTreeMap;
 use std::process::exit;
 
-use itertools::Itertools;
-
+use crate::internal::commands::completion::emit_completion;
+use crate::internal::commands::fromconfig::take_dry_run_flag;
 use crate::internal::commands::fromconfig::ConfigCommand;
 use crate::internal::commands::frommakefile::MakefileCommand;
 use crate::internal::commands::frompath::PathCommand;
+use crate::internal::commands::response_files::expand_response_files;
 use crate::internal::commands::utils::abs_or_rel_path;
 use crate::internal::commands::utils::path_auto_complete;
 use crate::internal::commands::void::VoidCommand;
+use crate::internal::config::parser::ArgparserOutput;
 use crate::internal::config::parser::ParseArgsErrorKind;
 use crate::internal::config::parser::ParseArgsValue;
 use crate::internal::config::CommandSyntax;
-use crate::internal::config::SyntaxGroup;
-use crate::internal::config::SyntaxOptArg;
 use crate::internal::config::SyntaxOptArgType;
 use crate::internal::dynenv::update_dynamic_env_for_command;
+use crate::internal::errors::ResponseFileError;
 use crate::internal::user_interface::colors::strip_colors;
-use crate::internal::user_interface::colors::strip_colors_if_needed;
+use crate::internal::user_interface::colors::ColorCapability;
 use crate::internal::user_interface::term_width;
 use crate::internal::user_interface::wrap_text;
 use crate::internal::user_interface::StringColor;
@@ -25,6 +26,13 @@ use crate::internal::workdir::is_trusted;
 use crate::internal::workdir::is_trusted_or_ask;
 use crate::omni_error;
 use crate::omni_print;
+use crate::omni_warning;
+
+/// Whether the argument parsing errors should be printed as JSON
+/// objects, for tools wrapping omni, instead of human-readable text
+fn parse_args_errors_as_json() -> bool {
+    std::env::var("OMNI_ERROR_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"))
+}
 
 pub trait BuiltinCommand: std::fmt::Debug + Send + Sync {
     fn new_command() -> Command
@@ -135,6 +143,15 @@ impl Command {
         }
     }
 
+    /// Returns the deprecation message of the alias the command was
+    /// called as, if the command was called through a deprecated alias
+    pub fn deprecated_alias(&self, called_as: &[String]) -> Option<String> {
+        match self {
+            Command::FromConfig(command) => command.deprecated_alias(called_as),
+            _ => None,
+        }
+    }
+
     pub fn all_names(&self) -> Vec<Vec<String>> {
         let mut names = vec![self.name()];
         names.extend(self.aliases());
@@ -200,7 +217,20 @@ impl Command {
         }
     }
 
+    /// Returns the directory from which the command is executed; when
+    /// that directory depends on the arguments of the command, it is only
+    /// known once they are parsed, and the directory of the source of the
+    /// command is returned instead
     pub fn exec_dir(&self) -> String {
+        match self {
+            Command::FromConfig(command) if command.cwd().uses_args() => self.source_dir(),
+            _ => self.resolved_exec_dir(),
+        }
+    }
+
+    /// Returns the directory from which the command is executed, once
+    /// its arguments are parsed
+    fn resolved_exec_dir(&self) -> String {
         match self {
             Command::FromConfig(command) => command
                 .exec_dir()
@@ -248,6 +278,15 @@ impl Command {
         }
     }
 
+    /// Whether the command is hidden from the help and the completions;
+    /// a hidden command can still be called explicitly
+    pub fn is_hidden(&self) -> bool {
+        match self {
+            Command::FromConfig(command) => command.is_hidden(),
+            _ => false,
+        }
+    }
+
     pub fn tags(&self) -> BTreeMap<String, String> {
         match self {
             Command::FromPath(command) => command.tags(),
@@ -296,34 +335,7 @@ impl Command {
             if let Some(syntax_usage) = syntax.usage {
                 usage += &format!(" {syntax_usage}");
             } else if !syntax.parameters.is_empty() {
-                let params = syntax.parameters.clone();
-
-                // Take all options, i.e. non-positional that are not required
-                let (options, params): (Vec<_>, Vec<_>) = params
-                    .into_iter()
-                    .partition(|param| !param.required && !param.is_positional());
-                if !options.is_empty() {
-                    usage += &" [OPTIONS]".cyan();
-                }
-
-                // Take all non-positional that are required
-                let (required_options, params): (Vec<_>, Vec<_>) = params
-                    .into_iter()
-                    .partition(|param| param.required && !param.is_positional());
-                for param in required_options {
-                    let param_usage = format!(" {}", param.usage());
-                    usage += &param_usage;
-                }
-
-                // Finally, we're only left with positional parameters
-                for param in params {
-                    if param.is_last() {
-                        usage += " --";
-                    }
-
-                    let param_usage = format!(" {}", param.usage());
-                    usage += &param_usage;
-                }
+                usage += &syntax.generated_usage();
             }
         }
 
@@ -388,8 +400,21 @@ impl Command {
         }
 
         let syntax = self.syntax().unwrap_or_default();
-        let parsed_args = self
-            .exec_parse_args_error_handling(syntax.parse_args(argv, called_as.clone()), called_as);
+        let parsed_args = match self.argparser_output() {
+            ArgparserOutput::Env => self.exec_parse_args_error_handling(
+                &syntax,
+                syntax.parse_args(argv, called_as.clone()),
+                called_as,
+            ),
+            ArgparserOutput::Json => {
+                let args_json = self.exec_parse_args_error_handling(
+                    &syntax,
+                    syntax.parse_args_json(argv, called_as.clone()),
+                    called_as,
+                );
+                BTreeMap::from([("OMNI_ARGS_JSON".to_string(), args_json)])
+            }
+        };
         Some(parsed_args)
     }
 
@@ -404,6 +429,7 @@ impl Command {
 
         let syntax = self.syntax().unwrap_or_default();
         let parsed_args = self.exec_parse_args_error_handling(
+            &syntax,
             syntax.parse_args_typed(argv, called_as.clone()),
             called_as,
         );
@@ -411,15 +437,27 @@ impl Command {
         Some(parsed_args)
     }
 
-    fn exec_parse_args_error_handling<V>(
+    fn exec_parse_args_error_handling<T>(
         &self,
-        result: Result<BTreeMap<String, V>, ParseArgsErrorKind>,
+        syntax: &CommandSyntax,
+        result: Result<T, ParseArgsErrorKind>,
         called_as: Vec<String>,
-    ) -> BTreeMap<String, V> {
+    ) -> T {
         match result {
             Ok(parsed_args) => parsed_args,
-            Err(ParseArgsErrorKind::ParserBuildError(err)) => {
-                omni_print!(format!("{} {}", "error building parser:".red(), err));
+            Err(err) if parse_args_errors_as_json() => {
+                eprintln!("{}", err.json(syntax.error_dest(&err)));
+                exit(1);
+            }
+            Err(ParseArgsErrorKind::ParserBuildError(errors)) => {
+                if let [err] = errors.as_slice() {
+                    omni_print!(format!("{} {}", "error building parser:".red(), err));
+                } else {
+                    omni_print!("error building parser:".red());
+                    for err in errors {
+                        omni_print!(format!("  - {}", err));
+                    }
+                }
                 exit(1);
             }
             Err(ParseArgsErrorKind::InvalidValue(err)) => {
@@ -427,8 +465,9 @@ impl Command {
                 exit(1);
             }
             Err(ParseArgsErrorKind::ArgumentParsingError(err)) => {
+                let colors = ColorCapability::current();
                 let clap_rich_error = err.render().ansi().to_string();
-                let clap_rich_error = strip_colors_if_needed(&clap_rich_error);
+                let clap_rich_error = colors.strip_if_disabled(&clap_rich_error);
                 let parts = clap_rich_error.trim().split('\n');
 
                 for (idx, line) in parts.enumerate() {
@@ -461,8 +500,17 @@ impl Command {
     }
 
     pub fn exec(&self, argv: Vec<String>, called_as: Option<Vec<String>>) {
+        // Check if the user only wants to see what the command would run,
+        // in which case nothing with side effects should happen
+        let (argv, dry_run) = match self {
+            Command::FromConfig(_) => take_dry_run_flag(argv),
+            _ => (argv, false),
+        };
+
         // Load the dynamic environment for that command
-        update_dynamic_env_for_command(self.exec_dir());
+        if !dry_run {
+            update_dynamic_env_for_command(self.exec_dir());
+        }
 
         // Set the general execution environment
         let called_as = match called_as {
@@ -472,20 +520,58 @@ impl Command {
         let name = called_as.join(" ");
         std::env::set_var("OMNI_SUBCOMMAND", name.clone());
 
+        // Steer the user away from the deprecated aliases, which keep
+        // working in the meantime
+        if let Some(message) = self.deprecated_alias(&called_as) {
+            omni_warning!(format!("this alias is deprecated: {}", message));
+        }
+
         // Add the omni version to the environment
         std::env::set_var("OMNI_VERSION", env!("CARGO_PKG_VERSION"));
 
-        // Clear all `OMNI_ARG_` environment variables
+        // Clear all `OMNI_ARG_` environment variables, and the arguments
+        // provided as JSON
         std::env::vars()
-            .filter(|(key, _)| key.starts_with("OMNI_ARG_"))
+            .filter(|(key, _)| key.starts_with("OMNI_ARG_") || key == "OMNI_ARGS_JSON")
             .for_each(|(key, _)| {
                 std::env::remove_var(&key);
             });
 
+        // Replace the response files by the arguments they contain, if
+        // the command allows them
+        let argv = match self.expand_response_files(argv) {
+            Ok(argv) => argv,
+            Err(err) => {
+                omni_print!(format!(
+                    "{} {}",
+                    format!("{}:", called_as.join(" ")).light_yellow(),
+                    err
+                ));
+                exit(1);
+            }
+        };
+
         // Set environment variables for the parsed arguments, if we are parsing any
-        if let Some(args) = self.exec_parse_args(argv.clone(), called_as.clone()) {
-            for (key, value) in args {
-                std::env::set_var(key, value);
+        let args = self
+            .exec_parse_args(argv.clone(), called_as.clone())
+            .unwrap_or_default();
+        for (key, value) in args.iter() {
+            std::env::set_var(key, value);
+        }
+
+        // Show the execution plan instead of running the command
+        if dry_run {
+            if let Command::FromConfig(command) = self {
+                command.exec_dry_run(&argv, &args);
+            }
+            exit(0);
+        }
+
+        // Load the dynamic environment for the directory of the command
+        // if it could only be resolved with the parsed arguments
+        if let Command::FromConfig(command) = self {
+            if command.cwd().uses_args() {
+                update_dynamic_env_for_command(self.resolved_exec_dir());
             }
         }
 
@@ -530,6 +616,41 @@ impl Command {
         }
     }
 
+    /// How the parsed arguments are provided to the command; only the
+    /// commands from the configuration can get them as JSON
+    pub fn argparser_output(&self) -> ArgparserOutput {
+        match self {
+            Command::FromConfig(command) => command.argparser_output(),
+            _ => ArgparserOutput::Env,
+        }
+    }
+
+    pub fn allow_response_files(&self) -> bool {
+        if !self.argparser() {
+            return false;
+        }
+
+        match self {
+            Command::FromConfig(command) => command.allow_response_files(),
+            Command::FromPath(command) => command.allow_response_files(),
+            _ => false,
+        }
+    }
+
+    /// Replaces the `@path` arguments by the arguments read from the
+    /// corresponding response files, if the command allows them;
+    /// otherwise, the arguments are returned as-is
+    pub fn expand_response_files(
+        &self,
+        argv: Vec<String>,
+    ) -> Result<Vec<String>, ResponseFileError> {
+        if !self.allow_response_files() {
+            return Ok(argv);
+        }
+
+        expand_response_files(argv)
+    }
+
     pub fn internal_argparser(&self) -> bool {
         self.argparser() || matches!(self, Command::Builtin(_))
     }
@@ -644,258 +765,34 @@ impl Command {
             None => return Err(()),
         };
 
-        fn allow_value_check_hyphen(param: &SyntaxOptArg, value: &str) -> bool {
-            if let Some(value_without_hyphen) = value.strip_prefix('-') {
-                if !param.allow_hyphen_values {
-                    // All good if we allow for a parameter to start with `-`
-                    return false;
-                }
-
-                if !param.allow_negative_numbers || value_without_hyphen.parse::<f64>().is_err() {
-                    // All good if we allow for negative numbers
-                    return false;
-                }
-            }
-
-            true
-        }
-
-        let mut state = None;
-        let mut parameters = syntax.parameters.clone();
-        let last_parameter = syntax
-            .parameters
-            .iter()
-            .find(|param| param.is_last())
-            .cloned();
-
-        // Keep track of what we are seeing, so we can pass it to the
-        // underlying functions when trying to complete a value
-        let mut seen_parameters = Vec::new();
-
-        // Go over the arguments we've seen until `comp_cword` and
-        // try to resolve the parameter in the syntax and the number
-        // of values that have been passed (according to the configuration
-        // of the parameter); if a given argument does not have a value,
-        // consider it is a positional (if any).
-        let args = argv.iter().take(comp_cword).cloned().collect::<Vec<_>>();
-        let mut current_arg = args.first().cloned();
-        let mut current_idx = 0;
-
-        'loop_args: while let Some(arg) = current_arg {
-            current_arg = None;
-
-            let (parameter, mut next_arg) = if arg == "--" {
-                // If we have `--`, find the parameter with the 'last' flag, if any
-                match last_parameter {
-                    Some(ref parameter) => {
-                        state = Some(ArgparserAutocompleteState::Value {
-                            param: parameter.clone(),
-                            param_idx: current_idx + 1,
-                        });
-                    }
-                    None => {
-                        // We do not need to autocomplete anything if we are
-                        // after the `--` and there is no parameter with the
-                        // 'last' flag
-                        return Ok(None);
-                    }
-                }
-
-                // No need to keep reading parameters
-                break;
-            } else if arg == "-" {
-                // If we have `-` we just can't complete any parameter, let's just skip
-                (None, None)
-            } else if arg.starts_with("--") {
-                let parameter = syntax
-                    .parameters
-                    .iter()
-                    .find(|param| param.all_names().iter().any(|name| name == arg.as_str()));
-
-                (parameter, None)
-            } else if let Some(arg_name) = arg.strip_prefix('-') {
-                // Split the first char and the following ones, if any
-                // as they would become the next argument
-                let (arg, next_arg) = arg_name.split_at(1);
-                let arg = format!("-{arg}");
-
-                let next_arg = if next_arg.is_empty() {
-                    None
-                } else {
-                    Some(next_arg.to_string())
-                };
-
-                let parameter = syntax
-                    .parameters
-                    .iter()
-                    .find(|param| param.all_names().iter().any(|name| name == arg.as_str()));
-
-                (parameter, next_arg)
-            } else {
-                // Get the parameters from the list of parameters left, since for positional
-                // we need to remove them from the list as we can't identify them by name alone
-                match syntax.parameters.iter().find(|param| {
-                    param.is_positional() && !param.is_last() && parameters.contains(param)
-                }) {
-                    Some(parameter) => {
-                        // We need to pass the parameter itself as "next_arg" since for a
-                        // positional, the parameter itself is the value
-                        (Some(parameter), Some(arg))
-                    }
-                    None => {
-                        // If we don't have any positional parameters left, then we can't
-                        // autocomplete this, just skip it
-                        (None, None)
-                    }
-                }
-            };
-
-            // If the parameter is not found, skip to the next
-            let parameter = match parameter {
-                Some(parameter) => parameter,
-                None => {
-                    current_idx += 1;
-                    current_arg = args.get(current_idx).cloned();
-                    continue;
-                }
-            };
-
-            // If the parameter is not repeatable, remove it from the list
-            // TODO: how does that work for positionals?
-            if !parameter.is_repeatable() {
-                parameters.retain(|param| param != parameter);
-            }
-
-            // Handle the conflicts between parameters
-            parameters.retain(|param| !check_parameter_conflicts(parameter, param, &syntax.groups));
-
-            // Consume values as needed
-            if parameter.takes_value() {
-                // How many values to consume at most?
-                let max_values: Option<usize> = parameter
-                    .num_values
-                    .map_or(if parameter.leftovers { None } else { Some(1) }, |num| {
-                        num.max()
-                    });
-                let min_values = parameter.num_values.map_or(1, |num| num.min().unwrap_or(0));
-
-                let param_start_idx = current_idx + if parameter.is_positional() { 0 } else { 1 };
-                let mut current_values = vec![];
-                let mut value_idx = 0;
-                loop {
-                    if let Some(max) = max_values {
-                        if value_idx >= max {
-                            // Stop here if we have the maximum number of values
-                            break;
-                        }
-                    }
-
-                    value_idx += 1;
-                    let value = if let Some(arg) = next_arg {
-                        next_arg = None;
-                        Some(arg)
-                    } else {
-                        current_idx += 1;
-                        args.get(current_idx).cloned()
-                    };
-
-                    if let Some(ref value) = value {
-                        if !allow_value_check_hyphen(parameter, value) {
-                            // If the value is not allowed, then consider it
-                            // is another argument, so exit this loop
-                            current_arg = Some(value.to_string());
-                            break;
-                        }
-
-                        current_values.push(value.clone());
-                    }
-
-                    if current_idx == comp_cword || value.is_none() {
-                        state = Some(if value_idx > min_values && !parameter.leftovers {
-                            ArgparserAutocompleteState::ValueAndParameters {
-                                param: parameter.clone(),
-                                param_idx: param_start_idx,
-                            }
-                        } else {
-                            ArgparserAutocompleteState::Value {
-                                param: parameter.clone(),
-                                param_idx: param_start_idx,
-                            }
-                        });
-                        break 'loop_args;
-                    }
-                }
-
-                // Add the parameter in the seen list, with values
-                seen_parameters.push((parameter.name(), current_values));
-            } else {
-                // Add the parameter in the seen list, without values
-                seen_parameters.push((parameter.name(), vec![]));
-
-                if let Some(next_arg) = next_arg {
-                    current_arg = Some(format!("-{next_arg}"));
-                }
-            }
-
-            if current_arg.is_none() {
-                current_idx += 1;
-                current_arg = args.get(current_idx).cloned();
-            }
-        }
-
-        let state = state.unwrap_or(
-            match (
-                parameters
-                    .iter()
-                    .find(|param| param.is_positional() && !param.is_last()),
-                argv.get(comp_cword),
-            ) {
-                (Some(param), Some(value)) if allow_value_check_hyphen(param, value) => {
-                    ArgparserAutocompleteState::ValueAndParameters {
-                        param: param.clone(),
-                        param_idx: current_idx,
-                    }
-                }
-                (Some(param), None) => ArgparserAutocompleteState::ValueAndParameters {
-                    param: param.clone(),
-                    param_idx: current_idx,
-                },
-                (_, _) => ArgparserAutocompleteState::Parameters,
-            },
-        );
+        let context = match syntax.completion_context(argv, comp_cword) {
+            Some(context) => context,
+            None => return Ok(None),
+        };
 
         // Grab the value to be completed, or default to the empty string
         let comp_value = argv.get(comp_cword).cloned().unwrap_or_default();
 
-        if state.complete_parameters() {
+        if context.state.complete_parameters() {
             // If we get here, go over the parameters still in the list, filter
             // them using the value to be completed, and return their names
-            parameters
-                .iter()
-                .filter(|param| !param.is_positional())
-                .flat_map(|param| param.all_names())
-                .filter(|name| name.starts_with(&comp_value))
-                .sorted()
-                .for_each(|name| {
-                    println!("{name}");
-                });
+            for (name, desc) in context.option_candidates(&comp_value) {
+                emit_completion(&name, desc.as_deref());
+            }
 
             // Autocomplete '--' if there is a last parameter
-            if last_parameter.is_some() && "--".starts_with(&comp_value) {
-                println!("--");
+            if context.last.is_some() && "--".starts_with(&comp_value) {
+                emit_completion("--", None);
             }
         }
 
-        if let Some((param, param_idx)) = state.parameter() {
+        if let Some((param, param_idx)) = context.state.parameter() {
             let arg_type = param.arg_type().terminal_type().clone();
 
-            if let Some(possible_values) = arg_type.possible_values() {
-                possible_values
-                    .iter()
-                    .filter(|val| val.starts_with(&comp_value))
-                    .for_each(|val| {
-                        println!("{val}");
-                    });
+            if arg_type.possible_values().is_some() {
+                param.completion_values(&comp_value).iter().for_each(|val| {
+                    emit_completion(val, None);
+                });
 
                 // We've done the whole completion for that parameter, no
                 // need to delegate to the underlying command
@@ -911,17 +808,28 @@ impl Command {
 
                 path_auto_complete(&comp_value, include_repositories, include_files)
                     .iter()
-                    .for_each(|s| println!("{s}"));
+                    .for_each(|s| emit_completion(s, None));
+
+                // The examples come last, as lower-priority suggestions
+                param
+                    .completion_examples(&comp_value)
+                    .iter()
+                    .for_each(|example| emit_completion(example, None));
 
                 // We offered path autocompletions, no need to delegate
                 // to the underlying command
                 return Ok(None);
             }
 
+            param
+                .completion_examples(&comp_value)
+                .iter()
+                .for_each(|example| emit_completion(example, None));
+
             Ok(Some(AutocompleteParameter {
                 name: param.name(),
                 index: param_idx,
-                seen: seen_parameters,
+                seen: context.seen,
             }))
         } else {
             Ok(None)
@@ -955,128 +863,6 @@ impl Command {
     }
 }
 
-#[inline]
-fn check_parameter_conflicts(
-    param1: &SyntaxOptArg,
-    param2: &SyntaxOptArg,
-    groups: &[SyntaxGroup],
-) -> bool {
-    // Get the groups for param1
-    let param1_groups = groups
-        .iter()
-        .filter(|group| {
-            group.parameters.iter().any(|name| {
-                param1
-                    .all_names()
-                    .iter()
-                    .any(|n| n.as_str() == name.as_str())
-            })
-        })
-        .collect::<Vec<_>>();
-    let param1_all = param1
-        .all_names()
-        .into_iter()
-        .chain(param1_groups.iter().map(|group| group.name.to_string()))
-        .collect::<Vec<_>>();
-
-    // Get the groups for param2
-    let param2_groups = groups
-        .iter()
-        .filter(|group| {
-            group.parameters.iter().any(|name| {
-                param2
-                    .all_names()
-                    .iter()
-                    .any(|n| n.as_str() == name.as_str())
-            })
-        })
-        .collect::<Vec<_>>();
-    let param2_all = param2
-        .all_names()
-        .into_iter()
-        .chain(param2_groups.iter().map(|group| group.name.to_string()))
-        .collect::<Vec<_>>();
-
-    // If param1 defines conflicts with param2 or any of its groups, return true
-    if param1
-        .conflicts_with
-        .iter()
-        .any(|name| param2_all.iter().any(|n| n == name))
-    {
-        return true;
-    }
-
-    // If param2 defines conflicts with param1 or any of its groups, return true
-    if param2
-        .conflicts_with
-        .iter()
-        .any(|name| param1_all.iter().any(|n| n == name))
-    {
-        return true;
-    }
-
-    // If param1 and param2 are in the same group, and that group does
-    // not allow multiple, return true
-    let common_groups = param1_groups
-        .iter()
-        .filter(|group| param2_groups.contains(group))
-        .collect::<Vec<_>>();
-    if common_groups.iter().any(|group| !group.multiple) {
-        return true;
-    }
-
-    // Check if any of the param1 groups conflicts with param2
-    if param1_groups.iter().any(|group| {
-        group
-            .conflicts_with
-            .iter()
-            .any(|name| param2_all.iter().any(|n| n == name))
-    }) {
-        return true;
-    }
-
-    // Check if any of the param2 groups conflicts with param1
-    if param2_groups.iter().any(|group| {
-        group
-            .conflicts_with
-            .iter()
-            .any(|name| param1_all.iter().any(|n| n == name))
-    }) {
-        return true;
-    }
-
-    // If we get here, there is no conflict
-    false
-}
-
-#[derive(Debug)]
-enum ArgparserAutocompleteState {
-    Parameters,
-    ValueAndParameters {
-        param: SyntaxOptArg,
-        param_idx: usize,
-    },
-    Value {
-        param: SyntaxOptArg,
-        param_idx: usize,
-    },
-}
-
-impl ArgparserAutocompleteState {
-    fn complete_parameters(&self) -> bool {
-        matches!(self, Self::Parameters | Self::ValueAndParameters { .. })
-    }
-
-    fn parameter(&self) -> Option<(SyntaxOptArg, usize)> {
-        match self {
-            Self::Value { param, param_idx } | Self::ValueAndParameters { param, param_idx } => {
-                Some((param.clone(), *param_idx))
-            }
-            _ => None,
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CommandAutocompletion {
     #[default]
@@ -0,0 +1,5 @@
+pub(crate) mod pin;
+pub(crate) use pin::CachePinCommand;
+
+pub(crate) mod stats;
+pub(crate) use stats::CacheStatsCommand;
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use std::str::FromStr;
+
+use crate::internal::cache::CachePinKind;
+use crate::internal::cache::CachePinsCache;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::user_interface::StringColor;
+use crate::omni_error;
+use crate::omni_info;
+
+#[derive(Debug, Clone)]
+struct CachePinCommandArgs {
+    kind: CachePinKind,
+    name: String,
+    version: Option<String>,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for CachePinCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let kind = match args.get("kind") {
+            Some(ParseArgsValue::SingleString(Some(kind))) => match CachePinKind::from_str(kind) {
+                Ok(kind) => kind,
+                Err(err) => {
+                    omni_error!(err);
+                    exit(1);
+                }
+            },
+            _ => unreachable!("kind is required"),
+        };
+
+        let name = match args.get("name") {
+            Some(ParseArgsValue::SingleString(Some(name))) => name.trim().to_string(),
+            _ => unreachable!("name is required"),
+        };
+
+        let version = match args.get("version") {
+            Some(ParseArgsValue::SingleString(Some(version))) => {
+                let version = version.trim();
+                if version.is_empty() {
+                    None
+                } else {
+                    Some(version.to_string())
+                }
+            }
+            _ => None,
+        };
+
+        Self {
+            kind,
+            name,
+            version,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachePinCommand {}
+
+impl CachePinCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn subcommand(&self) -> String {
+        std::env::var("OMNI_SUBCOMMAND").unwrap_or("cache pin".to_string())
+    }
+
+    fn is_pin(&self) -> bool {
+        self.subcommand() == "cache pin"
+    }
+}
+
+impl BuiltinCommand for CachePinCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["cache".to_string(), "pin".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![vec!["cache".to_string(), "unpin".to_string()]]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Pin or unpin a cached tool.\n",
+                "\n",
+                "Pinned tools are never removed when cleaning up the tools that are not ",
+                "required by any work directory anymore, so they do not need to be ",
+                "installed again the next time they are needed.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["kind".to_string()],
+                    desc: Some("The kind of cached tool".to_string()),
+                    required: true,
+                    arg_type: SyntaxOptArgType::Enum(
+                        CachePinKind::ALL
+                            .iter()
+                            .map(|kind| kind.as_str().to_string())
+                            .collect(),
+                    ),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["name".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The name of the tool, e.g. the repository for ",
+                            "\x1B[3mgithub-release\x1B[0m, the crate for ",
+                            "\x1B[3mcargo-install\x1B[0m, or the import path for ",
+                            "\x1B[3mgo-install\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    required: true,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["version".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The version of the tool to pin or unpin ",
+                            "[\x1B[1mdefault: all versions\x1B[0m]"
+                        )
+                        .to_string(),
+                    ),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = CachePinCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        let target = match &args.version {
+            Some(version) => format!("{} {}", args.name, version),
+            None => format!("{} (all versions)", args.name),
+        };
+
+        let cache = CachePinsCache::get();
+        let result = if self.is_pin() {
+            cache.pin(args.kind, &args.name, args.version.as_deref())
+        } else {
+            cache.unpin(args.kind, &args.name, args.version.as_deref())
+        };
+
+        match (self.is_pin(), result) {
+            (true, Ok(true)) => {
+                omni_info!(format!("{} is now {}", target, "pinned".light_green()));
+            }
+            (true, Ok(false)) => {
+                omni_info!(format!("{} is already {}", target, "pinned".light_green()));
+            }
+            (false, Ok(true)) => {
+                omni_info!(format!("{} is now {}", target, "unpinned".light_red()));
+            }
+            (false, Ok(false)) => {
+                omni_info!(format!("{} is not {}", target, "pinned".light_green()));
+            }
+            (_, Err(err)) => {
+                omni_error!(format!("failed to update the pins: {}", err));
+                exit(1);
+            }
+        }
+
+        exit(0);
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+
+use crate::internal::cache::CachePin;
+use crate::internal::cache::CachePinKind;
+use crate::internal::cache::CachePinsCache;
+use crate::internal::cache::UpEnvironmentsCache;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::user_interface::StringColor;
+use crate::internal::workdir::workdir_short_ids;
+use crate::omni_error;
+
+#[derive(Debug, Clone)]
+struct CacheStatsCommandArgs {
+    id: Option<String>,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for CacheStatsCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let id = match args.get("id") {
+            Some(ParseArgsValue::SingleString(Some(id))) if !id.trim().is_empty() => {
+                Some(id.trim().to_string())
+            }
+            _ => None,
+        };
+
+        Self { id }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheStatsCommand {}
+
+impl CacheStatsCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for CacheStatsCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["cache".to_string(), "stats".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Show statistics about the cached tools.\n",
+                "\n",
+                "This shows the number of installed tools for each kind of cached tool, ",
+                "and the pinned tools; pins that do not match any cached tool anymore ",
+                "are reported as stale. The tools required by the environment of a ",
+                "specific work directory can also be shown.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--id".to_string()],
+                desc: Some(
+                    concat!(
+                        "The id of a work directory, in its full or short form, to show the ",
+                        "tools required by its environment",
+                    )
+                    .to_string(),
+                ),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = CacheStatsCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        // Resolve the work directory first, so that an invalid id is
+        // reported before anything gets shown
+        let workdir_id = args.id.as_ref().map(|id| {
+            workdir_short_ids().resolve(id).unwrap_or_else(|err| {
+                omni_error!(err.to_string());
+                exit(2);
+            })
+        });
+
+        let cache = CachePinsCache::get();
+        let (counts, pins) = match (cache.count_installed(), cache.list()) {
+            (Ok(counts), Ok(pins)) => (counts, pins),
+            (Err(err), _) | (_, Err(err)) => {
+                omni_error!(format!("failed to read the cache: {}", err));
+                exit(1);
+            }
+        };
+
+        println!("{}", "Installed tools".bold());
+        for kind in CachePinKind::ALL {
+            let installed = counts.get(&kind).copied().unwrap_or(0);
+            let pinned = pins.iter().filter(|pin| pin.kind == kind).count();
+
+            let mut line = format!("  {}: {}", kind.as_str().light_cyan(), installed);
+            if pinned > 0 {
+                line.push_str(&format!(" ({pinned} pinned)").light_black());
+            }
+            println!("{line}");
+        }
+
+        println!("\n{}", "Pinned tools".bold());
+        if pins.is_empty() {
+            println!("  {}", "none".light_black());
+        }
+        for pin in &pins {
+            println!("  - {}", format_pin(pin));
+        }
+
+        if let Some(workdir_id) = workdir_id {
+            print_workdir_tools(&workdir_id);
+        }
+
+        exit(0);
+    }
+}
+
+/// Prints the tools required by the environment currently assigned to
+/// the given work directory
+fn print_workdir_tools(workdir_id: &str) {
+    println!(
+        "\n{}",
+        format!("Tools of {}", workdir_short_ids().display(workdir_id)).bold()
+    );
+
+    let versions = UpEnvironmentsCache::get()
+        .get_env(workdir_id)
+        .map(|environment| environment.versions)
+        .unwrap_or_default();
+    if versions.is_empty() {
+        println!("  {}", "none".light_black());
+    }
+    for version in &versions {
+        let mut line = format!("  - {} {}", version.tool.light_cyan(), version.version);
+        if !version.dir.is_empty() {
+            line.push_str(&format!(" (in {})", version.dir).light_black());
+        }
+        println!("{line}");
+    }
+}
+
+fn format_pin(pin: &CachePin) -> String {
+    let mut pin_str = format!(
+        "{} {} {}",
+        pin.kind.as_str().light_cyan(),
+        pin.name,
+        pin.version.as_deref().unwrap_or("(all versions)"),
+    );
+
+    if pin.is_stale() {
+        pin_str.push_str(&format!(" {}", "stale: not cached").light_red().italic());
+    }
+
+    pin_str
+}
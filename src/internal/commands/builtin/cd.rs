@@ -5,9 +5,15 @@ use std::process::exit;
 
 use shell_escape::escape;
 
+use crate::internal::cache::workdir_history::PreviousWorkdirs;
+use crate::internal::cache::RepoMatchCache;
+use crate::internal::cache::WorkdirHistoryCache;
 use crate::internal::commands::base::AutocompleteParameter;
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::base::CommandAutocompletion;
+use crate::internal::commands::completion::emit_completion;
+use crate::internal::commands::utils::abs_path;
+use crate::internal::commands::utils::find_editor;
 use crate::internal::commands::utils::omni_cmd_on_success;
 use crate::internal::commands::utils::path_auto_complete;
 use crate::internal::commands::utils::validate_sandbox_name;
@@ -19,21 +25,34 @@ use crate::internal::config::SyntaxOptArg;
 use crate::internal::config::SyntaxOptArgType;
 use crate::internal::env::omni_cmd_file;
 use crate::internal::env::shell_is_interactive;
+use crate::internal::git::forget_repo_lookups;
+use crate::internal::git::ignore_match_memory;
 use crate::internal::git::ORG_LOADER;
 use crate::internal::git_env;
 use crate::internal::user_interface::StringColor;
 use crate::internal::workdir;
 use crate::omni_error;
+use crate::omni_info;
 use crate::omni_warning;
 
 #[derive(Debug, Clone)]
 struct CdCommandArgs {
     locate: bool,
     edit: bool,
+    print_target: bool,
     include_packages: bool,
+    ignore_match_memory: bool,
+    clear_match_memory: bool,
+    history: bool,
     workdir: Option<String>,
 }
 
+impl CdCommandArgs {
+    fn allow_interactive(&self) -> bool {
+        !self.locate && !self.print_target
+    }
+}
+
 #[derive(Debug)]
 struct WorkdirLocation {
     path: String,
@@ -53,6 +72,11 @@ impl From<BTreeMap<String, ParseArgsValue>> for CdCommandArgs {
             Some(ParseArgsValue::SingleBoolean(Some(true)))
         );
 
+        let print_target = matches!(
+            args.get("print_target"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
         let yes_include_packages = matches!(
             args.get("include_packages"),
             Some(ParseArgsValue::SingleBoolean(Some(true)))
@@ -69,6 +93,21 @@ impl From<BTreeMap<String, ParseArgsValue>> for CdCommandArgs {
             locate
         };
 
+        let ignore_match_memory = matches!(
+            args.get("ignore_match_memory"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        let clear_match_memory = matches!(
+            args.get("clear_match_memory"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        let history = matches!(
+            args.get("history"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
         let workdir = match args.get("workdir") {
             Some(ParseArgsValue::SingleString(Some(workdir))) => Some(workdir.clone()),
             _ => None,
@@ -77,7 +116,11 @@ impl From<BTreeMap<String, ParseArgsValue>> for CdCommandArgs {
         Self {
             locate,
             edit,
+            print_target,
             include_packages,
+            ignore_match_memory,
+            clear_match_memory,
+            history,
             workdir,
         }
     }
@@ -101,7 +144,7 @@ impl CdCommand {
 
         let path_str = path.to_string();
 
-        if args.locate {
+        if args.locate || args.print_target {
             println!("{path_str}");
             exit(0);
         }
@@ -111,6 +154,8 @@ impl CdCommand {
             exit(0);
         }
 
+        Self::record_history(&path_str);
+
         let path_escaped = escape(std::borrow::Cow::Borrowed(path_str.as_str()));
         match omni_cmd_on_success(format!("cd {path_escaped}").as_str()) {
             Ok(_) => {}
@@ -134,6 +179,13 @@ impl CdCommand {
                 exit(0);
             }
 
+            Self::record_history(&location.path);
+
+            if args.print_target {
+                println!("{}", Self::print_target_path(&location.path));
+                exit(0);
+            }
+
             let path_escaped = escape(std::borrow::Cow::Borrowed(location.path.as_str()));
             match omni_cmd_on_success(format!("cd {path_escaped}").as_str()) {
                 Ok(_) => {}
@@ -153,7 +205,96 @@ impl CdCommand {
         exit(1);
     }
 
+    /// Resolves the path to print when using `--print-target`; the shell
+    /// function performing the `cd` quotes the path, so we need to expand
+    /// `~` and relative paths ourselves. `-` is kept as-is so the shell can
+    /// resolve it to the previous directory.
+    fn print_target_path(path: &str) -> String {
+        if path == "-" {
+            return path.to_string();
+        }
+
+        abs_path(path).to_string_lossy().to_string()
+    }
+
+    /// Records the work directories being left and entered in the
+    /// history; when delegating `-` to the shell, the directory being
+    /// entered is not known, so nothing is recorded
+    fn record_history(path: &str) {
+        if path == "-" {
+            return;
+        }
+
+        let _ = WorkdirHistoryCache::get().navigate(&abs_path(path).to_string_lossy());
+    }
+
+    /// Returns the position in the history of the work directory to go
+    /// back to, if the work directory is `-` or `-N`
+    fn history_index(wd: &str) -> Option<usize> {
+        match wd.strip_prefix('-') {
+            Some("") => Some(1),
+            Some(index) if index.chars().all(|c| c.is_ascii_digit()) => index.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the work directories that can be navigated back to,
+    /// notifying about the ones that were skipped since they do not
+    /// exist anymore
+    fn previous_workdirs() -> PreviousWorkdirs {
+        let wd = workdir(".");
+        let previous = WorkdirHistoryCache::get().previous(wd.root());
+
+        for path in &previous.missing {
+            omni_info!(format!(
+                "skipping {}: the directory does not exist anymore",
+                path.light_blue()
+            ));
+        }
+
+        previous
+    }
+
+    fn list_history(&self) {
+        let workdirs = Self::previous_workdirs().workdirs;
+        if workdirs.is_empty() {
+            omni_info!("no previous work directory in the history");
+            exit(0);
+        }
+
+        let width = format!("-{}", workdirs.len()).len();
+        for (index, path) in workdirs.iter().enumerate() {
+            let index = format!("{:>width$}", format!("-{}", index + 1));
+            println!("{}  {}", index.light_cyan(), path);
+        }
+        exit(0);
+    }
+
     fn cd_workdir_find(&self, wd: &str, args: &CdCommandArgs) -> Option<WorkdirLocation> {
+        // Handle `-` and `-N` to go back to the previous work directories
+        if let Some(index) = Self::history_index(wd) {
+            let previous = Self::previous_workdirs();
+            if let Some(path) = previous.nth(index) {
+                return Some(WorkdirLocation {
+                    path: path.to_string(),
+                    line_from: None,
+                    line_to: None,
+                });
+            }
+
+            // Without any previous work directory, `-` lets the shell go
+            // back to its previous directory
+            if wd == "-" && previous.workdirs.is_empty() {
+                return Some(WorkdirLocation {
+                    path: wd.to_string(),
+                    line_from: None,
+                    line_to: None,
+                });
+            }
+
+            return None;
+        }
+
         // Handle the special case of `...` to go to the work directory root
         if wd == "..." {
             let wd = workdir(".");
@@ -165,12 +306,7 @@ impl CdCommand {
         }
 
         // Delegate to the shell if this is a path
-        if wd.starts_with('/')
-            || wd.starts_with('.')
-            || wd.starts_with("~/")
-            || wd == "~"
-            || wd == "-"
-        {
+        if wd.starts_with('/') || wd.starts_with('.') || wd.starts_with("~/") || wd == "~" {
             return Some(WorkdirLocation {
                 path: wd.to_string(),
                 line_from: None,
@@ -193,7 +329,7 @@ impl CdCommand {
         }
 
         let only_worktree = !args.include_packages;
-        let allow_interactive = !args.locate;
+        let allow_interactive = args.allow_interactive();
 
         if let Some(wd_path) = ORG_LOADER.find_repo_quick(wd, only_worktree, false) {
             return Some(WorkdirLocation {
@@ -242,7 +378,7 @@ impl CdCommand {
 
         // Find the repository using existing logic
         let only_worktree = !args.include_packages;
-        let allow_interactive = !args.locate;
+        let allow_interactive = args.allow_interactive();
 
         // Use find_repo which combines quick and slow search
         let repo_path =
@@ -398,36 +534,8 @@ impl CdCommand {
         None
     }
 
-    fn find_editor() -> Option<String> {
-        // Try VISUAL first
-        if let Ok(visual) = std::env::var("VISUAL") {
-            if !visual.is_empty() {
-                return Some(visual);
-            }
-        }
-
-        // Then try EDITOR
-        if let Ok(editor) = std::env::var("EDITOR") {
-            if !editor.is_empty() {
-                return Some(editor);
-            }
-        }
-
-        // Try vim
-        if let Ok(vim) = which::which("vim") {
-            return Some(vim.to_string_lossy().to_string());
-        }
-
-        // Try nano
-        if let Ok(nano) = which::which("nano") {
-            return Some(nano.to_string_lossy().to_string());
-        }
-
-        None
-    }
-
     fn open_in_editor(&self, path: &str, line_from: Option<u32>, line_to: Option<u32>) {
-        let editor = match Self::find_editor() {
+        let editor = match find_editor() {
             Some(e) => e,
             None => {
                 omni_error!(
@@ -558,7 +666,7 @@ impl BuiltinCommand for CdCommand {
                         .to_string()
                     ),
                     arg_type: SyntaxOptArgType::Flag,
-                    conflicts_with: vec!["--edit".to_string()],
+                    conflicts_with: vec!["--edit".to_string(), "--print-target".to_string()],
                     ..Default::default()
                 },
                 SyntaxOptArg {
@@ -573,7 +681,23 @@ impl BuiltinCommand for CdCommand {
                         .to_string()
                     ),
                     arg_type: SyntaxOptArgType::Flag,
-                    conflicts_with: vec!["--locate".to_string()],
+                    conflicts_with: vec!["--locate".to_string(), "--print-target".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--print-target".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will print the absolute path of the directory to change to, and ",
+                            "nothing else, on stdout; errors are only reported on stderr with a non-zero exit ",
+                            "code. This is used by the minimal shell integration provided by ",
+                            "\x1B[3momni hook init --cd-only\x1B[0m, which performs the directory change ",
+                            "itself. When this flag is passed, interactions are also disabled.",
+                        )
+                        .to_string()
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec!["--locate".to_string(), "--edit".to_string()],
                     ..Default::default()
                 },
                 SyntaxOptArg {
@@ -603,6 +727,51 @@ impl BuiltinCommand for CdCommand {
                     arg_type: SyntaxOptArgType::Flag,
                     ..Default::default()
                 },
+                SyntaxOptArg {
+                    names: vec!["--ignore-match-memory".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will ignore the repositories previously chosen when ",
+                            "the work directory matched multiple repositories, and will not ",
+                            "remember the repository chosen this time.",
+                        )
+                        .to_string()
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--clear-match-memory".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will forget all the repositories previously chosen when ",
+                            "a work directory matched multiple repositories; if no work directory ",
+                            "is provided, this only clears that memory.",
+                        )
+                        .to_string()
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--history".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will list the work directories previously entered, the most ",
+                            "recent first, with the index to pass to \x1B[3momni cd -N\x1B[0m to go back ",
+                            "to them.",
+                        )
+                        .to_string()
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec![
+                        "--locate".to_string(),
+                        "--edit".to_string(),
+                        "--print-target".to_string(),
+                        "workdir".to_string(),
+                    ],
+                    ..Default::default()
+                },
                 SyntaxOptArg {
                     names: vec!["workdir".to_string()],
                     desc: Some(
@@ -610,10 +779,15 @@ impl BuiltinCommand for CdCommand {
                             "The name of the work directory to change directory to; this can be in the format ",
                             "<org>/<repo>, or just <repo>, in which case the work directory will be searched for ",
                             "in all the organizations, trying to use \x1B[3mOMNI_ORG\x1B[0m if it is set, and then ",
-                            "trying all the other organizations alphabetically.",
+                            "trying all the other organizations alphabetically. When multiple repositories ",
+                            "match, the ones of the organization of the current work directory and the ones ",
+                            "previously chosen for the same work directory name are preferred. ",
+                            "\x1B[3m-\x1B[0m goes back to the previous work directory, and ",
+                            "\x1B[3m-N\x1B[0m to the N-th previous one.",
                         )
                         .to_string()
                     ),
+                    allow_negative_numbers: true,
                     ..Default::default()
                 },
             ],
@@ -633,7 +807,30 @@ impl BuiltinCommand for CdCommand {
                 .expect("should have args to parse"),
         );
 
-        if omni_cmd_file().is_none() && !args.locate && !args.edit {
+        if args.clear_match_memory {
+            if let Err(err) = RepoMatchCache::get().clear() {
+                omni_error!(format!("failed to clear the match memory: {}", err));
+                exit(1);
+            }
+
+            // The results of the previous lookups were ranked with that memory
+            forget_repo_lookups();
+
+            if args.workdir.is_none() {
+                omni_info!("forgot the repositories previously chosen");
+                exit(0);
+            }
+        }
+
+        if args.ignore_match_memory {
+            ignore_match_memory();
+        }
+
+        if args.history {
+            self.list_history();
+        }
+
+        if omni_cmd_file().is_none() && !args.locate && !args.edit && !args.print_target {
             omni_error!("not available without the shell integration");
             exit(1);
         }
@@ -663,7 +860,7 @@ impl BuiltinCommand for CdCommand {
 
                 path_auto_complete(repo, true, false)
                     .iter()
-                    .for_each(|s| println!("{s}"));
+                    .for_each(|s| emit_completion(s, None));
             }
         }
 
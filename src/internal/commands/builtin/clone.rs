@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::PathBuf;
 use std::process::exit;
 use std::time::Duration;
@@ -9,6 +10,7 @@ use shell_escape::escape;
 use shell_words::join as shell_join;
 use tokio::process::Command as TokioCommand;
 
+use crate::internal::cache::WorkdirHistoryCache;
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::builtin::UpCommand;
 use crate::internal::commands::utils::omni_cmd_on_success;
@@ -22,6 +24,7 @@ use crate::internal::config::SyntaxOptArg;
 use crate::internal::config::SyntaxOptArgType;
 use crate::internal::env::omni_cmd_file;
 use crate::internal::env::shell_is_interactive;
+use crate::internal::git::forget_repo_lookups;
 use crate::internal::git::format_path_with_template;
 use crate::internal::git::full_git_url_parse;
 use crate::internal::git::package_path_from_git_url;
@@ -34,6 +37,7 @@ use crate::omni_error;
 struct CloneCommandArgs {
     repository: String,
     package: bool,
+    print_target: bool,
     options: Vec<String>,
 }
 
@@ -43,6 +47,10 @@ impl From<BTreeMap<String, ParseArgsValue>> for CloneCommandArgs {
             args.get("package"),
             Some(ParseArgsValue::SingleBoolean(Some(true)))
         );
+        let print_target = matches!(
+            args.get("print_target"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
         let repository = match args.get("repository") {
             Some(ParseArgsValue::SingleString(Some(repository))) => repository.clone(),
             _ => "".to_string(),
@@ -57,17 +65,22 @@ impl From<BTreeMap<String, ParseArgsValue>> for CloneCommandArgs {
         Self {
             repository,
             package,
+            print_target,
             options,
         }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct CloneCommand {}
+pub struct CloneCommand {
+    print_target: bool,
+}
 
 impl CloneCommand {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            print_target: false,
+        }
     }
 
     pub fn lookup_repo_handle(
@@ -269,16 +282,19 @@ impl CloneCommand {
                 format!("({})", clone_path.to_string_lossy()).light_black()
             ));
 
-            if should_run_up {
-                run_up = self.suggest_run_up();
-            }
+            // Never prompt when printing the target, as the output is
+            // expected to be consumed by the shell integration
+            run_up = should_run_up && !self.print_target && self.suggest_run_up();
         } else {
             log_progress(format!("Checking {clone_url}"));
 
+            let transport = config(".").git_transport.options_for_url(clone_url);
+
             // Check using git ls-remote if the repository exists
             let mut cmd = TokioCommand::new("git");
-            cmd.arg("ls-remote");
+            cmd.args(transport.git_args("ls-remote"));
             cmd.arg(clone_url);
+            cmd.envs(transport.env());
             cmd.stdout(std::process::Stdio::piped());
             cmd.stderr(std::process::Stdio::piped());
 
@@ -306,14 +322,21 @@ impl CloneCommand {
                 s.finish_and_clear()
             }
 
-            let mut cmd_args = vec!["git".to_string(), "clone".to_string()];
+            let mut cmd_args = vec!["git".to_string()];
+            cmd_args.extend(transport.git_args("clone"));
             cmd_args.push(clone_url.to_string());
             cmd_args.push(clone_path.to_string_lossy().to_string());
             cmd_args.extend(clone_args.to_owned());
 
             let mut cmd = std::process::Command::new(&cmd_args[0]);
             cmd.args(&cmd_args[1..]);
-            cmd.stdout(std::process::Stdio::inherit());
+            cmd.envs(transport.env());
+            if self.print_target {
+                // Keep stdout clean for the target path
+                cmd.stdout(std::io::stderr());
+            } else {
+                cmd.stdout(std::process::Stdio::inherit());
+            }
             cmd.stderr(std::process::Stdio::inherit());
 
             log_command(format!("$ {}", shell_join(cmd_args)).light_black());
@@ -328,11 +351,21 @@ impl CloneCommand {
                 omni_error!(msg);
                 exit(1);
             }
+
+            // Make sure that the lookups that missed the repository find it
+            forget_repo_lookups();
         }
 
         // If we reach here, the repo either exists or just got cloned, so we can
         // directly cd into it
-        if auto_cd && omni_cmd_file().is_some() {
+        if auto_cd && (self.print_target || omni_cmd_file().is_some()) {
+            let _ = WorkdirHistoryCache::get().navigate(&clone_path.to_string_lossy());
+        }
+
+        if auto_cd && self.print_target {
+            println!("{}", clone_path.to_string_lossy());
+            let _ = std::io::stdout().flush();
+        } else if auto_cd && omni_cmd_file().is_some() {
             let path_str = clone_path.to_string_lossy();
             let path_escaped = escape(path_str);
             match omni_cmd_on_success(format!("cd {path_escaped}").as_str()) {
@@ -354,6 +387,15 @@ impl CloneCommand {
                 exit(1);
             }
 
+            // The target path has already been printed, make sure that
+            // anything else goes to stderr
+            if self.print_target {
+                if let Err(err) = nix::unistd::dup2_stdout(std::io::stderr()) {
+                    omni_error!(format!("failed to redirect stdout: {err}"));
+                    exit(1);
+                }
+            }
+
             eprintln!("{}", "$ omni up --bootstrap".light_black());
 
             let up_cmd = UpCommand::new_command();
@@ -411,6 +453,20 @@ impl BuiltinCommand for CloneCommand {
                     arg_type: SyntaxOptArgType::Flag,
                     ..Default::default()
                 },
+                SyntaxOptArg {
+                    names: vec!["--print-target".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Print the path of the cloned repository, and nothing else, on stdout ",
+                            "instead of changing directory to it; errors are only reported on stderr. ",
+                            "This is used by the minimal shell integration provided by ",
+                            "\x1B[3momni hook init --cd-only\x1B[0m.",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
                 SyntaxOptArg {
                     names: vec!["repository".to_string()],
                     desc: Some(
@@ -468,7 +524,10 @@ impl BuiltinCommand for CloneCommand {
             None
         };
 
-        let cloned = self
+        let clone_command = Self {
+            print_target: args.print_target,
+        };
+        let cloned = clone_command
             .clone_repo_handle(
                 &repo,
                 &clone_args,
@@ -203,6 +203,41 @@ struct ConfigBootstrap {
     org: Vec<OrgConfig>,
 }
 
+/// Applies the bootstrapped configuration over the given user
+/// configuration, leaving the keys that were not bootstrapped as they
+/// are; returns whether the configuration should be saved
+fn apply_bootstrap_config(config_value: &mut ConfigValue, config: &ConfigBootstrap) -> bool {
+    // Dump our config object as yaml
+    let yaml = serde_yaml::to_string(config);
+
+    // Now get a ConfigValue object from the yaml
+    let new_config_value = match yaml {
+        Ok(yaml) => match ConfigValue::from_str(&yaml) {
+            Ok(config_value) => config_value,
+            Err(err) => {
+                omni_error!(format!("failed to parse configuration: {}", err));
+                return false;
+            }
+        },
+        Err(err) => {
+            omni_error!(format!("failed to serialize configuration: {}", err));
+            return false;
+        }
+    };
+
+    // Apply it over the existing configuration
+    config_value.extend(
+        new_config_value,
+        ConfigExtendOptions::new()
+            .with_strategy(ConfigExtendStrategy::Replace)
+            .with_transform(false),
+        vec![],
+    );
+
+    // And return true to save the configuration
+    true
+}
+
 pub fn config_bootstrap(options: Option<ConfigBootstrapOptions>) -> Result<bool, String> {
     let options = options.unwrap_or_default();
 
@@ -245,35 +280,7 @@ pub fn config_bootstrap(options: Option<ConfigBootstrapOptions>) -> Result<bool,
         };
 
         if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
-            // Dump our config object as yaml
-            let yaml = serde_yaml::to_string(&config);
-
-            // Now get a ConfigValue object from the yaml
-            let new_config_value = match yaml {
-                Ok(yaml) => match ConfigValue::from_str(&yaml) {
-                    Ok(config_value) => config_value,
-                    Err(err) => {
-                        omni_error!(format!("failed to parse configuration: {}", err));
-                        return false;
-                    }
-                },
-                Err(err) => {
-                    omni_error!(format!("failed to serialize configuration: {}", err));
-                    return false;
-                }
-            };
-
-            // Apply it over the existing configuration
-            config_value.extend(
-                new_config_value,
-                ConfigExtendOptions::new()
-                    .with_strategy(ConfigExtendStrategy::Replace)
-                    .with_transform(false),
-                vec![],
-            );
-
-            // And return true to save the configuration
-            true
+            apply_bootstrap_config(config_value, &config)
         }) {
             return Err(format!("Failed to update user configuration: {err}"));
         }
@@ -296,7 +303,7 @@ pub fn config_bootstrap(options: Option<ConfigBootstrapOptions>) -> Result<bool,
         // We reach here only if we're missing the shell integration
         let current_shell = Shell::current();
         match current_shell {
-            Shell::Unknown(_) | Shell::Posix => {
+            Shell::Unknown(_) | Shell::Posix | Shell::PowerShell => {
                 omni_warning!(format!(
                     "omni does not provide a shell integration for your shell ({})",
                     current_shell.to_str().light_cyan(),
@@ -837,11 +844,17 @@ fn question_org(worktree: &str) -> (Vec<OrgConfig>, bool) {
         .map(|org| {
             let trusted = trusted_orgs.contains(org);
             let worktree = current_orgs_worktrees.get(org);
+            let up_operations = current_orgs
+                .iter()
+                .find(|current| current.handle == *org)
+                .map(|current| current.up_operations.clone())
+                .unwrap_or_default();
             OrgConfig {
                 handle: org.clone(),
                 trusted,
                 worktree: worktree.cloned(),
                 repo_path_format: None,
+                up_operations,
             }
         })
         .collect();
@@ -948,3 +961,7 @@ fn question_rc_file(current_shell: &Shell) -> (PathBuf, bool) {
 
     (rc_file, true)
 }
+
+#[cfg(test)]
+#[path = "bootstrap_test.rs"]
+mod tests;
@@ -0,0 +1,46 @@
+use super::*;
+
+mod apply_bootstrap_config {
+    use super::*;
+
+    fn yaml(document: &str) -> serde_yaml::Value {
+        serde_yaml::from_str(document).expect("invalid yaml")
+    }
+
+    #[test]
+    fn keeps_unrelated_keys() {
+        let mut config_value = ConfigValue::from_str(concat!(
+            "worktree: /old/worktree\n",
+            "cd:\n",
+            "  fast_search: false\n",
+            "org:\n",
+            "  - handle: github.com/old\n",
+            "    trusted: true\n",
+            "up_command:\n",
+            "  auto_bootstrap: false\n",
+        ))
+        .expect("invalid yaml");
+
+        let config = ConfigBootstrap {
+            worktree: "/new/worktree".to_string(),
+            repo_path_format: "%{host}/%{org}/%{repo}".to_string(),
+            org: vec![],
+        };
+
+        assert!(apply_bootstrap_config(&mut config_value, &config));
+        assert_eq!(
+            config_value.as_serde_yaml(),
+            yaml(concat!(
+                "worktree: /new/worktree\n",
+                "repo_path_format: '%{host}/%{org}/%{repo}'\n",
+                "cd:\n",
+                "  fast_search: false\n",
+                "org:\n",
+                "  - handle: github.com/old\n",
+                "    trusted: true\n",
+                "up_command:\n",
+                "  auto_bootstrap: false\n",
+            ))
+        );
+    }
+}
@@ -10,6 +10,7 @@ use crate::internal::commands::frompath::PathCommand;
 use crate::internal::commands::Command;
 use crate::internal::config::config;
 use crate::internal::config::parser::path_pattern_from_str;
+use crate::internal::config::parser::ArgReferenceLint;
 use crate::internal::config::parser::ConfigError;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
@@ -22,11 +23,11 @@ use crate::internal::config::OmniConfig;
 use crate::internal::config::SyntaxOptArg;
 use crate::internal::config::SyntaxOptArgType;
 use crate::internal::env::omnipath_env;
-use crate::internal::git::is_path_gitignored;
 use crate::internal::git::package_root_path;
 use crate::internal::user_interface::StringColor;
 use crate::internal::workdir;
 use crate::omni_error;
+use crate::omni_info;
 
 #[derive(Debug, Clone)]
 struct ConfigCheckCommandArgs {
@@ -300,9 +301,9 @@ impl BuiltinCommand for ConfigCheckCommand {
         }
 
         let error_handler = ConfigErrorHandler::new();
-        self.aggregate_config_errors(&error_handler, &args);
+        let syntax_tests = self.aggregate_config_errors(&error_handler, &args);
         self.aggregate_path_errors(&error_handler, &args);
-        self.filter_and_print_errors(&error_handler, &args);
+        self.filter_and_print_errors(&error_handler, &args, &syntax_tests);
     }
 }
 
@@ -311,7 +312,7 @@ impl ConfigCheckCommand {
         &self,
         error_handler: &ConfigErrorHandler,
         args: &ConfigCheckCommandArgs,
-    ) {
+    ) -> SyntaxTestsSummary {
         // Get all the available configuration files
         let config_files: Vec<(String, ConfigScope)> = if args.use_files_from_cli() {
             args.config_files
@@ -338,59 +339,11 @@ impl ConfigCheckCommand {
                 .collect()
         };
 
+        let mut syntax_tests = SyntaxTestsSummary::default();
         for (file, scope) in config_files {
-            let loader = ConfigLoader::new_from_file(&file, scope);
-            let file_config = OmniConfig::from_config_value(
-                &loader.raw_config,
-                &error_handler.with_file(file.clone()),
-            );
-
-            // Load the check configuration for the location of the file,
-            // since we do not want to do local configuration checks that
-            // are not relevant to the file / work directory of the file
-            let local_check_config = config(&file).check;
-
-            // Go over all the commands defined in the configuration;
-            // commands can have subcommands, and subcommands can have
-            // subsubcommands, etc. We want all that in a single list
-            // to simplify some logic here
-            let mut commands_to_process: Vec<_> = file_config.commands.into_iter().collect();
-            let mut all_commands = vec![];
-            while let Some((name, command)) = commands_to_process.pop() {
-                all_commands.push((name.clone(), command.clone()));
-                if let Some(subcommands) = command.subcommands {
-                    commands_to_process.extend(
-                        subcommands
-                            .into_iter()
-                            .map(|(n, c)| (format!("{name} {n}"), c)),
-                    );
-                }
-            }
-
-            for (command_name, command) in all_commands {
-                // Validate the tags for the command
-                let tags = command.tags;
-                for (tag, filter) in local_check_config.tags.iter() {
-                    if let Some(value) = tags.get(tag) {
-                        if !filter.matches(value) {
-                            error_handler
-                                .with_key(&command_name)
-                                .with_file(file.clone())
-                                .with_context("tag", tag.to_string())
-                                .with_expected(filter.to_string())
-                                .with_actual(value.to_string())
-                                .error(ConfigErrorKind::UserDefinedConfigCommandInvalidTagValue);
-                        }
-                    } else {
-                        error_handler
-                            .with_key(&command_name)
-                            .with_file(file.clone())
-                            .with_context("tag", tag.to_string())
-                            .error(ConfigErrorKind::UserDefinedConfigCommandMissingTag);
-                    }
-                }
-            }
+            syntax_tests.add(&check_config_file(&file, scope, error_handler));
         }
+        syntax_tests
     }
 
     fn aggregate_path_errors(
@@ -512,6 +465,7 @@ impl ConfigCheckCommand {
         &self,
         error_handler: &ConfigErrorHandler,
         args: &ConfigCheckCommandArgs,
+        syntax_tests: &SyntaxTestsSummary,
     ) {
         let cliarg_patterns: Vec<String> = args
             .patterns
@@ -562,8 +516,9 @@ impl ConfigCheckCommand {
                     return false;
                 }
 
-                // Check if the file is gitignored
-                if is_path_gitignored(e.file()).unwrap_or(false) {
+                // Check if the file is gitignored, unless configured
+                // to still check gitignored files
+                if local_check_config.skips_gitignored(e.file()) {
                     return false;
                 }
 
@@ -578,6 +533,13 @@ impl ConfigCheckCommand {
                 for error in errors.iter() {
                     println!("{error}");
                 }
+
+                if syntax_tests.total() > 0 {
+                    omni_info!(format!(
+                        "syntax tests: {} passed, {} failed",
+                        syntax_tests.passed, syntax_tests.failed
+                    ));
+                }
             }
             ConfigCheckCommandOutput::Json => match serde_json::to_string_pretty(&errors) {
                 Ok(json) => println!("{json}"),
@@ -587,12 +549,135 @@ impl ConfigCheckCommand {
             },
         }
 
-        // Exit with the appropriate code
-        exit(if errors.is_empty() { 0 } else { 1 });
+        // Exit with the appropriate code; warnings alone do not fail the check
+        exit(if errors.iter().all(|e| e.is_warning()) {
+            0
+        } else {
+            1
+        });
     }
 }
 
-fn check_selected(
+/// The number of syntax tests of the commands that passed and failed
+/// during a check, reported along with the errors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SyntaxTestsSummary {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+impl SyntaxTestsSummary {
+    fn add(&mut self, other: &Self) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+    }
+
+    fn total(&self) -> usize {
+        self.passed + self.failed
+    }
+}
+
+/// Checks a configuration file for errors, including the rules that go
+/// beyond parsing it (tags, syntax tests and argument references of the
+/// commands it defines), without running anything; returns how many of
+/// the syntax tests passed and failed
+pub(crate) fn check_config_file(
+    file: &str,
+    scope: ConfigScope,
+    error_handler: &ConfigErrorHandler,
+) -> SyntaxTestsSummary {
+    let loader = ConfigLoader::new_from_file(file, scope);
+    let file_config =
+        OmniConfig::from_config_value(&loader.raw_config, &error_handler.with_file(file));
+
+    // Load the check configuration for the location of the file,
+    // since we do not want to do local configuration checks that
+    // are not relevant to the file / work directory of the file
+    let local_check_config = config(file).check;
+
+    // Go over all the commands defined in the configuration;
+    // commands can have subcommands, and subcommands can have
+    // subsubcommands, etc. We want all that in a single list
+    // to simplify some logic here
+    let mut commands_to_process: Vec<_> = file_config.commands.into_iter().collect();
+    let mut all_commands = vec![];
+    while let Some((name, command)) = commands_to_process.pop() {
+        all_commands.push((name.clone(), command.clone()));
+        if let Some(subcommands) = command.subcommands {
+            commands_to_process.extend(
+                subcommands
+                    .into_iter()
+                    .map(|(n, c)| (format!("{name} {n}"), c)),
+            );
+        }
+    }
+
+    let mut syntax_tests = SyntaxTestsSummary::default();
+    for (command_name, command) in all_commands {
+        // Validate the tags for the command
+        let tags = &command.tags;
+        for (tag, filter) in local_check_config.tags.iter() {
+            if let Some(value) = tags.get(tag) {
+                if !filter.matches(value) {
+                    error_handler
+                        .with_key(&command_name)
+                        .with_file(file)
+                        .with_context("tag", tag.to_string())
+                        .with_expected(filter.to_string())
+                        .with_actual(value.to_string())
+                        .error(ConfigErrorKind::UserDefinedConfigCommandInvalidTagValue);
+                }
+            } else {
+                error_handler
+                    .with_key(&command_name)
+                    .with_file(file)
+                    .with_context("tag", tag.to_string())
+                    .error(ConfigErrorKind::UserDefinedConfigCommandMissingTag);
+            }
+        }
+
+        // Run the syntax tests declared for the command
+        let called_as = command_name
+            .split_whitespace()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        for result in command.run_syntax_tests(called_as) {
+            if let Err(reason) = result.result {
+                syntax_tests.failed += 1;
+                error_handler
+                    .with_key(&command_name)
+                    .with_file(file)
+                    .with_context("case", result.index)
+                    .with_context("args", shell_words::join(&result.args))
+                    .with_context("reason", reason)
+                    .error(ConfigErrorKind::UserDefinedConfigCommandSyntaxTestFailed);
+            } else {
+                syntax_tests.passed += 1;
+            }
+        }
+
+        // Cross-check the declared parameters with the
+        // arguments referenced in the run body
+        for lint in command.lint_arg_references() {
+            match lint {
+                ArgReferenceLint::UndeclaredReference(argument) => error_handler
+                    .with_key(&command_name)
+                    .with_file(file)
+                    .with_context("argument", argument)
+                    .error(ConfigErrorKind::UserDefinedConfigCommandUndeclaredArgReference),
+                ArgReferenceLint::UnusedParameter(parameter) => error_handler
+                    .with_key(&command_name)
+                    .with_file(file)
+                    .with_context("parameter", parameter)
+                    .error(ConfigErrorKind::UserDefinedConfigCommandUnusedArg),
+            }
+        }
+    }
+
+    syntax_tests
+}
+
+pub(crate) fn check_selected(
     error: &ConfigError,
     select_errors: &HashSet<String>,
     ignore_errors: &HashSet<String>,
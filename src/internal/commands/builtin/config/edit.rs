@@ -0,0 +1,637 @@
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::exit;
+
+use itertools::Itertools;
+
+use crate::internal::build::current_omni_version;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::builtin::config::check::check_config_file;
+use crate::internal::commands::builtin::config::check::check_selected;
+use crate::internal::commands::utils::find_editor;
+use crate::internal::commands::Command;
+use crate::internal::config::config;
+use crate::internal::config::parser::ConfigError;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::ConfigLoader;
+use crate::internal::config::ConfigScope;
+use crate::internal::config::ConfigValue;
+use crate::internal::config::OmniConfig;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::user_interface::StringColor;
+use crate::internal::workdir;
+use crate::omni_error;
+use crate::omni_info;
+
+/// The template of the user configuration file, used when the
+/// file does not exist yet
+const USER_CONFIG_TEMPLATE: &str = concat!(
+    "# omni user configuration\n",
+    "#\n",
+    "# This file applies to all the omni commands you run. The available\n",
+    "# parameters are described in https://omnicli.dev/reference/configuration\n",
+    "#\n",
+    "# Uncomment and adapt the examples below to get started.\n",
+    "\n",
+    "# worktree: ~/git\n",
+    "\n",
+    "# repo_path_format: \"%{host}/%{org}/%{repo}\"\n",
+    "\n",
+    "# org:\n",
+    "#   - handle: git@github.com:myorg\n",
+    "#     trusted: true\n",
+    "\n",
+    "# path_repo_updates:\n",
+    "#   enabled: true\n",
+    "#   interval: 43200 # 12 hours\n",
+);
+
+/// The template of the work directory configuration file, used when
+/// the file does not exist yet
+const WORKDIR_CONFIG_TEMPLATE: &str = concat!(
+    "# omni work directory configuration\n",
+    "#\n",
+    "# This file applies to the omni commands run in this work directory. The\n",
+    "# available parameters are described in https://omnicli.dev/reference/configuration\n",
+    "#\n",
+    "# Uncomment and adapt the examples below to get started.\n",
+    "\n",
+    "# up:\n",
+    "#   - go\n",
+    "#   - node: 20\n",
+    "\n",
+    "# env:\n",
+    "#   MY_VARIABLE: my value\n",
+    "\n",
+    "# commands:\n",
+    "#   hello-world:\n",
+    "#     desc: Say hello\n",
+    "#     run: echo \"Hello world!\"\n",
+);
+
+#[derive(Debug, Clone)]
+struct ConfigEditCommandArgs {
+    scope: ConfigEditScope,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for ConfigEditCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let local_scope = matches!(
+            args.get("local"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        let scope = if local_scope {
+            ConfigEditScope::Workdir
+        } else {
+            ConfigEditScope::Global
+        };
+
+        Self { scope }
+    }
+}
+
+/// The configuration that can be edited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigEditScope {
+    Global,
+    Workdir,
+}
+
+impl ConfigEditScope {
+    fn config_scope(&self) -> ConfigScope {
+        match self {
+            Self::Global => ConfigScope::User,
+            Self::Workdir => ConfigScope::Workdir,
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            Self::Global => USER_CONFIG_TEMPLATE,
+            Self::Workdir => WORKDIR_CONFIG_TEMPLATE,
+        }
+    }
+}
+
+/// What to do with a configuration file that still has errors
+/// once the editor exits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InvalidConfigChoice {
+    Reopen,
+    Keep,
+    Discard,
+}
+
+/// How an edition of a configuration file ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigEditOutcome {
+    Valid,
+    Kept,
+    Discarded,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigEditCommand {}
+
+impl ConfigEditCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for ConfigEditCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["config".to_string(), "edit".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Edit a configuration file and check it for errors\n",
+                "\n",
+                "This opens the configuration file in your editor, creating it from ",
+                "a template with commented examples if it does not exist yet. Once the ",
+                "editor exits, the file is checked the same way as with \x1B[3momni ",
+                "config check\x1B[0m, and you can re-open the editor, keep the file, or ",
+                "discard your changes if errors are found.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--global".to_string()],
+                    desc: Some(
+                        "Edit the global configuration file; this is the default.".to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec!["local".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--local".to_string()],
+                    desc: Some(
+                        "Edit the configuration file of the current work directory.".to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec!["global".to_string()],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = ConfigEditCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        let wd = workdir(".");
+        let file = match config_file_for_scope(args.scope, wd.root()) {
+            Ok(file) => file,
+            Err(err) => {
+                omni_error!(err);
+                exit(1);
+            }
+        };
+
+        let editor = match find_editor() {
+            Some(editor) => editor,
+            None => {
+                omni_error!(format!(
+                    "no editor found; set the {} or {} environment variable",
+                    "VISUAL".light_yellow(),
+                    "EDITOR".light_yellow(),
+                ));
+                exit(1);
+            }
+        };
+
+        let original = match std::fs::read_to_string(&file) {
+            Ok(contents) => Some(contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                omni_error!(format!("unable to read {}: {}", file.display(), err));
+                exit(1);
+            }
+        };
+
+        if original.is_none() {
+            if let Err(err) = create_from_template(&file, args.scope) {
+                omni_error!(format!("unable to create {}: {}", file.display(), err));
+                exit(1);
+            }
+        }
+
+        let outcome = edit_until_valid(
+            &file,
+            args.scope,
+            original.as_deref(),
+            |file| run_editor(&editor, file),
+            ask_invalid_config_choice,
+        );
+
+        match outcome {
+            Ok(ConfigEditOutcome::Valid) => {}
+            Ok(ConfigEditOutcome::Kept) => {
+                omni_info!(format!("kept {} with errors", file.display()));
+            }
+            Ok(ConfigEditOutcome::Discarded) => {
+                omni_info!(format!("discarded the changes to {}", file.display()));
+            }
+            Err(err) => {
+                omni_error!(err);
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Returns the configuration file to edit for the given scope, resolved
+/// the same way as the files read when loading the configuration
+fn config_file_for_scope(scope: ConfigEditScope, wd_root: Option<&str>) -> Result<PathBuf, String> {
+    match scope {
+        ConfigEditScope::Global => ConfigLoader::main_user_config_file()
+            .ok_or("unable to find a writeable user config file".to_string()),
+        ConfigEditScope::Workdir => match wd_root {
+            Some(wd_root) => Ok(ConfigLoader::main_workdir_config_file(wd_root)),
+            None => Err("Not in a worktree".to_string()),
+        },
+    }
+}
+
+/// Creates the configuration file, and its parent directories if needed,
+/// from the template of the scope
+fn create_from_template(file: &Path, scope: ConfigEditScope) -> std::io::Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(file, scope.template())
+}
+
+/// Runs the editor on the file and waits for it to exit; the editor is
+/// run through the shell since it can contain arguments, e.g. `code --wait`
+fn run_editor(editor: &str, file: &Path) -> Result<(), String> {
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg("sh")
+        .arg(file)
+        .status()
+        .map_err(|err| format!("unable to run {editor}: {err}"))?;
+
+    if !status.success() {
+        return Err(format!("{editor} exited with {status}"));
+    }
+
+    Ok(())
+}
+
+fn ask_invalid_config_choice(_errors: &[ConfigError]) -> InvalidConfigChoice {
+    let choices = [
+        ("Re-open the editor", InvalidConfigChoice::Reopen),
+        ("Keep the file with its errors", InvalidConfigChoice::Keep),
+        ("Discard my changes", InvalidConfigChoice::Discard),
+    ];
+
+    let question = requestty::Question::select("config_edit_invalid")
+        .ask_if_answered(true)
+        .on_esc(requestty::OnEsc::Terminate)
+        .message(format!(
+            "{} {}",
+            "omni:".light_cyan(),
+            "The configuration has errors, what do you want to do?".yellow()
+        ))
+        .choices(choices.iter().map(|(label, _)| label.to_string()))
+        .should_loop(false)
+        .build();
+
+    match requestty::prompt_one(question) {
+        Ok(requestty::Answer::ListItem(listitem)) => choices[listitem.index].1,
+        // Keep the file as-is if the prompt was interrupted, as
+        // discarding the changes could lose the work of the user
+        _ => InvalidConfigChoice::Keep,
+    }
+}
+
+/// Opens the file in the editor, then checks it once the editor exits;
+/// while errors are found, the choice function decides whether to re-open
+/// the editor, keep the file, or discard the changes, in which case the
+/// original contents are restored, or the file removed if it was created
+/// for this edition. Warnings are shown but do not need to be fixed.
+fn edit_until_valid<E, C>(
+    file: &Path,
+    scope: ConfigEditScope,
+    original: Option<&str>,
+    mut edit: E,
+    mut choose: C,
+) -> Result<ConfigEditOutcome, String>
+where
+    E: FnMut(&Path) -> Result<(), String>,
+    C: FnMut(&[ConfigError]) -> InvalidConfigChoice,
+{
+    loop {
+        edit(file)?;
+
+        let errors = validate_config_file(file, scope);
+        for error in errors.iter() {
+            eprintln!("{error}");
+        }
+
+        if errors.iter().all(|error| error.is_warning()) {
+            return Ok(ConfigEditOutcome::Valid);
+        }
+
+        match choose(&errors) {
+            InvalidConfigChoice::Reopen => continue,
+            InvalidConfigChoice::Keep => return Ok(ConfigEditOutcome::Kept),
+            InvalidConfigChoice::Discard => {
+                let restored = match original {
+                    Some(contents) => std::fs::write(file, contents),
+                    None => std::fs::remove_file(file),
+                };
+                restored.map_err(|err| format!("unable to restore {}: {}", file.display(), err))?;
+                return Ok(ConfigEditOutcome::Discarded);
+            }
+        }
+    }
+}
+
+/// Checks the configuration file with the same rules as `omni config
+/// check`, filtered by the check configuration applying to the file;
+/// the errors that were not reported with a line are located in the file
+/// from their key when possible
+fn validate_config_file(file: &Path, scope: ConfigEditScope) -> Vec<ConfigError> {
+    let file_str = file.to_string_lossy().to_string();
+    let error_handler = ConfigErrorHandler::new().with_file(&file_str);
+
+    let contents = match std::fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(err) => {
+            error_handler
+                .with_context("error", err.to_string())
+                .error(ConfigErrorKind::InvalidSyntax);
+            return error_handler.errors();
+        }
+    };
+
+    // The configuration loader exits when the file cannot be parsed or
+    // requires a more recent version of omni, so check for those first
+    // to report them as errors instead
+    match ConfigValue::from_str(&contents) {
+        Err(err) => {
+            let handler = match err.location() {
+                Some(location) => error_handler.with_lineno(location.line()),
+                None => error_handler.clone(),
+            };
+            handler
+                .with_context("error", err.to_string())
+                .error(ConfigErrorKind::InvalidSyntax);
+        }
+        Ok(config_value) => {
+            if OmniConfig::check_min_omni_version(
+                &config_value,
+                current_omni_version(),
+                &error_handler,
+            ) {
+                check_config_file(&file_str, scope.config_scope(), &error_handler);
+            }
+        }
+    }
+
+    let check_config = config(&file_str).check;
+    let select_errors: HashSet<String> = check_config.select.iter().cloned().collect();
+    let ignore_errors: HashSet<String> = check_config.ignore.iter().cloned().collect();
+
+    error_handler
+        .errors()
+        .into_iter()
+        .filter(|error| check_selected(error, &select_errors, &ignore_errors))
+        .map(|mut error| {
+            if error.lineno() == 0 {
+                if let Some(lineno) = error.key().and_then(|key| key_line(&contents, key)) {
+                    error.set_lineno(lineno);
+                }
+            }
+            error
+        })
+        .sorted()
+        .collect()
+}
+
+/// A part of the key of a configuration error, e.g. `up` and `[1]` in
+/// `up[1].go`
+#[derive(Debug, PartialEq, Eq)]
+enum KeyPart<'a> {
+    Name(&'a str),
+    Index(usize),
+}
+
+fn key_parts(key: &str) -> Vec<KeyPart<'_>> {
+    let mut parts = vec![];
+    for segment in key.split('.') {
+        let (name, mut indexes) = match segment.find('[') {
+            Some(pos) => (&segment[..pos], &segment[pos..]),
+            None => (segment, ""),
+        };
+
+        if !name.is_empty() {
+            parts.push(KeyPart::Name(name));
+        }
+
+        while let Some(rest) = indexes.strip_prefix('[') {
+            let Some(end) = rest.find(']') else {
+                break;
+            };
+            if let Ok(index) = rest[..end].parse() {
+                parts.push(KeyPart::Index(index));
+            }
+            indexes = &rest[end + 1..];
+        }
+    }
+    parts
+}
+
+/// A line of a YAML file, split between its indentation, the list item
+/// marker if any, and its contents
+struct YamlLine<'a> {
+    indent: usize,
+    item: bool,
+    content_indent: usize,
+    content: &'a str,
+}
+
+impl<'a> YamlLine<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            return None;
+        }
+        let indent = line.len() - content.len();
+
+        match content
+            .strip_prefix("- ")
+            .or_else(|| content.strip_prefix('-').filter(|c| c.is_empty()))
+        {
+            Some(item_content) => {
+                let trimmed = item_content.trim_start();
+                Some(Self {
+                    indent,
+                    item: true,
+                    content_indent: indent + (content.len() - trimmed.len()),
+                    content: trimmed,
+                })
+            }
+            None => Some(Self {
+                indent,
+                item: false,
+                content_indent: indent,
+                content,
+            }),
+        }
+    }
+
+    fn has_key(&self, name: &str) -> bool {
+        [name.to_string(), format!("\"{name}\""), format!("'{name}'")]
+            .iter()
+            .any(|key| {
+                self.content
+                    .strip_prefix(key.as_str())
+                    .is_some_and(|rest| rest.trim_start().starts_with(':'))
+            })
+    }
+}
+
+/// Returns the line (starting at 1) at which the value for the given key
+/// is defined in the YAML contents, following the indentation of the
+/// file; this is best-effort, and returns the line of the deepest part of
+/// the key that could be found, if any
+fn key_line(contents: &str, key: &str) -> Option<usize> {
+    let lines = contents
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| YamlLine::parse(line).map(|line| (idx, line)))
+        .collect::<Vec<_>>();
+
+    let mut found = None;
+    // The position in the lines from which the current block starts,
+    // and whether its first line is a list item whose contents belong
+    // to the block
+    let mut start = 0;
+    let mut item_start = false;
+    // The indentation below which lines are out of the current block;
+    // list items at that same indentation are still in the block for
+    // the value of a key, but not for the contents of a list item
+    let mut block: Option<(usize, bool)> = None;
+
+    for part in key_parts(key) {
+        let in_block = |pos: usize, line: &YamlLine| {
+            if pos == start && item_start {
+                return true;
+            }
+            match block {
+                None => true,
+                Some((indent, items_allowed)) => {
+                    line.indent > indent || (line.indent == indent && items_allowed && line.item)
+                }
+            }
+        };
+
+        let mut matched = None;
+        let mut child_indent = None;
+        let mut item_count = 0;
+        for (pos, (idx, line)) in lines.iter().enumerate().skip(start) {
+            if !in_block(pos, line) {
+                break;
+            }
+
+            match part {
+                KeyPart::Name(name) => {
+                    let indent = if pos == start && item_start {
+                        line.content_indent
+                    } else if line.item {
+                        // A list item cannot hold the key we are looking for
+                        // unless it is the one the block is about
+                        if child_indent.is_none() {
+                            break;
+                        }
+                        continue;
+                    } else {
+                        line.indent
+                    };
+                    let child_indent = *child_indent.get_or_insert(indent);
+                    if indent == child_indent && line.has_key(name) {
+                        matched = Some((pos, *idx, indent, true));
+                        break;
+                    }
+                }
+                KeyPart::Index(index) => {
+                    if !line.item || (pos == start && item_start) {
+                        continue;
+                    }
+                    let child_indent = *child_indent.get_or_insert(line.indent);
+                    if line.indent != child_indent {
+                        continue;
+                    }
+                    if item_count == index {
+                        matched = Some((pos, *idx, line.indent, false));
+                        break;
+                    }
+                    item_count += 1;
+                }
+            }
+        }
+
+        match matched {
+            Some((pos, idx, indent, is_key)) => {
+                found = Some(idx + 1);
+                if is_key {
+                    start = pos + 1;
+                    item_start = false;
+                    block = Some((indent, true));
+                } else {
+                    start = pos;
+                    item_start = true;
+                    block = Some((indent, false));
+                }
+            }
+            None => break,
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+#[path = "edit_test.rs"]
+mod tests;
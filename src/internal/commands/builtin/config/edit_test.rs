@@ -0,0 +1,278 @@
+use super::*;
+
+use std::cell::Cell;
+
+use crate::internal::config::flush_config;
+use crate::internal::testutils::run_with_env;
+
+fn tmp_dir() -> PathBuf {
+    PathBuf::from(std::env::var("TMPDIR").expect("TMPDIR should be set"))
+}
+
+mod config_file_for_scope {
+    use super::*;
+
+    #[test]
+    fn global_uses_main_user_config_file() {
+        run_with_env(&[], || {
+            let file = tmp_dir().join("omni-config-edit.yaml");
+            std::env::set_var("OMNI_CONFIG", &file);
+
+            let resolved = config_file_for_scope(ConfigEditScope::Global, None);
+            std::env::remove_var("OMNI_CONFIG");
+
+            assert_eq!(resolved, Ok(file));
+        });
+    }
+
+    #[test]
+    fn workdir_defaults_to_omni_yaml() {
+        run_with_env(&[], || {
+            let root = tmp_dir();
+            let root_str = root.to_string_lossy().to_string();
+
+            assert_eq!(
+                config_file_for_scope(ConfigEditScope::Workdir, Some(&root_str)),
+                Ok(root.join(".omni.yaml"))
+            );
+        });
+    }
+
+    #[test]
+    fn workdir_prefers_last_existing_file() {
+        run_with_env(&[], || {
+            let root = tmp_dir();
+            let root_str = root.to_string_lossy().to_string();
+            std::fs::create_dir_all(root.join(".omni")).unwrap();
+            std::fs::write(root.join(".omni.yaml"), "").unwrap();
+            std::fs::write(root.join(".omni/config.yaml"), "").unwrap();
+
+            assert_eq!(
+                config_file_for_scope(ConfigEditScope::Workdir, Some(&root_str)),
+                Ok(root.join(".omni/config.yaml"))
+            );
+        });
+    }
+
+    #[test]
+    fn workdir_outside_of_worktree() {
+        assert!(config_file_for_scope(ConfigEditScope::Workdir, None).is_err());
+    }
+}
+
+mod create_from_template {
+    use super::*;
+
+    #[test]
+    fn creates_file_and_parents() {
+        run_with_env(&[], || {
+            let file = tmp_dir().join("wd/.omni/config.yaml");
+
+            create_from_template(&file, ConfigEditScope::Workdir).expect("should create file");
+
+            let contents = std::fs::read_to_string(&file).unwrap();
+            assert_eq!(contents, WORKDIR_CONFIG_TEMPLATE);
+        });
+    }
+
+    #[test]
+    fn templates_are_valid() {
+        for scope in [ConfigEditScope::Global, ConfigEditScope::Workdir] {
+            run_with_env(&[], || {
+                flush_config("/");
+                let file = tmp_dir().join("template.yaml");
+                create_from_template(&file, scope).unwrap();
+
+                let errors = validate_config_file(&file, scope);
+                assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+            });
+        }
+    }
+}
+
+mod key_line {
+    use super::*;
+
+    const CONTENTS: &str = concat!(
+        "# comment\n",
+        "up:\n",
+        "  - go\n",
+        "  - node:\n",
+        "      version: 20\n",
+        "  - python: 3.12\n",
+        "\n",
+        "commands:\n",
+        "  hello:\n",
+        "    desc: Say hello\n",
+        "    syntax:\n",
+        "      parameters:\n",
+        "        - name: who\n",
+        "          type: str\n",
+        "        - name: loud\n",
+        "          type: flag\n",
+        "env:\n",
+        "  desc: not the command one\n",
+    );
+
+    #[test]
+    fn top_level_key() {
+        assert_eq!(key_line(CONTENTS, "commands"), Some(8));
+        assert_eq!(key_line(CONTENTS, "env"), Some(17));
+    }
+
+    #[test]
+    fn nested_keys() {
+        assert_eq!(key_line(CONTENTS, "commands.hello.desc"), Some(10));
+        assert_eq!(key_line(CONTENTS, "env.desc"), Some(18));
+    }
+
+    #[test]
+    fn list_items() {
+        assert_eq!(key_line(CONTENTS, "up[0]"), Some(3));
+        assert_eq!(key_line(CONTENTS, "up[1].node.version"), Some(5));
+        assert_eq!(key_line(CONTENTS, "up[2].python"), Some(6));
+        assert_eq!(
+            key_line(CONTENTS, "commands.hello.syntax.parameters[1].type"),
+            Some(16)
+        );
+    }
+
+    #[test]
+    fn deepest_found_part() {
+        assert_eq!(key_line(CONTENTS, "commands.hello.unknown"), Some(9));
+        assert_eq!(key_line(CONTENTS, "up[5]"), Some(2));
+        assert_eq!(key_line(CONTENTS, "unknown"), None);
+    }
+}
+
+mod edit_until_valid {
+    use super::*;
+
+    const INVALID: &str = "commands:\n  hello:\n    run: [\n";
+    const VALID: &str = "commands:\n  hello:\n    run: echo hello\n";
+
+    /// Returns a fake editor writing the given contents in turn
+    fn fake_editor(edits: &'static [&'static str]) -> impl FnMut(&Path) -> Result<(), String> {
+        let mut edits = edits.iter();
+        move |file| {
+            let contents = edits.next().expect("editor opened too many times");
+            std::fs::write(file, contents).map_err(|err| err.to_string())
+        }
+    }
+
+    #[test]
+    fn reopens_until_valid() {
+        run_with_env(&[], || {
+            flush_config("/");
+            let file = tmp_dir().join("config.yaml");
+            std::fs::write(&file, "").unwrap();
+
+            let asked = Cell::new(0);
+            let outcome = edit_until_valid(
+                &file,
+                ConfigEditScope::Global,
+                Some(""),
+                fake_editor(&[INVALID, VALID]),
+                |errors| {
+                    asked.set(asked.get() + 1);
+                    assert_eq!(errors.len(), 1);
+                    assert_eq!(errors[0].errorcode(), "C121");
+                    assert_eq!(errors[0].lineno(), 4);
+                    InvalidConfigChoice::Reopen
+                },
+            );
+
+            assert_eq!(outcome, Ok(ConfigEditOutcome::Valid));
+            assert_eq!(asked.get(), 1);
+            assert_eq!(std::fs::read_to_string(&file).unwrap(), VALID);
+        });
+    }
+
+    #[test]
+    fn keeps_invalid_file() {
+        run_with_env(&[], || {
+            flush_config("/");
+            let file = tmp_dir().join("config.yaml");
+            std::fs::write(&file, VALID).unwrap();
+
+            let outcome = edit_until_valid(
+                &file,
+                ConfigEditScope::Global,
+                Some(VALID),
+                fake_editor(&[INVALID]),
+                |_| InvalidConfigChoice::Keep,
+            );
+
+            assert_eq!(outcome, Ok(ConfigEditOutcome::Kept));
+            assert_eq!(std::fs::read_to_string(&file).unwrap(), INVALID);
+        });
+    }
+
+    #[test]
+    fn discard_restores_original() {
+        run_with_env(&[], || {
+            flush_config("/");
+            let file = tmp_dir().join("config.yaml");
+            std::fs::write(&file, VALID).unwrap();
+
+            let outcome = edit_until_valid(
+                &file,
+                ConfigEditScope::Global,
+                Some(VALID),
+                fake_editor(&[INVALID]),
+                |_| InvalidConfigChoice::Discard,
+            );
+
+            assert_eq!(outcome, Ok(ConfigEditOutcome::Discarded));
+            assert_eq!(std::fs::read_to_string(&file).unwrap(), VALID);
+        });
+    }
+
+    #[test]
+    fn discard_removes_created_file() {
+        run_with_env(&[], || {
+            flush_config("/");
+            let file = tmp_dir().join("config.yaml");
+            create_from_template(&file, ConfigEditScope::Global).unwrap();
+
+            let outcome = edit_until_valid(
+                &file,
+                ConfigEditScope::Global,
+                None,
+                fake_editor(&[INVALID]),
+                |_| InvalidConfigChoice::Discard,
+            );
+
+            assert_eq!(outcome, Ok(ConfigEditOutcome::Discarded));
+            assert!(!file.exists());
+        });
+    }
+
+    #[test]
+    fn reports_check_errors_with_line() {
+        run_with_env(&[], || {
+            flush_config("/");
+            let file = tmp_dir().join("config.yaml");
+            std::fs::write(&file, "").unwrap();
+
+            let mut errors_seen = vec![];
+            let outcome = edit_until_valid(
+                &file,
+                ConfigEditScope::Global,
+                Some(""),
+                fake_editor(&["commands:\n  hello:\n    desc: 42\n    run: [1]\n"]),
+                |errors| {
+                    errors_seen = errors.to_vec();
+                    InvalidConfigChoice::Keep
+                },
+            );
+
+            assert_eq!(outcome, Ok(ConfigEditOutcome::Kept));
+            assert!(!errors_seen.is_empty());
+            assert!(
+                errors_seen.iter().all(|error| error.lineno() > 0),
+                "errors should be located: {errors_seen:?}"
+            );
+        });
+    }
+}
@@ -0,0 +1,425 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::process::exit;
+
+use serde::Serialize;
+use shell_escape::escape;
+
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::command_loader;
+use crate::internal::commands::utils::abs_path;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::user_interface::print::strip_ansi_codes;
+use crate::internal::user_interface::StringColor;
+use crate::internal::workdir;
+use crate::omni_error;
+use crate::omni_info;
+
+/// The version of the format of the manifest, to be increased whenever
+/// a change would break the consumers of the manifest
+const MANIFEST_VERSION: u32 = 1;
+
+/// The placeholder for the arguments in the invocation template
+const ARGS_PLACEHOLDER: &str = "{args}";
+
+#[derive(Debug, Clone)]
+struct ConfigManifestCommandArgs {
+    format: ManifestFormat,
+    include_builtins: bool,
+    output: Option<String>,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for ConfigManifestCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let format = match args.get("format") {
+            Some(ParseArgsValue::SingleString(Some(value))) => match value.as_str() {
+                "omni" => ManifestFormat::Omni,
+                "vscode" => ManifestFormat::VsCode,
+                _ => unreachable!("unknown value for format"),
+            },
+            _ => ManifestFormat::Omni,
+        };
+        let include_builtins = matches!(
+            args.get("include_builtins"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+        let output = match args.get("output") {
+            Some(ParseArgsValue::SingleString(Some(output))) => {
+                let output = output.trim();
+                if output.is_empty() || output == "-" {
+                    None
+                } else {
+                    Some(output.to_string())
+                }
+            }
+            _ => None,
+        };
+
+        Self {
+            format,
+            include_builtins,
+            output,
+        }
+    }
+}
+
+/// The flavors in which the manifest can be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// The omni manifest, describing the commands and their arguments
+    Omni,
+    /// The content of a VS Code `tasks.json` file
+    VsCode,
+}
+
+/// A static description of the commands available in a work directory,
+/// for tools that need to list and run omni commands without calling
+/// omni to resolve them (e.g. editor task runners, launchers)
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CommandManifest {
+    pub version: u32,
+    pub workdir: String,
+    /// Hash of the commands described in the manifest, which changes
+    /// whenever the configuration leads to different commands, so that
+    /// consumers can detect that the manifest is stale
+    pub config_hash: String,
+    pub commands: Vec<ManifestCommand>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ManifestCommand {
+    pub name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub desc: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub category: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<ManifestArgument>,
+    /// The shell invocation to run the command, in which `{args}` is to
+    /// be replaced by the shell-quoted arguments, if any
+    pub invocation: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ManifestArgument {
+    pub name: String,
+    pub names: Vec<String>,
+    #[serde(rename = "type")]
+    pub arg_type: String,
+    pub positional: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub desc: String,
+}
+
+impl From<&SyntaxOptArg> for ManifestArgument {
+    fn from(param: &SyntaxOptArg) -> Self {
+        Self {
+            name: param.dest(),
+            names: param.all_names(),
+            arg_type: param.arg_type.to_str().to_string(),
+            positional: param.is_positional(),
+            required: param.required,
+            default: param.default.clone(),
+            values: param.arg_type.possible_values(),
+            desc: strip_ansi_codes(param.desc.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+impl CommandManifest {
+    /// Builds the manifest of the given commands for the work directory;
+    /// the commands are sorted by name so that generating the manifest
+    /// for the same configuration always leads to the same output
+    pub fn from_commands(workdir: &str, commands: &[Command], include_builtins: bool) -> Self {
+        let mut commands = commands
+            .iter()
+            .filter(|command| match command {
+                Command::Void(_) => false,
+                Command::Builtin(_) => include_builtins,
+                _ => true,
+            })
+            .map(ManifestCommand::from_command)
+            .collect::<Vec<_>>();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        commands.dedup_by(|a, b| a.name == b.name);
+
+        let serialized = serde_json::to_string(&commands).expect("failed to serialize commands");
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(serialized.as_bytes());
+        let config_hash = hasher.finalize().to_hex()[..16].to_string();
+
+        Self {
+            version: MANIFEST_VERSION,
+            workdir: workdir.to_string(),
+            config_hash,
+            commands,
+        }
+    }
+
+    /// Returns the VS Code `tasks.json` content mapping each command of
+    /// the manifest to a shell task run from the workspace folder
+    pub fn vscode_tasks(&self) -> VsCodeTasks {
+        VsCodeTasks {
+            version: "2.0.0".to_string(),
+            tasks: self
+                .commands
+                .iter()
+                .map(|command| VsCodeTask {
+                    label: format!("omni: {}", command.name),
+                    task_type: "shell".to_string(),
+                    command: "omni".to_string(),
+                    args: command.name.split(' ').map(|s| s.to_string()).collect(),
+                    detail: command.desc.clone(),
+                    options: VsCodeTaskOptions {
+                        cwd: "${workspaceFolder}".to_string(),
+                    },
+                    problem_matcher: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    /// Serializes the manifest in the requested format
+    pub fn to_json(&self, format: ManifestFormat) -> String {
+        match format {
+            ManifestFormat::Omni => serde_json::to_string_pretty(self),
+            ManifestFormat::VsCode => serde_json::to_string_pretty(&self.vscode_tasks()),
+        }
+        .expect("failed to serialize manifest")
+    }
+}
+
+impl ManifestCommand {
+    fn from_command(command: &Command) -> Self {
+        let name = command.name().join(" ");
+        let aliases = command
+            .aliases()
+            .iter()
+            .map(|alias| alias.join(" "))
+            .collect();
+        // The category of the commands from the configuration files and
+        // makefiles starts with their source, which is not a category
+        let skip_source = matches!(command, Command::FromConfig(_) | Command::FromMakefile(_));
+        let category = command
+            .category()
+            .unwrap_or_default()
+            .into_iter()
+            .skip(if skip_source { 1 } else { 0 })
+            .collect();
+        let arguments = command
+            .syntax()
+            .map(|syntax| {
+                syntax
+                    .parameters
+                    .iter()
+                    .map(ManifestArgument::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let invocation = std::iter::once("omni".to_string())
+            .chain(
+                command
+                    .name()
+                    .into_iter()
+                    .map(|part| escape(Cow::Owned(part)).to_string()),
+            )
+            .chain(std::iter::once(ARGS_PLACEHOLDER.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            name,
+            aliases,
+            desc: strip_ansi_codes(&command.help_short()),
+            category,
+            arguments,
+            invocation,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct VsCodeTasks {
+    pub version: String,
+    pub tasks: Vec<VsCodeTask>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct VsCodeTask {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub task_type: String,
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    pub options: VsCodeTaskOptions,
+    #[serde(rename = "problemMatcher")]
+    pub problem_matcher: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct VsCodeTaskOptions {
+    pub cwd: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigManifestCommand {}
+
+impl ConfigManifestCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for ConfigManifestCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["config".to_string(), "manifest".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Generate a manifest of the commands available in the work directory\n",
+                "\n",
+                "The manifest lists the commands that can be resolved from the work ",
+                "directory, after applying the commands filters, with their description, ",
+                "category, arguments and the shell invocation to run them. It is meant to ",
+                "be consumed by external tools, such as editors and launchers, that want to ",
+                "list and run omni commands. The manifest includes a hash of the commands, ",
+                "allowing consumers to detect when it needs to be regenerated.\n",
+                "\n",
+                "Using \x1B[1m--format vscode\x1B[0m generates the content of a VS Code ",
+                "\x1B[3mtasks.json\x1B[0m file instead, with a task for each command.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["-f".to_string(), "--format".to_string()],
+                    desc: Some("The format of the manifest".to_string()),
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "omni".to_string(),
+                        "vscode".to_string(),
+                    ]),
+                    default: Some("omni".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--include-builtins".to_string()],
+                    desc: Some("Include the builtin omni commands in the manifest".to_string()),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["-o".to_string(), "--output".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The file to write the manifest to ",
+                            "[\x1B[1mdefault: standard output\x1B[0m]"
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::FilePath,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = ConfigManifestCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        // Resolve the output file before moving to the root of the
+        // work directory, so that relative paths work as expected
+        let output = args
+            .output
+            .map(|output| abs_path(output).to_string_lossy().to_string());
+
+        let wd = workdir(".");
+        let wd_root = match wd.root() {
+            Some(wd_root) => wd_root.to_string(),
+            None => {
+                omni_error!("not in a work directory");
+                exit(1);
+            }
+        };
+
+        if let Err(err) = std::env::set_current_dir(&wd_root) {
+            omni_error!(format!(
+                "failed to change directory to {}: {}",
+                wd_root.light_yellow(),
+                err
+            ));
+            exit(1);
+        }
+
+        let command_loader = command_loader(".");
+        let manifest = CommandManifest::from_commands(
+            &wd_root,
+            &command_loader.commands,
+            args.include_builtins,
+        );
+        let serialized = format!("{}\n", manifest.to_json(args.format));
+
+        match output {
+            Some(output) => {
+                if let Err(err) = std::fs::write(&output, serialized) {
+                    omni_error!(format!(
+                        "failed to write {}: {}",
+                        output.light_yellow(),
+                        err
+                    ));
+                    exit(1);
+                }
+                omni_info!(format!("manifest written to {}", output.light_yellow()));
+            }
+            None => print!("{serialized}"),
+        }
+
+        exit(0);
+    }
+}
+
+#[cfg(test)]
+#[path = "manifest_test.rs"]
+mod tests;
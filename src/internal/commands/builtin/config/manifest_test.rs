@@ -0,0 +1,224 @@
+use super::*;
+
+use crate::internal::commands::fromconfig::ConfigCommand;
+use crate::internal::config::CommandDefinition;
+use crate::internal::testutils::run_with_env;
+
+fn config_command(name: &str, yaml: &str, parameters: Vec<SyntaxOptArg>) -> Command {
+    let mut details: CommandDefinition =
+        serde_yaml::from_str(yaml).expect("failed to parse command definition");
+    if !parameters.is_empty() {
+        details.syntax = Some(CommandSyntax {
+            parameters,
+            ..Default::default()
+        });
+    }
+
+    ConfigCommand::new(name.to_string(), details).into()
+}
+
+/// The commands of the fixture configuration, with a builtin command
+/// that should only be part of the manifest when requested
+fn fixture_commands() -> Vec<Command> {
+    vec![
+        config_command("test", "desc: Run the tests\nrun: make test\n", vec![]),
+        ConfigManifestCommand::new_command(),
+        config_command(
+            "deploy",
+            concat!(
+                "desc: \"Deploy the project\\n\\nDeploys to the requested environment\"\n",
+                "run: ./deploy.sh\n",
+                "aliases: [ship]\n",
+                "category: [Release]\n",
+            ),
+            vec![
+                SyntaxOptArg {
+                    names: vec!["--env".to_string()],
+                    desc: Some("The environment to deploy to".to_string()),
+                    required: true,
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "staging".to_string(),
+                        "production".to_string(),
+                    ]),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["target".to_string()],
+                    default: Some("all".to_string()),
+                    ..Default::default()
+                },
+            ],
+        ),
+    ]
+}
+
+mod command_manifest {
+    use super::*;
+
+    #[test]
+    fn omni_format_snapshot() {
+        run_with_env(&[], || {
+            let manifest = CommandManifest::from_commands("/workdir", &fixture_commands(), false);
+
+            let expected = concat!(
+                "{\n",
+                "  \"version\": 1,\n",
+                "  \"workdir\": \"/workdir\",\n",
+                "  \"config_hash\": \"<HASH>\",\n",
+                "  \"commands\": [\n",
+                "    {\n",
+                "      \"name\": \"deploy\",\n",
+                "      \"aliases\": [\n",
+                "        \"ship\"\n",
+                "      ],\n",
+                "      \"desc\": \"Deploy the project\",\n",
+                "      \"category\": [\n",
+                "        \"Configuration\",\n",
+                "        \"Release\"\n",
+                "      ],\n",
+                "      \"arguments\": [\n",
+                "        {\n",
+                "          \"name\": \"env\",\n",
+                "          \"names\": [\n",
+                "            \"--env\"\n",
+                "          ],\n",
+                "          \"type\": \"enum\",\n",
+                "          \"positional\": false,\n",
+                "          \"required\": true,\n",
+                "          \"values\": [\n",
+                "            \"staging\",\n",
+                "            \"production\"\n",
+                "          ],\n",
+                "          \"desc\": \"The environment to deploy to\"\n",
+                "        },\n",
+                "        {\n",
+                "          \"name\": \"target\",\n",
+                "          \"names\": [\n",
+                "            \"target\"\n",
+                "          ],\n",
+                "          \"type\": \"string\",\n",
+                "          \"positional\": true,\n",
+                "          \"default\": \"all\"\n",
+                "        }\n",
+                "      ],\n",
+                "      \"invocation\": \"omni deploy {args}\"\n",
+                "    },\n",
+                "    {\n",
+                "      \"name\": \"test\",\n",
+                "      \"desc\": \"Run the tests\",\n",
+                "      \"category\": [\n",
+                "        \"Configuration\"\n",
+                "      ],\n",
+                "      \"invocation\": \"omni test {args}\"\n",
+                "    }\n",
+                "  ]\n",
+                "}",
+            );
+
+            assert_eq!(
+                manifest.to_json(ManifestFormat::Omni),
+                expected.replace("<HASH>", &manifest.config_hash)
+            );
+        });
+    }
+
+    #[test]
+    fn vscode_format_snapshot() {
+        run_with_env(&[], || {
+            let manifest = CommandManifest::from_commands("/workdir", &fixture_commands(), false);
+
+            let expected = concat!(
+                "{\n",
+                "  \"version\": \"2.0.0\",\n",
+                "  \"tasks\": [\n",
+                "    {\n",
+                "      \"label\": \"omni: deploy\",\n",
+                "      \"type\": \"shell\",\n",
+                "      \"command\": \"omni\",\n",
+                "      \"args\": [\n",
+                "        \"deploy\"\n",
+                "      ],\n",
+                "      \"detail\": \"Deploy the project\",\n",
+                "      \"options\": {\n",
+                "        \"cwd\": \"${workspaceFolder}\"\n",
+                "      },\n",
+                "      \"problemMatcher\": []\n",
+                "    },\n",
+                "    {\n",
+                "      \"label\": \"omni: test\",\n",
+                "      \"type\": \"shell\",\n",
+                "      \"command\": \"omni\",\n",
+                "      \"args\": [\n",
+                "        \"test\"\n",
+                "      ],\n",
+                "      \"detail\": \"Run the tests\",\n",
+                "      \"options\": {\n",
+                "        \"cwd\": \"${workspaceFolder}\"\n",
+                "      },\n",
+                "      \"problemMatcher\": []\n",
+                "    }\n",
+                "  ]\n",
+                "}",
+            );
+
+            assert_eq!(manifest.to_json(ManifestFormat::VsCode), expected);
+        });
+    }
+
+    #[test]
+    fn deterministic_across_runs() {
+        run_with_env(&[], || {
+            let manifest = CommandManifest::from_commands("/workdir", &fixture_commands(), false);
+
+            let mut reversed = fixture_commands();
+            reversed.reverse();
+            let regenerated = CommandManifest::from_commands("/workdir", &reversed, false);
+
+            assert_eq!(manifest, regenerated);
+            assert_eq!(
+                manifest.to_json(ManifestFormat::Omni),
+                regenerated.to_json(ManifestFormat::Omni)
+            );
+            assert_eq!(manifest.config_hash.len(), 16);
+        });
+    }
+
+    #[test]
+    fn config_hash_changes_with_commands() {
+        run_with_env(&[], || {
+            let manifest = CommandManifest::from_commands("/workdir", &fixture_commands(), false);
+
+            let mut commands = fixture_commands();
+            commands.push(config_command("lint", "run: make lint\n", vec![]));
+            let changed = CommandManifest::from_commands("/workdir", &commands, false);
+
+            assert_ne!(manifest.config_hash, changed.config_hash);
+        });
+    }
+
+    #[test]
+    fn config_hash_does_not_depend_on_workdir() {
+        run_with_env(&[], || {
+            let manifest = CommandManifest::from_commands("/workdir", &fixture_commands(), false);
+            let moved = CommandManifest::from_commands("/elsewhere", &fixture_commands(), false);
+
+            assert_eq!(manifest.config_hash, moved.config_hash);
+        });
+    }
+
+    #[test]
+    fn builtins_only_when_requested() {
+        run_with_env(&[], || {
+            let names = |include_builtins: bool| {
+                CommandManifest::from_commands("/workdir", &fixture_commands(), include_builtins)
+                    .commands
+                    .into_iter()
+                    .map(|command| command.name)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(names(false), vec!["deploy", "test"]);
+            assert_eq!(names(true), vec!["config manifest", "deploy", "test"]);
+        });
+    }
+}
@@ -5,6 +5,12 @@ pub(crate) use bootstrap::ConfigBootstrapCommand;
 pub(crate) mod check;
 pub(crate) use check::ConfigCheckCommand;
 
+pub(crate) mod edit;
+pub(crate) use edit::ConfigEditCommand;
+
+pub(crate) mod manifest;
+pub(crate) use manifest::ConfigManifestCommand;
+
 pub(crate) mod path;
 pub(crate) use path::ConfigPathSwitchCommand;
 
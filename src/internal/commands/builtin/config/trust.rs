@@ -11,14 +11,17 @@ use crate::internal::config::SyntaxOptArgType;
 use crate::internal::user_interface::StringColor;
 use crate::internal::workdir;
 use crate::internal::workdir::add_trust;
+use crate::internal::workdir::display_workdir_id;
 use crate::internal::workdir::is_trusted;
 use crate::internal::workdir::remove_trust;
+use crate::internal::workdir::workdir_short_ids;
 use crate::omni_error;
 use crate::omni_info;
 
 #[derive(Debug, Clone)]
 struct ConfigTrustCommandArgs {
     check_status: bool,
+    id: Option<String>,
     workdir: Option<String>,
 }
 
@@ -28,6 +31,12 @@ impl From<BTreeMap<String, ParseArgsValue>> for ConfigTrustCommandArgs {
             args.get("check"),
             Some(ParseArgsValue::SingleBoolean(Some(true)))
         );
+        let id = match args.get("id") {
+            Some(ParseArgsValue::SingleString(Some(id))) if !id.trim().is_empty() => {
+                Some(id.trim().to_string())
+            }
+            _ => None,
+        };
         let workdir = match args.get("workdir") {
             Some(ParseArgsValue::SingleString(Some(workdir))) => {
                 let workdir = workdir.trim();
@@ -42,6 +51,7 @@ impl From<BTreeMap<String, ParseArgsValue>> for ConfigTrustCommandArgs {
 
         Self {
             check_status,
+            id,
             workdir,
         }
     }
@@ -62,6 +72,82 @@ impl ConfigTrustCommand {
     fn is_trust(&self) -> bool {
         self.subcommand() == "config trust"
     }
+
+    /// Trusts or untrusts the work directory with the given id, in its
+    /// full or short form, which allows to manage the trust of work
+    /// directories that are not on disk anymore; the trust of the
+    /// organizations is not considered, as it requires the repository
+    fn exec_id(&self, input: &str, check_status: bool) {
+        let short_ids = workdir_short_ids();
+        let wd_id = match short_ids.resolve(input) {
+            Ok(wd_id) => wd_id,
+            Err(err) => {
+                omni_error!(err.to_string());
+                exit(2);
+            }
+        };
+        let display_id = short_ids.display(&wd_id);
+
+        // The trust of a package is stored for its repository id
+        let trust_id = wd_id.strip_prefix("package#").unwrap_or(&wd_id);
+
+        let mut cache = WorkdirsCache::get();
+        let is_trusted = cache.has_trusted(trust_id);
+
+        if check_status {
+            if is_trusted {
+                omni_info!(
+                    format!("work directory is {}", "trusted".light_green()),
+                    display_id
+                );
+                exit(0);
+            } else {
+                omni_info!(
+                    format!("work directory is {}", "not trusted".light_red()),
+                    display_id
+                );
+                exit(2);
+            }
+        } else if self.is_trust() {
+            if is_trusted {
+                omni_info!(
+                    format!("work directory is already {}", "trusted".light_green()),
+                    display_id
+                );
+                exit(0);
+            }
+
+            if let Err(err) = cache.add_trusted(trust_id) {
+                omni_error!(format!("Unable to update cache: {:?}", err.to_string()));
+                exit(1);
+            }
+
+            omni_info!(
+                format!("work directory is now {}", "trusted".light_green()),
+                display_id
+            );
+            exit(0);
+        } else {
+            if !is_trusted {
+                omni_info!(
+                    format!("work directory is already {}", "untrusted".light_red()),
+                    display_id
+                );
+                exit(0);
+            }
+
+            if let Err(err) = cache.remove_trusted(trust_id) {
+                omni_error!(format!("Unable to update cache: {:?}", err.to_string()));
+                exit(1);
+            }
+
+            omni_info!(
+                format!("work directory is now {}", "untrusted".light_red()),
+                display_id
+            );
+            exit(0);
+        }
+    }
 }
 
 impl BuiltinCommand for ConfigTrustCommand {
@@ -105,6 +191,15 @@ impl BuiltinCommand for ConfigTrustCommand {
                     arg_type: SyntaxOptArgType::Flag,
                     ..Default::default()
                 },
+                SyntaxOptArg {
+                    names: vec!["--id".to_string()],
+                    desc: Some(
+                        "The id of the work directory to trust or untrust, in its full or short form"
+                            .to_string(),
+                    ),
+                    conflicts_with: vec!["workdir".to_string()],
+                    ..Default::default()
+                },
                 SyntaxOptArg {
                     names: vec!["workdir".to_string()],
                     desc: Some(
@@ -134,10 +229,15 @@ impl BuiltinCommand for ConfigTrustCommand {
                 .expect("should have args to parse"),
         );
 
+        if let Some(id) = &args.id {
+            self.exec_id(id, args.check_status);
+            return;
+        }
+
         let path_str = args.workdir.as_deref().unwrap_or(".");
         let wd = workdir(path_str);
         let wd_id = match wd.id() {
-            Some(id) => id,
+            Some(id) => display_workdir_id(&id),
             None => {
                 omni_error!(format!(
                     "path {} is not a work directory",
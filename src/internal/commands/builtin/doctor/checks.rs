@@ -0,0 +1,262 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::internal::cache::CacheManager;
+use crate::internal::cache::UpEnvironmentsCache;
+use crate::internal::commands::builtin::doctor::registry::DoctorCheck;
+use crate::internal::commands::builtin::doctor::registry::DoctorFinding;
+use crate::internal::commands::builtin::doctor::registry::DoctorRegistry;
+use crate::internal::config::config;
+use crate::internal::config::up::utils::get_config_mod_times;
+use crate::internal::config::up::utils::reshim;
+use crate::internal::config::up::utils::VoidProgressHandler;
+use crate::internal::dynenv::current_path_conflicts;
+use crate::internal::env::current_exe;
+use crate::internal::env::shell_integration_is_loaded;
+use crate::internal::env::shims_dir;
+use crate::internal::workdir;
+use crate::internal::workdir::display_workdir_id;
+use crate::internal::workdir::is_trusted;
+
+impl DoctorRegistry {
+    /// Returns a registry with the checks provided by omni
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(Arc::new(ShellIntegrationCheck));
+        registry.register(Arc::new(CacheCheck));
+        registry.register(Arc::new(EnvironmentCheck));
+        registry.register(Arc::new(ShimsCheck));
+        registry.register(Arc::new(PathConflictsCheck));
+        registry.register(Arc::new(TrustCheck));
+
+        registry
+    }
+}
+
+/// Checks that the shell integration is loaded, since the dynamic
+/// environment and the `cd` command depend on it
+struct ShellIntegrationCheck;
+
+impl DoctorCheck for ShellIntegrationCheck {
+    fn name(&self) -> &'static str {
+        "shell-integration"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shell integration"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        if shell_integration_is_loaded() {
+            return vec![];
+        }
+
+        let shell = std::env::var("SHELL")
+            .ok()
+            .and_then(|shell| {
+                Path::new(&shell)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or("bash".to_string());
+
+        let remediation = match shell.as_str() {
+            "fish" => "omni hook init fish | source".to_string(),
+            shell => format!("eval \"$(omni hook init {shell})\""),
+        };
+
+        vec![DoctorFinding::warning("shell integration is not loaded").with_remediation(remediation)]
+    }
+}
+
+/// Checks that the cache database is readable and not corrupted
+struct CacheCheck;
+
+impl DoctorCheck for CacheCheck {
+    fn name(&self) -> &'static str {
+        "cache"
+    }
+
+    fn description(&self) -> &'static str {
+        "Cache consistency"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        let result: Result<String, _> =
+            CacheManager::get().query_row("PRAGMA integrity_check", &[], |row| row.get(0));
+
+        match result {
+            Ok(result) if result == "ok" => vec![],
+            Ok(result) => {
+                vec![
+                    DoctorFinding::error(format!("cache database is corrupted: {result}"))
+                        .with_remediation("remove the cache.db file from the cache directory"),
+                ]
+            }
+            Err(err) => vec![DoctorFinding::error(format!(
+                "failed to check the cache database: {err}"
+            ))],
+        }
+    }
+}
+
+/// Checks that the environment of the current work directory is up to
+/// date with its `up` configuration
+struct EnvironmentCheck;
+
+impl DoctorCheck for EnvironmentCheck {
+    fn name(&self) -> &'static str {
+        "environment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Work directory environment"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        let workdir_id = match workdir(".").id() {
+            Some(workdir_id) => workdir_id,
+            None => return vec![],
+        };
+
+        let config = config(".");
+        match &config.up {
+            Some(up_config) if up_config.has_steps() => {}
+            _ => return vec![],
+        }
+
+        let cache = UpEnvironmentsCache::get();
+        if cache.get_env(&workdir_id).is_none() {
+            return vec![
+                DoctorFinding::info("the work directory has not been set up")
+                    .with_remediation("omni up"),
+            ];
+        }
+
+        if !cache.check_config_hash(&workdir_id, &config.up_hash(), &config.up_hash_legacy()) {
+            return vec![DoctorFinding::warning(
+                "the up configuration changed since the environment was set up",
+            )
+            .with_remediation("omni up")];
+        }
+
+        vec![]
+    }
+}
+
+/// Checks that the shims point to the current omni binary
+struct ShimsCheck;
+
+impl ShimsCheck {
+    fn outdated_shims(&self) -> Vec<String> {
+        let entries = match std::fs::read_dir(shims_dir()) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+
+        let mut outdated = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| match std::fs::read_link(entry.path()) {
+                Ok(target) => target != current_exe(),
+                Err(_) => true,
+            })
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        outdated.sort();
+        outdated
+    }
+}
+
+impl DoctorCheck for ShimsCheck {
+    fn name(&self) -> &'static str {
+        "shims"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shims"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        let outdated = self.outdated_shims();
+        if outdated.is_empty() {
+            return vec![];
+        }
+
+        vec![DoctorFinding::warning(format!(
+            "{} shim{} not pointing to the current omni binary: {}",
+            outdated.len(),
+            if outdated.len() > 1 { "s are" } else { " is" },
+            outdated.join(", "),
+        ))
+        .with_remediation("omni config reshim")]
+    }
+
+    fn can_fix(&self) -> bool {
+        true
+    }
+
+    fn fix(&self) -> Result<(), String> {
+        reshim(&VoidProgressHandler::new())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Checks that the binaries of the tools of the environment of the
+/// current work directory are not shadowed by other binaries in the PATH
+struct PathConflictsCheck;
+
+impl DoctorCheck for PathConflictsCheck {
+    fn name(&self) -> &'static str {
+        "path-conflicts"
+    }
+
+    fn description(&self) -> &'static str {
+        "PATH conflicts"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        current_path_conflicts(".")
+            .into_iter()
+            .map(|conflict| {
+                DoctorFinding::warning(format!("{}; {}", conflict.message(), conflict.hint()))
+                    .with_remediation(
+                        "load the shell integration after anything prepending to PATH",
+                    )
+            })
+            .collect()
+    }
+}
+
+/// Checks whether the current work directory, when it provides a
+/// configuration, is trusted
+struct TrustCheck;
+
+impl DoctorCheck for TrustCheck {
+    fn name(&self) -> &'static str {
+        "trust"
+    }
+
+    fn description(&self) -> &'static str {
+        "Work directory trust"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        let wd = workdir(".");
+        let workdir_id = match wd.id() {
+            Some(workdir_id) => workdir_id,
+            None => return vec![],
+        };
+
+        if get_config_mod_times(".").is_empty() || is_trusted(".") {
+            return vec![];
+        }
+
+        vec![DoctorFinding::info(format!(
+            "the work directory {} is not trusted, its configuration will not be loaded without confirmation",
+            display_workdir_id(&workdir_id)
+        ))
+        .with_remediation("omni config trust")]
+    }
+}
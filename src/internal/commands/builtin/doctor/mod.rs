@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::user_interface::StringColor;
+use crate::omni_error;
+
+mod checks;
+
+pub(crate) mod registry;
+use registry::exit_code;
+use registry::fix_checks;
+use registry::run_checks;
+use registry::DoctorCheckResult;
+use registry::DoctorRegistry;
+use registry::DoctorSeverity;
+
+/// The time a single check, or its fix, is allowed to take before being
+/// reported as an error
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+struct DoctorCommandArgs {
+    only: Vec<String>,
+    skip: Vec<String>,
+    fix: bool,
+    output: DoctorCommandOutput,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for DoctorCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let only = match args.get("only") {
+            Some(ParseArgsValue::ManyString(only)) => only.iter().flat_map(|v| v.clone()).collect(),
+            _ => vec![],
+        };
+
+        let skip = match args.get("skip") {
+            Some(ParseArgsValue::ManyString(skip)) => skip.iter().flat_map(|v| v.clone()).collect(),
+            _ => vec![],
+        };
+
+        let fix = matches!(
+            args.get("fix"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        let output = match args.get("output") {
+            Some(ParseArgsValue::SingleString(Some(value))) => match value.as_str() {
+                "json" => DoctorCommandOutput::Json,
+                "plain" => DoctorCommandOutput::Plain,
+                _ => unreachable!("unknown value for output"),
+            },
+            _ => DoctorCommandOutput::Plain,
+        };
+
+        Self {
+            only,
+            skip,
+            fix,
+            output,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum DoctorCommandOutput {
+    Plain,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct DoctorReport<'a> {
+    checks: &'a [DoctorCheckResult],
+    errors: usize,
+    warnings: usize,
+}
+
+impl<'a> DoctorReport<'a> {
+    fn new(checks: &'a [DoctorCheckResult]) -> Self {
+        let count = |severity: DoctorSeverity| {
+            checks
+                .iter()
+                .flat_map(|check| check.findings.iter())
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+
+        Self {
+            checks,
+            errors: count(DoctorSeverity::Error),
+            warnings: count(DoctorSeverity::Warning),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorCommand {}
+
+impl DoctorCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for DoctorCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["doctor".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Run health checks on the omni installation and the current work directory\n",
+                "\n",
+                "This runs the shell integration, cache, environment, shims and trust ",
+                "checks, and reports their findings along with the way to address them. ",
+                "The command exits with a non-zero status if any of the checks reported ",
+                "an error.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--only".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Only run the given checks; can be used multiple times ",
+                            "\x1B[90m(shell-integration, cache, environment, shims, trust)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                    value_delimiter: Some(','),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--skip".to_string()],
+                    desc: Some("Skip the given checks; can be used multiple times".to_string()),
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                    value_delimiter: Some(','),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--fix".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Automatically fix the findings of the checks supporting it, ",
+                            "then run those checks again",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["-o".to_string(), "--output".to_string()],
+                    desc: Some("Output format".to_string()),
+                    arg_type: SyntaxOptArgType::Enum(vec!["json".to_string(), "plain".to_string()]),
+                    default: Some("plain".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = DoctorCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        let checks = match DoctorRegistry::builtin().select(&args.only, &args.skip) {
+            Ok(checks) => checks,
+            Err(err) => {
+                omni_error!(err);
+                exit(1);
+            }
+        };
+
+        let mut results = run_checks(&checks, CHECK_TIMEOUT);
+        if args.fix {
+            results = fix_checks(&checks, results, CHECK_TIMEOUT);
+        }
+
+        let report = DoctorReport::new(&results);
+        match args.output {
+            DoctorCommandOutput::Plain => self.print_report(&report),
+            DoctorCommandOutput::Json => match serde_json::to_string_pretty(&report) {
+                Ok(json) => println!("{json}"),
+                Err(e) => {
+                    omni_error!(format!("Error while serializing the report to JSON: {}", e));
+                    exit(1);
+                }
+            },
+        }
+
+        exit(exit_code(&results));
+    }
+}
+
+impl DoctorCommand {
+    fn print_report(&self, report: &DoctorReport) {
+        for result in report.checks {
+            let icon = match result.severity() {
+                None | Some(DoctorSeverity::Info) => "✔".green(),
+                Some(DoctorSeverity::Warning) => "!".yellow(),
+                Some(DoctorSeverity::Error) => "✖".red(),
+            };
+
+            let mut header = format!("{} {}", icon, result.description.bold());
+            if result.fixed {
+                header.push_str(&format!(" {}", "(fixed)".light_black()));
+            }
+            println!("{header}");
+
+            for finding in &result.findings {
+                let severity = match finding.severity {
+                    DoctorSeverity::Info => finding.severity.as_str().light_blue(),
+                    DoctorSeverity::Warning => finding.severity.as_str().light_yellow(),
+                    DoctorSeverity::Error => finding.severity.as_str().light_red(),
+                };
+                println!("  {}: {}", severity, finding.message);
+
+                if let Some(remediation) = &finding.remediation {
+                    println!("    {} {}", "→".light_black(), remediation.light_cyan());
+                }
+            }
+        }
+
+        let fixable = report
+            .checks
+            .iter()
+            .filter(|result| result.needs_fix())
+            .count();
+
+        println!();
+        println!(
+            "{} check{} run, {} error{}, {} warning{}",
+            report.checks.len(),
+            if report.checks.len() == 1 { "" } else { "s" },
+            report.errors,
+            if report.errors == 1 { "" } else { "s" },
+            report.warnings,
+            if report.warnings == 1 { "" } else { "s" },
+        );
+
+        if fixable > 0 {
+            println!(
+                "{}",
+                format!(
+                    "{} check{} can be fixed automatically with omni doctor --fix",
+                    fixable,
+                    if fixable == 1 { "" } else { "s" },
+                )
+                .light_black()
+            );
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// The severity of a finding reported by a doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoctorSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl DoctorSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A finding reported by a doctor check, along with the way to address it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DoctorFinding {
+    pub severity: DoctorSeverity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl DoctorFinding {
+    pub fn new(severity: DoctorSeverity, message: impl ToString) -> Self {
+        Self {
+            severity,
+            message: message.to_string(),
+            remediation: None,
+        }
+    }
+
+    pub fn info(message: impl ToString) -> Self {
+        Self::new(DoctorSeverity::Info, message)
+    }
+
+    pub fn warning(message: impl ToString) -> Self {
+        Self::new(DoctorSeverity::Warning, message)
+    }
+
+    pub fn error(message: impl ToString) -> Self {
+        Self::new(DoctorSeverity::Error, message)
+    }
+
+    pub fn with_remediation(mut self, remediation: impl ToString) -> Self {
+        self.remediation = Some(remediation.to_string());
+        self
+    }
+}
+
+/// A health check run by `omni doctor`
+pub trait DoctorCheck: Send + Sync {
+    /// The name of the check, used to select it with `--only` or `--skip`
+    fn name(&self) -> &'static str;
+
+    /// A short description of what the check verifies
+    fn description(&self) -> &'static str;
+
+    /// Runs the check and returns its findings; no findings means that
+    /// everything is healthy
+    fn run(&self) -> Vec<DoctorFinding>;
+
+    /// Whether the check can automatically fix its findings
+    fn can_fix(&self) -> bool {
+        false
+    }
+
+    /// Fixes the findings of the check
+    fn fix(&self) -> Result<(), String> {
+        Err("this check cannot be fixed automatically".to_string())
+    }
+}
+
+/// The checks known to `omni doctor`, in the order they are run
+#[derive(Clone, Default)]
+pub struct DoctorRegistry {
+    checks: Vec<Arc<dyn DoctorCheck>>,
+}
+
+impl DoctorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, check: Arc<dyn DoctorCheck>) {
+        self.checks.push(check);
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.checks.iter().map(|check| check.name()).collect()
+    }
+
+    /// Returns the checks to run, in the order they were registered;
+    /// when `only` is not empty, only those checks are selected, and the
+    /// checks in `skip` are never selected
+    pub fn select(
+        &self,
+        only: &[String],
+        skip: &[String],
+    ) -> Result<Vec<Arc<dyn DoctorCheck>>, String> {
+        let names = self.names();
+        if let Some(unknown) = only
+            .iter()
+            .chain(skip.iter())
+            .find(|name| !names.contains(&name.as_str()))
+        {
+            return Err(format!(
+                "unknown check '{}', available checks: {}",
+                unknown,
+                names.join(", ")
+            ));
+        }
+
+        Ok(self
+            .checks
+            .iter()
+            .filter(|check| only.is_empty() || only.iter().any(|name| name == check.name()))
+            .filter(|check| !skip.iter().any(|name| name == check.name()))
+            .cloned()
+            .collect())
+    }
+}
+
+/// The result of running one of the doctor checks
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheckResult {
+    pub name: String,
+    pub description: String,
+    pub findings: Vec<DoctorFinding>,
+    pub fixable: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub fixed: bool,
+}
+
+impl DoctorCheckResult {
+    /// The highest severity of the findings, if any
+    pub fn severity(&self) -> Option<DoctorSeverity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+
+    /// Whether the check can fix findings that need attention
+    pub fn needs_fix(&self) -> bool {
+        self.fixable && self.severity() > Some(DoctorSeverity::Info)
+    }
+}
+
+/// Runs the given function in its own thread, returning `None` if it did
+/// not complete within the timeout; the thread is left behind in that
+/// case, so that a hanging function does not block the caller
+fn run_with_timeout<T, F>(timeout: Duration, f: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // The receiver is gone if we timed out, nothing to do then
+        let _ = sender.send(f());
+    });
+
+    receiver.recv_timeout(timeout).ok()
+}
+
+fn run_check(check: &Arc<dyn DoctorCheck>, timeout: Duration) -> DoctorCheckResult {
+    let findings = {
+        let check = check.clone();
+        run_with_timeout(timeout, move || check.run())
+    };
+
+    let findings = findings.unwrap_or_else(|| {
+        vec![DoctorFinding::error(format!(
+            "check did not complete within {}s",
+            timeout.as_secs_f64()
+        ))]
+    });
+
+    DoctorCheckResult {
+        name: check.name().to_string(),
+        description: check.description().to_string(),
+        findings,
+        fixable: check.can_fix(),
+        fixed: false,
+    }
+}
+
+/// Runs the checks in order, each with its own timeout so that a hanging
+/// check is reported as an error without blocking the other checks
+pub fn run_checks(checks: &[Arc<dyn DoctorCheck>], timeout: Duration) -> Vec<DoctorCheckResult> {
+    checks
+        .iter()
+        .map(|check| run_check(check, timeout))
+        .collect()
+}
+
+/// Fixes the checks that reported findings they can fix, and runs those
+/// checks again to report their updated findings; the results of the
+/// other checks are returned as they were
+pub fn fix_checks(
+    checks: &[Arc<dyn DoctorCheck>],
+    results: Vec<DoctorCheckResult>,
+    timeout: Duration,
+) -> Vec<DoctorCheckResult> {
+    results
+        .into_iter()
+        .map(|result| {
+            if !result.needs_fix() {
+                return result;
+            }
+
+            let check = match checks.iter().find(|check| check.name() == result.name) {
+                Some(check) => check,
+                None => return result,
+            };
+
+            let fixed = {
+                let check = check.clone();
+                run_with_timeout(timeout, move || check.fix())
+            };
+
+            match fixed {
+                Some(Ok(())) => {
+                    let mut result = run_check(check, timeout);
+                    result.fixed = true;
+                    result
+                }
+                Some(Err(err)) => {
+                    let mut result = result;
+                    result
+                        .findings
+                        .push(DoctorFinding::error(format!("failed to fix: {err}")));
+                    result
+                }
+                None => {
+                    let mut result = result;
+                    result.findings.push(DoctorFinding::error(format!(
+                        "fix did not complete within {}s",
+                        timeout.as_secs_f64()
+                    )));
+                    result
+                }
+            }
+        })
+        .collect()
+}
+
+/// The exit code for the given results: non-zero if any check reported
+/// an error-severity finding
+pub fn exit_code(results: &[DoctorCheckResult]) -> i32 {
+    if results
+        .iter()
+        .any(|result| result.severity() == Some(DoctorSeverity::Error))
+    {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+#[path = "registry_test.rs"]
+mod tests;
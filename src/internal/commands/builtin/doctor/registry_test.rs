@@ -0,0 +1,316 @@
+use super::*;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A check returning predefined findings, recording when it is run and
+/// fixed; once fixed, it reports no findings anymore
+struct FakeCheck {
+    name: &'static str,
+    findings: Vec<DoctorFinding>,
+    delay: Option<Duration>,
+    can_fix: bool,
+    fix_result: Result<(), String>,
+    runs: Arc<Mutex<Vec<&'static str>>>,
+    fixes: Arc<AtomicUsize>,
+}
+
+impl FakeCheck {
+    fn new(name: &'static str, runs: &Arc<Mutex<Vec<&'static str>>>) -> Self {
+        Self {
+            name,
+            findings: vec![],
+            delay: None,
+            can_fix: false,
+            fix_result: Ok(()),
+            runs: runs.clone(),
+            fixes: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn with_findings(mut self, findings: Vec<DoctorFinding>) -> Self {
+        self.findings = findings;
+        self
+    }
+
+    fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn fixable(mut self, fix_result: Result<(), String>) -> Self {
+        self.can_fix = true;
+        self.fix_result = fix_result;
+        self
+    }
+}
+
+impl DoctorCheck for FakeCheck {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        "Fake check"
+    }
+
+    fn run(&self) -> Vec<DoctorFinding> {
+        self.runs.lock().unwrap().push(self.name);
+        if let Some(delay) = self.delay {
+            std::thread::sleep(delay);
+        }
+
+        if self.fixes.load(Ordering::SeqCst) > 0 && self.fix_result.is_ok() {
+            return vec![];
+        }
+
+        self.findings.clone()
+    }
+
+    fn can_fix(&self) -> bool {
+        self.can_fix
+    }
+
+    fn fix(&self) -> Result<(), String> {
+        self.fixes.fetch_add(1, Ordering::SeqCst);
+        self.fix_result.clone()
+    }
+}
+
+fn new_runs() -> Arc<Mutex<Vec<&'static str>>> {
+    Arc::new(Mutex::new(vec![]))
+}
+
+fn registry(checks: Vec<FakeCheck>) -> DoctorRegistry {
+    let mut registry = DoctorRegistry::new();
+    for check in checks {
+        registry.register(Arc::new(check));
+    }
+    registry
+}
+
+fn result(name: &str, findings: Vec<DoctorFinding>) -> DoctorCheckResult {
+    DoctorCheckResult {
+        name: name.to_string(),
+        description: "Fake check".to_string(),
+        findings,
+        fixable: false,
+        fixed: false,
+    }
+}
+
+mod select {
+    use super::*;
+
+    #[test]
+    fn runs_checks_in_registration_order() {
+        let runs = new_runs();
+        let registry = registry(vec![
+            FakeCheck::new("third", &runs),
+            FakeCheck::new("first", &runs),
+            FakeCheck::new("second", &runs),
+        ]);
+
+        let checks = registry.select(&[], &[]).expect("should select checks");
+        let results = run_checks(&checks, TIMEOUT);
+
+        assert_eq!(*runs.lock().unwrap(), vec!["third", "first", "second"]);
+        assert_eq!(
+            results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            vec!["third", "first", "second"]
+        );
+    }
+
+    #[test]
+    fn only_keeps_registration_order() {
+        let runs = new_runs();
+        let registry = registry(vec![
+            FakeCheck::new("a", &runs),
+            FakeCheck::new("b", &runs),
+            FakeCheck::new("c", &runs),
+        ]);
+
+        let checks = registry
+            .select(&["c".to_string(), "a".to_string()], &[])
+            .expect("should select checks");
+
+        assert_eq!(
+            checks.iter().map(|c| c.name()).collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+    }
+
+    #[test]
+    fn skip_removes_checks() {
+        let runs = new_runs();
+        let registry = registry(vec![
+            FakeCheck::new("a", &runs),
+            FakeCheck::new("b", &runs),
+            FakeCheck::new("c", &runs),
+        ]);
+
+        let checks = registry
+            .select(&[], &["b".to_string()])
+            .expect("should select checks");
+
+        assert_eq!(
+            checks.iter().map(|c| c.name()).collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+    }
+
+    #[test]
+    fn unknown_check_is_an_error() {
+        let runs = new_runs();
+        let registry = registry(vec![FakeCheck::new("a", &runs), FakeCheck::new("b", &runs)]);
+
+        let err = match registry.select(&[], &["nope".to_string()]) {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => err,
+        };
+
+        assert_eq!(err, "unknown check 'nope', available checks: a, b");
+    }
+}
+
+mod timeout {
+    use super::*;
+
+    #[test]
+    fn slow_check_does_not_block_the_others() {
+        let runs = new_runs();
+        let registry = registry(vec![
+            FakeCheck::new("slow", &runs)
+                .with_findings(vec![DoctorFinding::info("never seen")])
+                .with_delay(Duration::from_secs(2)),
+            FakeCheck::new("fast", &runs).with_findings(vec![DoctorFinding::warning("seen")]),
+        ]);
+
+        let checks = registry.select(&[], &[]).expect("should select checks");
+        let start = std::time::Instant::now();
+        let results = run_checks(&checks, Duration::from_millis(100));
+
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0].name, "slow");
+        assert_eq!(results[0].severity(), Some(DoctorSeverity::Error));
+        assert_eq!(
+            results[0].findings[0].message,
+            "check did not complete within 0.1s"
+        );
+
+        assert_eq!(results[1].name, "fast");
+        assert_eq!(results[1].findings, vec![DoctorFinding::warning("seen")]);
+    }
+}
+
+mod exit_code {
+    use super::*;
+
+    #[test]
+    fn zero_without_findings() {
+        assert_eq!(exit_code(&[result("a", vec![])]), 0);
+    }
+
+    #[test]
+    fn zero_with_warnings_and_infos() {
+        let results = vec![
+            result("a", vec![DoctorFinding::info("info")]),
+            result("b", vec![DoctorFinding::warning("warning")]),
+        ];
+        assert_eq!(exit_code(&results), 0);
+    }
+
+    #[test]
+    fn non_zero_with_any_error() {
+        let results = vec![
+            result("a", vec![DoctorFinding::warning("warning")]),
+            result(
+                "b",
+                vec![DoctorFinding::info("info"), DoctorFinding::error("error")],
+            ),
+        ];
+        assert_eq!(exit_code(&results), 1);
+    }
+}
+
+mod fix {
+    use super::*;
+
+    #[test]
+    fn only_fixes_fixable_checks_needing_it() {
+        let runs = new_runs();
+        let fixable_warning = FakeCheck::new("fixable-warning", &runs)
+            .with_findings(vec![DoctorFinding::warning("warning")])
+            .fixable(Ok(()));
+        let fixable_info = FakeCheck::new("fixable-info", &runs)
+            .with_findings(vec![DoctorFinding::info("info")])
+            .fixable(Ok(()));
+        let fixable_healthy = FakeCheck::new("fixable-healthy", &runs).fixable(Ok(()));
+        let not_fixable =
+            FakeCheck::new("not-fixable", &runs).with_findings(vec![DoctorFinding::error("error")]);
+
+        let fixes = [
+            fixable_warning.fixes.clone(),
+            fixable_info.fixes.clone(),
+            fixable_healthy.fixes.clone(),
+            not_fixable.fixes.clone(),
+        ];
+
+        let registry = registry(vec![
+            fixable_warning,
+            fixable_info,
+            fixable_healthy,
+            not_fixable,
+        ]);
+        let checks = registry.select(&[], &[]).expect("should select checks");
+
+        let results = run_checks(&checks, TIMEOUT);
+        runs.lock().unwrap().clear();
+        let results = fix_checks(&checks, results, TIMEOUT);
+
+        assert_eq!(
+            fixes
+                .iter()
+                .map(|f| f.load(Ordering::SeqCst))
+                .collect::<Vec<_>>(),
+            vec![1, 0, 0, 0]
+        );
+
+        // Only the fixed check is run again
+        assert_eq!(*runs.lock().unwrap(), vec!["fixable-warning"]);
+
+        assert!(results[0].fixed);
+        assert!(results[0].findings.is_empty());
+        assert!(!results[1].fixed);
+        assert_eq!(results[1].findings, vec![DoctorFinding::info("info")]);
+        assert!(!results[2].fixed);
+        assert!(!results[3].fixed);
+        assert_eq!(results[3].findings, vec![DoctorFinding::error("error")]);
+    }
+
+    #[test]
+    fn failed_fix_is_reported() {
+        let runs = new_runs();
+        let registry = registry(vec![FakeCheck::new("broken", &runs)
+            .with_findings(vec![DoctorFinding::warning("warning")])
+            .fixable(Err("nope".to_string()))]);
+        let checks = registry.select(&[], &[]).expect("should select checks");
+
+        let results = fix_checks(&checks, run_checks(&checks, TIMEOUT), TIMEOUT);
+
+        assert!(!results[0].fixed);
+        assert_eq!(
+            results[0].findings,
+            vec![
+                DoctorFinding::warning("warning"),
+                DoctorFinding::error("failed to fix: nope"),
+            ]
+        );
+        assert_eq!(exit_code(&results), 1);
+    }
+}
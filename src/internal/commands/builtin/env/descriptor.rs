@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::up_environments::UpEnvVar;
+use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::EnvOperationEnum;
+use crate::internal::config::up::UpConfig;
+use crate::internal::config::ConfigScope;
+use crate::internal::config::ConfigSource;
+use crate::internal::config::ConfigValue;
+
+/// The version of the format of the environment descriptor; this
+/// should be increased when a change is not backwards-compatible
+const ENV_DESCRIPTOR_FORMAT: u32 = 1;
+
+/// A portable description of the dynamic environment of a work directory.
+///
+/// Contrary to the `UpEnvironment` it is built from, the descriptor does
+/// not contain any machine-specific information (installation paths,
+/// data paths, etc.), so that it can be used to provision the same tools
+/// and versions on a different machine.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EnvDescriptor {
+    pub format: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<EnvDescriptorTool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<EnvDescriptorEnvVar>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EnvDescriptorTool {
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub dirs: BTreeSet<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EnvDescriptorEnvVar {
+    pub name: String,
+    #[serde(
+        default,
+        skip_serializing_if = "EnvOperationEnum::is_default",
+        serialize_with = "serialize_env_operation"
+    )]
+    pub operation: EnvOperationEnum,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// The command providing the value when the environment is applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
+}
+
+fn serialize_env_operation<S>(
+    operation: &EnvOperationEnum,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&operation.to_string())
+}
+
+impl EnvDescriptor {
+    /// Build a descriptor from the given environment; the values of the
+    /// environment variables are redacted unless `include_env_values` is set.
+    pub fn from_environment(environment: &UpEnvironment, include_env_values: bool) -> Self {
+        // Group the directories per tool, backend and version, so that
+        // each tool version only appears once in the descriptor
+        let mut tools: BTreeMap<(String, Option<String>, String), BTreeSet<String>> =
+            BTreeMap::new();
+        for version in environment.versions.iter() {
+            let backend = match version.backend.as_str() {
+                "" | "default" => None,
+                backend => Some(backend.to_string()),
+            };
+
+            tools
+                .entry((version.tool.clone(), backend, version.version.clone()))
+                .or_default()
+                .insert(version.dir.clone());
+        }
+
+        let tools = tools
+            .into_iter()
+            .map(|((tool, backend, version), mut dirs)| {
+                // The root of the work directory is the default
+                if dirs.len() == 1 && dirs.contains("") {
+                    dirs.clear();
+                }
+
+                EnvDescriptorTool {
+                    tool,
+                    backend,
+                    version,
+                    dirs,
+                }
+            })
+            .collect();
+
+        let env = environment
+            .env_vars
+            .iter()
+            .map(|env_var| {
+                let redacted = !include_env_values && env_var.value.is_some();
+                EnvDescriptorEnvVar {
+                    name: env_var.name.clone(),
+                    operation: env_var.operation,
+                    value: if redacted {
+                        None
+                    } else {
+                        env_var.value.clone()
+                    },
+                    command: if redacted {
+                        None
+                    } else {
+                        env_var.command.clone()
+                    },
+                    redacted,
+                }
+            })
+            .collect();
+
+        Self {
+            format: ENV_DESCRIPTOR_FORMAT,
+            tools,
+            env,
+        }
+    }
+
+    pub fn from_str(value: &str) -> Result<Self, String> {
+        let descriptor: Self = serde_yaml::from_str(value).map_err(|err| err.to_string())?;
+
+        if descriptor.format > ENV_DESCRIPTOR_FORMAT {
+            return Err(format!(
+                "unsupported descriptor format {} (expected at most {})",
+                descriptor.format, ENV_DESCRIPTOR_FORMAT
+            ));
+        }
+
+        Ok(descriptor)
+    }
+
+    /// Build the up configuration that provisions the tools of the
+    /// descriptor, going through the same parser as the `up` section
+    /// of the work directory configuration
+    pub fn to_up_config(&self, error_handler: &ConfigErrorHandler) -> UpConfig {
+        let steps = self
+            .tools
+            .iter()
+            .map(|tool| {
+                let mut params = serde_yaml::Mapping::new();
+                params.insert("version".into(), tool.version.clone().into());
+                if let Some(backend) = &tool.backend {
+                    params.insert("backend".into(), backend.clone().into());
+                }
+                if !tool.dirs.is_empty() {
+                    params.insert(
+                        "dir".into(),
+                        tool.dirs
+                            .iter()
+                            .cloned()
+                            .map(serde_yaml::Value::from)
+                            .collect::<Vec<_>>()
+                            .into(),
+                    );
+                }
+
+                let mut step = serde_yaml::Mapping::new();
+                step.insert(tool.tool.clone().into(), params.into());
+                serde_yaml::Value::Mapping(step)
+            })
+            .collect::<Vec<_>>();
+
+        let config_value = ConfigValue::from_value(
+            ConfigSource::Null,
+            ConfigScope::Null,
+            serde_yaml::Value::Sequence(steps),
+        );
+
+        UpConfig::from_config_value(Some(config_value), error_handler).unwrap_or(UpConfig {
+            steps: vec![],
+            errors: vec![],
+            inferred: vec![],
+        })
+    }
+
+    /// The environment variables that can be applied, i.e. that were
+    /// not redacted when exporting the descriptor
+    pub fn env_vars(&self) -> Vec<UpEnvVar> {
+        self.env
+            .iter()
+            .filter(|env_var| !env_var.redacted)
+            .map(|env_var| UpEnvVar {
+                name: env_var.name.clone(),
+                value: env_var.value.clone(),
+                operation: env_var.operation,
+                command: env_var.command.clone(),
+            })
+            .collect()
+    }
+
+    /// The names of the environment variables that were redacted
+    pub fn redacted_env_vars(&self) -> Vec<String> {
+        self.env
+            .iter()
+            .filter(|env_var| env_var.redacted)
+            .map(|env_var| env_var.name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "descriptor_test.rs"]
+mod tests;
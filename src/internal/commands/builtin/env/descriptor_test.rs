@@ -0,0 +1,253 @@
+use super::*;
+
+use crate::internal::cache::up_environments::UpVersion;
+use crate::internal::config::up::UpConfigTool;
+
+fn up_version(tool: &str, backend: &str, version: &str, dir: &str) -> UpVersion {
+    UpVersion {
+        tool: tool.to_string(),
+        plugin_name: tool.to_string(),
+        normalized_name: tool.to_string(),
+        backend: backend.to_string(),
+        version: version.to_string(),
+        bin_path: "bin".to_string(),
+        dir: dir.to_string(),
+        data_path: Some(format!("/home/user/.local/share/omni/wd/{tool}")),
+        env_vars: vec![],
+    }
+}
+
+fn up_env_var(name: &str, value: &str, operation: EnvOperationEnum) -> UpEnvVar {
+    UpEnvVar {
+        name: name.to_string(),
+        value: Some(value.to_string()),
+        operation,
+        command: None,
+    }
+}
+
+fn fixture_environment() -> UpEnvironment {
+    let mut environment = UpEnvironment::new();
+    environment.versions = vec![
+        up_version("python", "", "3.11.6", ""),
+        up_version("node", "default", "20.11.1", ""),
+        up_version("node", "default", "20.11.1", "web"),
+        up_version("node", "default", "18.19.0", "legacy"),
+        up_version("go", "", "1.22.1", "tools"),
+        up_version("terraform", "aqua", "1.7.4", ""),
+    ];
+    environment.paths = vec!["/home/user/.local/share/omni/wd/bin".into()];
+    environment.env_vars = vec![
+        up_env_var("API_TOKEN", "s3cr3t", EnvOperationEnum::Set),
+        up_env_var("PATH", "/opt/tools/bin", EnvOperationEnum::Prepend),
+    ];
+    environment
+}
+
+/// Extract the tool, backend, version and directories that a step
+/// of the up configuration would provision
+fn step_tool(step: &UpConfigTool) -> (String, Option<String>, String, BTreeSet<String>) {
+    match step {
+        UpConfigTool::Go(config) => (
+            "go".to_string(),
+            None,
+            config.version.clone().unwrap_or_default(),
+            config.dirs.clone(),
+        ),
+        UpConfigTool::Mise(config) => (
+            config.name(),
+            config.backend.clone(),
+            config.version.clone(),
+            config.dirs.clone(),
+        ),
+        UpConfigTool::Nodejs(config) => (
+            config.backend.name(),
+            config.backend.backend.clone(),
+            config.backend.version.clone(),
+            config.backend.dirs.clone(),
+        ),
+        UpConfigTool::Python(config) => (
+            config.backend.name(),
+            config.backend.backend.clone(),
+            config.backend.version.clone(),
+            config.backend.dirs.clone(),
+        ),
+        _ => panic!("unexpected step: {step:?}"),
+    }
+}
+
+fn dirs(dirs: &[&str]) -> BTreeSet<String> {
+    dirs.iter().map(|dir| dir.to_string()).collect()
+}
+
+mod from_environment {
+    use super::*;
+
+    #[test]
+    fn groups_dirs_per_tool_version() {
+        let descriptor = EnvDescriptor::from_environment(&fixture_environment(), false);
+
+        assert_eq!(descriptor.format, ENV_DESCRIPTOR_FORMAT);
+        assert_eq!(
+            descriptor.tools,
+            vec![
+                EnvDescriptorTool {
+                    tool: "go".to_string(),
+                    backend: None,
+                    version: "1.22.1".to_string(),
+                    dirs: dirs(&["tools"]),
+                },
+                EnvDescriptorTool {
+                    tool: "node".to_string(),
+                    backend: None,
+                    version: "18.19.0".to_string(),
+                    dirs: dirs(&["legacy"]),
+                },
+                EnvDescriptorTool {
+                    tool: "node".to_string(),
+                    backend: None,
+                    version: "20.11.1".to_string(),
+                    dirs: dirs(&["", "web"]),
+                },
+                EnvDescriptorTool {
+                    tool: "python".to_string(),
+                    backend: None,
+                    version: "3.11.6".to_string(),
+                    dirs: dirs(&[]),
+                },
+                EnvDescriptorTool {
+                    tool: "terraform".to_string(),
+                    backend: Some("aqua".to_string()),
+                    version: "1.7.4".to_string(),
+                    dirs: dirs(&[]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_contain_paths() {
+        let descriptor = EnvDescriptor::from_environment(&fixture_environment(), true);
+        let serialized = serde_yaml::to_string(&descriptor).expect("failed to serialize");
+
+        assert!(
+            !serialized.contains("/home/user"),
+            "descriptor contains a path: {serialized}"
+        );
+    }
+
+    #[test]
+    fn redacts_env_values_by_default() {
+        let descriptor = EnvDescriptor::from_environment(&fixture_environment(), false);
+        let serialized = serde_yaml::to_string(&descriptor).expect("failed to serialize");
+
+        assert!(!serialized.contains("s3cr3t"));
+        assert!(!serialized.contains("/opt/tools/bin"));
+        assert_eq!(
+            descriptor.redacted_env_vars(),
+            vec!["API_TOKEN".to_string(), "PATH".to_string()]
+        );
+        assert!(descriptor.env_vars().is_empty());
+    }
+
+    #[test]
+    fn includes_env_values_when_requested() {
+        let descriptor = EnvDescriptor::from_environment(&fixture_environment(), true);
+
+        assert!(descriptor.redacted_env_vars().is_empty());
+
+        let env_vars = descriptor.env_vars();
+        assert_eq!(env_vars.len(), 2);
+        assert_eq!(env_vars[0].name, "API_TOKEN");
+        assert_eq!(env_vars[0].value, Some("s3cr3t".to_string()));
+        assert_eq!(env_vars[0].operation, EnvOperationEnum::Set);
+        assert_eq!(env_vars[1].name, "PATH");
+        assert_eq!(env_vars[1].value, Some("/opt/tools/bin".to_string()));
+        assert_eq!(env_vars[1].operation, EnvOperationEnum::Prepend);
+    }
+}
+
+mod from_str {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let descriptor = EnvDescriptor::from_environment(&fixture_environment(), true);
+        let serialized = serde_yaml::to_string(&descriptor).expect("failed to serialize");
+
+        assert!(serialized.contains("operation: prepend"));
+        assert_eq!(EnvDescriptor::from_str(&serialized), Ok(descriptor));
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let result = EnvDescriptor::from_str("format: 999\n");
+
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .contains("unsupported descriptor format"));
+    }
+
+    #[test]
+    fn invalid_yaml() {
+        assert!(EnvDescriptor::from_str("tools: [").is_err());
+    }
+}
+
+mod to_up_config {
+    use super::*;
+
+    #[test]
+    fn export_import_reconstructs_tools() {
+        let environment = fixture_environment();
+
+        // Export the environment, and import it back as if it was
+        // read on a different machine
+        let exported = serde_yaml::to_string(&EnvDescriptor::from_environment(&environment, false))
+            .expect("failed to serialize");
+        let imported = EnvDescriptor::from_str(&exported).expect("failed to parse");
+
+        let error_handler = ConfigErrorHandler::default();
+        let up_config = imported.to_up_config(&error_handler);
+        assert!(
+            error_handler.errors().is_empty(),
+            "unexpected errors: {:?}",
+            error_handler.errors()
+        );
+
+        let provisioned = up_config
+            .steps
+            .iter()
+            .map(step_tool)
+            .collect::<BTreeSet<_>>();
+
+        let mut expected = BTreeSet::new();
+        for version in environment.versions.iter() {
+            let backend = match version.backend.as_str() {
+                "" | "default" => None,
+                backend => Some(backend.to_string()),
+            };
+            let mut dirs = environment
+                .versions
+                .iter()
+                .filter(|other| other.tool == version.tool && other.version == version.version)
+                .map(|other| other.dir.clone())
+                .collect::<BTreeSet<_>>();
+            if dirs.len() == 1 && dirs.contains("") {
+                dirs.clear();
+            }
+            expected.insert((version.tool.clone(), backend, version.version.clone(), dirs));
+        }
+
+        assert_eq!(provisioned, expected);
+    }
+
+    #[test]
+    fn empty_descriptor() {
+        let descriptor = EnvDescriptor::from_str("format: 1\n").expect("failed to parse");
+        let up_config = descriptor.to_up_config(&ConfigErrorHandler::default());
+
+        assert!(up_config.steps.is_empty());
+    }
+}
@@ -0,0 +1,580 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::process::exit;
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::internal::cache::UpEnvironmentsCache;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::porcelain::PorcelainOutput;
+use crate::internal::commands::porcelain::PorcelainRecords;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::dynenv::preview_dynamic_env;
+use crate::internal::dynenv::DynamicEnvPreview;
+use crate::internal::user_interface::StringColor;
+use crate::internal::utils::is_secret_env_var;
+use crate::internal::utils::mask_secret;
+use crate::internal::workdir;
+use crate::omni_error;
+use crate::omni_warning;
+
+/// The differences between the environments that would be set up for
+/// two paths
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct EnvDiff {
+    pub left: String,
+    pub right: String,
+    pub variables: Vec<EnvDiffValue>,
+    pub tools: Vec<EnvDiffValue>,
+    pub lists: Vec<EnvDiffList>,
+}
+
+/// A variable or tool version that differs between the environments; a
+/// `None` value means that it is not set by that environment
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct EnvDiffValue {
+    pub name: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub masked: bool,
+}
+
+/// A list variable (e.g. `PATH`) that differs between the environments
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct EnvDiffList {
+    pub name: String,
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    /// Whether the entries found in both environments are in a different
+    /// order, which matters as the first matching entry takes precedence
+    pub order_differs: bool,
+    /// The entries found in both environments, in the order of each
+    /// environment; only provided when the order differs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub left_order: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub right_order: Vec<String>,
+}
+
+impl EnvDiff {
+    pub fn new(
+        left: &str,
+        left_env: &DynamicEnvPreview,
+        right: &str,
+        right_env: &DynamicEnvPreview,
+    ) -> Self {
+        let lists = left_env
+            .lists
+            .keys()
+            .chain(right_env.lists.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|name| {
+                diff_list(
+                    name,
+                    left_env.lists.get(name).map(Vec::as_slice).unwrap_or(&[]),
+                    right_env.lists.get(name).map(Vec::as_slice).unwrap_or(&[]),
+                )
+            })
+            .collect();
+
+        Self {
+            left: left.to_string(),
+            right: right.to_string(),
+            variables: diff_values(&left_env.env_vars, &right_env.env_vars, is_secret_env_var),
+            tools: diff_values(&left_env.tools, &right_env.tools, |_| false),
+            lists,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.tools.is_empty() && self.lists.is_empty()
+    }
+
+    /// Returns the records of the porcelain format; see the
+    /// documentation of the `env diff` command for their description
+    pub fn porcelain_records(&self) -> PorcelainRecords {
+        let mut records = PorcelainRecords::new();
+        records.push("left", &self.left);
+        records.push("right", &self.right);
+
+        for (kind, values) in [("variable", &self.variables), ("tool", &self.tools)] {
+            records.push(format!("{kind}.count"), values.len().to_string());
+            for (idx, value) in values.iter().enumerate() {
+                records.push(format!("{kind}.{idx}.name"), &value.name);
+                records.push_opt(format!("{kind}.{idx}.left"), value.left.as_ref());
+                records.push_opt(format!("{kind}.{idx}.right"), value.right.as_ref());
+                records.push_bool(format!("{kind}.{idx}.masked"), value.masked);
+            }
+        }
+
+        records.push("list.count", self.lists.len().to_string());
+        for (idx, list) in self.lists.iter().enumerate() {
+            records.push(format!("list.{idx}.name"), &list.name);
+            records.push_bool(format!("list.{idx}.order_differs"), list.order_differs);
+
+            for (key, entries) in [
+                ("only_left", &list.only_left),
+                ("only_right", &list.only_right),
+                ("left_order", &list.left_order),
+                ("right_order", &list.right_order),
+            ] {
+                records.push(format!("list.{idx}.{key}.count"), entries.len().to_string());
+                for (jdx, entry) in entries.iter().enumerate() {
+                    records.push(format!("list.{idx}.{key}.{jdx}"), entry);
+                }
+            }
+        }
+
+        records
+    }
+
+    pub fn render_plain(&self) -> String {
+        if self.is_empty() {
+            return format!(
+                "no difference between the environments of {} and {}",
+                self.left.light_blue(),
+                self.right.light_blue()
+            );
+        }
+
+        let mut lines = vec![format!(
+            "{} {} {}",
+            format!("--- {}", self.left).light_red(),
+            "/".light_black(),
+            format!("+++ {}", self.right).light_green(),
+        )];
+
+        for (title, values) in [("Variables", &self.variables), ("Tools", &self.tools)] {
+            if values.is_empty() {
+                continue;
+            }
+
+            lines.push(String::new());
+            lines.push(title.bold());
+            for value in values {
+                lines.push(match (&value.left, &value.right) {
+                    (Some(left), None) => format!("  {} {}={}", "-".light_red(), value.name, left),
+                    (None, Some(right)) => {
+                        format!("  {} {}={}", "+".light_green(), value.name, right)
+                    }
+                    (left, right) => format!(
+                        "  {} {}: {} {} {}",
+                        "~".light_yellow(),
+                        value.name,
+                        left.as_deref().unwrap_or_default(),
+                        "→".light_black(),
+                        right.as_deref().unwrap_or_default(),
+                    ),
+                });
+            }
+        }
+
+        if !self.lists.is_empty() {
+            lines.push(String::new());
+            lines.push("Lists".bold());
+            for list in &self.lists {
+                lines.push(format!("  {}", list.name));
+                for entry in &list.only_left {
+                    lines.push(format!("    {} {}", "-".light_red(), entry));
+                }
+                for entry in &list.only_right {
+                    lines.push(format!("    {} {}", "+".light_green(), entry));
+                }
+                if list.order_differs {
+                    lines.push(format!(
+                        "    {} {}",
+                        "~".light_yellow(),
+                        "same entries in a different order; the first one found takes precedence"
+                            .light_black()
+                    ));
+                    lines.push(format!(
+                        "      {} {}",
+                        "-".light_red(),
+                        list.left_order.join(":")
+                    ));
+                    lines.push(format!(
+                        "      {} {}",
+                        "+".light_green(),
+                        list.right_order.join(":")
+                    ));
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    pub fn render_table(&self) -> String {
+        let missing = "-".to_string();
+        let mut rows = vec![];
+
+        for (kind, values) in [("variable", &self.variables), ("tool", &self.tools)] {
+            for value in values {
+                rows.push([
+                    kind.to_string(),
+                    value.name.clone(),
+                    value.left.clone().unwrap_or(missing.clone()),
+                    value.right.clone().unwrap_or(missing.clone()),
+                ]);
+            }
+        }
+
+        for list in &self.lists {
+            for entry in &list.only_left {
+                rows.push([
+                    "list".to_string(),
+                    list.name.clone(),
+                    entry.clone(),
+                    missing.clone(),
+                ]);
+            }
+            for entry in &list.only_right {
+                rows.push([
+                    "list".to_string(),
+                    list.name.clone(),
+                    missing.clone(),
+                    entry.clone(),
+                ]);
+            }
+            if list.order_differs {
+                rows.push([
+                    "list order".to_string(),
+                    list.name.clone(),
+                    list.left_order.join(":"),
+                    list.right_order.join(":"),
+                ]);
+            }
+        }
+
+        let headers = [
+            "KIND".to_string(),
+            "NAME".to_string(),
+            self.left.clone(),
+            self.right.clone(),
+        ];
+
+        let widths = (0..headers.len())
+            .map(|idx| {
+                rows.iter()
+                    .map(|row| row[idx].len())
+                    .chain(std::iter::once(headers[idx].len()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<_>>();
+
+        let render_row = |cells: &[String; 4]| {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+
+        std::iter::once(render_row(&headers))
+            .chain(rows.iter().map(render_row))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Returns the values that differ between the two maps, masking the
+/// values of the names matching the given predicate
+fn diff_values<F>(
+    left: &BTreeMap<String, String>,
+    right: &BTreeMap<String, String>,
+    is_secret: F,
+) -> Vec<EnvDiffValue>
+where
+    F: Fn(&str) -> bool,
+{
+    left.keys()
+        .chain(right.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|name| {
+            let left = left.get(name);
+            let right = right.get(name);
+            if left == right {
+                return None;
+            }
+
+            let masked = is_secret(name);
+            let mask = |value: Option<&String>| value.map(|value| mask_secret(value, masked));
+
+            Some(EnvDiffValue {
+                name: name.clone(),
+                left: mask(left),
+                right: mask(right),
+                masked,
+            })
+        })
+        .collect()
+}
+
+/// Returns the differences between two lists, or `None` if they are
+/// equivalent; only the first occurrence of an entry is considered, as
+/// it is the one taking precedence
+fn diff_list(name: &str, left: &[String], right: &[String]) -> Option<EnvDiffList> {
+    let left = left.iter().unique().collect::<Vec<_>>();
+    let right = right.iter().unique().collect::<Vec<_>>();
+
+    let only_left = left
+        .iter()
+        .filter(|entry| !right.contains(*entry))
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>();
+    let only_right = right
+        .iter()
+        .filter(|entry| !left.contains(*entry))
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>();
+
+    let left_common = left
+        .iter()
+        .filter(|entry| right.contains(*entry))
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>();
+    let right_common = right
+        .iter()
+        .filter(|entry| left.contains(*entry))
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>();
+    let order_differs = left_common != right_common;
+
+    if only_left.is_empty() && only_right.is_empty() && !order_differs {
+        return None;
+    }
+
+    let (left_order, right_order) = if order_differs {
+        (left_common, right_common)
+    } else {
+        (vec![], vec![])
+    };
+
+    Some(EnvDiffList {
+        name: name.to_string(),
+        only_left,
+        only_right,
+        order_differs,
+        left_order,
+        right_order,
+    })
+}
+
+#[derive(Debug, Clone)]
+struct EnvDiffCommandArgs {
+    left: String,
+    right: String,
+    output: EnvDiffCommandOutput,
+    porcelain: Option<PorcelainOutput>,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for EnvDiffCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let path = match args.get("path") {
+            Some(ParseArgsValue::SingleString(Some(path))) => path.trim().to_string(),
+            _ => unreachable!("no path specified"),
+        };
+
+        let other_path = match args.get("other_path") {
+            Some(ParseArgsValue::SingleString(Some(other_path))) => {
+                Some(other_path.trim().to_string())
+            }
+            _ => None,
+        };
+
+        // With a single path, the environment of the current directory
+        // is compared to the environment of that path
+        let (left, right) = match other_path {
+            Some(other_path) => (path, other_path),
+            None => (".".to_string(), path),
+        };
+
+        let output = match args.get("output") {
+            Some(ParseArgsValue::SingleString(Some(value))) => match value.as_str() {
+                "json" => EnvDiffCommandOutput::Json,
+                "plain" => EnvDiffCommandOutput::Plain,
+                "table" => EnvDiffCommandOutput::Table,
+                _ => unreachable!("unknown value for output"),
+            },
+            _ => EnvDiffCommandOutput::Plain,
+        };
+
+        let porcelain = PorcelainOutput::from_args(&args);
+
+        Self {
+            left,
+            right,
+            output,
+            porcelain,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum EnvDiffCommandOutput {
+    Plain,
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvDiffCommand {}
+
+impl EnvDiffCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the environment that would be set up for the given path,
+    /// from the dynamic environment assigned to its work directory
+    fn preview(&self, path: &str) -> DynamicEnvPreview {
+        let environment = workdir(path)
+            .id()
+            .and_then(|wd_id| UpEnvironmentsCache::get().get_env(&wd_id));
+
+        if environment.is_none() {
+            omni_warning!(format!(
+                "no dynamic environment found for {}",
+                path.light_blue()
+            ));
+        }
+
+        preview_dynamic_env(path, environment.as_ref())
+    }
+}
+
+impl BuiltinCommand for EnvDiffCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["env".to_string(), "diff".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Compare the dynamic environments of two directories\n",
+                "\n",
+                "The environments that omni would set up in each directory are computed ",
+                "without being applied, and compared: environment variables, tool versions, ",
+                "and entries of lists such as \x1B[1mPATH\x1B[0m, for which a different order ",
+                "is also reported as it changes which entry takes precedence.\n",
+                "\n",
+                "When a single path is given, the current directory is compared to it. ",
+                "The values of the environment variables that look like secrets are masked.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["-o".to_string(), "--output".to_string()],
+                    desc: Some("Output format".to_string()),
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "json".to_string(),
+                        "plain".to_string(),
+                        "table".to_string(),
+                    ]),
+                    default: Some("plain".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["path".to_string()],
+                    desc: Some("The directory to compare".to_string()),
+                    arg_type: SyntaxOptArgType::DirPath,
+                    required: true,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["other_path".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The directory to compare the first one to ",
+                            "[\x1B[1mdefault: the current directory is compared to ",
+                            "\x1B[1mpath\x1B[0m]"
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::DirPath,
+                    ..Default::default()
+                },
+            ]
+            .into_iter()
+            .chain(PorcelainOutput::parameters())
+            .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = EnvDiffCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        for path in [&args.left, &args.right] {
+            if !std::path::Path::new(path).is_dir() {
+                omni_error!(format!("{} is not a directory", path.light_yellow()));
+                exit(1);
+            }
+        }
+
+        let left_env = self.preview(&args.left);
+        let right_env = self.preview(&args.right);
+        let diff = EnvDiff::new(&args.left, &left_env, &args.right, &right_env);
+
+        if let Some(porcelain) = &args.porcelain {
+            print!("{}", porcelain.render(&diff.porcelain_records()));
+            exit(0);
+        }
+
+        match args.output {
+            EnvDiffCommandOutput::Plain => println!("{}", diff.render_plain()),
+            EnvDiffCommandOutput::Table => println!("{}", diff.render_table()),
+            EnvDiffCommandOutput::Json => match serde_json::to_string_pretty(&diff) {
+                Ok(json) => println!("{json}"),
+                Err(e) => {
+                    omni_error!(format!("Error while serializing the diff to JSON: {}", e));
+                    exit(1);
+                }
+            },
+        }
+
+        exit(0);
+    }
+}
+
+#[cfg(test)]
+#[path = "diff_test.rs"]
+mod tests;
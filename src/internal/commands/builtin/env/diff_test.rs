@@ -0,0 +1,458 @@
+use super::*;
+
+use crate::internal::cache::up_environments::UpEnvVar;
+use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::cache::up_environments::UpVersionParams;
+use crate::internal::config::parser::EnvOperationEnum;
+use crate::internal::config::up::github_release::github_release_tool_path;
+use crate::internal::testutils::run_with_env;
+use crate::internal::utils::SECRET_PLACEHOLDER;
+
+fn env_var(name: &str, operation: EnvOperationEnum, value: &str) -> UpEnvVar {
+    UpEnvVar {
+        name: name.to_string(),
+        value: Some(value.to_string()),
+        operation,
+        command: None,
+    }
+}
+
+fn environment(
+    env_vars: Vec<UpEnvVar>,
+    paths: &[&str],
+    releases: &[(&str, &str)],
+) -> UpEnvironment {
+    let mut environment = UpEnvironment::new();
+    environment.env_vars = env_vars;
+    environment.paths = paths.iter().map(std::path::PathBuf::from).collect();
+    for (tool, version) in releases {
+        environment.add_version(UpVersionParams {
+            backend: "ghrelease",
+            tool,
+            plugin_name: tool,
+            normalized_name: tool,
+            version,
+            bin_path: "",
+            dirs: Default::default(),
+            env_vars: vec![],
+        });
+    }
+    environment
+}
+
+fn preview(
+    env_vars: &[(&str, &str)],
+    lists: &[(&str, &[&str])],
+    tools: &[(&str, &str)],
+) -> DynamicEnvPreview {
+    DynamicEnvPreview {
+        env_vars: env_vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        lists: lists
+            .iter()
+            .map(|(name, entries)| {
+                (
+                    name.to_string(),
+                    entries.iter().map(|entry| entry.to_string()).collect(),
+                )
+            })
+            .collect(),
+        tools: tools
+            .iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect(),
+    }
+}
+
+fn value(name: &str, left: Option<&str>, right: Option<&str>, masked: bool) -> EnvDiffValue {
+    EnvDiffValue {
+        name: name.to_string(),
+        left: left.map(|value| value.to_string()),
+        right: right.map(|value| value.to_string()),
+        masked,
+    }
+}
+
+mod env_diff {
+    use super::*;
+
+    #[test]
+    fn test_identical_environments() {
+        let env = preview(
+            &[("FOO", "bar")],
+            &[("PATH", &["/a/bin", "/b/bin"])],
+            &[("python", "3.12.4")],
+        );
+
+        let diff = EnvDiff::new("left", &env, "right", &env);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_variables() {
+        let left = preview(
+            &[("ONLY_LEFT", "1"), ("CHANGED", "a"), ("SAME", "x")],
+            &[],
+            &[],
+        );
+        let right = preview(
+            &[("ONLY_RIGHT", "2"), ("CHANGED", "b"), ("SAME", "x")],
+            &[],
+            &[],
+        );
+
+        let diff = EnvDiff::new("left", &left, "right", &right);
+        assert_eq!(
+            diff.variables,
+            vec![
+                value("CHANGED", Some("a"), Some("b"), false),
+                value("ONLY_LEFT", Some("1"), None, false),
+                value("ONLY_RIGHT", None, Some("2"), false),
+            ]
+        );
+        assert!(diff.tools.is_empty());
+        assert!(diff.lists.is_empty());
+    }
+
+    #[test]
+    fn test_secrets_are_masked() {
+        let left = preview(
+            &[("GITHUB_TOKEN", "abc"), ("db_password", "hunter2")],
+            &[],
+            &[],
+        );
+        let right = preview(&[("GITHUB_TOKEN", "def"), ("API_KEY_FILE", "x")], &[], &[]);
+
+        let diff = EnvDiff::new("left", &left, "right", &right);
+        assert_eq!(
+            diff.variables,
+            vec![
+                value("API_KEY_FILE", None, Some(SECRET_PLACEHOLDER), true),
+                value(
+                    "GITHUB_TOKEN",
+                    Some(SECRET_PLACEHOLDER),
+                    Some(SECRET_PLACEHOLDER),
+                    true
+                ),
+                value("db_password", Some(SECRET_PLACEHOLDER), None, true),
+            ]
+        );
+
+        let json = serde_json::to_string(&diff).expect("failed to serialize diff");
+        assert!(!json.contains("abc"));
+        assert!(!json.contains("hunter2"));
+        assert!(!diff.render_table().contains("def"));
+    }
+
+    #[test]
+    fn test_equal_secrets_are_not_reported() {
+        let env = preview(&[("GITHUB_TOKEN", "abc")], &[], &[]);
+
+        let diff = EnvDiff::new("left", &env, "right", &env);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_tool_versions() {
+        let left = preview(&[], &[], &[("python", "3.11.9"), ("node", "20.12.0")]);
+        let right = preview(&[], &[], &[("python", "3.12.4"), ("go", "1.22.0")]);
+
+        let diff = EnvDiff::new("left", &left, "right", &right);
+        assert_eq!(
+            diff.tools,
+            vec![
+                value("go", None, Some("1.22.0"), false),
+                value("node", Some("20.12.0"), None, false),
+                value("python", Some("3.11.9"), Some("3.12.4"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_entries() {
+        let left = preview(&[], &[("PATH", &["/a/bin", "/shared/bin"])], &[]);
+        let right = preview(
+            &[],
+            &[
+                ("PATH", &["/shared/bin", "/b/bin"]),
+                ("MANPATH", &["/b/man"]),
+            ],
+            &[],
+        );
+
+        let diff = EnvDiff::new("left", &left, "right", &right);
+        assert_eq!(
+            diff.lists,
+            vec![
+                EnvDiffList {
+                    name: "MANPATH".to_string(),
+                    only_left: vec![],
+                    only_right: vec!["/b/man".to_string()],
+                    order_differs: false,
+                    left_order: vec![],
+                    right_order: vec![],
+                },
+                EnvDiffList {
+                    name: "PATH".to_string(),
+                    only_left: vec!["/a/bin".to_string()],
+                    only_right: vec!["/b/bin".to_string()],
+                    order_differs: false,
+                    left_order: vec![],
+                    right_order: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_order() {
+        let left = preview(&[], &[("PATH", &["/a/bin", "/b/bin", "/c/bin"])], &[]);
+        let right = preview(&[], &[("PATH", &["/b/bin", "/a/bin", "/c/bin"])], &[]);
+
+        let diff = EnvDiff::new("left", &left, "right", &right);
+        assert_eq!(
+            diff.lists,
+            vec![EnvDiffList {
+                name: "PATH".to_string(),
+                only_left: vec![],
+                only_right: vec![],
+                order_differs: true,
+                left_order: vec![
+                    "/a/bin".to_string(),
+                    "/b/bin".to_string(),
+                    "/c/bin".to_string()
+                ],
+                right_order: vec![
+                    "/b/bin".to_string(),
+                    "/a/bin".to_string(),
+                    "/c/bin".to_string()
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_list_duplicates_after_first_occurrence_are_ignored() {
+        let left = preview(&[], &[("PATH", &["/a/bin", "/b/bin", "/a/bin"])], &[]);
+        let right = preview(&[], &[("PATH", &["/a/bin", "/b/bin"])], &[]);
+
+        let diff = EnvDiff::new("left", &left, "right", &right);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_render_table() {
+        let left = preview(
+            &[("FOO", "bar")],
+            &[("PATH", &["/a/bin", "/b/bin"])],
+            &[("python", "3.11.9")],
+        );
+        let right = preview(
+            &[],
+            &[("PATH", &["/b/bin", "/a/bin"])],
+            &[("python", "3.12.4")],
+        );
+
+        let diff = EnvDiff::new("service-a", &left, "service-b", &right);
+        assert_eq!(
+            diff.render_table(),
+            [
+                "KIND        NAME    service-a      service-b",
+                "variable    FOO     bar            -",
+                "tool        python  3.11.9         3.12.4",
+                "list order  PATH    /a/bin:/b/bin  /b/bin:/a/bin",
+            ]
+            .join("\n")
+        );
+    }
+}
+
+mod porcelain {
+    use super::*;
+
+    use crate::internal::commands::porcelain::PorcelainOutput;
+    use crate::internal::commands::porcelain::PorcelainVersion;
+
+    fn v1(nul: bool) -> PorcelainOutput {
+        PorcelainOutput {
+            version: PorcelainVersion::V1,
+            nul,
+        }
+    }
+
+    #[test]
+    fn v1_contract() {
+        let diff = EnvDiff {
+            left: "service-a".to_string(),
+            right: "service-b".to_string(),
+            variables: vec![
+                value("FOO", Some("bar"), None, false),
+                value("TOKEN", Some("********"), Some("********"), true),
+            ],
+            tools: vec![value("python", Some("3.11.9"), Some("3.12.4"), false)],
+            lists: vec![EnvDiffList {
+                name: "PATH".to_string(),
+                only_left: vec!["/c/bin".to_string()],
+                only_right: vec![],
+                order_differs: true,
+                left_order: vec!["/a/bin".to_string(), "/b/bin".to_string()],
+                right_order: vec!["/b/bin".to_string(), "/a/bin".to_string()],
+            }],
+        };
+
+        assert_eq!(
+            v1(false).render(&diff.porcelain_records()),
+            concat!(
+                "porcelain\tv1\n",
+                "left\tservice-a\n",
+                "right\tservice-b\n",
+                "variable.count\t2\n",
+                "variable.0.name\tFOO\n",
+                "variable.0.left\tbar\n",
+                "variable.0.masked\tfalse\n",
+                "variable.1.name\tTOKEN\n",
+                "variable.1.left\t********\n",
+                "variable.1.right\t********\n",
+                "variable.1.masked\ttrue\n",
+                "tool.count\t1\n",
+                "tool.0.name\tpython\n",
+                "tool.0.left\t3.11.9\n",
+                "tool.0.right\t3.12.4\n",
+                "tool.0.masked\tfalse\n",
+                "list.count\t1\n",
+                "list.0.name\tPATH\n",
+                "list.0.order_differs\ttrue\n",
+                "list.0.only_left.count\t1\n",
+                "list.0.only_left.0\t/c/bin\n",
+                "list.0.only_right.count\t0\n",
+                "list.0.left_order.count\t2\n",
+                "list.0.left_order.0\t/a/bin\n",
+                "list.0.left_order.1\t/b/bin\n",
+                "list.0.right_order.count\t2\n",
+                "list.0.right_order.0\t/b/bin\n",
+                "list.0.right_order.1\t/a/bin\n",
+            )
+        );
+    }
+
+    #[test]
+    fn v1_contract_identical() {
+        let left = preview(&[("FOO", "bar")], &[], &[]);
+        let diff = EnvDiff::new("left dir", &left, "right\tdir", &left);
+
+        assert_eq!(
+            v1(false).render(&diff.porcelain_records()),
+            concat!(
+                "porcelain\tv1\n",
+                "left\tleft dir\n",
+                "right\tright\\tdir\n",
+                "variable.count\t0\n",
+                "tool.count\t0\n",
+                "list.count\t0\n",
+            )
+        );
+        assert_eq!(
+            v1(true).render(&diff.porcelain_records()),
+            concat!(
+                "porcelain\tv1\0",
+                "left\tleft dir\0",
+                "right\tright\tdir\0",
+                "variable.count\t0\0",
+                "tool.count\t0\0",
+                "list.count\t0\0",
+            )
+        );
+    }
+}
+
+mod cached_environments {
+    use super::*;
+
+    #[test]
+    fn test_diff_between_cached_environments() {
+        run_with_env(&[], || {
+            let left_dir = tempfile::tempdir().expect("failed to create temp directory");
+            let right_dir = tempfile::tempdir().expect("failed to create temp directory");
+            let left_path = left_dir.path().to_string_lossy().to_string();
+            let right_path = right_dir.path().to_string_lossy().to_string();
+
+            let left_env = environment(
+                vec![
+                    env_var("SERVICE_NAME", EnvOperationEnum::Set, "billing"),
+                    env_var("SERVICE_TOKEN", EnvOperationEnum::Set, "left-secret"),
+                    env_var("EXTRA_PATH", EnvOperationEnum::Prepend, "/left/extra"),
+                ],
+                &["/shared/bin", "/left/bin"],
+                &[("owner/tool", "1.0.0")],
+            );
+            let right_env = environment(
+                vec![
+                    env_var("SERVICE_NAME", EnvOperationEnum::Set, "invoicing"),
+                    env_var("SERVICE_TOKEN", EnvOperationEnum::Set, "right-secret"),
+                ],
+                &["/left/bin", "/shared/bin"],
+                &[("owner/tool", "2.0.0")],
+            );
+
+            let left = preview_dynamic_env(&left_path, Some(&left_env));
+            let right = preview_dynamic_env(&right_path, Some(&right_env));
+
+            assert_eq!(
+                left.tools.get("owner/tool").map(String::as_str),
+                Some("1.0.0")
+            );
+            assert_eq!(
+                left.lists.get("EXTRA_PATH"),
+                Some(&vec!["/left/extra".to_string()])
+            );
+
+            let diff = EnvDiff::new(&left_path, &left, &right_path, &right);
+            assert_eq!(
+                diff.variables,
+                vec![
+                    value("SERVICE_NAME", Some("billing"), Some("invoicing"), false),
+                    value(
+                        "SERVICE_TOKEN",
+                        Some(SECRET_PLACEHOLDER),
+                        Some(SECRET_PLACEHOLDER),
+                        true
+                    ),
+                ]
+            );
+            assert_eq!(
+                diff.tools,
+                vec![value("owner/tool", Some("1.0.0"), Some("2.0.0"), false)]
+            );
+
+            let left_release = github_release_tool_path("owner/tool", "1.0.0")
+                .to_string_lossy()
+                .to_string();
+            let right_release = github_release_tool_path("owner/tool", "2.0.0")
+                .to_string_lossy()
+                .to_string();
+            assert_eq!(
+                diff.lists,
+                vec![
+                    EnvDiffList {
+                        name: "EXTRA_PATH".to_string(),
+                        only_left: vec!["/left/extra".to_string()],
+                        only_right: vec![],
+                        order_differs: false,
+                        left_order: vec![],
+                        right_order: vec![],
+                    },
+                    EnvDiffList {
+                        name: "PATH".to_string(),
+                        only_left: vec![left_release],
+                        only_right: vec![right_release],
+                        order_differs: true,
+                        left_order: vec!["/shared/bin".to_string(), "/left/bin".to_string()],
+                        right_order: vec!["/left/bin".to_string(), "/shared/bin".to_string()],
+                    },
+                ]
+            );
+        });
+    }
+}
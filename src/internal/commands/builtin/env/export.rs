@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+
+use crate::internal::cache::up_environments::UpEnvironmentsCache;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::builtin::env::EnvDescriptor;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::user_interface::StringColor;
+use crate::internal::workdir;
+use crate::internal::workdir::display_workdir_id;
+use crate::omni_error;
+use crate::omni_info;
+
+#[derive(Debug, Clone)]
+struct EnvExportCommandArgs {
+    include_env_values: bool,
+    output: Option<String>,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for EnvExportCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let include_env_values = matches!(
+            args.get("include_env_values"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+        let output = match args.get("output") {
+            Some(ParseArgsValue::SingleString(Some(output))) => {
+                let output = output.trim();
+                if output.is_empty() || output == "-" {
+                    None
+                } else {
+                    Some(output.to_string())
+                }
+            }
+            _ => None,
+        };
+
+        Self {
+            include_env_values,
+            output,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvExportCommand {}
+
+impl EnvExportCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for EnvExportCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["env".to_string(), "export".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Export a portable descriptor of the dynamic environment of the work directory\n",
+                "\n",
+                "The descriptor lists the tools and versions that \x1B[1mup\x1B[0m installed for ",
+                "the current work directory, without any machine-specific path, so that it can ",
+                "be imported with \x1B[1momni env import\x1B[0m to provision the same environment ",
+                "on another machine (e.g. a dev container).\n",
+                "\n",
+                "The values of the environment variables are redacted unless ",
+                "\x1B[1m--include-env-values\x1B[0m is specified.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--include-env-values".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Include the values of the environment variables in the descriptor; ",
+                            "make sure not to share the descriptor if those contain secrets",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["-o".to_string(), "--output".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The file to write the descriptor to ",
+                            "[\x1B[1mdefault: standard output\x1B[0m]"
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::FilePath,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = EnvExportCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        let wd = workdir(".");
+        let wd_id = match wd.id() {
+            Some(id) => id,
+            None => {
+                omni_error!("not in a work directory");
+                exit(1);
+            }
+        };
+
+        let environment = match UpEnvironmentsCache::get().get_env(&wd_id) {
+            Some(environment) => environment,
+            None => {
+                omni_error!(format!(
+                    "no dynamic environment found for {}; run {} first",
+                    display_workdir_id(&wd_id).light_blue(),
+                    "omni up".light_yellow(),
+                ));
+                exit(1);
+            }
+        };
+
+        let descriptor = EnvDescriptor::from_environment(&environment, args.include_env_values);
+        let serialized = match serde_yaml::to_string(&descriptor) {
+            Ok(serialized) => serialized,
+            Err(err) => {
+                omni_error!(format!("failed to serialize descriptor: {}", err));
+                exit(1);
+            }
+        };
+
+        match args.output {
+            Some(output) => {
+                if let Err(err) = std::fs::write(&output, serialized) {
+                    omni_error!(format!(
+                        "failed to write {}: {}",
+                        output.light_yellow(),
+                        err
+                    ));
+                    exit(1);
+                }
+                omni_info!(format!("environment exported to {}", output.light_yellow()));
+            }
+            None => print!("{serialized}"),
+        }
+
+        exit(0);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::process::exit;
+
+use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::builtin::env::EnvDescriptor;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::up::UpOptions;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::user_interface::StringColor;
+use crate::internal::workdir;
+use crate::internal::workdir_or_init;
+use crate::omni_error;
+use crate::omni_info;
+use crate::omni_warning;
+
+#[derive(Debug, Clone)]
+struct EnvImportCommandArgs {
+    file: String,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for EnvImportCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let file = match args.get("file") {
+            Some(ParseArgsValue::SingleString(Some(file))) => file.trim().to_string(),
+            _ => unreachable!("no file specified"),
+        };
+
+        Self { file }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvImportCommand {}
+
+impl EnvImportCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn read_descriptor(&self, file: &str) -> Result<String, std::io::Error> {
+        if file == "-" {
+            let mut contents = String::new();
+            std::io::stdin().read_to_string(&mut contents)?;
+            Ok(contents)
+        } else {
+            std::fs::read_to_string(file)
+        }
+    }
+}
+
+impl BuiltinCommand for EnvImportCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["env".to_string(), "import".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Import a descriptor exported with \x1B[1momni env export\x1B[0m\n",
+                "\n",
+                "The tools and versions listed in the descriptor are provisioned for the ",
+                "current work directory, as \x1B[1mup\x1B[0m would do it, and the resulting ",
+                "dynamic environment is assigned to the work directory. Environment variables ",
+                "whose values were redacted when exporting are skipped.\n",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["file".to_string()],
+                desc: Some(
+                    concat!(
+                        "The descriptor file to import; use \x1B[1m-\x1B[0m to read ",
+                        "from the standard input"
+                    )
+                    .to_string(),
+                ),
+                arg_type: SyntaxOptArgType::FilePath,
+                required: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = EnvImportCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        let contents = match self.read_descriptor(&args.file) {
+            Ok(contents) => contents,
+            Err(err) => {
+                omni_error!(format!(
+                    "failed to read {}: {}",
+                    args.file.light_yellow(),
+                    err
+                ));
+                exit(1);
+            }
+        };
+
+        let descriptor = match EnvDescriptor::from_str(&contents) {
+            Ok(descriptor) => descriptor,
+            Err(err) => {
+                omni_error!(format!("invalid descriptor: {}", err));
+                exit(1);
+            }
+        };
+
+        let wd = workdir(".");
+        if !wd.in_workdir() {
+            omni_error!("not in a work directory");
+            exit(1);
+        }
+
+        // Make sure we have a workdir id to assign the environment to
+        if let Err(err) = workdir_or_init(".") {
+            omni_error!(format!("{}", err));
+            exit(1);
+        }
+
+        // Switch directory to the work directory root so that the
+        // directories of the descriptor are relative to it
+        if let Some(wd_root) = wd.root() {
+            if let Err(err) = std::env::set_current_dir(wd_root) {
+                omni_error!(format!(
+                    "failed to change directory {}: {}",
+                    format!("({wd_root})").light_black(),
+                    format!("{err}").red()
+                ));
+                exit(1);
+            }
+        }
+
+        let error_handler = ConfigErrorHandler::new();
+        let up_config = descriptor.to_up_config(&error_handler);
+        for error in error_handler.errors() {
+            omni_warning!(error);
+        }
+
+        let redacted = descriptor.redacted_env_vars();
+        if !redacted.is_empty() {
+            omni_warning!(format!(
+                "skipping redacted environment variables: {}",
+                redacted.join(", ").light_yellow()
+            ));
+        }
+
+        let mut environment = UpEnvironment::new().init();
+        environment.env_vars = descriptor.env_vars();
+
+        if let Err(err) = up_config.up(&UpOptions::new(), &mut environment) {
+            omni_error!(format!("issue while importing environment: {err}"));
+            exit(1);
+        }
+
+        omni_info!(format!(
+            "imported {} tool version(s)",
+            descriptor.tools.len().to_string().light_green()
+        ));
+        exit(0);
+    }
+}
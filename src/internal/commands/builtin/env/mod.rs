@@ -0,0 +1,11 @@
+pub(crate) mod descriptor;
+pub(crate) use descriptor::EnvDescriptor;
+
+pub(crate) mod diff;
+pub(crate) use diff::EnvDiffCommand;
+
+pub(crate) mod export;
+pub(crate) use export::EnvExportCommand;
+
+pub(crate) mod import;
+pub(crate) use import::EnvImportCommand;
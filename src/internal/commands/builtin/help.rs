@@ -15,8 +15,9 @@ use crate::internal::commands::Command;
 use crate::internal::config::parser::ParseArgsValue;
 use crate::internal::config::CommandSyntax;
 use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgOrigin;
 use crate::internal::config::SyntaxOptArgType;
-use crate::internal::user_interface::colors::strip_colors_if_needed;
+use crate::internal::user_interface::colors::ColorCapability;
 use crate::internal::user_interface::print::strip_ansi_codes;
 use crate::internal::user_interface::term_width;
 use crate::internal::user_interface::wrap_blocks;
@@ -133,7 +134,9 @@ impl HelpCommand {
         );
 
         let printer: Box<dyn HelpCommandPrinter> = match args.output {
-            HelpCommandOutput::Plain => Box::new(HelpCommandPlainPrinter::new()),
+            HelpCommandOutput::Plain => {
+                Box::new(HelpCommandPlainPrinter::new(ColorCapability::current()))
+            }
             HelpCommandOutput::Json => Box::new(HelpCommandJsonPrinter::new()),
         };
 
@@ -259,10 +262,16 @@ trait HelpCommandPrinter {
     fn print_error(&self, error_type: &str, error_msg: &str);
 }
 
-struct HelpCommandPlainPrinter {}
+/// Prints the help in plain text, rendering colors according to the
+/// color capability it was created with
+struct HelpCommandPlainPrinter {
+    colors: ColorCapability,
+}
 
 impl HelpCommandPrinter for HelpCommandPlainPrinter {
     fn print_global_help(&self, unfold: bool) {
+        let _colors = self.colors.scoped();
+
         eprintln!(
             "{}\n\n{} {} {} {} {}",
             omni_header!(),
@@ -274,7 +283,8 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
         );
 
         eprintln!("\n{}", "Options:".bold().underline());
-        if let Err(err) = self.print_syntax_column_help(&GLOBAL_OPTIONS.iter().collect::<Vec<_>>())
+        if let Err(err) =
+            self.print_syntax_column_help(&GLOBAL_OPTIONS.iter().collect::<Vec<_>>(), None)
         {
             omni_error!(err);
         }
@@ -284,6 +294,8 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
     }
 
     fn print_command_help(&self, command: &Command, called_as: Vec<String>, unfold: bool) {
+        let _colors = self.colors.scoped();
+
         eprintln!("{}", omni_header!());
 
         let max_width = term_width() - 4;
@@ -292,7 +304,7 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
         if !help.is_empty() {
             eprintln!(
                 "\n{}",
-                wrap_blocks(&strip_colors_if_needed(help), max_width).join("\n")
+                wrap_blocks(&self.colors.strip_if_disabled(help), max_width).join("\n")
             );
         }
 
@@ -346,12 +358,7 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
             let help_option;
             let parameters_iter: Box<dyn Iterator<Item = &SyntaxOptArg>> =
                 if command.internal_argparser() {
-                    help_option = SyntaxOptArg {
-                        names: vec!["-h".to_string(), "--help".to_string()],
-                        desc: Some("Show this help message and exit".to_string()),
-                        arg_type: SyntaxOptArgType::Flag,
-                        ..Default::default()
-                    };
+                    help_option = CommandSyntax::help_option();
 
                     Box::new(
                         syntax
@@ -363,19 +370,9 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
                     Box::new(syntax.parameters.iter())
                 };
 
-            let (arguments, options): (Vec<_>, Vec<_>) =
-                parameters_iter.partition(|arg| arg.is_positional());
-
-            if !arguments.is_empty() {
-                eprintln!("\n{}", "Arguments:".bold().underline());
-                if let Err(err) = self.print_syntax_column_help(&arguments) {
-                    omni_error!(err);
-                }
-            }
-
-            if !options.is_empty() {
-                eprintln!("\n{}", "Options:".bold().underline());
-                if let Err(err) = self.print_syntax_column_help(&options) {
+            for (section, params) in syntax.help_sections(parameters_iter) {
+                eprintln!("\n{}", format!("{section}:").bold().underline());
+                if let Err(err) = self.print_syntax_column_help(&params, Some(&syntax)) {
                     omni_error!(err);
                 }
             }
@@ -391,6 +388,8 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
     }
 
     fn print_error(&self, error_type: &str, error_msg: &str) {
+        let _colors = self.colors.scoped();
+
         omni_print!(format!(
             "{} {}",
             format!("{}:", error_type.red()),
@@ -400,11 +399,15 @@ impl HelpCommandPrinter for HelpCommandPlainPrinter {
 }
 
 impl HelpCommandPlainPrinter {
-    fn new() -> Self {
-        Self {}
+    fn new(colors: ColorCapability) -> Self {
+        Self { colors }
     }
 
-    fn print_syntax_column_help(&self, args: &[&SyntaxOptArg]) -> Result<(), String> {
+    fn print_syntax_column_help(
+        &self,
+        args: &[&SyntaxOptArg],
+        syntax: Option<&CommandSyntax>,
+    ) -> Result<(), String> {
         // Get the longest command so we know how to justify the help
         const MIN_LJUST: usize = 15;
         let abs_max_ljust = term_width() / 2;
@@ -424,7 +427,7 @@ impl HelpCommandPlainPrinter {
 
         // Print the help
         for arg in args.iter() {
-            let help_name = arg.help_name(true, true);
+            let help_name = arg.help_name(true, self.colors.enabled());
 
             let (print_name_on_same_line, wrap_threshold) = match strip_ansi_codes(&help_name).len()
             {
@@ -439,7 +442,11 @@ impl HelpCommandPlainPrinter {
                 .collect::<Vec<(String, usize)>>();
 
             // Prepare the help contents
-            let help_desc = wrap_text(&strip_colors_if_needed(arg.help_desc()), help_just);
+            let help_desc = match syntax {
+                Some(syntax) => syntax.parameter_help_desc(arg),
+                None => arg.help_desc(),
+            };
+            let help_desc = wrap_text(&self.colors.strip_if_disabled(help_desc), help_just);
             // Remove help_desc lines until we find the first non-empty line
             let help_desc = help_desc
                 .iter()
@@ -601,7 +608,10 @@ impl HelpCommandPlainPrinter {
                 .collect::<Vec<(String, usize)>>();
 
             // Prepare the help contents
-            let help_vec = wrap_text(&strip_colors_if_needed(command.help_short()), help_just);
+            let help_vec = wrap_text(
+                &self.colors.strip_if_disabled(command.help_short()),
+                help_just,
+            );
 
             // Prepare the help message to print for this command
             let empty_str = "".to_string();
@@ -691,6 +701,12 @@ struct SerializableCommandSyntax {
     name: String,
     #[serde(skip_serializing_if = "String::is_empty")]
     desc: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    section: Option<String>,
+    #[serde(skip_serializing_if = "SyntaxOptArgOrigin::is_command")]
+    origin: SyntaxOptArgOrigin,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -714,6 +730,9 @@ impl HelpCommandPrinter for HelpCommandJsonPrinter {
                 .map(|arg| SerializableCommandSyntax {
                     name: arg.help_name(true, false),
                     desc: strip_ansi_codes(&arg.help_desc()),
+                    examples: arg.examples.clone(),
+                    section: None,
+                    origin: arg.origin,
                 })
                 .collect(),
             subcommands,
@@ -736,14 +755,29 @@ impl HelpCommandPrinter for HelpCommandJsonPrinter {
         let mut arguments = vec![];
         let mut options = vec![];
         if let Some(syntax) = command.syntax() {
-            if !syntax.parameters.is_empty() {
-                for param in syntax.parameters.iter() {
-                    let name = param.help_name(true, false);
-                    let desc = strip_ansi_codes(&param.help_desc());
+            // Like in the plain help, the `-h` and `--help` options are
+            // listed when the command has the argparser enabled
+            let help_option = CommandSyntax::help_option();
+            let parameters = syntax
+                .parameters
+                .iter()
+                .chain(command.internal_argparser().then_some(&help_option));
 
-                    let serializable_syntax = SerializableCommandSyntax { name, desc };
+            for (section, params) in syntax.help_sections(parameters) {
+                let is_arguments = section == CommandSyntax::ARGUMENTS_SECTION;
+                for param in params {
+                    let name = param.help_name(true, false);
+                    let desc = strip_ansi_codes(&syntax.parameter_help_desc(param));
+
+                    let serializable_syntax = SerializableCommandSyntax {
+                        name,
+                        desc,
+                        examples: param.examples.clone(),
+                        section: (!is_arguments).then(|| section.clone()),
+                        origin: param.origin,
+                    };
 
-                    if param.is_positional() {
+                    if is_arguments {
                         arguments.push(serializable_syntax);
                     } else {
                         options.push(serializable_syntax);
@@ -855,7 +889,7 @@ impl HelpCommandOrganizer {
     fn new_from_commands(commands: Vec<Command>) -> Self {
         let mut metadata = Self::new();
 
-        for command in commands {
+        for command in commands.into_iter().filter(|command| !command.is_hidden()) {
             for command_name in command.all_names() {
                 metadata.add_command(command_name.clone(), &command);
             }
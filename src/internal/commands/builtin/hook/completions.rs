@@ -0,0 +1,463 @@
+use std::collections::BTreeMap;
+use std::os::unix::fs::PermissionsExt;
+use std::process::exit;
+use std::time::UNIX_EPOCH;
+
+use walkdir::WalkDir;
+
+use crate::internal::cache::CompletionsCache;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::command_loader;
+use crate::internal::commands::completion::dynamic_completion_script;
+use crate::internal::commands::path::omnipath;
+use crate::internal::commands::Command;
+use crate::internal::config::config_loader;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::env::workdir;
+use crate::internal::env::Shell;
+use crate::internal::user_interface::print::strip_ansi_codes;
+use crate::internal::user_interface::StringColor;
+use crate::omni_error;
+
+/// The fish function used in the conditions of the generated statements
+/// to check if the command line is calling a given omni command
+const FISH_USING_COMMAND_FUNCTION: &str = r#"function __omni_using_command
+    set -l tokens (commandline -opc)[2..-1]
+    test (count $tokens) -ge (count $argv); or return 1
+    test "$tokens[1..(count $argv)]" = "$argv"
+end
+"#;
+
+#[derive(Debug, Clone)]
+struct HookCompletionsCommandArgs {
+    shell: String,
+    dynamic: bool,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for HookCompletionsCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let shell = match args.get("shell") {
+            Some(ParseArgsValue::SingleString(Some(shell))) => shell.to_string(),
+            _ => unreachable!("no value for shell"),
+        };
+
+        let dynamic = matches!(
+            args.get("dynamic"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        Self { shell, dynamic }
+    }
+}
+
+/// A custom command for which to generate completions, with all the
+/// names it can be called by
+#[derive(Debug, Clone)]
+struct CompletionCommand {
+    names: Vec<Vec<String>>,
+    syntax: CommandSyntax,
+}
+
+#[derive(Debug, Clone)]
+pub struct HookCompletionsCommand {}
+
+impl HookCompletionsCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for HookCompletionsCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["hook".to_string(), "completions".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Hook to generate the completions of the custom commands\n",
+                "\n",
+                "The \x1B[1m\x1B[4mcompletions\x1B[0m hook generates, for the custom commands ",
+                "available from the current directory, the statements providing completions ",
+                "for their parameters as defined in their syntax. The output can be sourced ",
+                "directly, and is loaded automatically by the \x1B[1mfish\x1B[0m shell integration. ",
+                "For \x1B[1mbash\x1B[0m and \x1B[1mzsh\x1B[0m, the hook generates a completion ",
+                "script for all the commands, with their subcommands and parameters.\n",
+                "\n",
+                "With \x1B[1m--dynamic\x1B[0m, the hook instead generates a completion script ",
+                "calling omni on each completion to resolve the candidates from the commands ",
+                "available at that time, so that the completions never go stale.",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--dynamic".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Generate a script resolving the completions dynamically ",
+                            "instead of the static completions of the custom commands; ",
+                            "supports bash, zsh and fish."
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["shell".to_string()],
+                    desc: Some("Which shell to generate the completions for.".to_string()),
+                    required: true,
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "bash".to_string(),
+                        "zsh".to_string(),
+                        "fish".to_string(),
+                    ]),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = HookCompletionsCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        if args.dynamic {
+            match dynamic_completion_script(&Shell::from_str(&args.shell)) {
+                Some(script) => {
+                    print!("{script}");
+                    exit(0);
+                }
+                None => {
+                    omni_error!(
+                        format!(
+                            "invalid shell '{}', dynamic completions can only be generated for bash, zsh or fish",
+                            args.shell
+                        ),
+                        "hook completions"
+                    );
+                    exit(1);
+                }
+            }
+        }
+
+        if args.shell != "fish" {
+            match command_completions(&Shell::from_str(&args.shell)) {
+                Ok(script) => {
+                    print!("{script}");
+                    exit(0);
+                }
+                Err(err) => {
+                    omni_error!(err, "hook completions");
+                    exit(1);
+                }
+            }
+        }
+
+        // The fish completions are loaded each time the current directory
+        // changes, so they are cached for the work directory to avoid
+        // loading all the commands when nothing changed
+        let cache = CompletionsCache::get();
+        let location = workdir(".").root().unwrap_or_default().to_string();
+        let key = fish_completions_key(&config_loader(".").raw_config.as_yaml(), &omnipath());
+        if let Some(script) = cache.read(&location, &key) {
+            print!("{script}");
+            exit(0);
+        }
+
+        let commands = command_loader(".")
+            .commands
+            .iter()
+            .filter(|command| !matches!(command, Command::Builtin(_)) && is_completable(command))
+            .filter_map(|command| {
+                command.syntax().map(|syntax| CompletionCommand {
+                    names: command.all_names(),
+                    syntax,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let script = fish_completions(&commands);
+        cache.write(&location, &key, &script);
+        print!("{script}");
+        exit(0);
+    }
+}
+
+/// Whether the command is offered in the completions; hidden commands
+/// can still be called, but are not suggested
+fn is_completable(command: &Command) -> bool {
+    !matches!(command, Command::Void(_)) && !command.is_hidden()
+}
+
+/// Returns the key of the inputs of the fish completions: the custom
+/// commands, and thus their syntax, come from the configuration and from
+/// the files of the omnipath, which are identified by their metadata
+/// rather than read
+fn fish_completions_key(config: &str, omnipath: &[String]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(config.as_bytes());
+
+    for path in omnipath {
+        hasher.update(format!("{path}\n").as_bytes());
+
+        let entries = WalkDir::new(path)
+            .follow_links(true)
+            .sort_by_file_name()
+            .into_iter()
+            .flatten();
+        for entry in entries {
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+                .map(|mtime| mtime.as_nanos())
+                .unwrap_or_default();
+
+            hasher.update(
+                format!(
+                    "{}\0{}\0{}\0{:o}\n",
+                    entry.path().display(),
+                    metadata.len(),
+                    mtime,
+                    metadata.permissions().mode(),
+                )
+                .as_bytes(),
+            );
+        }
+    }
+
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Generates a completion script for all the commands available from
+/// the current directory, with their subcommands and parameters
+fn command_completions(shell: &Shell) -> Result<String, String> {
+    let commands = command_loader(".")
+        .commands
+        .iter()
+        .filter(|command| is_completable(command))
+        .map(|command| (command.all_names(), command.syntax().unwrap_or_default()))
+        .collect::<Vec<_>>();
+
+    CommandSyntax::completion_script(
+        shell,
+        "omni",
+        commands
+            .iter()
+            .flat_map(|(names, syntax)| names.iter().map(move |name| (name.clone(), syntax))),
+    )
+}
+
+/// Generates the fish `complete` statements for the parameters of the
+/// given commands, preceded by the function used in their conditions
+fn fish_completions(commands: &[CompletionCommand]) -> String {
+    let mut output = vec![
+        "# Completions for the parameters of the omni custom commands, as generated".to_string(),
+        "# by `omni hook completions fish`".to_string(),
+        FISH_USING_COMMAND_FUNCTION.to_string(),
+    ];
+
+    let all_names = commands
+        .iter()
+        .flat_map(|command| command.names.iter())
+        .collect::<Vec<_>>();
+
+    for command in commands {
+        let statements = fish_command_completions(command, &all_names);
+        if statements.is_empty() {
+            continue;
+        }
+
+        output.push(format!("# {}", command.names[0].join(" ")));
+        output.extend(statements);
+        output.push("".to_string());
+    }
+
+    output.join("\n")
+}
+
+fn fish_command_completions(
+    command: &CompletionCommand,
+    all_names: &[&Vec<String>],
+) -> Vec<String> {
+    // The condition matching the command, making sure that we are not
+    // calling one of its subcommands instead
+    let mut condition = command
+        .names
+        .iter()
+        .map(|name| fish_using_command(name))
+        .collect::<Vec<_>>()
+        .join("; or ");
+    for other in all_names {
+        let is_subcommand = command
+            .names
+            .iter()
+            .any(|name| other.len() > name.len() && other.starts_with(name));
+        if is_subcommand {
+            condition.push_str(&format!("; and not {}", fish_using_command(other)));
+        }
+    }
+
+    command
+        .syntax
+        .parameters
+        .iter()
+        .filter(|param| !param.hidden)
+        .filter_map(|param| fish_param_completion(param, &condition))
+        .collect()
+}
+
+fn fish_param_completion(param: &SyntaxOptArg, condition: &str) -> Option<String> {
+    let arg_type = param.arg_type();
+    let terminal_type = arg_type.terminal_type();
+    let is_path = matches!(
+        terminal_type,
+        SyntaxOptArgType::FilePath | SyntaxOptArgType::DirPath
+    );
+    let possible_values = arg_type.possible_values();
+
+    let mut condition = condition.to_string();
+    let mut parts = vec![];
+
+    if param.is_positional() {
+        // Positional parameters only have completions if they have
+        // a set of possible values or are paths
+        if is_path {
+            parts.push("-F".to_string());
+        } else if let Some(values) = &possible_values {
+            parts.push("-f".to_string());
+            parts.push(format!("-a {}", fish_quote(&fish_values(values))));
+        } else {
+            return None;
+        }
+    } else {
+        let mut names = vec![];
+        for name in param.all_names() {
+            if let Some(long) = name.strip_prefix("--") {
+                names.push(format!("-l {}", fish_quote(long)));
+            } else if let Some(short) = name.strip_prefix('-') {
+                match short.chars().count() {
+                    1 => names.push(format!("-s {}", fish_quote(short))),
+                    _ => names.push(format!("-o {}", fish_quote(short))),
+                }
+            }
+        }
+        if names.is_empty() {
+            return None;
+        }
+
+        // Do not suggest again the options that can only be provided once
+        if !param.is_repeatable() {
+            condition.push_str(&format!(
+                "; and not __fish_seen_argument {}",
+                names.join(" ")
+            ));
+        }
+
+        parts.extend(names);
+
+        if param.takes_value() {
+            if is_path {
+                parts.push("-r -F".to_string());
+            } else {
+                parts.push("-x".to_string());
+                if let Some(values) = &possible_values {
+                    parts.push(format!("-a {}", fish_quote(&fish_values(values))));
+                }
+            }
+        }
+    }
+
+    if let Some(desc) = &param.interpolated_desc() {
+        let desc = strip_ansi_codes(desc);
+        if let Some(desc) = desc.lines().map(str::trim).find(|line| !line.is_empty()) {
+            parts.push(format!("-d {}", fish_quote(desc)));
+        }
+    }
+
+    Some(format!(
+        "complete -c omni -n {} {}",
+        fish_quote(&condition),
+        parts.join(" ")
+    ))
+}
+
+fn fish_using_command(name: &[String]) -> String {
+    std::iter::once("__omni_using_command".to_string())
+        .chain(name.iter().map(|part| fish_escape(part)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the values as a list of fish tokens, to be used as the
+/// candidates of a completion
+fn fish_values(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| fish_escape(value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Escapes the special characters of the value so it is read by fish
+/// as a single token
+fn fish_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '\'' | '"' | '$' | '(' | ')' | '{' | '}' | '[' | ']' | '*' | '?' | '~' | '#'
+            | '&' | '|' | ';' | '<' | '>' | '%' | ' ' | '\t' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quotes the value for fish using single quotes, in which only the
+/// backslash and the single quote need to be escaped
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+#[path = "completions_test.rs"]
+mod tests;
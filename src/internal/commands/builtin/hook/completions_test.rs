@@ -0,0 +1,217 @@
+use super::*;
+
+fn names(names: &[&str]) -> Vec<Vec<String>> {
+    names
+        .iter()
+        .map(|name| name.split(' ').map(|part| part.to_string()).collect())
+        .collect()
+}
+
+fn arg(names: &[&str], arg_type: SyntaxOptArgType, desc: Option<&str>) -> SyntaxOptArg {
+    SyntaxOptArg {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        desc: desc.map(|desc| desc.to_string()),
+        arg_type,
+        ..Default::default()
+    }
+}
+
+fn representative_commands() -> Vec<CompletionCommand> {
+    vec![
+        CompletionCommand {
+            names: names(&["deploy", "dep"]),
+            syntax: CommandSyntax {
+                parameters: vec![
+                    arg(
+                        &["--env", "-e"],
+                        SyntaxOptArgType::Enum(vec!["prod".to_string(), "staging".to_string()]),
+                        Some("The environment to deploy to\nMore details on the next line"),
+                    ),
+                    arg(
+                        &["--config", "-c"],
+                        SyntaxOptArgType::FilePath,
+                        Some("Configuration file"),
+                    ),
+                    arg(&["--workdir"], SyntaxOptArgType::DirPath, None),
+                    arg(
+                        &["--tag"],
+                        SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                        Some("Tags to apply"),
+                    ),
+                    arg(&["--verbose", "-v"], SyntaxOptArgType::Counter, None),
+                    arg(&["--dry-run"], SyntaxOptArgType::Flag, Some("Do nothing")),
+                    arg(&["target"], SyntaxOptArgType::String, Some("Ignored")),
+                    arg(
+                        &["region"],
+                        SyntaxOptArgType::Enum(vec!["us east".to_string(), "eu".to_string()]),
+                        Some("The region"),
+                    ),
+                ],
+                ..Default::default()
+            },
+        },
+        CompletionCommand {
+            names: names(&["deploy rollback"]),
+            syntax: CommandSyntax {
+                parameters: vec![arg(&["file"], SyntaxOptArgType::FilePath, None)],
+                ..Default::default()
+            },
+        },
+        CompletionCommand {
+            names: names(&["lint"]),
+            syntax: CommandSyntax::default(),
+        },
+    ]
+}
+
+#[test]
+fn fish_completions_for_representative_syntax() {
+    let expected = [
+        "# Completions for the parameters of the omni custom commands, as generated",
+        "# by `omni hook completions fish`",
+        "function __omni_using_command",
+        "    set -l tokens (commandline -opc)[2..-1]",
+        "    test (count $tokens) -ge (count $argv); or return 1",
+        "    test \"$tokens[1..(count $argv)]\" = \"$argv\"",
+        "end",
+        "",
+        "# deploy",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback; and not __fish_seen_argument -l \\'env\\' -s \\'e\\'' -l 'env' -s 'e' -x -a 'prod staging' -d 'The environment to deploy to'",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback; and not __fish_seen_argument -l \\'config\\' -s \\'c\\'' -l 'config' -s 'c' -r -F -d 'Configuration file'",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback; and not __fish_seen_argument -l \\'workdir\\'' -l 'workdir' -r -F",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback' -l 'tag' -x -d 'Tags to apply'",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback' -l 'verbose' -s 'v'",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback; and not __fish_seen_argument -l \\'dry-run\\'' -l 'dry-run' -d 'Do nothing'",
+        "complete -c omni -n '__omni_using_command deploy; or __omni_using_command dep; and not __omni_using_command deploy rollback' -f -a 'us\\\\ east eu' -d 'The region'",
+        "",
+        "# deploy rollback",
+        "complete -c omni -n '__omni_using_command deploy rollback' -F",
+        "",
+    ]
+    .join("\n");
+
+    assert_eq!(fish_completions(&representative_commands()), expected);
+}
+
+#[test]
+fn fish_completions_without_commands() {
+    let output = fish_completions(&[]);
+    assert!(output.ends_with(FISH_USING_COMMAND_FUNCTION));
+    assert!(!output.contains("complete -c omni"));
+}
+
+#[test]
+fn fish_quote_hostile_description() {
+    let hostile = r#"it's a \'trap\' "$(rm -rf ~)"; echo `pwned` \"#;
+    let commands = vec![CompletionCommand {
+        names: names(&["it's"]),
+        syntax: CommandSyntax {
+            parameters: vec![arg(&["--flag"], SyntaxOptArgType::Flag, Some(hostile))],
+            ..Default::default()
+        },
+    }];
+
+    let output = fish_completions(&commands);
+    let statement = output
+        .lines()
+        .find(|line| line.starts_with("complete -c omni"))
+        .expect("should have a complete statement");
+
+    assert!(
+        statement.ends_with(r#" -d 'it\'s a \\\'trap\\\' "$(rm -rf ~)"; echo `pwned` \\'"#),
+        "unexpected statement: {statement}"
+    );
+    assert!(
+        statement.starts_with(r#"complete -c omni -n '__omni_using_command it\\\'s; "#),
+        "unexpected statement: {statement}"
+    );
+}
+
+#[test]
+fn fish_quote_roundtrip() {
+    // Undo the quoting the way fish reads single-quoted strings
+    fn unquote(quoted: &str) -> String {
+        let inner = &quoted[1..quoted.len() - 1];
+        let mut result = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(next @ ('\\' | '\'')) => result.push(next),
+                    Some(next) => {
+                        result.push('\\');
+                        result.push(next);
+                    }
+                    None => result.push('\\'),
+                },
+                '\'' => panic!("unescaped quote in {quoted}"),
+                _ => result.push(c),
+            }
+        }
+        result
+    }
+
+    for value in ["simple", "it's", r"back\slash", r"\'", r"trailing\", "'''"] {
+        assert_eq!(unquote(&fish_quote(value)), value);
+    }
+}
+
+mod fish_completions_key {
+    use super::*;
+
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    #[test]
+    fn changes_with_the_inputs() {
+        let tmpdir = tempfile::tempdir().expect("failed to create temporary directory");
+        let omnipath = vec![tmpdir.path().to_string_lossy().to_string()];
+        let command = tmpdir.path().join("deploy.sh");
+        std::fs::write(&command, "#!/bin/bash\n").expect("failed to write command");
+
+        let key = fish_completions_key("config", &omnipath);
+        assert_eq!(fish_completions_key("config", &omnipath), key);
+        assert_ne!(fish_completions_key("other config", &omnipath), key);
+        assert_ne!(fish_completions_key("config", &[]), key);
+
+        // A modified command might have a different syntax
+        std::fs::File::options()
+            .write(true)
+            .open(&command)
+            .expect("failed to open command")
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .expect("failed to set mtime");
+        let modified_key = fish_completions_key("config", &omnipath);
+        assert_ne!(modified_key, key);
+
+        // And a new command adds completions
+        std::fs::write(tmpdir.path().join("build.sh"), "#!/bin/bash\n")
+            .expect("failed to write command");
+        assert_ne!(fish_completions_key("config", &omnipath), modified_key);
+    }
+}
+
+mod is_completable {
+    use super::*;
+
+    use crate::internal::commands::fromconfig::ConfigCommand;
+    use crate::internal::config::CommandDefinition;
+
+    fn config_command(yaml: &str) -> Command {
+        let details: CommandDefinition =
+            serde_yaml::from_str(yaml).expect("failed to parse command definition");
+        Command::FromConfig(Box::new(ConfigCommand::new("test".to_string(), details)))
+    }
+
+    #[test]
+    fn visible_command() {
+        assert!(is_completable(&config_command("run: 'true'\n")));
+    }
+
+    #[test]
+    fn hidden_command() {
+        assert!(!is_completable(&config_command(
+            "run: 'true'\nhidden: true\n"
+        )));
+    }
+}
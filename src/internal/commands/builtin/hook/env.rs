@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::process::exit;
 
+use crate::internal::cache::WorkdirHistoryCache;
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::Command;
 use crate::internal::config::parser::ParseArgsValue;
@@ -144,6 +145,10 @@ impl BuiltinCommand for HookEnvCommand {
         let shell_type = &args.shell;
         match shell_type.dynenv_export_mode() {
             Some(export_mode) => {
+                // Keep track of the work directories entered from the shell,
+                // so that omni cd can navigate back to them
+                let _ = WorkdirHistoryCache::get().enter(".");
+
                 DynamicEnvExportOptions::new(export_mode)
                     .quiet(args.quiet)
                     .keep_shims(args.keep_shims)
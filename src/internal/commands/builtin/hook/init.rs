@@ -29,6 +29,7 @@ struct HookInitCommandArgs {
     shims: bool,
     keep_shims: bool,
     print_shims_path: bool,
+    cd_only: bool,
 }
 
 impl From<BTreeMap<String, ParseArgsValue>> for HookInitCommandArgs {
@@ -93,6 +94,11 @@ impl From<BTreeMap<String, ParseArgsValue>> for HookInitCommandArgs {
             Some(ParseArgsValue::SingleBoolean(Some(true)))
         );
 
+        let cd_only = matches!(
+            args.get("cd_only"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
         Self {
             shell,
             aliases,
@@ -100,6 +106,7 @@ impl From<BTreeMap<String, ParseArgsValue>> for HookInitCommandArgs {
             shims,
             keep_shims,
             print_shims_path,
+            cd_only,
         }
     }
 }
@@ -242,6 +249,20 @@ impl BuiltinCommand for HookInitCommand {
                     arg_type: SyntaxOptArgType::Flag,
                     ..Default::default()
                 },
+                SyntaxOptArg {
+                    names: vec!["--cd-only".to_string()],
+                    desc: Some(concat!(
+                        "Only load a minimal function wrapping the commands that change the ",
+                        "current directory, such as \x1B[1mcd\x1B[0m and \x1B[1mclone\x1B[0m, ",
+                        "without the dynamic environment, shims or autocompletion."
+                    ).to_string()),
+                    arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec![
+                        "--shims".to_string(),
+                        "--keep-shims-in-path".to_string(),
+                    ],
+                    ..Default::default()
+                },
                 SyntaxOptArg {
                     names: vec!["shell".to_string()],
                     desc: Some(
@@ -277,16 +298,24 @@ impl BuiltinCommand for HookInitCommand {
             exit(0);
         }
 
-        match args.shell.as_str() {
-            "bash" => dump_integration(
+        match (args.shell.as_str(), args.cd_only) {
+            ("bash" | "zsh", true) => dump_integration(
+                args,
+                include_bytes!("../../../../../templates/cd_integration.posix.tmpl"),
+            ),
+            ("fish", true) => dump_integration(
+                args,
+                include_bytes!("../../../../../templates/cd_integration.fish.tmpl"),
+            ),
+            ("bash", false) => dump_integration(
                 args,
                 include_bytes!("../../../../../templates/shell_integration.bash.tmpl"),
             ),
-            "zsh" => dump_integration(
+            ("zsh", false) => dump_integration(
                 args,
                 include_bytes!("../../../../../templates/shell_integration.zsh.tmpl"),
             ),
-            "fish" => dump_integration(
+            ("fish", false) => dump_integration(
                 args,
                 include_bytes!("../../../../../templates/shell_integration.fish.tmpl"),
             ),
@@ -306,8 +335,13 @@ impl BuiltinCommand for HookInitCommand {
 }
 
 fn dump_integration(args: HookInitCommandArgs, integration: &[u8]) {
-    let integration = String::from_utf8_lossy(integration).to_string();
+    let context = integration_context(&args);
+    let result = render_integration(integration, &context);
+
+    println!("{result}");
+}
 
+fn integration_context(args: &HookInitCommandArgs) -> Context {
     let mut context = Context::new();
     context.insert("OMNI_BIN", &escape(current_exe().to_string_lossy()));
     context.insert("OMNI_DATA_HOME", &escape(data_home().into()));
@@ -316,9 +350,15 @@ fn dump_integration(args: HookInitCommandArgs, integration: &[u8]) {
     context.insert("OMNI_COMMAND_ALIASES", &args.command_aliases);
     context.insert("SHIMS_ONLY", &args.shims);
     context.insert("KEEP_SHIMS", &args.keep_shims);
+    context
+}
 
-    let result = Tera::one_off(&integration, &context, false)
-        .expect("failed to render integration template");
+fn render_integration(integration: &[u8], context: &Context) -> String {
+    let integration = String::from_utf8_lossy(integration).to_string();
 
-    println!("{result}");
+    Tera::one_off(&integration, context, false).expect("failed to render integration template")
 }
+
+#[cfg(test)]
+#[path = "init_test.rs"]
+mod tests;
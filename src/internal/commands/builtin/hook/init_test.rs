@@ -0,0 +1,150 @@
+use super::*;
+
+fn cd_only_context(aliases: Vec<String>, command_aliases: Vec<InitHookAlias>) -> Context {
+    let mut context = Context::new();
+    context.insert("OMNI_BIN", "/opt/omni/bin/omni");
+    context.insert("OMNI_DATA_HOME", "/home/user/.local/share/omni");
+    context.insert("OMNI_SHIMS", "/home/user/.local/share/omni/shims");
+    context.insert("OMNI_ALIASES", &aliases);
+    context.insert("OMNI_COMMAND_ALIASES", &command_aliases);
+    context.insert("SHIMS_ONLY", &false);
+    context.insert("KEEP_SHIMS", &false);
+    context
+}
+
+const EXPECTED_POSIX: &str = r#"# This function wraps the omni command to handle the commands that need
+# to change the current working directory of the shell: omni only prints
+# the target directory, and the function performs the 'cd' itself. This
+# is a minimal alternative to the full shell integration, which means
+# that the dynamic environment, shims and autocompletion are not loaded
+function omni() {
+	case "$1" in
+		cd|clone)
+			local arg
+			for arg in "${@:2}"; do
+				case "${arg}" in
+					-h|--help|-l|--locate|-e|--edit|--history)
+						/opt/omni/bin/omni "$@"
+						return $?
+						;;
+				esac
+			done
+
+			local target
+			target=$(/opt/omni/bin/omni "$1" --print-target "${@:2}") || return $?
+			[[ -n "${target}" ]] || return 0
+			builtin cd "${target}"
+			;;
+		*)
+			/opt/omni/bin/omni "$@"
+			;;
+	esac
+}
+"#;
+
+const EXPECTED_FISH: &str = r#"# This function wraps the omni command to handle the commands that need
+# to change the current working directory of the shell: omni only prints
+# the target directory, and the function performs the 'cd' itself. This
+# is a minimal alternative to the full shell integration, which means
+# that the dynamic environment, shims and autocompletion are not loaded
+function omni
+    switch "$argv[1]"
+        case cd clone
+            for arg in $argv[2..-1]
+                switch "$arg"
+                    case -h --help -l --locate -e --edit --history
+                        /opt/omni/bin/omni $argv
+                        return $status
+                end
+            end
+
+            set -l target (/opt/omni/bin/omni $argv[1] --print-target $argv[2..-1])
+            or return $status
+            test -n "$target"; or return 0
+            builtin cd $target
+        case '*'
+            /opt/omni/bin/omni $argv
+    end
+end
+"#;
+
+mod render_integration {
+    use super::*;
+
+    #[test]
+    fn cd_only_posix() {
+        let context = cd_only_context(vec![], vec![]);
+        let result = render_integration(
+            include_bytes!("../../../../../templates/cd_integration.posix.tmpl"),
+            &context,
+        );
+
+        assert_eq!(result, EXPECTED_POSIX);
+    }
+
+    #[test]
+    fn cd_only_posix_with_aliases() {
+        let context = cd_only_context(
+            vec!["o".to_string()],
+            vec![InitHookAlias::new("oc".to_string(), "cd".to_string())],
+        );
+        let result = render_integration(
+            include_bytes!("../../../../../templates/cd_integration.posix.tmpl"),
+            &context,
+        );
+
+        let expected = format!(
+            "{}\n# Setup aliases for omni\nalias o=\"omni\"\nalias oc='omni cd'\n",
+            EXPECTED_POSIX
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn cd_only_fish() {
+        let context = cd_only_context(vec![], vec![]);
+        let result = render_integration(
+            include_bytes!("../../../../../templates/cd_integration.fish.tmpl"),
+            &context,
+        );
+
+        assert_eq!(result, EXPECTED_FISH);
+    }
+
+    #[test]
+    fn cd_only_fish_with_aliases() {
+        let context = cd_only_context(
+            vec!["o".to_string()],
+            vec![InitHookAlias::new("oc".to_string(), "cd".to_string())],
+        );
+        let result = render_integration(
+            include_bytes!("../../../../../templates/cd_integration.fish.tmpl"),
+            &context,
+        );
+
+        let expected = format!(
+            "{}\n# Setup aliases for omni\nalias o=\"omni\"\nalias oc='omni cd'\n",
+            EXPECTED_FISH
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn cd_only_passes_history_through() {
+        // The history is listed by omni itself, it must not be requested
+        // with `--print-target`, which conflicts with `--history`
+        let context = cd_only_context(vec![], vec![]);
+
+        let posix = render_integration(
+            include_bytes!("../../../../../templates/cd_integration.posix.tmpl"),
+            &context,
+        );
+        assert!(posix.contains("-h|--help|-l|--locate|-e|--edit|--history)"));
+
+        let fish = render_integration(
+            include_bytes!("../../../../../templates/cd_integration.fish.tmpl"),
+            &context,
+        );
+        assert!(fish.contains("case -h --help -l --locate -e --edit --history"));
+    }
+}
@@ -1,6 +1,9 @@
 pub(crate) mod base;
 pub(crate) use base::HookCommand;
 
+pub(crate) mod completions;
+pub(crate) use completions::HookCompletionsCommand;
+
 pub(crate) mod env;
 pub(crate) use env::HookEnvCommand;
 
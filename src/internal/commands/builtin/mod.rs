@@ -1,14 +1,27 @@
+pub(crate) mod cache;
+pub(crate) use cache::CachePinCommand;
+pub(crate) use cache::CacheStatsCommand;
+
 pub(crate) mod cd;
 pub(crate) use cd::CdCommand;
 
 pub(crate) mod clone;
 pub(crate) use clone::CloneCommand;
 
+pub(crate) mod doctor;
+pub(crate) use doctor::DoctorCommand;
+
+pub(crate) mod env;
+pub(crate) use env::EnvDiffCommand;
+pub(crate) use env::EnvExportCommand;
+pub(crate) use env::EnvImportCommand;
+
 pub(crate) mod help;
 pub(crate) use help::HelpCommand;
 
 pub(crate) mod hook;
 pub(crate) use hook::HookCommand;
+pub(crate) use hook::HookCompletionsCommand;
 pub(crate) use hook::HookEnvCommand;
 pub(crate) use hook::HookInitCommand;
 pub(crate) use hook::HookUuidCommand;
@@ -17,6 +30,8 @@ pub(crate) mod config;
 pub(crate) use config::config_bootstrap;
 pub(crate) use config::ConfigBootstrapCommand;
 pub(crate) use config::ConfigCheckCommand;
+pub(crate) use config::ConfigEditCommand;
+pub(crate) use config::ConfigManifestCommand;
 pub(crate) use config::ConfigPathSwitchCommand;
 pub(crate) use config::ConfigReshimCommand;
 pub(crate) use config::ConfigTrustCommand;
@@ -36,3 +51,6 @@ pub(crate) use tidy::TidyGitRepo;
 
 pub(crate) mod up;
 pub(crate) use up::UpCommand;
+
+pub(crate) mod with;
+pub(crate) use with::WithCommand;
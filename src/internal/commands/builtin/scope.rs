@@ -5,6 +5,7 @@ use crate::internal::commands::base::AutocompleteParameter;
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::base::CommandAutocompletion;
 use crate::internal::commands::command_loader;
+use crate::internal::commands::completion::emit_completion;
 use crate::internal::commands::utils::path_auto_complete;
 use crate::internal::commands::Command;
 use crate::internal::commands::HelpCommand;
@@ -275,7 +276,7 @@ impl BuiltinCommand for ScopeCommand {
 
                 path_auto_complete(repo, true, false)
                     .iter()
-                    .for_each(|s| println!("{s}"));
+                    .for_each(|s| emit_completion(s, None));
 
                 return Ok(());
             } else if param.name == "command" {
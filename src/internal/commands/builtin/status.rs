@@ -3,22 +3,29 @@ use std::collections::HashMap;
 use std::io::IsTerminal;
 use std::process::exit;
 
+use itertools::Itertools;
 use regex::Regex;
 
-use crate::internal::cache::utils::Empty;
+use crate::internal::cache::up_history::render_up_history_table;
+use crate::internal::cache::UpHistoryCache;
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::path::omnipath_entries;
+use crate::internal::commands::porcelain::PorcelainOutput;
+use crate::internal::commands::porcelain::PorcelainRecords;
 use crate::internal::commands::Command;
 use crate::internal::config::config;
 use crate::internal::config::config_loader;
 use crate::internal::config::parser::ParseArgsValue;
 use crate::internal::config::utils::sort_serde_yaml;
 use crate::internal::config::CommandSyntax;
+use crate::internal::config::ConfigProfileSelection;
 use crate::internal::config::SyntaxOptArg;
 use crate::internal::config::SyntaxOptArgType;
 use crate::internal::env::shell_integration_is_loaded;
 use crate::internal::git::ORG_LOADER;
 use crate::internal::user_interface::StringColor;
+use crate::internal::workdir;
+use crate::internal::workdir::workdir_short_ids;
 use crate::omni_error;
 use crate::omni_header;
 
@@ -32,6 +39,9 @@ struct StatusCommandArgs {
     sandbox: bool,
     orgs: bool,
     path: bool,
+    up_history: bool,
+    id: Option<String>,
+    porcelain: Option<PorcelainOutput>,
 }
 
 impl From<BTreeMap<String, ParseArgsValue>> for StatusCommandArgs {
@@ -44,6 +54,7 @@ impl From<BTreeMap<String, ParseArgsValue>> for StatusCommandArgs {
             "sandbox",
             "orgs",
             "path",
+            "up_history",
         ];
 
         let flag_values: HashMap<String, bool> = flags
@@ -69,6 +80,14 @@ impl From<BTreeMap<String, ParseArgsValue>> for StatusCommandArgs {
         let sandbox = *flag_values.get("sandbox").unwrap() || none_selected;
         let orgs = *flag_values.get("orgs").unwrap() || none_selected;
         let path = *flag_values.get("path").unwrap() || none_selected;
+        let up_history = *flag_values.get("up_history").unwrap();
+        let id = match args.get("id") {
+            Some(ParseArgsValue::SingleString(Some(id))) if !id.trim().is_empty() => {
+                Some(id.trim().to_string())
+            }
+            _ => None,
+        };
+        let porcelain = PorcelainOutput::from_args(&args);
 
         Self {
             single,
@@ -79,10 +98,153 @@ impl From<BTreeMap<String, ParseArgsValue>> for StatusCommandArgs {
             sandbox,
             orgs,
             path,
+            up_history,
+            id,
+            porcelain,
         }
     }
 }
 
+/// The status of omni for the sections that were requested, gathered
+/// once to be rendered either for humans or in the porcelain format;
+/// the sections that were not requested are `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct StatusReport {
+    shell_integration: Option<bool>,
+    config_files: Option<StatusConfigFiles>,
+    worktree: Option<String>,
+    sandbox: Option<String>,
+    orgs: Option<Vec<StatusOrg>>,
+    path: Option<Vec<StatusPathEntry>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct StatusConfigFiles {
+    files: Vec<String>,
+    profile: Option<(String, ConfigProfileSelection)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StatusOrg {
+    handle: String,
+    worktree: Option<String>,
+    trusted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StatusPathEntry {
+    path: String,
+    package: Option<String>,
+    full_path: String,
+}
+
+impl StatusReport {
+    fn gather(args: &StatusCommandArgs) -> Self {
+        let shell_integration = args.shell_integration.then(shell_integration_is_loaded);
+
+        let config_files = args.config_files.then(|| {
+            let config_loader = config_loader(".");
+            StatusConfigFiles {
+                files: config_loader.loaded_config_files.clone(),
+                profile: config_loader
+                    .profile
+                    .as_ref()
+                    .map(|profile| (profile.name.clone(), profile.selection.clone())),
+            }
+        });
+
+        let worktree = args.worktree.then(|| config(".").worktree());
+        let sandbox = args.sandbox.then(|| config(".").sandbox());
+
+        let orgs = args.orgs.then(|| {
+            ORG_LOADER
+                .printable_orgs()
+                .into_iter()
+                .map(|org| StatusOrg {
+                    handle: org.config.handle.to_string(),
+                    worktree: org.config.worktree.clone(),
+                    trusted: org.config.trusted,
+                })
+                .collect()
+        });
+
+        let path = args.path.then(|| {
+            omnipath_entries()
+                .into_iter()
+                .map(|entry| StatusPathEntry {
+                    path: entry.path,
+                    package: entry.package,
+                    full_path: entry.full_path,
+                })
+                .collect()
+        });
+
+        Self {
+            shell_integration,
+            config_files,
+            worktree,
+            sandbox,
+            orgs,
+            path,
+        }
+    }
+
+    /// Returns the records of the porcelain format; see the
+    /// documentation of the `status` command for their description
+    fn porcelain_records(&self) -> PorcelainRecords {
+        let mut records = PorcelainRecords::new();
+
+        if let Some(loaded) = self.shell_integration {
+            records.push(
+                "shell_integration",
+                if loaded { "loaded" } else { "not_loaded" },
+            );
+        }
+
+        if let Some(config_files) = &self.config_files {
+            records.push("config_file.count", config_files.files.len().to_string());
+            for (idx, file) in config_files.files.iter().enumerate() {
+                records.push(format!("config_file.{idx}"), file);
+            }
+
+            if let Some((name, selection)) = &config_files.profile {
+                records.push("profile.name", name);
+                let (selection, pattern) = match selection {
+                    ConfigProfileSelection::Env => ("env", None),
+                    ConfigProfileSelection::Workdir => ("workdir", None),
+                    ConfigProfileSelection::Path(pattern) => ("path", Some(pattern)),
+                    ConfigProfileSelection::Default => ("default", None),
+                };
+                records.push("profile.selection", selection);
+                records.push_opt("profile.pattern", pattern);
+            }
+        }
+
+        records.push_opt("worktree", self.worktree.as_ref());
+        records.push_opt("sandbox", self.sandbox.as_ref());
+
+        if let Some(orgs) = &self.orgs {
+            records.push("org.count", orgs.len().to_string());
+            for (idx, org) in orgs.iter().enumerate() {
+                records.push(format!("org.{idx}.handle"), &org.handle);
+                records.push_opt(format!("org.{idx}.worktree"), org.worktree.as_ref());
+                records.push_bool(format!("org.{idx}.trusted"), org.trusted);
+            }
+        }
+
+        if let Some(path) = &self.path {
+            records.push("path.count", path.len().to_string());
+            for (idx, entry) in path.iter().enumerate() {
+                records.push(format!("path.{idx}.path"), &entry.path);
+                records.push_opt(format!("path.{idx}.package"), entry.package.as_ref());
+                records.push(format!("path.{idx}.full_path"), &entry.full_path);
+            }
+        }
+
+        records
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StatusCommand {}
 
@@ -91,10 +253,11 @@ impl StatusCommand {
         Self {}
     }
 
-    fn print_shell_integration(&self, args: &StatusCommandArgs) {
-        if !args.shell_integration {
-            return;
-        }
+    fn print_shell_integration(&self, args: &StatusCommandArgs, report: &StatusReport) {
+        let loaded = match report.shell_integration {
+            Some(loaded) => loaded,
+            None => return,
+        };
 
         let prefix = if args.single {
             "".to_string()
@@ -103,7 +266,7 @@ impl StatusCommand {
             "  ".to_string()
         };
 
-        let status = if shell_integration_is_loaded() {
+        let status = if loaded {
             "loaded".light_green()
         } else {
             "not loaded".light_red()
@@ -125,6 +288,7 @@ impl StatusCommand {
             Ok(value) => {
                 let sorted_value = sort_serde_yaml(&value);
                 let yaml_code = serde_yaml::to_string(&sorted_value).unwrap();
+                let yaml_code = self.annotate_yaml(&yaml_code);
                 println!("{}", self.color_yaml(&yaml_code, args.single));
             }
             Err(err) => {
@@ -134,10 +298,11 @@ impl StatusCommand {
         }
     }
 
-    fn print_configuration_files(&self, args: &StatusCommandArgs) {
-        if !args.config_files {
-            return;
-        }
+    fn print_configuration_files(&self, args: &StatusCommandArgs, report: &StatusReport) {
+        let config_files = match &report.config_files {
+            Some(config_files) => config_files,
+            None => return,
+        };
 
         let prefix = if args.single {
             "".to_string()
@@ -146,21 +311,29 @@ impl StatusCommand {
             "  ".to_string()
         };
 
-        let config_loader = config_loader(".");
-
-        if config_loader.loaded_config_files.is_empty() {
+        if config_files.files.is_empty() {
             println!("{}{}", prefix, "none".light_red());
         } else {
-            for config_file in &config_loader.loaded_config_files {
+            for config_file in &config_files.files {
                 println!("{prefix}- {config_file}");
             }
         }
-    }
 
-    fn print_worktree(&self, args: &StatusCommandArgs) {
-        if !args.worktree {
-            return;
+        if let Some((name, selection)) = &config_files.profile {
+            println!(
+                "{}- profile {} {}",
+                prefix,
+                name.light_green(),
+                format!("({selection})").light_black(),
+            );
         }
+    }
+
+    fn print_worktree(&self, args: &StatusCommandArgs, report: &StatusReport) {
+        let worktree = match &report.worktree {
+            Some(worktree) => worktree,
+            None => return,
+        };
 
         let prefix = if args.single {
             "".to_string()
@@ -169,14 +342,14 @@ impl StatusCommand {
             "  ".to_string()
         };
 
-        let config = config(".");
-        println!("{}{}", prefix, config.worktree());
+        println!("{prefix}{worktree}");
     }
 
-    fn print_sandbox(&self, args: &StatusCommandArgs) {
-        if !args.sandbox {
-            return;
-        }
+    fn print_sandbox(&self, args: &StatusCommandArgs, report: &StatusReport) {
+        let sandbox = match &report.sandbox {
+            Some(sandbox) => sandbox,
+            None => return,
+        };
 
         let prefix = if args.single {
             "".to_string()
@@ -185,14 +358,14 @@ impl StatusCommand {
             "  ".to_string()
         };
 
-        let config = config(".");
-        println!("{}{}", prefix, config.sandbox());
+        println!("{prefix}{sandbox}");
     }
 
-    fn print_orgs(&self, args: &StatusCommandArgs) {
-        if !args.orgs {
-            return;
-        }
+    fn print_orgs(&self, args: &StatusCommandArgs, report: &StatusReport) {
+        let orgs = match &report.orgs {
+            Some(orgs) => orgs,
+            None => return,
+        };
 
         let prefix = if args.single {
             "".to_string()
@@ -201,16 +374,16 @@ impl StatusCommand {
             "  ".to_string()
         };
 
-        if ORG_LOADER.is_empty() {
+        if orgs.is_empty() {
             println!("{}{}", prefix, "none".light_red());
         } else {
-            for org in ORG_LOADER.printable_orgs() {
-                let mut org_str = org.config.handle.to_string();
-                if let Some(worktree) = &org.config.worktree {
+            for org in orgs {
+                let mut org_str = org.handle.to_string();
+                if let Some(worktree) = &org.worktree {
                     org_str.push_str(&format!(" ({worktree})").light_black());
                 }
 
-                if org.config.trusted {
+                if org.trusted {
                     org_str.push_str(&format!(" {}", "trusted").light_green().italic());
                 } else {
                     org_str.push_str(&format!(" {}", "untrusted").light_red().italic());
@@ -221,10 +394,11 @@ impl StatusCommand {
         }
     }
 
-    fn print_path(&self, args: &StatusCommandArgs) {
-        if !args.path {
-            return;
-        }
+    fn print_path(&self, args: &StatusCommandArgs, report: &StatusReport) {
+        let omnipath = match &report.path {
+            Some(omnipath) => omnipath,
+            None => return,
+        };
 
         let prefix = if args.single {
             "".to_string()
@@ -233,11 +407,10 @@ impl StatusCommand {
             "  ".to_string()
         };
 
-        let omnipath = omnipath_entries();
         if omnipath.is_empty() {
             println!("{}{}", prefix, "none".light_red());
         } else {
-            for path in &omnipath {
+            for path in omnipath {
                 if let Some(package) = &path.package {
                     let mut pkg_string = format!("{} {}", "package:".light_cyan(), package);
                     if !path.path.is_empty() {
@@ -257,6 +430,78 @@ impl StatusCommand {
         }
     }
 
+    fn print_up_history(&self, args: &StatusCommandArgs) {
+        if !args.up_history {
+            return;
+        }
+
+        let prefix = if args.single {
+            "".to_string()
+        } else {
+            println!("\n{}", "Up history".bold());
+            "  ".to_string()
+        };
+
+        let workdir_id = match &args.id {
+            Some(id) => match workdir_short_ids().resolve(id) {
+                Ok(workdir_id) => workdir_id,
+                Err(err) => {
+                    println!("{}{}", prefix, err.to_string().light_red());
+                    return;
+                }
+            },
+            None => match workdir(".").id() {
+                Some(workdir_id) => workdir_id,
+                None => {
+                    println!("{}{}", prefix, "not in a work directory".light_red());
+                    return;
+                }
+            },
+        };
+
+        let history = UpHistoryCache::get().list(&workdir_id);
+        let latest = match history.first() {
+            Some(latest) => latest,
+            None => {
+                println!("{}{}", prefix, "none".light_red());
+                return;
+            }
+        };
+
+        for line in render_up_history_table(&history).lines() {
+            println!("{prefix}{line}");
+        }
+
+        println!("\n{}{}", prefix, "Latest run".underline());
+        for line in latest.render_details().lines() {
+            println!("{prefix}{line}");
+        }
+    }
+
+    /// Annotates the top-level keys of the configuration whose values
+    /// were provided by the active profile
+    fn annotate_yaml(&self, yaml_code: &str) -> String {
+        let config_loader = config_loader(".");
+        if config_loader.profile.is_none() {
+            return yaml_code.to_string();
+        }
+
+        yaml_code
+            .lines()
+            .map(|line| {
+                let key = match line.split_once(':') {
+                    Some((key, _)) if !key.starts_with([' ', '-', '#']) => key,
+                    _ => return line.to_string(),
+                };
+
+                match config_loader.attribution(key) {
+                    Some(attribution) => format!("{line}  # {attribution}"),
+                    None => line.to_string(),
+                }
+            })
+            .join("\n")
+    }
+
     fn color_yaml(&self, yaml_code: &str, single: bool) -> String {
         let yaml_lines = &mut yaml_code.lines().collect::<Vec<&str>>();
         if yaml_lines[0] == "---" {
@@ -341,6 +586,7 @@ impl BuiltinCommand for StatusCommand {
                             .to_string(),
                     ),
                     arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec!["porcelain".to_string()],
                     ..Default::default()
                 },
                 SyntaxOptArg {
@@ -385,7 +631,29 @@ impl BuiltinCommand for StatusCommand {
                     arg_type: SyntaxOptArgType::Flag,
                     ..Default::default()
                 },
-            ],
+                SyntaxOptArg {
+                    names: vec!["--up-history".to_string()],
+                    desc: Some(
+                        "Show the history of the omni up runs for the current work directory. This is not shown by default."
+                            .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    conflicts_with: vec!["porcelain".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--id".to_string()],
+                    desc: Some(
+                        "The id of the work directory to show the up history of instead of the current one, in its full or short form."
+                            .to_string(),
+                    ),
+                    requires: vec!["up_history".to_string()],
+                    ..Default::default()
+                },
+            ]
+            .into_iter()
+            .chain(PorcelainOutput::parameters())
+            .collect(),
             ..Default::default()
         })
     }
@@ -402,18 +670,30 @@ impl BuiltinCommand for StatusCommand {
                 .expect("should have args to parse"),
         );
 
+        let report = StatusReport::gather(&args);
+
+        if let Some(porcelain) = &args.porcelain {
+            print!("{}", porcelain.render(&report.porcelain_records()));
+            exit(0);
+        }
+
         if !args.single {
             println!("{}", omni_header!());
         }
 
-        self.print_shell_integration(&args);
+        self.print_shell_integration(&args, &report);
         self.print_configuration(&args);
-        self.print_configuration_files(&args);
-        self.print_worktree(&args);
-        self.print_sandbox(&args);
-        self.print_orgs(&args);
-        self.print_path(&args);
+        self.print_configuration_files(&args, &report);
+        self.print_worktree(&args, &report);
+        self.print_sandbox(&args, &report);
+        self.print_orgs(&args, &report);
+        self.print_path(&args, &report);
+        self.print_up_history(&args);
 
         exit(0);
     }
 }
+
+#[cfg(test)]
+#[path = "status_test.rs"]
+mod tests;
@@ -0,0 +1,204 @@
+use super::*;
+
+use crate::internal::commands::porcelain::PorcelainVersion;
+
+fn fixture_report() -> StatusReport {
+    StatusReport {
+        shell_integration: Some(true),
+        config_files: Some(StatusConfigFiles {
+            files: vec![
+                "/home/user/.config/omni/config.yaml".to_string(),
+                "/home/user/git/project/.omni.yaml".to_string(),
+            ],
+            profile: Some((
+                "work".to_string(),
+                ConfigProfileSelection::Path("~/git/work/*".to_string()),
+            )),
+        }),
+        worktree: Some("/home/user/git".to_string()),
+        sandbox: Some("/home/user/sandbox".to_string()),
+        orgs: Some(vec![
+            StatusOrg {
+                handle: "github.com/xaf".to_string(),
+                worktree: Some("/home/user/xaf".to_string()),
+                trusted: true,
+            },
+            StatusOrg {
+                handle: "github.com/other".to_string(),
+                worktree: None,
+                trusted: false,
+            },
+        ]),
+        path: Some(vec![
+            StatusPathEntry {
+                path: "/home/user/bin".to_string(),
+                package: None,
+                full_path: "/home/user/bin".to_string(),
+            },
+            StatusPathEntry {
+                path: "commands".to_string(),
+                package: Some("github.com/xaf/omni".to_string()),
+                full_path: "/home/user/.omni/packages/github.com/xaf/omni/commands".to_string(),
+            },
+        ]),
+    }
+}
+
+fn v1(nul: bool) -> PorcelainOutput {
+    PorcelainOutput {
+        version: PorcelainVersion::V1,
+        nul,
+    }
+}
+
+mod porcelain {
+    use super::*;
+
+    #[test]
+    fn v1_contract() {
+        assert_eq!(
+            v1(false).render(&fixture_report().porcelain_records()),
+            concat!(
+                "porcelain\tv1\n",
+                "shell_integration\tloaded\n",
+                "config_file.count\t2\n",
+                "config_file.0\t/home/user/.config/omni/config.yaml\n",
+                "config_file.1\t/home/user/git/project/.omni.yaml\n",
+                "profile.name\twork\n",
+                "profile.selection\tpath\n",
+                "profile.pattern\t~/git/work/*\n",
+                "worktree\t/home/user/git\n",
+                "sandbox\t/home/user/sandbox\n",
+                "org.count\t2\n",
+                "org.0.handle\tgithub.com/xaf\n",
+                "org.0.worktree\t/home/user/xaf\n",
+                "org.0.trusted\ttrue\n",
+                "org.1.handle\tgithub.com/other\n",
+                "org.1.trusted\tfalse\n",
+                "path.count\t2\n",
+                "path.0.path\t/home/user/bin\n",
+                "path.0.full_path\t/home/user/bin\n",
+                "path.1.path\tcommands\n",
+                "path.1.package\tgithub.com/xaf/omni\n",
+                "path.1.full_path\t/home/user/.omni/packages/github.com/xaf/omni/commands\n",
+            )
+        );
+    }
+
+    #[test]
+    fn v1_contract_nul() {
+        let report = StatusReport {
+            worktree: Some("/home/user/my git".to_string()),
+            path: Some(vec![StatusPathEntry {
+                path: "/home/user/new\nline".to_string(),
+                package: None,
+                full_path: "/home/user/new\nline".to_string(),
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            v1(true).render(&report.porcelain_records()),
+            concat!(
+                "porcelain\tv1\0",
+                "worktree\t/home/user/my git\0",
+                "path.count\t1\0",
+                "path.0.path\t/home/user/new\nline\0",
+                "path.0.full_path\t/home/user/new\nline\0",
+            )
+        );
+    }
+
+    #[test]
+    fn v1_contract_empty_sections() {
+        let report = StatusReport {
+            shell_integration: Some(false),
+            config_files: Some(StatusConfigFiles::default()),
+            orgs: Some(vec![]),
+            path: Some(vec![]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            v1(false).render(&report.porcelain_records()),
+            concat!(
+                "porcelain\tv1\n",
+                "shell_integration\tnot_loaded\n",
+                "config_file.count\t0\n",
+                "org.count\t0\n",
+                "path.count\t0\n",
+            )
+        );
+    }
+
+    #[test]
+    fn only_requested_sections() {
+        let report = StatusReport {
+            sandbox: Some("/home/user/sandbox".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            v1(false).render(&report.porcelain_records()),
+            "porcelain\tv1\nsandbox\t/home/user/sandbox\n"
+        );
+    }
+}
+
+mod syntax {
+    use super::*;
+
+    fn parse(argv: &[&str]) -> Result<BTreeMap<String, ParseArgsValue>, String> {
+        StatusCommand::new()
+            .syntax()
+            .expect("should have a syntax")
+            .parse_args_typed(
+                argv.iter().map(|arg| arg.to_string()).collect(),
+                vec!["status".to_string()],
+            )
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn porcelain_default_version() {
+        let args = StatusCommandArgs::from(parse(&["--porcelain"]).expect("should parse"));
+        assert_eq!(args.porcelain, Some(v1(false)));
+    }
+
+    #[test]
+    fn porcelain_explicit_version_and_nul() {
+        let args = StatusCommandArgs::from(parse(&["--porcelain=v1", "-z"]).expect("should parse"));
+        assert_eq!(args.porcelain, Some(v1(true)));
+    }
+
+    #[test]
+    fn porcelain_with_section() {
+        let args =
+            StatusCommandArgs::from(parse(&["--porcelain", "--worktree"]).expect("should parse"));
+        assert_eq!(args.porcelain, Some(v1(false)));
+        assert!(args.worktree);
+        assert!(!args.orgs);
+    }
+
+    #[test]
+    fn not_porcelain() {
+        let args = StatusCommandArgs::from(parse(&[]).expect("should parse"));
+        assert_eq!(args.porcelain, None);
+    }
+
+    #[test]
+    fn unknown_version() {
+        assert!(parse(&["--porcelain=v0"]).is_err());
+    }
+
+    #[test]
+    fn nul_requires_porcelain() {
+        assert!(parse(&["-z"]).is_err());
+    }
+
+    #[test]
+    fn conflicts_with_config() {
+        assert!(parse(&["--porcelain", "--config"]).is_err());
+        assert!(parse(&["--porcelain", "--up-history"]).is_err());
+    }
+}
@@ -1,9 +1,16 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs::OpenOptions;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::exit;
+use std::process::Command as StdCommand;
+use std::process::Stdio;
 use std::str::FromStr;
 
 use blake3::Hasher as Blake3Hasher;
@@ -13,11 +20,16 @@ use imara_diff::Algorithm;
 use imara_diff::UnifiedDiffBuilder;
 use once_cell::sync::OnceCell;
 use serde::Serialize;
+use time::OffsetDateTime;
 use tokio::process::Command as TokioCommand;
 
+use crate::internal::cache::up_environments::UpEnvVar;
 use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::cache::up_history::format_time_ago;
+use crate::internal::cache::up_history::UpHistoryOutcome;
 use crate::internal::cache::utils::Empty;
 use crate::internal::cache::PromptsCache;
+use crate::internal::cache::UpHistoryCache;
 use crate::internal::cache::WorkdirsCache;
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::Command;
@@ -25,6 +37,7 @@ use crate::internal::config::config;
 use crate::internal::config::flush_config;
 use crate::internal::config::global_config;
 use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::up::selection::select_operations;
 use crate::internal::config::up::utils::run_progress;
 use crate::internal::config::up::utils::PrintProgressHandler;
 use crate::internal::config::up::utils::ProgressHandler;
@@ -34,7 +47,14 @@ use crate::internal::config::up::utils::SyncUpdateInit;
 use crate::internal::config::up::utils::SyncUpdateInitOption;
 use crate::internal::config::up::utils::SyncUpdateListener;
 use crate::internal::config::up::utils::SyncUpdateOperation;
+use crate::internal::config::up::utils::UpEventWriter;
+use crate::internal::config::up::utils::UpSummaryWriter;
+use crate::internal::config::up::workspace::run_workspace;
+use crate::internal::config::up::workspace::workspace_repos;
+use crate::internal::config::up::workspace::UpWorkspacePlan;
 use crate::internal::config::up::UpConfig;
+use crate::internal::config::up::UpConfigTool;
+use crate::internal::config::up::UpOperationSelection;
 use crate::internal::config::up::UpOptions;
 use crate::internal::config::CommandSyntax;
 use crate::internal::config::ConfigExtendOptions;
@@ -46,6 +66,7 @@ use crate::internal::config::SyntaxOptArgType;
 use crate::internal::env::now as omni_now;
 use crate::internal::env::shell_is_interactive;
 use crate::internal::errors::SyncUpdateError;
+use crate::internal::git::forget_repo_lookups;
 use crate::internal::git::format_path_with_template;
 use crate::internal::git::package_path_from_git_url;
 use crate::internal::git::path_entry_config;
@@ -54,9 +75,11 @@ use crate::internal::git::GitRepoUpdater;
 use crate::internal::git::ORG_LOADER;
 use crate::internal::git_env;
 use crate::internal::git_env_fresh;
+use crate::internal::user_interface::colors::strip_colors;
 use crate::internal::user_interface::StringColor;
 use crate::internal::workdir;
 use crate::internal::workdir::add_trust;
+use crate::internal::workdir::display_workdir_id;
 use crate::internal::workdir::is_trusted_or_ask;
 use crate::internal::workdir_or_init;
 use crate::omni_error;
@@ -68,13 +91,21 @@ struct UpCommandArgs {
     cache_enabled: bool,
     clone_suggested: UpCommandArgsCloneSuggestedOptions,
     fail_on_upgrade: bool,
+    jobs: Option<usize>,
+    no_wait: bool,
+    only: Option<Vec<String>>,
+    output: UpCommandArgsOutputOptions,
+    output_fd: Option<i64>,
     prompt: bool,
     prompt_all: bool,
     prompt_ids: HashSet<String>,
+    strict: bool,
+    summary_file: Option<PathBuf>,
     trust: UpCommandArgsTrustOptions,
     update_repository: bool,
     update_user_config: UpCommandArgsUpdateUserConfigOptions,
     upgrade: bool,
+    workspace: Vec<PathBuf>,
 }
 
 impl From<BTreeMap<String, ParseArgsValue>> for UpCommandArgs {
@@ -103,6 +134,44 @@ impl From<BTreeMap<String, ParseArgsValue>> for UpCommandArgs {
             _ => UpCommandArgsCloneSuggestedOptions::Unset,
         };
 
+        let jobs = match args.get("jobs") {
+            Some(ParseArgsValue::SingleInteger(Some(jobs))) if *jobs > 0 => Some(*jobs as usize),
+            _ => None,
+        };
+
+        let no_wait = matches!(
+            args.get("no_wait"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        // When provided without any value, `--only` gets a single empty
+        // value, which allows to distinguish it from not being provided
+        let only = match args.get("only") {
+            Some(ParseArgsValue::ManyString(values)) if !values.is_empty() => Some(
+                values
+                    .iter()
+                    .flatten()
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        let output = match args.get("output") {
+            Some(ParseArgsValue::SingleString(Some(output))) => output
+                .to_lowercase()
+                .parse::<UpCommandArgsOutputOptions>()
+                .unwrap(),
+            _ => UpCommandArgsOutputOptions::Text,
+        };
+
+        let output_fd = match args.get("output_fd") {
+            Some(ParseArgsValue::SingleInteger(output_fd)) => *output_fd,
+            _ => None,
+        };
+
         let mut prompt = bootstrap;
         let mut prompt_ids = HashSet::new();
         let prompt_all = matches!(
@@ -128,6 +197,18 @@ impl From<BTreeMap<String, ParseArgsValue>> for UpCommandArgs {
             prompt = !prompt_ids.is_empty();
         }
 
+        let strict = matches!(
+            args.get("strict"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        let summary_file = match args.get("summary_file") {
+            Some(ParseArgsValue::SingleString(Some(summary_file))) => {
+                Some(PathBuf::from(summary_file))
+            }
+            _ => None,
+        };
+
         let trust = match args.get("trust") {
             Some(ParseArgsValue::SingleString(Some(trust))) => trust
                 .to_lowercase()
@@ -155,17 +236,35 @@ impl From<BTreeMap<String, ParseArgsValue>> for UpCommandArgs {
             Some(ParseArgsValue::SingleBoolean(Some(true)))
         );
 
+        let workspace = match args.get("workspace") {
+            Some(ParseArgsValue::ManyString(values)) => values
+                .iter()
+                .flatten()
+                .filter(|value| !value.trim().is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            _ => vec![],
+        };
+
         Self {
             cache_enabled: !no_cache,
             clone_suggested,
             fail_on_upgrade,
+            jobs,
+            no_wait,
+            only,
+            output,
+            output_fd,
             prompt,
             prompt_all,
             prompt_ids,
+            strict,
+            summary_file,
             trust,
             update_repository,
             update_user_config,
             upgrade,
+            workspace,
         }
     }
 }
@@ -204,6 +303,24 @@ impl UpCommandArgsCloneSuggestedOptions {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+enum UpCommandArgsOutputOptions {
+    Text,
+    Json,
+}
+
+impl FromStr for UpCommandArgsOutputOptions {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum UpCommandArgsTrustOptions {
     Always,
@@ -293,6 +410,176 @@ impl UpCommand {
         self.subcommand() == "down"
     }
 
+    /// Returns the writer for the events, if requested, or the exit code
+    /// if the output cannot be opened
+    fn events_writer(&self) -> Result<Option<UpEventWriter>, i32> {
+        if self.cli_args().output != UpCommandArgsOutputOptions::Json {
+            return Ok(None);
+        }
+
+        let output: Box<dyn Write + Send> = match self.cli_args().output_fd {
+            Some(fd) => {
+                let fd_path = format!("/dev/fd/{fd}");
+                match OpenOptions::new().write(true).open(&fd_path) {
+                    Ok(file) => Box::new(file),
+                    Err(err) => {
+                        omni_error!(format!("failed to open output file descriptor {fd}: {err}"));
+                        return Err(1);
+                    }
+                }
+            }
+            None => Box::new(std::io::stdout()),
+        };
+
+        Ok(Some(UpEventWriter::new(output)))
+    }
+
+    /// Returns the arguments to pass to the `omni up` commands run for
+    /// each of the repositories of a workspace
+    fn workspace_repo_args(&self) -> Vec<String> {
+        let cli_args = self.cli_args();
+        let mut args = vec![];
+
+        if !cli_args.cache_enabled {
+            args.push("--no-cache".to_string());
+        }
+        if cli_args.fail_on_upgrade {
+            args.push("--fail-on-upgrade".to_string());
+        }
+        if cli_args.no_wait {
+            args.push("--no-wait".to_string());
+        }
+        if cli_args.strict {
+            args.push("--strict".to_string());
+        }
+        if cli_args.upgrade {
+            args.push("--upgrade".to_string());
+        }
+        match cli_args.trust {
+            UpCommandArgsTrustOptions::Always => args.push("--trust=always".to_string()),
+            UpCommandArgsTrustOptions::Yes => args.push("--trust=yes".to_string()),
+            UpCommandArgsTrustOptions::No => args.push("--trust=no".to_string()),
+            UpCommandArgsTrustOptions::Check => {}
+        }
+
+        args
+    }
+
+    /// Sets up all the repositories of the workspace, in parallel, with
+    /// the tool installations shared between repositories provisioned
+    /// only once, and summarizes the results
+    fn exec_workspace(&self) -> ! {
+        if !self.is_up() {
+            omni_error!(format!(
+                "{} is only supported by {}",
+                "--workspace".light_yellow(),
+                "omni up".light_yellow(),
+            ));
+            exit(1);
+        }
+
+        let repos = workspace_repos(&self.cli_args().workspace);
+        if repos.is_empty() {
+            omni_info!(format!(
+                "No repository with an {} configuration found, nothing to do.",
+                "up".italic(),
+            ));
+            exit(0);
+        }
+
+        let plan = UpWorkspacePlan::new(repos);
+        let jobs = self
+            .cli_args()
+            .jobs
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |jobs| jobs.get()));
+
+        omni_info!(format!(
+            "setting up {} repositories, {} at a time",
+            plan.repos.len().to_string().light_yellow(),
+            jobs.to_string().light_yellow(),
+        ));
+        for (install, provider, users) in plan.shared_installs() {
+            omni_info!(format!(
+                "{} is shared by {} repositories, provisioned with {}",
+                install.light_yellow(),
+                users,
+                provider.display().to_string().light_cyan(),
+            ));
+        }
+
+        let current_exe = match std::env::current_exe() {
+            Ok(current_exe) => current_exe,
+            Err(err) => {
+                omni_error!(format!("failed to get current executable path: {err}"));
+                exit(1);
+            }
+        };
+        let repo_args = self.workspace_repo_args();
+
+        let results = run_workspace(&plan, jobs, |repo| {
+            omni_info!(format!(
+                "running {} in {}",
+                "omni up".light_yellow(),
+                repo.path.display().to_string().light_cyan(),
+            ));
+
+            let output = StdCommand::new(&current_exe)
+                .arg("up")
+                .args(&repo_args)
+                .current_dir(&repo.path)
+                .env_remove("OMNI_FORCE_UPDATE")
+                .env("OMNI_SKIP_UPDATE", "1")
+                .stdin(Stdio::null())
+                .output()
+                .map_err(|err| format!("failed to spawn process: {err}"))?;
+
+            if output.status.success() {
+                return Ok(());
+            }
+
+            // Report the last error line of the command, if any
+            let stderr = strip_colors(String::from_utf8_lossy(&output.stderr));
+            match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+                Some(line) => {
+                    // Remove the `omni: up command failed:` prefix of the error
+                    let line = line.trim();
+                    let message = line
+                        .strip_prefix("omni:")
+                        .and_then(|rest| rest.split_once(" failed: "))
+                        .map_or(line, |(_, message)| message);
+                    Err(message.to_string())
+                }
+                None => Err("omni up failed".to_string()),
+            }
+        });
+
+        let mut failed = 0;
+        for result in &results {
+            let path = result.path.display().to_string().light_cyan();
+            let duration = format!("({:.1}s)", result.duration.as_secs_f64()).light_black();
+            match &result.result {
+                Ok(()) => {
+                    omni_info!(format!("{} {} {}", "✔".green(), path, duration));
+                }
+                Err(err) => {
+                    failed += 1;
+                    omni_error!(format!("{} {} {}: {}", "✖".red(), path, duration, err));
+                }
+            }
+        }
+
+        if failed > 0 {
+            omni_error!(format!(
+                "failed to set up {} out of {} repositories",
+                failed,
+                results.len(),
+            ));
+            exit(1);
+        }
+
+        exit(0);
+    }
+
     fn trust(&self) -> bool {
         *self.trust.get_or_init(|| {
             match self.cli_args().trust {
@@ -351,6 +638,50 @@ impl UpCommand {
         true
     }
 
+    /// Returns the indices of the operations to run when using `--only`,
+    /// asking the user to pick them if none were provided, or the exit
+    /// code if no operations could be selected
+    fn only_operations(&self, up_config: &UpConfig) -> Result<Option<BTreeSet<usize>>, i32> {
+        let values = match self.cli_args().only.as_ref() {
+            Some(values) => values,
+            None => return Ok(None),
+        };
+        let steps = up_config.available_steps();
+
+        let selection = if !values.is_empty() {
+            UpOperationSelection::Requested(values.clone())
+        } else if shell_is_interactive() {
+            match pick_operations(&steps) {
+                Some(picked) => UpOperationSelection::Picked(picked),
+                None => return Err(0),
+            }
+        } else {
+            omni_error!(format!(
+                "{} requires operation names or indices when not running interactively",
+                "--only".light_yellow(),
+            ));
+            return Err(1);
+        };
+
+        let selected = match select_operations(&steps, &selection) {
+            Ok(selected) => selected,
+            Err(err) => {
+                omni_error!(err.message());
+                return Err(1);
+            }
+        };
+
+        for included in selected.included.iter() {
+            omni_info!(format!(
+                "including operation {}, required by {}",
+                included.name.light_yellow(),
+                included.required_by.join(", ").light_yellow(),
+            ));
+        }
+
+        Ok(Some(selected.indices))
+    }
+
     fn should_suggest_config(&self) -> bool {
         self.cli_args().update_user_config.should_suggest()
     }
@@ -771,7 +1102,12 @@ impl UpCommand {
             };
             let progress_handler: Option<&dyn ProgressHandler> = Some(progress_handler.as_ref());
 
-            let mut cmd_args = vec!["git".to_string(), "clone".to_string()];
+            let transport = global_config()
+                .git_transport
+                .options_for_url(&repo.clone_url);
+
+            let mut cmd_args = vec!["git".to_string()];
+            cmd_args.extend(transport.git_args("clone"));
             cmd_args.push(repo.clone_url.to_string());
             let repo_clone_path = if repo.clone_as_package {
                 if let Some(package_path) = &repo.package_path {
@@ -791,6 +1127,7 @@ impl UpCommand {
 
             let mut cmd = TokioCommand::new(&cmd_args[0]);
             cmd.args(&cmd_args[1..]);
+            cmd.envs(transport.env());
             cmd.stdout(std::process::Stdio::piped());
             cmd.stderr(std::process::Stdio::piped());
 
@@ -801,6 +1138,7 @@ impl UpCommand {
                 }
 
                 cloned.insert(repo.clone());
+                forget_repo_lookups();
 
                 let new_to_clone =
                     self.suggest_clone_from_config(repo_clone_path.to_str().unwrap());
@@ -1122,7 +1460,7 @@ impl UpCommand {
                 self.handle_sync_operation(
                     SyncUpdateOperation::OmniInfo(format!(
                         "configuration suggestions for {} have an update",
-                        wd_id.light_blue(),
+                        display_workdir_id(&wd_id).light_blue(),
                     )),
                     options,
                 );
@@ -1150,7 +1488,8 @@ impl UpCommand {
             SyncUpdateOperation::Init(init) => {
                 panic!("unexpected init message: {init:?}");
             }
-            SyncUpdateOperation::Exit(exit_code) => exit(exit_code),
+            // The caller is expected to exit with the code
+            SyncUpdateOperation::Exit(_) => {}
             SyncUpdateOperation::OmniWarning(message) => {
                 omni_warning!(message);
             }
@@ -1165,275 +1504,77 @@ impl UpCommand {
             }
         }
     }
-}
 
-impl BuiltinCommand for UpCommand {
-    fn new_boxed() -> Box<dyn BuiltinCommand> {
-        Box::new(Self::new())
-    }
+    /// Runs the up or down operation, returning the exit code
+    fn exec_up(&self, summary: Option<&UpSummaryWriter>) -> i32 {
+        let wd = workdir(".");
+        if let Some(wd_root) = wd.root() {
+            // Switch directory to the work directory root so it can
+            // be assumed that all up commands will be ran from there
+            // (e.g. custom commands, bundler commands, etc.)
+            if let Err(err) = std::env::set_current_dir(wd_root) {
+                omni_error!(format!(
+                    "failed to change directory {}: {}",
+                    format!("({wd_root})").light_black(),
+                    format!("{err}").red()
+                ));
+                return 1;
+            }
+        }
 
-    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
-        Box::new(self.clone())
-    }
+        if !self.update_repository() {
+            if let (Some(wd_id), Some(git_commit)) = (wd.id(), git_env_fresh(".").commit()) {
+                if WorkdirsCache::get().check_fingerprint(
+                    &wd_id,
+                    "head_commit",
+                    fingerprint(&git_commit),
+                ) {
+                    // Nothing more to do if we tried updating and the
+                    // repo was already up to date, since the head commit
+                    // fingerprint is the same
+                    return 0;
+                }
+            } else {
+                // If we're not in a repository, we stop here
+                return 0;
+            }
+        }
 
-    fn name(&self) -> Vec<String> {
-        vec!["up".to_string()]
-    }
+        let cfg = config(".");
+        let up_config = cfg.up.clone();
+        if let Some(up_config) = up_config.clone() {
+            if up_config.has_errors() {
+                for error in up_config.errors() {
+                    omni_warning!(error);
+                }
+            }
+        }
 
-    fn aliases(&self) -> Vec<Vec<String>> {
-        vec![vec!["down".to_string()]]
-    }
+        if !self.handle_prompts() {
+            return 0;
+        }
 
-    fn help(&self) -> Option<String> {
-        Some(
-            concat!(
-                "Sets up or tear down a repository depending on its \x1B[3mup\x1B[0m ",
-                "configuration",
-            )
-            .to_string(),
-        )
-    }
-
-    fn syntax(&self) -> Option<CommandSyntax> {
-        Some(CommandSyntax {
-            parameters: vec![
-                SyntaxOptArg {
-                    names: vec!["--no-cache".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Whether we should disable the cache while running the command ",
-                            "\x1B[90m(default: no)\x1B[0m",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Flag,
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--fail-on-upgrade".to_string()],
-                    desc: Some(
-                        concat!(
-                            "If provided, will fail the operation if a resource failed to ",
-                            "upgrade, even if a currently-existing version can satisfy the dependencies ",
-                            "\x1B[90m(default: no)\x1B[0m",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Flag,
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--bootstrap".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Same as using \x1B[1m--update-user-config --clone-suggested\x1B[0m; if ",
-                            "any of the options are directly provided, they will take precedence over ",
-                            "the default values of the options",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Flag,
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--clone-suggested".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Whether we should clone suggested repositories found in the configuration ",
-                            "of the repository if any (yes/ask/no)",
-                        )
-                        .to_string(),
-                    ),
-                    num_values: Some(SyntaxOptArgNumValues::AtMost(1)),
-                    arg_type: SyntaxOptArgType::Enum(vec![
-                        "yes".to_string(),
-                        "ask".to_string(),
-                        "no".to_string(),
-                    ]),
-                    default_missing_value: Some("ask".to_string()),
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--prompt".to_string()],
-                    placeholders: vec!["PROMPT_ID".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Trigger prompts for the given prompt ids, specified as arguments, as ",
-                            "well as the currently unanswered prompts",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--prompt-all".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Trigger all prompts for the current work directory, even if they have ",
-                            "already been answered",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Flag,
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--trust".to_string()],
-                    desc: Some(
-                        "Define how to trust the repository (always/yes/no) to run the command"
-                            .to_string(),
-                    ),
-                    num_values: Some(SyntaxOptArgNumValues::AtMost(1)),
-                    arg_type: SyntaxOptArgType::Enum(vec![
-                        "always".to_string(),
-                        "yes".to_string(),
-                        "no".to_string(),
-                    ]),
-                    default_missing_value: Some("yes".to_string()),
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--update-repository".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Whether we should update the repository before running the command; ",
-                            "if the repository is already up to date, the rest of the process will ",
-                            "be skipped",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Flag,
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--update-user-config".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Whether we should handle suggestions found in the configuration of ",
-                            "the repository if any (yes/ask/no); When using \x1B[3mup\x1B[0m, the ",
-                            "\x1B[3msuggest_config\x1B[0m configuration will be copied to the home ",
-                            "directory of the user to be loaded on every omni call",
-                        )
-                        .to_string(),
-                    ),
-                    num_values: Some(SyntaxOptArgNumValues::AtMost(1)),
-                    arg_type: SyntaxOptArgType::Enum(vec![
-                        "yes".to_string(),
-                        "ask".to_string(),
-                        "no".to_string(),
-                    ]),
-                    default_missing_value: Some("ask".to_string()),
-                    ..Default::default()
-                },
-                SyntaxOptArg {
-                    names: vec!["--upgrade".to_string()],
-                    desc: Some(
-                        concat!(
-                            "Whether we should upgrade the resources when the currently-installed ",
-                            "version already matches version constraints. If false, this also means ",
-                            "that if an already installed version for another repository matches ",
-                            "version contraints, we will avoid downloading and building a more ",
-                            "recent version",
-                        )
-                        .to_string(),
-                    ),
-                    arg_type: SyntaxOptArgType::Flag,
-                    ..Default::default()
-                },
-            ],
-            ..Default::default()
-        })
-    }
-
-    fn category(&self) -> Option<Vec<String>> {
-        Some(vec!["Git commands".to_string()])
-    }
-
-    fn exec(&self, argv: Vec<String>) {
-        if self
-            .cli_args
-            .set({
-                let command = Command::Builtin(self.clone_boxed());
-                UpCommandArgs::from(
-                    command
-                        .exec_parse_args_typed(argv, self.name())
-                        .expect("should have args to parse"),
-                )
-            })
-            .is_err()
-        {
-            unreachable!();
-        }
-
-        let wd = workdir(".");
-        if let Some(wd_root) = wd.root() {
-            // Switch directory to the work directory root so it can
-            // be assumed that all up commands will be ran from there
-            // (e.g. custom commands, bundler commands, etc.)
-            if let Err(err) = std::env::set_current_dir(wd_root) {
-                omni_error!(format!(
-                    "failed to change directory {}: {}",
-                    format!("({wd_root})").light_black(),
-                    format!("{err}").red()
-                ));
-                exit(1);
-            }
-        }
-
-        if !self.update_repository() {
-            if let (Some(wd_id), Some(git_commit)) = (wd.id(), git_env_fresh(".").commit()) {
-                if WorkdirsCache::get().check_fingerprint(
-                    &wd_id,
-                    "head_commit",
-                    fingerprint(&git_commit),
-                ) {
-                    // Nothing more to do if we tried updating and the
-                    // repo was already up to date, since the head commit
-                    // fingerprint is the same
-                    exit(0);
-                }
-            } else {
-                // If we're not in a repository, we stop here
-                exit(0);
-            }
-        }
-
-        let cfg = config(".");
-        let up_config = cfg.up.clone();
-        if let Some(up_config) = up_config.clone() {
-            if up_config.has_errors() {
-                for error in up_config.errors() {
-                    omni_warning!(error);
-                }
-            }
-        }
-
-        if !self.handle_prompts() {
-            exit(0);
-        }
-
-        let mut suggest_config = None;
-        let mut suggest_config_updated = false;
-        let suggest_config_value = cfg.suggest_config.config();
-        if self.is_up() && !suggest_config_value.is_null() {
-            if self.should_suggest_config() {
-                suggest_config = Some(suggest_config_value);
-            } else if let Some(wd_id) = wd.id() {
-                let suggest_config_fingerprint = fingerprint(&suggest_config_value);
-                if !WorkdirsCache::get().check_fingerprint(
-                    &wd_id,
-                    "suggest_config",
-                    suggest_config_fingerprint,
-                ) {
-                    if self.auto_bootstrap_config() {
-                        suggest_config = Some(suggest_config_value);
-                    } else {
-                        suggest_config_updated = true;
-                    }
-                }
-            }
-        }
+        let mut suggest_config = None;
+        let mut suggest_config_updated = false;
+        let suggest_config_value = cfg.suggest_config.config();
+        if self.is_up() && !suggest_config_value.is_null() {
+            if self.should_suggest_config() {
+                suggest_config = Some(suggest_config_value);
+            } else if let Some(wd_id) = wd.id() {
+                let suggest_config_fingerprint = fingerprint(&suggest_config_value);
+                if !WorkdirsCache::get().check_fingerprint(
+                    &wd_id,
+                    "suggest_config",
+                    suggest_config_fingerprint,
+                ) {
+                    if self.auto_bootstrap_config() {
+                        suggest_config = Some(suggest_config_value);
+                    } else {
+                        suggest_config_updated = true;
+                    }
+                }
+            }
+        }
 
         let mut suggest_clone = false;
         let mut suggest_clone_updated = false;
@@ -1467,7 +1608,7 @@ impl BuiltinCommand for UpCommand {
 
         if self.is_down() && (!wd.in_workdir() || !wd.has_id()) {
             omni_info!(format!("Outside of a work directory, nothing to do."));
-            exit(0);
+            return 0;
         }
 
         let has_up_config = up_config.is_some() && up_config.clone().unwrap().has_steps();
@@ -1482,7 +1623,7 @@ impl BuiltinCommand for UpCommand {
                 "up".italic(),
             ));
             UpConfig::clear_cache();
-            exit(0);
+            return 0;
         }
 
         let trust = self.trust();
@@ -1491,14 +1632,30 @@ impl BuiltinCommand for UpCommand {
                 "Skipped running {} for this repository.",
                 format!("omni {}", self.subcommand()).bold(),
             ));
-            exit(0);
+            return 0;
         }
 
+        // Select the operations to run, if only some of them were requested
+        let only = match (&up_config, self.cli_args().only.is_some()) {
+            (Some(up_config), true) if has_up_config => match self.only_operations(up_config) {
+                Ok(only) => only,
+                Err(exit_code) => return exit_code,
+            },
+            (_, true) => {
+                omni_error!(format!(
+                    "{} requires operations to select from",
+                    "--only".light_yellow(),
+                ));
+                return 1;
+            }
+            (_, false) => None,
+        };
+
         // If we get here, we're about to run the command, so make sure we
         // have a workdir id
         if let Err(err) = workdir_or_init(".") {
             omni_error!(format!("{}", err));
-            exit(1);
+            return 1;
         }
 
         // Read the head commit of the repository
@@ -1518,11 +1675,13 @@ impl BuiltinCommand for UpCommand {
                 options: init_options,
                 cache: self.cli_args().cache_enabled,
                 pid: Some(std::process::id()),
+                started_at: Some(OffsetDateTime::now_utc().unix_timestamp()),
             }
         } else {
             SyncUpdateInit::Down {
                 cache: self.cli_args().cache_enabled,
                 pid: Some(std::process::id()),
+                started_at: Some(OffsetDateTime::now_utc().unix_timestamp()),
             }
         };
 
@@ -1531,11 +1690,11 @@ impl BuiltinCommand for UpCommand {
         listener.expect_init(&sync_command);
 
         // Lock the update process to avoid running it multiple times in parallel
-        let lock_file = match workdir(".").lock_update(&mut listener) {
+        let lock_file = match workdir(".").lock_update(&mut listener, !self.cli_args().no_wait) {
             Ok(Some(lock_file)) => lock_file,
             Ok(None) => {
                 // Nothing to do here, the update was done in an attached operation
-                exit(0);
+                return 0;
             }
             Err(SyncUpdateError::MissingInitOptions) => {
                 // If we get here, it means we were attached to an `up` operation that successfully
@@ -1548,11 +1707,17 @@ impl BuiltinCommand for UpCommand {
                     suggest_clone_updated,
                     &UpOptions::new(),
                 );
-                exit(0);
+                return 0;
+            }
+            Err(SyncUpdateError::AlreadyRunning(description)) => {
+                // Use a distinct exit code (EX_TEMPFAIL) so that callers
+                // running with --no-wait can tell that they can retry later
+                omni_error!(description);
+                return 75;
             }
             Err(err) => {
                 omni_error!(format!("{}", err));
-                exit(1);
+                return 1;
             }
         };
 
@@ -1561,8 +1726,20 @@ impl BuiltinCommand for UpCommand {
             omni_warning!(format!("failed to write sync file: {}", err));
         }
 
+        // Prepare the writer for the events, if requested
+        let events = match self.events_writer() {
+            Ok(events) => events,
+            Err(exit_code) => return exit_code,
+        };
+
         // Prepare the options for the up command
-        let options = UpOptions::new().lock_file(&lock_file);
+        let mut options = UpOptions::new().lock_file(&lock_file);
+        if let Some(events) = &events {
+            options = options.events(events);
+        }
+        if let Some(summary) = summary {
+            options = options.summary(summary);
+        }
 
         // If it has an up configuration, handle it
         if has_up_config {
@@ -1579,30 +1756,70 @@ impl BuiltinCommand for UpCommand {
                             &options,
                         );
                         self.handle_sync_operation(SyncUpdateOperation::Exit(1), &options);
-                        unreachable!();
+                        return 1;
                     }
                 };
 
                 let up_config = up_config.unwrap();
-                let options = options
+
+                let mut options = options
                     .clone()
                     .commit_sha(&head_commit)
                     .cache(self.cli_args().cache_enabled)
                     .fail_on_upgrade(self.cli_args().fail_on_upgrade)
-                    .upgrade(self.cli_args().upgrade);
+                    .upgrade(self.cli_args().upgrade)
+                    .strict(self.cli_args().strict)
+                    .record_history(true);
+                if let Some(only) = &only {
+                    options = options.only(only);
+                }
 
                 // Create the new environment we are going to build
                 let mut environment = UpEnvironment::new().init();
 
                 // Set environment variables
-                if let Some(env_vars) = env_vars.clone() {
-                    environment.env_vars = env_vars.into();
+                if let Some(mut env_vars) = env_vars.clone() {
+                    let wd_root = wd.root().unwrap_or(".").to_string();
+
+                    // Run the commands providing the values that are to be
+                    // computed now, so that their output is stored in the
+                    // environment; a failing command leaves the variable unset
+                    for env_op in env_vars.operations.iter_mut() {
+                        if let Err(err) = env_op.run_up_command(Path::new(&wd_root)) {
+                            self.handle_sync_operation(
+                                SyncUpdateOperation::OmniWarning(format!(
+                                    "command for {} failed ({}), variable will be unset",
+                                    env_op.name.light_yellow(),
+                                    err,
+                                )),
+                                &options,
+                            );
+                        }
+                    }
+
+                    environment.env_vars = Vec::<UpEnvVar>::from(env_vars)
+                        .into_iter()
+                        .map(|env_var| env_var.resolve_path(&wd_root))
+                        .collect();
                     self.handle_sync_operation(
                         SyncUpdateOperation::OmniInfo("workdir environment configured".to_string()),
                         &options,
                     );
                 }
 
+                // Report the runtime steps that were not declared but
+                // that are required by other steps
+                for inferred in up_config.inferred() {
+                    self.handle_sync_operation(
+                        SyncUpdateOperation::OmniInfo(format!(
+                            "inferred {} step, required by {}",
+                            inferred.runtime.light_yellow(),
+                            inferred.required_by.join(", ").light_yellow(),
+                        )),
+                        &options,
+                    );
+                }
+
                 // Configure the rest of the environment
                 if let Err(err) = up_config.up(&options, &mut environment) {
                     self.handle_sync_operation(
@@ -1612,6 +1829,7 @@ impl BuiltinCommand for UpCommand {
                         &options,
                     );
                     self.handle_sync_operation(SyncUpdateOperation::Exit(1), &options);
+                    return 1;
                 }
 
                 // Save the head commit fingerprint to the repositories cache
@@ -1641,6 +1859,7 @@ impl BuiltinCommand for UpCommand {
                         &options,
                     );
                     self.handle_sync_operation(SyncUpdateOperation::Exit(1), &options);
+                    return 1;
                 }
             }
         }
@@ -1652,29 +1871,468 @@ impl BuiltinCommand for UpCommand {
             suggest_clone_updated,
             &options,
         );
+
+        0
     }
 }
 
-#[derive(Debug, Eq, Clone)]
-struct RepositoryToClone {
-    suggested_by: Vec<String>,
-    clone_url: String,
-    clone_path: PathBuf,
-    package_path: Option<PathBuf>,
-    clone_args: Vec<String>,
-    clone_as_package: bool,
-}
+impl BuiltinCommand for UpCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
 
-impl Hash for RepositoryToClone {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.clone_path.hash(state);
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
     }
-}
 
-impl PartialEq for RepositoryToClone {
-    fn eq(&self, other: &Self) -> bool {
-        self.clone_path == other.clone_path
+    fn name(&self) -> Vec<String> {
+        vec!["up".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![vec!["down".to_string()]]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Sets up or tear down a repository depending on its \x1B[3mup\x1B[0m ",
+                "configuration",
+            )
+            .to_string(),
+        )
     }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--no-cache".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Whether we should disable the cache while running the command ",
+                            "\x1B[90m(default: no)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--fail-on-upgrade".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will fail the operation if a resource failed to ",
+                            "upgrade, even if a currently-existing version can satisfy the dependencies ",
+                            "\x1B[90m(default: no)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--bootstrap".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Same as using \x1B[1m--update-user-config --clone-suggested\x1B[0m; if ",
+                            "any of the options are directly provided, they will take precedence over ",
+                            "the default values of the options",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--clone-suggested".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Whether we should clone suggested repositories found in the configuration ",
+                            "of the repository if any (yes/ask/no)",
+                        )
+                        .to_string(),
+                    ),
+                    num_values: Some(SyntaxOptArgNumValues::AtMost(1)),
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "yes".to_string(),
+                        "ask".to_string(),
+                        "no".to_string(),
+                    ]),
+                    default_missing_value: Some("ask".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--jobs".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The maximum number of repositories to set up at the same time when ",
+                            "using \x1B[1m--workspace\x1B[0m ",
+                            "\x1B[90m(default: number of CPUs)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Integer,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--no-wait".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will exit immediately instead of waiting when another ",
+                            "operation is already running for the work directory ",
+                            "\x1B[90m(default: no)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--only".to_string()],
+                    placeholders: vec!["OPERATION".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Only run the given operations, specified by name or by index; the ",
+                            "operations they depend on are included automatically. When provided ",
+                            "without any operation, the operations to run are selected ",
+                            "interactively",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                    num_values: Some(SyntaxOptArgNumValues::AtLeast(0)),
+                    value_delimiter: Some(','),
+                    default_missing_value: Some("".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--output".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The format of the output of the operation (text/json); when using ",
+                            "\x1B[1mjson\x1B[0m, the progress of the operation is streamed as one ",
+                            "JSON event per line on stdout, or on the file descriptor provided with ",
+                            "\x1B[1m--output-fd\x1B[0m, while the human-readable output stays on ",
+                            "stderr \x1B[90m(default: text)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "text".to_string(),
+                        "json".to_string(),
+                    ]),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--output-fd".to_string()],
+                    placeholders: vec!["FD".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The file descriptor to write the JSON events to when using ",
+                            "\x1B[1m--output json\x1B[0m \x1B[90m(default: stdout)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Integer,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--prompt".to_string()],
+                    placeholders: vec!["PROMPT_ID".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Trigger prompts for the given prompt ids, specified as arguments, as ",
+                            "well as the currently unanswered prompts",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--prompt-all".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Trigger all prompts for the current work directory, even if they have ",
+                            "already been answered",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--strict".to_string()],
+                    desc: Some(
+                        concat!(
+                            "If provided, will fail the operation if a resolved tool version ",
+                            "matches a known end-of-life or security advisory, instead of only ",
+                            "warning about it \x1B[90m(default: no)\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--summary-file".to_string()],
+                    placeholders: vec!["PATH".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Write a JSON summary of the operation to the given file once it ",
+                            "completes, even if it failed; this is not supported with ",
+                            "\x1B[1m--workspace\x1B[0m",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::FilePath,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--trust".to_string()],
+                    desc: Some(
+                        "Define how to trust the repository (always/yes/no) to run the command"
+                            .to_string(),
+                    ),
+                    num_values: Some(SyntaxOptArgNumValues::AtMost(1)),
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "always".to_string(),
+                        "yes".to_string(),
+                        "no".to_string(),
+                    ]),
+                    default_missing_value: Some("yes".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--update-repository".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Whether we should update the repository before running the command; ",
+                            "if the repository is already up to date, the rest of the process will ",
+                            "be skipped",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--update-user-config".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Whether we should handle suggestions found in the configuration of ",
+                            "the repository if any (yes/ask/no); When using \x1B[3mup\x1B[0m, the ",
+                            "\x1B[3msuggest_config\x1B[0m configuration will be copied to the home ",
+                            "directory of the user to be loaded on every omni call",
+                        )
+                        .to_string(),
+                    ),
+                    num_values: Some(SyntaxOptArgNumValues::AtMost(1)),
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "yes".to_string(),
+                        "ask".to_string(),
+                        "no".to_string(),
+                    ]),
+                    default_missing_value: Some("ask".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--upgrade".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Whether we should upgrade the resources when the currently-installed ",
+                            "version already matches version constraints. If false, this also means ",
+                            "that if an already installed version for another repository matches ",
+                            "version contraints, we will avoid downloading and building a more ",
+                            "recent version",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--workspace".to_string()],
+                    desc: Some(
+                        concat!(
+                            "Set up the given repositories, or the repositories directly under ",
+                            "the given directories, in parallel; the tool versions required by ",
+                            "multiple repositories are only installed once",
+                        )
+                        .to_string(),
+                    ),
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::DirPath)),
+                    num_values: Some(SyntaxOptArgNumValues::AtLeast(1)),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["Git commands".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        if self
+            .cli_args
+            .set({
+                let command = Command::Builtin(self.clone_boxed());
+                UpCommandArgs::from(
+                    command
+                        .exec_parse_args_typed(argv, self.name())
+                        .expect("should have args to parse"),
+                )
+            })
+            .is_err()
+        {
+            unreachable!();
+        }
+
+        if !self.cli_args().workspace.is_empty() {
+            for (option, provided) in [
+                ("--only", self.cli_args().only.is_some()),
+                ("--summary-file", self.cli_args().summary_file.is_some()),
+            ] {
+                if provided {
+                    omni_error!(format!(
+                        "{} cannot be used with {}",
+                        option.light_yellow(),
+                        "--workspace".light_yellow(),
+                    ));
+                    exit(1);
+                }
+            }
+            self.exec_workspace();
+        }
+
+        if self.is_down() && self.cli_args().only.is_some() {
+            omni_error!(format!(
+                "{} cannot be used with {}",
+                "--only".light_yellow(),
+                "omni down".bold(),
+            ));
+            exit(1);
+        }
+
+        // The summary is written when dropped if the run did not get to
+        // complete, so that it is also written on the early exits
+        let exit_code = {
+            let summary = self
+                .cli_args()
+                .summary_file
+                .as_ref()
+                .map(UpSummaryWriter::new);
+            let exit_code = self.exec_up(summary.as_ref());
+            if let Some(summary) = &summary {
+                summary.set_exit_code(exit_code);
+            }
+            exit_code
+        };
+
+        exit(exit_code);
+    }
+}
+
+#[derive(Debug, Eq, Clone)]
+struct RepositoryToClone {
+    suggested_by: Vec<String>,
+    clone_url: String,
+    clone_path: PathBuf,
+    package_path: Option<PathBuf>,
+    clone_args: Vec<String>,
+    clone_as_package: bool,
+}
+
+impl Hash for RepositoryToClone {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.clone_path.hash(state);
+    }
+}
+
+impl PartialEq for RepositoryToClone {
+    fn eq(&self, other: &Self) -> bool {
+        self.clone_path == other.clone_path
+    }
+}
+
+/// Asks the user which operations to run, showing for each of them its
+/// backend and the outcome of the last run that included it
+fn pick_operations(steps: &[&UpConfigTool]) -> Option<BTreeSet<usize>> {
+    let last_outcomes = last_operation_outcomes();
+
+    let choices = steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| {
+            let name = step.to_name();
+            let state = match last_outcomes.get(&name) {
+                Some((outcome, started_at)) => {
+                    format!("{} {}", outcome, format_time_ago(started_at))
+                }
+                None => "never run".to_string(),
+            };
+
+            format!(
+                "{} {} {}",
+                format!("{:>2}.", idx + 1).light_black(),
+                name,
+                format!("({}, {})", step.backend(), state).light_black(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let question = requestty::Question::multi_select("select_operations")
+        .ask_if_answered(true)
+        .on_esc(requestty::OnEsc::Terminate)
+        .message("Which operations do you want to run?")
+        .transform(|selected, _, backend| {
+            write!(backend, "{} selected", format!("{}", selected.len()).bold())
+        })
+        .choices(choices)
+        .should_loop(false)
+        .build();
+
+    match requestty::prompt_one(question) {
+        Ok(answer) => match answer {
+            requestty::Answer::ListItems(items) => {
+                Some(items.into_iter().map(|item| item.index).collect())
+            }
+            _ => unreachable!(),
+        },
+        Err(err) => {
+            println!("{}", format!("[✘] {err:?}").red());
+            None
+        }
+    }
+}
+
+/// Returns the outcome of the last run of each operation of the work
+/// directory, along with when that run started, keyed by operation name
+fn last_operation_outcomes() -> HashMap<String, (UpHistoryOutcome, OffsetDateTime)> {
+    let mut outcomes = HashMap::new();
+
+    let workdir_id = match workdir(".").id() {
+        Some(workdir_id) => workdir_id,
+        None => return outcomes,
+    };
+
+    // The history is sorted with the most recent run first
+    for entry in UpHistoryCache::get().list(&workdir_id) {
+        for operation in entry.operations {
+            if operation.outcome == UpHistoryOutcome::Skipped {
+                continue;
+            }
+
+            outcomes
+                .entry(operation.name)
+                .or_insert((operation.outcome, entry.started_at));
+        }
+    }
+
+    outcomes
 }
 
 fn color_diff(diff: &str) -> String {
@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::exit;
+use std::process::Command as ProcessCommand;
+
+use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::commands::base::BuiltinCommand;
+use crate::internal::commands::Command;
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::up::UpConfig;
+use crate::internal::config::up::UpOptions;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgNumValues;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::dynenv::ephemeral_env_changes;
+use crate::internal::env::cache_home;
+use crate::internal::user_interface::StringColor;
+use crate::omni_error;
+
+#[derive(Debug, Clone)]
+struct WithCommandArgs {
+    tools: Vec<String>,
+    command: Vec<String>,
+}
+
+impl From<BTreeMap<String, ParseArgsValue>> for WithCommandArgs {
+    fn from(args: BTreeMap<String, ParseArgsValue>) -> Self {
+        let tools = match args.get("tools") {
+            Some(ParseArgsValue::ManyString(tools)) => {
+                tools.iter().flat_map(|v| v.clone()).collect()
+            }
+            _ => vec![],
+        };
+
+        let command = match args.get("command") {
+            Some(ParseArgsValue::ManyString(command)) => {
+                command.iter().flat_map(|v| v.clone()).collect()
+            }
+            _ => vec![],
+        };
+
+        Self { tools, command }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WithCommand {}
+
+impl WithCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinCommand for WithCommand {
+    fn new_boxed() -> Box<dyn BuiltinCommand> {
+        Box::new(Self::new())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn BuiltinCommand> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> Vec<String> {
+        vec!["with".to_string()]
+    }
+
+    fn aliases(&self) -> Vec<Vec<String>> {
+        vec![]
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(
+            concat!(
+                "Runs a command with the specified tools available\n",
+                "\n",
+                "The tools are installed if needed, reusing the already installed versions, ",
+                "and are only made available to the command being run. This does not change ",
+                "the environment of the work directory or of the shell, and does not require ",
+                "running \x1B[3momni up\x1B[0m.",
+            )
+            .to_string(),
+        )
+    }
+
+    fn syntax(&self) -> Option<CommandSyntax> {
+        Some(CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["tools".to_string()],
+                    desc: Some(
+                        concat!(
+                            "The tools to make available, in the \x1B[3m<tool>@<version>\x1B[0m ",
+                            "format (e.g. \x1B[3mnode@20\x1B[0m); the version defaults to ",
+                            "\x1B[3mlatest\x1B[0m if not provided. Only the tools that can be ",
+                            "installed through mise are supported.",
+                        )
+                        .to_string(),
+                    ),
+                    required: true,
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                    num_values: Some(SyntaxOptArgNumValues::AtLeast(1)),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["command".to_string()],
+                    desc: Some(
+                        "The command to run with the tools available, after \x1B[3m--\x1B[0m."
+                            .to_string(),
+                    ),
+                    required: true,
+                    last_arg_double_hyphen: true,
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn category(&self) -> Option<Vec<String>> {
+        Some(vec!["General".to_string()])
+    }
+
+    fn exec(&self, argv: Vec<String>) {
+        let command = Command::Builtin(self.clone_boxed());
+        let args = WithCommandArgs::from(
+            command
+                .exec_parse_args_typed(argv, self.name())
+                .expect("should have args to parse"),
+        );
+
+        if args.command.is_empty() {
+            omni_error!("no command to run", "with");
+            exit(1);
+        }
+
+        let up_config = match UpConfig::from_tool_specs(&args.tools) {
+            Ok(up_config) => up_config,
+            Err(err) => {
+                omni_error!(err.message(), "with");
+                exit(1);
+            }
+        };
+
+        // Keep the data paths of the tools, such as python virtual
+        // environments, out of the data path of the work directory
+        let options = UpOptions::new().data_path(PathBuf::from(cache_home()).join("with"));
+        let mut environment = UpEnvironment::new();
+        if let Err(err) = up_config.up_ephemeral(&options, &mut environment) {
+            omni_error!(format!("failed to provision tools: {}", err), "with");
+            exit(1);
+        }
+
+        let err = command_with_environment(&args.command, &environment).exec();
+        omni_error!(
+            format!("failed to run {}: {}", args.command[0].light_yellow(), err),
+            "with"
+        );
+        exit(1);
+    }
+}
+
+/// Prepares the process for the given command so that it runs with the
+/// tools of the environment available
+fn command_with_environment(command: &[String], environment: &UpEnvironment) -> ProcessCommand {
+    let mut process = ProcessCommand::new(&command[0]);
+    process.args(&command[1..]);
+
+    for (key, value) in ephemeral_env_changes(environment) {
+        match value {
+            Some(value) => process.env(key, value),
+            None => process.env_remove(key),
+        };
+    }
+
+    process
+}
+
+#[cfg(test)]
+#[path = "with_test.rs"]
+mod tests;
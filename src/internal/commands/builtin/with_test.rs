@@ -0,0 +1,68 @@
+use super::*;
+
+use std::collections::BTreeSet;
+
+use crate::internal::cache::up_environments::UpVersionParams;
+use crate::internal::config::up::mise_tool_path;
+use crate::internal::testutils::run_with_env;
+
+fn run_and_capture(command: &[&str], environment: &UpEnvironment) -> String {
+    let command = command
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<_>>();
+    let output = command_with_environment(&command, environment)
+        .output()
+        .expect("failed to run command");
+    assert!(output.status.success(), "command failed: {output:?}");
+    String::from_utf8(output.stdout).expect("output should be valid utf-8")
+}
+
+#[test]
+fn child_process_sees_requested_tools() {
+    run_with_env(&[], || {
+        let tool_prefix = mise_tool_path("node", "20.1.0");
+        std::fs::create_dir_all(format!("{tool_prefix}/bin")).expect("failed to create tool dir");
+
+        let mut environment = UpEnvironment::new();
+        environment.add_version(UpVersionParams {
+            backend: "",
+            tool: "node",
+            plugin_name: "node",
+            normalized_name: "node",
+            version: "20.1.0",
+            bin_path: "bin",
+            dirs: BTreeSet::new(),
+            env_vars: vec![],
+        });
+
+        let output = run_and_capture(
+            &["sh", "-c", "printf '%s\\n%s' \"$PATH\" \"$NODE_VERSION\""],
+            &environment,
+        );
+        let (path, node_version) = output.split_once('\n').expect("should have two lines");
+
+        assert_eq!(
+            path.split(':').next(),
+            Some(format!("{tool_prefix}/bin").as_str())
+        );
+        assert_eq!(node_version, "20.1.0");
+
+        // The environment of the current process is not changed
+        assert_ne!(
+            std::env::var("NODE_VERSION").ok().as_deref(),
+            Some("20.1.0")
+        );
+    });
+}
+
+#[test]
+fn child_process_without_tools_keeps_environment() {
+    run_with_env(&[], || {
+        let output = run_and_capture(
+            &["sh", "-c", "printf '%s' \"$PATH\""],
+            &UpEnvironment::new(),
+        );
+        assert_eq!(output, std::env::var("PATH").unwrap_or_default());
+    });
+}
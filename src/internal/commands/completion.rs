@@ -0,0 +1,202 @@
+use std::cell::RefCell;
+
+use crate::internal::commands::command_loader;
+use crate::internal::env::Shell;
+use crate::internal::user_interface::colors::strip_colors;
+
+thread_local! {
+    static COMPLETION_OUTPUT: RefCell<CompletionOutput> = const { RefCell::new(CompletionOutput::Plain) };
+}
+
+/// Where the completion candidates are written to, and in which format
+#[derive(Debug, Clone)]
+enum CompletionOutput {
+    /// One candidate per line, without description, as expected by the
+    /// completion functions of the shell integration
+    Plain,
+    /// One candidate per line, in the format expected by the given shell
+    Shell(Shell),
+    /// Candidates kept in memory instead of being printed
+    #[cfg(test)]
+    Capture(Vec<CompletionCandidate>),
+}
+
+/// A candidate for the completion of the current word, with the
+/// description to show alongside it for the shells supporting it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionCandidate {
+    pub value: String,
+    pub description: Option<String>,
+}
+
+impl CompletionCandidate {
+    pub fn new(value: &str, description: Option<&str>) -> Self {
+        // Only keep the first non-empty line of the description, without
+        // colors, as the shells only show a single line per candidate
+        let description = description.and_then(|desc| {
+            strip_colors(desc)
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(|line| line.to_string())
+        });
+
+        Self {
+            value: value.to_string(),
+            description,
+        }
+    }
+
+    /// Returns the candidate as a line in the format expected by the
+    /// completion function of the given shell
+    pub fn to_shell_line(&self, shell: &Shell) -> String {
+        match (shell, &self.description) {
+            // zsh's `_describe` splits the value from its description on
+            // the first unescaped colon
+            (Shell::Zsh, Some(desc)) => format!("{}:{}", self.value.replace(':', "\\:"), desc),
+            (Shell::Zsh, None) => self.value.replace(':', "\\:"),
+            (Shell::Fish, Some(desc)) => format!("{}\t{}", self.value, desc),
+            _ => self.value.clone(),
+        }
+    }
+}
+
+/// Writes a completion candidate to the completion output of the
+/// current thread; see [`scoped_completion_shell`]
+pub fn emit_completion(value: &str, description: Option<&str>) {
+    let candidate = CompletionCandidate::new(value, description);
+    COMPLETION_OUTPUT.with(|output| match &mut *output.borrow_mut() {
+        CompletionOutput::Plain => println!("{}", candidate.value),
+        CompletionOutput::Shell(shell) => println!("{}", candidate.to_shell_line(shell)),
+        #[cfg(test)]
+        CompletionOutput::Capture(candidates) => candidates.push(candidate),
+    });
+}
+
+/// Writes the completion candidates in the format expected by the given
+/// shell for the current thread, until the returned guard is dropped
+pub fn scoped_completion_shell(shell: Shell) -> CompletionOutputGuard {
+    CompletionOutputGuard::replace(CompletionOutput::Shell(shell))
+}
+
+/// Runs the closure while capturing the completion candidates emitted
+/// by the current thread, instead of printing them
+#[cfg(test)]
+pub fn capture_completions<F>(closure: F) -> Vec<CompletionCandidate>
+where
+    F: FnOnce(),
+{
+    let _guard = CompletionOutputGuard::replace(CompletionOutput::Capture(vec![]));
+    closure();
+    COMPLETION_OUTPUT.with(|output| match &*output.borrow() {
+        CompletionOutput::Capture(candidates) => candidates.clone(),
+        _ => unreachable!("completion output should be captured"),
+    })
+}
+
+/// Restores the completion output of the current thread when dropped
+#[must_use = "the completion output is only overridden until the guard is dropped"]
+pub struct CompletionOutputGuard {
+    previous: Option<CompletionOutput>,
+}
+
+impl CompletionOutputGuard {
+    fn replace(output: CompletionOutput) -> Self {
+        let previous = COMPLETION_OUTPUT.with(|current| current.replace(output));
+        Self {
+            previous: Some(previous),
+        }
+    }
+}
+
+impl Drop for CompletionOutputGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            COMPLETION_OUTPUT.with(|current| current.replace(previous));
+        }
+    }
+}
+
+/// Completes the last of the given words, the previous ones being the
+/// words of the command line after `omni`, against the commands available
+/// from the current directory; the candidates are printed in the format
+/// expected by the given shell
+pub fn dynamic_complete(shell: Shell, mut words: Vec<String>) -> Result<(), ()> {
+    if words.is_empty() {
+        words.push("".to_string());
+    }
+    let comp_cword = words.len() - 1;
+
+    let _guard = scoped_completion_shell(shell);
+    command_loader(".").complete(comp_cword, words, true)
+}
+
+/// Parses the arguments of the `__complete` entry point, in the form
+/// `[--shell <shell>] [--] <words>...`, defaulting to the shell of the
+/// environment if none is provided
+pub fn parse_dynamic_complete_args(argv: &[String]) -> (Shell, Vec<String>) {
+    let mut shell = None;
+    let mut idx = 0;
+
+    while let Some(arg) = argv.get(idx) {
+        if arg == "--" {
+            idx += 1;
+            break;
+        } else if arg == "--shell" {
+            shell = argv.get(idx + 1).map(|value| Shell::from_str(value));
+            idx += 2;
+        } else if let Some(value) = arg.strip_prefix("--shell=") {
+            shell = Some(Shell::from_str(value));
+            idx += 1;
+        } else {
+            break;
+        }
+    }
+
+    let words = argv.get(idx..).unwrap_or_default().to_vec();
+    (shell.unwrap_or_else(Shell::from_env), words)
+}
+
+/// Returns the script registering the dynamic completions of omni for
+/// the given shell, which calls `omni __complete` on each completion
+/// so that the candidates are always up to date with the commands
+pub fn dynamic_completion_script(shell: &Shell) -> Option<String> {
+    let script = match shell {
+        Shell::Bash => concat!(
+            "# Dynamic completions for omni, as generated by\n",
+            "# `omni hook completions --dynamic bash`\n",
+            "_omni_dynamic_complete() {\n",
+            "\tlocal IFS=$'\\n'\n",
+            "\tCOMPREPLY=($(omni __complete --shell bash -- \"${COMP_WORDS[@]:1:$COMP_CWORD}\"))\n",
+            "}\n",
+            "complete -o default -F _omni_dynamic_complete omni\n",
+        ),
+        Shell::Zsh => concat!(
+            "# Dynamic completions for omni, as generated by\n",
+            "# `omni hook completions --dynamic zsh`\n",
+            "_omni_dynamic_complete() {\n",
+            "\tlocal -a candidates\n",
+            "\tcandidates=(${(f)\"$(omni __complete --shell zsh -- \"${(@)words[2,CURRENT]}\")\"})\n",
+            "\t_describe 'omni' candidates\n",
+            "}\n",
+            "compdef _omni_dynamic_complete omni\n",
+        ),
+        Shell::Fish => concat!(
+            "# Dynamic completions for omni, as generated by\n",
+            "# `omni hook completions --dynamic fish`\n",
+            "function __omni_dynamic_complete\n",
+            "    set -l tokens (commandline -opc)[2..-1] (commandline -ct)\n",
+            "    omni __complete --shell fish -- $tokens\n",
+            "end\n",
+            "complete -c omni -e\n",
+            "complete -c omni -f -a '(__omni_dynamic_complete)'\n",
+        ),
+        Shell::Posix | Shell::PowerShell | Shell::Unknown(_) => return None,
+    };
+
+    Some(script.to_string())
+}
+
+#[cfg(test)]
+#[path = "completion_test.rs"]
+mod tests;
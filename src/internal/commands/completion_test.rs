@@ -0,0 +1,219 @@
+use super::*;
+
+use crate::internal::commands::loader::CommandLoader;
+use crate::internal::commands::Command;
+use crate::internal::commands::ConfigCommand;
+use crate::internal::config::CommandDefinition;
+use crate::internal::config::CommandSyntax;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgType;
+use crate::internal::testutils::run_with_env;
+
+fn command(name: &str, desc: &str, parameters: Vec<SyntaxOptArg>) -> Command {
+    command_with_visibility(name, desc, parameters, false)
+}
+
+fn hidden_command(name: &str, desc: &str, parameters: Vec<SyntaxOptArg>) -> Command {
+    command_with_visibility(name, desc, parameters, true)
+}
+
+fn command_with_visibility(
+    name: &str,
+    desc: &str,
+    parameters: Vec<SyntaxOptArg>,
+    hidden: bool,
+) -> Command {
+    let mut details: CommandDefinition = serde_yaml::from_str(&format!(
+        "desc: {desc}\nrun: echo {name}\nargparser: true\nhidden: {hidden}\n"
+    ))
+    .expect("failed to parse command definition");
+    details.syntax = Some(CommandSyntax {
+        parameters,
+        ..Default::default()
+    });
+    ConfigCommand::new(name.to_string(), details).into()
+}
+
+fn fixture_loader() -> CommandLoader {
+    CommandLoader {
+        commands: vec![
+            command(
+                "deploy",
+                "Deploy the application",
+                vec![
+                    SyntaxOptArg {
+                        names: vec!["--env".to_string()],
+                        desc: Some("The environment to deploy to".to_string()),
+                        arg_type: SyntaxOptArgType::Enum(vec![
+                            "production".to_string(),
+                            "preview".to_string(),
+                            "staging".to_string(),
+                        ]),
+                        ..Default::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--dry-run".to_string()],
+                        desc: Some("Do not deploy anything".to_string()),
+                        arg_type: SyntaxOptArgType::Flag,
+                        ..Default::default()
+                    },
+                ],
+            ),
+            command("deploy rollback", "Rollback the last deployment", vec![]),
+            hidden_command(
+                "debug",
+                "Debug the application",
+                vec![SyntaxOptArg {
+                    names: vec!["--trace".to_string()],
+                    desc: Some("Trace the calls".to_string()),
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                }],
+            ),
+            command("describe", "Describe the application", vec![]),
+            command("lint", "Lint the code", vec![]),
+        ],
+    }
+}
+
+fn complete(words: &[&str]) -> Vec<CompletionCandidate> {
+    let words = words
+        .iter()
+        .map(|word| word.to_string())
+        .collect::<Vec<_>>();
+    let comp_cword = words.len() - 1;
+
+    let mut candidates = capture_completions(|| {
+        fixture_loader()
+            .complete(comp_cword, words, true)
+            .expect("completion should succeed");
+    });
+    candidates.sort_by(|a, b| a.value.cmp(&b.value));
+    candidates
+}
+
+fn values(candidates: &[CompletionCandidate]) -> Vec<&str> {
+    candidates.iter().map(|c| c.value.as_str()).collect()
+}
+
+#[test]
+fn partial_subcommand_name() {
+    run_with_env(&[], || {
+        let candidates = complete(&["de"]);
+
+        assert_eq!(values(&candidates), vec!["deploy", "describe"]);
+        assert_eq!(
+            candidates[1].description.as_deref(),
+            Some("Describe the application")
+        );
+    });
+}
+
+#[test]
+fn partial_nested_subcommand_name() {
+    run_with_env(&[], || {
+        let candidates = complete(&["deploy", "ro"]);
+
+        assert_eq!(values(&candidates), vec!["rollback"]);
+    });
+}
+
+#[test]
+fn flag_enum_values() {
+    run_with_env(&[], || {
+        let candidates = complete(&["deploy", "--env", "p"]);
+
+        assert_eq!(values(&candidates), vec!["preview", "production"]);
+    });
+}
+
+#[test]
+fn flag_names_with_descriptions() {
+    run_with_env(&[], || {
+        let candidates = complete(&["deploy", "--"]);
+
+        assert_eq!(
+            candidates,
+            vec![
+                CompletionCandidate::new("--dry-run", Some("Do not deploy anything")),
+                CompletionCandidate::new("--env", Some("The environment to deploy to")),
+            ]
+        );
+    });
+}
+
+#[test]
+fn hidden_command_name_not_completed() {
+    run_with_env(&[], || {
+        let candidates = complete(&["deb"]);
+
+        assert!(
+            candidates.is_empty(),
+            "unexpected candidates: {candidates:?}"
+        );
+    });
+}
+
+#[test]
+fn hidden_command_arguments_completed() {
+    run_with_env(&[], || {
+        let candidates = complete(&["debug", "--"]);
+
+        assert_eq!(
+            candidates,
+            vec![CompletionCandidate::new("--trace", Some("Trace the calls"))]
+        );
+    });
+}
+
+#[test]
+fn parse_args_with_shell() {
+    let argv = ["--shell", "zsh", "--", "deploy", "--env"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<_>>();
+
+    let (shell, words) = parse_dynamic_complete_args(&argv);
+
+    assert!(matches!(shell, Shell::Zsh));
+    assert_eq!(words, vec!["deploy", "--env"]);
+}
+
+#[test]
+fn parse_args_keeps_words_after_separator() {
+    let argv = ["--shell=fish", "--", "--shell", ""]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<_>>();
+
+    let (shell, words) = parse_dynamic_complete_args(&argv);
+
+    assert!(shell.is_fish());
+    assert_eq!(words, vec!["--shell", ""]);
+}
+
+#[test]
+fn shell_line_formats() {
+    let candidate = CompletionCandidate::new("host:port", Some("\x1B[1mThe host\x1B[0m\nmore"));
+
+    assert_eq!(candidate.to_shell_line(&Shell::Bash), "host:port");
+    assert_eq!(candidate.to_shell_line(&Shell::Zsh), "host\\:port:The host");
+    assert_eq!(candidate.to_shell_line(&Shell::Fish), "host:port\tThe host");
+
+    let candidate = CompletionCandidate::new("--env", None);
+    assert_eq!(candidate.to_shell_line(&Shell::Zsh), "--env");
+    assert_eq!(candidate.to_shell_line(&Shell::Fish), "--env");
+}
+
+#[test]
+fn dynamic_scripts_call_complete_entry_point() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let script = dynamic_completion_script(&shell).expect("script should be generated");
+        assert!(
+            script.contains(&format!("omni __complete --shell {shell} --")),
+            "unexpected script for {shell}: {script}"
+        );
+    }
+
+    assert!(dynamic_completion_script(&Shell::Posix).is_none());
+}
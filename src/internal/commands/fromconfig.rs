@@ -1,21 +1,63 @@
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::os::fd::AsFd;
+use std::os::fd::BorrowedFd;
 use std::os::unix::process::CommandExt;
+use std::os::unix::process::ExitStatusExt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::exit;
 use std::process::Command as ProcessCommand;
-
+use std::process::ExitStatus;
+use std::time::Duration;
+use std::time::Instant;
+
+use nix::sys::signal::killpg;
+use nix::sys::signal::raise;
+use nix::sys::signal::signal;
+use nix::sys::signal::SigHandler;
+use nix::sys::signal::Signal;
+use nix::sys::wait::waitpid;
+use nix::sys::wait::WaitPidFlag;
+use nix::sys::wait::WaitStatus;
+use nix::unistd::getpgrp;
+use nix::unistd::isatty;
+use nix::unistd::setpgid;
+use nix::unistd::tcgetpgrp;
+use nix::unistd::tcsetpgrp;
+use nix::unistd::Pid;
+
+use crate::internal::cache::up_environments::UpEnvVar;
 use crate::internal::commands::utils::abs_or_rel_path;
 use crate::internal::commands::utils::abs_path;
 use crate::internal::commands::utils::split_name;
+use crate::internal::commands::utils::str_to_bool;
 use crate::internal::config::config;
+use crate::internal::config::parser::env_command_output;
+use crate::internal::config::parser::ArgparserOutput;
+use crate::internal::config::parser::CommandCwd;
+use crate::internal::config::parser::CommandCwdArgFn;
+use crate::internal::config::parser::ENV_COMMAND_UP_TIMEOUT;
 use crate::internal::config::CommandDefinition;
+use crate::internal::config::CommandRunner;
 use crate::internal::config::CommandSyntax;
 use crate::internal::config::ConfigScope;
 use crate::internal::config::ConfigSource;
+use crate::internal::dynenv::env_var_operations_changes;
+use crate::internal::env::workdir;
 use crate::internal::user_interface::colors::StringColor;
+use crate::internal::utils::is_secret_env_var;
+use crate::internal::utils::SECRET_PLACEHOLDER;
 use crate::omni_error;
+use crate::omni_warning;
+
+/// The exit code of a command killed for exceeding its timeout, which
+/// is the one used by the coreutils `timeout` command
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// The time given to a command to stop after being asked to when it
+/// exceeded its timeout, before it gets killed
+const TIMEOUT_KILL_GRACE: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone)]
 pub struct ConfigCommand {
@@ -26,12 +68,35 @@ pub struct ConfigCommand {
 
 impl ConfigCommand {
     pub fn all() -> Vec<Self> {
-        Self::all_commands(config(".").commands.clone(), vec![])
+        let config = config(".");
+        let (commands, _) = config.commands_filter.partition(config.commands.clone());
+        Self::all_commands(commands, vec![])
+    }
+
+    /// Returns the name of the command from the configuration that would
+    /// have been called with the provided arguments, if that command has
+    /// been filtered out through the `commands_filter` configuration.
+    pub fn filtered_out(argv: &[String]) -> Option<String> {
+        let config = config(".");
+        config
+            .commands_filter
+            .filtered_out_command(&config.commands, argv)
     }
 
     pub fn all_commands(
         command_definitions: HashMap<String, CommandDefinition>,
         parent_aliases: Vec<String>,
+    ) -> Vec<Self> {
+        Self::all_commands_with_deprecated(command_definitions, parent_aliases, BTreeMap::new())
+    }
+
+    /// Returns the commands for the given definitions, where the aliases
+    /// of the subcommands are deprecated if the alias of their parent
+    /// they are called through, or their own alias, is deprecated
+    fn all_commands_with_deprecated(
+        command_definitions: HashMap<String, CommandDefinition>,
+        parent_aliases: Vec<String>,
+        parent_deprecated_aliases: BTreeMap<String, String>,
     ) -> Vec<Self> {
         let mut all_commands = Vec::new();
 
@@ -43,29 +108,69 @@ impl ConfigCommand {
             };
 
             let mut aliases = Vec::new();
+            let mut deprecated_aliases = BTreeMap::new();
             if parent_aliases.is_empty() {
                 aliases.clone_from(&command_details.aliases);
+                deprecated_aliases.clone_from(&command_details.deprecated_aliases);
             } else {
                 for parent_alias in parent_aliases[1..].iter() {
-                    aliases.push(format!("{parent_alias} {command_name}"));
+                    let alias = format!("{parent_alias} {command_name}");
+                    if let Some(message) = parent_deprecated_aliases.get(parent_alias) {
+                        deprecated_aliases.insert(alias.clone(), message.clone());
+                    }
+                    aliases.push(alias);
                 }
                 for command_alias in command_details.aliases.iter() {
                     for parent_alias in parent_aliases.iter() {
-                        aliases.push(format!("{parent_alias} {command_alias}"));
+                        let alias = format!("{parent_alias} {command_alias}");
+                        if let Some(message) = command_details
+                            .deprecated_aliases
+                            .get(command_alias)
+                            .or_else(|| parent_deprecated_aliases.get(parent_alias))
+                        {
+                            deprecated_aliases.insert(alias.clone(), message.clone());
+                        }
+                        aliases.push(alias);
                     }
                 }
             }
 
             let mut command_details = command_details.clone();
             command_details.aliases.clone_from(&aliases);
+            command_details
+                .deprecated_aliases
+                .clone_from(&deprecated_aliases);
 
             all_commands.push(Self::new(name.clone(), command_details.clone()));
 
-            if let Some(subcommands) = command_details.subcommands {
+            if let Some(mut subcommands) = command_details.subcommands {
+                // Subcommands are executed from the same directory, with
+                // the same environment and timeout as their parent, unless
+                // they override them, and are hidden when their parent is
+                for subcommand in subcommands.values_mut() {
+                    if subcommand.cwd.is_none() {
+                        subcommand.cwd.clone_from(&command_details.cwd);
+                    }
+                    if subcommand.timeout.is_none() {
+                        subcommand.timeout = command_details.timeout;
+                    }
+                    subcommand.hidden |= command_details.hidden;
+
+                    // The environment of the parent is applied first, so
+                    // that the subcommands can override it
+                    let mut env = command_details.env.clone();
+                    env.operations.append(&mut subcommand.env.operations);
+                    subcommand.env = env;
+                }
+
                 let mut parent_aliases = vec![name];
                 parent_aliases.extend(aliases.clone());
 
-                all_commands.extend(Self::all_commands(subcommands, parent_aliases));
+                all_commands.extend(Self::all_commands_with_deprecated(
+                    subcommands,
+                    parent_aliases,
+                    deprecated_aliases,
+                ));
             }
         }
 
@@ -107,33 +212,45 @@ impl ConfigCommand {
     }
 
     pub fn aliases(&self) -> Vec<Vec<String>> {
-        let mut aliases: Vec<Vec<String>> = Vec::new();
+        self.details
+            .aliases
+            .iter()
+            .map(|alias| Self::split_alias(alias))
+            .collect()
+    }
 
-        for alias in self.details.aliases.iter() {
-            let mut alias = vec![alias.to_string()];
+    fn split_alias(alias: &str) -> Vec<String> {
+        let mut alias = vec![alias.to_string()];
 
+        alias = alias
+            .into_iter()
+            .flat_map(|n| split_name(&n, " "))
+            .collect();
+
+        if config(".").config_commands.split_on_dash {
             alias = alias
                 .into_iter()
-                .flat_map(|n| split_name(&n, " "))
+                .flat_map(|n| split_name(&n, "-"))
+                .collect();
+        }
+        if config(".").config_commands.split_on_slash {
+            alias = alias
+                .into_iter()
+                .flat_map(|n| split_name(&n, "/"))
                 .collect();
-
-            if config(".").config_commands.split_on_dash {
-                alias = alias
-                    .into_iter()
-                    .flat_map(|n| split_name(&n, "-"))
-                    .collect();
-            }
-            if config(".").config_commands.split_on_slash {
-                alias = alias
-                    .into_iter()
-                    .flat_map(|n| split_name(&n, "/"))
-                    .collect();
-            }
-
-            aliases.push(alias);
         }
 
-        aliases
+        alias
+    }
+
+    /// Returns the deprecation message of the alias the command was
+    /// called as, if the command was called through a deprecated alias
+    pub fn deprecated_alias(&self, called_as: &[String]) -> Option<String> {
+        self.details
+            .deprecated_aliases
+            .iter()
+            .find(|(alias, _)| Self::split_alias(alias) == called_as)
+            .map(|(_, message)| message.clone())
     }
 
     pub fn is_trusted(&self) -> bool {
@@ -175,6 +292,14 @@ impl ConfigCommand {
         self.details.argparser
     }
 
+    pub fn argparser_output(&self) -> ArgparserOutput {
+        self.details.argparser_output
+    }
+
+    pub fn allow_response_files(&self) -> bool {
+        self.details.allow_response_files
+    }
+
     pub fn tags(&self) -> &BTreeMap<String, String> {
         &self.details.tags
     }
@@ -183,7 +308,34 @@ impl ConfigCommand {
         self.details.export
     }
 
+    pub fn is_hidden(&self) -> bool {
+        self.details.hidden
+    }
+
+    /// Returns the runner through which the command should be executed,
+    /// unless runners are disabled through `omni --no-runner`
+    pub fn runner(&self) -> Option<&CommandRunner> {
+        if std::env::var("OMNI_NO_RUNNER").is_ok_and(|value| !value.is_empty()) {
+            return None;
+        }
+
+        self.details.runner.as_ref()
+    }
+
+    /// The strategy to resolve the directory from which the command is
+    /// executed, defaulting to the directory of its configuration file
+    pub fn cwd(&self) -> CommandCwd {
+        self.details.cwd.clone().unwrap_or_default()
+    }
+
     pub fn exec_dir(&self) -> Result<PathBuf, String> {
+        let current_dir = std::env::current_dir().expect("Failed to get current directory");
+        self.exec_dir_from(&current_dir)
+    }
+
+    /// Returns the directory from which the command is executed when
+    /// invoked from the given directory
+    fn exec_dir_from(&self, invocation_dir: &Path) -> Result<PathBuf, String> {
         let config_file = self.source();
         let config_dir = abs_path(
             Path::new(&config_file)
@@ -191,28 +343,61 @@ impl ConfigCommand {
                 .expect("Failed to get config directory"),
         );
 
-        let exec_dir = if let Some(dir) = self.details.dir.clone() {
-            abs_path(config_dir.join(dir))
+        let parsed_arg = |name: &str| self.parsed_arg(name);
+        let args: Option<CommandCwdArgFn> = if self.argparser() {
+            Some(&parsed_arg)
         } else {
-            config_dir.to_path_buf()
+            None
         };
 
-        // Raise error if the resulting directory is not in the config directory
-        if !exec_dir.starts_with(config_dir.clone()) {
+        self.cwd()
+            .resolve_with_args(&config_dir, invocation_dir, args)
+    }
+
+    /// Returns the value of the argument referenced as `name` in the
+    /// directory of the command, as provided to the command once its
+    /// arguments are parsed, or why it cannot be expanded
+    fn parsed_arg(&self, name: &str) -> Result<String, String> {
+        let syntax = self.syntax().unwrap_or_default();
+        let param = syntax
+            .template_parameter(name)
+            .ok_or_else(|| "no parameter with that name is declared".to_string())?;
+        let dest = param.dest();
+
+        if param.takes_multiple_values() {
             return Err(format!(
-                "directory {} is not a subpath of {}",
-                exec_dir.display(),
-                config_dir.display()
+                "the parameter takes multiple values, exported as 'OMNI_ARG_{}_VALUE_N', which cannot be expanded",
+                dest.to_uppercase()
             ));
         }
 
-        Ok(exec_dir)
+        let value = match self.argparser_output() {
+            ArgparserOutput::Env => {
+                std::env::var(format!("OMNI_ARG_{}_VALUE", dest.to_uppercase())).ok()
+            }
+            ArgparserOutput::Json => std::env::var("OMNI_ARGS_JSON")
+                .ok()
+                .and_then(|args| serde_json::from_str::<serde_json::Value>(&args).ok())
+                .and_then(|args| match args.get(&dest)? {
+                    serde_json::Value::String(value) => Some(value.clone()),
+                    serde_json::Value::Number(value) => Some(value.to_string()),
+                    serde_json::Value::Bool(value) => Some(value.to_string()),
+                    _ => None,
+                }),
+        };
+
+        value
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| "the argument is empty".to_string())
     }
 
     pub fn exec(&self, argv: Vec<String>) {
-        // Get the current directory so we can store it in a variable
+        // Get the current directory so we can store it in a variable; it
+        // is also exported as the invocation directory, for commands that
+        // are not executed from it to be able to reference it
         let current_dir = std::env::current_dir().expect("Failed to get current directory");
         std::env::set_var("OMNI_CWD", current_dir.display().to_string());
+        std::env::set_var("OMNI_INVOCATION_DIR", current_dir.display().to_string());
 
         // Raise error if the resulting directory is not in the config directory
         match self.exec_dir() {
@@ -231,13 +416,392 @@ impl ConfigCommand {
             }
         }
 
-        let err = ProcessCommand::new("bash")
-            .arg("-c")
-            .arg(self.details.run.clone())
-            .arg(self.source())
-            .args(argv)
-            .exec();
+        // Apply the environment of the command on top of the dynamic
+        // environment, so that it only affects the process of the command
+        for (key, value) in env_var_operations_changes(&self.env_vars()) {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
 
-        panic!("Something went wrong: {err:?}");
+        let mut process = ProcessCommand::new("bash");
+        process.arg("-c");
+
+        match self.runner() {
+            Some(runner) => {
+                process.arg(self.runner_command_line(runner, &argv));
+            }
+            None => {
+                process
+                    .arg(self.details.run.clone())
+                    .arg(self.source())
+                    .args(argv);
+            }
+        }
+
+        let timeout = match self.timeout() {
+            Some(timeout) => timeout,
+            None => {
+                let err = process.exec();
+                panic!("Something went wrong: {err:?}");
+            }
+        };
+
+        match run_with_timeout(process, timeout) {
+            Ok(Some(status)) => exit(
+                status
+                    .code()
+                    .unwrap_or_else(|| 128 + status.signal().unwrap_or_default()),
+            ),
+            Ok(None) => {
+                eprintln!(
+                    "{} {}",
+                    "omni:".light_cyan(),
+                    format!(
+                        "command timed out after {}",
+                        humantime::format_duration(timeout)
+                    )
+                    .red()
+                );
+                exit(TIMEOUT_EXIT_CODE);
+            }
+            Err(err) => {
+                omni_error!(format!("failed to run command: {err}"));
+                exit(1);
+            }
+        }
+    }
+
+    /// Prints the plan of the execution of the command with the given
+    /// parsed arguments, without executing it or loading its dynamic
+    /// environment
+    pub fn exec_dry_run(&self, argv: &[String], args: &BTreeMap<String, String>) {
+        let current_dir = std::env::current_dir().expect("Failed to get current directory");
+        print!("{}", self.dry_run_plan(argv, args, &current_dir));
+    }
+
+    /// Returns the plan of the execution of the command when invoked
+    /// from the given directory with the given arguments, along with
+    /// their parsed values; the environment operations are shown as
+    /// defined, since resolving them could require running commands
+    fn dry_run_plan(
+        &self,
+        argv: &[String],
+        args: &BTreeMap<String, String>,
+        invocation_dir: &Path,
+    ) -> String {
+        let mut plan = String::new();
+
+        let syntax = self.syntax().unwrap_or_default();
+        let command_line = std::iter::once("omni".to_string())
+            .chain(self.name())
+            .chain(syntax.redacted_argv(argv, args))
+            .collect::<Vec<_>>();
+        plan.push_str(&format!(
+            "{} {}\n",
+            "Command:".bold(),
+            shell_words::join(command_line).light_yellow()
+        ));
+
+        let exec_dir = match self.exec_dir_from(invocation_dir) {
+            Ok(exec_dir) => exec_dir.display().to_string(),
+            Err(err) => err.red(),
+        };
+        plan.push_str(&format!("{} {}\n", "Directory:".bold(), exec_dir));
+
+        if let Some(runner) = self.runner() {
+            plan.push_str(&format!("{} {}\n", "Runner:".bold(), runner.command));
+        }
+
+        if let Some(timeout) = self.timeout() {
+            plan.push_str(&format!(
+                "{} {}\n",
+                "Timeout:".bold(),
+                humantime::format_duration(timeout)
+            ));
+        }
+
+        plan.push_str(&format!("\n{}\n", "Script:".bold()));
+        for line in self.details.run.lines() {
+            plan.push_str(&format!("  {line}\n"));
+        }
+
+        plan.push_str(&format!("\n{}\n", "Arguments:".bold()));
+        if args.is_empty() {
+            plan.push_str(&format!("  {}\n", "none".light_black()));
+        }
+        let args = syntax.redacted_args(args);
+        for (key, value) in args {
+            plan.push_str(&format!("  {}={}\n", key.light_cyan(), value));
+        }
+
+        plan.push_str(&format!("\n{}\n", "Environment:".bold()));
+        if self.details.env.is_empty() {
+            plan.push_str(&format!("  {}\n", "none".light_black()));
+        }
+        for operation in self.details.env.iter() {
+            // The values of the variables that look like secrets are masked,
+            // including the commands providing them, which may embed them
+            let value = match (&operation.command, &operation.value) {
+                (Some(_), _) | (None, Some(_)) if is_secret_env_var(&operation.name) => {
+                    SECRET_PLACEHOLDER.to_string()
+                }
+                (Some(command), _) => format!("$({})", command.command),
+                (None, Some(value)) => value.clone(),
+                (None, None) => "null".to_string(),
+            };
+            plan.push_str(&format!(
+                "  {} {} {}\n",
+                operation.name.light_cyan(),
+                operation.operation.to_string().light_black(),
+                value
+            ));
+        }
+
+        plan
+    }
+
+    /// Returns the maximum duration of a run of the command, if any
+    fn timeout(&self) -> Option<Duration> {
+        self.details
+            .timeout
+            .filter(|timeout| *timeout > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Returns the environment variables defined for the command, running
+    /// the commands providing the value of some of them from the current
+    /// directory; if such a command fails, the variable is unset
+    fn env_vars(&self) -> Vec<UpEnvVar> {
+        let current_dir = std::env::current_dir().expect("failed to get current directory");
+
+        self.details
+            .env
+            .iter()
+            .cloned()
+            .map(|mut env_var| {
+                if let Some(command) = env_var.command.take() {
+                    env_var.value = match env_command_output(
+                        &command.command,
+                        &current_dir,
+                        ENV_COMMAND_UP_TIMEOUT,
+                    ) {
+                        Ok(value) => Some(value),
+                        Err(err) => {
+                            omni_warning!(format!(
+                                "failed to get the value of {}: {}",
+                                env_var.name.light_yellow(),
+                                err
+                            ));
+                            None
+                        }
+                    };
+                }
+
+                env_var.into()
+            })
+            .collect()
+    }
+
+    /// Returns the command line running the command through the runner,
+    /// or exits with an error if the runner is not available
+    fn runner_command_line(&self, runner: &CommandRunner, argv: &[String]) -> String {
+        match runner.program() {
+            Some(program) => {
+                if let Err(err) = which::which(&program) {
+                    omni_error!(format!(
+                        "runner {} is not available: {}",
+                        program.light_yellow(),
+                        err
+                    ));
+                    exit(1);
+                }
+            }
+            None => {
+                omni_error!(format!(
+                    "invalid runner command: {}",
+                    runner.command.light_yellow()
+                ));
+                exit(1);
+            }
+        }
+
+        // The command is executed from its directory, which is used to
+        // expand the work directory tokens of the runner command
+        let exec_dir = std::env::current_dir().expect("failed to get current directory");
+        let exec_dir = exec_dir.to_string_lossy().to_string();
+        let wd = workdir(&exec_dir);
+        let (wd_root, reldir) = match (wd.root(), wd.reldir(&exec_dir)) {
+            (Some(root), Some(reldir)) => (root.to_string(), reldir),
+            _ => (exec_dir.clone(), "".to_string()),
+        };
+
+        // Only the names of the variables are part of the command line, so
+        // that their values do not show in the list of processes
+        let env = runner.filter_env(std::env::vars().map(|(key, _)| key), self.argparser());
+
+        runner.command_line(
+            &self.details.run,
+            &self.source(),
+            argv,
+            &env,
+            &wd_root,
+            &reldir,
+        )
     }
 }
+
+/// Removes the `--omni-dry-run` flag from the arguments of a command,
+/// stopping at the `--` separator; returns the remaining arguments and
+/// whether the command should only show its execution plan, which can
+/// also be requested by setting `OMNI_DRY_RUN`
+pub fn take_dry_run_flag(argv: Vec<String>) -> (Vec<String>, bool) {
+    let mut dry_run = std::env::var("OMNI_DRY_RUN")
+        .ok()
+        .and_then(|value| str_to_bool(&value))
+        .unwrap_or(false);
+
+    let mut remaining = Vec::with_capacity(argv.len());
+    let mut after_separator = false;
+    for arg in argv {
+        if !after_separator && arg == "--omni-dry-run" {
+            dry_run = true;
+            continue;
+        }
+        after_separator |= arg == "--";
+        remaining.push(arg);
+    }
+
+    (remaining, dry_run)
+}
+
+/// Runs the given process in its own process group, so that the whole
+/// group can be killed if the process does not finish before the
+/// timeout; returns the exit status of the process, or `None` if it
+/// was killed for exceeding the timeout
+fn run_with_timeout(
+    mut process: ProcessCommand,
+    timeout: Duration,
+) -> std::io::Result<Option<ExitStatus>> {
+    // The process group is given the terminal if omni has it, so that
+    // the command can still be interactive and receive the signals sent
+    // from the keyboard; this is done both here and in the parent, as
+    // either can run first
+    let omni_pgrp = getpgrp();
+    unsafe {
+        process.pre_exec(move || {
+            setpgid(Pid::from_raw(0), Pid::from_raw(0))?;
+
+            let stdin = BorrowedFd::borrow_raw(0);
+            if tcgetpgrp(stdin) == Ok(omni_pgrp) {
+                give_terminal_to(stdin, getpgrp());
+            }
+
+            Ok(())
+        });
+    }
+
+    let stdin = std::io::stdin();
+    let has_terminal = tcgetpgrp(&stdin) == Ok(omni_pgrp);
+
+    let child = process.spawn()?;
+    let pgid = Pid::from_raw(child.id() as i32);
+    if has_terminal {
+        give_terminal_to(stdin.as_fd(), pgid);
+    }
+
+    let wait_flags = Some(WaitPidFlag::WNOHANG | WaitPidFlag::WUNTRACED);
+    let mut deadline = Instant::now() + timeout;
+    let status = loop {
+        let wait_status = waitpid(pgid, wait_flags)?;
+        if let Some(status) = exit_status(wait_status) {
+            break Some(status);
+        }
+
+        if let WaitStatus::Stopped(..) = wait_status {
+            // The command was suspended, e.g. through Ctrl-Z; omni is
+            // suspended in turn so that the shell sees its job as stopped,
+            // and the command is resumed along with omni. The time spent
+            // suspended does not count toward the timeout.
+            let stopped_at = Instant::now();
+            if tcgetpgrp(&stdin) == Ok(pgid) {
+                give_terminal_to(stdin.as_fd(), omni_pgrp);
+            }
+            let _ = raise(Signal::SIGTSTP);
+
+            // Only give the terminal back if omni was resumed in the
+            // foreground, as the shell keeps it otherwise
+            if tcgetpgrp(&stdin) == Ok(omni_pgrp) {
+                give_terminal_to(stdin.as_fd(), pgid);
+            }
+            let _ = killpg(pgid, Signal::SIGCONT);
+            deadline += stopped_at.elapsed();
+        } else if Instant::now() >= deadline {
+            break None;
+        } else {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    if status.is_none() {
+        // Ask the processes to stop before killing the ones that are
+        // left, including those that kept running after the command;
+        // stopped processes are resumed so that they can handle it
+        let _ = killpg(pgid, Signal::SIGTERM);
+        let _ = killpg(pgid, Signal::SIGCONT);
+        let grace_deadline = Instant::now() + TIMEOUT_KILL_GRACE;
+        let mut exited = false;
+        while !exited && Instant::now() < grace_deadline {
+            exited = exit_status(waitpid(pgid, Some(WaitPidFlag::WNOHANG))?).is_some();
+            if !exited {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let _ = killpg(pgid, Signal::SIGKILL);
+        if !exited {
+            waitpid(pgid, None)?;
+        }
+    }
+
+    // Take the terminal back if the command had it
+    if tcgetpgrp(&stdin) == Ok(pgid) {
+        give_terminal_to(stdin.as_fd(), omni_pgrp);
+    }
+
+    Ok(status)
+}
+
+/// Makes the given process group the foreground process group of the
+/// terminal open on the given file descriptor, if any; `SIGTTOU`, which
+/// is sent when doing so from a background process group, is ignored
+/// for the time of the change only
+fn give_terminal_to(fd: BorrowedFd, pgrp: Pid) {
+    if !isatty(fd).unwrap_or(false) {
+        return;
+    }
+
+    unsafe {
+        let previous = signal(Signal::SIGTTOU, SigHandler::SigIgn);
+        let _ = tcsetpgrp(fd, pgrp);
+        if let Ok(previous) = previous {
+            let _ = signal(Signal::SIGTTOU, previous);
+        }
+    }
+}
+
+/// Returns the exit status of a process from the given wait status, if
+/// the process has terminated
+fn exit_status(wait_status: WaitStatus) -> Option<ExitStatus> {
+    match wait_status {
+        WaitStatus::Exited(_, code) => Some(ExitStatus::from_raw((code & 0xff) << 8)),
+        WaitStatus::Signaled(_, signal, core_dumped) => Some(ExitStatus::from_raw(
+            signal as i32 | if core_dumped { 0x80 } else { 0 },
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[path = "fromconfig_test.rs"]
+mod tests;
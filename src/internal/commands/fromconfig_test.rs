@@ -0,0 +1,928 @@
+use super::*;
+
+use crate::internal::config::parser::ConfigError;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::ConfigValue;
+use crate::internal::testutils::run_with_env;
+
+fn command(yaml: &str) -> ConfigCommand {
+    let details: CommandDefinition =
+        serde_yaml::from_str(yaml).expect("failed to parse command definition");
+    ConfigCommand::new("test".to_string(), details)
+}
+
+/// Loads the command definition the same way as from the configuration
+/// files, returning it along with the errors found while loading it
+fn definition_with_errors(yaml: &str) -> (CommandDefinition, Vec<ConfigError>) {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+    let definition = CommandDefinition::from_config_value(&config_value, &error_handler);
+    (definition, error_handler.errors())
+}
+
+mod hidden {
+    use super::*;
+
+    #[test]
+    fn visible_by_default() {
+        run_with_env(&[], || {
+            let command = command("run: 'true'\n");
+            assert!(!command.is_hidden());
+        });
+    }
+
+    #[test]
+    fn subcommands_inherit_hidden() {
+        run_with_env(&[], || {
+            let parent: CommandDefinition = serde_yaml::from_str(concat!(
+                "run: 'true'\n",
+                "hidden: true\n",
+                "aliases: [legacy]\n",
+                "subcommands:\n",
+                "  child:\n",
+                "    run: 'true'\n",
+            ))
+            .expect("failed to parse command definition");
+
+            let commands = ConfigCommand::all_commands(
+                HashMap::from([("parent".to_string(), parent)]),
+                vec![],
+            );
+
+            assert_eq!(commands.len(), 2);
+            assert!(commands.iter().all(|command| command.is_hidden()));
+
+            // Hidden commands can still be called through their aliases
+            let child = commands
+                .iter()
+                .find(|command| command.name() == vec!["parent", "child"])
+                .expect("child should exist");
+            assert_eq!(
+                child.aliases(),
+                vec![vec!["legacy".to_string(), "child".to_string()]]
+            );
+        });
+    }
+}
+
+mod deprecated_aliases {
+    use super::*;
+
+    fn definition(yaml: &str) -> CommandDefinition {
+        let (definition, errors) = definition_with_errors(yaml);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        definition
+    }
+
+    fn called_as(name: &str) -> Vec<String> {
+        name.split(' ').map(|part| part.to_string()).collect()
+    }
+
+    #[test]
+    fn only_deprecated_alias_has_message() {
+        run_with_env(&[], || {
+            let command = ConfigCommand::new(
+                "test".to_string(),
+                definition(concat!(
+                    "run: 'true'\n",
+                    "aliases:\n",
+                    "  - short\n",
+                    "  - name: oldname\n",
+                    "    deprecated: use test\n",
+                )),
+            );
+
+            assert_eq!(
+                command.deprecated_alias(&called_as("oldname")),
+                Some("use test".to_string())
+            );
+            assert_eq!(command.deprecated_alias(&called_as("short")), None);
+            assert_eq!(command.deprecated_alias(&called_as("test")), None);
+        });
+    }
+
+    #[test]
+    fn subcommands_inherit_deprecated_aliases() {
+        run_with_env(&[], || {
+            let parent = definition(concat!(
+                "run: 'true'\n",
+                "aliases:\n",
+                "  - name: legacy\n",
+                "    deprecated: use parent\n",
+                "  - short\n",
+                "subcommands:\n",
+                "  child:\n",
+                "    run: 'true'\n",
+                "    aliases:\n",
+                "      - kid\n",
+                "      - name: oldchild\n",
+                "        deprecated: use child\n",
+            ));
+
+            let commands = ConfigCommand::all_commands(
+                HashMap::from([("parent".to_string(), parent)]),
+                vec![],
+            );
+            let child = commands
+                .iter()
+                .find(|command| command.name() == vec!["parent", "child"])
+                .expect("child should exist");
+
+            let deprecated = |name: &str| child.deprecated_alias(&called_as(name));
+            assert_eq!(deprecated("parent child"), None);
+            assert_eq!(deprecated("short child"), None);
+            assert_eq!(deprecated("parent kid"), None);
+            assert_eq!(deprecated("legacy child"), Some("use parent".to_string()));
+            assert_eq!(deprecated("legacy kid"), Some("use parent".to_string()));
+            assert_eq!(deprecated("parent oldchild"), Some("use child".to_string()));
+            assert_eq!(deprecated("legacy oldchild"), Some("use child".to_string()));
+        });
+    }
+}
+
+mod runner {
+    use super::*;
+
+    #[test]
+    fn runs_through_runner() {
+        run_with_env(&[], || {
+            let command = command("run: make test\nrunner:\n  command: docker run mycorp/dev\n");
+
+            assert_eq!(
+                command.runner().map(|runner| runner.command.as_str()),
+                Some("docker run mycorp/dev")
+            );
+        });
+    }
+
+    #[test]
+    fn no_runner_runs_locally() {
+        run_with_env(
+            &[("OMNI_NO_RUNNER".to_string(), Some("1".to_string()))],
+            || {
+                let command =
+                    command("run: make test\nrunner:\n  command: docker run mycorp/dev\n");
+
+                assert_eq!(command.runner(), None);
+            },
+        );
+    }
+
+    #[test]
+    fn empty_no_runner_is_ignored() {
+        run_with_env(
+            &[("OMNI_NO_RUNNER".to_string(), Some("".to_string()))],
+            || {
+                let command =
+                    command("run: make test\nrunner:\n  command: docker run mycorp/dev\n");
+
+                assert!(command.runner().is_some());
+            },
+        );
+    }
+
+    #[test]
+    fn without_runner() {
+        run_with_env(&[], || {
+            let command = command("run: make test\n");
+
+            assert_eq!(command.runner(), None);
+        });
+    }
+}
+
+mod response_files {
+    use super::*;
+
+    use crate::internal::commands::Command;
+
+    fn expand(yaml: &str, argv: &[String]) -> Vec<String> {
+        Command::FromConfig(Box::new(command(yaml)))
+            .expand_response_files(argv.to_vec())
+            .expect("should expand")
+    }
+
+    fn with_response_file<F>(f: F)
+    where
+        F: FnOnce(&[String]),
+    {
+        let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tempdir.path().join("args.txt");
+        std::fs::write(&path, "--name 'John Doe'").expect("failed to write file");
+
+        f(&["-v".to_string(), format!("@{}", path.display())]);
+    }
+
+    #[test]
+    fn expanded_when_allowed() {
+        with_response_file(|argv| {
+            assert_eq!(
+                expand(
+                    "run: 'true'\nargparser: true\nallow_response_files: true\n",
+                    argv
+                ),
+                vec!["-v", "--name", "John Doe"]
+            );
+        });
+    }
+
+    #[test]
+    fn kept_as_is_by_default() {
+        with_response_file(|argv| {
+            assert_eq!(expand("run: 'true'\nargparser: true\n", argv), argv);
+        });
+    }
+
+    #[test]
+    fn kept_as_is_without_argparser() {
+        with_response_file(|argv| {
+            assert_eq!(
+                expand("run: 'true'\nallow_response_files: true\n", argv),
+                argv
+            );
+        });
+    }
+}
+
+mod cwd {
+    use super::*;
+
+    use crate::internal::env::workdir_or_init;
+
+    /// The directories of a work directory in which a command is defined
+    /// in `tools/.omni.yaml`, and invoked from `src/deep`
+    struct Dirs {
+        root: PathBuf,
+        config_dir: PathBuf,
+        invocation_dir: PathBuf,
+    }
+
+    fn with_workdir<F>(f: F)
+    where
+        F: FnOnce(&Dirs),
+    {
+        run_with_env(&[], || {
+            let project = PathBuf::from(std::env::var("HOME").unwrap()).join("project");
+            let config_dir = project.join("tools");
+            let invocation_dir = project.join("src").join("deep");
+            std::fs::create_dir_all(&config_dir).expect("failed to create config dir");
+            std::fs::create_dir_all(&invocation_dir).expect("failed to create invocation dir");
+            workdir_or_init(project.to_string_lossy()).expect("failed to init workdir");
+
+            let root = PathBuf::from(
+                workdir(project.to_string_lossy())
+                    .root()
+                    .expect("should be in a workdir"),
+            );
+
+            f(&Dirs {
+                root,
+                config_dir: abs_path(&config_dir),
+                invocation_dir: abs_path(&invocation_dir),
+            });
+        });
+    }
+
+    const PACKAGE_DIR_COMMAND: &str = concat!(
+        "run: 'true'\n",
+        "argparser: true\n",
+        "dir: '{{ workdir_root }}/packages/{{ arg:package }}'\n",
+        "syntax:\n",
+        "  parameters:\n",
+        "    - name: package\n",
+    );
+
+    fn command_in(yaml: &str, config_dir: &Path) -> ConfigCommand {
+        let (mut details, _) = definition_with_errors(yaml);
+        details.source = ConfigSource::File(config_dir.join(".omni.yaml").to_string_lossy().into());
+        ConfigCommand::new("test".to_string(), details)
+    }
+
+    fn exec_dir(yaml: &str, dirs: &Dirs) -> Result<PathBuf, String> {
+        command_in(yaml, &dirs.config_dir).exec_dir_from(&dirs.invocation_dir)
+    }
+
+    #[test]
+    fn template_with_workdir_root_and_arg() {
+        with_workdir(|dirs| {
+            let package_dir = dirs.root.join("packages").join("core");
+            std::fs::create_dir_all(&package_dir).expect("failed to create package dir");
+
+            temp_env::with_var("OMNI_ARG_PACKAGE_VALUE", Some("core"), || {
+                assert_eq!(exec_dir(PACKAGE_DIR_COMMAND, dirs), Ok(package_dir.clone()));
+            });
+        });
+    }
+
+    #[test]
+    fn template_with_json_args() {
+        with_workdir(|dirs| {
+            let package_dir = dirs.root.join("packages").join("core");
+            std::fs::create_dir_all(&package_dir).expect("failed to create package dir");
+
+            temp_env::with_var("OMNI_ARGS_JSON", Some(r#"{"package":"core"}"#), || {
+                assert_eq!(
+                    exec_dir(
+                        concat!(
+                            "run: 'true'\n",
+                            "argparser: true\n",
+                            "argparser_output: json\n",
+                            "dir: '{{ workdir_root }}/packages/{{ arg:package }}'\n",
+                            "syntax:\n",
+                            "  parameters:\n",
+                            "    - name: package\n",
+                        ),
+                        dirs
+                    ),
+                    Ok(package_dir.clone())
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn template_with_missing_arg() {
+        with_workdir(|dirs| {
+            let err =
+                exec_dir(PACKAGE_DIR_COMMAND, dirs).expect_err("should fail without the argument");
+            assert!(
+                err.contains("the argument is empty"),
+                "unexpected error: {err}"
+            );
+        });
+    }
+
+    #[test]
+    fn template_with_dashed_arg_name() {
+        with_workdir(|dirs| {
+            let package_dir = dirs.root.join("packages").join("core");
+            std::fs::create_dir_all(&package_dir).expect("failed to create package dir");
+
+            temp_env::with_var("OMNI_ARG_PACKAGE_NAME_VALUE", Some("core"), || {
+                assert_eq!(
+                    exec_dir(
+                        concat!(
+                            "run: 'true'\n",
+                            "argparser: true\n",
+                            "dir: '{{ workdir_root }}/packages/{{ arg:package-name }}'\n",
+                            "syntax:\n",
+                            "  parameters:\n",
+                            "    - name: --package-name\n",
+                        ),
+                        dirs
+                    ),
+                    Ok(package_dir.clone())
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn template_with_undeclared_arg() {
+        with_workdir(|dirs| {
+            temp_env::with_var("OMNI_ARG_PACKAGE_VALUE", Some("core"), || {
+                let err = exec_dir(
+                    "run: 'true'\nargparser: true\ndir: 'packages/{{ arg:package }}'\n",
+                    dirs,
+                )
+                .expect_err("should fail for an undeclared argument");
+                assert!(
+                    err.contains("no parameter with that name is declared"),
+                    "unexpected error: {err}"
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn template_with_multiple_values_arg() {
+        with_workdir(|dirs| {
+            temp_env::with_var("OMNI_ARG_PACKAGES_VALUE_0", Some("core"), || {
+                let err = exec_dir(
+                    concat!(
+                        "run: 'true'\n",
+                        "argparser: true\n",
+                        "dir: 'packages/{{ arg:packages }}'\n",
+                        "syntax:\n",
+                        "  parameters:\n",
+                        "    - name: --packages\n",
+                        "      type: array/str\n",
+                    ),
+                    dirs,
+                )
+                .expect_err("should fail for an argument with multiple values");
+                assert!(
+                    err.contains("takes multiple values")
+                        && err.contains("OMNI_ARG_PACKAGES_VALUE_N"),
+                    "unexpected error: {err}"
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn template_with_missing_directory() {
+        with_workdir(|dirs| {
+            temp_env::with_var("OMNI_ARG_PACKAGE_VALUE", Some("unknown"), || {
+                let err = exec_dir(PACKAGE_DIR_COMMAND, dirs)
+                    .expect_err("should fail for a missing directory");
+                assert!(err.contains("does not exist"), "unexpected error: {err}");
+            });
+        });
+    }
+
+    #[test]
+    fn config_dir_by_default() {
+        with_workdir(|dirs| {
+            assert_eq!(exec_dir("run: 'true'\n", dirs), Ok(dirs.config_dir.clone()));
+            assert_eq!(
+                exec_dir("run: 'true'\ncwd: config_dir\n", dirs),
+                Ok(dirs.config_dir.clone())
+            );
+        });
+    }
+
+    #[test]
+    fn workdir_root() {
+        with_workdir(|dirs| {
+            assert_eq!(
+                exec_dir("run: 'true'\ncwd: workdir_root\n", dirs),
+                Ok(dirs.root.clone())
+            );
+        });
+    }
+
+    #[test]
+    fn workdir_root_of_invocation_dir_outside_workdir() {
+        with_workdir(|dirs| {
+            let user_config_dir = PathBuf::from(std::env::var("HOME").unwrap()).join(".config");
+            std::fs::create_dir_all(&user_config_dir).expect("failed to create config dir");
+
+            let command = command_in("run: 'true'\ncwd: workdir_root\n", &user_config_dir);
+            assert_eq!(
+                command.exec_dir_from(&dirs.invocation_dir),
+                Ok(dirs.root.clone())
+            );
+
+            let home = abs_path(std::env::var("HOME").unwrap());
+            assert!(command.exec_dir_from(&home).is_err());
+        });
+    }
+
+    #[test]
+    fn invocation_dir() {
+        with_workdir(|dirs| {
+            assert_eq!(
+                exec_dir("run: 'true'\ncwd: invocation_dir\n", dirs),
+                Ok(dirs.invocation_dir.clone())
+            );
+        });
+    }
+
+    #[test]
+    fn explicit_path() {
+        with_workdir(|dirs| {
+            let expected = Ok(dirs.config_dir.join("scripts"));
+            assert_eq!(exec_dir("run: 'true'\ncwd: scripts\n", dirs), expected);
+            assert_eq!(exec_dir("run: 'true'\ndir: scripts\n", dirs), expected);
+
+            assert!(exec_dir("run: 'true'\ncwd: ../src\n", dirs).is_err());
+        });
+    }
+
+    #[test]
+    fn subcommands_inherit_cwd() {
+        with_workdir(|dirs| {
+            let mut parent: CommandDefinition = serde_yaml::from_str(concat!(
+                "run: 'true'\n",
+                "cwd: workdir_root\n",
+                "subcommands:\n",
+                "  inherited:\n",
+                "    run: 'true'\n",
+                "  overridden:\n",
+                "    run: 'true'\n",
+                "    cwd: invocation_dir\n",
+            ))
+            .expect("failed to parse command definition");
+            parent.source =
+                ConfigSource::File(dirs.config_dir.join(".omni.yaml").to_string_lossy().into());
+            for subcommand in parent.subcommands.as_mut().unwrap().values_mut() {
+                subcommand.source = parent.source.clone();
+            }
+
+            let commands = ConfigCommand::all_commands(
+                HashMap::from([("parent".to_string(), parent)]),
+                vec![],
+            );
+            let exec_dir = |name: &str| {
+                commands
+                    .iter()
+                    .find(|command| command.name().join(" ") == name)
+                    .expect("command should exist")
+                    .exec_dir_from(&dirs.invocation_dir)
+            };
+
+            assert_eq!(exec_dir("parent"), Ok(dirs.root.clone()));
+            assert_eq!(exec_dir("parent inherited"), Ok(dirs.root.clone()));
+            assert_eq!(
+                exec_dir("parent overridden"),
+                Ok(dirs.invocation_dir.clone())
+            );
+        });
+    }
+}
+
+mod env {
+    use super::*;
+
+    use crate::internal::config::parser::EnvConfig;
+
+    fn env(yaml: &str) -> EnvConfig {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default();
+        let env = EnvConfig::from_config_value(Some(config_value), &error_handler);
+        assert!(
+            error_handler.errors().is_empty(),
+            "unexpected errors: {:?}",
+            error_handler.errors()
+        );
+        env
+    }
+
+    fn changes(command: &ConfigCommand) -> HashMap<String, Option<String>> {
+        env_var_operations_changes(&command.env_vars())
+    }
+
+    #[test]
+    fn layered_on_current_env() {
+        run_with_env(&[], || {
+            temp_env::with_vars(
+                [
+                    ("PATH", Some("/workdir/bin:/usr/bin")),
+                    ("OMNI_TEST_UNSET", Some("value")),
+                ],
+                || {
+                    let mut command = command("run: 'true'\n");
+                    command.details.env = env(concat!(
+                        "- PATH:\n",
+                        "    prepend: /command/bin\n",
+                        "- OMNI_TEST_SET: set\n",
+                        "- OMNI_TEST_UNSET: null\n",
+                    ));
+
+                    let changes = changes(&command);
+                    assert_eq!(
+                        changes.get("PATH"),
+                        Some(&Some("/command/bin:/workdir/bin:/usr/bin".to_string()))
+                    );
+                    assert_eq!(changes.get("OMNI_TEST_SET"), Some(&Some("set".to_string())));
+                    assert_eq!(changes.get("OMNI_TEST_UNSET"), Some(&None));
+                },
+            );
+        });
+    }
+
+    #[test]
+    fn value_from_command() {
+        run_with_env(&[], || {
+            let mut command = command("run: 'true'\n");
+            command.details.env = env(concat!(
+                "OMNI_TEST_CMD:\n",
+                "  command: echo from-command\n",
+            ));
+
+            assert_eq!(
+                changes(&command).get("OMNI_TEST_CMD"),
+                Some(&Some("from-command".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn subcommands_inherit_env() {
+        run_with_env(&[], || {
+            let mut parent: CommandDefinition = serde_yaml::from_str(concat!(
+                "run: 'true'\n",
+                "subcommands:\n",
+                "  inherited:\n",
+                "    run: 'true'\n",
+                "  overridden:\n",
+                "    run: 'true'\n",
+            ))
+            .expect("failed to parse command definition");
+            parent.env = env(concat!(
+                "- OMNI_TEST_VAR: parent\n",
+                "- OMNI_TEST_LIST:\n",
+                "    append: parent\n",
+            ));
+            parent
+                .subcommands
+                .as_mut()
+                .unwrap()
+                .get_mut("overridden")
+                .unwrap()
+                .env = env(concat!(
+                "- OMNI_TEST_VAR: child\n",
+                "- OMNI_TEST_LIST:\n",
+                "    append: child\n",
+            ));
+
+            let commands = ConfigCommand::all_commands(
+                HashMap::from([("parent".to_string(), parent)]),
+                vec![],
+            );
+            let changes = |name: &str| {
+                changes(
+                    commands
+                        .iter()
+                        .find(|command| command.name().join(" ") == name)
+                        .expect("command should exist"),
+                )
+            };
+
+            let inherited = changes("parent inherited");
+            assert_eq!(
+                inherited.get("OMNI_TEST_VAR"),
+                Some(&Some("parent".to_string()))
+            );
+            assert_eq!(
+                inherited.get("OMNI_TEST_LIST"),
+                Some(&Some("parent".to_string()))
+            );
+
+            let overridden = changes("parent overridden");
+            assert_eq!(
+                overridden.get("OMNI_TEST_VAR"),
+                Some(&Some("child".to_string()))
+            );
+            assert_eq!(
+                overridden.get("OMNI_TEST_LIST"),
+                Some(&Some("parent:child".to_string()))
+            );
+        });
+    }
+}
+
+mod timeout {
+    use super::*;
+
+    fn bash(script: &str) -> ProcessCommand {
+        let mut process = ProcessCommand::new("bash");
+        process.arg("-c").arg(script);
+        process
+    }
+
+    #[test]
+    fn finishes_before_timeout() {
+        let status = run_with_timeout(bash("sleep 0.1; exit 3"), Duration::from_secs(10))
+            .expect("failed to run command");
+
+        assert_eq!(status.and_then(|status| status.code()), Some(3));
+    }
+
+    #[test]
+    fn kills_the_process_group() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let marker = tmp.path().join("marker");
+
+        // The background process is part of the process group of the
+        // command, and should be killed along with it
+        let start = Instant::now();
+        let status = run_with_timeout(
+            bash(&format!(
+                "(sleep 1; touch {}) & sleep 30",
+                marker.to_string_lossy()
+            )),
+            Duration::from_millis(200),
+        )
+        .expect("failed to run command");
+
+        assert!(status.is_none());
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        std::thread::sleep(Duration::from_millis(1500));
+        assert!(!marker.exists(), "background process was not killed");
+    }
+
+    #[test]
+    fn exit_status_of_terminated_processes() {
+        let pid = Pid::from_raw(1);
+
+        let exited = exit_status(WaitStatus::Exited(pid, 3)).expect("should have exited");
+        assert_eq!(exited.code(), Some(3));
+
+        let signaled = exit_status(WaitStatus::Signaled(pid, Signal::SIGKILL, false))
+            .expect("should have been signaled");
+        assert_eq!(signaled.code(), None);
+        assert_eq!(signaled.signal(), Some(Signal::SIGKILL as i32));
+
+        assert!(exit_status(WaitStatus::Stopped(pid, Signal::SIGTSTP)).is_none());
+        assert!(exit_status(WaitStatus::StillAlive).is_none());
+    }
+
+    #[test]
+    fn zero_disables_timeout() {
+        run_with_env(&[], || {
+            assert_eq!(command("run: 'true'\n").timeout(), None);
+            assert_eq!(command("run: 'true'\ntimeout: 0\n").timeout(), None);
+            assert_eq!(
+                command("run: 'true'\ntimeout: 30\n").timeout(),
+                Some(Duration::from_secs(30))
+            );
+        });
+    }
+
+    #[test]
+    fn subcommands_inherit_timeout() {
+        run_with_env(&[], || {
+            let parent: CommandDefinition = serde_yaml::from_str(concat!(
+                "run: 'true'\n",
+                "timeout: 300\n",
+                "subcommands:\n",
+                "  inherited:\n",
+                "    run: 'true'\n",
+                "  overridden:\n",
+                "    run: 'true'\n",
+                "    timeout: 30\n",
+                "  disabled:\n",
+                "    run: 'true'\n",
+                "    timeout: 0\n",
+            ))
+            .expect("failed to parse command definition");
+
+            let commands = ConfigCommand::all_commands(
+                HashMap::from([("parent".to_string(), parent)]),
+                vec![],
+            );
+            let timeout = |name: &str| {
+                commands
+                    .iter()
+                    .find(|command| command.name().join(" ") == name)
+                    .expect("command should exist")
+                    .timeout()
+            };
+
+            assert_eq!(timeout("parent"), Some(Duration::from_secs(300)));
+            assert_eq!(timeout("parent inherited"), Some(Duration::from_secs(300)));
+            assert_eq!(timeout("parent overridden"), Some(Duration::from_secs(30)));
+            assert_eq!(timeout("parent disabled"), None);
+        });
+    }
+}
+
+mod dry_run {
+    use super::*;
+
+    use crate::internal::commands::Command;
+
+    fn args(argv: &[&str]) -> Vec<String> {
+        argv.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn shows_resolved_plan() {
+        run_with_env(&[], || {
+            let config_dir = PathBuf::from(std::env::var("HOME").unwrap()).join("project");
+            std::fs::create_dir_all(&config_dir).expect("failed to create config dir");
+
+            let config_value = ConfigValue::from_str(concat!(
+                "run: echo \"$OMNI_ARG_FILES_VALUE_0\"\n",
+                "argparser: true\n",
+                "dir: scripts\n",
+                "env:\n",
+                "  - GREETING: hello\n",
+                "syntax:\n",
+                "  parameters:\n",
+                "    - name: --files\n",
+                "      type: array/str\n",
+            ))
+            .expect("failed to parse yaml");
+            let mut details =
+                CommandDefinition::from_config_value(&config_value, &ConfigErrorHandler::default());
+            details.source =
+                ConfigSource::File(config_dir.join(".omni.yaml").to_string_lossy().into());
+            let command = ConfigCommand::new("test".to_string(), details);
+
+            let argv = args(&["--files", "a.txt"]);
+            let parsed = Command::FromConfig(Box::new(command.clone()))
+                .exec_parse_args(argv.clone(), args(&["test"]))
+                .expect("arguments should be parsed");
+
+            let plan = command.dry_run_plan(&argv, &parsed, &config_dir);
+            assert!(plan.contains("omni test --files a.txt"), "{plan}");
+            assert!(plan.contains("OMNI_ARG_LIST"), "{plan}");
+            assert!(plan.contains("OMNI_ARG_FILES_VALUE_0"), "{plan}");
+            assert!(plan.contains("a.txt"), "{plan}");
+            assert!(
+                plan.contains(&abs_path(config_dir.join("scripts")).display().to_string()),
+                "{plan}"
+            );
+            assert!(plan.contains("echo \"$OMNI_ARG_FILES_VALUE_0\""), "{plan}");
+            assert!(plan.contains("GREETING"), "{plan}");
+        });
+    }
+
+    #[test]
+    fn masks_secrets() {
+        run_with_env(&[], || {
+            let config_dir = PathBuf::from(std::env::var("HOME").unwrap()).join("project");
+            std::fs::create_dir_all(&config_dir).expect("failed to create config dir");
+
+            let config_value = ConfigValue::from_str(concat!(
+                "run: deploy\n",
+                "argparser: true\n",
+                "env:\n",
+                "  - API_TOKEN: token-in-env\n",
+                "  - DB_PASSWORD:\n",
+                "      command: echo password-from-command\n",
+                "  - REGION: eu-west-1\n",
+                "syntax:\n",
+                "  parameters:\n",
+                "    - name: --password\n",
+                "      secret: true\n",
+                "    - name: --keys\n",
+                "      type: array/str\n",
+                "      sensitive: true\n",
+                "    - name: --user\n",
+            ))
+            .expect("failed to parse yaml");
+            let mut details =
+                CommandDefinition::from_config_value(&config_value, &ConfigErrorHandler::default());
+            details.source =
+                ConfigSource::File(config_dir.join(".omni.yaml").to_string_lossy().into());
+            let command = ConfigCommand::new("test".to_string(), details);
+
+            let argv = args(&["--password", "hunter2", "--keys=key-one", "--user", "alice"]);
+            let parsed = Command::FromConfig(Box::new(command.clone()))
+                .exec_parse_args(argv.clone(), args(&["test"]))
+                .expect("arguments should be parsed");
+
+            let plan = command.dry_run_plan(&argv, &parsed, &config_dir);
+            assert!(
+                plan.contains("omni test --password '********' '--keys=********' --user alice"),
+                "{plan}"
+            );
+            assert!(!plan.contains("hunter2"), "{plan}");
+            assert!(!plan.contains("key-one"), "{plan}");
+            assert!(!plan.contains("token-in-env"), "{plan}");
+            assert!(!plan.contains("password-from-command"), "{plan}");
+            assert!(plan.contains("OMNI_ARG_PASSWORD_VALUE"), "{plan}");
+            assert!(plan.contains("OMNI_ARG_KEYS_VALUE_0"), "{plan}");
+            assert!(plan.contains("alice"), "{plan}");
+            assert!(plan.contains("eu-west-1"), "{plan}");
+        });
+    }
+
+    #[test]
+    fn masks_secrets_in_json_arguments() {
+        run_with_env(&[], || {
+            let config_value = ConfigValue::from_str(concat!(
+                "run: deploy\n",
+                "argparser: true\n",
+                "argparser_output: json\n",
+                "syntax:\n",
+                "  parameters:\n",
+                "    - name: --password\n",
+                "      secret: true\n",
+                "    - name: --user\n",
+            ))
+            .expect("failed to parse yaml");
+            let details =
+                CommandDefinition::from_config_value(&config_value, &ConfigErrorHandler::default());
+            let command = ConfigCommand::new("test".to_string(), details);
+
+            let argv = args(&["--password", "hunter2", "--user", "alice"]);
+            let parsed = Command::FromConfig(Box::new(command.clone()))
+                .exec_parse_args(argv.clone(), args(&["test"]))
+                .expect("arguments should be parsed");
+
+            let plan = command.dry_run_plan(&argv, &parsed, &PathBuf::from("/"));
+            assert!(!plan.contains("hunter2"), "{plan}");
+            assert!(plan.contains("alice"), "{plan}");
+        });
+    }
+
+    #[test]
+    fn flag_is_taken_before_separator() {
+        run_with_env(&[("OMNI_DRY_RUN".to_string(), None)], || {
+            assert_eq!(
+                take_dry_run_flag(args(&["--omni-dry-run", "a", "--", "--omni-dry-run"])),
+                (args(&["a", "--", "--omni-dry-run"]), true)
+            );
+            assert_eq!(
+                take_dry_run_flag(args(&["a", "--", "--omni-dry-run"])),
+                (args(&["a", "--", "--omni-dry-run"]), false)
+            );
+        });
+    }
+
+    #[test]
+    fn flag_from_environment() {
+        run_with_env(
+            &[("OMNI_DRY_RUN".to_string(), Some("1".to_string()))],
+            || {
+                assert_eq!(take_dry_run_flag(args(&["a"])), (args(&["a"]), true));
+            },
+        );
+    }
+}
@@ -16,6 +16,7 @@ use walkdir::WalkDir;
 
 use crate::internal::commands::base::AutocompleteParameter;
 use crate::internal::commands::base::CommandAutocompletion;
+use crate::internal::commands::completion::emit_completion;
 use crate::internal::commands::fromconfig::ConfigCommand;
 use crate::internal::commands::path::omnipath;
 use crate::internal::commands::utils::str_to_bool;
@@ -24,7 +25,7 @@ use crate::internal::commands::Command;
 use crate::internal::config;
 use crate::internal::config::config_loader;
 use crate::internal::config::loader::WORKDIR_CONFIG_FILES;
-use crate::internal::config::parser::parse_arg_name;
+use crate::internal::config::parser::parse_arg_name_checked;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
 use crate::internal::config::utils::is_executable;
@@ -35,6 +36,7 @@ use crate::internal::config::OmniConfig;
 use crate::internal::config::SyntaxGroup;
 use crate::internal::config::SyntaxOptArg;
 use crate::internal::config::SyntaxOptArgNumValues;
+use crate::internal::config::SyntaxOptArgOrigin;
 use crate::internal::config::SyntaxOptArgType;
 use crate::internal::git::package_path_from_handle;
 use crate::internal::workdir;
@@ -306,6 +308,12 @@ impl PathCommand {
             .unwrap_or(false)
     }
 
+    pub fn allow_response_files(&self) -> bool {
+        self.file_details()
+            .map(|details| details.allow_response_files)
+            .unwrap_or(false)
+    }
+
     pub fn tags(&self) -> BTreeMap<String, String> {
         self.file_details()
             .map(|details| details.tags.clone())
@@ -350,7 +358,10 @@ impl PathCommand {
 
         match command.output() {
             Ok(output) => {
-                println!("{}", String::from_utf8_lossy(&output.stdout));
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .for_each(|line| emit_completion(line, None));
                 Ok(())
             }
             Err(_) => Err(()),
@@ -387,6 +398,7 @@ pub struct PathCommandFileDetails {
     tags: BTreeMap<String, String>,
     sync_update: bool,
     argparser: bool,
+    allow_response_files: bool,
 }
 
 impl<'de> Deserialize<'de> for PathCommandFileDetails {
@@ -458,6 +470,20 @@ impl<'de> PathCommandFileDetails {
                         false
                     }
                 });
+            let allow_response_files = map
+                .remove(YamlValue::String("allow_response_files".to_string()))
+                .is_some_and(|v| match bool::deserialize(v.clone()) {
+                    Ok(b) => b,
+                    Err(_err) => {
+                        error_handler
+                            .with_key("allow_response_files")
+                            .with_expected("boolean")
+                            .with_actual(v.to_owned())
+                            .error(ConfigErrorKind::InvalidValueType);
+
+                        false
+                    }
+                });
 
             // Deserialize the help message
             let help = map
@@ -567,6 +593,7 @@ impl<'de> PathCommandFileDetails {
                 autocompletion,
                 sync_update,
                 argparser,
+                allow_response_files,
                 help,
                 category,
                 syntax,
@@ -645,6 +672,11 @@ impl PathCommandFileDetails {
         let mut multiple = false;
         let mut requires = vec![];
         let mut conflicts_with = vec![];
+        let mut conflicts_with_all = vec![];
+        let mut required_without = vec![];
+        let mut required_without_all = vec![];
+        let mut required_if_eq = HashMap::new();
+        let mut required_if_eq_all = HashMap::new();
 
         let mut value_parts = SplitOnSeparators::new(value, &[':', '\n']);
 
@@ -665,7 +697,11 @@ impl PathCommandFileDetails {
                     match key.as_str() {
                         "required" => required = str_to_bool(value).unwrap_or(false),
                         "multiple" => multiple = str_to_bool(value).unwrap_or(false),
-                        "requires" | "conflicts_with" => {
+                        "requires"
+                        | "conflicts_with"
+                        | "conflicts_with_all"
+                        | "required_without"
+                        | "required_without_all" => {
                             let args = value
                                 .split(' ')
                                 .map(|s| s.trim().to_lowercase())
@@ -674,9 +710,27 @@ impl PathCommandFileDetails {
                             match key.as_str() {
                                 "requires" => requires.extend(args),
                                 "conflicts_with" => conflicts_with.extend(args),
+                                "conflicts_with_all" => conflicts_with_all.extend(args),
+                                "required_without" => required_without.extend(args),
+                                "required_without_all" => required_without_all.extend(args),
                                 _ => unreachable!(),
                             }
                         }
+                        "required_if_eq" | "required_if_eq_all" => {
+                            if let Some(conditions) = Self::parse_header_conditions(value) {
+                                match key.as_str() {
+                                    "required_if_eq" => required_if_eq.extend(conditions),
+                                    "required_if_eq_all" => required_if_eq_all.extend(conditions),
+                                    _ => unreachable!(),
+                                }
+                            } else {
+                                error_handler
+                                    .with_context("group", group_name)
+                                    .with_context("key", key)
+                                    .with_context("value", value)
+                                    .error(ConfigErrorKind::MetadataHeaderGroupInvalidKeyValue);
+                            }
+                        }
                         _ => {
                             error_handler
                                 .with_context("group", group_name)
@@ -713,9 +767,48 @@ impl PathCommandFileDetails {
             multiple,
             requires,
             conflicts_with,
+            conflicts_with_all,
+            required_without,
+            required_without_all,
+            required_if_eq,
+            required_if_eq_all,
         })
     }
 
+    /// Parses the conditions of `required_if_eq` and `required_if_eq_all`,
+    /// e.g. `param1=value1 param2="value 2"`, returning `None` if the
+    /// value cannot be split
+    fn parse_header_conditions(value: &str) -> Option<Vec<(String, String)>> {
+        let args = shell_words::split(value).ok()?;
+
+        let mut conditions = vec![];
+        for arg in args {
+            let mut parts = arg.splitn(2, '=');
+
+            let arg = match parts.next() {
+                Some(arg) => arg.to_lowercase(),
+                None => continue,
+            };
+
+            let value = parts.next().unwrap_or("").to_string();
+
+            // Unquote the value if needed
+            let value = if (value.starts_with('"') && value.ends_with('"'))
+                || (value.starts_with('\'') && value.ends_with('\''))
+            {
+                value[1..value.len() - 1].to_string()
+            } else {
+                value
+            };
+
+            if !arg.is_empty() {
+                conditions.push((arg, value));
+            }
+        }
+
+        Some(conditions)
+    }
+
     fn parse_header_arg(
         required: bool,
         arg_name: &str,
@@ -729,15 +822,26 @@ impl PathCommandFileDetails {
         // Prepare all arguments
         let mut dest = None;
         let mut default = None;
+        let mut default_from_config = None;
+        let mut env = None;
         let mut default_missing_value = None;
         let mut num_values = None;
         let mut value_delimiter = None;
         let mut last_arg_double_hyphen = false;
         let mut allow_hyphen_values = false;
         let mut allow_negative_numbers = false;
+        let mut ignore_case = false;
         let mut group_occurrences = false;
+        let mut secret = false;
+        let mut sensitive = false;
+        let mut hidden = false;
+        let mut deprecated = None;
+        let mut export_repo_org = false;
+        let mut section = None;
         let mut requires = vec![];
+        let mut requires_one_of = vec![];
         let mut conflicts_with = vec![];
+        let mut conflicts_with_all = vec![];
         let mut required_without = vec![];
         let mut required_without_all = vec![];
         let mut required_if_eq = HashMap::new();
@@ -745,7 +849,8 @@ impl PathCommandFileDetails {
         let mut description = String::new();
 
         // Parse the argument name
-        let (names, arg_type, placeholders, mut leftovers) = parse_arg_name(arg_name);
+        let (names, arg_type, placeholders, mut leftovers) =
+            parse_arg_name_checked(arg_name, error_handler);
         let mut arg_type = arg_type.to_string();
 
         // Now parse the rest of the string
@@ -772,6 +877,8 @@ impl PathCommandFileDetails {
 
                     match key.as_str() {
                         "default" => default = Some(value.to_string()),
+                        "default_from_config" => default_from_config = Some(value.to_string()),
+                        "env" | "default_from_env" => env = Some(value.to_string()),
                         "default_missing_value" => default_missing_value = Some(value.to_string()),
                         "dest" => dest = Some(value.to_string()),
                         "type" => arg_type = value.to_string(),
@@ -802,11 +909,20 @@ impl PathCommandFileDetails {
                         "allow_negative_numbers" | "negative_numbers" => {
                             allow_negative_numbers = str_to_bool(value).unwrap_or(false)
                         }
+                        "ignore_case" => ignore_case = str_to_bool(value).unwrap_or(false),
                         "group_occurrences" => {
                             group_occurrences = str_to_bool(value).unwrap_or(false)
                         }
+                        "secret" => secret = str_to_bool(value).unwrap_or(false),
+                        "sensitive" => sensitive = str_to_bool(value).unwrap_or(false),
+                        "hidden" => hidden = str_to_bool(value).unwrap_or(false),
+                        "deprecated" => deprecated = Some(value.to_string()),
+                        "export_repo_org" => export_repo_org = str_to_bool(value).unwrap_or(false),
+                        "section" => section = Some(value.to_string()),
                         "requires"
+                        | "requires_one_of"
                         | "conflicts_with"
+                        | "conflicts_with_all"
                         | "required_without"
                         | "required_without_all" => {
                             let args = value
@@ -816,44 +932,20 @@ impl PathCommandFileDetails {
 
                             match key.as_str() {
                                 "requires" => requires.extend(args),
+                                "requires_one_of" => requires_one_of.extend(args),
                                 "conflicts_with" => conflicts_with.extend(args),
+                                "conflicts_with_all" => conflicts_with_all.extend(args),
                                 "required_without" => required_without.extend(args),
                                 "required_without_all" => required_without_all.extend(args),
                                 _ => unreachable!(),
                             }
                         }
                         "required_if_eq" | "required_if_eq_all" => {
-                            if let Ok(args) = shell_words::split(value) {
-                                for arg in args {
-                                    let mut parts = arg.splitn(2, '=');
-
-                                    let arg = match parts.next() {
-                                        Some(arg) => arg.to_lowercase(),
-                                        None => continue,
-                                    };
-
-                                    let value = parts.next().unwrap_or("").to_string();
-
-                                    // Unquote the value if needed
-                                    let value = if (value.starts_with('"') && value.ends_with('"'))
-                                        || (value.starts_with('\'') && value.ends_with('\''))
-                                    {
-                                        value[1..value.len() - 1].to_string()
-                                    } else {
-                                        value
-                                    };
-
-                                    if !arg.is_empty() {
-                                        match key.as_str() {
-                                            "required_if_eq" => {
-                                                required_if_eq.insert(arg, value);
-                                            }
-                                            "required_if_eq_all" => {
-                                                required_if_eq_all.insert(arg, value);
-                                            }
-                                            _ => unreachable!(),
-                                        }
-                                    }
+                            if let Some(conditions) = Self::parse_header_conditions(value) {
+                                match key.as_str() {
+                                    "required_if_eq" => required_if_eq.extend(conditions),
+                                    "required_if_eq_all" => required_if_eq_all.extend(conditions),
+                                    _ => unreachable!(),
                                 }
                             } else {
                                 error_handler
@@ -895,13 +987,15 @@ impl PathCommandFileDetails {
         let arg_type = SyntaxOptArgType::from_str(&arg_type, &error_handler.with_key("arg_type"))
             .unwrap_or(SyntaxOptArgType::String);
 
-        Some(SyntaxOptArg {
+        let mut param = SyntaxOptArg {
             names,
             dest,
             desc,
             required,
             placeholders,
             default,
+            default_from_config,
+            env,
             default_missing_value,
             arg_type,
             num_values,
@@ -910,14 +1004,60 @@ impl PathCommandFileDetails {
             leftovers,
             allow_hyphen_values,
             allow_negative_numbers,
+            require_equals: false,
+            ignore_case,
             group_occurrences,
             requires,
+            requires_one_of,
             conflicts_with,
+            conflicts_with_all,
             required_without,
             required_without_all,
             required_if_eq,
             required_if_eq_all,
-        })
+            variants: vec![],
+            examples: vec![],
+            validate: None,
+            min: None,
+            max: None,
+            secret,
+            sensitive,
+            hidden,
+            deprecated,
+            deprecated_names: BTreeMap::new(),
+            export_repo_org,
+            section,
+            origin: SyntaxOptArgOrigin::Command,
+        };
+
+        if param.secret && !param.can_be_secret() {
+            error_handler
+                .with_context("parameter", arg_name)
+                .with_context("key", "secret")
+                .with_context("value", "true")
+                .error(ConfigErrorKind::MetadataHeaderParameterInvalidKeyValue);
+            param.secret = false;
+        }
+
+        if param.export_repo_org && !param.can_export_repo_org() {
+            error_handler
+                .with_context("parameter", arg_name)
+                .with_context("key", "export_repo_org")
+                .with_context("value", "true")
+                .error(ConfigErrorKind::MetadataHeaderParameterInvalidKeyValue);
+            param.export_repo_org = false;
+        }
+
+        if param.hidden && param.required {
+            error_handler
+                .with_context("parameter", arg_name)
+                .with_context("key", "hidden")
+                .with_context("value", "true")
+                .error(ConfigErrorKind::MetadataHeaderParameterInvalidKeyValue);
+            param.hidden = false;
+        }
+
+        Some(param)
     }
 
     fn from_source_file_header<R: BufRead>(
@@ -927,6 +1067,7 @@ impl PathCommandFileDetails {
         let mut autocompletion = CommandAutocompletion::Null;
         let mut sync_update = false;
         let mut argparser = false;
+        let mut allow_response_files = false;
         let mut category: Option<Vec<String>> = None;
         let mut help_lines: Vec<String> = Vec::new();
         let mut tags: BTreeMap<String, String> = BTreeMap::new();
@@ -1065,6 +1206,22 @@ impl PathCommandFileDetails {
                         }
                     };
                 }
+                ("allow_response_files", None, value) => {
+                    key_tracker.handle_seen_key(&key, lineno, false, error_handler);
+                    allow_response_files = match str_to_bool(&value) {
+                        Some(b) => b,
+                        None => {
+                            error_handler
+                                .with_lineno(lineno)
+                                .with_context("key", key)
+                                .with_context("value", value)
+                                .with_expected("boolean")
+                                .error(ConfigErrorKind::MetadataHeaderInvalidValueType);
+
+                            false
+                        }
+                    };
+                }
                 ("help", None, value) => {
                     key_tracker.handle_seen_key(&key, lineno, true, error_handler);
 
@@ -1182,6 +1339,7 @@ impl PathCommandFileDetails {
             help,
             autocompletion,
             argparser,
+            allow_response_files,
             syntax,
             tags,
             sync_update,
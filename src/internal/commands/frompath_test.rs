@@ -651,6 +651,62 @@ mod from_source_file_header {
         );
     }
 
+    #[test]
+    fn opt_with_secret() {
+        let mut reader = BufReader::new("# opt: --password: secret=true: test desc".as_bytes());
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.parameters.len(), 1);
+
+        let arg = &syntax.parameters[0];
+        assert_eq!(
+            arg,
+            &SyntaxOptArg {
+                names: vec!["--password".to_string()],
+                desc: Some("test desc".to_string()),
+                secret: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn opt_with_hidden() {
+        let mut reader = BufReader::new("# opt: --internal: hidden=true: test desc".as_bytes());
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.parameters.len(), 1);
+
+        let arg = &syntax.parameters[0];
+        assert_eq!(
+            arg,
+            &SyntaxOptArg {
+                names: vec!["--internal".to_string()],
+                desc: Some("test desc".to_string()),
+                hidden: true,
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn arg_with_allow_negative_numbers() {
         let mut reader = BufReader::new("# arg: -a: allow_negative_numbers=true: test desc\n# arg: -b: negative_numbers=true: test desc2".as_bytes());
@@ -868,6 +924,36 @@ mod from_source_file_header {
         );
     }
 
+    #[test]
+    fn arg_with_conflicts_with_all() {
+        let mut reader =
+            BufReader::new("# arg: -a: conflicts_with_all=b c: test desc\n".as_bytes());
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.parameters.len(), 1);
+
+        let arg = &syntax.parameters[0];
+        assert_eq!(
+            arg,
+            &SyntaxOptArg {
+                names: vec!["-a".to_string()],
+                desc: Some("test desc".to_string()),
+                required: true,
+                conflicts_with_all: vec!["b".to_string(), "c".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn arg_with_required_without() {
         let mut reader = BufReader::new("# arg: -a: required_without=b: test desc\n".as_bytes());
@@ -1082,6 +1168,37 @@ mod from_source_file_header {
         );
     }
 
+    #[test]
+    fn arg_with_env() {
+        let mut reader = BufReader::new(
+            "# opt: --region: env=REGION: default=us-east-1: test desc\n".as_bytes(),
+        );
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.parameters.len(), 1);
+
+        let arg = &syntax.parameters[0];
+        assert_eq!(
+            arg,
+            &SyntaxOptArg {
+                names: vec!["--region".to_string()],
+                desc: Some("test desc".to_string()),
+                env: Some("REGION".to_string()),
+                default: Some("us-east-1".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn arg_multiline_between_options_using_repeat() {
         let mut reader = BufReader::new(
@@ -1342,6 +1459,35 @@ mod from_source_file_header {
         );
     }
 
+    #[test]
+    fn arggroup_conflicts_with_all() {
+        let mut reader =
+            BufReader::new("# arggroup: a_group: conflicts_with_all=b_group c: a b\n".as_bytes());
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.groups.len(), 1);
+
+        let group = &syntax.groups[0];
+        assert_eq!(
+            group,
+            &SyntaxGroup {
+                name: "a_group".to_string(),
+                conflicts_with_all: vec!["b_group".to_string(), "c".to_string()],
+                parameters: vec!["a".to_string(), "b".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn arggroup_requires() {
         let mut reader =
@@ -1371,6 +1517,75 @@ mod from_source_file_header {
         );
     }
 
+    #[test]
+    fn arggroup_required_without() {
+        let mut reader = BufReader::new(
+            "# arggroup: a_group: required_without=d: required_without_all=e f: a b c\n".as_bytes(),
+        );
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.groups.len(), 1);
+
+        let group = &syntax.groups[0];
+        assert_eq!(
+            group,
+            &SyntaxGroup {
+                name: "a_group".to_string(),
+                required_without: vec!["d".to_string()],
+                required_without_all: vec!["e".to_string(), "f".to_string()],
+                parameters: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn arggroup_required_if_eq() {
+        let mut reader = BufReader::new(
+            concat!(
+                "# arggroup: a_group: required_if_eq=source=remote: ",
+                "required_if_eq_all=d=1 e=\"two words\": a b c\n",
+            )
+            .as_bytes(),
+        );
+        let details = PathCommandFileDetails::from_source_file_header(
+            &mut reader,
+            &ConfigErrorHandler::noop(),
+        );
+
+        assert!(details.is_some(), "Details are not present");
+        let details = details.unwrap();
+
+        assert!(details.syntax.is_some(), "Syntax is not present");
+
+        let syntax = details.syntax.unwrap();
+        assert_eq!(syntax.groups.len(), 1);
+
+        let group = &syntax.groups[0];
+        assert_eq!(
+            group,
+            &SyntaxGroup {
+                name: "a_group".to_string(),
+                required_if_eq: HashMap::from([("source".to_string(), "remote".to_string())]),
+                required_if_eq_all: HashMap::from([
+                    ("d".to_string(), "1".to_string()),
+                    ("e".to_string(), "two words".to_string()),
+                ]),
+                parameters: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn arggroup_repeat() {
         let mut reader =
@@ -1732,6 +1947,59 @@ mod from_source_file_header {
             );
         }
 
+        #[test]
+        fn test_metadata_header_parameter_secret_not_string() {
+            let mut reader =
+                BufReader::new("# opt: --count: type=int: secret=true: The count\n".as_bytes());
+
+            let error_handler = ConfigErrorHandler::new().with_file("myfile.txt");
+            let details =
+                PathCommandFileDetails::from_source_file_header(&mut reader, &error_handler);
+            let errors = error_handler.errors();
+
+            assert!(
+                errors.iter().any(|err| {
+                    matches!(
+                        err.kind(),
+                        ConfigErrorKind::MetadataHeaderParameterInvalidKeyValue
+                    ) && err.context_str("parameter") == "--count"
+                        && err.context_str("key") == "secret"
+                }),
+                "Did not find expected error, found: {errors:?}"
+            );
+
+            let details = details.expect("details should be parsed");
+            let syntax = details.syntax.expect("syntax should be parsed");
+            assert!(!syntax.parameters[0].secret);
+        }
+
+        #[test]
+        fn test_metadata_header_parameter_hidden_required() {
+            let mut reader =
+                BufReader::new("# arg: --internal: hidden=true: The internal\n".as_bytes());
+
+            let error_handler = ConfigErrorHandler::new().with_file("myfile.txt");
+            let details =
+                PathCommandFileDetails::from_source_file_header(&mut reader, &error_handler);
+            let errors = error_handler.errors();
+
+            assert!(
+                errors.iter().any(|err| {
+                    matches!(
+                        err.kind(),
+                        ConfigErrorKind::MetadataHeaderParameterInvalidKeyValue
+                    ) && err.context_str("parameter") == "--internal"
+                        && err.context_str("key") == "hidden"
+                }),
+                "Did not find expected error, found: {errors:?}"
+            );
+
+            let details = details.expect("details should be parsed");
+            let syntax = details.syntax.expect("syntax should be parsed");
+            assert!(syntax.parameters[0].required);
+            assert!(!syntax.parameters[0].hidden);
+        }
+
         #[test]
         fn test_metadata_header_parameter_unknown_config_key() {
             let mut reader = BufReader::new("# arg: test_param: unknown_key=value\n".as_bytes());
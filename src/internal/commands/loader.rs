@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::IsTerminal;
 use std::sync::Mutex;
 use std::sync::OnceLock;
 
@@ -8,15 +9,24 @@ use strsim::normalized_damerau_levenshtein;
 
 use crate::internal::commands::base::BuiltinCommand;
 use crate::internal::commands::base::Command;
+use crate::internal::commands::builtin::CachePinCommand;
+use crate::internal::commands::builtin::CacheStatsCommand;
 use crate::internal::commands::builtin::CdCommand;
 use crate::internal::commands::builtin::CloneCommand;
 use crate::internal::commands::builtin::ConfigBootstrapCommand;
 use crate::internal::commands::builtin::ConfigCheckCommand;
+use crate::internal::commands::builtin::ConfigEditCommand;
+use crate::internal::commands::builtin::ConfigManifestCommand;
 use crate::internal::commands::builtin::ConfigPathSwitchCommand;
 use crate::internal::commands::builtin::ConfigReshimCommand;
 use crate::internal::commands::builtin::ConfigTrustCommand;
+use crate::internal::commands::builtin::DoctorCommand;
+use crate::internal::commands::builtin::EnvDiffCommand;
+use crate::internal::commands::builtin::EnvExportCommand;
+use crate::internal::commands::builtin::EnvImportCommand;
 use crate::internal::commands::builtin::HelpCommand;
 use crate::internal::commands::builtin::HookCommand;
+use crate::internal::commands::builtin::HookCompletionsCommand;
 use crate::internal::commands::builtin::HookEnvCommand;
 use crate::internal::commands::builtin::HookInitCommand;
 use crate::internal::commands::builtin::HookUuidCommand;
@@ -25,9 +35,13 @@ use crate::internal::commands::builtin::ScopeCommand;
 use crate::internal::commands::builtin::StatusCommand;
 use crate::internal::commands::builtin::TidyCommand;
 use crate::internal::commands::builtin::UpCommand;
+use crate::internal::commands::builtin::WithCommand;
+use crate::internal::commands::completion::emit_completion;
 use crate::internal::commands::fromconfig::ConfigCommand;
 use crate::internal::commands::frommakefile::MakefileCommand;
 use crate::internal::commands::frompath::PathCommand;
+use crate::internal::commands::resolution::LenientAction;
+use crate::internal::commands::resolution::LenientResolution;
 use crate::internal::config;
 use crate::internal::env::shell_is_interactive;
 use crate::internal::user_interface::colors::StringColor;
@@ -90,15 +104,24 @@ impl CommandLoader {
         let mut seen = HashSet::new();
 
         // Load all builtins first
+        commands.push(CachePinCommand::new_command());
+        commands.push(CacheStatsCommand::new_command());
         commands.push(CdCommand::new_command());
         commands.push(CloneCommand::new_command());
         commands.push(ConfigBootstrapCommand::new_command());
         commands.push(ConfigCheckCommand::new_command());
+        commands.push(ConfigEditCommand::new_command());
+        commands.push(ConfigManifestCommand::new_command());
         commands.push(ConfigPathSwitchCommand::new_command());
         commands.push(ConfigReshimCommand::new_command());
         commands.push(ConfigTrustCommand::new_command());
+        commands.push(DoctorCommand::new_command());
+        commands.push(EnvDiffCommand::new_command());
+        commands.push(EnvExportCommand::new_command());
+        commands.push(EnvImportCommand::new_command());
         commands.push(HelpCommand::new_command());
         commands.push(HookCommand::new_command());
+        commands.push(HookCompletionsCommand::new_command());
         commands.push(HookEnvCommand::new_command());
         commands.push(HookInitCommand::new_command());
         commands.push(HookUuidCommand::new_command());
@@ -107,6 +130,7 @@ impl CommandLoader {
         commands.push(StatusCommand::new_command());
         commands.push(TidyCommand::new_command());
         commands.push(UpCommand::new_command());
+        commands.push(WithCommand::new_command());
 
         // Add all the builtin to seen commands
         for command in commands.iter() {
@@ -211,11 +235,44 @@ impl CommandLoader {
             match_name: Vec<String>,
             match_level: f32,
         }
+
+        // Emits the words of the command names at the position being completed,
+        // only once each; a word is described by the help of the command only
+        // if it is the last of its name, as it otherwise completes to a group
+        // of subcommands
+        fn emit_command_completions(matched_commands: &[MatchedCommand], match_pos: usize) {
+            let mut completions: Vec<(&String, Option<String>)> = vec![];
+            for matched_command in matched_commands {
+                let word = &matched_command.match_name[match_pos];
+                let description = (matched_command.match_name.len() == match_pos + 1)
+                    .then(|| matched_command.command.help_short());
+
+                match completions.iter_mut().find(|(seen, _)| *seen == word) {
+                    Some((_, seen_description)) => {
+                        if seen_description.is_none() {
+                            *seen_description = description;
+                        }
+                    }
+                    None => completions.push((word, description)),
+                }
+            }
+
+            for (word, description) in completions {
+                emit_completion(word, description.as_deref());
+            }
+        }
+
         let mut matched_commands = vec![];
 
-        // Check how much each command matches until the match_pos
+        // Check how much each command matches until the match_pos; hidden
+        // commands are only considered once their name is fully typed, so
+        // that their arguments can still be completed
         for command in self.commands.iter() {
             for command_name in command.all_names() {
+                if command.is_hidden() && command_name.len() > match_pos {
+                    continue;
+                }
+
                 let mut match_level: f32 = 0.0;
                 let match_until = std::cmp::min(command_name.len(), match_pos + 1);
                 for i in 0..match_until {
@@ -276,16 +333,13 @@ impl CommandLoader {
         // If the score ends with .5, it means that we have a partial match, so we can
         // return the matching commands right away
         if max_match_level.fract() == 0.5 {
-            for matched_command in matched_commands.iter() {
-                println!("{}", matched_command.match_name[match_pos]);
-            }
+            emit_command_completions(&matched_commands, match_pos);
             return Ok(());
         }
 
         // If we have a full match, we also want to return it
         if max_match_level == match_pos as f32 + 1.0 {
-            let matched_command = &matched_commands[0];
-            println!("{}", matched_command.match_name[match_pos]);
+            emit_command_completions(&matched_commands[..1], match_pos);
             return Ok(());
         }
 
@@ -319,13 +373,8 @@ impl CommandLoader {
         }
 
         // Finally, we can just return the list of commands that fit, if any
-        for matched_command in matched_commands.iter() {
-            if matched_command.match_name.len() <= match_pos {
-                continue;
-            }
-
-            println!("{}", matched_command.match_name[match_pos]);
-        }
+        matched_commands.retain(|matched_command| matched_command.match_name.len() > match_pos);
+        emit_command_completions(&matched_commands, match_pos);
 
         Ok(())
     }
@@ -424,6 +473,41 @@ impl CommandLoader {
             }
         }
 
+        // Then try to resolve the command leniently, as configured by the
+        // `command_resolution` configuration, before falling back on the
+        // score search
+        let resolution_config = config(".").command_resolution;
+        if !argv.is_empty() && resolution_config.allows_leniency(std::io::stdin().is_terminal()) {
+            let resolution = LenientResolution::resolve(&self.commands, argv, &resolution_config);
+            match resolution.action(&resolution_config, shell_is_interactive()) {
+                Some(LenientAction::Run) => {
+                    let found = resolution.matches()[0];
+                    omni_info!(format!(
+                        "{} resolved to {}",
+                        argv[..found.called_as.len()].join(" ").light_blue(),
+                        found.called_as.join(" ").light_blue(),
+                    ));
+                    return Some((
+                        found.command.clone(),
+                        found.called_as.clone(),
+                        found.argv.clone(),
+                    ));
+                }
+                Some(LenientAction::Suggest) => {
+                    eprintln!(
+                        "{} {} did you mean one of these commands?",
+                        "omni:".light_cyan(),
+                        "hint:".light_yellow(),
+                    );
+                    for found in resolution.matches() {
+                        eprintln!("  {}", found.called_as.join(" ").light_blue());
+                    }
+                    return None;
+                }
+                None => {}
+            }
+        }
+
         // Otherwise, we can try to find the command with the highest score
         // and ask the user if they meant that command
         let mut with_score = self
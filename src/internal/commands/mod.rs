@@ -8,7 +8,10 @@ pub(crate) use builtin::HookEnvCommand;
 pub(crate) use builtin::HookInitCommand;
 pub(crate) use builtin::HookUuidCommand;
 
+pub(crate) mod completion;
+
 mod fromconfig;
+pub(crate) use fromconfig::ConfigCommand;
 
 mod frommakefile;
 
@@ -19,6 +22,12 @@ pub(crate) use loader::command_loader;
 
 pub(crate) mod path;
 
+pub(crate) mod porcelain;
+
+pub(crate) mod resolution;
+
+mod response_files;
+
 pub(crate) mod utils;
 
 pub(crate) mod void;
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::SyntaxOptArg;
+use crate::internal::config::SyntaxOptArgNumValues;
+use crate::internal::config::SyntaxOptArgType;
+
+/// The versions of the porcelain format; once released, the format of a
+/// version never changes, so that scripts can rely on it. Any change to
+/// the format requires a new version, and the previous versions keep
+/// being supported. The `v1_contract` tests of the commands supporting
+/// the porcelain format pin that contract, and must never be changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PorcelainVersion {
+    V1,
+}
+
+impl PorcelainVersion {
+    /// The version used when `--porcelain` is passed without a version
+    pub const DEFAULT: Self = Self::V1;
+
+    /// All the versions that can be requested, from the oldest
+    pub const ALL: &[Self] = &[Self::V1];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|version| version.as_str() == name)
+            .copied()
+    }
+}
+
+/// The records of a porcelain output, in the order in which they are
+/// to be written; each record is a key and a value. Keys are made of
+/// dot-separated lowercase words and indexes, e.g. `org.0.handle`, and
+/// records that would not have a value are omitted.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PorcelainRecords {
+    records: Vec<(String, String)>,
+}
+
+impl PorcelainRecords {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        self.records.push((key.into(), value.into()));
+    }
+
+    pub fn push_bool<K: Into<String>>(&mut self, key: K, value: bool) {
+        self.push(key, if value { "true" } else { "false" });
+    }
+
+    pub fn push_opt<K: Into<String>, V: Into<String>>(&mut self, key: K, value: Option<V>) {
+        if let Some(value) = value {
+            self.push(key, value);
+        }
+    }
+}
+
+/// How to write a porcelain output, as requested on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PorcelainOutput {
+    pub version: PorcelainVersion,
+    /// Whether the records are terminated by NUL characters instead of
+    /// newlines, in which case the values are written without escaping
+    pub nul: bool,
+}
+
+impl PorcelainOutput {
+    /// Returns the parameters to add to the syntax of a command that
+    /// supports a porcelain output
+    pub fn parameters() -> Vec<SyntaxOptArg> {
+        vec![
+            SyntaxOptArg {
+                names: vec!["--porcelain".to_string()],
+                desc: Some(
+                    concat!(
+                        "Output in a stable, line-oriented format meant to be parsed by scripts; ",
+                        "the format of a version never changes between releases of omni"
+                    )
+                    .to_string(),
+                ),
+                arg_type: SyntaxOptArgType::Enum(
+                    PorcelainVersion::ALL
+                        .iter()
+                        .map(|version| version.as_str().to_string())
+                        .collect(),
+                ),
+                num_values: Some(SyntaxOptArgNumValues::Between(0, 1)),
+                default_missing_value: Some(PorcelainVersion::DEFAULT.as_str().to_string()),
+                require_equals: true,
+                ..Default::default()
+            },
+            SyntaxOptArg {
+                names: vec!["-z".to_string()],
+                desc: Some(
+                    concat!(
+                        "Terminate the porcelain records with NUL characters instead of newlines, ",
+                        "and do not escape the values"
+                    )
+                    .to_string(),
+                ),
+                arg_type: SyntaxOptArgType::Flag,
+                requires: vec!["porcelain".to_string()],
+                ..Default::default()
+            },
+        ]
+    }
+
+    /// Returns the porcelain output requested in the parsed arguments,
+    /// if any
+    pub fn from_args(args: &BTreeMap<String, ParseArgsValue>) -> Option<Self> {
+        let version = match args.get("porcelain") {
+            Some(ParseArgsValue::SingleString(Some(version))) => {
+                PorcelainVersion::from_name(version).expect("version should be validated")
+            }
+            _ => return None,
+        };
+
+        let nul = matches!(
+            args.get("z"),
+            Some(ParseArgsValue::SingleBoolean(Some(true)))
+        );
+
+        Some(Self { version, nul })
+    }
+
+    /// Renders the records in the requested version of the format.
+    ///
+    /// Version `v1` starts with a `porcelain` record holding the version,
+    /// followed by the records, each written as the key, a tab, and the
+    /// value. Records are terminated by a newline, in which case
+    /// backslashes, tabs, carriage returns and newlines in the values are
+    /// escaped as `\\`, `\t`, `\r` and `\n`, or by a NUL character, in
+    /// which case the values are written as-is.
+    pub fn render(&self, records: &PorcelainRecords) -> String {
+        match self.version {
+            PorcelainVersion::V1 => self.render_v1(records),
+        }
+    }
+
+    fn render_v1(&self, records: &PorcelainRecords) -> String {
+        let terminator = if self.nul { '\0' } else { '\n' };
+
+        let mut output = String::new();
+        let header = ("porcelain", PorcelainVersion::V1.as_str());
+        let records = records
+            .records
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()));
+
+        for (key, value) in std::iter::once(header).chain(records) {
+            output.push_str(key);
+            output.push('\t');
+            if self.nul {
+                output.push_str(value);
+            } else {
+                output.push_str(&escape_v1(value));
+            }
+            output.push(terminator);
+        }
+
+        output
+    }
+}
+
+fn escape_v1(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[path = "porcelain_test.rs"]
+mod tests;
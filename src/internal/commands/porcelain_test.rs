@@ -0,0 +1,111 @@
+use super::*;
+
+fn fixture_records() -> PorcelainRecords {
+    let mut records = PorcelainRecords::new();
+    records.push("worktree", "/home/user/git");
+    records.push("path.0.path", "/home/user/my\tdir\\bin");
+    records.push("desc", "line one\nline two\r");
+    records.push_bool("org.0.trusted", true);
+    records.push_opt("org.0.worktree", None::<String>);
+    records
+}
+
+mod porcelain_version {
+    use super::*;
+
+    #[test]
+    fn from_name() {
+        assert_eq!(
+            PorcelainVersion::from_name("v1"),
+            Some(PorcelainVersion::V1)
+        );
+        assert_eq!(PorcelainVersion::from_name("v2"), None);
+        assert_eq!(PorcelainVersion::from_name(""), None);
+    }
+
+    #[test]
+    fn default_is_v1() {
+        assert_eq!(PorcelainVersion::DEFAULT, PorcelainVersion::V1);
+    }
+}
+
+mod porcelain_output {
+    use super::*;
+
+    fn args(porcelain: Option<&str>, nul: bool) -> BTreeMap<String, ParseArgsValue> {
+        BTreeMap::from([
+            (
+                "porcelain".to_string(),
+                ParseArgsValue::SingleString(porcelain.map(str::to_string)),
+            ),
+            ("z".to_string(), ParseArgsValue::SingleBoolean(Some(nul))),
+        ])
+    }
+
+    #[test]
+    fn from_args() {
+        assert_eq!(PorcelainOutput::from_args(&args(None, false)), None);
+        assert_eq!(
+            PorcelainOutput::from_args(&args(Some("v1"), false)),
+            Some(PorcelainOutput {
+                version: PorcelainVersion::V1,
+                nul: false,
+            })
+        );
+        assert_eq!(
+            PorcelainOutput::from_args(&args(Some("v1"), true)),
+            Some(PorcelainOutput {
+                version: PorcelainVersion::V1,
+                nul: true,
+            })
+        );
+    }
+
+    #[test]
+    fn v1_contract_newline() {
+        let output = PorcelainOutput {
+            version: PorcelainVersion::V1,
+            nul: false,
+        };
+
+        assert_eq!(
+            output.render(&fixture_records()),
+            concat!(
+                "porcelain\tv1\n",
+                "worktree\t/home/user/git\n",
+                "path.0.path\t/home/user/my\\tdir\\\\bin\n",
+                "desc\tline one\\nline two\\r\n",
+                "org.0.trusted\ttrue\n",
+            )
+        );
+    }
+
+    #[test]
+    fn v1_contract_nul() {
+        let output = PorcelainOutput {
+            version: PorcelainVersion::V1,
+            nul: true,
+        };
+
+        assert_eq!(
+            output.render(&fixture_records()),
+            concat!(
+                "porcelain\tv1\0",
+                "worktree\t/home/user/git\0",
+                "path.0.path\t/home/user/my\tdir\\bin\0",
+                "desc\tline one\nline two\r\0",
+                "org.0.trusted\ttrue\0",
+            )
+        );
+    }
+
+    #[test]
+    fn v1_without_records() {
+        let output = PorcelainOutput {
+            version: PorcelainVersion::V1,
+            nul: false,
+        };
+
+        assert_eq!(output.render(&PorcelainRecords::new()), "porcelain\tv1\n");
+    }
+}
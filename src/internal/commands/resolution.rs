@@ -0,0 +1,216 @@
+use strsim::damerau_levenshtein;
+
+use crate::internal::commands::base::Command;
+use crate::internal::config::parser::CommandResolutionConfig;
+
+/// How leniently a command name was matched when it did not match
+/// any command exactly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionTier {
+    /// The words match the name of the command ignoring case
+    IgnoreCase,
+    /// The words are each contained in the words of the command name
+    Substring,
+    /// The words are within a small edit distance of the command name
+    Typo,
+}
+
+/// A command matched leniently, with the name it matched and the
+/// arguments left to be passed to it
+#[derive(Debug, Clone)]
+pub struct LenientMatch {
+    pub command: Command,
+    pub called_as: Vec<String>,
+    pub argv: Vec<String>,
+    pub tier: ResolutionTier,
+    distance: usize,
+}
+
+#[derive(Debug)]
+pub enum LenientResolution {
+    Single(Box<LenientMatch>),
+    Multiple(Vec<LenientMatch>),
+    NotFound,
+}
+
+/// What to do with the result of a lenient resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LenientAction {
+    /// Run the matched command, with a notice
+    Run,
+    /// List the matched commands as suggestions, and fail
+    Suggest,
+}
+
+impl LenientResolution {
+    /// Resolves the command for the given arguments by trying, in order,
+    /// the lenient tiers enabled in the configuration; this is expected
+    /// to be called only once an exact match has not been found
+    pub fn resolve(
+        commands: &[Command],
+        argv: &[String],
+        config: &CommandResolutionConfig,
+    ) -> Self {
+        if config.ignore_case {
+            let matches = words_matches(commands, argv, ResolutionTier::IgnoreCase, |arg, word| {
+                arg.to_lowercase() == word.to_lowercase()
+            });
+            if !matches.is_empty() {
+                return Self::from_matches(matches);
+            }
+        }
+
+        if config.substring {
+            let matches = words_matches(commands, argv, ResolutionTier::Substring, |arg, word| {
+                word.to_lowercase().contains(&arg.to_lowercase())
+            });
+            if !matches.is_empty() {
+                return Self::from_matches(matches);
+            }
+        }
+
+        if config.max_distance > 0 {
+            let matches = typo_matches(commands, argv, config.max_distance as usize);
+            if !matches.is_empty() {
+                return Self::from_matches(matches);
+            }
+        }
+
+        Self::NotFound
+    }
+
+    fn from_matches(mut matches: Vec<LenientMatch>) -> Self {
+        if matches.len() == 1 {
+            Self::Single(Box::new(matches.remove(0)))
+        } else {
+            matches.sort_by(|a, b| {
+                a.distance
+                    .cmp(&b.distance)
+                    .then_with(|| a.called_as.cmp(&b.called_as))
+            });
+            Self::Multiple(matches)
+        }
+    }
+
+    /// Returns what should be done with the resolved command(s), if any;
+    /// a command matched ignoring case is run directly as there is no doubt
+    /// about which command was meant, while other single matches are only
+    /// run directly if autocorrect is enabled; when running interactively,
+    /// anything else is left to the interactive command search
+    pub fn action(
+        &self,
+        config: &CommandResolutionConfig,
+        interactive: bool,
+    ) -> Option<LenientAction> {
+        match self {
+            Self::Single(found) if found.tier == ResolutionTier::IgnoreCase => {
+                Some(LenientAction::Run)
+            }
+            Self::Single(_) if config.autocorrect => Some(LenientAction::Run),
+            Self::Single(_) | Self::Multiple(_) if !interactive => Some(LenientAction::Suggest),
+            Self::Single(_) | Self::Multiple(_) | Self::NotFound => None,
+        }
+    }
+
+    pub fn matches(&self) -> Vec<&LenientMatch> {
+        match self {
+            Self::Single(found) => vec![found.as_ref()],
+            Self::Multiple(matches) => matches.iter().collect(),
+            Self::NotFound => vec![],
+        }
+    }
+}
+
+/// Returns the commands for which each word of one of their names matches
+/// the argument at the same position, keeping only the most specific ones
+fn words_matches<F>(
+    commands: &[Command],
+    argv: &[String],
+    tier: ResolutionTier,
+    word_matches: F,
+) -> Vec<LenientMatch>
+where
+    F: Fn(&str, &str) -> bool,
+{
+    let mut matches: Vec<LenientMatch> = vec![];
+
+    for command in resolvable_commands(commands) {
+        let best_name = command
+            .all_names()
+            .into_iter()
+            .filter(|name| !name.is_empty() && name.len() <= argv.len())
+            .filter(|name| {
+                name.iter()
+                    .zip(argv.iter())
+                    .all(|(word, arg)| word_matches(arg, word))
+            })
+            .max_by_key(|name| name.len());
+
+        if let Some(name) = best_name {
+            matches.push(LenientMatch {
+                command: command.clone(),
+                argv: argv[name.len()..].to_vec(),
+                called_as: name,
+                tier,
+                distance: 0,
+            });
+        }
+    }
+
+    // Only keep the matches for the longest names, as a command matching
+    // more words is more specific than its parent commands
+    let longest = matches
+        .iter()
+        .map(|found| found.called_as.len())
+        .max()
+        .unwrap_or(0);
+    matches.retain(|found| found.called_as.len() == longest);
+
+    matches
+}
+
+/// Returns the commands for which one of the names is within the allowed
+/// edit distance of the arguments; the distance allowed is also limited
+/// to a third of the length of the name, with a minimum of 1, so that
+/// short names do not match about anything
+fn typo_matches(commands: &[Command], argv: &[String], max_distance: usize) -> Vec<LenientMatch> {
+    let mut matches = vec![];
+
+    for command in resolvable_commands(commands) {
+        let best_name = command
+            .all_names()
+            .into_iter()
+            .filter(|name| !name.is_empty() && name.len() <= argv.len())
+            .filter_map(|name| {
+                let typed = argv[..name.len()].join(" ").to_lowercase();
+                let expected = name.join(" ").to_lowercase();
+                let allowed = max_distance.min((expected.chars().count() / 3).max(1));
+
+                let distance = damerau_levenshtein(&typed, &expected);
+                (distance <= allowed).then_some((name, distance))
+            })
+            .min_by_key(|(name, distance)| (*distance, usize::MAX - name.len()));
+
+        if let Some((name, distance)) = best_name {
+            matches.push(LenientMatch {
+                command: command.clone(),
+                argv: argv[name.len()..].to_vec(),
+                called_as: name,
+                tier: ResolutionTier::Typo,
+                distance,
+            });
+        }
+    }
+
+    matches
+}
+
+fn resolvable_commands(commands: &[Command]) -> impl Iterator<Item = &Command> {
+    commands
+        .iter()
+        .filter(|command| !matches!(command, Command::Void(_)))
+}
+
+#[cfg(test)]
+#[path = "resolution_test.rs"]
+mod tests;
@@ -0,0 +1,219 @@
+use super::*;
+
+use crate::internal::commands::ConfigCommand;
+use crate::internal::config::CommandDefinition;
+use crate::internal::testutils::run_with_env;
+
+fn command(name: &str) -> Command {
+    let details: CommandDefinition =
+        serde_yaml::from_str(&format!("run: echo {name}\n")).expect("failed to parse command");
+    ConfigCommand::new(name.to_string(), details).into()
+}
+
+fn fixture_commands() -> Vec<Command> {
+    ["deploy", "deploy rollback", "describe", "test", "text"]
+        .iter()
+        .map(|name| command(name))
+        .collect()
+}
+
+fn args(argv: &[&str]) -> Vec<String> {
+    argv.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn resolve(argv: &[&str], config: &CommandResolutionConfig) -> LenientResolution {
+    LenientResolution::resolve(&fixture_commands(), &args(argv), config)
+}
+
+fn called_as(resolution: &LenientResolution) -> Vec<String> {
+    resolution
+        .matches()
+        .iter()
+        .map(|found| found.called_as.join(" "))
+        .collect()
+}
+
+mod tiers {
+    use super::*;
+
+    #[test]
+    fn ignore_case() {
+        run_with_env(&[], || {
+            let resolution = resolve(&["Deploy", "--env", "prod"], &Default::default());
+
+            match &resolution {
+                LenientResolution::Single(found) => {
+                    assert_eq!(found.tier, ResolutionTier::IgnoreCase);
+                    assert_eq!(found.called_as, args(&["deploy"]));
+                    assert_eq!(found.argv, args(&["--env", "prod"]));
+                }
+                _ => panic!("expected a single match, got {resolution:?}"),
+            }
+        });
+    }
+
+    #[test]
+    fn ignore_case_prefers_longest_name() {
+        run_with_env(&[], || {
+            let resolution = resolve(&["DEPLOY", "Rollback"], &Default::default());
+
+            assert_eq!(called_as(&resolution), vec!["deploy rollback"]);
+            assert_eq!(resolution.matches()[0].argv, Vec::<String>::new());
+        });
+    }
+
+    #[test]
+    fn substring() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig {
+                substring: true,
+                max_distance: 0,
+                ..Default::default()
+            };
+            let resolution = resolve(&["scri"], &config);
+
+            match &resolution {
+                LenientResolution::Single(found) => {
+                    assert_eq!(found.tier, ResolutionTier::Substring);
+                    assert_eq!(found.called_as, args(&["describe"]));
+                }
+                _ => panic!("expected a single match, got {resolution:?}"),
+            }
+        });
+    }
+
+    #[test]
+    fn substring_disabled_by_default() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig {
+                max_distance: 0,
+                ..Default::default()
+            };
+            let resolution = resolve(&["scri"], &config);
+
+            assert!(matches!(resolution, LenientResolution::NotFound));
+        });
+    }
+
+    #[test]
+    fn typo() {
+        run_with_env(&[], || {
+            let resolution = resolve(&["dpeloy", "now"], &Default::default());
+
+            match &resolution {
+                LenientResolution::Single(found) => {
+                    assert_eq!(found.tier, ResolutionTier::Typo);
+                    assert_eq!(found.called_as, args(&["deploy"]));
+                    assert_eq!(found.argv, args(&["now"]));
+                }
+                _ => panic!("expected a single match, got {resolution:?}"),
+            }
+        });
+    }
+
+    #[test]
+    fn typo_beyond_max_distance() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig {
+                max_distance: 1,
+                ..Default::default()
+            };
+            let resolution = resolve(&["dpeolyy"], &config);
+
+            assert!(matches!(resolution, LenientResolution::NotFound));
+        });
+    }
+
+    #[test]
+    fn typo_limited_for_short_names() {
+        run_with_env(&[], || {
+            let resolution = resolve(&["tset"], &Default::default());
+            assert_eq!(called_as(&resolution), vec!["test"]);
+
+            // "txsx" is within the maximum distance of "test", but
+            // too far for a name of that length
+            let resolution = resolve(&["txsx"], &Default::default());
+            assert!(matches!(resolution, LenientResolution::NotFound));
+        });
+    }
+
+    #[test]
+    fn not_found() {
+        run_with_env(&[], || {
+            let resolution = resolve(&["unknown"], &Default::default());
+
+            assert!(matches!(resolution, LenientResolution::NotFound));
+            assert_eq!(resolution.action(&Default::default(), true), None);
+        });
+    }
+}
+
+mod actions {
+    use super::*;
+
+    #[test]
+    fn ignore_case_runs() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig::default();
+            let resolution = resolve(&["DESCRIBE"], &config);
+
+            assert_eq!(resolution.action(&config, true), Some(LenientAction::Run));
+            assert_eq!(resolution.action(&config, false), Some(LenientAction::Run));
+        });
+    }
+
+    #[test]
+    fn single_typo_left_to_interactive_search() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig::default();
+            let resolution = resolve(&["dpeloy"], &config);
+
+            assert_eq!(resolution.action(&config, true), None);
+        });
+    }
+
+    #[test]
+    fn single_typo_suggests_when_not_interactive() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig::default();
+            let resolution = resolve(&["dpeloy"], &config);
+
+            assert_eq!(
+                resolution.action(&config, false),
+                Some(LenientAction::Suggest)
+            );
+        });
+    }
+
+    #[test]
+    fn single_typo_runs_with_autocorrect() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig {
+                autocorrect: true,
+                ..Default::default()
+            };
+            let resolution = resolve(&["dpeloy"], &config);
+
+            assert_eq!(resolution.action(&config, true), Some(LenientAction::Run));
+            assert_eq!(resolution.action(&config, false), Some(LenientAction::Run));
+        });
+    }
+
+    #[test]
+    fn multiple_candidates_suggest_when_not_interactive() {
+        run_with_env(&[], || {
+            let config = CommandResolutionConfig {
+                autocorrect: true,
+                ..Default::default()
+            };
+            let resolution = resolve(&["tezt"], &config);
+
+            assert_eq!(called_as(&resolution), vec!["test", "text"]);
+            assert_eq!(
+                resolution.action(&config, false),
+                Some(LenientAction::Suggest)
+            );
+            assert_eq!(resolution.action(&config, true), None);
+        });
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::PathBuf;
+
+use crate::internal::errors::ResponseFileError;
+
+/// The maximum number of response files that can be nested in one another
+const MAX_DEPTH: usize = 8;
+
+/// A word read from a response file, along with the line and column
+/// at which it starts
+#[derive(Debug, PartialEq)]
+struct ResponseFileWord {
+    value: String,
+    line: usize,
+    column: usize,
+}
+
+/// Replaces the `@path` arguments by the arguments read from the
+/// response file at `path`, relative to the current directory; the
+/// arguments read from a response file can themselves be response files,
+/// up to a maximum depth. An argument starting with `@@` is kept as an
+/// argument starting with a single `@`, and `@` alone is kept as-is.
+pub fn expand_response_files(argv: Vec<String>) -> Result<Vec<String>, ResponseFileError> {
+    let mut expanded = vec![];
+    let mut stack = vec![];
+
+    for (idx, arg) in argv.into_iter().enumerate() {
+        let origin = format!("argument {}", idx + 1);
+        expand_arg(arg, &origin, &mut stack, &mut expanded)?;
+    }
+
+    Ok(expanded)
+}
+
+fn expand_arg(
+    arg: String,
+    origin: &str,
+    stack: &mut Vec<(PathBuf, String)>,
+    expanded: &mut Vec<String>,
+) -> Result<(), ResponseFileError> {
+    let path = match arg.strip_prefix('@') {
+        Some(rest) if rest.starts_with('@') => {
+            expanded.push(rest.to_string());
+            return Ok(());
+        }
+        Some(path) if !path.is_empty() => path.to_string(),
+        _ => {
+            expanded.push(arg);
+            return Ok(());
+        }
+    };
+
+    let read_error = |error: std::io::Error| ResponseFileError::Read {
+        origin: origin.to_string(),
+        path: path.clone(),
+        error: error.to_string(),
+    };
+
+    let canonical = std::fs::canonicalize(&path).map_err(read_error)?;
+    if let Some(pos) = stack.iter().position(|(seen, _)| *seen == canonical) {
+        let mut chain = stack[pos..]
+            .iter()
+            .map(|(_, path)| path.clone())
+            .collect::<Vec<_>>();
+        chain.push(path);
+        return Err(ResponseFileError::Cycle {
+            origin: origin.to_string(),
+            chain,
+        });
+    }
+
+    if stack.len() >= MAX_DEPTH {
+        return Err(ResponseFileError::TooDeep {
+            origin: origin.to_string(),
+            path,
+            max_depth: MAX_DEPTH,
+        });
+    }
+
+    let contents = std::fs::read_to_string(&canonical).map_err(read_error)?;
+    let words = split_response_file(&path, &contents)?;
+
+    stack.push((canonical, path.clone()));
+    for word in words {
+        let origin = format!("{}:{}:{}", path, word.line, word.column);
+        expand_arg(word.value, &origin, stack, expanded)?;
+    }
+    stack.pop();
+
+    Ok(())
+}
+
+/// Splits the contents of a response file into words. Words are separated
+/// by whitespace, including newlines. Single quotes preserve the literal
+/// value of the characters they enclose. Double quotes do the same, except
+/// for a backslash followed by a double quote, a backslash or a newline.
+/// Outside of quotes, a backslash preserves the literal value of the next
+/// character. A backslash followed by a newline is a line continuation and
+/// is removed. A `#` at the start of a word starts a comment that runs
+/// until the end of the line.
+fn split_response_file(
+    path: &str,
+    contents: &str,
+) -> Result<Vec<ResponseFileWord>, ResponseFileError> {
+    let contents = contents.replace("\r\n", "\n");
+
+    let mut words = vec![];
+    let mut current: Option<ResponseFileWord> = None;
+
+    let mut chars = contents.chars().peekable();
+    let (mut line, mut column) = (1, 0);
+
+    // Advances to the next character, keeping track of its position
+    let mut next = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        let c = chars.next()?;
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+        Some((c, line, column))
+    };
+
+    while let Some((c, c_line, c_column)) = next(&mut chars) {
+        if c.is_whitespace() {
+            if let Some(word) = current.take() {
+                words.push(word);
+            }
+            continue;
+        }
+
+        if c == '#' && current.is_none() {
+            while chars.peek().is_some_and(|c| *c != '\n') {
+                next(&mut chars);
+            }
+            continue;
+        }
+
+        let word = current.get_or_insert_with(|| ResponseFileWord {
+            value: String::new(),
+            line: c_line,
+            column: c_column,
+        });
+
+        match c {
+            '\'' => loop {
+                match next(&mut chars) {
+                    Some(('\'', _, _)) => break,
+                    Some((c, _, _)) => word.value.push(c),
+                    None => {
+                        return Err(ResponseFileError::UnterminatedQuote {
+                            path: path.to_string(),
+                            line: c_line,
+                            column: c_column,
+                            quote: "single",
+                        })
+                    }
+                }
+            },
+            '"' => loop {
+                match next(&mut chars) {
+                    Some(('"', _, _)) => break,
+                    Some(('\\', _, _)) => match chars.peek() {
+                        Some('\n') => {
+                            next(&mut chars);
+                        }
+                        Some('"') | Some('\\') => {
+                            let (c, _, _) = next(&mut chars).expect("peeked character");
+                            word.value.push(c);
+                        }
+                        _ => word.value.push('\\'),
+                    },
+                    Some((c, _, _)) => word.value.push(c),
+                    None => {
+                        return Err(ResponseFileError::UnterminatedQuote {
+                            path: path.to_string(),
+                            line: c_line,
+                            column: c_column,
+                            quote: "double",
+                        })
+                    }
+                }
+            },
+            '\\' => match next(&mut chars) {
+                Some(('\n', _, _)) => {
+                    // A line continuation does not start a word
+                    if word.value.is_empty() && word.line == c_line && word.column == c_column {
+                        current = None;
+                    }
+                }
+                Some((c, _, _)) => word.value.push(c),
+                None => {
+                    return Err(ResponseFileError::DanglingEscape {
+                        path: path.to_string(),
+                        line: c_line,
+                        column: c_column,
+                    })
+                }
+            },
+            c => word.value.push(c),
+        }
+    }
+
+    if let Some(word) = current.take() {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+#[path = "response_files_test.rs"]
+mod tests;
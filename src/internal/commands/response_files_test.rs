@@ -0,0 +1,289 @@
+use super::*;
+
+fn words(contents: &str) -> Vec<String> {
+    split_response_file("args.txt", contents)
+        .expect("should split")
+        .into_iter()
+        .map(|word| word.value)
+        .collect()
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Writes the given files in a temporary directory, returning the
+/// directory along with a function to get the path of a file in it
+fn response_files(files: &[(&str, &str)]) -> (tempfile::TempDir, impl Fn(&str) -> String) {
+    let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+    for (name, contents) in files {
+        std::fs::write(tempdir.path().join(name), contents).expect("failed to write file");
+    }
+
+    let base = tempdir.path().to_path_buf();
+    let path = move |name: &str| base.join(name).to_string_lossy().to_string();
+
+    (tempdir, path)
+}
+
+mod split_response_file {
+    use super::*;
+
+    #[test]
+    fn whitespace_and_newlines() {
+        assert_eq!(
+            words("  --input a.txt\n\t--output  b.txt\n\n-v"),
+            args(&["--input", "a.txt", "--output", "b.txt", "-v"])
+        );
+    }
+
+    #[test]
+    fn single_quotes() {
+        assert_eq!(
+            words(r#"'hello world' 'it''s' 'a\b' '' '"x"'"#),
+            args(&["hello world", "its", r"a\b", "", r#""x""#])
+        );
+    }
+
+    #[test]
+    fn double_quotes() {
+        assert_eq!(
+            words(r#""hello world" "say \"hi\"" "a\\b" "a\b" "'x'" """#),
+            args(&["hello world", r#"say "hi""#, r"a\b", r"a\b", "'x'", ""])
+        );
+    }
+
+    #[test]
+    fn quotes_within_a_word() {
+        assert_eq!(
+            words(r#"--name="John Doe" pre'fix 'suffix"#),
+            args(&["--name=John Doe", "prefix suffix"])
+        );
+    }
+
+    #[test]
+    fn backslash_escapes() {
+        assert_eq!(
+            words(r"hello\ world \'x\' \#not-a-comment"),
+            args(&["hello world", "'x'", "#not-a-comment"])
+        );
+    }
+
+    #[test]
+    fn line_continuations() {
+        assert_eq!(
+            words("--input \\\n  a.txt ab\\\ncd \"multi\\\nline\"\r\nlast \\\r\n"),
+            args(&["--input", "a.txt", "abcd", "multiline", "last"])
+        );
+    }
+
+    #[test]
+    fn quoted_newlines_are_kept() {
+        assert_eq!(words("'a\nb' \"c\nd\""), args(&["a\nb", "c\nd"]));
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            words("# generated file\n--flag # trailing comment\nvalue#hash '# quoted'"),
+            args(&["--flag", "value#hash", "# quoted"])
+        );
+    }
+
+    #[test]
+    fn word_positions() {
+        let words = split_response_file("args.txt", "a  'b c'\n\n  d")
+            .expect("should split")
+            .into_iter()
+            .map(|word| (word.value, word.line, word.column))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            words,
+            vec![
+                ("a".to_string(), 1, 1),
+                ("b c".to_string(), 1, 4),
+                ("d".to_string(), 3, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_quotes() {
+        assert_eq!(
+            split_response_file("args.txt", "a\n  'b c\nd"),
+            Err(ResponseFileError::UnterminatedQuote {
+                path: "args.txt".to_string(),
+                line: 2,
+                column: 3,
+                quote: "single",
+            })
+        );
+        assert_eq!(
+            split_response_file("args.txt", r#"a "b \""#),
+            Err(ResponseFileError::UnterminatedQuote {
+                path: "args.txt".to_string(),
+                line: 1,
+                column: 3,
+                quote: "double",
+            })
+        );
+    }
+
+    #[test]
+    fn dangling_escape() {
+        let err = split_response_file("args.txt", "a b\\").expect_err("should fail");
+        assert_eq!(
+            err.to_string(),
+            "args.txt:1:4: escape character at the end of the file"
+        );
+    }
+}
+
+mod expand_response_files {
+    use super::*;
+
+    #[test]
+    fn splices_arguments_in_place() {
+        let (_tempdir, path) = response_files(&[("args.txt", "--input 'a b.txt'\n--force\n")]);
+
+        let expanded =
+            expand_response_files(args(&["first", &format!("@{}", path("args.txt")), "last"]))
+                .expect("should expand");
+
+        assert_eq!(
+            expanded,
+            args(&["first", "--input", "a b.txt", "--force", "last"])
+        );
+    }
+
+    #[test]
+    fn escaped_and_lone_at_signs_are_kept() {
+        let expanded = expand_response_files(args(&["@@user", "@", "a@b"])).expect("should expand");
+
+        assert_eq!(expanded, args(&["@user", "@", "a@b"]));
+    }
+
+    #[test]
+    fn nested_response_files() {
+        let (_tempdir, path) = response_files(&[("outer.txt", ""), ("inner.txt", "b '@@literal'")]);
+        std::fs::write(path("outer.txt"), format!("a @{} c", path("inner.txt")))
+            .expect("failed to write file");
+
+        let expanded = expand_response_files(args(&[&format!("@{}", path("outer.txt"))]))
+            .expect("should expand");
+
+        assert_eq!(expanded, args(&["a", "b", "@literal", "c"]));
+    }
+
+    #[test]
+    fn same_file_can_be_used_multiple_times() {
+        let (_tempdir, path) = response_files(&[("args.txt", "a")]);
+        let arg = format!("@{}", path("args.txt"));
+
+        let expanded = expand_response_files(args(&[&arg, &arg])).expect("should expand");
+
+        assert_eq!(expanded, args(&["a", "a"]));
+    }
+
+    #[test]
+    fn cycle_is_an_error() {
+        let (_tempdir, path) = response_files(&[("a.txt", ""), ("b.txt", "")]);
+        std::fs::write(path("a.txt"), format!("x @{}", path("b.txt"))).unwrap();
+        std::fs::write(path("b.txt"), format!("y\n  @{}", path("a.txt"))).unwrap();
+
+        let err = expand_response_files(args(&["first", &format!("@{}", path("a.txt"))]))
+            .expect_err("should fail");
+
+        assert_eq!(
+            err,
+            ResponseFileError::Cycle {
+                origin: format!("{}:2:3", path("b.txt")),
+                chain: vec![path("a.txt"), path("b.txt"), path("a.txt")],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}:2:3: response file cycle: {} -> {} -> {}",
+                path("b.txt"),
+                path("a.txt"),
+                path("b.txt"),
+                path("a.txt"),
+            )
+        );
+    }
+
+    #[test]
+    fn self_reference_is_a_cycle() {
+        let (_tempdir, path) = response_files(&[("a.txt", "")]);
+        std::fs::write(path("a.txt"), format!("@{}", path("a.txt"))).unwrap();
+
+        let err = expand_response_files(args(&[&format!("@{}", path("a.txt"))]))
+            .expect_err("should fail");
+
+        assert!(matches!(err, ResponseFileError::Cycle { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn maximum_depth() {
+        let names = (0..=MAX_DEPTH)
+            .map(|idx| format!("{idx}.txt"))
+            .collect::<Vec<_>>();
+        let (_tempdir, path) = response_files(&[]);
+        for (idx, name) in names.iter().enumerate() {
+            let contents = match names.get(idx + 1) {
+                Some(next) => format!("@{}", path(next)),
+                None => "end".to_string(),
+            };
+            std::fs::write(path(name), contents).unwrap();
+        }
+
+        // Starting one level down stays within the maximum depth
+        let expanded = expand_response_files(args(&[&format!("@{}", path(&names[1]))]))
+            .expect("should expand");
+        assert_eq!(expanded, args(&["end"]));
+
+        let err = expand_response_files(args(&[&format!("@{}", path(&names[0]))]))
+            .expect_err("should fail");
+        assert_eq!(
+            err,
+            ResponseFileError::TooDeep {
+                origin: format!("{}:1:1", path(&names[MAX_DEPTH - 1])),
+                path: path(&names[MAX_DEPTH]),
+                max_depth: MAX_DEPTH,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_file_names_the_argument() {
+        let (_tempdir, path) = response_files(&[]);
+
+        let err = expand_response_files(args(&["a", &format!("@{}", path("missing.txt"))]))
+            .expect_err("should fail");
+
+        match err {
+            ResponseFileError::Read {
+                origin, path: p, ..
+            } => {
+                assert_eq!(origin, "argument 2");
+                assert_eq!(p, path("missing.txt"));
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn split_errors_name_the_file() {
+        let (_tempdir, path) = response_files(&[("args.txt", "ok\n\"oops")]);
+
+        let err = expand_response_files(args(&[&format!("@{}", path("args.txt"))]))
+            .expect_err("should fail");
+
+        assert_eq!(
+            err.to_string(),
+            format!("{}:2:1: unterminated double quote", path("args.txt"))
+        );
+    }
+}
@@ -10,6 +10,7 @@ use requestty::question::completions;
 use requestty::question::Completions;
 
 use crate::internal::config::config;
+use crate::internal::config::parser::PathsCanonicalize;
 use crate::internal::env::omni_cmd_file;
 use crate::internal::env::user_home;
 use crate::internal::env::Shell;
@@ -75,6 +76,42 @@ where
     absolute_path
 }
 
+/// Returns the absolute path for the given path, the same way as
+/// `abs_path`, but resolving relative paths from the current directory
+/// as it was reached, i.e. without resolving the symlinks of `$PWD`
+pub fn logical_abs_path(path: impl AsRef<Path>) -> PathBuf {
+    abs_path_from_path(path.as_ref(), Some(logical_current_dir().as_path()))
+}
+
+/// Returns the current directory as it was reached, i.e. `$PWD` if it
+/// points to the current directory, which keeps the symlinks that were
+/// followed to get there; the physical current directory otherwise
+pub fn logical_current_dir() -> PathBuf {
+    let current_dir = std::env::current_dir().expect("Failed to determine current directory");
+
+    if let Some(pwd) = std::env::var_os("PWD").map(PathBuf::from) {
+        if pwd.is_absolute() && std::fs::canonicalize(&pwd).ok().as_ref() == Some(&current_dir) {
+            return pwd.clean();
+        }
+    }
+
+    current_dir
+}
+
+/// Canonicalizes the path according to the given `paths.canonicalize`
+/// policy: with the `physical` policy, symlinks are resolved if the path
+/// exists, while with the `logical` policy the path is only made absolute
+pub fn canonicalize_path(path: impl AsRef<Path>, policy: PathsCanonicalize) -> PathBuf {
+    let path = path.as_ref();
+
+    match policy {
+        PathsCanonicalize::Physical => {
+            std::fs::canonicalize(path).unwrap_or_else(|_| logical_abs_path(path))
+        }
+        PathsCanonicalize::Logical => logical_abs_path(path),
+    }
+}
+
 fn omni_cmd_with_disposition(cmd: &str, disposition: Option<&str>) -> Result<(), io::Error> {
     let cmd_file = omni_cmd_file().expect("shell integration not loaded");
 
@@ -96,6 +133,36 @@ fn omni_cmd_with_disposition(cmd: &str, disposition: Option<&str>) -> Result<(),
     Ok(())
 }
 
+/// Returns the editor to use to edit files, from the `VISUAL` or `EDITOR`
+/// environment variables, falling back to vim or nano if available
+pub fn find_editor() -> Option<String> {
+    // Try VISUAL first
+    if let Ok(visual) = std::env::var("VISUAL") {
+        if !visual.is_empty() {
+            return Some(visual);
+        }
+    }
+
+    // Then try EDITOR
+    if let Ok(editor) = std::env::var("EDITOR") {
+        if !editor.is_empty() {
+            return Some(editor);
+        }
+    }
+
+    // Try vim
+    if let Ok(vim) = which::which("vim") {
+        return Some(vim.to_string_lossy().to_string());
+    }
+
+    // Try nano
+    if let Ok(nano) = which::which("nano") {
+        return Some(nano.to_string_lossy().to_string());
+    }
+
+    None
+}
+
 pub fn omni_cmd_always(cmd: &str) -> Result<(), io::Error> {
     omni_cmd_with_disposition(cmd, Some("always"))
 }
@@ -364,3 +431,7 @@ impl<'a> Iterator for SplitOnSeparators<'a> {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "utils_test.rs"]
+mod tests;
@@ -0,0 +1,84 @@
+use super::*;
+
+use std::os::unix::fs::symlink;
+
+use crate::internal::testutils::run_with_env;
+
+/// Creates a `link` symlink to a `real` directory in a temporary directory,
+/// returning the canonical path of the temporary directory
+fn symlinked_tree(tempdir: &tempfile::TempDir) -> PathBuf {
+    let base = std::fs::canonicalize(tempdir.path()).expect("failed to canonicalize");
+    std::fs::create_dir_all(base.join("real/dir")).expect("failed to create dir");
+    symlink(base.join("real"), base.join("link")).expect("failed to create symlink");
+    base
+}
+
+mod abs_path {
+    use super::*;
+
+    #[test]
+    fn keeps_symlinks() {
+        run_with_env(&[], || {
+            let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+            let base = symlinked_tree(&tempdir);
+
+            assert_eq!(abs_path(base.join("link/dir")), base.join("link/dir"));
+            assert_eq!(
+                abs_path_from_path(PathBuf::from("dir"), Some(base.join("link"))),
+                base.join("link/dir")
+            );
+        });
+    }
+
+    #[test]
+    fn logical_abs_path_keeps_symlinks() {
+        run_with_env(&[], || {
+            let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+            let base = symlinked_tree(&tempdir);
+
+            assert_eq!(
+                logical_abs_path(base.join("link/dir")),
+                base.join("link/dir")
+            );
+        });
+    }
+}
+
+mod canonicalize_path {
+    use super::*;
+
+    #[test]
+    fn physical_policy_resolves_symlinks() {
+        run_with_env(&[], || {
+            let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+            let base = symlinked_tree(&tempdir);
+
+            assert_eq!(
+                canonicalize_path(base.join("link/dir"), PathsCanonicalize::Physical),
+                base.join("real/dir")
+            );
+
+            // Paths that do not exist are only cleaned
+            assert_eq!(
+                canonicalize_path(
+                    base.join("link/dir/../missing"),
+                    PathsCanonicalize::Physical
+                ),
+                base.join("link/missing")
+            );
+        });
+    }
+
+    #[test]
+    fn logical_policy_keeps_symlinks() {
+        run_with_env(&[], || {
+            let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+            let base = symlinked_tree(&tempdir);
+
+            assert_eq!(
+                canonicalize_path(base.join("link/dir"), PathsCanonicalize::Logical),
+                base.join("link/dir")
+            );
+        });
+    }
+}
@@ -184,12 +184,35 @@ impl ConfigValue {
                 None => continue,
             };
 
+            // A merge strategy tag on the value is equivalent to the
+            // corresponding suffix on the key, e.g. `key: !append [...]`
+            // is handled the same way as `key__toappend: [...]`
+            let (key, value) = match value {
+                serde_yaml::Value::Tagged(tagged) => match Self::tag_key_suffix(&tagged.tag) {
+                    Some(suffix) => (format!("{key}{suffix}"), tagged.value),
+                    None => (key.to_string(), serde_yaml::Value::Tagged(tagged)),
+                },
+                value => (key.to_string(), value),
+            };
+
             let new_value = ConfigValue::from_value(source.clone(), scope.clone(), value);
-            config_mapping.insert(key.to_string(), new_value);
+            config_mapping.insert(key, new_value);
         }
         config_mapping
     }
 
+    fn tag_key_suffix(tag: &serde_yaml::value::Tag) -> Option<&'static str> {
+        if *tag == "append" {
+            Some("__toappend")
+        } else if *tag == "prepend" {
+            Some("__toprepend")
+        } else if *tag == "replace" {
+            Some("__toreplace")
+        } else {
+            None
+        }
+    }
+
     fn from_sequence(
         source: ConfigSource,
         scope: ConfigScope,
@@ -909,7 +932,12 @@ impl ConfigValue {
                         let mut keypath = keypath.clone();
                         keypath.push(key.clone());
 
-                        if let Some(self_value) = self_mapping.get_mut(&key) {
+                        // A key marked to be replaced does not keep any of
+                        // its previous contents, even when it is a mapping
+                        if let Some(self_value) = self_mapping
+                            .get_mut(&key)
+                            .filter(|_| children_strategy != ConfigExtendStrategy::Replace)
+                        {
                             self_value.extend(
                                 value,
                                 options.with_strategy(children_strategy),
@@ -1241,3 +1269,7 @@ impl From<&ConfigValue> for serde_yaml::Value {
         value.as_serde_yaml()
     }
 }
+
+#[cfg(test)]
+#[path = "config_value_test.rs"]
+mod tests;
@@ -0,0 +1,96 @@
+use super::*;
+
+/// Merges the given YAML documents, in order, the same way the
+/// configuration files are layered by the loader
+fn merged(documents: &[&str]) -> serde_yaml::Value {
+    let mut config = ConfigValue::empty();
+    for document in documents {
+        let value: serde_yaml::Value = serde_yaml::from_str(document).expect("invalid yaml");
+        config.extend(
+            ConfigValue::from_value(ConfigSource::Null, ConfigScope::Null, value),
+            ConfigExtendOptions::new(),
+            vec![],
+        );
+    }
+    config.as_serde_yaml()
+}
+
+fn yaml(document: &str) -> serde_yaml::Value {
+    serde_yaml::from_str(document).expect("invalid yaml")
+}
+
+mod extend_strategies {
+    use super::*;
+
+    #[test]
+    fn list_replaced_by_default() {
+        let config = merged(&["category: [a, b]", "category: [c]"]);
+
+        assert_eq!(config, yaml("category: [c]"));
+    }
+
+    #[test]
+    fn mapping_merged_by_default() {
+        let config = merged(&["env: {A: '1', B: '2'}", "env: {B: '3'}"]);
+
+        assert_eq!(config, yaml("env: {A: '1', B: '3'}"));
+    }
+
+    #[test]
+    fn append_tag_extends_list() {
+        let config = merged(&["category: [a, b]", "category: !append [b, c]"]);
+
+        assert_eq!(config, yaml("category: [a, b, c]"));
+    }
+
+    #[test]
+    fn prepend_tag_extends_list() {
+        let config = merged(&["category: [a, b]", "category: !prepend [c]"]);
+
+        assert_eq!(config, yaml("category: [c, a, b]"));
+    }
+
+    #[test]
+    fn replace_tag_overrides_list() {
+        let config = merged(&[
+            "category: [a, b]",
+            "category: !append [c]",
+            "category: !replace [d]",
+        ]);
+
+        assert_eq!(config, yaml("category: [d]"));
+    }
+
+    #[test]
+    fn replace_tag_overrides_mapping() {
+        let config = merged(&["env: {A: '1', B: '2'}", "env: !replace {B: '3'}"]);
+
+        assert_eq!(config, yaml("env: {B: '3'}"));
+    }
+
+    #[test]
+    fn replace_strategy_keeps_other_keys() {
+        let mut config = ConfigValue::from_str("env: {A: '1'}\ncategory: [a]").unwrap();
+        config.extend(
+            ConfigValue::from_str("category: [b]").unwrap(),
+            ConfigExtendOptions::new().with_strategy(ConfigExtendStrategy::Replace),
+            vec![],
+        );
+
+        assert_eq!(config.as_serde_yaml(), yaml("env: {A: '1'}\ncategory: [b]"));
+    }
+
+    #[test]
+    fn tag_on_nested_key() {
+        let config = merged(&["parent: {items: [a, b]}", "parent: {items: !append [c]}"]);
+
+        assert_eq!(config, yaml("parent: {items: [a, b, c]}"));
+    }
+
+    #[test]
+    fn tag_equivalent_to_key_suffix() {
+        let config = merged(&["category: [a]", "category__toappend: [b]"]);
+
+        assert_eq!(config, yaml("category: [a, b]"));
+    }
+}
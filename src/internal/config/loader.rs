@@ -14,11 +14,23 @@ use fs4::fs_std::FileExt;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 
+use crate::internal::build::current_omni_version;
+use crate::internal::cache::ParsedConfigCache;
+use crate::internal::commands::utils::canonicalize_path;
+use crate::internal::config::parser::canonicalize_policy;
+use crate::internal::config::parser::flush_canonicalize_policy;
+use crate::internal::config::parser::ConfigError;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::profile::apply_profile;
+use crate::internal::config::profile::select_profile;
+use crate::internal::config::profile::PROFILE_ENV_VAR;
 use crate::internal::config::ConfigExtendOptions;
 use crate::internal::config::ConfigExtendStrategy;
+use crate::internal::config::ConfigProfile;
 use crate::internal::config::ConfigScope;
 use crate::internal::config::ConfigSource;
 use crate::internal::config::ConfigValue;
+use crate::internal::config::OmniConfig;
 use crate::internal::env::config_home;
 use crate::internal::env::user_home;
 use crate::internal::env::xdg_config_home;
@@ -27,6 +39,7 @@ use crate::internal::user_interface::StringColor;
 use crate::internal::workdir;
 use crate::omni_error;
 use crate::omni_print;
+use crate::omni_warning;
 
 lazy_static! {
     #[derive(Debug)]
@@ -42,8 +55,7 @@ pub fn config_loader(path: &str) -> ConfigLoader {
     let path = if path == "/" {
         path.to_owned()
     } else {
-        std::fs::canonicalize(path)
-            .unwrap_or(path.to_owned().into())
+        canonicalize_path(path, canonicalize_policy())
             .to_str()
             .unwrap()
             .to_owned()
@@ -57,11 +69,11 @@ pub fn flush_config_loader(path: &str) {
     if path == "/" {
         let mut config_loader_per_path = CONFIG_LOADER_PER_PATH.lock().unwrap();
         config_loader_per_path.loaders.clear();
+        flush_canonicalize_policy();
         return;
     }
 
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -105,6 +117,14 @@ impl ConfigLoaderPerPath {
 pub struct ConfigLoader {
     pub loaded_config_files: Vec<String>,
     pub raw_config: ConfigValue,
+    /// The profile overlaid onto the configuration, if any
+    pub profile: Option<ConfigProfile>,
+    /// The global configuration before any profile was overlaid, from
+    /// which the configuration of each path selects its own profile
+    unprofiled_config: ConfigValue,
+    /// The errors of the configuration files requiring a more recent
+    /// version of omni, reported before running a command
+    pub min_omni_version_errors: Vec<ConfigError>,
 }
 
 impl ConfigLoader {
@@ -196,10 +216,13 @@ impl ConfigLoader {
         config_files
     }
 
-    fn new_global() -> Self {
+    pub(crate) fn new_global() -> Self {
         let mut new_config_loader = Self {
             loaded_config_files: vec![],
             raw_config: ConfigValue::empty(),
+            profile: None,
+            unprofiled_config: ConfigValue::empty(),
+            min_omni_version_errors: vec![],
         };
 
         new_config_loader
@@ -210,6 +233,9 @@ impl ConfigLoader {
         new_config_loader
             .import_config_files(Self::system_config_files("post"), ConfigScope::System);
 
+        new_config_loader.unprofiled_config = new_config_loader.raw_config.clone();
+        new_config_loader.overlay_profile(None, None);
+
         new_config_loader
     }
 
@@ -217,6 +243,9 @@ impl ConfigLoader {
         Self {
             loaded_config_files: vec![],
             raw_config: ConfigValue::new_null(ConfigSource::Null, ConfigScope::Null),
+            profile: None,
+            unprofiled_config: ConfigValue::new_null(ConfigSource::Null, ConfigScope::Null),
+            min_omni_version_errors: vec![],
         }
     }
 
@@ -226,10 +255,11 @@ impl ConfigLoader {
         loader
     }
 
-    pub fn edit_main_user_config_file<F>(edit_fn: F) -> io::Result<()>
-    where
-        F: FnOnce(&mut ConfigValue) -> bool,
-    {
+    /// Returns the main user configuration file, which is the one edited
+    /// when updating the user configuration: the last loaded user
+    /// configuration file that can be written to, or the last one that
+    /// could be created if none exists
+    pub fn main_user_config_file() -> Option<PathBuf> {
         // We will use the list of user config files, but in reverse, to
         // look for the first user configuration file that we can write
         // into, and that would be the LAST loaded file when reading
@@ -288,12 +318,32 @@ impl ConfigLoader {
             }
         }
 
-        // If we get here and we still have no file, we can raise an error
-        if found_file.is_none() {
-            omni_error!("unable to find a writeable user config file");
-            exit(1);
-        }
-        let found_file = found_file.unwrap();
+        found_file
+    }
+
+    /// Returns the main configuration file of the work directory at the
+    /// given root: the last loaded workdir configuration file that exists,
+    /// or the first one if none exists
+    pub fn main_workdir_config_file(wd_root: &str) -> PathBuf {
+        WORKDIR_CONFIG_FILES
+            .iter()
+            .rev()
+            .map(|file| PathBuf::from(wd_root).join(file))
+            .find(|file| file.is_file())
+            .unwrap_or_else(|| PathBuf::from(wd_root).join(WORKDIR_CONFIG_FILES[0]))
+    }
+
+    pub fn edit_main_user_config_file<F>(edit_fn: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut ConfigValue) -> bool,
+    {
+        let found_file = match Self::main_user_config_file() {
+            Some(found_file) => found_file,
+            None => {
+                omni_error!("unable to find a writeable user config file");
+                exit(1);
+            }
+        };
         let file_path = format!("{}", found_file.display());
 
         Self::edit_user_config_file(file_path, edit_fn)
@@ -365,7 +415,10 @@ impl ConfigLoader {
     pub fn get_local(&self, path: &str) -> Self {
         let mut new_config_loader = Self {
             loaded_config_files: self.loaded_config_files.clone(),
-            raw_config: self.raw_config.clone(),
+            raw_config: self.unprofiled_config.clone(),
+            profile: None,
+            unprofiled_config: self.unprofiled_config.clone(),
+            min_omni_version_errors: self.min_omni_version_errors.clone(),
         };
 
         let wd = workdir(path);
@@ -380,11 +433,71 @@ impl ConfigLoader {
             workdir_config_files.push(format!("{wd_root}/{workdir_config_file}"));
         }
 
+        // The profile is overlaid before the workdir configuration files
+        // are imported, so that they take precedence over the profile
+        let workdir_profile = Self::workdir_profile(&workdir_config_files);
+        new_config_loader.overlay_profile(Some(wd_root), workdir_profile.as_deref());
+
         new_config_loader.import_config_files(workdir_config_files, ConfigScope::Workdir);
 
         new_config_loader
     }
 
+    /// Returns the attribution of the value of the given top-level key
+    /// of the configuration, if it was provided by the active profile and
+    /// not overridden by the workdir configuration
+    pub fn attribution(&self, key: &str) -> Option<String> {
+        let profile = self.profile.as_ref()?;
+        if !profile.provides(key) {
+            return None;
+        }
+
+        match self.raw_config.get(key) {
+            Some(value) if value.current_scope() != ConfigScope::Workdir => {
+                Some(profile.attribution())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the profile selected by the given workdir configuration
+    /// files, if any; the last file defining it takes precedence
+    fn workdir_profile(config_files: &[String]) -> Option<String> {
+        config_files.iter().rev().find_map(|config_file| {
+            let contents = std::fs::read_to_string(config_file).ok()?;
+            let metadata = std::fs::metadata(config_file).ok();
+            let value = ParsedConfigCache::get()
+                .parse(config_file, &contents, metadata.as_ref())
+                .ok()?;
+            value.get("profile")?.as_str().map(|name| name.to_string())
+        })
+    }
+
+    /// Selects the active profile and overlays its settings onto the
+    /// configuration; the profile can be selected, in order of precedence,
+    /// by the `OMNI_PROFILE` environment variable, the `profile` key of
+    /// the workdir configuration, the `profiles_by_path` rules, or the
+    /// `profile` key of the global configuration
+    fn overlay_profile(&mut self, path: Option<&str>, workdir_profile: Option<&str>) {
+        let env_profile = std::env::var(PROFILE_ENV_VAR).ok();
+        let (name, selection) = match select_profile(
+            env_profile.as_deref(),
+            workdir_profile,
+            path,
+            &self.unprofiled_config,
+        ) {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        self.profile = apply_profile(&mut self.raw_config, &name, selection);
+        if self.profile.is_none() {
+            omni_warning!(format!(
+                "profile '{name}' is not defined in the configuration"
+            ));
+        }
+    }
+
     pub fn import_config_files(&mut self, config_files: Vec<String>, scope: ConfigScope) {
         for config_file in &config_files.clone() {
             if !self.loaded_config_files.contains(config_file) {
@@ -397,6 +510,53 @@ impl ConfigLoader {
         self.import_config_file_with_strategy(config_file, scope, ConfigExtendStrategy::Default)
     }
 
+    /// Records an error if the running omni does not satisfy the minimum
+    /// version required by the configuration file; the configuration file
+    /// is still loaded, so that the shell hooks keep working, and the error
+    /// is only reported before running a command, see
+    /// `report_min_omni_version_errors`.
+    fn check_min_omni_version(&mut self, config_file: &str, config_value: &ConfigValue) {
+        let error_handler = ConfigErrorHandler::new().with_file(config_file);
+        if !OmniConfig::check_min_omni_version(config_value, current_omni_version(), &error_handler)
+        {
+            self.min_omni_version_errors.extend(error_handler.errors());
+        }
+    }
+
+    /// Reports the configuration files requiring a more recent version of
+    /// omni, as a more recent configuration would otherwise likely be
+    /// misinterpreted; exits with an error if there is any, unless
+    /// `OMNI_LENIENT_MIN_VERSION` is set, in which case only a warning is
+    /// shown.
+    pub fn report_min_omni_version_errors(&self) {
+        if self.min_omni_version_errors.is_empty() {
+            return;
+        }
+
+        let lenient =
+            std::env::var_os("OMNI_LENIENT_MIN_VERSION").is_some_and(|lenient| !lenient.is_empty());
+
+        for error in &self.min_omni_version_errors {
+            let config_file = error.file();
+            if lenient {
+                omni_warning!(format!(
+                    "{config_file}: {}; loading it anyway",
+                    error.message()
+                ));
+            } else {
+                omni_print!(format!(
+                    "{} {}; please upgrade omni",
+                    format!("configuration error: {config_file}:").red(),
+                    error.message()
+                ));
+            }
+        }
+
+        if !lenient {
+            exit(1);
+        }
+    }
+
     pub fn import_config_file_with_strategy(
         &mut self,
         config_file: &str,
@@ -414,7 +574,8 @@ impl ConfigLoader {
             return;
         }
 
-        match serde_yaml::from_str::<serde_yaml::Value>(&contents) {
+        let metadata = file.metadata().ok();
+        match ParsedConfigCache::get().parse(config_file, &contents, metadata.as_ref()) {
             Ok(value) => {
                 self.loaded_config_files.push(config_file.to_string());
 
@@ -426,6 +587,8 @@ impl ConfigLoader {
                 };
 
                 let config_value = ConfigValue::from_value(source, scope.clone(), value);
+                self.check_min_omni_version(config_file, &config_value);
+
                 self.raw_config.extend(
                     config_value,
                     ConfigExtendOptions::new().with_strategy(strategy),
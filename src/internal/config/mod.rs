@@ -16,14 +16,20 @@ pub(crate) use parser::config;
 pub(crate) use parser::flush_config;
 pub(crate) use parser::global_config;
 pub(crate) use parser::CommandDefinition;
+pub(crate) use parser::CommandRunner;
 pub(crate) use parser::CommandSyntax;
 pub(crate) use parser::OmniConfig;
 pub(crate) use parser::OrgConfig;
 pub(crate) use parser::SyntaxGroup;
 pub(crate) use parser::SyntaxOptArg;
 pub(crate) use parser::SyntaxOptArgNumValues;
+pub(crate) use parser::SyntaxOptArgOrigin;
 pub(crate) use parser::SyntaxOptArgType;
 
+pub(crate) mod profile;
+pub(crate) use profile::ConfigProfile;
+pub(crate) use profile::ConfigProfileSelection;
+
 pub(crate) mod up;
 
 pub(crate) mod bootstrap;
@@ -3,6 +3,7 @@ use serde::Serialize;
 
 use crate::internal::config::parser::errors::ConfigErrorHandler;
 use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::utils::parse_duration_or_default;
 use crate::internal::config::ConfigScope;
 use crate::internal::config::ConfigValue;
 
@@ -11,6 +12,8 @@ pub struct AskPassConfig {
     pub enabled: bool,
     pub enable_gui: bool,
     pub prefer_gui: bool,
+    pub cache_ttl: u64,
+    pub always_prompt: Vec<String>,
 }
 
 impl Default for AskPassConfig {
@@ -19,6 +22,8 @@ impl Default for AskPassConfig {
             enabled: Self::DEFAULT_ENABLED,
             enable_gui: Self::DEFAULT_ENABLE_GUI,
             prefer_gui: Self::DEFAULT_PREFER_GUI,
+            cache_ttl: Self::DEFAULT_CACHE_TTL,
+            always_prompt: vec![],
         }
     }
 }
@@ -27,6 +32,7 @@ impl AskPassConfig {
     const DEFAULT_ENABLED: bool = true;
     const DEFAULT_ENABLE_GUI: bool = true;
     const DEFAULT_PREFER_GUI: bool = false;
+    const DEFAULT_CACHE_TTL: u64 = 300; // 5 minutes
 
     pub(super) fn from_config_value(
         config_value: Option<ConfigValue>,
@@ -67,6 +73,13 @@ impl AskPassConfig {
                 Self::DEFAULT_PREFER_GUI,
                 &error_handler.with_key("prefer_gui"),
             ),
+            cache_ttl: parse_duration_or_default(
+                config_value.get("cache_ttl").as_ref(),
+                Self::DEFAULT_CACHE_TTL,
+                &error_handler.with_key("cache_ttl"),
+            ),
+            always_prompt: config_value
+                .get_as_str_array("always_prompt", &error_handler.with_key("always_prompt")),
         }
     }
 }
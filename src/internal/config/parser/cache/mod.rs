@@ -16,5 +16,20 @@ pub(crate) use homebrew::HomebrewCacheConfig;
 mod mise;
 pub(crate) use mise::MiseCacheConfig;
 
+mod pipx;
+pub(crate) use pipx::PipxCacheConfig;
+
+mod repo_match;
+pub(crate) use repo_match::RepoMatchCacheConfig;
+
+mod up_advisories;
+pub(crate) use up_advisories::UpAdvisoriesCacheConfig;
+
 mod up_environment;
 pub(crate) use up_environment::UpEnvironmentCacheConfig;
+
+mod up_history;
+pub(crate) use up_history::UpHistoryCacheConfig;
+
+mod workdir_history;
+pub(crate) use workdir_history::WorkdirHistoryCacheConfig;
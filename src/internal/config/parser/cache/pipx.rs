@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::utils::parse_duration_or_default;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipxCacheConfig {
+    pub cleanup_after: u64,
+}
+
+impl Default for PipxCacheConfig {
+    fn default() -> Self {
+        Self {
+            cleanup_after: Self::DEFAULT_CLEANUP_AFTER,
+        }
+    }
+}
+
+impl PipxCacheConfig {
+    const DEFAULT_CLEANUP_AFTER: u64 = 604800; // 1 week
+
+    pub fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        let cleanup_after = parse_duration_or_default(
+            config_value.get("cleanup_after").as_ref(),
+            Self::DEFAULT_CLEANUP_AFTER,
+            &error_handler.with_key("cleanup_after"),
+        );
+
+        Self { cleanup_after }
+    }
+}
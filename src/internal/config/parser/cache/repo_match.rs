@@ -0,0 +1,64 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::utils::parse_duration_or_default;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepoMatchCacheConfig {
+    pub retention: u64,
+    pub max_entries: usize,
+}
+
+impl Default for RepoMatchCacheConfig {
+    fn default() -> Self {
+        Self {
+            retention: Self::DEFAULT_RETENTION,
+            max_entries: Self::DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+impl RepoMatchCacheConfig {
+    const DEFAULT_RETENTION: u64 = 15552000; // 180 days
+    const DEFAULT_MAX_ENTRIES: usize = 200;
+
+    pub fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        let retention = parse_duration_or_default(
+            config_value.get("retention").as_ref(),
+            Self::DEFAULT_RETENTION,
+            &error_handler.with_key("retention"),
+        );
+
+        let max_entries = match config_value.get("max_entries") {
+            Some(v) => match v.as_unsigned_integer() {
+                Some(v) => v as usize,
+                None => {
+                    error_handler
+                        .with_key("max_entries")
+                        .with_expected("unsigned integer")
+                        .with_actual(v)
+                        .error(ConfigErrorKind::InvalidValueType);
+
+                    Self::DEFAULT_MAX_ENTRIES
+                }
+            },
+            None => Self::DEFAULT_MAX_ENTRIES,
+        };
+
+        Self {
+            retention,
+            max_entries,
+        }
+    }
+}
@@ -6,7 +6,12 @@ use crate::internal::config::parser::cache::GithubReleaseCacheConfig;
 use crate::internal::config::parser::cache::GoInstallCacheConfig;
 use crate::internal::config::parser::cache::HomebrewCacheConfig;
 use crate::internal::config::parser::cache::MiseCacheConfig;
+use crate::internal::config::parser::cache::PipxCacheConfig;
+use crate::internal::config::parser::cache::RepoMatchCacheConfig;
+use crate::internal::config::parser::cache::UpAdvisoriesCacheConfig;
 use crate::internal::config::parser::cache::UpEnvironmentCacheConfig;
+use crate::internal::config::parser::cache::UpHistoryCacheConfig;
+use crate::internal::config::parser::cache::WorkdirHistoryCacheConfig;
 use crate::internal::config::parser::errors::ConfigErrorHandler;
 use crate::internal::config::parser::errors::ConfigErrorKind;
 use crate::internal::config::ConfigValue;
@@ -16,11 +21,16 @@ use crate::internal::env::cache_home;
 pub struct CacheConfig {
     pub path: String,
     pub environment: UpEnvironmentCacheConfig,
+    pub up_history: UpHistoryCacheConfig,
+    pub repo_match: RepoMatchCacheConfig,
+    pub workdir_history: WorkdirHistoryCacheConfig,
+    pub up_advisories: UpAdvisoriesCacheConfig,
     pub github_release: GithubReleaseCacheConfig,
     pub cargo_install: CargoInstallCacheConfig,
     pub go_install: GoInstallCacheConfig,
     pub homebrew: HomebrewCacheConfig,
     pub mise: MiseCacheConfig,
+    pub pipx: PipxCacheConfig,
 }
 
 impl Default for CacheConfig {
@@ -28,11 +38,16 @@ impl Default for CacheConfig {
         Self {
             path: cache_home(),
             environment: UpEnvironmentCacheConfig::default(),
+            up_history: UpHistoryCacheConfig::default(),
+            repo_match: RepoMatchCacheConfig::default(),
+            workdir_history: WorkdirHistoryCacheConfig::default(),
+            up_advisories: UpAdvisoriesCacheConfig::default(),
             github_release: GithubReleaseCacheConfig::default(),
             cargo_install: CargoInstallCacheConfig::default(),
             go_install: GoInstallCacheConfig::default(),
             homebrew: HomebrewCacheConfig::default(),
             mise: MiseCacheConfig::default(),
+            pipx: PipxCacheConfig::default(),
         }
     }
 }
@@ -67,6 +82,22 @@ impl CacheConfig {
             config_value.get("environment"),
             &error_handler.with_key("environment"),
         );
+        let up_history = UpHistoryCacheConfig::from_config_value(
+            config_value.get("up_history"),
+            &error_handler.with_key("up_history"),
+        );
+        let repo_match = RepoMatchCacheConfig::from_config_value(
+            config_value.get("repo_match"),
+            &error_handler.with_key("repo_match"),
+        );
+        let workdir_history = WorkdirHistoryCacheConfig::from_config_value(
+            config_value.get("workdir_history"),
+            &error_handler.with_key("workdir_history"),
+        );
+        let up_advisories = UpAdvisoriesCacheConfig::from_config_value(
+            config_value.get("up_advisories"),
+            &error_handler.with_key("up_advisories"),
+        );
         let github_release = GithubReleaseCacheConfig::from_config_value(
             config_value.get("github_release"),
             &error_handler.with_key("github_release"),
@@ -87,15 +118,24 @@ impl CacheConfig {
             config_value.get("mise"),
             &error_handler.with_key("mise"),
         );
+        let pipx = PipxCacheConfig::from_config_value(
+            config_value.get("pipx"),
+            &error_handler.with_key("pipx"),
+        );
 
         Self {
             path,
             environment,
+            up_history,
+            repo_match,
+            workdir_history,
+            up_advisories,
             github_release,
             cargo_install,
             go_install,
             homebrew,
             mise,
+            pipx,
         }
     }
 }
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::utils::parse_duration_or_default;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpAdvisoriesCacheConfig {
+    pub feeds_expire: u64,
+}
+
+impl Default for UpAdvisoriesCacheConfig {
+    fn default() -> Self {
+        Self {
+            feeds_expire: Self::DEFAULT_FEEDS_EXPIRE,
+        }
+    }
+}
+
+impl UpAdvisoriesCacheConfig {
+    const DEFAULT_FEEDS_EXPIRE: u64 = 86400; // 1 day
+
+    pub fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        let feeds_expire = parse_duration_or_default(
+            config_value.get("feeds_expire").as_ref(),
+            Self::DEFAULT_FEEDS_EXPIRE,
+            &error_handler.with_key("feeds_expire"),
+        );
+
+        Self { feeds_expire }
+    }
+}
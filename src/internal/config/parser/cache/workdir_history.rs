@@ -0,0 +1,51 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkdirHistoryCacheConfig {
+    pub max_entries: usize,
+}
+
+impl Default for WorkdirHistoryCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: Self::DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+impl WorkdirHistoryCacheConfig {
+    const DEFAULT_MAX_ENTRIES: usize = 50;
+
+    pub fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        let max_entries = match config_value.get("max_entries") {
+            Some(v) => match v.as_unsigned_integer() {
+                Some(v) => v as usize,
+                None => {
+                    error_handler
+                        .with_key("max_entries")
+                        .with_expected("unsigned integer")
+                        .with_actual(v)
+                        .error(ConfigErrorKind::InvalidValueType);
+
+                    Self::DEFAULT_MAX_ENTRIES
+                }
+            },
+            None => Self::DEFAULT_MAX_ENTRIES,
+        };
+
+        Self { max_entries }
+    }
+}
@@ -12,8 +12,9 @@ use crate::internal::config::parser::errors::ConfigErrorKind;
 use crate::internal::config::parser::github::StringFilter;
 use crate::internal::config::ConfigScope;
 use crate::internal::config::ConfigValue;
+use crate::internal::git::is_path_gitignored;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CheckConfig {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     patterns: Vec<ConfigValue>,
@@ -23,15 +24,33 @@ pub struct CheckConfig {
     pub select: HashSet<String>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub tags: HashMap<String, StringFilter>,
+    pub exclude_gitignored: bool,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            ignore: HashSet::new(),
+            select: HashSet::new(),
+            tags: HashMap::new(),
+            exclude_gitignored: Self::DEFAULT_EXCLUDE_GITIGNORED,
+        }
+    }
 }
 
 impl Empty for CheckConfig {
     fn is_empty(&self) -> bool {
-        self.patterns.is_empty() && self.ignore.is_empty() && self.select.is_empty()
+        self.patterns.is_empty()
+            && self.ignore.is_empty()
+            && self.select.is_empty()
+            && self.exclude_gitignored == Self::DEFAULT_EXCLUDE_GITIGNORED
     }
 }
 
 impl CheckConfig {
+    const DEFAULT_EXCLUDE_GITIGNORED: bool = true;
+
     pub(super) fn from_config_value(
         config_value: Option<ConfigValue>,
         error_handler: &ConfigErrorHandler,
@@ -85,62 +104,23 @@ impl CheckConfig {
             .into_iter()
             .collect();
 
-        let tags = if let Some(value) = config_value.get("tags") {
-            if let Some(table) = value.as_table() {
-                table
-                    .into_iter()
-                    .map(|(key, value)| {
-                        let filter = StringFilter::from_config_value(
-                            Some(value),
-                            &error_handler.with_key("tags").with_key(&key),
-                        );
-                        (key.clone(), filter)
-                    })
-                    .collect()
-            } else if let Some(array) = value.as_array() {
-                let mut tags = HashMap::new();
-                for (idx, value) in array.iter().enumerate() {
-                    if let Some(value) = value.as_str_forced() {
-                        tags.insert(value.to_string(), StringFilter::default());
-                    } else if let Some(table) = value.as_table() {
-                        for (key, value) in table {
-                            let filter = StringFilter::from_config_value(
-                                Some(value),
-                                &error_handler
-                                    .with_key("tags")
-                                    .with_index(idx)
-                                    .with_key(&key),
-                            );
-                            tags.insert(key.clone(), filter);
-                        }
-                    } else {
-                        error_handler
-                            .with_key("tags")
-                            .with_index(idx)
-                            .with_expected(vec!["string", "table"])
-                            .with_actual(value)
-                            .error(ConfigErrorKind::InvalidValueType);
-                    }
-                }
-                tags
-            } else {
-                error_handler
-                    .with_key("tags")
-                    .with_expected(vec!["table", "array"])
-                    .with_actual(value)
-                    .error(ConfigErrorKind::InvalidValueType);
+        let tags = tag_filters_from_config_value(
+            config_value.get("tags"),
+            &error_handler.with_key("tags"),
+        );
 
-                HashMap::new()
-            }
-        } else {
-            HashMap::new()
-        };
+        let exclude_gitignored = config_value.get_as_bool_or_default(
+            "exclude_gitignored",
+            Self::DEFAULT_EXCLUDE_GITIGNORED,
+            &error_handler.with_key("exclude_gitignored"),
+        );
 
         Self {
             patterns,
             ignore,
             select,
             tags,
+            exclude_gitignored,
         }
     }
 
@@ -150,6 +130,67 @@ impl CheckConfig {
             .map(path_pattern_from_config_value)
             .collect()
     }
+
+    /// Returns whether the file at the given path should be skipped by
+    /// the checks because it is ignored by git; this is always false if
+    /// `exclude_gitignored` is disabled or if the file is not part of a
+    /// git repository.
+    pub fn skips_gitignored(&self, path: &str) -> bool {
+        self.exclude_gitignored && is_path_gitignored(path).unwrap_or(false)
+    }
+}
+
+/// Parses a set of tag filters, which can either be provided as a table
+/// of tag names to filters, or as an array of tag names (any value) or
+/// single-entry tables.
+pub(super) fn tag_filters_from_config_value(
+    config_value: Option<ConfigValue>,
+    error_handler: &ConfigErrorHandler,
+) -> HashMap<String, StringFilter> {
+    let value = match config_value {
+        Some(value) => value,
+        None => return HashMap::new(),
+    };
+
+    if let Some(table) = value.as_table() {
+        table
+            .into_iter()
+            .map(|(key, value)| {
+                let filter =
+                    StringFilter::from_config_value(Some(value), &error_handler.with_key(&key));
+                (key.clone(), filter)
+            })
+            .collect()
+    } else if let Some(array) = value.as_array() {
+        let mut tags = HashMap::new();
+        for (idx, value) in array.iter().enumerate() {
+            if let Some(value) = value.as_str_forced() {
+                tags.insert(value.to_string(), StringFilter::default());
+            } else if let Some(table) = value.as_table() {
+                for (key, value) in table {
+                    let filter = StringFilter::from_config_value(
+                        Some(value),
+                        &error_handler.with_index(idx).with_key(&key),
+                    );
+                    tags.insert(key.clone(), filter);
+                }
+            } else {
+                error_handler
+                    .with_index(idx)
+                    .with_expected(vec!["string", "table"])
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValueType);
+            }
+        }
+        tags
+    } else {
+        error_handler
+            .with_expected(vec!["table", "array"])
+            .with_actual(value)
+            .error(ConfigErrorKind::InvalidValueType);
+
+        HashMap::new()
+    }
 }
 
 fn path_pattern_from_config_value(value: &ConfigValue) -> String {
@@ -98,3 +98,115 @@ mod path_pattern_from_str {
         );
     }
 }
+
+mod exclude_gitignored {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Write;
+
+    use crate::internal::config::utils::check_allowed;
+    use crate::internal::testutils::run_with_env;
+
+    fn setup_test_repo() -> tempfile::TempDir {
+        let temp_dir = tempfile::TempDir::new().expect("failed to create temp directory");
+        let repo_path = temp_dir
+            .path()
+            .canonicalize()
+            .expect("failed to canonicalize temp directory path");
+
+        git2::Repository::init(&repo_path).expect("failed to init git repo");
+
+        let mut gitignore =
+            File::create(repo_path.join(".gitignore")).expect("failed to create .gitignore");
+        writeln!(gitignore, "generated/").expect("failed to write to .gitignore");
+
+        std::fs::create_dir_all(repo_path.join("generated")).expect("failed to create dir");
+        std::fs::create_dir_all(repo_path.join("src")).expect("failed to create dir");
+        File::create(repo_path.join("generated/.omni.yaml")).expect("failed to create file");
+        File::create(repo_path.join("src/.omni.yaml")).expect("failed to create file");
+
+        temp_dir
+    }
+
+    fn check_config_from_str(yaml: &str) -> CheckConfig {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        CheckConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop())
+    }
+
+    #[test]
+    fn test_enabled_by_default() {
+        assert!(CheckConfig::default().exclude_gitignored);
+        assert!(check_config_from_str("ignore: [C001]").exclude_gitignored);
+        assert!(!check_config_from_str("exclude_gitignored: false").exclude_gitignored);
+    }
+
+    #[test]
+    fn test_gitignored_file_is_skipped_when_enabled() {
+        run_with_env(&[], || {
+            let temp_dir = setup_test_repo();
+            let repo_path = temp_dir.path().canonicalize().unwrap();
+            let ignored = repo_path.join("generated/.omni.yaml");
+            let tracked = repo_path.join("src/.omni.yaml");
+
+            let check_config = CheckConfig::default();
+            assert!(check_config.skips_gitignored(&ignored.to_string_lossy()));
+            assert!(!check_config.skips_gitignored(&tracked.to_string_lossy()));
+        });
+    }
+
+    #[test]
+    fn test_gitignored_file_is_included_when_disabled() {
+        run_with_env(&[], || {
+            let temp_dir = setup_test_repo();
+            let repo_path = temp_dir.path().canonicalize().unwrap();
+            let ignored = repo_path.join("generated/.omni.yaml");
+
+            let check_config = check_config_from_str("exclude_gitignored: false");
+            assert!(!check_config.skips_gitignored(&ignored.to_string_lossy()));
+        });
+    }
+
+    #[test]
+    fn test_noop_outside_of_git_repository() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::TempDir::new().expect("failed to create temp directory");
+            let file = temp_dir.path().join(".omni.yaml");
+            File::create(&file).expect("failed to create file");
+
+            let check_config = CheckConfig::default();
+            assert!(!check_config.skips_gitignored(&file.to_string_lossy()));
+        });
+    }
+
+    #[test]
+    fn test_composes_with_negative_patterns() {
+        run_with_env(&[], || {
+            let temp_dir = setup_test_repo();
+            let repo_path = temp_dir.path().canonicalize().unwrap();
+            let repo_str = repo_path.to_string_lossy().to_string();
+            let ignored = repo_path.join("generated/.omni.yaml");
+            let tracked = repo_path.join("src/.omni.yaml");
+
+            let patterns = vec![
+                path_pattern_from_str("!src/**", Some(&repo_str), false),
+                path_pattern_from_str("**", Some(&repo_str), false),
+            ];
+            let check_config = CheckConfig::default();
+            let is_checked = |path: &std::path::Path| {
+                let path = path.to_string_lossy();
+                check_allowed(&path, &patterns) && !check_config.skips_gitignored(&path)
+            };
+
+            // Excluded by the negative pattern
+            assert!(!is_checked(&tracked));
+            // Matched by the pattern, but gitignored
+            assert!(!is_checked(&ignored));
+
+            // With the option disabled, only the negative pattern applies
+            let check_config = check_config_from_str("exclude_gitignored: false");
+            assert!(check_allowed(&ignored.to_string_lossy(), &patterns));
+            assert!(!check_config.skips_gitignored(&ignored.to_string_lossy()));
+        });
+    }
+}
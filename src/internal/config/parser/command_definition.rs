@@ -2,24 +2,55 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Mutex;
 
+use clap::builder::TypedValueParser;
+use itertools::Itertools;
+use lazy_static::lazy_static;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::internal::cache::utils as cache_utils;
+use crate::internal::cache::utils::Empty;
 use crate::internal::commands::utils::abs_path;
 use crate::internal::commands::utils::str_to_bool;
 use crate::internal::commands::HelpCommand;
+use crate::internal::config::config_loader;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::EnvConfig;
 use crate::internal::config::parser::ParseArgsErrorKind;
 use crate::internal::config::parser::ParseArgsValue;
+use crate::internal::config::up::utils::prompt_secret;
+use crate::internal::config::utils::parse_duration_or_default;
 use crate::internal::config::ConfigScope;
 use crate::internal::config::ConfigSource;
 use crate::internal::config::ConfigValue;
+use crate::internal::env::shell_is_interactive;
+use crate::internal::env::workdir;
+use crate::internal::env::Shell;
+use crate::internal::user_interface::colors::ColorCapability;
 use crate::internal::user_interface::colors::StringColor;
+use crate::internal::utils::mask_secret;
+use crate::internal::utils::SECRET_PLACEHOLDER;
+use crate::internal::workdir::hosting_orgs;
 use crate::internal::ORG_LOADER;
+use crate::omni_warning;
+
+lazy_static! {
+    /// The argument parsers already built in this process, by key of the
+    /// syntax they were built for; see [`CommandSyntax::argparser_key`]
+    static ref ARGPARSER_CACHE: Mutex<HashMap<String, clap::Command>> = Mutex::new(HashMap::new());
+}
+
+#[cfg(test)]
+lazy_static! {
+    /// The number of times an argument parser was built for each key
+    static ref ARGPARSER_BUILDS: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::new());
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CommandDefinition {
@@ -28,20 +59,36 @@ pub struct CommandDefinition {
     pub run: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deprecated_aliases: BTreeMap<String, String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub syntax: Option<CommandSyntax>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub category: Option<Vec<String>>,
+    #[serde(default, alias = "dir", skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<CommandCwd>,
+    #[serde(default, skip_serializing_if = "EnvConfig::is_empty")]
+    pub env: EnvConfig,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub dir: Option<String>,
+    pub timeout: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub subcommands: Option<HashMap<String, CommandDefinition>>,
     #[serde(default, skip_serializing_if = "cache_utils::is_false")]
     pub argparser: bool,
+    #[serde(default, skip_serializing_if = "ArgparserOutput::is_default")]
+    pub argparser_output: ArgparserOutput,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub allow_response_files: bool,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub tags: BTreeMap<String, String>,
     #[serde(default, skip_serializing_if = "cache_utils::is_false")]
     pub export: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<SyntaxTestCase>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner: Option<CommandRunner>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub hidden: bool,
     #[serde(skip)]
     pub source: ConfigSource,
     #[serde(skip)]
@@ -49,7 +96,7 @@ pub struct CommandDefinition {
 }
 
 impl CommandDefinition {
-    pub(super) fn from_config_value(
+    pub(crate) fn from_config_value(
         config_value: &ConfigValue,
         error_handler: &ConfigErrorHandler,
     ) -> Self {
@@ -64,7 +111,8 @@ impl CommandDefinition {
                 "true".to_string()
             });
 
-        let aliases = config_value.get_as_str_array("aliases", &error_handler.with_key("aliases"));
+        let (aliases, deprecated_aliases) =
+            Self::aliases_from_config_value(config_value, &error_handler.with_key("aliases"));
 
         let syntax = match config_value.get("syntax") {
             Some(value) => {
@@ -109,7 +157,45 @@ impl CommandDefinition {
             Some(category)
         };
 
+        // The `dir` key is the explicit form of `cwd`, which it predates
         let dir = config_value.get_as_str_or_none("dir", &error_handler.with_key("dir"));
+        let cwd = match config_value.get("cwd") {
+            Some(value) => match value.as_str() {
+                Some(cwd) if !cwd.trim().is_empty() => Some(CommandCwd::from(cwd)),
+                _ => {
+                    error_handler
+                        .with_key("cwd")
+                        .with_expected("string")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+                    None
+                }
+            },
+            None => None,
+        };
+        let (cwd, cwd_key) = match (cwd, dir) {
+            (Some(cwd), Some(dir)) => {
+                error_handler
+                    .with_key("dir")
+                    .with_actual(dir)
+                    .error(ConfigErrorKind::UnsupportedValueInContext);
+                (Some(cwd), "cwd")
+            }
+            (Some(cwd), None) => (Some(cwd), "cwd"),
+            (None, Some(dir)) => (Some(CommandCwd::from_path(&dir)), "dir"),
+            (None, None) => (None, "cwd"),
+        };
+
+        let env =
+            EnvConfig::from_config_value(config_value.get("env"), &error_handler.with_key("env"));
+
+        let timeout = config_value.get("timeout").map(|value| {
+            parse_duration_or_default(
+                Some(&value),
+                0, // Do not time out if the duration is invalid
+                &error_handler.with_key("timeout"),
+            )
+        });
 
         let subcommands = match config_value.get("subcommands") {
             Some(value) => {
@@ -142,148 +228,996 @@ impl CommandDefinition {
             &error_handler.with_key("argparser"),
         );
 
+        // The arguments are only available to expand the directory of
+        // the command when they are parsed by the argument parser
+        if let Some(CommandCwd::Template(template)) = &cwd {
+            match CommandCwd::template_parts(template) {
+                Err(err) => {
+                    error_handler
+                        .with_key(cwd_key)
+                        .with_context("error", err)
+                        .error(ConfigErrorKind::InvalidSyntax);
+                }
+                Ok(_) if !argparser && cwd.as_ref().is_some_and(CommandCwd::uses_args) => {
+                    error_handler
+                        .with_key(cwd_key)
+                        .with_actual(template.clone())
+                        .error(ConfigErrorKind::UnsupportedValueInContext);
+                }
+                Ok(parts) => {
+                    // Only the parameters taking a single value can be
+                    // expanded, as the others are exported as one
+                    // variable per value
+                    let syntax = syntax.clone().unwrap_or_default();
+                    for part in parts {
+                        let name = match part {
+                            CommandCwdTemplatePart::Arg(name) => name,
+                            _ => continue,
+                        };
+
+                        if syntax
+                            .template_parameter(&name)
+                            .is_some_and(|param| !param.takes_multiple_values())
+                        {
+                            continue;
+                        }
+
+                        let expected = syntax
+                            .parameters
+                            .iter()
+                            .filter(|param| !param.takes_multiple_values())
+                            .map(|param| format!("arg:{}", param.dest()))
+                            .collect::<Vec<_>>();
+                        error_handler
+                            .with_key(cwd_key)
+                            .with_expected(expected)
+                            .with_actual(format!("arg:{name}"))
+                            .error(ConfigErrorKind::InvalidValue);
+                    }
+                }
+            }
+        }
+
+        let argparser_output = match config_value.get("argparser_output") {
+            Some(value) => match value.as_str().as_deref() {
+                Some("env") => ArgparserOutput::Env,
+                Some("json") => ArgparserOutput::Json,
+                _ => {
+                    error_handler
+                        .with_key("argparser_output")
+                        .with_expected(vec!["env", "json"])
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValue);
+                    ArgparserOutput::default()
+                }
+            },
+            None => ArgparserOutput::default(),
+        };
+
+        let allow_response_files = config_value.get_as_bool_or_default(
+            "allow_response_files",
+            false, // Do not expand response files by default
+            &error_handler.with_key("allow_response_files"),
+        );
+
         let export = config_value.get_as_bool_or_default(
             "export",
             false, // Do not export by default
             &error_handler.with_key("export"),
         );
 
+        let hidden = config_value.get_as_bool_or_default(
+            "hidden",
+            false, // Show in the help and completions by default
+            &error_handler.with_key("hidden"),
+        );
+
+        let tests = match config_value.get("tests") {
+            Some(value) => {
+                let tests_error_handler = error_handler.with_key("tests");
+                if let Some(array) = value.as_array() {
+                    array
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(idx, value)| {
+                            SyntaxTestCase::from_config_value(
+                                value,
+                                &tests_error_handler.with_index(idx),
+                            )
+                        })
+                        .collect()
+                } else {
+                    tests_error_handler
+                        .with_expected("array")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+                    vec![]
+                }
+            }
+            None => vec![],
+        };
+
+        let runner = match config_value.get("runner") {
+            Some(value) => {
+                CommandRunner::from_config_value(&value, &error_handler.with_key("runner"))
+            }
+            None => None,
+        };
+
         Self {
             desc,
             run,
             aliases,
+            deprecated_aliases,
             syntax,
             category,
-            dir,
+            cwd,
+            env,
+            timeout,
             subcommands,
             argparser,
+            argparser_output,
+            allow_response_files,
             tags,
             export,
+            tests,
+            runner,
+            hidden,
             source: config_value.get_source().clone(),
             scope: config_value.current_scope().clone(),
         }
     }
-}
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
-pub struct CommandSyntax {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub usage: Option<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub parameters: Vec<SyntaxOptArg>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub groups: Vec<SyntaxGroup>,
-}
+    /// Parses the aliases of the command, which can be provided either
+    /// as plain names or as tables with a `name` and a `deprecated`
+    /// message; returns all the aliases, along with the messages of the
+    /// deprecated ones by alias
+    fn aliases_from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> (Vec<String>, BTreeMap<String, String>) {
+        let mut aliases = Vec::new();
+        let mut deprecated_aliases = BTreeMap::new();
 
-impl CommandSyntax {
-    const RESERVED_NAMES: [&'static str; 2] = ["-h", "--help"];
+        let value = match config_value.get("aliases") {
+            Some(value) => value,
+            None => return (aliases, deprecated_aliases),
+        };
 
-    pub fn new() -> Self {
-        Self::default()
+        let values = if let Some(array) = value.as_array() {
+            array
+        } else {
+            vec![value]
+        };
+
+        for (idx, value) in values.iter().enumerate() {
+            let error_handler = error_handler.with_index(idx);
+
+            if let Some(alias) = value.as_str_forced() {
+                aliases.push(alias.to_string());
+            } else if value.is_table() {
+                let name = match value.get_as_str_or_none("name", &error_handler.with_key("name")) {
+                    Some(name) => name,
+                    None => {
+                        error_handler
+                            .with_key("name")
+                            .error(ConfigErrorKind::MissingKey);
+                        continue;
+                    }
+                };
+
+                if let Some(message) =
+                    value.get_as_str_or_none("deprecated", &error_handler.with_key("deprecated"))
+                {
+                    deprecated_aliases.insert(name.clone(), message);
+                }
+
+                aliases.push(name);
+            } else {
+                error_handler
+                    .with_expected("string or table")
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValueType);
+            }
+        }
+
+        (aliases, deprecated_aliases)
     }
 
-    pub fn deserialize<'de, D>(
-        deserializer: D,
-        error_handler: &ConfigErrorHandler,
-    ) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let value = serde_yaml::Value::deserialize(deserializer)?;
-        let config_value = ConfigValue::from_value(ConfigSource::Null, ConfigScope::Null, value);
-        if let Some(command_syntax) = CommandSyntax::from_config_value(&config_value, error_handler)
-        {
-            Ok(command_syntax)
-        } else {
-            Err(serde::de::Error::custom("invalid command syntax"))
+    /// Runs the syntax test cases declared for the command, returning
+    /// the result of each of them in the order they were declared
+    pub fn run_syntax_tests(&self, called_as: Vec<String>) -> Vec<SyntaxTestResult> {
+        let syntax = self.syntax.clone().unwrap_or_default();
+
+        self.tests
+            .iter()
+            .enumerate()
+            .map(|(index, case)| SyntaxTestResult {
+                index,
+                args: case.args.clone(),
+                result: case.run(&syntax, called_as.clone()),
+            })
+            .collect()
+    }
+
+    /// Scans the run body of the command for references to `OMNI_ARG_*`
+    /// variables, and compares them with the parameters declared in the
+    /// syntax; this is heuristic, as variables could be referenced in
+    /// ways that a simple string scan cannot identify, and should thus
+    /// only be used to warn the command authors.
+    pub fn lint_arg_references(&self) -> Vec<ArgReferenceLint> {
+        if !self.argparser {
+            return vec![];
+        }
+
+        let syntax = self.syntax.clone().unwrap_or_default();
+
+        // The names that are exported to the environment for each of
+        // the declared parameters and groups; parameters with variants
+        // are exported under the destination of the matching variant
+        let mut declared = vec![];
+        for param in &syntax.parameters {
+            let mut names = vec![param.dest()];
+            names.extend(param.variants.iter().map(|variant| variant.dest()));
+            names.extend(param.repo_org_dest());
+            declared.push((param.dest(), names));
+        }
+        for group in &syntax.groups {
+            declared.push((group.dest(), vec![group.dest()]));
+        }
+
+        let reference_re = regex::Regex::new(r"OMNI_ARG_([A-Za-z0-9_]+)").expect("valid regex");
+        let name_re = regex::Regex::new(r"^(.+?)_(?:TYPE|VALUE)(?:_|$)").expect("valid regex");
+
+        let mut lints = vec![];
+        let mut used = HashSet::new();
+        let mut uses_arg_list = false;
+        let mut seen_undeclared = HashSet::new();
+
+        for captures in reference_re.captures_iter(&self.run) {
+            let reference = &captures[1];
+            if reference == "LIST" {
+                uses_arg_list = true;
+                continue;
+            }
+
+            let matched = declared.iter().find(|(_, names)| {
+                names.iter().any(|name| {
+                    let name = name.to_uppercase();
+                    reference
+                        .strip_prefix(&name)
+                        .is_some_and(|rest| rest.starts_with("_TYPE") || rest.starts_with("_VALUE"))
+                })
+            });
+
+            match matched {
+                Some((dest, _)) => {
+                    used.insert(dest.clone());
+                }
+                None => {
+                    let name = name_re
+                        .captures(reference)
+                        .map(|captures| captures[1].to_string())
+                        .unwrap_or_else(|| reference.to_string());
+                    if seen_undeclared.insert(name.clone()) {
+                        lints.push(ArgReferenceLint::UndeclaredReference(name));
+                    }
+                }
+            }
+        }
+
+        // When the run body goes over the list of arguments, parameters
+        // might be used without being referenced by name
+        if !uses_arg_list {
+            for (dest, _) in &declared {
+                if !used.contains(dest) {
+                    lints.push(ArgReferenceLint::UnusedParameter(dest.clone()));
+                }
+            }
         }
+
+        lints
     }
+}
 
-    pub(super) fn from_config_value(
+/// A potential mismatch between the parameters declared in the syntax
+/// of a command and the `OMNI_ARG_*` variables referenced in its run body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgReferenceLint {
+    /// An `OMNI_ARG_*` variable is referenced for an argument name that
+    /// does not correspond to any declared parameter
+    UndeclaredReference(String),
+    /// A declared parameter is never referenced in the run body
+    UnusedParameter(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SyntaxTestExpect {
+    #[default]
+    Ok,
+    Error,
+}
+
+/// A test case for the syntax of a command, allowing command authors
+/// to assert that their syntax accepts or rejects some arguments
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct SyntaxTestCase {
+    pub args: Vec<String>,
+    pub expect: SyntaxTestExpect,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_contains: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub expect_env: BTreeMap<String, String>,
+}
+
+impl SyntaxTestCase {
+    fn from_config_value(
         config_value: &ConfigValue,
         error_handler: &ConfigErrorHandler,
     ) -> Option<Self> {
-        let mut usage = None;
-        let mut parameters = vec![];
-        let mut groups = vec![];
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+            return None;
+        }
 
-        if let Some(array) = config_value.as_array() {
-            parameters.extend(array.iter().enumerate().filter_map(|(idx, value)| {
-                SyntaxOptArg::from_config_value(value, None, &error_handler.with_index(idx))
-            }));
-        } else if let Some(table) = config_value.as_table() {
-            let keys = [
-                ("parameters", None),
-                ("arguments", Some(true)),
-                ("argument", Some(true)),
-                ("options", Some(false)),
-                ("option", Some(false)),
-                ("optional", Some(false)),
-            ];
+        let args = config_value.get_as_str_array("args", &error_handler.with_key("args"));
 
-            for (key, required) in keys {
-                if let Some(value) = table.get(key) {
-                    let param_error_handler = error_handler.with_key(key);
-                    if let Some(value) = value.as_array() {
-                        let arguments = value
-                            .iter()
-                            .enumerate()
-                            .filter_map(|(idx, value)| {
-                                SyntaxOptArg::from_config_value(
-                                    value,
-                                    required,
-                                    &param_error_handler.with_index(idx),
-                                )
-                            })
-                            .collect::<Vec<SyntaxOptArg>>();
-                        parameters.extend(arguments);
-                    } else if let Some(arg) =
-                        SyntaxOptArg::from_config_value(value, required, &param_error_handler)
-                    {
-                        parameters.push(arg);
+        let error_contains = config_value
+            .get_as_str_or_none("error_contains", &error_handler.with_key("error_contains"));
+
+        let expect =
+            match config_value.get_as_str_or_none("expect", &error_handler.with_key("expect")) {
+                Some(expect) => match expect.to_lowercase().as_str() {
+                    "ok" => SyntaxTestExpect::Ok,
+                    "error" => SyntaxTestExpect::Error,
+                    _ => {
+                        error_handler
+                            .with_key("expect")
+                            .with_expected(vec!["ok", "error"])
+                            .with_actual(expect)
+                            .error(ConfigErrorKind::InvalidValue);
+                        return None;
+                    }
+                },
+                None if error_contains.is_some() => SyntaxTestExpect::Error,
+                None => SyntaxTestExpect::Ok,
+            };
+
+        let mut expect_env = BTreeMap::new();
+        if let Some(value) = config_value.get("expect_env") {
+            if let Some(table) = value.as_table() {
+                for (key, value) in table {
+                    if let Some(value) = value.as_str_forced() {
+                        expect_env.insert(key.to_string(), value.to_string());
                     } else {
-                        param_error_handler
-                            .with_expected("array or table")
+                        error_handler
+                            .with_key("expect_env")
+                            .with_key(&key)
+                            .with_expected("string")
                             .with_actual(value)
                             .error(ConfigErrorKind::InvalidValueType);
                     }
                 }
+            } else {
+                error_handler
+                    .with_key("expect_env")
+                    .with_expected("table")
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValueType);
             }
+        }
 
-            if let Some(value) = table.get("groups") {
-                groups =
-                    SyntaxGroup::from_config_value_multi(value, &error_handler.with_key("groups"));
-            }
+        Some(Self {
+            args,
+            expect,
+            error_contains,
+            expect_env,
+        })
+    }
 
-            if let Some(value) = table.get("usage") {
-                if let Some(value) = value.as_str_forced() {
-                    usage = Some(value.to_string());
-                } else {
-                    error_handler
-                        .with_key("usage")
-                        .with_expected("string")
-                        .with_actual(value)
-                        .error(ConfigErrorKind::InvalidValueType);
+    /// Runs the test case against the given syntax, without exiting in
+    /// case of parsing error; returns the reason of the failure if the
+    /// expectations of the test case are not met
+    pub fn run(&self, syntax: &CommandSyntax, called_as: Vec<String>) -> Result<(), String> {
+        match (
+            syntax.validate_args(self.args.clone(), called_as),
+            self.expect,
+        ) {
+            (Ok(env), SyntaxTestExpect::Ok) => {
+                for (key, expected) in self.expect_env.iter() {
+                    match env.get(key) {
+                        Some(actual) if actual == expected => {}
+                        Some(actual) => {
+                            return Err(format!(
+                                "expected {key} to be '{expected}', but found '{actual}'"
+                            ));
+                        }
+                        None => {
+                            return Err(format!(
+                                "expected {key} to be '{expected}', but it was not set"
+                            ));
+                        }
+                    }
                 }
+                Ok(())
             }
-        } else if let Some(value) = config_value.as_str_forced() {
-            usage = Some(value.to_string());
-        } else {
-            error_handler
-                .with_expected("array, table or string")
-                .with_actual(config_value)
-                .error(ConfigErrorKind::InvalidValueType);
+            (Ok(_), SyntaxTestExpect::Error) => {
+                Err("expected an error, but the arguments were accepted".to_string())
+            }
+            (Err(err), SyntaxTestExpect::Ok) => Err(format!(
+                "expected the arguments to be accepted, but found error: {}",
+                err.simple()
+            )),
+            (Err(err), SyntaxTestExpect::Error) => match &self.error_contains {
+                Some(error_contains) if !err.to_string().contains(error_contains) => Err(format!(
+                    "expected an error containing '{}', but found error: {}",
+                    error_contains,
+                    err.simple()
+                )),
+                _ => Ok(()),
+            },
         }
+    }
+}
 
-        if parameters.is_empty() && groups.is_empty() && usage.is_none() {
-            return None;
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTestResult {
+    pub index: usize,
+    pub args: Vec<String>,
+    pub result: Result<(), String>,
+}
+
+/// The directory from which a command is executed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(from = "String", into = "String")]
+pub enum CommandCwd {
+    /// The root of the work directory of the command
+    WorkdirRoot,
+    /// The directory from which omni was invoked
+    InvocationDir,
+    /// The directory of the configuration file defining the command
+    #[default]
+    ConfigDir,
+    /// A path relative to the directory of the configuration file
+    /// defining the command, which needs to be a subdirectory of it
+    Path(String),
+    /// A path expanded from `{{ workdir_root }}` and `{{ arg:NAME }}`
+    /// variables, relative to the directory of the configuration file
+    /// defining the command if not absolute once expanded
+    Template(String),
+}
+
+impl From<&str> for CommandCwd {
+    fn from(value: &str) -> Self {
+        match value {
+            "workdir_root" => Self::WorkdirRoot,
+            "invocation_dir" => Self::InvocationDir,
+            "config_dir" => Self::ConfigDir,
+            path => Self::from_path(path),
         }
+    }
+}
 
-        Some(Self {
-            usage,
-            parameters,
-            groups,
-        })
+impl From<String> for CommandCwd {
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl From<CommandCwd> for String {
+    fn from(value: CommandCwd) -> Self {
+        match value {
+            CommandCwd::WorkdirRoot => "workdir_root".to_string(),
+            CommandCwd::InvocationDir => "invocation_dir".to_string(),
+            CommandCwd::ConfigDir => "config_dir".to_string(),
+            CommandCwd::Path(path) => path,
+            CommandCwd::Template(template) => template,
+        }
+    }
+}
+
+/// Returns the value of the parsed argument referenced by the given
+/// name, or why it cannot be expanded
+pub type CommandCwdArgFn<'a> = &'a dyn Fn(&str) -> Result<String, String>;
+
+/// A part of the template of the directory of a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandCwdTemplatePart {
+    Literal(String),
+    WorkdirRoot,
+    Arg(String),
+}
+
+impl CommandCwd {
+    /// Returns the directory for the given path, which is a template if
+    /// it contains any `{{`, even escaped
+    pub fn from_path(path: &str) -> Self {
+        if path.contains("{{") {
+            Self::Template(path.to_string())
+        } else {
+            Self::Path(path.to_string())
+        }
+    }
+
+    /// Parses the template of a directory into its parts; a `{{` can be
+    /// escaped as `\{{` to be kept literally
+    pub fn template_parts(template: &str) -> Result<Vec<CommandCwdTemplatePart>, String> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("\\{{") {
+                literal.push_str("{{");
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("{{") {
+                let (variable, after) = after
+                    .split_once("}}")
+                    .ok_or_else(|| format!("unclosed '{{{{' in '{template}'"))?;
+
+                let part = match variable.trim() {
+                    "workdir_root" => CommandCwdTemplatePart::WorkdirRoot,
+                    variable => match variable.strip_prefix("arg:").map(str::trim) {
+                        Some(name) if !name.is_empty() => {
+                            CommandCwdTemplatePart::Arg(name.to_string())
+                        }
+                        _ => {
+                            return Err(format!(
+                                "unknown variable '{variable}' in '{template}', expected 'workdir_root' or 'arg:NAME'"
+                            ))
+                        }
+                    },
+                };
+
+                if !literal.is_empty() {
+                    parts.push(CommandCwdTemplatePart::Literal(std::mem::take(
+                        &mut literal,
+                    )));
+                }
+                parts.push(part);
+                rest = after;
+            } else {
+                let mut chars = rest.chars();
+                literal.extend(chars.next());
+                rest = chars.as_str();
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(CommandCwdTemplatePart::Literal(literal));
+        }
+
+        Ok(parts)
+    }
+
+    /// Returns whether the directory depends on the arguments of the
+    /// command, in which case it can only be resolved once they are parsed
+    pub fn uses_args(&self) -> bool {
+        match self {
+            Self::Template(template) => Self::template_parts(template)
+                .unwrap_or_default()
+                .iter()
+                .any(|part| matches!(part, CommandCwdTemplatePart::Arg(_))),
+            _ => false,
+        }
+    }
+
+    /// Resolves the directory the same way as [`CommandCwd::resolve_with_args`],
+    /// for when the arguments of the command are not available
+    pub fn resolve(&self, config_dir: &Path, invocation_dir: &Path) -> Result<PathBuf, String> {
+        self.resolve_with_args(config_dir, invocation_dir, None)
+    }
+
+    /// Resolves the directory from which to execute a command defined in
+    /// the configuration file of `config_dir`, when invoked from
+    /// `invocation_dir`; the work directory of a command is the one of
+    /// its configuration file or, for commands that are not defined in a
+    /// work directory, the one from which it was invoked. The `args`
+    /// function returns the value of the parsed argument referenced as
+    /// `NAME`, or why it cannot be expanded, and is needed to expand the
+    /// `{{ arg:NAME }}` variables
+    pub fn resolve_with_args(
+        &self,
+        config_dir: &Path,
+        invocation_dir: &Path,
+        args: Option<CommandCwdArgFn>,
+    ) -> Result<PathBuf, String> {
+        match self {
+            Self::WorkdirRoot => {
+                let config_wd = workdir(config_dir.to_string_lossy());
+                let invocation_wd = workdir(invocation_dir.to_string_lossy());
+                match config_wd.root().or(invocation_wd.root()) {
+                    Some(root) => Ok(PathBuf::from(root)),
+                    None => Err(format!(
+                        "{} is not in a work directory",
+                        invocation_dir.display()
+                    )),
+                }
+            }
+            Self::InvocationDir => Ok(invocation_dir.to_path_buf()),
+            Self::ConfigDir => Ok(config_dir.to_path_buf()),
+            Self::Path(dir) => {
+                let exec_dir = abs_path(config_dir.join(dir));
+                if !exec_dir.starts_with(config_dir) {
+                    return Err(format!(
+                        "directory {} is not a subpath of {}",
+                        exec_dir.display(),
+                        config_dir.display()
+                    ));
+                }
+
+                Ok(exec_dir)
+            }
+            Self::Template(template) => {
+                let mut dir = String::new();
+                for part in Self::template_parts(template)? {
+                    match part {
+                        CommandCwdTemplatePart::Literal(literal) => dir.push_str(&literal),
+                        CommandCwdTemplatePart::WorkdirRoot => {
+                            let root = Self::WorkdirRoot.resolve(config_dir, invocation_dir)?;
+                            dir.push_str(&root.to_string_lossy());
+                        }
+                        CommandCwdTemplatePart::Arg(name) => {
+                            let args = args.ok_or_else(|| {
+                                format!(
+                                    "cannot expand argument '{name}' in directory '{template}': the arguments of the command are not parsed"
+                                )
+                            })?;
+                            let value = args(&name).map_err(|err| {
+                                format!(
+                                    "cannot expand argument '{name}' in directory '{template}': {err}"
+                                )
+                            })?;
+                            dir.push_str(&value);
+                        }
+                    }
+                }
+
+                let exec_dir = abs_path(config_dir.join(dir));
+                if !exec_dir.is_dir() {
+                    return Err(format!("directory {} does not exist", exec_dir.display()));
+                }
+
+                Ok(exec_dir)
+            }
+        }
+    }
+}
+
+/// How the arguments parsed by the argument parser are provided to
+/// the command
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArgparserOutput {
+    /// Each argument is provided through its own `OMNI_ARG_*`
+    /// environment variables
+    #[default]
+    #[serde(rename = "env")]
+    Env,
+    /// All the arguments are provided as a single JSON document in
+    /// the `OMNI_ARGS_JSON` environment variable
+    #[serde(rename = "json")]
+    Json,
+}
+
+impl ArgparserOutput {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A wrapper command through which the run body of a command is
+/// executed, e.g. to run it inside a container
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct CommandRunner {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub passthrough_env: Vec<String>,
+}
+
+impl CommandRunner {
+    fn from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Option<Self> {
+        // The runner can be provided as a simple string, in which
+        // case it is the runner command
+        if let Some(command) = config_value.as_str() {
+            return Some(Self {
+                command,
+                passthrough_env: vec![],
+            });
+        }
+
+        if !config_value.is_table() {
+            error_handler
+                .with_expected(vec!["string", "table"])
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+            return None;
+        }
+
+        let command =
+            match config_value.get_as_str_or_none("command", &error_handler.with_key("command")) {
+                Some(command) => command,
+                None => {
+                    error_handler
+                        .with_key("command")
+                        .error(ConfigErrorKind::MissingKey);
+                    return None;
+                }
+            };
+
+        let passthrough_env = config_value.get_as_str_array(
+            "passthrough_env",
+            &error_handler.with_key("passthrough_env"),
+        );
+
+        Some(Self {
+            command,
+            passthrough_env,
+        })
+    }
+
+    /// Returns the runner command with its tokens expanded: `%{workdir}`
+    /// is replaced by the root of the work directory of the command,
+    /// `%{reldir}` by the directory of the command relative to that root,
+    /// and `%{env}` by the names of the environment variables to forward
+    pub fn expand_command(&self, workdir: &str, reldir: &str, env: &[String]) -> String {
+        let env = env
+            .iter()
+            .map(|key| shell_escape::escape(key.into()).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        self.command
+            .replace("%{workdir}", &shell_escape::escape(workdir.into()))
+            .replace("%{reldir}", &shell_escape::escape(reldir.into()))
+            .replace("%{env}", &env)
+    }
+
+    /// Returns the program called by the runner command, skipping any
+    /// leading environment variable assignment
+    pub fn program(&self) -> Option<String> {
+        shell_words::split(&self.command)
+            .ok()?
+            .into_iter()
+            .find(|word| match word.split_once('=') {
+                Some((name, _)) => {
+                    name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                }
+                None => true,
+            })
+    }
+
+    /// Whether the environment variable should be forwarded through the
+    /// runner; the `passthrough_env` entries can end with `*` to match
+    /// any variable starting with the given prefix, and the `OMNI_ARG_*`
+    /// variables are always forwarded if `include_args` is set
+    pub fn passes_env(&self, key: &str, include_args: bool) -> bool {
+        if include_args && key.starts_with("OMNI_ARG_") {
+            return true;
+        }
+
+        self.passthrough_env
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == pattern,
+            })
+    }
+
+    /// Filters the names of the environment variables to forward through
+    /// the runner, sorted by name; their values are never part of the
+    /// command line, and are read from the environment of the runner
+    pub fn filter_env<I>(&self, keys: I, include_args: bool) -> Vec<String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut env = keys
+            .into_iter()
+            .filter(|key| self.passes_env(key, include_args))
+            .collect::<Vec<_>>();
+        env.sort();
+        env
+    }
+
+    /// Composes the command line to run through `bash -c` so that the
+    /// run body of the command, called with its source and arguments, is
+    /// executed through the runner, forwarding the provided environment
+    /// variables through the `%{env}` token
+    pub fn command_line(
+        &self,
+        run: &str,
+        source: &str,
+        argv: &[String],
+        env: &[String],
+        workdir: &str,
+        reldir: &str,
+    ) -> String {
+        let mut parts = vec![self.expand_command(workdir, reldir, env)];
+
+        parts.push("bash -c".to_string());
+        parts.extend(
+            std::iter::once(run)
+                .chain(std::iter::once(source))
+                .chain(argv.iter().map(|arg| arg.as_str()))
+                .map(|word| shell_escape::escape(word.into()).to_string()),
+        );
+
+        parts.join(" ")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct CommandSyntax {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<SyntaxOptArg>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<SyntaxGroup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<String>,
+}
+
+impl CommandSyntax {
+    const RESERVED_NAMES: [&'static str; 2] = ["-h", "--help"];
+
+    /// The heading of the help section of the positional parameters
+    pub const ARGUMENTS_SECTION: &'static str = "Arguments";
+
+    /// The heading of the help section of the options without a section
+    pub const DEFAULT_OPTIONS_SECTION: &'static str = "Options";
+
+    /// The heading of the help section of the options without a section
+    /// when global options are shown after them
+    pub const COMMAND_OPTIONS_SECTION: &'static str = "Command options";
+
+    /// The heading of the help section of the options that are not
+    /// defined by the command itself, e.g. `-h/--help`
+    pub const GLOBAL_OPTIONS_SECTION: &'static str = "Global options";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the parameter referenced as `NAME` by an `{{ arg:NAME }}`
+    /// variable, which can be any of the names of the parameter, with or
+    /// without its dashes, or its destination
+    pub fn template_parameter(&self, name: &str) -> Option<&SyntaxOptArg> {
+        let name = sanitize_str(name);
+        self.parameters.iter().find(|param| {
+            param.dest() == name
+                || param
+                    .all_names()
+                    .iter()
+                    .any(|param_name| sanitize_str(param_name) == name)
+        })
+    }
+
+    /// Returns the `-h/--help` option that the argument parser adds to
+    /// the parameters of the commands
+    pub fn help_option() -> SyntaxOptArg {
+        SyntaxOptArg {
+            names: vec!["-h".to_string(), "--help".to_string()],
+            desc: Some("Show this help message and exit".to_string()),
+            arg_type: SyntaxOptArgType::Flag,
+            origin: SyntaxOptArgOrigin::Reserved,
+            ..SyntaxOptArg::default()
+        }
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+        error_handler: &ConfigErrorHandler,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_yaml::Value::deserialize(deserializer)?;
+        let config_value = ConfigValue::from_value(ConfigSource::Null, ConfigScope::Null, value);
+        if let Some(command_syntax) = CommandSyntax::from_config_value(&config_value, error_handler)
+        {
+            Ok(command_syntax)
+        } else {
+            Err(serde::de::Error::custom("invalid command syntax"))
+        }
+    }
+
+    pub(super) fn from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Option<Self> {
+        let mut usage = None;
+        let mut parameters = vec![];
+        let mut groups = vec![];
+        let mut sections = vec![];
+
+        if let Some(array) = config_value.as_array() {
+            parameters.extend(array.iter().enumerate().filter_map(|(idx, value)| {
+                SyntaxOptArg::from_config_value(value, None, &error_handler.with_index(idx))
+            }));
+        } else if let Some(table) = config_value.as_table() {
+            let keys = [
+                ("parameters", None),
+                ("arguments", Some(true)),
+                ("argument", Some(true)),
+                ("options", Some(false)),
+                ("option", Some(false)),
+                ("optional", Some(false)),
+            ];
+
+            for (key, required) in keys {
+                if let Some(value) = table.get(key) {
+                    let param_error_handler = error_handler.with_key(key);
+                    if let Some(value) = value.as_array() {
+                        let arguments = value
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(idx, value)| {
+                                SyntaxOptArg::from_config_value(
+                                    value,
+                                    required,
+                                    &param_error_handler.with_index(idx),
+                                )
+                            })
+                            .collect::<Vec<SyntaxOptArg>>();
+                        parameters.extend(arguments);
+                    } else if let Some(arg) =
+                        SyntaxOptArg::from_config_value(value, required, &param_error_handler)
+                    {
+                        parameters.push(arg);
+                    } else {
+                        param_error_handler
+                            .with_expected("array or table")
+                            .with_actual(value)
+                            .error(ConfigErrorKind::InvalidValueType);
+                    }
+                }
+            }
+
+            if let Some(value) = table.get("groups") {
+                groups =
+                    SyntaxGroup::from_config_value_multi(value, &error_handler.with_key("groups"));
+            }
+
+            sections =
+                config_value.get_as_str_array("sections", &error_handler.with_key("sections"));
+
+            if let Some(value) = table.get("usage") {
+                if let Some(value) = value.as_str_forced() {
+                    usage = Some(value.to_string());
+                } else {
+                    error_handler
+                        .with_key("usage")
+                        .with_expected("string")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+                }
+            }
+        } else if let Some(value) = config_value.as_str_forced() {
+            usage = Some(value.to_string());
+        } else {
+            error_handler
+                .with_expected("array, table or string")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+        }
+
+        if parameters.is_empty() && groups.is_empty() && usage.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            usage,
+            parameters,
+            groups,
+            sections,
+        })
     }
 
     /// The 'leftovers' parameter is used to capture all the remaining arguments
@@ -292,20 +1226,20 @@ impl CommandSyntax {
     /// - Using 'leftovers' more than once
     /// - Using 'leftovers' before the last positional argument
     /// - Using 'leftovers' with a non-positional argument
-    fn check_parameters_leftovers(&self) -> Result<(), String> {
+    fn check_parameters_leftovers(&self) -> Vec<String> {
         // Grab all the leftovers params
         let leftovers_params = self.parameters.iter().filter(|param| param.leftovers);
 
         // Check if the count is greater than one
         if leftovers_params.clone().count() > 1 {
-            return Err(format!(
+            return vec![format!(
                 "only one argument can use {}; found {}",
                 "leftovers".light_yellow(),
                 leftovers_params
                     .map(|param| param.name().light_yellow())
                     .collect::<Vec<_>>()
                     .join(", ")
-            ));
+            )];
         }
 
         // Check if any is non-positional
@@ -313,14 +1247,14 @@ impl CommandSyntax {
             .clone()
             .filter(|param| !param.is_positional());
         if nonpositional_leftovers.clone().count() > 0 {
-            return Err(format!(
+            return vec![format!(
                 "only positional arguments can use {}; found {}",
                 "leftovers".light_yellow(),
                 nonpositional_leftovers
                     .map(|param| param.name().light_yellow())
                     .collect::<Vec<_>>()
                     .join(", ")
-            ));
+            )];
         }
 
         // Check if our leftovers argument is before the last positional argument
@@ -331,15 +1265,15 @@ impl CommandSyntax {
         if let Some(lpidx) = last_positional_idx {
             for (idx, param) in self.parameters.iter().enumerate() {
                 if param.leftovers && idx < lpidx {
-                    return Err(format!(
+                    return vec![format!(
                         "only the last positional argument can use {}",
                         "leftovers".light_yellow()
-                    ));
+                    )];
                 }
             }
         }
 
-        Ok(())
+        vec![]
     }
 
     /// The 'last' parameter is used to capture arguments after using '--' on the command line
@@ -347,7 +1281,7 @@ impl CommandSyntax {
     /// The following will lead to panic:
     /// - Flags using 'last'
     /// - non-positional using 'last'
-    fn check_parameters_last(&self) -> Result<(), String> {
+    fn check_parameters_last(&self) -> Vec<String> {
         // Grab all the last params
         let params = self
             .parameters
@@ -357,24 +1291,26 @@ impl CommandSyntax {
         // Check if any is a non-positional argument
         let nonpositional_last = params.clone().filter(|param| !param.is_positional());
         if nonpositional_last.clone().count() > 0 {
-            return Err(format!(
+            return vec![format!(
                 "only positional arguments can use {}; found {}",
                 "last".light_yellow(),
                 nonpositional_last
                     .map(|param| param.name().light_yellow())
                     .collect::<Vec<_>>()
                     .join(", ")
-            ));
+            )];
         }
 
-        Ok(())
+        vec![]
     }
 
     /// Since when setting a counter we do not expect any value, parameters using
     /// the `counter` type will panic if:
     /// - They are positional
     /// - They have a num_values
-    fn check_parameters_counter(&self) -> Result<(), String> {
+    fn check_parameters_counter(&self) -> Vec<String> {
+        let mut errors = vec![];
+
         // Grab all the counter params
         let params = self
             .parameters
@@ -383,21 +1319,21 @@ impl CommandSyntax {
 
         for param in params {
             if param.is_positional() {
-                return Err(format!(
+                errors.push(format!(
                     "{}: counter argument cannot be positional",
                     param.name().light_yellow()
                 ));
             }
 
             if param.num_values.is_some() {
-                return Err(format!(
+                errors.push(format!(
                     "{}: counter argument cannot have a num_values (counters do not take any values)",
                     param.name().light_yellow()
                 ));
             }
         }
 
-        Ok(())
+        errors
     }
 
     fn check_parameters_references_iter(
@@ -406,24 +1342,22 @@ impl CommandSyntax {
         available_references: &HashSet<String>,
         reference_type: &str,
         param_name: &str,
-    ) -> Result<(), String> {
-        for reference in references {
-            let reference = reference.to_string();
-
-            if !available_references.contains(&reference) {
-                return Err(format!(
+    ) -> Vec<String> {
+        references
+            .map(|reference| reference.to_string())
+            .filter(|reference| !available_references.contains(reference))
+            .map(|reference| {
+                format!(
                     "parameter or group {} specified in {} for {} does not exist",
                     reference.light_yellow(),
                     reference_type.light_yellow(),
                     param_name.light_yellow(),
-                ));
-            }
-        }
-
-        Ok(())
+                )
+            })
+            .collect()
     }
 
-    fn check_parameters_references(&self) -> Result<(), String> {
+    fn check_parameters_references(&self) -> Vec<String> {
         let available_references = self
             .parameters
             .iter()
@@ -431,343 +1365,2013 @@ impl CommandSyntax {
             .chain(self.groups.iter().map(|group| group.dest()))
             .collect::<HashSet<_>>();
 
+        let mut errors = vec![];
         for param in &self.parameters {
             let dest = param.dest();
 
-            self.check_parameters_references_iter(
+            errors.extend(self.check_parameters_references_iter(
                 param.requires.iter().map(|param| sanitize_str(param)),
                 &available_references,
                 "requires",
                 &dest,
-            )?;
-            self.check_parameters_references_iter(
+            ));
+            errors.extend(
+                self.check_parameters_references_iter(
+                    param
+                        .requires_one_of
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "requires_one_of",
+                    &dest,
+                ),
+            );
+            errors.extend(self.check_parameters_references_iter(
                 param.conflicts_with.iter().map(|param| sanitize_str(param)),
                 &available_references,
                 "conflicts_with",
                 &dest,
-            )?;
-            self.check_parameters_references_iter(
-                param
-                    .required_without
-                    .iter()
-                    .map(|param| sanitize_str(param)),
-                &available_references,
-                "required_without",
-                &dest,
-            )?;
-            self.check_parameters_references_iter(
-                param
-                    .required_without_all
-                    .iter()
-                    .map(|param| sanitize_str(param)),
-                &available_references,
-                "required_without_all",
-                &dest,
-            )?;
-            self.check_parameters_references_iter(
-                param
-                    .required_if_eq
-                    .keys()
-                    .map(|k| sanitize_str(k))
-                    .collect::<Vec<_>>()
-                    .iter(),
-                &available_references,
-                "required_if_eq",
-                &dest,
-            )?;
-            self.check_parameters_references_iter(
+            ));
+            errors.extend(
+                self.check_parameters_references_iter(
+                    param
+                        .conflicts_with_all
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "conflicts_with_all",
+                    &dest,
+                ),
+            );
+            errors.extend(
+                self.check_parameters_references_iter(
+                    param
+                        .required_without
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "required_without",
+                    &dest,
+                ),
+            );
+            errors.extend(
+                self.check_parameters_references_iter(
+                    param
+                        .required_without_all
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "required_without_all",
+                    &dest,
+                ),
+            );
+            errors.extend(
+                self.check_parameters_references_iter(
+                    param
+                        .required_if_eq
+                        .keys()
+                        .map(|k| sanitize_str(k))
+                        .collect::<Vec<_>>()
+                        .iter(),
+                    &available_references,
+                    "required_if_eq",
+                    &dest,
+                ),
+            );
+            errors.extend(self.check_parameters_references_iter(
                 param.required_if_eq_all.keys().map(|k| sanitize_str(k)),
                 &available_references,
                 "required_if_eq_all",
                 &dest,
-            )?;
+            ));
         }
 
         for group in &self.groups {
             let dest = group.dest();
 
-            self.check_parameters_references_iter(
+            errors.extend(self.check_parameters_references_iter(
                 group.parameters.iter().map(|param| sanitize_str(param)),
                 &available_references,
                 "parameters",
                 &dest,
-            )?;
+            ));
 
-            self.check_parameters_references_iter(
+            errors.extend(self.check_parameters_references_iter(
                 group.requires.iter().map(|param| sanitize_str(param)),
                 &available_references,
                 "requires",
                 &dest,
-            )?;
+            ));
 
-            self.check_parameters_references_iter(
+            errors.extend(self.check_parameters_references_iter(
                 group.conflicts_with.iter().map(|param| sanitize_str(param)),
                 &available_references,
                 "conflicts_with",
                 &dest,
-            )?;
+            ));
+
+            errors.extend(
+                self.check_parameters_references_iter(
+                    group
+                        .conflicts_with_all
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "conflicts_with_all",
+                    &dest,
+                ),
+            );
+
+            errors.extend(
+                self.check_parameters_references_iter(
+                    group
+                        .required_without
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "required_without",
+                    &dest,
+                ),
+            );
+
+            errors.extend(
+                self.check_parameters_references_iter(
+                    group
+                        .required_without_all
+                        .iter()
+                        .map(|param| sanitize_str(param)),
+                    &available_references,
+                    "required_without_all",
+                    &dest,
+                ),
+            );
+
+            errors.extend(self.check_parameters_references_iter(
+                group.required_if_eq.keys().map(|k| sanitize_str(k)),
+                &available_references,
+                "required_if_eq",
+                &dest,
+            ));
+
+            errors.extend(self.check_parameters_references_iter(
+                group.required_if_eq_all.keys().map(|k| sanitize_str(k)),
+                &available_references,
+                "required_if_eq_all",
+                &dest,
+            ));
         }
 
-        Ok(())
+        errors
     }
 
     /// The identifiers in the parameters and groups should be unique
     /// across the parameters and groups, or else it will lead to panic
-    fn check_parameters_unique_names(&self) -> Result<(), String> {
+    fn check_parameters_unique_names(&self) -> Vec<String> {
+        let mut errors = vec![];
         let mut dests = HashSet::new();
         let mut names = HashSet::new();
 
         for param in &self.parameters {
-            let dest = param.dest();
-            if !dests.insert(dest.clone()) {
-                return Err(format!(
-                    "identifier {} is defined more than once",
-                    dest.light_yellow()
-                ));
+            for dest in std::iter::once(param.dest()).chain(param.repo_org_dest()) {
+                if !dests.insert(dest.clone()) {
+                    errors.push(format!(
+                        "identifier {} is defined more than once",
+                        dest.light_yellow()
+                    ));
+                }
             }
 
             for name in param.all_names() {
                 // Check if name is -h or --help or any other reserved names
                 if Self::RESERVED_NAMES.contains(&name.as_str()) {
-                    return Err(format!(
+                    errors.push(format!(
                         "name {} is reserved and cannot be used",
                         name.light_yellow()
                     ));
+                    continue;
+                }
+
+                if !names.insert(name.clone()) {
+                    errors.push(format!(
+                        "name {} is defined more than once",
+                        name.light_yellow()
+                    ));
+                }
+            }
+        }
+
+        for group in &self.groups {
+            let dest = group.dest();
+            if !dests.insert(dest.clone()) {
+                errors.push(format!(
+                    "identifier {} is defined more than once",
+                    dest.light_yellow()
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Allow hyphen values requires that the argument can take a value.
+    /// It will thus panic if:
+    /// - Set when num_values is set to 0
+    /// - Set on a counter
+    /// - Set on a flag
+    fn check_parameters_allow_hyphen_values(&self) -> Vec<String> {
+        let mut errors = vec![];
+
+        // Grab all the counter params
+        let params = self
+            .parameters
+            .iter()
+            .filter(|param| param.allow_hyphen_values);
+
+        for param in params {
+            if let Some(SyntaxOptArgNumValues::Exactly(0)) = param.num_values {
+                errors.push(format!(
+                    "{}: cannot use {} with 'num_values=0'",
+                    param.name().light_yellow(),
+                    "allow_hyphen_values".light_yellow(),
+                ));
+            }
+
+            match param.arg_type {
+                SyntaxOptArgType::Flag | SyntaxOptArgType::Counter => errors.push(format!(
+                    "{}: cannot use {} on a {}",
+                    param.name().light_yellow(),
+                    "allow_hyphen_values".light_yellow(),
+                    param.arg_type.to_str(),
+                )),
+                _ => {}
+            }
+        }
+
+        errors
+    }
+
+    /// Positional parameters have some constraints that could lead the
+    /// building of the argument parser to panic:
+    /// - If a non-required positional argument appears before a required one
+    /// - If a num_values > 1 positional argument appears before a non-required
+    ///   one, the latter must have last=true or required=true
+    /// - If using num_values=0 or any number of values lower than 1 for a required
+    ///   positional argument
+    fn check_parameters_positional(&self) -> Vec<String> {
+        let mut prev_positional_with_num_values: Option<String> = None;
+        let mut prev_positional_with_multiple_values: Option<String> = None;
+        let mut prev_positional_without_required: Option<String> = None;
+
+        for param in self.parameters.iter().filter(|param| param.is_positional()) {
+            if !param.required {
+                if !param.last_arg_double_hyphen {
+                    if let Some(prev) = prev_positional_with_num_values {
+                        return vec![format!(
+                            "{}: positional need to be required or use '{}' if appearing after {} with num_values > 1",
+                            param.name().light_yellow(),
+                            "last=true".light_yellow(),
+                            prev.light_yellow(),
+                        )];
+                    }
+
+                    if let Some(prev) = prev_positional_with_multiple_values {
+                        return vec![format!(
+                            "{}: positional need to be required or use '{}' if appearing after {} which takes multiple values",
+                            param.name().light_yellow(),
+                            "last=true".light_yellow(),
+                            prev.light_yellow(),
+                        )];
+                    }
+                }
+
+                if prev_positional_without_required.is_none() {
+                    prev_positional_without_required = Some(param.name().clone());
+                }
+            } else if let Some(prev) = prev_positional_without_required {
+                return vec![format!(
+                    "{}: required positional argument cannot appear after non-required one {}",
+                    param.name().light_yellow(),
+                    prev.light_yellow(),
+                )];
+            } else if let Some(
+                SyntaxOptArgNumValues::Exactly(0)
+                | SyntaxOptArgNumValues::AtMost(0)
+                | SyntaxOptArgNumValues::Between(_, 0),
+            ) = param.num_values
+            {
+                return vec![format!(
+                    "{}: positional argument cannot have 'num_values=0'",
+                    param.name().light_yellow(),
+                )];
+            }
+
+            if param.num_values.is_some() && prev_positional_with_num_values.is_none() {
+                prev_positional_with_num_values = Some(param.name().clone());
+            }
+
+            if param.takes_multiple_values() && prev_positional_with_multiple_values.is_none() {
+                prev_positional_with_multiple_values = Some(param.name().clone());
+            }
+        }
+
+        // Unless the final positional uses 'last=true', only the final
+        // positional, or the one right before it, can take multiple values
+        let positionals = self
+            .parameters
+            .iter()
+            .filter(|param| param.is_positional())
+            .collect::<Vec<_>>();
+        if !positionals.iter().any(|param| param.is_last()) && positionals.len() > 2 {
+            if let Some(param) = positionals[..positionals.len() - 2]
+                .iter()
+                .find(|param| param.takes_multiple_values())
+            {
+                return vec![format!(
+                    "{}: only the last two positional arguments can take multiple values, unless using '{}'",
+                    param.name().light_yellow(),
+                    "last=true".light_yellow(),
+                )];
+            }
+        }
+
+        vec![]
+    }
+
+    /// The flag parameters have some constraints that could lead the
+    /// building of the argument parser to panic:
+    /// - If a flag has num_values set
+    fn check_parameters_flag(&self) -> Vec<String> {
+        self.parameters
+            .iter()
+            .filter(|param| param.arg_type == SyntaxOptArgType::Flag)
+            .filter(|param| param.num_values.is_some())
+            .map(|param| {
+                format!(
+                    "{}: flag argument cannot have 'num_values' set",
+                    param.name().light_yellow(),
+                )
+            })
+            .collect()
+    }
+
+    /// The validation patterns have some constraints that could lead the
+    /// building of the argument parser to fail:
+    /// - If the pattern is not a valid regular expression
+    /// - If the parameter does not take string values
+    fn check_parameters_validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+
+        for param in &self.parameters {
+            let pattern = match &param.validate {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            if !param.can_be_validated() {
+                errors.push(format!(
+                    "{}: cannot use {} on a {}",
+                    param.name().light_yellow(),
+                    "validate".light_yellow(),
+                    param.arg_type().terminal_type().to_str(),
+                ));
+            } else if let Err(err) = regex::Regex::new(pattern) {
+                errors.push(format!(
+                    "{}: invalid {} pattern '{}': {}",
+                    param.name().light_yellow(),
+                    "validate".light_yellow(),
+                    pattern,
+                    err,
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// The checks that the parameters need to pass for the argument
+    /// parser to be built, in the order in which they are run
+    const PARAMETER_CHECKS: [fn(&Self) -> Vec<String>; 9] = [
+        Self::check_parameters_unique_names,
+        Self::check_parameters_references,
+        Self::check_parameters_leftovers,
+        Self::check_parameters_last,
+        Self::check_parameters_counter,
+        Self::check_parameters_allow_hyphen_values,
+        Self::check_parameters_positional,
+        Self::check_parameters_flag,
+        Self::check_parameters_validate,
+    ];
+
+    /// Returns the first problem found in the parameters, stopping at
+    /// the first check that fails
+    fn check_parameters(&self) -> Result<(), String> {
+        for check in Self::PARAMETER_CHECKS {
+            if let Some(error) = check(self).into_iter().next() {
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns all the problems found in the parameters, going through
+    /// all the checks instead of stopping at the first one that fails,
+    /// so that they can all be fixed at once
+    pub fn check_parameters_all(&self) -> Vec<String> {
+        Self::PARAMETER_CHECKS
+            .iter()
+            .flat_map(|check| check(self))
+            .collect()
+    }
+
+    /// Returns the argument parser for the syntax, for the command called
+    /// as `called_as`; the parameters are only checked and the parser only
+    /// built the first time for a given syntax, the following calls in the
+    /// same process reusing the parser that was built
+    pub fn argparser(&self, called_as: Vec<String>) -> Result<clap::Command, String> {
+        let key = self.argparser_key(&called_as);
+
+        let cached = ARGPARSER_CACHE
+            .lock()
+            .expect("failed to lock the argument parsers cache")
+            .get(&key)
+            .cloned();
+        if let Some(parser) = cached {
+            return Ok(parser);
+        }
+
+        let parser = self.build_argparser(called_as)?;
+
+        #[cfg(test)]
+        {
+            *ARGPARSER_BUILDS
+                .lock()
+                .expect("failed to lock the argument parsers builds")
+                .entry(key.clone())
+                .or_default() += 1;
+        }
+
+        ARGPARSER_CACHE
+            .lock()
+            .expect("failed to lock the argument parsers cache")
+            .insert(key, parser.clone());
+
+        Ok(parser)
+    }
+
+    /// Returns the key identifying the argument parser built for the
+    /// syntax: a hash of the syntax, of how the command is called, and
+    /// of the values that the parser is built from that do not come from
+    /// the syntax itself, i.e. the default values read from the
+    /// configuration and the values of the environment variables of the
+    /// parameters, so that a different parser is built if any changes
+    fn argparser_key(&self, called_as: &[String]) -> String {
+        let resolved = self
+            .parameters
+            .iter()
+            .map(|param| (param.default_value(), param.env_value()))
+            .collect::<Vec<_>>();
+        // Going through a JSON value sorts the keys of the maps, so that
+        // the same syntax always gives the same key
+        let serialized = serde_json::to_value((self, called_as, resolved))
+            .expect("failed to serialize the syntax")
+            .to_string();
+
+        blake3::hash(serialized.as_bytes()).to_hex().to_string()
+    }
+
+    /// Returns the number of times an argument parser was built for the
+    /// syntax called as `called_as`
+    #[cfg(test)]
+    fn argparser_builds(&self, called_as: &[String]) -> usize {
+        ARGPARSER_BUILDS
+            .lock()
+            .expect("failed to lock the argument parsers builds")
+            .get(&self.argparser_key(called_as))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn build_argparser(&self, called_as: Vec<String>) -> Result<clap::Command, String> {
+        let mut parser = clap::Command::new(called_as.join(" "))
+            .disable_help_subcommand(true)
+            .disable_version_flag(true);
+
+        self.check_parameters()?;
+
+        for param in &self.parameters {
+            parser = param.add_to_argparser(parser);
+        }
+
+        for group in &self.groups {
+            parser = group.add_to_argparser(parser);
+        }
+
+        // The argument parser requires all the arguments listed when an
+        // argument requires others, so the parameters that a parameter
+        // requires one of are put in a hidden group that it requires
+        for param in &self.parameters {
+            if param.requires_one_of.is_empty() {
+                continue;
+            }
+
+            parser = parser.group(
+                clap::ArgGroup::new(param.requires_one_of_group())
+                    .args(
+                        self.requires_one_of_parameters(param)
+                            .iter()
+                            .map(|member| member.dest()),
+                    )
+                    .multiple(true),
+            );
+        }
+
+        // The argument parser does not support requiring a group unless
+        // other arguments are provided, so the members of such a group
+        // and those arguments are put in a hidden required group
+        for group in &self.groups {
+            for (id, alternatives) in group.required_alternatives() {
+                parser = parser.group(
+                    clap::ArgGroup::new(id)
+                        .args(
+                            self.group_parameters_or(group, &alternatives)
+                                .iter()
+                                .map(|member| member.dest()),
+                        )
+                        .multiple(true)
+                        .required(true),
+                );
+            }
+        }
+
+        Ok(parser)
+    }
+
+    pub fn parse_args_typed(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind> {
+        self.parse_args_typed_interactive(argv, called_as, true)
+    }
+
+    /// Parses the arguments; when `interactive`, the arguments are parsed
+    /// to run the command, so the help is shown if requested, the secrets
+    /// are prompted for, and the deprecated parameters are warned about
+    fn parse_args_typed_interactive(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+        interactive: bool,
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind> {
+        // Secrets are only prompted for when the arguments are parsed to
+        // run the command, and if the user can actually answer the prompt
+        self.parse_args_typed_with_prompt(argv, called_as, interactive, |param| {
+            if !interactive || !shell_is_interactive() {
+                return None;
+            }
+
+            prompt_secret(&param.dest(), &format!("{}:", param.name()))
+                .ok()
+                .map(|secret| secret.to_string())
+        })
+    }
+
+    /// Parses the arguments, using the given prompt function to obtain
+    /// the value of the secrets that were not provided; the values of
+    /// the sensitive parameters are redacted from the errors
+    fn parse_args_typed_with_prompt<F>(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+        interactive: bool,
+        prompt: F,
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind>
+    where
+        F: Fn(&SyntaxOptArg) -> Option<String>,
+    {
+        self.try_parse_args_typed(argv, called_as, interactive, prompt)
+            .map_err(|err| self.redacted_error(err))
+    }
+
+    fn try_parse_args_typed<F>(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+        interactive: bool,
+        prompt: F,
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind>
+    where
+        F: Fn(&SyntaxOptArg) -> Option<String>,
+    {
+        let mut parse_argv = vec!["".to_string()];
+        parse_argv.extend(argv);
+
+        let parser = match self.argparser(called_as.clone()) {
+            Ok(parser) => parser,
+            Err(err) => {
+                // Report all the problems of the syntax at once, so that
+                // they do not have to be fixed one at a time
+                let mut errors = self.check_parameters_all();
+                if errors.is_empty() {
+                    errors.push(err);
+                }
+                return Err(ParseArgsErrorKind::ParserBuildError(errors));
+            }
+        };
+
+        let (parse_argv, mut parser) =
+            self.terminate_trailing_positional(parse_argv, &called_as, parser)?;
+
+        let matches = match parser.try_get_matches_from_mut(&parse_argv) {
+            Err(err) => match err.kind() {
+                clap::error::ErrorKind::DisplayHelp if interactive => {
+                    HelpCommand::new().exec_with_exit_code(called_as, 0);
+                    unreachable!("help command should have exited");
+                }
+                clap::error::ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand if interactive => {
+                    HelpCommand::new().exec_with_exit_code(called_as, 1);
+                    unreachable!("help command should have exited");
+                }
+                clap::error::ErrorKind::DisplayVersion => {
+                    unreachable!("version flag is disabled");
+                }
+                clap::error::ErrorKind::MissingRequiredArgument => {
+                    let err = self.explain_missing_groups(err, &mut parser);
+                    return Err(ParseArgsErrorKind::ArgumentParsingError(err));
+                }
+                _ => {
+                    return Err(ParseArgsErrorKind::ArgumentParsingError(err));
+                }
+            },
+            Ok(matches) => matches,
+        };
+
+        self.check_conflicts_with_all(&matches, &mut parser)?;
+
+        let mut args = BTreeMap::new();
+
+        for param in &self.parameters {
+            param.add_to_args(&mut args, &matches, &parser, None)?;
+            if !param.variants.is_empty() {
+                param.dispatch_to_variant(&mut args)?;
+            }
+        }
+
+        self.check_groups_required_if_eq(&matches, &mut parser)?;
+
+        for group in &self.groups {
+            group.add_to_args(&mut args, &matches, &parser, &self.parameters)?;
+        }
+
+        self.prompt_secrets(&mut args, &parser, prompt)?;
+
+        if interactive {
+            for warning in self.deprecation_warnings(&matches, &parse_argv) {
+                omni_warning!(warning, called_as.join(" "));
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// Returns the warnings for the deprecated parameters, and for the
+    /// deprecated names of parameters, that were used on the command
+    /// line; the parameters that got their value from their default or
+    /// from the environment are not reported
+    fn deprecation_warnings(&self, matches: &clap::ArgMatches, argv: &[String]) -> Vec<String> {
+        // Only the arguments before `--` can be names of parameters
+        let argv = argv
+            .iter()
+            .take_while(|arg| arg.as_str() != "--")
+            .collect::<Vec<_>>();
+
+        let mut warnings = vec![];
+
+        for param in &self.parameters {
+            let provided = matches!(
+                matches.value_source(&param.dest()),
+                Some(clap::parser::ValueSource::CommandLine)
+            );
+            if !provided {
+                continue;
+            }
+
+            if let Some(message) = &param.deprecated {
+                warnings.push(format!("{} is deprecated: {}", param.name(), message));
+            }
+
+            for (name, message) in &param.deprecated_names {
+                if argv.iter().any(|arg| arg_uses_name(arg, name)) {
+                    warnings.push(format!("{} is deprecated: {}", name, message));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Reports the groups that are missing while the conditions of their
+    /// `required_if_eq` or `required_if_eq_all` are met; the argument
+    /// parser only supports those conditions on arguments, so they are
+    /// checked once the arguments are parsed, and reported the same way
+    /// as the groups found missing by the argument parser
+    fn check_groups_required_if_eq(
+        &self,
+        matches: &clap::ArgMatches,
+        parser: &mut clap::Command,
+    ) -> Result<(), ParseArgsErrorKind> {
+        let is_met = |(reference, value): (&String, &String)| {
+            self.referenced_parameters(std::slice::from_ref(reference))
+                .iter()
+                .filter(|param| Self::provided(matches, param))
+                .any(|param| {
+                    raw_values(matches, &param.dest()).iter().any(|raw| {
+                        if param.ignore_case {
+                            raw.eq_ignore_ascii_case(value)
+                        } else {
+                            raw == value
+                        }
+                    })
+                })
+        };
+
+        let missing = self
+            .groups
+            .iter()
+            .filter(|group| {
+                group.required_if_eq.iter().any(&is_met)
+                    || (!group.required_if_eq_all.is_empty()
+                        && group.required_if_eq_all.iter().all(&is_met))
+            })
+            .map(|group| self.group_parameters(group))
+            .filter(|members| {
+                !members.is_empty() && !members.iter().any(|member| Self::provided(matches, member))
+            })
+            .map(|members| {
+                let members = members
+                    .iter()
+                    .filter_map(|member| {
+                        parser
+                            .get_arguments()
+                            .find(|arg| arg.get_id() == member.dest().as_str())
+                            .map(rendered_group_member)
+                    })
+                    .collect::<Vec<_>>();
+                format!("<{}>", members.join("|"))
+            })
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut err =
+            clap::Error::new(clap::error::ErrorKind::MissingRequiredArgument).with_cmd(parser);
+        err.insert(
+            clap::error::ContextKind::InvalidArg,
+            clap::error::ContextValue::Strings(missing),
+        );
+        err.insert(
+            clap::error::ContextKind::Usage,
+            clap::error::ContextValue::StyledStr(parser.render_usage()),
+        );
+        Err(ParseArgsErrorKind::ArgumentParsingError(
+            self.explain_missing_groups(err, parser),
+        ))
+    }
+
+    /// Reports the parameters and groups provided along with all of the
+    /// parameters and groups listed in their `conflicts_with_all`; the
+    /// argument parser only supports conflicting with any of the listed
+    /// arguments, so those conflicts are checked once the arguments are
+    /// parsed, and reported the same way as the other conflicts
+    fn check_conflicts_with_all(
+        &self,
+        matches: &clap::ArgMatches,
+        parser: &mut clap::Command,
+    ) -> Result<(), ParseArgsErrorKind> {
+        let sources = self
+            .parameters
+            .iter()
+            .map(|param| (vec![param], &param.conflicts_with_all))
+            .chain(
+                self.groups
+                    .iter()
+                    .map(|group| (self.group_parameters(group), &group.conflicts_with_all)),
+            );
+
+        for (members, conflicts_with_all) in sources {
+            if conflicts_with_all.is_empty() {
+                continue;
+            }
+
+            let provided = match members
+                .into_iter()
+                .find(|param| Self::provided(matches, param))
+            {
+                Some(provided) => provided,
+                None => continue,
+            };
+
+            // Each of the listed parameters or groups needs to be provided
+            // for the conflict to happen
+            let conflicting = conflicts_with_all
+                .iter()
+                .map(|name| {
+                    self.referenced_parameters(std::slice::from_ref(name))
+                        .into_iter()
+                        .find(|param| Self::provided(matches, param))
+                })
+                .collect::<Option<Vec<_>>>();
+            let conflicting = match conflicting {
+                Some(conflicting) => conflicting,
+                None => continue,
+            };
+
+            let rendered = |param: &SyntaxOptArg| {
+                parser
+                    .get_arguments()
+                    .find(|arg| arg.get_id() == param.dest().as_str())
+                    .map(|arg| arg.to_string())
+                    .unwrap_or_else(|| param.name())
+            };
+            let invalid_arg = rendered(provided);
+            let prior_args = conflicting.into_iter().map(rendered).collect::<Vec<_>>();
+
+            let message = format!(
+                "the argument '{}' cannot be used with all of: {}",
+                invalid_arg,
+                prior_args.iter().map(|arg| format!("'{arg}'")).join(", "),
+            );
+            let mut err =
+                clap::Error::raw(clap::error::ErrorKind::ArgumentConflict, message).format(parser);
+            err.insert(
+                clap::error::ContextKind::InvalidArg,
+                clap::error::ContextValue::String(invalid_arg),
+            );
+            err.insert(
+                clap::error::ContextKind::PriorArg,
+                clap::error::ContextValue::Strings(prior_args),
+            );
+            return Err(ParseArgsErrorKind::ArgumentParsingError(err));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the parameter was provided on the command line
+    fn provided(matches: &clap::ArgMatches, param: &SyntaxOptArg) -> bool {
+        matches.value_source(&param.dest()) == Some(clap::parser::ValueSource::CommandLine)
+    }
+
+    /// Prompts for the value of the required secret parameters that were
+    /// not provided and do not have a default value, using the given
+    /// prompt function; a required secret for which no value could be
+    /// obtained is reported the same way as any other missing required
+    /// argument
+    fn prompt_secrets<F>(
+        &self,
+        args: &mut BTreeMap<String, ParseArgsValue>,
+        parser: &clap::Command,
+        prompt: F,
+    ) -> Result<(), ParseArgsErrorKind>
+    where
+        F: Fn(&SyntaxOptArg) -> Option<String>,
+    {
+        let mut missing = vec![];
+
+        for param in self.parameters.iter().filter(|param| param.secret) {
+            let dest = param.dest();
+            let provided = matches!(
+                args.get(&dest),
+                Some(ParseArgsValue::SingleString(Some(value))) if !value.is_empty()
+            );
+            // An optional secret that is not provided is left unset, and
+            // never prompted for, whether the shell is interactive or not
+            if provided || !param.required {
+                continue;
+            }
+
+            match prompt(param) {
+                Some(value) if !value.is_empty() => {
+                    args.insert(dest, ParseArgsValue::SingleString(Some(value)));
+                }
+                _ => {
+                    missing.push(
+                        parser
+                            .get_arguments()
+                            .find(|arg| arg.get_id() == dest.as_str())
+                            .map(|arg| arg.to_string())
+                            .unwrap_or_else(|| param.name()),
+                    );
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut err =
+            clap::Error::new(clap::error::ErrorKind::MissingRequiredArgument).with_cmd(parser);
+        err.insert(
+            clap::error::ContextKind::InvalidArg,
+            clap::error::ContextValue::Strings(missing),
+        );
+        err.insert(
+            clap::error::ContextKind::Usage,
+            clap::error::ContextValue::StyledStr(parser.clone().render_usage()),
+        );
+        Err(ParseArgsErrorKind::ArgumentParsingError(err))
+    }
+
+    /// Returns the indexes of the parameters for a positional taking
+    /// multiple values followed by a single required positional, when
+    /// they are the last positionals of the syntax, e.g. `SRC... DST`
+    fn trailing_positional(&self) -> Option<(usize, usize)> {
+        let positionals = self
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, param)| param.is_positional())
+            .collect::<Vec<_>>();
+
+        let [.., (multi_idx, multi), (trailing_idx, trailing)] = positionals.as_slice() else {
+            return None;
+        };
+
+        if !multi.takes_multiple_values() || multi.is_last() || multi.leftovers {
+            return None;
+        }
+
+        if trailing.takes_multiple_values()
+            || !trailing.required
+            || trailing.is_last()
+            || trailing.leftovers
+        {
+            return None;
+        }
+
+        Some((*multi_idx, *trailing_idx))
+    }
+
+    /// When the syntax ends with a positional taking multiple values
+    /// followed by a single required positional, e.g. `SRC... DST`,
+    /// inserts a value terminator right before the value of the trailing
+    /// positional. The argument parser otherwise attributes to the
+    /// trailing positional the value that precedes an option or the only
+    /// value provided, which leads to misattributed values or misleading
+    /// errors. Returns the arguments and the parser to use for parsing.
+    fn terminate_trailing_positional(
+        &self,
+        parse_argv: Vec<String>,
+        called_as: &[String],
+        mut parser: clap::Command,
+    ) -> Result<(Vec<String>, clap::Command), ParseArgsErrorKind> {
+        let (multi_idx, trailing_idx) = match self.trailing_positional() {
+            Some(indexes) => indexes,
+            None => return Ok((parse_argv, parser)),
+        };
+
+        // Parse the arguments without the trailing positional, so that
+        // all the positional values are captured by the positional taking
+        // multiple values; if that fails, the arguments are left as-is so
+        // that the actual parsing reports the error
+        let mut folded = self.clone();
+        let trailing = folded.parameters.remove(trailing_idx);
+        folded.remove_references(&trailing.dest());
+        let multi = &mut folded.parameters[multi_idx];
+        let min_values = multi
+            .num_values
+            .as_ref()
+            .and_then(|num_values| num_values.min())
+            .unwrap_or(1)
+            .max(1);
+        if !multi.arg_type.is_array() {
+            multi.arg_type = SyntaxOptArgType::Array(Box::new(multi.arg_type.clone()));
+        }
+        multi.num_values = None;
+        let multi_dest = multi.dest();
+
+        let mut values = match folded
+            .argparser(called_as.to_vec())
+            .and_then(|folded_parser| {
+                folded_parser
+                    .try_get_matches_from(&parse_argv)
+                    .map_err(|err| err.to_string())
+            }) {
+            Ok(matches) => raw_values(&matches, &multi_dest),
+            Err(_) => return Ok((parse_argv, parser)),
+        };
+
+        if values.is_empty() {
+            return Ok((parse_argv, parser));
+        }
+
+        if values.len() <= min_values {
+            let trailing_arg = parser
+                .get_arguments()
+                .find(|arg| arg.get_id() == trailing.dest().as_str())
+                .map(|arg| arg.to_string())
+                .unwrap_or_else(|| format!("<{}>", trailing.name()));
+            let mut err =
+                clap::Error::new(clap::error::ErrorKind::MissingRequiredArgument).with_cmd(&parser);
+            err.insert(
+                clap::error::ContextKind::InvalidArg,
+                clap::error::ContextValue::Strings(vec![trailing_arg]),
+            );
+            err.insert(
+                clap::error::ContextKind::Usage,
+                clap::error::ContextValue::StyledStr(parser.render_usage()),
+            );
+            return Err(ParseArgsErrorKind::ArgumentParsingError(err));
+        }
+
+        let trailing_value = values.pop().expect("values should not be empty");
+
+        let mut terminator = ";".to_string();
+        while values.contains(&terminator) {
+            terminator.push(';');
+        }
+
+        // Updating the arguments moves them at the end of the parser, the
+        // positions of the positionals thus need to be explicit
+        let multi_position = self.parameters[..multi_idx]
+            .iter()
+            .filter(|param| param.is_positional())
+            .count()
+            + 1;
+        let mut terminated_parser = parser
+            .clone()
+            .mut_arg(&multi_dest, |arg| {
+                arg.index(multi_position)
+                    .value_terminator(terminator.clone())
+            })
+            .mut_arg(trailing.dest(), |arg| arg.index(multi_position + 1));
+
+        // The value of the trailing positional is the last positional
+        // value, and can thus only be followed by options; since an option
+        // could take the same value, make sure that the values end up
+        // attributed as expected
+        let candidates = parse_argv
+            .iter()
+            .enumerate()
+            .skip(1)
+            .rev()
+            .filter(|(_, arg)| **arg == trailing_value)
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        let mut failed_argv = None;
+        for pos in candidates {
+            let mut terminated_argv = parse_argv.clone();
+            terminated_argv.insert(pos, terminator.clone());
+
+            match terminated_parser.try_get_matches_from_mut(&terminated_argv) {
+                Ok(matches) => {
+                    if raw_values(&matches, &multi_dest) == values
+                        && raw_values(&matches, &trailing.dest()) == vec![trailing_value.clone()]
+                    {
+                        return Ok((terminated_argv, terminated_parser));
+                    }
+                }
+                Err(_) => {
+                    failed_argv.get_or_insert(terminated_argv);
+                }
+            }
+        }
+
+        // If the values could not be attributed as expected but parsing
+        // failed, report the error with the most likely attribution
+        if let Some(failed_argv) = failed_argv {
+            return Ok((failed_argv, terminated_parser));
+        }
+
+        Ok((parse_argv, parser))
+    }
+
+    /// Removes the references to the given parameter from the other
+    /// parameters and from the groups
+    fn remove_references(&mut self, dest: &str) {
+        let is_other = |name: &String| sanitize_str(name) != dest;
+
+        for param in self.parameters.iter_mut() {
+            param.requires.retain(is_other);
+            param.requires_one_of.retain(is_other);
+            param.conflicts_with.retain(is_other);
+            param.conflicts_with_all.retain(is_other);
+            param.required_without.retain(is_other);
+            param.required_without_all.retain(is_other);
+            param.required_if_eq.retain(|name, _| is_other(name));
+            param.required_if_eq_all.retain(|name, _| is_other(name));
+        }
+
+        for group in self.groups.iter_mut() {
+            group.parameters.retain(is_other);
+            group.requires.retain(is_other);
+            group.conflicts_with.retain(is_other);
+            group.conflicts_with_all.retain(is_other);
+            group.required_without.retain(is_other);
+            group.required_without_all.retain(is_other);
+            group.required_if_eq.retain(|name, _| is_other(name));
+            group.required_if_eq_all.retain(|name, _| is_other(name));
+        }
+    }
+
+    pub fn parse_args(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+    ) -> Result<BTreeMap<String, String>, ParseArgsErrorKind> {
+        let typed_args = self.parse_args_typed(argv, called_as)?;
+        Ok(self.export_args(typed_args))
+    }
+
+    /// Parses the arguments the same way as `parse_args`, but returns
+    /// them as a single JSON document mapping the destination of each
+    /// argument to its value, keeping its type; arguments that were not
+    /// provided are `null`, so that they can be distinguished from empty
+    /// values
+    pub fn parse_args_json(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+    ) -> Result<String, ParseArgsErrorKind> {
+        let typed_args = self.parse_args_typed(argv, called_as)?;
+        let json_args = typed_args
+            .iter()
+            .map(|(key, value)| (key.clone(), value.to_json()))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(serde_json::to_string(&json_args).expect("failed to serialize arguments"))
+    }
+
+    /// Parses the arguments the same way as `parse_args`, but without
+    /// showing the help and exiting if the help is requested or if
+    /// arguments are missing; this returns an error instead.
+    pub fn validate_args(
+        &self,
+        argv: Vec<String>,
+        called_as: Vec<String>,
+    ) -> Result<BTreeMap<String, String>, ParseArgsErrorKind> {
+        let typed_args = self.parse_args_typed_interactive(argv, called_as, false)?;
+        Ok(self.export_args(typed_args))
+    }
+
+    /// Rewrites the error for missing required arguments when some of
+    /// them are groups with multiple members, which the argument parser
+    /// shows as `<--param1 <param1>|--param2 <param2>>`, to explain that
+    /// any of the members can be provided, e.g. `requires one of:
+    /// --param1, --param2 (group 'group1')`; the kind and context of
+    /// the error are kept, so that it is handled the same way
+    fn explain_missing_groups(&self, err: clap::Error, parser: &mut clap::Command) -> clap::Error {
+        let missing = match err.get(clap::error::ContextKind::InvalidArg) {
+            Some(clap::error::ContextValue::Strings(missing)) => missing.clone(),
+            _ => return err,
+        };
+
+        let rendered_args = parser
+            .get_arguments()
+            .map(|arg| (rendered_group_member(arg), arg.get_id().to_string()))
+            .collect::<HashMap<_, _>>();
+
+        let mut missing_args = vec![];
+        let mut missing_groups = vec![];
+        for entry in &missing {
+            match self.missing_group(entry, &rendered_args) {
+                Some((origin, members)) => missing_groups.push(format!(
+                    "requires one of: {} ({})",
+                    members
+                        .iter()
+                        .map(|param| if param.is_positional() {
+                            param.help_name(false, false)
+                        } else {
+                            param.name()
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    origin,
+                )),
+                None => missing_args.push(entry.clone()),
+            }
+        }
+
+        if missing_groups.is_empty() {
+            return err;
+        }
+
+        let mut message = vec![];
+        if !missing_args.is_empty() {
+            message.push(format!(
+                "the following required arguments were not provided: {}",
+                missing_args.join(" ")
+            ));
+        }
+        message.extend(missing_groups);
+
+        let mut explained = clap::Error::raw(err.kind(), message.join("; ")).format(parser);
+        explained.insert(
+            clap::error::ContextKind::InvalidArg,
+            clap::error::ContextValue::Strings(missing),
+        );
+        explained
+    }
+
+    /// Returns the parameters matching the given missing argument, as
+    /// rendered by the argument parser, along with where they come from:
+    /// either a group with multiple members, or the parameters that a
+    /// parameter requires one of
+    fn missing_group(
+        &self,
+        entry: &str,
+        rendered_args: &HashMap<String, String>,
+    ) -> Option<(String, Vec<&SyntaxOptArg>)> {
+        let members = entry.strip_prefix('<')?.strip_suffix('>')?;
+        let members = members
+            .split('|')
+            .map(|member| rendered_args.get(member))
+            .collect::<Option<HashSet<_>>>()?;
+        let same_members = |params: &[&SyntaxOptArg]| {
+            params.len() == members.len()
+                && params.iter().all(|param| members.contains(&param.dest()))
+        };
+
+        if members.len() >= 2 {
+            let group = self.groups.iter().find_map(|group| {
+                let params = self.group_parameters(group);
+                same_members(&params).then(|| (format!("group '{}'", group.name), params))
+            });
+            if group.is_some() {
+                return group;
+            }
+
+            // A group required unless other parameters are provided is
+            // missing along with those parameters
+            let group = self.groups.iter().find_map(|group| {
+                group
+                    .required_alternatives()
+                    .into_iter()
+                    .find_map(|(_, alternatives)| {
+                        let params = self.group_parameters_or(group, &alternatives);
+                        same_members(&params).then(|| (format!("group '{}'", group.name), params))
+                    })
+            });
+            if group.is_some() {
+                return group;
+            }
+        }
+
+        self.parameters.iter().find_map(|param| {
+            let params = self.requires_one_of_parameters(param);
+            (!params.is_empty() && same_members(&params))
+                .then(|| (format!("required by {}", param.name()), params))
+        })
+    }
+
+    /// Replaces the value echoed in the error by the argument parser with
+    /// a placeholder when it was provided to a sensitive parameter, e.g.
+    /// `invalid value '***' for '--password <password>'`; the parameter
+    /// itself is still shown, and the kind and context of the error are
+    /// kept, so that it is handled the same way
+    fn redacted_error(&self, err: ParseArgsErrorKind) -> ParseArgsErrorKind {
+        let sensitive = self.error_dest(&err).is_some_and(|dest| {
+            self.parameters
+                .iter()
+                .any(|param| param.dest() == dest && param.is_sensitive())
+        });
+
+        match err {
+            ParseArgsErrorKind::ArgumentParsingError(mut err) if sensitive => {
+                // An empty value is reported as missing, which does not
+                // echo anything
+                let echoes_value = matches!(
+                    err.get(clap::error::ContextKind::InvalidValue),
+                    Some(clap::error::ContextValue::String(value)) if !value.is_empty()
+                );
+                if echoes_value {
+                    err.insert(
+                        clap::error::ContextKind::InvalidValue,
+                        clap::error::ContextValue::String(SECRET_PLACEHOLDER.to_string()),
+                    );
+                }
+                ParseArgsErrorKind::ArgumentParsingError(err)
+            }
+            err => err,
+        }
+    }
+
+    /// Returns the destination of the parameter involved in the
+    /// given parsing error, if it can be identified
+    pub fn error_dest(&self, error: &ParseArgsErrorKind) -> Option<String> {
+        let invalid_arg = error.invalid_arg()?;
+        let arg_name = invalid_arg
+            .split([' ', '='])
+            .next()
+            .filter(|name| !name.is_empty())?;
+
+        if arg_name.starts_with('-') {
+            return self
+                .parameters
+                .iter()
+                .find(|param| param.all_names().iter().any(|name| name == arg_name))
+                .map(|param| param.dest());
+        }
+
+        let value_name = arg_name
+            .trim_end_matches("...")
+            .trim_start_matches(['[', '<'])
+            .trim_end_matches([']', '>']);
+
+        self.parameters
+            .iter()
+            .filter(|param| param.is_positional())
+            .find(|param| {
+                param.dest() == value_name || param.placeholders.iter().any(|ph| ph == value_name)
+            })
+            .map(|param| param.dest())
+    }
+
+    /// Returns the parsed arguments with the values of the sensitive
+    /// parameters replaced by a placeholder, so that they can be shown;
+    /// this handles both the `OMNI_ARG_*` variables and the arguments
+    /// provided as JSON
+    pub fn redacted_args(&self, args: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let sensitive_dests = self.sensitive_dests();
+        if sensitive_dests.is_empty() {
+            return args.clone();
+        }
+
+        args.iter()
+            .map(|(key, value)| {
+                let value = if key == "OMNI_ARGS_JSON" {
+                    redacted_args_json(value, &sensitive_dests)
+                } else {
+                    mask_secret(value, is_value_key_of(key, &sensitive_dests))
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Returns the command line arguments with the values provided to
+    /// the sensitive parameters replaced by a placeholder, so that the
+    /// command line can be shown, e.g. for the command to be rerun; the
+    /// values are found from the given parsed arguments, and are replaced
+    /// whether they are separate arguments or attached to their option
+    pub fn redacted_argv(&self, argv: &[String], args: &BTreeMap<String, String>) -> Vec<String> {
+        let secrets = self.sensitive_values(args);
+        if secrets.is_empty() {
+            return argv.to_vec();
+        }
+
+        argv.iter()
+            .map(|arg| {
+                if secrets.contains(arg) {
+                    return SECRET_PLACEHOLDER.to_string();
+                }
+
+                if arg.starts_with('-') {
+                    if let Some((name, value)) = arg.split_once('=') {
+                        if secrets.contains(value) {
+                            return format!("{name}={SECRET_PLACEHOLDER}");
+                        }
+                    }
+
+                    if !arg.starts_with("--") {
+                        if let (Some(name), Some(value)) = (arg.get(..2), arg.get(2..)) {
+                            if secrets.contains(value) {
+                                return format!("{name}{SECRET_PLACEHOLDER}");
+                            }
+                        }
+                    }
+                }
+
+                arg.clone()
+            })
+            .collect()
+    }
+
+    /// Returns the destinations of the sensitive parameters
+    fn sensitive_dests(&self) -> Vec<String> {
+        self.parameters
+            .iter()
+            .filter(|param| param.is_sensitive())
+            .map(|param| param.dest())
+            .collect()
+    }
+
+    /// Returns the non-empty values of the sensitive parameters in the
+    /// given parsed arguments, whether they are provided through the
+    /// `OMNI_ARG_*` variables or as JSON
+    fn sensitive_values(&self, args: &BTreeMap<String, String>) -> HashSet<String> {
+        let sensitive_dests = self.sensitive_dests();
+        let mut values = HashSet::new();
+
+        for (key, value) in args {
+            if key == "OMNI_ARGS_JSON" {
+                let json_args = match serde_json::from_str::<serde_json::Value>(value) {
+                    Ok(serde_json::Value::Object(json_args)) => json_args,
+                    _ => continue,
+                };
+
+                for dest in &sensitive_dests {
+                    let json_values = match json_args.get(dest) {
+                        Some(serde_json::Value::Array(json_values)) => json_values.clone(),
+                        Some(json_value) => vec![json_value.clone()],
+                        None => continue,
+                    };
+                    values.extend(json_values.into_iter().filter_map(
+                        |json_value| match json_value {
+                            serde_json::Value::String(value) => Some(value),
+                            serde_json::Value::Null => None,
+                            json_value => Some(json_value.to_string()),
+                        },
+                    ));
+                }
+            } else if is_value_key_of(key, &sensitive_dests) {
+                values.insert(value.clone());
+            }
+        }
+
+        values.retain(|value| !value.is_empty());
+        values
+    }
+
+    fn export_args(
+        &self,
+        typed_args: BTreeMap<String, ParseArgsValue>,
+    ) -> BTreeMap<String, String> {
+        let mut args = BTreeMap::new();
+        for (key, value) in &typed_args {
+            value.export_to_env(key, &mut args);
+        }
+
+        let mut all_args = Vec::new();
+        for param in &self.parameters {
+            if param.variants.is_empty() {
+                all_args.push(param.dest());
+            } else if let Some(dest) = param.dispatched_dest(&typed_args) {
+                all_args.push(dest);
+            }
+            all_args.extend(param.repo_org_dest());
+        }
+        for group in &self.groups {
+            all_args.push(group.dest());
+        }
+        args.insert("OMNI_ARG_LIST".to_string(), all_args.join(" "));
+
+        args
+    }
+
+    /// Returns the usage string generated from the parameters of the
+    /// syntax, e.g. `[OPTIONS] --required <REQUIRED> <POSITIONAL>`.
+    /// Required groups are shown as a single element listing their
+    /// members, e.g. `<--param1 | --param2>`, instead of showing each
+    /// of the members separately. Hidden parameters are not shown.
+    pub fn generated_usage(&self) -> String {
+        let mut usage = String::new();
+
+        // Resolve the members of the required groups; a parameter that
+        // is part of multiple required groups is only shown in the first
+        let mut grouped = HashSet::new();
+        let mut groups_usage = vec![];
+        for group in self.groups.iter().filter(|group| group.required) {
+            let members = self
+                .group_parameters(group)
+                .into_iter()
+                .filter(|param| !param.hidden && grouped.insert(param.dest()))
+                .collect::<Vec<_>>();
+            if !members.is_empty() {
+                groups_usage.push(group.usage(&members));
+            }
+        }
+
+        let params = self
+            .parameters
+            .iter()
+            .filter(|param| !param.hidden && !grouped.contains(&param.dest()))
+            .collect::<Vec<_>>();
+
+        // Take all options, i.e. non-positional that are not required, as
+        // well as the parameters not defined by the command, which are not
+        // detailed to keep the usage short
+        let (options, params): (Vec<_>, Vec<_>) = params.into_iter().partition(|param| {
+            param.origin.is_global() || (!param.required && !param.is_positional())
+        });
+        if !options.is_empty() {
+            usage += &" [OPTIONS]".cyan();
+        }
+
+        // Take all non-positional that are required
+        let (required_options, params): (Vec<_>, Vec<_>) = params
+            .into_iter()
+            .partition(|param| param.required && !param.is_positional());
+        for param in required_options {
+            usage += &format!(" {}", param.usage());
+        }
+
+        // Then the required groups
+        for group_usage in groups_usage {
+            usage += &format!(" {group_usage}");
+        }
+
+        // Finally, we're only left with positional parameters
+        for param in params {
+            if param.is_last() {
+                usage += " --";
+            }
+
+            usage += &format!(" {}", param.usage());
+        }
+
+        usage
+    }
+
+    /// Returns a single completion script of the given shell for all the
+    /// given commands, provided with their path under `bin_name`; the
+    /// commands whose parameters are not valid are completed without
+    /// their parameters
+    pub fn completion_script<'a>(
+        shell: &Shell,
+        bin_name: &str,
+        commands: impl IntoIterator<Item = (Vec<String>, &'a CommandSyntax)>,
+    ) -> Result<String, String> {
+        let generator = match shell {
+            Shell::Bash => clap_complete::Shell::Bash,
+            Shell::Zsh => clap_complete::Shell::Zsh,
+            Shell::Fish => clap_complete::Shell::Fish,
+            Shell::PowerShell => clap_complete::Shell::PowerShell,
+            _ => return Err(format!("completions cannot be generated for {shell}")),
+        };
+
+        let mut root = clap::Command::new(bin_name.to_string())
+            .disable_help_subcommand(true)
+            .disable_version_flag(true);
+        for (path, syntax) in commands {
+            let called_as = std::iter::once(bin_name.to_string())
+                .chain(path.iter().cloned())
+                .collect::<Vec<_>>();
+            let parser = syntax
+                .argparser(called_as.clone())
+                .unwrap_or_else(|_| clap::Command::new(called_as.join(" ")));
+            root = Self::nest_completion_command(root, &path, parser);
+        }
+
+        let mut buffer = vec![];
+        clap_complete::generate(generator, &mut root, bin_name, &mut buffer);
+        String::from_utf8(buffer).map_err(|err| err.to_string())
+    }
+
+    /// Adds the command at the given path of subcommands of the parent,
+    /// creating the intermediate commands that do not exist yet, and
+    /// keeping the subcommands of a command that already exists at
+    /// that path
+    fn nest_completion_command(
+        parent: clap::Command,
+        path: &[String],
+        command: clap::Command,
+    ) -> clap::Command {
+        let (name, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return parent,
+        };
+
+        let exists = parent
+            .get_subcommands()
+            .any(|subcommand| subcommand.get_name() == name);
+        let parent = if exists {
+            parent
+        } else {
+            parent.subcommand(
+                clap::Command::new(name.to_string())
+                    .disable_help_subcommand(true)
+                    .disable_version_flag(true),
+            )
+        };
+
+        parent.mut_subcommand(name, |subcommand| {
+            if !rest.is_empty() {
+                return Self::nest_completion_command(subcommand, rest, command);
+            }
+
+            subcommand
+                .get_subcommands()
+                .cloned()
+                .fold(command.name(name.to_string()), |command, nested| {
+                    command.subcommand(nested)
+                })
+        })
+    }
+
+    /// Returns the context of the completion of the word at index
+    /// `current` of the given words, the arguments of the command, from
+    /// the parameters and values provided in the words before it; `None`
+    /// if nothing can be completed, e.g. after `--` when no parameter
+    /// takes the last values
+    pub fn completion_context(
+        &self,
+        words: &[String],
+        current: usize,
+    ) -> Option<SyntaxCompletionContext> {
+        fn allow_value_check_hyphen(param: &SyntaxOptArg, value: &str) -> bool {
+            if let Some(value_without_hyphen) = value.strip_prefix('-') {
+                if !param.allow_hyphen_values {
+                    // All good if we allow for a parameter to start with `-`
+                    return false;
                 }
 
-                if !names.insert(name.clone()) {
-                    return Err(format!(
-                        "name {} is defined more than once",
-                        name.light_yellow()
-                    ));
+                if !param.allow_negative_numbers || value_without_hyphen.parse::<f64>().is_err() {
+                    // All good if we allow for negative numbers
+                    return false;
                 }
             }
-        }
 
-        for group in &self.groups {
-            let dest = group.dest();
-            if !dests.insert(dest.clone()) {
-                return Err(format!(
-                    "identifier {} is defined more than once",
-                    dest.light_yellow()
-                ));
-            }
+            true
         }
 
-        Ok(())
-    }
-
-    /// Allow hyphen values requires that the argument can take a value.
-    /// It will thus panic if:
-    /// - Set when num_values is set to 0
-    /// - Set on a counter
-    /// - Set on a flag
-    fn check_parameters_allow_hyphen_values(&self) -> Result<(), String> {
-        // Grab all the counter params
-        let params = self
+        let mut state = None;
+        let mut parameters = self.parameters.clone();
+        let last_parameter = self
             .parameters
             .iter()
-            .filter(|param| param.allow_hyphen_values);
+            .find(|param| param.is_last())
+            .cloned();
+
+        // Keep track of what we are seeing, so we can pass it to the
+        // underlying functions when trying to complete a value
+        let mut seen_parameters = Vec::new();
+
+        // Go over the arguments we've seen until `current` and
+        // try to resolve the parameter in the syntax and the number
+        // of values that have been passed (according to the configuration
+        // of the parameter); if a given argument does not have a value,
+        // consider it is a positional (if any).
+        let args = words.iter().take(current).cloned().collect::<Vec<_>>();
+        let mut current_arg = args.first().cloned();
+        let mut current_idx = 0;
+
+        'loop_args: while let Some(arg) = current_arg {
+            current_arg = None;
+
+            let (parameter, mut next_arg) = if arg == "--" {
+                // If we have `--`, find the parameter with the 'last' flag, if any
+                match last_parameter {
+                    Some(ref parameter) => {
+                        state = Some(SyntaxCompletionState::Value {
+                            param: parameter.clone(),
+                            param_idx: current_idx + 1,
+                        });
+                    }
+                    None => {
+                        // We do not need to autocomplete anything if we are
+                        // after the `--` and there is no parameter with the
+                        // 'last' flag
+                        return None;
+                    }
+                }
 
-        for param in params {
-            if let Some(SyntaxOptArgNumValues::Exactly(0)) = param.num_values {
-                return Err(format!(
-                    "{}: cannot use {} with 'num_values=0'",
-                    param.name().light_yellow(),
-                    "allow_hyphen_values".light_yellow(),
-                ));
-            }
+                // No need to keep reading parameters
+                break;
+            } else if arg == "-" {
+                // If we have `-` we just can't complete any parameter, let's just skip
+                (None, None)
+            } else if arg.starts_with("--") {
+                let parameter = self
+                    .parameters
+                    .iter()
+                    .find(|param| param.all_names().iter().any(|name| name == arg.as_str()));
 
-            match param.arg_type {
-                SyntaxOptArgType::Flag | SyntaxOptArgType::Counter => {
-                    return Err(format!(
-                        "{}: cannot use {} on a {}",
-                        param.name().light_yellow(),
-                        "allow_hyphen_values".light_yellow(),
-                        param.arg_type.to_str(),
-                    ))
+                (parameter, None)
+            } else if let Some(arg_name) = arg.strip_prefix('-') {
+                // Split the first char and the following ones, if any
+                // as they would become the next argument
+                let (arg, next_arg) = arg_name.split_at(1);
+                let arg = format!("-{arg}");
+
+                let next_arg = if next_arg.is_empty() {
+                    None
+                } else {
+                    Some(next_arg.to_string())
+                };
+
+                let parameter = self
+                    .parameters
+                    .iter()
+                    .find(|param| param.all_names().iter().any(|name| name == arg.as_str()));
+
+                (parameter, next_arg)
+            } else {
+                // Get the parameters from the list of parameters left, since for positional
+                // we need to remove them from the list as we can't identify them by name alone
+                match self.parameters.iter().find(|param| {
+                    param.is_positional() && !param.is_last() && parameters.contains(param)
+                }) {
+                    Some(parameter) => {
+                        // We need to pass the parameter itself as "next_arg" since for a
+                        // positional, the parameter itself is the value
+                        (Some(parameter), Some(arg))
+                    }
+                    None => {
+                        // If we don't have any positional parameters left, then we can't
+                        // autocomplete this, just skip it
+                        (None, None)
+                    }
                 }
-                _ => {}
+            };
+
+            // If the parameter is not found, skip to the next
+            let parameter = match parameter {
+                Some(parameter) => parameter,
+                None => {
+                    current_idx += 1;
+                    current_arg = args.get(current_idx).cloned();
+                    continue;
+                }
+            };
+
+            // If the parameter is not repeatable, remove it from the list
+            // TODO: how does that work for positionals?
+            if !parameter.is_repeatable() {
+                parameters.retain(|param| param != parameter);
             }
-        }
 
-        Ok(())
-    }
+            // Handle the conflicts between parameters
+            parameters.retain(|param| !check_parameter_conflicts(parameter, param, &self.groups));
+
+            // Consume values as needed
+            if parameter.takes_value() {
+                // How many values to consume at most?
+                let max_values: Option<usize> = parameter
+                    .num_values
+                    .map_or(if parameter.leftovers { None } else { Some(1) }, |num| {
+                        num.max()
+                    });
+                let min_values = parameter.num_values.map_or(1, |num| num.min().unwrap_or(0));
+
+                let param_start_idx = current_idx + if parameter.is_positional() { 0 } else { 1 };
+                let mut current_values = vec![];
+                let mut value_idx = 0;
+                loop {
+                    if let Some(max) = max_values {
+                        if value_idx >= max {
+                            // Stop here if we have the maximum number of values
+                            break;
+                        }
+                    }
 
-    /// Positional parameters have some constraints that could lead the
-    /// building of the argument parser to panic:
-    /// - If a non-required positional argument appears before a required one
-    /// - If a num_values > 1 positional argument appears before a non-required
-    ///   one, the latter must have last=true or required=true
-    /// - If using num_values=0 or any number of values lower than 1 for a required
-    ///   positional argument
-    fn check_parameters_positional(&self) -> Result<(), String> {
-        let mut prev_positional_with_num_values: Option<String> = None;
-        let mut prev_positional_without_required: Option<String> = None;
+                    value_idx += 1;
+                    let value = if let Some(arg) = next_arg {
+                        next_arg = None;
+                        Some(arg)
+                    } else {
+                        current_idx += 1;
+                        args.get(current_idx).cloned()
+                    };
 
-        for param in self.parameters.iter().filter(|param| param.is_positional()) {
-            if !param.required {
-                if !param.last_arg_double_hyphen {
-                    if let Some(prev) = prev_positional_with_num_values {
-                        return Err(format!(
-                            "{}: positional need to be required or use '{}' if appearing after {} with num_values > 1",
-                            param.name().light_yellow(),
-                            "last=true".light_yellow(),
-                            prev.light_yellow(),
-                        ));
+                    if let Some(ref value) = value {
+                        if !allow_value_check_hyphen(parameter, value) {
+                            // If the value is not allowed, then consider it
+                            // is another argument, so exit this loop
+                            current_arg = Some(value.to_string());
+                            break;
+                        }
+
+                        current_values.push(value.clone());
+                    }
+
+                    if current_idx == current || value.is_none() {
+                        state = Some(if value_idx > min_values && !parameter.leftovers {
+                            SyntaxCompletionState::ValueAndParameters {
+                                param: parameter.clone(),
+                                param_idx: param_start_idx,
+                            }
+                        } else {
+                            SyntaxCompletionState::Value {
+                                param: parameter.clone(),
+                                param_idx: param_start_idx,
+                            }
+                        });
+                        break 'loop_args;
                     }
                 }
 
-                if prev_positional_without_required.is_none() {
-                    prev_positional_without_required = Some(param.name().clone());
+                // Add the parameter in the seen list, with values
+                seen_parameters.push((parameter.name(), current_values));
+            } else {
+                // Add the parameter in the seen list, without values
+                seen_parameters.push((parameter.name(), vec![]));
+
+                if let Some(next_arg) = next_arg {
+                    current_arg = Some(format!("-{next_arg}"));
                 }
-            } else if let Some(prev) = prev_positional_without_required {
-                return Err(format!(
-                    "{}: required positional argument cannot appear after non-required one {}",
-                    param.name().light_yellow(),
-                    prev.light_yellow(),
-                ));
-            } else if let Some(
-                SyntaxOptArgNumValues::Exactly(0)
-                | SyntaxOptArgNumValues::AtMost(0)
-                | SyntaxOptArgNumValues::Between(_, 0),
-            ) = param.num_values
-            {
-                return Err(format!(
-                    "{}: positional argument cannot have 'num_values=0'",
-                    param.name().light_yellow(),
-                ));
             }
 
-            if param.num_values.is_some() && prev_positional_with_num_values.is_none() {
-                prev_positional_with_num_values = Some(param.name().clone());
+            if current_arg.is_none() {
+                current_idx += 1;
+                current_arg = args.get(current_idx).cloned();
             }
         }
 
-        Ok(())
+        let state = state.unwrap_or(
+            match (
+                parameters
+                    .iter()
+                    .find(|param| param.is_positional() && !param.is_last()),
+                words.get(current),
+            ) {
+                (Some(param), Some(value)) if allow_value_check_hyphen(param, value) => {
+                    SyntaxCompletionState::ValueAndParameters {
+                        param: param.clone(),
+                        param_idx: current_idx,
+                    }
+                }
+                (Some(param), None) => SyntaxCompletionState::ValueAndParameters {
+                    param: param.clone(),
+                    param_idx: current_idx,
+                },
+                (_, _) => SyntaxCompletionState::Parameters,
+            },
+        );
+
+        Some(SyntaxCompletionContext {
+            state,
+            seen: seen_parameters,
+            remaining: parameters,
+            last: last_parameter,
+        })
     }
 
-    /// The flag parameters have some constraints that could lead the
-    /// building of the argument parser to panic:
-    /// - If a flag has num_values set
-    fn check_parameters_flag(&self) -> Result<(), String> {
-        for param in self
-            .parameters
+    /// Returns the parameters grouped by the section of the help message
+    /// they are shown in, along with the heading of each section: the
+    /// positional parameters first, then the options of each section in
+    /// the order of `sections` followed by the order in which they first
+    /// appear, and the options without a section last, unless `sections`
+    /// places the default section elsewhere; the options that are not
+    /// defined by the command come after all of them, in a global options
+    /// section, in which case the options without a section are shown as
+    /// command options; empty sections are omitted, as are the hidden
+    /// parameters
+    pub fn help_sections<'a>(
+        &self,
+        parameters: impl IntoIterator<Item = &'a SyntaxOptArg>,
+    ) -> Vec<(String, Vec<&'a SyntaxOptArg>)> {
+        let (arguments, options): (Vec<_>, Vec<_>) = parameters
+            .into_iter()
+            .filter(|param| !param.hidden)
+            .partition(|param| param.is_positional());
+        let (global_options, options): (Vec<_>, Vec<_>) = options
+            .into_iter()
+            .partition(|param| param.origin.is_global());
+        let default_section = if global_options.is_empty() {
+            Self::DEFAULT_OPTIONS_SECTION
+        } else {
+            Self::COMMAND_OPTIONS_SECTION
+        };
+
+        let mut sections = self
+            .sections
             .iter()
-            .filter(|param| param.arg_type == SyntaxOptArgType::Flag)
-        {
-            if param.num_values.is_some() {
-                return Err(format!(
-                    "{}: flag argument cannot have 'num_values' set",
-                    param.name().light_yellow(),
-                ));
+            .map(|name| (name.clone(), vec![]))
+            .collect::<Vec<(String, Vec<&SyntaxOptArg>)>>();
+        let mut unsectioned = vec![];
+        for option in options {
+            let name = option
+                .section
+                .as_deref()
+                .unwrap_or(Self::DEFAULT_OPTIONS_SECTION);
+            match sections.iter_mut().find(|(section, _)| section == name) {
+                Some((_, members)) => members.push(option),
+                None if name == Self::DEFAULT_OPTIONS_SECTION => unsectioned.push(option),
+                None => sections.push((name.to_string(), vec![option])),
             }
         }
 
-        Ok(())
-    }
-
-    fn check_parameters(&self) -> Result<(), String> {
-        self.check_parameters_unique_names()?;
-        self.check_parameters_references()?;
-        self.check_parameters_leftovers()?;
-        self.check_parameters_last()?;
-        self.check_parameters_counter()?;
-        self.check_parameters_allow_hyphen_values()?;
-        self.check_parameters_positional()?;
-        self.check_parameters_flag()?;
+        let mut help_sections = vec![];
+        if !arguments.is_empty() {
+            help_sections.push((Self::ARGUMENTS_SECTION.to_string(), arguments));
+        }
+        help_sections.extend(
+            sections
+                .into_iter()
+                .filter(|(_, members)| !members.is_empty())
+                .map(|(section, members)| {
+                    if section == Self::DEFAULT_OPTIONS_SECTION {
+                        (default_section.to_string(), members)
+                    } else {
+                        (section, members)
+                    }
+                }),
+        );
+        if !unsectioned.is_empty() {
+            help_sections.push((default_section.to_string(), unsectioned));
+        }
+        if !global_options.is_empty() {
+            help_sections.push((Self::GLOBAL_OPTIONS_SECTION.to_string(), global_options));
+        }
 
-        Ok(())
+        help_sections
     }
 
-    pub fn argparser(&self, called_as: Vec<String>) -> Result<clap::Command, String> {
-        let mut parser = clap::Command::new(called_as.join(" "))
-            .disable_help_subcommand(true)
-            .disable_version_flag(true);
+    /// Returns the help description of the parameter, including a note
+    /// for each of the groups the parameter is a member of
+    pub fn parameter_help_desc(&self, param: &SyntaxOptArg) -> String {
+        let mut help_desc = param.help_desc();
 
-        self.check_parameters()?;
-
-        for param in &self.parameters {
-            parser = param.add_to_argparser(parser);
-        }
+        for group in self.groups.iter() {
+            if !self
+                .group_parameters(group)
+                .iter()
+                .any(|member| member.dest() == param.dest())
+            {
+                continue;
+            }
 
-        for group in &self.groups {
-            parser = group.add_to_argparser(parser);
+            if !help_desc.is_empty() {
+                help_desc.push(' ');
+            }
+            help_desc.push_str(&group.help_note().light_black());
         }
 
-        Ok(parser)
+        help_desc
     }
 
-    pub fn parse_args_typed(
-        &self,
-        argv: Vec<String>,
-        called_as: Vec<String>,
-    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind> {
-        let mut parse_argv = vec!["".to_string()];
-        parse_argv.extend(argv);
+    /// Returns the parameters that the given parameter requires one of,
+    /// with the groups replaced by their members
+    fn requires_one_of_parameters(&self, param: &SyntaxOptArg) -> Vec<&SyntaxOptArg> {
+        self.referenced_parameters(&param.requires_one_of)
+    }
 
-        let parser = match self.argparser(called_as.clone()) {
-            Ok(parser) => parser,
-            Err(err) => {
-                return Err(ParseArgsErrorKind::ParserBuildError(err));
-            }
-        };
+    /// Returns the parameters matching the given references, with the
+    /// groups replaced by their members; each parameter is only
+    /// returned once
+    fn referenced_parameters(&self, references: &[String]) -> Vec<&SyntaxOptArg> {
+        let mut members: Vec<&SyntaxOptArg> = vec![];
+        for reference in references.iter() {
+            let reference = sanitize_str(reference);
+            let params = if let Some(param) = self.parameters.iter().find(|p| p.dest() == reference)
+            {
+                vec![param]
+            } else if let Some(group) = self.groups.iter().find(|g| g.dest() == reference) {
+                self.group_parameters(group)
+            } else {
+                vec![]
+            };
 
-        let matches = match parser.try_get_matches_from(&parse_argv) {
-            Err(err) => match err.kind() {
-                clap::error::ErrorKind::DisplayHelp => {
-                    HelpCommand::new().exec_with_exit_code(called_as, 0);
-                    unreachable!("help command should have exited");
-                }
-                clap::error::ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => {
-                    HelpCommand::new().exec_with_exit_code(called_as, 1);
-                    unreachable!("help command should have exited");
-                }
-                clap::error::ErrorKind::DisplayVersion => {
-                    unreachable!("version flag is disabled");
+            for param in params {
+                if !members.iter().any(|known| known.dest() == param.dest()) {
+                    members.push(param);
                 }
-                _ => {
-                    return Err(ParseArgsErrorKind::ArgumentParsingError(err));
-                }
-            },
-            Ok(matches) => matches,
-        };
-
-        let mut args = BTreeMap::new();
-
-        for param in &self.parameters {
-            param.add_to_args(&mut args, &matches, None)?;
-        }
-
-        for group in &self.groups {
-            group.add_to_args(&mut args, &matches, &self.parameters)?;
+            }
         }
+        members
+    }
 
-        Ok(args)
+    /// Returns the parameters that are members of the group, resolving
+    /// the members that are groups themselves; unknown members are
+    /// ignored, and each parameter is only returned once
+    fn group_parameters(&self, group: &SyntaxGroup) -> Vec<&SyntaxOptArg> {
+        let mut seen_groups = HashSet::new();
+        let mut members = vec![];
+        self.collect_group_parameters(group, &mut seen_groups, &mut members);
+        members
     }
 
-    pub fn parse_args(
+    /// Returns the parameters that are members of the group, followed
+    /// by the parameters matching the given alternatives to the group
+    fn group_parameters_or(
         &self,
-        argv: Vec<String>,
-        called_as: Vec<String>,
-    ) -> Result<BTreeMap<String, String>, ParseArgsErrorKind> {
-        let typed_args = self.parse_args_typed(argv, called_as)?;
-
-        let mut args = BTreeMap::new();
-        for (key, value) in typed_args {
-            value.export_to_env(&key, &mut args);
+        group: &SyntaxGroup,
+        alternatives: &[String],
+    ) -> Vec<&SyntaxOptArg> {
+        let mut members = self.group_parameters(group);
+        for param in self.referenced_parameters(alternatives) {
+            if !members.iter().any(|known| known.dest() == param.dest()) {
+                members.push(param);
+            }
         }
+        members
+    }
 
-        let mut all_args = Vec::new();
-        for param in &self.parameters {
-            all_args.push(param.dest());
-        }
-        for group in &self.groups {
-            all_args.push(group.dest());
+    fn collect_group_parameters<'a>(
+        &'a self,
+        group: &SyntaxGroup,
+        seen_groups: &mut HashSet<String>,
+        members: &mut Vec<&'a SyntaxOptArg>,
+    ) {
+        if !seen_groups.insert(group.dest()) {
+            return;
         }
-        args.insert("OMNI_ARG_LIST".to_string(), all_args.join(" "));
 
-        Ok(args)
+        for member in group.parameters.iter() {
+            let member = sanitize_str(member);
+            if let Some(param) = self.parameters.iter().find(|param| param.dest() == member) {
+                if !members.iter().any(|known| known.dest() == param.dest()) {
+                    members.push(param);
+                }
+            } else if let Some(subgroup) = self.groups.iter().find(|group| group.dest() == member) {
+                self.collect_group_parameters(subgroup, seen_groups, members);
+            }
+        }
     }
 }
 
@@ -788,7 +3392,19 @@ pub struct SyntaxOptArg {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_from_config: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default_missing_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub num_values: Option<SyntaxOptArgNumValues>,
     #[serde(rename = "delimiter", skip_serializing_if = "Option::is_none")]
@@ -801,12 +3417,22 @@ pub struct SyntaxOptArg {
     pub allow_hyphen_values: bool,
     #[serde(skip_serializing_if = "cache_utils::is_false")]
     pub allow_negative_numbers: bool,
+    /// Whether options require their value to be provided with an equal
+    /// sign; only used by the builtin commands, e.g. for `--porcelain[=v1]`
+    #[serde(skip)]
+    pub require_equals: bool,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub ignore_case: bool,
     #[serde(skip_serializing_if = "cache_utils::is_false")]
     pub group_occurrences: bool,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub requires: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires_one_of: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub conflicts_with: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts_with_all: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub required_without: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -815,6 +3441,24 @@ pub struct SyntaxOptArg {
     pub required_if_eq: HashMap<String, String>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub required_if_eq_all: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<SyntaxOptArgVariant>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub secret: bool,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub sensitive: bool,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deprecated_names: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub export_repo_org: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    #[serde(default, skip_serializing_if = "SyntaxOptArgOrigin::is_command")]
+    pub origin: SyntaxOptArgOrigin,
 }
 
 impl Default for SyntaxOptArg {
@@ -827,22 +3471,70 @@ impl Default for SyntaxOptArg {
             placeholders: vec![],
             arg_type: SyntaxOptArgType::String,
             default: None,
+            default_from_config: None,
+            env: None,
             default_missing_value: None,
+            examples: vec![],
+            validate: None,
+            min: None,
+            max: None,
             num_values: None,
             value_delimiter: None,
             last_arg_double_hyphen: false,
             leftovers: false,
             allow_hyphen_values: false,
             allow_negative_numbers: false,
+            require_equals: false,
+            ignore_case: false,
             group_occurrences: false,
             requires: vec![],
+            requires_one_of: vec![],
             conflicts_with: vec![],
+            conflicts_with_all: vec![],
             required_without: vec![],
             required_without_all: vec![],
             required_if_eq: HashMap::new(),
             required_if_eq_all: HashMap::new(),
+            variants: vec![],
+            secret: false,
+            sensitive: false,
+            hidden: false,
+            deprecated: None,
+            deprecated_names: BTreeMap::new(),
+            export_repo_org: false,
+            section: None,
+            origin: SyntaxOptArgOrigin::Command,
+        }
+    }
+}
+
+/// Whether the given key is one of the `OMNI_ARG_*_VALUE` variables of
+/// the parameters with the given destinations
+fn is_value_key_of(key: &str, dests: &[String]) -> bool {
+    dests.iter().any(|dest| {
+        let value_key = format!("OMNI_ARG_{}_VALUE", dest.to_uppercase());
+        key == value_key || key.starts_with(&format!("{value_key}_"))
+    })
+}
+
+/// Returns the arguments provided as JSON with the values of the given
+/// sensitive parameters replaced by a placeholder; if the arguments cannot
+/// be parsed, they are entirely replaced by the placeholder
+fn redacted_args_json(args_json: &str, sensitive_dests: &[String]) -> String {
+    let mut json_args = match serde_json::from_str::<serde_json::Value>(args_json) {
+        Ok(serde_json::Value::Object(json_args)) => json_args,
+        _ => return SECRET_PLACEHOLDER.to_string(),
+    };
+
+    for dest in sensitive_dests {
+        if let Some(value) = json_args.get_mut(dest) {
+            if !value.is_null() {
+                *value = serde_json::Value::String(SECRET_PLACEHOLDER.to_string());
+            }
         }
     }
+
+    serde_json::Value::Object(json_args).to_string()
 }
 
 impl SyntaxOptArg {
@@ -860,26 +3552,51 @@ impl SyntaxOptArg {
         let mut dest = None;
         let mut required = required;
         let mut default = None;
+        let mut default_from_config = None;
+        let mut env = None;
         let mut default_missing_value = None;
         let mut num_values = None;
         let mut value_delimiter = None;
         let mut last_arg_double_hyphen = false;
         let mut allow_hyphen_values = false;
         let mut allow_negative_numbers = false;
+        let mut ignore_case = false;
         let mut group_occurrences = false;
         let mut requires = vec![];
+        let mut requires_one_of = vec![];
         let mut conflicts_with = vec![];
+        let mut conflicts_with_all = vec![];
         let mut required_without = vec![];
         let mut required_without_all = vec![];
         let mut required_if_eq = HashMap::new();
         let mut required_if_eq_all = HashMap::new();
+        let mut variants = vec![];
+        let mut examples = vec![];
+        let mut validate = None;
+        let mut min = None;
+        let mut max = None;
+        let mut secret = false;
+        let mut sensitive = false;
+        let mut hidden = false;
+        let mut deprecated = None;
+        let mut deprecated_names = BTreeMap::new();
+        let mut export_repo_org = false;
+        let mut section = None;
 
         if let Some(table) = config_value.as_table() {
             let value_for_details;
 
-            if let Some(name_value) = table.get("name") {
+            if let Some(names_value) = table.get("names") {
+                let name_value;
+                (name_value, deprecated_names) =
+                    Self::names_from_config_value(names_value, &error_handler.with_key("names"))?;
+                (names, arg_type, placeholders, leftovers) =
+                    parse_arg_name_checked(&name_value, &error_handler.with_key("names"));
+                value_for_details = Some(config_value.clone());
+            } else if let Some(name_value) = table.get("name") {
                 if let Some(name_value) = name_value.as_str() {
-                    (names, arg_type, placeholders, leftovers) = parse_arg_name(&name_value);
+                    (names, arg_type, placeholders, leftovers) =
+                        parse_arg_name_checked(&name_value, &error_handler.with_key("name"));
                     value_for_details = Some(config_value.clone());
                 } else {
                     error_handler
@@ -891,7 +3608,8 @@ impl SyntaxOptArg {
                 }
             } else if table.len() == 1 {
                 if let Some((key, value)) = table.into_iter().next() {
-                    (names, arg_type, placeholders, leftovers) = parse_arg_name(&key);
+                    (names, arg_type, placeholders, leftovers) =
+                        parse_arg_name_checked(&key, error_handler);
                     value_for_details = Some(value);
                 } else {
                     return None;
@@ -933,10 +3651,32 @@ impl SyntaxOptArg {
 
                     default = value_for_details
                         .get_as_str_or_none("default", &error_handler.with_key("default"));
+                    default_from_config = value_for_details.get_as_str_or_none(
+                        "default_from_config",
+                        &error_handler.with_key("default_from_config"),
+                    );
+                    // `default_from_env` is accepted as an alias of `env`, to
+                    // go along with `default_from_config`
+                    env = value_for_details
+                        .get_as_str_or_none("env", &error_handler.with_key("env"))
+                        .or_else(|| {
+                            value_for_details.get_as_str_or_none(
+                                "default_from_env",
+                                &error_handler.with_key("default_from_env"),
+                            )
+                        });
                     default_missing_value = value_for_details.get_as_str_or_none(
                         "default_missing_value",
                         &error_handler.with_key("default_missing_value"),
                     );
+                    examples = value_for_details
+                        .get_as_str_array("examples", &error_handler.with_key("examples"));
+                    validate = value_for_details
+                        .get_as_str_or_none("validate", &error_handler.with_key("validate"));
+                    min = value_for_details
+                        .get_as_float_or_none("min", &error_handler.with_key("min"));
+                    max = value_for_details
+                        .get_as_float_or_none("max", &error_handler.with_key("max"));
                     num_values = SyntaxOptArgNumValues::from_config_value(
                         value_table.get("num_values"),
                         &error_handler.with_key("num_values"),
@@ -973,6 +3713,11 @@ impl SyntaxOptArg {
                         false,
                         &error_handler.with_key("allow_negative_numbers"),
                     );
+                    ignore_case = value_for_details.get_as_bool_or_default(
+                        "ignore_case",
+                        false,
+                        &error_handler.with_key("ignore_case"),
+                    );
                     group_occurrences = value_for_details.get_as_bool_or_default(
                         "group_occurrences",
                         false,
@@ -990,11 +3735,21 @@ impl SyntaxOptArg {
                     requires = value_for_details
                         .get_as_str_array("requires", &error_handler.with_key("requires"));
 
+                    requires_one_of = value_for_details.get_as_str_array(
+                        "requires_one_of",
+                        &error_handler.with_key("requires_one_of"),
+                    );
+
                     conflicts_with = value_for_details.get_as_str_array(
                         "conflicts_with",
                         &error_handler.with_key("conflicts_with"),
                     );
 
+                    conflicts_with_all = value_for_details.get_as_str_array(
+                        "conflicts_with_all",
+                        &error_handler.with_key("conflicts_with_all"),
+                    );
+
                     required_without = value_for_details.get_as_str_array(
                         "required_without",
                         &error_handler.with_key("required_without"),
@@ -1028,67 +3783,645 @@ impl SyntaxOptArg {
                         }
                     }
 
-                    if let Some(required_if_eq_all_value) = value_table.get("required_if_eq_all") {
-                        if let Some(value) = required_if_eq_all_value.as_table() {
-                            for (key, value) in value {
-                                if let Some(value) = value.as_str_forced() {
-                                    required_if_eq_all.insert(key.to_string(), value.to_string());
-                                } else {
-                                    error_handler
-                                        .with_key("required_if_eq_all")
-                                        .with_key(key)
-                                        .with_expected("string")
-                                        .with_actual(value)
-                                        .error(ConfigErrorKind::InvalidValueType);
-                                }
-                            }
-                        } else {
-                            error_handler
-                                .with_key("required_if_eq_all")
-                                .with_expected("table")
-                                .with_actual(required_if_eq_all_value)
-                                .error(ConfigErrorKind::InvalidValueType);
-                        }
-                    }
+                    if let Some(required_if_eq_all_value) = value_table.get("required_if_eq_all") {
+                        if let Some(value) = required_if_eq_all_value.as_table() {
+                            for (key, value) in value {
+                                if let Some(value) = value.as_str_forced() {
+                                    required_if_eq_all.insert(key.to_string(), value.to_string());
+                                } else {
+                                    error_handler
+                                        .with_key("required_if_eq_all")
+                                        .with_key(key)
+                                        .with_expected("string")
+                                        .with_actual(value)
+                                        .error(ConfigErrorKind::InvalidValueType);
+                                }
+                            }
+                        } else {
+                            error_handler
+                                .with_key("required_if_eq_all")
+                                .with_expected("table")
+                                .with_actual(required_if_eq_all_value)
+                                .error(ConfigErrorKind::InvalidValueType);
+                        }
+                    }
+
+                    let aliases = value_for_details
+                        .get_as_str_array("aliases", &error_handler.with_key("aliases"));
+                    names.extend(aliases);
+
+                    variants = SyntaxOptArgVariant::from_config_value_multi(
+                        value_table.get("variants"),
+                        &error_handler.with_key("variants"),
+                    );
+
+                    secret = value_for_details.get_as_bool_or_default(
+                        "secret",
+                        false,
+                        &error_handler.with_key("secret"),
+                    );
+
+                    sensitive = value_for_details.get_as_bool_or_default(
+                        "sensitive",
+                        false,
+                        &error_handler.with_key("sensitive"),
+                    );
+
+                    hidden = value_for_details.get_as_bool_or_default(
+                        "hidden",
+                        false,
+                        &error_handler.with_key("hidden"),
+                    );
+
+                    deprecated = value_for_details
+                        .get_as_str_or_none("deprecated", &error_handler.with_key("deprecated"));
+
+                    export_repo_org = value_for_details.get_as_bool_or_default(
+                        "export_repo_org",
+                        false,
+                        &error_handler.with_key("export_repo_org"),
+                    );
+
+                    section = value_for_details
+                        .get_as_str_or_none("section", &error_handler.with_key("section"));
+                }
+            }
+        } else if let Some(value) = config_value.as_str() {
+            (names, arg_type, placeholders, leftovers) =
+                parse_arg_name_checked(&value, error_handler);
+        } else {
+            error_handler
+                .with_expected("string or table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+            return None;
+        }
+
+        let param = Self {
+            names,
+            dest,
+            desc,
+            required: required.unwrap_or(false),
+            placeholders,
+            arg_type,
+            default,
+            default_from_config,
+            env,
+            default_missing_value,
+            examples,
+            validate,
+            min,
+            max,
+            num_values,
+            value_delimiter,
+            last_arg_double_hyphen,
+            leftovers,
+            allow_hyphen_values,
+            allow_negative_numbers,
+            require_equals: false,
+            ignore_case,
+            group_occurrences,
+            requires,
+            requires_one_of,
+            conflicts_with,
+            conflicts_with_all,
+            required_without,
+            required_without_all,
+            required_if_eq,
+            required_if_eq_all,
+            variants,
+            secret,
+            sensitive,
+            hidden,
+            deprecated,
+            deprecated_names,
+            export_repo_org,
+            section,
+            origin: SyntaxOptArgOrigin::Command,
+        };
+
+        Some(
+            param
+                .with_validated_variants(error_handler)
+                .with_validated_pattern(error_handler)
+                .with_validated_range(error_handler)
+                .with_validated_examples(error_handler)
+                .with_validated_secret(error_handler)
+                .with_validated_hidden(error_handler)
+                .with_validated_repo_org(error_handler),
+        )
+    }
+
+    /// Parses the names of a parameter provided as a list, in which each
+    /// name can be provided either as a plain string or as a table with a
+    /// `name` and a `deprecated` message; returns the names joined as a
+    /// single parameter name, along with the messages of the deprecated
+    /// ones by name
+    fn names_from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Option<(String, BTreeMap<String, String>)> {
+        let values = match config_value.as_array() {
+            Some(values) => values,
+            None => {
+                error_handler
+                    .with_expected("array")
+                    .with_actual(config_value)
+                    .error(ConfigErrorKind::InvalidValueType);
+                return None;
+            }
+        };
+
+        let mut names = vec![];
+        let mut deprecated_names = BTreeMap::new();
+
+        for (idx, value) in values.iter().enumerate() {
+            let error_handler = error_handler.with_index(idx);
+
+            if let Some(name) = value.as_str_forced() {
+                names.push(name.to_string());
+            } else if value.is_table() {
+                let name = match value.get_as_str_or_none("name", &error_handler.with_key("name")) {
+                    Some(name) => name,
+                    None => {
+                        error_handler
+                            .with_key("name")
+                            .error(ConfigErrorKind::MissingKey);
+                        continue;
+                    }
+                };
+
+                if let Some(message) =
+                    value.get_as_str_or_none("deprecated", &error_handler.with_key("deprecated"))
+                {
+                    let (parsed_names, _, _, _) = parse_arg_name(&name);
+                    for parsed_name in parsed_names {
+                        deprecated_names.insert(parsed_name, message.clone());
+                    }
+                }
+
+                names.push(name);
+            } else {
+                error_handler
+                    .with_expected("string or table")
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValueType);
+            }
+        }
+
+        if names.is_empty() {
+            error_handler.error(ConfigErrorKind::EmptyKey);
+            return None;
+        }
+
+        Some((names.join(", "), deprecated_names))
+    }
+
+    /// Validates that the parameter can be a secret; only parameters
+    /// taking a single string value, without variants, can be secrets.
+    fn with_validated_secret(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        if self.secret && !self.can_be_secret() {
+            error_handler
+                .with_key("secret")
+                .with_actual(self.name())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+            self.secret = false;
+        }
+
+        self
+    }
+
+    /// Validates that the parameter can be hidden; required parameters
+    /// cannot be, since they need to be known to call the command.
+    fn with_validated_hidden(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        if self.hidden && self.required {
+            error_handler
+                .with_key("hidden")
+                .with_actual(self.name())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+            self.hidden = false;
+        }
+
+        self
+    }
+
+    /// Validates that the organization of the repositories can be
+    /// exported for this parameter; it is otherwise disabled.
+    fn with_validated_repo_org(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        if self.export_repo_org && !self.can_export_repo_org() {
+            error_handler
+                .with_key("export_repo_org")
+                .with_actual(self.name())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+            self.export_repo_org = false;
+        }
+
+        self
+    }
+
+    /// Whether the organization of the repositories can be exported for
+    /// the parameter, i.e. whether it takes repository paths and does
+    /// not dispatch them to variants
+    pub fn can_export_repo_org(&self) -> bool {
+        matches!(self.arg_type().terminal_type(), SyntaxOptArgType::RepoPath)
+            && self.variants.is_empty()
+    }
+
+    /// Returns the destination under which the organization of the
+    /// repositories is exported, if the parameter exports it
+    pub fn repo_org_dest(&self) -> Option<String> {
+        self.export_repo_org.then(|| format!("{}_org", self.dest()))
+    }
+
+    /// Whether the parameter can be a secret, i.e. whether it takes a
+    /// single string value and does not dispatch it to variants
+    pub fn can_be_secret(&self) -> bool {
+        matches!(self.arg_type(), SyntaxOptArgType::String)
+            && !self.num_values.as_ref().is_some_and(|n| n.is_many())
+            && self.variants.is_empty()
+    }
+
+    /// Returns the possible values of the parameter that complete the
+    /// given word; when the values are split on a delimiter, only the
+    /// part after the last delimiter is completed, the values before it
+    /// being kept in the candidates
+    pub fn completion_values(&self, word: &str) -> Vec<String> {
+        let possible_values = match self.arg_type().possible_values() {
+            Some(possible_values) => possible_values,
+            None => return vec![],
+        };
+
+        let (prefix, partial) = self
+            .value_delimiter
+            .and_then(|delimiter| {
+                word.rfind(delimiter)
+                    .map(|idx| word.split_at(idx + delimiter.len_utf8()))
+            })
+            .unwrap_or(("", word));
+
+        possible_values
+            .iter()
+            .filter(|value| value.starts_with(partial))
+            .map(|value| format!("{prefix}{value}"))
+            .collect()
+    }
+
+    /// Whether the values provided to the parameter are redacted when
+    /// they are echoed back; secrets are always sensitive
+    pub fn is_sensitive(&self) -> bool {
+        self.sensitive || self.secret
+    }
+
+    /// Returns the value as it can be echoed back to the user, i.e.
+    /// redacted if the parameter is sensitive
+    fn echoed_value(&self, value: &str) -> String {
+        mask_secret(value, self.is_sensitive())
+    }
+
+    /// Validates that the pattern the values need to match, if any, is
+    /// a valid regular expression and can be used for this parameter;
+    /// the pattern is dropped otherwise.
+    fn with_validated_pattern(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        let pattern = match &self.validate {
+            Some(pattern) => pattern,
+            None => return self,
+        };
+
+        if !self.can_be_validated() {
+            error_handler
+                .with_key("validate")
+                .with_actual(self.name())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+            self.validate = None;
+        } else if let Err(err) = regex::Regex::new(pattern) {
+            error_handler
+                .with_key("validate")
+                .with_actual(pattern.to_string())
+                .with_context("error", err.to_string())
+                .error(ConfigErrorKind::ParsingError);
+            self.validate = None;
+        }
+
+        self
+    }
+
+    /// Whether the values of the parameter can be validated against a
+    /// pattern, i.e. whether the parameter takes string values
+    pub fn can_be_validated(&self) -> bool {
+        matches!(
+            self.arg_type().terminal_type(),
+            SyntaxOptArgType::String
+                | SyntaxOptArgType::DirPath
+                | SyntaxOptArgType::FilePath
+                | SyntaxOptArgType::RepoPath
+        )
+    }
+
+    /// Validates that the bounds of the values, if any, can be used for
+    /// this parameter, and that the default value is within them; the
+    /// bounds and default value that cannot be used are dropped.
+    fn with_validated_range(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        if self.min.is_none() && self.max.is_none() {
+            return self;
+        }
+
+        let terminal_type = self.arg_type().terminal_type().clone();
+        if !matches!(
+            terminal_type,
+            SyntaxOptArgType::Integer | SyntaxOptArgType::Float
+        ) {
+            for (key, bound) in [("min", self.min.take()), ("max", self.max.take())] {
+                if let Some(bound) = bound {
+                    error_handler
+                        .with_key(key)
+                        .with_expected("bound for an int or float parameter")
+                        .with_actual(bound)
+                        .error(ConfigErrorKind::InvalidValue);
+                }
+            }
+            return self;
+        }
+
+        if terminal_type == SyntaxOptArgType::Integer {
+            for (key, bound) in [("min", &mut self.min), ("max", &mut self.max)] {
+                if let Some(value) = bound.filter(|value| value.fract() != 0.0) {
+                    error_handler
+                        .with_key(key)
+                        .with_expected("int")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValue);
+                    *bound = None;
+                }
+            }
+        }
+
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                error_handler
+                    .with_key("max")
+                    .with_expected(format!("value greater than or equal to {min}"))
+                    .with_actual(max)
+                    .error(ConfigErrorKind::InvalidValue);
+                self.min = None;
+                self.max = None;
+                return self;
+            }
+        }
+
+        if let (Some(default), Some(range)) = (&self.default, self.range_str()) {
+            let values = if self.arg_type().is_array() {
+                default.split(',').map(str::trim).collect::<Vec<_>>()
+            } else {
+                vec![default.as_str()]
+            };
+
+            let out_of_range = values.into_iter().find(|value| {
+                value
+                    .parse::<f64>()
+                    .is_ok_and(|value| !self.in_range(value))
+            });
+            if let Some(value) = out_of_range {
+                error_handler
+                    .with_key("default")
+                    .with_expected(range)
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValue);
+                self.default = None;
+            }
+        }
+
+        self
+    }
+
+    /// Returns the range of the values of the parameter, in the format
+    /// `min..=max`, `min..` or `..=max`, if the values are bounded
+    pub fn range_str(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => Some(format!("{min}..={max}")),
+            (Some(min), None) => Some(format!("{min}..")),
+            (None, Some(max)) => Some(format!("..={max}")),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether the value is within the bounds of the parameter, if any
+    fn in_range(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+
+    /// Returns a function checking that a value is within the bounds of
+    /// the parameter, with an error describing the range otherwise; the
+    /// function can be moved into the value parser of the argument
+    fn range_checker(
+        &self,
+    ) -> Option<impl Fn(f64) -> Result<(), String> + Clone + Send + Sync + 'static> {
+        let range = self.range_str()?;
+        let (min, max) = (self.min, self.max);
+        let sensitive = self.is_sensitive();
+
+        Some(move |value: f64| {
+            if min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max) {
+                Ok(())
+            } else {
+                let value = mask_secret(&value.to_string(), sensitive);
+                Err(format!("{value} is not in {range}"))
+            }
+        })
+    }
+
+    /// Returns the compiled pattern that the values of the parameter
+    /// need to match, if any and if it is valid
+    fn validate_regex(&self) -> Option<regex::Regex> {
+        self.validate
+            .as_ref()
+            .and_then(|pattern| regex::Regex::new(pattern).ok())
+    }
+
+    /// Returns a function checking that a value matches the pattern
+    /// of the parameter, if any and if it is valid
+    fn pattern_checker(
+        &self,
+    ) -> Option<impl Fn(&str) -> Result<(), String> + Clone + Send + Sync + 'static> {
+        let pattern = self.validate_regex()?;
+        let sensitive = self.is_sensitive();
+
+        Some(move |value: &str| {
+            if pattern.is_match(value) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "value '{}' does not match the pattern '{}'",
+                    mask_secret(value, sensitive),
+                    pattern.as_str()
+                ))
+            }
+        })
+    }
+
+    /// Validates that the examples, if any, are values that the
+    /// parameter accepts; examples that are not are dropped.
+    fn with_validated_examples(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        if self.examples.is_empty() {
+            return self;
+        }
+
+        if !self.takes_value() {
+            error_handler
+                .with_key("examples")
+                .with_actual(self.name())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+            self.examples.clear();
+            return self;
+        }
+
+        let arg_type = self.arg_type();
+        let terminal_type = arg_type.terminal_type();
+        let value_delimiter = self.value_delimiter;
+        let pattern = self.validate_regex();
+
+        let mut index = 0;
+        self.examples.retain(|example| {
+            let values = match (arg_type.is_array(), value_delimiter) {
+                (true, Some(delimiter)) => example.split(delimiter).collect::<Vec<_>>(),
+                _ => vec![example.as_str()],
+            };
+
+            let invalid = values
+                .iter()
+                .find(|value| !terminal_type.accepts_value(value))
+                .copied();
+            if let Some(value) = invalid {
+                let error_handler = error_handler.with_key("examples").with_index(index);
+                match terminal_type.possible_values() {
+                    Some(possible_values) => error_handler.with_expected(possible_values),
+                    None => error_handler.with_expected(terminal_type.to_str()),
+                }
+                .with_actual(value)
+                .error(ConfigErrorKind::InvalidValue);
+            }
+
+            let unmatched = match (&pattern, invalid) {
+                (Some(pattern), None) => values
+                    .iter()
+                    .find(|value| !pattern.is_match(value))
+                    .map(|value| (pattern, *value)),
+                _ => None,
+            };
+            if let Some((pattern, value)) = unmatched {
+                error_handler
+                    .with_key("examples")
+                    .with_index(index)
+                    .with_expected(format!("value matching '{}'", pattern.as_str()))
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValue);
+            }
+
+            index += 1;
+            invalid.is_none() && unmatched.is_none()
+        });
+
+        self
+    }
+
+    /// Returns the examples to suggest when completing the value of the
+    /// parameter; examples are only suggested when nothing has been typed
+    /// yet for the value, so they do not get in the way of real values
+    pub fn completion_examples(&self, comp_value: &str) -> Vec<String> {
+        if !comp_value.is_empty() {
+            return vec![];
+        }
+
+        self.examples.clone()
+    }
+
+    /// Validates that the variants, if any, can be used for this
+    /// parameter; variants are dropped if they cannot be used at all.
+    fn with_validated_variants(mut self, error_handler: &ConfigErrorHandler) -> Self {
+        if self.variants.is_empty() {
+            return self;
+        }
+
+        if !self.is_positional() || self.arg_type().is_array() {
+            error_handler
+                .with_key("variants")
+                .with_actual(self.name())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+            self.variants.clear();
+            return self;
+        }
+
+        // A required dispatch needs a variant without pattern, or
+        // values matching none of the patterns would be rejected
+        // even though the parameter was provided
+        if self.required
+            && self
+                .variants
+                .iter()
+                .all(|variant| variant.pattern.is_some())
+        {
+            error_handler
+                .with_key("required")
+                .with_actual(true)
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+        }
+
+        self
+    }
+
+    /// Moves the value of the parameter to the dest of the first
+    /// variant matching it; other variants do not receive any value.
+    fn dispatch_to_variant(
+        &self,
+        args: &mut BTreeMap<String, ParseArgsValue>,
+    ) -> Result<(), ParseArgsErrorKind> {
+        let value = match args.remove(&self.dest()) {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+
+        let value_str = match &value {
+            ParseArgsValue::SingleString(value) => value.clone(),
+            ParseArgsValue::SingleInteger(value) => value.map(|value| value.to_string()),
+            ParseArgsValue::SingleFloat(value) => value.map(|value| value.to_string()),
+            ParseArgsValue::SingleBoolean(value) => value.map(|value| value.to_string()),
+            _ => None,
+        };
 
-                    let aliases = value_for_details
-                        .get_as_str_array("aliases", &error_handler.with_key("aliases"));
-                    names.extend(aliases);
-                }
+        // Nothing to dispatch if the parameter was not provided
+        let value_str = match value_str {
+            Some(value_str) => value_str,
+            None => return Ok(()),
+        };
+
+        match self
+            .variants
+            .iter()
+            .find(|variant| variant.matches(&value_str))
+        {
+            Some(variant) => {
+                args.insert(variant.dest(), value);
+                Ok(())
             }
-        } else if let Some(value) = config_value.as_str() {
-            (names, arg_type, placeholders, leftovers) = parse_arg_name(&value);
-        } else {
-            error_handler
-                .with_expected("string or table")
-                .with_actual(config_value)
-                .error(ConfigErrorKind::InvalidValueType);
-            return None;
+            None => Err(ParseArgsErrorKind::InvalidValue(format!(
+                "invalid value '{}' for '{}': expected a value matching one of: {}",
+                self.echoed_value(&value_str),
+                self.name(),
+                self.variants
+                    .iter()
+                    .filter_map(|variant| variant.pattern.clone())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ))),
         }
+    }
 
-        Some(Self {
-            names,
-            dest,
-            desc,
-            required: required.unwrap_or(false),
-            placeholders,
-            arg_type,
-            default,
-            default_missing_value,
-            num_values,
-            value_delimiter,
-            last_arg_double_hyphen,
-            leftovers,
-            allow_hyphen_values,
-            allow_negative_numbers,
-            group_occurrences,
-            requires,
-            conflicts_with,
-            required_without,
-            required_without_all,
-            required_if_eq,
-            required_if_eq_all,
-        })
+    /// Returns the dest of the variant that received the value of the
+    /// parameter in the given arguments, if any.
+    fn dispatched_dest(&self, args: &BTreeMap<String, ParseArgsValue>) -> Option<String> {
+        self.variants
+            .iter()
+            .map(|variant| variant.dest())
+            .find(|dest| args.contains_key(dest))
     }
 
     pub fn arg_type(&self) -> SyntaxOptArgType {
@@ -1100,6 +4433,9 @@ impl SyntaxOptArg {
                 | SyntaxOptArgType::Integer
                 | SyntaxOptArgType::Float
                 | SyntaxOptArgType::Boolean
+                | SyntaxOptArgType::IpAddr
+                | SyntaxOptArgType::SocketAddr
+                | SyntaxOptArgType::Bytes
                 | SyntaxOptArgType::Enum(_) => {
                     SyntaxOptArgType::Array(Box::new(self.arg_type.clone()))
                 }
@@ -1119,6 +4455,13 @@ impl SyntaxOptArg {
         sanitize_str(&dest)
     }
 
+    /// Returns the identifier of the hidden group of the parameters that
+    /// this parameter requires one of; it cannot conflict with the other
+    /// identifiers since these are sanitized
+    fn requires_one_of_group(&self) -> String {
+        format!("{}::requires_one_of", self.dest())
+    }
+
     fn organized_names(
         &self,
     ) -> (
@@ -1128,23 +4471,27 @@ impl SyntaxOptArg {
         Vec<String>,
         Vec<String>,
     ) {
-        let long_names = self
+        // The deprecated names are kept last, so that they are never
+        // picked as the main names while another name is available
+        let mut long_names = self
             .names
             .iter()
             .filter(|name| name.starts_with("--"))
             .map(|name| name.to_string())
             .collect::<Vec<_>>();
+        long_names.sort_by_key(|name| self.deprecated_names.contains_key(name));
         let (main_long, long_names) = long_names
             .split_first()
             .map(|(f, r)| (Some(f.clone()), r.to_vec()))
             .unwrap_or((None, vec![]));
 
-        let short_names = self
+        let mut short_names = self
             .names
             .iter()
             .filter(|name| name.starts_with('-') && !name.starts_with("--"))
             .map(|name| name.to_string())
             .collect::<Vec<_>>();
+        short_names.sort_by_key(|name| self.deprecated_names.contains_key(name));
         let (main_short, short_names) = short_names
             .split_first()
             .map(|(f, r)| (Some(f.clone()), r.to_vec()))
@@ -1181,6 +4528,16 @@ impl SyntaxOptArg {
         self.last_arg_double_hyphen
     }
 
+    /// Whether the parameter can receive multiple values, either by
+    /// being repeated or by taking multiple values at once
+    pub fn takes_multiple_values(&self) -> bool {
+        self.arg_type().is_array()
+            || self
+                .num_values
+                .as_ref()
+                .is_some_and(|num_values| num_values.is_many())
+    }
+
     pub fn is_repeatable(&self) -> bool {
         self.arg_type().is_array() || matches!(self.arg_type(), SyntaxOptArgType::Counter)
     }
@@ -1203,7 +4560,7 @@ impl SyntaxOptArg {
     /// Returns the representation of that argument for the
     /// 'usage' string in the help message
     pub fn usage(&self) -> String {
-        self.help_name(false, true)
+        self.help_name(false, ColorCapability::current().enabled())
     }
 
     /// Returns the representation of that argument for the help message
@@ -1387,18 +4744,219 @@ impl SyntaxOptArg {
         help_name
     }
 
+    /// Returns the default value of the argument; if `default_from_config`
+    /// is set, the key path is first resolved against the merged
+    /// configuration, falling back to `default` if the key is not set.
+    pub fn default_value(&self) -> Option<String> {
+        self.default_from_config
+            .as_ref()
+            .and_then(|keypath| {
+                Self::default_value_from_config(&config_loader(".").raw_config, keypath)
+            })
+            .or_else(|| self.default.clone())
+    }
+
+    /// Resolves a dot-separated key path against the provided configuration,
+    /// returning the value only if it is a scalar.
+    fn default_value_from_config(config: &ConfigValue, keypath: &str) -> Option<String> {
+        config.dig(keypath.split('.').collect())?.as_str_forced()
+    }
+
+    /// Returns the value of the environment variable set in `env`, if
+    /// any; a variable set to an empty value is considered as not set.
+    pub fn env_value(&self) -> Option<String> {
+        self.env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Returns the value of the environment variable set in `env`, if
+    /// any, after checking it with the same value parser as the values
+    /// provided on the command line, so that an invalid value is reported
+    /// the same way; values of a flag are normalized to `true` or `false`,
+    /// and values of an enum ignoring the case to their listed form.
+    fn checked_env_value(
+        &self,
+        parser: &clap::Command,
+        split: bool,
+    ) -> Result<Option<String>, ParseArgsErrorKind> {
+        let value = match self.env_value() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let dest = self.dest();
+        let arg = match parser
+            .get_arguments()
+            .find(|arg| arg.get_id() == dest.as_str())
+        {
+            Some(arg) => arg,
+            None => return Ok(Some(value)),
+        };
+
+        let values = if split {
+            value.split(',').map(str::trim).collect::<Vec<_>>()
+        } else {
+            vec![value.as_str()]
+        };
+
+        let mut checked = Vec::with_capacity(values.len());
+        for value in values {
+            let os_value = std::ffi::OsStr::new(value);
+            let result = match self.arg_type().terminal_type() {
+                // The value parser of the argument cannot be called from
+                // here, so the checks it does are repeated, including the
+                // bounds of the values, if any
+                SyntaxOptArgType::Integer => {
+                    let in_range = self.range_checker();
+                    (move |value: &str| {
+                        let parsed = value.parse::<i64>().map_err(|err| err.to_string())?;
+                        match &in_range {
+                            Some(in_range) => in_range(parsed as f64),
+                            None => Ok(()),
+                        }
+                        .map(|_| value.to_string())
+                    })
+                    .parse_ref(parser, Some(arg), os_value)
+                }
+                SyntaxOptArgType::Float => {
+                    let in_range = self.range_checker();
+                    (move |value: &str| {
+                        let parsed = value.parse::<f64>().map_err(|err| err.to_string())?;
+                        match &in_range {
+                            Some(in_range) => in_range(parsed),
+                            None => Ok(()),
+                        }
+                        .map(|_| value.to_string())
+                    })
+                    .parse_ref(parser, Some(arg), os_value)
+                }
+                SyntaxOptArgType::Boolean => clap::builder::BoolValueParser::new()
+                    .parse_ref(parser, Some(arg), os_value)
+                    .map(|_| value.to_string()),
+                SyntaxOptArgType::Counter => clap::value_parser!(u8)
+                    .parse_ref(parser, Some(arg), os_value)
+                    .map(|_| value.to_string()),
+                SyntaxOptArgType::IpAddr => {
+                    parse_keeping_str::<std::net::IpAddr>.parse_ref(parser, Some(arg), os_value)
+                }
+                SyntaxOptArgType::SocketAddr => {
+                    parse_keeping_str::<std::net::SocketAddr>.parse_ref(parser, Some(arg), os_value)
+                }
+                SyntaxOptArgType::Bytes => parse_bytes
+                    .parse_ref(parser, Some(arg), os_value)
+                    .map(|_| value.to_string()),
+                SyntaxOptArgType::Flag => clap::builder::BoolishValueParser::new()
+                    .parse_ref(parser, Some(arg), os_value)
+                    .map(|value| value.to_string()),
+                SyntaxOptArgType::Enum(possible_values) => {
+                    clap::builder::PossibleValuesParser::new(possible_values.clone())
+                        .parse_ref(parser, Some(arg), os_value)
+                        .map(|value| Self::canonical_enum_value(possible_values, value))
+                }
+                _ => match self.pattern_checker() {
+                    Some(matches_pattern) => {
+                        (move |value: &str| matches_pattern(value).map(|_| value.to_string()))
+                            .parse_ref(parser, Some(arg), os_value)
+                    }
+                    None => Ok(value.to_string()),
+                },
+            };
+            let checked_value = result.map_err(|mut err| {
+                // Report where the value comes from, as it does not appear
+                // on the command line
+                let env = self.env.as_deref().unwrap_or_default();
+                err.insert(
+                    clap::error::ContextKind::Suggested,
+                    clap::error::ContextValue::StyledStrs(vec![format!(
+                        "the value was read from the environment variable '{env}'"
+                    )
+                    .into()]),
+                );
+                ParseArgsErrorKind::ArgumentParsingError(err)
+            })?;
+            checked.push(checked_value);
+        }
+
+        Ok(Some(checked.join(",")))
+    }
+
+    /// Returns the possible value of the enum matching the given value
+    /// regardless of the case, so that the value is exported the way it
+    /// is listed in the definition; an exact match is preferred, and the
+    /// value is returned as is if none of the possible values matches it
+    fn canonical_enum_value(possible_values: &[String], value: String) -> String {
+        if possible_values.contains(&value) {
+            return value;
+        }
+
+        possible_values
+            .iter()
+            .find(|possible_value| possible_value.eq_ignore_ascii_case(&value))
+            .cloned()
+            .unwrap_or(value)
+    }
+
+    /// Returns the default value as it can be shown to the user; the
+    /// default value of a sensitive parameter is never shown
+    fn displayed_default_value(&self) -> Option<String> {
+        let default = self.default_value()?;
+        if default.is_empty() {
+            return Some(default);
+        }
+        Some(mask_secret(&default, self.is_sensitive()))
+    }
+
+    /// Returns the value of the given environment variable as it can be
+    /// shown in the description of the argument; only the variables of
+    /// omni and the one the argument reads its value from are resolved,
+    /// so that a description cannot expose the rest of the environment
+    fn displayed_env_value(&self, name: &str) -> Option<String> {
+        if self.env.as_deref() == Some(name) {
+            let value = self.env_value()?;
+            return Some(mask_secret(&value, self.is_sensitive()));
+        }
+
+        if name.starts_with("OMNI_") {
+            return std::env::var(name).ok();
+        }
+
+        None
+    }
+
+    /// Returns the description of the argument with its placeholders
+    /// interpolated; see [`interpolate_desc`] for the supported placeholders.
+    pub fn interpolated_desc(&self) -> Option<String> {
+        self.desc.as_ref().map(|desc| {
+            interpolate_desc(
+                desc,
+                || self.displayed_default_value(),
+                |name| self.displayed_env_value(name),
+            )
+        })
+    }
+
     /// Returns the description of that argument for the help message
     pub fn help_desc(&self) -> String {
         let mut help_desc = String::new();
 
         // Add the description if any
-        if let Some(desc) = &self.desc {
+        if let Some(desc) = &self.interpolated_desc() {
             help_desc.push_str(desc);
         }
 
+        // Add the environment variable if any
+        if let Some(env) = &self.env {
+            if !help_desc.is_empty() {
+                help_desc.push(' ');
+            }
+            help_desc.push_str(&format!("[{}: {}]", "env".italic(), env).light_black());
+        }
+
         // Add the default value if any
         if !matches!(self.arg_type, SyntaxOptArgType::Flag) {
-            if let Some(default) = &self.default {
+            if let Some(default) = &self.displayed_default_value() {
                 if !default.is_empty() {
                     if !help_desc.is_empty() {
                         help_desc.push(' ');
@@ -1440,8 +4998,34 @@ impl SyntaxOptArg {
             );
         }
 
-        // Add the aliases if any
+        // Add the range of the values if any
+        if let Some(range) = self.range_str() {
+            if !help_desc.is_empty() {
+                help_desc.push(' ');
+            }
+            help_desc.push_str(&format!("[{}: {}]", "range".italic(), range).light_black());
+        }
+
+        // Add the examples if any
+        if !self.examples.is_empty() {
+            if !help_desc.is_empty() {
+                help_desc.push(' ');
+            }
+            help_desc.push_str(
+                &format!("[{}: {}]", "examples".italic(), self.examples.join(", ")).light_black(),
+            );
+        }
+
+        // Add the aliases if any, leaving out the deprecated ones
         let (_, _, _, long_aliases, short_aliases) = self.organized_names();
+        let long_aliases = long_aliases
+            .into_iter()
+            .filter(|name| !self.deprecated_names.contains_key(name))
+            .collect::<Vec<_>>();
+        let short_aliases = short_aliases
+            .into_iter()
+            .filter(|name| !self.deprecated_names.contains_key(name))
+            .collect::<Vec<_>>();
 
         if !long_aliases.is_empty() {
             if !help_desc.is_empty() {
@@ -1475,7 +5059,7 @@ impl SyntaxOptArg {
         let mut arg = clap::Arg::new(self.dest());
 
         // Add the help for the argument
-        if let Some(desc) = &self.desc {
+        if let Some(desc) = &self.interpolated_desc() {
             arg = arg.help(desc);
         }
 
@@ -1513,7 +5097,11 @@ impl SyntaxOptArg {
                 }
 
                 let long = long_name.trim_start_matches("-").to_string();
-                arg = arg.visible_alias(long);
+                arg = if self.deprecated_names.contains_key(long_name) {
+                    arg.alias(long)
+                } else {
+                    arg.visible_alias(long)
+                };
             }
 
             for short_name in &short_names {
@@ -1526,7 +5114,11 @@ impl SyntaxOptArg {
                     .chars()
                     .next()
                     .expect("short name should have at least one character");
-                arg = arg.visible_short_alias(short);
+                arg = if self.deprecated_names.contains_key(short_name) {
+                    arg.short_alias(short)
+                } else {
+                    arg.visible_short_alias(short)
+                };
             }
         }
 
@@ -1549,8 +5141,11 @@ impl SyntaxOptArg {
         }
 
         // Set the default value
-        if let Some(default) = &self.default {
+        if let Some(default) = &self.default_value() {
             arg = arg.default_value(default);
+            if self.is_sensitive() {
+                arg = arg.hide_default_value(true);
+            }
         }
 
         // Set the default missing value
@@ -1577,12 +5172,21 @@ impl SyntaxOptArg {
         if self.allow_negative_numbers {
             arg = arg.allow_negative_numbers(true);
         }
+        if self.require_equals && !self.is_positional() {
+            arg = arg.require_equals(true);
+        }
+        if self.hidden {
+            arg = arg.hide(true);
+        }
 
         // Set conflicts and requirements
         for require_arg in &self.requires {
             let require_arg = sanitize_str(require_arg);
             arg = arg.requires(&require_arg);
         }
+        if !self.requires_one_of.is_empty() {
+            arg = arg.requires(self.requires_one_of_group());
+        }
         for conflict_arg in &self.conflicts_with {
             let conflict_arg = sanitize_str(conflict_arg);
             arg = arg.conflicts_with(&conflict_arg);
@@ -1619,7 +5223,10 @@ impl SyntaxOptArg {
                     .collect::<Vec<(String, String)>>(),
             );
         }
-        if self.required {
+        // Secrets missing from the command line are prompted for once
+        // the arguments are parsed, and arguments with an environment
+        // variable set take its value, so they are not required here
+        if self.required && !self.secret && self.env_value().is_none() {
             arg = arg.required(true);
         }
 
@@ -1632,6 +5239,9 @@ impl SyntaxOptArg {
             | SyntaxOptArgType::Integer
             | SyntaxOptArgType::Float
             | SyntaxOptArgType::Boolean
+            | SyntaxOptArgType::IpAddr
+            | SyntaxOptArgType::SocketAddr
+            | SyntaxOptArgType::Bytes
             | SyntaxOptArgType::Enum(_) => {
                 arg = arg.action(clap::ArgAction::Set);
             }
@@ -1639,7 +5249,7 @@ impl SyntaxOptArg {
                 arg = arg.action(clap::ArgAction::Append);
             }
             SyntaxOptArgType::Flag => {
-                if str_to_bool(&self.default.clone().unwrap_or_default()).unwrap_or(false) {
+                if str_to_bool(&self.default_value().unwrap_or_default()).unwrap_or(false) {
                     arg = arg.action(clap::ArgAction::SetFalse);
                 } else {
                     arg = arg.action(clap::ArgAction::SetTrue);
@@ -1652,19 +5262,61 @@ impl SyntaxOptArg {
 
         // Set the validators, i.e. how the values are checked when the parameter is used
         match &self.arg_type().terminal_type() {
-            SyntaxOptArgType::Integer => {
-                arg = arg.value_parser(clap::value_parser!(i64));
-            }
-            SyntaxOptArgType::Float => {
-                arg = arg.value_parser(clap::value_parser!(f64));
-            }
+            SyntaxOptArgType::Integer => match self.range_checker() {
+                Some(in_range) => {
+                    arg = arg.value_parser(move |value: &str| {
+                        let parsed = value.parse::<i64>().map_err(|err| err.to_string())?;
+                        in_range(parsed as f64).map(|_| parsed)
+                    });
+                }
+                None => {
+                    arg = arg.value_parser(clap::value_parser!(i64));
+                }
+            },
+            SyntaxOptArgType::Float => match self.range_checker() {
+                Some(in_range) => {
+                    arg = arg.value_parser(move |value: &str| {
+                        let parsed = value.parse::<f64>().map_err(|err| err.to_string())?;
+                        in_range(parsed).map(|_| parsed)
+                    });
+                }
+                None => {
+                    arg = arg.value_parser(clap::value_parser!(f64));
+                }
+            },
             SyntaxOptArgType::Boolean => {
                 arg = arg.value_parser(clap::value_parser!(bool));
             }
+            SyntaxOptArgType::IpAddr => {
+                arg = arg.value_parser(parse_keeping_str::<std::net::IpAddr>);
+            }
+            SyntaxOptArgType::SocketAddr => {
+                arg = arg.value_parser(parse_keeping_str::<std::net::SocketAddr>);
+            }
+            SyntaxOptArgType::Bytes => {
+                arg = arg.value_parser(parse_bytes);
+            }
+            SyntaxOptArgType::Enum(possible_values) if self.ignore_case => {
+                // The argument parser keeps the value as it was provided,
+                // so it is replaced by the matching possible value
+                let canonical_values = possible_values.clone();
+                arg = arg.ignore_case(true).value_parser(
+                    clap::builder::PossibleValuesParser::new(possible_values.clone())
+                        .map(move |value| Self::canonical_enum_value(&canonical_values, value)),
+                );
+            }
             SyntaxOptArgType::Enum(possible_values) => {
                 arg = arg.value_parser(possible_values.clone());
             }
-            _ => {}
+            _ => {
+                // Each value is checked on its own, so when the values are
+                // split on a delimiter, each of them needs to match
+                if let Some(matches_pattern) = self.pattern_checker() {
+                    arg = arg.value_parser(move |value: &str| {
+                        matches_pattern(value).map(|_| value.to_string())
+                    });
+                }
+            }
         }
 
         parser.arg(arg)
@@ -1674,6 +5326,7 @@ impl SyntaxOptArg {
         &self,
         args: &mut BTreeMap<String, ParseArgsValue>,
         matches: &clap::ArgMatches,
+        parser: &clap::Command,
         override_dest: Option<String>,
     ) -> Result<(), ParseArgsErrorKind> {
         let dest = self.dest();
@@ -1688,17 +5341,58 @@ impl SyntaxOptArg {
         let arg_type = self.arg_type();
         let has_multi = arg_type.is_array();
 
+        // The environment variable takes precedence over the default value,
+        // but only when the argument was not provided on the command line,
+        // in which case the matches are ignored since they only hold the
+        // default value the parser applied
+        let from_command_line =
+            matches.value_source(&dest) == Some(clap::parser::ValueSource::CommandLine);
+        let env_value = if from_command_line {
+            None
+        } else {
+            self.checked_env_value(parser, has_occurrences || has_multi)?
+        };
+        let (matches, default) = match env_value {
+            Some(value) => (None, Some(value)),
+            None => (Some(matches), self.default_value()),
+        };
+
         let terminal_type = &arg_type.terminal_type();
         match terminal_type {
+            SyntaxOptArgType::RepoPath if self.export_repo_org => {
+                // The repository paths are resolved here instead of through
+                // a transform function, so that the organization through
+                // which each of them was found can be kept alongside
+                let arg_dest = override_dest.unwrap_or(dest.clone());
+                extract_value_to_typed::<String>(
+                    matches,
+                    &dest,
+                    &default,
+                    args,
+                    Some(arg_dest.clone()),
+                    has_occurrences,
+                    has_multi,
+                    self.group_occurrences,
+                    None,
+                )?;
+
+                if let Some(value) = args.remove(&arg_dest) {
+                    let (paths, orgs) = transform_repo_path_with_org(value)?;
+                    args.insert(arg_dest.clone(), paths);
+                    args.insert(format!("{arg_dest}_org"), orgs);
+                }
+            }
             SyntaxOptArgType::String
             | SyntaxOptArgType::DirPath
             | SyntaxOptArgType::FilePath
             | SyntaxOptArgType::RepoPath
+            | SyntaxOptArgType::IpAddr
+            | SyntaxOptArgType::SocketAddr
             | SyntaxOptArgType::Enum(_) => {
                 extract_value_to_typed::<String>(
                     matches,
                     &dest,
-                    &self.default,
+                    &default,
                     args,
                     override_dest,
                     has_occurrences,
@@ -1717,7 +5411,24 @@ impl SyntaxOptArg {
                 extract_value_to_typed::<i64>(
                     matches,
                     &dest,
-                    &self.default,
+                    &default,
+                    args,
+                    override_dest,
+                    has_occurrences,
+                    has_multi,
+                    self.group_occurrences,
+                    None,
+                )?;
+            }
+            SyntaxOptArgType::Bytes => {
+                // The sizes of the default value, or of the environment
+                // variable, are resolved like the values of the command
+                // line, so they can be extracted as numbers of bytes
+                let default = default.as_deref().map(resolve_bytes);
+                extract_value_to_typed::<i64>(
+                    matches,
+                    &dest,
+                    &default,
                     args,
                     override_dest,
                     has_occurrences,
@@ -1730,7 +5441,7 @@ impl SyntaxOptArg {
                 extract_value_to_typed::<u8>(
                     matches,
                     &dest,
-                    &self.default,
+                    &default,
                     args,
                     override_dest,
                     has_occurrences,
@@ -1743,7 +5454,7 @@ impl SyntaxOptArg {
                 extract_value_to_typed::<f64>(
                     matches,
                     &dest,
-                    &self.default,
+                    &default,
                     args,
                     override_dest,
                     has_occurrences,
@@ -1754,7 +5465,7 @@ impl SyntaxOptArg {
             }
             SyntaxOptArgType::Boolean | SyntaxOptArgType::Flag => {
                 let default = Some(
-                    str_to_bool(&self.default.clone().unwrap_or_default())
+                    str_to_bool(&default.unwrap_or_default())
                         .unwrap_or(false)
                         .to_string(),
                 );
@@ -1770,15 +5481,267 @@ impl SyntaxOptArg {
                     None,
                 )?;
             }
-            SyntaxOptArgType::Array(_) => unreachable!("array type should be handled differently"),
+            SyntaxOptArgType::Array(_) => unreachable!("array type should be handled differently"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Interpolates the placeholders of a parameter description, so that the
+/// description stays accurate when the values it mentions change:
+/// - `{default}` is replaced by the resolved default value of the parameter
+/// - `${NAME}` is replaced by the value of the `NAME` environment variable,
+///   as resolved by `env`, which only resolves the variables that can be
+///   shown to the user
+///
+/// Placeholders that cannot be resolved, as well as any other text between
+/// braces, are left as written. Literal braces can be written as `{{` and
+/// `}}`, e.g. `${{HOME}}` renders as `${HOME}`. No other expansion is done.
+pub(crate) fn interpolate_desc(
+    desc: &str,
+    default: impl Fn() -> Option<String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> String {
+    let mut result = String::with_capacity(desc.len());
+    let mut rest = desc;
+
+    while let Some(pos) = rest.find(['{', '}', '$']) {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if let Some(after) = rest.strip_prefix("{{").or_else(|| rest.strip_prefix("}}")) {
+            result.push_str(&rest[..1]);
+            rest = after;
+            continue;
+        }
+
+        let placeholder = if let Some(after) = rest.strip_prefix("{default}") {
+            default().map(|value| (value, after))
+        } else if let Some(after) = rest.strip_prefix("${") {
+            after.split_once('}').and_then(|(name, after)| {
+                let is_name = name.chars().enumerate().all(|(i, c)| {
+                    c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+                });
+                if name.is_empty() || !is_name {
+                    return None;
+                }
+                env(name).map(|value| (value, after))
+            })
+        } else {
+            None
+        };
+
+        match placeholder {
+            Some((value, after)) => {
+                result.push_str(&value);
+                rest = after;
+            }
+            None => {
+                result.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// The context of the completion of a command line, as resolved from
+/// the words preceding the word being completed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxCompletionContext {
+    /// What can be completed for the word being completed
+    pub state: SyntaxCompletionState,
+    /// The parameters seen before the word being completed, along with
+    /// their values
+    pub seen: Vec<(String, Vec<String>)>,
+    /// The parameters that can still be provided
+    pub remaining: Vec<SyntaxOptArg>,
+    /// The parameter taking the values after `--`, if any
+    pub last: Option<SyntaxOptArg>,
+}
+
+impl SyntaxCompletionContext {
+    /// Returns the names of the options that can still be provided and
+    /// complete the given word, along with their description, sorted
+    /// by name; hidden options are not completed
+    pub fn option_candidates(&self, word: &str) -> Vec<(String, Option<String>)> {
+        self.remaining
+            .iter()
+            .filter(|param| !param.is_positional() && !param.hidden)
+            .flat_map(|param| {
+                let desc = param.interpolated_desc();
+                param
+                    .all_names()
+                    .into_iter()
+                    .map(move |name| (name, desc.clone()))
+            })
+            .filter(|(name, _)| name.starts_with(word))
+            .sorted()
+            .collect()
+    }
+}
+
+#[inline]
+fn check_parameter_conflicts(
+    param1: &SyntaxOptArg,
+    param2: &SyntaxOptArg,
+    groups: &[SyntaxGroup],
+) -> bool {
+    // Get the groups for param1
+    let param1_groups = groups
+        .iter()
+        .filter(|group| {
+            group.parameters.iter().any(|name| {
+                param1
+                    .all_names()
+                    .iter()
+                    .any(|n| n.as_str() == name.as_str())
+            })
+        })
+        .collect::<Vec<_>>();
+    let param1_all = param1
+        .all_names()
+        .into_iter()
+        .chain(param1_groups.iter().map(|group| group.name.to_string()))
+        .collect::<Vec<_>>();
+
+    // Get the groups for param2
+    let param2_groups = groups
+        .iter()
+        .filter(|group| {
+            group.parameters.iter().any(|name| {
+                param2
+                    .all_names()
+                    .iter()
+                    .any(|n| n.as_str() == name.as_str())
+            })
+        })
+        .collect::<Vec<_>>();
+    let param2_all = param2
+        .all_names()
+        .into_iter()
+        .chain(param2_groups.iter().map(|group| group.name.to_string()))
+        .collect::<Vec<_>>();
+
+    // If param1 defines conflicts with param2 or any of its groups, return true
+    if param1
+        .conflicts_with
+        .iter()
+        .any(|name| param2_all.iter().any(|n| n == name))
+    {
+        return true;
+    }
+
+    // If param2 defines conflicts with param1 or any of its groups, return true
+    if param2
+        .conflicts_with
+        .iter()
+        .any(|name| param1_all.iter().any(|n| n == name))
+    {
+        return true;
+    }
+
+    // If param1 and param2 are in the same group, and that group does
+    // not allow multiple, return true
+    let common_groups = param1_groups
+        .iter()
+        .filter(|group| param2_groups.contains(group))
+        .collect::<Vec<_>>();
+    if common_groups.iter().any(|group| !group.multiple) {
+        return true;
+    }
+
+    // Check if any of the param1 groups conflicts with param2
+    if param1_groups.iter().any(|group| {
+        group
+            .conflicts_with
+            .iter()
+            .any(|name| param2_all.iter().any(|n| n == name))
+    }) {
+        return true;
+    }
+
+    // Check if any of the param2 groups conflicts with param1
+    if param2_groups.iter().any(|group| {
+        group
+            .conflicts_with
+            .iter()
+            .any(|name| param1_all.iter().any(|n| n == name))
+    }) {
+        return true;
+    }
+
+    // If we get here, there is no conflict
+    false
+}
+
+/// What can be completed for the word being completed
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxCompletionState {
+    Parameters,
+    ValueAndParameters {
+        param: SyntaxOptArg,
+        param_idx: usize,
+    },
+    Value {
+        param: SyntaxOptArg,
+        param_idx: usize,
+    },
+}
+
+impl SyntaxCompletionState {
+    /// Whether the names of the options can be completed
+    pub fn complete_parameters(&self) -> bool {
+        matches!(self, Self::Parameters | Self::ValueAndParameters { .. })
+    }
+
+    /// Returns the parameter whose value can be completed, along with
+    /// the index of the word where its values start
+    pub fn parameter(&self) -> Option<(SyntaxOptArg, usize)> {
+        match self {
+            Self::Value { param, param_idx } | Self::ValueAndParameters { param, param_idx } => {
+                Some((param.clone(), *param_idx))
+            }
+            _ => None,
         }
+    }
+}
 
-        Ok(())
+/// Returns the argument as it is rendered by the argument parser when
+/// listing the members of a group
+fn rendered_group_member(arg: &clap::Arg) -> String {
+    if arg.is_positional() {
+        match arg.get_value_names() {
+            Some([name]) => name.to_string(),
+            Some(names) if !names.is_empty() => names
+                .iter()
+                .map(|name| format!("<{name}>"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => arg.get_id().to_string(),
+        }
+    } else {
+        arg.to_string()
     }
 }
 
 /// If the provided value is a path, we want to return the
 /// absolute path no matter what was passed (relative, absolute, ~, etc.)
+/// Returns the raw values provided for the given argument
+fn raw_values(matches: &clap::ArgMatches, id: &str) -> Vec<String> {
+    matches
+        .get_raw(id)
+        .map(|values| {
+            values
+                .map(|value| value.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn transform_path(value: Option<String>) -> Result<Option<String>, ParseArgsErrorKind> {
     let value = match value {
         Some(value) => value,
@@ -1797,13 +5760,85 @@ fn transform_repo_path(value: Option<String>) -> Result<Option<String>, ParseArg
         None => return Ok(None),
     };
 
-    if let Ok(path) = std::fs::canonicalize(&value) {
-        return Ok(Some(path.to_string_lossy().to_string()));
+    let (path, _org) = resolve_repo_path(&value)?;
+    Ok(Some(path))
+}
+
+/// Resolves the repository paths of a value, returning the resolved
+/// paths and the handles of the organizations through which they were
+/// found; the handle is empty for a repository path that was found on
+/// the filesystem, or that no configured organization hosts
+fn transform_repo_path_with_org(
+    value: ParseArgsValue,
+) -> Result<(ParseArgsValue, ParseArgsValue), ParseArgsErrorKind> {
+    let resolve = |value: Option<String>| -> Result<_, ParseArgsErrorKind> {
+        match value {
+            Some(value) => {
+                let (path, org) = resolve_repo_path(&value)?;
+                Ok((Some(path), Some(org.unwrap_or_default())))
+            }
+            None => Ok((None, None)),
+        }
+    };
+
+    match value {
+        ParseArgsValue::SingleString(value) => {
+            let (path, org) = resolve(value)?;
+            Ok((
+                ParseArgsValue::SingleString(path),
+                ParseArgsValue::SingleString(org),
+            ))
+        }
+        ParseArgsValue::ManyString(values) => {
+            let (paths, orgs) = values
+                .into_iter()
+                .map(resolve)
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .unzip();
+            Ok((
+                ParseArgsValue::ManyString(paths),
+                ParseArgsValue::ManyString(orgs),
+            ))
+        }
+        ParseArgsValue::GroupedString(groups) => {
+            let (paths, orgs) = groups
+                .into_iter()
+                .map(|values| {
+                    values
+                        .into_iter()
+                        .map(resolve)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(|resolved| resolved.into_iter().unzip())
+                })
+                .collect::<Result<Vec<(Vec<_>, Vec<_>)>, _>>()?
+                .into_iter()
+                .unzip();
+            Ok((
+                ParseArgsValue::GroupedString(paths),
+                ParseArgsValue::GroupedString(orgs),
+            ))
+        }
+        _ => unreachable!("repository paths should be extracted as strings"),
+    }
+}
+
+/// Resolves a repository path, either as a path on the filesystem or
+/// through the organizations, in which case the handle of the first
+/// configured organization hosting the repository is also returned
+fn resolve_repo_path(value: &str) -> Result<(String, Option<String>), ParseArgsErrorKind> {
+    if let Ok(path) = std::fs::canonicalize(value) {
+        return Ok((path.to_string_lossy().to_string(), None));
     }
 
     let only_worktree = false;
-    if let Some(path) = ORG_LOADER.find_repo(&value, only_worktree, false, true) {
-        return Ok(Some(path.to_string_lossy().to_string()));
+    if let Some(path) = ORG_LOADER.find_repo(value, only_worktree, false, true) {
+        let path = path.to_string_lossy().to_string();
+        let org = hosting_orgs(&path)
+            .into_iter()
+            .find(|org| !org.is_default())
+            .map(|org| org.config.handle.clone());
+        return Ok((path, org));
     }
 
     Err(ParseArgsErrorKind::InvalidValue(format!(
@@ -1815,7 +5850,11 @@ trait ParserExtractType<T> {
     type BaseType;
     type Output;
 
-    fn extract(matches: &clap::ArgMatches, dest: &str, default: &Option<String>) -> Self::Output;
+    fn extract(
+        matches: Option<&clap::ArgMatches>,
+        dest: &str,
+        default: &Option<String>,
+    ) -> Self::Output;
 }
 
 impl<T: Into<ParseArgsValue> + Clone + FromStr + Send + Sync + 'static> ParserExtractType<T>
@@ -1824,8 +5863,15 @@ impl<T: Into<ParseArgsValue> + Clone + FromStr + Send + Sync + 'static> ParserEx
     type BaseType = T;
     type Output = Option<T>;
 
-    fn extract(matches: &clap::ArgMatches, dest: &str, default: &Option<String>) -> Self::Output {
-        match (matches.get_one::<T>(dest), default) {
+    fn extract(
+        matches: Option<&clap::ArgMatches>,
+        dest: &str,
+        default: &Option<String>,
+    ) -> Self::Output {
+        match (
+            matches.and_then(|matches| matches.get_one::<T>(dest)),
+            default,
+        ) {
             (Some(value), _) => Some(value.clone()),
             (None, Some(default)) => default.parse::<T>().ok(),
             _ => None,
@@ -1839,8 +5885,15 @@ impl<T: Into<ParseArgsValue> + Clone + FromStr + Send + Sync + 'static> ParserEx
     type BaseType = T;
     type Output = Vec<Option<T>>;
 
-    fn extract(matches: &clap::ArgMatches, dest: &str, default: &Option<String>) -> Self::Output {
-        match (matches.get_many::<T>(dest), default) {
+    fn extract(
+        matches: Option<&clap::ArgMatches>,
+        dest: &str,
+        default: &Option<String>,
+    ) -> Self::Output {
+        match (
+            matches.and_then(|matches| matches.get_many::<T>(dest)),
+            default,
+        ) {
             (Some(values), _) => values
                 .collect::<Vec<_>>()
                 .into_iter()
@@ -1862,8 +5915,15 @@ impl<T: Into<ParseArgsValue> + Clone + FromStr + Send + Sync + 'static> ParserEx
     type BaseType = T;
     type Output = Vec<Vec<Option<T>>>;
 
-    fn extract(matches: &clap::ArgMatches, dest: &str, default: &Option<String>) -> Self::Output {
-        match (matches.get_occurrences(dest), default) {
+    fn extract(
+        matches: Option<&clap::ArgMatches>,
+        dest: &str,
+        default: &Option<String>,
+    ) -> Self::Output {
+        match (
+            matches.and_then(|matches| matches.get_occurrences(dest)),
+            default,
+        ) {
             (Some(occurrences), _) => occurrences
                 .into_iter()
                 .map(|values| {
@@ -1882,17 +5942,109 @@ impl<T: Into<ParseArgsValue> + Clone + FromStr + Send + Sync + 'static> ParserEx
     }
 }
 
+/// Checks that a value can be parsed as `T`, the same way and with the same
+/// error as `clap::value_parser!(T)`, but keeps the value as it was provided
+/// so that it can be extracted as a string
+fn parse_keeping_str<T>(value: &str) -> Result<String, T::Err>
+where
+    T: FromStr,
+{
+    value.parse::<T>().map(|_| value.to_string())
+}
+
+/// The units that can follow a number of bytes, with their multiplier;
+/// the decimal units are powers of 1000, and the binary units powers of 1024
+const BYTES_UNITS: [(&str, i64); 9] = [
+    ("B", 1),
+    ("KB", 1000),
+    ("MB", 1000 * 1000),
+    ("GB", 1000 * 1000 * 1000),
+    ("TB", 1000 * 1000 * 1000 * 1000),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+];
+
+/// Parses a size, e.g. `512`, `10KB` or `4MiB`, into its number of bytes;
+/// the unit is matched without regard to case and can be separated from
+/// the number by whitespace
+fn parse_bytes(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    let (number, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let unit = unit.trim_start();
+
+    if number.is_empty() {
+        return Err(
+            "expected a number of bytes, optionally followed by a unit, e.g. 512, 10KB or 4MiB"
+                .to_string(),
+        );
+    }
+
+    if unit.starts_with('.') {
+        return Err(
+            "fractional sizes are not supported, use a smaller unit instead, e.g. 1536MiB"
+                .to_string(),
+        );
+    }
+
+    let multiplier = if unit.is_empty() {
+        1
+    } else {
+        match BYTES_UNITS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+        {
+            Some((_, multiplier)) => *multiplier,
+            None => {
+                return Err(format!(
+                    "unknown unit '{unit}', expected one of {}",
+                    BYTES_UNITS
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        }
+    };
+
+    number
+        .parse::<i64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("size is too large, the maximum is {} bytes", i64::MAX))
+}
+
+/// Resolves each of the comma-separated sizes of a value to its number
+/// of bytes; the sizes that cannot be parsed are kept as they are
+fn resolve_bytes(value: &str) -> String {
+    value
+        .split(',')
+        .map(|part| match parse_bytes(part) {
+            Ok(bytes) => bytes.to_string(),
+            Err(_) => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// A function that can transform a value into another value of the same type
 type TransformFn<T> = fn(Option<T>) -> Result<Option<T>, ParseArgsErrorKind>;
 
 /// Extracts a value from the matches and inserts it into the args map
-/// The value is extracted based on the type of the argument and the number of values
+/// The value is extracted based on the type of the argument and the number of values,
+/// from the matches if provided and holding a value, or from the default otherwise
 /// The value is then transformed if a transform function is provided
 /// The value is then inserted into the args map with the correct destination
 #[allow(clippy::too_many_arguments)]
 #[inline]
 fn extract_value_to_typed<T>(
-    matches: &clap::ArgMatches,
+    matches: Option<&clap::ArgMatches>,
     dest: &str,
     default: &Option<String>,
     args: &mut BTreeMap<String, ParseArgsValue>,
@@ -1952,27 +6104,95 @@ where
     Ok(())
 }
 
+/// Returns whether the given command line argument uses the given option
+/// name, either on its own or with an attached value; a short name is
+/// considered used when it starts a group of short options
+fn arg_uses_name(arg: &str, name: &str) -> bool {
+    if name.starts_with("--") {
+        arg == name
+            || arg
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with('='))
+    } else {
+        !arg.starts_with("--") && arg.starts_with(name)
+    }
+}
+
 pub fn parse_arg_name(arg_name: &str) -> (Vec<String>, SyntaxOptArgType, Vec<String>, bool) {
+    parse_arg_name_checked(arg_name, &ConfigErrorHandler::noop())
+}
+
+/// Parses the name of an argument, following the grammar:
+///
+/// ```text
+/// arg_name     := part ( "," part )*
+/// part         := name [ separator placeholders ]
+/// name         := ( "-" NAME | "--" NAME | NAME ) [ "..." ]
+/// separator    := ( whitespace | "=" )+
+/// placeholders := PLACEHOLDER ( whitespace PLACEHOLDER )*
+/// ```
+///
+/// Names starting with `-` are options, and the placeholders of all their
+/// parts are accumulated; a name ending with `...` is a counter. A name not
+/// starting with `-` is a positional, which replaces any name parsed before
+/// it and ends the parsing; a positional ending with `...` takes leftovers.
+///
+/// Inputs deviating from the grammar are still parsed as closely as
+/// possible, and the oddities are reported as warnings to the error handler.
+pub fn parse_arg_name_checked(
+    arg_name: &str,
+    error_handler: &ConfigErrorHandler,
+) -> (Vec<String>, SyntaxOptArgType, Vec<String>, bool) {
     let mut names = Vec::new();
     let mut arg_type = SyntaxOptArgType::String;
     let mut placeholders = vec![];
     let mut leftovers = false;
 
-    // Parse the argument name; it can be a single name or multiple names separated by commas.
-    // There can be short names (starting with `-`) and long names (starting with `--`).
-    // Each name can have one or more placeholders, or the placeholders can be put at the end.
-    // The placeholders are separated by a space from the name, and by a space from each other.
-    // If the argument name does not start with `-`, only this value will be kept as part of
-    // the names and the others will be ignored.
+    let warn = |issue: String| {
+        error_handler
+            .with_context("arg_name", arg_name)
+            .with_context("issue", issue)
+            .error(ConfigErrorKind::MalformedArgName);
+    };
+
     let def_parts: Vec<&str> = arg_name.split(',').map(str::trim).collect();
+    if arg_name.trim().is_empty() {
+        warn("the name is empty".to_string());
+    }
 
-    for part in def_parts {
-        let name_parts = part.splitn(2, [' ', '\t', '=']).collect::<Vec<&str>>();
-        if name_parts.is_empty() {
+    for (idx, part) in def_parts.iter().enumerate() {
+        if part.is_empty() {
+            if !arg_name.trim().is_empty() {
+                warn("empty name between commas".to_string());
+            }
             continue;
         }
 
-        let name = name_parts[0];
+        // Split the name from its placeholders over the first run of
+        // whitespaces and `=` signs, which are all valid separators
+        let (name, rest) = match part.find(|c: char| c.is_whitespace() || c == '=') {
+            Some(pos) => (&part[..pos], Some(&part[pos..])),
+            None => (*part, None),
+        };
+        let rest = rest.map(|rest| {
+            let separator_len = rest
+                .find(|c: char| !c.is_whitespace() && c != '=')
+                .unwrap_or(rest.len());
+            let separator = &rest[..separator_len];
+            if separator.matches('=').count() > 1 {
+                warn(format!("repeated '=' after '{name}'"));
+            }
+            &rest[separator_len..]
+        });
+
+        let part_placeholders = rest
+            .map(|rest| {
+                rest.split_whitespace()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+
         let (name, ends_with_dots) = if name.ends_with("...") {
             (name.trim_end_matches("..."), true)
         } else {
@@ -1980,15 +6200,17 @@ pub fn parse_arg_name(arg_name: &str) -> (Vec<String>, SyntaxOptArgType, Vec<Str
         };
 
         if name.starts_with('-') {
-            if name_parts.len() > 1 {
-                placeholders.extend(
-                    name_parts[1]
-                        .split_whitespace()
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect::<Vec<String>>(),
-                );
+            if name.trim_start_matches('-').is_empty() {
+                warn(format!("option '{name}' has an empty name"));
+                continue;
+            }
+
+            if let Some(placeholder) = part_placeholders.iter().find(|p| p.starts_with('-')) {
+                warn(format!(
+                    "placeholder '{placeholder}' looks like an option name, names should be separated by commas"
+                ));
             }
+            placeholders.extend(part_placeholders);
 
             if ends_with_dots {
                 // If the name ends with `...`, we consider it a counter
@@ -1997,6 +6219,27 @@ pub fn parse_arg_name(arg_name: &str) -> (Vec<String>, SyntaxOptArgType, Vec<Str
 
             names.push(name.to_string());
         } else {
+            if !names.is_empty() {
+                warn(format!(
+                    "positional '{}' discards previously parsed names {}",
+                    name,
+                    names.join(", "),
+                ));
+            }
+
+            let ignored = def_parts[idx + 1..]
+                .iter()
+                .filter(|part| !part.is_empty())
+                .copied()
+                .collect::<Vec<&str>>();
+            if !ignored.is_empty() {
+                warn(format!(
+                    "names after positional '{}' are ignored: {}",
+                    name,
+                    ignored.join(", "),
+                ));
+            }
+
             names.clear();
             names.push(name.to_string());
 
@@ -2005,14 +6248,8 @@ pub fn parse_arg_name(arg_name: &str) -> (Vec<String>, SyntaxOptArgType, Vec<Str
                 leftovers = true;
             }
 
-            if name_parts.len() > 1 {
-                placeholders.push(
-                    name_parts[1]
-                        .split_whitespace()
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect(),
-                );
+            if !part_placeholders.is_empty() {
+                placeholders.push(part_placeholders.concat());
             }
 
             // If we have a parameter without a leading `-`, we stop parsing
@@ -2021,9 +6258,144 @@ pub fn parse_arg_name(arg_name: &str) -> (Vec<String>, SyntaxOptArgType, Vec<Str
         }
     }
 
+    // Keep the raw name if nothing could be parsed, as there is no better
+    // name to identify the argument with
+    if names.is_empty() {
+        names.push(def_parts[0].to_string());
+    }
+
     (names, arg_type, placeholders, leftovers)
 }
 
+/// Where a parameter comes from; the parameters that are not defined by
+/// the command itself are shown apart in the help, as global options.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyntaxOptArgOrigin {
+    /// Defined by the command
+    #[default]
+    Command,
+    /// Reserved by omni for all the commands, e.g. `-h/--help`
+    Reserved,
+    /// A common flag injected in the parameters of the command
+    Common,
+    /// Inherited from a parent command
+    Inherited,
+}
+
+impl SyntaxOptArgOrigin {
+    pub fn is_command(&self) -> bool {
+        matches!(self, Self::Command)
+    }
+
+    pub fn is_global(&self) -> bool {
+        !self.is_command()
+    }
+}
+
+/// A variant of a positional parameter dispatching its value: the value
+/// is exported under the dest of the first variant whose pattern
+/// matches it, or of the first variant without pattern.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SyntaxOptArgVariant {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl SyntaxOptArgVariant {
+    fn from_config_value_multi(
+        config_value: Option<&ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Vec<Self> {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return vec![],
+        };
+
+        let array = match config_value.as_array() {
+            Some(array) => array,
+            None => {
+                error_handler
+                    .with_expected("array")
+                    .with_actual(config_value)
+                    .error(ConfigErrorKind::InvalidValueType);
+                return vec![];
+            }
+        };
+
+        array
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, value)| {
+                Self::from_config_value(value, &error_handler.with_index(idx))
+            })
+            .collect()
+    }
+
+    fn from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Option<Self> {
+        if let Some(name) = config_value.as_str() {
+            return Some(Self {
+                name: name.to_string(),
+                pattern: None,
+            });
+        }
+
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("string or table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+            return None;
+        }
+
+        let name = match config_value.get_as_str_or_none("name", &error_handler.with_key("name")) {
+            Some(name) if !name.is_empty() => name,
+            Some(_) => {
+                error_handler
+                    .with_key("name")
+                    .error(ConfigErrorKind::EmptyKey);
+                return None;
+            }
+            None => {
+                error_handler
+                    .with_key("name")
+                    .error(ConfigErrorKind::MissingKey);
+                return None;
+            }
+        };
+
+        let pattern =
+            config_value.get_as_str_or_none("pattern", &error_handler.with_key("pattern"));
+        if let Some(pattern) = &pattern {
+            if let Err(err) = regex::Regex::new(pattern) {
+                error_handler
+                    .with_key("pattern")
+                    .with_actual(pattern.to_string())
+                    .with_context("error", err.to_string())
+                    .error(ConfigErrorKind::ParsingError);
+                return None;
+            }
+        }
+
+        Some(Self { name, pattern })
+    }
+
+    pub fn dest(&self) -> String {
+        sanitize_str(&self.name)
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => regex::Regex::new(pattern).is_ok_and(|re| re.is_match(value)),
+            None => true,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Copy)]
 pub enum SyntaxOptArgNumValues {
     Any,
@@ -2208,6 +6580,8 @@ impl SyntaxOptArgNumValues {
 
         if let Some(value) = config_value.as_integer() {
             Some(Self::Exactly(value as usize))
+        } else if config_value.is_array() {
+            Self::from_array(config_value, error_handler)
         } else if let Some(value) = config_value.as_str_forced() {
             Self::from_str(&value, error_handler)
         } else {
@@ -2219,6 +6593,47 @@ impl SyntaxOptArgNumValues {
         }
     }
 
+    /// Parses a range written as a list, where `[min, max]` is the
+    /// inclusive range between min and max, and `[min]` has no maximum
+    fn from_array(config_value: &ConfigValue, error_handler: &ConfigErrorHandler) -> Option<Self> {
+        let values = config_value.as_array().unwrap_or_default();
+        if values.is_empty() || values.len() > 2 {
+            error_handler
+                .with_expected("list of one or two non-negative integers")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+            return None;
+        }
+
+        let mut bounds = Vec::with_capacity(values.len());
+        for (idx, value) in values.iter().enumerate() {
+            match value.as_unsigned_integer() {
+                Some(bound) => bounds.push(bound as usize),
+                None => {
+                    error_handler
+                        .with_index(idx)
+                        .with_expected("non-negative integer")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+                    return None;
+                }
+            }
+        }
+
+        match bounds[..] {
+            [min] => Some(Self::AtLeast(min)),
+            [min, max] if min <= max => Some(Self::Between(min, max)),
+            [min, max] => {
+                error_handler
+                    .with_context("min", min)
+                    .with_context("max", max)
+                    .error(ConfigErrorKind::InvalidRange);
+                None
+            }
+            _ => unreachable!("bounds should have one or two values"),
+        }
+    }
+
     fn is_many(&self) -> bool {
         match self {
             Self::Any => true,
@@ -2267,6 +6682,12 @@ pub enum SyntaxOptArgType {
     Float,
     #[serde(rename = "bool")]
     Boolean,
+    #[serde(rename = "ipaddr", alias = "ip")]
+    IpAddr,
+    #[serde(rename = "socketaddr")]
+    SocketAddr,
+    #[serde(rename = "bytes")]
+    Bytes,
     #[serde(rename = "flag")]
     Flag,
     #[serde(rename = "count", alias = "counter")]
@@ -2297,6 +6718,9 @@ impl SyntaxOptArgType {
             Self::Integer => "int",
             Self::Float => "float",
             Self::Boolean => "bool",
+            Self::IpAddr => "ipaddr",
+            Self::SocketAddr => "socketaddr",
+            Self::Bytes => "bytes",
             Self::Flag => "flag",
             Self::Counter => "counter",
             Self::Enum(_) => "enum",
@@ -2308,6 +6732,9 @@ impl SyntaxOptArgType {
                 Self::Integer => "array/int",
                 Self::Float => "array/float",
                 Self::Boolean => "array/bool",
+                Self::IpAddr => "array/ipaddr",
+                Self::SocketAddr => "array/socketaddr",
+                Self::Bytes => "array/bytes",
                 Self::Enum(_) => "array/enum",
                 _ => unimplemented!("unsupported array type: {:?}", self),
             },
@@ -2391,6 +6818,9 @@ impl SyntaxOptArgType {
             "int" | "integer" => Self::Integer,
             "float" => Self::Float,
             "bool" | "boolean" => Self::Boolean,
+            "ip" | "ipaddr" => Self::IpAddr,
+            "socketaddr" => Self::SocketAddr,
+            "bytes" => Self::Bytes,
             "flag" => Self::Flag,
             "count" | "counter" => Self::Counter,
             "str" | "string" => Self::String,
@@ -2427,6 +6857,9 @@ impl SyntaxOptArgType {
                             "count",
                             "str",
                             "path",
+                            "ipaddr",
+                            "socketaddr",
+                            "bytes",
                             "enum",
                             "array/<type>",
                         ])
@@ -2463,6 +6896,22 @@ impl SyntaxOptArgType {
             _ => None,
         }
     }
+
+    /// Whether the given value would be accepted by the argument
+    /// parser for a parameter of this type
+    pub fn accepts_value(&self, value: &str) -> bool {
+        match self.terminal_type() {
+            Self::Integer => value.parse::<i64>().is_ok(),
+            Self::Float => value.parse::<f64>().is_ok(),
+            Self::IpAddr => value.parse::<std::net::IpAddr>().is_ok(),
+            Self::SocketAddr => value.parse::<std::net::SocketAddr>().is_ok(),
+            Self::Bytes => parse_bytes(value).is_ok(),
+            Self::Enum(_) | Self::Boolean => self
+                .possible_values()
+                .is_some_and(|values| values.iter().any(|v| v == value)),
+            _ => true,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -2477,6 +6926,16 @@ pub struct SyntaxGroup {
     pub requires: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub conflicts_with: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts_with_all: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_without: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_without_all: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub required_if_eq: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub required_if_eq_all: HashMap<String, String>,
 }
 
 impl Default for SyntaxGroup {
@@ -2488,6 +6947,11 @@ impl Default for SyntaxGroup {
             required: false,
             requires: vec![],
             conflicts_with: vec![],
+            conflicts_with_all: vec![],
+            required_without: vec![],
+            required_without_all: vec![],
+            required_if_eq: HashMap::new(),
+            required_if_eq_all: HashMap::new(),
         }
     }
 }
@@ -2508,8 +6972,11 @@ impl SyntaxGroup {
     ///   parameters: param3
     ///   requires: group1
     ///   conflicts_with: group3
+    ///   conflicts_with_all: [param5, param6]
     /// - group3:
     ///     parameters: param4
+    ///     required_if_eq:
+    ///       param5: value
     /// ```
     ///
     /// Or as:
@@ -2526,8 +6993,11 @@ impl SyntaxGroup {
     ///     parameters: param3
     ///     requires: group1
     ///     conflicts_with: group3
+    ///     conflicts_with_all: [param5, param6]
     ///   group3:
     ///     parameters: param4
+    ///     required_if_eq:
+    ///       param5: value
     /// ```
     ///
     /// The ConfigValue object received is the contents of the `groups` key in the config file.
@@ -2669,6 +7139,31 @@ impl SyntaxGroup {
         let conflicts_with = config_value
             .get_as_str_array("conflicts_with", &error_handler.with_key("conflicts_with"));
 
+        let conflicts_with_all = config_value.get_as_str_array(
+            "conflicts_with_all",
+            &error_handler.with_key("conflicts_with_all"),
+        );
+
+        let required_without = config_value.get_as_str_array(
+            "required_without",
+            &error_handler.with_key("required_without"),
+        );
+
+        let required_without_all = config_value.get_as_str_array(
+            "required_without_all",
+            &error_handler.with_key("required_without_all"),
+        );
+
+        let required_if_eq = Self::conditions_from_config_value(
+            config_value.get("required_if_eq"),
+            &error_handler.with_key("required_if_eq"),
+        );
+
+        let required_if_eq_all = Self::conditions_from_config_value(
+            config_value.get("required_if_eq_all"),
+            &error_handler.with_key("required_if_eq_all"),
+        );
+
         Some(Self {
             name,
             parameters,
@@ -2676,13 +7171,93 @@ impl SyntaxGroup {
             required,
             requires,
             conflicts_with,
+            conflicts_with_all,
+            required_without,
+            required_without_all,
+            required_if_eq,
+            required_if_eq_all,
         })
     }
 
+    /// Parses the conditions of `required_if_eq` and `required_if_eq_all`,
+    /// which map the name of a parameter to the value it needs to have
+    fn conditions_from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> HashMap<String, String> {
+        let mut conditions = HashMap::new();
+
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return conditions,
+        };
+
+        if let Some(table) = config_value.as_table() {
+            for (key, value) in table {
+                if let Some(value) = value.as_str_forced() {
+                    conditions.insert(key.to_string(), value.to_string());
+                } else {
+                    error_handler
+                        .with_key(key)
+                        .with_expected("string")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+                }
+            }
+        } else {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+        }
+
+        conditions
+    }
+
     fn dest(&self) -> String {
         sanitize_str(&self.name)
     }
 
+    /// Returns the representation of the group for the 'usage' string
+    /// in the help message, e.g. `<--param1 | --param2>`
+    fn usage(&self, members: &[&SyntaxOptArg]) -> String {
+        let members = members
+            .iter()
+            .map(|param| {
+                // The members are shown as required, since the requirement
+                // is on the group and not on the parameters themselves
+                let param = SyntaxOptArg {
+                    required: true,
+                    ..(*param).clone()
+                };
+                param.usage()
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        format!("<{members}>")
+    }
+
+    /// Returns the note describing the group in the help message of
+    /// its members, e.g. `[group: source (required, exclusive)]`
+    fn help_note(&self) -> String {
+        format!(
+            "[{}: {} ({}, {})]",
+            "group".italic(),
+            self.name,
+            if self.required {
+                "required"
+            } else {
+                "optional"
+            },
+            if self.multiple {
+                "multiple"
+            } else {
+                "exclusive"
+            },
+        )
+    }
+
     fn add_to_argparser(&self, parser: clap::Command) -> clap::Command {
         let args = self
             .parameters
@@ -2708,10 +7283,41 @@ impl SyntaxGroup {
         parser.group(group)
     }
 
+    /// Returns the identifiers of the hidden groups that make the group
+    /// required unless other parameters are provided, along with those
+    /// parameters. Being required unless all of the parameters of
+    /// `required_without_all` are provided is the same as being required
+    /// unless each of them is provided, which is why each of them gets
+    /// its own hidden group.
+    fn required_alternatives(&self) -> Vec<(String, Vec<String>)> {
+        let mut alternatives = vec![];
+
+        if !self.required_without.is_empty() {
+            alternatives.push((
+                format!("{}::required_without", self.dest()),
+                self.required_without.clone(),
+            ));
+        }
+
+        for name in &self.required_without_all {
+            alternatives.push((
+                format!(
+                    "{}::required_without_all::{}",
+                    self.dest(),
+                    sanitize_str(name)
+                ),
+                vec![name.clone()],
+            ));
+        }
+
+        alternatives
+    }
+
     fn add_to_args(
         &self,
         args: &mut BTreeMap<String, ParseArgsValue>,
         matches: &clap::ArgMatches,
+        parser: &clap::Command,
         parameters: &[SyntaxOptArg],
     ) -> Result<(), ParseArgsErrorKind> {
         let dest = self.dest();
@@ -2726,7 +7332,7 @@ impl SyntaxGroup {
             None => return Ok(()),
         };
 
-        param.add_to_args(args, matches, Some(dest.clone()))
+        param.add_to_args(args, matches, parser, Some(dest.clone()))
     }
 }
 
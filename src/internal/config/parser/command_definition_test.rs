@@ -1,7 +1,10 @@
 use super::*;
 
-fn disable_colors() {
-    std::env::set_var("NO_COLOR", "true");
+use crate::internal::user_interface::colors::ColorCapability;
+use crate::internal::user_interface::colors::ColorCapabilityGuard;
+
+fn disable_colors() -> ColorCapabilityGuard {
+    ColorCapability::new(false).scoped()
 }
 
 mod command_syntax {
@@ -12,7 +15,7 @@ mod command_syntax {
 
         #[test]
         fn test_params_dest() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -33,13 +36,13 @@ mod command_syntax {
             let errmsg = "identifier paramdest is defined more than once";
             assert_eq!(
                 syntax.check_parameters_unique_names(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_params_names() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -58,13 +61,13 @@ mod command_syntax {
             let errmsg = "name --param2 is defined more than once";
             assert_eq!(
                 syntax.check_parameters_unique_names(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_params_and_groups() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -82,7 +85,7 @@ mod command_syntax {
             let errmsg = "identifier param1 is defined more than once";
             assert_eq!(
                 syntax.check_parameters_unique_names(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
     }
@@ -105,7 +108,26 @@ mod command_syntax {
                 "parameter or group param2 specified in requires for param1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_param_requires_one_of() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    requires_one_of: vec!["--param2".to_string()],
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let errmsg =
+                "parameter or group param2 specified in requires_one_of for param1 does not exist";
+            assert_eq!(
+                syntax.check_parameters_references(),
+                vec![errmsg.to_string()]
             );
         }
 
@@ -124,7 +146,26 @@ mod command_syntax {
                 "parameter or group param2 specified in conflicts_with for param1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_param_conflicts_with_all() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    conflicts_with_all: vec!["--param2".to_string()],
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let errmsg =
+                "parameter or group param2 specified in conflicts_with_all for param1 does not exist";
+            assert_eq!(
+                syntax.check_parameters_references(),
+                vec![errmsg.to_string()]
             );
         }
 
@@ -143,7 +184,7 @@ mod command_syntax {
                 "parameter or group param2 specified in required_without for param1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -162,7 +203,7 @@ mod command_syntax {
                 "parameter or group param2 specified in required_without_all for param1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -184,7 +225,7 @@ mod command_syntax {
                 "parameter or group param2 specified in required_if_eq for param1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -206,7 +247,7 @@ mod command_syntax {
                 "parameter or group param2 specified in required_if_eq_all for param1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -225,7 +266,7 @@ mod command_syntax {
                 "parameter or group param1 specified in parameters for group1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -248,7 +289,7 @@ mod command_syntax {
                 ..CommandSyntax::default()
             };
 
-            assert_eq!(syntax.check_parameters_references(), Ok(()));
+            assert_eq!(syntax.check_parameters_references(), Vec::<String>::new());
         }
 
         #[test]
@@ -267,7 +308,7 @@ mod command_syntax {
                 ..CommandSyntax::default()
             };
 
-            assert_eq!(syntax.check_parameters_references(), Ok(()));
+            assert_eq!(syntax.check_parameters_references(), Vec::<String>::new());
         }
 
         #[test]
@@ -286,7 +327,7 @@ mod command_syntax {
                 "parameter or group group2 specified in requires for group1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -306,7 +347,57 @@ mod command_syntax {
                 "parameter or group group2 specified in conflicts_with for group1 does not exist";
             assert_eq!(
                 syntax.check_parameters_references(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_group_conflicts_with_all() {
+            let syntax = CommandSyntax {
+                groups: vec![SyntaxGroup {
+                    name: "group1".to_string(),
+                    parameters: vec![],
+                    conflicts_with_all: vec!["group2".to_string()],
+                    ..SyntaxGroup::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let errmsg =
+                "parameter or group group2 specified in conflicts_with_all for group1 does not exist";
+            assert_eq!(
+                syntax.check_parameters_references(),
+                vec![errmsg.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_group_conditional_requirements() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    ..SyntaxOptArg::default()
+                }],
+                groups: vec![SyntaxGroup {
+                    name: "group1".to_string(),
+                    parameters: vec!["--param1".to_string()],
+                    required_without: vec!["--param2".to_string()],
+                    required_without_all: vec!["param1".to_string(), "param3".to_string()],
+                    required_if_eq: HashMap::from([("param4".to_string(), "a".to_string())]),
+                    required_if_eq_all: HashMap::from([("--param5".to_string(), "b".to_string())]),
+                    ..SyntaxGroup::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            assert_eq!(
+                syntax.check_parameters_references(),
+                vec![
+                    "parameter or group param2 specified in required_without for group1 does not exist".to_string(),
+                    "parameter or group param3 specified in required_without_all for group1 does not exist".to_string(),
+                    "parameter or group param4 specified in required_if_eq for group1 does not exist".to_string(),
+                    "parameter or group param5 specified in required_if_eq_all for group1 does not exist".to_string(),
+                ]
             );
         }
     }
@@ -316,7 +407,7 @@ mod command_syntax {
 
         #[test]
         fn test_use_more_than_once() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -335,12 +426,15 @@ mod command_syntax {
             };
 
             let errmsg = "only one argument can use leftovers; found param1, param2";
-            assert_eq!(syntax.check_parameters_leftovers(), Err(errmsg.to_string()));
+            assert_eq!(
+                syntax.check_parameters_leftovers(),
+                vec![errmsg.to_string()]
+            );
         }
 
         #[test]
         fn test_use_before_last_positional() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -362,12 +456,15 @@ mod command_syntax {
             };
 
             let errmsg = "only the last positional argument can use leftovers";
-            assert_eq!(syntax.check_parameters_leftovers(), Err(errmsg.to_string()));
+            assert_eq!(
+                syntax.check_parameters_leftovers(),
+                vec![errmsg.to_string()]
+            );
         }
 
         #[test]
         fn test_use_with_non_positional() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -385,7 +482,10 @@ mod command_syntax {
             };
 
             let errmsg = "only positional arguments can use leftovers; found --param2";
-            assert_eq!(syntax.check_parameters_leftovers(), Err(errmsg.to_string()));
+            assert_eq!(
+                syntax.check_parameters_leftovers(),
+                vec![errmsg.to_string()]
+            );
         }
     }
 
@@ -394,7 +494,7 @@ mod command_syntax {
 
         #[test]
         fn test_non_positional() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -406,7 +506,7 @@ mod command_syntax {
             };
 
             let errmsg = "only positional arguments can use last; found --param1";
-            assert_eq!(syntax.check_parameters_last(), Err(errmsg.to_string()));
+            assert_eq!(syntax.check_parameters_last(), vec![errmsg.to_string()]);
         }
     }
 
@@ -415,7 +515,7 @@ mod command_syntax {
 
         #[test]
         fn test_positional() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -427,12 +527,12 @@ mod command_syntax {
             };
 
             let errmsg = "param1: counter argument cannot be positional";
-            assert_eq!(syntax.check_parameters_counter(), Err(errmsg.to_string()));
+            assert_eq!(syntax.check_parameters_counter(), vec![errmsg.to_string()]);
         }
 
         #[test]
         fn test_num_values() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -445,7 +545,7 @@ mod command_syntax {
             };
 
             let errmsg = "--param1: counter argument cannot have a num_values (counters do not take any values)";
-            assert_eq!(syntax.check_parameters_counter(), Err(errmsg.to_string()));
+            assert_eq!(syntax.check_parameters_counter(), vec![errmsg.to_string()]);
         }
     }
 
@@ -454,7 +554,7 @@ mod command_syntax {
 
         #[test]
         fn test_num_values() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -470,13 +570,13 @@ mod command_syntax {
             let errmsg = "--param1: cannot use allow_hyphen_values with 'num_values=0'";
             assert_eq!(
                 syntax.check_parameters_allow_hyphen_values(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_counter() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -491,13 +591,13 @@ mod command_syntax {
             let errmsg = "--param1: cannot use allow_hyphen_values on a counter";
             assert_eq!(
                 syntax.check_parameters_allow_hyphen_values(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_flag() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -512,7 +612,7 @@ mod command_syntax {
             let errmsg = "--param1: cannot use allow_hyphen_values on a flag";
             assert_eq!(
                 syntax.check_parameters_allow_hyphen_values(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
     }
@@ -522,7 +622,7 @@ mod command_syntax {
 
         #[test]
         fn test_positional_required_before_non_required() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -544,13 +644,13 @@ mod command_syntax {
                 "param2: required positional argument cannot appear after non-required one param1";
             assert_eq!(
                 syntax.check_parameters_positional(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_positional_num_values() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![
@@ -571,7 +671,7 @@ mod command_syntax {
             let errmsg = "param2: positional need to be required or use 'last=true' if appearing after param1 with num_values > 1";
             assert_eq!(
                 syntax.check_parameters_positional(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
@@ -594,7 +694,7 @@ mod command_syntax {
                 ..CommandSyntax::default()
             };
 
-            assert_eq!(syntax.check_parameters_positional(), Ok(()));
+            assert_eq!(syntax.check_parameters_positional(), Vec::<String>::new());
         }
 
         #[test]
@@ -615,12 +715,12 @@ mod command_syntax {
                 ..CommandSyntax::default()
             };
 
-            assert_eq!(syntax.check_parameters_positional(), Ok(()));
+            assert_eq!(syntax.check_parameters_positional(), Vec::<String>::new());
         }
 
         #[test]
         fn test_positional_required_num_values_exactly_zero() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -635,13 +735,13 @@ mod command_syntax {
             let errmsg = "param1: positional argument cannot have 'num_values=0'";
             assert_eq!(
                 syntax.check_parameters_positional(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_positional_required_num_values_at_most_zero() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -656,13 +756,13 @@ mod command_syntax {
             let errmsg = "param1: positional argument cannot have 'num_values=0'";
             assert_eq!(
                 syntax.check_parameters_positional(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
             );
         }
 
         #[test]
         fn test_positional_required_num_values_between_max_zero() {
-            disable_colors();
+            let _colors = disable_colors();
 
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
@@ -677,7 +777,167 @@ mod command_syntax {
             let errmsg = "param1: positional argument cannot have 'num_values=0'";
             assert_eq!(
                 syntax.check_parameters_positional(),
-                Err(errmsg.to_string())
+                vec![errmsg.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_positional_array_followed_by_non_required() {
+            let _colors = disable_colors();
+
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["sources".to_string()],
+                        arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["dest".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            let errmsg = "dest: positional need to be required or use 'last=true' if appearing after sources which takes multiple values";
+            assert_eq!(
+                syntax.check_parameters_positional(),
+                vec![errmsg.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_positional_array_followed_by_required() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["sources".to_string()],
+                        arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["dest".to_string()],
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            assert_eq!(syntax.check_parameters_positional(), Vec::<String>::new());
+        }
+
+        #[test]
+        fn test_positional_multiple_values_not_in_last_two() {
+            let _colors = disable_colors();
+
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["sources".to_string()],
+                        arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["dest".to_string()],
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["mode".to_string()],
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            let errmsg = "sources: only the last two positional arguments can take multiple values, unless using 'last=true'";
+            assert_eq!(
+                syntax.check_parameters_positional(),
+                vec![errmsg.to_string()]
+            );
+        }
+    }
+
+    mod check_parameters_all {
+        use super::*;
+
+        fn syntax_with_three_problems() -> CommandSyntax {
+            CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        requires: vec!["--missing".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        dest: Some("paramdest".to_string()),
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        dest: Some("paramdest".to_string()),
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--flag".to_string()],
+                        arg_type: SyntaxOptArgType::Flag,
+                        num_values: Some(SyntaxOptArgNumValues::Exactly(1)),
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            }
+        }
+
+        fn expected_errors() -> Vec<String> {
+            vec![
+                "identifier paramdest is defined more than once".to_string(),
+                "parameter or group missing specified in requires for param1 does not exist"
+                    .to_string(),
+                "--flag: flag argument cannot have 'num_values' set".to_string(),
+            ]
+        }
+
+        #[test]
+        fn test_reports_all_problems() {
+            let _colors = disable_colors();
+
+            let syntax = syntax_with_three_problems();
+
+            assert_eq!(syntax.check_parameters_all(), expected_errors());
+            assert_eq!(syntax.check_parameters(), Err(expected_errors()[0].clone()));
+        }
+
+        #[test]
+        fn test_no_problems() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            assert_eq!(syntax.check_parameters_all(), Vec::<String>::new());
+            assert_eq!(syntax.check_parameters(), Ok(()));
+        }
+
+        #[test]
+        fn test_parser_build_error_carries_all_problems() {
+            let _colors = disable_colors();
+
+            let syntax = syntax_with_three_problems();
+
+            assert_eq!(
+                syntax.parse_args(vec![], vec!["test".to_string()]),
+                Err(ParseArgsErrorKind::ParserBuildError(expected_errors()))
             );
         }
     }
@@ -898,98 +1158,304 @@ mod command_syntax {
         }
 
         #[test]
-        fn test_value_enum() {
+        fn test_value_ipaddr() {
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
                     names: vec!["--param1".to_string()],
-                    arg_type: SyntaxOptArgType::Enum(vec![
-                        "a".to_string(),
-                        "b".to_string(),
-                        "c".to_string(),
-                    ]),
+                    arg_type: SyntaxOptArgType::IpAddr,
                     ..SyntaxOptArg::default()
                 }],
                 ..CommandSyntax::default()
             };
 
             let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
-                (vec!["--param1", "a"], Ok("a")),
-                (vec!["--param1", "b"], Ok("b")),
-                (vec!["--param1", "c"], Ok("c")),
-                (vec!["--param1", "d"], Err("invalid value 'd' for '--param1 <param1>' [possible values: a, b, c]")),
-                (vec!["--param1", ""], Err("a value is required for '--param1 <param1>' but none was supplied [possible values: a, b, c]")),
+                (vec!["--param1", "127.0.0.1"], Ok("127.0.0.1")),
+                (vec!["--param1", "::1"], Ok("::1")),
+                (vec!["--param1", "fe80::1ff:fe23:4567:890a"], Ok("fe80::1ff:fe23:4567:890a")),
+                (vec!["--param1", ""], Err("invalid value '' for '--param1 <param1>': invalid IP address syntax")),
+                (vec!["--param1", "256.0.0.1"], Err("invalid value '256.0.0.1' for '--param1 <param1>': invalid IP address syntax")),
+                (vec!["--param1", "127.0.0.1:8080"], Err("invalid value '127.0.0.1:8080' for '--param1 <param1>': invalid IP address syntax")),
             ];
 
             check_type_expectations("param1", "str", &syntax, &expectations);
         }
 
         #[test]
-        fn test_value_flag() {
+        fn test_value_socketaddr() {
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
                     names: vec!["--param1".to_string()],
-                    arg_type: SyntaxOptArgType::Flag,
+                    arg_type: SyntaxOptArgType::SocketAddr,
                     ..SyntaxOptArg::default()
                 }],
                 ..CommandSyntax::default()
             };
 
             let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
-                (vec!["--param1"], Ok("true")),
-                (vec![], Ok("false")),
-                (vec!["--param1", "c"], Err("unexpected argument 'c' found")),
-                (vec!["--param1", ""], Err("unexpected argument '' found")),
+                (vec!["--param1", "127.0.0.1:8080"], Ok("127.0.0.1:8080")),
+                (vec!["--param1", "[::1]:8080"], Ok("[::1]:8080")),
+                (vec!["--param1", "127.0.0.1"], Err("invalid value '127.0.0.1' for '--param1 <param1>': invalid socket address syntax")),
+                (vec!["--param1", "::1:8080"], Err("invalid value '::1:8080' for '--param1 <param1>': invalid socket address syntax")),
+                (vec!["--param1", "localhost:8080"], Err("invalid value 'localhost:8080' for '--param1 <param1>': invalid socket address syntax")),
             ];
 
-            check_type_expectations("param1", "bool", &syntax, &expectations);
+            check_type_expectations("param1", "str", &syntax, &expectations);
         }
 
         #[test]
-        fn test_value_counter() {
+        fn test_value_array_ipaddr() {
             let syntax = CommandSyntax {
                 parameters: vec![SyntaxOptArg {
-                    names: vec!["--count".to_string(), "-c".to_string()],
-                    arg_type: SyntaxOptArgType::Counter,
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::IpAddr)),
                     ..SyntaxOptArg::default()
                 }],
                 ..CommandSyntax::default()
             };
 
-            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
-                (vec![], Ok("0")),
-                (vec!["--count"], Ok("1")),
-                (vec!["--count", "--count"], Ok("2")),
-                (vec!["-c", "-c", "-c"], Ok("3")),
-                (vec!["-cc", "-c"], Ok("3")),
-                (vec!["-ccc"], Ok("3")),
-                (
-                    vec!["--count", "blah"],
-                    Err("unexpected argument 'blah' found"),
-                ),
-                (vec!["--count", ""], Err("unexpected argument '' found")),
-            ];
+            let args = syntax
+                .parse_args(
+                    ["--param1", "10.0.0.1", "--param1", "::1"]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    vec!["test".to_string()],
+                )
+                .expect("should parse the addresses");
+            assert_eq!(
+                args.get("OMNI_ARG_PARAM1_VALUE_0"),
+                Some(&"10.0.0.1".to_string())
+            );
+            assert_eq!(
+                args.get("OMNI_ARG_PARAM1_VALUE_1"),
+                Some(&"::1".to_string())
+            );
 
-            check_type_expectations("count", "int", &syntax, &expectations);
+            let err = syntax
+                .parse_args(
+                    ["--param1", "10.0.0.1", "--param1", "10.0.0"]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    vec!["test".to_string()],
+                )
+                .expect_err("should reject the malformed address");
+            assert_eq!(
+                err.simple(),
+                "invalid value '10.0.0' for '--param1 <param1>': invalid IP address syntax"
+            );
         }
 
         #[test]
-        fn test_unexpected_argument() {
+        fn test_value_bytes() {
             let syntax = CommandSyntax {
-                parameters: vec![
-                    SyntaxOptArg {
-                        names: vec!["--param1".to_string()],
-                        arg_type: SyntaxOptArgType::String,
-                        required: true,
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param2".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
-                        desc: Some("takes an int".to_string()),
-                        required: false,
-                        ..SyntaxOptArg::default()
-                    },
-                ],
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Bytes,
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
+                (vec!["--param1", "512"], Ok("512")),
+                (vec!["--param1", "10KB"], Ok("10000")),
+                (vec!["--param1", "4MiB"], Ok("4194304")),
+                (vec!["--param1", "2GB"], Ok("2000000000")),
+                (vec!["--param1", "1 kib"], Ok("1024")),
+                (vec!["--param1", ""], Err("invalid value '' for '--param1 <param1>': expected a number of bytes, optionally followed by a unit, e.g. 512, 10KB or 4MiB")),
+                (vec!["--param1", "MB"], Err("invalid value 'MB' for '--param1 <param1>': expected a number of bytes, optionally followed by a unit, e.g. 512, 10KB or 4MiB")),
+                (vec!["--param1", "10XB"], Err("invalid value '10XB' for '--param1 <param1>': unknown unit 'XB', expected one of B, KB, MB, GB, TB, KiB, MiB, GiB, TiB")),
+            ];
+
+            check_type_expectations("param1", "int", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_value_bytes_default() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Bytes,
+                    default: Some("16KiB".to_string()),
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> =
+                vec![(vec![], Ok("16384")), (vec!["--param1", "1KB"], Ok("1000"))];
+
+            check_type_expectations("param1", "int", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_value_array_bytes() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::Bytes)),
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let args = syntax
+                .parse_args(
+                    ["--param1", "1KiB", "--param1", "3"]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    vec!["test".to_string()],
+                )
+                .expect("should parse the sizes");
+            assert_eq!(args.get("OMNI_ARG_PARAM1_TYPE"), Some(&"int/2".to_string()));
+            assert_eq!(
+                args.get("OMNI_ARG_PARAM1_VALUE_0"),
+                Some(&"1024".to_string())
+            );
+            assert_eq!(args.get("OMNI_ARG_PARAM1_VALUE_1"), Some(&"3".to_string()));
+        }
+
+        #[test]
+        fn test_value_enum() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "a".to_string(),
+                        "b".to_string(),
+                        "c".to_string(),
+                    ]),
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
+                (vec!["--param1", "a"], Ok("a")),
+                (vec!["--param1", "b"], Ok("b")),
+                (vec!["--param1", "c"], Ok("c")),
+                (vec!["--param1", "d"], Err("invalid value 'd' for '--param1 <param1>' [possible values: a, b, c]")),
+                (vec!["--param1", ""], Err("a value is required for '--param1 <param1>' but none was supplied [possible values: a, b, c]")),
+            ];
+
+            check_type_expectations("param1", "str", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_value_enum_ignore_case() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "DEBUG".to_string(),
+                        "INFO".to_string(),
+                        "Warn".to_string(),
+                    ]),
+                    ignore_case: true,
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
+                (vec!["--param1", "DEBUG"], Ok("DEBUG")),
+                (vec!["--param1", "debug"], Ok("DEBUG")),
+                (vec!["--param1", "Info"], Ok("INFO")),
+                (vec!["--param1", "WARN"], Ok("Warn")),
+                (vec!["--param1", "error"], Err("invalid value 'error' for '--param1 <param1>' [possible values: DEBUG, INFO, Warn]")),
+            ];
+
+            check_type_expectations("param1", "str", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_value_enum_case_sensitive_by_default() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec!["DEBUG".to_string(), "INFO".to_string()]),
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
+                (vec!["--param1", "DEBUG"], Ok("DEBUG")),
+                (vec!["--param1", "debug"], Err("invalid value 'debug' for '--param1 <param1>' [possible values: DEBUG, INFO]")),
+            ];
+
+            check_type_expectations("param1", "str", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_value_flag() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
+                (vec!["--param1"], Ok("true")),
+                (vec![], Ok("false")),
+                (vec!["--param1", "c"], Err("unexpected argument 'c' found")),
+                (vec!["--param1", ""], Err("unexpected argument '' found")),
+            ];
+
+            check_type_expectations("param1", "bool", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_value_counter() {
+            let syntax = CommandSyntax {
+                parameters: vec![SyntaxOptArg {
+                    names: vec!["--count".to_string(), "-c".to_string()],
+                    arg_type: SyntaxOptArgType::Counter,
+                    ..SyntaxOptArg::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(Vec<&str>, Result<&str, &str>)> = vec![
+                (vec![], Ok("0")),
+                (vec!["--count"], Ok("1")),
+                (vec!["--count", "--count"], Ok("2")),
+                (vec!["-c", "-c", "-c"], Ok("3")),
+                (vec!["-cc", "-c"], Ok("3")),
+                (vec!["-ccc"], Ok("3")),
+                (
+                    vec!["--count", "blah"],
+                    Err("unexpected argument 'blah' found"),
+                ),
+                (vec!["--count", ""], Err("unexpected argument '' found")),
+            ];
+
+            check_type_expectations("count", "int", &syntax, &expectations);
+        }
+
+        #[test]
+        fn test_unexpected_argument() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        required: true,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        desc: Some("takes an int".to_string()),
+                        required: false,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
                 ..CommandSyntax::default()
             };
 
@@ -2015,8 +2481,14 @@ mod command_syntax {
             };
 
             let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&[], Some("the following required arguments were not provided: <--param1 <param1>|--param2 <param2>>")),
-                (&["--param3", "3.14"], Some("the following required arguments were not provided: <--param1 <param1>|--param2 <param2>>")),
+                (
+                    &[],
+                    Some("requires one of: --param1, --param2 (group 'group1')"),
+                ),
+                (
+                    &["--param3", "3.14"],
+                    Some("requires one of: --param1, --param2 (group 'group1')"),
+                ),
                 (&["--param1", "value1", "--param3", "3.14"], None),
                 (&["--param2", "42", "--param3", "3.14"], None),
                 (&["--param1", "value1"], None),
@@ -2154,13 +2626,12 @@ mod command_syntax {
         }
 
         #[test]
-        fn test_param_requires() {
+        fn test_group_conflicts_with_all() {
             let syntax = CommandSyntax {
                 parameters: vec![
                     SyntaxOptArg {
                         names: vec!["--param1".to_string()],
                         arg_type: SyntaxOptArgType::String,
-                        requires: vec!["param2".to_string()],
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
@@ -2171,19 +2642,6 @@ mod command_syntax {
                     SyntaxOptArg {
                         names: vec!["--param3".to_string()],
                         arg_type: SyntaxOptArgType::Float,
-                        requires: vec!["group2".to_string()],
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param4".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
-                        requires: vec!["param1".to_string()],
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param5".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
-                        requires: vec!["group1".to_string()],
                         ..SyntaxOptArg::default()
                     },
                 ],
@@ -2191,6 +2649,7 @@ mod command_syntax {
                     SyntaxGroup {
                         name: "group1".to_string(),
                         parameters: vec!["--param1".to_string()],
+                        conflicts_with_all: vec!["group2".to_string(), "param3".to_string()],
                         ..SyntaxGroup::default()
                     },
                     SyntaxGroup {
@@ -2203,86 +2662,95 @@ mod command_syntax {
             };
 
             let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&["--param2", "42"], None),
+                (&[], None),
+                (&["--param1", "value1"], None),
+                (&["--param1", "value1", "--param2", "42"], None),
+                (&["--param1", "value1", "--param3", "3.14"], None),
+                (&["--param2", "42", "--param3", "3.14"], None),
                 (
-                    &["--param1", "value1"],
-                    Some("the following required arguments were not provided: --param2 <param2>"),
+                    &["--param1", "value1", "--param2", "42", "--param3", "3.14"],
+                    Some(
+                        "the argument '--param1 <param1>' cannot be used with all of: '--param2 <param2>', '--param3 <param3>'",
+                    ),
                 ),
-                (&["--param1", "value1", "--param2", "42"], None),
-                (&["--param3", "3.14"], Some("the following required arguments were not provided: <--param2 <param2>>")),
-                (&["--param3", "3.14", "--param2", "42"], None),
-                (&["--param4", "10"], Some("the following required arguments were not provided: --param2 <param2> --param1 <param1>")),
-                (&["--param4", "10", "--param1", "value1"], Some("the following required arguments were not provided: --param2 <param2>")),
-                (&["--param4", "10", "--param1", "value1", "--param2", "42"], None),
-                (&["--param5", "20"], Some("the following required arguments were not provided: <--param1 <param1>>")),
-                (&["--param5", "20", "--param1", "value1"], Some("the following required arguments were not provided: --param2 <param2>")),
-                (&["--param5", "20", "--param2", "42"], Some("the following required arguments were not provided: <--param1 <param1>>")),
-                (&["--param5", "20", "--param1", "value1", "--param2", "42"], None),
-                (&[], None),
             ];
 
             check_expectations(&syntax, &expectations);
         }
 
+        /// The parameters used to test the conditional requirements of
+        /// a group of `--from-file` and `--from-url`
+        fn source_parameters() -> Vec<SyntaxOptArg> {
+            vec![
+                SyntaxOptArg {
+                    names: vec!["--from-file".to_string()],
+                    arg_type: SyntaxOptArgType::String,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--from-url".to_string()],
+                    arg_type: SyntaxOptArgType::String,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--source".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "local".to_string(),
+                        "remote".to_string(),
+                    ]),
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--mode".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec![
+                        "lenient".to_string(),
+                        "strict".to_string(),
+                    ]),
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--cached".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--offline".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+            ]
+        }
+
         #[test]
-        fn test_param_conflicts_with() {
+        fn test_group_required_without() {
             let syntax = CommandSyntax {
-                parameters: vec![
-                    SyntaxOptArg {
-                        names: vec!["--param1".to_string()],
-                        arg_type: SyntaxOptArgType::String,
-                        conflicts_with: vec!["param2".to_string()],
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param2".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param3".to_string()],
-                        arg_type: SyntaxOptArgType::Float,
-                        conflicts_with: vec!["group2".to_string()],
-                        ..SyntaxOptArg::default()
-                    },
-                ],
-                groups: vec![
-                    SyntaxGroup {
-                        name: "group1".to_string(),
-                        parameters: vec!["--param1".to_string()],
-                        ..SyntaxGroup::default()
-                    },
-                    SyntaxGroup {
-                        name: "group2".to_string(),
-                        parameters: vec!["--param2".to_string()],
-                        ..SyntaxGroup::default()
-                    },
-                ],
+                parameters: source_parameters(),
+                groups: vec![SyntaxGroup {
+                    name: "origin".to_string(),
+                    parameters: vec!["--from-file".to_string(), "--from-url".to_string()],
+                    required_without: vec!["--cached".to_string(), "offline".to_string()],
+                    ..SyntaxGroup::default()
+                }],
                 ..CommandSyntax::default()
             };
 
             let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&[], None),
-                (&["--param1", "value1"], None),
-                (&["--param2", "42"], None),
-                (&["--param3", "3.14"], None),
-                (&["--param1", "value1", "--param3", "3.14"], None),
                 (
-                    &["--param1", "value1", "--param2", "42"],
-                    Some(
-                        "the argument '--param1 <param1>' cannot be used with '--param2 <param2>'",
-                    ),
+                    &[],
+                    Some("requires one of: --from-file, --from-url, --cached, --offline (group 'origin')"),
                 ),
                 (
-                    &["--param2", "42", "--param3", "3.14"],
-                    Some(
-                        "the argument '--param3 <param3>' cannot be used with '--param2 <param2>'",
-                    ),
+                    &["--source", "remote"],
+                    Some("requires one of: --from-file, --from-url, --cached, --offline (group 'origin')"),
                 ),
+                (&["--from-file", "file.txt"], None),
+                (&["--from-url", "https://example.com"], None),
+                (&["--cached"], None),
+                (&["--offline"], None),
                 (
-                    &["--param1", "value1", "--param2", "42", "--param3", "3.14"],
+                    &["--from-file", "file.txt", "--from-url", "https://example.com"],
                     Some(
-                        "the argument '--param1 <param1>' cannot be used with '--param2 <param2>'",
+                        "the argument '--from-file <from_file>' cannot be used with '--from-url <from_url>'",
                     ),
                 ),
             ];
@@ -2291,93 +2759,171 @@ mod command_syntax {
         }
 
         #[test]
-        fn test_param_required_without() {
+        fn test_group_required_without_all() {
             let syntax = CommandSyntax {
-                parameters: vec![
-                    SyntaxOptArg {
-                        names: vec!["--param1".to_string()],
-                        arg_type: SyntaxOptArgType::String,
-                        required_without: vec!["param2".to_string(), "param3".to_string()],
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param2".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
-                        required: false,
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param3".to_string()],
-                        arg_type: SyntaxOptArgType::Float,
-                        required: false,
-                        ..SyntaxOptArg::default()
-                    },
-                    SyntaxOptArg {
-                        names: vec!["--param4".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
-                        required_without: vec!["group1".to_string()],
-                        ..SyntaxOptArg::default()
-                    },
-                ],
+                parameters: source_parameters(),
                 groups: vec![SyntaxGroup {
-                    name: "group1".to_string(),
-                    parameters: vec!["--param1".to_string()],
+                    name: "origin".to_string(),
+                    parameters: vec!["--from-file".to_string(), "--from-url".to_string()],
+                    required_without_all: vec!["cached".to_string(), "offline".to_string()],
                     ..SyntaxGroup::default()
                 }],
                 ..CommandSyntax::default()
             };
 
             let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&["--param1", "value1"], None),
-                (&["--param2", "42"], Some("the following required arguments were not provided: --param4 <param4>")),
-                (&["--param3", "3.14"], Some("the following required arguments were not provided: --param4 <param4>")),
-                (&["--param2", "42", "--param3", "43"], Some("the following required arguments were not provided: --param4 <param4>")),
-                (&["--param1", "value1", "--param2", "42"], None),
-                (&["--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4", "10"], None),
-                (&[], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
+                (
+                    &[],
+                    Some(concat!(
+                        "requires one of: --from-file, --from-url, --cached (group 'origin'); ",
+                        "requires one of: --from-file, --from-url, --offline (group 'origin')",
+                    )),
+                ),
+                (
+                    &["--cached"],
+                    Some("requires one of: --from-file, --from-url, --offline (group 'origin')"),
+                ),
+                (
+                    &["--offline"],
+                    Some("requires one of: --from-file, --from-url, --cached (group 'origin')"),
+                ),
+                (&["--cached", "--offline"], None),
+                (&["--from-file", "file.txt"], None),
+                (&["--from-url", "https://example.com", "--cached"], None),
             ];
 
             check_expectations(&syntax, &expectations);
         }
 
         #[test]
-        fn test_param_required_without_all() {
+        fn test_group_required_if_eq() {
+            let group_config = ConfigValue::from_str(
+                r#"
+                parameters: [--from-file, --from-url]
+                required_if_eq:
+                  source: remote
+                  --mode: strict
+                "#,
+            )
+            .expect("failed to parse yaml");
+            let group = SyntaxGroup::from_config_value(
+                &group_config,
+                Some("origin".to_string()),
+                &ConfigErrorHandler::noop(),
+            )
+            .expect("failed to parse group");
+
+            let syntax = CommandSyntax {
+                parameters: source_parameters(),
+                groups: vec![group],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&[], None),
+                (&["--source", "local"], None),
+                (&["--mode", "lenient"], None),
+                (
+                    &["--source", "remote"],
+                    Some("requires one of: --from-file, --from-url (group 'origin')"),
+                ),
+                (
+                    &["--mode", "strict"],
+                    Some("requires one of: --from-file, --from-url (group 'origin')"),
+                ),
+                (
+                    &["--source", "local", "--mode", "strict"],
+                    Some("requires one of: --from-file, --from-url (group 'origin')"),
+                ),
+                (
+                    &["--source", "remote", "--from-url", "https://example.com"],
+                    None,
+                ),
+                (&["--mode", "strict", "--from-file", "file.txt"], None),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_group_required_if_eq_all() {
+            let syntax = CommandSyntax {
+                parameters: source_parameters(),
+                groups: vec![SyntaxGroup {
+                    name: "origin".to_string(),
+                    parameters: vec!["--from-file".to_string(), "--from-url".to_string()],
+                    required_if_eq_all: HashMap::from([
+                        ("source".to_string(), "remote".to_string()),
+                        ("mode".to_string(), "strict".to_string()),
+                    ]),
+                    ..SyntaxGroup::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&[], None),
+                (&["--source", "remote"], None),
+                (&["--mode", "strict"], None),
+                (&["--source", "local", "--mode", "strict"], None),
+                (
+                    &["--source", "remote", "--mode", "strict"],
+                    Some("requires one of: --from-file, --from-url (group 'origin')"),
+                ),
+                (
+                    &[
+                        "--source",
+                        "remote",
+                        "--mode",
+                        "strict",
+                        "--from-file",
+                        "file.txt",
+                    ],
+                    None,
+                ),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_param_requires() {
             let syntax = CommandSyntax {
                 parameters: vec![
                     SyntaxOptArg {
                         names: vec!["--param1".to_string()],
                         arg_type: SyntaxOptArgType::String,
-                        required_without_all: vec!["param2".to_string(), "param3".to_string()],
+                        requires: vec!["param2".to_string()],
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
                         names: vec!["--param2".to_string()],
                         arg_type: SyntaxOptArgType::Integer,
-                        required: false,
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
                         names: vec!["--param3".to_string()],
                         arg_type: SyntaxOptArgType::Float,
-                        required: false,
+                        requires: vec!["group2".to_string()],
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
                         names: vec!["--param4".to_string()],
                         arg_type: SyntaxOptArgType::Integer,
-                        required_without_all: vec!["group5".to_string(), "group2".to_string()],
+                        requires: vec!["param1".to_string()],
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
                         names: vec!["--param5".to_string()],
                         arg_type: SyntaxOptArgType::Integer,
+                        requires: vec!["group1".to_string()],
                         ..SyntaxOptArg::default()
                     },
                 ],
                 groups: vec![
                     SyntaxGroup {
-                        name: "group5".to_string(),
-                        parameters: vec!["--param5".to_string()],
+                        name: "group1".to_string(),
+                        parameters: vec!["--param1".to_string()],
                         ..SyntaxGroup::default()
                     },
                     SyntaxGroup {
@@ -2390,75 +2936,81 @@ mod command_syntax {
             };
 
             let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&["--param1", "value1"], Some("the following required arguments were not provided: --param4 <param4>")),
-                (&["--param2", "42"], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
-                (&["--param3", "3.14"], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
-                (&["--param2", "42", "--param3", "43"], Some("the following required arguments were not provided: --param4 <param4>")),
-                (&["--param1", "value1", "--param2", "42"], Some("the following required arguments were not provided: --param4 <param4>")),
-                (&["--param1", "value1", "--param4", "10"], None),
-                (&["--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4", "10", "--param5", "20"], None),
-                (&["--param2", "42", "--param3", "3.14", "--param5", "20"], None),
-                (&[], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
+                (&["--param2", "42"], None),
+                (
+                    &["--param1", "value1"],
+                    Some("the following required arguments were not provided: --param2 <param2>"),
+                ),
+                (&["--param1", "value1", "--param2", "42"], None),
+                (&["--param3", "3.14"], Some("the following required arguments were not provided: <--param2 <param2>>")),
+                (&["--param3", "3.14", "--param2", "42"], None),
+                (&["--param4", "10"], Some("the following required arguments were not provided: --param2 <param2> --param1 <param1>")),
+                (&["--param4", "10", "--param1", "value1"], Some("the following required arguments were not provided: --param2 <param2>")),
+                (&["--param4", "10", "--param1", "value1", "--param2", "42"], None),
+                (&["--param5", "20"], Some("the following required arguments were not provided: <--param1 <param1>>")),
+                (&["--param5", "20", "--param1", "value1"], Some("the following required arguments were not provided: --param2 <param2>")),
+                (&["--param5", "20", "--param2", "42"], Some("the following required arguments were not provided: <--param1 <param1>>")),
+                (&["--param5", "20", "--param1", "value1", "--param2", "42"], None),
+                (&[], None),
             ];
 
             check_expectations(&syntax, &expectations);
         }
 
         #[test]
-        fn test_param_required_if_eq() {
+        fn test_param_requires_group_with_multiple_members() {
             let syntax = CommandSyntax {
                 parameters: vec![
                     SyntaxOptArg {
                         names: vec!["--param1".to_string()],
                         arg_type: SyntaxOptArgType::String,
-                        required_if_eq: HashMap::from_iter(vec![(
-                            "param2".to_string(),
-                            "42".to_string(),
-                        )]),
+                        requires: vec!["credentials".to_string()],
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
                         names: vec!["--param2".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
+                        arg_type: SyntaxOptArgType::String,
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
-                        names: vec!["--param3".to_string()],
-                        arg_type: SyntaxOptArgType::Float,
-                        required_if_eq: HashMap::from_iter(vec![(
-                            "param4".to_string(),
-                            "true".to_string(),
-                        )]),
+                        names: vec!["--param3".to_string(), "-p".to_string()],
+                        arg_type: SyntaxOptArgType::String,
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
                         names: vec!["--param4".to_string()],
-                        arg_type: SyntaxOptArgType::Boolean,
+                        arg_type: SyntaxOptArgType::Integer,
+                        requires: vec!["credentials".to_string(), "param5".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param5".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
                         ..SyntaxOptArg::default()
                     },
                 ],
+                groups: vec![SyntaxGroup {
+                    name: "credentials".to_string(),
+                    parameters: vec!["--param2".to_string(), "--param3".to_string()],
+                    ..SyntaxGroup::default()
+                }],
                 ..CommandSyntax::default()
             };
 
             let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&["--param1", "value1"], None),
                 (
-                    &["--param2", "42"],
-                    Some("the following required arguments were not provided: --param1 <param1>"),
+                    &["--param1", "value1"],
+                    Some("requires one of: --param2, --param3 (group 'credentials')"),
                 ),
-                (&["--param1", "value1", "--param2", "42"], None),
-                (&["--param3", "3.14"], None),
+                (&["--param1", "value1", "--param2", "value2"], None),
+                (&["--param1", "value1", "-p", "value3"], None),
                 (
-                    &["--param4", "true"],
-                    Some("the following required arguments were not provided: --param3 <param3>"),
+                    &["--param4", "10"],
+                    Some("the following required arguments were not provided: --param5 <param5>; requires one of: --param2, --param3 (group 'credentials')"),
                 ),
-                (&["--param3", "3.14", "--param4", "true"], None),
                 (
-                    &[
-                        "--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4",
-                        "true",
-                    ],
-                    None,
+                    &["--param4", "10", "--param3", "value3"],
+                    Some("the following required arguments were not provided: --param5 <param5>"),
                 ),
                 (&[], None),
             ];
@@ -2467,355 +3019,5496 @@ mod command_syntax {
         }
 
         #[test]
-        fn test_param_required_if_eq_all() {
+        fn test_param_requires_one_of() {
             let syntax = CommandSyntax {
                 parameters: vec![
                     SyntaxOptArg {
-                        names: vec!["--param1".to_string()],
+                        names: vec!["--deploy".to_string()],
+                        arg_type: SyntaxOptArgType::Flag,
+                        requires_one_of: vec!["cluster".to_string(), "context".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--cluster".to_string()],
                         arg_type: SyntaxOptArgType::String,
-                        required_if_eq_all: HashMap::from_iter(vec![
-                            ("param2".to_string(), "42".to_string()),
-                            ("param3".to_string(), "3.14".to_string()),
-                        ]),
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
-                        names: vec!["--param2".to_string()],
-                        arg_type: SyntaxOptArgType::Integer,
+                        names: vec!["--context".to_string()],
+                        arg_type: SyntaxOptArgType::String,
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
-                        names: vec!["--param3".to_string()],
-                        arg_type: SyntaxOptArgType::Float,
-                        required_if_eq_all: HashMap::from_iter(vec![(
-                            "param4".to_string(),
-                            "true".to_string(),
-                        )]),
+                        names: vec!["--token".to_string()],
+                        arg_type: SyntaxOptArgType::String,
                         ..SyntaxOptArg::default()
                     },
                     SyntaxOptArg {
-                        names: vec!["--param4".to_string()],
-                        arg_type: SyntaxOptArgType::Boolean,
+                        names: vec!["--user".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--login".to_string()],
+                        arg_type: SyntaxOptArgType::Flag,
+                        requires_one_of: vec!["credentials".to_string(), "context".to_string()],
+                        requires: vec!["cluster".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--logout".to_string()],
+                        arg_type: SyntaxOptArgType::Flag,
+                        requires_one_of: vec!["context".to_string()],
                         ..SyntaxOptArg::default()
                     },
                 ],
+                groups: vec![SyntaxGroup {
+                    name: "credentials".to_string(),
+                    parameters: vec!["--user".to_string(), "--token".to_string()],
+                    multiple: true,
+                    ..SyntaxGroup::default()
+                }],
                 ..CommandSyntax::default()
             };
 
-            let expectations: Vec<(&[&str], Option<&str>)> = vec![
-                (&["--param1", "value1"], None),
-                (&["--param2", "42"], None),
-                (&["--param3", "3.14"], None),
+            let expectations: Vec<(&[&str], Option<&str>)> =
+                vec![
                 (
-                    &["--param2", "42", "--param3", "3.14"],
-                    Some("the following required arguments were not provided: --param1 <param1>"),
+                    &["--deploy"],
+                    Some("requires one of: --cluster, --context (required by --deploy)"),
                 ),
-                (&["--param1", "value1", "--param2", "42"], None),
-                (&["--param1", "value1", "--param3", "3.14"], None),
+                (&["--deploy", "--cluster", "prod"], None),
+                (&["--deploy", "--context", "prod"], None),
+                (&["--deploy", "--cluster", "prod", "--context", "prod"], None),
                 (
-                    &["--param1", "value1", "--param4", "true"],
-                    Some("the following required arguments were not provided: --param3 <param3>"),
+                    &["--login", "--cluster", "prod"],
+                    Some("requires one of: --user, --token, --context (required by --login)"),
                 ),
-                (&["--param3", "3.14"], None),
+                (&["--login", "--cluster", "prod", "--user", "me"], None),
+                (&["--login", "--cluster", "prod", "--context", "prod"], None),
                 (
-                    &["--param4", "true"],
-                    Some("the following required arguments were not provided: --param3 <param3>"),
+                    &["--login", "--user", "me"],
+                    Some("the following required arguments were not provided: --cluster <cluster>"),
                 ),
-                (&["--param3", "3.14", "--param4", "true"], None),
                 (
-                    &[
-                        "--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4",
-                        "true",
-                    ],
-                    None,
+                    &["--logout"],
+                    Some("requires one of: --context (required by --logout)"),
                 ),
+                (&["--logout", "--context", "prod"], None),
+                (&["--cluster", "prod"], None),
                 (&[], None),
             ];
 
             check_expectations(&syntax, &expectations);
         }
-    }
-}
-
-mod parse_arg_name {
-    use super::*;
-
-    #[test]
-    fn test_simple_positional() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("arg");
-        assert_eq!(names, vec!["arg"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert!(placeholders.is_empty());
-        assert!(!leftovers);
-    }
-
-    #[test]
-    fn test_short_option() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("-a");
-        assert_eq!(names, vec!["-a"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert!(placeholders.is_empty());
-        assert!(!leftovers);
-    }
+
+        #[test]
+        fn test_missing_group_keeps_error_details() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        requires: vec!["credentials".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                groups: vec![SyntaxGroup {
+                    name: "credentials".to_string(),
+                    parameters: vec!["--param2".to_string(), "--param3".to_string()],
+                    ..SyntaxGroup::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let err = syntax
+                .parse_args(
+                    vec!["--param1".to_string(), "value1".to_string()],
+                    vec!["test".to_string()],
+                )
+                .expect_err("should fail");
+
+            assert_eq!(
+                err.invalid_arg(),
+                Some("<--param2 <param2>|--param3 <param3>>".to_string())
+            );
+            match &err {
+                ParseArgsErrorKind::ArgumentParsingError(clap_err) => {
+                    assert_eq!(
+                        clap_err.kind(),
+                        clap::error::ErrorKind::MissingRequiredArgument
+                    );
+                    assert!(clap_err.to_string().contains("Usage: "), "{clap_err}");
+                }
+                _ => panic!("unexpected error: {err:?}"),
+            }
+        }
+
+        #[test]
+        fn test_param_conflicts_with() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        conflicts_with: vec!["param2".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::Float,
+                        conflicts_with: vec!["group2".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                groups: vec![
+                    SyntaxGroup {
+                        name: "group1".to_string(),
+                        parameters: vec!["--param1".to_string()],
+                        ..SyntaxGroup::default()
+                    },
+                    SyntaxGroup {
+                        name: "group2".to_string(),
+                        parameters: vec!["--param2".to_string()],
+                        ..SyntaxGroup::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&[], None),
+                (&["--param1", "value1"], None),
+                (&["--param2", "42"], None),
+                (&["--param3", "3.14"], None),
+                (&["--param1", "value1", "--param3", "3.14"], None),
+                (
+                    &["--param1", "value1", "--param2", "42"],
+                    Some(
+                        "the argument '--param1 <param1>' cannot be used with '--param2 <param2>'",
+                    ),
+                ),
+                (
+                    &["--param2", "42", "--param3", "3.14"],
+                    Some(
+                        "the argument '--param3 <param3>' cannot be used with '--param2 <param2>'",
+                    ),
+                ),
+                (
+                    &["--param1", "value1", "--param2", "42", "--param3", "3.14"],
+                    Some(
+                        "the argument '--param1 <param1>' cannot be used with '--param2 <param2>'",
+                    ),
+                ),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_param_conflicts_with_all() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        conflicts_with_all: vec!["param2".to_string(), "group1".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::Float,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param4".to_string()],
+                        arg_type: SyntaxOptArgType::Flag,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                groups: vec![SyntaxGroup {
+                    name: "group1".to_string(),
+                    parameters: vec!["--param3".to_string(), "--param4".to_string()],
+                    multiple: true,
+                    ..SyntaxGroup::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&[], None),
+                (&["--param1", "value1"], None),
+                (&["--param1", "value1", "--param2", "42"], None),
+                (&["--param1", "value1", "--param3", "3.14"], None),
+                (&["--param1", "value1", "--param4"], None),
+                (&["--param2", "42", "--param3", "3.14", "--param4"], None),
+                (
+                    &["--param1", "value1", "--param2", "42", "--param3", "3.14"],
+                    Some(
+                        "the argument '--param1 <param1>' cannot be used with all of: '--param2 <param2>', '--param3 <param3>'",
+                    ),
+                ),
+                (
+                    &["--param1", "value1", "--param2", "42", "--param4"],
+                    Some(
+                        "the argument '--param1 <param1>' cannot be used with all of: '--param2 <param2>', '--param4'",
+                    ),
+                ),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_param_required_without() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        required_without: vec!["param2".to_string(), "param3".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        required: false,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::Float,
+                        required: false,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param4".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        required_without: vec!["group1".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                groups: vec![SyntaxGroup {
+                    name: "group1".to_string(),
+                    parameters: vec!["--param1".to_string()],
+                    ..SyntaxGroup::default()
+                }],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&["--param1", "value1"], None),
+                (&["--param2", "42"], Some("the following required arguments were not provided: --param4 <param4>")),
+                (&["--param3", "3.14"], Some("the following required arguments were not provided: --param4 <param4>")),
+                (&["--param2", "42", "--param3", "43"], Some("the following required arguments were not provided: --param4 <param4>")),
+                (&["--param1", "value1", "--param2", "42"], None),
+                (&["--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4", "10"], None),
+                (&[], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_param_required_without_all() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        required_without_all: vec!["param2".to_string(), "param3".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        required: false,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::Float,
+                        required: false,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param4".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        required_without_all: vec!["group5".to_string(), "group2".to_string()],
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param5".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                groups: vec![
+                    SyntaxGroup {
+                        name: "group5".to_string(),
+                        parameters: vec!["--param5".to_string()],
+                        ..SyntaxGroup::default()
+                    },
+                    SyntaxGroup {
+                        name: "group2".to_string(),
+                        parameters: vec!["--param2".to_string()],
+                        ..SyntaxGroup::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&["--param1", "value1"], Some("the following required arguments were not provided: --param4 <param4>")),
+                (&["--param2", "42"], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
+                (&["--param3", "3.14"], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
+                (&["--param2", "42", "--param3", "43"], Some("the following required arguments were not provided: --param4 <param4>")),
+                (&["--param1", "value1", "--param2", "42"], Some("the following required arguments were not provided: --param4 <param4>")),
+                (&["--param1", "value1", "--param4", "10"], None),
+                (&["--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4", "10", "--param5", "20"], None),
+                (&["--param2", "42", "--param3", "3.14", "--param5", "20"], None),
+                (&[], Some("the following required arguments were not provided: --param1 <param1> --param4 <param4>")),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_param_required_if_eq() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        required_if_eq: HashMap::from_iter(vec![(
+                            "param2".to_string(),
+                            "42".to_string(),
+                        )]),
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::Float,
+                        required_if_eq: HashMap::from_iter(vec![(
+                            "param4".to_string(),
+                            "true".to_string(),
+                        )]),
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param4".to_string()],
+                        arg_type: SyntaxOptArgType::Boolean,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&["--param1", "value1"], None),
+                (
+                    &["--param2", "42"],
+                    Some("the following required arguments were not provided: --param1 <param1>"),
+                ),
+                (&["--param1", "value1", "--param2", "42"], None),
+                (&["--param3", "3.14"], None),
+                (
+                    &["--param4", "true"],
+                    Some("the following required arguments were not provided: --param3 <param3>"),
+                ),
+                (&["--param3", "3.14", "--param4", "true"], None),
+                (
+                    &[
+                        "--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4",
+                        "true",
+                    ],
+                    None,
+                ),
+                (&[], None),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+
+        #[test]
+        fn test_param_required_if_eq_all() {
+            let syntax = CommandSyntax {
+                parameters: vec![
+                    SyntaxOptArg {
+                        names: vec!["--param1".to_string()],
+                        arg_type: SyntaxOptArgType::String,
+                        required_if_eq_all: HashMap::from_iter(vec![
+                            ("param2".to_string(), "42".to_string()),
+                            ("param3".to_string(), "3.14".to_string()),
+                        ]),
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param2".to_string()],
+                        arg_type: SyntaxOptArgType::Integer,
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param3".to_string()],
+                        arg_type: SyntaxOptArgType::Float,
+                        required_if_eq_all: HashMap::from_iter(vec![(
+                            "param4".to_string(),
+                            "true".to_string(),
+                        )]),
+                        ..SyntaxOptArg::default()
+                    },
+                    SyntaxOptArg {
+                        names: vec!["--param4".to_string()],
+                        arg_type: SyntaxOptArgType::Boolean,
+                        ..SyntaxOptArg::default()
+                    },
+                ],
+                ..CommandSyntax::default()
+            };
+
+            let expectations: Vec<(&[&str], Option<&str>)> = vec![
+                (&["--param1", "value1"], None),
+                (&["--param2", "42"], None),
+                (&["--param3", "3.14"], None),
+                (
+                    &["--param2", "42", "--param3", "3.14"],
+                    Some("the following required arguments were not provided: --param1 <param1>"),
+                ),
+                (&["--param1", "value1", "--param2", "42"], None),
+                (&["--param1", "value1", "--param3", "3.14"], None),
+                (
+                    &["--param1", "value1", "--param4", "true"],
+                    Some("the following required arguments were not provided: --param3 <param3>"),
+                ),
+                (&["--param3", "3.14"], None),
+                (
+                    &["--param4", "true"],
+                    Some("the following required arguments were not provided: --param3 <param3>"),
+                ),
+                (&["--param3", "3.14", "--param4", "true"], None),
+                (
+                    &[
+                        "--param1", "value1", "--param2", "42", "--param3", "3.14", "--param4",
+                        "true",
+                    ],
+                    None,
+                ),
+                (&[], None),
+            ];
+
+            check_expectations(&syntax, &expectations);
+        }
+    }
+}
+
+mod parse_arg_name {
+    use super::*;
+
+    #[test]
+    fn test_simple_positional() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("arg");
+        assert_eq!(names, vec!["arg"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_short_option() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("-a");
+        assert_eq!(names, vec!["-a"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_long_option() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option");
+        assert_eq!(names, vec!["--option"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_multiple_names() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("-a, --alpha");
+        assert_eq!(names, vec!["-a", "--alpha"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_counter_option() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--count...");
+        assert_eq!(names, vec!["--count"]);
+        assert_eq!(arg_type, SyntaxOptArgType::Counter);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_positional_with_placeholder() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("arg PLACEHOLDER");
+        assert_eq!(names, vec!["arg"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["PLACEHOLDER"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_option_with_placeholder() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option VALUE");
+        assert_eq!(names, vec!["--option"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["VALUE"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_multiple_placeholders() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option FIRST SECOND");
+        assert_eq!(names, vec!["--option"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["FIRST", "SECOND"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_leftovers_positional() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("args...");
+        assert_eq!(names, vec!["args"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(leftovers);
+    }
+
+    #[test]
+    fn test_multiple_names_with_placeholder_at_the_end() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("-f, --file FILENAME");
+        assert_eq!(names, vec!["-f", "--file"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["FILENAME"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_multiple_names_with_placeholders_for_each() {
+        let (names, arg_type, placeholders, leftovers) =
+            parse_arg_name("-f FILENAME1, --file FILENAME2");
+        assert_eq!(names, vec!["-f", "--file"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["FILENAME1", "FILENAME2"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_equals_separator() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option=VALUE");
+        assert_eq!(names, vec!["--option"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["VALUE"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("");
+        assert_eq!(names, vec![""]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_whitespace_handling() {
+        let (names, arg_type, placeholders, leftovers) = parse_arg_name("  --option  VALUE  ");
+        assert_eq!(names, vec!["--option"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["VALUE"]);
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_multiple_names_whitespace() {
+        let (names, arg_type, placeholders, leftovers) =
+            parse_arg_name("-f,   --file,  -F  FILENAME");
+        assert_eq!(names, vec!["-f", "--file", "-F"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert_eq!(placeholders, vec!["FILENAME"]);
+        assert!(!leftovers);
+    }
+
+    type ParsedArgName = (Vec<String>, SyntaxOptArgType, Vec<String>, bool);
+
+    fn parse_checked(arg_name: &str) -> (ParsedArgName, Vec<String>) {
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let parsed = parse_arg_name_checked(arg_name, &error_handler);
+        let errors = error_handler.errors();
+        assert!(errors
+            .iter()
+            .all(|error| error.kind() == &ConfigErrorKind::MalformedArgName && error.is_warning()));
+        (parsed, errors.iter().map(|error| error.message()).collect())
+    }
+
+    #[test]
+    fn test_valid_names_have_no_diagnostics() {
+        for arg_name in [
+            "arg",
+            "-a, --alpha",
+            "--count...",
+            "args...",
+            "-f FILENAME1, --file FILENAME2",
+            "--option=VALUE",
+            "  --option  VALUE  ",
+        ] {
+            let (_, diagnostics) = parse_checked(arg_name);
+            assert!(diagnostics.is_empty(), "{arg_name}: {diagnostics:?}");
+        }
+    }
+
+    #[test]
+    fn test_empty_input_diagnostic() {
+        let ((names, _, placeholders, _), diagnostics) = parse_checked("");
+        assert_eq!(names, vec![""]);
+        assert!(placeholders.is_empty());
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name '': the name is empty"]
+        );
+    }
+
+    #[test]
+    fn test_double_dash_only() {
+        let ((names, arg_type, placeholders, leftovers), diagnostics) = parse_checked("--");
+        assert_eq!(names, vec!["--"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name '--': option '--' has an empty name"]
+        );
+    }
+
+    #[test]
+    fn test_empty_option_name_is_skipped() {
+        let ((names, _, _, _), diagnostics) = parse_checked("-a, --");
+        assert_eq!(names, vec!["-a"]);
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name '-a, --': option '--' has an empty name"]
+        );
+    }
+
+    #[test]
+    fn test_empty_part_is_skipped() {
+        let ((names, _, placeholders, _), diagnostics) = parse_checked("-a,, --alpha VALUE,");
+        assert_eq!(names, vec!["-a", "--alpha"]);
+        assert_eq!(placeholders, vec!["VALUE"]);
+        assert_eq!(
+            diagnostics,
+            vec![
+                "malformed argument name '-a,, --alpha VALUE,': empty name between commas",
+                "malformed argument name '-a,, --alpha VALUE,': empty name between commas",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_positional_discards_previous_names() {
+        let ((names, arg_type, placeholders, leftovers), diagnostics) = parse_checked("-a, b");
+        assert_eq!(names, vec!["b"]);
+        assert_eq!(arg_type, SyntaxOptArgType::String);
+        assert!(placeholders.is_empty());
+        assert!(!leftovers);
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name '-a, b': positional 'b' discards previously parsed names -a"]
+        );
+    }
+
+    #[test]
+    fn test_names_after_positional_are_ignored() {
+        let ((names, _, _, _), diagnostics) = parse_checked("b, -c, --delta");
+        assert_eq!(names, vec!["b"]);
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name 'b, -c, --delta': names after positional 'b' are ignored: -c, --delta"]
+        );
+    }
+
+    #[test]
+    fn test_double_equals_separator() {
+        let ((names, _, placeholders, _), diagnostics) = parse_checked("--opt==VALUE");
+        assert_eq!(names, vec!["--opt"]);
+        assert_eq!(placeholders, vec!["VALUE"]);
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name '--opt==VALUE': repeated '=' after '--opt'"]
+        );
+    }
+
+    #[test]
+    fn test_equals_surrounded_by_spaces() {
+        let ((names, _, placeholders, _), diagnostics) = parse_checked("--opt = VALUE");
+        assert_eq!(names, vec!["--opt"]);
+        assert_eq!(placeholders, vec!["VALUE"]);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_tabs_behave_like_spaces() {
+        let (with_spaces, _) = parse_checked("-f, --file  FIRST SECOND");
+        let (with_tabs, diagnostics) = parse_checked("-f,\t--file\t\tFIRST\tSECOND");
+        assert_eq!(with_tabs, with_spaces);
+        assert!(diagnostics.is_empty());
+
+        let (with_spaces, _) = parse_checked("arg PLACEHOLDER");
+        let (with_tabs, diagnostics) = parse_checked("arg\tPLACEHOLDER");
+        assert_eq!(with_tabs, with_spaces);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_missing_comma_between_names() {
+        let ((names, _, placeholders, _), diagnostics) = parse_checked("-a --alpha");
+        assert_eq!(names, vec!["-a"]);
+        assert_eq!(placeholders, vec!["--alpha"]);
+        assert_eq!(
+            diagnostics,
+            vec!["malformed argument name '-a --alpha': placeholder '--alpha' looks like an option name, names should be separated by commas"]
+        );
+    }
+}
+
+mod syntax_opt_arg_type {
+    use super::*;
+    use crate::internal::config::ConfigValue;
+
+    #[test]
+    fn test_from_config_value_list_as_enum() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test with array of strings as type
+        let type_value = ConfigValue::from_str("[debug, info, warn, error]").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+
+        assert_eq!(
+            result,
+            Some(SyntaxOptArgType::Enum(vec![
+                "debug".to_string(),
+                "info".to_string(),
+                "warn".to_string(),
+                "error".to_string(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_from_config_value_traditional_enum() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test traditional enum syntax with separate values
+        let type_value = ConfigValue::from_str("enum").unwrap();
+        let values_value = ConfigValue::from_str("[one, two, three]").unwrap();
+        let result = SyntaxOptArgType::from_config_value(
+            Some(&type_value),
+            Some(&values_value),
+            None,
+            &error_handler,
+        );
+
+        assert_eq!(
+            result,
+            Some(SyntaxOptArgType::Enum(vec![
+                "one".to_string(),
+                "two".to_string(),
+                "three".to_string(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_from_config_value_inline_enum() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test inline enum syntax
+        let type_value = ConfigValue::from_str("enum(fast, safe, rollback)").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+
+        assert_eq!(
+            result,
+            Some(SyntaxOptArgType::Enum(vec![
+                "fast".to_string(),
+                "safe".to_string(),
+                "rollback".to_string(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_from_config_value_basic_types() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test basic string type
+        let type_value = ConfigValue::from_str("str").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+        assert_eq!(result, Some(SyntaxOptArgType::String));
+
+        // Test integer type
+        let type_value = ConfigValue::from_str("int").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+        assert_eq!(result, Some(SyntaxOptArgType::Integer));
+
+        // Test boolean type
+        let type_value = ConfigValue::from_str("bool").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+        assert_eq!(result, Some(SyntaxOptArgType::Boolean));
+    }
+
+    #[test]
+    fn test_network_address_types() {
+        let error_handler = ConfigErrorHandler::default();
+
+        for (type_str, expected) in [
+            ("ipaddr", SyntaxOptArgType::IpAddr),
+            ("ip", SyntaxOptArgType::IpAddr),
+            ("socketaddr", SyntaxOptArgType::SocketAddr),
+            (
+                "array/ipaddr",
+                SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::IpAddr)),
+            ),
+            (
+                "[socketaddr]",
+                SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::SocketAddr)),
+            ),
+        ] {
+            assert_eq!(
+                SyntaxOptArgType::from_str(type_str, &error_handler),
+                Some(expected),
+                "{type_str}"
+            );
+        }
+
+        assert_eq!(SyntaxOptArgType::IpAddr.to_str(), "ipaddr");
+        assert_eq!(SyntaxOptArgType::SocketAddr.to_str(), "socketaddr");
+        assert_eq!(
+            SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::IpAddr)).to_str(),
+            "array/ipaddr"
+        );
+        assert_eq!(
+            SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::SocketAddr)).to_str(),
+            "array/socketaddr"
+        );
+
+        assert!(SyntaxOptArgType::IpAddr.accepts_value("192.168.1.1"));
+        assert!(!SyntaxOptArgType::IpAddr.accepts_value("192.168.1"));
+        assert!(SyntaxOptArgType::SocketAddr.accepts_value("[::1]:22"));
+        assert!(!SyntaxOptArgType::SocketAddr.accepts_value("::1"));
+    }
+
+    #[test]
+    fn test_bytes_type() {
+        let error_handler = ConfigErrorHandler::default();
+
+        assert_eq!(
+            SyntaxOptArgType::from_str("bytes", &error_handler),
+            Some(SyntaxOptArgType::Bytes)
+        );
+        assert_eq!(
+            SyntaxOptArgType::from_str("array/bytes", &error_handler),
+            Some(SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::Bytes)))
+        );
+        assert_eq!(SyntaxOptArgType::Bytes.to_str(), "bytes");
+        assert_eq!(
+            SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::Bytes)).to_str(),
+            "array/bytes"
+        );
+
+        assert!(SyntaxOptArgType::Bytes.accepts_value("10MB"));
+        assert!(!SyntaxOptArgType::Bytes.accepts_value("10 parsecs"));
+    }
+
+    #[test]
+    fn test_from_config_value_empty_list() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test with empty array
+        let type_value = ConfigValue::from_str("[]").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+
+        assert_eq!(result, Some(SyntaxOptArgType::Enum(vec![])));
+    }
+
+    #[test]
+    fn test_from_config_value_single_item_list() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test with single item array
+        let type_value = ConfigValue::from_str("[debug]").unwrap();
+        let result =
+            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+
+        assert_eq!(
+            result,
+            Some(SyntaxOptArgType::Enum(vec!["debug".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_from_config_value_precedence() {
+        let error_handler = ConfigErrorHandler::default();
+
+        // Test that list syntax takes precedence over values field
+        let type_value = ConfigValue::from_str("[debug, info]").unwrap();
+        let values_value = ConfigValue::from_str("[ignored, values]").unwrap();
+        let result = SyntaxOptArgType::from_config_value(
+            Some(&type_value),
+            Some(&values_value),
+            None,
+            &error_handler,
+        );
+
+        // Should use the list from type, not values
+        assert_eq!(
+            result,
+            Some(SyntaxOptArgType::Enum(vec![
+                "debug".to_string(),
+                "info".to_string(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_from_config_value_ignore_case() {
+        let config_value =
+            ConfigValue::from_str("--log-level: {type: [DEBUG, INFO], ignore_case: true}").unwrap();
+        let param =
+            SyntaxOptArg::from_config_value(&config_value, None, &ConfigErrorHandler::new())
+                .expect("should parse the parameter");
+
+        assert!(param.ignore_case);
+        assert_eq!(
+            param.arg_type,
+            SyntaxOptArgType::Enum(vec!["DEBUG".to_string(), "INFO".to_string()])
+        );
+    }
+}
+
+mod syntax_tests {
+    use super::*;
+
+    fn env_syntax() -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--env".to_string()],
+                arg_type: SyntaxOptArgType::Enum(vec![
+                    "dev".to_string(),
+                    "staging".to_string(),
+                    "prod".to_string(),
+                ]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn command_with_tests(tests: &str) -> CommandDefinition {
+        let yaml = format!(
+            r#"
+            run: echo "deploying to $OMNI_ARG_ENV_VALUE"
+            syntax:
+              parameters:
+                - name: --env
+                  type: enum(dev, staging, prod)
+            tests: {tests}
+            "#
+        );
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        assert!(
+            error_handler.errors().is_empty(),
+            "unexpected errors: {:?}",
+            error_handler.errors()
+        );
+        command
+    }
+
+    fn case(args: &[&str], expect: SyntaxTestExpect) -> SyntaxTestCase {
+        SyntaxTestCase {
+            args: args.iter().map(|s| s.to_string()).collect(),
+            expect,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn passing_case() {
+        let mut test_case = case(&["--env", "prod"], SyntaxTestExpect::Ok);
+        test_case
+            .expect_env
+            .insert("OMNI_ARG_ENV_VALUE".to_string(), "prod".to_string());
+
+        assert_eq!(
+            test_case.run(&env_syntax(), vec!["test".to_string()]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn failing_expectation_ok() {
+        let test_case = case(&["--env", "bogus"], SyntaxTestExpect::Ok);
+
+        let result = test_case.run(&env_syntax(), vec!["test".to_string()]);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .starts_with("expected the arguments to be accepted"));
+    }
+
+    #[test]
+    fn failing_expectation_error() {
+        let test_case = case(&["--env", "prod"], SyntaxTestExpect::Error);
+
+        assert_eq!(
+            test_case.run(&env_syntax(), vec!["test".to_string()]),
+            Err("expected an error, but the arguments were accepted".to_string())
+        );
+    }
+
+    #[test]
+    fn failing_expectation_env() {
+        let mut test_case = case(&["--env", "dev"], SyntaxTestExpect::Ok);
+        test_case
+            .expect_env
+            .insert("OMNI_ARG_ENV_VALUE".to_string(), "prod".to_string());
+
+        assert_eq!(
+            test_case.run(&env_syntax(), vec!["test".to_string()]),
+            Err("expected OMNI_ARG_ENV_VALUE to be 'prod', but found 'dev'".to_string())
+        );
+    }
+
+    #[test]
+    fn error_contains_matching() {
+        let mut test_case = case(&["--env", "bogus"], SyntaxTestExpect::Error);
+        test_case.error_contains = Some("possible values".to_string());
+
+        assert_eq!(
+            test_case.run(&env_syntax(), vec!["test".to_string()]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn error_contains_not_matching() {
+        let mut test_case = case(&["--env", "bogus"], SyntaxTestExpect::Error);
+        test_case.error_contains = Some("unexpected argument".to_string());
+
+        let result = test_case.run(&env_syntax(), vec!["test".to_string()]);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .starts_with("expected an error containing 'unexpected argument'"));
+    }
+
+    #[test]
+    fn help_does_not_exit() {
+        let mut test_case = case(&["--help"], SyntaxTestExpect::Error);
+        test_case.error_contains = Some("Usage".to_string());
+
+        assert_eq!(
+            test_case.run(&env_syntax(), vec!["test".to_string()]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn from_config_value() {
+        let command = command_with_tests(
+            r#"
+              - args: ["--env", "prod"]
+                expect: ok
+                expect_env:
+                  OMNI_ARG_ENV_VALUE: prod
+              - args: ["--env", "bogus"]
+                error_contains: "possible values"
+            "#,
+        );
+
+        assert_eq!(command.tests.len(), 2);
+        assert_eq!(command.tests[0].expect, SyntaxTestExpect::Ok);
+        assert_eq!(
+            command.tests[0].expect_env.get("OMNI_ARG_ENV_VALUE"),
+            Some(&"prod".to_string())
+        );
+        assert_eq!(command.tests[1].expect, SyntaxTestExpect::Error);
+        assert_eq!(
+            command.tests[1].error_contains,
+            Some("possible values".to_string())
+        );
+    }
+
+    #[test]
+    fn run_syntax_tests() {
+        let command = command_with_tests(
+            r#"
+              - args: ["--env", "prod"]
+                expect_env:
+                  OMNI_ARG_ENV_VALUE: prod
+              - args: ["--env", "bogus"]
+                expect: ok
+              - args: ["--env", "bogus"]
+                expect: error
+                error_contains: "possible values"
+            "#,
+        );
+
+        let results = command.run_syntax_tests(vec!["deploy".to_string()]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].index, 0);
+        assert!(results[0].result.is_ok());
+        assert_eq!(results[1].index, 1);
+        assert_eq!(results[1].args, vec!["--env", "bogus"]);
+        assert!(results[1].result.is_err());
+        assert!(results[2].result.is_ok());
+    }
+
+    #[test]
+    fn invalid_expect() {
+        let config_value =
+            ConfigValue::from_str("run: 'true'\ntests:\n  - args: []\n    expect: maybe\n")
+                .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+
+        assert!(command.tests.is_empty());
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+    }
+}
+
+mod command_syntax_help {
+    use super::*;
+
+    fn syntax_with_group(group: SyntaxGroup) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--param2".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--other".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["file".to_string()],
+                    required: true,
+                    ..Default::default()
+                },
+            ],
+            groups: vec![group],
+            ..Default::default()
+        }
+    }
+
+    fn param<'a>(syntax: &'a CommandSyntax, name: &str) -> &'a SyntaxOptArg {
+        syntax
+            .parameters
+            .iter()
+            .find(|param| param.name() == name)
+            .expect("parameter not found")
+    }
+
+    #[test]
+    fn required_exclusive_group() {
+        let _colors = disable_colors();
+
+        let syntax = syntax_with_group(SyntaxGroup {
+            name: "source".to_string(),
+            parameters: vec!["param1".to_string(), "param2".to_string()],
+            required: true,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            syntax.generated_usage(),
+            " [OPTIONS] <--param1 <PARAM1> | --param2> <FILE>"
+        );
+        assert_eq!(
+            syntax.parameter_help_desc(param(&syntax, "--param1")),
+            "[group: source (required, exclusive)]"
+        );
+        assert_eq!(
+            syntax.parameter_help_desc(param(&syntax, "--param2")),
+            "[group: source (required, exclusive)]"
+        );
+        assert_eq!(syntax.parameter_help_desc(param(&syntax, "--other")), "");
+    }
+
+    #[test]
+    fn non_required_group() {
+        let _colors = disable_colors();
+
+        let syntax = syntax_with_group(SyntaxGroup {
+            name: "source".to_string(),
+            parameters: vec!["param1".to_string(), "param2".to_string()],
+            multiple: true,
+            ..Default::default()
+        });
+
+        assert_eq!(syntax.generated_usage(), " [OPTIONS] <FILE>");
+        assert_eq!(
+            syntax.parameter_help_desc(param(&syntax, "--param1")),
+            "[group: source (optional, multiple)]"
+        );
+        assert_eq!(syntax.parameter_help_desc(param(&syntax, "--other")), "");
+    }
+
+    #[test]
+    fn overlapping_and_nested_groups() {
+        let _colors = disable_colors();
+
+        let mut syntax = syntax_with_group(SyntaxGroup {
+            name: "first".to_string(),
+            parameters: vec!["param1".to_string(), "param2".to_string()],
+            required: true,
+            ..Default::default()
+        });
+        syntax.groups.push(SyntaxGroup {
+            name: "second".to_string(),
+            parameters: vec!["first".to_string(), "other".to_string()],
+            required: true,
+            ..Default::default()
+        });
+
+        // Parameters only show once in the usage, in the first group
+        // they are a member of
+        assert_eq!(
+            syntax.generated_usage(),
+            " <--param1 <PARAM1> | --param2> <--other <OTHER>> <FILE>"
+        );
+        assert_eq!(
+            syntax.parameter_help_desc(param(&syntax, "--param1")),
+            "[group: first (required, exclusive)] [group: second (required, exclusive)]"
+        );
+        assert_eq!(
+            syntax.parameter_help_desc(param(&syntax, "--other")),
+            "[group: second (required, exclusive)]"
+        );
+    }
+
+    fn sectioned_syntax(sections: &[&str]) -> CommandSyntax {
+        let option = |name: &str, section: Option<&str>| SyntaxOptArg {
+            names: vec![name.to_string()],
+            section: section.map(|section| section.to_string()),
+            ..Default::default()
+        };
+
+        CommandSyntax {
+            parameters: vec![
+                option("--verbose", None),
+                option("--proxy", Some("Networking")),
+                SyntaxOptArg {
+                    names: vec!["target".to_string()],
+                    section: Some("Networking".to_string()),
+                    ..Default::default()
+                },
+                option("--format", Some("Output")),
+                option("--timeout", Some("Networking")),
+                option("--token", Some("Authentication")),
+                option("--quiet", None),
+                option("--color", Some("Output")),
+            ],
+            sections: sections.iter().map(|section| section.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn section_names(syntax: &CommandSyntax) -> Vec<(String, Vec<String>)> {
+        syntax
+            .help_sections(syntax.parameters.iter())
+            .into_iter()
+            .map(|(section, params)| {
+                (
+                    section,
+                    params.iter().map(|param| param.name()).collect::<Vec<_>>(),
+                )
+            })
+            .collect()
+    }
+
+    fn expected_sections(sections: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+        sections
+            .iter()
+            .map(|(section, names)| {
+                (
+                    section.to_string(),
+                    names.iter().map(|name| name.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sections_in_order_of_first_appearance() {
+        let syntax = sectioned_syntax(&[]);
+
+        assert_eq!(
+            section_names(&syntax),
+            expected_sections(&[
+                ("Arguments", &["target"]),
+                ("Networking", &["--proxy", "--timeout"]),
+                ("Output", &["--format", "--color"]),
+                ("Authentication", &["--token"]),
+                ("Options", &["--verbose", "--quiet"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn sections_in_explicit_order() {
+        // Sections that are not listed follow the listed ones, and
+        // listed sections without any option are omitted
+        let syntax = sectioned_syntax(&["Authentication", "Unused", "Output"]);
+
+        assert_eq!(
+            section_names(&syntax),
+            expected_sections(&[
+                ("Arguments", &["target"]),
+                ("Authentication", &["--token"]),
+                ("Output", &["--format", "--color"]),
+                ("Networking", &["--proxy", "--timeout"]),
+                ("Options", &["--verbose", "--quiet"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn default_section_in_explicit_order() {
+        let syntax = sectioned_syntax(&["Options", "Output"]);
+
+        assert_eq!(
+            section_names(&syntax),
+            expected_sections(&[
+                ("Arguments", &["target"]),
+                ("Options", &["--verbose", "--quiet"]),
+                ("Output", &["--format", "--color"]),
+                ("Networking", &["--proxy", "--timeout"]),
+                ("Authentication", &["--token"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn default_section_only() {
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--verbose".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--quiet".to_string()],
+                    section: Some("Options".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            section_names(&syntax),
+            expected_sections(&[("Options", &["--verbose", "--quiet"])])
+        );
+    }
+
+    #[test]
+    fn sections_from_config_value() {
+        let config_value = ConfigValue::from_str(concat!(
+            "sections: [Output, Networking]\n",
+            "options:\n",
+            "  - --proxy: {desc: the proxy, section: Networking}\n",
+            "  - --format: {section: Output}\n",
+            "  - --verbose\n",
+        ))
+        .unwrap();
+        let syntax = CommandSyntax::from_config_value(&config_value, &ConfigErrorHandler::new())
+            .expect("should parse the syntax");
+
+        assert_eq!(
+            syntax.sections,
+            vec!["Output".to_string(), "Networking".to_string()]
+        );
+        assert_eq!(
+            section_names(&syntax),
+            expected_sections(&[
+                ("Output", &["--format"]),
+                ("Networking", &["--proxy"]),
+                ("Options", &["--verbose"]),
+            ])
+        );
+    }
+
+    fn global_option(name: &str, origin: SyntaxOptArgOrigin) -> SyntaxOptArg {
+        SyntaxOptArg {
+            names: vec![name.to_string()],
+            origin,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn global_options_after_command_options() {
+        let mut syntax = sectioned_syntax(&[]);
+        syntax
+            .parameters
+            .insert(0, global_option("--profile", SyntaxOptArgOrigin::Inherited));
+        syntax
+            .parameters
+            .push(global_option("--dry-run", SyntaxOptArgOrigin::Common));
+        let help_option = CommandSyntax::help_option();
+
+        let sections = syntax
+            .help_sections(
+                syntax
+                    .parameters
+                    .iter()
+                    .chain(std::iter::once(&help_option)),
+            )
+            .into_iter()
+            .map(|(section, params)| {
+                (
+                    section,
+                    params.iter().map(|param| param.name()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            sections,
+            expected_sections(&[
+                ("Arguments", &["target"]),
+                ("Networking", &["--proxy", "--timeout"]),
+                ("Output", &["--format", "--color"]),
+                ("Authentication", &["--token"]),
+                ("Command options", &["--verbose", "--quiet"]),
+                ("Global options", &["--profile", "--dry-run", "--help"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn global_options_with_default_section_in_explicit_order() {
+        let mut syntax = sectioned_syntax(&["Options", "Output"]);
+        syntax
+            .parameters
+            .push(global_option("--dry-run", SyntaxOptArgOrigin::Common));
+
+        assert_eq!(
+            section_names(&syntax),
+            expected_sections(&[
+                ("Arguments", &["target"]),
+                ("Command options", &["--verbose", "--quiet"]),
+                ("Output", &["--format", "--color"]),
+                ("Networking", &["--proxy", "--timeout"]),
+                ("Authentication", &["--token"]),
+                ("Global options", &["--dry-run"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn global_options_only() {
+        let syntax = CommandSyntax::default();
+        let help_option = CommandSyntax::help_option();
+
+        let sections = syntax.help_sections(std::iter::once(&help_option));
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].0, "Global options");
+        assert_eq!(sections[0].1[0].origin, SyntaxOptArgOrigin::Reserved);
+    }
+
+    #[test]
+    fn origin_classification() {
+        assert!(SyntaxOptArgOrigin::Command.is_command());
+        assert!(!SyntaxOptArgOrigin::Command.is_global());
+        for origin in [
+            SyntaxOptArgOrigin::Reserved,
+            SyntaxOptArgOrigin::Common,
+            SyntaxOptArgOrigin::Inherited,
+        ] {
+            assert!(origin.is_global(), "{origin:?}");
+        }
+
+        // Parameters from the configuration are always defined by the command
+        let config_value = ConfigValue::from_str("options: [--verbose]").unwrap();
+        let syntax = CommandSyntax::from_config_value(&config_value, &ConfigErrorHandler::new())
+            .expect("should parse the syntax");
+        assert_eq!(syntax.parameters[0].origin, SyntaxOptArgOrigin::Command);
+
+        assert_eq!(
+            CommandSyntax::help_option().origin,
+            SyntaxOptArgOrigin::Reserved
+        );
+    }
+
+    #[test]
+    fn origin_serialization() {
+        let value = serde_json::to_value(global_option("--dry-run", SyntaxOptArgOrigin::Common))
+            .expect("should serialize");
+        assert_eq!(value["origin"], "common");
+
+        let value = serde_json::to_value(SyntaxOptArg {
+            names: vec!["--verbose".to_string()],
+            ..Default::default()
+        })
+        .expect("should serialize");
+        assert!(value.get("origin").is_none());
+    }
+
+    #[test]
+    fn usage_only_shows_required_command_options() {
+        let _colors = disable_colors();
+
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--name".to_string()],
+                    required: true,
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--profile".to_string()],
+                    required: true,
+                    origin: SyntaxOptArgOrigin::Inherited,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(syntax.generated_usage(), " [OPTIONS] --name <NAME>");
+    }
+
+    #[test]
+    fn no_color_disables_colors_in_help() {
+        temp_env::with_vars(
+            [("NO_COLOR", Some("1")), ("CLICOLOR_FORCE", Some("1"))],
+            || {
+                let syntax = CommandSyntax {
+                    parameters: vec![
+                        SyntaxOptArg {
+                            names: vec!["--mode".to_string(), "-m".to_string()],
+                            desc: Some("The mode to use".to_string()),
+                            required: true,
+                            env: Some("MODE".to_string()),
+                            default: Some("fast".to_string()),
+                            arg_type: SyntaxOptArgType::Enum(vec![
+                                "fast".to_string(),
+                                "slow".to_string(),
+                            ]),
+                            examples: vec!["slow".to_string()],
+                            ..Default::default()
+                        },
+                        SyntaxOptArg {
+                            names: vec!["file".to_string()],
+                            required: true,
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                };
+
+                assert!(!ColorCapability::current().enabled());
+
+                let mut rendered = vec![syntax.generated_usage()];
+                for param in &syntax.parameters {
+                    rendered.push(param.usage());
+                    rendered.push(param.help_name(true, ColorCapability::current().enabled()));
+                    rendered.push(param.help_desc());
+                    rendered.push(syntax.parameter_help_desc(param));
+                }
+
+                for output in rendered {
+                    assert!(
+                        !output.contains('\x1B'),
+                        "unexpected color codes in {output:?}"
+                    );
+                }
+            },
+        );
+    }
+}
+
+mod completion {
+    use super::*;
+
+    fn deploy_syntax() -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--cluster".to_string(), "-c".to_string()],
+                    desc: Some("The cluster to deploy to".to_string()),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--log-level".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec!["debug".to_string(), "info".to_string()]),
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["target".to_string()],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn called_as(name: &str) -> Vec<String> {
+        name.split(' ').map(|part| part.to_string()).collect()
+    }
+
+    /// Returns the completion script of the given shell for the given
+    /// syntax, as the `deploy` command of omni
+    fn deploy_completion(shell: &Shell, syntax: &CommandSyntax) -> Result<String, String> {
+        CommandSyntax::completion_script(shell, "omni", [(called_as("deploy"), syntax)])
+    }
+
+    #[test]
+    fn fish_completes_the_options_of_the_command() {
+        let script = deploy_completion(&Shell::Fish, &deploy_syntax())
+            .expect("should generate the completions");
+
+        assert!(script.contains("deploy"), "unexpected script: {script}");
+        assert!(script.contains("-l cluster"), "unexpected script: {script}");
+        assert!(script.contains("-s c"), "unexpected script: {script}");
+        assert!(script.contains("debug"), "unexpected script: {script}");
+        assert!(
+            script.contains("The cluster to deploy to"),
+            "unexpected script: {script}"
+        );
+    }
+
+    #[test]
+    fn bash_and_zsh_complete_the_options_of_the_command() {
+        for shell in [Shell::Bash, Shell::Zsh] {
+            let script = deploy_completion(&shell, &deploy_syntax())
+                .expect("should generate the completions");
+
+            assert!(script.contains("omni"), "unexpected script: {script}");
+            assert!(script.contains("--cluster"), "unexpected script: {script}");
+            assert!(
+                script.contains("--log-level"),
+                "unexpected script: {script}"
+            );
+        }
+    }
+
+    #[test]
+    fn script_nests_the_subcommands() {
+        let empty = CommandSyntax::default();
+        let deploy = deploy_syntax();
+        let script = CommandSyntax::completion_script(
+            &Shell::Fish,
+            "omni",
+            [
+                (called_as("tools deploy"), &deploy),
+                (called_as("tools"), &empty),
+                (called_as("status"), &empty),
+            ],
+        )
+        .expect("should generate the completions");
+
+        assert!(
+            script.contains("-a \"tools\""),
+            "unexpected script: {script}"
+        );
+        assert!(
+            script.contains("-a \"status\""),
+            "unexpected script: {script}"
+        );
+        assert!(
+            script.contains("-a \"deploy\""),
+            "unexpected script: {script}"
+        );
+        assert!(script.contains("-l cluster"), "unexpected script: {script}");
+    }
+
+    #[test]
+    fn invalid_parameters_are_not_completed() {
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--help".to_string()],
+                    ..Default::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--cluster".to_string()],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let script =
+            deploy_completion(&Shell::Bash, &syntax).expect("should generate the completions");
+        assert!(script.contains("deploy"), "unexpected script: {script}");
+        assert!(!script.contains("--cluster"), "unexpected script: {script}");
+    }
+
+    #[test]
+    fn unsupported_shell() {
+        assert_eq!(
+            deploy_completion(&Shell::Posix, &deploy_syntax()),
+            Err("completions cannot be generated for posix".to_string())
+        );
+    }
+}
+
+mod completion_context {
+    use super::*;
+
+    fn enum_type(values: &[&str]) -> SyntaxOptArgType {
+        SyntaxOptArgType::Enum(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    fn syntax(with_last: bool) -> CommandSyntax {
+        let mut parameters = vec![
+            SyntaxOptArg {
+                names: vec!["--env".to_string(), "-e".to_string()],
+                arg_type: enum_type(&["production", "preview", "staging"]),
+                ..Default::default()
+            },
+            SyntaxOptArg {
+                names: vec!["--dry-run".to_string()],
+                arg_type: SyntaxOptArgType::Flag,
+                ..Default::default()
+            },
+            SyntaxOptArg {
+                names: vec!["--tags".to_string()],
+                arg_type: SyntaxOptArgType::Array(Box::new(enum_type(&["alpha", "beta", "gamma"]))),
+                value_delimiter: Some(','),
+                ..Default::default()
+            },
+            SyntaxOptArg {
+                names: vec!["--config".to_string()],
+                arg_type: SyntaxOptArgType::FilePath,
+                ..Default::default()
+            },
+            SyntaxOptArg {
+                names: vec!["--out".to_string()],
+                arg_type: SyntaxOptArgType::DirPath,
+                ..Default::default()
+            },
+            SyntaxOptArg {
+                names: vec!["target".to_string()],
+                arg_type: enum_type(&["dev", "prod"]),
+                ..Default::default()
+            },
+        ];
+        if with_last {
+            parameters.push(SyntaxOptArg {
+                names: vec!["rest".to_string()],
+                arg_type: SyntaxOptArgType::FilePath,
+                last_arg_double_hyphen: true,
+                ..Default::default()
+            });
+        }
+
+        CommandSyntax {
+            parameters,
+            ..Default::default()
+        }
+    }
+
+    fn context(syntax: &CommandSyntax, words: &[&str]) -> Option<SyntaxCompletionContext> {
+        let words = words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>();
+        syntax.completion_context(&words, words.len() - 1)
+    }
+
+    fn options(syntax: &CommandSyntax, words: &[&str]) -> Vec<String> {
+        let context = context(syntax, words).expect("context should be built");
+        if !context.state.complete_parameters() {
+            return vec![];
+        }
+
+        context
+            .option_candidates(words[words.len() - 1])
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    fn values(syntax: &CommandSyntax, words: &[&str]) -> Vec<String> {
+        context(syntax, words)
+            .and_then(|context| context.state.parameter())
+            .map(|(param, _)| param.completion_values(words[words.len() - 1]))
+            .unwrap_or_default()
+    }
+
+    fn parameter_type(syntax: &CommandSyntax, words: &[&str]) -> Option<SyntaxOptArgType> {
+        context(syntax, words)
+            .and_then(|context| context.state.parameter())
+            .map(|(param, _)| param.arg_type().terminal_type().clone())
+    }
+
+    #[test]
+    fn options_when_word_starts_with_hyphen() {
+        let syntax = syntax(true);
+
+        assert_eq!(
+            options(&syntax, &["-"]),
+            vec!["--config", "--dry-run", "--env", "--out", "--tags", "-e"]
+        );
+        assert_eq!(options(&syntax, &["--e"]), vec!["--env"]);
+        assert!(context(&syntax, &["-"])
+            .expect("context should be built")
+            .last
+            .is_some());
+    }
+
+    #[test]
+    fn options_already_provided_are_not_completed() {
+        let syntax = syntax(true);
+
+        assert_eq!(
+            options(&syntax, &["--dry-run", "--d"]),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn enum_option_values() {
+        let syntax = syntax(true);
+
+        assert_eq!(
+            values(&syntax, &["--env", ""]),
+            vec!["production", "preview", "staging"]
+        );
+        assert_eq!(values(&syntax, &["--env", "st"]), vec!["staging"]);
+        assert_eq!(values(&syntax, &["-e", "p"]), vec!["production", "preview"]);
+    }
+
+    #[test]
+    fn enum_option_values_with_delimiter() {
+        let syntax = syntax(true);
+
+        assert_eq!(
+            values(&syntax, &["--tags", "alpha,"]),
+            vec!["alpha,alpha", "alpha,beta", "alpha,gamma"]
+        );
+        assert_eq!(values(&syntax, &["--tags", "alpha,b"]), vec!["alpha,beta"]);
+        assert_eq!(values(&syntax, &["--tags", "g"]), vec!["gamma"]);
+    }
+
+    #[test]
+    fn path_option_values() {
+        let syntax = syntax(true);
+
+        assert_eq!(
+            parameter_type(&syntax, &["--config", ""]),
+            Some(SyntaxOptArgType::FilePath)
+        );
+        assert_eq!(
+            parameter_type(&syntax, &["--out", "sr"]),
+            Some(SyntaxOptArgType::DirPath)
+        );
+    }
+
+    #[test]
+    fn positional_values() {
+        let syntax = syntax(true);
+
+        assert_eq!(values(&syntax, &[""]), vec!["dev", "prod"]);
+        assert_eq!(values(&syntax, &["--dry-run", "p"]), vec!["prod"]);
+    }
+
+    #[test]
+    fn after_double_hyphen() {
+        assert_eq!(
+            parameter_type(&syntax(true), &["--env", "preview", "--", ""]),
+            Some(SyntaxOptArgType::FilePath)
+        );
+        assert_eq!(parameter_type(&syntax(false), &["--", ""]), None);
+    }
+}
+
+mod syntax_opt_arg_variants {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn dispatch_syntax() -> CommandSyntax {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: target\n",
+            "      required: true\n",
+            "      variants:\n",
+            "        - name: filter\n",
+            "          pattern: '^\\w+='\n",
+            "        - name: id\n",
+            "          pattern: '^[0-9]+$'\n",
+            "        - name: query\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        command.syntax.expect("syntax should be defined")
+    }
+
+    fn parse(syntax: &CommandSyntax, argv: &[&str]) -> BTreeMap<String, String> {
+        syntax
+            .parse_args(
+                argv.iter().map(|s| s.to_string()).collect(),
+                vec!["test".to_string()],
+            )
+            .expect("should parse")
+    }
+
+    #[test]
+    fn first_variant_matches() {
+        let args = parse(&dispatch_syntax(), &["name=omni"]);
+
+        assert_eq!(
+            args.get("OMNI_ARG_FILTER_VALUE"),
+            Some(&"name=omni".to_string())
+        );
+        assert_eq!(args.get("OMNI_ARG_LIST"), Some(&"filter".to_string()));
+        assert!(!args.contains_key("OMNI_ARG_ID_TYPE"));
+        assert!(!args.contains_key("OMNI_ARG_QUERY_TYPE"));
+        assert!(!args.contains_key("OMNI_ARG_TARGET_TYPE"));
+    }
+
+    #[test]
+    fn second_variant_matches() {
+        let args = parse(&dispatch_syntax(), &["42"]);
+
+        assert_eq!(args.get("OMNI_ARG_ID_VALUE"), Some(&"42".to_string()));
+        assert_eq!(args.get("OMNI_ARG_LIST"), Some(&"id".to_string()));
+        assert!(!args.contains_key("OMNI_ARG_FILTER_TYPE"));
+        assert!(!args.contains_key("OMNI_ARG_QUERY_TYPE"));
+    }
+
+    #[test]
+    fn overlapping_patterns_resolve_by_order() {
+        // Matches both the filter and the catch-all query variants
+        let args = parse(&dispatch_syntax(), &["42=answer"]);
+
+        assert_eq!(
+            args.get("OMNI_ARG_FILTER_VALUE"),
+            Some(&"42=answer".to_string())
+        );
+        assert_eq!(args.get("OMNI_ARG_LIST"), Some(&"filter".to_string()));
+    }
+
+    #[test]
+    fn no_match_falls_to_catch_all() {
+        let args = parse(&dispatch_syntax(), &["some query"]);
+
+        assert_eq!(
+            args.get("OMNI_ARG_QUERY_VALUE"),
+            Some(&"some query".to_string())
+        );
+        assert_eq!(args.get("OMNI_ARG_LIST"), Some(&"query".to_string()));
+        assert!(!args.contains_key("OMNI_ARG_FILTER_TYPE"));
+        assert!(!args.contains_key("OMNI_ARG_ID_TYPE"));
+    }
+
+    #[test]
+    fn required_applies_to_dispatch() {
+        let result = dispatch_syntax().parse_args(vec![], vec!["test".to_string()]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn optional_dispatch_not_provided() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: target\n",
+            "      variants:\n",
+            "        - name: filter\n",
+            "          pattern: '^\\w+='\n",
+            "        - query\n",
+            "    - --verbose\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        let args = parse(&command.syntax.expect("syntax should be defined"), &[]);
+
+        assert_eq!(args.get("OMNI_ARG_LIST"), Some(&"verbose".to_string()));
+        assert!(!args.contains_key("OMNI_ARG_FILTER_TYPE"));
+        assert!(!args.contains_key("OMNI_ARG_QUERY_TYPE"));
+    }
+
+    #[test]
+    fn no_match_without_catch_all() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: target\n",
+            "      variants:\n",
+            "        - name: filter\n",
+            "          pattern: '^\\w+='\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let result = command
+            .syntax
+            .expect("syntax should be defined")
+            .parse_args(vec!["query".to_string()], vec!["test".to_string()]);
+        assert_eq!(
+            result.expect_err("should fail").simple(),
+            "invalid value 'query' for 'target': expected a value matching one of: ^\\w+=",
+        );
+    }
+
+    #[test]
+    fn required_without_catch_all() {
+        let (_command, errors) = command_from_yaml(concat!(
+            "    - name: target\n",
+            "      required: true\n",
+            "      variants:\n",
+            "        - name: filter\n",
+            "          pattern: '^\\w+='\n",
+            "        - name: id\n",
+            "          pattern: '^[0-9]+$'\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::UnsupportedValueInContext);
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: target\n",
+            "      variants:\n",
+            "        - name: filter\n",
+            "          pattern: '^(\\w+='\n",
+            "        - query\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::ParsingError);
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert_eq!(syntax.parameters[0].variants.len(), 1);
+        assert_eq!(syntax.parameters[0].variants[0].name, "query");
+    }
+
+    #[test]
+    fn variants_on_option() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --target\n",
+            "      variants:\n",
+            "        - query\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::UnsupportedValueInContext);
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(syntax.parameters[0].variants.is_empty());
+    }
+}
+
+mod parse_args_error_code {
+    use super::*;
+
+    fn syntax() -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--param1".to_string()],
+                    arg_type: SyntaxOptArgType::String,
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--param2".to_string(), "-p".to_string()],
+                    arg_type: SyntaxOptArgType::Integer,
+                    conflicts_with: vec!["param3".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--param3".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["target".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec!["a".to_string(), "b".to_string()]),
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn parse_error(syntax: &CommandSyntax, argv: &[&str]) -> ParseArgsErrorKind {
+        match syntax.parse_args(
+            argv.iter().map(|s| s.to_string()).collect(),
+            vec!["test".to_string()],
+        ) {
+            Ok(_) => panic!("case with args {argv:?} should have failed but succeeded"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn clap_errors_map_to_codes() {
+        let syntax = syntax();
+
+        let expectations: Vec<(&[&str], &str, Option<&str>)> = vec![
+            (&[], "E_ARG_MISSING_REQUIRED", Some("param1")),
+            (
+                &["--param1", "x", "--param2", "abc"],
+                "E_ARG_INVALID_VALUE",
+                Some("param2"),
+            ),
+            (
+                &["--param1", "x", "c"],
+                "E_ARG_INVALID_VALUE",
+                Some("target"),
+            ),
+            (&["--param1", "x", "--unknown"], "E_ARG_UNEXPECTED", None),
+            (
+                &["--param1", "x", "-p", "1", "--param3"],
+                "E_ARG_CONFLICT",
+                Some("param2"),
+            ),
+        ];
+
+        for (argv, code, dest) in expectations {
+            let err = parse_error(&syntax, argv);
+            assert_eq!((argv, err.code().as_str()), (argv, code), "error: {err}");
+            assert_eq!(
+                (argv, syntax.error_dest(&err)),
+                (argv, dest.map(|dest| dest.to_string())),
+                "error: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn clap_error_kinds() {
+        let expectations = vec![
+            (
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "E_ARG_MISSING_REQUIRED",
+            ),
+            (
+                clap::error::ErrorKind::TooFewValues,
+                "E_ARG_MISSING_REQUIRED",
+            ),
+            (
+                clap::error::ErrorKind::ValueValidation,
+                "E_ARG_INVALID_VALUE",
+            ),
+            (
+                clap::error::ErrorKind::WrongNumberOfValues,
+                "E_ARG_INVALID_VALUE",
+            ),
+            (clap::error::ErrorKind::UnknownArgument, "E_ARG_UNEXPECTED"),
+            (clap::error::ErrorKind::ArgumentConflict, "E_ARG_CONFLICT"),
+            (clap::error::ErrorKind::Io, "E_ARG_OTHER"),
+        ];
+
+        for (kind, code) in expectations {
+            let err = ParseArgsErrorKind::ArgumentParsingError(clap::Error::new(kind));
+            assert_eq!((kind, err.code().as_str()), (kind, code));
+        }
+    }
+
+    #[test]
+    fn omni_errors_map_to_codes() {
+        assert_eq!(
+            ParseArgsErrorKind::ParserBuildError(vec!["failed".to_string()])
+                .code()
+                .as_str(),
+            "E_PARSER_BUILD"
+        );
+        assert_eq!(
+            ParseArgsErrorKind::InvalidValue("invalid".to_string())
+                .code()
+                .as_str(),
+            "E_ARG_INVALID_VALUE"
+        );
+    }
+
+    #[test]
+    fn json_missing_required() {
+        let syntax = syntax();
+        let err = parse_error(&syntax, &[]);
+
+        assert_eq!(
+            err.json(syntax.error_dest(&err)),
+            concat!(
+                r#"{"code":"E_ARG_MISSING_REQUIRED","#,
+                r#""message":"the following required arguments were not provided: --param1 <param1>","#,
+                r#""dest":"param1"}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn json_without_dest() {
+        let err = ParseArgsErrorKind::ParserBuildError(vec!["failed to build".to_string()]);
+
+        assert_eq!(
+            err.json(None),
+            r#"{"code":"E_PARSER_BUILD","message":"failed to build","dest":null}"#
+        );
+    }
+}
+
+mod lint_arg_references {
+    use super::*;
+
+    fn command_from_yaml(run: &str, parameters: &str) -> CommandDefinition {
+        let yaml = format!(
+            "argparser: true\nrun: {run}\nsyntax:\n  parameters:\n{parameters}",
+            run = serde_json::to_string(run).expect("failed to serialize run"),
+        );
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        assert!(
+            error_handler.errors().is_empty(),
+            "unexpected errors: {:?}",
+            error_handler.errors()
+        );
+        command
+    }
+
+    #[test]
+    fn all_references_declared() {
+        let command = command_from_yaml(
+            "echo \"$OMNI_ARG_ENV_VALUE\" \"${OMNI_ARG_DRY_RUN_VALUE}\"",
+            "    - name: --env\n    - name: --dry-run\n      type: flag\n",
+        );
+
+        assert_eq!(command.lint_arg_references(), vec![]);
+    }
+
+    #[test]
+    fn undeclared_reference() {
+        let command = command_from_yaml(
+            "echo \"$OMNI_ARG_ENV_VALUE\" \"$OMNI_ARG_ENVIRONMENT_VALUE\" \"$OMNI_ARG_ENVIRONMENT_TYPE\"",
+            "    - name: --env\n",
+        );
+
+        assert_eq!(
+            command.lint_arg_references(),
+            vec![ArgReferenceLint::UndeclaredReference(
+                "ENVIRONMENT".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn unused_parameter() {
+        let command = command_from_yaml(
+            "echo \"$OMNI_ARG_ENV_VALUE\"",
+            "    - name: --env\n    - name: --verbose\n      type: flag\n",
+        );
+
+        assert_eq!(
+            command.lint_arg_references(),
+            vec![ArgReferenceLint::UnusedParameter("verbose".to_string())]
+        );
+    }
+
+    #[test]
+    fn array_and_variant_references() {
+        let command = command_from_yaml(
+            "echo \"$OMNI_ARG_FILES_VALUE_0\" \"$OMNI_ARG_ID_VALUE\"",
+            concat!(
+                "    - name: --files\n",
+                "      type: array/str\n",
+                "    - name: target\n",
+                "      variants:\n",
+                "        - name: id\n",
+                "          pattern: '^[0-9]+$'\n",
+                "        - name: query\n",
+            ),
+        );
+
+        assert_eq!(command.lint_arg_references(), vec![]);
+    }
+
+    #[test]
+    fn arg_list_skips_unused_parameters() {
+        let command = command_from_yaml(
+            "for arg in $OMNI_ARG_LIST; do echo $arg; done",
+            "    - name: --env\n",
+        );
+
+        assert_eq!(command.lint_arg_references(), vec![]);
+    }
+
+    #[test]
+    fn disabled_without_argparser() {
+        let mut command =
+            command_from_yaml("echo \"$OMNI_ARG_OTHER_VALUE\"", "    - name: --env\n");
+        command.argparser = false;
+
+        assert_eq!(command.lint_arg_references(), vec![]);
+    }
+}
+
+mod default_from_config {
+    use super::*;
+
+    use crate::internal::testutils::run_with_env;
+
+    const CONFIG: &str = "deploy:\n  region: eu-west-1\n  replicas: 3\n  zones: [a, b]\n";
+
+    fn with_config<F: FnOnce()>(closure: F) {
+        let config_file = tempfile::Builder::new()
+            .suffix(".yaml")
+            .tempfile()
+            .expect("failed to create config file");
+        std::fs::write(config_file.path(), CONFIG).expect("failed to write config file");
+
+        run_with_env(
+            &[(
+                "OMNI_CONFIG".to_string(),
+                Some(config_file.path().to_string_lossy().to_string()),
+            )],
+            closure,
+        );
+    }
+
+    fn syntax_with(param: SyntaxOptArg) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![param],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn parse(syntax: &CommandSyntax, argv: &[&str]) -> BTreeMap<String, ParseArgsValue> {
+        syntax
+            .parse_args_typed(
+                argv.iter().map(|arg| arg.to_string()).collect(),
+                vec!["test".to_string()],
+            )
+            .expect("should parse the arguments")
+    }
+
+    #[test]
+    fn test_parse_config_value() {
+        let config_value = ConfigValue::from_str(
+            "--region: {desc: the region, default_from_config: deploy.region, default: us-east-1}",
+        )
+        .unwrap();
+        let param =
+            SyntaxOptArg::from_config_value(&config_value, None, &ConfigErrorHandler::new())
+                .expect("should parse the parameter");
+
+        assert_eq!(param.default_from_config, Some("deploy.region".to_string()));
+        assert_eq!(param.default, Some("us-east-1".to_string()));
+    }
+
+    #[test]
+    fn test_default_resolved_from_config() {
+        with_config(|| {
+            let syntax = syntax_with(SyntaxOptArg {
+                names: vec!["--region".to_string()],
+                default_from_config: Some("deploy.region".to_string()),
+                default: Some("us-east-1".to_string()),
+                ..SyntaxOptArg::default()
+            });
+
+            let args = parse(&syntax, &[]);
+            assert_eq!(
+                args.get("region"),
+                Some(&ParseArgsValue::SingleString(Some("eu-west-1".to_string())))
+            );
+        });
+    }
+
+    #[test]
+    fn test_default_resolved_from_config_with_type() {
+        with_config(|| {
+            let syntax = syntax_with(SyntaxOptArg {
+                names: vec!["--replicas".to_string()],
+                arg_type: SyntaxOptArgType::Integer,
+                default_from_config: Some("deploy.replicas".to_string()),
+                ..SyntaxOptArg::default()
+            });
+
+            let args = parse(&syntax, &[]);
+            assert_eq!(
+                args.get("replicas"),
+                Some(&ParseArgsValue::SingleInteger(Some(3)))
+            );
+        });
+    }
+
+    #[test]
+    fn test_cli_value_takes_precedence() {
+        with_config(|| {
+            let syntax = syntax_with(SyntaxOptArg {
+                names: vec!["--region".to_string()],
+                default_from_config: Some("deploy.region".to_string()),
+                ..SyntaxOptArg::default()
+            });
+
+            let args = parse(&syntax, &["--region", "ap-south-1"]);
+            assert_eq!(
+                args.get("region"),
+                Some(&ParseArgsValue::SingleString(Some(
+                    "ap-south-1".to_string()
+                )))
+            );
+        });
+    }
+
+    #[test]
+    fn test_absent_key_falls_back_to_default() {
+        with_config(|| {
+            let syntax = syntax_with(SyntaxOptArg {
+                names: vec!["--region".to_string()],
+                default_from_config: Some("deploy.missing".to_string()),
+                default: Some("us-east-1".to_string()),
+                ..SyntaxOptArg::default()
+            });
+
+            let args = parse(&syntax, &[]);
+            assert_eq!(
+                args.get("region"),
+                Some(&ParseArgsValue::SingleString(Some("us-east-1".to_string())))
+            );
+        });
+    }
+
+    #[test]
+    fn test_absent_key_without_default() {
+        with_config(|| {
+            let syntax = syntax_with(SyntaxOptArg {
+                names: vec!["--region".to_string()],
+                default_from_config: Some("other.region".to_string()),
+                ..SyntaxOptArg::default()
+            });
+
+            let args = parse(&syntax, &[]);
+            assert_eq!(
+                args.get("region"),
+                Some(&ParseArgsValue::SingleString(None))
+            );
+        });
+    }
+
+    #[test]
+    fn test_non_scalar_value_falls_back_to_default() {
+        with_config(|| {
+            let param = SyntaxOptArg {
+                names: vec!["--zone".to_string()],
+                default_from_config: Some("deploy.zones".to_string()),
+                default: Some("c".to_string()),
+                ..SyntaxOptArg::default()
+            };
+
+            assert_eq!(param.default_value(), Some("c".to_string()));
+        });
+    }
+}
+
+mod argparser_cache {
+    use super::*;
+
+    use crate::internal::testutils::run_with_env;
+
+    // Each test uses its own parameter names, so that the parsers built
+    // by the other tests running in parallel are not counted
+    fn syntax_with(name: &str) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec![format!("--{name}")],
+                    env: Some(format!("OMNI_TEST_ARGPARSER_CACHE_{}", name.to_uppercase())),
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--flag".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            groups: vec![SyntaxGroup {
+                name: "group".to_string(),
+                parameters: vec![name.to_string(), "flag".to_string()],
+                ..SyntaxGroup::default()
+            }],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn called_as() -> Vec<String> {
+        vec!["test".to_string()]
+    }
+
+    fn parse(syntax: &CommandSyntax, argv: &[&str]) -> BTreeMap<String, String> {
+        syntax
+            .parse_args(
+                argv.iter().map(|arg| arg.to_string()).collect(),
+                called_as(),
+            )
+            .expect("should parse the arguments")
+    }
+
+    #[test]
+    fn same_syntax_builds_parser_once() {
+        let syntax = syntax_with("reuse");
+
+        let first = parse(&syntax, &["--reuse", "one"]);
+        let second = parse(&syntax.clone(), &["--flag"]);
+
+        assert_eq!(first.get("OMNI_ARG_REUSE_VALUE"), Some(&"one".to_string()));
+        assert_eq!(second.get("OMNI_ARG_FLAG_VALUE"), Some(&"true".to_string()));
+        assert_eq!(syntax.argparser_builds(&called_as()), 1);
+    }
+
+    #[test]
+    fn different_syntax_builds_another_parser() {
+        let syntax = syntax_with("differ");
+        let mut other = syntax.clone();
+        other.parameters[1].desc = Some("A flag".to_string());
+
+        parse(&syntax, &[]);
+        parse(&other, &[]);
+        parse(&syntax, &[]);
+
+        assert_eq!(syntax.argparser_builds(&called_as()), 1);
+        assert_eq!(other.argparser_builds(&called_as()), 1);
+    }
+
+    #[test]
+    fn different_environment_builds_another_parser() {
+        let syntax = syntax_with("environ");
+
+        let args = parse(&syntax, &[]);
+        assert_eq!(args.get("OMNI_ARG_ENVIRON_VALUE"), None);
+
+        run_with_env(
+            &[(
+                "OMNI_TEST_ARGPARSER_CACHE_ENVIRON".to_string(),
+                Some("from-env".to_string()),
+            )],
+            || {
+                let args = parse(&syntax, &[]);
+                assert_eq!(
+                    args.get("OMNI_ARG_ENVIRON_VALUE"),
+                    Some(&"from-env".to_string())
+                );
+                assert_eq!(syntax.argparser_builds(&called_as()), 1);
+            },
+        );
+
+        assert_eq!(syntax.argparser_builds(&called_as()), 1);
+    }
+
+    #[test]
+    fn invalid_syntax_is_not_cached() {
+        let mut syntax = syntax_with("invalid");
+        syntax.parameters[1].names = vec!["--invalid".to_string()];
+
+        assert!(syntax.argparser(called_as()).is_err());
+        assert!(syntax.argparser(called_as()).is_err());
+        assert_eq!(syntax.argparser_builds(&called_as()), 0);
+    }
+}
+
+mod env_fallback {
+    use super::*;
+
+    use crate::internal::testutils::run_with_env;
+
+    const VAR: &str = "OMNI_TEST_ARG_FALLBACK";
+
+    fn with_var<F: FnOnce()>(value: Option<&str>, closure: F) {
+        run_with_env(
+            &[(VAR.to_string(), value.map(|value| value.to_string()))],
+            closure,
+        );
+    }
+
+    fn syntax_with(arg_type: SyntaxOptArgType, default: Option<&str>) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--value".to_string()],
+                arg_type,
+                env: Some(VAR.to_string()),
+                default: default.map(|default| default.to_string()),
+                ..SyntaxOptArg::default()
+            }],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn parse(
+        syntax: &CommandSyntax,
+        argv: &[&str],
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind> {
+        syntax.parse_args_typed(
+            argv.iter().map(|arg| arg.to_string()).collect(),
+            vec!["test".to_string()],
+        )
+    }
+
+    fn parse_value(syntax: &CommandSyntax, argv: &[&str]) -> ParseArgsValue {
+        parse(syntax, argv)
+            .expect("should parse the arguments")
+            .remove("value")
+            .expect("should have a value")
+    }
+
+    #[test]
+    fn test_parse_config_value() {
+        let config_value =
+            ConfigValue::from_str("--region: {desc: the region, env: REGION, default: us-east-1}")
+                .unwrap();
+        let param =
+            SyntaxOptArg::from_config_value(&config_value, None, &ConfigErrorHandler::new())
+                .expect("should parse the parameter");
+
+        assert_eq!(param.env, Some("REGION".to_string()));
+        assert_eq!(param.default, Some("us-east-1".to_string()));
+    }
+
+    #[test]
+    fn test_parse_config_value_default_from_env() {
+        let config_value = ConfigValue::from_str(
+            "--token: {desc: the token, default_from_env: MY_SERVICE_TOKEN, default: none}",
+        )
+        .unwrap();
+        let param =
+            SyntaxOptArg::from_config_value(&config_value, None, &ConfigErrorHandler::new())
+                .expect("should parse the parameter");
+
+        assert_eq!(param.env, Some("MY_SERVICE_TOKEN".to_string()));
+        assert_eq!(param.default, Some("none".to_string()));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let syntax = syntax_with(SyntaxOptArgType::String, Some("from-default"));
+
+        with_var(Some("from-env"), || {
+            assert_eq!(
+                parse_value(&syntax, &["--value", "from-cli"]),
+                ParseArgsValue::SingleString(Some("from-cli".to_string()))
+            );
+            assert_eq!(
+                parse_value(&syntax, &[]),
+                ParseArgsValue::SingleString(Some("from-env".to_string()))
+            );
+        });
+
+        for value in [None, Some("")] {
+            with_var(value, || {
+                assert_eq!(
+                    parse_value(&syntax, &[]),
+                    ParseArgsValue::SingleString(Some("from-default".to_string()))
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn test_takes_precedence_over_default_missing_value() {
+        let mut syntax = syntax_with(SyntaxOptArgType::String, None);
+        syntax.parameters[0].default_missing_value = Some("from-missing".to_string());
+        syntax.parameters[0].num_values = Some(SyntaxOptArgNumValues::Between(0, 1));
+
+        with_var(Some("from-env"), || {
+            assert_eq!(
+                parse_value(&syntax, &[]),
+                ParseArgsValue::SingleString(Some("from-env".to_string()))
+            );
+            assert_eq!(
+                parse_value(&syntax, &["--value"]),
+                ParseArgsValue::SingleString(Some("from-missing".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn test_typed_values() {
+        with_var(Some("42"), || {
+            assert_eq!(
+                parse_value(&syntax_with(SyntaxOptArgType::Integer, Some("1")), &[]),
+                ParseArgsValue::SingleInteger(Some(42))
+            );
+            assert_eq!(
+                parse_value(&syntax_with(SyntaxOptArgType::Float, None), &[]),
+                ParseArgsValue::SingleFloat(Some(42.0))
+            );
+        });
+
+        with_var(Some("yes"), || {
+            assert_eq!(
+                parse_value(&syntax_with(SyntaxOptArgType::Flag, None), &[]),
+                ParseArgsValue::SingleBoolean(Some(true))
+            );
+        });
+
+        with_var(Some("1, 2,3"), || {
+            let array = SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::Integer));
+            assert_eq!(
+                parse_value(&syntax_with(array, None), &[]),
+                ParseArgsValue::ManyInteger(vec![Some(1), Some(2), Some(3)])
+            );
+        });
+    }
+
+    #[test]
+    fn test_invalid_values_report_the_same_error_as_cli() {
+        let enum_type = SyntaxOptArgType::Enum(vec!["a".to_string(), "b".to_string()]);
+        let cases = [
+            (SyntaxOptArgType::Integer, "abc"),
+            (SyntaxOptArgType::Float, "abc"),
+            (SyntaxOptArgType::Boolean, "maybe"),
+            (enum_type, "c"),
+        ];
+
+        for (arg_type, value) in cases {
+            let syntax = syntax_with(arg_type, None);
+
+            with_var(Some(value), || {
+                let cli_err = parse(&syntax, &["--value", value]).expect_err("cli should fail");
+                let env_err = parse(&syntax, &[]).expect_err("env should fail");
+
+                assert_eq!(env_err.code(), cli_err.code());
+                assert_eq!(env_err.simple(), cli_err.simple());
+                assert_eq!(syntax.error_dest(&env_err), Some("value".to_string()));
+
+                let message = env_err.to_string();
+                assert!(
+                    message.contains(&format!(
+                        "tip: the value was read from the environment variable '{VAR}'"
+                    )),
+                    "unexpected error: {message}"
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn test_enum_ignoring_case_is_normalized() {
+        let mut syntax = syntax_with(
+            SyntaxOptArgType::Enum(vec!["DEBUG".to_string(), "INFO".to_string()]),
+            None,
+        );
+        syntax.parameters[0].ignore_case = true;
+
+        with_var(Some("info"), || {
+            assert_eq!(
+                parse_value(&syntax, &[]),
+                ParseArgsValue::SingleString(Some("INFO".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn test_required_satisfied_by_env() {
+        let mut syntax = syntax_with(SyntaxOptArgType::String, None);
+        syntax.parameters[0].required = true;
+
+        with_var(Some("from-env"), || {
+            assert_eq!(
+                parse_value(&syntax, &[]),
+                ParseArgsValue::SingleString(Some("from-env".to_string()))
+            );
+        });
+
+        with_var(None, || {
+            let err = parse(&syntax, &[]).expect_err("should fail without the variable");
+            assert_eq!(err.code().as_str(), "E_ARG_MISSING_REQUIRED");
+        });
+    }
+
+    #[test]
+    fn test_help_desc() {
+        let _colors = disable_colors();
+
+        let mut param = syntax_with(SyntaxOptArgType::String, Some("x")).parameters[0].clone();
+        param.desc = Some("The value".to_string());
+
+        assert_eq!(
+            param.help_desc(),
+            format!("The value [env: {VAR}] [default: x]")
+        );
+    }
+}
+
+mod interpolate_desc {
+    use super::*;
+
+    use crate::internal::testutils::run_with_env;
+
+    fn param_with_desc(desc: &str, default: Option<&str>) -> SyntaxOptArg {
+        SyntaxOptArg {
+            names: vec!["--cache-dir".to_string()],
+            desc: Some(desc.to_string()),
+            default: default.map(|default| default.to_string()),
+            ..SyntaxOptArg::default()
+        }
+    }
+
+    #[test]
+    fn test_default_placeholder() {
+        let _colors = disable_colors();
+
+        let param = param_with_desc("Where to cache, defaults to {default}", Some("/tmp/cache"));
+        assert_eq!(
+            param.interpolated_desc(),
+            Some("Where to cache, defaults to /tmp/cache".to_string())
+        );
+        assert_eq!(
+            param.help_desc(),
+            "Where to cache, defaults to /tmp/cache [default: /tmp/cache]"
+        );
+    }
+
+    #[test]
+    fn test_default_placeholder_with_colors() {
+        let _colors = ColorCapability::new(true).scoped();
+
+        let param = param_with_desc("Where to cache, defaults to {default}", Some("/tmp/cache"));
+        assert_eq!(
+            param.help_desc(),
+            "Where to cache, defaults to /tmp/cache \x1B[90m[\x1B[3mdefault\x1B[23m: /tmp/cache]\x1B[39m"
+        );
+    }
+
+    #[test]
+    fn test_default_placeholder_without_default() {
+        let param = param_with_desc("Defaults to {default}", None);
+        assert_eq!(
+            param.interpolated_desc(),
+            Some("Defaults to {default}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let param = param_with_desc("Literal {{default}} and ${{HOME}}, {a,b} }", Some("x"));
+        assert_eq!(
+            param.interpolated_desc(),
+            Some("Literal {default} and ${HOME}, {a,b} }".to_string())
+        );
+    }
+
+    #[test]
+    fn test_env_placeholder() {
+        run_with_env(
+            &[
+                (
+                    "OMNI_TEST_DESC_VAR".to_string(),
+                    Some("/some/path".to_string()),
+                ),
+                ("OMNI_TEST_DESC_UNSET".to_string(), None),
+            ],
+            || {
+                assert_eq!(
+                    interpolate_desc(
+                        "In ${OMNI_TEST_DESC_VAR}, not ${OMNI_TEST_DESC_UNSET} nor ${NOT A NAME} or $(whoami)",
+                        || None,
+                        |name| std::env::var(name).ok(),
+                    ),
+                    "In /some/path, not ${OMNI_TEST_DESC_UNSET} nor ${NOT A NAME} or $(whoami)"
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_env_placeholder_allowlist() {
+        run_with_env(
+            &[
+                (
+                    "OMNI_TEST_DESC_VAR".to_string(),
+                    Some("/some/path".to_string()),
+                ),
+                ("TEST_DESC_CACHE".to_string(), Some("/cache".to_string())),
+                ("TEST_DESC_TOKEN".to_string(), Some("s3cr3t".to_string())),
+            ],
+            || {
+                let param = SyntaxOptArg {
+                    env: Some("TEST_DESC_CACHE".to_string()),
+                    ..param_with_desc(
+                        "In ${OMNI_TEST_DESC_VAR} or ${TEST_DESC_CACHE}, not ${TEST_DESC_TOKEN}",
+                        None,
+                    )
+                };
+                assert_eq!(
+                    param.interpolated_desc(),
+                    Some("In /some/path or /cache, not ${TEST_DESC_TOKEN}".to_string())
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_env_placeholder_sensitive() {
+        run_with_env(
+            &[("TEST_DESC_TOKEN".to_string(), Some("s3cr3t".to_string()))],
+            || {
+                let param = SyntaxOptArg {
+                    env: Some("TEST_DESC_TOKEN".to_string()),
+                    sensitive: true,
+                    ..param_with_desc("Currently ${TEST_DESC_TOKEN}", None)
+                };
+                assert_eq!(
+                    param.interpolated_desc(),
+                    Some(format!("Currently {}", SECRET_PLACEHOLDER))
+                );
+            },
+        );
+    }
+}
+
+mod syntax_opt_arg_examples {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn first_param(command: &CommandDefinition) -> &SyntaxOptArg {
+        &command
+            .syntax
+            .as_ref()
+            .expect("syntax should be defined")
+            .parameters[0]
+    }
+
+    #[test]
+    fn help_rendering() {
+        let _colors = disable_colors();
+
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --schedule\n",
+            "      desc: When to run\n",
+            "      examples: ['0 9 * * 1', '@daily']\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        assert_eq!(
+            first_param(&command).help_desc(),
+            "When to run [examples: 0 9 * * 1, @daily]"
+        );
+    }
+
+    #[test]
+    fn completion_suggestions() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --schedule\n",
+            "      examples: ['0 9 * * 1', '@daily']\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let param = first_param(&command);
+        assert_eq!(param.completion_examples(""), vec!["0 9 * * 1", "@daily"]);
+        // Once the user started typing, the examples are not suggested
+        assert!(param.completion_examples("@").is_empty());
+    }
+
+    #[test]
+    fn validation_catches_invalid_enum_example() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --level\n",
+            "      type: enum(debug, info, warn)\n",
+            "      examples: [info, verbose]\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+        assert_eq!(errors[0].context_str("actual"), "verbose");
+
+        // The invalid example is dropped
+        assert_eq!(first_param(&command).examples, vec!["info"]);
+    }
+
+    #[test]
+    fn validation_checks_array_values() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --ports\n",
+            "      type: array/int\n",
+            "      delimiter: ','\n",
+            "      examples: ['80,443', '8080,http']\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(first_param(&command).examples, vec!["80,443"]);
+    }
+
+    #[test]
+    fn unsupported_on_flags() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --force\n",
+            "      type: flag\n",
+            "      examples: ['true']\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+        assert!(first_param(&command).examples.is_empty());
+    }
+}
+
+mod command_runner {
+    use super::*;
+
+    fn command_with_runner(runner: &str) -> (CommandDefinition, ConfigErrorHandler) {
+        let yaml = format!("run: make test\nrunner: {runner}\n");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler)
+    }
+
+    fn runner(command: &str, passthrough_env: &[&str]) -> CommandRunner {
+        CommandRunner {
+            command: command.to_string(),
+            passthrough_env: passthrough_env.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn vars(vars: &[&str]) -> Vec<String> {
+        vars.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn from_config_value_table() {
+        let (command, error_handler) = command_with_runner(
+            "{command: \"docker run --rm mycorp/dev\", passthrough_env: [\"OMNI_ARG_*\", CI]}",
+        );
+
+        assert!(error_handler.errors().is_empty());
+        assert_eq!(
+            command.runner,
+            Some(runner("docker run --rm mycorp/dev", &["OMNI_ARG_*", "CI"]))
+        );
+    }
+
+    #[test]
+    fn from_config_value_string() {
+        let (command, error_handler) = command_with_runner("docker run --rm mycorp/dev");
+
+        assert!(error_handler.errors().is_empty());
+        assert_eq!(
+            command.runner,
+            Some(runner("docker run --rm mycorp/dev", &[]))
+        );
+    }
+
+    #[test]
+    fn from_config_value_missing_command() {
+        let (command, error_handler) = command_with_runner("{passthrough_env: [CI]}");
+
+        assert_eq!(command.runner, None);
+        assert_eq!(error_handler.errors().len(), 1);
+    }
+
+    #[test]
+    fn expand_command_tokens() {
+        let runner = runner(
+            "docker run -v %{workdir}:/w -w /w/%{reldir} mycorp/dev %{unknown}",
+            &[],
+        );
+
+        assert_eq!(
+            runner.expand_command("/src/project", "backend/api", &[]),
+            "docker run -v /src/project:/w -w /w/backend/api mycorp/dev %{unknown}"
+        );
+        assert_eq!(
+            runner.expand_command("/src/my project", "", &[]),
+            "docker run -v '/src/my project':/w -w /w/'' mycorp/dev %{unknown}"
+        );
+    }
+
+    #[test]
+    fn expand_command_env_token() {
+        let runner = runner(
+            "docker run --env-file <(printf '%s\\n' %{env}) mycorp/dev",
+            &[],
+        );
+
+        assert_eq!(
+            runner.expand_command("/src", "", &vars(&["CI", "OMNI_ARG_NAME_VALUE"])),
+            "docker run --env-file <(printf '%s\\n' CI OMNI_ARG_NAME_VALUE) mycorp/dev"
+        );
+        assert_eq!(
+            runner.expand_command("/src", "", &[]),
+            "docker run --env-file <(printf '%s\\n' ) mycorp/dev"
+        );
+    }
+
+    #[test]
+    fn program() {
+        assert_eq!(
+            runner("docker run --rm mycorp/dev", &[]).program(),
+            Some("docker".to_string())
+        );
+        assert_eq!(
+            runner("DOCKER_HOST=tcp://host:2375 /usr/bin/docker run", &[]).program(),
+            Some("/usr/bin/docker".to_string())
+        );
+        assert_eq!(runner("", &[]).program(), None);
+        assert_eq!(runner("docker 'unterminated", &[]).program(), None);
+    }
+
+    #[test]
+    fn filter_env() {
+        let runner = runner("docker run", &["CI", "GITHUB_*"]);
+        let env = vars(&[
+            "PATH",
+            "GITHUB_TOKEN",
+            "CI",
+            "CIRCLECI",
+            "OMNI_ARG_LIST",
+            "OMNI_ARG_ENV_VALUE",
+            "GITHUB_SHA",
+        ]);
+
+        assert_eq!(
+            runner.filter_env(env.clone(), false),
+            vars(&["CI", "GITHUB_SHA", "GITHUB_TOKEN"])
+        );
+        assert_eq!(
+            runner.filter_env(env, true),
+            vars(&[
+                "CI",
+                "GITHUB_SHA",
+                "GITHUB_TOKEN",
+                "OMNI_ARG_ENV_VALUE",
+                "OMNI_ARG_LIST",
+            ])
+        );
+    }
+
+    #[test]
+    fn command_line() {
+        let runner = runner(
+            "docker run --rm -w /w/%{reldir} --env-file <(printf '%s\\n' %{env}) mycorp/dev",
+            &[],
+        );
+
+        assert_eq!(
+            runner.command_line(
+                "echo \"$1\"",
+                "/src/project/.omni.yaml",
+                &["hello world".to_string(), "--flag".to_string()],
+                &vars(&["CI", "OMNI_ARG_NAME_VALUE"]),
+                "/src/project",
+                "backend",
+            ),
+            concat!(
+                "docker run --rm -w /w/backend ",
+                "--env-file <(printf '%s\\n' CI OMNI_ARG_NAME_VALUE) mycorp/dev ",
+                "bash -c 'echo \"$1\"' /src/project/.omni.yaml 'hello world' --flag",
+            )
+        );
+    }
+
+    #[test]
+    fn command_line_without_env_token() {
+        let runner = runner("ssh devbox", &[]);
+
+        assert_eq!(
+            runner.command_line("make test", "/src/.omni.yaml", &[], &[], "/src", ""),
+            "ssh devbox bash -c 'make test' /src/.omni.yaml"
+        );
+    }
+}
+
+mod trailing_positional {
+    use super::*;
+
+    fn sources_dest_syntax(sources_type: SyntaxOptArgType) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["sources".to_string()],
+                    arg_type: sources_type,
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["dest".to_string()],
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--force".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--name".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn array_syntax() -> CommandSyntax {
+        sources_dest_syntax(SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)))
+    }
+
+    fn parse(syntax: &CommandSyntax, argv: &[&str]) -> Result<BTreeMap<String, String>, String> {
+        syntax
+            .parse_args(
+                argv.iter().map(|s| s.to_string()).collect(),
+                vec!["test".to_string()],
+            )
+            .map_err(|err| err.simple())
+    }
+
+    fn assert_sources_dest(args: &BTreeMap<String, String>, sources: &[&str], dest: &str) {
+        assert_eq!(
+            args.get("OMNI_ARG_SOURCES_TYPE"),
+            Some(&format!("str/{}", sources.len()))
+        );
+        for (idx, source) in sources.iter().enumerate() {
+            assert_eq!(
+                args.get(&format!("OMNI_ARG_SOURCES_VALUE_{idx}")),
+                Some(&source.to_string())
+            );
+        }
+        assert_eq!(
+            args.get(&format!("OMNI_ARG_SOURCES_VALUE_{}", sources.len())),
+            None
+        );
+        assert_eq!(args.get("OMNI_ARG_DEST_TYPE"), Some(&"str".to_string()));
+        assert_eq!(args.get("OMNI_ARG_DEST_VALUE"), Some(&dest.to_string()));
+    }
+
+    #[test]
+    fn multiple_sources() {
+        let args = parse(&array_syntax(), &["a", "b", "c", "dst"]).expect("should parse");
+        assert_sources_dest(&args, &["a", "b", "c"], "dst");
+    }
+
+    #[test]
+    fn single_source() {
+        let args = parse(&array_syntax(), &["a", "dst"]).expect("should parse");
+        assert_sources_dest(&args, &["a"], "dst");
+    }
+
+    #[test]
+    fn options_between_values() {
+        let args = parse(
+            &array_syntax(),
+            &["a", "--force", "b", "--name", "x", "dst"],
+        )
+        .expect("should parse");
+        assert_sources_dest(&args, &["a", "b"], "dst");
+        assert_eq!(args.get("OMNI_ARG_NAME_VALUE"), Some(&"x".to_string()));
+        assert_eq!(args.get("OMNI_ARG_FORCE_VALUE"), Some(&"true".to_string()));
+    }
+
+    #[test]
+    fn option_before_dest() {
+        let args = parse(&array_syntax(), &["a", "--force", "dst"]).expect("should parse");
+        assert_sources_dest(&args, &["a"], "dst");
+    }
+
+    #[test]
+    fn escaped_values() {
+        let args = parse(&array_syntax(), &["a", "--", "-b", "--force"]).expect("should parse");
+        assert_sources_dest(&args, &["a", "-b"], "--force");
+        assert_eq!(args.get("OMNI_ARG_FORCE_VALUE"), Some(&"false".to_string()));
+    }
+
+    #[test]
+    fn dest_value_repeated_in_option() {
+        let args = parse(&array_syntax(), &["a", "dst", "--name", "dst"]).expect("should parse");
+        assert_sources_dest(&args, &["a"], "dst");
+        assert_eq!(args.get("OMNI_ARG_NAME_VALUE"), Some(&"dst".to_string()));
+    }
+
+    #[test]
+    fn source_value_like_terminator() {
+        let args = parse(&array_syntax(), &["a", ";", "dst"]).expect("should parse");
+        assert_sources_dest(&args, &["a", ";"], "dst");
+    }
+
+    #[test]
+    fn missing_dest() {
+        assert_eq!(
+            parse(&array_syntax(), &["a"]),
+            Err("the following required arguments were not provided: <dest>".to_string())
+        );
+        assert_eq!(
+            parse(&array_syntax(), &["a", "--force"]),
+            Err("the following required arguments were not provided: <dest>".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_dest_error_dest() {
+        let syntax = array_syntax();
+        let err = syntax
+            .parse_args(vec!["a".to_string()], vec!["test".to_string()])
+            .expect_err("should fail");
+        assert_eq!(syntax.error_dest(&err), Some("dest".to_string()));
+    }
+
+    #[test]
+    fn missing_sources_and_dest() {
+        assert_eq!(
+            parse(&array_syntax(), &[]),
+            Err(
+                "the following required arguments were not provided: <sources>... <dest>"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn num_values_sources() {
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["sources".to_string()],
+                    num_values: Some(SyntaxOptArgNumValues::AtLeast(2)),
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["dest".to_string()],
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        };
+
+        let args = parse(&syntax, &["a", "b", "dst"]).expect("should parse");
+        assert_sources_dest(&args, &["a", "b"], "dst");
+
+        assert_eq!(
+            parse(&syntax, &["a", "dst"]),
+            Err("the following required arguments were not provided: <dest>".to_string())
+        );
+    }
+
+    #[test]
+    fn preceding_positional() {
+        let mut syntax = array_syntax();
+        syntax.parameters.insert(
+            0,
+            SyntaxOptArg {
+                names: vec!["mode".to_string()],
+                required: true,
+                ..SyntaxOptArg::default()
+            },
+        );
+
+        let args = parse(&syntax, &["copy", "a", "--force", "b", "dst"]).expect("should parse");
+        assert_eq!(args.get("OMNI_ARG_MODE_VALUE"), Some(&"copy".to_string()));
+        assert_sources_dest(&args, &["a", "b"], "dst");
+    }
+}
+
+mod secret_args {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn password_syntax(required: bool, default: Option<&str>) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--password".to_string()],
+                    desc: Some("The password".to_string()),
+                    required,
+                    default: default.map(|default| default.to_string()),
+                    secret: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--user".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn prompt_with(
+        syntax: &CommandSyntax,
+        args: &[&str],
+        prompt: impl Fn(&SyntaxOptArg) -> Option<String>,
+    ) -> Result<BTreeMap<String, String>, ParseArgsErrorKind> {
+        let typed_args = syntax.parse_args_typed_with_prompt(
+            argv(args),
+            vec!["test".to_string()],
+            false,
+            prompt,
+        )?;
+        Ok(syntax.export_args(typed_args))
+    }
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    #[test]
+    fn missing_required_secret_when_non_interactive() {
+        let syntax = password_syntax(true, None);
+
+        let err = syntax
+            .parse_args(argv(&["--user", "me"]), vec!["test".to_string()])
+            .expect_err("should fail without the secret");
+
+        assert_eq!(
+            err.simple(),
+            "the following required arguments were not provided: --password <password>"
+        );
+        assert_eq!(syntax.error_dest(&err), Some("password".to_string()));
+    }
+
+    #[test]
+    fn missing_optional_secret_is_left_unset() {
+        let syntax = password_syntax(false, None);
+
+        // An optional secret is not prompted for, so that it behaves the
+        // same whether the shell is interactive or not
+        let args = prompt_with(&syntax, &["--user", "me"], |_| panic!("should not prompt"))
+            .expect("should parse");
+
+        assert_eq!(args.get("OMNI_ARG_PASSWORD_TYPE"), Some(&"str".to_string()));
+        assert_eq!(args.get("OMNI_ARG_PASSWORD_VALUE"), None);
+    }
+
+    #[test]
+    fn provided_secret_is_not_prompted() {
+        let syntax = password_syntax(true, None);
+
+        let args = prompt_with(&syntax, &["--password", "hunter2"], |_| {
+            panic!("should not prompt")
+        })
+        .expect("should parse");
+
+        assert_eq!(
+            args.get("OMNI_ARG_PASSWORD_VALUE"),
+            Some(&"hunter2".to_string())
+        );
+    }
+
+    #[test]
+    fn default_secret_is_not_prompted() {
+        let syntax = password_syntax(true, Some("hunter2"));
+
+        let args =
+            prompt_with(&syntax, &[], |_| panic!("should not prompt")).expect("should parse");
+
+        assert_eq!(
+            args.get("OMNI_ARG_PASSWORD_VALUE"),
+            Some(&"hunter2".to_string())
+        );
+    }
+
+    #[test]
+    fn prompted_secret_is_exported() {
+        let syntax = password_syntax(true, None);
+
+        let args = prompt_with(&syntax, &["--user", "me"], |param| {
+            assert_eq!(param.dest(), "password");
+            Some("hunter2".to_string())
+        })
+        .expect("should parse");
+
+        assert_eq!(
+            args.get("OMNI_ARG_PASSWORD_VALUE"),
+            Some(&"hunter2".to_string())
+        );
+        assert_eq!(args.get("OMNI_ARG_USER_VALUE"), Some(&"me".to_string()));
+    }
+
+    #[test]
+    fn empty_prompt_answer_is_missing() {
+        let syntax = password_syntax(true, None);
+
+        let err = prompt_with(&syntax, &[], |_| Some("".to_string()))
+            .expect_err("should fail with an empty secret");
+
+        assert_eq!(syntax.error_dest(&err), Some("password".to_string()));
+    }
+
+    #[test]
+    fn argv_is_redacted() {
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--password".to_string(), "-p".to_string()],
+                    secret: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--user".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        };
+
+        let password_attached = format!("--password={SECRET_PLACEHOLDER}");
+        let short_attached = format!("-p{SECRET_PLACEHOLDER}");
+        for (args, expected) in [
+            (
+                vec!["--password", "hunter2", "--user", "me"],
+                vec!["--password", SECRET_PLACEHOLDER, "--user", "me"],
+            ),
+            (vec!["--password=hunter2"], vec![password_attached.as_str()]),
+            (vec!["-phunter2"], vec![short_attached.as_str()]),
+            (vec!["--user", "me"], vec!["--user", "me"]),
+        ] {
+            let parsed = syntax
+                .parse_args(argv(&args), vec!["test".to_string()])
+                .expect("should parse");
+            assert_eq!(syntax.redacted_argv(&argv(&args), &parsed), argv(&expected));
+        }
+    }
+
+    #[test]
+    fn default_is_redacted_in_help() {
+        let _colors = disable_colors();
+
+        let param = SyntaxOptArg {
+            names: vec!["--password".to_string()],
+            desc: Some("The password, defaults to {default}".to_string()),
+            default: Some("hunter2".to_string()),
+            secret: true,
+            ..SyntaxOptArg::default()
+        };
+
+        assert_eq!(
+            param.interpolated_desc(),
+            Some("The password, defaults to ********".to_string())
+        );
+        assert_eq!(
+            param.help_desc(),
+            "The password, defaults to ******** [default: ********]"
+        );
+    }
+
+    #[test]
+    fn default_is_hidden_from_parser_help() {
+        let syntax = password_syntax(false, Some("hunter2"));
+        let mut parser = syntax
+            .argparser(vec!["test".to_string()])
+            .expect("parser should build");
+
+        let help = parser.render_help().to_string();
+        assert!(!help.contains("hunter2"), "default leaked in: {help}");
+    }
+
+    #[test]
+    fn config_secret() {
+        let (command, errors) =
+            command_from_yaml(concat!("    - name: --password\n", "      secret: true\n",));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(syntax.parameters[0].secret);
+    }
+
+    #[test]
+    fn config_secret_unsupported_for_non_string() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --tokens\n",
+            "      type: array/str\n",
+            "      secret: true\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(!syntax.parameters[0].secret);
+    }
+}
+
+mod sensitive_args {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn token_syntax(sensitive: bool) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--token".to_string()],
+                    arg_type: SyntaxOptArgType::Enum(vec!["alpha".to_string(), "beta".to_string()]),
+                    sensitive,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--code".to_string()],
+                    validate: Some("^[a-z]+$".to_string()),
+                    sensitive,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--pin".to_string()],
+                    arg_type: SyntaxOptArgType::Integer,
+                    min: Some(1000.0),
+                    sensitive,
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn parse_error(syntax: &CommandSyntax, args: &[&str]) -> String {
+        let argv = args.iter().map(|s| s.to_string()).collect();
+        match syntax.parse_args(argv, vec!["test".to_string()]) {
+            Err(ParseArgsErrorKind::ArgumentParsingError(err)) => err.to_string(),
+            Err(err) => err.simple(),
+            Ok(_) => panic!("arguments should not parse"),
+        }
+    }
+
+    #[test]
+    fn invalid_enum_value_is_redacted() {
+        let _colors = disable_colors();
+
+        let message = parse_error(&token_syntax(true), &["--token", "hunter2"]);
+
+        assert!(
+            message.contains("invalid value '********' for '--token <token>'"),
+            "unexpected error: {message}"
+        );
+        assert!(!message.contains("hunter2"), "value leaked in: {message}");
+    }
+
+    #[test]
+    fn invalid_enum_value_is_shown_if_not_sensitive() {
+        let _colors = disable_colors();
+
+        let message = parse_error(&token_syntax(false), &["--token", "hunter2"]);
+
+        assert!(
+            message.contains("invalid value 'hunter2' for '--token <token>'"),
+            "unexpected error: {message}"
+        );
+    }
+
+    #[test]
+    fn value_not_matching_pattern_is_redacted() {
+        let _colors = disable_colors();
+
+        let message = parse_error(&token_syntax(true), &["--code", "Hunter2"]);
+
+        assert!(
+            message.contains("'--code <code>'"),
+            "unexpected error: {message}"
+        );
+        assert!(!message.contains("Hunter2"), "value leaked in: {message}");
+    }
+
+    #[test]
+    fn value_out_of_range_is_redacted() {
+        let _colors = disable_colors();
+
+        let message = parse_error(&token_syntax(true), &["--pin", "42"]);
+
+        assert!(
+            message.contains("'--pin <pin>'"),
+            "unexpected error: {message}"
+        );
+        assert!(!message.contains("42"), "value leaked in: {message}");
+    }
+
+    #[test]
+    fn exported_value_is_not_redacted() {
+        let syntax = token_syntax(true);
+
+        let args = syntax
+            .parse_args(
+                vec!["--token".to_string(), "beta".to_string()],
+                vec!["test".to_string()],
+            )
+            .expect("should parse");
+
+        assert_eq!(args.get("OMNI_ARG_TOKEN_VALUE"), Some(&"beta".to_string()));
+    }
+
+    #[test]
+    fn default_is_redacted_in_help() {
+        let _colors = disable_colors();
+
+        let param = SyntaxOptArg {
+            names: vec!["--code".to_string()],
+            default: Some("hunter".to_string()),
+            sensitive: true,
+            ..SyntaxOptArg::default()
+        };
+
+        assert_eq!(param.help_desc(), "[default: ********]");
+    }
+
+    #[test]
+    fn secret_is_sensitive() {
+        let param = SyntaxOptArg {
+            names: vec!["--password".to_string()],
+            secret: true,
+            ..SyntaxOptArg::default()
+        };
+
+        assert!(param.is_sensitive());
+    }
+
+    #[test]
+    fn config_sensitive() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --token\n",
+            "      type: enum\n",
+            "      values: [alpha, beta]\n",
+            "      sensitive: true\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(syntax.parameters[0].sensitive);
+    }
+}
+
+mod parse_args_json {
+    use super::*;
+
+    use crate::internal::config::parser::ConfigError;
+
+    fn parse_json(syntax: &CommandSyntax, argv: &[&str]) -> serde_json::Value {
+        let args_json = syntax
+            .parse_args_json(
+                argv.iter().map(|s| s.to_string()).collect(),
+                vec!["test".to_string()],
+            )
+            .expect("arguments should parse");
+
+        serde_json::from_str(&args_json).expect("output should be valid json")
+    }
+
+    fn command_from_yaml(yaml: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    #[test]
+    fn values_keep_their_types() {
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--name".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--count".to_string()],
+                    arg_type: SyntaxOptArgType::Integer,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--ratio".to_string()],
+                    arg_type: SyntaxOptArgType::Float,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--verbose".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--ids".to_string()],
+                    num_values: Some(SyntaxOptArgNumValues::AtLeast(1)),
+                    arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::Integer)),
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        };
+
+        let args = parse_json(
+            &syntax,
+            &[
+                "--name",
+                "omni",
+                "--count",
+                "42",
+                "--ratio",
+                "1.5",
+                "--verbose",
+                "--ids",
+                "1",
+                "2",
+            ],
+        );
+
+        assert_eq!(
+            args,
+            serde_json::json!({
+                "name": "omni",
+                "count": 42,
+                "ratio": 1.5,
+                "verbose": true,
+                "ids": [1, 2],
+            })
+        );
+    }
+
+    #[test]
+    fn missing_values_are_null() {
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--name".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--other".to_string()],
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--count".to_string()],
+                    arg_type: SyntaxOptArgType::Integer,
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        };
+
+        let args = parse_json(&syntax, &["--name", ""]);
+
+        assert_eq!(
+            args,
+            serde_json::json!({
+                "name": "",
+                "other": null,
+                "count": null,
+            })
+        );
+    }
+
+    #[test]
+    fn grouped_occurrences_are_nested_arrays() {
+        let syntax = CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--group".to_string()],
+                num_values: Some(SyntaxOptArgNumValues::AtLeast(1)),
+                arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+                group_occurrences: true,
+                ..SyntaxOptArg::default()
+            }],
+            ..CommandSyntax::default()
+        };
+
+        let args = parse_json(
+            &syntax,
+            &[
+                "--group", "a", "b", "--group", "c", "--group", "d", "e", "f",
+            ],
+        );
+
+        assert_eq!(
+            args,
+            serde_json::json!({
+                "group": [["a", "b"], ["c"], ["d", "e", "f"]],
+            })
+        );
+    }
+
+    #[test]
+    fn config_argparser_output() {
+        let (command, errors) = command_from_yaml("run: 'true'\n");
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        assert_eq!(command.argparser_output, ArgparserOutput::Env);
+
+        let (command, errors) =
+            command_from_yaml("run: 'true'\nargparser: true\nargparser_output: json\n");
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        assert_eq!(command.argparser_output, ArgparserOutput::Json);
+    }
+
+    #[test]
+    fn config_argparser_output_invalid() {
+        let (command, errors) = command_from_yaml("run: 'true'\nargparser_output: xml\n");
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+        assert_eq!(command.argparser_output, ArgparserOutput::Env);
+    }
+}
+
+mod command_cwd {
+    use super::*;
+
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(yaml: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    #[test]
+    fn strategies() {
+        for (cwd, expected) in [
+            ("workdir_root", CommandCwd::WorkdirRoot),
+            ("invocation_dir", CommandCwd::InvocationDir),
+            ("config_dir", CommandCwd::ConfigDir),
+            ("scripts/bin", CommandCwd::Path("scripts/bin".to_string())),
+        ] {
+            let (command, errors) = command_from_yaml(&format!("run: 'true'\ncwd: {cwd}\n"));
+            assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+            assert_eq!(command.cwd, Some(expected));
+        }
+    }
+
+    #[test]
+    fn dir_is_explicit_path() {
+        let (command, errors) = command_from_yaml("run: 'true'\ndir: workdir_root\n");
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        assert_eq!(
+            command.cwd,
+            Some(CommandCwd::Path("workdir_root".to_string()))
+        );
+    }
+
+    #[test]
+    fn cwd_and_dir_conflict() {
+        let (command, errors) =
+            command_from_yaml("run: 'true'\ncwd: invocation_dir\ndir: scripts\n");
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+        assert_eq!(command.cwd, Some(CommandCwd::InvocationDir));
+    }
+
+    #[test]
+    fn not_set() {
+        let (command, errors) = command_from_yaml("run: 'true'\n");
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        assert_eq!(command.cwd, None);
+    }
+
+    #[test]
+    fn dir_template() {
+        let (command, errors) = command_from_yaml(concat!(
+            "run: 'true'\n",
+            "argparser: true\n",
+            "dir: '{{ workdir_root }}/packages/{{ arg:package }}'\n",
+            "syntax:\n",
+            "  parameters:\n",
+            "    - name: package\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        assert_eq!(
+            command.cwd,
+            Some(CommandCwd::Template(
+                "{{ workdir_root }}/packages/{{ arg:package }}".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn template_with_args_requires_argparser() {
+        let (_, errors) = command_from_yaml(concat!(
+            "run: 'true'\n",
+            "dir: 'packages/{{ arg:package }}'\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+    }
+
+    #[test]
+    fn template_args_by_name_or_dest() {
+        for name in ["--package-name", "package-name", "package_name", "-p"] {
+            let (_, errors) = command_from_yaml(&format!(
+                concat!(
+                    "run: 'true'\n",
+                    "argparser: true\n",
+                    "dir: 'packages/{{{{ arg:{} }}}}'\n",
+                    "syntax:\n",
+                    "  parameters:\n",
+                    "    - name: --package-name, -p\n",
+                ),
+                name
+            ));
+            assert!(
+                errors.is_empty(),
+                "unexpected errors for {name}: {errors:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn template_with_undeclared_arg() {
+        let (_, errors) = command_from_yaml(concat!(
+            "run: 'true'\n",
+            "argparser: true\n",
+            "dir: 'packages/{{ arg:pkg }}'\n",
+            "syntax:\n",
+            "  parameters:\n",
+            "    - name: package\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+        let message = errors[0].to_string();
+        assert!(
+            message.contains("arg:package"),
+            "unexpected error: {message}"
+        );
+    }
+
+    #[test]
+    fn template_with_multiple_values_arg() {
+        let (_, errors) = command_from_yaml(concat!(
+            "run: 'true'\n",
+            "argparser: true\n",
+            "dir: 'packages/{{ arg:packages }}'\n",
+            "syntax:\n",
+            "  parameters:\n",
+            "    - name: --packages\n",
+            "      type: array/str\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn template_without_args_does_not_require_argparser() {
+        let (_, errors) = command_from_yaml("run: 'true'\ndir: '{{ workdir_root }}/tools'\n");
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn invalid_template() {
+        let (_, errors) = command_from_yaml(concat!(
+            "run: 'true'\n",
+            "argparser: true\n",
+            "cwd: '{{ unknown }}/tools'\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidSyntax);
+    }
+
+    #[test]
+    fn template_parts() {
+        assert_eq!(
+            CommandCwd::template_parts("{{workdir_root}}/packages/{{ arg: package }}/src"),
+            Ok(vec![
+                CommandCwdTemplatePart::WorkdirRoot,
+                CommandCwdTemplatePart::Literal("/packages/".to_string()),
+                CommandCwdTemplatePart::Arg("package".to_string()),
+                CommandCwdTemplatePart::Literal("/src".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn template_parts_escaped_braces() {
+        assert_eq!(
+            CommandCwd::template_parts("build/\\{{ literal }}/{{ arg:name }}"),
+            Ok(vec![
+                CommandCwdTemplatePart::Literal("build/{{ literal }}/".to_string()),
+                CommandCwdTemplatePart::Arg("name".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn template_parts_errors() {
+        for template in ["{{ workdir_root", "{{ arg: }}", "{{ home }}"] {
+            assert!(
+                CommandCwd::template_parts(template).is_err(),
+                "template {template:?} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn template_resolution_errors() {
+        let config_dir = std::env::temp_dir();
+        let cwd = CommandCwd::Template("{{ arg:package }}".to_string());
+
+        let err = cwd
+            .resolve(&config_dir, &config_dir)
+            .expect_err("arguments should be required");
+        assert!(err.contains("not parsed"), "unexpected error: {err}");
+
+        let empty: &dyn Fn(&str) -> Result<String, String> =
+            &|_| Err("the argument is empty".to_string());
+        let err = cwd
+            .resolve_with_args(&config_dir, &config_dir, Some(empty))
+            .expect_err("empty argument should be rejected");
+        assert!(
+            err.contains("cannot expand argument 'package'")
+                && err.contains("the argument is empty"),
+            "unexpected error: {err}"
+        );
+
+        let missing: &dyn Fn(&str) -> Result<String, String> =
+            &|_| Ok("omni-missing-directory".to_string());
+        let err = cwd
+            .resolve_with_args(&config_dir, &config_dir, Some(missing))
+            .expect_err("missing directory should be rejected");
+        assert!(err.contains("does not exist"), "unexpected error: {err}");
+    }
+}
+
+mod syntax_opt_arg_validate {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+    use crate::internal::testutils::run_with_env;
+
+    const PATTERN: &str = "^(dev|staging|prod)-[a-z0-9]+$";
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn syntax_with(param: SyntaxOptArg) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                validate: Some(PATTERN.to_string()),
+                ..param
+            }],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn parse(
+        syntax: &CommandSyntax,
+        argv: &[&str],
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind> {
+        syntax.parse_args_typed(
+            argv.iter().map(|arg| arg.to_string()).collect(),
+            vec!["test".to_string()],
+        )
+    }
+
+    fn assert_rejected(syntax: &CommandSyntax, argv: &[&str], value: &str) {
+        let err = parse(syntax, argv).expect_err("should reject the value");
+        assert_eq!(err.code().as_str(), "E_ARG_INVALID_VALUE");
+
+        let message = err.to_string();
+        assert!(message.contains(value), "missing value in: {message}");
+        assert!(message.contains(PATTERN), "missing pattern in: {message}");
+    }
 
     #[test]
-    fn test_long_option() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option");
-        assert_eq!(names, vec!["--option"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert!(placeholders.is_empty());
-        assert!(!leftovers);
+    fn parse_config_value() {
+        let (command, errors) =
+            command_from_yaml(&format!("    - name: --env\n      validate: '{PATTERN}'\n"));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert_eq!(syntax.parameters[0].validate, Some(PATTERN.to_string()));
+    }
+
+    #[test]
+    fn invalid_pattern_is_reported() {
+        let (command, errors) =
+            command_from_yaml("    - name: --env\n      validate: '^(dev|prod'\n");
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::ParsingError);
+        assert_eq!(errors[0].context_str("actual"), "^(dev|prod");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert_eq!(syntax.parameters[0].validate, None);
+    }
+
+    #[test]
+    fn unsupported_on_non_string_types() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --count\n",
+            "      type: int\n",
+            "      validate: '^[0-9]$'\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert_eq!(syntax.parameters[0].validate, None);
+    }
+
+    #[test]
+    fn examples_must_match() {
+        let (command, errors) = command_from_yaml(&format!(
+            "    - name: --env\n      validate: '{PATTERN}'\n      examples: [dev-1, test-1]\n"
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+        assert_eq!(errors[0].context_str("actual"), "test-1");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert_eq!(syntax.parameters[0].examples, vec!["dev-1"]);
+    }
+
+    #[test]
+    fn invalid_pattern_fails_argparser() {
+        let _colors = disable_colors();
+
+        let syntax = CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--env".to_string()],
+                validate: Some("^(dev|prod".to_string()),
+                ..SyntaxOptArg::default()
+            }],
+            ..CommandSyntax::default()
+        };
+
+        let errors = syntax.check_parameters_validate();
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert!(errors[0].starts_with("--env: invalid validate pattern '^(dev|prod'"));
+        assert!(syntax.argparser(vec!["test".to_string()]).is_err());
+    }
+
+    #[test]
+    fn positional() {
+        let syntax = syntax_with(SyntaxOptArg {
+            names: vec!["env".to_string()],
+            required: true,
+            ..SyntaxOptArg::default()
+        });
+
+        let args = parse(&syntax, &["staging-42"]).expect("should parse the arguments");
+        assert_eq!(
+            args.get("env"),
+            Some(&ParseArgsValue::SingleString(Some(
+                "staging-42".to_string()
+            )))
+        );
+
+        assert_rejected(&syntax, &["qa-42"], "qa-42");
+    }
+
+    #[test]
+    fn optional() {
+        let syntax = syntax_with(SyntaxOptArg {
+            names: vec!["--env".to_string()],
+            ..SyntaxOptArg::default()
+        });
+
+        let args = parse(&syntax, &["--env", "prod-eu1"]).expect("should parse the arguments");
+        assert_eq!(
+            args.get("env"),
+            Some(&ParseArgsValue::SingleString(Some("prod-eu1".to_string())))
+        );
+
+        // Not providing the parameter does not trigger the validation
+        let args = parse(&syntax, &[]).expect("should parse the arguments");
+        assert_eq!(args.get("env"), Some(&ParseArgsValue::SingleString(None)));
+
+        assert_rejected(&syntax, &["--env", "prod-EU1"], "prod-EU1");
+    }
+
+    #[test]
+    fn array_with_delimiter() {
+        let syntax = syntax_with(SyntaxOptArg {
+            names: vec!["--envs".to_string()],
+            arg_type: SyntaxOptArgType::Array(Box::new(SyntaxOptArgType::String)),
+            value_delimiter: Some(','),
+            ..SyntaxOptArg::default()
+        });
+
+        let args = parse(&syntax, &["--envs", "dev-a,staging-b", "--envs", "prod-c"])
+            .expect("should parse the arguments");
+        assert_eq!(
+            args.get("envs"),
+            Some(&ParseArgsValue::ManyString(vec![
+                Some("dev-a".to_string()),
+                Some("staging-b".to_string()),
+                Some("prod-c".to_string()),
+            ]))
+        );
+
+        // Each value is checked individually, not the whole delimited string
+        assert_rejected(&syntax, &["--envs", "dev-a,qa-b"], "qa-b");
+    }
+
+    #[test]
+    fn env_fallback_is_checked() {
+        let syntax = syntax_with(SyntaxOptArg {
+            names: vec!["--env".to_string()],
+            env: Some("OMNI_TEST_ARG_VALIDATE".to_string()),
+            ..SyntaxOptArg::default()
+        });
+
+        run_with_env(
+            &[(
+                "OMNI_TEST_ARG_VALIDATE".to_string(),
+                Some("qa-1".to_string()),
+            )],
+            || assert_rejected(&syntax, &[], "qa-1"),
+        );
+    }
+}
+
+mod export_repo_org {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn syntax_with(arg_type: SyntaxOptArgType) -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--repo".to_string()],
+                arg_type,
+                export_repo_org: true,
+                ..SyntaxOptArg::default()
+            }],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn canonical(path: &std::path::Path) -> String {
+        std::fs::canonicalize(path)
+            .expect("should canonicalize the path")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn parse_config_value() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --repo\n",
+            "      type: repopath\n",
+            "      export_repo_org: true\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(syntax.parameters[0].export_repo_org);
+        assert_eq!(
+            syntax.parameters[0].repo_org_dest(),
+            Some("repo_org".to_string())
+        );
+    }
+
+    #[test]
+    fn unsupported_on_other_types() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --repo\n",
+            "      type: dir\n",
+            "      export_repo_org: true\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(!syntax.parameters[0].export_repo_org);
+        assert_eq!(syntax.parameters[0].repo_org_dest(), None);
+    }
+
+    #[test]
+    fn filesystem_path_has_no_org() {
+        let dir = tempfile::tempdir().expect("should create a temporary directory");
+        let path = dir.path().to_string_lossy().to_string();
+
+        let syntax = syntax_with(SyntaxOptArgType::RepoPath);
+        let args = syntax
+            .parse_args_typed(vec!["--repo".to_string(), path], vec!["test".to_string()])
+            .expect("should parse the arguments");
+
+        assert_eq!(
+            args.get("repo"),
+            Some(&ParseArgsValue::SingleString(Some(canonical(dir.path()))))
+        );
+        assert_eq!(
+            args.get("repo_org"),
+            Some(&ParseArgsValue::SingleString(Some("".to_string())))
+        );
+    }
+
+    #[test]
+    fn not_provided() {
+        let syntax = syntax_with(SyntaxOptArgType::RepoPath);
+        let args = syntax
+            .parse_args_typed(vec![], vec!["test".to_string()])
+            .expect("should parse the arguments");
+
+        assert_eq!(args.get("repo"), Some(&ParseArgsValue::SingleString(None)));
+        assert_eq!(
+            args.get("repo_org"),
+            Some(&ParseArgsValue::SingleString(None))
+        );
+    }
+
+    #[test]
+    fn array_exports_one_org_per_value() {
+        let first = tempfile::tempdir().expect("should create a temporary directory");
+        let second = tempfile::tempdir().expect("should create a temporary directory");
+
+        let syntax = syntax_with(SyntaxOptArgType::Array(Box::new(
+            SyntaxOptArgType::RepoPath,
+        )));
+        let args = syntax
+            .parse_args(
+                vec![
+                    "--repo".to_string(),
+                    first.path().to_string_lossy().to_string(),
+                    "--repo".to_string(),
+                    second.path().to_string_lossy().to_string(),
+                ],
+                vec!["test".to_string()],
+            )
+            .expect("should parse the arguments");
+
+        assert_eq!(
+            args.get("OMNI_ARG_REPO_VALUE_1"),
+            Some(&canonical(second.path()))
+        );
+        assert_eq!(
+            args.get("OMNI_ARG_REPO_ORG_TYPE"),
+            Some(&"str/2".to_string())
+        );
+        // Repositories found on the filesystem do not have an organization
+        assert_eq!(args.get("OMNI_ARG_REPO_ORG_VALUE_0"), None);
+        assert_eq!(
+            args.get("OMNI_ARG_LIST"),
+            Some(&"repo repo_org".to_string())
+        );
+    }
+
+    #[test]
+    fn org_dest_must_be_unique() {
+        let mut syntax = syntax_with(SyntaxOptArgType::RepoPath);
+        syntax.parameters.push(SyntaxOptArg {
+            names: vec!["--repo-org".to_string()],
+            ..SyntaxOptArg::default()
+        });
+
+        let _colors = disable_colors();
+        assert_eq!(
+            syntax.check_parameters_unique_names(),
+            vec!["identifier repo_org is defined more than once".to_string()]
+        );
+    }
+}
+
+mod hidden_args {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn hidden_syntax() -> CommandSyntax {
+        CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["--verbose".to_string()],
+                    arg_type: SyntaxOptArgType::Flag,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["--wrapper".to_string()],
+                    required: true,
+                    hidden: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["file".to_string()],
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["extra".to_string()],
+                    hidden: true,
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        }
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_config_value() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --wrapper\n",
+            "      hidden: true\n",
+            "    - name: --other\n",
+        ));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(syntax.parameters[0].hidden);
+        assert!(!syntax.parameters[1].hidden);
+    }
+
+    #[test]
+    fn hidden_required_is_rejected() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --wrapper\n",
+            "      required: true\n",
+            "      hidden: true\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert!(syntax.parameters[0].required);
+        assert!(!syntax.parameters[0].hidden);
+    }
+
+    #[test]
+    fn hidden_command() {
+        let config_value =
+            ConfigValue::from_str("run: 'true'\nhidden: true\n").expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+
+        assert!(error_handler.errors().is_empty());
+        assert!(command.hidden);
+    }
+
+    #[test]
+    fn hidden_args_are_parsed_and_exported() {
+        let syntax = hidden_syntax();
+
+        let args = syntax
+            .parse_args(
+                argv(&["--wrapper", "outer", "file.txt", "more"]),
+                vec!["test".to_string()],
+            )
+            .expect("should parse");
+
+        assert_eq!(
+            args.get("OMNI_ARG_WRAPPER_VALUE"),
+            Some(&"outer".to_string())
+        );
+        assert_eq!(args.get("OMNI_ARG_EXTRA_VALUE"), Some(&"more".to_string()));
+        assert_eq!(
+            args.get("OMNI_ARG_FILE_VALUE"),
+            Some(&"file.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn hidden_args_are_still_required() {
+        let syntax = hidden_syntax();
+
+        let err = syntax
+            .parse_args(argv(&["file.txt"]), vec!["test".to_string()])
+            .expect_err("should fail without the hidden required argument");
+
+        assert_eq!(
+            err.simple(),
+            "the following required arguments were not provided: --wrapper <wrapper>"
+        );
+    }
+
+    #[test]
+    fn generated_usage_skips_hidden_args() {
+        let _colors = disable_colors();
+
+        let syntax = hidden_syntax();
+
+        assert_eq!(syntax.generated_usage(), " [OPTIONS] <FILE>");
+    }
+
+    #[test]
+    fn generated_usage_skips_hidden_group_members() {
+        let _colors = disable_colors();
+
+        let mut syntax = hidden_syntax();
+        syntax.parameters[1].required = false;
+        syntax.groups = vec![SyntaxGroup {
+            name: "mode".to_string(),
+            parameters: vec!["verbose".to_string(), "wrapper".to_string()],
+            required: true,
+            ..SyntaxGroup::default()
+        }];
+
+        assert_eq!(syntax.generated_usage(), " <--verbose> <FILE>");
+    }
+
+    #[test]
+    fn help_sections_skip_hidden_args() {
+        let syntax = hidden_syntax();
+
+        let sections = syntax
+            .help_sections(syntax.parameters.iter())
+            .into_iter()
+            .map(|(section, params)| {
+                (
+                    section,
+                    params.iter().map(|param| param.name()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            sections,
+            vec![
+                (
+                    CommandSyntax::ARGUMENTS_SECTION.to_string(),
+                    vec!["file".to_string()]
+                ),
+                (
+                    CommandSyntax::DEFAULT_OPTIONS_SECTION.to_string(),
+                    vec!["--verbose".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_positional_is_checked_for_ordering() {
+        let _colors = disable_colors();
+
+        let syntax = CommandSyntax {
+            parameters: vec![
+                SyntaxOptArg {
+                    names: vec!["param1".to_string()],
+                    hidden: true,
+                    ..SyntaxOptArg::default()
+                },
+                SyntaxOptArg {
+                    names: vec!["param2".to_string()],
+                    required: true,
+                    ..SyntaxOptArg::default()
+                },
+            ],
+            ..CommandSyntax::default()
+        };
+
+        let errmsg =
+            "param2: required positional argument cannot appear after non-required one param1";
+        assert_eq!(
+            syntax.check_parameters_positional(),
+            vec![errmsg.to_string()]
+        );
+    }
+}
+
+mod syntax_opt_arg_range {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn command_from_yaml(parameters: &str) -> (CommandDefinition, Vec<ConfigError>) {
+        let yaml = format!("run: 'true'\nsyntax:\n  parameters:\n{parameters}");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler.errors())
+    }
+
+    fn syntax_from_yaml(parameters: &str) -> CommandSyntax {
+        let (command, errors) = command_from_yaml(parameters);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        command.syntax.expect("syntax should be defined")
+    }
+
+    fn parse(
+        syntax: &CommandSyntax,
+        argv: &[&str],
+    ) -> Result<BTreeMap<String, ParseArgsValue>, ParseArgsErrorKind> {
+        syntax.parse_args_typed(
+            argv.iter().map(|arg| arg.to_string()).collect(),
+            vec!["test".to_string()],
+        )
+    }
+
+    fn assert_out_of_range(syntax: &CommandSyntax, argv: &[&str], expected: &str) {
+        let err = parse(syntax, argv).expect_err("should reject the value");
+        assert_eq!(err.code().as_str(), "E_ARG_INVALID_VALUE");
+
+        let message = err.to_string();
+        assert!(message.contains(expected), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn min_and_max() {
+        let syntax = syntax_from_yaml(concat!(
+            "    - name: --parallelism\n",
+            "      type: int\n",
+            "      min: 1\n",
+            "      max: 64\n",
+        ));
+
+        let args = parse(&syntax, &["--parallelism", "64"]).expect("should parse the arguments");
+        assert_eq!(
+            args.get("parallelism"),
+            Some(&ParseArgsValue::SingleInteger(Some(64)))
+        );
+
+        assert_out_of_range(
+            &syntax,
+            &["--parallelism", "128"],
+            "invalid value '128' for '--parallelism <parallelism>': 128 is not in 1..=64",
+        );
+        assert_out_of_range(&syntax, &["--parallelism", "0"], "0 is not in 1..=64");
+    }
+
+    #[test]
+    fn min_without_max() {
+        let syntax = syntax_from_yaml(concat!(
+            "    - name: --workers\n",
+            "      type: int\n",
+            "      min: 1\n",
+        ));
+
+        assert!(parse(&syntax, &["--workers", "1000"]).is_ok());
+        assert_out_of_range(&syntax, &["--workers", "0"], "0 is not in 1..");
+    }
+
+    #[test]
+    fn max_without_min() {
+        let syntax = syntax_from_yaml(concat!(
+            "    - name: --offset\n",
+            "      type: int\n",
+            "      max: 10\n",
+            "      allow_negative_numbers: true\n",
+        ));
+
+        assert!(parse(&syntax, &["--offset", "-1000"]).is_ok());
+        assert_out_of_range(&syntax, &["--offset", "11"], "11 is not in ..=10");
+    }
+
+    #[test]
+    fn float_with_fractional_bounds() {
+        let syntax = syntax_from_yaml(concat!(
+            "    - name: --ratio\n",
+            "      type: float\n",
+            "      min: 0.5\n",
+            "      max: 2.5\n",
+        ));
+
+        let args = parse(&syntax, &["--ratio", "1.25"]).expect("should parse the arguments");
+        assert_eq!(
+            args.get("ratio"),
+            Some(&ParseArgsValue::SingleFloat(Some(1.25)))
+        );
+
+        assert_out_of_range(&syntax, &["--ratio", "2.75"], "2.75 is not in 0.5..=2.5");
+        assert_out_of_range(&syntax, &["--ratio", "0.25"], "0.25 is not in 0.5..=2.5");
+    }
+
+    #[test]
+    fn array_values_are_checked_individually() {
+        let syntax = syntax_from_yaml(concat!(
+            "    - name: --ports\n",
+            "      type: array/int\n",
+            "      delimiter: ','\n",
+            "      min: 1\n",
+            "      max: 65535\n",
+        ));
+
+        assert!(parse(&syntax, &["--ports", "80,443"]).is_ok());
+        assert_out_of_range(
+            &syntax,
+            &["--ports", "80,70000"],
+            "70000 is not in 1..=65535",
+        );
+    }
+
+    #[test]
+    fn help_desc() {
+        let _colors = disable_colors();
+
+        let syntax = syntax_from_yaml(concat!(
+            "    - name: --parallelism\n",
+            "      desc: How many jobs to run at once\n",
+            "      type: int\n",
+            "      default: 4\n",
+            "      min: 1\n",
+            "      max: 64\n",
+        ));
+
+        assert_eq!(
+            syntax.parameters[0].help_desc(),
+            "How many jobs to run at once [default: 4] [range: 1..=64]"
+        );
+    }
+
+    #[test]
+    fn default_out_of_range() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --parallelism\n",
+            "      type: int\n",
+            "      default: 128\n",
+            "      min: 1\n",
+            "      max: 64\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+        assert_eq!(errors[0].context_str("actual"), "128");
+        assert_eq!(errors[0].context_str("expected"), "1..=64");
+
+        // The default value is dropped, but the bounds are kept
+        let param = &command.syntax.expect("syntax should be defined").parameters[0];
+        assert_eq!(param.default, None);
+        assert_eq!(param.range_str(), Some("1..=64".to_string()));
+    }
+
+    #[test]
+    fn fractional_bound_on_int() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --parallelism\n",
+            "      type: int\n",
+            "      min: 0.5\n",
+            "      max: 64\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+
+        let param = &command.syntax.expect("syntax should be defined").parameters[0];
+        assert_eq!(param.range_str(), Some("..=64".to_string()));
+    }
+
+    #[test]
+    fn min_greater_than_max() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --parallelism\n",
+            "      type: int\n",
+            "      min: 64\n",
+            "      max: 1\n",
+        ));
+
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValue);
+
+        let param = &command.syntax.expect("syntax should be defined").parameters[0];
+        assert_eq!(param.range_str(), None);
+    }
+
+    #[test]
+    fn invalid_on_non_numeric_types() {
+        let (command, errors) = command_from_yaml(concat!(
+            "    - name: --name\n",
+            "      min: 1\n",
+            "      max: 3\n",
+        ));
+
+        assert_eq!(errors.len(), 2, "unexpected errors: {errors:?}");
+        assert!(errors
+            .iter()
+            .all(|error| error.kind() == &ConfigErrorKind::InvalidValue));
+
+        let param = &command.syntax.expect("syntax should be defined").parameters[0];
+        assert_eq!(param.range_str(), None);
+    }
+}
+
+mod syntax_opt_arg_num_values {
+    use super::*;
+    use crate::internal::config::parser::ConfigError;
+
+    fn num_values_from_yaml(yaml: &str) -> (Option<SyntaxOptArgNumValues>, Vec<ConfigError>) {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default()
+            .with_file("test.yaml")
+            .with_key("num_values");
+        let num_values =
+            SyntaxOptArgNumValues::from_config_value(Some(&config_value), &error_handler);
+        (num_values, error_handler.errors())
+    }
+
+    #[test]
+    fn list_with_min_and_max() {
+        let (num_values, errors) = num_values_from_yaml("[0, 0]");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::Between(0, 0)));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let (num_values, errors) = num_values_from_yaml("[2, 5]");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::Between(2, 5)));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn list_with_min_only() {
+        let (num_values, errors) = num_values_from_yaml("[3]");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::AtLeast(3)));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
     }
 
     #[test]
-    fn test_multiple_names() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("-a, --alpha");
-        assert_eq!(names, vec!["-a", "--alpha"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert!(placeholders.is_empty());
-        assert!(!leftovers);
+    fn list_with_zero_min() {
+        let (num_values, errors) = num_values_from_yaml("[0]");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::AtLeast(0)));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let (num_values, errors) = num_values_from_yaml("[0, 3]");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::Between(0, 3)));
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
     }
 
     #[test]
-    fn test_counter_option() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--count...");
-        assert_eq!(names, vec!["--count"]);
-        assert_eq!(arg_type, SyntaxOptArgType::Counter);
-        assert!(placeholders.is_empty());
-        assert!(!leftovers);
+    fn list_with_negative_value() {
+        let (num_values, errors) = num_values_from_yaml("[-1]");
+        assert_eq!(num_values, None);
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert!(
+            errors[0].message().contains("non-negative integer"),
+            "unexpected message: {}",
+            errors[0].message()
+        );
     }
 
     #[test]
-    fn test_positional_with_placeholder() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("arg PLACEHOLDER");
-        assert_eq!(names, vec!["arg"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["PLACEHOLDER"]);
-        assert!(!leftovers);
+    fn list_with_min_greater_than_max() {
+        let (num_values, errors) = num_values_from_yaml("[5, 2]");
+        assert_eq!(num_values, None);
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidRange);
+        assert_eq!(errors[0].context_usize("min"), 5);
+        assert_eq!(errors[0].context_usize("max"), 2);
     }
 
     #[test]
-    fn test_option_with_placeholder() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option VALUE");
-        assert_eq!(names, vec!["--option"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["VALUE"]);
-        assert!(!leftovers);
+    fn list_with_invalid_values() {
+        for yaml in ["[]", "[1, 2, 3]", "[-1]", "[1, two]"] {
+            let (num_values, errors) = num_values_from_yaml(yaml);
+            assert_eq!(num_values, None, "for {yaml}");
+            assert_eq!(errors.len(), 1, "unexpected errors for {yaml}: {errors:?}");
+            assert_eq!(errors[0].kind(), &ConfigErrorKind::InvalidValueType);
+        }
     }
 
     #[test]
-    fn test_multiple_placeholders() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option FIRST SECOND");
-        assert_eq!(names, vec!["--option"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["FIRST", "SECOND"]);
-        assert!(!leftovers);
+    fn range_string_and_integer() {
+        let (num_values, _) = num_values_from_yaml("'2..=5'");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::Between(2, 5)));
+
+        let (num_values, _) = num_values_from_yaml("3");
+        assert_eq!(num_values, Some(SyntaxOptArgNumValues::Exactly(3)));
     }
+}
+
+mod parse_bytes {
+    use super::*;
 
     #[test]
-    fn test_leftovers_positional() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("args...");
-        assert_eq!(names, vec!["args"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert!(placeholders.is_empty());
-        assert!(leftovers);
+    fn without_unit() {
+        assert_eq!(parse_bytes("0"), Ok(0));
+        assert_eq!(parse_bytes("512"), Ok(512));
+        assert_eq!(parse_bytes(" 512 "), Ok(512));
     }
 
     #[test]
-    fn test_multiple_names_with_placeholder_at_the_end() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("-f, --file FILENAME");
-        assert_eq!(names, vec!["-f", "--file"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["FILENAME"]);
-        assert!(!leftovers);
+    fn decimal_units() {
+        assert_eq!(parse_bytes("1B"), Ok(1));
+        assert_eq!(parse_bytes("10KB"), Ok(10_000));
+        assert_eq!(parse_bytes("3MB"), Ok(3_000_000));
+        assert_eq!(parse_bytes("2GB"), Ok(2_000_000_000));
+        assert_eq!(parse_bytes("1TB"), Ok(1_000_000_000_000));
     }
 
     #[test]
-    fn test_multiple_names_with_placeholders_for_each() {
-        let (names, arg_type, placeholders, leftovers) =
-            parse_arg_name("-f FILENAME1, --file FILENAME2");
-        assert_eq!(names, vec!["-f", "--file"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["FILENAME1", "FILENAME2"]);
-        assert!(!leftovers);
+    fn binary_units() {
+        assert_eq!(parse_bytes("10KiB"), Ok(10_240));
+        assert_eq!(parse_bytes("4MiB"), Ok(4 * 1024 * 1024));
+        assert_eq!(parse_bytes("2GiB"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_bytes("1TiB"), Ok(1024_i64.pow(4)));
     }
 
     #[test]
-    fn test_equals_separator() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("--option=VALUE");
-        assert_eq!(names, vec!["--option"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["VALUE"]);
-        assert!(!leftovers);
+    fn units_ignore_case_and_whitespace() {
+        assert_eq!(parse_bytes("10kb"), Ok(10_000));
+        assert_eq!(parse_bytes("10 kib"), Ok(10_240));
+        assert_eq!(parse_bytes("10 MiB"), Ok(10 * 1024 * 1024));
     }
 
     #[test]
-    fn test_empty_input() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("");
-        assert_eq!(names, vec![""]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert!(placeholders.is_empty());
-        assert!(!leftovers);
+    fn unknown_unit() {
+        assert_eq!(
+            parse_bytes("10K"),
+            Err(
+                "unknown unit 'K', expected one of B, KB, MB, GB, TB, KiB, MiB, GiB, TiB"
+                    .to_string()
+            )
+        );
+        assert!(parse_bytes("-1KB").is_err());
     }
 
     #[test]
-    fn test_whitespace_handling() {
-        let (names, arg_type, placeholders, leftovers) = parse_arg_name("  --option  VALUE  ");
-        assert_eq!(names, vec!["--option"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["VALUE"]);
-        assert!(!leftovers);
+    fn fractional() {
+        let expected = Err(
+            "fractional sizes are not supported, use a smaller unit instead, e.g. 1536MiB"
+                .to_string(),
+        );
+        assert_eq!(parse_bytes("1.5GB"), expected);
+        assert_eq!(parse_bytes("1.5"), expected);
     }
 
     #[test]
-    fn test_multiple_names_whitespace() {
-        let (names, arg_type, placeholders, leftovers) =
-            parse_arg_name("-f,   --file,  -F  FILENAME");
-        assert_eq!(names, vec!["-f", "--file", "-F"]);
-        assert_eq!(arg_type, SyntaxOptArgType::String);
-        assert_eq!(placeholders, vec!["FILENAME"]);
-        assert!(!leftovers);
+    fn too_large() {
+        assert_eq!(
+            parse_bytes("9000000TiB"),
+            Err(format!(
+                "size is too large, the maximum is {} bytes",
+                i64::MAX
+            ))
+        );
+        assert!(parse_bytes("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn resolve_sizes() {
+        assert_eq!(resolve_bytes("1KB, 2KiB,3"), "1000,2048,3");
+        assert_eq!(resolve_bytes("1KB,invalid"), "1000,invalid");
     }
 }
 
-mod syntax_opt_arg_type {
+mod deprecated_args {
     use super::*;
-    use crate::internal::config::ConfigValue;
 
-    #[test]
-    fn test_from_config_value_list_as_enum() {
-        let error_handler = ConfigErrorHandler::default();
+    fn deprecated_syntax() -> CommandSyntax {
+        let yaml = concat!(
+            "run: 'true'\n",
+            "syntax:\n",
+            "  parameters:\n",
+            "    - names:\n",
+            "        - --new-flag\n",
+            "        - -n\n",
+            "        - name: --old-flag\n",
+            "          deprecated: use --new-flag\n",
+            "        - name: -o\n",
+            "          deprecated: use -n\n",
+            "      type: flag\n",
+            "    - name: --old-level\n",
+            "      default: info\n",
+            "      deprecated: use --log-level\n",
+        );
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        assert!(
+            error_handler.errors().is_empty(),
+            "unexpected errors: {:?}",
+            error_handler.errors()
+        );
 
-        // Test with array of strings as type
-        let type_value = ConfigValue::from_str("[debug, info, warn, error]").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+        command.syntax.expect("syntax should be defined")
+    }
 
-        assert_eq!(
-            result,
-            Some(SyntaxOptArgType::Enum(vec![
-                "debug".to_string(),
-                "info".to_string(),
-                "warn".to_string(),
-                "error".to_string(),
-            ]))
-        );
+    fn warnings(args: &[&str]) -> Vec<String> {
+        let syntax = deprecated_syntax();
+        let parser = syntax
+            .argparser(vec!["test".to_string()])
+            .expect("failed to build parser");
+        let argv = std::iter::once("")
+            .chain(args.iter().copied())
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+        let matches = parser.try_get_matches_from(&argv).expect("should parse");
+
+        syntax.deprecation_warnings(&matches, &argv)
     }
 
     #[test]
-    fn test_from_config_value_traditional_enum() {
-        let error_handler = ConfigErrorHandler::default();
+    fn parse_config_value() {
+        let syntax = deprecated_syntax();
 
-        // Test traditional enum syntax with separate values
-        let type_value = ConfigValue::from_str("enum").unwrap();
-        let values_value = ConfigValue::from_str("[one, two, three]").unwrap();
-        let result = SyntaxOptArgType::from_config_value(
-            Some(&type_value),
-            Some(&values_value),
-            None,
-            &error_handler,
+        assert_eq!(
+            syntax.parameters[0].names,
+            vec!["--new-flag", "-n", "--old-flag", "-o"]
         );
-
         assert_eq!(
-            result,
-            Some(SyntaxOptArgType::Enum(vec![
-                "one".to_string(),
-                "two".to_string(),
-                "three".to_string(),
-            ]))
+            syntax.parameters[0].deprecated_names,
+            BTreeMap::from([
+                ("--old-flag".to_string(), "use --new-flag".to_string()),
+                ("-o".to_string(), "use -n".to_string()),
+            ])
+        );
+        assert_eq!(syntax.parameters[0].deprecated, None);
+        assert_eq!(
+            syntax.parameters[1].deprecated,
+            Some("use --log-level".to_string())
         );
     }
 
     #[test]
-    fn test_from_config_value_inline_enum() {
-        let error_handler = ConfigErrorHandler::default();
+    fn deprecated_name_is_never_the_main_name() {
+        let syntax = CommandSyntax {
+            parameters: vec![SyntaxOptArg {
+                names: vec!["--old-flag".to_string(), "--new-flag".to_string()],
+                arg_type: SyntaxOptArgType::Flag,
+                deprecated_names: BTreeMap::from([(
+                    "--old-flag".to_string(),
+                    "use --new-flag".to_string(),
+                )]),
+                ..SyntaxOptArg::default()
+            }],
+            ..CommandSyntax::default()
+        };
+
+        assert_eq!(syntax.parameters[0].name(), "--new-flag");
+    }
 
-        // Test inline enum syntax
-        let type_value = ConfigValue::from_str("enum(fast, safe, rollback)").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+    #[test]
+    fn names_without_name_are_rejected() {
+        let config_value = ConfigValue::from_str(concat!(
+            "run: 'true'\n",
+            "syntax:\n",
+            "  parameters:\n",
+            "    - names:\n",
+            "        - --new-flag\n",
+            "        - deprecated: use --new-flag\n",
+        ))
+        .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::MissingKey);
+
+        let syntax = command.syntax.expect("syntax should be defined");
+        assert_eq!(syntax.parameters[0].names, vec!["--new-flag"]);
+    }
 
+    #[test]
+    fn warns_when_deprecated_name_is_used() {
         assert_eq!(
-            result,
-            Some(SyntaxOptArgType::Enum(vec![
-                "fast".to_string(),
-                "safe".to_string(),
-                "rollback".to_string(),
-            ]))
+            warnings(&["--old-flag"]),
+            vec!["--old-flag is deprecated: use --new-flag"]
         );
+        assert_eq!(warnings(&["-o"]), vec!["-o is deprecated: use -n"]);
     }
 
     #[test]
-    fn test_from_config_value_basic_types() {
-        let error_handler = ConfigErrorHandler::default();
+    fn warns_when_deprecated_arg_is_provided() {
+        assert_eq!(
+            warnings(&["--old-level", "debug"]),
+            vec!["--old-level is deprecated: use --log-level"]
+        );
+        assert_eq!(
+            warnings(&["--old-level=debug"]),
+            vec!["--old-level is deprecated: use --log-level"]
+        );
+    }
 
-        // Test basic string type
-        let type_value = ConfigValue::from_str("str").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
-        assert_eq!(result, Some(SyntaxOptArgType::String));
+    #[test]
+    fn no_warning_for_current_names() {
+        assert!(warnings(&["--new-flag"]).is_empty());
+        assert!(warnings(&["-n"]).is_empty());
+    }
 
-        // Test integer type
-        let type_value = ConfigValue::from_str("int").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
-        assert_eq!(result, Some(SyntaxOptArgType::Integer));
+    #[test]
+    fn no_warning_for_defaulted_arg() {
+        assert!(warnings(&[]).is_empty());
+    }
 
-        // Test boolean type
-        let type_value = ConfigValue::from_str("bool").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
-        assert_eq!(result, Some(SyntaxOptArgType::Boolean));
+    #[test]
+    fn deprecated_names_feed_the_same_value() {
+        for flag in ["--new-flag", "--old-flag", "-o"] {
+            let args = deprecated_syntax()
+                .parse_args(vec![flag.to_string()], vec!["test".to_string()])
+                .expect("should parse");
+
+            assert_eq!(
+                args.get("OMNI_ARG_NEW_FLAG_VALUE"),
+                Some(&"true".to_string()),
+                "unexpected value when using {flag}"
+            );
+            assert_eq!(args.get("OMNI_ARG_OLD_FLAG_VALUE"), None);
+        }
     }
 
     #[test]
-    fn test_from_config_value_empty_list() {
-        let error_handler = ConfigErrorHandler::default();
+    fn deprecated_names_are_hidden_from_help() {
+        let syntax = deprecated_syntax();
+        let help_desc = syntax.parameters[0].help_desc();
 
-        // Test with empty array
-        let type_value = ConfigValue::from_str("[]").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+        assert!(!help_desc.contains("aliases"), "{help_desc}");
+        assert!(!help_desc.contains("--old-flag"), "{help_desc}");
+    }
+}
 
-        assert_eq!(result, Some(SyntaxOptArgType::Enum(vec![])));
+mod deprecated_aliases {
+    use super::*;
+
+    fn command_from_yaml(yaml: &str) -> CommandDefinition {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        assert!(
+            error_handler.errors().is_empty(),
+            "unexpected errors: {:?}",
+            error_handler.errors()
+        );
+        command
     }
 
     #[test]
-    fn test_from_config_value_single_item_list() {
-        let error_handler = ConfigErrorHandler::default();
+    fn plain_aliases() {
+        let command = command_from_yaml("run: 'true'\naliases: [short, other]\n");
 
-        // Test with single item array
-        let type_value = ConfigValue::from_str("[debug]").unwrap();
-        let result =
-            SyntaxOptArgType::from_config_value(Some(&type_value), None, None, &error_handler);
+        assert_eq!(command.aliases, vec!["short", "other"]);
+        assert!(command.deprecated_aliases.is_empty());
+    }
+
+    #[test]
+    fn single_plain_alias() {
+        let command = command_from_yaml("run: 'true'\naliases: short\n");
+
+        assert_eq!(command.aliases, vec!["short"]);
+    }
 
+    #[test]
+    fn mixed_aliases() {
+        let command = command_from_yaml(concat!(
+            "run: 'true'\n",
+            "aliases:\n",
+            "  - short\n",
+            "  - name: oldname\n",
+            "    deprecated: use newname\n",
+            "  - name: other\n",
+        ));
+
+        assert_eq!(command.aliases, vec!["short", "oldname", "other"]);
         assert_eq!(
-            result,
-            Some(SyntaxOptArgType::Enum(vec!["debug".to_string()]))
+            command.deprecated_aliases,
+            BTreeMap::from([("oldname".to_string(), "use newname".to_string())])
         );
     }
 
     #[test]
-    fn test_from_config_value_precedence() {
-        let error_handler = ConfigErrorHandler::default();
+    fn alias_without_name_is_rejected() {
+        let config_value = ConfigValue::from_str(concat!(
+            "run: 'true'\n",
+            "aliases:\n",
+            "  - deprecated: use newname\n",
+        ))
+        .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+
+        assert!(command.aliases.is_empty());
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(errors[0].kind(), &ConfigErrorKind::MissingKey);
+    }
+}
 
-        // Test that list syntax takes precedence over values field
-        let type_value = ConfigValue::from_str("[debug, info]").unwrap();
-        let values_value = ConfigValue::from_str("[ignored, values]").unwrap();
-        let result = SyntaxOptArgType::from_config_value(
-            Some(&type_value),
-            Some(&values_value),
-            None,
-            &error_handler,
+mod command_env {
+    use super::*;
+
+    use crate::internal::config::parser::EnvOperationEnum;
+
+    fn command_with_env(env: &str) -> (CommandDefinition, ConfigErrorHandler) {
+        let yaml = format!("run: make test\nenv: {env}\n");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler)
+    }
+
+    #[test]
+    fn from_config_value() {
+        let (command, error_handler) = command_with_env(
+            "[{RUST_LOG: debug}, {PATH: {prepend: /opt/bin}}, {OMNI_TEST_UNSET: null}]",
         );
 
-        // Should use the list from type, not values
+        assert!(error_handler.errors().is_empty());
+
+        let operations = command
+            .env
+            .iter()
+            .map(|op| (op.name.as_str(), op.value.as_deref(), op.operation))
+            .collect::<Vec<_>>();
         assert_eq!(
-            result,
-            Some(SyntaxOptArgType::Enum(vec![
-                "debug".to_string(),
-                "info".to_string(),
-            ]))
+            operations,
+            vec![
+                ("RUST_LOG", Some("debug"), EnvOperationEnum::Set),
+                ("PATH", Some("/opt/bin"), EnvOperationEnum::Prepend),
+                ("OMNI_TEST_UNSET", None, EnvOperationEnum::Set),
+            ]
         );
     }
+
+    #[test]
+    fn empty_by_default() {
+        let config_value = ConfigValue::from_str("run: make test").expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default();
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+
+        assert!(error_handler.errors().is_empty());
+        assert!(command.env.is_empty());
+    }
+
+    #[test]
+    fn invalid_type() {
+        let (command, error_handler) = command_with_env("not-a-list");
+
+        assert!(command.env.is_empty());
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].key(), Some("env"));
+    }
+}
+
+mod command_timeout {
+    use super::*;
+
+    fn command_with_timeout(timeout: &str) -> (CommandDefinition, ConfigErrorHandler) {
+        let yaml = format!("run: make test\ntimeout: {timeout}\n");
+        let config_value = ConfigValue::from_str(&yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+        (command, error_handler)
+    }
+
+    #[test]
+    fn from_config_value() {
+        for (timeout, expected) in [("30s", 30), ("5m", 300), ("45", 45), ("0", 0)] {
+            let (command, error_handler) = command_with_timeout(timeout);
+
+            assert!(error_handler.errors().is_empty(), "{timeout}");
+            assert_eq!(command.timeout, Some(expected), "{timeout}");
+        }
+    }
+
+    #[test]
+    fn not_set_by_default() {
+        let config_value = ConfigValue::from_str("run: make test").expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default();
+        let command = CommandDefinition::from_config_value(&config_value, &error_handler);
+
+        assert!(error_handler.errors().is_empty());
+        assert_eq!(command.timeout, None);
+    }
+
+    #[test]
+    fn invalid_duration() {
+        let (command, error_handler) = command_with_timeout("forever");
+
+        assert_eq!(command.timeout, Some(0));
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].key(), Some("timeout"));
+    }
 }
@@ -0,0 +1,110 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommandResolutionConfig {
+    pub ignore_case: bool,
+    pub substring: bool,
+    /// Read from `commands.autocorrect`, next to the commands it applies to
+    #[serde(skip)]
+    pub autocorrect: bool,
+    pub max_distance: u64,
+    pub non_interactive: bool,
+}
+
+impl Default for CommandResolutionConfig {
+    fn default() -> Self {
+        Self {
+            ignore_case: Self::DEFAULT_IGNORE_CASE,
+            substring: Self::DEFAULT_SUBSTRING,
+            autocorrect: Self::DEFAULT_AUTOCORRECT,
+            max_distance: Self::DEFAULT_MAX_DISTANCE,
+            non_interactive: Self::DEFAULT_NON_INTERACTIVE,
+        }
+    }
+}
+
+impl CommandResolutionConfig {
+    const DEFAULT_IGNORE_CASE: bool = true;
+    const DEFAULT_SUBSTRING: bool = false;
+    const DEFAULT_AUTOCORRECT: bool = false;
+    const DEFAULT_MAX_DISTANCE: u64 = 2;
+    const DEFAULT_NON_INTERACTIVE: bool = false;
+
+    /// Parses the `command_resolution` configuration, along with the
+    /// `autocorrect` setting of the `commands` configuration; the latter
+    /// is ignored if it is a table, as it is then the definition of a
+    /// command named `autocorrect`
+    pub(super) fn from_config_value(
+        config_value: Option<ConfigValue>,
+        commands_config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let autocorrect = match commands_config_value
+            .and_then(|commands| commands.get("autocorrect"))
+            .filter(|value| !value.is_table())
+        {
+            Some(value) => match value.as_bool_forced() {
+                Some(autocorrect) => autocorrect,
+                None => {
+                    error_handler
+                        .with_key("commands")
+                        .with_key("autocorrect")
+                        .with_expected("bool")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+                    Self::DEFAULT_AUTOCORRECT
+                }
+            },
+            None => Self::DEFAULT_AUTOCORRECT,
+        };
+
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => {
+                return Self {
+                    autocorrect,
+                    ..Self::default()
+                }
+            }
+        };
+
+        let error_handler = error_handler.with_key("command_resolution");
+        Self {
+            ignore_case: config_value.get_as_bool_or_default(
+                "ignore_case",
+                Self::DEFAULT_IGNORE_CASE,
+                &error_handler.with_key("ignore_case"),
+            ),
+            substring: config_value.get_as_bool_or_default(
+                "substring",
+                Self::DEFAULT_SUBSTRING,
+                &error_handler.with_key("substring"),
+            ),
+            autocorrect,
+            max_distance: config_value
+                .get_as_unsigned_integer("max_distance")
+                .unwrap_or(Self::DEFAULT_MAX_DISTANCE),
+            non_interactive: config_value.get_as_bool_or_default(
+                "non_interactive",
+                Self::DEFAULT_NON_INTERACTIVE,
+                &error_handler.with_key("non_interactive"),
+            ),
+        }
+    }
+
+    /// Whether the lenient resolution of the command names can be used;
+    /// unless explicitly enabled, it is only used when stdin is a terminal
+    /// so that scripts keep calling exactly the commands they name
+    pub fn allows_leniency(&self, stdin_is_terminal: bool) -> bool {
+        stdin_is_terminal || self.non_interactive
+    }
+}
+
+#[cfg(test)]
+#[path = "command_resolution_test.rs"]
+mod tests;
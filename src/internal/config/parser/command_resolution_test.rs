@@ -0,0 +1,89 @@
+use super::*;
+
+fn resolution_from_str(yaml: &str) -> CommandResolutionConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    CommandResolutionConfig::from_config_value(
+        config_value.get("command_resolution"),
+        config_value.get("commands"),
+        &ConfigErrorHandler::noop(),
+    )
+}
+
+mod from_config_value {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let config =
+            CommandResolutionConfig::from_config_value(None, None, &ConfigErrorHandler::noop());
+
+        assert!(config.ignore_case);
+        assert!(!config.substring);
+        assert!(!config.autocorrect);
+        assert_eq!(config.max_distance, 2);
+        assert!(!config.non_interactive);
+    }
+
+    #[test]
+    fn all_values() {
+        let config = resolution_from_str(concat!(
+            "command_resolution:\n",
+            "  ignore_case: false\n",
+            "  substring: true\n",
+            "  max_distance: 3\n",
+            "  non_interactive: true\n",
+            "commands:\n",
+            "  autocorrect: true\n",
+        ));
+
+        assert_eq!(
+            config,
+            CommandResolutionConfig {
+                ignore_case: false,
+                substring: true,
+                autocorrect: true,
+                max_distance: 3,
+                non_interactive: true,
+            }
+        );
+    }
+
+    #[test]
+    fn autocorrect_under_command_resolution_is_ignored() {
+        let config = resolution_from_str("command_resolution:\n  autocorrect: true\n");
+
+        assert!(!config.autocorrect);
+    }
+
+    #[test]
+    fn autocorrect_command_is_not_a_setting() {
+        let config = resolution_from_str("commands:\n  autocorrect:\n    run: echo hello\n");
+
+        assert!(!config.autocorrect);
+    }
+}
+
+mod allows_leniency {
+    use super::*;
+
+    #[test]
+    fn on_terminal() {
+        let config = CommandResolutionConfig::default();
+
+        assert!(config.allows_leniency(true));
+    }
+
+    #[test]
+    fn not_on_scripts_by_default() {
+        let config = CommandResolutionConfig::default();
+
+        assert!(!config.allows_leniency(false));
+    }
+
+    #[test]
+    fn on_scripts_when_enabled() {
+        let config = resolution_from_str("command_resolution:\n  non_interactive: true\n");
+
+        assert!(config.allows_leniency(false));
+    }
+}
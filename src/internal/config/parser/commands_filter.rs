@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::utils::Empty;
+use crate::internal::config::parser::check::tag_filters_from_config_value;
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::parser::CommandDefinition;
+use crate::internal::config::parser::StringFilter;
+use crate::internal::config::ConfigValue;
+
+/// Filters which of the commands defined in the configuration files are
+/// made available. Filters are matched against the top-level command
+/// names, and subcommands follow their parent command.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CommandsFilterConfig {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub include_tags: HashMap<String, StringFilter>,
+}
+
+impl Empty for CommandsFilterConfig {
+    fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.include_tags.is_empty()
+    }
+}
+
+impl CommandsFilterConfig {
+    pub(super) fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            return Self::default();
+        }
+
+        let include = config_value.get_as_str_array("include", &error_handler.with_key("include"));
+        let exclude = config_value.get_as_str_array("exclude", &error_handler.with_key("exclude"));
+        let include_tags = tag_filters_from_config_value(
+            config_value.get("include_tags"),
+            &error_handler.with_key("include_tags"),
+        );
+
+        Self {
+            include,
+            exclude,
+            include_tags,
+        }
+    }
+
+    /// Returns whether a command with the given name and tags is allowed
+    /// by the filter; a command is allowed if it matches any of the
+    /// `include` patterns and all of the `include_tags` filters, when
+    /// provided, and does not match any of the `exclude` patterns.
+    pub fn is_allowed(&self, name: &str, tags: &BTreeMap<String, String>) -> bool {
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|pat| pat.matches(name)))
+        };
+
+        if !self.include.is_empty() && !matches_any(&self.include) {
+            return false;
+        }
+
+        if matches_any(&self.exclude) {
+            return false;
+        }
+
+        self.include_tags
+            .iter()
+            .all(|(tag, filter)| match tags.get(tag) {
+                Some(value) => filter.matches(value),
+                None => false,
+            })
+    }
+
+    /// Splits the provided command definitions between the ones that are
+    /// allowed by the filter and the ones that are filtered out.
+    pub fn partition(
+        &self,
+        commands: HashMap<String, CommandDefinition>,
+    ) -> (
+        HashMap<String, CommandDefinition>,
+        HashMap<String, CommandDefinition>,
+    ) {
+        if self.is_empty() {
+            return (commands, HashMap::new());
+        }
+
+        commands
+            .into_iter()
+            .partition(|(name, command)| self.is_allowed(name, &command.tags))
+    }
+
+    /// Returns the name of the filtered out command that would have been
+    /// called by the provided arguments, if any; this allows to give a
+    /// hint to the user when a command cannot be found.
+    pub fn filtered_out_command(
+        &self,
+        commands: &HashMap<String, CommandDefinition>,
+        argv: &[String],
+    ) -> Option<String> {
+        let (_, filtered_out) = self.partition(commands.clone());
+        let argv = split_command_name(&argv.join(" "));
+
+        filtered_out
+            .into_iter()
+            .filter(|(name, command)| {
+                std::iter::once(name)
+                    .chain(command.aliases.iter())
+                    .any(|name| {
+                        let name = split_command_name(name);
+                        !name.is_empty() && argv.starts_with(&name)
+                    })
+            })
+            .map(|(name, _)| name)
+            .min()
+    }
+}
+
+fn split_command_name(name: &str) -> Vec<String> {
+    name.split([' ', '-', '/'])
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "commands_filter_test.rs"]
+mod tests;
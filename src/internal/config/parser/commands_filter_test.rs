@@ -0,0 +1,219 @@
+use super::*;
+
+fn filter_from_str(yaml: &str) -> CommandsFilterConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    CommandsFilterConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop())
+}
+
+fn commands_from_str(yaml: &str) -> HashMap<String, CommandDefinition> {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    config_value
+        .as_table()
+        .expect("commands should be a table")
+        .into_iter()
+        .map(|(name, value)| {
+            (
+                name.clone(),
+                CommandDefinition::from_config_value(&value, &ConfigErrorHandler::noop()),
+            )
+        })
+        .collect()
+}
+
+fn kept_names(
+    filter: &CommandsFilterConfig,
+    commands: HashMap<String, CommandDefinition>,
+) -> Vec<String> {
+    let (kept, _) = filter.partition(commands);
+    let mut names: Vec<String> = kept.into_keys().collect();
+    names.sort();
+    names
+}
+
+const COMMANDS: &str = r#"
+deploy:
+  run: echo deploy
+  tags:
+    team: infra
+  subcommands:
+    prod:
+      run: echo prod
+    staging:
+      run: echo staging
+db-migrate:
+  run: echo migrate
+  aliases:
+    - migrate
+  tags:
+    team: data
+db-seed:
+  run: echo seed
+lint:
+  run: echo lint
+"#;
+
+mod from_config_value {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let filter = CommandsFilterConfig::from_config_value(None, &ConfigErrorHandler::noop());
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn full() {
+        let filter = filter_from_str(
+            r#"
+            include: ["db-*", lint]
+            exclude: db-seed
+            include_tags:
+              team: data
+            "#,
+        );
+
+        assert_eq!(filter.include, vec!["db-*", "lint"]);
+        assert_eq!(filter.exclude, vec!["db-seed"]);
+        assert_eq!(
+            filter.include_tags.get("team"),
+            Some(&StringFilter::Glob("data".to_string()))
+        );
+    }
+}
+
+mod partition {
+    use super::*;
+
+    #[test]
+    fn no_filter_keeps_everything() {
+        let filter = CommandsFilterConfig::default();
+        assert_eq!(
+            kept_names(&filter, commands_from_str(COMMANDS)),
+            vec!["db-migrate", "db-seed", "deploy", "lint"]
+        );
+    }
+
+    #[test]
+    fn include_only() {
+        let filter = filter_from_str("include: ['db-*']");
+        assert_eq!(
+            kept_names(&filter, commands_from_str(COMMANDS)),
+            vec!["db-migrate", "db-seed"]
+        );
+    }
+
+    #[test]
+    fn exclude_only() {
+        let filter = filter_from_str("exclude: ['db-*', lint]");
+        assert_eq!(
+            kept_names(&filter, commands_from_str(COMMANDS)),
+            vec!["deploy"]
+        );
+    }
+
+    #[test]
+    fn include_and_exclude() {
+        let filter = filter_from_str("{include: ['db-*'], exclude: [db-seed]}");
+        assert_eq!(
+            kept_names(&filter, commands_from_str(COMMANDS)),
+            vec!["db-migrate"]
+        );
+    }
+
+    #[test]
+    fn include_tags() {
+        let filter = filter_from_str("include_tags: {team: '*'}");
+        assert_eq!(
+            kept_names(&filter, commands_from_str(COMMANDS)),
+            vec!["db-migrate", "deploy"]
+        );
+
+        let filter = filter_from_str("include_tags: [{team: infra}]");
+        assert_eq!(
+            kept_names(&filter, commands_from_str(COMMANDS)),
+            vec!["deploy"]
+        );
+    }
+
+    #[test]
+    fn subcommands_follow_their_parent() {
+        // The subcommand names do not match the include pattern, but
+        // are kept since their parent is
+        let filter = filter_from_str("include: [deploy]");
+        let (kept, _) = filter.partition(commands_from_str(COMMANDS));
+        let subcommands = kept
+            .get("deploy")
+            .and_then(|command| command.subcommands.clone())
+            .expect("subcommands should be kept");
+        assert!(subcommands.contains_key("prod"));
+        assert!(subcommands.contains_key("staging"));
+
+        // The subcommand names match the include pattern, but are
+        // filtered out since their parent is
+        let filter = filter_from_str("include: [prod, staging]");
+        let (kept, filtered_out) = filter.partition(commands_from_str(COMMANDS));
+        assert!(kept.is_empty());
+        assert!(filtered_out.contains_key("deploy"));
+    }
+}
+
+mod filtered_out_command {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_excluded_command() {
+        let filter = filter_from_str("exclude: ['db-*']");
+        let commands = commands_from_str(COMMANDS);
+
+        assert_eq!(
+            filter.filtered_out_command(&commands, &argv(&["db", "seed"])),
+            Some("db-seed".to_string())
+        );
+        assert_eq!(
+            filter.filtered_out_command(&commands, &argv(&["db-seed", "--force"])),
+            Some("db-seed".to_string())
+        );
+    }
+
+    #[test]
+    fn matches_excluded_command_alias() {
+        let filter = filter_from_str("exclude: ['db-*']");
+        let commands = commands_from_str(COMMANDS);
+
+        assert_eq!(
+            filter.filtered_out_command(&commands, &argv(&["migrate"])),
+            Some("db-migrate".to_string())
+        );
+    }
+
+    #[test]
+    fn matches_subcommand_of_excluded_command() {
+        let filter = filter_from_str("include: ['db-*']");
+        let commands = commands_from_str(COMMANDS);
+
+        assert_eq!(
+            filter.filtered_out_command(&commands, &argv(&["deploy", "prod"])),
+            Some("deploy".to_string())
+        );
+    }
+
+    #[test]
+    fn ignores_allowed_and_unknown_commands() {
+        let filter = filter_from_str("exclude: ['db-*']");
+        let commands = commands_from_str(COMMANDS);
+
+        assert_eq!(
+            filter.filtered_out_command(&commands, &argv(&["lint"])),
+            None
+        );
+        assert_eq!(
+            filter.filtered_out_command(&commands, &argv(&["unknown"])),
+            None
+        );
+        assert_eq!(filter.filtered_out_command(&commands, &argv(&["db"])), None);
+    }
+}
@@ -1,6 +1,11 @@
 use std::collections::HashMap;
+use std::io::Read;
 use std::ops::Deref;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
 
 use itertools::Itertools;
 use serde::Deserialize;
@@ -106,9 +111,125 @@ pub struct EnvOperationConfig {
     pub name: String,
     pub value: Option<String>,
     pub operation: EnvOperationEnum,
+    /// The command whose output is the value of the variable, if the
+    /// value is not provided directly
+    pub command: Option<EnvCommand>,
+}
+
+/// A command whose output provides the value of an environment variable
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct EnvCommand {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "EnvCommandWhen::is_default")]
+    pub when: EnvCommandWhen,
+}
+
+/// When the command providing the value of an environment variable is run
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum EnvCommandWhen {
+    /// The command is run during `omni up`, and its output is stored
+    /// in the environment as the value of the variable
+    #[default]
+    #[serde(rename = "up")]
+    Up,
+    /// The command is stored in the environment, and run each time the
+    /// dynamic environment is applied
+    #[serde(rename = "apply")]
+    Apply,
+}
+
+impl EnvCommandWhen {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The maximum time a command providing the value of an environment
+/// variable can take when run during `omni up`
+pub const ENV_COMMAND_UP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The maximum time a command providing the value of an environment
+/// variable can take when run while applying the dynamic environment,
+/// which happens in the shell prompt and thus needs to be fast
+pub const ENV_COMMAND_APPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Runs the command providing the value of an environment variable from
+/// the given directory, returning its output trimmed of trailing newlines;
+/// the command is killed if it does not finish before the timeout, and
+/// the error describes why no value could be obtained
+pub fn env_command_output(command: &str, cwd: &Path, timeout: Duration) -> Result<String, String> {
+    let mut child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("failed to run: {err}"))?;
+
+    // Read the output in a separate thread, so that the command cannot
+    // be blocked by a full pipe while we are waiting for it
+    let mut stdout = child.stdout.take().expect("stdout should be piped");
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs_f64()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(err) => return Err(format!("failed to wait: {err}")),
+        }
+    };
+
+    if !status.success() {
+        return Err(match status.code() {
+            Some(code) => format!("exited with code {code}"),
+            None => "terminated by a signal".to_string(),
+        });
+    }
+
+    let output = match reader.join() {
+        Ok(Ok(output)) => output,
+        _ => return Err("failed to read the output".to_string()),
+    };
+
+    Ok(String::from_utf8_lossy(&output)
+        .trim_end_matches(['\n', '\r'])
+        .to_string())
 }
 
 impl EnvOperationConfig {
+    /// Runs the command providing the value of the variable if it is to
+    /// be run during `omni up`, storing its output as the value; if the
+    /// command fails, the variable is unset and the error is returned
+    pub fn run_up_command(&mut self, cwd: &Path) -> Result<(), String> {
+        let command = match &self.command {
+            Some(command) if command.when == EnvCommandWhen::Up => command.command.clone(),
+            _ => return Ok(()),
+        };
+
+        self.command = None;
+        match env_command_output(&command, cwd, ENV_COMMAND_UP_TIMEOUT) {
+            Ok(output) => {
+                self.value = Some(output);
+                Ok(())
+            }
+            Err(err) => {
+                self.value = None;
+                Err(err)
+            }
+        }
+    }
+
     fn from_config_value_multi(
         name: &str,
         config_value: &ConfigValue,
@@ -154,6 +275,10 @@ impl EnvOperationConfig {
         operation: EnvOperationEnum,
         error_handler: &ConfigErrorHandler,
     ) -> Option<Self> {
+        if let Some(command) = table.get("command") {
+            return Self::from_command_table(name, &table, command, operation, error_handler);
+        }
+
         let value_type = match table.get("type") {
             Some(value_type) => match value_type.as_str() {
                 Some(vtype) if vtype == "text" || vtype == "path" => vtype.to_string(),
@@ -247,6 +372,71 @@ impl EnvOperationConfig {
             name: name.to_string(),
             value,
             operation,
+            command: None,
+        })
+    }
+
+    fn from_command_table(
+        name: &str,
+        table: &HashMap<String, ConfigValue>,
+        command: &ConfigValue,
+        operation: EnvOperationEnum,
+        error_handler: &ConfigErrorHandler,
+    ) -> Option<Self> {
+        if operation != EnvOperationEnum::Set {
+            error_handler
+                .with_key("command")
+                .with_actual(command)
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+
+            return None;
+        }
+
+        let command = match command.as_str() {
+            Some(command) if !command.trim().is_empty() => command,
+            Some(_) => {
+                error_handler
+                    .with_key("command")
+                    .error(ConfigErrorKind::EmptyKey);
+
+                return None;
+            }
+            None => {
+                error_handler
+                    .with_key("command")
+                    .with_expected("string")
+                    .with_actual(command)
+                    .error(ConfigErrorKind::InvalidValueType);
+
+                return None;
+            }
+        };
+
+        let when = match table.get("when") {
+            Some(when) => match when.as_str().as_deref() {
+                Some("up") => EnvCommandWhen::Up,
+                Some("apply") => EnvCommandWhen::Apply,
+                _ => {
+                    error_handler
+                        .with_key("when")
+                        .with_expected(vec!["up", "apply"])
+                        .with_actual(when)
+                        .error(ConfigErrorKind::InvalidValue);
+
+                    return None;
+                }
+            },
+            None => EnvCommandWhen::default(),
+        };
+
+        Some(Self {
+            name: name.to_string(),
+            value: None,
+            operation,
+            command: Some(EnvCommand {
+                command: command.to_string(),
+                when,
+            }),
         })
     }
 
@@ -349,6 +539,26 @@ impl EnvOperationConfig {
                 ))
             }
 
+            if let Some(config_value) = table.get("prepend_path") {
+                matched_any = true;
+                operations.extend(Self::from_config_value_multi(
+                    name,
+                    config_value,
+                    EnvOperationEnum::PrependPath,
+                    &error_handler.with_key("prepend_path"),
+                ))
+            }
+
+            if let Some(config_value) = table.get("append_path") {
+                matched_any = true;
+                operations.extend(Self::from_config_value_multi(
+                    name,
+                    config_value,
+                    EnvOperationEnum::AppendPath,
+                    &error_handler.with_key("append_path"),
+                ))
+            }
+
             if matched_any {
                 return operations;
             }
@@ -373,16 +583,25 @@ impl Serialize for EnvOperationConfig {
         S: serde::Serializer,
     {
         match self.operation {
-            EnvOperationEnum::Set => {
-                let mut env_var = HashMap::new();
-                env_var.insert(self.name.clone(), self.value.clone());
-                env_var.serialize(serializer)
-            }
+            EnvOperationEnum::Set => match &self.command {
+                Some(command) => {
+                    let mut env_var = HashMap::new();
+                    env_var.insert(self.name.clone(), command);
+                    env_var.serialize(serializer)
+                }
+                None => {
+                    let mut env_var = HashMap::new();
+                    env_var.insert(self.name.clone(), self.value.clone());
+                    env_var.serialize(serializer)
+                }
+            },
             EnvOperationEnum::Prepend
             | EnvOperationEnum::Append
             | EnvOperationEnum::Remove
             | EnvOperationEnum::Prefix
-            | EnvOperationEnum::Suffix => {
+            | EnvOperationEnum::Suffix
+            | EnvOperationEnum::PrependPath
+            | EnvOperationEnum::AppendPath => {
                 let mut env_var_wrapped = HashMap::new();
                 env_var_wrapped.insert(self.operation.to_string(), self.value.clone());
 
@@ -422,6 +641,18 @@ pub enum EnvOperationEnum {
     /// If the variable is not set, it will be created with the specified value.
     #[serde(rename = "sf", alias = "suffix")]
     Suffix,
+    /// Prepend the specified directory to a list-style environment variable.
+    /// The path is resolved relative to the work directory root, and the
+    /// operation is skipped if the directory does not exist or if it is
+    /// already part of the list.
+    #[serde(rename = "pp", alias = "prepend_path")]
+    PrependPath,
+    /// Append the specified directory to a list-style environment variable.
+    /// The path is resolved relative to the work directory root, and the
+    /// operation is skipped if the directory does not exist or if it is
+    /// already part of the list.
+    #[serde(rename = "ap", alias = "append_path")]
+    AppendPath,
 }
 
 impl std::fmt::Display for EnvOperationEnum {
@@ -439,9 +670,20 @@ impl EnvOperationEnum {
             EnvOperationEnum::Remove => b"remove",
             EnvOperationEnum::Prefix => b"prefix",
             EnvOperationEnum::Suffix => b"suffix",
+            EnvOperationEnum::PrependPath => b"prepend_path",
+            EnvOperationEnum::AppendPath => b"append_path",
         }
     }
 
+    /// Whether the value of the operation is a path to a directory, that
+    /// needs to be resolved before being added to the environment
+    pub fn is_path(&self) -> bool {
+        matches!(
+            self,
+            EnvOperationEnum::PrependPath | EnvOperationEnum::AppendPath
+        )
+    }
+
     pub fn is_default(other: &EnvOperationEnum) -> bool {
         *other == EnvOperationEnum::default()
     }
@@ -236,6 +236,17 @@ impl ConfigError {
         self.lineno
     }
 
+    /// Sets the line of the error, for errors that were located in
+    /// the file after being reported
+    pub fn set_lineno(&mut self, lineno: usize) {
+        self.lineno = lineno;
+    }
+
+    /// Returns the key path the error is about, if any
+    pub fn key(&self) -> Option<&str> {
+        self.context.get("key").and_then(|key| key.as_str())
+    }
+
     pub fn errorcode(&self) -> String {
         self.kind.to_string()
     }
@@ -254,13 +265,23 @@ impl ConfigError {
         self.kind.default_ignored()
     }
 
+    pub fn is_warning(&self) -> bool {
+        self.kind.is_warning()
+    }
+
     pub fn printable(&self) -> String {
+        let errorcode = if self.is_warning() {
+            self.errorcode().yellow()
+        } else {
+            self.errorcode().red()
+        };
+
         format!(
             "{file}{colon}{lineno}{colon}{errorcode}{colon}{message}",
             colon = ":".light_black(),
             file = abs_or_rel_path(self.file()).light_blue(),
             lineno = self.lineno().light_green(),
-            errorcode = self.errorcode().red(),
+            errorcode = errorcode,
             message = self.message(),
         )
     }
@@ -328,6 +349,12 @@ pub enum ConfigErrorKind {
     UnsupportedValueInContext,
     #[error("C120")]
     ParsingError,
+    #[error("C121")]
+    InvalidSyntax,
+    #[error("C122")]
+    MalformedArgName,
+    #[error("C130")]
+    UnsatisfiedMinOmniVersion,
 
     //  Mxxx for metadata errors
     //    M0xx for larger missing errors
@@ -353,6 +380,8 @@ pub enum ConfigErrorKind {
     //    M3xx for group errors
     #[error("M301")]
     MetadataHeaderGroupMissingParameters,
+    #[error("M302")]
+    MetadataHeaderGroupInvalidKeyValue,
     #[error("M308")]
     MetadataHeaderGroupEmptyPart,
     #[error("M309")]
@@ -388,6 +417,12 @@ pub enum ConfigErrorKind {
     UserDefinedConfigCommandMissingTag,
     #[error("U202")]
     UserDefinedConfigCommandInvalidTagValue,
+    #[error("U203")]
+    UserDefinedConfigCommandSyntaxTestFailed,
+    #[error("U204")]
+    UserDefinedConfigCommandUndeclaredArgReference,
+    #[error("U205")]
+    UserDefinedConfigCommandUnusedArg,
 }
 
 impl ConfigErrorKind {
@@ -395,6 +430,17 @@ impl ConfigErrorKind {
         matches!(self, ConfigErrorKind::MetadataHeaderMissingSyntax)
     }
 
+    /// Whether the error is only a warning, resulting from a heuristic
+    /// check, and should thus not be considered as a failure
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            ConfigErrorKind::MalformedArgName
+                | ConfigErrorKind::UserDefinedConfigCommandUndeclaredArgReference
+                | ConfigErrorKind::UserDefinedConfigCommandUnusedArg
+        )
+    }
+
     pub fn message_from_context(
         &self,
         context: &HashMap<String, YamlValue>,
@@ -566,6 +612,47 @@ impl ConfigErrorKind {
 
                 format!("value {actual:?} for '{key}' is not supported in this context")
             }
+            ConfigErrorKind::UnsatisfiedMinOmniVersion => {
+                let expected = context
+                    .get("expected")
+                    .ok_or("Missing 'expected' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'expected' is not a string")?;
+
+                let actual = context
+                    .get("actual")
+                    .ok_or("Missing 'actual' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'actual' is not a string")?;
+
+                format!(
+                    "configuration requires omni {expected} or later, but the current version is {actual}"
+                )
+            }
+            ConfigErrorKind::InvalidSyntax => {
+                let error = context
+                    .get("error")
+                    .ok_or("Missing 'error' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'error' is not a string")?;
+
+                format!("invalid syntax: {error}")
+            }
+            ConfigErrorKind::MalformedArgName => {
+                let arg_name = context
+                    .get("arg_name")
+                    .ok_or("Missing 'arg_name' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'arg_name' is not a string")?;
+
+                let issue = context
+                    .get("issue")
+                    .ok_or("Missing 'issue' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'issue' is not a string")?;
+
+                format!("malformed argument name '{arg_name}': {issue}")
+            }
             ConfigErrorKind::ParsingError => {
                 let key = context
                     .get("key")
@@ -683,6 +770,29 @@ impl ConfigErrorKind {
 
                 format!("group '{group}' does not have any parameters")
             }
+            ConfigErrorKind::MetadataHeaderGroupInvalidKeyValue => {
+                let group = context
+                    .get("group")
+                    .ok_or("Missing 'group' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'group' is not a string")?;
+
+                let key = context
+                    .get("key")
+                    .ok_or("Missing 'key' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'key' is not a string")?;
+
+                let value = context
+                    .get("value")
+                    .ok_or("Missing 'value' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'value' is not a string")?;
+
+                format!(
+                    "invalid value '{value}' for key '{key}' in the definition of group '{group}'"
+                )
+            }
             ConfigErrorKind::MetadataHeaderParameterEmptyPart => {
                 let parameter = context
                     .get("parameter")
@@ -794,6 +904,68 @@ impl ConfigErrorKind {
                     "invalid value '{actual}' for tag '{tag}', expected value to {expected}{key}",
                 )
             }
+            ConfigErrorKind::UserDefinedConfigCommandSyntaxTestFailed => {
+                let case = context
+                    .get("case")
+                    .ok_or("Missing 'case' key in context")?
+                    .as_u64()
+                    .ok_or("Value for 'case' is not a number")?;
+
+                let args = context
+                    .get("args")
+                    .ok_or("Missing 'args' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'args' is not a string")?;
+
+                let reason = context
+                    .get("reason")
+                    .ok_or("Missing 'reason' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'reason' is not a string")?;
+
+                let key = context
+                    .get("key")
+                    .unwrap_or(&YamlValue::Null)
+                    .as_str()
+                    .map(|s| format!(" for command '{s}'"))
+                    .unwrap_or_default();
+
+                format!("syntax test #{case} failed{key} with args [{args}]: {reason}")
+            }
+            ConfigErrorKind::UserDefinedConfigCommandUndeclaredArgReference => {
+                let argument = context
+                    .get("argument")
+                    .ok_or("Missing 'argument' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'argument' is not a string")?;
+
+                let key = context
+                    .get("key")
+                    .unwrap_or(&YamlValue::Null)
+                    .as_str()
+                    .map(|s| format!(" for command '{s}'"))
+                    .unwrap_or_default();
+
+                format!(
+                    "run references 'OMNI_ARG_{argument}_*' but no parameter with that name is declared{key}"
+                )
+            }
+            ConfigErrorKind::UserDefinedConfigCommandUnusedArg => {
+                let parameter = context
+                    .get("parameter")
+                    .ok_or("Missing 'parameter' key in context")?
+                    .as_str()
+                    .ok_or("Value for 'parameter' is not a string")?;
+
+                let key = context
+                    .get("key")
+                    .unwrap_or(&YamlValue::Null)
+                    .as_str()
+                    .map(|s| format!(" for command '{s}'"))
+                    .unwrap_or_default();
+
+                format!("parameter '{parameter}' is declared but never referenced in run{key}")
+            }
         };
 
         Ok(message)
@@ -803,16 +975,50 @@ impl ConfigErrorKind {
 /// This is the error type for the `parse_args` function
 #[derive(Debug)]
 pub enum ParseArgsErrorKind {
-    ParserBuildError(String),
+    ParserBuildError(Vec<String>),
     ArgumentParsingError(clap::Error),
     InvalidValue(String),
 }
 
 impl ParseArgsErrorKind {
-    #[cfg(test)]
+    /// Returns the stable error code for this error, that tools
+    /// wrapping omni can rely on instead of the error message
+    pub fn code(&self) -> ParseArgsErrorCode {
+        match self {
+            Self::ParserBuildError(_) => ParseArgsErrorCode::ParserBuild,
+            Self::ArgumentParsingError(e) => ParseArgsErrorCode::from(e.kind()),
+            Self::InvalidValue(_) => ParseArgsErrorCode::InvalidValue,
+        }
+    }
+
+    /// Returns the argument involved in the error, as rendered by
+    /// the argument parser (e.g. `--param <PARAM>`), when known
+    pub fn invalid_arg(&self) -> Option<String> {
+        match self {
+            Self::ArgumentParsingError(e) => match e.get(clap::error::ContextKind::InvalidArg)? {
+                clap::error::ContextValue::String(arg) => Some(arg.clone()),
+                clap::error::ContextValue::Strings(args) => args.first().cloned(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the structured representation of this error, used
+    /// when the errors are requested in JSON format
+    pub fn json(&self, dest: Option<String>) -> String {
+        let report = ParseArgsErrorReport {
+            code: self.code(),
+            message: self.simple(),
+            dest,
+        };
+
+        serde_json::to_string(&report).expect("failed to serialize error")
+    }
+
     pub fn simple(&self) -> String {
         match self {
-            Self::ParserBuildError(e) => e.clone(),
+            Self::ParserBuildError(errors) => errors.join("; "),
             Self::ArgumentParsingError(e) => {
                 // Return the first block until the first empty line
                 let err_str = e
@@ -846,9 +1052,75 @@ impl PartialEq for ParseArgsErrorKind {
 impl fmt::Display for ParseArgsErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::ParserBuildError(e) => write!(f, "{e}"),
+            Self::ParserBuildError(errors) => write!(f, "{}", errors.join("\n")),
             Self::ArgumentParsingError(e) => write!(f, "{e}"),
             Self::InvalidValue(e) => write!(f, "{e}"),
         }
     }
 }
+
+/// The stable, machine-readable codes for the errors of
+/// the `parse_args` function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ParseArgsErrorCode {
+    #[serde(rename = "E_ARG_MISSING_REQUIRED")]
+    MissingRequired,
+    #[serde(rename = "E_ARG_INVALID_VALUE")]
+    InvalidValue,
+    #[serde(rename = "E_ARG_UNEXPECTED")]
+    Unexpected,
+    #[serde(rename = "E_ARG_CONFLICT")]
+    Conflict,
+    #[serde(rename = "E_ARG_OTHER")]
+    Other,
+    #[serde(rename = "E_PARSER_BUILD")]
+    ParserBuild,
+}
+
+impl ParseArgsErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingRequired => "E_ARG_MISSING_REQUIRED",
+            Self::InvalidValue => "E_ARG_INVALID_VALUE",
+            Self::Unexpected => "E_ARG_UNEXPECTED",
+            Self::Conflict => "E_ARG_CONFLICT",
+            Self::Other => "E_ARG_OTHER",
+            Self::ParserBuild => "E_PARSER_BUILD",
+        }
+    }
+}
+
+impl From<clap::error::ErrorKind> for ParseArgsErrorCode {
+    fn from(kind: clap::error::ErrorKind) -> Self {
+        use clap::error::ErrorKind;
+
+        match kind {
+            ErrorKind::MissingRequiredArgument
+            | ErrorKind::MissingSubcommand
+            | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+            | ErrorKind::TooFewValues => Self::MissingRequired,
+            ErrorKind::InvalidValue
+            | ErrorKind::ValueValidation
+            | ErrorKind::InvalidUtf8
+            | ErrorKind::NoEquals
+            | ErrorKind::TooManyValues
+            | ErrorKind::WrongNumberOfValues => Self::InvalidValue,
+            ErrorKind::UnknownArgument | ErrorKind::InvalidSubcommand => Self::Unexpected,
+            ErrorKind::ArgumentConflict => Self::Conflict,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl fmt::Display for ParseArgsErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Serialize)]
+struct ParseArgsErrorReport {
+    code: ParseArgsErrorCode,
+    message: String,
+    dest: Option<String>,
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::utils::Empty;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::StringFilter;
+use crate::internal::config::ConfigScope;
+use crate::internal::config::ConfigValue;
+use crate::internal::git::full_git_url_parse;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(transparent)]
+pub struct GitTransportConfig {
+    rules: Vec<GitTransportRuleConfig>,
+}
+
+impl Empty for GitTransportConfig {
+    fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl GitTransportConfig {
+    pub(super) fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        // The transport options can run arbitrary commands through the
+        // ssh command, so they cannot be defined by a work directory
+        let config_value = match config_value {
+            Some(config_value) => match config_value.reject_scope(&ConfigScope::Workdir) {
+                Some(config_value) => config_value,
+                None => return Self::default(),
+            },
+            None => return Self::default(),
+        };
+
+        let rules = if let Some(array) = config_value.as_array() {
+            array
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    GitTransportRuleConfig::from_config_value(
+                        item,
+                        &error_handler.with_index(index),
+                    )
+                })
+                .collect()
+        } else if config_value.is_table() {
+            vec![GitTransportRuleConfig::from_config_value(
+                &config_value,
+                error_handler,
+            )]
+        } else {
+            error_handler
+                .with_expected(vec!["array", "table"])
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            vec![]
+        };
+
+        Self { rules }
+    }
+
+    /// Returns the transport options to use for the git operations on the
+    /// given remote url; for each option, the first matching rule defining
+    /// it is used, while url rewrites are merged across matching rules
+    pub fn options_for_url(&self, url: &str) -> GitTransportOptions {
+        let hostname = full_git_url_parse(url)
+            .ok()
+            .and_then(|url| url.host)
+            .unwrap_or_default();
+
+        self.options_for_host(&hostname)
+    }
+
+    pub fn options_for_host(&self, hostname: &str) -> GitTransportOptions {
+        let mut options = GitTransportOptions::default();
+
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.hostname.matches(hostname))
+        {
+            if options.ipv4.is_none() {
+                options.ipv4 = rule.ipv4;
+            }
+
+            if options.ssh_command.is_none() {
+                options.ssh_command.clone_from(&rule.ssh_command);
+            }
+
+            for (prefix, base) in &rule.url_rewrites {
+                options
+                    .url_rewrites
+                    .entry(prefix.clone())
+                    .or_insert_with(|| base.clone());
+            }
+        }
+
+        options
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GitTransportRuleConfig {
+    #[serde(
+        default,
+        with = "serde_yaml::with::singleton_map",
+        skip_serializing_if = "StringFilter::is_default"
+    )]
+    pub hostname: StringFilter,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv4: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_command: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub url_rewrites: BTreeMap<String, String>,
+}
+
+impl GitTransportRuleConfig {
+    fn from_config_value(config_value: &ConfigValue, error_handler: &ConfigErrorHandler) -> Self {
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+        }
+
+        let ipv4 = match config_value.get("ipv4") {
+            Some(value) => match value.as_bool_forced() {
+                Some(ipv4) => Some(ipv4),
+                None => {
+                    error_handler
+                        .with_key("ipv4")
+                        .with_expected("boolean")
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValueType);
+
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let mut url_rewrites = BTreeMap::new();
+        if let Some(value) = config_value.get("url_rewrites") {
+            let error_handler = error_handler.with_key("url_rewrites");
+            if let Some(table) = value.as_table() {
+                for (prefix, base) in table {
+                    match base.as_str_forced() {
+                        Some(base) => {
+                            url_rewrites.insert(prefix.to_string(), base);
+                        }
+                        None => {
+                            error_handler
+                                .with_key(&prefix)
+                                .with_expected("string")
+                                .with_actual(base)
+                                .error(ConfigErrorKind::InvalidValueType);
+                        }
+                    }
+                }
+            } else {
+                error_handler
+                    .with_expected("table")
+                    .with_actual(value)
+                    .error(ConfigErrorKind::InvalidValueType);
+            }
+        }
+
+        Self {
+            hostname: StringFilter::from_config_value(
+                config_value.get("hostname"),
+                &error_handler.with_key("hostname"),
+            ),
+            ipv4,
+            ssh_command: config_value
+                .get_as_str_or_none("ssh_command", &error_handler.with_key("ssh_command")),
+            url_rewrites,
+        }
+    }
+}
+
+/// The transport options resolved for a given remote, to be applied to
+/// the git commands interacting with that remote
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitTransportOptions {
+    pub ipv4: Option<bool>,
+    pub ssh_command: Option<String>,
+    /// The url prefixes to rewrite, mapped to the url to use instead
+    pub url_rewrites: BTreeMap<String, String>,
+}
+
+impl GitTransportOptions {
+    /// The git subcommands supporting the `--ipv4` option
+    const IPV4_SUBCOMMANDS: [&'static str; 3] = ["clone", "fetch", "pull"];
+
+    /// Returns the arguments to pass to git to run the given subcommand,
+    /// i.e. the `-c` flags for the url rewrites, the subcommand itself, and
+    /// the `--ipv4` option if forced and supported by the subcommand; the
+    /// subcommand arguments should be appended after those
+    pub fn git_args(&self, subcommand: &str) -> Vec<String> {
+        let mut args = vec![];
+
+        for (prefix, base) in &self.url_rewrites {
+            args.push("-c".to_string());
+            args.push(format!("url.{base}.insteadOf={prefix}"));
+        }
+
+        args.push(subcommand.to_string());
+
+        if self.ipv4.unwrap_or(false) && Self::IPV4_SUBCOMMANDS.contains(&subcommand) {
+            args.push("--ipv4".to_string());
+        }
+
+        args
+    }
+
+    /// Returns the environment variables to set for the git commands
+    pub fn env(&self) -> Vec<(String, String)> {
+        let mut env = vec![];
+
+        if let Some(ssh_command) = &self.ssh_command {
+            env.push(("GIT_SSH_COMMAND".to_string(), ssh_command.clone()));
+        }
+
+        env
+    }
+}
+
+#[cfg(test)]
+#[path = "git_transport_test.rs"]
+mod tests;
@@ -0,0 +1,161 @@
+use super::*;
+
+use crate::internal::config::ConfigSource;
+
+fn transport_from_str(yaml: &str) -> GitTransportConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    GitTransportConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop())
+}
+
+const CONFIG: &str = r#"
+- hostname: "*.corp.example.com"
+  ipv4: true
+  ssh_command: ssh -i ~/.ssh/corp_key -o IdentitiesOnly=yes
+- hostname:
+    exact: github.com
+  url_rewrites:
+    "git@github.com:": "ssh://git@ssh.github.com:443/"
+- ssh_command: ssh -o ConnectTimeout=10
+"#;
+
+mod from_config_value {
+    use super::*;
+
+    #[test]
+    fn single_rule() {
+        let config = transport_from_str("ipv4: true");
+
+        assert_eq!(
+            config.options_for_host("example.com"),
+            GitTransportOptions {
+                ipv4: Some(true),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        let config_value = ConfigValue::from_str(
+            r#"
+            - ipv4: [true]
+              url_rewrites: "git@github.com:"
+            - "ipv4"
+            "#,
+        )
+        .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("config.yaml");
+        let config = GitTransportConfig::from_config_value(Some(config_value), &error_handler);
+
+        assert_eq!(
+            config.options_for_host("example.com"),
+            GitTransportOptions::default()
+        );
+
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 3, "errors: {errors:?}");
+    }
+
+    #[test]
+    fn rejected_from_workdir() {
+        let value = serde_yaml::from_str("ssh_command: ./run-anything").unwrap();
+        let config_value = ConfigValue::from_value(ConfigSource::Null, ConfigScope::Workdir, value);
+        let config =
+            GitTransportConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop());
+
+        assert!(config.is_empty());
+    }
+}
+
+mod options_for_url {
+    use super::*;
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let config = transport_from_str(CONFIG);
+
+        let options = config.options_for_url("git@git.corp.example.com:team/repo.git");
+        assert_eq!(options.ipv4, Some(true));
+        assert_eq!(
+            options.ssh_command.as_deref(),
+            Some("ssh -i ~/.ssh/corp_key -o IdentitiesOnly=yes")
+        );
+        assert!(options.url_rewrites.is_empty());
+    }
+
+    #[test]
+    fn options_are_merged_across_rules() {
+        let config = transport_from_str(CONFIG);
+
+        let options = config.options_for_url("https://github.com/omnicli/omni");
+        assert_eq!(options.ipv4, None);
+        assert_eq!(
+            options.ssh_command.as_deref(),
+            Some("ssh -o ConnectTimeout=10")
+        );
+        assert_eq!(
+            options.url_rewrites,
+            BTreeMap::from([(
+                "git@github.com:".to_string(),
+                "ssh://git@ssh.github.com:443/".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn no_matching_rule() {
+        let config = transport_from_str("hostname: gitlab.com\nipv4: true");
+
+        assert_eq!(
+            config.options_for_url("git@github.com:omnicli/omni.git"),
+            GitTransportOptions::default()
+        );
+    }
+}
+
+mod git_command {
+    use super::*;
+
+    #[test]
+    fn forced_ipv4_and_ssh_command() {
+        let config = transport_from_str(CONFIG);
+        let options = config.options_for_url("git@git.corp.example.com:team/repo.git");
+
+        assert_eq!(options.git_args("clone"), vec!["clone", "--ipv4"]);
+        assert_eq!(options.git_args("fetch"), vec!["fetch", "--ipv4"]);
+        assert_eq!(options.git_args("pull"), vec!["pull", "--ipv4"]);
+        // ls-remote does not support forcing the IP version
+        assert_eq!(options.git_args("ls-remote"), vec!["ls-remote"]);
+
+        assert_eq!(
+            options.env(),
+            vec![(
+                "GIT_SSH_COMMAND".to_string(),
+                "ssh -i ~/.ssh/corp_key -o IdentitiesOnly=yes".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn url_rewrites() {
+        let config = transport_from_str(CONFIG);
+        let options = config.options_for_url("git@github.com:omnicli/omni.git");
+
+        assert_eq!(
+            options.git_args("clone"),
+            vec![
+                "-c",
+                "url.ssh://git@ssh.github.com:443/.insteadOf=git@github.com:",
+                "clone",
+            ]
+        );
+    }
+
+    #[test]
+    fn no_options() {
+        let options = GitTransportOptions::default();
+
+        assert_eq!(options.git_args("clone"), vec!["clone"]);
+        assert!(options.env().is_empty());
+    }
+}
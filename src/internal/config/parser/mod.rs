@@ -20,21 +20,37 @@ mod clone;
 pub(crate) use clone::CloneConfig;
 
 mod command_definition;
-pub(crate) use command_definition::parse_arg_name;
+pub(crate) use command_definition::parse_arg_name_checked;
+pub(crate) use command_definition::ArgReferenceLint;
+pub(crate) use command_definition::ArgparserOutput;
+pub(crate) use command_definition::CommandCwd;
+pub(crate) use command_definition::CommandCwdArgFn;
 pub(crate) use command_definition::CommandDefinition;
+pub(crate) use command_definition::CommandRunner;
 pub(crate) use command_definition::CommandSyntax;
 pub(crate) use command_definition::SyntaxGroup;
 pub(crate) use command_definition::SyntaxOptArg;
 pub(crate) use command_definition::SyntaxOptArgNumValues;
+pub(crate) use command_definition::SyntaxOptArgOrigin;
 pub(crate) use command_definition::SyntaxOptArgType;
 
+mod command_resolution;
+pub(crate) use command_resolution::CommandResolutionConfig;
+
+mod commands_filter;
+pub(crate) use commands_filter::CommandsFilterConfig;
+
 mod config_commands;
 pub(crate) use config_commands::ConfigCommandsConfig;
 
 mod env;
+pub(crate) use env::env_command_output;
+pub(crate) use env::EnvCommandWhen;
 pub(crate) use env::EnvConfig;
 pub(crate) use env::EnvOperationConfig;
 pub(crate) use env::EnvOperationEnum;
+pub(crate) use env::ENV_COMMAND_APPLY_TIMEOUT;
+pub(crate) use env::ENV_COMMAND_UP_TIMEOUT;
 
 mod errors;
 pub(crate) use errors::ConfigError;
@@ -42,6 +58,10 @@ pub(crate) use errors::ConfigErrorHandler;
 pub(crate) use errors::ConfigErrorKind;
 pub(crate) use errors::ParseArgsErrorKind;
 
+mod git_transport;
+pub(crate) use git_transport::GitTransportConfig;
+pub(crate) use git_transport::GitTransportOptions;
+
 mod github;
 pub(crate) use github::GithubAuthConfig;
 pub(crate) use github::GithubConfig;
@@ -66,6 +86,16 @@ mod path;
 pub(crate) use path::PathConfig;
 pub(crate) use path::PathEntryConfig;
 
+mod path_conflicts;
+pub(crate) use path_conflicts::PathConflictsConfig;
+
+mod paths;
+pub(crate) use paths::canonicalize_policy;
+pub(crate) use paths::flush_canonicalize_policy;
+pub(crate) use paths::set_canonicalize_policy;
+pub(crate) use paths::PathsCanonicalize;
+pub(crate) use paths::PathsConfig;
+
 mod path_repo_updates;
 pub(crate) use path_repo_updates::PathRepoUpdatesConfig;
 
@@ -76,6 +106,11 @@ mod shell_aliases;
 
 pub(crate) use shell_aliases::ShellAliasesConfig;
 
+mod shell_session;
+pub(crate) use shell_session::ShellSessionConfig;
+#[cfg(test)]
+pub(crate) use shell_session::ShellSessionIdSource;
+
 mod suggest_clone;
 pub(crate) use suggest_clone::SuggestCloneConfig;
 
@@ -83,4 +118,8 @@ mod suggest_config;
 pub(crate) use suggest_config::SuggestConfig;
 
 mod up_command;
+pub(crate) use up_command::UpAmbiguousVersionsPolicy;
+pub(crate) use up_command::UpCommandAdvisoriesConfig;
 pub(crate) use up_command::UpCommandConfig;
+pub(crate) use up_command::UpCommandOperationConfig;
+pub(crate) use up_command::UpDataPathsPolicy;
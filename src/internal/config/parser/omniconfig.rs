@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 
 use lazy_static::lazy_static;
+use semver::Version;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::internal::build::current_omni_version;
 use crate::internal::cache::utils::Empty;
 use crate::internal::config::parser::AskPassConfig;
 use crate::internal::config::parser::CacheConfig;
@@ -11,17 +14,23 @@ use crate::internal::config::parser::CdConfig;
 use crate::internal::config::parser::CheckConfig;
 use crate::internal::config::parser::CloneConfig;
 use crate::internal::config::parser::CommandDefinition;
+use crate::internal::config::parser::CommandResolutionConfig;
+use crate::internal::config::parser::CommandsFilterConfig;
 use crate::internal::config::parser::ConfigCommandsConfig;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
 use crate::internal::config::parser::EnvConfig;
+use crate::internal::config::parser::GitTransportConfig;
 use crate::internal::config::parser::GithubConfig;
 use crate::internal::config::parser::MakefileCommandsConfig;
 use crate::internal::config::parser::MatchSkipPromptIfConfig;
 use crate::internal::config::parser::PathConfig;
+use crate::internal::config::parser::PathConflictsConfig;
 use crate::internal::config::parser::PathRepoUpdatesConfig;
+use crate::internal::config::parser::PathsConfig;
 use crate::internal::config::parser::PromptsConfig;
 use crate::internal::config::parser::ShellAliasesConfig;
+use crate::internal::config::parser::ShellSessionConfig;
 use crate::internal::config::parser::SuggestCloneConfig;
 use crate::internal::config::parser::SuggestConfig;
 use crate::internal::config::parser::UpCommandConfig;
@@ -32,6 +41,7 @@ use crate::internal::config::ConfigValue;
 use crate::internal::config::OrgConfig;
 use crate::internal::env::omni_git_env;
 use crate::internal::env::user_home;
+use crate::internal::user_interface::colors::ColorMode;
 
 lazy_static! {
     #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -65,19 +75,29 @@ pub struct OmniConfig {
     #[serde(skip_serializing_if = "CheckConfig::is_empty")]
     pub check: CheckConfig,
     pub clone: CloneConfig,
+    pub color: ColorMode,
     pub command_match_min_score: f64,
     pub command_match_skip_prompt_if: MatchSkipPromptIfConfig,
+    pub command_resolution: CommandResolutionConfig,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub commands: HashMap<String, CommandDefinition>,
+    #[serde(skip_serializing_if = "CommandsFilterConfig::is_empty")]
+    pub commands_filter: CommandsFilterConfig,
     pub config_commands: ConfigCommandsConfig,
     #[serde(skip_serializing_if = "EnvConfig::is_empty")]
     pub env: EnvConfig,
+    #[serde(skip_serializing_if = "GitTransportConfig::is_empty")]
+    pub git_transport: GitTransportConfig,
     #[serde(skip_serializing_if = "GithubConfig::is_empty")]
     pub github: GithubConfig,
     pub makefile_commands: MakefileCommandsConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_omni_version: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub org: Vec<OrgConfig>,
     pub path: PathConfig,
+    pub path_conflicts: PathConflictsConfig,
+    pub paths: PathsConfig,
     pub path_repo_updates: PathRepoUpdatesConfig,
     #[serde(skip_serializing_if = "PromptsConfig::is_empty")]
     pub prompts: PromptsConfig,
@@ -85,6 +105,7 @@ pub struct OmniConfig {
     pub repo_path_format: String,
     #[serde(skip_serializing_if = "ShellAliasesConfig::is_empty")]
     pub shell_aliases: ShellAliasesConfig,
+    pub shell_session: ShellSessionConfig,
     #[serde(skip_serializing_if = "SuggestCloneConfig::is_empty")]
     pub suggest_clone: SuggestCloneConfig,
     #[serde(skip_serializing_if = "SuggestConfig::is_empty")]
@@ -106,11 +127,30 @@ impl OmniConfig {
         config_value: &ConfigValue,
         error_handler: &ConfigErrorHandler,
     ) -> Self {
+        // Check the minimum version first; if not satisfied, the rest of the
+        // configuration is still parsed, but without reporting errors, since
+        // keys and values that this version does not know about are expected
+        let noop_error_handler = ConfigErrorHandler::noop();
+        let error_handler =
+            if Self::check_min_omni_version(config_value, current_omni_version(), error_handler) {
+                error_handler
+            } else {
+                &noop_error_handler
+            };
+        let min_omni_version =
+            config_value.get_as_str_or_none("min_omni_version", &ConfigErrorHandler::noop());
+
         let mut commands_config = HashMap::new();
         if let Some(value) = config_value.get("commands") {
             let commands_error_handler = error_handler.with_key("commands");
             if let Some(table) = value.as_table() {
                 for (key, value) in table {
+                    // A non-table `autocorrect` is a setting of the lenient
+                    // resolution of the commands, not a command definition
+                    if key == "autocorrect" && !value.is_table() {
+                        continue;
+                    }
+
                     commands_config.insert(
                         key.to_string(),
                         CommandDefinition::from_config_value(
@@ -134,9 +174,12 @@ impl OmniConfig {
             // by any work directory specific configuration.
             if let Some(value) = value.reject_scope(&ConfigScope::Workdir) {
                 if let Some(array) = value.as_array() {
-                    for value in array.iter() {
+                    for (idx, value) in array.iter().enumerate() {
                         // TODO: handle errors
-                        org_config.push(OrgConfig::from_config_value(value));
+                        org_config.push(OrgConfig::from_config_value(
+                            value,
+                            &error_handler.with_key("org").with_index(idx),
+                        ));
                     }
                 } else {
                     error_handler
@@ -165,6 +208,10 @@ impl OmniConfig {
             config_value.get("clone"),
             &error_handler.with_key("clone"),
         );
+        let color = Self::color_from_config_value(
+            config_value.get("color"),
+            &error_handler.with_key("color"),
+        );
         let command_match_min_score = config_value.get_as_float_or_default(
             "command_match_min_score",
             Self::DEFAULT_COMMAND_MATCH_MIN_SCORE,
@@ -174,12 +221,25 @@ impl OmniConfig {
             config_value.get("command_match_skip_prompt_if"),
             &error_handler.with_key("command_match_skip_prompt_if"),
         );
+        let command_resolution = CommandResolutionConfig::from_config_value(
+            config_value.get("command_resolution"),
+            config_value.get("commands"),
+            error_handler,
+        );
+        let commands_filter = CommandsFilterConfig::from_config_value(
+            config_value.get("commands_filter"),
+            &error_handler.with_key("commands_filter"),
+        );
         let config_commands = ConfigCommandsConfig::from_config_value(
             config_value.get("config_commands"),
             &error_handler.with_key("config_commands"),
         );
         let env =
             EnvConfig::from_config_value(config_value.get("env"), &error_handler.with_key("env"));
+        let git_transport = GitTransportConfig::from_config_value(
+            config_value.get("git_transport"),
+            &error_handler.with_key("git_transport"),
+        );
         let github = GithubConfig::from_config_value(
             config_value.get("github"),
             &error_handler.with_key("github"),
@@ -192,6 +252,14 @@ impl OmniConfig {
             config_value.get("path"),
             &error_handler.with_key("path"),
         );
+        let paths = PathsConfig::from_config_value(
+            config_value.get("paths"),
+            &error_handler.with_key("paths"),
+        );
+        let path_conflicts = PathConflictsConfig::from_config_value(
+            config_value.get("path_conflicts"),
+            &error_handler.with_key("path_conflicts"),
+        );
         let path_repo_updates = PathRepoUpdatesConfig::from_config_value(
             config_value.get("path_repo_updates"),
             &error_handler.with_key("path_repo_updates"),
@@ -209,6 +277,10 @@ impl OmniConfig {
             config_value.get("shell_aliases"),
             &error_handler.with_key("shell_aliases"),
         );
+        let shell_session = ShellSessionConfig::from_config_value(
+            config_value.get("shell_session"),
+            &error_handler.with_key("shell_session"),
+        );
         let suggest_clone = SuggestCloneConfig::from_config_value(
             config_value.get("suggest_clone"),
             &error_handler.with_key("suggest_clone"),
@@ -217,11 +289,18 @@ impl OmniConfig {
             config_value.get("suggest_config"),
             &error_handler.with_key("suggest_config"),
         );
-        let up = UpConfig::from_config_value(config_value.get("up"), &error_handler.with_key("up"));
         let up_command = UpCommandConfig::from_config_value(
             config_value.get("up_command"),
             &error_handler.with_key("up_command"),
         );
+        let up = UpConfig::from_config_value(config_value.get("up"), &error_handler.with_key("up"))
+            .map(|up| {
+                if up_command.infer_runtimes {
+                    up.with_inferred_runtimes()
+                } else {
+                    up
+                }
+            });
 
         let sandbox = config_value.get_as_str_or_default(
             "sandbox",
@@ -241,19 +320,27 @@ impl OmniConfig {
             cd,
             check,
             clone,
+            color,
             command_match_min_score,
             command_match_skip_prompt_if,
+            command_resolution,
             commands: commands_config,
+            commands_filter,
             config_commands,
             env,
+            git_transport,
             github,
             makefile_commands,
+            min_omni_version,
             org: org_config,
             path,
+            path_conflicts,
+            paths,
             path_repo_updates,
             prompts,
             repo_path_format,
             shell_aliases,
+            shell_session,
             suggest_clone,
             suggest_config,
             up,
@@ -263,6 +350,31 @@ impl OmniConfig {
         }
     }
 
+    fn color_from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> ColorMode {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return ColorMode::default(),
+        };
+
+        match config_value
+            .as_str()
+            .as_deref()
+            .and_then(ColorMode::from_str)
+        {
+            Some(mode) => mode,
+            None => {
+                error_handler
+                    .with_expected(vec!["auto", "always", "never"])
+                    .with_actual(config_value)
+                    .error(ConfigErrorKind::InvalidValue);
+                ColorMode::default()
+            }
+        }
+    }
+
     pub fn sandbox(&self) -> String {
         self.sandbox.clone()
     }
@@ -295,7 +407,73 @@ impl OmniConfig {
     /// - suggest_config
     /// - suggest_clone
     /// - env
+    ///
+    /// The hash is computed over a canonical representation of the parsed
+    /// configuration, so that cosmetic changes (key order, comments,
+    /// formatting) do not change it; lists are kept in order since order
+    /// matters for up operations.
     pub fn up_hash(&self) -> String {
+        let mut config = serde_json::Map::new();
+        for (key, value) in [
+            ("up", serde_json::to_value(&self.up)),
+            ("suggest_config", serde_json::to_value(&self.suggest_config)),
+            ("suggest_clone", serde_json::to_value(&self.suggest_clone)),
+            ("env", serde_json::to_value(&self.env)),
+        ] {
+            config.insert(key.to_string(), value.unwrap_or_default());
+        }
+        let canonical = canonical_json(&serde_json::Value::Object(config));
+
+        let mut config_hasher = blake3::Hasher::new();
+        config_hasher.update(canonical.to_string().as_bytes());
+        config_hasher.finalize().to_hex()[..16].to_string()
+    }
+
+    /// Checks that the given omni version satisfies the minimum version
+    /// required by the `min_omni_version` key of the configuration, if
+    /// any, reporting an error if it does not. Development builds, which
+    /// have version `0.0.0`, are considered to satisfy any minimum version.
+    pub fn check_min_omni_version(
+        config_value: &ConfigValue,
+        current_version: &Version,
+        error_handler: &ConfigErrorHandler,
+    ) -> bool {
+        let error_handler = error_handler.with_key("min_omni_version");
+
+        let min_version = match config_value.get_as_str_or_none("min_omni_version", &error_handler)
+        {
+            Some(min_version) => min_version,
+            None => return true,
+        };
+
+        let parsed_min_version = match parse_omni_version(&min_version) {
+            Ok(parsed_min_version) => parsed_min_version,
+            Err(err) => {
+                error_handler
+                    .with_actual(min_version)
+                    .with_context("error", err.to_string())
+                    .error(ConfigErrorKind::ParsingError);
+                return true;
+            }
+        };
+
+        if *current_version == Version::new(0, 0, 0) || *current_version >= parsed_min_version {
+            return true;
+        }
+
+        error_handler
+            .with_expected(parsed_min_version.to_string())
+            .with_actual(current_version.to_string())
+            .error(ConfigErrorKind::UnsatisfiedMinOmniVersion);
+
+        false
+    }
+
+    /// Hash of the configuration as computed by previous versions of
+    /// omni, over the serialized configuration; this is only kept to
+    /// avoid notifying for all the work directories that were set up
+    /// before the canonical hash was introduced.
+    pub fn up_hash_legacy(&self) -> String {
         let mut config_hasher = blake3::Hasher::new();
 
         if let Some(up) = &self.up {
@@ -320,6 +498,49 @@ impl OmniConfig {
     }
 }
 
+/// Parses an omni version, allowing to omit the minor and patch
+/// components (e.g. `2025.1` is considered as `2025.1.0`) and to
+/// prefix the version with `v`.
+fn parse_omni_version(version: &str) -> Result<Version, semver::Error> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+
+    let core_len = version.find(['-', '+']).unwrap_or(version.len());
+    let missing = 2usize.saturating_sub(version[..core_len].matches('.').count());
+    let mut padded = version[..core_len].to_string();
+    for _ in 0..missing {
+        padded.push_str(".0");
+    }
+    padded.push_str(&version[core_len..]);
+
+    Version::parse(&padded)
+}
+
+/// Returns a canonical version of the provided value: object keys are
+/// sorted and floats without a fractional part are converted to integers.
+/// Arrays are kept in order.
+fn canonical_json(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let sorted: BTreeMap<String, serde_json::Value> = map
+                .iter()
+                .map(|(key, value)| (key.clone(), canonical_json(value)))
+                .collect();
+            serde_json::Value::Object(sorted.into_iter().collect())
+        }
+        serde_json::Value::Array(array) => {
+            serde_json::Value::Array(array.iter().map(canonical_json).collect())
+        }
+        serde_json::Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() && float.fract() == 0.0 && float.abs() < 1e15 => {
+                serde_json::Value::from(float as i64)
+            }
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
 impl From<ConfigValue> for OmniConfig {
     fn from(config_value: ConfigValue) -> Self {
         OmniConfig::from_config_value(&config_value, &ConfigErrorHandler::noop())
@@ -331,3 +552,7 @@ impl From<ConfigLoader> for OmniConfig {
         config_loader.raw_config.into()
     }
 }
+
+#[cfg(test)]
+#[path = "omniconfig_test.rs"]
+mod tests;
@@ -0,0 +1,423 @@
+use super::*;
+
+fn omniconfig(yaml: &str) -> OmniConfig {
+    OmniConfig::from(ConfigValue::from_str(yaml).expect("failed to parse yaml"))
+}
+
+mod up_hash {
+    use super::*;
+
+    const CONFIG: &str = r#"
+up:
+  - python:
+      version: "3.11.6"
+      dir:
+        - api
+        - tools
+  - custom:
+      meet: make install
+      met?: make check
+      name: install deps
+env:
+  API_URL: https://example.com
+  DEBUG: "1"
+suggest_config:
+  up_command:
+    infer_runtimes: false
+"#;
+
+    #[test]
+    fn reordered_keys() {
+        let reordered = r#"
+suggest_config:
+  up_command:
+    infer_runtimes: false
+env:
+  DEBUG: "1"
+  API_URL: https://example.com
+up:
+  - python:
+      dir:
+        - api
+        - tools
+      version: "3.11.6"
+  - custom:
+      name: install deps
+      met?: make check
+      meet: make install
+"#;
+
+        assert_eq!(
+            omniconfig(CONFIG).up_hash(),
+            omniconfig(reordered).up_hash()
+        );
+    }
+
+    #[test]
+    fn comments_and_formatting() {
+        let commented = r#"
+# The steps to set up the repository
+up:
+  # We need python for the api
+  - python: { version: "3.11.6", dir: [api, tools] }
+  - custom:
+      meet: make install  # install the dependencies
+      met?: make check
+      name: install deps
+
+env:
+  API_URL: https://example.com
+  DEBUG: "1"  # enable debug logs
+
+suggest_config: { up_command: { infer_runtimes: false } }
+"#;
+
+        assert_eq!(
+            omniconfig(CONFIG).up_hash(),
+            omniconfig(commented).up_hash()
+        );
+    }
+
+    #[test]
+    fn changed_version() {
+        let changed = CONFIG.replace("3.11.6", "3.12.1");
+
+        assert_ne!(omniconfig(CONFIG).up_hash(), omniconfig(&changed).up_hash());
+    }
+
+    #[test]
+    fn reordered_steps() {
+        let reordered = r#"
+up:
+  - custom:
+      meet: make install
+      met?: make check
+      name: install deps
+  - python:
+      version: "3.11.6"
+      dir:
+        - api
+        - tools
+env:
+  API_URL: https://example.com
+  DEBUG: "1"
+suggest_config:
+  up_command:
+    infer_runtimes: false
+"#;
+
+        assert_ne!(
+            omniconfig(CONFIG).up_hash(),
+            omniconfig(reordered).up_hash()
+        );
+    }
+
+    #[test]
+    fn stable() {
+        let config = omniconfig(CONFIG);
+
+        assert_eq!(config.up_hash(), config.up_hash());
+        assert_eq!(config.up_hash().len(), 16);
+    }
+
+    #[test]
+    fn differs_from_legacy() {
+        let config = omniconfig(CONFIG);
+
+        assert_ne!(config.up_hash(), config.up_hash_legacy());
+    }
+}
+
+mod canonical_json {
+    use super::*;
+
+    #[test]
+    fn sorts_object_keys() {
+        let value = serde_json::json!({"b": 1, "a": {"d": 2, "c": 3}});
+
+        assert_eq!(
+            canonical_json(&value).to_string(),
+            r#"{"a":{"c":3,"d":2},"b":1}"#
+        );
+    }
+
+    #[test]
+    fn keeps_array_order() {
+        let value = serde_json::json!(["b", "a", {"z": 1, "y": 2}]);
+
+        assert_eq!(
+            canonical_json(&value).to_string(),
+            r#"["b","a",{"y":2,"z":1}]"#
+        );
+    }
+
+    #[test]
+    fn normalizes_integral_floats() {
+        let value = serde_json::json!({"a": 2.0, "b": 2.5, "c": 2});
+
+        assert_eq!(
+            canonical_json(&value).to_string(),
+            r#"{"a":2,"b":2.5,"c":2}"#
+        );
+    }
+}
+
+mod min_omni_version {
+    use super::*;
+
+    fn check(yaml: &str, current_version: &str) -> (bool, Vec<ConfigErrorKind>) {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let current_version = Version::parse(current_version).expect("failed to parse version");
+        let error_handler = ConfigErrorHandler::new().with_file("test.yaml");
+
+        let satisfied =
+            OmniConfig::check_min_omni_version(&config_value, &current_version, &error_handler);
+        let errors = error_handler
+            .errors()
+            .into_iter()
+            .map(|error| error.kind().clone())
+            .collect();
+
+        (satisfied, errors)
+    }
+
+    #[test]
+    fn higher_version_required() {
+        let (satisfied, errors) = check("min_omni_version: '2025.3.0'\n", "2025.2.1");
+
+        assert!(!satisfied);
+        assert_eq!(errors, vec![ConfigErrorKind::UnsatisfiedMinOmniVersion]);
+    }
+
+    #[test]
+    fn satisfied_version() {
+        for current_version in ["2025.3.0", "2025.3.1", "2026.1.0"] {
+            let (satisfied, errors) = check("min_omni_version: '2025.3.0'\n", current_version);
+
+            assert!(satisfied, "{current_version} should satisfy 2025.3.0");
+            assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn partial_version() {
+        assert!(check("min_omni_version: 'v2025.3'\n", "2025.3.0").0);
+        assert!(!check("min_omni_version: '2026'\n", "2025.12.3").0);
+    }
+
+    #[test]
+    fn development_build() {
+        let (satisfied, errors) = check("min_omni_version: '2025.3.0'\n", "0.0.0");
+
+        assert!(satisfied);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn not_set() {
+        let (satisfied, errors) = check("worktree: /tmp\n", "2025.2.1");
+
+        assert!(satisfied);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn invalid_version() {
+        let (satisfied, errors) = check("min_omni_version: 'latest'\n", "2025.2.1");
+
+        assert!(satisfied);
+        assert_eq!(errors, vec![ConfigErrorKind::ParsingError]);
+    }
+
+    #[test]
+    fn other_errors_not_reported_when_unsatisfied() {
+        let config_value = ConfigValue::from_str(concat!(
+            "min_omni_version: '999.0.0'\n",
+            "command_match_min_score: not-a-float\n",
+        ))
+        .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::new().with_file("test.yaml");
+
+        let config = OmniConfig::from_config_value(&config_value, &error_handler);
+
+        assert_eq!(config.min_omni_version, Some("999.0.0".to_string()));
+        // Development builds satisfy any minimum version
+        let expected = if *current_omni_version() == Version::new(0, 0, 0) {
+            vec![ConfigErrorKind::InvalidValueType]
+        } else {
+            vec![ConfigErrorKind::UnsatisfiedMinOmniVersion]
+        };
+        let errors = error_handler
+            .errors()
+            .into_iter()
+            .map(|error| error.kind().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(errors, expected);
+    }
+}
+
+mod color {
+    use super::*;
+
+    fn parse(yaml: &str) -> (ColorMode, Vec<ConfigErrorKind>) {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::new().with_file("test.yaml");
+
+        let config = OmniConfig::from_config_value(&config_value, &error_handler);
+        let errors = error_handler
+            .errors()
+            .into_iter()
+            .map(|error| error.kind().clone())
+            .collect();
+
+        (config.color, errors)
+    }
+
+    #[test]
+    fn defaults_to_auto() {
+        let (color, errors) = parse("worktree: /tmp\n");
+
+        assert_eq!(color, ColorMode::Auto);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn modes() {
+        for (value, expected) in [
+            ("auto", ColorMode::Auto),
+            ("always", ColorMode::Always),
+            ("never", ColorMode::Never),
+        ] {
+            let (color, errors) = parse(&format!("color: {value}\n"));
+
+            assert_eq!(color, expected);
+            assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_mode() {
+        let (color, errors) = parse("color: sometimes\n");
+
+        assert_eq!(color, ColorMode::Auto);
+        assert_eq!(errors, vec![ConfigErrorKind::InvalidValue]);
+    }
+}
+
+mod paths {
+    use super::*;
+
+    use crate::internal::config::parser::PathsCanonicalize;
+
+    fn parse(yaml: &str) -> (PathsConfig, Vec<ConfigErrorKind>) {
+        let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::new().with_file("test.yaml");
+
+        let config = OmniConfig::from_config_value(&config_value, &error_handler);
+        let errors = error_handler
+            .errors()
+            .into_iter()
+            .map(|error| error.kind().clone())
+            .collect();
+
+        (config.paths, errors)
+    }
+
+    #[test]
+    fn defaults() {
+        let (paths, errors) = parse("worktree: /tmp\n");
+
+        assert_eq!(paths, PathsConfig::default());
+        assert_eq!(paths.dir_mode, 0o755);
+        assert_eq!(paths.file_mode, 0o644);
+        assert!(!paths.private);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn modes_as_octal_strings() {
+        let (paths, errors) = parse("paths:\n  dir_mode: '0750'\n  file_mode: '0o640'\n");
+
+        assert_eq!(paths.dir_mode, 0o750);
+        assert_eq!(paths.file_mode, 0o640);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn modes_as_integers() {
+        let (paths, errors) = parse("paths:\n  dir_mode: 0o770\n  file_mode: 0660\n");
+
+        assert_eq!(paths.dir_mode, 0o770);
+        assert_eq!(paths.file_mode, 0o660);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn private_preset() {
+        let (paths, errors) = parse("paths:\n  private: true\n");
+
+        assert!(paths.private);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    }
+
+    #[test]
+    fn invalid_modes() {
+        let (paths, errors) = parse("paths:\n  dir_mode: '0999'\n  file_mode: 1777\n");
+
+        assert_eq!(paths, PathsConfig::default());
+        assert_eq!(
+            errors,
+            vec![ConfigErrorKind::InvalidValue, ConfigErrorKind::InvalidValue]
+        );
+    }
+    #[test]
+    fn canonicalize_policy() {
+        let (paths, errors) = parse("worktree: /tmp\n");
+        assert_eq!(paths.canonicalize, PathsCanonicalize::Physical);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let (paths, errors) = parse("paths:\n  canonicalize: logical\n");
+        assert_eq!(paths.canonicalize, PathsCanonicalize::Logical);
+        assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+        let (paths, errors) = parse("paths:\n  canonicalize: resolved\n");
+        assert_eq!(paths.canonicalize, PathsCanonicalize::Physical);
+        assert_eq!(errors, vec![ConfigErrorKind::InvalidValue]);
+    }
+}
+
+mod org_up_operations {
+    use super::*;
+
+    use crate::internal::config::ConfigSource;
+
+    const CONFIG: &str = r#"
+org:
+  - handle: github.com/org
+    up_operations:
+      allowed: ["!cargo-install"]
+"#;
+
+    fn scoped_omniconfig(yaml: &str, scope: ConfigScope) -> OmniConfig {
+        let value: serde_yaml::Value = serde_yaml::from_str(yaml).expect("failed to parse yaml");
+        OmniConfig::from(ConfigValue::from_value(ConfigSource::Null, scope, value))
+    }
+
+    #[test]
+    fn parsed_from_global_config() {
+        let config = scoped_omniconfig(CONFIG, ConfigScope::User);
+
+        assert_eq!(config.org.len(), 1);
+        assert!(!config.org[0]
+            .up_operations
+            .is_operation_allowed("cargo-install"));
+        assert!(config.org[0].up_operations.is_operation_allowed("mise"));
+    }
+
+    #[test]
+    fn ignored_from_workdir_config() {
+        let config = scoped_omniconfig(CONFIG, ConfigScope::Workdir);
+
+        assert!(config.org.is_empty());
+    }
+}
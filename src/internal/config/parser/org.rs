@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::UpCommandOperationConfig;
 use crate::internal::config::ConfigValue;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,6 +13,8 @@ pub struct OrgConfig {
     pub worktree: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub repo_path_format: Option<String>,
+    #[serde(default, skip_serializing_if = "UpCommandOperationConfig::is_empty")]
+    pub up_operations: UpCommandOperationConfig,
 }
 
 impl Default for OrgConfig {
@@ -20,6 +24,7 @@ impl Default for OrgConfig {
             trusted: false,
             worktree: None,
             repo_path_format: None,
+            up_operations: UpCommandOperationConfig::default(),
         }
     }
 }
@@ -34,10 +39,14 @@ impl OrgConfig {
             trusted: true,
             worktree,
             repo_path_format: None,
+            up_operations: UpCommandOperationConfig::default(),
         }
     }
 
-    pub fn from_config_value(config_value: &ConfigValue) -> Self {
+    pub fn from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
         // If the config_value contains a value directly, we want to consider
         // it as the "handle=worktree", and not as a table.
         if config_value.is_str() {
@@ -50,6 +59,10 @@ impl OrgConfig {
             trusted: config_value.get_as_bool_forced("trusted").unwrap_or(false),
             worktree: config_value.get_as_str("worktree"),
             repo_path_format: config_value.get_as_str("repo_path_format"),
+            up_operations: UpCommandOperationConfig::from_config_value(
+                config_value.get("up_operations"),
+                &error_handler.with_key("up_operations"),
+            ),
         }
     }
 }
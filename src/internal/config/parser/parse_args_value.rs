@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::json;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum ParseArgsValue {
@@ -20,6 +21,26 @@ pub enum ParseArgsValue {
 }
 
 impl ParseArgsValue {
+    /// Returns the value as JSON, keeping its type; values that were not
+    /// provided are `null`, multiple values are arrays, and the values of
+    /// grouped occurrences are arrays of arrays
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::SingleString(value) => json!(value),
+            Self::SingleInteger(value) => json!(value),
+            Self::SingleFloat(value) => json!(value),
+            Self::SingleBoolean(value) => json!(value),
+            Self::ManyString(values) => json!(values),
+            Self::ManyInteger(values) => json!(values),
+            Self::ManyFloat(values) => json!(values),
+            Self::ManyBoolean(values) => json!(values),
+            Self::GroupedString(values) => json!(values),
+            Self::GroupedInteger(values) => json!(values),
+            Self::GroupedFloat(values) => json!(values),
+            Self::GroupedBoolean(values) => json!(values),
+        }
+    }
+
     pub fn export_to_env(&self, key: &str, args: &mut BTreeMap<String, String>) {
         let type_key = format!("OMNI_ARG_{}_TYPE", key.to_uppercase());
         let value_key = format!("OMNI_ARG_{}_VALUE", key.to_uppercase());
@@ -0,0 +1,53 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PathConflictsConfig {
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+}
+
+impl Default for PathConflictsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::DEFAULT_ENABLED,
+            ignore: vec![],
+        }
+    }
+}
+
+impl PathConflictsConfig {
+    const DEFAULT_ENABLED: bool = true;
+
+    pub(super) fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        Self {
+            enabled: config_value.get_as_bool_or_default(
+                "enabled",
+                Self::DEFAULT_ENABLED,
+                &error_handler.with_key("enabled"),
+            ),
+            ignore: config_value.get_as_str_array("ignore", &error_handler.with_key("ignore")),
+        }
+    }
+
+    /// Whether the conflicts for the given tool should be reported
+    pub fn is_checked(&self, tool: &str) -> bool {
+        self.enabled && !self.ignore.iter().any(|ignored| ignored == tool)
+    }
+}
+
+#[cfg(test)]
+#[path = "path_conflicts_test.rs"]
+mod tests;
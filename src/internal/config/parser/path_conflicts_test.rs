@@ -0,0 +1,57 @@
+use super::*;
+
+fn path_conflicts_from_str(yaml: &str) -> PathConflictsConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    PathConflictsConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop())
+}
+
+mod from_config_value {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let config = PathConflictsConfig::from_config_value(None, &ConfigErrorHandler::noop());
+
+        assert!(config.enabled);
+        assert!(config.ignore.is_empty());
+    }
+
+    #[test]
+    fn all_values() {
+        let config = path_conflicts_from_str("enabled: false\nignore: [terraform, node]\n");
+
+        assert_eq!(
+            config,
+            PathConflictsConfig {
+                enabled: false,
+                ignore: vec!["terraform".to_string(), "node".to_string()],
+            }
+        );
+    }
+}
+
+mod is_checked {
+    use super::*;
+
+    #[test]
+    fn checked_by_default() {
+        let config = PathConflictsConfig::default();
+
+        assert!(config.is_checked("terraform"));
+    }
+
+    #[test]
+    fn ignored_tool() {
+        let config = path_conflicts_from_str("ignore: [terraform]");
+
+        assert!(!config.is_checked("terraform"));
+        assert!(config.is_checked("node"));
+    }
+
+    #[test]
+    fn disabled() {
+        let config = path_conflicts_from_str("enabled: false");
+
+        assert!(!config.is_checked("node"));
+    }
+}
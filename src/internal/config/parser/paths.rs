@@ -0,0 +1,199 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::ConfigScope;
+use crate::internal::config::ConfigValue;
+
+/// The canonicalization policy of the loaded global configuration; it is
+/// kept apart from the configuration since paths are canonicalized while
+/// loading the configuration, and reading it from there would be reentrant
+static CANONICALIZE_POLICY: Lazy<RwLock<Option<PathsCanonicalize>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Returns the canonicalization policy of the global configuration, or
+/// the default policy if the global configuration is not loaded yet
+pub fn canonicalize_policy() -> PathsCanonicalize {
+    CANONICALIZE_POLICY
+        .read()
+        .ok()
+        .and_then(|policy| *policy)
+        .unwrap_or_default()
+}
+
+/// Records the canonicalization policy of the global configuration,
+/// once it has been loaded
+pub(crate) fn set_canonicalize_policy(policy: PathsCanonicalize) {
+    if let Ok(mut cached) = CANONICALIZE_POLICY.write() {
+        *cached = Some(policy);
+    }
+}
+
+/// Forgets the canonicalization policy, until the global configuration
+/// is loaded again
+pub(crate) fn flush_canonicalize_policy() {
+    if let Ok(mut cached) = CANONICALIZE_POLICY.write() {
+        *cached = None;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PathsConfig {
+    pub dir_mode: u32,
+    pub file_mode: u32,
+    pub private: bool,
+    pub canonicalize: PathsCanonicalize,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            dir_mode: Self::DEFAULT_DIR_MODE,
+            file_mode: Self::DEFAULT_FILE_MODE,
+            private: Self::DEFAULT_PRIVATE,
+            canonicalize: PathsCanonicalize::default(),
+        }
+    }
+}
+
+impl PathsConfig {
+    const DEFAULT_DIR_MODE: u32 = 0o755;
+    const DEFAULT_FILE_MODE: u32 = 0o644;
+    const DEFAULT_PRIVATE: bool = false;
+
+    pub(super) fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        let config_value = match config_value.reject_scope(&ConfigScope::Workdir) {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            return Self::default();
+        }
+
+        Self {
+            dir_mode: Self::mode_from_config_value(
+                config_value.get("dir_mode"),
+                Self::DEFAULT_DIR_MODE,
+                &error_handler.with_key("dir_mode"),
+            ),
+            file_mode: Self::mode_from_config_value(
+                config_value.get("file_mode"),
+                Self::DEFAULT_FILE_MODE,
+                &error_handler.with_key("file_mode"),
+            ),
+            private: config_value.get_as_bool_or_default(
+                "private",
+                Self::DEFAULT_PRIVATE,
+                &error_handler.with_key("private"),
+            ),
+            canonicalize: PathsCanonicalize::from_config_value(
+                config_value.get("canonicalize"),
+                &error_handler.with_key("canonicalize"),
+            ),
+        }
+    }
+
+    /// Parse a permission mode, which can be provided either as a string
+    /// of octal digits (e.g. "0755" or "0o755"), or as an integer; since
+    /// YAML reads `0755` as the decimal integer 755, integers written with
+    /// octal digits only are read as octal when they would otherwise be
+    /// out of the range of valid modes.
+    fn mode_from_config_value(
+        config_value: Option<ConfigValue>,
+        default: u32,
+        error_handler: &ConfigErrorHandler,
+    ) -> u32 {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return default,
+        };
+
+        let mode = if let Some(mode) = config_value.as_unsigned_integer() {
+            if mode > 0o777 {
+                u32::from_str_radix(&mode.to_string(), 8).ok()
+            } else {
+                Some(mode as u32)
+            }
+        } else if let Some(mode) = config_value.as_str() {
+            let mode = mode.trim();
+            let mode = mode.strip_prefix("0o").unwrap_or(mode);
+            u32::from_str_radix(mode, 8).ok()
+        } else {
+            error_handler
+                .with_expected(vec!["string", "integer"])
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            return default;
+        };
+
+        match mode {
+            Some(mode) if mode <= 0o777 => mode,
+            _ => {
+                error_handler
+                    .with_expected("octal permission mode between 0000 and 0777")
+                    .with_actual(config_value)
+                    .error(ConfigErrorKind::InvalidValue);
+
+                default
+            }
+        }
+    }
+}
+
+/// How the paths are canonicalized when identifying work directories and
+/// recording paths, so that a same location reached through different
+/// paths (e.g. through a symlink) is always identified the same way
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PathsCanonicalize {
+    /// Paths are kept as they were used to reach a location, without
+    /// resolving the symlinks they go through
+    Logical,
+    /// Paths are resolved to the physical location they point to
+    #[default]
+    Physical,
+}
+
+impl PathsCanonicalize {
+    fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        match config_value.as_str().as_deref() {
+            Some("logical") => Self::Logical,
+            Some("physical") => Self::Physical,
+            _ => {
+                error_handler
+                    .with_expected(vec!["logical", "physical"])
+                    .with_actual(config_value)
+                    .error(ConfigErrorKind::InvalidValue);
+
+                Self::default()
+            }
+        }
+    }
+}
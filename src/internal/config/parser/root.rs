@@ -6,8 +6,11 @@ use lazy_static::lazy_static;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::internal::commands::utils::canonicalize_path;
 use crate::internal::config::config_loader;
 use crate::internal::config::flush_config_loader;
+use crate::internal::config::parser::canonicalize_policy;
+use crate::internal::config::parser::set_canonicalize_policy;
 use crate::internal::config::OmniConfig;
 use crate::internal::workdir;
 
@@ -17,17 +20,16 @@ lazy_static! {
 }
 
 pub fn config(path: &str) -> OmniConfig {
-    let path = if path == "/" {
-        path.to_owned()
-    } else {
-        std::fs::canonicalize(path)
-            .unwrap_or(path.to_owned().into())
-            .to_str()
-            .unwrap()
-            .to_owned()
-    };
-
     let mut config_per_path = CONFIG_PER_PATH.lock().unwrap();
+    if path == "/" {
+        return config_per_path.get(path).clone();
+    }
+
+    // The global configuration is loaded first, so that the path is
+    // canonicalized following its policy
+    let policy = config_per_path.get("/").paths.canonicalize;
+    let path = canonicalize_path(path, policy).to_str().unwrap().to_owned();
+
     config_per_path.get(&path).clone()
 }
 
@@ -41,8 +43,7 @@ pub fn flush_config(path: &str) {
         return;
     }
 
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -88,6 +89,9 @@ impl OmniConfigPerPath {
         if !self.config.contains_key(&key) {
             let config_loader = config_loader(&key);
             let new_config: OmniConfig = config_loader.into();
+            if key == "/" {
+                set_canonicalize_policy(new_config.paths.canonicalize);
+            }
             self.config.insert(key.clone(), new_config);
         }
 
@@ -0,0 +1,113 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::parser::errors::ConfigErrorHandler;
+use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::ConfigScope;
+use crate::internal::config::ConfigValue;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ShellSessionConfig {
+    pub id_source: ShellSessionIdSource,
+    pub id_env_var: String,
+}
+
+impl Default for ShellSessionConfig {
+    fn default() -> Self {
+        Self {
+            id_source: ShellSessionIdSource::default(),
+            id_env_var: Self::DEFAULT_ID_ENV_VAR.to_string(),
+        }
+    }
+}
+
+impl ShellSessionConfig {
+    const DEFAULT_ID_ENV_VAR: &str = "OMNI_SESSION_ID";
+
+    pub(super) fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        let config_value = match config_value.reject_scope(&ConfigScope::Workdir) {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            return Self::default();
+        }
+
+        let id_source = match config_value
+            .get_as_str_or_none("id_source", &error_handler.with_key("id_source"))
+        {
+            Some(value) => match value.to_lowercase().as_str() {
+                "ppid" => ShellSessionIdSource::Ppid,
+                "env" => ShellSessionIdSource::Env,
+                "none" => ShellSessionIdSource::None,
+                _ => {
+                    error_handler
+                        .with_key("id_source")
+                        .with_expected(vec!["ppid", "env", "none"])
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValue);
+                    ShellSessionIdSource::default()
+                }
+            },
+            None => ShellSessionIdSource::default(),
+        };
+
+        let id_env_var = config_value.get_as_str_or_default(
+            "id_env_var",
+            Self::DEFAULT_ID_ENV_VAR,
+            &error_handler.with_key("id_env_var"),
+        );
+
+        Self {
+            id_source,
+            id_env_var,
+        }
+    }
+
+    /// Returns the identifier of the current shell session, used to scope
+    /// the dynamic environment to the shell it was loaded in
+    pub fn session_id(&self) -> String {
+        let ppid = || std::env::var("OMNI_SHELL_PPID").unwrap_or("".to_string());
+
+        match self.id_source {
+            ShellSessionIdSource::Ppid => ppid(),
+            ShellSessionIdSource::Env => match std::env::var(&self.id_env_var) {
+                Ok(id) if !id.is_empty() => format!("env:{id}"),
+                _ => ppid(),
+            },
+            ShellSessionIdSource::None => "".to_string(),
+        }
+    }
+}
+
+/// Where the identifier of the shell session is read from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellSessionIdSource {
+    /// The process id of the shell, as provided by the shell integration
+    #[default]
+    Ppid,
+    /// The value of the environment variable configured in `id_env_var`,
+    /// falling back to the process id of the shell if it is not set
+    Env,
+    /// No session scoping, the dynamic environment is shared by all shells
+    None,
+}
+
+#[cfg(test)]
+#[path = "shell_session_test.rs"]
+mod tests;
@@ -0,0 +1,105 @@
+use super::*;
+
+use crate::internal::config::ConfigSource;
+use crate::internal::testutils::run_with_env;
+
+fn session_from_str(yaml: &str) -> ShellSessionConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    ShellSessionConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop())
+}
+
+fn session_env(ppid: &str, session_id: Option<&str>) -> Vec<(String, Option<String>)> {
+    vec![
+        ("OMNI_SHELL_PPID".to_string(), Some(ppid.to_string())),
+        (
+            "OMNI_SESSION_ID".to_string(),
+            session_id.map(|id| id.to_string()),
+        ),
+    ]
+}
+
+mod from_config_value {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let config = ShellSessionConfig::from_config_value(None, &ConfigErrorHandler::noop());
+
+        assert_eq!(config.id_source, ShellSessionIdSource::Ppid);
+        assert_eq!(config.id_env_var, "OMNI_SESSION_ID");
+    }
+
+    #[test]
+    fn env_source() {
+        let config = session_from_str("id_source: env\nid_env_var: TMUX_PANE");
+
+        assert_eq!(config.id_source, ShellSessionIdSource::Env);
+        assert_eq!(config.id_env_var, "TMUX_PANE");
+    }
+
+    #[test]
+    fn invalid_source() {
+        let config_value = ConfigValue::from_str("id_source: tty").expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("config.yaml");
+        let config = ShellSessionConfig::from_config_value(Some(config_value), &error_handler);
+
+        assert_eq!(config.id_source, ShellSessionIdSource::Ppid);
+
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1, "errors: {errors:?}");
+    }
+
+    #[test]
+    fn rejected_from_workdir() {
+        let value = serde_yaml::from_str("id_source: none").unwrap();
+        let config_value = ConfigValue::from_value(ConfigSource::Null, ConfigScope::Workdir, value);
+        let config =
+            ShellSessionConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop());
+
+        assert_eq!(config, ShellSessionConfig::default());
+    }
+}
+
+mod session_id {
+    use super::*;
+
+    #[test]
+    fn ppid_source_uses_shell_ppid() {
+        run_with_env(&session_env("1234", Some("stable")), || {
+            let config = ShellSessionConfig::default();
+            assert_eq!(config.session_id(), "1234");
+        });
+    }
+
+    #[test]
+    fn env_source_uses_env_var() {
+        let config = session_from_str("id_source: env");
+
+        for ppid in ["1234", "5678"] {
+            run_with_env(&session_env(ppid, Some("stable")), || {
+                assert_eq!(config.session_id(), "env:stable");
+            });
+        }
+    }
+
+    #[test]
+    fn env_source_falls_back_to_ppid() {
+        let config = session_from_str("id_source: env");
+
+        run_with_env(&session_env("1234", None), || {
+            assert_eq!(config.session_id(), "1234");
+        });
+        run_with_env(&session_env("1234", Some("")), || {
+            assert_eq!(config.session_id(), "1234");
+        });
+    }
+
+    #[test]
+    fn none_source_is_not_scoped() {
+        let config = session_from_str("id_source: none");
+
+        run_with_env(&session_env("1234", Some("stable")), || {
+            assert_eq!(config.session_id(), "");
+        });
+    }
+}
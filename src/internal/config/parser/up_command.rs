@@ -3,8 +3,10 @@ use std::collections::HashMap;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::internal::cache::utils as cache_utils;
 use crate::internal::config::parser::errors::ConfigErrorHandler;
 use crate::internal::config::parser::errors::ConfigErrorKind;
+use crate::internal::config::up::advisories::UpAdvisory;
 use crate::internal::config::utils::check_allowed;
 use crate::internal::config::utils::parse_duration_or_default;
 use crate::internal::config::ConfigScope;
@@ -17,13 +19,25 @@ pub struct UpCommandConfig {
     pub notify_workdir_config_available: bool,
     pub attach_kill_timeout: u64,
     pub attach_lock_timeout: u64,
+    #[serde(default = "UpCommandConfig::default_wait_timeout")]
+    pub wait_timeout: u64,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub preferred_tools: Vec<String>,
     pub mise_version: String,
+    pub mise_min_version: String,
+    pub mise_bootstrap: bool,
+    pub mise_from_path: bool,
     pub uv_version: String,
     pub upgrade: bool,
+    pub infer_runtimes: bool,
+    #[serde(default)]
+    pub data_paths: UpDataPathsPolicy,
+    #[serde(default)]
+    pub ambiguous_versions: UpAmbiguousVersionsPolicy,
     #[serde(default, skip_serializing_if = "UpCommandOperationConfig::is_empty")]
     pub operations: UpCommandOperationConfig,
+    #[serde(default, skip_serializing_if = "UpCommandAdvisoriesConfig::is_empty")]
+    pub advisories: UpCommandAdvisoriesConfig,
 }
 
 impl Default for UpCommandConfig {
@@ -31,14 +45,22 @@ impl Default for UpCommandConfig {
         Self {
             attach_kill_timeout: Self::DEFAULT_ATTACH_KILL_TIMEOUT,
             attach_lock_timeout: Self::DEFAULT_ATTACH_LOCK_TIMEOUT,
+            wait_timeout: Self::DEFAULT_WAIT_TIMEOUT,
             auto_bootstrap: Self::DEFAULT_AUTO_BOOTSTRAP,
             mise_version: Self::DEFAULT_MISE_VERSION.to_string(),
+            mise_min_version: Self::DEFAULT_MISE_MIN_VERSION.to_string(),
+            mise_bootstrap: Self::DEFAULT_MISE_BOOTSTRAP,
+            mise_from_path: Self::DEFAULT_MISE_FROM_PATH,
             uv_version: Self::DEFAULT_UV_VERSION.to_string(),
             notify_workdir_config_updated: Self::DEFAULT_NOTIFY_WORKDIR_CONFIG_UPDATED,
             notify_workdir_config_available: Self::DEFAULT_NOTIFY_WORKDIR_CONFIG_AVAILABLE,
             operations: UpCommandOperationConfig::default(),
+            advisories: UpCommandAdvisoriesConfig::default(),
             preferred_tools: Vec::new(),
             upgrade: Self::DEFAULT_UPGRADE,
+            infer_runtimes: Self::DEFAULT_INFER_RUNTIMES,
+            data_paths: UpDataPathsPolicy::default(),
+            ambiguous_versions: UpAmbiguousVersionsPolicy::default(),
         }
     }
 }
@@ -46,12 +68,21 @@ impl Default for UpCommandConfig {
 impl UpCommandConfig {
     const DEFAULT_ATTACH_KILL_TIMEOUT: u64 = 600; // 10 minutes
     const DEFAULT_ATTACH_LOCK_TIMEOUT: u64 = 5; // 5 seconds
+    const DEFAULT_WAIT_TIMEOUT: u64 = 3600; // 1 hour
     const DEFAULT_AUTO_BOOTSTRAP: bool = true;
     const DEFAULT_NOTIFY_WORKDIR_CONFIG_UPDATED: bool = true;
     const DEFAULT_NOTIFY_WORKDIR_CONFIG_AVAILABLE: bool = true;
     const DEFAULT_MISE_VERSION: &str = "latest";
+    const DEFAULT_MISE_MIN_VERSION: &str = "2024.1.0";
+    const DEFAULT_MISE_BOOTSTRAP: bool = true;
+    const DEFAULT_MISE_FROM_PATH: bool = false;
     const DEFAULT_UV_VERSION: &str = "latest";
     const DEFAULT_UPGRADE: bool = false;
+    const DEFAULT_INFER_RUNTIMES: bool = true;
+
+    fn default_wait_timeout() -> u64 {
+        Self::DEFAULT_WAIT_TIMEOUT
+    }
 
     pub(super) fn from_config_value(
         config_value: Option<ConfigValue>,
@@ -79,6 +110,12 @@ impl UpCommandConfig {
             &error_handler.with_key("attach_lock_timeout"),
         );
 
+        let wait_timeout = parse_duration_or_default(
+            config_value.get("wait_timeout").as_ref(),
+            Self::DEFAULT_WAIT_TIMEOUT,
+            &error_handler.with_key("wait_timeout"),
+        );
+
         let auto_bootstrap = config_value_global.get_as_bool_or_default(
             "auto_bootstrap",
             Self::DEFAULT_AUTO_BOOTSTRAP,
@@ -108,6 +145,24 @@ impl UpCommandConfig {
             &error_handler.with_key("mise_version"),
         );
 
+        let mise_min_version = config_value_global.get_as_str_or_default(
+            "mise_min_version",
+            Self::DEFAULT_MISE_MIN_VERSION,
+            &error_handler.with_key("mise_min_version"),
+        );
+
+        let mise_bootstrap = config_value_global.get_as_bool_or_default(
+            "mise_bootstrap",
+            Self::DEFAULT_MISE_BOOTSTRAP,
+            &error_handler.with_key("mise_bootstrap"),
+        );
+
+        let mise_from_path = config_value_global.get_as_bool_or_default(
+            "mise_from_path",
+            Self::DEFAULT_MISE_FROM_PATH,
+            &error_handler.with_key("mise_from_path"),
+        );
+
         let uv_version = config_value_global.get_as_str_or_default(
             "uv_version",
             Self::DEFAULT_UV_VERSION,
@@ -121,26 +176,160 @@ impl UpCommandConfig {
             &error_handler.with_key("upgrade"),
         );
 
+        // For infer_runtimes, we allow overriding in the workdir
+        let infer_runtimes = config_value.get_as_bool_or_default(
+            "infer_runtimes",
+            Self::DEFAULT_INFER_RUNTIMES,
+            &error_handler.with_key("infer_runtimes"),
+        );
+
+        // For data_paths, we allow overriding in the workdir, so that
+        // a repository needing true isolation can opt-out of sharing
+        let data_paths = match config_value
+            .get_as_str_or_none("data_paths", &error_handler.with_key("data_paths"))
+        {
+            Some(value) => match value.to_lowercase().as_str() {
+                "isolated" => UpDataPathsPolicy::Isolated,
+                "shared" => UpDataPathsPolicy::Shared,
+                _ => {
+                    error_handler
+                        .with_key("data_paths")
+                        .with_expected(vec!["isolated", "shared"])
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValue);
+                    UpDataPathsPolicy::default()
+                }
+            },
+            None => UpDataPathsPolicy::default(),
+        };
+
+        // For ambiguous_versions, we allow overriding in the workdir, since
+        // the repository knows best whether its root needs a pinned version
+        let ambiguous_versions = match config_value.get_as_str_or_none(
+            "ambiguous_versions",
+            &error_handler.with_key("ambiguous_versions"),
+        ) {
+            Some(value) => match value.to_lowercase().as_str() {
+                "newest" => UpAmbiguousVersionsPolicy::Newest,
+                "error" => UpAmbiguousVersionsPolicy::Error,
+                _ => {
+                    error_handler
+                        .with_key("ambiguous_versions")
+                        .with_expected(vec!["newest", "error"])
+                        .with_actual(value)
+                        .error(ConfigErrorKind::InvalidValue);
+                    UpAmbiguousVersionsPolicy::default()
+                }
+            },
+            None => UpAmbiguousVersionsPolicy::default(),
+        };
+
         let operations = UpCommandOperationConfig::from_config_value(
             config_value.get("operations"),
             &error_handler.with_key("operations"),
         );
 
+        let advisories = UpCommandAdvisoriesConfig::from_config_value(
+            config_value.get("advisories"),
+            &error_handler.with_key("advisories"),
+        );
+
         Self {
             attach_kill_timeout,
             attach_lock_timeout,
+            wait_timeout,
             auto_bootstrap,
             mise_version,
+            mise_min_version,
+            mise_bootstrap,
+            mise_from_path,
             uv_version,
             notify_workdir_config_available,
             notify_workdir_config_updated,
             operations,
+            advisories,
             preferred_tools,
             upgrade,
+            infer_runtimes,
+            data_paths,
+            ambiguous_versions,
         }
     }
 }
 
+/// The policy to use for the data paths of the tools that support
+/// isolating their installation paths (e.g. `GEM_HOME` for ruby,
+/// `GOPATH` for go or `CARGO_INSTALL_ROOT` for rust)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpDataPathsPolicy {
+    /// Each work directory, and each directory in it, gets its own
+    /// data path for each tool version
+    #[default]
+    Isolated,
+    /// Work directories using the same tool version share the same
+    /// data path for that tool version
+    Shared,
+}
+
+/// The policy to use when different versions of a tool are scoped to
+/// different subdirectories, and the current directory is not covered
+/// by any of those scopes (e.g. the root of a monorepo)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpAmbiguousVersionsPolicy {
+    /// Use the newest of those versions, and let the user know about it
+    #[default]
+    Newest,
+    /// Do not use any of those versions, requiring a version to be
+    /// explicitly defined for the current directory
+    Error,
+}
+
+/// The advisories to check the resolved tool versions against, either
+/// listed directly or fetched from remote feeds
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct UpCommandAdvisoriesConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<UpAdvisory>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feeds: Vec<String>,
+}
+
+impl UpCommandAdvisoriesConfig {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.feeds.is_empty()
+    }
+
+    fn from_config_value(
+        config_value: Option<ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => return Self::default(),
+        };
+
+        // The feeds are fetched from the network, so they cannot be
+        // defined by a work directory; entries can, since they can only
+        // add warnings
+        let config_value_global = config_value
+            .reject_scope(&ConfigScope::Workdir)
+            .unwrap_or_default();
+
+        let entries = match config_value.get("entries") {
+            Some(value) => {
+                UpAdvisory::from_config_value_list(&value, &error_handler.with_key("entries"))
+            }
+            None => vec![],
+        };
+
+        let feeds = config_value_global.get_as_str_array("feeds", &error_handler.with_key("feeds"));
+
+        Self { entries, feeds }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub(crate) struct UpCommandOperationConfig {
     pub allowed: Vec<String>,
@@ -171,7 +360,7 @@ pub(crate) struct UpCommandOperationConfig {
 }
 
 impl UpCommandOperationConfig {
-    fn is_empty(&self) -> bool {
+    pub(crate) fn is_empty(&self) -> bool {
         self.allowed.is_empty() && self.sources.is_empty()
     }
 
@@ -203,7 +392,7 @@ impl UpCommandOperationConfig {
             && check_allowed(repository, &self.github_release.repositories)
     }
 
-    fn from_config_value(
+    pub(crate) fn from_config_value(
         config_value: Option<ConfigValue>,
         error_handler: &ConfigErrorHandler,
     ) -> Self {
@@ -258,6 +447,8 @@ pub(crate) struct UpCommandOperationMiseConfig {
     pub backends: Vec<String>,
     pub sources: Vec<String>,
     pub default_plugin_sources: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub content_store: bool,
 }
 
 impl UpCommandOperationMiseConfig {
@@ -312,10 +503,17 @@ impl UpCommandOperationMiseConfig {
                 HashMap::new()
             };
 
+        let content_store = config_value_global.get_as_bool_or_default(
+            "content_store",
+            false,
+            &error_handler.with_key("content_store"),
+        );
+
         Self {
             backends,
             sources,
             default_plugin_sources,
+            content_store,
         }
     }
 
@@ -323,12 +521,15 @@ impl UpCommandOperationMiseConfig {
         self.backends.is_empty()
             && self.sources.is_empty()
             && self.default_plugin_sources.is_empty()
+            && !self.content_store
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub(crate) struct UpCommandOperationCargoInstallConfig {
     pub crates: Vec<String>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub content_store: bool,
 }
 
 impl UpCommandOperationCargoInstallConfig {
@@ -348,17 +549,28 @@ impl UpCommandOperationCargoInstallConfig {
         let crates =
             config_value_global.get_as_str_array("crates", &error_handler.with_key("crates"));
 
-        Self { crates }
+        let content_store = config_value_global.get_as_bool_or_default(
+            "content_store",
+            false,
+            &error_handler.with_key("content_store"),
+        );
+
+        Self {
+            crates,
+            content_store,
+        }
     }
 
     fn is_empty(&self) -> bool {
-        self.crates.is_empty()
+        self.crates.is_empty() && !self.content_store
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpCommandOperationGoInstallConfig {
     pub sources: Vec<String>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub content_store: bool,
 }
 
 impl UpCommandOperationGoInstallConfig {
@@ -378,17 +590,28 @@ impl UpCommandOperationGoInstallConfig {
         let sources =
             config_value_global.get_as_str_array("sources", &error_handler.with_key("sources"));
 
-        Self { sources }
+        let content_store = config_value_global.get_as_bool_or_default(
+            "content_store",
+            false,
+            &error_handler.with_key("content_store"),
+        );
+
+        Self {
+            sources,
+            content_store,
+        }
     }
 
     fn is_empty(&self) -> bool {
-        self.sources.is_empty()
+        self.sources.is_empty() && !self.content_store
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpCommandOperationGithubReleaseConfig {
     pub repositories: Vec<String>,
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub content_store: bool,
 }
 
 impl UpCommandOperationGithubReleaseConfig {
@@ -408,11 +631,20 @@ impl UpCommandOperationGithubReleaseConfig {
         let repositories = config_value_global
             .get_as_str_array("repositories", &error_handler.with_key("repositories"));
 
-        Self { repositories }
+        let content_store = config_value_global.get_as_bool_or_default(
+            "content_store",
+            false,
+            &error_handler.with_key("content_store"),
+        );
+
+        Self {
+            repositories,
+            content_store,
+        }
     }
 
     fn is_empty(&self) -> bool {
-        self.repositories.is_empty()
+        self.repositories.is_empty() && !self.content_store
     }
 }
 
@@ -175,3 +175,94 @@ fn test_invalid_patterns() {
     // Should ignore invalid pattern and match against valid one
     assert!(check_url_allowed("example1.com/repo", &patterns));
 }
+
+#[test]
+fn test_infer_runtimes_default() {
+    let config = UpCommandConfig::from_config_value(None, &ConfigErrorHandler::default());
+    assert!(config.infer_runtimes);
+
+    let config_value = ConfigValue::from_str("upgrade: true").unwrap();
+    let config =
+        UpCommandConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default());
+    assert!(config.infer_runtimes);
+}
+
+#[test]
+fn test_infer_runtimes_disabled() {
+    let config_value = ConfigValue::from_str("infer_runtimes: false").unwrap();
+    let config =
+        UpCommandConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default());
+    assert!(!config.infer_runtimes);
+}
+
+#[test]
+fn test_data_paths_default() {
+    let config = UpCommandConfig::from_config_value(None, &ConfigErrorHandler::default());
+    assert_eq!(config.data_paths, UpDataPathsPolicy::Isolated);
+}
+
+#[test]
+fn test_data_paths_shared() {
+    let config_value = ConfigValue::from_str("data_paths: shared").unwrap();
+    let config =
+        UpCommandConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default());
+    assert_eq!(config.data_paths, UpDataPathsPolicy::Shared);
+}
+
+#[test]
+fn test_advisory_feeds_rejected_from_workdir() {
+    use crate::internal::config::ConfigSource;
+
+    let value = serde_yaml::from_str(
+        r#"
+        advisories:
+          entries:
+            - tool: python
+              version: "<3.9"
+          feeds:
+            - https://advisories.example.com/feed.json
+        "#,
+    )
+    .unwrap();
+    let config_value = ConfigValue::from_value(ConfigSource::Null, ConfigScope::Workdir, value);
+    let config =
+        UpCommandConfig::from_config_value(Some(config_value), &ConfigErrorHandler::noop());
+
+    // Entries can only add warnings, but feeds are fetched from the network
+    assert_eq!(config.advisories.entries.len(), 1);
+    assert!(config.advisories.feeds.is_empty());
+}
+
+#[test]
+fn test_ambiguous_versions_default() {
+    let config = UpCommandConfig::from_config_value(None, &ConfigErrorHandler::default());
+    assert_eq!(config.ambiguous_versions, UpAmbiguousVersionsPolicy::Newest);
+}
+
+#[test]
+fn test_ambiguous_versions_error() {
+    let config_value = ConfigValue::from_str("ambiguous_versions: error").unwrap();
+    let config =
+        UpCommandConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default());
+    assert_eq!(config.ambiguous_versions, UpAmbiguousVersionsPolicy::Error);
+}
+
+#[test]
+fn test_content_store_per_operation() {
+    let config = UpCommandConfig::from_config_value(None, &ConfigErrorHandler::default());
+    assert!(!config.operations.mise.content_store);
+    assert!(!config.operations.cargo_install.content_store);
+    assert!(!config.operations.go_install.content_store);
+    assert!(!config.operations.github_release.content_store);
+
+    let config_value = ConfigValue::from_str(
+        "operations:\n  github-release:\n    content_store: true\n  go-install:\n    content_store: true\n  mise:\n    content_store: true",
+    )
+    .unwrap();
+    let config =
+        UpCommandConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default());
+    assert!(config.operations.mise.content_store);
+    assert!(!config.operations.cargo_install.content_store);
+    assert!(config.operations.go_install.content_store);
+    assert!(config.operations.github_release.content_store);
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use itertools::Itertools;
+
+use crate::internal::config::config_value::ConfigData;
+use crate::internal::config::ConfigExtendOptions;
+use crate::internal::config::ConfigValue;
+use crate::internal::env::user_home;
+
+/// The environment variable that can be used to select the active
+/// profile, taking precedence over any other selection mechanism
+pub const PROFILE_ENV_VAR: &str = "OMNI_PROFILE";
+
+/// How the active profile was selected, in order of precedence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProfileSelection {
+    /// Selected through the `OMNI_PROFILE` environment variable
+    Env,
+    /// Selected through the `profile` key of the work directory configuration
+    Workdir,
+    /// Selected through the given pattern of `profiles_by_path`
+    Path(String),
+    /// Selected through the `profile` key of the global configuration
+    Default,
+}
+
+impl std::fmt::Display for ConfigProfileSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Env => write!(f, "selected by ${PROFILE_ENV_VAR}"),
+            Self::Workdir => write!(f, "selected by the work directory configuration"),
+            Self::Path(pattern) => write!(f, "selected by the path rule {pattern}"),
+            Self::Default => write!(f, "selected by default"),
+        }
+    }
+}
+
+/// A profile overlaid onto the configuration, along with the top-level
+/// keys of the configuration that it provided
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProfile {
+    pub name: String,
+    pub selection: ConfigProfileSelection,
+    pub keys: Vec<String>,
+}
+
+impl ConfigProfile {
+    /// Returns the attribution of the values provided by the profile
+    pub fn attribution(&self) -> String {
+        format!("from profile '{}'", self.name)
+    }
+
+    /// Whether the given top-level key of the configuration was provided
+    /// by the profile
+    pub fn provides(&self, key: &str) -> bool {
+        self.keys.iter().any(|k| k == key)
+    }
+}
+
+/// Returns the name of the active profile and how it was selected; the
+/// environment variable takes precedence, then the `profile` key of the
+/// work directory configuration, then the most specific pattern of the
+/// `profiles_by_path` rules matching the path, and finally the `profile`
+/// key of the global configuration
+pub fn select_profile(
+    env_profile: Option<&str>,
+    workdir_profile: Option<&str>,
+    path: Option<&str>,
+    global_config: &ConfigValue,
+) -> Option<(String, ConfigProfileSelection)> {
+    if let Some(name) = env_profile.filter(|name| !name.is_empty()) {
+        return Some((name.to_string(), ConfigProfileSelection::Env));
+    }
+
+    if let Some(name) = workdir_profile.filter(|name| !name.is_empty()) {
+        return Some((name.to_string(), ConfigProfileSelection::Workdir));
+    }
+
+    if let Some(path) = path {
+        if let Some((pattern, name)) = profile_by_path(path, global_config) {
+            return Some((name, ConfigProfileSelection::Path(pattern)));
+        }
+    }
+
+    global_config
+        .get_as_str("profile")
+        .filter(|name| !name.is_empty())
+        .map(|name| (name, ConfigProfileSelection::Default))
+}
+
+/// Returns the pattern and profile of the most specific rule of the
+/// `profiles_by_path` configuration matching the given path, where the
+/// most specific rule is the one with the longest pattern
+fn profile_by_path(path: &str, global_config: &ConfigValue) -> Option<(String, String)> {
+    let rules = global_config.get_as_table("profiles_by_path")?;
+
+    let match_options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    rules
+        .into_iter()
+        .filter_map(|(pattern, name)| Some((pattern, name.as_str()?)))
+        .filter(|(pattern, _)| {
+            let expanded = expand_home(pattern);
+            let expanded = expanded.trim_end_matches('/');
+            let matches_pattern = glob::Pattern::new(expanded)
+                .is_ok_and(|glob| glob.matches_with(path, match_options));
+
+            // A pattern ending with `/**` also matches the directory itself
+            let matches_dir = expanded
+                .strip_suffix("/**")
+                .is_some_and(|dir| Path::new(path) == Path::new(dir));
+
+            matches_pattern || matches_dir
+        })
+        .sorted_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)))
+        .next()
+}
+
+fn expand_home(pattern: &str) -> String {
+    match pattern.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", user_home(), rest),
+        None if pattern == "~" => user_home(),
+        None => pattern.to_string(),
+    }
+}
+
+/// Overlays the settings of the given profile onto the configuration;
+/// returns `None` if the profile is not defined in `profiles`
+pub fn apply_profile(
+    config: &mut ConfigValue,
+    name: &str,
+    selection: ConfigProfileSelection,
+) -> Option<ConfigProfile> {
+    let profile = config.dig(vec!["profiles", name])?;
+    let profile_table = profile.as_table()?;
+
+    // Profiles cannot select other profiles, so those keys are not
+    // overlaid onto the configuration
+    let profile_table = profile_table
+        .into_iter()
+        .filter(|(key, _)| !is_profile_key(key))
+        .collect::<HashMap<_, _>>();
+    let keys = profile_table.keys().cloned().sorted().collect::<Vec<_>>();
+
+    let profile = ConfigValue::new(
+        profile.get_source().clone(),
+        profile.get_scope(),
+        Some(Box::new(ConfigData::Mapping(profile_table))),
+    );
+    config.extend(profile, ConfigExtendOptions::new(), vec![]);
+
+    Some(ConfigProfile {
+        name: name.to_string(),
+        selection,
+        keys,
+    })
+}
+
+fn is_profile_key(key: &str) -> bool {
+    matches!(key, "profile" | "profiles" | "profiles_by_path")
+}
+
+#[cfg(test)]
+#[path = "profile_test.rs"]
+mod tests;
@@ -0,0 +1,281 @@
+use super::*;
+
+use crate::internal::config::ConfigLoader;
+use crate::internal::testutils::run_with_env;
+
+fn global_config(yaml: &str) -> ConfigValue {
+    ConfigValue::from_str(yaml).expect("failed to parse configuration")
+}
+
+const GLOBAL_CONFIG: &str = r#"
+profile: personal
+profiles:
+  work:
+    worktree: /work/git
+    clone:
+      ls_remote_timeout: 10
+    org:
+      - handle: git@github.com:work-org
+  personal:
+    worktree: /personal/git
+profiles_by_path:
+  /work/**: work
+  /work/oss/**: personal
+worktree: /default/git
+clone:
+  auto_up: false
+  ls_remote_timeout: 5
+org:
+  - handle: git@github.com:personal-org
+"#;
+
+mod select_profile {
+    use super::*;
+
+    #[test]
+    fn test_env_takes_precedence() {
+        let config = global_config(GLOBAL_CONFIG);
+        assert_eq!(
+            select_profile(Some("env"), Some("workdir"), Some("/work/repo"), &config),
+            Some(("env".to_string(), ConfigProfileSelection::Env))
+        );
+    }
+
+    #[test]
+    fn test_workdir_takes_precedence_over_path() {
+        let config = global_config(GLOBAL_CONFIG);
+        assert_eq!(
+            select_profile(None, Some("workdir"), Some("/work/repo"), &config),
+            Some(("workdir".to_string(), ConfigProfileSelection::Workdir))
+        );
+    }
+
+    #[test]
+    fn test_path_takes_precedence_over_default() {
+        let config = global_config(GLOBAL_CONFIG);
+        assert_eq!(
+            select_profile(None, None, Some("/work/repo"), &config),
+            Some((
+                "work".to_string(),
+                ConfigProfileSelection::Path("/work/**".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_most_specific_path_rule() {
+        let config = global_config(GLOBAL_CONFIG);
+        assert_eq!(
+            select_profile(None, None, Some("/work/oss/repo"), &config),
+            Some((
+                "personal".to_string(),
+                ConfigProfileSelection::Path("/work/oss/**".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_path_rule_matches_directory_itself() {
+        let config = global_config(GLOBAL_CONFIG);
+        assert_eq!(
+            select_profile(None, None, Some("/work"), &config),
+            Some((
+                "work".to_string(),
+                ConfigProfileSelection::Path("/work/**".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_path_rule_expands_home() {
+        run_with_env(&[], || {
+            let config = global_config("profiles_by_path:\n  ~/work/**: work\n");
+            let path = format!("{}/work/repo", user_home());
+            assert_eq!(
+                select_profile(None, None, Some(&path), &config),
+                Some((
+                    "work".to_string(),
+                    ConfigProfileSelection::Path("~/work/**".to_string())
+                ))
+            );
+        });
+    }
+
+    #[test]
+    fn test_default_when_nothing_else_matches() {
+        let config = global_config(GLOBAL_CONFIG);
+        assert_eq!(
+            select_profile(None, Some(""), Some("/elsewhere"), &config),
+            Some(("personal".to_string(), ConfigProfileSelection::Default))
+        );
+    }
+
+    #[test]
+    fn test_no_profile() {
+        let config = global_config("worktree: /default/git\n");
+        assert_eq!(
+            select_profile(None, None, Some("/work/repo"), &config),
+            None
+        );
+    }
+}
+
+mod apply_profile {
+    use super::*;
+
+    #[test]
+    fn test_overlay_merges_onto_config() {
+        let mut config = global_config(GLOBAL_CONFIG);
+
+        let profile = apply_profile(&mut config, "work", ConfigProfileSelection::Env)
+            .expect("profile should be applied");
+
+        assert_eq!(profile.keys, vec!["clone", "org", "worktree"]);
+        assert_eq!(config.get_as_str("worktree"), Some("/work/git".to_string()));
+
+        // Mappings are merged, lists are replaced
+        let clone = config.get("clone").expect("clone should be set");
+        assert_eq!(clone.get_as_bool("auto_up"), Some(false));
+        assert_eq!(clone.get_as_integer("ls_remote_timeout"), Some(10));
+
+        let orgs = config.get_as_array("org").expect("org should be set");
+        assert_eq!(orgs.len(), 1);
+        assert_eq!(
+            orgs[0].get_as_str("handle"),
+            Some("git@github.com:work-org".to_string())
+        );
+    }
+
+    #[test]
+    fn test_profile_keys_are_not_overlaid() {
+        let mut config = global_config(
+            "profile: personal\nprofiles:\n  work:\n    profile: personal\n    worktree: /work/git\n",
+        );
+
+        let profile = apply_profile(&mut config, "work", ConfigProfileSelection::Env)
+            .expect("profile should be applied");
+
+        assert_eq!(profile.keys, vec!["worktree"]);
+        assert_eq!(config.get_as_str("profile"), Some("personal".to_string()));
+    }
+
+    #[test]
+    fn test_undefined_profile() {
+        let mut config = global_config(GLOBAL_CONFIG);
+        let before = config.clone();
+
+        assert_eq!(
+            apply_profile(&mut config, "unknown", ConfigProfileSelection::Env),
+            None
+        );
+        assert_eq!(config, before);
+    }
+
+    #[test]
+    fn test_attribution() {
+        let mut config = global_config(GLOBAL_CONFIG);
+        let profile = apply_profile(&mut config, "work", ConfigProfileSelection::Default)
+            .expect("profile should be applied");
+
+        assert_eq!(profile.attribution(), "from profile 'work'");
+        assert!(profile.provides("worktree"));
+        assert!(!profile.provides("sandbox"));
+    }
+}
+
+mod loader {
+    use super::*;
+
+    fn write_file(path: &std::path::Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).expect("failed to create directory");
+        std::fs::write(path, contents).expect("failed to write file");
+    }
+
+    fn with_config<F>(envs: &[(String, Option<String>)], closure: F)
+    where
+        F: FnOnce(&str),
+    {
+        run_with_env(envs, || {
+            let home = user_home();
+            let config = GLOBAL_CONFIG.replace("/work", &format!("{home}/work"));
+            write_file(&std::path::Path::new(&home).join(".omni.yaml"), &config);
+            closure(&home);
+        });
+    }
+
+    #[test]
+    fn test_global_uses_default_profile() {
+        with_config(&[], |_| {
+            let loader = ConfigLoader::new_global();
+            let profile = loader.profile.as_ref().expect("profile should be active");
+            assert_eq!(profile.name, "personal");
+            assert_eq!(profile.selection, ConfigProfileSelection::Default);
+            assert_eq!(
+                loader.raw_config.get_as_str("worktree"),
+                Some("/personal/git".to_string())
+            );
+        });
+    }
+
+    #[test]
+    fn test_local_uses_path_rule() {
+        with_config(&[], |home| {
+            let path = format!("{home}/work/repo");
+            std::fs::create_dir_all(&path).expect("failed to create directory");
+
+            let loader = ConfigLoader::new_global().get_local(&path);
+            let profile = loader.profile.as_ref().expect("profile should be active");
+            assert_eq!(profile.name, "work");
+            assert_eq!(
+                loader.raw_config.get_as_str("worktree"),
+                Some(format!("{home}/work/git"))
+            );
+            assert_eq!(
+                loader.attribution("worktree"),
+                Some("from profile 'work'".to_string())
+            );
+            assert_eq!(loader.attribution("sandbox"), None);
+        });
+    }
+
+    #[test]
+    fn test_env_overrides_path_rule() {
+        let envs = [(PROFILE_ENV_VAR.to_string(), Some("personal".to_string()))];
+        with_config(&envs, |home| {
+            let path = format!("{home}/work/repo");
+            std::fs::create_dir_all(&path).expect("failed to create directory");
+
+            let loader = ConfigLoader::new_global().get_local(&path);
+            let profile = loader.profile.as_ref().expect("profile should be active");
+            assert_eq!(profile.name, "personal");
+            assert_eq!(profile.selection, ConfigProfileSelection::Env);
+        });
+    }
+
+    #[test]
+    fn test_workdir_selects_profile_and_takes_precedence() {
+        with_config(&[], |home| {
+            let path = format!("{home}/elsewhere");
+            write_file(
+                &std::path::Path::new(&path).join(".omni.yaml"),
+                "profile: work\nworktree: /workdir/git\n",
+            );
+
+            let loader = ConfigLoader::new_global().get_local(&path);
+            let profile = loader.profile.as_ref().expect("profile should be active");
+            assert_eq!(profile.name, "work");
+            assert_eq!(profile.selection, ConfigProfileSelection::Workdir);
+
+            // The workdir configuration takes precedence over the profile
+            assert_eq!(
+                loader.raw_config.get_as_str("worktree"),
+                Some("/workdir/git".to_string())
+            );
+            assert_eq!(loader.attribution("worktree"), None);
+            assert_eq!(
+                loader.attribution("clone"),
+                Some("from profile 'work'".to_string())
+            );
+        });
+    }
+}
@@ -9,6 +9,7 @@ use serde::Serialize;
 use tera::Tera;
 
 use crate::internal::cache::PromptsCache;
+use crate::internal::env::container_context;
 use crate::internal::git::Repo;
 use crate::internal::git_env;
 use crate::internal::workdir;
@@ -59,6 +60,9 @@ pub fn config_template_context<T: AsRef<str>>(path: T) -> tera::Context {
     let env = std::env::vars().collect::<HashMap<String, String>>();
     context.insert("env", &env);
 
+    // Load context for the container omni is running in, if any
+    context.insert("container", &container_context().name());
+
     // Load context for the user prompts
     let prompts = PromptsCache::get().answers(path);
     context.insert("prompts", &prompts);
@@ -0,0 +1,380 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::up_environments::UpVersion;
+use crate::internal::cache::UpAdvisoriesCache;
+use crate::internal::config::global_config;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::UpCommandAdvisoriesConfig;
+use crate::internal::config::up::utils::VersionMatcher;
+use crate::internal::config::ConfigValue;
+use crate::internal::user_interface::StringColor;
+
+/// The kind of advisory that applies to a tool version
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpAdvisoryKind {
+    /// The version is end-of-life and does not receive fixes anymore
+    Eol,
+    /// The version is affected by a known security issue
+    #[default]
+    Insecure,
+}
+
+impl UpAdvisoryKind {
+    fn from_str(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "eol" | "end-of-life" => Some(Self::Eol),
+            "insecure" | "vulnerable" => Some(Self::Insecure),
+            _ => None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Eol => "end-of-life",
+            Self::Insecure => "insecure",
+        }
+    }
+}
+
+/// An advisory applying to the versions of a tool matching the
+/// version constraint
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UpAdvisory {
+    pub tool: String,
+    pub version: String,
+    #[serde(default)]
+    pub kind: UpAdvisoryKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl UpAdvisory {
+    pub(crate) fn from_config_value(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Option<Self> {
+        if !config_value.is_table() {
+            error_handler
+                .with_expected("table")
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            return None;
+        }
+
+        let tool = match config_value.get_as_str_or_none("tool", &error_handler.with_key("tool")) {
+            Some(tool) => tool,
+            None => {
+                error_handler
+                    .with_key("tool")
+                    .error(ConfigErrorKind::MissingKey);
+
+                return None;
+            }
+        };
+
+        let version =
+            match config_value.get_as_str_or_none("version", &error_handler.with_key("version")) {
+                Some(version) => version,
+                None => {
+                    error_handler
+                        .with_key("version")
+                        .error(ConfigErrorKind::MissingKey);
+
+                    return None;
+                }
+            };
+
+        let kind = match config_value.get_as_str_or_none("kind", &error_handler.with_key("kind")) {
+            Some(kind) => match UpAdvisoryKind::from_str(&kind) {
+                Some(kind) => kind,
+                None => {
+                    error_handler
+                        .with_key("kind")
+                        .with_expected(vec!["eol", "insecure"])
+                        .with_actual(kind)
+                        .error(ConfigErrorKind::InvalidValue);
+
+                    UpAdvisoryKind::default()
+                }
+            },
+            None => UpAdvisoryKind::default(),
+        };
+
+        Some(Self {
+            tool,
+            version,
+            kind,
+            id: config_value.get_as_str_or_none("id", &error_handler.with_key("id")),
+            message: config_value.get_as_str_or_none("message", &error_handler.with_key("message")),
+            url: config_value.get_as_str_or_none("url", &error_handler.with_key("url")),
+        })
+    }
+
+    /// Parses a list of advisories, either provided directly as an array
+    /// or under the `advisories` key of a table
+    pub(crate) fn from_config_value_list(
+        config_value: &ConfigValue,
+        error_handler: &ConfigErrorHandler,
+    ) -> Vec<Self> {
+        let (array, error_handler) = match config_value.get("advisories") {
+            Some(advisories) if config_value.is_table() => {
+                (advisories.as_array(), error_handler.with_key("advisories"))
+            }
+            _ => (config_value.as_array(), error_handler.clone()),
+        };
+
+        match array {
+            Some(array) => array
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| {
+                    Self::from_config_value(item, &error_handler.with_index(index))
+                })
+                .collect(),
+            None => {
+                error_handler
+                    .with_expected("array")
+                    .with_actual(config_value)
+                    .error(ConfigErrorKind::InvalidValueType);
+
+                vec![]
+            }
+        }
+    }
+
+    /// Whether the advisory applies to the given resolved tool version
+    pub fn matches(&self, version: &UpVersion) -> bool {
+        let tool_matches = [
+            &version.tool,
+            &version.normalized_name,
+            &version.plugin_name,
+        ]
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(&self.tool));
+        if !tool_matches {
+            return false;
+        }
+
+        let mut matcher = VersionMatcher::new(&self.version);
+        matcher.prerelease(true);
+        matcher.build(true);
+        matcher.prefix(true);
+        matcher.matches(&version.version)
+    }
+}
+
+/// A warning emitted for a resolved tool version matching an advisory
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpAdvisoryWarning {
+    pub tool: String,
+    pub version: String,
+    pub advisory: UpAdvisory,
+}
+
+impl std::fmt::Display for UpAdvisoryWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} is {}",
+            self.tool.light_yellow(),
+            self.version.light_yellow(),
+            self.advisory.kind.description(),
+        )?;
+
+        if let Some(id) = &self.advisory.id {
+            write!(f, " ({id})")?;
+        }
+
+        if let Some(message) = &self.advisory.message {
+            write!(f, ": {message}")?;
+        }
+
+        if let Some(url) = &self.advisory.url {
+            write!(f, " {}", format!("<{url}>").light_black())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the warnings for the resolved tool versions that match any of
+/// the advisories; each tool version is reported once per advisory
+pub fn check_advisories(
+    versions: &[UpVersion],
+    advisories: &[UpAdvisory],
+) -> Vec<UpAdvisoryWarning> {
+    let mut warnings: Vec<UpAdvisoryWarning> = vec![];
+
+    for version in versions {
+        for advisory in advisories
+            .iter()
+            .filter(|advisory| advisory.matches(version))
+        {
+            let warning = UpAdvisoryWarning {
+                tool: version.tool.clone(),
+                version: version.version.clone(),
+                advisory: advisory.clone(),
+            };
+
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    warnings
+}
+
+/// A source of advisories to check the resolved tool versions against
+pub trait UpAdvisorySource {
+    /// The name of the source, used when reporting errors
+    fn name(&self) -> String;
+
+    /// Returns the advisories provided by the source
+    fn advisories(&self) -> Result<Vec<UpAdvisory>, String>;
+}
+
+/// A static list of advisories, as provided in the configuration
+#[derive(Debug, Clone, Default)]
+pub struct StaticAdvisorySource {
+    advisories: Vec<UpAdvisory>,
+}
+
+impl StaticAdvisorySource {
+    pub fn new(advisories: Vec<UpAdvisory>) -> Self {
+        Self { advisories }
+    }
+}
+
+impl UpAdvisorySource for StaticAdvisorySource {
+    fn name(&self) -> String {
+        "configuration".to_string()
+    }
+
+    fn advisories(&self) -> Result<Vec<UpAdvisory>, String> {
+        Ok(self.advisories.clone())
+    }
+}
+
+/// A feed of advisories fetched from a remote url; the feed is cached
+/// and only fetched again once its cached version expired, and a stale
+/// cached version is used if the feed cannot be fetched
+#[derive(Debug, Clone)]
+pub struct FeedAdvisorySource {
+    url: String,
+    expire: u64,
+}
+
+impl FeedAdvisorySource {
+    const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(url: &str, expire: u64) -> Self {
+        Self {
+            url: url.to_string(),
+            expire,
+        }
+    }
+
+    fn fetch(&self) -> Result<Vec<UpAdvisory>, String> {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(format!("omni {}", env!("CARGO_PKG_VERSION")))
+            .timeout(Self::FETCH_TIMEOUT)
+            .build()
+            .map_err(|err| format!("failed to create client: {err}"))?;
+
+        let response = client
+            .get(&self.url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| format!("failed to fetch feed: {err}"))?;
+
+        let contents = response
+            .text()
+            .map_err(|err| format!("failed to read feed: {err}"))?;
+
+        let config_value = ConfigValue::from_str(&contents)
+            .map_err(|err| format!("failed to parse feed: {err}"))?;
+
+        Ok(UpAdvisory::from_config_value_list(
+            &config_value,
+            &ConfigErrorHandler::noop(),
+        ))
+    }
+}
+
+impl UpAdvisorySource for FeedAdvisorySource {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    fn advisories(&self) -> Result<Vec<UpAdvisory>, String> {
+        let cache = UpAdvisoriesCache::get();
+        let cached = cache.get_feed(&self.url);
+
+        if let Some(cached) = &cached {
+            if !cached.is_stale(self.expire) {
+                return Ok(cached.advisories.clone());
+            }
+        }
+
+        match self.fetch() {
+            Ok(advisories) => {
+                // Failing to cache the feed should not prevent the check
+                let _ = cache.add_feed(&self.url, &advisories);
+                Ok(advisories)
+            }
+            Err(err) => match cached {
+                Some(cached) => Ok(cached.advisories),
+                None => Err(err),
+            },
+        }
+    }
+}
+
+/// Returns the advisory sources configured for the current context
+pub fn advisory_sources(advisories: &UpCommandAdvisoriesConfig) -> Vec<Box<dyn UpAdvisorySource>> {
+    let mut sources: Vec<Box<dyn UpAdvisorySource>> = vec![];
+
+    if !advisories.entries.is_empty() {
+        sources.push(Box::new(StaticAdvisorySource::new(
+            advisories.entries.clone(),
+        )));
+    }
+
+    let expire = global_config().cache.up_advisories.feeds_expire;
+    for feed in &advisories.feeds {
+        sources.push(Box::new(FeedAdvisorySource::new(feed, expire)));
+    }
+
+    sources
+}
+
+/// Collects the advisories from all the sources; the sources failing to
+/// provide their advisories are reported through the returned errors
+pub fn collect_advisories(sources: &[Box<dyn UpAdvisorySource>]) -> (Vec<UpAdvisory>, Vec<String>) {
+    let mut advisories = vec![];
+    let mut errors = vec![];
+
+    for source in sources {
+        match source.advisories() {
+            Ok(source_advisories) => advisories.extend(source_advisories),
+            Err(err) => errors.push(format!("advisory source {}: {}", source.name(), err)),
+        }
+    }
+
+    (advisories, errors)
+}
+
+#[cfg(test)]
+#[path = "advisories_test.rs"]
+mod tests;
@@ -0,0 +1,217 @@
+use super::*;
+
+use crate::internal::testutils::run_with_env;
+
+fn up_version(tool: &str, version: &str) -> UpVersion {
+    UpVersion {
+        tool: tool.to_string(),
+        plugin_name: tool.to_string(),
+        normalized_name: tool.to_string(),
+        backend: "".to_string(),
+        version: version.to_string(),
+        bin_path: "bin".to_string(),
+        dir: "".to_string(),
+        data_path: None,
+        env_vars: vec![],
+    }
+}
+
+fn advisories_from_str(yaml: &str) -> Vec<UpAdvisory> {
+    let config_value = ConfigValue::from_str(yaml).expect("failed to parse yaml");
+    let error_handler = ConfigErrorHandler::default().with_file("config.yaml");
+    let advisories = UpAdvisory::from_config_value_list(&config_value, &error_handler);
+    assert!(
+        error_handler.errors().is_empty(),
+        "errors: {:?}",
+        error_handler.errors()
+    );
+    advisories
+}
+
+const ADVISORIES: &str = r#"
+- tool: python
+  version: "<3.9"
+  kind: eol
+  message: upgrade to a supported version
+  url: https://devguide.python.org/versions/
+- tool: node
+  version: ">=20.0.0 <20.11.1"
+  kind: insecure
+  id: CVE-2024-21892
+"#;
+
+mod check_advisories {
+    use super::*;
+
+    #[test]
+    fn matching_version_warns() {
+        let advisories = advisories_from_str(ADVISORIES);
+        let versions = vec![up_version("python", "3.8.18")];
+
+        let warnings = check_advisories(&versions, &advisories);
+        assert_eq!(
+            warnings,
+            vec![UpAdvisoryWarning {
+                tool: "python".to_string(),
+                version: "3.8.18".to_string(),
+                advisory: advisories[0].clone(),
+            }]
+        );
+
+        let message = warnings[0].to_string();
+        assert!(message.contains("is end-of-life"), "message: {message}");
+        assert!(
+            message.contains(": upgrade to a supported version"),
+            "message: {message}"
+        );
+        assert!(
+            message.contains("https://devguide.python.org/versions/"),
+            "message: {message}"
+        );
+    }
+
+    #[test]
+    fn matching_version_with_advisory_id() {
+        let advisories = advisories_from_str(ADVISORIES);
+        let versions = vec![
+            up_version("python", "3.12.1"),
+            up_version("node", "20.10.0"),
+        ];
+
+        let warnings = check_advisories(&versions, &advisories);
+        assert_eq!(warnings.len(), 1, "warnings: {warnings:?}");
+        assert_eq!(warnings[0].tool, "node");
+        assert_eq!(warnings[0].version, "20.10.0");
+
+        let message = warnings[0].to_string();
+        assert!(
+            message.contains("is insecure (CVE-2024-21892)"),
+            "message: {message}"
+        );
+    }
+
+    #[test]
+    fn clean_versions_do_not_warn() {
+        let advisories = advisories_from_str(ADVISORIES);
+        let versions = vec![
+            up_version("python", "3.12.1"),
+            up_version("node", "20.11.1"),
+            up_version("go", "1.8.0"),
+        ];
+
+        assert!(check_advisories(&versions, &advisories).is_empty());
+    }
+
+    #[test]
+    fn matches_normalized_tool_name() {
+        let advisories = advisories_from_str("- tool: NodeJS\n  version: 18.x");
+        let mut version = up_version("node", "18.19.0");
+        version.normalized_name = "nodejs".to_string();
+
+        assert_eq!(check_advisories(&[version], &advisories).len(), 1);
+    }
+
+    #[test]
+    fn no_advisories() {
+        let versions = vec![up_version("python", "3.8.18")];
+
+        assert!(check_advisories(&versions, &[]).is_empty());
+    }
+}
+
+mod from_config_value {
+    use super::*;
+
+    #[test]
+    fn list_under_advisories_key() {
+        let advisories = advisories_from_str("advisories:\n  - tool: ruby\n    version: '<3.0'");
+
+        assert_eq!(
+            advisories,
+            vec![UpAdvisory {
+                tool: "ruby".to_string(),
+                version: "<3.0".to_string(),
+                kind: UpAdvisoryKind::Insecure,
+                id: None,
+                message: None,
+                url: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let config_value = ConfigValue::from_str(
+            r#"
+            - tool: python
+            - version: "<3.9"
+            - tool: python
+              version: "<3.9"
+              kind: unsupported
+            - "python"
+            "#,
+        )
+        .expect("failed to parse yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("config.yaml");
+        let advisories = UpAdvisory::from_config_value_list(&config_value, &error_handler);
+
+        // Only the entry with an invalid kind is kept, with the default kind
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].kind, UpAdvisoryKind::Insecure);
+
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 4, "errors: {errors:?}");
+    }
+}
+
+mod feed_advisory_source {
+    use super::*;
+
+    // Nothing listens on that port, so fetching the feed always fails
+    const UNREACHABLE_FEED: &str = "http://127.0.0.1:1/advisories.json";
+
+    #[test]
+    fn uses_fresh_cached_feed() {
+        run_with_env(&[], || {
+            let advisories = advisories_from_str(ADVISORIES);
+            UpAdvisoriesCache::get()
+                .add_feed(UNREACHABLE_FEED, &advisories)
+                .expect("failed to cache feed");
+
+            let source = FeedAdvisorySource::new(UNREACHABLE_FEED, 3600);
+            assert_eq!(source.advisories(), Ok(advisories));
+        });
+    }
+
+    #[test]
+    fn falls_back_to_stale_cached_feed() {
+        run_with_env(&[], || {
+            let advisories = advisories_from_str(ADVISORIES);
+            UpAdvisoriesCache::get()
+                .add_feed(UNREACHABLE_FEED, &advisories)
+                .expect("failed to cache feed");
+
+            // A zero expiration makes the cached feed stale right away
+            let source = FeedAdvisorySource::new(UNREACHABLE_FEED, 0);
+            assert_eq!(source.advisories(), Ok(advisories));
+        });
+    }
+
+    #[test]
+    fn unavailable_feed_is_reported() {
+        run_with_env(&[], || {
+            let sources: Vec<Box<dyn UpAdvisorySource>> = vec![
+                Box::new(FeedAdvisorySource::new(UNREACHABLE_FEED, 3600)),
+                Box::new(StaticAdvisorySource::new(advisories_from_str(ADVISORIES))),
+            ];
+
+            let (advisories, errors) = collect_advisories(&sources);
+            assert_eq!(advisories.len(), 2);
+            assert_eq!(errors.len(), 1, "errors: {errors:?}");
+            assert!(
+                errors[0].starts_with(&format!("advisory source {UNREACHABLE_FEED}: ")),
+                "errors: {errors:?}"
+            );
+        });
+    }
+}
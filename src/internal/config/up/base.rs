@@ -1,34 +1,60 @@
+use std::time::Duration;
+use std::time::Instant;
+
 use itertools::Itertools;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::cache::up_history::UpHistoryOperation;
+use crate::internal::cache::up_history::UpHistoryOutcome;
 use crate::internal::cache::utils::Empty;
+use crate::internal::cache::CacheBatch;
 use crate::internal::cache::UpEnvironmentsCache;
+use crate::internal::cache::UpHistoryCache;
+use crate::internal::config::config;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::UpCommandOperationConfig;
+use crate::internal::config::up::advisories::advisory_sources;
+use crate::internal::config::up::advisories::check_advisories;
+use crate::internal::config::up::advisories::collect_advisories;
 use crate::internal::config::up::utils::cleanup_path;
 use crate::internal::config::up::utils::reshim;
+use crate::internal::config::up::utils::ContentStore;
 use crate::internal::config::up::utils::ProgressHandler;
+use crate::internal::config::up::utils::UpEventStep;
 use crate::internal::config::up::utils::UpProgressHandler;
-use crate::internal::config::up::UpConfigCargoInstalls;
-use crate::internal::config::up::UpConfigGithubReleases;
-use crate::internal::config::up::UpConfigGoInstalls;
 use crate::internal::config::up::UpConfigHomebrew;
 use crate::internal::config::up::UpConfigMise;
 use crate::internal::config::up::UpConfigTool;
 use crate::internal::config::up::UpError;
 use crate::internal::config::up::UpOptions;
+use crate::internal::config::ConfigScope;
+use crate::internal::config::ConfigSource;
 use crate::internal::config::ConfigValue;
+use crate::internal::dynenv::env_backends;
 use crate::internal::dynenv::update_dynamic_env_for_command;
 use crate::internal::user_interface::colors::StringColor;
 use crate::internal::workdir;
+use crate::internal::workdir::hosting_orgs;
 use crate::omni_warning;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct UpConfig {
     pub steps: Vec<UpConfigTool>,
     pub errors: Vec<UpError>,
+    #[serde(default)]
+    pub inferred: Vec<UpConfigInferredRuntime>,
+}
+
+/// UpConfigInferredRuntime represents a runtime step that was not
+/// explicitly declared in the configuration, but that was added
+/// because other steps require it.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct UpConfigInferredRuntime {
+    pub runtime: String,
+    pub required_by: Vec<String>,
 }
 
 impl Empty for UpConfig {
@@ -134,9 +160,92 @@ impl UpConfig {
         Some(UpConfig {
             steps,
             errors: up_errors,
+            inferred: vec![],
         })
     }
 
+    /// Builds an up configuration from tool specifications in the
+    /// `<tool>@<version>` format (e.g. `node@20`), the version being
+    /// `latest` if not provided. Only the tools installed through mise
+    /// can be specified this way.
+    pub fn from_tool_specs(specs: &[String]) -> Result<Self, UpError> {
+        let mut steps = Vec::with_capacity(specs.len());
+
+        for spec in specs {
+            let (tool, version) = match spec.split_once('@') {
+                Some((tool, version)) => (tool, version),
+                None => (spec.as_str(), "latest"),
+            };
+            if tool.is_empty() || version.is_empty() {
+                return Err(UpError::Config(format!(
+                    "invalid tool specification '{spec}', expected <tool>@<version>"
+                )));
+            }
+
+            let version = ConfigValue::from_value(
+                ConfigSource::Null,
+                ConfigScope::Null,
+                serde_yaml::Value::String(version.to_string()),
+            );
+            let step =
+                UpConfigTool::from_config_value(tool, Some(&version), &ConfigErrorHandler::noop());
+
+            match step {
+                Some(
+                    step @ (UpConfigTool::Bash(_)
+                    | UpConfigTool::Go(_)
+                    | UpConfigTool::Mise(_)
+                    | UpConfigTool::Nodejs(_)
+                    | UpConfigTool::Python(_)),
+                ) => steps.push(step),
+                _ => {
+                    return Err(UpError::Config(format!(
+                        "invalid tool specification '{spec}', only tools installed through mise are supported"
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            steps,
+            errors: vec![],
+            inferred: vec![],
+        })
+    }
+
+    /// Adds the runtime steps required by other steps but that are not
+    /// explicitly declared in the configuration. Each inferred runtime
+    /// is added right before the first step that requires it.
+    pub fn with_inferred_runtimes(mut self) -> Self {
+        let mut steps = Vec::with_capacity(self.steps.len());
+        let mut inferred: Vec<UpConfigInferredRuntime> = vec![];
+
+        for step in self.steps.iter() {
+            for runtime in step.required_runtimes() {
+                if let Some(inferred) = inferred.iter_mut().find(|inf| inf.runtime == runtime) {
+                    inferred.required_by.push(step.to_name());
+                    continue;
+                }
+
+                if self.steps.iter().any(|step| step.provides_runtime(runtime)) {
+                    continue;
+                }
+
+                steps.push(UpConfigTool::Mise(UpConfigMise::new_any_version(runtime)));
+                inferred.push(UpConfigInferredRuntime {
+                    runtime: runtime.to_string(),
+                    required_by: vec![step.to_name()],
+                });
+            }
+
+            steps.push(step.clone());
+        }
+
+        self.steps = steps;
+        self.inferred = inferred;
+        self
+    }
+
     // pub fn steps(&self) -> Vec<UpConfigTool> {
     // self.steps.clone()
     // }
@@ -145,6 +254,17 @@ impl UpConfig {
         self.errors.clone()
     }
 
+    /// Returns the keys identifying the tool installations required by
+    /// the steps, when those are known before running the steps
+    pub fn install_keys(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter(|step| step.is_available())
+            .flat_map(|step| step.install_keys())
+            .unique()
+            .collect()
+    }
+
     pub fn has_steps(&self) -> bool {
         !self.steps.is_empty()
     }
@@ -153,6 +273,48 @@ impl UpConfig {
         !self.errors.is_empty()
     }
 
+    pub fn inferred(&self) -> Vec<UpConfigInferredRuntime> {
+        self.inferred.clone()
+    }
+
+    /// Returns this configuration restricted to what the given operations
+    /// policy allows, so that a configuration requesting a forbidden
+    /// operation is refused before anything gets installed; the
+    /// alternatives of `any` steps that are not allowed are dropped
+    pub fn allowed_by(&self, operations: &UpCommandOperationConfig) -> Result<Self, UpError> {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| step.allowed_by(operations))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(UpError::Config)?;
+
+        Ok(Self {
+            steps,
+            ..self.clone()
+        })
+    }
+
+    /// Returns this configuration restricted to what the organizations
+    /// hosting the current repository allow; those policies can only
+    /// come from the global configuration
+    pub fn allowed_by_orgs(&self) -> Result<Self, UpError> {
+        let mut up_config = self.clone();
+        for org in hosting_orgs(".") {
+            up_config = up_config
+                .allowed_by(&org.config.up_operations)
+                .map_err(|err| {
+                    UpError::Config(format!(
+                        "{}, per the policy of organization {}",
+                        err,
+                        org.config.handle.light_blue(),
+                    ))
+                })?;
+        }
+
+        Ok(up_config)
+    }
+
     pub fn clear_cache() {
         let workdir = workdir(".");
         if let Some(workdir_id) = workdir.id() {
@@ -163,18 +325,174 @@ impl UpConfig {
     }
 
     pub fn up(&self, options: &UpOptions, environment: &mut UpEnvironment) -> Result<(), UpError> {
-        // Get current directory
-        let current_dir = std::env::current_dir().expect("Failed to get current directory");
+        // Refuse the operations that the organizations hosting the
+        // repository do not allow, before anything gets installed
+        let up_config = match self.allowed_by_orgs() {
+            Ok(up_config) => up_config,
+            Err(err) => {
+                if let Some(summary) = options.summary {
+                    summary.run_completed(&Err(err.clone()), Duration::ZERO);
+                }
+                return Err(err);
+            }
+        };
 
-        // Filter the steps to only the available ones
-        let steps = self
+        let steps = up_config.selected_steps(options);
+        let mut operations = steps
+            .iter()
+            .map(|step| UpHistoryOperation::new(&step.to_name()))
+            .collect::<Vec<_>>();
+
+        // Record the start of the run before anything happens, so that
+        // the run stays in the history even if it gets interrupted
+        let started = Instant::now();
+        let history_id = if options.record_history {
+            Self::start_history(&operations)
+        } else {
+            None
+        };
+
+        if let Some(summary) = options.summary {
+            let previous_environment = Self::previous_environment();
+            summary.run_started(
+                &config(".").up_hash(),
+                previous_environment
+                    .as_ref()
+                    .map(|(environment_id, environment)| (environment_id.clone(), environment)),
+                &operations
+                    .iter()
+                    .map(|operation| operation.name.clone())
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        // Batch the cache writes of the run, which get committed after
+        // each group of operations, and at the end of the run when the
+        // batch closes
+        let _batch = CacheBatch::begin();
+
+        let result = up_config.up_steps(options, environment, &steps, &mut operations);
+        if let Some(events) = options.events {
+            events.run_completed(&result);
+        }
+        if let Some(summary) = options.summary {
+            summary.run_completed(&result, started.elapsed());
+        }
+
+        if let Some(history_id) = history_id {
+            let outcome = match result {
+                Ok(()) => UpHistoryOutcome::Success,
+                Err(_) => UpHistoryOutcome::Failed,
+            };
+            if let Err(err) =
+                UpHistoryCache::get().finish(history_id, started.elapsed(), outcome, &operations)
+            {
+                omni_warning!(format!("failed to update up history: {}", err));
+            }
+        }
+
+        result
+    }
+
+    /// Runs the steps without assigning the resulting environment to the
+    /// work directory or cleaning up, so that the environment can be used
+    /// for a single command without changing the persisted one; the
+    /// environment is still recorded so that the tools it uses are not
+    /// removed by the next cleanup
+    pub fn up_ephemeral(
+        &self,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+    ) -> Result<(), UpError> {
+        let up_config = self.allowed_by_orgs()?;
+        let steps = up_config.available_steps();
+        let num_steps = steps.len();
+        for (idx, step) in steps.iter().enumerate() {
+            run_step(
+                (idx + 1, num_steps),
+                &step.to_name(),
+                options,
+                |progress_handler| step.up(options, environment, progress_handler),
+            )?;
+        }
+
+        let (new_env, env_version_id) = UpEnvironmentsCache::get()
+            .register_ephemeral_environment(environment)
+            .map_err(|err| UpError::Cache(err.to_string()))?;
+        if new_env {
+            up_config.commit(options, &env_version_id)?;
+        }
+
+        Ok(())
+    }
+
+    fn start_history(operations: &[UpHistoryOperation]) -> Option<i64> {
+        let workdir_id = workdir(".").id()?;
+        let config_hash = config(".").up_hash();
+
+        match UpHistoryCache::get().start(&workdir_id, &config_hash, operations) {
+            Ok(history_id) => Some(history_id),
+            Err(err) => {
+                omni_warning!(format!("failed to update up history: {}", err));
+                None
+            }
+        }
+    }
+
+    /// Returns the environment currently assigned to the work directory,
+    /// along with its identifier
+    fn previous_environment() -> Option<(String, UpEnvironment)> {
+        let workdir_id = workdir(".").id()?;
+        let environment = UpEnvironmentsCache::get().get_env(&workdir_id)?;
+        let environment_id = format!("{}%{}", workdir_id, environment.hash_string());
+        Some((environment_id, environment))
+    }
+
+    /// Returns the steps that are available, with the bootstrap steps
+    /// first, since they need to run before any version resolution or
+    /// installation so that they can generate the files needed by the
+    /// other steps
+    pub fn available_steps(&self) -> Vec<&UpConfigTool> {
+        let (bootstrap_steps, other_steps): (Vec<_>, Vec<_>) = self
             .steps
             .iter()
             .filter(|step| step.is_available())
-            .collect::<Vec<&UpConfigTool>>();
+            .partition(|step| step.is_bootstrap());
+
+        bootstrap_steps.into_iter().chain(other_steps).collect()
+    }
+
+    /// Returns the available steps, restricted to the ones selected
+    /// through the options if any
+    fn selected_steps(&self, options: &UpOptions) -> Vec<&UpConfigTool> {
+        let steps = self.available_steps();
+        match options.only {
+            Some(only) => steps
+                .into_iter()
+                .enumerate()
+                .filter(|(idx, _)| only.contains(idx))
+                .map(|(_, step)| step)
+                .collect(),
+            None => steps,
+        }
+    }
+
+    fn up_steps(
+        &self,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        steps: &[&UpConfigTool],
+        operations: &mut [UpHistoryOperation],
+    ) -> Result<(), UpError> {
+        // Get current directory
+        let current_dir = std::env::current_dir().expect("Failed to get current directory");
+
+        // When only running a subset of the steps, the resources of the
+        // other steps are unknown, so we cannot safely clean up
+        let cleanup = options.only.is_none();
 
         // Go through the steps
-        let num_steps = steps.len() + 2;
+        let num_steps = steps.len() + if cleanup { 2 } else { 1 };
         for (idx, step) in steps.iter().enumerate() {
             // Make sure that we're in the right directory
             let step_dir = current_dir.join(step.dir().unwrap_or("".to_string()));
@@ -186,19 +504,124 @@ impl UpConfig {
                 )));
             }
 
-            let mut progress_handler = UpProgressHandler::new(Some((idx + 1, num_steps)));
-            if let Some(sync_file) = &options.lock_file {
-                progress_handler.set_sync_file(sync_file);
+            let known_versions = environment.versions.len();
+            if let Some(summary) = options.summary {
+                summary.operation_started(idx);
+            }
+
+            let result = run_step(
+                (idx + 1, num_steps),
+                &step.to_name(),
+                options,
+                |progress_handler| step.up(options, environment, progress_handler),
+            );
+
+            if let Some(summary) = options.summary {
+                summary.operation_finished(
+                    environment
+                        .versions
+                        .get(known_versions..)
+                        .unwrap_or_default(),
+                    &result,
+                );
+            }
+
+            operations[idx].outcome = match result {
+                Ok(()) => UpHistoryOutcome::Success,
+                Err(_) => UpHistoryOutcome::Failed,
+            };
+
+            // Commit the cache writes at the end of each group of
+            // operations, i.e. once the bootstrap operations are done
+            // and once all the operations are done
+            let group_done = steps
+                .get(idx + 1)
+                .is_none_or(|next| next.is_bootstrap() != step.is_bootstrap());
+            if group_done {
+                Self::checkpoint_cache();
             }
+            result?;
+        }
 
-            step.up(options, environment, &progress_handler)?
+        // Keep the contributions of the steps that were not run from
+        // the environment currently assigned to the work directory, and
+        // its configuration hash, since the configuration was not fully
+        // applied
+        if options.only.is_some() {
+            let previous_environment = Self::previous_environment();
+            if let Some((_, previous_environment)) = &previous_environment {
+                environment.merge_untouched(previous_environment);
+            }
+            environment.keep_config_hash(
+                previous_environment
+                    .as_ref()
+                    .map(|(_, previous_environment)| previous_environment),
+            );
         }
 
+        // Check the resolved versions against the known advisories
+        // before the environment gets assigned
+        self.check_advisories(options, environment)?;
+
         // Save and assign the environment
-        self.assign_environment(environment, Some((num_steps - 1, num_steps)), options)?;
+        run_step(
+            (steps.len() + 1, num_steps),
+            "environment",
+            options,
+            |progress_handler| self.assign_environment(environment, progress_handler, options),
+        )?;
+        Self::checkpoint_cache();
 
         // Cleanup anything that's not needed
-        self.cleanup(Some((num_steps, num_steps)), options)?;
+        if cleanup {
+            run_step(
+                (num_steps, num_steps),
+                "cleanup",
+                options,
+                |progress_handler| self.cleanup(progress_handler),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Commits the cache writes of the operations that ran so far, so
+    /// that they are kept even if the run gets interrupted afterwards
+    fn checkpoint_cache() {
+        if let Err(err) = CacheBatch::checkpoint() {
+            omni_warning!(format!("failed to update cache: {}", err));
+        }
+    }
+
+    /// Warns about the resolved tool versions matching any of the
+    /// configured advisories; this only fails in strict mode
+    fn check_advisories(
+        &self,
+        options: &UpOptions,
+        environment: &UpEnvironment,
+    ) -> Result<(), UpError> {
+        let sources = advisory_sources(&config(".").up_command.advisories);
+        if sources.is_empty() {
+            return Ok(());
+        }
+
+        let (advisories, errors) = collect_advisories(&sources);
+        for error in errors {
+            omni_warning!(error);
+        }
+
+        let warnings = check_advisories(&environment.versions, &advisories);
+        for warning in &warnings {
+            omni_warning!(warning.to_string());
+        }
+
+        if options.strict && !warnings.is_empty() {
+            return Err(UpError::Config(format!(
+                "{} tool version{} matched an advisory, which is not allowed in strict mode",
+                warnings.len(),
+                if warnings.len() > 1 { "s" } else { "" },
+            )));
+        }
 
         Ok(())
     }
@@ -206,13 +629,9 @@ impl UpConfig {
     fn assign_environment(
         &self,
         environment: &mut UpEnvironment,
-        progress: Option<(usize, usize)>,
+        progress_handler: &UpProgressHandler,
         options: &UpOptions,
     ) -> Result<(), UpError> {
-        let mut progress_handler = UpProgressHandler::new(progress);
-        if let Some(sync_file) = &options.lock_file {
-            progress_handler.set_sync_file(sync_file);
-        }
         progress_handler.init("apply environment:".light_blue());
 
         let workdir = workdir(".");
@@ -251,6 +670,10 @@ impl UpConfig {
             }
         }
 
+        if let Some(summary) = options.summary {
+            summary.environment_assigned(&assigned_environment);
+        }
+
         if newly_assigned {
             progress_handler.success_with_message("done".light_green());
         } else {
@@ -283,6 +706,14 @@ impl UpConfig {
     }
 
     pub fn down(&self, options: &UpOptions) -> Result<(), UpError> {
+        let result = self.down_steps(options);
+        if let Some(events) = options.events {
+            events.run_completed(&result);
+        }
+        result
+    }
+
+    fn down_steps(&self, options: &UpOptions) -> Result<(), UpError> {
         // Filter the steps to only the available ones
         let steps = self
             .steps
@@ -297,16 +728,21 @@ impl UpConfig {
             // the command can consider it right away
             update_dynamic_env_for_command(".");
 
-            let mut progress_handler = UpProgressHandler::new(Some((idx + 1, num_steps)));
-            if let Some(sync_file) = &options.lock_file {
-                progress_handler.set_sync_file(sync_file);
-            }
-
-            step.down(&progress_handler)?
+            run_step(
+                (idx + 1, num_steps),
+                &step.to_name(),
+                options,
+                |progress_handler| step.down(progress_handler),
+            )?;
         }
 
         // Cleanup anything that's not needed
-        self.cleanup(Some((num_steps, num_steps)), options)?;
+        run_step(
+            (num_steps, num_steps),
+            "cleanup",
+            options,
+            |progress_handler| self.cleanup(progress_handler),
+        )?;
 
         Ok(())
     }
@@ -315,43 +751,35 @@ impl UpConfig {
     /// method of every existing tool, so that it can cleanup dependencies from
     /// steps that do not exist anymore on top of previous versions of recently
     /// upgraded tools.
-    pub fn cleanup(
-        &self,
-        progress: Option<(usize, usize)>,
-        options: &UpOptions,
-    ) -> Result<(), UpError> {
-        let mut progress_handler = UpProgressHandler::new(progress);
-        if let Some(sync_file) = &options.lock_file {
-            progress_handler.set_sync_file(sync_file);
-        }
+    pub fn cleanup(&self, progress_handler: &UpProgressHandler) -> Result<(), UpError> {
         progress_handler.init("resources cleanup:".light_blue());
 
         let mut cleanups = vec![];
 
-        // Call cleanup on the different operation types
-        if let Some(cleanup) = UpConfigMise::cleanup(&progress_handler)? {
-            cleanups.push(cleanup);
-        }
-        if let Some(cleanup) = UpConfigHomebrew::cleanup(&progress_handler)? {
-            cleanups.push(cleanup);
-        }
-        if let Some(cleanup) = UpConfigGithubReleases::cleanup(&progress_handler)? {
-            cleanups.push(cleanup);
+        // Call cleanup on the different backends through which tools
+        // are installed, then on homebrew which is not one of them
+        for backend in env_backends() {
+            if let Some(cleanup) = backend.cleanup(progress_handler)? {
+                cleanups.push(cleanup);
+            }
         }
-        if let Some(cleanup) = UpConfigGoInstalls::cleanup(&progress_handler)? {
+        if let Some(cleanup) = UpConfigHomebrew::cleanup(progress_handler)? {
             cleanups.push(cleanup);
         }
-        if let Some(cleanup) = UpConfigCargoInstalls::cleanup(&progress_handler)? {
+
+        // Then remove the objects of the content store that are not
+        // used anymore by the installations that were just removed
+        if let Some(cleanup) = ContentStore::cleanup(progress_handler)? {
             cleanups.push(cleanup);
         }
 
         // Then cleanup the data path
-        if let Some(cleanup) = self.cleanup_data_path(&progress_handler)? {
+        if let Some(cleanup) = self.cleanup_data_path(progress_handler)? {
             cleanups.push(cleanup);
         }
 
         // Then regenerate the shims
-        if let Some(reshim) = reshim(&progress_handler)? {
+        if let Some(reshim) = reshim(progress_handler)? {
             cleanups.push(reshim);
         }
 
@@ -410,3 +838,43 @@ impl UpConfig {
         )))
     }
 }
+
+/// Runs a step of the operation with its own progress handler, emitting
+/// the events of the lifecycle of the step if events were requested
+fn run_step<F>(
+    progress: (usize, usize),
+    name: &str,
+    options: &UpOptions,
+    run: F,
+) -> Result<(), UpError>
+where
+    F: FnOnce(&UpProgressHandler) -> Result<(), UpError>,
+{
+    let mut progress_handler = UpProgressHandler::new(Some(progress));
+    if let Some(sync_file) = &options.lock_file {
+        progress_handler.set_sync_file(sync_file);
+    }
+
+    let events = options
+        .events
+        .map(|events| (events, UpEventStep::new(progress, name)));
+    if let Some((events, step)) = &events {
+        events.step_started(step);
+        progress_handler.set_events(events, step.clone());
+    }
+    if let Some(summary) = options.summary {
+        progress_handler.set_summary(summary);
+    }
+
+    let result = run(&progress_handler);
+
+    if let Some((events, step)) = &events {
+        events.step_finished(step, &result);
+    }
+
+    result
+}
+
+#[cfg(test)]
+#[path = "base_test.rs"]
+mod tests;
@@ -0,0 +1,957 @@
+use super::*;
+
+fn up_config_from_str(yaml: &str) -> UpConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("should parse yaml");
+    UpConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default())
+        .expect("should have an up configuration")
+}
+
+fn step_names(up_config: &UpConfig) -> Vec<String> {
+    up_config.steps.iter().map(|step| step.to_name()).collect()
+}
+
+struct DirGuard {
+    original: std::path::PathBuf,
+}
+
+impl DirGuard {
+    fn change_to(target: &std::path::Path) -> Self {
+        let original = std::env::current_dir().expect("failed to get current directory");
+        std::env::set_current_dir(target).expect("failed to change directory");
+        Self { original }
+    }
+}
+
+impl Drop for DirGuard {
+    fn drop(&mut self) {
+        std::env::set_current_dir(&self.original).expect("failed to restore working directory");
+    }
+}
+
+/// Runs `up` for the given configuration in a temporary work directory
+/// identified as `workdir_id`; `with_options` receives the temporary home
+/// directory and a function running `up` with the options it built, and
+/// returns what the test needs to check
+fn up_in_workdir<T>(
+    yaml: &str,
+    envs: &[(String, Option<String>)],
+    workdir_id: &str,
+    with_options: impl FnOnce(&std::path::Path, &dyn Fn(&UpOptions) -> Result<(), UpError>) -> T,
+) -> T {
+    let mut output = None;
+
+    crate::internal::testutils::run_with_env(envs, || {
+        let home = std::path::PathBuf::from(std::env::var("HOME").expect("HOME should be set"));
+        let workdir = home.join("workdir");
+        std::fs::create_dir_all(&workdir).expect("create workdir");
+        crate::internal::env::init_workdir(workdir.to_string_lossy(), Some(workdir_id))
+            .expect("init workdir");
+        let _guard = DirGuard::change_to(&workdir);
+
+        let up_config = up_config_from_str(yaml);
+        let up = |options: &UpOptions| {
+            let mut environment = UpEnvironment::new().init();
+            up_config.up(options, &mut environment)
+        };
+
+        output = Some(with_options(&home, &up));
+    });
+
+    output.expect("should have run up")
+}
+
+mod with_inferred_runtimes {
+    use super::*;
+
+    #[test]
+    fn adds_missing_runtime_before_first_step_requiring_it() {
+        let up_config = up_config_from_str("[go, bundler]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["go", "ruby", "bundler"]);
+        assert_eq!(
+            up_config.inferred(),
+            vec![UpConfigInferredRuntime {
+                runtime: "ruby".to_string(),
+                required_by: vec!["bundler".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn inferred_runtime_is_any_version() {
+        let up_config = up_config_from_str("[bundler]").with_inferred_runtimes();
+
+        match up_config.steps.first() {
+            Some(UpConfigTool::Mise(mise)) => {
+                assert_eq!(mise.name(), "ruby");
+                assert_eq!(mise.version, "*");
+            }
+            other => panic!("expected an inferred mise step, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn adds_runtime_only_once() {
+        let up_config =
+            up_config_from_str("[bundler, {bundler: alt/Gemfile}]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["ruby", "bundler", "bundler"]);
+        assert_eq!(
+            up_config.inferred(),
+            vec![UpConfigInferredRuntime {
+                runtime: "ruby".to_string(),
+                required_by: vec!["bundler".to_string(), "bundler".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn does_not_add_declared_runtime() {
+        let up_config = up_config_from_str("[ruby, bundler]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["ruby", "bundler"]);
+        assert!(up_config.inferred().is_empty());
+    }
+
+    #[test]
+    fn considers_runtime_declared_in_nested_steps() {
+        let up_config = up_config_from_str("[{and: [ruby, go]}, bundler]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["and", "bundler"]);
+        assert!(up_config.inferred().is_empty());
+    }
+
+    #[test]
+    fn adds_runtime_required_by_nested_steps() {
+        let up_config = up_config_from_str("[{and: [go, bundler]}]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["ruby", "and"]);
+        assert_eq!(
+            up_config.inferred(),
+            vec![UpConfigInferredRuntime {
+                runtime: "ruby".to_string(),
+                required_by: vec!["and".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn does_nothing_without_required_runtimes() {
+        let up_config = up_config_from_str("[go, nodejs]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["go", "nodejs"]);
+        assert!(up_config.inferred().is_empty());
+    }
+
+    #[test]
+    fn does_nothing_for_operations_providing_their_runtime() {
+        let up_config = up_config_from_str("[{cargo-install: ripgrep}]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["cargo-install"]);
+        assert!(up_config.inferred().is_empty());
+    }
+
+    #[test]
+    fn adds_go_for_go_install() {
+        let up_config =
+            up_config_from_str("[{go-install: golang.org/x/tools/gopls}]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["go", "go-install"]);
+        assert_eq!(
+            up_config.inferred(),
+            vec![UpConfigInferredRuntime {
+                runtime: "go".to_string(),
+                required_by: vec!["go-install".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn adds_python_for_pipx() {
+        let up_config = up_config_from_str("[{pipx: black}]").with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["python", "pipx"]);
+        assert_eq!(
+            up_config.inferred(),
+            vec![UpConfigInferredRuntime {
+                runtime: "python".to_string(),
+                required_by: vec!["pipx".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn does_not_add_python_for_pipx_with_interpreter() {
+        let up_config = up_config_from_str("[{pipx: {package: black, python: python3.12}}]")
+            .with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["pipx"]);
+        assert!(up_config.inferred().is_empty());
+    }
+
+    #[test]
+    fn does_not_add_go_declared_for_go_install() {
+        let up_config = up_config_from_str("[go, {go-install: golang.org/x/tools/gopls}]")
+            .with_inferred_runtimes();
+
+        assert_eq!(step_names(&up_config), vec!["go", "go-install"]);
+        assert!(up_config.inferred().is_empty());
+    }
+}
+
+mod up_events {
+    use super::*;
+
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::internal::config::up::utils::UpEventWriter;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs `up` for the given configuration in a temporary work directory,
+    /// and returns the result along with the events that were emitted
+    fn up_with_events(yaml: &str) -> (Result<(), UpError>, Vec<serde_json::Value>) {
+        up_with_events_and_env(yaml, &[])
+    }
+
+    fn up_with_events_and_env(
+        yaml: &str,
+        envs: &[(String, Option<String>)],
+    ) -> (Result<(), UpError>, Vec<serde_json::Value>) {
+        up_with_events_and_selection(yaml, envs, None)
+    }
+
+    fn up_with_events_and_selection(
+        yaml: &str,
+        envs: &[(String, Option<String>)],
+        only: Option<&std::collections::BTreeSet<usize>>,
+    ) -> (Result<(), UpError>, Vec<serde_json::Value>) {
+        up_in_workdir(yaml, envs, "up-events", |_home, up| {
+            let buffer = SharedBuffer::default();
+            let events = UpEventWriter::new(Box::new(buffer.clone()));
+            let mut options = UpOptions::new().events(&events);
+            if let Some(only) = only {
+                options = options.only(only);
+            }
+
+            let result = up(&options);
+
+            let contents = String::from_utf8(buffer.0.lock().unwrap().clone())
+                .expect("events should be valid utf-8");
+            let events = contents
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("each line should be json"))
+                .collect();
+
+            (result, events)
+        })
+    }
+
+    fn summarize(events: &[serde_json::Value]) -> Vec<String> {
+        events
+            .iter()
+            .filter(|event| event["event"] != "step_progress")
+            .map(|event| match event["event"].as_str() {
+                Some("run_completed") => format!("run_completed:{}", event["status"]),
+                Some(kind) => format!(
+                    "{kind}:{}/{}:{}",
+                    event["step"], event["total"], event["name"]
+                ),
+                None => panic!("event without type: {event}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn emits_step_lifecycle_and_run_completion() {
+        let (result, events) = up_with_events(
+            "[{custom: {meet: 'true', name: first}}, {custom: {meet: 'true', name: second}}]",
+        );
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+        assert_eq!(
+            summarize(&events),
+            vec![
+                r#"step_started:1/4:"custom""#,
+                r#"step_completed:1/4:"custom""#,
+                r#"step_started:2/4:"custom""#,
+                r#"step_completed:2/4:"custom""#,
+                r#"step_started:3/4:"environment""#,
+                r#"step_completed:3/4:"environment""#,
+                r#"step_started:4/4:"cleanup""#,
+                r#"step_completed:4/4:"cleanup""#,
+                r#"run_completed:"success""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_only_selected_steps_without_cleanup() {
+        let only = std::collections::BTreeSet::from([1]);
+        let (result, events) = up_with_events_and_selection(
+            "[{custom: {meet: 'exit 1', name: skipped}}, {custom: {meet: 'true', name: selected}}]",
+            &[],
+            Some(&only),
+        );
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+        assert_eq!(
+            summarize(&events),
+            vec![
+                r#"step_started:1/2:"custom""#,
+                r#"step_completed:1/2:"custom""#,
+                r#"step_started:2/2:"environment""#,
+                r#"step_completed:2/2:"environment""#,
+                r#"run_completed:"success""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn emits_progress_events_for_the_current_step() {
+        let (_, events) = up_with_events("[{custom: {meet: 'true', name: first}}]");
+
+        let progress = events
+            .iter()
+            .filter(|event| event["event"] == "step_progress")
+            .collect::<Vec<_>>();
+        assert!(!progress.is_empty(), "expected progress events: {events:?}");
+        assert!(progress
+            .iter()
+            .all(|event| event["step"].is_u64() && event["message"].is_string()));
+    }
+
+    #[test]
+    fn emits_step_failure_and_stops_the_run() {
+        let (result, events) = up_with_events(
+            "[{custom: {meet: 'exit 1', name: failing}}, {custom: {meet: 'true', name: never}}]",
+        );
+
+        assert!(result.is_err(), "up should fail");
+        assert_eq!(
+            summarize(&events),
+            vec![
+                r#"step_started:1/4:"custom""#,
+                r#"step_failed:1/4:"custom""#,
+                r#"run_completed:"failure""#,
+            ]
+        );
+
+        let failed = events
+            .iter()
+            .find(|event| event["event"] == "step_failed")
+            .expect("should have a step_failed event");
+        assert!(failed["error"].is_string());
+
+        let completed = events.last().expect("should have events");
+        assert!(completed["error"].is_string());
+    }
+
+    #[test]
+    fn runs_bootstrap_before_other_steps() {
+        let (result, events) = up_with_events(concat!(
+            "[{custom: {meet: 'test -f generated.txt', name: consumer}},",
+            " {bootstrap: 'echo 1.2.3 > generated.txt'}]",
+        ));
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+        assert_eq!(
+            summarize(&events),
+            vec![
+                r#"step_started:1/4:"bootstrap""#,
+                r#"step_completed:1/4:"bootstrap""#,
+                r#"step_started:2/4:"custom""#,
+                r#"step_completed:2/4:"custom""#,
+                r#"step_started:3/4:"environment""#,
+                r#"step_completed:3/4:"environment""#,
+                r#"step_started:4/4:"cleanup""#,
+                r#"step_completed:4/4:"cleanup""#,
+                r#"run_completed:"success""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn failing_bootstrap_aborts_the_run() {
+        let (result, events) =
+            up_with_events("[{custom: {meet: 'true', name: never}}, {bootstrap: 'exit 1'}]");
+
+        assert!(result.is_err(), "up should fail");
+        assert_eq!(
+            summarize(&events),
+            vec![
+                r#"step_started:1/4:"bootstrap""#,
+                r#"step_failed:1/4:"bootstrap""#,
+                r#"run_completed:"failure""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn bootstrap_runs_in_a_minimal_environment() {
+        let (result, _) = up_with_events_and_env(
+            "[{bootstrap: 'test -z \"${OMNI_TEST_BOOTSTRAP_LEAK:-}\"'}]",
+            &[(
+                "OMNI_TEST_BOOTSTRAP_LEAK".to_string(),
+                Some("leaked".to_string()),
+            )],
+        );
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+    }
+}
+
+mod up_history {
+    use super::*;
+
+    use crate::internal::cache::up_history::UpHistoryEntry;
+    use crate::internal::cache::up_history::UpHistoryOperation;
+    use crate::internal::cache::up_history::UpHistoryOutcome;
+    use crate::internal::cache::UpHistoryCache;
+
+    /// Runs `up` for the given configuration in a temporary work directory,
+    /// and returns the result along with the recorded history
+    fn up_with_history(
+        yaml: &str,
+        record_history: bool,
+    ) -> (Result<(), UpError>, Vec<UpHistoryEntry>) {
+        up_in_workdir(yaml, &[], "up-history", |_home, up| {
+            let result = up(&UpOptions::new().record_history(record_history));
+
+            let workdir_id = crate::internal::workdir(".")
+                .id()
+                .expect("should have a workdir id");
+            (result, UpHistoryCache::get().list(&workdir_id))
+        })
+    }
+
+    fn operation(name: &str, outcome: UpHistoryOutcome) -> UpHistoryOperation {
+        UpHistoryOperation {
+            name: name.to_string(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn records_successful_run() {
+        let (result, history) = up_with_history(
+            "[{custom: {meet: 'true', name: first}}, {bootstrap: 'true'}]",
+            true,
+        );
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+        assert_eq!(history.len(), 1, "unexpected history: {history:?}");
+
+        let entry = &history[0];
+        assert_eq!(entry.outcome, UpHistoryOutcome::Success);
+        assert!(entry.duration.is_some());
+        assert!(!entry.config_hash.is_empty());
+        assert_eq!(
+            entry.operations,
+            vec![
+                operation("bootstrap", UpHistoryOutcome::Success),
+                operation("custom", UpHistoryOutcome::Success),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_failed_run() {
+        let (result, history) = up_with_history(
+            concat!(
+                "[{custom: {meet: 'true', name: first}},",
+                " {custom: {meet: 'exit 1', name: failing}},",
+                " {custom: {meet: 'true', name: never}}]",
+            ),
+            true,
+        );
+
+        assert!(result.is_err(), "up should fail");
+        assert_eq!(history.len(), 1, "unexpected history: {history:?}");
+
+        let entry = &history[0];
+        assert_eq!(entry.outcome, UpHistoryOutcome::Failed);
+        assert_eq!(
+            entry.operations,
+            vec![
+                operation("custom", UpHistoryOutcome::Success),
+                operation("custom", UpHistoryOutcome::Failed),
+                operation("custom", UpHistoryOutcome::Skipped),
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_record_unless_requested() {
+        let (result, history) = up_with_history("[{custom: {meet: 'true'}}]", false);
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+        assert!(history.is_empty(), "unexpected history: {history:?}");
+    }
+}
+
+mod up_summary {
+    use super::*;
+
+    use crate::internal::config::up::utils::UpSummaryWriter;
+
+    /// Runs `up` for the given configuration in a temporary work directory,
+    /// and returns the result along with the summary that was written
+    fn up_with_summary(yaml: &str) -> (Result<(), UpError>, serde_json::Value) {
+        up_in_workdir(yaml, &[], "up-summary", |home, up| {
+            let path = home.join("summary.json");
+            let summary = UpSummaryWriter::new(&path);
+            let result = up(&UpOptions::new().summary(&summary));
+
+            let contents = std::fs::read_to_string(&path).expect("summary should be written");
+            let summary = serde_json::from_str(&contents).expect("summary should be valid json");
+            (result, summary)
+        })
+    }
+
+    fn outcomes(summary: &serde_json::Value) -> Vec<String> {
+        summary["operations"]
+            .as_array()
+            .expect("operations should be an array")
+            .iter()
+            .map(|operation| format!("{}:{}", operation["name"], operation["outcome"]))
+            .collect()
+    }
+
+    #[test]
+    fn writes_summary_of_successful_run() {
+        let (result, summary) = up_with_summary("[{custom: {meet: 'true', name: first}}]");
+
+        assert!(result.is_ok(), "up should succeed: {result:?}");
+        assert_eq!(summary["status"], "success");
+        assert!(summary["config_hash"].is_string());
+        assert_eq!(outcomes(&summary), vec![r#""custom":"cached""#]);
+        assert_eq!(
+            summary["environment"]["previous_id"],
+            serde_json::Value::Null
+        );
+        assert!(summary["environment"]["current_id"].is_string());
+        assert_eq!(summary["environment"]["changed"], true);
+    }
+
+    #[test]
+    fn writes_partial_summary_on_failure() {
+        let (result, summary) = up_with_summary(concat!(
+            "[{custom: {meet: 'true', name: first}},",
+            " {custom: {meet: 'exit 1', name: failing}},",
+            " {custom: {meet: 'true', name: never}}]",
+        ));
+
+        assert!(result.is_err(), "up should fail");
+        assert_eq!(summary["status"], "failure");
+        assert!(summary["error"].is_string());
+        assert_eq!(
+            outcomes(&summary),
+            vec![
+                r#""custom":"cached""#,
+                r#""custom":"failed""#,
+                r#""custom":"skipped""#,
+            ]
+        );
+        assert_eq!(
+            summary["environment"]["current_id"],
+            serde_json::Value::Null
+        );
+        assert_eq!(summary["environment"]["changed"], false);
+    }
+}
+
+mod bootstrap {
+    use super::*;
+
+    use crate::internal::config::up::UpConfigBootstrap;
+
+    #[test]
+    fn parses_string_and_table_forms() {
+        let up_config = up_config_from_str(
+            "[{bootstrap: ./configure}, {bootstrap: {run: make gen, name: codegen, dir: sub}}]",
+        );
+
+        let bootstraps = up_config
+            .steps
+            .iter()
+            .map(|step| match step {
+                UpConfigTool::Bootstrap(config) => config.clone(),
+                other => panic!("expected a bootstrap step, got {other:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            bootstraps,
+            vec![
+                UpConfigBootstrap {
+                    run: "./configure".to_string(),
+                    name: None,
+                    dir: None,
+                },
+                UpConfigBootstrap {
+                    run: "make gen".to_string(),
+                    name: Some("codegen".to_string()),
+                    dir: Some("sub".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejected_in_nested_steps() {
+        let config_value =
+            ConfigValue::from_str("[{and: [{bootstrap: ./configure}, go]}]").expect("yaml");
+        let error_handler = ConfigErrorHandler::default().with_file("test.yaml");
+        let up_config = UpConfig::from_config_value(Some(config_value), &error_handler)
+            .expect("should have an up configuration");
+
+        match up_config.steps.as_slice() {
+            [UpConfigTool::And(steps)] => assert_eq!(
+                steps.iter().map(|step| step.to_name()).collect::<Vec<_>>(),
+                vec!["go"]
+            ),
+            other => panic!("expected a single and step, got {other:?}"),
+        }
+
+        let errors = error_handler.errors();
+        assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+        assert_eq!(
+            errors[0].kind(),
+            &ConfigErrorKind::UnsupportedValueInContext
+        );
+    }
+}
+
+mod from_tool_specs {
+    use super::*;
+
+    fn specs(specs: &[&str]) -> Result<UpConfig, UpError> {
+        UpConfig::from_tool_specs(&specs.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    fn mise_version(step: &UpConfigTool) -> (Option<String>, String) {
+        match step {
+            UpConfigTool::Mise(config) => (config.backend.clone(), config.version.clone()),
+            UpConfigTool::Nodejs(config) => (
+                config.backend.backend.clone(),
+                config.backend.version.clone(),
+            ),
+            UpConfigTool::Python(config) => (
+                config.backend.backend.clone(),
+                config.backend.version.clone(),
+            ),
+            _ => panic!("unexpected step: {step:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_tools_and_versions() {
+        let up_config =
+            specs(&["node@20", "python", "aqua:cli/cli@2.40"]).expect("specs should be valid");
+
+        assert_eq!(step_names(&up_config), vec!["nodejs", "python", "cli/cli"]);
+        assert_eq!(mise_version(&up_config.steps[0]), (None, "20".to_string()));
+        assert_eq!(
+            mise_version(&up_config.steps[1]),
+            (None, "latest".to_string())
+        );
+        assert_eq!(
+            mise_version(&up_config.steps[2]),
+            (Some("aqua".to_string()), "2.40".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_versions_as_strings() {
+        let up_config = specs(&["python@3.10"]).expect("spec should be valid");
+        assert_eq!(
+            mise_version(&up_config.steps[0]),
+            (None, "3.10".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        for spec in ["@20", "node@", "custom@1", "and@1", "bootstrap"] {
+            assert!(
+                matches!(specs(&[spec]), Err(UpError::Config(_))),
+                "spec should be rejected: {spec}"
+            );
+        }
+    }
+}
+
+mod up_ephemeral {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::internal::env::init_workdir;
+    use crate::internal::testutils::run_with_env;
+
+    #[test]
+    fn does_not_assign_environment_to_workdir() {
+        run_with_env(&[], || {
+            let home = PathBuf::from(std::env::var("HOME").expect("HOME should be set"));
+            let workdir = home.join("workdir");
+            std::fs::create_dir_all(&workdir).expect("create workdir");
+            init_workdir(workdir.to_string_lossy(), Some("up-ephemeral")).expect("init workdir");
+            let _guard = DirGuard::change_to(&workdir);
+
+            let up_config = up_config_from_str("[{custom: {meet: 'true'}}]");
+            let mut environment = UpEnvironment::new();
+            let result = up_config.up_ephemeral(&UpOptions::new(), &mut environment);
+            assert!(result.is_ok(), "up should succeed: {result:?}");
+
+            let workdir_id = crate::internal::workdir(".")
+                .id()
+                .expect("should have a workdir id");
+            assert!(UpEnvironmentsCache::get().get_env(&workdir_id).is_none());
+
+            // Whereas a regular run assigns the environment
+            let up_config = up_config_from_str("[{custom: {meet: 'true'}}]");
+            let mut environment = UpEnvironment::new().init();
+            up_config
+                .up(&UpOptions::new(), &mut environment)
+                .expect("up should succeed");
+            assert!(UpEnvironmentsCache::get().get_env(&workdir_id).is_some());
+        });
+    }
+
+    #[test]
+    fn records_the_environment() {
+        run_with_env(&[], || {
+            let home = PathBuf::from(std::env::var("HOME").expect("HOME should be set"));
+            let workdir = home.join("workdir");
+            std::fs::create_dir_all(&workdir).expect("create workdir");
+            init_workdir(workdir.to_string_lossy(), Some("up-ephemeral")).expect("init workdir");
+            let _guard = DirGuard::change_to(&workdir);
+
+            let up_config = up_config_from_str("[{custom: {meet: 'true'}}]");
+            let mut environment = UpEnvironment::new();
+            up_config
+                .up_ephemeral(&UpOptions::new(), &mut environment)
+                .expect("up should succeed");
+
+            let prefix = format!("{}%", UpEnvironmentsCache::EPHEMERAL_ENV_PREFIX);
+            let environment_ids = UpEnvironmentsCache::get().environment_ids();
+            assert_eq!(environment_ids.len(), 1);
+            assert!(environment_ids.iter().all(|id| id.starts_with(&prefix)));
+        });
+    }
+}
+
+mod allowed_by {
+    use super::*;
+
+    use crate::internal::config::OrgConfig;
+
+    fn org_operations(yaml: &str) -> UpCommandOperationConfig {
+        let config_value = ConfigValue::from_str(yaml).expect("should parse yaml");
+        OrgConfig::from_config_value(&config_value, &ConfigErrorHandler::noop()).up_operations
+    }
+
+    #[test]
+    fn denied_operation_is_refused() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  allowed: ['!cargo-install']\n",
+        ));
+        let up_config = up_config_from_str("[go, {cargo-install: ripgrep}]");
+
+        assert_eq!(
+            up_config.allowed_by(&operations).err(),
+            Some(UpError::Config(
+                "cargo-install operation is not allowed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn allowed_operations_proceed() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  allowed: [mise, cargo-install]\n",
+            "  cargo-install:\n",
+            "    crates: [ripgrep]\n",
+        ));
+        let up_config = up_config_from_str("[go, node, {cargo-install: ripgrep}]");
+
+        assert!(up_config.allowed_by(&operations).is_ok());
+    }
+
+    #[test]
+    fn operation_not_in_allowlist_is_refused() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  allowed: [mise]\n",
+        ));
+        let up_config = up_config_from_str("[go, {custom: {meet: 'true'}}]");
+
+        assert_eq!(
+            up_config.allowed_by(&operations).err(),
+            Some(UpError::Config(
+                "custom operation is not allowed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn denied_source_is_refused() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  cargo-install:\n",
+            "    crates: ['!untrusted-*', '*']\n",
+            "  github-release:\n",
+            "    repositories: [org/*]\n",
+        ));
+
+        assert_eq!(
+            up_config_from_str("[{cargo-install: [ripgrep, untrusted-tool]}]")
+                .allowed_by(&operations)
+                .err(),
+            Some(UpError::Config(
+                "crate untrusted-tool not allowed".to_string()
+            ))
+        );
+        assert_eq!(
+            up_config_from_str("[{github-release: other/tool}]")
+                .allowed_by(&operations)
+                .err(),
+            Some(UpError::Config(
+                "repository other/tool not allowed".to_string()
+            ))
+        );
+        assert!(up_config_from_str("[{github-release: org/tool}]")
+            .allowed_by(&operations)
+            .is_ok());
+    }
+
+    #[test]
+    fn nested_steps_are_checked() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  allowed: ['!go-install']\n",
+        ));
+        let up_config =
+            up_config_from_str("[{and: [{go-install: github.com/owner/tool}, {homebrew: tool}]}]");
+
+        assert_eq!(
+            up_config.allowed_by(&operations).err(),
+            Some(UpError::Config(
+                "go-install operation is not allowed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn disallowed_alternatives_are_dropped() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  allowed: ['!go-install']\n",
+        ));
+        let up_config =
+            up_config_from_str("[{or: [{go-install: github.com/owner/tool}, {homebrew: tool}]}]");
+
+        let allowed = up_config
+            .allowed_by(&operations)
+            .expect("should keep the allowed alternative");
+        match &allowed.steps[..] {
+            [UpConfigTool::Or(configs)] => {
+                assert_eq!(configs.len(), 1);
+                assert!(matches!(configs[0], UpConfigTool::Homebrew(_)));
+            }
+            steps => panic!("unexpected steps: {:?}", steps),
+        }
+    }
+
+    #[test]
+    fn no_allowed_alternative_is_refused() {
+        let operations = org_operations(concat!(
+            "handle: github.com/org\n",
+            "up_operations:\n",
+            "  allowed: ['!go-install', '!homebrew']\n",
+        ));
+        let up_config =
+            up_config_from_str("[{any: [{go-install: github.com/owner/tool}, {homebrew: tool}]}]");
+
+        assert_eq!(
+            up_config.allowed_by(&operations).err(),
+            Some(UpError::Config(
+                "go-install operation is not allowed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn no_policy_allows_everything() {
+        let operations = org_operations("handle: github.com/org\n");
+        let up_config = up_config_from_str("[go, {cargo-install: ripgrep}, {homebrew: tool}]");
+
+        assert!(up_config.allowed_by(&operations).is_ok());
+    }
+}
+
+mod install_keys {
+    use super::*;
+
+    #[test]
+    fn lists_requested_tool_versions() {
+        let up_config = up_config_from_str(
+            r#"
+            - python: 3.11.4
+            - go: 1.21.0
+            - terraform:
+                version: 1.5.0
+                backend: aqua
+            - and:
+              - node: 20.10.0
+              - python: 3.11.4
+            "#,
+        );
+
+        assert_eq!(
+            up_config.install_keys(),
+            vec![
+                "mise:python@3.11.4",
+                "mise:go@1.21.0",
+                "mise:aqua:terraform@1.5.0",
+                "mise:node@20.10.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_versions_only_known_when_upping() {
+        let up_config = up_config_from_str(
+            r#"
+            - python: auto
+            - any:
+              - go: 1.21.0
+              - node: 20.10.0
+            "#,
+        );
+
+        assert!(up_config.install_keys().is_empty());
+    }
+}
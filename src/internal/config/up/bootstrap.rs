@@ -0,0 +1,154 @@
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::process::Command as TokioCommand;
+
+use crate::internal::config::global_config;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::up::utils::run_progress;
+use crate::internal::config::up::utils::ProgressHandler;
+use crate::internal::config::up::utils::RunConfig;
+use crate::internal::config::up::utils::UpProgressHandler;
+use crate::internal::config::up::UpError;
+use crate::internal::config::up::UpOptions;
+use crate::internal::config::ConfigValue;
+use crate::internal::user_interface::StringColor;
+
+/// The environment variables that are passed through to the bootstrap
+/// command; any other variable, including the dynamic environment of
+/// the work directory, is not available to the command.
+const BOOTSTRAP_ENV_PASSTHROUGH: &[&str] = &[
+    "HOME", "LANG", "LC_ALL", "LOGNAME", "PATH", "SHELL", "TERM", "TMPDIR", "USER",
+];
+
+/// UpConfigBootstrap represents a command that needs to be run before
+/// any other operation of `omni up`, so that it can generate the files
+/// that the other operations depend on (e.g. a generated manifest from
+/// which the version of a tool is read). The command runs in a minimal
+/// environment, and its failure aborts the whole `omni up` run.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UpConfigBootstrap {
+    pub run: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+impl Default for UpConfigBootstrap {
+    fn default() -> Self {
+        Self {
+            run: Self::DEFAULT_RUN.to_string(),
+            name: None,
+            dir: None,
+        }
+    }
+}
+
+impl UpConfigBootstrap {
+    const DEFAULT_RUN: &str = "true";
+
+    pub fn from_config_value(
+        config_value: Option<&ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => {
+                error_handler.error(ConfigErrorKind::EmptyKey);
+                return Self::default();
+            }
+        };
+
+        if let Some(run) = config_value.as_str() {
+            return Self {
+                run,
+                ..Self::default()
+            };
+        }
+
+        if !config_value.is_table() {
+            error_handler
+                .with_expected(vec!["string", "table"])
+                .with_actual(config_value)
+                .error(ConfigErrorKind::InvalidValueType);
+
+            return Self::default();
+        }
+
+        let run = config_value
+            .get_as_str_or_none("run", &error_handler.with_key("run"))
+            .unwrap_or_else(|| {
+                error_handler
+                    .with_key("run")
+                    .error(ConfigErrorKind::MissingKey);
+
+                Self::DEFAULT_RUN.to_string()
+            });
+        let name = config_value.get_as_str_or_none("name", &error_handler.with_key("name"));
+        let dir = config_value.get_as_str_or_none("dir", &error_handler.with_key("dir"));
+
+        Self { run, name, dir }
+    }
+
+    fn name(&self) -> String {
+        match &self.name {
+            Some(name) => name.to_string(),
+            None => "bootstrap".to_string(),
+        }
+    }
+
+    pub fn dir(&self) -> Option<String> {
+        self.dir.as_ref().map(|dir| dir.to_string())
+    }
+
+    pub fn up(
+        &self,
+        _options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<(), UpError> {
+        let name = self.name();
+
+        progress_handler.init(format!("{name}:").light_blue());
+
+        if !global_config()
+            .up_command
+            .operations
+            .is_operation_allowed("bootstrap")
+        {
+            let errmsg = "bootstrap operation is not allowed".to_string();
+            progress_handler.error_with_message(errmsg.clone());
+            return Err(UpError::Config(errmsg));
+        }
+
+        progress_handler.progress("running bootstrap command".to_string());
+
+        let mut command = TokioCommand::new("bash");
+        command.arg("-c");
+        command.arg(&self.run);
+        command.env_clear();
+        for key in BOOTSTRAP_ENV_PASSTHROUGH {
+            if let Some(value) = std::env::var_os(key) {
+                command.env(key, value);
+            }
+        }
+        command.stdout(std::process::Stdio::piped());
+        command.stderr(std::process::Stdio::piped());
+
+        if let Err(err) = run_progress(&mut command, Some(progress_handler), RunConfig::default()) {
+            progress_handler.error_with_message(format!("{err}").light_red());
+            return Err(UpError::StepFailed(name, progress_handler.step()));
+        }
+
+        progress_handler.success();
+
+        Ok(())
+    }
+
+    pub fn down(&self, progress_handler: &UpProgressHandler) -> Result<(), UpError> {
+        progress_handler.init(format!("{}:", self.name()).light_blue());
+        progress_handler.success_with_message("nothing to do".light_black());
+
+        Ok(())
+    }
+}
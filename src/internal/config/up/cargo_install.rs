@@ -20,25 +20,33 @@ use crate::internal::config::config;
 use crate::internal::config::global_config;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::UpCommandOperationConfig;
 use crate::internal::config::up::mise::mise_path;
 use crate::internal::config::up::mise_tool_path;
 use crate::internal::config::up::utils::cleanup_path;
-use crate::internal::config::up::utils::directory::force_remove_all;
 use crate::internal::config::up::utils::directory::safe_rename;
+use crate::internal::config::up::utils::is_installed;
 use crate::internal::config::up::utils::progress_handler::ProgressHandler;
 use crate::internal::config::up::utils::run_progress;
+use crate::internal::config::up::utils::staging::STAGING_DIR_MAX_AGE;
+use crate::internal::config::up::utils::sweep_staging_dirs;
+use crate::internal::config::up::utils::ContentStore;
+use crate::internal::config::up::utils::InstallMarker;
+use crate::internal::config::up::utils::InstallMarkerAsset;
+use crate::internal::config::up::utils::InstallStaging;
 use crate::internal::config::up::utils::RunConfig;
 use crate::internal::config::up::utils::UpProgressHandler;
 use crate::internal::config::up::utils::VersionMatcher;
 use crate::internal::config::up::utils::VersionParser;
-use crate::internal::config::up::UpConfigMise;
-use crate::internal::config::up::UpConfigTool;
 use crate::internal::config::up::UpError;
 use crate::internal::config::up::UpOptions;
 use crate::internal::config::ConfigValue;
+use crate::internal::dynenv::env_backend;
 use crate::internal::env::data_home;
 use crate::internal::env::tmpdir_cleanup_prefix;
 use crate::internal::user_interface::StringColor;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
 
 cfg_if::cfg_if! {
     if #[cfg(test)] {
@@ -176,6 +184,20 @@ impl UpConfigCargoInstalls {
         UpConfigCargoInstalls::default()
     }
 
+    /// Returns why this operation is not allowed by the given operations
+    /// policy, if it is not, so that it can be refused before installing
+    /// any of the crates
+    pub fn disallowed_reason(&self, operations: &UpCommandOperationConfig) -> Option<String> {
+        if !operations.is_operation_allowed("cargo-install") {
+            return Some("cargo-install operation is not allowed".to_string());
+        }
+
+        self.crates
+            .iter()
+            .find(|install| !operations.is_cargo_install_crate_allowed(&install.crate_name))
+            .map(|install| format!("crate {} not allowed", install.crate_name))
+    }
+
     pub fn up(
         &self,
         options: &UpOptions,
@@ -325,6 +347,47 @@ impl UpConfigCargoInstalls {
         Ok(())
     }
 
+    /// Resolves the version of the crate matching the requested version
+    /// among its published versions, without installing it
+    pub fn resolve_tool_version(
+        crate_name: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let install = UpConfigCargoInstall {
+            crate_name: crate_name.to_string(),
+            version: Some(version.to_string()),
+            ..UpConfigCargoInstall::default()
+        };
+
+        install.resolve_available_version(options, progress_handler, None)
+    }
+
+    /// Installs the version of the crate matching the requested version,
+    /// and records it in the environment; returns the installed version
+    pub fn install_tool_version(
+        crate_name: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let installs = Self {
+            crates: vec![UpConfigCargoInstall {
+                crate_name: crate_name.to_string(),
+                version: Some(version.to_string()),
+                ..UpConfigCargoInstall::default()
+            }],
+        };
+
+        installs.up(options, environment, progress_handler)?;
+        match installs.crates[0].actual_version.get() {
+            Some(version) => Ok(version.clone()),
+            None => Err(UpError::Exec("version not set".to_string())),
+        }
+    }
+
     pub fn cleanup(progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
         progress_handler.init("cargo install:".light_blue());
 
@@ -344,7 +407,7 @@ impl UpConfigCargoInstalls {
             UpError::Cache(msg)
         })?;
 
-        let expected_paths = expected_crates
+        let mut expected_paths = expected_crates
             .iter()
             .map(|install| {
                 cargo_install_bin_path()
@@ -353,6 +416,20 @@ impl UpConfigCargoInstalls {
             })
             .collect::<Vec<PathBuf>>();
 
+        // Remove the staging directories left behind by installations
+        // that were interrupted, but keep the recent ones as they might
+        // be in use by an installation in progress; they are next to
+        // the versions of a crate
+        let (_, staging_paths) =
+            sweep_staging_dirs(cargo_install_bin_path(), 2, STAGING_DIR_MAX_AGE).map_err(
+                |err| {
+                    let msg = format!("failed to sweep cargo install staging directories: {err}");
+                    progress_handler.progress(msg.clone());
+                    UpError::Exec(msg)
+                },
+            )?;
+        expected_paths.extend(staging_paths);
+
         let (root_removed, num_removed, removed_paths) = cleanup_path(
             cargo_install_bin_path(),
             expected_paths,
@@ -931,37 +1008,7 @@ impl UpConfigCargoInstall {
         }
 
         if version.is_empty() {
-            let versions = match versions {
-                Some(versions) => versions,
-                None => self.list_versions(options, progress_handler)?,
-            };
-            version = match self.resolve_version(&versions.versions) {
-                Ok(version) => version,
-                Err(err) => {
-                    // If the versions are not fresh of now, and we failed to
-                    // resolve the version to install, we should try to refresh the
-                    // versions list and try again
-                    if options.read_cache && !versions.is_fresh() {
-                        progress_handler.progress("no matching version found in cache".to_string());
-
-                        let versions = self.list_versions(
-                            &UpOptions {
-                                read_cache: false,
-                                ..options.clone()
-                            },
-                            progress_handler,
-                        )?;
-
-                        self.resolve_version(&versions.versions)
-                            .inspect_err(|err| {
-                                progress_handler.error_with_message(err.message());
-                            })?
-                    } else {
-                        progress_handler.error_with_message(err.message());
-                        return Err(err);
-                    }
-                }
-            };
+            version = self.resolve_available_version(options, progress_handler, versions)?;
 
             // Try installing the version found
             install_version = self.install_version(cargo_bin, options, &version, progress_handler);
@@ -989,6 +1036,48 @@ impl UpConfigCargoInstall {
         self.handle_installed(&version, install_version)
     }
 
+    /// Resolves the version matching the requested version among the
+    /// available versions, using the given list of versions if any; the
+    /// list is refreshed if it comes from the cache and has no match
+    fn resolve_available_version(
+        &self,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+        versions: Option<CargoInstallVersions>,
+    ) -> Result<String, UpError> {
+        let versions = match versions {
+            Some(versions) => versions,
+            None => self.list_versions(options, progress_handler)?,
+        };
+
+        match self.resolve_version(&versions.versions) {
+            Ok(version) => Ok(version),
+            Err(err) => {
+                // If the versions are not fresh of now, and we failed to
+                // resolve the version to install, we should try to refresh the
+                // versions list and try again
+                if options.read_cache && !versions.is_fresh() {
+                    progress_handler.progress("no matching version found in cache".to_string());
+
+                    let versions = self.list_versions(
+                        &UpOptions {
+                            read_cache: false,
+                            ..options.clone()
+                        },
+                        progress_handler,
+                    )?;
+
+                    self.resolve_version(&versions.versions).inspect_err(|err| {
+                        progress_handler.error_with_message(err.message());
+                    })
+                } else {
+                    progress_handler.error_with_message(err.message());
+                    Err(err)
+                }
+            }
+        }
+    }
+
     fn handle_installed(
         &self,
         version: &str,
@@ -1196,7 +1285,7 @@ impl UpConfigCargoInstall {
             })?
             .filter_map(|entry| {
                 entry.ok().and_then(|entry| {
-                    if entry.file_type().ok()?.is_dir() {
+                    if is_installed(entry.path()) {
                         entry.file_name().into_string().ok()
                     } else {
                         None
@@ -1217,7 +1306,7 @@ impl UpConfigCargoInstall {
     ) -> Result<bool, UpError> {
         let install_path = self.version_crate_name(version);
 
-        if options.read_cache && install_path.exists() && install_path.is_dir() {
+        if options.read_cache && is_installed(&install_path) {
             progress_handler.progress(
                 format!("installed {}@{} (cached)", self.crate_name, version).light_black(),
             );
@@ -1287,28 +1376,46 @@ impl UpConfigCargoInstall {
             return Err(UpError::Exec(msg));
         }
 
-        // Move the installed version to the correct crate_name
-        std::fs::create_dir_all(&install_path).map_err(|err| {
-            let msg = format!("failed to create install directory: {err}");
+        // Prepare the installation in a staging directory next to the
+        // install path, so that the install path only exists once the
+        // installation fully completed
+        let mut staging = InstallStaging::new(&install_path).map_err(|err| {
+            let msg = format!("failed to create staging directory: {err}");
             progress_handler.error_with_message(msg.clone());
             UpError::Exec(msg)
         })?;
+        if global_config()
+            .up_command
+            .operations
+            .cargo_install
+            .content_store
+        {
+            staging = staging.with_content_store(ContentStore::get());
+        }
 
-        // Make sure the target directory does not exist
-        let target_bin_dir = install_path.join("bin");
-        force_remove_all(&target_bin_dir).map_err(|err| {
-            let msg = format!("failed to remove target bin directory: {err}");
+        create_dir_all(staging.path(), PathKind::Shared).map_err(|err| {
+            let msg = format!("failed to create install directory: {err}");
             progress_handler.error_with_message(msg.clone());
             UpError::Exec(msg)
         })?;
 
-        // Move the tmp_bin_crate_name to the install_crate_name/<bin> directory
-        safe_rename(&tmp_bin_path, target_bin_dir).map_err(|err| {
+        // Move the tmp_bin_path to the <staging>/bin directory
+        safe_rename(&tmp_bin_path, staging.path().join("bin")).map_err(|err| {
             let msg = format!("failed to move bin directory: {err}");
             progress_handler.error_with_message(msg.clone());
             UpError::Exec(msg)
         })?;
 
+        let marker = InstallMarker::new(vec![InstallMarkerAsset::new(
+            format!("{}@{}", self.crate_name, version),
+            None,
+        )]);
+        staging.commit(&marker).map_err(|err| {
+            let msg = format!("failed to move install directory: {err}");
+            progress_handler.error_with_message(msg.clone());
+            UpError::Exec(msg)
+        })?;
+
         Ok(true)
     }
 
@@ -1412,7 +1519,7 @@ struct CargoBin {
 impl CargoBin {
     fn get(options: &UpOptions, progress_handler: &UpProgressHandler) -> Result<Self, UpError> {
         progress_handler.progress("install dependencies".to_string());
-        let rust_tool = UpConfigTool::Mise(UpConfigMise::new_any_version("rust"));
+        let mise_backend = env_backend("").expect("the mise backend should be registered");
 
         // We create a fake environment since we do not want to add this
         // rust version as part of it, but we want to be able to use `cargo`
@@ -1420,15 +1527,8 @@ impl CargoBin {
         let mut fake_env = UpEnvironment::new();
 
         let subhandler = progress_handler.subhandler(&"rust: ".light_black());
-        rust_tool.up(options, &mut fake_env, &subhandler)?;
-
-        // Grab the tool from inside go_tool
-        let mise = match rust_tool {
-            UpConfigTool::Mise(mise) => mise,
-            _ => unreachable!("rust_tool is not a mise tool"),
-        };
-
-        let installed_version = mise.version()?;
+        let installed_version =
+            mise_backend.install_version("rust", "*", options, &mut fake_env, &subhandler)?;
         let install_path = PathBuf::from(mise_tool_path("rust", &installed_version));
         let cargo_bin = install_path.join("cargo");
 
@@ -363,6 +363,7 @@ where
                     name: var.to_string(),
                     operation: EnvOperationEnum::Set,
                     value: None,
+                    command: None,
                 });
             }
 
@@ -395,6 +396,7 @@ where
                     name: var.to_string(),
                     operation: *operation,
                     value: Some(value.to_string()),
+                    command: None,
                 });
 
                 continue 'outer;
@@ -526,6 +528,7 @@ where
                 name: var.to_string(),
                 operation: EnvOperationEnum::Set,
                 value: Some(value),
+                command: None,
             });
 
             continue;
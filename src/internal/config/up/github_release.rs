@@ -1,10 +1,10 @@
 use std::cell::RefCell;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
-use std::fs::OpenOptions;
 use std::io;
 use std::io::BufRead;
 use std::io::BufReader;
+use std::io::Read;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
@@ -41,11 +41,18 @@ use crate::internal::config::parser::ConfigErrorKind;
 use crate::internal::config::parser::EnvConfig;
 use crate::internal::config::parser::EnvOperationEnum;
 use crate::internal::config::parser::GithubAuthConfig;
+use crate::internal::config::parser::UpCommandOperationConfig;
 use crate::internal::config::template::config_template_context;
 use crate::internal::config::template::tera_render_error_message;
 use crate::internal::config::up::utils::cleanup_path;
 use crate::internal::config::up::utils::directory::safe_rename;
-use crate::internal::config::up::utils::force_remove_dir_all;
+use crate::internal::config::up::utils::is_installed;
+use crate::internal::config::up::utils::staging::STAGING_DIR_MAX_AGE;
+use crate::internal::config::up::utils::sweep_staging_dirs;
+use crate::internal::config::up::utils::ContentStore;
+use crate::internal::config::up::utils::InstallMarker;
+use crate::internal::config::up::utils::InstallMarkerAsset;
+use crate::internal::config::up::utils::InstallStaging;
 use crate::internal::config::up::utils::ProgressHandler;
 use crate::internal::config::up::utils::UpProgressHandler;
 use crate::internal::config::up::utils::VersionMatcher;
@@ -58,6 +65,9 @@ use crate::internal::config::ConfigValue;
 use crate::internal::dynenv::update_dynamic_env_for_command_from_env;
 use crate::internal::env::data_home;
 use crate::internal::user_interface::StringColor;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+use crate::internal::utils::PathPermissions;
 
 const GITHUB_API_URL: &str = "https://api.github.com";
 
@@ -248,6 +258,20 @@ impl UpConfigGithubReleases {
         Self::default()
     }
 
+    /// Returns why this operation is not allowed by the given operations
+    /// policy, if it is not, so that it can be refused before installing
+    /// any of the releases
+    pub fn disallowed_reason(&self, operations: &UpCommandOperationConfig) -> Option<String> {
+        if !operations.is_operation_allowed("github-release") {
+            return Some("github-release operation is not allowed".to_string());
+        }
+
+        self.releases
+            .iter()
+            .find(|release| !operations.is_github_repository_allowed(&release.repository))
+            .map(|release| format!("repository {} not allowed", release.repository))
+    }
+
     pub fn up(
         &self,
         options: &UpOptions,
@@ -398,6 +422,38 @@ impl UpConfigGithubReleases {
         Ok(())
     }
 
+    /// Resolves the version of the release matching the requested version
+    /// among the releases of the repository, without installing it
+    pub fn resolve_tool_version(
+        repository: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let release = UpConfigGithubRelease::new_with_version(repository, version, false);
+
+        let releases = release.list_releases(options, progress_handler)?;
+        let resolved = release.resolve_release(&releases).inspect_err(|err| {
+            progress_handler.error_with_message(err.message());
+        })?;
+
+        Ok(resolved.version())
+    }
+
+    /// Installs the release matching the requested version, and records
+    /// it in the environment; returns the installed version
+    pub fn install_tool_version(
+        repository: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let release = UpConfigGithubRelease::new_with_version(repository, version, false);
+        release.up(options, environment, progress_handler)?;
+        Ok(release.version_with_config(&release.version()?))
+    }
+
     pub fn cleanup(progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
         progress_handler.init("github releases:".light_blue());
 
@@ -415,7 +471,7 @@ impl UpConfigGithubReleases {
             UpError::Cache(format!("failed to list installed github releases: {err}"))
         })?;
 
-        let expected_paths = expected_releases
+        let mut expected_paths = expected_releases
             .iter()
             .map(|install| {
                 github_releases_bin_path()
@@ -424,6 +480,20 @@ impl UpConfigGithubReleases {
             })
             .collect::<Vec<PathBuf>>();
 
+        // Remove the staging directories left behind by installations
+        // that were interrupted, but keep the recent ones as they might
+        // be in use by an installation in progress; they are next to
+        // the versions of a release, under its owner and repository
+        let (_, staging_paths) =
+            sweep_staging_dirs(github_releases_bin_path(), 3, STAGING_DIR_MAX_AGE).map_err(
+                |err| {
+                    let msg = format!("failed to sweep github releases staging directories: {err}");
+                    progress_handler.progress(msg.clone());
+                    UpError::Exec(msg)
+                },
+            )?;
+        expected_paths.extend(staging_paths);
+
         let (root_removed, num_removed, removed_paths) = cleanup_path(
             github_releases_bin_path(),
             expected_paths,
@@ -1536,7 +1606,7 @@ impl UpConfigGithubRelease {
             })?
             .filter_map(|entry| {
                 entry.ok().and_then(|entry| {
-                    if entry.file_type().ok()?.is_dir() {
+                    if is_installed(entry.path()) {
                         entry.file_name().into_string().ok()
                     } else {
                         None
@@ -1777,18 +1847,22 @@ impl UpConfigGithubRelease {
         }
 
         // Write the file to disk
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(asset_path)
+        let mut file = PathPermissions::for_kind(PathKind::Shared)
+            .create_file(asset_path)
             .map_err(|err| {
                 let errmsg = format!("failed to open {asset_name}: {err}");
                 progress_handler.error_with_message(errmsg.clone());
                 UpError::Exec(errmsg)
             })?;
 
-        io::copy(&mut response, &mut file).map_err(|err| {
+        let total = response.content_length();
+        let mut reader = DownloadProgressReader::new(
+            &mut response,
+            format!("downloading {asset_name}"),
+            total,
+            progress_handler,
+        );
+        io::copy(&mut reader, &mut file).map_err(|err| {
             let errmsg = format!("failed to write {asset_name}: {err}");
             progress_handler.error_with_message(errmsg.clone());
             UpError::Exec(errmsg)
@@ -2120,7 +2194,7 @@ impl UpConfigGithubRelease {
         let version = release.version();
         let install_path = self.release_version_path(&self.version_with_config(&version));
 
-        if options.read_cache && install_path.exists() && install_path.is_dir() {
+        if options.read_cache && is_installed(&install_path) {
             progress_handler.progress(
                 format!("downloaded {} {} (cached)", self.repository, version).light_black(),
             );
@@ -2131,6 +2205,24 @@ impl UpConfigGithubRelease {
         // Download, verify, and extract to a temporary directory
         let tmp_dir = self.download_and_extract_to_temp(release, progress_handler)?;
 
+        // Prepare the installation in a staging directory next to the
+        // install path, so that the install path only exists once the
+        // installation fully completed
+        let mut staging = InstallStaging::new(&install_path).map_err(|err| {
+            let errmsg = format!("failed to create staging directory: {err}");
+            progress_handler.error_with_message(errmsg.clone());
+            UpError::Exec(errmsg)
+        })?;
+        if global_config()
+            .up_command
+            .operations
+            .github_release
+            .content_store
+        {
+            staging = staging.with_content_store(ContentStore::get());
+        }
+        let staging_path = staging.path();
+
         // Check if the extracted content is an SDK-like structure (has bin/ + lib/src/pkg/etc)
         let sdk_detection = self.detect_sdk_structure(tmp_dir.path());
 
@@ -2143,7 +2235,7 @@ impl UpConfigGithubRelease {
             ));
 
             // Move the entire SDK directory using safe_rename (falls back to copy if needed)
-            safe_rename(&sdk_root, &install_path).map_err(|err| {
+            safe_rename(&sdk_root, &staging_path).map_err(|err| {
                 let errmsg = format!("failed to move SDK directory: {err}");
                 progress_handler.error_with_message(errmsg.clone());
                 UpError::Exec(errmsg)
@@ -2181,19 +2273,21 @@ impl UpConfigGithubRelease {
 
                 progress_handler.progress(format!("found binary {}", binary_name.light_yellow()));
 
-                let target_path = install_path.join(&binary_name);
+                let target_path = staging_path.join(&binary_name);
 
                 // Make sure the target directory exists
-                if !install_path.exists() {
-                    std::fs::create_dir_all(&install_path).map_err(|err| {
+                if !staging_path.exists() {
+                    create_dir_all(&staging_path, PathKind::Shared).map_err(|err| {
                         let errmsg =
-                            format!("failed to create {}: {}", install_path.display(), err);
+                            format!("failed to create {}: {}", staging_path.display(), err);
                         progress_handler.error_with_message(errmsg.clone());
                         UpError::Exec(errmsg)
                     })?;
                 }
 
-                // Copy the binary to the install path
+                // Copy the binary to the staging path; if this fails, the
+                // staging directory is removed when dropped, which avoids
+                // leaving a partial installation behind
                 let copy = std::fs::copy(source_path, &target_path);
                 if copy.is_err() || !target_path.exists() {
                     let err = if let Err(err) = copy {
@@ -2206,12 +2300,6 @@ impl UpConfigGithubRelease {
                     };
                     let errmsg = format!("failed to copy {binary_name}: {err}");
                     progress_handler.error_with_message(errmsg.clone());
-
-                    // Force delete the install path if we fail to copy
-                    // the binary to avoid leaving a partial installation
-                    // behind
-                    let _ = force_remove_dir_all(&install_path);
-
                     return Err(UpError::Exec(errmsg));
                 }
 
@@ -2225,6 +2313,15 @@ impl UpConfigGithubRelease {
             return Err(UpError::Exec("no binaries found".to_string()));
         }
 
+        // Move the installation to its final path, along with the marker
+        // recording the assets it was installed from
+        let marker = self.install_marker(release, tmp_dir.path());
+        staging.commit(&marker).map_err(|err| {
+            let errmsg = format!("failed to move install directory: {err}");
+            progress_handler.error_with_message(errmsg.clone());
+            UpError::Exec(errmsg)
+        })?;
+
         progress_handler.progress(format!(
             "downloaded {} {}",
             self.repository.light_yellow(),
@@ -2234,6 +2331,39 @@ impl UpConfigGithubRelease {
         Ok(true)
     }
 
+    /// Returns the completion marker of the installation of the given
+    /// release, recording the checksum of the assets that were downloaded
+    /// to the given path
+    fn install_marker(
+        &self,
+        release: &GithubReleaseVersion,
+        download_path: &Path,
+    ) -> InstallMarker {
+        let version = release.version();
+        let assets = release
+            .assets
+            .iter()
+            .map(|asset| {
+                // Binary assets are renamed after being downloaded
+                let sha256 = [
+                    download_path.join(&asset.name),
+                    download_path.join(asset.clean_name(&version)),
+                ]
+                .iter()
+                .find(|path| path.is_file())
+                .and_then(|path| {
+                    GithubReleaseChecksumAlgorithm::Sha256
+                        .compute_file_hash(path)
+                        .ok()
+                });
+
+                InstallMarkerAsset::new(&asset.name, sha256)
+            })
+            .collect();
+
+        InstallMarker::new(assets)
+    }
+
     fn handling(&self) -> GithubReleaseHandled {
         match self.was_handled.get() {
             Some(handled) => handled.clone(),
@@ -2353,6 +2483,7 @@ impl UpConfigGithubRelease {
                             name: LIBRARY_PATH_ENV_VAR.to_string(),
                             operation: EnvOperationEnum::Prepend,
                             value: Some(install_path.join("lib").to_string_lossy().to_string()),
+                            command: None,
                         });
                     }
                     "man" => {
@@ -2360,6 +2491,7 @@ impl UpConfigGithubRelease {
                             name: "MANPATH".to_string(),
                             operation: EnvOperationEnum::Prepend,
                             value: Some(install_path.join("man").to_string_lossy().to_string()),
+                            command: None,
                         });
                     }
                     "include" => {
@@ -2369,11 +2501,13 @@ impl UpConfigGithubRelease {
                             name: "C_INCLUDE_PATH".to_string(),
                             operation: EnvOperationEnum::Prepend,
                             value: Some(include_path.clone()),
+                            command: None,
                         });
                         env_vars.push(UpEnvVar {
                             name: "CPLUS_INCLUDE_PATH".to_string(),
                             operation: EnvOperationEnum::Prepend,
                             value: Some(include_path),
+                            command: None,
                         });
                     }
                     _ => {
@@ -2401,11 +2535,16 @@ impl UpConfigGithubRelease {
                         }
                     });
 
-            env_vars.push(UpEnvVar {
-                name: env_op.name.clone(),
-                operation: env_op.operation,
-                value,
-            });
+            // Paths are resolved relative to the installation directory
+            env_vars.push(
+                UpEnvVar {
+                    name: env_op.name.clone(),
+                    operation: env_op.operation,
+                    value,
+                    command: None,
+                }
+                .resolve_path(&install_path),
+            );
         }
 
         env_vars
@@ -2787,6 +2926,57 @@ impl GithubApiError {
     }
 }
 
+/// Minimum number of bytes between two download progress reports
+const DOWNLOAD_PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Wraps a reader to report the number of bytes downloaded so far
+/// to the progress handler, at most once per interval of bytes
+struct DownloadProgressReader<'a, R: Read> {
+    inner: R,
+    message: String,
+    downloaded: u64,
+    reported: u64,
+    total: Option<u64>,
+    progress_handler: &'a dyn ProgressHandler,
+}
+
+impl<'a, R: Read> DownloadProgressReader<'a, R> {
+    fn new(
+        inner: R,
+        message: String,
+        total: Option<u64>,
+        progress_handler: &'a dyn ProgressHandler,
+    ) -> Self {
+        Self {
+            inner,
+            message,
+            downloaded: 0,
+            reported: 0,
+            total,
+            progress_handler,
+        }
+    }
+}
+
+impl<R: Read> Read for DownloadProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.downloaded += read as u64;
+
+        let finished = read == 0 && self.downloaded > self.reported;
+        if finished || self.downloaded - self.reported >= DOWNLOAD_PROGRESS_INTERVAL {
+            self.reported = self.downloaded;
+            self.progress_handler.download_progress(
+                self.message.clone(),
+                self.downloaded,
+                self.total,
+            );
+        }
+
+        Ok(read)
+    }
+}
+
 #[cfg(test)]
 #[path = "github_release_test.rs"]
 mod tests;
@@ -532,6 +532,18 @@ mod up {
             mock_list_releases.assert();
             mock_downloads.iter().for_each(|mock| mock.assert());
 
+            if let Some(expected_version) = &test.expected_version {
+                // Check the installation was marked as completed
+                let expected_path = github_releases_bin_path()
+                    .join("owner/repo")
+                    .join(expected_version);
+                assert!(
+                    is_installed(&expected_path),
+                    "installation not marked as completed at {}",
+                    expected_path.display()
+                );
+            }
+
             for asset_id in 1..=test.assets {
                 // Check the binary file exists
                 let expected_bin = github_releases_bin_path()
@@ -704,6 +716,7 @@ mod compute_sdk_env_vars {
                 name: "CUSTOM_VAR".to_string(),
                 operation: EnvOperationEnum::Set,
                 value: Some("static_value".to_string()),
+                command: None,
             });
 
             let config = UpConfigGithubRelease {
@@ -729,6 +742,7 @@ mod compute_sdk_env_vars {
                 name: "SDK_ROOT".to_string(),
                 operation: EnvOperationEnum::Set,
                 value: Some("{{ install_dir }}".to_string()),
+                command: None,
             });
 
             let config = UpConfigGithubRelease {
@@ -761,6 +775,7 @@ mod compute_sdk_env_vars {
                 name: "CUSTOM_PATH".to_string(),
                 operation: EnvOperationEnum::Prepend,
                 value: Some("{{ install_dir }}/custom/bin".to_string()),
+                command: None,
             });
 
             let config = UpConfigGithubRelease {
@@ -791,11 +806,13 @@ mod compute_sdk_env_vars {
                 name: "SDK_ROOT".to_string(),
                 operation: EnvOperationEnum::Set,
                 value: Some("{{ install_dir }}".to_string()),
+                command: None,
             });
             env_config.operations.push(EnvOperationConfig {
                 name: "CUSTOM_VAR".to_string(),
                 operation: EnvOperationEnum::Set,
                 value: Some("custom_static_value".to_string()),
+                command: None,
             });
 
             let config = UpConfigGithubRelease {
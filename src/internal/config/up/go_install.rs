@@ -23,13 +23,20 @@ use crate::internal::config::config;
 use crate::internal::config::global_config;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::UpCommandOperationConfig;
 use crate::internal::config::up::mise_tool_path;
 use crate::internal::config::up::utils::cleanup_path;
-use crate::internal::config::up::utils::directory::force_remove_all;
 use crate::internal::config::up::utils::directory::safe_rename;
 use crate::internal::config::up::utils::get_command_output;
+use crate::internal::config::up::utils::is_installed;
 use crate::internal::config::up::utils::progress_handler::ProgressHandler;
 use crate::internal::config::up::utils::run_progress;
+use crate::internal::config::up::utils::staging::STAGING_DIR_MAX_AGE;
+use crate::internal::config::up::utils::sweep_staging_dirs;
+use crate::internal::config::up::utils::ContentStore;
+use crate::internal::config::up::utils::InstallMarker;
+use crate::internal::config::up::utils::InstallMarkerAsset;
+use crate::internal::config::up::utils::InstallStaging;
 use crate::internal::config::up::utils::RunConfig;
 use crate::internal::config::up::utils::UpProgressHandler;
 use crate::internal::config::up::utils::VersionMatcher;
@@ -43,6 +50,8 @@ use crate::internal::config::ConfigValue;
 use crate::internal::env::data_home;
 use crate::internal::env::tmpdir_cleanup_prefix;
 use crate::internal::user_interface::StringColor;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
 
 cfg_if::cfg_if! {
     if #[cfg(test)] {
@@ -180,6 +189,20 @@ impl UpConfigGoInstalls {
         Self::default()
     }
 
+    /// Returns why this operation is not allowed by the given operations
+    /// policy, if it is not, so that it can be refused before installing
+    /// any of the tools
+    pub fn disallowed_reason(&self, operations: &UpCommandOperationConfig) -> Option<String> {
+        if !operations.is_operation_allowed("go-install") {
+            return Some("go-install operation is not allowed".to_string());
+        }
+
+        self.tools
+            .iter()
+            .find(|tool| !operations.is_go_install_source_allowed(&tool.path))
+            .map(|tool| format!("go-install source not allowed: {}", tool.path))
+    }
+
     pub fn up(
         &self,
         options: &UpOptions,
@@ -331,6 +354,48 @@ impl UpConfigGoInstalls {
         Ok(())
     }
 
+    /// Resolves the version of the package matching the requested version
+    /// among its published versions, without installing it
+    pub fn resolve_tool_version(
+        path: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let install = UpConfigGoInstall {
+            path: path.to_string(),
+            version: Some(version.to_string()),
+            ..UpConfigGoInstall::default()
+        };
+
+        let go_bin = GoBin::get(options, progress_handler)?;
+        install.resolve_available_version(&go_bin, options, progress_handler, None)
+    }
+
+    /// Installs the version of the package matching the requested version,
+    /// and records it in the environment; returns the installed version
+    pub fn install_tool_version(
+        path: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let installs = Self {
+            tools: vec![UpConfigGoInstall {
+                path: path.to_string(),
+                version: Some(version.to_string()),
+                ..UpConfigGoInstall::default()
+            }],
+        };
+
+        installs.up(options, environment, progress_handler)?;
+        match installs.tools[0].actual_version.get() {
+            Some(version) => Ok(version.clone()),
+            None => Err(UpError::Exec("version not set".to_string())),
+        }
+    }
+
     pub fn cleanup(progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
         progress_handler.init("go install:".light_blue());
 
@@ -350,7 +415,7 @@ impl UpConfigGoInstalls {
             UpError::Cache(msg)
         })?;
 
-        let expected_paths = expected_tools
+        let mut expected_paths = expected_tools
             .iter()
             .map(|install| {
                 go_install_bin_path()
@@ -359,6 +424,18 @@ impl UpConfigGoInstalls {
             })
             .collect::<Vec<PathBuf>>();
 
+        // Remove the staging directories left behind by installations
+        // that were interrupted, but keep the recent ones as they might
+        // be in use by an installation in progress; they are next to
+        // the versions of a package, under its module path
+        let (_, staging_paths) = sweep_staging_dirs(go_install_bin_path(), 10, STAGING_DIR_MAX_AGE)
+            .map_err(|err| {
+                let msg = format!("failed to sweep go install staging directories: {err}");
+                progress_handler.progress(msg.clone());
+                UpError::Exec(msg)
+            })?;
+        expected_paths.extend(staging_paths);
+
         let (root_removed, num_removed, removed_paths) = cleanup_path(
             go_install_bin_path(),
             expected_paths,
@@ -939,38 +1016,8 @@ impl UpConfigGoInstall {
         }
 
         if version.is_empty() {
-            let versions = match versions {
-                Some(versions) => versions,
-                None => self.list_versions(go_bin, options, progress_handler)?,
-            };
-            version = match self.resolve_version(&versions.versions) {
-                Ok(version) => version,
-                Err(err) => {
-                    // If the versions are not fresh of now, and we failed to
-                    // resolve the version to install, we should try to refresh the
-                    // versions list and try again
-                    if options.read_cache && !versions.is_fresh() {
-                        progress_handler.progress("no matching version found in cache".to_string());
-
-                        let versions = self.list_versions(
-                            go_bin,
-                            &UpOptions {
-                                read_cache: false,
-                                ..options.clone()
-                            },
-                            progress_handler,
-                        )?;
-
-                        self.resolve_version(&versions.versions)
-                            .inspect_err(|err| {
-                                progress_handler.error_with_message(err.message());
-                            })?
-                    } else {
-                        progress_handler.error_with_message(err.message());
-                        return Err(err);
-                    }
-                }
-            };
+            version =
+                self.resolve_available_version(go_bin, options, progress_handler, versions)?;
 
             // Try installing the version found
             install_version = self.install_version(go_bin, options, &version, progress_handler);
@@ -998,6 +1045,50 @@ impl UpConfigGoInstall {
         self.handle_installed(&version, install_version)
     }
 
+    /// Resolves the version matching the requested version among the
+    /// available versions, using the given list of versions if any; the
+    /// list is refreshed if it comes from the cache and has no match
+    fn resolve_available_version(
+        &self,
+        go_bin: &GoBin,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+        versions: Option<GoInstallVersions>,
+    ) -> Result<String, UpError> {
+        let versions = match versions {
+            Some(versions) => versions,
+            None => self.list_versions(go_bin, options, progress_handler)?,
+        };
+
+        match self.resolve_version(&versions.versions) {
+            Ok(version) => Ok(version),
+            Err(err) => {
+                // If the versions are not fresh of now, and we failed to
+                // resolve the version to install, we should try to refresh the
+                // versions list and try again
+                if options.read_cache && !versions.is_fresh() {
+                    progress_handler.progress("no matching version found in cache".to_string());
+
+                    let versions = self.list_versions(
+                        go_bin,
+                        &UpOptions {
+                            read_cache: false,
+                            ..options.clone()
+                        },
+                        progress_handler,
+                    )?;
+
+                    self.resolve_version(&versions.versions).inspect_err(|err| {
+                        progress_handler.error_with_message(err.message());
+                    })
+                } else {
+                    progress_handler.error_with_message(err.message());
+                    Err(err)
+                }
+            }
+        }
+    }
+
     fn handle_installed(
         &self,
         version: &str,
@@ -1184,7 +1275,7 @@ impl UpConfigGoInstall {
             })?
             .filter_map(|entry| {
                 entry.ok().and_then(|entry| {
-                    if entry.file_type().ok()?.is_dir() {
+                    if is_installed(entry.path()) {
                         entry.file_name().into_string().ok()
                     } else {
                         None
@@ -1205,7 +1296,7 @@ impl UpConfigGoInstall {
     ) -> Result<bool, UpError> {
         let install_path = self.version_path(version);
 
-        if options.read_cache && install_path.exists() && install_path.is_dir() {
+        if options.read_cache && is_installed(&install_path) {
             progress_handler
                 .progress(format!("installed {}@{} (cached)", self.path, version).light_black());
 
@@ -1274,28 +1365,46 @@ impl UpConfigGoInstall {
             return Err(UpError::Exec(msg));
         }
 
-        // Move the installed version to the correct crate_name
-        std::fs::create_dir_all(&install_path).map_err(|err| {
-            let msg = format!("failed to create install directory: {err}");
+        // Prepare the installation in a staging directory next to the
+        // install path, so that the install path only exists once the
+        // installation fully completed
+        let mut staging = InstallStaging::new(&install_path).map_err(|err| {
+            let msg = format!("failed to create staging directory: {err}");
             progress_handler.error_with_message(msg.clone());
             UpError::Exec(msg)
         })?;
+        if global_config()
+            .up_command
+            .operations
+            .go_install
+            .content_store
+        {
+            staging = staging.with_content_store(ContentStore::get());
+        }
 
-        // Make sure the target directory does not exist
-        let target_bin_dir = install_path.join("bin");
-        force_remove_all(&target_bin_dir).map_err(|err| {
-            let msg = format!("failed to remove target bin directory: {err}");
+        create_dir_all(staging.path(), PathKind::Shared).map_err(|err| {
+            let msg = format!("failed to create install directory: {err}");
             progress_handler.error_with_message(msg.clone());
             UpError::Exec(msg)
         })?;
 
-        // Move the tmp_bin_crate_name to the install_crate_name/<bin> directory
-        safe_rename(&tmp_bin_path, target_bin_dir).map_err(|err| {
+        // Move the tmp_bin_path to the <staging>/bin directory
+        safe_rename(&tmp_bin_path, staging.path().join("bin")).map_err(|err| {
             let msg = format!("failed to move bin directory: {err}");
             progress_handler.error_with_message(msg.clone());
             UpError::Exec(msg)
         })?;
 
+        let marker = InstallMarker::new(vec![InstallMarkerAsset::new(
+            format!("{}@{}", self.path, version),
+            None,
+        )]);
+        staging.commit(&marker).map_err(|err| {
+            let msg = format!("failed to move install directory: {err}");
+            progress_handler.error_with_message(msg.clone());
+            UpError::Exec(msg)
+        })?;
+
         Ok(true)
     }
 
@@ -13,15 +13,16 @@ use serde::Serialize;
 use crate::internal::cache::up_environments::UpEnvironment;
 use crate::internal::cache::utils as cache_utils;
 use crate::internal::commands::utils::abs_path;
+use crate::internal::config::config;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::up::mise::PostInstallFuncArgs;
-use crate::internal::config::up::utils::data_path_dir_hash;
+use crate::internal::config::up::utils::setup_version_data_path;
+use crate::internal::config::up::utils::version_data_path;
 use crate::internal::config::up::utils::UpProgressHandler;
 use crate::internal::config::up::UpConfigMise;
 use crate::internal::config::up::UpError;
 use crate::internal::config::up::UpOptions;
 use crate::internal::env::current_dir;
-use crate::internal::workdir;
 use crate::internal::ConfigValue;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -259,7 +260,7 @@ fn extract_version_from_gomod_file(
 }
 
 fn setup_individual_gopath(
-    _options: &UpOptions,
+    options: &UpOptions,
     environment: &mut UpEnvironment,
     _progress_handler: &UpProgressHandler,
     args: &PostInstallFuncArgs,
@@ -272,9 +273,7 @@ fn setup_individual_gopath(
     }
 
     // Get the data path for the work directory
-    let workdir = workdir(".");
-
-    let data_path = match workdir.data_path() {
+    let data_path = match options.tools_data_path() {
         Some(data_path) => data_path,
         None => {
             return Err(UpError::Exec(format!(
@@ -285,16 +284,19 @@ fn setup_individual_gopath(
     };
 
     // Handle each version individually
+    let data_paths_policy = config(".").up_command.data_paths;
     for version in &args.versions {
         for dir in &version.dirs {
-            let gopath_dir = data_path_dir_hash(dir);
-
             let normalized_name = args.fqtn.normalized_plugin_name()?;
 
-            let gopath = data_path
-                .join(&normalized_name)
-                .join(&version.version)
-                .join(&gopath_dir);
+            let gopath = version_data_path(
+                data_paths_policy,
+                &data_path,
+                &normalized_name,
+                &version.version,
+                dir,
+            );
+            setup_version_data_path(data_paths_policy, &gopath)?;
 
             environment.add_version_data_path(
                 &normalized_name,
@@ -4,6 +4,7 @@ use std::os::unix::fs::symlink;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command as StdCommand;
+use std::sync::RwLock;
 
 use normalize_path::NormalizePath;
 use once_cell::sync::Lazy;
@@ -19,17 +20,26 @@ use crate::internal::cache::mise_operation::MisePluginVersions;
 use crate::internal::cache::up_environments::UpEnvironment;
 use crate::internal::cache::up_environments::UpVersionParams;
 use crate::internal::cache::utils as cache_utils;
+use crate::internal::cache::CacheDir;
 use crate::internal::cache::CacheManagerError;
 use crate::internal::cache::MiseOperationCache;
 use crate::internal::config;
 use crate::internal::config::global_config;
 use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::UpCommandOperationConfig;
 use crate::internal::config::up::homebrew::HomebrewInstall;
-use crate::internal::config::up::utils::data_path_dir_hash;
 use crate::internal::config::up::utils::directory::safe_rename;
 use crate::internal::config::up::utils::force_remove_dir_all;
+use crate::internal::config::up::utils::remove_shared_data_path;
 use crate::internal::config::up::utils::run_progress;
+use crate::internal::config::up::utils::setup_version_data_path;
+use crate::internal::config::up::utils::shared_data_home;
+use crate::internal::config::up::utils::staging::STAGING_DIR_MAX_AGE;
+use crate::internal::config::up::utils::staging::STAGING_DIR_PREFIX;
+use crate::internal::config::up::utils::sweep_staging_dirs;
+use crate::internal::config::up::utils::version_data_path;
 use crate::internal::config::up::utils::CommandExt;
+use crate::internal::config::up::utils::ContentStore;
 use crate::internal::config::up::utils::ProgressHandler;
 use crate::internal::config::up::utils::RunConfig;
 use crate::internal::config::up::utils::UpProgressHandler;
@@ -51,13 +61,22 @@ use crate::internal::env::state_home;
 use crate::internal::git::is_path_gitignored_from;
 use crate::internal::git_env;
 use crate::internal::user_interface::StringColor;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
 use crate::internal::workdir;
 use crate::omni_warning;
 
 static MISE_PATH: Lazy<String> = Lazy::new(|| format!("{}/mise", data_home()));
 static MISE_BIN_DIR: Lazy<String> = Lazy::new(|| format!("{}/bin", *MISE_PATH));
 static MISE_BIN: Lazy<String> = Lazy::new(|| format!("{}/mise", *MISE_BIN_DIR));
-static MISE_CACHE_PATH: Lazy<String> = Lazy::new(|| format!("{}/mise", cache_home()));
+/// The mise binary selected by the bootstrap, if different from the
+/// binary managed by omni (i.e. a mise binary found in the PATH)
+static MISE_SELECTED_BIN: RwLock<Option<String>> = RwLock::new(None);
+static MISE_CACHE_PATH: Lazy<String> = Lazy::new(|| {
+    let cache_dir = CacheDir::get(cache_home());
+    let cache_dir = cache_dir.writable().unwrap_or(cache_dir.primary());
+    format!("{}/mise", cache_dir.display())
+});
 static MISE_REGISTRY: Lazy<MiseRegistry> =
     Lazy::new(|| MiseRegistry::new().expect("failed to load mise registry"));
 
@@ -91,10 +110,25 @@ fn mise_bin_dir() -> &'static str {
     MISE_BIN_DIR.as_str()
 }
 
-fn mise_bin() -> &'static str {
+fn managed_mise_bin() -> &'static str {
     MISE_BIN.as_str()
 }
 
+fn mise_bin() -> String {
+    match MISE_SELECTED_BIN.read() {
+        Ok(selected) => selected
+            .clone()
+            .unwrap_or_else(|| managed_mise_bin().to_string()),
+        Err(_) => managed_mise_bin().to_string(),
+    }
+}
+
+fn select_mise_bin(path: Option<&Path>) {
+    if let Ok(mut selected) = MISE_SELECTED_BIN.write() {
+        *selected = path.map(|path| path.to_string_lossy().to_string());
+    }
+}
+
 fn configure_mise_command<T>(command: &mut T) -> &mut T
 where
     T: CommandExt,
@@ -148,14 +182,173 @@ pub fn mise_tool_path(tool: &str, version: &str) -> String {
 }
 
 fn is_mise_installed() -> bool {
-    is_executable(Path::new(mise_bin()))
+    is_executable(Path::new(&mise_bin()))
+}
+
+/// A mise binary that can be used by omni
+#[derive(Debug, Clone, PartialEq)]
+struct MiseBinaryCandidate {
+    path: PathBuf,
+    /// Whether the binary is the one installed and managed by omni
+    managed: bool,
+}
+
+/// The state of the mise binaries available to omni
+#[derive(Debug, Clone, PartialEq)]
+enum MiseBinaryStatus {
+    /// A binary satisfying the minimum version is available
+    Ready(MiseBinaryCandidate),
+    /// Binaries are available, but none satisfies the minimum version;
+    /// holds the version of the first one found
+    Outdated(MiseBinaryCandidate, String),
+    /// No binary is available
+    Missing,
+}
+
+/// What to do to get a usable mise binary
+#[derive(Debug, Clone, PartialEq)]
+enum MiseBootstrapAction {
+    Use(MiseBinaryCandidate),
+    Install(String),
+}
+
+/// Returns the mise binaries that omni can use, in order of preference:
+/// the one managed by omni, then the ones in the PATH if allowed
+fn mise_binary_candidates(from_path: bool) -> Vec<MiseBinaryCandidate> {
+    let managed = PathBuf::from(managed_mise_bin());
+    let mut candidates = vec![MiseBinaryCandidate {
+        path: managed.clone(),
+        managed: true,
+    }];
+
+    if from_path {
+        if let Ok(paths) = which::which_all("mise") {
+            candidates.extend(paths.filter(|path| path != &managed).map(|path| {
+                MiseBinaryCandidate {
+                    path,
+                    managed: false,
+                }
+            }));
+        }
+    }
+
+    candidates
+}
+
+/// Returns the version of the given mise binary, or `None` if the binary
+/// is not available; the version of the binary managed by omni is read
+/// from the cache when recorded, to avoid calling the binary every time
+fn mise_binary_version(candidate: &MiseBinaryCandidate) -> Option<String> {
+    if !is_executable(&candidate.path) {
+        return None;
+    }
+
+    if candidate.managed {
+        if let Some(version) = MiseOperationCache::get().managed_mise_version() {
+            return Some(version);
+        }
+    }
+
+    let mut command = StdCommand::new(&candidate.path);
+    configure_mise_command(&mut command);
+    command.arg("--version");
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // The version of mise is the first word in stdout
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .split_whitespace()
+        .next()
+        .map(|version| version.to_string())
+}
+
+fn check_mise_binary<F>(
+    candidates: &[MiseBinaryCandidate],
+    min_version: &str,
+    version_of: F,
+) -> MiseBinaryStatus
+where
+    F: Fn(&MiseBinaryCandidate) -> Option<String>,
+{
+    let mut outdated = None;
+
+    for candidate in candidates {
+        let version = match version_of(candidate) {
+            Some(version) => version,
+            None => continue,
+        };
+
+        if min_version.is_empty()
+            || VersionParser::compare(&version, min_version) != std::cmp::Ordering::Less
+        {
+            return MiseBinaryStatus::Ready(candidate.clone());
+        }
+
+        if outdated.is_none() {
+            outdated = Some((candidate.clone(), version));
+        }
+    }
+
+    match outdated {
+        Some((candidate, version)) => MiseBinaryStatus::Outdated(candidate, version),
+        None => MiseBinaryStatus::Missing,
+    }
+}
+
+fn mise_bootstrap_action(
+    status: MiseBinaryStatus,
+    min_version: &str,
+    bootstrap: bool,
+) -> Result<MiseBootstrapAction, UpError> {
+    match status {
+        MiseBinaryStatus::Ready(candidate) => Ok(MiseBootstrapAction::Use(candidate)),
+        MiseBinaryStatus::Outdated(candidate, version) => {
+            if bootstrap {
+                Ok(MiseBootstrapAction::Install(format!(
+                    "mise {version} is older than {min_version}, installing mise"
+                )))
+            } else {
+                Err(UpError::Exec(format!(
+                    "mise {} ({}) is older than the minimum version {} and bootstrapping mise is disabled",
+                    version,
+                    candidate.path.display(),
+                    min_version,
+                )))
+            }
+        }
+        MiseBinaryStatus::Missing => {
+            if bootstrap {
+                Ok(MiseBootstrapAction::Install("installing mise".to_string()))
+            } else {
+                Err(UpError::Exec(
+                    "mise is not installed and bootstrapping mise is disabled".to_string(),
+                ))
+            }
+        }
+    }
 }
 
 fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Result<(), UpError> {
     let cache = MiseOperationCache::get();
+    let up_command = global_config().up_command;
+
+    let status = check_mise_binary(
+        &mise_binary_candidates(up_command.mise_from_path),
+        &up_command.mise_min_version,
+        mise_binary_version,
+    );
+    let action = mise_bootstrap_action(
+        status,
+        &up_command.mise_min_version,
+        up_command.mise_bootstrap,
+    )?;
 
-    let (fail_on_error, migrate_from_asdf) = if !is_mise_installed() {
-        progress_handler.progress("installing mise".to_string());
+    let (fail_on_error, migrate_from_asdf) = if let MiseBootstrapAction::Install(reason) = action {
+        progress_handler.progress(reason);
+        select_mise_bin(None);
 
         // Check if we need to perform any migration, which is true if the `data_home()/asdf`
         // directory exists and the `mise_path()` directory does not exist
@@ -163,7 +356,18 @@ fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Re
             !Path::new(&mise_path()).exists() && Path::new(&data_home()).join("asdf").exists();
 
         (true, should_migrate)
+    } else if let MiseBootstrapAction::Use(MiseBinaryCandidate {
+        path,
+        managed: false,
+    }) = action
+    {
+        // A mise binary that is not managed by omni is never updated
+        // by omni, we only need to make sure it gets used
+        select_mise_bin(Some(&path));
+        return Ok(());
     } else if cache.should_update_mise() {
+        select_mise_bin(None);
+
         // Run `mise --version` to check if mise has an update available
         let mut command = mise_sync_command();
         command.arg("--version");
@@ -174,7 +378,11 @@ fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Re
         // Get the current version of mise, which is the first word in stdout
         let stdout = String::from_utf8_lossy(&output.stdout);
         let current_version = stdout.split_whitespace().next().unwrap_or_default();
-        let wanted_version = &global_config().up_command.mise_version;
+        if let Err(err) = cache.set_managed_mise_version(current_version) {
+            progress_handler.progress(format!("failed to record mise version: {err}"));
+        }
+
+        let wanted_version = &up_command.mise_version;
         let version_up_to_date = wanted_version != "latest"
             && VersionMatcher::new(wanted_version)
                 .prefix(true)
@@ -196,12 +404,13 @@ fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Re
             return Ok(());
         }
     } else {
+        select_mise_bin(None);
         return Ok(());
     };
 
     let gh_release = UpConfigGithubRelease::new_immutable_with_version(
         "jdx/mise",
-        &global_config().up_command.mise_version,
+        &up_command.mise_version,
         true, // We force the upgrade here, since we want to make sure we get the
               // the latest version of mise that satisfies the version constraint
     );
@@ -233,11 +442,12 @@ fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Re
     }
 
     // Create the directory for the mise binary
-    let mise_bin_dest = Path::new(mise_bin());
-    if let Err(err) = std::fs::create_dir_all(
+    let mise_bin_dest = Path::new(managed_mise_bin());
+    if let Err(err) = create_dir_all(
         mise_bin_dest
             .parent()
             .expect("failed to get parent of mise binary"),
+        PathKind::Shared,
     ) {
         let errmsg = format!("failed to create mise binary directory: {err}");
         if fail_on_error {
@@ -250,8 +460,19 @@ fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Re
         }
     }
 
-    // Now copy the mise binary to the correct location
-    if let Err(err) = std::fs::copy(&install_bin, mise_bin_dest) {
+    // Now copy the mise binary to the correct location, through a staging
+    // file so that the binary in use is never left halfway copied
+    let mise_bin_staging = mise_bin_dest.with_file_name(format!(
+        "{STAGING_DIR_PREFIX}{}",
+        mise_bin_dest
+            .file_name()
+            .expect("failed to get name of mise binary")
+            .to_string_lossy()
+    ));
+    if let Err(err) = std::fs::copy(&install_bin, &mise_bin_staging)
+        .and_then(|_| std::fs::rename(&mise_bin_staging, mise_bin_dest))
+    {
+        let _ = std::fs::remove_file(&mise_bin_staging);
         let errmsg = format!("failed to copy mise binary: {err}");
         if fail_on_error {
             return Err(UpError::Exec(errmsg));
@@ -267,6 +488,15 @@ fn install_mise(options: &UpOptions, progress_handler: &UpProgressHandler) -> Re
         ));
     }
 
+    // Record the version that was installed, so that the managed binary
+    // does not need to be called to check its version
+    let installed_version = gh_release.version().unwrap_or_default();
+    if !installed_version.is_empty() {
+        if let Err(err) = cache.set_managed_mise_version(&installed_version) {
+            progress_handler.progress(format!("failed to record mise version: {err}"));
+        }
+    }
+
     if migrate_from_asdf {
         if let Err(err) = migrate_asdf_to_mise() {
             progress_handler.progress(format!("failed to migrate from asdf to mise: {err}"));
@@ -362,10 +592,10 @@ fn migrate_asdf_to_mise() -> Result<(), UpError> {
     // Finally, create the shims
     let asdf_shims = asdf_path.join("shims");
     let mise_shims = mise_path.join("shims");
-    let mise_bin = Path::new(mise_bin());
+    let mise_bin = Path::new(managed_mise_bin());
 
     if !mise_shims.exists() {
-        if let Err(err) = std::fs::create_dir_all(&mise_shims) {
+        if let Err(err) = create_dir_all(&mise_shims, PathKind::Shared) {
             return Err(UpError::Exec(format!(
                 "failed to create mise shims directory: {err}"
             )));
@@ -1336,6 +1566,60 @@ impl UpConfigMise {
         self.requested_tool.clone()
     }
 
+    /// Returns the key identifying the installation of the requested
+    /// tool version, so that identical installations required by multiple
+    /// work directories can be identified; versions detected from the
+    /// work directory do not have a key since they are only known once
+    /// the tool is being upped
+    pub fn install_key(&self) -> Option<String> {
+        if self.version == "auto" {
+            return None;
+        }
+
+        let backend = match &self.backend {
+            Some(backend) => format!("{backend}:"),
+            None => "".to_string(),
+        };
+
+        Some(format!("mise:{}{}@{}", backend, self.name(), self.version))
+    }
+
+    /// Returns why the tool is not allowed by the given operations policy,
+    /// if it is not; this only checks what is known before resolving the
+    /// tool, the backend and source resolved from the registry are
+    /// checked when installing it
+    pub fn disallowed_reason(&self, operations: &UpCommandOperationConfig) -> Option<String> {
+        if !operations.is_operation_allowed("mise") {
+            return Some(format!(
+                "mise operations ({}) are not allowed",
+                self.requested_tool
+            ));
+        }
+
+        if let Some(backend) = &self.backend {
+            if !operations.is_mise_backend_allowed(backend) {
+                return Some(format!(
+                    "mise backend {} not allowed for tool {}",
+                    backend, self.requested_tool
+                ));
+            }
+        }
+
+        if let Some(url) = &self.override_tool_url {
+            if !operations.is_mise_backend_allowed("custom") {
+                return Some("cannot use custom URLs for tool installations".to_string());
+            }
+
+            if !operations.is_mise_source_allowed(url) {
+                return Some(format!(
+                    "cannot use URL {url} as a source for tool installations"
+                ));
+            }
+        }
+
+        None
+    }
+
     fn update_cache(
         &self,
         environment: &mut UpEnvironment,
@@ -1778,9 +2062,7 @@ impl UpConfigMise {
         // in the directory initially
         if tool_config.setup_data_paths() {
             // Get the data path for the work directory
-            let workdir = workdir(".");
-
-            let data_path = match workdir.data_path() {
+            let data_path = match options.tools_data_path() {
                 Some(data_path) => data_path,
                 None => {
                     return Err(UpError::Exec(format!(
@@ -1791,15 +2073,18 @@ impl UpConfigMise {
             };
 
             // Add data paths
+            let data_paths_policy = config(".").up_command.data_paths;
             let normalized_name = fqtn.normalized_plugin_name()?;
             for (version, dirs) in versions.iter() {
                 for dir in dirs.iter() {
-                    let version_dir = data_path_dir_hash(dir);
-
-                    let path = data_path
-                        .join(&normalized_name)
-                        .join(version)
-                        .join(&version_dir);
+                    let path = version_data_path(
+                        data_paths_policy,
+                        &data_path,
+                        &normalized_name,
+                        version,
+                        dir,
+                    );
+                    setup_version_data_path(data_paths_policy, &path)?;
 
                     if !environment.add_version_data_path(
                         &normalized_name,
@@ -2103,12 +2388,47 @@ impl UpConfigMise {
             }
         }
 
+        let version = self.resolve_available_version(options, progress_handler, versions)?;
+
+        // Try installing the version found
+        let mut install_version = self.install_version(&version, options, progress_handler);
+        if install_version.is_err() && !options.fail_on_upgrade {
+            // If we get here and there is an issue installing the version,
+            // list all installed versions and check if one of those could
+            // fit the requirement, in which case we can fallback to it
+            let installed_versions = self.list_installed_versions_from_plugin(progress_handler)?;
+            match self.resolve_version(&installed_versions) {
+                Ok(installed_version) => {
+                    progress_handler.progress(format!(
+                        "falling back to installed version {}",
+                        installed_version.light_yellow()
+                    ));
+                    install_version =
+                        self.install_version(&installed_version, options, progress_handler);
+                }
+                Err(_err) => {}
+            }
+        }
+
+        install_version
+    }
+
+    /// Resolves the version matching the requested version among the
+    /// available versions, using the given list of versions if any; the
+    /// list is refreshed if it comes from the cache and has no match
+    fn resolve_available_version(
+        &self,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+        versions: Option<MisePluginVersions>,
+    ) -> Result<String, UpError> {
         let versions = match versions {
             Some(versions) => versions,
             None => self.list_versions(options, progress_handler)?,
         };
-        let version = match self.resolve_version(&versions) {
-            Ok(available_version) => available_version,
+
+        match self.resolve_version(&versions) {
+            Ok(available_version) => Ok(available_version),
             Err(err) => {
                 // If the versions are not fresh of now, and we failed to
                 // resolve the version, we should try to refresh the
@@ -2126,35 +2446,13 @@ impl UpConfigMise {
 
                     self.resolve_version(&versions).inspect_err(|err| {
                         progress_handler.error_with_message(err.message());
-                    })?
+                    })
                 } else {
                     progress_handler.error_with_message(err.message());
-                    return Err(err);
-                }
-            }
-        };
-
-        // Try installing the version found
-        let mut install_version = self.install_version(&version, options, progress_handler);
-        if install_version.is_err() && !options.fail_on_upgrade {
-            // If we get here and there is an issue installing the version,
-            // list all installed versions and check if one of those could
-            // fit the requirement, in which case we can fallback to it
-            let installed_versions = self.list_installed_versions_from_plugin(progress_handler)?;
-            match self.resolve_version(&installed_versions) {
-                Ok(installed_version) => {
-                    progress_handler.progress(format!(
-                        "falling back to installed version {}",
-                        installed_version.light_yellow()
-                    ));
-                    install_version =
-                        self.install_version(&installed_version, options, progress_handler);
+                    Err(err)
                 }
-                Err(_err) => {}
             }
         }
-
-        install_version
     }
 
     fn install_version(
@@ -2189,6 +2487,20 @@ impl UpConfigMise {
             true
         };
 
+        // Store the files of the version in the content store once
+        // installed, or again if a previous attempt was interrupted
+        if global_config().up_command.operations.mise.content_store {
+            let version_path = mise_tool_path(&self.normalized_plugin_name()?, version);
+            if installed || ContentStore::has_leftover_links(&version_path) {
+                if let Err(err) = ContentStore::get().materialize(&version_path) {
+                    progress_handler.progress(format!(
+                        "failed to store {} {} in the content store: {}",
+                        tool, version, err
+                    ));
+                }
+            }
+        }
+
         self.actual_version.set(version.to_string()).map_err(|_| {
             let errmsg = "failed to set actual version".to_string();
             UpError::Exec(errmsg)
@@ -2227,14 +2539,17 @@ impl UpConfigMise {
             Err(_err) => return vec![],
         };
 
+        let data_paths_policy = config(".").up_command.data_paths;
         let mut data_paths = BTreeSet::new();
-        let tool_data_path = wd_data_path.join(&plugin_name);
         for (version, dirs) in dirs_per_version.iter() {
-            let version_data_path = tool_data_path.join(version);
-
             for dir in dirs {
-                let hashed_dir = data_path_dir_hash(dir);
-                data_paths.insert(version_data_path.join(&hashed_dir));
+                data_paths.insert(version_data_path(
+                    data_paths_policy,
+                    wd_data_path,
+                    &plugin_name,
+                    version,
+                    dir,
+                ));
             }
         }
 
@@ -2244,6 +2559,40 @@ impl UpConfigMise {
         data_paths.into_iter().collect()
     }
 
+    /// Resolves the version of the tool matching the requested version
+    /// among the versions available through mise, without installing it
+    pub fn resolve_tool_version(
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let mise = Self::new(tool, version, BTreeSet::new(), false);
+
+        install_mise(options, progress_handler)?;
+        mise.install_plugin(progress_handler)?;
+        mise.resolve_available_version(options, progress_handler, None)
+    }
+
+    /// Installs the version of the tool matching the requested version
+    /// through mise, and records it in the environment; returns the
+    /// installed version
+    pub fn install_tool_version(
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let mise_tool = UpConfigTool::Mise(Self::new(tool, version, BTreeSet::new(), false));
+        mise_tool.up(options, environment, progress_handler)?;
+
+        match mise_tool {
+            UpConfigTool::Mise(mise) => mise.version(),
+            _ => unreachable!("mise_tool is not a mise tool"),
+        }
+    }
+
     pub fn cleanup(progress_handler: &dyn ProgressHandler) -> Result<Option<String>, UpError> {
         let mut uninstalled = Vec::new();
 
@@ -2270,10 +2619,24 @@ impl UpConfigMise {
                     uninstalled.push(format!("{tool}:{version}"));
                 }
 
+                // The data path shared across work directories for that
+                // tool version cannot be used anymore
+                remove_shared_data_path(tool, version).map_err(|err| {
+                    progress_handler.error_with_message(format!(
+                        "failed to remove shared data path for {tool} {version}"
+                    ));
+                    CacheManagerError::Other(err.to_string())
+                })?;
+
                 Ok(())
             })
             .map_err(|err| UpError::Cache(err.to_string()))?;
 
+        // Remove the staging directories left behind by shared data
+        // paths that were interrupted while being set up
+        sweep_staging_dirs(shared_data_home(), 2, STAGING_DIR_MAX_AGE)
+            .map_err(|err| UpError::Exec(format!("failed to sweep shared data paths: {err}")))?;
+
         if uninstalled.is_empty() {
             Ok(None)
         } else {
@@ -2514,6 +2877,7 @@ pub struct MiseToolUpVersion {
 /// take care of tasks, or if there are special configurations to take
 /// care of.
 enum PerToolConfig {
+    Bun,
     Go,
     Nodejs,
     Ruby,
@@ -2525,6 +2889,7 @@ enum PerToolConfig {
 impl PerToolConfig {
     fn new(tool_name: &str) -> Self {
         match tool_name {
+            "bun" => Self::Bun,
             "go" => Self::Go,
             "node" => Self::Nodejs,
             "ruby" => Self::Ruby,
@@ -2538,7 +2903,7 @@ impl PerToolConfig {
     /// these can be used to isolate installation paths for tools that allow the
     /// installation of binaries (e.g. `gem install`, `cargo install`, etc.)
     fn setup_data_paths(&self) -> bool {
-        matches!(self, Self::Helm | Self::Ruby | Self::Rust)
+        matches!(self, Self::Bun | Self::Helm | Self::Ruby | Self::Rust)
     }
 
     /// post_install performs post-installation operations for the tool
@@ -2591,3 +2956,7 @@ fn post_install_ruby(
 
     Ok(())
 }
+
+#[cfg(test)]
+#[path = "mise_test.rs"]
+mod tests;
@@ -0,0 +1,120 @@
+use super::*;
+
+fn candidates() -> Vec<MiseBinaryCandidate> {
+    vec![
+        MiseBinaryCandidate {
+            path: PathBuf::from("/omni/mise/bin/mise"),
+            managed: true,
+        },
+        MiseBinaryCandidate {
+            path: PathBuf::from("/usr/bin/mise"),
+            managed: false,
+        },
+    ]
+}
+
+/// Returns a version check stub answering the given version for each
+/// of the candidates, in order
+fn versions(versions: &[Option<&str>]) -> impl Fn(&MiseBinaryCandidate) -> Option<String> {
+    let versions = candidates()
+        .into_iter()
+        .zip(versions.iter().map(|version| version.map(str::to_string)))
+        .collect::<Vec<_>>();
+
+    move |candidate| {
+        versions
+            .iter()
+            .find(|(c, _)| c == candidate)
+            .and_then(|(_, version)| version.clone())
+    }
+}
+
+mod check_mise_binary {
+    use super::*;
+
+    #[test]
+    fn present_and_new_enough() {
+        let status = check_mise_binary(
+            &candidates(),
+            "2024.1.0",
+            versions(&[Some("2025.1.6"), None]),
+        );
+        assert_eq!(status, MiseBinaryStatus::Ready(candidates()[0].clone()));
+
+        let action = mise_bootstrap_action(status, "2024.1.0", false).unwrap();
+        assert_eq!(action, MiseBootstrapAction::Use(candidates()[0].clone()));
+    }
+
+    #[test]
+    fn managed_binary_preferred() {
+        let status = check_mise_binary(
+            &candidates(),
+            "2024.1.0",
+            versions(&[Some("2025.1.6"), Some("2025.2.0")]),
+        );
+        assert_eq!(status, MiseBinaryStatus::Ready(candidates()[0].clone()));
+    }
+
+    #[test]
+    fn falls_back_to_binary_in_path() {
+        let status = check_mise_binary(
+            &candidates(),
+            "2024.1.0",
+            versions(&[Some("2023.12.0"), Some("2025.2.0")]),
+        );
+        assert_eq!(status, MiseBinaryStatus::Ready(candidates()[1].clone()));
+    }
+
+    #[test]
+    fn outdated() {
+        let status = check_mise_binary(
+            &candidates(),
+            "2024.1.0",
+            versions(&[Some("2023.12.0"), Some("2023.1.0")]),
+        );
+        assert_eq!(
+            status,
+            MiseBinaryStatus::Outdated(candidates()[0].clone(), "2023.12.0".to_string())
+        );
+
+        let action = mise_bootstrap_action(status.clone(), "2024.1.0", true).unwrap();
+        assert!(matches!(action, MiseBootstrapAction::Install(_)));
+
+        let err = mise_bootstrap_action(status, "2024.1.0", false).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("older than the minimum version 2024.1.0"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn missing_with_bootstrap() {
+        let status = check_mise_binary(&candidates(), "2024.1.0", versions(&[None, None]));
+        assert_eq!(status, MiseBinaryStatus::Missing);
+
+        let action = mise_bootstrap_action(status, "2024.1.0", true).unwrap();
+        assert_eq!(
+            action,
+            MiseBootstrapAction::Install("installing mise".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_with_bootstrap_forbidden() {
+        let status = check_mise_binary(&candidates(), "2024.1.0", versions(&[None, None]));
+        assert_eq!(status, MiseBinaryStatus::Missing);
+
+        let err = mise_bootstrap_action(status, "2024.1.0", false).unwrap_err();
+        assert!(
+            err.to_string().contains("bootstrapping mise is disabled"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn no_minimum_version() {
+        let status = check_mise_binary(&candidates(), "", versions(&[Some("2020.1.0"), None]));
+        assert_eq!(status, MiseBinaryStatus::Ready(candidates()[0].clone()));
+    }
+}
@@ -1,3 +1,5 @@
+pub(crate) mod advisories;
+
 pub(crate) mod base;
 pub(crate) use base::UpConfig;
 
@@ -7,6 +9,9 @@ pub(crate) use options::UpOptions;
 pub(crate) mod tool;
 pub(crate) use tool::UpConfigTool;
 
+pub(crate) mod bootstrap;
+pub(crate) use bootstrap::UpConfigBootstrap;
+
 pub(crate) mod bundler;
 pub(crate) use bundler::UpConfigBundler;
 
@@ -32,6 +37,9 @@ pub(crate) use nix::UpConfigNix;
 pub(crate) mod nodejs;
 pub(crate) use nodejs::UpConfigNodejs;
 
+pub(crate) mod pipx;
+pub(crate) use pipx::UpConfigPipxInstalls;
+
 pub(crate) mod python;
 pub(crate) use python::UpConfigPython;
 
@@ -47,4 +55,9 @@ pub(crate) use mise::UpConfigMiseParams;
 pub(crate) mod error;
 pub(crate) use error::UpError;
 
+pub(crate) mod selection;
+pub(crate) use selection::UpOperationSelection;
+
 pub(crate) mod utils;
+
+pub(crate) mod workspace;
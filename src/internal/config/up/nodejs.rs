@@ -248,7 +248,7 @@ fn remove_mise_reshim_from_bin(
 }
 
 fn setup_individual_npm_prefix(
-    _options: &UpOptions,
+    options: &UpOptions,
     environment: &mut UpEnvironment,
     progress_handler: &UpProgressHandler,
     args: &PostInstallFuncArgs,
@@ -261,9 +261,7 @@ fn setup_individual_npm_prefix(
     }
 
     // Get the data path for the work directory
-    let workdir = workdir(".");
-
-    let data_path = match workdir.data_path() {
+    let data_path = match options.tools_data_path() {
         Some(data_path) => data_path,
         None => {
             return Err(UpError::Exec(format!(
@@ -294,6 +292,13 @@ fn setup_individual_npm_prefix(
         }
     }
 
+    // The packages of the work directory are not installed when the
+    // tools are set up outside of its data path
+    if options.data_path.is_some() {
+        return Ok(());
+    }
+
+    let workdir = workdir(".");
     let workdir_root = match workdir.root() {
         Some(workdir_root) => workdir_root,
         None => {
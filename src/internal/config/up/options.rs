@@ -1,6 +1,13 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::internal::config::up::utils::UpEventWriter;
+use crate::internal::config::up::utils::UpSummaryWriter;
+use crate::internal::workdir;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpOptions<'a> {
     pub commit_sha: Option<String>,
@@ -8,8 +15,17 @@ pub struct UpOptions<'a> {
     pub write_cache: bool,
     pub fail_on_upgrade: bool,
     pub upgrade: bool,
+    pub strict: bool,
+    pub record_history: bool,
+    pub data_path: Option<PathBuf>,
     #[serde(skip)]
     pub lock_file: Option<&'a std::fs::File>,
+    #[serde(skip)]
+    pub events: Option<&'a UpEventWriter>,
+    #[serde(skip)]
+    pub summary: Option<&'a UpSummaryWriter>,
+    #[serde(skip)]
+    pub only: Option<&'a BTreeSet<usize>>,
 }
 
 impl Default for UpOptions<'_> {
@@ -20,7 +36,13 @@ impl Default for UpOptions<'_> {
             write_cache: true,
             fail_on_upgrade: false,
             upgrade: false,
+            strict: false,
+            record_history: false,
+            data_path: None,
             lock_file: None,
+            events: None,
+            summary: None,
+            only: None,
         }
     }
 }
@@ -56,8 +78,52 @@ impl<'a> UpOptions<'a> {
         self
     }
 
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Sets the directory under which the data paths of the tools get
+    /// created, instead of the data path of the current work directory
+    pub fn data_path(mut self, data_path: PathBuf) -> Self {
+        self.data_path = Some(data_path);
+        self
+    }
+
     pub fn lock_file(mut self, lock_file: &'a std::fs::File) -> Self {
         self.lock_file = Some(lock_file);
         self
     }
+
+    pub fn events(mut self, events: &'a UpEventWriter) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn summary(mut self, summary: &'a UpSummaryWriter) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    /// Restricts the run to the steps at the given indices, among the
+    /// available steps of the configuration
+    pub fn only(mut self, only: &'a BTreeSet<usize>) -> Self {
+        self.only = Some(only);
+        self
+    }
+
+    /// Returns the directory under which the data paths of the tools
+    /// get created: the one set through the options if any, or else the
+    /// data path of the current work directory
+    pub fn tools_data_path(&self) -> Option<PathBuf> {
+        match &self.data_path {
+            Some(data_path) => Some(data_path.clone()),
+            None => workdir(".").data_path().cloned(),
+        }
+    }
 }
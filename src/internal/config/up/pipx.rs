@@ -0,0 +1,1357 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use blake3::Hasher;
+use itertools::Itertools;
+use normalize_path::NormalizePath;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::process::Command as TokioCommand;
+
+use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::cache::up_environments::UpVersionParams;
+use crate::internal::cache::utils as cache_utils;
+use crate::internal::cache::PipxOperationCache;
+use crate::internal::config::config;
+use crate::internal::config::global_config;
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::UpCommandOperationConfig;
+use crate::internal::config::up::mise_tool_path;
+use crate::internal::config::up::utils::cleanup_path;
+use crate::internal::config::up::utils::install_lock_path;
+use crate::internal::config::up::utils::is_installed;
+use crate::internal::config::up::utils::progress_handler::ProgressHandler;
+use crate::internal::config::up::utils::run_command_with_handler;
+use crate::internal::config::up::utils::staging::STAGING_DIR_MAX_AGE;
+use crate::internal::config::up::utils::sweep_staging_dirs;
+use crate::internal::config::up::utils::InstallLock;
+use crate::internal::config::up::utils::InstallMarker;
+use crate::internal::config::up::utils::InstallMarkerAsset;
+use crate::internal::config::up::utils::InstallStaging;
+use crate::internal::config::up::utils::RunConfig;
+use crate::internal::config::up::utils::UpProgressHandler;
+use crate::internal::config::up::UpError;
+use crate::internal::config::up::UpOptions;
+use crate::internal::config::ConfigValue;
+use crate::internal::env::data_home;
+use crate::internal::user_interface::StringColor;
+use crate::internal::utils::base62_encode;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+
+cfg_if::cfg_if! {
+    if #[cfg(test)] {
+        fn pipx_bin_path() -> PathBuf {
+            PathBuf::from(data_home()).join("pipx")
+        }
+    } else {
+        use once_cell::sync::Lazy;
+
+        static PIPX_BIN_PATH: Lazy<PathBuf> = Lazy::new(|| PathBuf::from(data_home()).join("pipx"));
+
+        fn pipx_bin_path() -> PathBuf {
+            PIPX_BIN_PATH.clone()
+        }
+    }
+}
+
+/// The interpreter used to create the isolated environments when the
+/// work directory does not pin a python version through mise, and no
+/// interpreter is configured for the package
+const DEFAULT_PYTHON: &str = "python3";
+
+/// The maximum number of lines of the output of a failed command that
+/// are reported in the error
+const ERROR_OUTPUT_MAX_LINES: usize = 20;
+
+/// The sections of the entry points of a package that define the
+/// scripts it provides
+const SCRIPT_ENTRY_POINT_SECTIONS: &[&str] = &["console_scripts", "gui_scripts"];
+
+pub fn pipx_tool_path(package: &str, install_id: &str) -> PathBuf {
+    pipx_bin_path().join(package).join(install_id)
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct UpConfigPipxInstalls {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    packages: Vec<UpConfigPipxInstall>,
+}
+
+impl Serialize for UpConfigPipxInstalls {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.packages.len() {
+            0 => serializer.serialize_none(),
+            1 => serializer.serialize_newtype_struct("UpConfigPipxInstalls", &self.packages[0]),
+            _ => serializer.collect_seq(self.packages.iter()),
+        }
+    }
+}
+
+impl UpConfigPipxInstalls {
+    pub fn from_config_value(
+        config_value: Option<&ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => {
+                error_handler.error(ConfigErrorKind::EmptyKey);
+                return Self::default();
+            }
+        };
+
+        if config_value.as_str_forced().is_some() {
+            return Self {
+                packages: vec![UpConfigPipxInstall::from_config_value(
+                    Some(config_value),
+                    error_handler,
+                )],
+            };
+        }
+
+        if let Some(array) = config_value.as_array() {
+            return Self {
+                packages: array
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, config_value)| {
+                        UpConfigPipxInstall::from_config_value(
+                            Some(config_value),
+                            &error_handler.with_index(idx),
+                        )
+                    })
+                    .collect(),
+            };
+        }
+
+        if let Some(table) = config_value.as_table() {
+            // Check if there is a 'package' key, in which case it's a single
+            // package and we can just parse it and return it
+            if table.contains_key("package") {
+                return Self {
+                    packages: vec![UpConfigPipxInstall::from_config_value(
+                        Some(config_value),
+                        error_handler,
+                    )],
+                };
+            }
+
+            // Otherwise, we have a table of packages, where packages are
+            // the keys and the values are the configuration for the package;
+            // we want to go over them in lexico-graphical order to ensure that
+            // the order is consistent
+            let mut packages = Vec::new();
+            for package_str in table.keys().sorted() {
+                let value = table.get(package_str).expect("package config not found");
+                let package = match ConfigValue::from_str(package_str) {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+
+                let mut package_config = if let Some(table) = value.as_table() {
+                    table.clone()
+                } else if let Some(version) = value.as_str_forced() {
+                    let mut package_config = HashMap::new();
+                    let value = match ConfigValue::from_str(&version) {
+                        Ok(value) => value,
+                        Err(_) => continue,
+                    };
+                    package_config.insert("version".to_string(), value);
+                    package_config
+                } else {
+                    HashMap::new()
+                };
+
+                package_config.insert("package".to_string(), package.clone());
+                packages.push(UpConfigPipxInstall::from_table(
+                    &package_config,
+                    &error_handler.with_key(package_str),
+                ));
+            }
+
+            if packages.is_empty() {
+                error_handler.error(ConfigErrorKind::EmptyKey);
+            }
+
+            return Self { packages };
+        }
+
+        error_handler
+            .with_expected(vec!["string", "array", "table"])
+            .with_actual(config_value)
+            .error(ConfigErrorKind::InvalidValueType);
+
+        UpConfigPipxInstalls::default()
+    }
+
+    /// Returns the runtimes that need to be available in the environment
+    /// for the packages to be installed: the packages that do not specify
+    /// a python interpreter are installed with the python of the environment
+    pub fn required_runtimes(&self) -> Vec<&'static str> {
+        if self.packages.iter().any(|package| package.python.is_none()) {
+            vec!["python"]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Returns why this operation is not allowed by the given operations
+    /// policy, if it is not, so that it can be refused before installing
+    /// any of the packages
+    pub fn disallowed_reason(&self, operations: &UpCommandOperationConfig) -> Option<String> {
+        if !operations.is_operation_allowed("pipx") {
+            return Some("pipx operation is not allowed".to_string());
+        }
+
+        None
+    }
+
+    pub fn up(
+        &self,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<(), UpError> {
+        if self.packages.len() == 1 {
+            progress_handler.init(self.packages[0].desc().light_blue());
+        } else {
+            progress_handler.init("pipx:".light_blue());
+            if self.packages.is_empty() {
+                progress_handler.error_with_message("no package".to_string());
+                return Err(UpError::Config("at least one package required".to_string()));
+            }
+        }
+
+        if !global_config()
+            .up_command
+            .operations
+            .is_operation_allowed("pipx")
+        {
+            let errmsg = "pipx operation is not allowed".to_string();
+            progress_handler.error_with_message(errmsg.clone());
+            return Err(UpError::Config(errmsg));
+        }
+
+        let num = self.packages.len();
+        for (idx, tool) in self.packages.iter().enumerate() {
+            let subhandler = if self.packages.len() == 1 {
+                progress_handler
+            } else {
+                &progress_handler.subhandler(
+                    &format!(
+                        "[{current:padding$}/{total:padding$}] {tool} ",
+                        current = idx + 1,
+                        total = num,
+                        padding = format!("{num}").len(),
+                        tool = tool.desc(),
+                    )
+                    .light_yellow(),
+                )
+            };
+            tool.up(options, environment, subhandler)
+                .inspect_err(|_err| {
+                    progress_handler.error();
+                })?;
+        }
+
+        if self.packages.len() != 1 {
+            progress_handler.success_with_message(self.get_up_message());
+        }
+
+        Ok(())
+    }
+
+    pub fn commit(&self, options: &UpOptions, env_version_id: &str) -> Result<(), UpError> {
+        for tool in &self.packages {
+            if tool.was_upped() {
+                tool.commit(options, env_version_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn was_upped(&self) -> bool {
+        self.packages.iter().any(|tool| tool.was_upped())
+    }
+
+    fn get_up_message(&self) -> String {
+        let count: HashMap<PipxInstallHandled, usize> = self
+            .packages
+            .iter()
+            .map(|tool| tool.handling())
+            .fold(HashMap::new(), |mut map, item| {
+                *map.entry(item).or_insert(0) += 1;
+                map
+            });
+        let handled: Vec<String> = self
+            .packages
+            .iter()
+            .filter_map(|tool| match tool.handling() {
+                PipxInstallHandled::Handled | PipxInstallHandled::Noop => {
+                    Some(tool.package_version())
+                }
+                _ => None,
+            })
+            .sorted()
+            .collect();
+
+        if handled.is_empty() {
+            return "nothing done".to_string();
+        }
+
+        let mut numbers = vec![];
+
+        if let Some(count) = count.get(&PipxInstallHandled::Handled) {
+            numbers.push(format!("{count} installed").green());
+        }
+
+        if let Some(count) = count.get(&PipxInstallHandled::Noop) {
+            numbers.push(format!("{count} already installed").light_black());
+        }
+
+        if numbers.is_empty() {
+            return "nothing done".to_string();
+        }
+
+        format!(
+            "{} {}",
+            numbers.join(", "),
+            format!("({})", handled.join(", ")).light_black().italic(),
+        )
+    }
+
+    pub fn down(&self, _progress_handler: &UpProgressHandler) -> Result<(), UpError> {
+        Ok(())
+    }
+
+    /// Installs the package with the requested version in an isolated
+    /// environment, and records it in the environment; returns the id
+    /// of the installation, which is the version recorded for it
+    pub fn install_tool_version(
+        package: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        let installs = Self {
+            packages: vec![UpConfigPipxInstall {
+                package: package.to_string(),
+                version: Some(version.to_string()),
+                ..UpConfigPipxInstall::default()
+            }],
+        };
+
+        installs.up(options, environment, progress_handler)?;
+        match installs.packages[0].install_id.get() {
+            Some(install_id) => Ok(install_id.clone()),
+            None => Err(UpError::Exec("installation id not set".to_string())),
+        }
+    }
+
+    pub fn cleanup(progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        progress_handler.init("pipx:".light_blue());
+
+        let cache = PipxOperationCache::get();
+
+        // Cleanup removable installations from the database
+        cache.cleanup().map_err(|err| {
+            let msg = format!("failed to cleanup pipx cache: {err}");
+            progress_handler.progress(msg.clone());
+            UpError::Cache(msg)
+        })?;
+
+        // List installations that should exist
+        let expected_installs = cache.list_installed().map_err(|err| {
+            let msg = format!("failed to list pipx-installed packages: {err}");
+            progress_handler.progress(msg.clone());
+            UpError::Cache(msg)
+        })?;
+
+        let mut expected_paths = expected_installs
+            .iter()
+            .map(|install| pipx_tool_path(&install.package, &install.install_id))
+            .collect::<Vec<PathBuf>>();
+
+        // Keep the lock files of the installations that should exist
+        let lock_paths = expected_paths
+            .iter()
+            .filter_map(|path| install_lock_path(path).ok())
+            .collect::<Vec<_>>();
+        expected_paths.extend(lock_paths);
+
+        // Remove the staging directories left behind by installations
+        // that were interrupted, but keep the recent ones as they might
+        // be in use by an installation in progress
+        let (_, staging_paths) = sweep_staging_dirs(pipx_bin_path(), 2, STAGING_DIR_MAX_AGE)
+            .map_err(|err| {
+                let msg = format!("failed to sweep pipx staging directories: {err}");
+                progress_handler.progress(msg.clone());
+                UpError::Exec(msg)
+            })?;
+        expected_paths.extend(staging_paths);
+
+        let (root_removed, num_removed, removed_paths) =
+            cleanup_path(pipx_bin_path(), expected_paths, progress_handler, true)?;
+
+        if root_removed {
+            return Ok(Some("removed all pipx-installed packages".to_string()));
+        }
+
+        if num_removed == 0 {
+            return Ok(None);
+        }
+
+        // We want to go over the paths that were removed to
+        // return a proper message about the packages that
+        // were removed
+        let removed_packages = removed_paths
+            .iter()
+            .filter_map(|path| {
+                let rest_of_path = path.strip_prefix(pipx_bin_path()).ok()?;
+                let package = rest_of_path.components().next()?;
+                Some(package.as_os_str().to_string_lossy().to_string())
+            })
+            .unique()
+            .map(|package| package.light_yellow())
+            .collect::<Vec<_>>();
+
+        if removed_packages.is_empty() {
+            return Ok(Some(format!(
+                "removed {} pipx installation{}",
+                num_removed.light_yellow(),
+                if num_removed > 1 { "s" } else { "" }
+            )));
+        }
+
+        Ok(Some(format!("removed {}", removed_packages.join(", "))))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+pub enum PipxInstallHandled {
+    Handled,
+    Noop,
+    Unhandled,
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum PipxInstallError {
+    #[error("invalid package: {0}")]
+    InvalidPackage(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct UpConfigPipxInstall {
+    /// The name of the package to install
+    pub package: String,
+
+    /// The version of the package to install; this can be an exact
+    /// version, or a version specifier (e.g. `>=1.2,<2`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// The URL of the git repository to install the package from,
+    /// instead of the package index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+
+    /// The extras of the package to install
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extras: Vec<String>,
+
+    /// The python interpreter to use to create the isolated environment
+    /// when the work directory does not pin a python version through mise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub python: Option<String>,
+
+    /// Whether to always reinstall the package when the version is not
+    /// pinned, so that the latest matching version is used
+    #[serde(default, skip_serializing_if = "cache_utils::is_false")]
+    pub upgrade: bool,
+
+    /// A list of directories to make the console scripts available for
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub dirs: BTreeSet<String>,
+
+    /// In case there was an error while parsing the configuration, this field
+    /// will contain the error message
+    #[serde(default, skip)]
+    config_error: Option<String>,
+
+    #[serde(default, skip)]
+    install_id: OnceCell<String>,
+
+    #[serde(default, skip)]
+    was_handled: OnceCell<PipxInstallHandled>,
+}
+
+impl Default for UpConfigPipxInstall {
+    fn default() -> Self {
+        UpConfigPipxInstall {
+            package: "".to_string(),
+            version: None,
+            git: None,
+            extras: vec![],
+            python: None,
+            upgrade: false,
+            dirs: BTreeSet::new(),
+            config_error: None,
+            install_id: OnceCell::new(),
+            was_handled: OnceCell::new(),
+        }
+    }
+}
+
+impl UpConfigPipxInstall {
+    pub fn from_config_value(
+        config_value: Option<&ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = match config_value {
+            Some(config_value) => config_value,
+            None => {
+                return Self {
+                    config_error: Some("no configuration provided".to_string()),
+                    ..Default::default()
+                }
+            }
+        };
+
+        if let Some(table) = config_value.as_table() {
+            Self::from_table(&table, error_handler)
+        } else if let Some(package) = config_value.as_str_forced() {
+            match parse_pipx_package(&package) {
+                Ok(spec) => spec,
+                Err(err) => {
+                    error_handler
+                        .with_context("error", err.to_string())
+                        .with_actual(package.clone())
+                        .error(ConfigErrorKind::ParsingError);
+
+                    Self {
+                        package,
+                        config_error: Some(err.to_string()),
+                        ..Default::default()
+                    }
+                }
+            }
+        } else {
+            Self {
+                config_error: Some("no package provided".to_string()),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn from_table(
+        table: &HashMap<String, ConfigValue>,
+        error_handler: &ConfigErrorHandler,
+    ) -> Self {
+        let config_value = ConfigValue::from_table(table.clone());
+
+        let package = match table.get("package") {
+            Some(package) => {
+                if let Some(package) = package.as_str_forced() {
+                    package.to_string()
+                } else {
+                    error_handler
+                        .with_key("package")
+                        .with_expected("string")
+                        .with_actual(package)
+                        .error(ConfigErrorKind::InvalidValueType);
+                    return UpConfigPipxInstall {
+                        config_error: Some("package must be a string".to_string()),
+                        ..Default::default()
+                    };
+                }
+            }
+            None => {
+                error_handler
+                    .with_key("package")
+                    .error(ConfigErrorKind::MissingKey);
+
+                return UpConfigPipxInstall {
+                    config_error: Some("package is required".to_string()),
+                    ..Default::default()
+                };
+            }
+        };
+
+        let spec = match parse_pipx_package(&package) {
+            Ok(spec) => spec,
+            Err(err) => {
+                error_handler
+                    .with_key("package")
+                    .with_context("error", err.to_string())
+                    .with_actual(package.clone())
+                    .error(ConfigErrorKind::ParsingError);
+
+                return UpConfigPipxInstall {
+                    package,
+                    config_error: Some(err.to_string()),
+                    ..Default::default()
+                };
+            }
+        };
+
+        // The version, source and extras can either be specified in the
+        // package string or in their own fields, but not in both
+        let mut conflicting = vec![];
+
+        let version =
+            match config_value.get_as_str_or_none("version", &error_handler.with_key("version")) {
+                Some(version) if spec.version.is_some() || spec.git.is_some() => {
+                    conflicting.push(("version", version));
+                    spec.version.clone()
+                }
+                Some(version) => Some(version),
+                None => spec.version.clone(),
+            };
+
+        let git = match config_value.get_as_str_or_none("git", &error_handler.with_key("git")) {
+            Some(git) if spec.git.is_some() || version.is_some() => {
+                conflicting.push(("git", git));
+                spec.git.clone()
+            }
+            Some(git) => Some(git),
+            None => spec.git.clone(),
+        };
+
+        let extras = config_value.get_as_str_array("extras", &error_handler.with_key("extras"));
+        let extras = if extras.is_empty() {
+            spec.extras.clone()
+        } else if !spec.extras.is_empty() {
+            conflicting.push(("extras", extras.join(",")));
+            spec.extras.clone()
+        } else {
+            extras
+        };
+
+        if let Some((key, value)) = conflicting.first() {
+            error_handler
+                .with_key(*key)
+                .with_actual(value.to_string())
+                .error(ConfigErrorKind::UnsupportedValueInContext);
+
+            return UpConfigPipxInstall {
+                package: spec.package,
+                config_error: Some(format!(
+                    "{key} should not be specified along with the package version or source"
+                )),
+                ..Default::default()
+            };
+        }
+
+        let python = config_value.get_as_str_or_none("python", &error_handler.with_key("python"));
+        let upgrade = config_value.get_as_bool_or_default(
+            "upgrade",
+            false,
+            &error_handler.with_key("upgrade"),
+        );
+
+        let dirs = config_value
+            .get_as_str_array("dir", &error_handler.with_key("dir"))
+            .iter()
+            .map(|dir| PathBuf::from(dir).normalize().to_string_lossy().to_string())
+            .collect::<BTreeSet<_>>();
+
+        UpConfigPipxInstall {
+            package: spec.package,
+            version,
+            git,
+            extras,
+            python,
+            upgrade,
+            dirs,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the requirement to install the package with pip, following
+    /// the format of PEP 508
+    fn requirement(&self) -> String {
+        let extras = if self.extras.is_empty() {
+            "".to_string()
+        } else {
+            format!("[{}]", self.extras.join(","))
+        };
+
+        if let Some(git) = &self.git {
+            let url = if git.starts_with("git+") {
+                git.to_string()
+            } else {
+                format!("git+{git}")
+            };
+            return format!("{}{} @ {}", self.package, extras, url);
+        }
+
+        match self.version.as_deref() {
+            None | Some("") | Some("latest") => format!("{}{}", self.package, extras),
+            Some(version) if version.starts_with(['<', '>', '=', '!', '~']) => {
+                format!("{}{}{}", self.package, extras, version)
+            }
+            Some(version) => format!("{}{}=={}", self.package, extras, version),
+        }
+    }
+
+    /// Whether the version of the package is pinned, in which case the
+    /// installation never needs to be upgraded
+    fn is_pinned(&self) -> bool {
+        match (&self.git, self.version.as_deref()) {
+            (Some(_), _) => false,
+            (None, None | Some("") | Some("latest")) => false,
+            (None, Some(version)) => !version.starts_with(['<', '>', '!', '~']),
+        }
+    }
+
+    /// Returns the id of the installation of the package with the given
+    /// interpreter version; the id starts with the requested version, and
+    /// ends with a hash of the requirement and of the interpreter version,
+    /// so that a change to any of those leads to a separate installation
+    fn compute_install_id(&self, python_version: &str) -> String {
+        let label = if self.git.is_some() {
+            "git".to_string()
+        } else {
+            match self.version.as_deref() {
+                None | Some("") => "latest".to_string(),
+                Some(version) => version
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect(),
+            }
+        };
+
+        let mut hasher = Hasher::new();
+        hasher.update(self.requirement().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(python_version.as_bytes());
+        let hash = base62_encode(hasher.finalize().as_bytes())[..12].to_string();
+
+        format!("{label}-{hash}")
+    }
+
+    /// Returns the interpreter to use to create the isolated environment:
+    /// the python version pinned for the work directory through mise if
+    /// any, or the configured interpreter otherwise
+    fn python_interpreter(&self, environment: &UpEnvironment) -> String {
+        let pinned = environment
+            .versions_for_dir("")
+            .into_iter()
+            .find(|version| version.backend.is_empty() && version.tool == "python");
+
+        match pinned {
+            Some(version) => {
+                PathBuf::from(mise_tool_path(&version.normalized_name, &version.version))
+                    .join("bin")
+                    .join("python")
+                    .to_string_lossy()
+                    .to_string()
+            }
+            None => self
+                .python
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PYTHON.to_string()),
+        }
+    }
+
+    fn package_version(&self) -> String {
+        match self.install_id.get() {
+            Some(install_id) => format!("{}@{}", self.package, install_id),
+            None => self.package.clone(),
+        }
+    }
+
+    fn desc(&self) -> String {
+        if self.package.is_empty() {
+            "pipx:".to_string()
+        } else if self.config_error.is_some() {
+            format!("{}:", self.package)
+        } else {
+            format!(
+                "{} ({}):",
+                self.package,
+                match (&self.git, &self.version) {
+                    (Some(git), _) => git.clone(),
+                    (None, None) => "latest".to_string(),
+                    (None, Some(version)) if version.is_empty() => "latest".to_string(),
+                    (None, Some(version)) => version.clone(),
+                }
+            )
+        }
+    }
+
+    fn update_cache(
+        &self,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &dyn ProgressHandler,
+    ) {
+        let install_id = match self.install_id.get() {
+            Some(install_id) => install_id,
+            None => {
+                progress_handler.error_with_message("installation id not set".to_string());
+                return;
+            }
+        };
+
+        if options.write_cache {
+            progress_handler.progress("updating cache".to_string());
+
+            if let Err(err) = PipxOperationCache::get().add_installed(&self.package, install_id) {
+                progress_handler.progress(format!("failed to update pipx cache: {err}"));
+                return;
+            }
+        }
+
+        // Update environment
+        environment.add_version(UpVersionParams {
+            backend: "pipx",
+            tool: &self.package,
+            version: install_id,
+            bin_path: "bin",
+            dirs: self.dirs.clone(),
+            ..UpVersionParams::default()
+        });
+
+        environment.add_path(pipx_tool_path(&self.package, install_id).join("bin"));
+
+        progress_handler.progress("updated cache".to_string());
+    }
+
+    pub fn up(
+        &self,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<(), UpError> {
+        progress_handler.init(self.desc().light_blue());
+
+        if let Some(config_error) = &self.config_error {
+            progress_handler.error_with_message(config_error.clone());
+            return Err(UpError::Config(config_error.clone()));
+        }
+
+        if self.package.is_empty() {
+            progress_handler.error_with_message("package is required".to_string());
+            return Err(UpError::Config("package is required".to_string()));
+        }
+
+        let python = self.python_interpreter(environment);
+        let python_version = python_version(&python).inspect_err(|err| {
+            progress_handler.error_with_message(err.message());
+        })?;
+        let install_id = self.compute_install_id(&python_version);
+
+        let installed = self
+            .install(options, &python, &install_id, progress_handler)
+            .inspect_err(|err| {
+                progress_handler.error_with_message(err.message());
+            })?;
+
+        self.handle_installed(&install_id, installed)?;
+
+        self.update_cache(options, environment, progress_handler);
+
+        let msg = if installed {
+            format!("{} installed", self.requirement().light_yellow())
+        } else {
+            format!("{} already installed", self.requirement()).light_black()
+        };
+        progress_handler.success_with_message(msg);
+
+        Ok(())
+    }
+
+    pub fn was_upped(&self) -> bool {
+        matches!(
+            self.was_handled.get(),
+            Some(PipxInstallHandled::Handled) | Some(PipxInstallHandled::Noop)
+        )
+    }
+
+    pub fn commit(&self, _options: &UpOptions, env_version_id: &str) -> Result<(), UpError> {
+        let install_id = match self.install_id.get() {
+            Some(install_id) => install_id,
+            None => {
+                return Err(UpError::Exec("installation id not set".to_string()));
+            }
+        };
+
+        if let Err(err) =
+            PipxOperationCache::get().add_required_by(env_version_id, &self.package, install_id)
+        {
+            return Err(UpError::Cache(format!(
+                "failed to update pipx cache: {err}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn handling(&self) -> PipxInstallHandled {
+        match self.was_handled.get() {
+            Some(handled) => handled.clone(),
+            None => PipxInstallHandled::Unhandled,
+        }
+    }
+
+    fn upgrade_tool(&self, options: &UpOptions) -> bool {
+        self.upgrade || options.upgrade || config(".").up_command.upgrade
+    }
+
+    fn handle_installed(&self, install_id: &str, installed: bool) -> Result<(), UpError> {
+        self.install_id
+            .set(install_id.to_string())
+            .map_err(|_| UpError::Exec("failed to set installation id".to_string()))?;
+
+        if self
+            .was_handled
+            .set(if installed {
+                PipxInstallHandled::Handled
+            } else {
+                PipxInstallHandled::Noop
+            })
+            .is_err()
+        {
+            unreachable!("failed to set was_handled");
+        }
+
+        Ok(())
+    }
+
+    /// Whether the installation at the given path can be used as is
+    fn is_cached(&self, options: &UpOptions, install_path: &Path) -> bool {
+        options.read_cache
+            && is_installed(install_path)
+            && (self.is_pinned() || !self.upgrade_tool(options))
+    }
+
+    /// Installs the package in an isolated environment, unless it is
+    /// already installed for the same requirement and interpreter;
+    /// returns whether the package was installed
+    fn install(
+        &self,
+        options: &UpOptions,
+        python: &str,
+        install_id: &str,
+        progress_handler: &dyn ProgressHandler,
+    ) -> Result<bool, UpError> {
+        let install_path = pipx_tool_path(&self.package, install_id);
+
+        if self.is_cached(options, &install_path) {
+            progress_handler
+                .progress(format!("installed {} (cached)", self.requirement()).light_black());
+
+            return Ok(false);
+        }
+
+        // Installations are shared between work directories, so make sure
+        // that only one process prepares this one at a time, and check
+        // again once the lock is held in case it was just installed
+        let _lock = InstallLock::acquire(&install_path)
+            .map_err(|err| UpError::Exec(format!("failed to lock installation: {err}")))?;
+        if self.is_cached(options, &install_path) {
+            progress_handler
+                .progress(format!("installed {} (cached)", self.requirement()).light_black());
+
+            return Ok(false);
+        }
+
+        // The installation is prepared in a staging directory and only
+        // renamed into place once complete, so that the work directories
+        // using the previous installation can keep doing so meanwhile
+        let staging = InstallStaging::new(&install_path)
+            .map_err(|err| UpError::Exec(format!("failed to create staging directory: {err}")))?;
+
+        let scripts = self.install_in(python, &staging.path(), &install_path, progress_handler)?;
+
+        let marker = InstallMarker::new(vec![InstallMarkerAsset::new(self.requirement(), None)]);
+        staging
+            .commit(&marker)
+            .map_err(|err| UpError::Exec(format!("failed to move installation in place: {err}")))?;
+
+        progress_handler.progress(format!("exposed {}", scripts.iter().join(", ")));
+
+        Ok(true)
+    }
+
+    /// Installs the package in an isolated environment at the given
+    /// staging path, exposing its scripts so that they work once the
+    /// installation is moved to the given install path; returns the
+    /// names of the exposed scripts
+    fn install_in(
+        &self,
+        python: &str,
+        staging_path: &Path,
+        install_path: &Path,
+        progress_handler: &dyn ProgressHandler,
+    ) -> Result<BTreeSet<String>, UpError> {
+        create_dir_all(staging_path, PathKind::Shared)
+            .map_err(|err| UpError::Exec(format!("failed to create install directory: {err}")))?;
+
+        let venv_path = staging_path.join("venv");
+        let venv_bin_path = venv_path.join("bin");
+
+        progress_handler.progress(format!("creating isolated environment with {python}"));
+
+        let mut venv_create = TokioCommand::new(python);
+        venv_create.arg("-m");
+        venv_create.arg("venv");
+        venv_create.arg(&venv_path);
+        run_reporting_output(
+            &mut venv_create,
+            "failed to create isolated environment",
+            progress_handler,
+        )?;
+
+        progress_handler.progress(format!("installing {}", self.requirement()));
+
+        let mut pip_install = TokioCommand::new(venv_bin_path.join("python"));
+        pip_install.arg("-m");
+        pip_install.arg("pip");
+        pip_install.arg("install");
+        pip_install.arg("--disable-pip-version-check");
+        pip_install.arg("--no-input");
+        pip_install.arg(self.requirement());
+        run_reporting_output(
+            &mut pip_install,
+            &format!("failed to install {}", self.requirement()),
+            progress_handler,
+        )?;
+
+        let scripts = package_scripts(&venv_path, &self.package);
+        if scripts.is_empty() {
+            return Err(UpError::Exec(format!(
+                "failed to install {} (no console script found)",
+                self.requirement()
+            )));
+        }
+
+        // Expose the scripts of the package through a directory that
+        // only contains those, so that the interpreter, the other tools
+        // of the environment and the scripts of the dependencies do not
+        // end up in the PATH; the scripts refer to the interpreter of
+        // the environment by its absolute path, so they are exposed
+        // referring to it at the path of the installation once moved
+        let bin_path = staging_path.join("bin");
+        create_dir_all(&bin_path, PathKind::Shared)
+            .map_err(|err| UpError::Exec(format!("failed to create bin directory: {err}")))?;
+
+        let staging_venv = venv_path.to_string_lossy().to_string();
+        let final_venv = install_path.join("venv").to_string_lossy().to_string();
+        for script in &scripts {
+            let source = venv_bin_path.join(script);
+            let target = bin_path.join(script);
+
+            let contents = std::fs::read(&source)
+                .map_err(|err| UpError::Exec(format!("failed to expose {script}: {err}")))?;
+            let contents = match String::from_utf8(contents) {
+                Ok(text) => text.replace(&staging_venv, &final_venv).into_bytes(),
+                Err(err) => err.into_bytes(),
+            };
+
+            std::fs::write(&target, contents)
+                .and_then(|_| {
+                    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))
+                })
+                .map_err(|err| UpError::Exec(format!("failed to expose {script}: {err}")))?;
+        }
+
+        Ok(scripts)
+    }
+}
+
+/// Returns the version of the given python interpreter, along with its
+/// implementation, e.g. `CPython 3.12.1`
+fn python_version(python: &str) -> Result<String, UpError> {
+    let output = std::process::Command::new(python)
+        .arg("-c")
+        .arg("import platform; print(platform.python_implementation(), platform.python_version())")
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .map_err(|err| UpError::Exec(format!("failed to run {python}: {err}")))?;
+
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || version.is_empty() {
+        return Err(UpError::Exec(format!(
+            "failed to get the version of {python}"
+        )));
+    }
+
+    Ok(version)
+}
+
+/// Returns the names of the scripts provided by the given package in the
+/// given isolated environment, from the entry points of the package and
+/// the files it installed, excluding the scripts of its dependencies
+fn package_scripts(venv_path: &Path, package: &str) -> BTreeSet<String> {
+    let dist_info = match find_dist_info(venv_path, package) {
+        Some(dist_info) => dist_info,
+        None => return BTreeSet::new(),
+    };
+
+    let entry_points = std::fs::read_to_string(dist_info.join("entry_points.txt"))
+        .map(|contents| parse_script_entry_points(&contents))
+        .unwrap_or_default();
+    let record = std::fs::read_to_string(dist_info.join("RECORD"))
+        .map(|contents| parse_record_scripts(&contents))
+        .unwrap_or_default();
+
+    let venv_bin_path = venv_path.join("bin");
+    entry_points
+        .into_iter()
+        .chain(record)
+        .filter(|script| venv_bin_path.join(script).is_file())
+        .collect()
+}
+
+/// Returns the metadata directory of the given package in the given
+/// isolated environment, if the package is installed there
+fn find_dist_info(venv_path: &Path, package: &str) -> Option<PathBuf> {
+    let package = normalize_package_name(package);
+
+    let lib_path = venv_path.join("lib");
+    std::fs::read_dir(lib_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| std::fs::read_dir(entry.path().join("site-packages")).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".dist-info"))
+                .and_then(|name| name.split_once('-'))
+                .is_some_and(|(name, _version)| normalize_package_name(name) == package)
+        })
+}
+
+/// Normalizes the given package name, so that names differing only by
+/// case or separators are considered the same, as package indexes do
+fn normalize_package_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(['-', '_', '.'])
+        .filter(|part| !part.is_empty())
+        .join("-")
+}
+
+/// Returns the names of the scripts defined in the given contents of
+/// an `entry_points.txt` file
+fn parse_script_entry_points(contents: &str) -> BTreeSet<String> {
+    let mut scripts = BTreeSet::new();
+    let mut in_scripts_section = false;
+
+    for line in contents.lines().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(section) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            in_scripts_section = SCRIPT_ENTRY_POINT_SECTIONS.contains(&section.trim());
+            continue;
+        }
+
+        if in_scripts_section {
+            if let Some((name, _)) = line.split_once('=') {
+                scripts.insert(name.trim().to_string());
+            }
+        }
+    }
+
+    scripts
+}
+
+/// Returns the names of the scripts listed in the given contents of a
+/// `RECORD` file, which are installed in the `bin` directory of the
+/// environment, relative to the `site-packages` directory
+fn parse_record_scripts(contents: &str) -> BTreeSet<String> {
+    contents
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .filter_map(|path| {
+            let components = Path::new(path).components().collect::<Vec<_>>();
+            match components.as_slice() {
+                [parents @ .., bin, name]
+                    if !parents.is_empty()
+                        && parents.iter().all(|c| matches!(c, Component::ParentDir))
+                        && bin.as_os_str() == "bin" =>
+                {
+                    name.as_os_str().to_str().map(|name| name.to_string())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Runs the given command, reporting its output as progress, and returns
+/// an error including the last lines of output of the command if it failed,
+/// so that the reason for the failure is reported along with the operation
+fn run_reporting_output(
+    command: &mut TokioCommand,
+    errmsg: &str,
+    progress_handler: &dyn ProgressHandler,
+) -> Result<(), UpError> {
+    command.stdout(std::process::Stdio::piped());
+    command.stderr(std::process::Stdio::piped());
+
+    let mut stdout_lines = vec![];
+    let mut stderr_lines = vec![];
+    let result = run_command_with_handler(
+        command,
+        |stdout, stderr| {
+            if let Some(line) = stdout {
+                progress_handler.progress(line.clone());
+                stdout_lines.push(line);
+            }
+            if let Some(line) = stderr {
+                progress_handler.progress(line.clone());
+                stderr_lines.push(line);
+            }
+        },
+        RunConfig::default(),
+    );
+
+    let err = match result {
+        Ok(()) => return Ok(()),
+        Err(err) => err,
+    };
+
+    let details = if stderr_lines.iter().all(|line| line.trim().is_empty()) {
+        stdout_lines
+    } else {
+        stderr_lines
+    };
+    let lines = details
+        .iter()
+        .map(|line| line.trim_end())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let lines = &lines[lines.len().saturating_sub(ERROR_OUTPUT_MAX_LINES)..];
+
+    if lines.is_empty() {
+        return Err(UpError::Exec(format!("{errmsg}: {}", err.message())));
+    }
+
+    Err(UpError::Exec(format!("{errmsg}:\n{}", lines.join("\n"))))
+}
+
+/// Parses a package string, which is either the URL of a git repository,
+/// prefixed by `git+`, or a package name with optional extras and version,
+/// e.g. `black[d]@24.1.0`
+fn parse_pipx_package<T>(input: T) -> Result<UpConfigPipxInstall, PipxInstallError>
+where
+    T: AsRef<str>,
+{
+    let input = input.as_ref().trim();
+
+    if let Some(url) = input.strip_prefix("git+") {
+        // Use the last part of the URL path as package name, without
+        // the reference to check out nor the .git suffix
+        let path = url.split('#').next().unwrap_or(url);
+        let name = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or("")
+            .split('@')
+            .next()
+            .unwrap_or("")
+            .trim_end_matches(".git");
+        if !is_valid_package_name(name) {
+            return Err(PipxInstallError::InvalidPackage(format!(
+                "cannot determine the package name from {input}"
+            )));
+        }
+
+        return Ok(UpConfigPipxInstall {
+            package: name.to_string(),
+            git: Some(input.to_string()),
+            ..Default::default()
+        });
+    }
+
+    let parts: Vec<&str> = input.split('@').collect();
+    if parts.len() > 2 {
+        return Err(PipxInstallError::InvalidPackage(
+            "multiple @ symbols found".to_string(),
+        ));
+    }
+
+    let version = match parts.get(1) {
+        Some(version) if version.trim().is_empty() => {
+            return Err(PipxInstallError::InvalidPackage(
+                "empty version".to_string(),
+            ));
+        }
+        Some(version) => Some(version.trim().to_string()),
+        None => None,
+    };
+
+    let (name, extras) = match parts[0].split_once('[') {
+        Some((name, extras)) => {
+            let extras = extras.strip_suffix(']').ok_or_else(|| {
+                PipxInstallError::InvalidPackage(format!("unterminated extras in {input}"))
+            })?;
+            let extras = extras
+                .split(',')
+                .map(|extra| extra.trim().to_string())
+                .filter(|extra| !extra.is_empty())
+                .collect::<Vec<_>>();
+            (name.trim(), extras)
+        }
+        None => (parts[0].trim(), vec![]),
+    };
+
+    if !is_valid_package_name(name) {
+        return Err(PipxInstallError::InvalidPackage(format!(
+            "invalid package name: {name}"
+        )));
+    }
+
+    Ok(UpConfigPipxInstall {
+        package: name.to_string(),
+        version,
+        extras,
+        ..Default::default()
+    })
+}
+
+/// Whether the given name is a valid python package name, as defined
+/// by the core metadata specifications
+fn is_valid_package_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+#[path = "pipx_test.rs"]
+mod tests;
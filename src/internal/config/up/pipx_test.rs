@@ -0,0 +1,492 @@
+use super::*;
+
+use std::os::unix::fs::PermissionsExt;
+
+use crate::internal::config::up::utils::staging::STAGING_DIR_PREFIX;
+use crate::internal::testutils::run_with_env;
+
+mod parse_pipx_package {
+    use super::*;
+
+    #[test]
+    fn simple_package() {
+        let spec = parse_pipx_package("black").unwrap();
+        assert_eq!(spec.package, "black");
+        assert_eq!(spec.version, None);
+        assert!(spec.extras.is_empty());
+        assert_eq!(spec.git, None);
+    }
+
+    #[test]
+    fn package_with_version() {
+        let spec = parse_pipx_package("black@24.1.0").unwrap();
+        assert_eq!(spec.package, "black");
+        assert_eq!(spec.version, Some("24.1.0".to_string()));
+    }
+
+    #[test]
+    fn package_with_extras_and_version() {
+        let spec = parse_pipx_package("black[d, jupyter]@24.1.0").unwrap();
+        assert_eq!(spec.package, "black");
+        assert_eq!(spec.extras, vec!["d".to_string(), "jupyter".to_string()]);
+        assert_eq!(spec.version, Some("24.1.0".to_string()));
+    }
+
+    #[test]
+    fn git_url() {
+        let spec = parse_pipx_package("git+https://github.com/psf/black.git@main").unwrap();
+        assert_eq!(spec.package, "black");
+        assert_eq!(
+            spec.git,
+            Some("git+https://github.com/psf/black.git@main".to_string())
+        );
+        assert_eq!(spec.version, None);
+    }
+
+    #[test]
+    fn invalid_multiple_at() {
+        let result = parse_pipx_package("black@24.1.0@latest");
+        assert!(matches!(result, Err(PipxInstallError::InvalidPackage(_))));
+    }
+
+    #[test]
+    fn invalid_unterminated_extras() {
+        let result = parse_pipx_package("black[d");
+        assert!(matches!(result, Err(PipxInstallError::InvalidPackage(_))));
+    }
+}
+
+mod requirement {
+    use super::*;
+
+    fn requirement(package: &str, version: Option<&str>) -> String {
+        UpConfigPipxInstall {
+            package: package.to_string(),
+            version: version.map(|version| version.to_string()),
+            ..UpConfigPipxInstall::default()
+        }
+        .requirement()
+    }
+
+    #[test]
+    fn latest() {
+        assert_eq!(requirement("black", None), "black");
+        assert_eq!(requirement("black", Some("latest")), "black");
+    }
+
+    #[test]
+    fn exact_version() {
+        assert_eq!(requirement("black", Some("24.1.0")), "black==24.1.0");
+    }
+
+    #[test]
+    fn version_specifier() {
+        assert_eq!(requirement("black", Some(">=24,<25")), "black>=24,<25");
+    }
+
+    #[test]
+    fn extras() {
+        let config = UpConfigPipxInstall {
+            package: "black".to_string(),
+            version: Some("24.1.0".to_string()),
+            extras: vec!["d".to_string()],
+            ..UpConfigPipxInstall::default()
+        };
+        assert_eq!(config.requirement(), "black[d]==24.1.0");
+    }
+
+    #[test]
+    fn git() {
+        let config = UpConfigPipxInstall {
+            package: "black".to_string(),
+            git: Some("https://github.com/psf/black".to_string()),
+            ..UpConfigPipxInstall::default()
+        };
+        assert_eq!(
+            config.requirement(),
+            "black @ git+https://github.com/psf/black"
+        );
+    }
+}
+
+mod install {
+    use super::*;
+
+    /// Writes a python stub that creates a fake isolated environment
+    /// for `-m venv`, and for `-m pip install` a console script named
+    /// after the package, along with a console script of a dependency;
+    /// every call is logged in the given file, and installing a package
+    /// starting with `failing` fails
+    fn stub_python(dir: &Path) -> (PathBuf, PathBuf) {
+        let python_path = dir.join("python");
+        let log_path = dir.join("calls.log");
+
+        let script = r##"#!/usr/bin/env bash
+            log="{log}"
+            if [[ "$1" == "-c" ]]; then
+                echo "CPython 3.12.1"
+                exit 0
+            fi
+            if [[ "$1" == "-m" && "$2" == "venv" ]]; then
+                echo "venv $3" >> "$log"
+                mkdir -p "$3/bin"
+                cp "$0" "$3/bin/python"
+                touch "$3/bin/pip"
+                chmod +x "$3/bin/python" "$3/bin/pip"
+                exit 0
+            fi
+            if [[ "$1" == "-m" && "$2" == "pip" && "$3" == "install" ]]; then
+                for arg in "$@"; do req="$arg"; done
+                echo "install $req" >> "$log"
+                if [[ "$req" == failing* ]]; then
+                    echo "Collecting $req"
+                    echo "ERROR: No matching distribution found for $req" >&2
+                    exit 1
+                fi
+                name="$(echo "$req" | sed 's/[^A-Za-z0-9._-].*//')"
+                bin_dir="$(dirname "$0")"
+                site_packages="$bin_dir/../lib/python3.12/site-packages"
+                for script in "$name" dependency-tool; do
+                    dist_info="$site_packages/${script//-/_}-1.0.0.dist-info"
+                    mkdir -p "$dist_info"
+                    printf '[console_scripts]\n%s = tool:main\n' "$script" \
+                        > "$dist_info/entry_points.txt"
+                    echo "#!$bin_dir/python" > "$bin_dir/$script"
+                    chmod +x "$bin_dir/$script"
+                done
+                exit 0
+            fi
+            echo "unexpected call: $*" >&2
+            exit 1
+        "##
+        .replace("{log}", &log_path.to_string_lossy());
+
+        std::fs::write(&python_path, script).expect("failed to write python script");
+        std::fs::set_permissions(&python_path, std::fs::Permissions::from_mode(0o755))
+            .expect("failed to set permissions");
+
+        (python_path, log_path)
+    }
+
+    fn staging_entries(path: &Path) -> Vec<String> {
+        std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .filter(|name| name.starts_with(STAGING_DIR_PREFIX))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn installs(log_path: &Path) -> Vec<String> {
+        std::fs::read_to_string(log_path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.strip_prefix("install "))
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    fn config(python: &Path, version: Option<&str>) -> UpConfigPipxInstall {
+        UpConfigPipxInstall {
+            package: "fake-tool".to_string(),
+            version: version.map(|version| version.to_string()),
+            python: Some(python.to_string_lossy().to_string()),
+            ..UpConfigPipxInstall::default()
+        }
+    }
+
+    #[test]
+    fn installs_package() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, log_path) = stub_python(temp_dir.path());
+
+            let config = config(&python, Some("1.0.0"));
+            let options = UpOptions::default().cache_disabled();
+            let mut environment = UpEnvironment::new();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let result = config.up(&options, &mut environment, &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+            assert_eq!(installs(&log_path), vec!["fake-tool==1.0.0".to_string()]);
+
+            let install_id = config.install_id.get().expect("install id should be set");
+            assert!(install_id.starts_with("1.0.0-"), "got {install_id}");
+
+            // Only the console script of the package is exposed, and
+            // not the one of its dependency, referring to the interpreter
+            // of the environment at its final path
+            let install_path = pipx_tool_path("fake-tool", install_id);
+            assert!(is_installed(&install_path));
+            let exposed = std::fs::read_dir(install_path.join("bin"))
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(exposed, vec!["fake-tool".to_string()]);
+            assert_eq!(
+                std::fs::read_to_string(install_path.join("bin/fake-tool")).unwrap(),
+                format!("#!{}/venv/bin/python\n", install_path.display())
+            );
+            assert!(config.was_upped());
+        });
+    }
+
+    #[test]
+    fn adds_bin_path_to_environment() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, _log_path) = stub_python(temp_dir.path());
+
+            let config = config(&python, None);
+            let options = UpOptions::default().cache_disabled();
+            let mut environment = UpEnvironment::new();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let result = config.up(&options, &mut environment, &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+
+            let install_id = config.install_id.get().expect("install id should be set");
+            assert!(install_id.starts_with("latest-"), "got {install_id}");
+            assert!(environment
+                .paths
+                .contains(&pipx_tool_path("fake-tool", install_id).join("bin")));
+
+            let versions = environment.versions_for_dir("");
+            let version = versions
+                .iter()
+                .find(|version| version.tool == "fake-tool")
+                .expect("version should be in the environment");
+            assert_eq!(version.backend, "pipx");
+            assert_eq!(&version.version, install_id);
+        });
+    }
+
+    #[test]
+    fn uses_cached_installation() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, log_path) = stub_python(temp_dir.path());
+
+            let options = UpOptions::default();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let first = config(&python, Some("1.0.0"));
+            let result = first.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+            assert_eq!(first.handling(), PipxInstallHandled::Handled);
+
+            let second = config(&python, Some("1.0.0"));
+            let result = second.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+            assert_eq!(second.handling(), PipxInstallHandled::Noop);
+            assert_eq!(first.install_id.get(), second.install_id.get());
+
+            assert_eq!(installs(&log_path).len(), 1, "pip should only run once");
+        });
+    }
+
+    #[test]
+    fn installs_again_on_version_change() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, log_path) = stub_python(temp_dir.path());
+
+            let options = UpOptions::default();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let first = config(&python, Some("1.0.0"));
+            let result = first.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+
+            let second = config(&python, Some("2.0.0"));
+            let result = second.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+            assert_eq!(second.handling(), PipxInstallHandled::Handled);
+
+            let first_id = first.install_id.get().unwrap();
+            let second_id = second.install_id.get().unwrap();
+            assert_ne!(first_id, second_id);
+            assert!(is_installed(pipx_tool_path("fake-tool", first_id)));
+            assert!(is_installed(pipx_tool_path("fake-tool", second_id)));
+
+            assert_eq!(
+                installs(&log_path),
+                vec![
+                    "fake-tool==1.0.0".to_string(),
+                    "fake-tool==2.0.0".to_string()
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn same_python_version_shares_installation() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, log_path) = stub_python(temp_dir.path());
+            let other_dir = temp_dir.path().join("other");
+            std::fs::create_dir_all(&other_dir).unwrap();
+            let (other_python, _other_log_path) = stub_python(&other_dir);
+
+            let options = UpOptions::default();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let first = config(&python, Some("1.0.0"));
+            let result = first.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+
+            let second = config(&other_python, Some("1.0.0"));
+            let result = second.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+            assert_eq!(second.handling(), PipxInstallHandled::Noop);
+            assert_eq!(first.install_id.get(), second.install_id.get());
+
+            assert_eq!(installs(&log_path).len(), 1, "pip should only run once");
+        });
+    }
+
+    #[test]
+    fn upgrade_replaces_installation_in_place() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, log_path) = stub_python(temp_dir.path());
+
+            let options = UpOptions::default();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let first = config(&python, None);
+            let result = first.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+
+            let second = UpConfigPipxInstall {
+                upgrade: true,
+                ..config(&python, None)
+            };
+            let result = second.up(&options, &mut UpEnvironment::new(), &progress_handler);
+            assert!(result.is_ok(), "result should be ok, got {result:?}");
+            assert_eq!(second.handling(), PipxInstallHandled::Handled);
+            assert_eq!(first.install_id.get(), second.install_id.get());
+
+            let install_path = pipx_tool_path("fake-tool", second.install_id.get().unwrap());
+            assert!(is_installed(&install_path));
+            assert!(install_path.join("bin/fake-tool").is_file());
+            assert!(staging_entries(install_path.parent().unwrap()).is_empty());
+            assert_eq!(installs(&log_path).len(), 2);
+        });
+    }
+
+    #[test]
+    fn reports_pip_errors() {
+        run_with_env(&[], || {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (python, _log_path) = stub_python(temp_dir.path());
+
+            let config = UpConfigPipxInstall {
+                package: "failing-tool".to_string(),
+                ..config(&python, Some("1.0.0"))
+            };
+            let options = UpOptions::default().cache_disabled();
+            let mut environment = UpEnvironment::new();
+            let progress_handler = UpProgressHandler::new_void();
+
+            let result = config.up(&options, &mut environment, &progress_handler);
+            let err = result.expect_err("installation should fail");
+            let message = err.message();
+            assert!(
+                message.contains("failed to install failing-tool==1.0.0"),
+                "got {message}"
+            );
+            assert!(
+                message.contains("ERROR: No matching distribution found"),
+                "got {message}"
+            );
+
+            // The partial installation is not kept around
+            let install_id = config.compute_install_id("CPython 3.12.1");
+            let install_path = pipx_tool_path("failing-tool", &install_id);
+            assert!(!install_path.exists());
+            assert!(staging_entries(install_path.parent().unwrap()).is_empty());
+            assert!(environment.paths.is_empty());
+        });
+    }
+}
+
+mod package_scripts {
+    use super::*;
+
+    #[test]
+    fn entry_points() {
+        let contents = concat!(
+            "[console_scripts]\n",
+            "black = black:patched_main\n",
+            "blackd = blackd:patched_main [d]\n",
+            "\n",
+            "[gui_scripts]\n",
+            "black-gui = black.gui:main\n",
+            "\n",
+            "[black.plugins]\n",
+            "other = black.plugins:other\n",
+        );
+        assert_eq!(
+            parse_script_entry_points(contents),
+            BTreeSet::from([
+                "black".to_string(),
+                "black-gui".to_string(),
+                "blackd".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn record() {
+        let contents = concat!(
+            "../../../bin/black,sha256=abc,123\n",
+            "black/__init__.py,sha256=def,456\n",
+            "black/bin/helper,sha256=ghi,789\n",
+            "black-24.1.0.dist-info/RECORD,,\n",
+        );
+        assert_eq!(
+            parse_record_scripts(contents),
+            BTreeSet::from(["black".to_string()])
+        );
+    }
+
+    #[test]
+    fn normalized_package_name() {
+        assert_eq!(normalize_package_name("Fake_Tool"), "fake-tool");
+        assert_eq!(normalize_package_name("fake.tool"), "fake-tool");
+        assert_eq!(normalize_package_name("fake--tool"), "fake-tool");
+    }
+}
+
+mod cleanup {
+    use super::*;
+
+    #[test]
+    fn cleanup_removes_unused() {
+        run_with_env(&[], || {
+            let progress_handler = UpProgressHandler::new_void();
+
+            let base_path = pipx_bin_path();
+            std::fs::create_dir_all(base_path.join("black/24.1.0-abc/bin")).unwrap();
+            std::fs::create_dir_all(base_path.join("ruff/latest-def/bin")).unwrap();
+            std::fs::create_dir_all(base_path.join("old-tool/1.0.0-ghi/bin")).unwrap();
+
+            let cache = PipxOperationCache::get();
+            cache.add_installed("black", "24.1.0-abc").unwrap();
+            cache.add_installed("ruff", "latest-def").unwrap();
+
+            let result = UpConfigPipxInstalls::cleanup(&progress_handler).unwrap();
+
+            assert!(result.is_some());
+            assert!(result.unwrap().contains("old-tool"));
+
+            assert!(base_path.join("black/24.1.0-abc").exists());
+            assert!(base_path.join("ruff/latest-def").exists());
+            assert!(!base_path.join("old-tool").exists());
+        });
+    }
+}
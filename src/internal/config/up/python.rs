@@ -13,7 +13,7 @@ use crate::internal::commands::utils::abs_path;
 use crate::internal::config::global_config;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
-use crate::internal::config::up::github_release::UpConfigGithubRelease;
+use crate::internal::config::up::github_release::github_release_tool_path;
 use crate::internal::config::up::mise::FullyQualifiedToolName;
 use crate::internal::config::up::mise::PostInstallFuncArgs;
 use crate::internal::config::up::mise_tool_path;
@@ -27,6 +27,7 @@ use crate::internal::config::up::UpConfigMise;
 use crate::internal::config::up::UpError;
 use crate::internal::config::up::UpOptions;
 use crate::internal::config::utils::is_executable;
+use crate::internal::dynenv::env_backend;
 use crate::internal::dynenv::update_dynamic_env_for_command_from_env;
 use crate::internal::env::current_dir;
 use crate::internal::env::tmpdir_cleanup_prefix;
@@ -190,23 +191,27 @@ impl UvBin {
             return Ok(uv_bin.clone());
         }
 
-        // If we get here, let's make sure we get the uv binary from GitHub
-        let gh_release = UpConfigGithubRelease::new_with_version(
-            "astral-sh/uv",
-            &global_config().up_command.uv_version,
-            false, // We do not force the upgrade here, since we want to use any
-                   // version of uv that satisfies the version constraint
-        );
+        // If we get here, let's make sure we get the uv binary from GitHub;
+        // the upgrade is not forced here, since we want to use any version
+        // of uv that satisfies the version constraint
+        let ghrelease_backend =
+            env_backend("ghrelease").expect("the github release backend should be registered");
 
         // We create a fake environment since we do not want to add this
         // release to the environment, we just want the uv binary
         let mut fake_env = UpEnvironment::new();
 
         let subhandler = progress_handler.subhandler(&"uv: ".light_black());
-        gh_release.up(options, &mut fake_env, &subhandler)?;
+        let version = ghrelease_backend.install_version(
+            "astral-sh/uv",
+            &global_config().up_command.uv_version,
+            options,
+            &mut fake_env,
+            &subhandler,
+        )?;
 
         // Check that the uv binary is installed
-        let install_path = gh_release.install_path()?;
+        let install_path = github_release_tool_path("astral-sh/uv", &version);
         let install_bin_path = install_path.join("bin");
         let install_bin = if install_bin_path.is_dir() {
             install_bin_path.join("uv")
@@ -386,9 +391,7 @@ fn setup_python_venv_per_dir(
     dir: String,
 ) -> Result<(), UpError> {
     // Get the data path for the work directory
-    let workdir = workdir(".");
-
-    let data_path = if let Some(data_path) = workdir.data_path() {
+    let data_path = if let Some(data_path) = options.tools_data_path() {
         data_path
     } else {
         return Err(UpError::Exec(format!(
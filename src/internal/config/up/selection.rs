@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+
+use crate::internal::config::up::UpConfigTool;
+use crate::internal::config::up::UpError;
+
+/// How the operations to run were selected when using `omni up --only`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpOperationSelection {
+    /// The names or 1-based indices of the operations, as provided on
+    /// the command line; the dependencies of the requested operations
+    /// are automatically included
+    Requested(Vec<String>),
+    /// The indices of the operations picked interactively; since all the
+    /// operations were presented, leaving out a dependency of a picked
+    /// operation is considered an error
+    Picked(BTreeSet<usize>),
+}
+
+/// The operations to run, along with the dependencies that were not
+/// selected but that had to be included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpSelectedOperations {
+    pub indices: BTreeSet<usize>,
+    pub included: Vec<UpIncludedDependency>,
+}
+
+/// An operation that was not selected, but that was included because
+/// selected operations depend on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpIncludedDependency {
+    pub index: usize,
+    pub name: String,
+    pub required_by: Vec<String>,
+}
+
+/// Resolves the operations to run out of the given steps, making sure that
+/// the dependencies of the selected operations are part of the selection
+pub fn select_operations(
+    steps: &[&UpConfigTool],
+    selection: &UpOperationSelection,
+) -> Result<UpSelectedOperations, UpError> {
+    match selection {
+        UpOperationSelection::Requested(values) => {
+            let requested = parse_operation_selection(values, steps)?;
+            Ok(include_dependencies(steps, requested))
+        }
+        UpOperationSelection::Picked(picked) => {
+            if picked.is_empty() {
+                return Err(UpError::Config("no operation selected".to_string()));
+            }
+
+            for index in picked.iter() {
+                if *index >= steps.len() {
+                    return Err(invalid_index(*index + 1, steps.len()));
+                }
+
+                if let Some(dependency) = operation_dependencies(steps, *index)
+                    .into_iter()
+                    .find(|dependency| !picked.contains(dependency))
+                {
+                    return Err(UpError::Config(format!(
+                        "operation {} is required by operation {} but was not selected",
+                        operation_label(steps, dependency),
+                        operation_label(steps, *index),
+                    )));
+                }
+            }
+
+            Ok(UpSelectedOperations {
+                indices: picked.clone(),
+                included: vec![],
+            })
+        }
+    }
+}
+
+/// Parses the operations selected by name or by 1-based index, as shown
+/// in the progress of `omni up`; values can be separated by commas, and
+/// a name selects all the operations with that name
+pub fn parse_operation_selection(
+    values: &[String],
+    steps: &[&UpConfigTool],
+) -> Result<BTreeSet<usize>, UpError> {
+    let mut selected = BTreeSet::new();
+
+    for value in values
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+    {
+        if let Ok(index) = value.parse::<usize>() {
+            if index == 0 || index > steps.len() {
+                return Err(invalid_index(index, steps.len()));
+            }
+            selected.insert(index - 1);
+            continue;
+        }
+
+        let matching = steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| step.to_name() == value)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        if matching.is_empty() {
+            return Err(UpError::Config(format!(
+                "unknown operation '{}', expected one of: {}",
+                value,
+                steps.iter().map(|step| step.to_name()).unique().join(", "),
+            )));
+        }
+
+        selected.extend(matching);
+    }
+
+    if selected.is_empty() {
+        return Err(UpError::Config("no operation selected".to_string()));
+    }
+
+    Ok(selected)
+}
+
+/// Returns the indices of the operations that the operation at the given
+/// index depends on, i.e. the ones providing the runtimes it requires
+pub fn operation_dependencies(steps: &[&UpConfigTool], index: usize) -> Vec<usize> {
+    let step = match steps.get(index) {
+        Some(step) => step,
+        None => return vec![],
+    };
+
+    step.required_runtimes()
+        .into_iter()
+        .flat_map(|runtime| {
+            steps
+                .iter()
+                .enumerate()
+                .filter(move |(other, provider)| {
+                    *other != index && provider.provides_runtime(runtime)
+                })
+                .map(|(other, _)| other)
+        })
+        .unique()
+        .collect()
+}
+
+/// Returns the label of the operation at the given index, as used in the
+/// messages about the selection
+pub fn operation_label(steps: &[&UpConfigTool], index: usize) -> String {
+    match steps.get(index) {
+        Some(step) => format!("#{} ({})", index + 1, step.to_name()),
+        None => format!("#{}", index + 1),
+    }
+}
+
+fn include_dependencies(
+    steps: &[&UpConfigTool],
+    requested: BTreeSet<usize>,
+) -> UpSelectedOperations {
+    let mut indices = requested.clone();
+    let mut included: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+
+    let mut queue = requested.into_iter().collect::<Vec<_>>();
+    while let Some(index) = queue.pop() {
+        for dependency in operation_dependencies(steps, index) {
+            if let Some(required_by) = included.get_mut(&dependency) {
+                required_by.push(operation_label(steps, index));
+                continue;
+            }
+
+            if indices.insert(dependency) {
+                included.insert(dependency, vec![operation_label(steps, index)]);
+                queue.push(dependency);
+            }
+        }
+    }
+
+    UpSelectedOperations {
+        indices,
+        included: included
+            .into_iter()
+            .map(|(index, required_by)| UpIncludedDependency {
+                index,
+                name: operation_label(steps, index),
+                required_by: required_by.into_iter().sorted().dedup().collect(),
+            })
+            .collect(),
+    }
+}
+
+fn invalid_index(index: usize, num_steps: usize) -> UpError {
+    UpError::Config(format!(
+        "invalid operation index {index}, expected a value between 1 and {num_steps}"
+    ))
+}
+
+#[cfg(test)]
+#[path = "selection_test.rs"]
+mod tests;
@@ -0,0 +1,226 @@
+use super::*;
+
+use crate::internal::config::parser::ConfigErrorHandler;
+use crate::internal::config::up::UpConfig;
+use crate::internal::config::ConfigValue;
+
+fn up_config_from_str(yaml: &str) -> UpConfig {
+    let config_value = ConfigValue::from_str(yaml).expect("should parse yaml");
+    UpConfig::from_config_value(Some(config_value), &ConfigErrorHandler::default())
+        .expect("should have an up configuration")
+}
+
+fn values(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn indices(indices: &[usize]) -> BTreeSet<usize> {
+    indices.iter().copied().collect()
+}
+
+mod parse_operation_selection {
+    use super::*;
+
+    const CONFIG: &str = "[ruby, bundler, {github-release: owner/repo}, {custom: {meet: 'true'}}, {custom: {meet: 'true'}}]";
+
+    #[test]
+    fn selects_by_index() {
+        let up_config = up_config_from_str(CONFIG);
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            parse_operation_selection(&values(&["3", "1"]), &steps),
+            Ok(indices(&[0, 2]))
+        );
+    }
+
+    #[test]
+    fn selects_all_operations_with_a_name() {
+        let up_config = up_config_from_str(CONFIG);
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            parse_operation_selection(&values(&["custom"]), &steps),
+            Ok(indices(&[3, 4]))
+        );
+    }
+
+    #[test]
+    fn accepts_comma_separated_values() {
+        let up_config = up_config_from_str(CONFIG);
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            parse_operation_selection(&values(&["github-release, 1", "5"]), &steps),
+            Ok(indices(&[0, 2, 4]))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_index() {
+        let up_config = up_config_from_str(CONFIG);
+        let steps = up_config.available_steps();
+
+        for value in ["0", "6"] {
+            assert_eq!(
+                parse_operation_selection(&values(&[value]), &steps),
+                Err(UpError::Config(format!(
+                    "invalid operation index {value}, expected a value between 1 and 5"
+                )))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_operation() {
+        let up_config = up_config_from_str(CONFIG);
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            parse_operation_selection(&values(&["python"]), &steps),
+            Err(UpError::Config(
+                "unknown operation 'python', expected one of: ruby, bundler, github-release, custom"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_empty_selection() {
+        let up_config = up_config_from_str(CONFIG);
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            parse_operation_selection(&values(&["", " , "]), &steps),
+            Err(UpError::Config("no operation selected".to_string()))
+        );
+    }
+
+    #[test]
+    fn indices_follow_bootstrap_first_order() {
+        let up_config = up_config_from_str("[{custom: {meet: 'true'}}, {bootstrap: 'true'}]");
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            parse_operation_selection(&values(&["bootstrap"]), &steps),
+            Ok(indices(&[0]))
+        );
+    }
+}
+
+mod select_operations {
+    use super::*;
+
+    #[test]
+    fn requested_operations_include_their_dependencies() {
+        let up_config = up_config_from_str("[go, ruby, {custom: {meet: 'true'}}, bundler]");
+        let steps = up_config.available_steps();
+
+        let selected = select_operations(
+            &steps,
+            &UpOperationSelection::Requested(values(&["bundler"])),
+        )
+        .expect("selection should be valid");
+
+        assert_eq!(selected.indices, indices(&[1, 3]));
+        assert_eq!(
+            selected.included,
+            vec![UpIncludedDependency {
+                index: 1,
+                name: "#2 (ruby)".to_string(),
+                required_by: vec!["#4 (bundler)".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn requested_dependencies_are_not_reported_as_included() {
+        let up_config = up_config_from_str("[ruby, bundler]");
+        let steps = up_config.available_steps();
+
+        let selected = select_operations(
+            &steps,
+            &UpOperationSelection::Requested(values(&["1", "2"])),
+        )
+        .expect("selection should be valid");
+
+        assert_eq!(selected.indices, indices(&[0, 1]));
+        assert!(selected.included.is_empty());
+    }
+
+    #[test]
+    fn dependency_required_by_multiple_operations_is_included_once() {
+        let up_config =
+            up_config_from_str("[ruby, bundler, {bundler: alt/Gemfile}, {custom: {meet: 'true'}}]");
+        let steps = up_config.available_steps();
+
+        let selected = select_operations(
+            &steps,
+            &UpOperationSelection::Requested(values(&["bundler"])),
+        )
+        .expect("selection should be valid");
+
+        assert_eq!(selected.indices, indices(&[0, 1, 2]));
+        assert_eq!(
+            selected.included,
+            vec![UpIncludedDependency {
+                index: 0,
+                name: "#1 (ruby)".to_string(),
+                required_by: vec!["#2 (bundler)".to_string(), "#3 (bundler)".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn inferred_runtimes_are_dependencies() {
+        let up_config = up_config_from_str("[go, bundler]").with_inferred_runtimes();
+        let steps = up_config.available_steps();
+
+        let selected = select_operations(
+            &steps,
+            &UpOperationSelection::Requested(values(&["bundler"])),
+        )
+        .expect("selection should be valid");
+
+        assert_eq!(selected.indices, indices(&[1, 2]));
+        assert_eq!(selected.included.len(), 1);
+        assert_eq!(selected.included[0].name, "#2 (ruby)");
+    }
+
+    #[test]
+    fn picked_operations_with_their_dependencies() {
+        let up_config = up_config_from_str("[ruby, bundler, go]");
+        let steps = up_config.available_steps();
+
+        let selected = select_operations(&steps, &UpOperationSelection::Picked(indices(&[0, 1])))
+            .expect("selection should be valid");
+
+        assert_eq!(selected.indices, indices(&[0, 1]));
+        assert!(selected.included.is_empty());
+    }
+
+    #[test]
+    fn picked_operations_without_a_dependency_are_refused() {
+        let up_config = up_config_from_str("[ruby, bundler, go]");
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            select_operations(&steps, &UpOperationSelection::Picked(indices(&[1, 2]))),
+            Err(UpError::Config(
+                "operation #1 (ruby) is required by operation #2 (bundler) but was not selected"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn picked_operations_cannot_be_empty() {
+        let up_config = up_config_from_str("[ruby, bundler]");
+        let steps = up_config.available_steps();
+
+        assert_eq!(
+            select_operations(&steps, &UpOperationSelection::Picked(BTreeSet::new())),
+            Err(UpError::Config("no operation selected".to_string()))
+        );
+    }
+}
@@ -10,8 +10,10 @@ use crate::internal::cache::up_environments::UpEnvironment;
 use crate::internal::config::global_config;
 use crate::internal::config::parser::ConfigErrorHandler;
 use crate::internal::config::parser::ConfigErrorKind;
+use crate::internal::config::parser::UpCommandOperationConfig;
 use crate::internal::config::up::utils::UpProgressHandler;
 use crate::internal::config::up::UpConfig;
+use crate::internal::config::up::UpConfigBootstrap;
 use crate::internal::config::up::UpConfigBundler;
 use crate::internal::config::up::UpConfigCargoInstalls;
 use crate::internal::config::up::UpConfigCustom;
@@ -23,6 +25,7 @@ use crate::internal::config::up::UpConfigMise;
 use crate::internal::config::up::UpConfigMiseParams;
 use crate::internal::config::up::UpConfigNix;
 use crate::internal::config::up::UpConfigNodejs;
+use crate::internal::config::up::UpConfigPipxInstalls;
 use crate::internal::config::up::UpConfigPython;
 use crate::internal::config::up::UpError;
 use crate::internal::config::up::UpOptions;
@@ -48,6 +51,10 @@ pub enum UpConfigTool {
     /// Bash represents the bash tool.
     Bash(UpConfigMise),
 
+    /// Bootstrap represents a command that runs before any other
+    /// operation, to generate the files that those depend on.
+    Bootstrap(UpConfigBootstrap),
+
     /// Bundler represents the bundler tool.
     Bundler(UpConfigBundler),
 
@@ -92,6 +99,10 @@ pub enum UpConfigTool {
     /// only try the others if the first one fails.
     Or(Vec<UpConfigTool>),
 
+    /// Pipx represents python tools installed in their own isolated
+    /// environments, in the way of `pipx install`.
+    Pipx(UpConfigPipxInstalls),
+
     // TODO: Pacman(UpConfigPacman),
     /// Python represents the python tool.
     Python(UpConfigPython),
@@ -115,6 +126,9 @@ impl Serialize for UpConfigTool {
             UpConfigTool::And(configs) => create_hashmap("and", configs).serialize(serializer),
             UpConfigTool::Any(configs) => create_hashmap("any", configs).serialize(serializer),
             UpConfigTool::Bash(config) => create_hashmap("bash", config).serialize(serializer),
+            UpConfigTool::Bootstrap(config) => {
+                create_hashmap("bootstrap", config).serialize(serializer)
+            }
             UpConfigTool::Bundler(config) => {
                 create_hashmap("bundler", config).serialize(serializer)
             }
@@ -138,6 +152,7 @@ impl Serialize for UpConfigTool {
             UpConfigTool::Nix(config) => create_hashmap("nix", config).serialize(serializer),
             UpConfigTool::Nodejs(config) => create_hashmap("nodejs", config).serialize(serializer),
             UpConfigTool::Or(configs) => create_hashmap("or", configs).serialize(serializer),
+            UpConfigTool::Pipx(config) => create_hashmap("pipx", config).serialize(serializer),
             UpConfigTool::Python(config) => create_hashmap("python", config).serialize(serializer),
         }
     }
@@ -157,7 +172,20 @@ impl UpConfigTool {
                     return None;
                 }
 
-                let upconfig = UpConfig::from_config_value(config_value.cloned(), error_handler)?;
+                let mut upconfig =
+                    UpConfig::from_config_value(config_value.cloned(), error_handler)?;
+
+                // Bootstrap operations run before all the other operations,
+                // they thus cannot be part of a combination of operations
+                upconfig.steps.retain(|step| {
+                    if step.is_bootstrap() {
+                        error_handler
+                            .with_key("bootstrap")
+                            .error(ConfigErrorKind::UnsupportedValueInContext);
+                        return false;
+                    }
+                    true
+                });
 
                 if upconfig.steps.is_empty() {
                     error_handler.error(ConfigErrorKind::EmptyKey);
@@ -182,6 +210,9 @@ impl UpConfigTool {
                     error_handler,
                 ),
             )),
+            "bootstrap" => Some(UpConfigTool::Bootstrap(
+                UpConfigBootstrap::from_config_value(config_value, error_handler),
+            )),
             "bundler" | "bundle" => Some(UpConfigTool::Bundler(
                 UpConfigBundler::from_config_value(config_value, error_handler),
             )),
@@ -216,6 +247,9 @@ impl UpConfigTool {
                 config_value,
                 error_handler,
             ))),
+            "pipx" | "pipx-install" | "pipx_install" | "pipxinstall" => Some(UpConfigTool::Pipx(
+                UpConfigPipxInstalls::from_config_value(config_value, error_handler),
+            )),
             "python" => Some(UpConfigTool::Python(UpConfigPython::from_config_value(
                 config_value,
                 error_handler,
@@ -235,7 +269,12 @@ impl UpConfigTool {
         progress_handler: &UpProgressHandler,
     ) -> Result<(), UpError> {
         match self {
-            UpConfigTool::And(_) | UpConfigTool::Any(_) | UpConfigTool::Or(_) => {}
+            // The bootstrap operation runs in a minimal environment, and
+            // does not need the dynamic environment to be updated
+            UpConfigTool::And(_)
+            | UpConfigTool::Any(_)
+            | UpConfigTool::Or(_)
+            | UpConfigTool::Bootstrap(_) => {}
             _ => {
                 // Update the dynamic environment so that if anything has changed
                 // the command can consider it right away
@@ -263,6 +302,7 @@ impl UpConfigTool {
                 result
             }
             UpConfigTool::Bash(config) => config.up(options, environment, progress_handler),
+            UpConfigTool::Bootstrap(config) => config.up(options, progress_handler),
             UpConfigTool::Bundler(config) => config.up(options, environment, progress_handler),
             UpConfigTool::CargoInstall(config) => config.up(options, environment, progress_handler),
             UpConfigTool::Custom(config) => config.up(options, environment, progress_handler),
@@ -275,6 +315,7 @@ impl UpConfigTool {
             UpConfigTool::Mise(config) => config.up(options, environment, progress_handler),
             UpConfigTool::Nix(config) => config.up(options, environment, progress_handler),
             UpConfigTool::Nodejs(config) => config.up(options, environment, progress_handler),
+            UpConfigTool::Pipx(config) => config.up(options, environment, progress_handler),
             UpConfigTool::Or(configs) => {
                 // We stop at the first successful up, we only return
                 // an error if all the configs failed.
@@ -305,6 +346,7 @@ impl UpConfigTool {
                     config.commit(options, env_version_id)?;
                 }
             }
+            UpConfigTool::Bootstrap(_config) => {}
             UpConfigTool::Bundler(_config) => {}
             UpConfigTool::CargoInstall(config) => {
                 if config.was_upped() {
@@ -343,6 +385,11 @@ impl UpConfigTool {
                     config.backend.commit(options, env_version_id)?;
                 }
             }
+            UpConfigTool::Pipx(config) => {
+                if config.was_upped() {
+                    config.commit(options, env_version_id)?;
+                }
+            }
             UpConfigTool::Python(config) => {
                 if config.backend.was_upped() {
                     config.backend.commit(options, env_version_id)?;
@@ -362,6 +409,7 @@ impl UpConfigTool {
                 Ok(())
             }
             UpConfigTool::Bash(config) => config.down(progress_handler),
+            UpConfigTool::Bootstrap(config) => config.down(progress_handler),
             UpConfigTool::Bundler(config) => config.down(progress_handler),
             UpConfigTool::CargoInstall(config) => config.down(progress_handler),
             UpConfigTool::Custom(config) => config.down(progress_handler),
@@ -372,6 +420,7 @@ impl UpConfigTool {
             UpConfigTool::Mise(config) => config.down(progress_handler),
             UpConfigTool::Nix(config) => config.down(progress_handler),
             UpConfigTool::Nodejs(config) => config.down(progress_handler),
+            UpConfigTool::Pipx(config) => config.down(progress_handler),
             UpConfigTool::Python(config) => config.down(progress_handler),
         }
     }
@@ -389,11 +438,18 @@ impl UpConfigTool {
 
     pub fn dir(&self) -> Option<String> {
         match self {
+            UpConfigTool::Bootstrap(config) => config.dir(),
             UpConfigTool::Custom(config) => config.dir(),
             _ => None,
         }
     }
 
+    /// Returns whether this is a bootstrap operation, which needs
+    /// to run before any other operation
+    pub fn is_bootstrap(&self) -> bool {
+        matches!(self, UpConfigTool::Bootstrap(_))
+    }
+
     pub fn was_upped(&self) -> bool {
         match self {
             UpConfigTool::And(configs) | UpConfigTool::Any(configs) | UpConfigTool::Or(configs) => {
@@ -410,6 +466,7 @@ impl UpConfigTool {
             UpConfigTool::Mise(config) => config.was_upped(),
             UpConfigTool::Nix(config) => config.was_upped(),
             UpConfigTool::Nodejs(config) => config.backend.was_upped(),
+            UpConfigTool::Pipx(config) => config.was_upped(),
             UpConfigTool::Python(config) => config.backend.was_upped(),
             _ => false,
         }
@@ -452,6 +509,7 @@ impl UpConfigTool {
             UpConfigTool::Any(_) => "any".into(),
             UpConfigTool::Or(_) => "or".into(),
             UpConfigTool::Bash(_) => "bash".into(),
+            UpConfigTool::Bootstrap(_) => "bootstrap".into(),
             UpConfigTool::Bundler(_) => "bundler".into(),
             UpConfigTool::CargoInstall(_) => "cargo-install".into(),
             UpConfigTool::Custom(_) => "custom".into(),
@@ -462,10 +520,163 @@ impl UpConfigTool {
             UpConfigTool::Mise(config) => config.name(),
             UpConfigTool::Nix(_) => "nix".into(),
             UpConfigTool::Nodejs(_) => "nodejs".into(),
+            UpConfigTool::Pipx(_) => "pipx".into(),
             UpConfigTool::Python(_) => "python".into(),
         }
     }
 
+    /// Returns the backend used to handle this tool, for display purposes
+    pub fn backend(&self) -> &'static str {
+        match self {
+            UpConfigTool::And(_) | UpConfigTool::Any(_) | UpConfigTool::Or(_) => "group",
+            UpConfigTool::Bash(_)
+            | UpConfigTool::Go(_)
+            | UpConfigTool::Mise(_)
+            | UpConfigTool::Nodejs(_)
+            | UpConfigTool::Python(_) => "mise",
+            UpConfigTool::Bootstrap(_) => "bootstrap",
+            UpConfigTool::Bundler(_) => "bundler",
+            UpConfigTool::CargoInstall(_) => "cargo-install",
+            UpConfigTool::Custom(_) => "custom",
+            UpConfigTool::GithubRelease(_) => "github-release",
+            UpConfigTool::GoInstall(_) => "go-install",
+            UpConfigTool::Homebrew(_) => "homebrew",
+            UpConfigTool::Nix(_) => "nix",
+            UpConfigTool::Pipx(_) => "pipx",
+        }
+    }
+
+    /// Returns the keys identifying the tool installations that this
+    /// tool requires, when those are known before upping the tool; the
+    /// tools of `any` and `or` operations are not listed since which one
+    /// gets installed is only known once upping them
+    pub fn install_keys(&self) -> Vec<String> {
+        match self {
+            UpConfigTool::And(configs) => configs
+                .iter()
+                .flat_map(|config| config.install_keys())
+                .unique()
+                .collect(),
+            UpConfigTool::Bash(config) | UpConfigTool::Mise(config) => {
+                config.install_key().into_iter().collect()
+            }
+            UpConfigTool::Go(config) if config.version_file.is_none() => {
+                vec![format!(
+                    "mise:go@{}",
+                    config.version.as_deref().unwrap_or("latest")
+                )]
+            }
+            UpConfigTool::Nodejs(config) => config.backend.install_key().into_iter().collect(),
+            UpConfigTool::Python(config) => config.backend.install_key().into_iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns this tool restricted to what the given operations policy
+    /// allows: the alternatives of `any` that are not allowed are dropped,
+    /// and the tool is refused only if none of them remain
+    pub fn allowed_by(&self, operations: &UpCommandOperationConfig) -> Result<Self, String> {
+        match self {
+            UpConfigTool::And(configs) => Ok(UpConfigTool::And(
+                configs
+                    .iter()
+                    .map(|config| config.allowed_by(operations))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            UpConfigTool::Any(configs) | UpConfigTool::Or(configs) => {
+                let mut reason = None;
+                let allowed = configs
+                    .iter()
+                    .filter_map(|config| match config.allowed_by(operations) {
+                        Ok(config) => Some(config),
+                        Err(err) => {
+                            reason.get_or_insert(err);
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                if let Some(reason) = reason.filter(|_| allowed.is_empty()) {
+                    return Err(reason);
+                }
+
+                Ok(match self {
+                    UpConfigTool::Any(_) => UpConfigTool::Any(allowed),
+                    _ => UpConfigTool::Or(allowed),
+                })
+            }
+            _ => match self.disallowed_reason(operations) {
+                Some(reason) => Err(reason),
+                None => Ok(self.clone()),
+            },
+        }
+    }
+
+    /// Returns why this tool is not allowed by the given operations policy,
+    /// if it is not, so that it can be refused before anything is installed
+    pub fn disallowed_reason(&self, operations: &UpCommandOperationConfig) -> Option<String> {
+        let operation = match self {
+            UpConfigTool::And(_) | UpConfigTool::Any(_) | UpConfigTool::Or(_) => {
+                return self.allowed_by(operations).err();
+            }
+            UpConfigTool::Bash(config) | UpConfigTool::Mise(config) => {
+                return config.disallowed_reason(operations)
+            }
+            UpConfigTool::Nodejs(config) => return config.backend.disallowed_reason(operations),
+            UpConfigTool::Python(config) => return config.backend.disallowed_reason(operations),
+            UpConfigTool::CargoInstall(config) => return config.disallowed_reason(operations),
+            UpConfigTool::GithubRelease(config) => return config.disallowed_reason(operations),
+            UpConfigTool::GoInstall(config) => return config.disallowed_reason(operations),
+            UpConfigTool::Pipx(config) => return config.disallowed_reason(operations),
+            UpConfigTool::Go(_) => "mise",
+            UpConfigTool::Bootstrap(_) => "bootstrap",
+            UpConfigTool::Bundler(_) => "bundler",
+            UpConfigTool::Custom(_) => "custom",
+            UpConfigTool::Homebrew(_) => "homebrew",
+            UpConfigTool::Nix(_) => "nix",
+        };
+
+        if operations.is_operation_allowed(operation) {
+            None
+        } else {
+            Some(format!("{operation} operation is not allowed"))
+        }
+    }
+
+    /// Returns the runtimes that need to be available in the environment
+    /// for this tool to be upped. The `cargo-install` operation is left
+    /// out since it provisions its own toolchain without exposing it, and
+    /// there is no operation installing packages through npm.
+    pub fn required_runtimes(&self) -> Vec<&'static str> {
+        match self {
+            UpConfigTool::And(configs) | UpConfigTool::Any(configs) | UpConfigTool::Or(configs) => {
+                configs
+                    .iter()
+                    .flat_map(|config| config.required_runtimes())
+                    .unique()
+                    .collect()
+            }
+            UpConfigTool::Bundler(_) => vec!["ruby"],
+            UpConfigTool::GoInstall(_) => vec!["go"],
+            UpConfigTool::Pipx(config) => config.required_runtimes(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns whether this tool provides the given runtime
+    pub fn provides_runtime(&self, runtime: &str) -> bool {
+        match self {
+            UpConfigTool::And(configs) | UpConfigTool::Any(configs) | UpConfigTool::Or(configs) => {
+                any(configs, |config| config.provides_runtime(runtime))
+            }
+            UpConfigTool::Go(_) => runtime == "go",
+            UpConfigTool::Mise(config) => config.name() == runtime,
+            UpConfigTool::Nodejs(_) => runtime == "node",
+            UpConfigTool::Python(_) => runtime == "python",
+            _ => false,
+        }
+    }
+
     pub fn sort_value(&self) -> i32 {
         match self {
             UpConfigTool::And(configs) | UpConfigTool::Any(configs) | UpConfigTool::Or(configs) => {
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fs::set_permissions;
 use std::fs::Permissions;
 use std::mem;
@@ -5,6 +6,8 @@ use std::os::unix::fs::FileTypeExt;
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use futures::Future;
 use serde::Deserialize;
@@ -20,9 +23,13 @@ use tokio::net::UnixListener;
 use tokio::net::UnixStream;
 use tokio::process::Command as TokioCommand;
 use tokio::sync::Mutex as TokioMutex;
+use zeroize::Zeroizing;
 
 use crate::internal::config::global_config;
 use crate::internal::config::template::render_askpass_template;
+use crate::internal::config::up::utils::askpass_cache::cached_credential;
+use crate::internal::config::up::utils::askpass_cache::forget_credential;
+use crate::internal::config::up::utils::askpass_cache::CredentialCachePolicy;
 use crate::internal::config::up::utils::force_remove_dir_all;
 use crate::internal::config::up::utils::EventHandlerFn;
 use crate::internal::config::up::utils::Listener;
@@ -47,13 +54,21 @@ pub struct AskPassRequest {
         skip_serializing_if = "String::is_empty"
     )]
     prompt_type: String,
+    #[serde(
+        default,
+        rename = "i",
+        alias = "identity",
+        skip_serializing_if = "String::is_empty"
+    )]
+    identity: String,
 }
 
 impl AskPassRequest {
-    pub fn new(prompt: impl ToString, prompt_type: impl ToString) -> Self {
+    pub fn new(prompt: impl ToString, prompt_type: impl ToString, identity: impl ToString) -> Self {
         Self {
             prompt: prompt.to_string(),
             prompt_type: prompt_type.to_string().to_lowercase(),
+            identity: identity.to_string(),
         }
     }
 
@@ -135,6 +150,22 @@ impl AskPassRequest {
             self.prompt.clone()
         }
     }
+
+    /// Returns the identity of the requested credential, used to reuse
+    /// the credential across operations; defaults to the prompt
+    pub fn identity(&self) -> String {
+        if self.identity.is_empty() {
+            self.prompt()
+        } else {
+            self.identity.clone()
+        }
+    }
+
+    /// Whether the answer to the request is a credential that can be
+    /// reused, as opposed to a confirmation or a simple notice
+    fn is_credential(&self) -> bool {
+        !matches!(self.prompt_type.as_str(), "none" | "confirm")
+    }
 }
 
 #[derive(Debug)]
@@ -146,6 +177,11 @@ struct AskPassListenerInner {
 #[derive(Debug)]
 pub struct AskPassListener {
     inner: TokioMutex<AskPassListenerInner>,
+    cache_policy: CredentialCachePolicy,
+    /// The identities of the credentials already provided to the command
+    /// this listener is for; a new request for one of them means that the
+    /// credential was rejected, so it is prompted for again
+    provided: Arc<Mutex<HashSet<String>>>,
 }
 
 impl Drop for AskPassListener {
@@ -189,10 +225,18 @@ impl Listener for AskPassListener {
                 let lock = self.inner.lock().await;
                 match lock.listener.accept().await {
                     Ok((mut stream, _addr)) => {
+                        let cache_policy = self.cache_policy.clone();
+                        let provided = self.provided.clone();
+
                         // Create the handler function with the correct type
                         let handler: EventHandlerFn = Box::new(move || {
                             Box::pin(async move {
-                                AskPassListener::handle_request(&mut stream).await?;
+                                AskPassListener::handle_request(
+                                    &mut stream,
+                                    &cache_policy,
+                                    &provided,
+                                )
+                                .await?;
                                 Ok(())
                             })
                         });
@@ -350,10 +394,16 @@ impl AskPassListener {
 
         Ok(Some(Self {
             inner: TokioMutex::new(AskPassListenerInner { listener, tmp_dir }),
+            cache_policy: CredentialCachePolicy::from(&config.askpass),
+            provided: Arc::new(Mutex::new(HashSet::new())),
         }))
     }
 
-    pub async fn handle_request(stream: &mut UnixStream) -> Result<(), String> {
+    pub async fn handle_request(
+        stream: &mut UnixStream,
+        cache_policy: &CredentialCachePolicy,
+        provided: &Mutex<HashSet<String>>,
+    ) -> Result<(), String> {
         ensure_newline();
 
         // Read the request object from the stream, byte by byte because
@@ -397,24 +447,26 @@ impl AskPassListener {
             eprintln!("{} {}", "!".green().bold(), request.prompt().bold());
 
             // Return an empty string if the prompt type is "none"
-            "".to_string()
-        } else {
-            let question = requestty::Question::password("askpass_request")
-                .ask_if_answered(true)
-                .on_esc(requestty::OnEsc::Terminate)
-                .message(request.prompt())
-                .build();
-
-            match requestty::prompt_one(question) {
-                Ok(answer) => match answer {
-                    requestty::Answer::String(password) => password,
-                    _ => return Err("no password provided".to_string()),
-                },
-                Err(err) => {
-                    println!("{}", format!("[✘] {err:?}").red());
-                    return Err("no password provided".to_string());
-                }
+            Zeroizing::new("".to_string())
+        } else if request.is_credential() {
+            let identity = request.identity();
+
+            // If the credential was already provided to the same command,
+            // it was most likely rejected, so make sure to prompt again
+            let already_provided = {
+                let mut provided = match provided.lock() {
+                    Ok(provided) => provided,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                !provided.insert(identity.clone())
+            };
+            if already_provided {
+                forget_credential(&identity);
             }
+
+            cached_credential(&identity, cache_policy, || Self::prompt_password(&request))?
+        } else {
+            Self::prompt_password(&request)?
         };
 
         let future = stream.write_all(password.as_bytes());
@@ -428,4 +480,28 @@ impl AskPassListener {
 
         Ok(())
     }
+
+    fn prompt_password(request: &AskPassRequest) -> Result<Zeroizing<String>, String> {
+        prompt_secret("askpass_request", &request.prompt())
+    }
+}
+
+/// Prompts for a secret value without echoing it
+pub fn prompt_secret(id: &str, message: &str) -> Result<Zeroizing<String>, String> {
+    let question = requestty::Question::password(id)
+        .ask_if_answered(true)
+        .on_esc(requestty::OnEsc::Terminate)
+        .message(message)
+        .build();
+
+    match requestty::prompt_one(question) {
+        Ok(answer) => match answer {
+            requestty::Answer::String(password) => Ok(Zeroizing::new(password)),
+            _ => Err("no password provided".to_string()),
+        },
+        Err(err) => {
+            println!("{}", format!("[✘] {err:?}").red());
+            Err("no password provided".to_string())
+        }
+    }
 }
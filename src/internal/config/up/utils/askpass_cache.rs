@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use zeroize::Zeroizing;
+
+use crate::internal::config::parser::AskPassConfig;
+
+/// The credentials obtained through askpass by this process, so that
+/// operations requiring the same credential only prompt for it once; this
+/// is only ever kept in memory, and never written anywhere
+static CREDENTIALS_CACHE: Lazy<Mutex<CredentialsCache>> =
+    Lazy::new(|| Mutex::new(CredentialsCache::new()));
+
+/// Clears the credentials cached for this process, e.g. when the process
+/// is interrupted
+pub fn clear_credentials_cache() {
+    let mut cache = match CREDENTIALS_CACHE.lock() {
+        Ok(cache) => cache,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cache.clear();
+}
+
+/// Returns the credential for the given identity, reusing the cached one
+/// if any, or prompting for it otherwise; the prompt is not run while the
+/// cache is locked, so that it can still be cleared during the prompt.
+pub fn cached_credential<F>(
+    identity: &str,
+    policy: &CredentialCachePolicy,
+    prompt: F,
+) -> Result<Zeroizing<String>, String>
+where
+    F: FnOnce() -> Result<Zeroizing<String>, String>,
+{
+    let cached = {
+        let mut cache = match CREDENTIALS_CACHE.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        cache.get(identity, policy, Instant::now())
+    };
+    if let Some(credential) = cached {
+        return Ok(credential);
+    }
+
+    let credential = prompt()?;
+
+    let mut cache = match CREDENTIALS_CACHE.lock() {
+        Ok(cache) => cache,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cache.insert(identity, &credential, policy, Instant::now());
+
+    Ok(credential)
+}
+
+/// Removes the cached credential for the given identity, e.g. when it
+/// was requested again after being provided, indicating it was rejected
+pub fn forget_credential(identity: &str) {
+    let mut cache = match CREDENTIALS_CACHE.lock() {
+        Ok(cache) => cache,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cache.remove(identity);
+}
+
+/// How the credentials obtained through askpass can be cached
+#[derive(Debug, Clone, Default)]
+pub struct CredentialCachePolicy {
+    /// How long a credential can be reused for; credentials are not
+    /// cached if zero
+    pub ttl: Duration,
+    /// The patterns of the identities of the credentials that must
+    /// always be prompted for
+    pub always_prompt: Vec<String>,
+}
+
+impl From<&AskPassConfig> for CredentialCachePolicy {
+    fn from(config: &AskPassConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.cache_ttl),
+            always_prompt: config.always_prompt.clone(),
+        }
+    }
+}
+
+impl CredentialCachePolicy {
+    /// Whether the credential for the given identity can be cached
+    pub fn allows(&self, identity: &str) -> bool {
+        !self.ttl.is_zero()
+            && !self.always_prompt.iter().any(|pattern| {
+                glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(identity))
+            })
+    }
+}
+
+struct CachedCredential {
+    secret: Zeroizing<String>,
+    expires_at: Instant,
+}
+
+/// The cached credentials, indexed by identity; the secrets are zeroed
+/// when removed from the cache or when the cache is dropped
+pub(crate) struct CredentialsCache {
+    entries: HashMap<String, CachedCredential>,
+}
+
+impl fmt::Debug for CredentialsCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never show the identities or the secrets
+        f.debug_struct("CredentialsCache")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl CredentialsCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn get(
+        &mut self,
+        identity: &str,
+        policy: &CredentialCachePolicy,
+        now: Instant,
+    ) -> Option<Zeroizing<String>> {
+        if !policy.allows(identity) {
+            return None;
+        }
+
+        match self.entries.get(identity) {
+            Some(entry) if entry.expires_at > now => Some(entry.secret.clone()),
+            Some(_) => {
+                self.entries.remove(identity);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        identity: &str,
+        secret: &Zeroizing<String>,
+        policy: &CredentialCachePolicy,
+        now: Instant,
+    ) {
+        if !policy.allows(identity) {
+            return;
+        }
+
+        self.entries.insert(
+            identity.to_string(),
+            CachedCredential {
+                secret: secret.clone(),
+                expires_at: now + policy.ttl,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, identity: &str) {
+        self.entries.remove(identity);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+#[path = "askpass_cache_test.rs"]
+mod tests;
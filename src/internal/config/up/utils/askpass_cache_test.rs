@@ -0,0 +1,153 @@
+use super::*;
+
+use std::cell::Cell;
+
+/// A fake askpass backend counting how many times it prompted
+struct CountingAskPass {
+    prompts: Cell<usize>,
+}
+
+impl CountingAskPass {
+    fn new() -> Self {
+        Self {
+            prompts: Cell::new(0),
+        }
+    }
+
+    fn prompt(&self) -> Result<Zeroizing<String>, String> {
+        self.prompts.set(self.prompts.get() + 1);
+        Ok(Zeroizing::new(format!("secret-{}", self.prompts.get())))
+    }
+}
+
+fn policy(ttl: u64, always_prompt: &[&str]) -> CredentialCachePolicy {
+    CredentialCachePolicy {
+        ttl: Duration::from_secs(ttl),
+        always_prompt: always_prompt.iter().map(|p| p.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_prompts_once_across_operations() {
+    let askpass = CountingAskPass::new();
+    let policy = policy(300, &[]);
+
+    for _ in 0..3 {
+        let credential = cached_credential("test-single-prompt:github.com", &policy, || {
+            askpass.prompt()
+        })
+        .expect("should get a credential");
+        assert_eq!(*credential, "secret-1");
+    }
+
+    assert_eq!(askpass.prompts.get(), 1);
+}
+
+#[test]
+fn test_identities_are_cached_separately() {
+    let askpass = CountingAskPass::new();
+    let policy = policy(300, &[]);
+
+    let first = cached_credential("test-separate:one", &policy, || askpass.prompt()).unwrap();
+    let second = cached_credential("test-separate:two", &policy, || askpass.prompt()).unwrap();
+
+    assert_eq!(*first, "secret-1");
+    assert_eq!(*second, "secret-2");
+    assert_eq!(askpass.prompts.get(), 2);
+}
+
+#[test]
+fn test_failed_prompt_is_not_cached() {
+    let askpass = CountingAskPass::new();
+    let policy = policy(300, &[]);
+
+    let result = cached_credential("test-failed:host", &policy, || {
+        Err("no password provided".to_string())
+    });
+    assert!(result.is_err());
+
+    let credential = cached_credential("test-failed:host", &policy, || askpass.prompt()).unwrap();
+    assert_eq!(*credential, "secret-1");
+    assert_eq!(askpass.prompts.get(), 1);
+}
+
+#[test]
+fn test_ttl_expiry() {
+    let policy = policy(60, &[]);
+    let mut cache = CredentialsCache::new();
+    let now = Instant::now();
+
+    cache.insert("host", &Zeroizing::new("secret".to_string()), &policy, now);
+
+    assert_eq!(
+        cache
+            .get("host", &policy, now + Duration::from_secs(59))
+            .as_deref()
+            .map(String::as_str),
+        Some("secret")
+    );
+    assert!(cache
+        .get("host", &policy, now + Duration::from_secs(60))
+        .is_none());
+
+    // The expired entry is removed from the cache
+    assert!(cache.get("host", &policy, now).is_none());
+}
+
+#[test]
+fn test_zero_ttl_disables_cache() {
+    let askpass = CountingAskPass::new();
+    let policy = policy(0, &[]);
+
+    for _ in 0..2 {
+        cached_credential("test-zero-ttl:host", &policy, || askpass.prompt()).unwrap();
+    }
+
+    assert_eq!(askpass.prompts.get(), 2);
+}
+
+#[test]
+fn test_always_prompt_opt_out() {
+    let askpass = CountingAskPass::new();
+    let policy = policy(300, &["sudo:*"]);
+
+    for _ in 0..2 {
+        cached_credential("sudo:[sudo] password for test:", &policy, || {
+            askpass.prompt()
+        })
+        .unwrap();
+    }
+    assert_eq!(askpass.prompts.get(), 2);
+
+    for _ in 0..2 {
+        cached_credential("test-opt-out:host", &policy, || askpass.prompt()).unwrap();
+    }
+    assert_eq!(askpass.prompts.get(), 3);
+}
+
+#[test]
+fn test_forget_credential() {
+    let askpass = CountingAskPass::new();
+    let policy = policy(300, &[]);
+
+    cached_credential("test-forget:host", &policy, || askpass.prompt()).unwrap();
+    forget_credential("test-forget:host");
+    let credential = cached_credential("test-forget:host", &policy, || askpass.prompt()).unwrap();
+
+    assert_eq!(*credential, "secret-2");
+}
+
+#[test]
+fn test_debug_output_hides_credentials() {
+    let policy = policy(300, &[]);
+    let mut cache = CredentialsCache::new();
+    cache.insert(
+        "vault/path",
+        &Zeroizing::new("hunter2".to_string()),
+        &policy,
+        Instant::now(),
+    );
+
+    let debug = format!("{cache:?}");
+    assert_eq!(debug, "CredentialsCache { entries: 1 }");
+}
@@ -0,0 +1,271 @@
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use blake3::Hasher;
+
+use crate::internal::config::up::utils::ProgressHandler;
+use crate::internal::config::up::UpError;
+use crate::internal::env::data_home;
+use crate::internal::user_interface::StringColor;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+
+/// The suffix of the temporary links created while replacing a file
+/// of an installation by a link to an object of the store
+const LINK_TMP_SUFFIX: &str = ".omni-store-link";
+
+/// A content-addressed store holding the files of the installed tools,
+/// so that identical files across tool versions, or across backends,
+/// are only stored once on disk.
+///
+/// The files of an installation are hard links to the objects of the
+/// store, which makes the store transparent to anything using the tool
+/// paths. The objects are read-only, so that a tool writing to one of
+/// its files cannot change the files of the other installations. The
+/// number of links of an object is its reference count: an object only
+/// linked from the store is not used by any installation anymore, and
+/// can be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+impl ContentStore {
+    pub fn get() -> Self {
+        Self::new(PathBuf::from(data_home()).join("store"))
+    }
+
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Moves the files of the installation at the given path to the
+    /// store, replacing each of them by a hard link to its object; the
+    /// files that cannot be linked, e.g. if the store is on a different
+    /// device, are kept as copies. Returns the number of files linked.
+    pub fn materialize(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let mut linked = 0;
+
+        // List the files first, as linking them creates temporary files
+        // in the directories being walked
+        let mut files = vec![];
+        for entry in walkdir::WalkDir::new(path.as_ref()) {
+            let entry = entry.map_err(io::Error::other)?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            // A temporary link left behind by an interrupted materialization
+            // would otherwise keep its object referenced forever, while the
+            // file it was meant to replace is still in place
+            if is_leftover_link(entry.path()) {
+                std::fs::remove_file(entry.path())?;
+                continue;
+            }
+
+            files.push(entry.into_path());
+        }
+
+        for file_path in files {
+            let object_path = self.object_path(&object_hash(&file_path)?);
+
+            if self.link(&file_path, &object_path)? {
+                linked += 1;
+            }
+        }
+
+        Ok(linked)
+    }
+
+    /// Returns whether the installation at the given path holds temporary
+    /// links left behind by an interrupted [`ContentStore::materialize`],
+    /// in which case it should be materialized again
+    pub fn has_leftover_links(path: impl AsRef<Path>) -> bool {
+        walkdir::WalkDir::new(path.as_ref())
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.file_type().is_file() && is_leftover_link(entry.path()))
+    }
+
+    /// Links the given file to the given object, adding the object to
+    /// the store if it is not there yet; returns whether the file could
+    /// be linked
+    fn link(&self, file_path: &Path, object_path: &Path) -> io::Result<bool> {
+        if !object_path.exists() {
+            if let Some(parent) = object_path.parent() {
+                if create_dir_all(parent, PathKind::Shared).is_err() {
+                    return Ok(false);
+                }
+            }
+
+            match std::fs::hard_link(file_path, object_path) {
+                Ok(()) => {
+                    make_read_only(object_path)?;
+                    return Ok(true);
+                }
+                // Another installation added the same object meanwhile
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+                // The store cannot hold links to this file, e.g. as it
+                // is on a different device, keep the file as a copy
+                Err(_) => return Ok(false),
+            }
+        }
+        make_read_only(object_path)?;
+
+        // The file might already be a link to the object, in which case
+        // renaming a new link over it would be a no-op
+        if same_file(file_path, object_path) {
+            return Ok(true);
+        }
+
+        // Link the object next to the file before renaming it over the
+        // file, so that the file is never missing from the installation
+        let mut tmp_path = file_path.as_os_str().to_owned();
+        tmp_path.push(LINK_TMP_SUFFIX);
+        let tmp_path = PathBuf::from(tmp_path);
+
+        if std::fs::hard_link(object_path, &tmp_path).is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Ok(false);
+        }
+
+        if let Err(err) = std::fs::rename(&tmp_path, file_path) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the path of the object with the given hash
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(hash)
+    }
+
+    /// Returns the number of installation files referencing the object
+    /// with the given hash, or `None` if the object is not in the store
+    pub fn references(&self, hash: &str) -> Option<u64> {
+        let metadata = std::fs::metadata(self.object_path(hash)).ok()?;
+        Some(metadata.nlink().saturating_sub(1))
+    }
+
+    /// Returns the hashes of the objects in the store
+    pub fn objects(&self) -> Vec<String> {
+        if !self.root.is_dir() {
+            return vec![];
+        }
+
+        walkdir::WalkDir::new(&self.root)
+            .min_depth(2)
+            .max_depth(2)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// Removes the objects that are not referenced by any installation
+    /// anymore, as well as the directories left empty; returns the
+    /// hashes of the objects that were removed
+    pub fn gc(&self) -> io::Result<Vec<String>> {
+        let mut removed = vec![];
+
+        for hash in self.objects() {
+            if self.references(&hash) != Some(0) {
+                continue;
+            }
+
+            match std::fs::remove_file(self.object_path(&hash)) {
+                Ok(()) => removed.push(hash),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        if self.root.is_dir() {
+            for entry in std::fs::read_dir(&self.root)? {
+                let entry = entry?;
+                // Only succeeds if the directory is empty
+                let _ = std::fs::remove_dir(entry.path());
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Removes the objects of the store that are not referenced by any
+    /// installation anymore, which happens once all the installations
+    /// sharing them were removed
+    pub fn cleanup(progress_handler: &dyn ProgressHandler) -> Result<Option<String>, UpError> {
+        let store = Self::get();
+
+        let removed = store.gc().map_err(|err| {
+            let msg = format!("failed to cleanup the content store: {err}");
+            progress_handler.progress(msg.clone());
+            UpError::Exec(msg)
+        })?;
+
+        if removed.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(format!(
+            "removed {} unreferenced object{} from the content store",
+            removed.len().light_yellow(),
+            if removed.len() > 1 { "s" } else { "" },
+        )))
+    }
+}
+
+/// Whether the given path is a temporary link created while replacing
+/// a file by a link to an object of the store
+fn is_leftover_link(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(LINK_TMP_SUFFIX))
+}
+
+/// Removes the write permissions of the given object; since all the
+/// installation files linked to an object share its contents, writing
+/// to one of them would otherwise silently change all the others
+fn make_read_only(object_path: &Path) -> io::Result<()> {
+    let mut permissions = std::fs::metadata(object_path)?.permissions();
+    let mode = permissions.mode();
+    if mode & 0o222 != 0 {
+        permissions.set_mode(mode & !0o222);
+        std::fs::set_permissions(object_path, permissions)?;
+    }
+
+    Ok(())
+}
+
+/// Whether the given paths are links to the same file
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// Returns the hash identifying the contents of the given file; the
+/// permissions are part of the hash, since all the links to an object
+/// share the same permissions, except for the write permissions that
+/// the objects never have
+fn object_hash(path: &Path) -> io::Result<String> {
+    let metadata = std::fs::metadata(path)?;
+
+    let mut hasher = Hasher::new();
+    hasher.update(&(metadata.mode() & 0o7555).to_le_bytes());
+    io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+#[path = "content_store_test.rs"]
+mod tests;
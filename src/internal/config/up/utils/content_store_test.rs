@@ -0,0 +1,221 @@
+use super::*;
+
+use std::os::unix::fs::PermissionsExt;
+
+use crate::internal::config::up::utils::force_remove_dir_all;
+use crate::internal::config::up::utils::is_installed;
+use crate::internal::config::up::utils::InstallMarker;
+use crate::internal::config::up::utils::InstallStaging;
+
+/// Installs a fixture version of a tool under the given root, through
+/// the content store, with the given files and their contents
+fn install(
+    store: &ContentStore,
+    root: &Path,
+    version: &str,
+    files: &[(&str, &str, u32)],
+) -> PathBuf {
+    let target = root.join("tool").join(version);
+
+    let staging = InstallStaging::new(&target)
+        .expect("failed to create staging")
+        .with_content_store(store.clone());
+
+    for (name, contents, mode) in files {
+        let path = staging.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("failed to create directory");
+        std::fs::write(&path, contents).expect("failed to write file");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(*mode))
+            .expect("failed to set permissions");
+    }
+
+    staging
+        .commit(&InstallMarker::new(vec![]))
+        .expect("failed to commit")
+}
+
+fn inode(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .expect("failed to read metadata")
+        .ino()
+}
+
+mod content_store {
+    use super::*;
+
+    const V1_FILES: &[(&str, &str, u32)] = &[
+        ("bin/tool", "tool v1", 0o755),
+        ("lib/shared.txt", "shared library", 0o644),
+        ("README.md", "documentation", 0o644),
+    ];
+
+    const V2_FILES: &[(&str, &str, u32)] = &[
+        ("bin/tool", "tool v2", 0o755),
+        ("lib/shared.txt", "shared library", 0o644),
+        ("README.md", "documentation", 0o644),
+    ];
+
+    #[test]
+    fn test_shared_files_are_stored_once() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        let v1 = install(&store, tempdir.path(), "1.0.0", V1_FILES);
+        let v2 = install(&store, tempdir.path(), "2.0.0", V2_FILES);
+
+        // Two binaries, and the two files shared by both versions
+        assert_eq!(store.objects().len(), 4);
+
+        for shared in ["lib/shared.txt", "README.md"] {
+            assert_eq!(inode(&v1.join(shared)), inode(&v2.join(shared)));
+        }
+        assert_ne!(inode(&v1.join("bin/tool")), inode(&v2.join("bin/tool")));
+
+        let shared_hash = object_hash(&v1.join("lib/shared.txt")).unwrap();
+        assert_eq!(store.references(&shared_hash), Some(2));
+    }
+
+    #[test]
+    fn test_installations_are_materialized() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        install(&store, tempdir.path(), "1.0.0", V1_FILES);
+        let v2 = install(&store, tempdir.path(), "2.0.0", V2_FILES);
+
+        for (name, contents, mode) in V2_FILES {
+            let path = v2.join(name);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), *contents);
+            assert_eq!(
+                std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+                *mode & !0o222
+            );
+        }
+        assert!(is_installed(&v2));
+    }
+
+    #[test]
+    fn test_same_contents_with_different_permissions_are_not_shared() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        let v1 = install(&store, tempdir.path(), "1.0.0", &[("tool", "same", 0o755)]);
+        let v2 = install(&store, tempdir.path(), "2.0.0", &[("tool", "same", 0o644)]);
+
+        assert_eq!(store.objects().len(), 2);
+        assert_ne!(inode(&v1.join("tool")), inode(&v2.join("tool")));
+    }
+
+    #[test]
+    fn test_gc_removes_objects_once_unreferenced() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        let v1 = install(&store, tempdir.path(), "1.0.0", V1_FILES);
+        let v2 = install(&store, tempdir.path(), "2.0.0", V2_FILES);
+
+        // Objects still referenced by an installation are kept
+        assert!(store.gc().unwrap().is_empty());
+
+        // Only the binary of the pruned version is not referenced anymore
+        force_remove_dir_all(&v1).expect("failed to remove version");
+        assert_eq!(store.gc().unwrap().len(), 1);
+        assert_eq!(store.objects().len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(v2.join("lib/shared.txt")).unwrap(),
+            "shared library"
+        );
+
+        force_remove_dir_all(&v2).expect("failed to remove version");
+        assert_eq!(store.gc().unwrap().len(), 3);
+        assert!(store.objects().is_empty());
+        assert_eq!(
+            std::fs::read_dir(store.root)
+                .expect("failed to read store")
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_materialize_is_idempotent() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        let dir = tempdir.path().join("tool");
+        std::fs::create_dir_all(&dir).expect("failed to create directory");
+        std::fs::write(dir.join("tool"), "tool").expect("failed to write file");
+
+        assert_eq!(store.materialize(&dir).unwrap(), 1);
+        let before = inode(&dir.join("tool"));
+
+        assert_eq!(store.materialize(&dir).unwrap(), 1);
+        assert_eq!(inode(&dir.join("tool")), before);
+        assert_eq!(store.objects().len(), 1);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_materialize_removes_leftover_links() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        let v1 = install(&store, tempdir.path(), "1.0.0", V1_FILES);
+        let hash = object_hash(&v1.join("bin/tool")).unwrap();
+
+        // Simulate a materialization interrupted before the temporary
+        // link of the object could be renamed over the file
+        let leftover = v1.join(format!("bin/tool{LINK_TMP_SUFFIX}"));
+        std::fs::hard_link(store.object_path(&hash), &leftover).expect("failed to link object");
+        assert!(ContentStore::has_leftover_links(&v1));
+        assert_eq!(store.references(&hash), Some(2));
+
+        store.materialize(&v1).expect("failed to materialize");
+        assert!(!leftover.exists());
+        assert!(!ContentStore::has_leftover_links(&v1));
+        assert_eq!(store.references(&hash), Some(1));
+
+        // The objects can then be removed once the version is pruned
+        force_remove_dir_all(&v1).expect("failed to remove version");
+        store.gc().expect("failed to gc");
+        assert!(store.objects().is_empty());
+    }
+
+    #[test]
+    fn test_shared_files_are_read_only() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        let v1 = install(&store, tempdir.path(), "1.0.0", V1_FILES);
+        let v2 = install(&store, tempdir.path(), "2.0.0", V2_FILES);
+
+        // The files of one version cannot be written to, as that would
+        // change the files of the other
+        for (name, _, _) in V1_FILES {
+            let mode = std::fs::metadata(v1.join(name))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o222, 0, "{name} is writable");
+        }
+
+        // Materializing the version again reuses the same objects, only
+        // adding the install marker written after the files were stored
+        let objects = store.objects().len();
+        store.materialize(&v1).expect("failed to materialize");
+        assert_eq!(store.objects().len(), objects + 1);
+        assert_eq!(
+            inode(&v1.join("lib/shared.txt")),
+            inode(&v2.join("lib/shared.txt"))
+        );
+    }
+
+    #[test]
+    fn test_gc_on_missing_store() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let store = ContentStore::new(tempdir.path().join("store"));
+
+        assert!(store.objects().is_empty());
+        assert!(store.gc().unwrap().is_empty());
+    }
+}
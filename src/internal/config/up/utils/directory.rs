@@ -10,8 +10,15 @@ use itertools::any;
 use normalize_path::NormalizePath;
 
 use crate::internal::config::loader::WORKDIR_CONFIG_FILES;
+use crate::internal::config::parser::UpDataPathsPolicy;
+use crate::internal::config::up::utils::install_lock_path;
+use crate::internal::config::up::utils::is_installed;
+use crate::internal::config::up::utils::InstallLock;
+use crate::internal::config::up::utils::InstallMarker;
+use crate::internal::config::up::utils::InstallStaging;
 use crate::internal::config::up::utils::ProgressHandler;
 use crate::internal::config::up::UpError;
+use crate::internal::env::data_home;
 use crate::internal::utils::base62_encode;
 use crate::internal::workdir;
 
@@ -31,6 +38,84 @@ pub fn data_path_dir_hash(dir: &str) -> String {
     }
 }
 
+/// Return the data path to use for the given version of a tool and
+/// subdirectory of the work directory. When the data paths are shared,
+/// the path only depends on the tool and version, so that all the work
+/// directories using that tool version use the same data path; that
+/// path is outside of the data path of any work directory and is thus
+/// never removed when cleaning up a work directory.
+pub fn version_data_path(
+    policy: UpDataPathsPolicy,
+    wd_data_path: &Path,
+    normalized_name: &str,
+    version: &str,
+    dir: &str,
+) -> PathBuf {
+    match policy {
+        UpDataPathsPolicy::Isolated => wd_data_path
+            .join(normalized_name)
+            .join(version)
+            .join(data_path_dir_hash(dir)),
+        UpDataPathsPolicy::Shared => shared_data_path(normalized_name, version),
+    }
+}
+
+/// Return the path under which the data paths shared across work
+/// directories are stored
+pub fn shared_data_home() -> PathBuf {
+    PathBuf::from(data_home()).join("shared")
+}
+
+/// Return the data path shared across work directories for the given
+/// version of a tool
+pub fn shared_data_path(normalized_name: &str, version: &str) -> PathBuf {
+    shared_data_home().join(normalized_name).join(version)
+}
+
+/// Make sure that the given data path exists before it gets used. Shared
+/// data paths are used by all the work directories using the same tool
+/// version, so they are created under a lock and moved into place once
+/// ready, so that concurrent work directories never see a partial one;
+/// isolated data paths are left to be created by the tools using them.
+pub fn setup_version_data_path(policy: UpDataPathsPolicy, path: &Path) -> Result<(), UpError> {
+    if policy != UpDataPathsPolicy::Shared || is_installed(path) {
+        return Ok(());
+    }
+
+    let setup = || -> io::Result<()> {
+        let _lock = InstallLock::acquire(path)?;
+        if is_installed(path) {
+            return Ok(());
+        }
+
+        let staging = InstallStaging::new(path)?;
+        staging.commit(&InstallMarker::new(vec![]))?;
+
+        Ok(())
+    };
+
+    setup().map_err(|err| {
+        UpError::Exec(format!(
+            "failed to set up data path {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+/// Remove the data path shared across work directories for the given
+/// version of a tool, along with its lock file; this is called when the
+/// tool version is removed, since no work directory can use it anymore
+pub fn remove_shared_data_path(normalized_name: &str, version: &str) -> io::Result<()> {
+    let path = shared_data_path(normalized_name, version);
+
+    let lock = InstallLock::acquire(&path)?;
+    force_remove_dir_all(&path)?;
+    drop(lock);
+
+    force_remove_all(install_lock_path(&path)?)
+}
+
 /// Remove the given path, recursively if it is a directory, even
 /// if it contains read-only files. This will first try to remove
 /// the path normally, and if that fails with a PermissionDenied
@@ -286,3 +371,7 @@ pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<
     }
     Ok(())
 }
+
+#[cfg(test)]
+#[path = "directory_test.rs"]
+mod tests;
@@ -0,0 +1,94 @@
+use super::*;
+
+use crate::internal::config::up::utils::staging::STAGING_DIR_PREFIX;
+use crate::internal::testutils::run_with_env;
+
+fn staging_entries(path: &Path) -> Vec<String> {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.starts_with(STAGING_DIR_PREFIX))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+mod setup_version_data_path {
+    use super::*;
+
+    #[test]
+    fn test_creates_shared_data_path() {
+        run_with_env(&[], || {
+            let path = shared_data_path("ruby", "3.1.0");
+            assert!(!path.exists());
+
+            setup_version_data_path(UpDataPathsPolicy::Shared, &path)
+                .expect("failed to set up data path");
+
+            assert!(path.is_dir());
+            assert!(is_installed(&path));
+            assert!(staging_entries(path.parent().unwrap()).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_keeps_existing_shared_data_path() {
+        run_with_env(&[], || {
+            let path = shared_data_path("ruby", "3.1.0");
+            std::fs::create_dir_all(path.join("gems")).expect("failed to create directory");
+
+            setup_version_data_path(UpDataPathsPolicy::Shared, &path)
+                .expect("failed to set up data path");
+
+            assert!(path.join("gems").is_dir());
+            assert!(is_installed(&path));
+        });
+    }
+
+    #[test]
+    fn test_leaves_isolated_data_path() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let path = version_data_path(
+            UpDataPathsPolicy::Isolated,
+            tempdir.path(),
+            "ruby",
+            "3.1.0",
+            "",
+        );
+
+        setup_version_data_path(UpDataPathsPolicy::Isolated, &path)
+            .expect("failed to set up data path");
+
+        assert!(!path.exists());
+    }
+}
+
+mod remove_shared_data_path {
+    use super::*;
+
+    #[test]
+    fn test_removes_data_path_and_lock() {
+        run_with_env(&[], || {
+            let path = shared_data_path("ruby", "3.1.0");
+            setup_version_data_path(UpDataPathsPolicy::Shared, &path)
+                .expect("failed to set up data path");
+            std::fs::write(path.join("data"), "data").expect("failed to write file");
+            assert!(install_lock_path(&path).unwrap().exists());
+
+            remove_shared_data_path("ruby", "3.1.0").expect("failed to remove data path");
+
+            assert!(!path.exists());
+            assert!(!install_lock_path(&path).unwrap().exists());
+        });
+    }
+
+    #[test]
+    fn test_missing_data_path() {
+        run_with_env(&[], || {
+            remove_shared_data_path("ruby", "3.1.0").expect("failed to remove data path");
+            assert!(!shared_data_path("ruby", "3.1.0").exists());
+        });
+    }
+}
@@ -1,15 +1,26 @@
 pub(crate) mod askpass;
+pub(crate) use askpass::prompt_secret;
 pub(crate) use askpass::AskPassListener;
 pub(crate) use askpass::AskPassRequest;
 
+pub(crate) mod askpass_cache;
+pub(crate) use askpass_cache::clear_credentials_cache;
+
 mod command_ext;
 pub(crate) use command_ext::CommandExt;
 
+pub(crate) mod content_store;
+pub(crate) use content_store::ContentStore;
+
 pub(crate) mod directory;
 pub(crate) use directory::cleanup_path;
 pub(crate) use directory::data_path_dir_hash;
 pub(crate) use directory::force_remove_dir_all;
 pub(crate) use directory::get_config_mod_times;
+pub(crate) use directory::remove_shared_data_path;
+pub(crate) use directory::setup_version_data_path;
+pub(crate) use directory::shared_data_home;
+pub(crate) use directory::version_data_path;
 
 mod listener_manager;
 pub(crate) use listener_manager::EventHandlerFn;
@@ -38,6 +49,19 @@ pub(crate) use shims::reshim;
 pub(crate) mod spinner_progress_handler;
 pub(crate) use spinner_progress_handler::SpinnerProgressHandler;
 
+pub(crate) mod staging;
+pub(crate) use staging::install_lock_path;
+pub(crate) use staging::is_installed;
+pub(crate) use staging::sweep_staging_dirs;
+pub(crate) use staging::InstallLock;
+pub(crate) use staging::InstallMarker;
+pub(crate) use staging::InstallMarkerAsset;
+pub(crate) use staging::InstallStaging;
+
+pub(crate) mod up_events;
+pub(crate) use up_events::UpEventStep;
+pub(crate) use up_events::UpEventWriter;
+
 pub(crate) mod up_progress_handler;
 pub(crate) use up_progress_handler::SyncUpdateInit;
 pub(crate) use up_progress_handler::SyncUpdateInitOption;
@@ -45,12 +69,13 @@ pub(crate) use up_progress_handler::SyncUpdateListener;
 pub(crate) use up_progress_handler::SyncUpdateOperation;
 pub(crate) use up_progress_handler::UpProgressHandler;
 
+pub(crate) mod up_summary;
+pub(crate) use up_summary::UpSummaryWriter;
+
 pub(crate) mod version;
 pub(crate) use version::VersionMatcher;
 pub(crate) use version::VersionParser;
 pub(crate) use version::VersionParserOptions;
 
-#[cfg(test)]
 pub(crate) mod void_progress_handler;
-#[cfg(test)]
 pub(crate) use void_progress_handler::VoidProgressHandler;
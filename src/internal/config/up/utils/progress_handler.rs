@@ -15,6 +15,10 @@ use crate::internal::user_interface::print::filter_control_characters;
 use crate::internal::user_interface::StringColor;
 use crate::omni_warning;
 
+/// How long to keep reading the output left in the streams of a command
+/// once it has finished
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
 pub trait ProgressHandler: Send + Sync {
     fn println(&self, message: String);
     fn progress(&self, message: String);
@@ -24,6 +28,11 @@ pub trait ProgressHandler: Send + Sync {
     fn error_with_message(&self, message: String);
     fn hide(&self);
     fn show(&self);
+
+    /// Reports the progress of a download, in bytes; this is only
+    /// used for the events of the operation, and does not show
+    /// anything by default
+    fn download_progress(&self, _message: String, _downloaded: u64, _total: Option<u64>) {}
 }
 
 impl std::fmt::Debug for dyn ProgressHandler {
@@ -421,8 +430,8 @@ where
                     }
                     _ = command.wait() => {
                         // The command has finished, we can stop reading
-                        stdout_open = false;
-                        stderr_open = false;
+                        // once we got what is left of its output
+                        break;
                     }
                 }
 
@@ -430,6 +439,29 @@ where
                     break;
                 }
             }
+
+            // Read the output left in the streams; this does not wait for
+            // processes left in the background, which could keep the
+            // streams open after the command has finished
+            let drain = async {
+                while stdout_open || stderr_open {
+                    tokio::select! {
+                        stdout_line = stdout_reader.next_line(), if stdout_open => match stdout_line {
+                            Ok(Some(line)) => handler_fn(Some(if run_config.strip_ctrl_chars {
+                                filter_control_characters(&line)
+                            } else { line }), None),
+                            _ => stdout_open = false,
+                        },
+                        stderr_line = stderr_reader.next_line(), if stderr_open => match stderr_line {
+                            Ok(Some(line)) => handler_fn(None, Some(if run_config.strip_ctrl_chars {
+                                filter_control_characters(&line)
+                            } else { line })),
+                            _ => stderr_open = false,
+                        },
+                    }
+                }
+            };
+            let _ = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, drain).await;
         }
 
         // Close the listener
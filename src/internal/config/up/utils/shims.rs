@@ -6,19 +6,24 @@ use std::os::unix::process::CommandExt;
 use std::path::PathBuf;
 use std::process::exit;
 
-use crate::internal::commands::utils::abs_path;
+use crate::internal::commands::utils::logical_abs_path;
+use crate::internal::config::parser::UpAmbiguousVersionsPolicy;
 use crate::internal::config::up::mise::mise_path;
 use crate::internal::config::up::utils::cleanup_path;
 use crate::internal::config::up::utils::directory::force_remove_all;
 use crate::internal::config::up::utils::ProgressHandler;
 use crate::internal::config::up::UpError;
 use crate::internal::config::utils::is_executable;
+use crate::internal::dynenv::ambiguous_versions_for_binary;
+use crate::internal::dynenv::ambiguous_versions_message;
 use crate::internal::dynenv::update_dynamic_env_for_command;
 use crate::internal::env::current_exe;
 use crate::internal::env::data_home;
 use crate::internal::env::shims_dir;
 use crate::internal::env::tmpdir_cleanup_prefix;
 use crate::internal::user_interface::StringColor;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
 
 pub fn handle_shims() {
     let argv0 = match env::args().next() {
@@ -26,9 +31,10 @@ pub fn handle_shims() {
         None => return,
     };
 
-    // Check if argv0 is a path, or if it is just a binary name called from the PATH
+    // Check if argv0 is a path, or if it is just a binary name called from the PATH;
+    // symlinks are not resolved, since the shims are symlinks to omni itself
     let path = if argv0.contains('/') {
-        abs_path(&argv0)
+        logical_abs_path(&argv0)
     } else {
         env::var("PATH")
             .unwrap_or_else(|_| "".to_string())
@@ -53,6 +59,18 @@ pub fn handle_shims() {
     // Load the dynamic environment for the current directory
     update_dynamic_env_for_command(".");
 
+    // If the binary is provided by a tool with different versions scoped
+    // to different subdirectories, and the configuration requires an
+    // explicit version for the current directory, fail instead of
+    // falling back to another version of the binary
+    if let Some(ambiguous) = ambiguous_versions_for_binary(".", &binary) {
+        eprintln!(
+            "{binary}: {}",
+            ambiguous_versions_message(&ambiguous, UpAmbiguousVersionsPolicy::Error)
+        );
+        exit(1);
+    }
+
     // Resolve the binary full path
     let binary_path = match which::which(&binary) {
         Ok(binary_path) => binary_path,
@@ -125,6 +143,16 @@ pub fn reshim(progress_handler: &dyn ProgressHandler) -> Result<Option<String>,
         }
     }
 
+    // Use a glob to get the shims from the pipx tools bin directories;
+    // the glob stops at the depth of the installations so that the
+    // binaries of their isolated environments are not included
+    let pipx_glob = format!("{}/pipx/*/*/bin", data_home());
+    if let Ok(entries) = glob::glob(&pipx_glob) {
+        for entry in entries.flatten() {
+            shims_sources.push(entry);
+        }
+    }
+
     // Figure out the required shims
     let mut expected_shims = BTreeSet::new();
 
@@ -213,7 +241,7 @@ pub fn reshim(progress_handler: &dyn ProgressHandler) -> Result<Option<String>,
 
     // Create the shims directory if it does not exist and is needed
     if !shims_to_create.is_empty() && !shims_dir().exists() {
-        std::fs::create_dir_all(shims_dir()).map_err(|err| {
+        create_dir_all(shims_dir(), PathKind::Shared).map_err(|err| {
             UpError::Exec(format!(
                 "failed to create shims directory {}: {}",
                 shims_dir().display(),
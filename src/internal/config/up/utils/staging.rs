@@ -0,0 +1,300 @@
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use fs4::fs_std::FileExt;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::internal::config::up::utils::force_remove_dir_all;
+use crate::internal::config::up::utils::ContentStore;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
+
+/// The prefix of the staging directories in which installations are
+/// prepared before being moved to their final path
+pub const STAGING_DIR_PREFIX: &str = ".omni-staging.";
+
+/// The prefix of the lock files held while preparing an installation,
+/// next to the final path of the installation
+pub const INSTALL_LOCK_PREFIX: &str = ".omni-lock.";
+
+/// The name of the file written in an installation directory once the
+/// installation completed successfully
+pub const INSTALL_MARKER_FILE: &str = ".omni-installed.json";
+
+/// How long a staging directory can stay around before being considered
+/// orphaned, e.g. because the process preparing it was interrupted
+pub const STAGING_DIR_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The contents of the completion marker of an installation, recording
+/// what was used to install it so that it can be verified later on
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InstallMarker {
+    #[serde(default = "OffsetDateTime::now_utc", with = "time::serde::rfc3339")]
+    pub installed_at: OffsetDateTime,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<InstallMarkerAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InstallMarkerAsset {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl InstallMarker {
+    pub fn new(assets: Vec<InstallMarkerAsset>) -> Self {
+        Self {
+            installed_at: OffsetDateTime::now_utc(),
+            assets,
+        }
+    }
+}
+
+impl InstallMarkerAsset {
+    pub fn new(name: impl ToString, sha256: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            sha256,
+        }
+    }
+}
+
+/// Reads the completion marker of the installation at the given path,
+/// if the installation completed
+pub fn read_install_marker(path: impl AsRef<Path>) -> Option<InstallMarker> {
+    let contents = std::fs::read_to_string(path.as_ref().join(INSTALL_MARKER_FILE)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Writes the completion marker of the installation at the given path;
+/// this is only needed for installations that cannot be prepared in a
+/// staging directory, as those are marked as completed when committed
+pub fn write_install_marker(path: impl AsRef<Path>, marker: &InstallMarker) -> io::Result<()> {
+    let contents = serde_json::to_string_pretty(marker)?;
+    std::fs::write(path.as_ref().join(INSTALL_MARKER_FILE), contents)
+}
+
+/// Whether the installation at the given path completed; installations
+/// are only moved to their final path once completed, so a directory
+/// there without any marker was installed before markers were written,
+/// and gets marked the first time it is seen
+pub fn is_installed(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    let is_staging = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(STAGING_DIR_PREFIX));
+    if is_staging || !path.is_dir() {
+        return false;
+    }
+
+    if !path.join(INSTALL_MARKER_FILE).exists() {
+        let _ = write_install_marker(path, &InstallMarker::new(vec![]));
+        return true;
+    }
+
+    read_install_marker(path).is_some()
+}
+
+/// A staging directory in which an installation is prepared, next to
+/// its final path so that it can be atomically renamed into place once
+/// the installation succeeded. The staging directory is removed when
+/// dropped, unless the installation was committed.
+#[derive(Debug)]
+pub struct InstallStaging {
+    dir: tempfile::TempDir,
+    target: PathBuf,
+    content_store: Option<ContentStore>,
+}
+
+impl InstallStaging {
+    pub fn new(target: impl AsRef<Path>) -> io::Result<Self> {
+        let target = target.as_ref().to_path_buf();
+        let parent = target.parent().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("no parent directory for {}", target.display()),
+            )
+        })?;
+
+        create_dir_all(parent, PathKind::Shared)?;
+        let dir = tempfile::Builder::new()
+            .prefix(STAGING_DIR_PREFIX)
+            .tempdir_in(parent)?;
+
+        Ok(Self {
+            dir,
+            target,
+            content_store: None,
+        })
+    }
+
+    /// Stores the files of the installation in the given content store
+    /// when committed, so that files identical to the ones of other
+    /// installations are only stored once
+    pub fn with_content_store(mut self, content_store: ContentStore) -> Self {
+        self.content_store = Some(content_store);
+        self
+    }
+
+    /// The path in which the installation should be prepared; it does not
+    /// exist until created by the caller, so that a directory can also
+    /// be renamed into it
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().join("install")
+    }
+
+    /// Writes the completion marker and moves the prepared installation
+    /// to its final path, replacing any previous installation there; the
+    /// previous installation is only moved aside, and put back if the
+    /// prepared one cannot be moved in place
+    pub fn commit(self, marker: &InstallMarker) -> io::Result<PathBuf> {
+        let path = self.path();
+        create_dir_all(&path, PathKind::Shared)?;
+
+        if let Some(content_store) = &self.content_store {
+            content_store.materialize(&path)?;
+        }
+
+        write_install_marker(&path, marker)?;
+
+        let previous = self.dir.path().join("previous");
+        let has_previous = match std::fs::symlink_metadata(&self.target) {
+            Ok(_) => {
+                std::fs::rename(&self.target, &previous)?;
+                true
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => false,
+            Err(err) => return Err(err),
+        };
+
+        if let Err(err) = std::fs::rename(&path, &self.target) {
+            if has_previous {
+                let _ = std::fs::rename(&previous, &self.target);
+            }
+            return Err(err);
+        }
+
+        if has_previous {
+            force_remove_dir_all(&previous)?;
+        }
+
+        Ok(self.target.clone())
+    }
+}
+
+/// An exclusive lock on the installation at a given path, held until
+/// dropped, so that concurrent processes do not prepare the same
+/// installation at the same time
+#[derive(Debug)]
+pub struct InstallLock {
+    _file: std::fs::File,
+}
+
+impl InstallLock {
+    /// Waits until the lock of the installation at the given path can
+    /// be acquired, and acquires it
+    pub fn acquire(target: impl AsRef<Path>) -> io::Result<Self> {
+        let path = install_lock_path(target)?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent, PathKind::Shared)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        file.lock_exclusive()?;
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Returns the path of the lock file of the installation at the given
+/// path, which is next to it so that it is cleaned up along with it
+pub fn install_lock_path(target: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let target = target.as_ref();
+    let name = target.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no file name for {}", target.display()),
+        )
+    })?;
+
+    Ok(target.with_file_name(format!("{}{}", INSTALL_LOCK_PREFIX, name.to_string_lossy())))
+}
+
+/// Removes the staging directories found under the given path, down to
+/// the given depth, that were not modified for longer than the given
+/// age; returns the paths that were removed, and the ones that were kept
+/// as they might still be in use by an installation in progress
+pub fn sweep_staging_dirs(
+    path: impl AsRef<Path>,
+    max_depth: usize,
+    max_age: Duration,
+) -> io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let path = path.as_ref();
+    if !path.is_dir() {
+        return Ok((vec![], vec![]));
+    }
+
+    let mut removed = vec![];
+    let mut kept = vec![];
+
+    let mut entries = walkdir::WalkDir::new(path)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .into_iter();
+    while let Some(entry) = entries.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(STAGING_DIR_PREFIX)
+        {
+            // Staging directories are never found inside installations
+            if entry.path().join(INSTALL_MARKER_FILE).exists() {
+                entries.skip_current_dir();
+            }
+            continue;
+        }
+
+        // Do not look into the staging directory itself
+        entries.skip_current_dir();
+
+        let age = entry
+            .metadata()
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+
+        if age > max_age {
+            force_remove_dir_all(entry.path())?;
+            removed.push(entry.path().to_path_buf());
+        } else {
+            kept.push(entry.path().to_path_buf());
+        }
+    }
+
+    Ok((removed, kept))
+}
+
+#[cfg(test)]
+#[path = "staging_test.rs"]
+mod tests;
@@ -0,0 +1,252 @@
+use super::*;
+
+fn set_age(path: &Path, age: Duration) {
+    let modified = SystemTime::now() - age;
+    std::fs::File::open(path)
+        .and_then(|dir| dir.set_modified(modified))
+        .expect("failed to set modification time");
+}
+
+fn staging_entries(path: &Path) -> Vec<String> {
+    std::fs::read_dir(path)
+        .expect("failed to read directory")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(STAGING_DIR_PREFIX))
+        .collect()
+}
+
+mod install_staging {
+    use super::*;
+
+    #[test]
+    fn test_commit_moves_installation_with_marker() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let target = tempdir.path().join("owner").join("repo").join("1.0.0");
+
+        let staging = InstallStaging::new(&target).expect("failed to create staging");
+        create_dir_all(staging.path(), PathKind::Shared).expect("failed to create directory");
+        std::fs::write(staging.path().join("tool"), "binary").expect("failed to write file");
+        assert!(!target.exists());
+
+        let marker = InstallMarker::new(vec![InstallMarkerAsset::new(
+            "tool-linux-amd64.tar.gz",
+            Some("abc123".to_string()),
+        )]);
+        staging.commit(&marker).expect("failed to commit");
+
+        assert!(target.join("tool").is_file());
+        assert!(is_installed(&target));
+        assert_eq!(read_install_marker(&target), Some(marker));
+        assert!(staging_entries(target.parent().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_failed_installation_leaves_no_final_path() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let target = tempdir.path().join("owner").join("repo").join("1.0.0");
+
+        let install = |fail_extraction: bool| -> io::Result<PathBuf> {
+            let staging = InstallStaging::new(&target)?;
+            create_dir_all(staging.path(), PathKind::Shared)?;
+            std::fs::write(staging.path().join("partial"), "half an archive")?;
+
+            // Simulate an extraction failing halfway through
+            if fail_extraction {
+                return Err(io::Error::other("failed to extract archive"));
+            }
+
+            staging.commit(&InstallMarker::new(vec![]))
+        };
+
+        assert!(install(true).is_err());
+        assert!(!target.exists());
+        assert!(!is_installed(&target));
+        assert!(staging_entries(target.parent().unwrap()).is_empty());
+
+        assert_eq!(install(false).ok(), Some(target.clone()));
+        assert!(is_installed(&target));
+    }
+
+    #[test]
+    fn test_commit_replaces_incomplete_installation() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let target = tempdir.path().join("crate").join("1.0.0");
+
+        std::fs::create_dir_all(target.join("bin")).expect("failed to create directory");
+        std::fs::write(target.join("bin").join("leftover"), "").expect("failed to write file");
+
+        let staging = InstallStaging::new(&target).expect("failed to create staging");
+        create_dir_all(staging.path().join("bin"), PathKind::Shared)
+            .expect("failed to create directory");
+        std::fs::write(staging.path().join("bin").join("tool"), "").expect("failed to write file");
+        staging
+            .commit(&InstallMarker::new(vec![]))
+            .expect("failed to commit");
+
+        assert!(target.join("bin").join("tool").is_file());
+        assert!(!target.join("bin").join("leftover").exists());
+        assert!(staging_entries(target.parent().unwrap()).is_empty());
+    }
+}
+
+mod is_installed {
+    use super::*;
+
+    #[test]
+    fn test_directory_without_marker() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        std::fs::write(tempdir.path().join("tool"), "").expect("failed to write file");
+
+        // Installations from before the markers are marked when first seen
+        assert!(is_installed(tempdir.path()));
+        assert!(read_install_marker(tempdir.path()).is_some());
+    }
+
+    #[test]
+    fn test_staging_directory() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let staging = tempdir.path().join(format!("{STAGING_DIR_PREFIX}abc"));
+        std::fs::create_dir_all(&staging).expect("failed to create directory");
+
+        assert!(!is_installed(&staging));
+        assert!(!staging.join(INSTALL_MARKER_FILE).exists());
+    }
+
+    #[test]
+    fn test_directory_with_invalid_marker() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        std::fs::write(tempdir.path().join(INSTALL_MARKER_FILE), "{not json")
+            .expect("failed to write file");
+
+        assert!(!is_installed(tempdir.path()));
+    }
+
+    #[test]
+    fn test_directory_with_marker() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        std::fs::write(
+            tempdir.path().join(INSTALL_MARKER_FILE),
+            r#"{"installed_at": "2025-01-01T00:00:00Z", "assets": [{"name": "tool.zip"}]}"#,
+        )
+        .expect("failed to write file");
+
+        assert!(is_installed(tempdir.path()));
+        let marker = read_install_marker(tempdir.path()).expect("marker should be read");
+        assert_eq!(
+            marker.assets,
+            vec![InstallMarkerAsset::new("tool.zip", None)]
+        );
+    }
+
+    #[test]
+    fn test_missing_directory() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+
+        assert!(!is_installed(tempdir.path().join("missing")));
+    }
+}
+
+mod sweep_staging_dirs {
+    use super::*;
+
+    #[test]
+    fn test_removes_stale_staging_dirs() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let tool_path = tempdir.path().join("github.com").join("owner").join("tool");
+
+        let stale = tool_path.join(format!("{STAGING_DIR_PREFIX}stale"));
+        let fresh = tool_path.join(format!("{STAGING_DIR_PREFIX}fresh"));
+        let installed = tool_path.join("1.0.0");
+        for dir in [&stale, &fresh, &installed] {
+            std::fs::create_dir_all(dir.join("install")).expect("failed to create directory");
+        }
+        set_age(&stale, STAGING_DIR_MAX_AGE * 2);
+
+        let (removed, kept) = sweep_staging_dirs(tempdir.path(), 4, STAGING_DIR_MAX_AGE)
+            .expect("failed to sweep staging directories");
+
+        assert_eq!(removed, vec![stale.clone()]);
+        assert_eq!(kept, vec![fresh.clone()]);
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+        assert!(installed.exists());
+    }
+
+    #[test]
+    fn test_stops_at_max_depth() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let stale = tempdir
+            .path()
+            .join("a")
+            .join("b")
+            .join(format!("{STAGING_DIR_PREFIX}stale"));
+        std::fs::create_dir_all(&stale).expect("failed to create directory");
+        set_age(&stale, STAGING_DIR_MAX_AGE * 2);
+
+        let (removed, _) = sweep_staging_dirs(tempdir.path(), 2, STAGING_DIR_MAX_AGE)
+            .expect("failed to sweep staging directories");
+        assert!(removed.is_empty());
+        assert!(stale.exists());
+
+        let (removed, _) = sweep_staging_dirs(tempdir.path(), 3, STAGING_DIR_MAX_AGE)
+            .expect("failed to sweep staging directories");
+        assert_eq!(removed, vec![stale.clone()]);
+    }
+
+    #[test]
+    fn test_does_not_look_into_installations() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let installed = tempdir.path().join("tool").join("1.0.0");
+        let inner = installed.join(format!("{STAGING_DIR_PREFIX}inner"));
+        std::fs::create_dir_all(&inner).expect("failed to create directory");
+        write_install_marker(&installed, &InstallMarker::new(vec![]))
+            .expect("failed to write marker");
+        set_age(&inner, STAGING_DIR_MAX_AGE * 2);
+
+        let (removed, kept) = sweep_staging_dirs(tempdir.path(), 4, STAGING_DIR_MAX_AGE)
+            .expect("failed to sweep staging directories");
+
+        assert!(removed.is_empty());
+        assert!(kept.is_empty());
+        assert!(inner.exists());
+    }
+
+    #[test]
+    fn test_missing_root() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+
+        let (removed, kept) =
+            sweep_staging_dirs(tempdir.path().join("missing"), 4, STAGING_DIR_MAX_AGE)
+                .expect("failed to sweep staging directories");
+
+        assert!(removed.is_empty());
+        assert!(kept.is_empty());
+    }
+}
+
+mod install_lock {
+    use super::*;
+
+    #[test]
+    fn test_lock_path_is_next_to_installation() {
+        assert_eq!(
+            install_lock_path("/data/tool/1.0.0").expect("should have a lock path"),
+            PathBuf::from("/data/tool/.omni-lock.1.0.0")
+        );
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+        let target = tempdir.path().join("tool").join("1.0.0");
+
+        let lock = InstallLock::acquire(&target).expect("failed to acquire lock");
+        let lock_path = install_lock_path(&target).expect("should have a lock path");
+        let file = std::fs::File::open(&lock_path).expect("failed to open lock file");
+        assert!(matches!(file.try_lock_exclusive(), Ok(false)));
+
+        drop(lock);
+        assert!(matches!(file.try_lock_exclusive(), Ok(true)));
+    }
+}
@@ -0,0 +1,171 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::config::up::UpError;
+use crate::internal::user_interface::print::strip_ansi_codes;
+
+/// An event emitted while an up or down operation progresses, written
+/// as a single JSON line so that the stream of events can be consumed
+/// in real time (NDJSON) by other tools, e.g. a UI or a dashboard.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum UpEvent {
+    StepStarted {
+        step: usize,
+        total: usize,
+        name: String,
+    },
+    StepProgress {
+        step: usize,
+        total: usize,
+        name: String,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        downloaded_bytes: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        total_bytes: Option<u64>,
+    },
+    StepCompleted {
+        step: usize,
+        total: usize,
+        name: String,
+    },
+    StepFailed {
+        step: usize,
+        total: usize,
+        name: String,
+        error: String,
+    },
+    RunCompleted {
+        status: UpEventStatus,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpEventStatus {
+    Success,
+    Failure,
+}
+
+/// Identifies the step that an event relates to
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpEventStep {
+    step: usize,
+    total: usize,
+    name: String,
+}
+
+impl UpEventStep {
+    pub fn new(progress: (usize, usize), name: &str) -> Self {
+        Self {
+            step: progress.0,
+            total: progress.1,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Writes the events of an operation to the given output, one JSON
+/// object per line; the output is flushed after each event so that
+/// consumers receive the events as soon as they happen.
+pub struct UpEventWriter {
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl std::fmt::Debug for UpEventWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "UpEventWriter")
+    }
+}
+
+impl UpEventWriter {
+    pub fn new(output: Box<dyn Write + Send>) -> Self {
+        Self {
+            output: Mutex::new(output),
+        }
+    }
+
+    pub fn emit(&self, event: &UpEvent) {
+        let mut line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        line.push('\n');
+
+        // Failing to write an event should not fail the operation itself
+        let mut output = self.output.lock().unwrap_or_else(|err| err.into_inner());
+        let _ = output.write_all(line.as_bytes());
+        let _ = output.flush();
+    }
+
+    pub fn step_started(&self, step: &UpEventStep) {
+        self.emit(&UpEvent::StepStarted {
+            step: step.step,
+            total: step.total,
+            name: step.name.clone(),
+        });
+    }
+
+    pub fn step_progress(&self, step: &UpEventStep, message: &str) {
+        self.emit(&UpEvent::StepProgress {
+            step: step.step,
+            total: step.total,
+            name: step.name.clone(),
+            message: strip_ansi_codes(message),
+            downloaded_bytes: None,
+            total_bytes: None,
+        });
+    }
+
+    pub fn step_download_progress(
+        &self,
+        step: &UpEventStep,
+        message: &str,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    ) {
+        self.emit(&UpEvent::StepProgress {
+            step: step.step,
+            total: step.total,
+            name: step.name.clone(),
+            message: strip_ansi_codes(message),
+            downloaded_bytes: Some(downloaded_bytes),
+            total_bytes,
+        });
+    }
+
+    pub fn step_finished(&self, step: &UpEventStep, result: &Result<(), UpError>) {
+        self.emit(&match result {
+            Ok(()) => UpEvent::StepCompleted {
+                step: step.step,
+                total: step.total,
+                name: step.name.clone(),
+            },
+            Err(err) => UpEvent::StepFailed {
+                step: step.step,
+                total: step.total,
+                name: step.name.clone(),
+                error: strip_ansi_codes(&err.to_string()),
+            },
+        });
+    }
+
+    pub fn run_completed(&self, result: &Result<(), UpError>) {
+        self.emit(&match result {
+            Ok(()) => UpEvent::RunCompleted {
+                status: UpEventStatus::Success,
+                error: None,
+            },
+            Err(err) => UpEvent::RunCompleted {
+                status: UpEventStatus::Failure,
+                error: Some(strip_ansi_codes(&err.to_string())),
+            },
+        });
+    }
+}
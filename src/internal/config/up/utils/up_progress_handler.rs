@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use std::fmt::Display;
 use std::io::BufRead;
 use std::io::Write;
+use std::path::Path;
 use std::process::exit;
 use std::time::Duration as StdDuration;
 use std::time::Instant as StdInstant;
@@ -11,11 +12,16 @@ use fs4::fs_std::FileExt as Fs4FileExt;
 use once_cell::sync::OnceCell;
 use serde::Deserialize;
 use serde::Serialize;
+use time::OffsetDateTime;
 
+use crate::internal::cache::up_history::format_time_ago;
 use crate::internal::config::global_config;
 use crate::internal::config::up::utils::PrintProgressHandler;
 use crate::internal::config::up::utils::ProgressHandler;
 use crate::internal::config::up::utils::SpinnerProgressHandler;
+use crate::internal::config::up::utils::UpEventStep;
+use crate::internal::config::up::utils::UpEventWriter;
+use crate::internal::config::up::utils::UpSummaryWriter;
 use crate::internal::env::shell_is_interactive;
 use crate::internal::errors::SyncUpdateError;
 use crate::internal::user_interface::colors::StringColor;
@@ -34,6 +40,8 @@ pub struct UpProgressHandler<'a> {
     parent: Option<&'a UpProgressHandler<'a>>,
     allow_ending: bool,
     sync_file: Option<&'a std::fs::File>,
+    events: Option<(&'a UpEventWriter, UpEventStep)>,
+    summary: Option<&'a UpSummaryWriter>,
     desc: OnceCell<String>,
 }
 
@@ -47,6 +55,8 @@ impl Default for UpProgressHandler<'_> {
             parent: None,
             allow_ending: true,
             sync_file: None,
+            events: None,
+            summary: None,
             desc: OnceCell::new(),
         }
     }
@@ -153,6 +163,8 @@ impl<'a> UpProgressHandler<'a> {
             parent: Some(self),
             allow_ending: false,
             sync_file: None,
+            events: None,
+            summary: None,
             desc: OnceCell::new(),
         }
     }
@@ -169,6 +181,30 @@ impl<'a> UpProgressHandler<'a> {
         self.sync_file = Some(sync_file);
     }
 
+    pub fn set_events(&mut self, events: &'a UpEventWriter, step: UpEventStep) {
+        self.events = Some((events, step));
+    }
+
+    pub fn set_summary(&mut self, summary: &'a UpSummaryWriter) {
+        self.summary = Some(summary);
+    }
+
+    fn summary(&self) -> Option<&'a UpSummaryWriter> {
+        match (self.summary, self.parent) {
+            (Some(summary), _) => Some(summary),
+            (None, Some(parent)) => parent.summary(),
+            (None, None) => None,
+        }
+    }
+
+    fn events(&self) -> Option<&(&'a UpEventWriter, UpEventStep)> {
+        match (&self.events, self.parent) {
+            (Some(events), _) => Some(events),
+            (None, Some(parent)) => parent.events(),
+            (None, None) => None,
+        }
+    }
+
     fn update_sync_file(&self, action: SyncUpdateProgressAction) {
         if let Some(sync_file) = self.sync_file {
             // Overwrite the handler id and description with the current ones
@@ -222,6 +258,9 @@ impl<'a> UpProgressHandler<'a> {
 impl ProgressHandler for UpProgressHandler<'_> {
     fn progress(&self, message: String) {
         let message = self.format_message(message);
+        if let Some((events, step)) = self.events() {
+            events.step_progress(step, &message);
+        }
         self.update_sync_file(SyncUpdateProgressAction::Progress(message.clone()));
         self.handler().progress(message);
     }
@@ -275,6 +314,15 @@ impl ProgressHandler for UpProgressHandler<'_> {
         self.update_sync_file(SyncUpdateProgressAction::Println(message.clone()));
         self.handler().println(message);
     }
+
+    fn download_progress(&self, message: String, downloaded: u64, total: Option<u64>) {
+        if let Some((events, step)) = self.events() {
+            events.step_download_progress(step, &self.format_message(message), downloaded, total);
+        }
+        if let Some(summary) = self.summary() {
+            summary.download_progress(downloaded);
+        }
+    }
 }
 
 pub struct SyncUpdateListener<'a> {
@@ -353,7 +401,7 @@ impl SyncUpdateListener<'_> {
                 }
             }
 
-            if file.try_lock_exclusive().is_ok() {
+            if matches!(file.try_lock_exclusive(), Ok(true)) {
                 Fs4FileExt::unlock(file)?;
 
                 // The process was completed
@@ -561,11 +609,15 @@ pub enum SyncUpdateInit {
         cache: bool,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pid: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        started_at: Option<i64>,
     },
     Down {
         cache: bool,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pid: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        started_at: Option<i64>,
     },
 }
 
@@ -601,6 +653,65 @@ impl SyncUpdateInit {
             SyncUpdateInit::Down { pid, .. } => *pid,
         }
     }
+
+    /// The unix timestamp at which the operation started, if known
+    pub fn started_at(&self) -> Option<i64> {
+        match self {
+            SyncUpdateInit::Up { started_at, .. } => *started_at,
+            SyncUpdateInit::Down { started_at, .. } => *started_at,
+        }
+    }
+
+    /// Reads the init operation written at the beginning of the sync
+    /// file of a running operation, if any
+    pub fn from_sync_file(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        let line = contents.lines().next()?;
+        let line = line.trim_start_matches(|c| c != '{');
+        match serde_json::from_str::<SyncUpdateOperation>(line).ok()? {
+            SyncUpdateOperation::Init(init) => Some(init),
+            _ => None,
+        }
+    }
+
+    /// Whether the process that started the operation is known and is
+    /// not running anymore, in which case the operation is stale
+    pub fn is_stale(&self) -> bool {
+        let pid = match self.pid().and_then(|pid| i32::try_from(pid).ok()) {
+            Some(pid) => pid,
+            None => return false,
+        };
+
+        matches!(
+            nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None),
+            Err(nix::errno::Errno::ESRCH)
+        )
+    }
+
+    /// Describes the running operation for the user, e.g. `another omni
+    /// up is running (pid 1234, started 2 minutes ago)`
+    pub fn running_description(&self) -> String {
+        let mut details = vec![];
+        if let Some(pid) = self.pid() {
+            details.push(format!("pid {pid}"));
+        }
+        if let Some(started_at) = self
+            .started_at()
+            .and_then(|started_at| OffsetDateTime::from_unix_timestamp(started_at).ok())
+        {
+            details.push(format!("started {}", format_time_ago(&started_at)));
+        }
+
+        if details.is_empty() {
+            format!("another omni {} is running", self.name())
+        } else {
+            format!(
+                "another omni {} is running ({})",
+                self.name(),
+                details.join(", ")
+            )
+        }
+    }
 }
 
 impl PartialEq for SyncUpdateInit {
@@ -0,0 +1,357 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::cache::up_environments::UpVersion;
+use crate::internal::config::up::UpError;
+use crate::internal::user_interface::print::strip_ansi_codes;
+use crate::internal::user_interface::StringColor;
+use crate::omni_warning;
+
+/// The version of the schema of the summary; this needs to be bumped
+/// whenever a change that is not backward-compatible is made, so that
+/// the tools consuming the summary can detect it
+pub const UP_SUMMARY_SCHEMA_VERSION: u32 = 1;
+
+/// A machine-readable summary of an up operation, written at the end
+/// of the run so that it can be consumed by other tools, e.g. in CI
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UpSummary {
+    pub schema_version: u32,
+    pub status: UpSummaryStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub config_hash: String,
+    pub environment: UpSummaryEnvironment,
+    pub operations: Vec<UpSummaryOperation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpSummaryStatus {
+    Success,
+    Failure,
+}
+
+/// The environment assigned to the work directory before and after
+/// the run; the current environment is only known if the run went
+/// as far as assigning it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct UpSummaryEnvironment {
+    pub previous_id: Option<String>,
+    pub current_id: Option<String>,
+    pub changed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UpSummaryOperation {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    pub outcome: UpSummaryOutcome,
+    pub tools: Vec<UpSummaryTool>,
+    pub duration_ms: u64,
+    pub downloaded_bytes: u64,
+}
+
+/// The outcome of an operation of the run
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpSummaryOutcome {
+    /// A tool version not in the previous environment was set up, or
+    /// something had to be downloaded
+    Installed,
+    /// A tool version changed from the previous environment
+    Updated,
+    /// The operation did not run, as a previous operation failed
+    Skipped,
+    /// The operation failed
+    Failed,
+    /// The operation did not need to install anything new
+    Cached,
+}
+
+/// A tool version resolved by an operation, along with the version
+/// of the same tool in the previous environment, if any
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UpSummaryTool {
+    pub name: String,
+    pub version_before: Option<String>,
+    pub version_after: Option<String>,
+}
+
+#[derive(Debug)]
+struct UpSummaryCurrentOperation {
+    index: usize,
+    started: Instant,
+    downloaded_completed: u64,
+    downloaded_last: u64,
+}
+
+#[derive(Debug)]
+struct UpSummaryState {
+    summary: UpSummary,
+    previous_versions: Vec<UpVersion>,
+    current: Option<UpSummaryCurrentOperation>,
+    exit_code: Option<i32>,
+    completed: bool,
+}
+
+/// Records the summary of an up operation as it progresses, and writes
+/// it to the given path once the run completes, whether it succeeded or
+/// not, so that a failed run still provides the partial data. If the
+/// run does not get to complete, the summary is written when the writer
+/// is dropped.
+pub struct UpSummaryWriter {
+    path: PathBuf,
+    started: Instant,
+    state: Mutex<UpSummaryState>,
+}
+
+impl std::fmt::Debug for UpSummaryWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "UpSummaryWriter({})", self.path.display())
+    }
+}
+
+impl UpSummaryWriter {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            started: Instant::now(),
+            state: Mutex::new(UpSummaryState {
+                summary: UpSummary {
+                    schema_version: UP_SUMMARY_SCHEMA_VERSION,
+                    status: UpSummaryStatus::Failure,
+                    error: None,
+                    duration_ms: 0,
+                    config_hash: "".to_string(),
+                    environment: UpSummaryEnvironment::default(),
+                    operations: vec![],
+                },
+                previous_versions: vec![],
+                current: None,
+                exit_code: None,
+                completed: false,
+            }),
+        }
+    }
+
+    fn with_state<F, T>(&self, closure: F) -> T
+    where
+        F: FnOnce(&mut UpSummaryState) -> T,
+    {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        closure(&mut state)
+    }
+
+    /// Initializes the summary with the operations of the run, which are
+    /// considered skipped until they run, and with the environment that
+    /// was assigned to the work directory before the run, if any
+    pub fn run_started(
+        &self,
+        config_hash: &str,
+        previous_environment: Option<(String, &UpEnvironment)>,
+        operations: &[String],
+    ) {
+        self.with_state(|state| {
+            state.summary.config_hash = config_hash.to_string();
+            state.summary.operations = operations
+                .iter()
+                .map(|name| UpSummaryOperation {
+                    name: name.clone(),
+                    backend: None,
+                    outcome: UpSummaryOutcome::Skipped,
+                    tools: vec![],
+                    duration_ms: 0,
+                    downloaded_bytes: 0,
+                })
+                .collect();
+
+            if let Some((environment_id, environment)) = previous_environment {
+                state.summary.environment.previous_id = Some(environment_id);
+                state.previous_versions = environment.versions.clone();
+            }
+        });
+    }
+
+    pub fn operation_started(&self, index: usize) {
+        self.with_state(|state| {
+            state.current = Some(UpSummaryCurrentOperation {
+                index,
+                started: Instant::now(),
+                downloaded_completed: 0,
+                downloaded_last: 0,
+            });
+        });
+    }
+
+    /// Records the progress of a download of the current operation; the
+    /// downloaded size is cumulative for a given download, so a smaller
+    /// value than the previous one means that a new download started
+    pub fn download_progress(&self, downloaded: u64) {
+        self.with_state(|state| {
+            if let Some(current) = &mut state.current {
+                if downloaded < current.downloaded_last {
+                    current.downloaded_completed += current.downloaded_last;
+                }
+                current.downloaded_last = downloaded;
+            }
+        });
+    }
+
+    /// Records the end of the current operation, with the tool versions
+    /// that it added to the environment
+    pub fn operation_finished(&self, versions: &[UpVersion], result: &Result<(), UpError>) {
+        self.with_state(|state| {
+            let current = match state.current.take() {
+                Some(current) => current,
+                None => return,
+            };
+
+            let mut tools: Vec<UpSummaryTool> = vec![];
+            for version in versions {
+                if tools
+                    .iter()
+                    .any(|tool| tool.name == version.normalized_name)
+                {
+                    continue;
+                }
+
+                let version_before = state
+                    .previous_versions
+                    .iter()
+                    .find(|previous| {
+                        previous.backend == version.backend
+                            && previous.normalized_name == version.normalized_name
+                            && previous.dir == version.dir
+                    })
+                    .map(|previous| previous.version.clone());
+
+                tools.push(UpSummaryTool {
+                    name: version.normalized_name.clone(),
+                    version_before,
+                    version_after: Some(version.version.clone()),
+                });
+            }
+
+            let downloaded_bytes = current.downloaded_completed + current.downloaded_last;
+            let outcome = match result {
+                Ok(()) => success_outcome(&tools, downloaded_bytes),
+                Err(_) => UpSummaryOutcome::Failed,
+            };
+
+            let backend = versions
+                .iter()
+                .map(|version| version.backend.as_str())
+                .find(|backend| !backend.is_empty() && *backend != "default")
+                .map(|backend| backend.to_string());
+
+            if let Some(operation) = state.summary.operations.get_mut(current.index) {
+                operation.backend = backend;
+                operation.outcome = outcome;
+                operation.tools = tools;
+                operation.duration_ms = current.started.elapsed().as_millis() as u64;
+                operation.downloaded_bytes = downloaded_bytes;
+            }
+        });
+    }
+
+    pub fn environment_assigned(&self, environment_id: &str) {
+        self.with_state(|state| {
+            let environment = &mut state.summary.environment;
+            environment.changed = environment.previous_id.as_deref() != Some(environment_id);
+            environment.current_id = Some(environment_id.to_string());
+        });
+    }
+
+    /// Records the end of the run and writes the summary; failing to write
+    /// the summary does not fail the operation itself
+    pub fn run_completed(&self, result: &Result<(), UpError>, duration: Duration) {
+        let summary = self.with_state(|state| {
+            state.summary.duration_ms = duration.as_millis() as u64;
+            match result {
+                Ok(()) => {
+                    state.summary.status = UpSummaryStatus::Success;
+                    state.summary.error = None;
+                }
+                Err(err) => {
+                    state.summary.status = UpSummaryStatus::Failure;
+                    state.summary.error = Some(strip_ansi_codes(&err.to_string()));
+                }
+            }
+            state.completed = true;
+            state.summary.clone()
+        });
+
+        if let Err(err) = self.write(&summary) {
+            omni_warning!(format!(
+                "failed to write up summary to {}: {}",
+                self.path.display(),
+                err
+            ));
+        }
+    }
+
+    /// Records the exit code of omni, which decides the status of the
+    /// summary if it is written when dropped
+    pub fn set_exit_code(&self, exit_code: i32) {
+        self.with_state(|state| state.exit_code = Some(exit_code));
+    }
+
+    fn write(&self, summary: &UpSummary) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let mut contents = serde_json::to_string_pretty(summary)?;
+        contents.push('\n');
+        std::fs::write(&self.path, contents)
+    }
+}
+
+impl Drop for UpSummaryWriter {
+    fn drop(&mut self) {
+        // Write the summary if the run did not get to complete, e.g. if
+        // there was nothing to do or if omni failed before running the
+        // operations, with the data recorded so far
+        let (completed, exit_code) = self.with_state(|state| (state.completed, state.exit_code));
+        if completed {
+            return;
+        }
+
+        let result = match exit_code {
+            Some(0) => Ok(()),
+            Some(exit_code) => Err(UpError::Exec(format!("exited with code {exit_code}"))),
+            None => Err(UpError::Exec("interrupted".to_string())),
+        };
+        self.run_completed(&result, self.started.elapsed());
+    }
+}
+
+/// Returns the outcome of an operation that succeeded, depending on
+/// how the tool versions it resolved compare to the previous ones
+fn success_outcome(tools: &[UpSummaryTool], downloaded_bytes: u64) -> UpSummaryOutcome {
+    if tools
+        .iter()
+        .any(|tool| tool.version_before.is_some() && tool.version_before != tool.version_after)
+    {
+        UpSummaryOutcome::Updated
+    } else if downloaded_bytes > 0 || tools.iter().any(|tool| tool.version_before.is_none()) {
+        UpSummaryOutcome::Installed
+    } else {
+        UpSummaryOutcome::Cached
+    }
+}
+
+#[cfg(test)]
+#[path = "up_summary_test.rs"]
+mod tests;
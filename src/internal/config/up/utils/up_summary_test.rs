@@ -0,0 +1,238 @@
+use super::*;
+
+use crate::internal::cache::up_environments::UpVersionParams;
+
+fn version(backend: &str, tool: &str, version: &str) -> UpVersion {
+    let mut environment = UpEnvironment::new();
+    environment.add_version(UpVersionParams {
+        backend,
+        tool,
+        plugin_name: tool,
+        normalized_name: tool,
+        version,
+        bin_path: "bin",
+        dirs: Default::default(),
+        env_vars: vec![],
+    });
+    environment.versions.remove(0)
+}
+
+fn previous_environment() -> UpEnvironment {
+    let mut environment = UpEnvironment::new();
+    environment.versions = vec![
+        version("", "python", "3.11.9"),
+        version("", "node", "20.12.0"),
+    ];
+    environment
+}
+
+fn read_summary(path: &std::path::Path) -> serde_json::Value {
+    let contents = std::fs::read_to_string(path).expect("summary should be written");
+    serde_json::from_str(&contents).expect("summary should be valid json")
+}
+
+fn outcomes(summary: &serde_json::Value) -> Vec<String> {
+    summary["operations"]
+        .as_array()
+        .expect("operations should be an array")
+        .iter()
+        .map(|operation| format!("{}:{}", operation["name"], operation["outcome"]))
+        .collect()
+}
+
+/// Records a run where the operations have the different possible
+/// outcomes, without going through actual up operations
+fn record_mixed_run(writer: &UpSummaryWriter, result: Result<(), UpError>) {
+    let previous = previous_environment();
+    writer.run_started(
+        "confighash",
+        Some(("wd%previous".to_string(), &previous)),
+        &[
+            "python".to_string(),
+            "node".to_string(),
+            "github-release".to_string(),
+            "custom".to_string(),
+            "homebrew".to_string(),
+        ],
+    );
+
+    writer.operation_started(0);
+    writer.operation_finished(&[version("", "python", "3.12.4")], &Ok(()));
+
+    writer.operation_started(1);
+    writer.operation_finished(&[version("", "node", "20.12.0")], &Ok(()));
+
+    writer.operation_started(2);
+    writer.download_progress(512);
+    writer.download_progress(1024);
+    writer.download_progress(256);
+    writer.operation_finished(&[version("github-release", "xaf/omni", "1.0.0")], &Ok(()));
+
+    writer.operation_started(3);
+    writer.operation_finished(&[], &Err(UpError::Exec("custom failed".to_string())));
+
+    writer.run_completed(&result, Duration::from_millis(1500));
+}
+
+#[test]
+fn writes_mixed_outcomes() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("nested").join("summary.json");
+    let writer = UpSummaryWriter::new(&path);
+
+    record_mixed_run(&writer, Ok(()));
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["schema_version"], UP_SUMMARY_SCHEMA_VERSION);
+    assert_eq!(summary["status"], "success");
+    assert!(summary.get("error").is_none());
+    assert_eq!(summary["duration_ms"], 1500);
+    assert_eq!(summary["config_hash"], "confighash");
+    assert_eq!(
+        outcomes(&summary),
+        vec![
+            r#""python":"updated""#,
+            r#""node":"cached""#,
+            r#""github-release":"installed""#,
+            r#""custom":"failed""#,
+            r#""homebrew":"skipped""#,
+        ]
+    );
+
+    let python = &summary["operations"][0];
+    assert!(python.get("backend").is_none());
+    assert_eq!(
+        python["tools"],
+        serde_json::json!([{
+            "name": "python",
+            "version_before": "3.11.9",
+            "version_after": "3.12.4",
+        }])
+    );
+    assert!(python["duration_ms"].is_u64());
+    assert_eq!(python["downloaded_bytes"], 0);
+
+    let release = &summary["operations"][2];
+    assert_eq!(release["backend"], "github-release");
+    assert_eq!(release["downloaded_bytes"], 1024 + 256);
+    assert_eq!(
+        release["tools"][0]["version_before"],
+        serde_json::Value::Null
+    );
+    assert_eq!(release["tools"][0]["version_after"], "1.0.0");
+
+    let skipped = &summary["operations"][4];
+    assert_eq!(skipped["tools"], serde_json::json!([]));
+    assert_eq!(skipped["duration_ms"], 0);
+}
+
+#[test]
+fn writes_partial_summary_on_failure() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("summary.json");
+    let writer = UpSummaryWriter::new(&path);
+
+    record_mixed_run(&writer, Err(UpError::Exec("custom failed".to_string())));
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["status"], "failure");
+    assert_eq!(summary["error"], "execution error: custom failed");
+    assert_eq!(
+        summary["environment"],
+        serde_json::json!({
+            "previous_id": "wd%previous",
+            "current_id": null,
+            "changed": false,
+        })
+    );
+    assert_eq!(summary["operations"].as_array().map(Vec::len), Some(5));
+}
+
+#[test]
+fn records_environment_change() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("summary.json");
+    let writer = UpSummaryWriter::new(&path);
+
+    let previous = previous_environment();
+    writer.run_started(
+        "confighash",
+        Some(("wd%previous".to_string(), &previous)),
+        &[],
+    );
+    writer.environment_assigned("wd%current");
+    writer.run_completed(&Ok(()), Duration::ZERO);
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["environment"]["previous_id"], "wd%previous");
+    assert_eq!(summary["environment"]["current_id"], "wd%current");
+    assert_eq!(summary["environment"]["changed"], true);
+
+    writer.run_started(
+        "confighash",
+        Some(("wd%current".to_string(), &previous)),
+        &[],
+    );
+    writer.environment_assigned("wd%current");
+    writer.run_completed(&Ok(()), Duration::ZERO);
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["environment"]["changed"], false);
+}
+
+#[test]
+fn writes_summary_on_drop_after_early_exit() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("summary.json");
+
+    let writer = UpSummaryWriter::new(&path);
+    writer.set_exit_code(0);
+    drop(writer);
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["status"], "success");
+    assert_eq!(summary["operations"], serde_json::json!([]));
+}
+
+#[test]
+fn writes_failure_on_drop_with_exit_code() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("summary.json");
+
+    let writer = UpSummaryWriter::new(&path);
+    writer.run_started("confighash", None, &["python".to_string()]);
+    writer.set_exit_code(75);
+    drop(writer);
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["status"], "failure");
+    assert_eq!(summary["error"], "execution error: exited with code 75");
+    assert_eq!(outcomes(&summary), vec![r#""python":"skipped""#]);
+}
+
+#[test]
+fn writes_failure_on_drop_without_exit_code() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("summary.json");
+
+    drop(UpSummaryWriter::new(&path));
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["status"], "failure");
+    assert_eq!(summary["error"], "execution error: interrupted");
+}
+
+#[test]
+fn keeps_summary_of_completed_run_on_drop() {
+    let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+    let path = tempdir.path().join("summary.json");
+
+    let writer = UpSummaryWriter::new(&path);
+    writer.run_completed(&Err(UpError::Exec("failed".to_string())), Duration::ZERO);
+    writer.set_exit_code(0);
+    drop(writer);
+
+    let summary = read_summary(&path);
+    assert_eq!(summary["status"], "failure");
+    assert_eq!(summary["error"], "execution error: failed");
+}
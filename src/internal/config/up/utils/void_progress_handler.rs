@@ -4,7 +4,6 @@ use crate::internal::config::up::utils::ProgressHandler;
 pub struct VoidProgressHandler {}
 
 impl VoidProgressHandler {
-    #[cfg(test)]
     pub fn new() -> Self {
         VoidProgressHandler {}
     }
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::internal::config::config;
+use crate::internal::workdir;
+
+/// A repository to set up as part of a workspace, along with the tool
+/// installations its `up` configuration requires
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpWorkspaceRepo {
+    pub path: PathBuf,
+    pub install_keys: BTreeSet<String>,
+}
+
+impl UpWorkspaceRepo {
+    /// Returns the repository at the given path, if it is the root of a
+    /// work directory with an `up` configuration
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let path_str = path.to_string_lossy();
+        let wd = workdir(&path_str);
+        let root = wd.root()?;
+        if Path::new(root) != path {
+            return None;
+        }
+
+        let up_config = config(root).up?;
+        if !up_config.has_steps() {
+            return None;
+        }
+
+        Some(Self {
+            path: path.to_path_buf(),
+            install_keys: up_config.install_keys().into_iter().collect(),
+        })
+    }
+}
+
+/// Returns the repositories to set up for the given paths; each path can
+/// either be a repository, or a directory whose direct children are the
+/// repositories to set up. Repositories are returned in the order they
+/// are found, without duplicates.
+pub fn workspace_repos(paths: &[PathBuf]) -> Vec<UpWorkspaceRepo> {
+    let mut seen = BTreeSet::new();
+    let mut repos = vec![];
+
+    for path in paths {
+        let path = std::fs::canonicalize(path).unwrap_or(path.clone());
+
+        if let Some(repo) = UpWorkspaceRepo::from_path(&path) {
+            if seen.insert(path) {
+                repos.push(repo);
+            }
+            continue;
+        }
+
+        let mut children = std::fs::read_dir(&path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        children.sort();
+
+        for child in children {
+            if seen.contains(&child) {
+                continue;
+            }
+
+            if let Some(repo) = UpWorkspaceRepo::from_path(&child) {
+                seen.insert(child);
+                repos.push(repo);
+            }
+        }
+    }
+
+    repos
+}
+
+/// The plan to set up the repositories of a workspace: each tool
+/// installation required by multiple repositories is provisioned by the
+/// first repository requiring it, and the other repositories requiring
+/// it wait for that repository to be set up, so that they reuse the
+/// installed version instead of installing it concurrently
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpWorkspacePlan {
+    pub repos: Vec<UpWorkspaceRepo>,
+    /// The index of the repository provisioning each tool installation
+    pub installs: BTreeMap<String, usize>,
+    /// The indexes of the repositories each repository waits for
+    pub waits_for: Vec<BTreeSet<usize>>,
+}
+
+impl UpWorkspacePlan {
+    pub fn new(repos: Vec<UpWorkspaceRepo>) -> Self {
+        let mut installs: BTreeMap<String, usize> = BTreeMap::new();
+        let mut waits_for = vec![BTreeSet::new(); repos.len()];
+
+        for (idx, repo) in repos.iter().enumerate() {
+            for key in &repo.install_keys {
+                match installs.get(key) {
+                    Some(provider) => {
+                        waits_for[idx].insert(*provider);
+                    }
+                    None => {
+                        installs.insert(key.clone(), idx);
+                    }
+                }
+            }
+        }
+
+        Self {
+            repos,
+            installs,
+            waits_for,
+        }
+    }
+
+    /// Returns the tool installations required by multiple repositories,
+    /// along with the repository provisioning them and the number of
+    /// repositories sharing them
+    pub fn shared_installs(&self) -> Vec<(&str, &Path, usize)> {
+        self.installs
+            .iter()
+            .filter_map(|(key, provider)| {
+                let users = self
+                    .repos
+                    .iter()
+                    .filter(|repo| repo.install_keys.contains(key))
+                    .count();
+
+                if users > 1 {
+                    Some((key.as_str(), self.repos[*provider].path.as_path(), users))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// The result of setting up one of the repositories of a workspace
+#[derive(Debug, Clone)]
+pub struct UpWorkspaceResult {
+    pub path: PathBuf,
+    pub result: Result<(), String>,
+    pub duration: Duration,
+}
+
+struct SchedulerState {
+    pending: Vec<usize>,
+    done: BTreeSet<usize>,
+    results: Vec<(usize, UpWorkspaceResult)>,
+}
+
+/// Sets up the repositories of the plan using the given function, with
+/// at most `jobs` repositories being set up at the same time; a
+/// repository only starts once the repositories it waits for are done,
+/// even if they failed, in which case it provisions the tools itself.
+/// The results are returned in the order of the repositories in the plan.
+pub fn run_workspace<F>(plan: &UpWorkspacePlan, jobs: usize, run: F) -> Vec<UpWorkspaceResult>
+where
+    F: Fn(&UpWorkspaceRepo) -> Result<(), String> + Sync,
+{
+    let state = Mutex::new(SchedulerState {
+        pending: (0..plan.repos.len()).collect(),
+        done: BTreeSet::new(),
+        results: vec![],
+    });
+    let ready = Condvar::new();
+
+    let worker = || loop {
+        let idx = {
+            let mut state = state.lock().expect("failed to lock scheduler state");
+            loop {
+                if state.pending.is_empty() {
+                    return;
+                }
+
+                let next = state
+                    .pending
+                    .iter()
+                    .position(|idx| plan.waits_for[*idx].is_subset(&state.done));
+
+                match next {
+                    Some(position) => break state.pending.remove(position),
+                    None => {
+                        state = ready.wait(state).expect("failed to lock scheduler state");
+                    }
+                }
+            }
+        };
+
+        let repo = &plan.repos[idx];
+        let started = Instant::now();
+        let result = run(repo);
+
+        let mut state = state.lock().expect("failed to lock scheduler state");
+        state.done.insert(idx);
+        state.results.push((
+            idx,
+            UpWorkspaceResult {
+                path: repo.path.clone(),
+                result,
+                duration: started.elapsed(),
+            },
+        ));
+        ready.notify_all();
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, plan.repos.len().max(1)) {
+            scope.spawn(worker);
+        }
+    });
+
+    let mut results = state
+        .into_inner()
+        .expect("failed to lock scheduler state")
+        .results;
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+#[path = "workspace_test.rs"]
+mod tests;
@@ -0,0 +1,166 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+fn repo(path: &str, install_keys: &[&str]) -> UpWorkspaceRepo {
+    UpWorkspaceRepo {
+        path: PathBuf::from(path),
+        install_keys: install_keys.iter().map(|key| key.to_string()).collect(),
+    }
+}
+
+/// Simulates the tool installations of the repositories: each tool
+/// installation of a repository that is not already installed gets
+/// installed, and the number of times each one was installed is recorded
+struct FakeInstaller {
+    installs: Mutex<HashMap<String, usize>>,
+}
+
+impl FakeInstaller {
+    fn new() -> Self {
+        Self {
+            installs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn up(&self, repo: &UpWorkspaceRepo) -> Result<(), String> {
+        for key in &repo.install_keys {
+            let installed = self.installs.lock().unwrap().contains_key(key);
+            if !installed {
+                // Leave time for other repositories to try to install
+                // the same tool concurrently
+                std::thread::sleep(Duration::from_millis(20));
+                *self
+                    .installs
+                    .lock()
+                    .unwrap()
+                    .entry(key.clone())
+                    .or_default() += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn install_count(&self, key: &str) -> usize {
+        self.installs.lock().unwrap().get(key).copied().unwrap_or(0)
+    }
+}
+
+mod plan {
+    use super::*;
+
+    #[test]
+    fn shared_install_provisioned_by_first_repo() {
+        let plan = UpWorkspacePlan::new(vec![
+            repo("/ws/api", &["mise:python@3.11.4", "mise:node@20.10.0"]),
+            repo("/ws/web", &["mise:node@20.10.0"]),
+            repo("/ws/cli", &["mise:go@1.21.0"]),
+        ]);
+
+        assert_eq!(
+            plan.installs,
+            BTreeMap::from([
+                ("mise:go@1.21.0".to_string(), 2),
+                ("mise:node@20.10.0".to_string(), 0),
+                ("mise:python@3.11.4".to_string(), 0),
+            ])
+        );
+        assert_eq!(
+            plan.waits_for,
+            vec![BTreeSet::new(), BTreeSet::from([0]), BTreeSet::new()]
+        );
+        assert_eq!(
+            plan.shared_installs(),
+            vec![("mise:node@20.10.0", Path::new("/ws/api"), 2)]
+        );
+    }
+
+    #[test]
+    fn different_versions_are_not_shared() {
+        let plan = UpWorkspacePlan::new(vec![
+            repo("/ws/api", &["mise:python@3.11.4"]),
+            repo("/ws/web", &["mise:python@3.12.1"]),
+        ]);
+
+        assert_eq!(plan.installs.len(), 2);
+        assert!(plan.waits_for.iter().all(|waits_for| waits_for.is_empty()));
+        assert!(plan.shared_installs().is_empty());
+    }
+}
+
+mod run_workspace {
+    use super::*;
+
+    #[test]
+    fn same_tool_version_installed_once() {
+        let plan = UpWorkspacePlan::new(vec![
+            repo("/ws/api", &["mise:python@3.11.4"]),
+            repo("/ws/web", &["mise:python@3.11.4"]),
+        ]);
+
+        let installer = FakeInstaller::new();
+        let results = run_workspace(&plan, 4, |repo| installer.up(repo));
+
+        assert_eq!(installer.install_count("mise:python@3.11.4"), 1);
+        assert_eq!(
+            results.iter().map(|r| r.path.clone()).collect::<Vec<_>>(),
+            vec![PathBuf::from("/ws/api"), PathBuf::from("/ws/web")]
+        );
+        assert!(results.iter().all(|r| r.result.is_ok()));
+    }
+
+    #[test]
+    fn dependent_repo_runs_after_failed_provider() {
+        let plan = UpWorkspacePlan::new(vec![
+            repo("/ws/api", &["mise:python@3.11.4"]),
+            repo("/ws/web", &["mise:python@3.11.4"]),
+        ]);
+
+        let installer = FakeInstaller::new();
+        let results = run_workspace(&plan, 4, |repo| {
+            if repo.path == Path::new("/ws/api") {
+                return Err("failed to install python".to_string());
+            }
+            installer.up(repo)
+        });
+
+        assert_eq!(
+            results[0].result,
+            Err("failed to install python".to_string())
+        );
+        assert_eq!(results[1].result, Ok(()));
+        assert_eq!(installer.install_count("mise:python@3.11.4"), 1);
+    }
+
+    #[test]
+    fn concurrency_is_bounded() {
+        let plan = UpWorkspacePlan::new(
+            (0..6)
+                .map(|idx| repo(&format!("/ws/repo{idx}"), &[]))
+                .collect(),
+        );
+
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let results = run_workspace(&plan, 2, |_repo| {
+            let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now_running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        assert_eq!(results.len(), 6);
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn empty_plan() {
+        let plan = UpWorkspacePlan::new(vec![]);
+
+        assert!(run_workspace(&plan, 4, |_repo| Ok(())).is_empty());
+    }
+}
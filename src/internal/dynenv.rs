@@ -1,31 +1,65 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::Duration;
 
 use blake3::Hasher;
 use itertools::Itertools;
+use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
 use serde::Deserialize;
 use serde::Serialize;
 use shell_escape::escape;
 
+use crate::internal::cache::up_environments::UpAmbiguousVersions;
+use crate::internal::cache::up_environments::UpEnvVar;
 use crate::internal::cache::up_environments::UpEnvironment;
+use crate::internal::cache::up_environments::UpVersion;
+use crate::internal::cache::up_history::format_time_ago;
 use crate::internal::cache::UpEnvironmentsCache;
+use crate::internal::cache::UpHistoryCache;
 use crate::internal::config;
+use crate::internal::config::global_config;
+use crate::internal::config::parser::env_command_output;
 use crate::internal::config::parser::EnvOperationEnum;
+use crate::internal::config::parser::PathConflictsConfig;
+use crate::internal::config::parser::ShellSessionConfig;
+use crate::internal::config::parser::UpAmbiguousVersionsPolicy;
+use crate::internal::config::parser::ENV_COMMAND_APPLY_TIMEOUT;
 use crate::internal::config::up::cargo_install::cargo_install_tool_path;
 use crate::internal::config::up::github_release::github_release_tool_path;
 use crate::internal::config::up::go_install::go_install_tool_path;
 use crate::internal::config::up::mise::mise_path;
 use crate::internal::config::up::mise_tool_path;
+use crate::internal::config::up::pipx::pipx_tool_path;
 use crate::internal::config::up::utils::get_config_mod_times;
+use crate::internal::config::up::utils::UpProgressHandler;
+use crate::internal::config::up::UpConfigCargoInstalls;
+use crate::internal::config::up::UpConfigGithubReleases;
+use crate::internal::config::up::UpConfigGoInstalls;
+use crate::internal::config::up::UpConfigMise;
+use crate::internal::config::up::UpConfigPipxInstalls;
+use crate::internal::config::up::UpError;
+use crate::internal::config::up::UpOptions;
+use crate::internal::config::utils::is_executable;
 use crate::internal::env::shims_dir;
 use crate::internal::env::user_home;
+use crate::internal::user_interface::colors::ColorCapability;
 use crate::internal::user_interface::StringColor;
 use crate::internal::workdir;
 
 const DATA_SEPARATOR: &str = "\x1C";
 const DYNENV_VAR: &str = "__omni_dynenv";
 const DYNENV_SEPARATOR: &str = ";";
+const MISSING_TOOLS_VAR: &str = "__omni_missing_tools";
+const INCOMPLETE_TOOLS_VAR: &str = "__omni_incomplete_tools";
+const AMBIGUOUS_VERSIONS_VAR: &str = "__omni_ambiguous_versions";
+const ENV_COMMAND_FAILURES_VAR: &str = "__omni_env_command_failures";
+const PATH_CONFLICTS_VAR: &str = "__omni_path_conflicts";
 const WD_CONFIG_MODTIME_VAR: &str = "__omni_wd_config_modtime";
 
 pub fn update_dynamic_env_for_command<T: ToString>(path: T) {
@@ -41,6 +75,67 @@ pub fn update_dynamic_env_for_command_from_env<T: ToString>(path: T, environment
         .apply();
 }
 
+/// Returns the changes to apply to the current environment so that the
+/// tools of the given up environment are available, without involving
+/// the environment of the work directory or the shell; a `None` value
+/// means that the variable should be unset
+pub fn ephemeral_env_changes(environment: &UpEnvironment) -> HashMap<String, Option<String>> {
+    let mut dynenv = DynamicEnv::default();
+    let mut envsetter = DynamicEnvSetter::new();
+    dynenv.apply_up_env(environment, &mut envsetter, "", true);
+
+    envsetter.set_value_by_fn("CFLAGS", dedup_flags);
+    envsetter.set_value_by_fn("CPPFLAGS", dedup_flags);
+    envsetter.set_value_by_fn("LDFLAGS", dedup_flags);
+
+    envsetter.get_env_data().env
+}
+
+/// Returns the changes to apply to the current environment for the given
+/// environment variable operations, which are layered on top of the
+/// current values of the variables, so that e.g. a prepended path comes
+/// before the paths of the dynamic environment; a `None` value means
+/// that the variable should be unset
+pub fn env_var_operations_changes(env_vars: &[UpEnvVar]) -> HashMap<String, Option<String>> {
+    let mut envsetter = DynamicEnvSetter::new();
+    for env_var in env_vars {
+        envsetter.apply_env_var(env_var);
+    }
+
+    envsetter.get_env_data().env
+}
+
+/// The environment that the dynamic environment of a path would set up,
+/// computed without applying it and independently of the current
+/// environment, so that the environments of different paths can be
+/// compared
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DynamicEnvPreview {
+    /// The values of the variables set by the environment
+    pub env_vars: BTreeMap<String, String>,
+    /// The entries of the list variables (e.g. `PATH`) contributed by the
+    /// environment, in order of precedence
+    pub lists: BTreeMap<String, Vec<String>>,
+    /// The versions of the tools loaded by the environment
+    pub tools: BTreeMap<String, String>,
+}
+
+/// Returns the environment that would be set up for the given path, as
+/// would be derived when applying the dynamic environment, but without
+/// applying anything; if no up environment is provided, the one assigned
+/// to the work directory of the path is used
+pub fn preview_dynamic_env(path: &str, environment: Option<&UpEnvironment>) -> DynamicEnvPreview {
+    let mut dynenv = DynamicEnv::default()
+        .with_path(Some(path.to_string()))
+        .with_environment(environment);
+
+    // Tools removed since the environment was cached are still part of
+    // what the environment is expected to set up
+    dynenv.check_tool_paths = false;
+
+    dynenv.preview()
+}
+
 fn remove_wd_config_modtime_var(export_mode: DynamicEnvExportMode) {
     let mut dynenvdata = DynamicEnvData::new();
     dynenvdata.env_unset_var(WD_CONFIG_MODTIME_VAR);
@@ -48,13 +143,14 @@ fn remove_wd_config_modtime_var(export_mode: DynamicEnvExportMode) {
 }
 
 fn remove_shims_dir_from_path(export_mode: DynamicEnvExportMode) {
-    let mut dynenvdata = DynamicEnvData::new();
+    let mut dynenvdata = DynamicEnvData::new().with_list_separator(export_mode.list_separator());
     dynenvdata.remove_all_from_list("PATH", shims_dir().to_str().unwrap());
     dynenvdata.export(export_mode);
 }
 
 fn check_workdir_config_updated(
     export_mode: DynamicEnvExportMode,
+    colors: &ColorCapability,
     path: Option<String>,
     cache: &UpEnvironmentsCache,
 ) {
@@ -144,16 +240,31 @@ fn check_workdir_config_updated(
     // in the cache. If it is, we don't need to notify the user, but we
     // still need to set the environment variable to avoid checking on
     // every prompt.
-    if let Some(wdcache) = cache.get_env(&wdid) {
-        if wdcache.config_hash == config.up_hash() {
-            notify_change = false;
-        }
+    if cache.check_config_hash(&wdid, &config.up_hash(), &config.up_hash_legacy()) {
+        notify_change = false;
     }
 
     if notify_change {
+        // If omni up already succeeded for this workdir, let the user
+        // know how long ago that was
+        let last_success = match change_type {
+            "update" => UpHistoryCache::get()
+                .last_success(&wdid)
+                .map(|last_success| {
+                    format!(
+                        "config changed since your last {} {}, ",
+                        "omni up".force_light_blue(),
+                        format_time_ago(&last_success),
+                    )
+                }),
+            _ => None,
+        };
+
         print_update(
+            colors,
             format!(
-                "run {} to {} the dependencies",
+                "{}run {} to {} the dependencies",
+                last_success.unwrap_or_default(),
                 "omni up".force_light_blue(),
                 change_type.force_light_yellow(),
             )
@@ -168,7 +279,412 @@ fn check_workdir_config_updated(
     dynenvdata.export(export_mode.clone());
 }
 
+/// Returns the value identifying the given set of missing tools if the
+/// user has not yet been notified about it, as indicated by the value
+/// currently held by the `MISSING_TOOLS_VAR` environment variable, or
+/// by the `INCOMPLETE_TOOLS_VAR` one for incomplete tools.
+fn missing_tools_to_notify(missing_tools: &[String], notified: Option<&str>) -> Option<String> {
+    if missing_tools.is_empty() {
+        return None;
+    }
+
+    let hashed = blake3::hash(missing_tools.join(",").as_bytes()).to_hex()[..16].to_string();
+    if notified == Some(hashed.as_str()) {
+        return None;
+    }
+
+    Some(hashed)
+}
+
+fn notify_missing_tools(
+    export_mode: DynamicEnvExportMode,
+    colors: &ColorCapability,
+    missing_tools: &[String],
+) {
+    let mut dynenvdata = DynamicEnvData::new();
+
+    if missing_tools.is_empty() {
+        dynenvdata.env_unset_var(MISSING_TOOLS_VAR);
+        dynenvdata.export(export_mode);
+        return;
+    }
+
+    let notified = std::env::var(MISSING_TOOLS_VAR).ok();
+    let hashed = match missing_tools_to_notify(missing_tools, notified.as_deref()) {
+        Some(hashed) => hashed,
+        None => return,
+    };
+
+    print_update(
+        colors,
+        format!(
+            "{} {} not found, run {} to reinstall",
+            missing_tools
+                .iter()
+                .map(|tool| tool.force_light_yellow())
+                .join(", "),
+            if missing_tools.len() == 1 {
+                "is"
+            } else {
+                "are"
+            },
+            "omni up".force_light_blue(),
+        )
+        .as_str(),
+    );
+
+    // Set the environment variable to indicate that the user
+    // has been notified about those tools being missing
+    dynenvdata.env_set_var(MISSING_TOOLS_VAR, &hashed);
+    dynenvdata.export(export_mode);
+}
+
+fn notify_incomplete_tools(
+    export_mode: DynamicEnvExportMode,
+    colors: &ColorCapability,
+    incomplete_tools: &[String],
+) {
+    let mut dynenvdata = DynamicEnvData::new();
+
+    if incomplete_tools.is_empty() {
+        dynenvdata.env_unset_var(INCOMPLETE_TOOLS_VAR);
+        dynenvdata.export(export_mode);
+        return;
+    }
+
+    let notified = std::env::var(INCOMPLETE_TOOLS_VAR).ok();
+    let hashed = match missing_tools_to_notify(incomplete_tools, notified.as_deref()) {
+        Some(hashed) => hashed,
+        None => return,
+    };
+
+    for tool in incomplete_tools {
+        print_update(
+            colors,
+            format!(
+                "cargo-install tool {} appears incomplete, run {}",
+                tool.force_light_yellow(),
+                "omni up".force_light_blue(),
+            )
+            .as_str(),
+        );
+    }
+
+    // Set the environment variable to indicate that the user
+    // has been notified about those tools being incomplete
+    dynenvdata.env_set_var(INCOMPLETE_TOOLS_VAR, &hashed);
+    dynenvdata.export(export_mode);
+}
+
+/// Returns the message letting the user know how a tool with different
+/// versions scoped to different subdirectories was resolved
+pub fn ambiguous_versions_message(
+    ambiguous: &UpAmbiguousVersions,
+    policy: UpAmbiguousVersionsPolicy,
+) -> String {
+    let resolution = match policy {
+        UpAmbiguousVersionsPolicy::Newest => {
+            format!("using {}", ambiguous.newest().version.force_light_green())
+        }
+        UpAmbiguousVersionsPolicy::Error => format!(
+            "none loaded, pin a version for this directory to use {} here",
+            ambiguous.tool.force_light_yellow(),
+        ),
+    };
+
+    format!(
+        "{} has different versions in subdirectories ({}), {}",
+        ambiguous.tool.force_light_yellow(),
+        ambiguous.describe(),
+        resolution,
+    )
+}
+
+fn notify_ambiguous_versions(
+    export_mode: DynamicEnvExportMode,
+    colors: &ColorCapability,
+    ambiguous_versions: &[UpAmbiguousVersions],
+    policy: UpAmbiguousVersionsPolicy,
+) {
+    let mut dynenvdata = DynamicEnvData::new();
+
+    if ambiguous_versions.is_empty() {
+        dynenvdata.env_unset_var(AMBIGUOUS_VERSIONS_VAR);
+        dynenvdata.export(export_mode);
+        return;
+    }
+
+    let messages = ambiguous_versions
+        .iter()
+        .map(|ambiguous| ambiguous_versions_message(ambiguous, policy))
+        .collect::<Vec<_>>();
+
+    let hashed = blake3::hash(messages.join(",").as_bytes()).to_hex()[..16].to_string();
+    if std::env::var(AMBIGUOUS_VERSIONS_VAR).ok().as_deref() == Some(hashed.as_str()) {
+        return;
+    }
+
+    for message in messages {
+        print_update(colors, &message);
+    }
+
+    // Set the environment variable to indicate that the user
+    // has been notified about those ambiguous versions
+    dynenvdata.env_set_var(AMBIGUOUS_VERSIONS_VAR, &hashed);
+    dynenvdata.export(export_mode);
+}
+
+fn notify_env_command_failures(
+    export_mode: DynamicEnvExportMode,
+    colors: &ColorCapability,
+    failures: &[String],
+) {
+    let mut dynenvdata = DynamicEnvData::new();
+
+    if failures.is_empty() {
+        dynenvdata.env_unset_var(ENV_COMMAND_FAILURES_VAR);
+        dynenvdata.export(export_mode);
+        return;
+    }
+
+    let hashed = blake3::hash(failures.join(",").as_bytes()).to_hex()[..16].to_string();
+    if std::env::var(ENV_COMMAND_FAILURES_VAR).ok().as_deref() == Some(hashed.as_str()) {
+        return;
+    }
+
+    for failure in failures {
+        print_update(colors, failure);
+    }
+
+    // Set the environment variable to indicate that the user
+    // has been notified about those failing commands
+    dynenvdata.env_set_var(ENV_COMMAND_FAILURES_VAR, &hashed);
+    dynenvdata.export(export_mode);
+}
+
+/// Returns the tool with ambiguous versions providing the given binary
+/// for the given path, if the configuration requires a version to be
+/// explicitly defined in that case; this allows shims to fail instead of
+/// silently falling back to another version of that binary
+pub fn ambiguous_versions_for_binary(path: &str, binary: &str) -> Option<UpAmbiguousVersions> {
+    let workdir = workdir(path);
+    if !workdir.in_workdir() {
+        return None;
+    }
+
+    let dynenv = DynamicEnv::new(UpEnvironmentsCache::get()).with_path(Some(path.to_string()));
+    if dynenv.ambiguous_versions_policy() != UpAmbiguousVersionsPolicy::Error {
+        return None;
+    }
+
+    let up_env = dynenv.environment()?;
+    let dir = workdir.reldir(path).unwrap_or("".to_string());
+    let (_, ambiguous_versions) = dynenv.resolve_versions(&up_env, &dir);
+
+    ambiguous_versions.into_iter().find(|ambiguous| {
+        ambiguous
+            .versions
+            .iter()
+            .any(|version| version_provides_binary(version, binary))
+    })
+}
+
+/// Whether the given tool version adds the given binary to the PATH
+fn version_provides_binary(toolversion: &UpVersion, binary: &str) -> bool {
+    version_path_entries(toolversion)
+        .iter()
+        .any(|path| path.join(binary).is_file())
+}
+
+/// Returns the directories that the given tool version adds to the PATH
+fn version_path_entries(toolversion: &UpVersion) -> Vec<PathBuf> {
+    let backend = match env_backend(&toolversion.backend) {
+        Some(backend) => backend,
+        None => return vec![],
+    };
+
+    let mut dynenv = DynamicEnv::default();
+    let mut envsetter = DynamicEnvSetter::new();
+    if !backend.apply_version(toolversion, &mut dynenv, &mut envsetter) {
+        return vec![];
+    }
+
+    envsetter
+        .get_env_data()
+        .lists
+        .get("PATH")
+        .map(|paths| {
+            paths
+                .iter()
+                .map(|path| PathBuf::from(&path.value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A binary of a tool managed by omni which resolves, through the PATH,
+/// to another binary than the one provided by omni
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathConflict {
+    pub tool: String,
+    pub binary: String,
+    pub winning: PathBuf,
+    pub expected: PathBuf,
+}
+
+impl PathConflict {
+    pub fn message(&self) -> String {
+        format!(
+            "{} resolves to {} instead of {}",
+            self.tool,
+            self.winning.display(),
+            self.expected.display(),
+        )
+    }
+
+    /// Returns a hint about why the conflict happens; since omni prepends
+    /// the paths of the tools it manages to the PATH, a directory winning
+    /// over them has been placed before them after omni updated the PATH
+    pub fn hint(&self) -> String {
+        let winning_dir = self
+            .winning
+            .parent()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+
+        format!(
+            "{winning_dir} comes before the omni-managed paths in PATH, something prepends to PATH after omni's shell hook (e.g. the prompt or a shell rc file)",
+        )
+    }
+}
+
+/// Returns the conflicts between the binaries of the tools managed by
+/// omni, given with the directories omni adds to the PATH for each of
+/// them, and the binaries that actually resolve through the given PATH;
+/// only the first conflicting binary of each tool is reported
+pub fn find_path_conflicts(
+    tool_paths: &[(String, Vec<PathBuf>)],
+    path_var: &str,
+    config: &PathConflictsConfig,
+) -> Vec<PathConflict> {
+    let search_path = std::env::split_paths(path_var).collect::<Vec<_>>();
+    let managed_dirs = tool_paths
+        .iter()
+        .flat_map(|(_, dirs)| dirs.iter())
+        .chain(std::iter::once(&shims_dir()))
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    let mut conflicts = vec![];
+    for (tool, dirs) in tool_paths {
+        if !config.is_checked(tool) {
+            continue;
+        }
+
+        let conflict = dirs.iter().find_map(|dir| {
+            tool_binaries(tool, dir).into_iter().find_map(|binary| {
+                let winning_dir = search_path
+                    .iter()
+                    .find(|path_dir| is_executable(&path_dir.join(&binary)))?;
+
+                if managed_dirs.contains(winning_dir) {
+                    return None;
+                }
+
+                Some(PathConflict {
+                    tool: tool.clone(),
+                    winning: winning_dir.join(&binary),
+                    expected: dir.join(&binary),
+                    binary,
+                })
+            })
+        });
+
+        if let Some(conflict) = conflict {
+            conflicts.push(conflict);
+        }
+    }
+
+    conflicts
+}
+
+/// Returns the executables in the given directory, with the one named
+/// after the tool first, if any, as it is the most likely to be used
+fn tool_binaries(tool: &str, dir: &Path) -> Vec<String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| is_executable(&entry.path()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .sorted_by_key(|binary| (binary != tool, binary.clone()))
+        .collect()
+}
+
+/// Returns the conflicts between the binaries of the tools of the
+/// dynamic environment expected for the given path and the binaries
+/// resolving through the current PATH
+pub fn current_path_conflicts(path: &str) -> Vec<PathConflict> {
+    let config = config(path).path_conflicts;
+    if !config.enabled {
+        return vec![];
+    }
+
+    let path_var = match std::env::var("PATH") {
+        Ok(path_var) => path_var,
+        Err(_) => return vec![],
+    };
+
+    let dynenv = DynamicEnv::new(UpEnvironmentsCache::get()).with_path(Some(path.to_string()));
+    let tool_paths = dynenv.tool_paths();
+    find_path_conflicts(&tool_paths, &path_var, &config)
+}
+
+/// Warns about the binaries of the tools managed by omni that are
+/// shadowed by other binaries in the PATH; this is only checked when
+/// the PATH or the environment changed since the last check, and each
+/// conflict is only reported once
+fn notify_path_conflicts(
+    export_mode: DynamicEnvExportMode,
+    colors: &ColorCapability,
+    path: &str,
+    env_id: u64,
+) {
+    let path_var = std::env::var("PATH").unwrap_or_default();
+    let hashed = blake3::hash(format!("{env_id}{DATA_SEPARATOR}{path_var}").as_bytes()).to_hex()
+        [..16]
+        .to_string();
+    if std::env::var(PATH_CONFLICTS_VAR).ok().as_deref() == Some(hashed.as_str()) {
+        return;
+    }
+
+    for conflict in current_path_conflicts(path) {
+        print_update(
+            colors,
+            &format!(
+                "{} {}; {}",
+                "PATH conflict:".force_light_yellow(),
+                conflict.message(),
+                conflict.hint(),
+            ),
+        );
+    }
+
+    // Set the environment variable to indicate that the PATH
+    // has been checked for conflicts for that environment
+    let mut dynenvdata = DynamicEnvData::new();
+    dynenvdata.env_set_var(PATH_CONFLICTS_VAR, &hashed);
+    dynenvdata.export(export_mode);
+}
+
 pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
+    // The updates are always colored, as they are printed from the
+    // shell hook, whose output is not a terminal
+    let colors = ColorCapability::new(true);
+
     if !options.keep_shims {
         remove_shims_dir_from_path(options.mode.clone());
     }
@@ -180,10 +696,20 @@ pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
         .with_environment(options.environment.as_ref());
 
     if !options.is_quiet() {
-        check_workdir_config_updated(options.mode.clone(), options.path.clone(), &cache);
+        check_workdir_config_updated(options.mode.clone(), &colors, options.path.clone(), &cache);
     }
 
     if current_env.id() == expected_env.id() {
+        // The environment is already applied, but the PATH might have
+        // been reordered since, e.g. by the prompt
+        if !options.is_quiet() && expected_env.id() != 0 {
+            notify_path_conflicts(
+                options.mode.clone(),
+                &colors,
+                options.path.as_deref().unwrap_or("."),
+                expected_env.id(),
+            );
+        }
         return;
     }
 
@@ -191,6 +717,24 @@ pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
     expected_env.apply(options.mode.clone(), options.keep_shims);
 
     if !options.is_quiet() {
+        notify_missing_tools(options.mode.clone(), &colors, &expected_env.missing_tools);
+        notify_incomplete_tools(
+            options.mode.clone(),
+            &colors,
+            &expected_env.incomplete_tools,
+        );
+        notify_ambiguous_versions(
+            options.mode.clone(),
+            &colors,
+            &expected_env.ambiguous_versions,
+            expected_env.ambiguous_versions_policy(),
+        );
+        notify_env_command_failures(
+            options.mode.clone(),
+            &colors,
+            &expected_env.failed_env_commands,
+        );
+
         match (current_env.id(), expected_env.id()) {
             (0, 0) => {}
             (0, _) => {
@@ -208,6 +752,7 @@ pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
                     .force_italic()
                 };
                 print_update(
+                    &colors,
                     format!(
                         "dynamic environment {}{}",
                         "enabled".force_light_green(),
@@ -218,6 +763,7 @@ pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
             }
             (_, 0) => {
                 print_update(
+                    &colors,
                     format!("dynamic environment {}", "disabled".force_light_red(),).as_str(),
                 );
             }
@@ -236,6 +782,7 @@ pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
                     .force_italic()
                 };
                 print_update(
+                    &colors,
                     format!(
                         "dynamic environment {}{}",
                         "updated".force_light_blue(),
@@ -248,8 +795,14 @@ pub fn update_dynamic_env(options: &DynamicEnvExportOptions) {
     }
 }
 
-fn print_update(status: &str) {
-    eprintln!("{} {}", "omni:".force_light_cyan(), status);
+fn print_update(colors: &ColorCapability, status: &str) {
+    eprintln!("{}", update_message(colors, status));
+}
+
+/// Returns the line printed to let the user know about a change of the
+/// dynamic environment, without its colors if those are disabled
+fn update_message(colors: &ColorCapability, status: &str) -> String {
+    colors.strip_if_disabled(format!("{} {}", "omni:".force_light_cyan(), status))
 }
 
 #[derive(Debug, Clone, Default)]
@@ -302,10 +855,29 @@ impl DynamicEnvExportOptions {
 pub enum DynamicEnvExportMode {
     Posix,
     Fish,
+    PowerShell,
     #[default]
     Env,
 }
 
+impl DynamicEnvExportMode {
+    /// Returns the separator between the entries of list variables,
+    /// such as `PATH`, in the environment of the target shell; PowerShell
+    /// follows the convention of the platform it is running on
+    pub fn list_separator(&self) -> char {
+        match self {
+            Self::PowerShell => {
+                if cfg!(windows) {
+                    ';'
+                } else {
+                    ':'
+                }
+            }
+            Self::Posix | Self::Fish | Self::Env => ':',
+        }
+    }
+}
+
 pub struct DynamicEnv {
     path: Option<String>,
     environment: OnceCell<Option<UpEnvironment>>,
@@ -313,6 +885,14 @@ pub struct DynamicEnv {
     data_str: Option<String>,
     data: Option<DynamicEnvData>,
     features: Vec<String>,
+    missing_tools: Vec<String>,
+    incomplete_tools: Vec<String>,
+    ambiguous_versions: Vec<UpAmbiguousVersions>,
+    ambiguous_versions_policy: Option<UpAmbiguousVersionsPolicy>,
+    shell_session: Option<ShellSessionConfig>,
+    check_tool_paths: bool,
+    env_command_timeout: Duration,
+    failed_env_commands: Vec<String>,
     cache: UpEnvironmentsCache,
 }
 
@@ -325,6 +905,14 @@ impl Default for DynamicEnv {
             data_str: None,
             data: None,
             features: Vec::new(),
+            missing_tools: Vec::new(),
+            incomplete_tools: Vec::new(),
+            ambiguous_versions: Vec::new(),
+            ambiguous_versions_policy: None,
+            shell_session: None,
+            check_tool_paths: true,
+            env_command_timeout: ENV_COMMAND_APPLY_TIMEOUT,
+            failed_env_commands: Vec::new(),
             cache: UpEnvironmentsCache::get(),
         }
     }
@@ -380,6 +968,113 @@ impl DynamicEnv {
             .clone()
     }
 
+    /// The policy to use for the tools with different versions scoped to
+    /// different subdirectories, as configured for the current path
+    fn ambiguous_versions_policy(&self) -> UpAmbiguousVersionsPolicy {
+        match self.ambiguous_versions_policy {
+            Some(policy) => policy,
+            None => {
+                let path = self.path.clone().unwrap_or(".".to_string());
+                config(&path).up_command.ambiguous_versions
+            }
+        }
+    }
+
+    /// Returns the tools of the environment for the current path, along
+    /// with the directories added to the PATH for each of them
+    fn tool_paths(&self) -> Vec<(String, Vec<PathBuf>)> {
+        let path = self.path.clone().unwrap_or(".".to_string());
+        let workdir = workdir(&path);
+        if !workdir.in_workdir() {
+            return vec![];
+        }
+
+        let up_env = match self.environment() {
+            Some(up_env) => up_env,
+            None => return vec![],
+        };
+
+        let dir = workdir.reldir(&path).unwrap_or("".to_string());
+        let (versions, _) = self.resolve_versions(&up_env, &dir);
+        versions
+            .iter()
+            .map(|toolversion| (toolversion.tool.clone(), version_path_entries(toolversion)))
+            .filter(|(_, dirs)| !dirs.is_empty())
+            .collect()
+    }
+
+    /// The identifier of the shell session the dynamic environment is
+    /// scoped to, as configured globally
+    fn shell_session_id(&self) -> String {
+        match &self.shell_session {
+            Some(shell_session) => shell_session.session_id(),
+            None => global_config().shell_session.session_id(),
+        }
+    }
+
+    /// Returns the tool versions to load for the given directory of the
+    /// work directory, along with the tools with ambiguous versions
+    fn resolve_versions(
+        &self,
+        up_env: &UpEnvironment,
+        dir: &str,
+    ) -> (Vec<UpVersion>, Vec<UpAmbiguousVersions>) {
+        up_env.resolve_versions_for_dir(dir, self.ambiguous_versions_policy())
+    }
+
+    /// Returns what applying the environment would set up, without
+    /// applying it; see [`preview_dynamic_env`]
+    fn preview(&mut self) -> DynamicEnvPreview {
+        let up_env = match self.environment() {
+            Some(up_env) => up_env,
+            None => return DynamicEnvPreview::default(),
+        };
+
+        let path = self.path.clone().unwrap_or(".".to_string());
+        let dir = workdir(&path).reldir(&path).unwrap_or("".to_string());
+
+        let mut envsetter = DynamicEnvSetter::new();
+        self.apply_up_env(&up_env, &mut envsetter, &dir, true);
+        envsetter.set_value_by_fn("CFLAGS", dedup_flags);
+        envsetter.set_value_by_fn("CPPFLAGS", dedup_flags);
+        envsetter.set_value_by_fn("LDFLAGS", dedup_flags);
+
+        let mut preview = DynamicEnvPreview::default();
+
+        let env_data = envsetter.get_isolated_env_data();
+        for (key, value) in env_data.env.iter() {
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+
+            if env_data.lists.contains_key(key) {
+                let entries = value
+                    .split(env_data.list_separator)
+                    .filter(|entry| !entry.is_empty())
+                    .map(|entry| entry.to_string())
+                    .collect();
+                preview.lists.insert(key.clone(), entries);
+            } else {
+                preview.env_vars.insert(key.clone(), value.clone());
+            }
+        }
+
+        let (versions, _) = self.resolve_versions(&up_env, &dir);
+        for toolversion in versions.iter() {
+            preview
+                .tools
+                .entry(toolversion.tool.clone())
+                .and_modify(|version| {
+                    version.push_str(", ");
+                    version.push_str(&toolversion.version);
+                })
+                .or_insert_with(|| toolversion.version.clone());
+        }
+
+        preview
+    }
+
     pub fn id(&self) -> u64 {
         *self.id.get_or_init(|| {
             // Get the current path
@@ -408,13 +1103,17 @@ impl DynamicEnv {
             // Prepare the hash
             let mut hasher = Hasher::new();
 
-            // Try and get the shell PPID by using the PPID environment variables
-            let ppid = std::env::var("OMNI_SHELL_PPID").unwrap_or("".to_string());
-            hasher.update(ppid.as_bytes());
+            // Scope the environment to the shell session, by default
+            // identified by the shell PPID
+            hasher.update(self.shell_session_id().as_bytes());
             hasher.update(DATA_SEPARATOR.as_bytes());
 
-            // Let's add the workdir location and the workdir id to the hash
-            hasher.update(workdir.root().unwrap().as_bytes());
+            // Let's add the workdir location and the workdir id to the hash;
+            // the physical location is used so that the hash is the same no
+            // matter the path that was used to reach the workdir
+            let root = workdir.root().unwrap();
+            let root = std::fs::canonicalize(root).unwrap_or_else(|_| PathBuf::from(root));
+            hasher.update(root.to_string_lossy().as_bytes());
             hasher.update(DATA_SEPARATOR.as_bytes());
             hasher.update(workdir.id().unwrap().as_bytes());
             hasher.update(DATA_SEPARATOR.as_bytes());
@@ -429,6 +1128,13 @@ impl DynamicEnv {
                     hasher.update(value.as_bytes());
                     hasher.update(DATA_SEPARATOR.as_bytes());
                 }
+                // The command is used instead of its output, so that the
+                // environment is not considered changed each time the
+                // output of the command changes
+                if let Some(command) = &env_var.command {
+                    hasher.update(command.as_bytes());
+                    hasher.update(DATA_SEPARATOR.as_bytes());
+                }
             }
 
             // Add the requested paths to the hash
@@ -438,7 +1144,8 @@ impl DynamicEnv {
             }
 
             // Go over the tool versions in the up environment cache
-            for toolversion in up_env.versions_for_dir(&dir).iter() {
+            let (versions, _) = self.resolve_versions(&up_env, &dir);
+            for toolversion in versions.iter() {
                 hasher.update(toolversion.backend.as_bytes());
                 hasher.update(DATA_SEPARATOR.as_bytes());
                 hasher.update(toolversion.tool.as_bytes());
@@ -481,7 +1188,8 @@ impl DynamicEnv {
     }
 
     pub fn apply(&mut self, export_mode: DynamicEnvExportMode, keep_shims: bool) {
-        let mut envsetter = DynamicEnvSetter::new();
+        let mut envsetter =
+            DynamicEnvSetter::new().with_list_separator(export_mode.list_separator());
 
         let mut up_env = None;
         let path = self.path.clone().unwrap_or(".".to_string());
@@ -491,22 +1199,8 @@ impl DynamicEnv {
         }
 
         if let Some(up_env) = &up_env {
-            // Apply direct changes to the environment
-            self.apply_env(up_env, &mut envsetter);
-
-            if !keep_shims {
-                // Remove the shims directory from the PATH
-                envsetter.remove_all_from_list("PATH", shims_dir().to_str().unwrap());
-            }
-
-            // Add the requested paths
-            for path in up_env.paths.iter().rev() {
-                envsetter.prepend_to_list("PATH", path.to_str().unwrap());
-            }
-
-            // Apply environment changes for the tool versions
             let dir = workdir.reldir(&path).unwrap_or("".to_string());
-            self.apply_versions(up_env, &mut envsetter, &dir);
+            self.apply_up_env(up_env, &mut envsetter, &dir, keep_shims);
         }
 
         // If any FLAGS variable is set, we can clean it up by removing the duplicate
@@ -526,7 +1220,9 @@ impl DynamicEnv {
         }
 
         // Set the dynamic env variable so we can easily undo things
-        let json_data = envsetter.get_env_data().to_json();
+        let mut env_data = envsetter.get_env_data();
+        env_data.degraded = self.missing_tools.clone();
+        let json_data = env_data.to_json();
         if self.id() == 0 {
             envsetter.unset_value(DYNENV_VAR);
         } else {
@@ -536,7 +1232,9 @@ impl DynamicEnv {
             );
         }
 
-        self.data = Some(envsetter.get_env_data());
+        let mut env_data = envsetter.get_env_data();
+        env_data.degraded = self.missing_tools.clone();
+        self.data = Some(env_data);
         self.data.clone().unwrap().export(export_mode.clone());
     }
 
@@ -555,11 +1253,39 @@ impl DynamicEnv {
             return;
         }
 
-        let mut data = self.data.clone().unwrap();
+        let mut data = self
+            .data
+            .clone()
+            .unwrap()
+            .with_list_separator(export_mode.list_separator());
         data.prepare_undo();
         data.export(export_mode.clone());
     }
 
+    fn apply_up_env(
+        &mut self,
+        up_env: &UpEnvironment,
+        envsetter: &mut DynamicEnvSetter,
+        dir: &str,
+        keep_shims: bool,
+    ) {
+        // Apply direct changes to the environment
+        self.apply_env(up_env, envsetter);
+
+        if !keep_shims {
+            // Remove the shims directory from the PATH
+            envsetter.remove_all_from_list("PATH", shims_dir().to_str().unwrap());
+        }
+
+        // Add the requested paths
+        for path in up_env.paths.iter().rev() {
+            envsetter.prepend_to_list("PATH", path.to_str().unwrap());
+        }
+
+        // Apply environment changes for the tool versions
+        self.apply_versions(up_env, envsetter, dir);
+    }
+
     fn apply_env(&mut self, up_env: &UpEnvironment, envsetter: &mut DynamicEnvSetter) {
         if up_env.env_vars.is_empty() {
             return;
@@ -567,9 +1293,36 @@ impl DynamicEnv {
 
         self.features.push("env".to_string());
 
+        // Commands providing the values of variables are run from the
+        // root of the work directory
+        let path = self.path.clone().unwrap_or(".".to_string());
+        let cwd = workdir(&path).root().unwrap_or(&path).to_string();
+
         // Add the requested environments
         for env_var in up_env.env_vars.iter() {
-            envsetter.apply_env_var(env_var);
+            let command = match &env_var.command {
+                Some(command) => command,
+                None => {
+                    envsetter.apply_env_var(env_var);
+                    continue;
+                }
+            };
+
+            // A failing command leads to the variable being unset
+            let mut env_var = env_var.clone();
+            env_var.value =
+                match env_command_output(command, Path::new(&cwd), self.env_command_timeout) {
+                    Ok(output) => Some(output),
+                    Err(err) => {
+                        self.failed_env_commands.push(format!(
+                            "command for {} failed ({}), variable unset",
+                            env_var.name.force_light_yellow(),
+                            err,
+                        ));
+                        None
+                    }
+                };
+            envsetter.apply_env_var(&env_var);
         }
     }
 
@@ -578,200 +1331,633 @@ impl DynamicEnv {
         up_env: &UpEnvironment,
         envsetter: &mut DynamicEnvSetter,
         dir: &str,
+    ) {
+        self.apply_versions_with_backends(&env_backend_registry(), up_env, envsetter, dir);
+    }
+
+    /// Applies the tool versions of the up environment through the
+    /// backends of the given registry
+    fn apply_versions_with_backends(
+        &mut self,
+        backends: &EnvBackendRegistry,
+        up_env: &UpEnvironment,
+        envsetter: &mut DynamicEnvSetter,
+        dir: &str,
     ) {
         // Go over the tool versions in the up environment cache
-        for toolversion in up_env.versions_for_dir(dir).iter() {
-            let tool = toolversion.tool.clone();
-            let version = toolversion.version.clone();
-
-            // Handle backends that won't require extra setup
-            let skip_tool_specific = match toolversion.backend.as_str() {
-                "" | "default" => {
-                    // Continue with tool-specific handling for default backend
-                    false
-                }
-                "ghrelease" => {
-                    let tool_path = github_release_tool_path(&tool, &version);
-                    let bin_path = if toolversion.bin_path.is_empty() {
-                        tool_path
-                    } else {
-                        tool_path.join(&toolversion.bin_path)
-                    };
-                    envsetter.prepend_to_list("PATH", &bin_path.to_string_lossy());
-                    true
-                }
-                "cargo-install" => {
-                    envsetter.prepend_to_list(
-                        "PATH",
-                        &cargo_install_tool_path(&tool, &version)
-                            .join("bin")
-                            .to_string_lossy(),
-                    );
-                    true
-                }
-                "go-install" => {
-                    envsetter.prepend_to_list(
-                        "PATH",
-                        &go_install_tool_path(&tool, &version)
-                            .join("bin")
-                            .to_string_lossy(),
-                    );
-                    true
-                }
-                _ => {
-                    // Skip the tool if we don't know the backend
-                    continue;
-                }
+        let (versions, ambiguous_versions) = self.resolve_versions(up_env, dir);
+        self.ambiguous_versions = ambiguous_versions;
+        for toolversion in versions.iter() {
+            // Skip the tool if we don't know the backend
+            let backend = match backends.get(&toolversion.backend) {
+                Some(backend) => backend,
+                None => continue,
             };
 
-            if !skip_tool_specific {
-                self.features.push(format!("{tool}:{version}"));
+            // If the tool was removed since the environment was cached,
+            // do not add dead paths to the environment; the environment
+            // is marked as degraded instead, and the user will be notified
+            // that the tool needs reinstalling
+            if self.check_tool_paths && !backend.is_installed(toolversion) {
+                self.missing_tools
+                    .push(format!("{}:{}", toolversion.tool, toolversion.version));
+                continue;
+            }
 
-                let normalized_name = toolversion.normalized_name.clone();
-                let tool_prefix = mise_tool_path(&normalized_name, &version);
-                let bin_path = if toolversion.bin_path.is_empty() {
-                    String::new()
-                } else {
-                    format!("/{}", toolversion.bin_path.clone())
-                };
+            if !backend.apply_version(toolversion, self, envsetter) {
+                continue;
+            }
+
+            // Apply any tool-specific environment variables (works for all backends)
+            for env_var in &toolversion.env_vars {
+                envsetter.apply_env_var(env_var);
+            }
+        }
+    }
+}
 
-                match tool.as_str() {
-                    "ruby" => {
-                        envsetter.remove_from_list_by_fn("PATH", || {
-                            let mut values_to_remove = Vec::new();
+/// The backends through which tool versions can be made available in the
+/// environment, indexed by the backend name recorded for the tool versions
+static ENV_BACKENDS: Lazy<RwLock<EnvBackendRegistry>> =
+    Lazy::new(|| RwLock::new(EnvBackendRegistry::builtin()));
+
+/// Returns a snapshot of the registry of the backends, so that the lock
+/// is not held while the backends are used
+fn env_backend_registry() -> EnvBackendRegistry {
+    match ENV_BACKENDS.read() {
+        Ok(registry) => registry.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
 
-                            if let Some(rubyroot) = std::env::var_os("RUBY_ROOT") {
-                                values_to_remove
-                                    .push(format!("{}/bin", rubyroot.to_str().unwrap()));
-                            }
+/// Returns the backend handling the tool versions recorded with the given
+/// backend name, if that backend is known
+pub fn env_backend(name: &str) -> Option<Arc<dyn EnvBackend>> {
+    env_backend_registry().get(name)
+}
 
-                            if let Some(gemroot) = std::env::var_os("GEM_ROOT") {
-                                values_to_remove.push(format!("{}/bin", gemroot.to_str().unwrap()));
-                            }
+/// Returns the known backends, in the order in which they were registered
+pub fn env_backends() -> Vec<Arc<dyn EnvBackend>> {
+    env_backend_registry().backends()
+}
 
-                            if let Some(gemhome) = std::env::var_os("GEM_HOME") {
-                                values_to_remove.push(format!("{}/bin", gemhome.to_str().unwrap()));
-                            }
+/// Registers a backend through which tools can be installed and made
+/// available in the dynamic environment; registering a backend with the
+/// same names as a known backend replaces it
+#[allow(dead_code)]
+pub fn register_env_backend(backend: Arc<dyn EnvBackend>) {
+    let mut registry = match ENV_BACKENDS.write() {
+        Ok(registry) => registry,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    registry.register(backend);
+}
 
-                            values_to_remove
-                        });
-
-                        let version_minor = version.split('.').take(2).join(".");
-                        let gems_dir = format!("{tool_prefix}/lib/ruby/gems");
-                        let gem_home = format!("{gems_dir}/{version_minor}.0");
-
-                        envsetter.set_value("GEM_HOME", &gem_home);
-                        envsetter.set_value("GEM_ROOT", &gem_home);
-                        envsetter.set_value("RUBY_ENGINE", "ruby");
-                        envsetter.set_value("RUBY_ROOT", &tool_prefix);
-                        envsetter.set_value("RUBY_VERSION", &version);
-                        envsetter.prepend_to_list("GEM_PATH", &gem_home);
-                        envsetter
-                            .prepend_to_list("PATH", &format!("{gems_dir}/{version_minor}/bin"));
-                        envsetter.prepend_to_list("PATH", &format!("{tool_prefix}/bin"));
-
-                        // Handle the isolated GEM_HOME
-                        if let Some(data_path) = &toolversion.data_path {
-                            envsetter.set_value("GEM_HOME", data_path);
-                            envsetter.prepend_to_list("GEM_PATH", data_path);
-                            envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
-                        }
-                    }
-                    "rust" => {
-                        envsetter.set_value("RUSTUP_HOME", &format!("{}/rustup", mise_path()));
-                        envsetter.set_value("CARGO_HOME", &format!("{}/cargo", mise_path()));
-                        envsetter.set_value("RUSTUP_TOOLCHAIN", &version);
-                        envsetter.prepend_to_list("PATH", &tool_prefix);
-
-                        // Handle the isolated CARGO_INSTALL_PATH
-                        if let Some(data_path) = &toolversion.data_path {
-                            envsetter.set_value("CARGO_INSTALL_ROOT", data_path);
-                            envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
-                        }
-                    }
-                    "go" => {
-                        if let Some(goroot) = std::env::var_os("GOROOT") {
-                            envsetter.remove_from_list(
-                                "PATH",
-                                &format!("{}/bin", goroot.to_str().unwrap()),
-                            );
-                        }
+/// A backend through which tools are installed, which knows how to
+/// resolve and install a tool version, whether it is still installed,
+/// how to make it available in the dynamic environment, and how to
+/// cleanup the installations that are not used anymore
+pub trait EnvBackend: Send + Sync {
+    /// The backend names recorded for the tool versions installed
+    /// through that backend
+    fn names(&self) -> Vec<&'static str>;
+
+    /// Resolves the version of the tool matching the requested version
+    /// among the versions available through that backend, without
+    /// installing it
+    #[allow(dead_code)]
+    fn resolve_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError>;
+
+    /// Installs the version of the tool matching the requested version,
+    /// and records it in the environment; returns the version recorded
+    /// for the tool, which is the one given back to `apply_version`
+    fn install_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError>;
+
+    /// Returns whether the tool version is still installed; the tool
+    /// versions that are not are reported as missing instead of being
+    /// applied to the environment
+    fn is_installed(&self, _toolversion: &UpVersion) -> bool {
+        true
+    }
 
-                        if std::env::var_os("GOMODCACHE").is_none() {
-                            let gopath = match std::env::var_os("GOPATH") {
-                                Some(gopath) => match gopath.to_str() {
-                                    Some("") | None => format!("{}/go", user_home()),
-                                    Some(gopath) => gopath.to_string(),
-                                },
-                                None => format!("{}/go", user_home()),
-                            };
-                            envsetter.set_value("GOMODCACHE", &format!("{gopath}/pkg/mod"));
-                        }
+    /// Applies the environment changes making the tool version available;
+    /// returns false if the tool version cannot be made available, in which
+    /// case its environment variables are not applied either
+    fn apply_version(
+        &self,
+        toolversion: &UpVersion,
+        dynenv: &mut DynamicEnv,
+        envsetter: &mut DynamicEnvSetter,
+    ) -> bool;
 
-                        envsetter.set_value("GOROOT", &tool_prefix);
-                        envsetter.set_value("GOVERSION", &version);
+    /// Removes the installations of that backend that are not used
+    /// anymore; returns a message describing what was removed, if anything
+    fn cleanup(&self, _progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        Ok(None)
+    }
+}
 
-                        let gorootbin = format!("{tool_prefix}/bin");
-                        envsetter.set_value("GOBIN", &gorootbin);
-                        envsetter.prepend_to_list("PATH", &gorootbin);
+/// The backends through which tool versions can be made available in
+/// the environment, indexed by the backend names they handle
+#[derive(Clone, Default)]
+pub struct EnvBackendRegistry {
+    backends: HashMap<String, Arc<dyn EnvBackend>>,
+    ordered: Vec<Arc<dyn EnvBackend>>,
+}
 
-                        // Handle the isolated GOPATH
-                        if let Some(data_path) = &toolversion.data_path {
-                            envsetter.prepend_to_list("GOPATH", data_path);
+impl EnvBackendRegistry {
+    /// Returns a registry with the backends supported by omni
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
 
-                            let gobin = format!("{data_path}/bin");
-                            envsetter.set_value("GOBIN", &gobin);
-                            envsetter.prepend_to_list("PATH", &gobin);
-                        }
-                    }
-                    "python" => {
-                        let tool_prefix = if let Some(data_path) = &toolversion.data_path {
-                            envsetter.set_value("VIRTUAL_ENV", data_path);
-                            envsetter.set_value("UV_PROJECT_ENVIRONMENT", data_path);
-                            data_path.clone()
-                        } else {
-                            tool_prefix
-                        };
-
-                        envsetter.unset_value("PYTHONHOME");
-                        envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
-
-                        let poetry_dir = format!("{tool_prefix}/poetry");
-                        envsetter.set_value("POETRY_CONFIG_DIR", &format!("{poetry_dir}/config"));
-                        envsetter.set_value("POETRY_CACHE_DIR", &format!("{poetry_dir}/cache"));
-                        envsetter.set_value("POETRY_DATA_DIR", &poetry_dir);
-                    }
-                    "node" => {
-                        envsetter.set_value("NODE_VERSION", &version);
-                        envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
-
-                        // Handle the isolated NPM prefix
-                        if let Some(data_path) = &toolversion.data_path {
-                            envsetter.set_value("npm_config_prefix", data_path);
-                            envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
-                        };
+        registry.register(Arc::new(MiseEnvBackend));
+        registry.register(Arc::new(GithubReleaseEnvBackend));
+        registry.register(Arc::new(GoInstallEnvBackend));
+        registry.register(Arc::new(CargoInstallEnvBackend));
+        registry.register(Arc::new(PipxEnvBackend));
+
+        registry
+    }
+
+    /// Registers the backend for each of its names; registering a
+    /// backend again replaces the previous registration
+    pub fn register(&mut self, backend: Arc<dyn EnvBackend>) {
+        for name in backend.names() {
+            self.backends.insert(name.to_string(), backend.clone());
+        }
+
+        self.ordered
+            .retain(|registered| registered.names() != backend.names());
+        self.ordered.push(backend);
+    }
+
+    /// Returns the backend registered for the given name, if any
+    pub fn get(&self, name: &str) -> Option<Arc<dyn EnvBackend>> {
+        self.backends.get(name).cloned()
+    }
+
+    /// Returns the registered backends, in the order in which they
+    /// were registered
+    pub fn backends(&self) -> Vec<Arc<dyn EnvBackend>> {
+        self.ordered.clone()
+    }
+}
+
+/// The default backend, for the tools installed through mise, which
+/// may require tool-specific environment changes
+struct MiseEnvBackend;
+
+impl EnvBackend for MiseEnvBackend {
+    fn names(&self) -> Vec<&'static str> {
+        vec!["", "default"]
+    }
+
+    fn resolve_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigMise::resolve_tool_version(tool, version, options, progress_handler)
+    }
+
+    fn install_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigMise::install_tool_version(tool, version, options, environment, progress_handler)
+    }
+
+    fn is_installed(&self, toolversion: &UpVersion) -> bool {
+        Path::new(&mise_tool_path(
+            &toolversion.normalized_name,
+            &toolversion.version,
+        ))
+        .exists()
+    }
+
+    fn apply_version(
+        &self,
+        toolversion: &UpVersion,
+        dynenv: &mut DynamicEnv,
+        envsetter: &mut DynamicEnvSetter,
+    ) -> bool {
+        let tool = toolversion.tool.clone();
+        let version = toolversion.version.clone();
+        let normalized_name = toolversion.normalized_name.clone();
+        let tool_prefix = mise_tool_path(&normalized_name, &version);
+
+        dynenv.features.push(format!("{tool}:{version}"));
+        let bin_path = if toolversion.bin_path.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", toolversion.bin_path.clone())
+        };
+
+        match tool.as_str() {
+            "ruby" => {
+                envsetter.remove_from_list_by_fn("PATH", || {
+                    let mut values_to_remove = Vec::new();
+
+                    if let Some(rubyroot) = std::env::var_os("RUBY_ROOT") {
+                        values_to_remove.push(format!("{}/bin", rubyroot.to_str().unwrap()));
                     }
-                    "helm" => {
-                        envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
-
-                        // Handle the isolated HELM configuration and cache
-                        if let Some(data_path) = &toolversion.data_path {
-                            envsetter.set_value("HELM_CONFIG_HOME", &format!("{data_path}/config"));
-                            envsetter.set_value("HELM_CACHE_HOME", &format!("{data_path}/cache"));
-                            envsetter.set_value("HELM_DATA_HOME", &format!("{data_path}/data"));
-                        }
+
+                    if let Some(gemroot) = std::env::var_os("GEM_ROOT") {
+                        values_to_remove.push(format!("{}/bin", gemroot.to_str().unwrap()));
                     }
-                    _ => {
-                        envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
+
+                    if let Some(gemhome) = std::env::var_os("GEM_HOME") {
+                        values_to_remove.push(format!("{}/bin", gemhome.to_str().unwrap()));
                     }
+
+                    values_to_remove
+                });
+
+                let version_minor = version.split('.').take(2).join(".");
+                let gems_dir = format!("{tool_prefix}/lib/ruby/gems");
+                let gem_home = format!("{gems_dir}/{version_minor}.0");
+
+                envsetter.set_value("GEM_HOME", &gem_home);
+                envsetter.set_value("GEM_ROOT", &gem_home);
+                envsetter.set_value("RUBY_ENGINE", "ruby");
+                envsetter.set_value("RUBY_ROOT", &tool_prefix);
+                envsetter.set_value("RUBY_VERSION", &version);
+                envsetter.prepend_to_list("GEM_PATH", &gem_home);
+                envsetter.prepend_to_list("PATH", &format!("{gems_dir}/{version_minor}/bin"));
+                envsetter.prepend_to_list("PATH", &format!("{tool_prefix}/bin"));
+
+                // Handle the isolated GEM_HOME
+                if let Some(data_path) = &toolversion.data_path {
+                    envsetter.set_value("GEM_HOME", data_path);
+                    envsetter.prepend_to_list("GEM_PATH", data_path);
+                    envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
                 }
             }
+            "rust" => {
+                envsetter.set_value("RUSTUP_HOME", &format!("{}/rustup", mise_path()));
+                envsetter.set_value("CARGO_HOME", &format!("{}/cargo", mise_path()));
+                envsetter.set_value("RUSTUP_TOOLCHAIN", &version);
+                envsetter.prepend_to_list("PATH", &tool_prefix);
 
-            // Apply any tool-specific environment variables (works for all backends)
-            for env_var in &toolversion.env_vars {
-                envsetter.apply_env_var(env_var);
+                // Handle the isolated CARGO_INSTALL_PATH
+                if let Some(data_path) = &toolversion.data_path {
+                    envsetter.set_value("CARGO_INSTALL_ROOT", data_path);
+                    envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
+                }
+            }
+            "go" => {
+                if let Some(goroot) = std::env::var_os("GOROOT") {
+                    envsetter
+                        .remove_from_list("PATH", &format!("{}/bin", goroot.to_str().unwrap()));
+                }
+
+                if std::env::var_os("GOMODCACHE").is_none() {
+                    let gopath = match std::env::var_os("GOPATH") {
+                        Some(gopath) => match gopath.to_str() {
+                            Some("") | None => format!("{}/go", user_home()),
+                            Some(gopath) => gopath.to_string(),
+                        },
+                        None => format!("{}/go", user_home()),
+                    };
+                    envsetter.set_value("GOMODCACHE", &format!("{gopath}/pkg/mod"));
+                }
+
+                envsetter.set_value("GOROOT", &tool_prefix);
+                envsetter.set_value("GOVERSION", &version);
+
+                let gorootbin = format!("{tool_prefix}/bin");
+                envsetter.set_value("GOBIN", &gorootbin);
+                envsetter.prepend_to_list("PATH", &gorootbin);
+
+                // Handle the isolated GOPATH
+                if let Some(data_path) = &toolversion.data_path {
+                    envsetter.prepend_to_list("GOPATH", data_path);
+
+                    let gobin = format!("{data_path}/bin");
+                    envsetter.set_value("GOBIN", &gobin);
+                    envsetter.prepend_to_list("PATH", &gobin);
+                }
+            }
+            "java" => {
+                if let Some(javahome) = std::env::var_os("JAVA_HOME") {
+                    envsetter
+                        .remove_from_list("PATH", &format!("{}/bin", javahome.to_str().unwrap()));
+                }
+
+                // The java home is the parent of the directory holding the
+                // binaries, which is not the tool prefix for some JDKs (e.g.
+                // `Contents/Home/bin` on macOS)
+                let javabin = format!("{tool_prefix}{bin_path}");
+                let javahome = javabin.strip_suffix("/bin").unwrap_or(&javabin);
+                envsetter.set_value("JAVA_HOME", javahome);
+                envsetter.prepend_to_list("PATH", &javabin);
+            }
+            "python" => {
+                let tool_prefix = if let Some(data_path) = &toolversion.data_path {
+                    envsetter.set_value("VIRTUAL_ENV", data_path);
+                    envsetter.set_value("UV_PROJECT_ENVIRONMENT", data_path);
+                    data_path.clone()
+                } else {
+                    tool_prefix
+                };
+
+                envsetter.unset_value("PYTHONHOME");
+                envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
+
+                let poetry_dir = format!("{tool_prefix}/poetry");
+                envsetter.set_value("POETRY_CONFIG_DIR", &format!("{poetry_dir}/config"));
+                envsetter.set_value("POETRY_CACHE_DIR", &format!("{poetry_dir}/cache"));
+                envsetter.set_value("POETRY_DATA_DIR", &poetry_dir);
+            }
+            "node" => {
+                envsetter.set_value("NODE_VERSION", &version);
+                envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
+
+                // Handle the isolated NPM prefix
+                if let Some(data_path) = &toolversion.data_path {
+                    envsetter.set_value("npm_config_prefix", data_path);
+                    envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
+                };
+            }
+            "helm" => {
+                envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
+
+                // Handle the isolated HELM configuration and cache
+                if let Some(data_path) = &toolversion.data_path {
+                    envsetter.set_value("HELM_CONFIG_HOME", &format!("{data_path}/config"));
+                    envsetter.set_value("HELM_CACHE_HOME", &format!("{data_path}/cache"));
+                    envsetter.set_value("HELM_DATA_HOME", &format!("{data_path}/data"));
+                }
+            }
+            "bun" => {
+                envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
+
+                // Handle the isolated global packages and cache
+                if let Some(data_path) = &toolversion.data_path {
+                    envsetter.set_value("BUN_INSTALL", data_path);
+                    envsetter.set_value(
+                        "BUN_INSTALL_CACHE_DIR",
+                        &format!("{data_path}/install/cache"),
+                    );
+                    envsetter.prepend_to_list("PATH", &format!("{data_path}/bin"));
+                }
+            }
+            _ => {
+                envsetter.prepend_to_list("PATH", &format!("{tool_prefix}{bin_path}"));
             }
         }
+
+        true
+    }
+
+    fn cleanup(&self, progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        UpConfigMise::cleanup(progress_handler)
+    }
+}
+
+struct GithubReleaseEnvBackend;
+
+impl EnvBackend for GithubReleaseEnvBackend {
+    fn names(&self) -> Vec<&'static str> {
+        vec!["ghrelease"]
+    }
+
+    fn resolve_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigGithubReleases::resolve_tool_version(tool, version, options, progress_handler)
+    }
+
+    fn install_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigGithubReleases::install_tool_version(
+            tool,
+            version,
+            options,
+            environment,
+            progress_handler,
+        )
+    }
+
+    fn apply_version(
+        &self,
+        toolversion: &UpVersion,
+        _dynenv: &mut DynamicEnv,
+        envsetter: &mut DynamicEnvSetter,
+    ) -> bool {
+        let tool_path = github_release_tool_path(&toolversion.tool, &toolversion.version);
+        let bin_path = if toolversion.bin_path.is_empty() {
+            tool_path
+        } else {
+            tool_path.join(&toolversion.bin_path)
+        };
+        envsetter.prepend_to_list("PATH", &bin_path.to_string_lossy());
+        true
+    }
+
+    fn cleanup(&self, progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        UpConfigGithubReleases::cleanup(progress_handler)
+    }
+}
+
+struct CargoInstallEnvBackend;
+
+impl EnvBackend for CargoInstallEnvBackend {
+    fn names(&self) -> Vec<&'static str> {
+        vec!["cargo-install"]
+    }
+
+    fn resolve_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigCargoInstalls::resolve_tool_version(tool, version, options, progress_handler)
+    }
+
+    fn install_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigCargoInstalls::install_tool_version(
+            tool,
+            version,
+            options,
+            environment,
+            progress_handler,
+        )
+    }
+
+    fn apply_version(
+        &self,
+        toolversion: &UpVersion,
+        dynenv: &mut DynamicEnv,
+        envsetter: &mut DynamicEnvSetter,
+    ) -> bool {
+        let bin_path = cargo_install_tool_path(&toolversion.tool, &toolversion.version).join("bin");
+
+        // If the installation was interrupted or its binaries removed
+        // since the environment was cached, the directory might not hold
+        // any executable; the path is still added so that the environment
+        // stays the same, but the user will be notified to run omni up
+        if dynenv.check_tool_paths && tool_binaries(&toolversion.tool, &bin_path).is_empty() {
+            dynenv
+                .incomplete_tools
+                .push(format!("{}:{}", toolversion.tool, toolversion.version));
+        }
+
+        envsetter.prepend_to_list("PATH", &bin_path.to_string_lossy());
+        true
+    }
+
+    fn cleanup(&self, progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        UpConfigCargoInstalls::cleanup(progress_handler)
+    }
+}
+
+struct GoInstallEnvBackend;
+
+impl EnvBackend for GoInstallEnvBackend {
+    fn names(&self) -> Vec<&'static str> {
+        vec!["go-install"]
+    }
+
+    fn resolve_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigGoInstalls::resolve_tool_version(tool, version, options, progress_handler)
+    }
+
+    fn install_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigGoInstalls::install_tool_version(
+            tool,
+            version,
+            options,
+            environment,
+            progress_handler,
+        )
+    }
+
+    fn apply_version(
+        &self,
+        toolversion: &UpVersion,
+        _dynenv: &mut DynamicEnv,
+        envsetter: &mut DynamicEnvSetter,
+    ) -> bool {
+        envsetter.prepend_to_list(
+            "PATH",
+            &go_install_tool_path(&toolversion.tool, &toolversion.version)
+                .join("bin")
+                .to_string_lossy(),
+        );
+        true
+    }
+
+    fn cleanup(&self, progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        UpConfigGoInstalls::cleanup(progress_handler)
+    }
+}
+
+struct PipxEnvBackend;
+
+impl EnvBackend for PipxEnvBackend {
+    fn names(&self) -> Vec<&'static str> {
+        vec!["pipx"]
+    }
+
+    /// The version of a package is resolved by pip when installing it
+    fn resolve_version(
+        &self,
+        tool: &str,
+        _version: &str,
+        _options: &UpOptions,
+        _progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        Err(UpError::Exec(format!(
+            "the version of {tool} can only be resolved when installing it"
+        )))
+    }
+
+    fn install_version(
+        &self,
+        tool: &str,
+        version: &str,
+        options: &UpOptions,
+        environment: &mut UpEnvironment,
+        progress_handler: &UpProgressHandler,
+    ) -> Result<String, UpError> {
+        UpConfigPipxInstalls::install_tool_version(
+            tool,
+            version,
+            options,
+            environment,
+            progress_handler,
+        )
+    }
+
+    fn apply_version(
+        &self,
+        toolversion: &UpVersion,
+        _dynenv: &mut DynamicEnv,
+        envsetter: &mut DynamicEnvSetter,
+    ) -> bool {
+        envsetter.prepend_to_list(
+            "PATH",
+            &pipx_tool_path(&toolversion.tool, &toolversion.version)
+                .join("bin")
+                .to_string_lossy(),
+        );
+        true
+    }
+
+    fn cleanup(&self, progress_handler: &UpProgressHandler) -> Result<Option<String>, UpError> {
+        UpConfigPipxInstalls::cleanup(progress_handler)
     }
 }
 
@@ -787,31 +1973,44 @@ enum DynamicEnvOperation {
     PrefixValue(String, String),
     /// Suffix a value to a variable
     SuffixValue(String, String),
-    /// Prepend a value to a list, using ':' as separator
+    /// Prepend a value to a list, using the list separator
     PrependToList(String, String),
-    /// Append a value to a list, using ':' as separator
+    /// Append a value to a list, using the list separator
     AppendToList(String, String),
-    /// Remove a value from a list, using ':' as separator
+    /// Prepend a directory to a list, using the list separator, unless
+    /// the directory does not exist or is already in the list
+    PrependPathToList(String, String),
+    /// Append a directory to a list, using the list separator, unless
+    /// the directory does not exist or is already in the list
+    AppendPathToList(String, String),
+    /// Remove a value from a list, using the list separator
     RemoveFromList(String, String),
-    /// Remove all occurrences of a value from a list, using ':' as separator
+    /// Remove all occurrences of a value from a list, using the list separator
     RemoveAllFromList(String, String),
-    /// Remove values from a list by a function, using ':' as separator;
+    /// Remove values from a list by a function, using the list separator;
     /// the function should return a list of values to remove
     RemoveFromListByFn(String, Box<dyn Fn() -> Vec<String>>),
 }
 
-struct DynamicEnvSetter {
+pub struct DynamicEnvSetter {
     operations: Vec<DynamicEnvOperation>,
+    list_separator: char,
 }
 
 impl DynamicEnvSetter {
     fn new() -> Self {
         DynamicEnvSetter {
             operations: Vec::new(),
+            list_separator: default_list_separator(),
         }
     }
 
-    fn set_value(&mut self, key: &str, value: &str) {
+    fn with_list_separator(mut self, list_separator: char) -> Self {
+        self.list_separator = list_separator;
+        self
+    }
+
+    pub fn set_value(&mut self, key: &str, value: &str) {
         self.operations.push(DynamicEnvOperation::SetValue(
             key.to_string(),
             value.to_string(),
@@ -828,7 +2027,7 @@ impl DynamicEnvSetter {
         ));
     }
 
-    fn unset_value(&mut self, key: &str) {
+    pub fn unset_value(&mut self, key: &str) {
         self.operations
             .push(DynamicEnvOperation::UnsetValue(key.to_string()));
     }
@@ -847,7 +2046,7 @@ impl DynamicEnvSetter {
         ));
     }
 
-    fn prepend_to_list(&mut self, key: &str, value: &str) {
+    pub fn prepend_to_list(&mut self, key: &str, value: &str) {
         self.operations.push(DynamicEnvOperation::PrependToList(
             key.to_string(),
             value.to_string(),
@@ -861,7 +2060,21 @@ impl DynamicEnvSetter {
         ));
     }
 
-    fn remove_from_list(&mut self, key: &str, value: &str) {
+    fn prepend_path_to_list(&mut self, key: &str, value: &str) {
+        self.operations.push(DynamicEnvOperation::PrependPathToList(
+            key.to_string(),
+            value.to_string(),
+        ));
+    }
+
+    fn append_path_to_list(&mut self, key: &str, value: &str) {
+        self.operations.push(DynamicEnvOperation::AppendPathToList(
+            key.to_string(),
+            value.to_string(),
+        ));
+    }
+
+    pub fn remove_from_list(&mut self, key: &str, value: &str) {
         self.operations.push(DynamicEnvOperation::RemoveFromList(
             key.to_string(),
             value.to_string(),
@@ -875,7 +2088,7 @@ impl DynamicEnvSetter {
         ));
     }
 
-    fn remove_from_list_by_fn<F>(&mut self, key: &str, f: F)
+    pub fn remove_from_list_by_fn<F>(&mut self, key: &str, f: F)
     where
         F: Fn() -> Vec<String> + 'static,
     {
@@ -909,13 +2122,28 @@ impl DynamicEnvSetter {
             (EnvOperationEnum::Suffix, Some(value)) => {
                 self.suffix_value(&env_var.name, &value);
             }
+            (EnvOperationEnum::PrependPath, Some(value)) => {
+                self.prepend_path_to_list(&env_var.name, &value);
+            }
+            (EnvOperationEnum::AppendPath, Some(value)) => {
+                self.append_path_to_list(&env_var.name, &value);
+            }
             (_, None) => {}
         }
     }
 
     fn get_env_data(&self) -> DynamicEnvData {
-        let mut data = DynamicEnvData::new();
+        self.env_data(DynamicEnvData::new())
+    }
+
+    /// Returns the data of the environment as applied on top of an empty
+    /// environment, without considering the current environment
+    fn get_isolated_env_data(&self) -> DynamicEnvData {
+        self.env_data(DynamicEnvData::isolated())
+    }
 
+    fn env_data(&self, data: DynamicEnvData) -> DynamicEnvData {
+        let mut data = data.with_list_separator(self.list_separator);
         for operation in self.operations.iter() {
             match operation {
                 DynamicEnvOperation::SetValue(key, value) => {
@@ -941,6 +2169,16 @@ impl DynamicEnvSetter {
                 DynamicEnvOperation::AppendToList(key, value) => {
                     data.append_to_list(key, value);
                 }
+                DynamicEnvOperation::PrependPathToList(key, value) => {
+                    if data.should_add_path_to_list(key, value) {
+                        data.prepend_to_list(key, value);
+                    }
+                }
+                DynamicEnvOperation::AppendPathToList(key, value) => {
+                    if data.should_add_path_to_list(key, value) {
+                        data.append_to_list(key, value);
+                    }
+                }
                 DynamicEnvOperation::RemoveFromList(key, value) => {
                     data.remove_from_list(key, value);
                 }
@@ -974,8 +2212,30 @@ struct DynamicEnvData {
         skip_serializing_if = "HashMap::is_empty"
     )]
     lists: HashMap<String, Vec<DynamicEnvListValue>>,
+    /// The tools, as `tool:version`, that the environment references
+    /// but that could not be found when it was applied; the environment
+    /// is considered degraded when this is not empty.
+    #[serde(
+        rename = "d",
+        default = "Vec::new",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    degraded: Vec<String>,
     #[serde(skip)]
     env: HashMap<String, Option<String>>,
+    /// Whether the data is computed on top of an empty environment
+    /// instead of the current environment
+    #[serde(skip)]
+    isolated: bool,
+    /// The separator between the entries of list variables; it is not
+    /// part of the serialized data since the operations on lists only
+    /// refer to entries, so that they can be undone in any shell
+    #[serde(skip, default = "default_list_separator")]
+    list_separator: char,
+}
+
+fn default_list_separator() -> char {
+    DynamicEnvExportMode::default().list_separator()
 }
 
 impl DynamicEnvData {
@@ -983,16 +2243,31 @@ impl DynamicEnvData {
         DynamicEnvData {
             values: HashMap::new(),
             lists: HashMap::new(),
+            degraded: Vec::new(),
             env: HashMap::new(),
+            isolated: false,
+            list_separator: default_list_separator(),
         }
     }
 
+    fn isolated() -> Self {
+        DynamicEnvData {
+            isolated: true,
+            ..Self::new()
+        }
+    }
+
+    fn with_list_separator(mut self, list_separator: char) -> Self {
+        self.list_separator = list_separator;
+        self
+    }
+
     fn env_set_var(&mut self, key: &str, value: &str) {
         self.env.insert(key.to_string(), Some(value.to_string()));
     }
 
     fn env_unset_var(&mut self, key: &str) {
-        if self.env.contains_key(key) || std::env::var(key).is_ok() {
+        if self.env.contains_key(key) || (!self.isolated && std::env::var(key).is_ok()) {
             self.env.insert(key.to_string(), None);
         }
     }
@@ -1000,6 +2275,8 @@ impl DynamicEnvData {
     fn env_get_var(&self, key: &str) -> Option<String> {
         if self.env.contains_key(key) {
             self.env.get(key).unwrap().clone()
+        } else if self.isolated {
+            None
         } else {
             std::env::var(key).ok()
         }
@@ -1127,7 +2404,7 @@ impl DynamicEnvData {
         if cur_val.is_empty() {
             self.env_set_var(key, value);
         } else {
-            self.env_set_var(key, &format!("{value}:{cur_val}"));
+            self.env_set_var(key, &format!("{value}{}{cur_val}", self.list_separator));
         }
     }
 
@@ -1142,7 +2419,7 @@ impl DynamicEnvData {
         };
 
         let index = {
-            let prev = cur_val.split(':').collect::<Vec<&str>>();
+            let prev = cur_val.split(self.list_separator).collect::<Vec<&str>>();
             prev.len()
         };
 
@@ -1155,13 +2432,38 @@ impl DynamicEnvData {
         if cur_val.is_empty() {
             self.env_set_var(key, value);
         } else {
-            self.env_set_var(key, &format!("{cur_val}:{value}"));
+            self.env_set_var(key, &format!("{cur_val}{}{value}", self.list_separator));
         }
     }
 
+    /// Returns whether the directory should be added to the list, which
+    /// is only the case if it exists and if the list does not already
+    /// contain an entry pointing to the same directory
+    fn should_add_path_to_list(&self, key: &str, value: &str) -> bool {
+        let path = match std::fs::canonicalize(value) {
+            Ok(path) if path.is_dir() => path,
+            _ => return false,
+        };
+
+        let cur_val = match self.env_get_var(key) {
+            Some(cur_val) => cur_val,
+            None => return true,
+        };
+
+        !cur_val
+            .split(self.list_separator)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match Path::new(entry).strip_prefix("~") {
+                Ok(entry) => PathBuf::from(user_home()).join(entry),
+                Err(_) => PathBuf::from(entry),
+            })
+            .filter_map(|entry| std::fs::canonicalize(entry).ok())
+            .any(|entry| entry == path)
+    }
+
     fn remove_from_list(&mut self, key: &str, value: &str) {
         if let Some(prev) = self.env_get_var(key) {
-            let mut prev = prev.split(':').collect::<Vec<&str>>();
+            let mut prev = prev.split(self.list_separator).collect::<Vec<&str>>();
             if let Some(index) = prev.iter().position(|&r| r == value) {
                 if !self.lists.contains_key(key) {
                     self.lists.insert(key.to_string(), Vec::new());
@@ -1174,14 +2476,14 @@ impl DynamicEnvData {
                 });
 
                 prev.remove(index);
-                self.env_set_var(key, &prev.join(":"));
+                self.env_set_var(key, &prev.join(&self.list_separator.to_string()));
             }
         };
     }
 
     fn remove_all_from_list(&mut self, key: &str, value: &str) {
         if let Some(prev) = self.env_get_var(key) {
-            let mut prev = prev.split(':').collect::<Vec<&str>>();
+            let mut prev = prev.split(self.list_separator).collect::<Vec<&str>>();
             let indexes = prev
                 .iter()
                 .enumerate()
@@ -1208,7 +2510,7 @@ impl DynamicEnvData {
                 prev.remove(*index);
             }
 
-            self.env_set_var(key, &prev.join(":"));
+            self.env_set_var(key, &prev.join(&self.list_separator.to_string()));
         };
     }
 
@@ -1243,7 +2545,7 @@ impl DynamicEnvData {
             // operations we've done to the closest of our ability; since it's
             // a list, we'll also split it, so we're ready to "search and update"
             let cur_val = self.env_get_var(key).unwrap_or("".to_string());
-            let mut cur_val = cur_val.split(':').collect::<Vec<&str>>();
+            let mut cur_val = cur_val.split(self.list_separator).collect::<Vec<&str>>();
 
             for operation in operations.iter().rev() {
                 match operation.operation {
@@ -1272,7 +2574,7 @@ impl DynamicEnvData {
             }
 
             // We can now write the restored value to the environment
-            let cur_val = cur_val.join(":");
+            let cur_val = cur_val.join(&self.list_separator.to_string());
             self.env_set_var(key, &cur_val);
         }
     }
@@ -1287,6 +2589,10 @@ impl DynamicEnvData {
                 self.export_fish();
                 self.export_env();
             }
+            DynamicEnvExportMode::PowerShell => {
+                self.export_powershell();
+                self.export_env();
+            }
             DynamicEnvExportMode::Env => {
                 self.export_env();
             }
@@ -1347,6 +2653,44 @@ impl DynamicEnvData {
             }
         }
     }
+
+    fn export_powershell(&self) {
+        for (key, value) in self.env.iter() {
+            match value {
+                Some(value) => {
+                    println!("$env:{} = {}", key, powershell_quote(value));
+                }
+                None => {
+                    println!("Remove-Item Env:{key} -ErrorAction SilentlyContinue");
+                }
+            }
+        }
+    }
+}
+
+/// Quotes a value as a PowerShell double-quoted string; the backtick is
+/// the escape character of PowerShell, and needs to be used in front of
+/// any character that would otherwise end the string or be expanded,
+/// which includes the typographic double quotes that PowerShell also
+/// accepts as string delimiters
+fn powershell_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '`' | '"' | '$' | '\u{201C}' | '\u{201D}' | '\u{201E}' => {
+                quoted.push('`');
+                quoted.push(c);
+            }
+            '\0' => quoted.push_str("`0"),
+            '\n' => quoted.push_str("`n"),
+            '\r' => quoted.push_str("`r"),
+            '\t' => quoted.push_str("`t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
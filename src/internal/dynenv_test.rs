@@ -6,6 +6,9 @@ mod dynamic_env {
     use crate::internal::cache::up_environments::UpEnvironment;
     use crate::internal::cache::up_environments::UpVersion;
     use crate::internal::config::parser::EnvOperationEnum;
+    use crate::internal::config::parser::UpAmbiguousVersionsPolicy;
+    use crate::internal::config::parser::UpDataPathsPolicy;
+    use crate::internal::config::up::utils::version_data_path;
 
     fn create_test_up_version(
         tool: &str,
@@ -56,10 +59,542 @@ mod dynamic_env {
             data_str: None,
             data: None,
             features: Vec::new(),
+            missing_tools: Vec::new(),
+            incomplete_tools: Vec::new(),
+            ambiguous_versions: Vec::new(),
+            ambiguous_versions_policy: Some(UpAmbiguousVersionsPolicy::Newest),
+            shell_session: None,
+            check_tool_paths: false,
+            env_command_timeout: ENV_COMMAND_APPLY_TIMEOUT,
+            failed_env_commands: Vec::new(),
             cache: UpEnvironmentsCache::get(),
         }
     }
 
+    mod missing_tools {
+        use super::*;
+
+        const MISSING_VERSION: &str = "0.0.0-omni-missing";
+
+        #[test]
+        fn test_missing_tool_path_is_skipped() {
+            let versions = vec![
+                create_test_up_version("node", "", MISSING_VERSION, "bin", None),
+                create_test_up_version("gh", "ghrelease", "2.0.0", "bin", None),
+            ];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            dynamic_env.check_tool_paths = true;
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+            let env_data = envsetter.get_env_data();
+            let path_additions = env_data.lists.get("PATH").unwrap();
+            assert_eq!(path_additions.len(), 1);
+            assert!(path_additions[0].value.ends_with("/gh/2.0.0/bin"));
+            assert!(!env_data.values.contains_key("NODE_VERSION"));
+
+            assert_eq!(
+                dynamic_env.missing_tools,
+                vec![format!("node:{MISSING_VERSION}")]
+            );
+            assert!(dynamic_env.features.is_empty());
+        }
+
+        #[test]
+        fn test_missing_tool_path_does_not_change_id() {
+            let versions = vec![create_test_up_version(
+                "node",
+                "",
+                MISSING_VERSION,
+                "bin",
+                None,
+            )];
+            let up_env = create_test_environment_with_versions(versions);
+
+            let unchecked_env = create_test_dynamic_env().with_environment(Some(&up_env));
+            let mut checked_env = create_test_dynamic_env().with_environment(Some(&up_env));
+            checked_env.check_tool_paths = true;
+
+            let mut envsetter = DynamicEnvSetter::new();
+            checked_env.apply_versions(&up_env, &mut envsetter, "");
+
+            assert!(!checked_env.missing_tools.is_empty());
+            assert_eq!(checked_env.id(), unchecked_env.id());
+        }
+
+        #[test]
+        fn test_degraded_marker_is_exported() {
+            let mut env_data = DynamicEnvData::new();
+            env_data.degraded = vec![format!("node:{MISSING_VERSION}")];
+
+            let json_data = env_data.to_json();
+            assert!(json_data.contains(r#""d":["node:0.0.0-omni-missing"]"#));
+
+            let parsed: DynamicEnvData = serde_json::from_str(&json_data).unwrap();
+            assert_eq!(parsed.degraded, env_data.degraded);
+        }
+
+        #[test]
+        fn test_degraded_marker_omitted_when_healthy() {
+            let env_data = DynamicEnvData::new();
+            assert!(!env_data.to_json().contains(r#""d""#));
+
+            let parsed: DynamicEnvData = serde_json::from_str("{}").unwrap();
+            assert!(parsed.degraded.is_empty());
+        }
+
+        #[test]
+        fn test_notification_is_only_sent_once() {
+            let missing_tools = vec![format!("node:{MISSING_VERSION}")];
+
+            let hashed = missing_tools_to_notify(&missing_tools, None)
+                .expect("should notify when not yet notified");
+
+            assert_eq!(missing_tools_to_notify(&missing_tools, Some(&hashed)), None);
+
+            let other_missing_tools = vec![
+                format!("node:{MISSING_VERSION}"),
+                format!("python:{MISSING_VERSION}"),
+            ];
+            assert!(missing_tools_to_notify(&other_missing_tools, Some(&hashed)).is_some());
+        }
+
+        #[test]
+        fn test_no_notification_when_nothing_missing() {
+            assert_eq!(missing_tools_to_notify(&[], None), None);
+        }
+    }
+
+    mod incomplete_tools {
+        use super::*;
+
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::internal::testutils::run_with_env;
+
+        fn apply_cargo_install(tool: &str, version: &str) -> (DynamicEnv, DynamicEnvData) {
+            let versions = vec![create_test_up_version(
+                tool,
+                "cargo-install",
+                version,
+                "",
+                None,
+            )];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            dynamic_env.check_tool_paths = true;
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+            (dynamic_env, envsetter.get_env_data())
+        }
+
+        #[test]
+        fn test_installed_tool_is_complete() {
+            run_with_env(&[], || {
+                let bin_dir = cargo_install_tool_path("ripgrep", "14.1.0").join("bin");
+                std::fs::create_dir_all(&bin_dir).expect("failed to create bin dir");
+                let binary = bin_dir.join("rg");
+                std::fs::write(&binary, "").expect("failed to create binary");
+                std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))
+                    .expect("failed to set permissions");
+
+                let (dynamic_env, _) = apply_cargo_install("ripgrep", "14.1.0");
+
+                assert!(dynamic_env.incomplete_tools.is_empty());
+            });
+        }
+
+        #[test]
+        fn test_tool_without_executable_is_incomplete() {
+            run_with_env(&[], || {
+                let bin_dir = cargo_install_tool_path("ripgrep", "14.1.0").join("bin");
+                std::fs::create_dir_all(&bin_dir).expect("failed to create bin dir");
+                std::fs::write(bin_dir.join("rg"), "").expect("failed to create file");
+
+                let (dynamic_env, env_data) = apply_cargo_install("ripgrep", "14.1.0");
+
+                assert_eq!(
+                    dynamic_env.incomplete_tools,
+                    vec!["ripgrep:14.1.0".to_string()]
+                );
+
+                // The path is still added to keep the environment unchanged
+                let path_additions = env_data.lists.get("PATH").unwrap();
+                assert_eq!(path_additions.len(), 1);
+                assert!(path_additions[0].value.ends_with("/ripgrep/14.1.0/bin"));
+            });
+        }
+
+        #[test]
+        fn test_missing_bin_directory_is_incomplete() {
+            run_with_env(&[], || {
+                let (dynamic_env, _) = apply_cargo_install("ripgrep", "14.1.0");
+
+                assert_eq!(
+                    dynamic_env.incomplete_tools,
+                    vec!["ripgrep:14.1.0".to_string()]
+                );
+            });
+        }
+    }
+
+    mod path_conflicts {
+        use super::*;
+
+        use std::os::unix::fs::PermissionsExt;
+
+        fn create_binary(dir: &Path, name: &str) {
+            std::fs::create_dir_all(dir).expect("failed to create directory");
+            let path = dir.join(name);
+            std::fs::write(&path, "#!/bin/sh\n").expect("failed to write binary");
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+                .expect("failed to set permissions");
+        }
+
+        fn join_path(dirs: &[&Path]) -> String {
+            std::env::join_paths(dirs)
+                .expect("failed to join paths")
+                .to_string_lossy()
+                .to_string()
+        }
+
+        struct Layout {
+            tempdir: tempfile::TempDir,
+            tool_dir: PathBuf,
+            system_dir: PathBuf,
+        }
+
+        /// Creates a tool directory managed by omni and a system directory
+        /// both providing a `node` binary
+        fn layout() -> Layout {
+            let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+            let tool_dir = tempdir.path().join("omni/node/20.12.0/bin");
+            let system_dir = tempdir.path().join("usr/local/bin");
+            create_binary(&tool_dir, "node");
+            create_binary(&tool_dir, "npm");
+            create_binary(&system_dir, "node");
+
+            Layout {
+                tempdir,
+                tool_dir,
+                system_dir,
+            }
+        }
+
+        fn tool_paths(layout: &Layout) -> Vec<(String, Vec<PathBuf>)> {
+            vec![("node".to_string(), vec![layout.tool_dir.clone()])]
+        }
+
+        #[test]
+        fn test_shadowed_binary_is_reported() {
+            let layout = layout();
+            let path_var = join_path(&[&layout.system_dir, &layout.tool_dir]);
+
+            let conflicts = find_path_conflicts(
+                &tool_paths(&layout),
+                &path_var,
+                &PathConflictsConfig::default(),
+            );
+
+            assert_eq!(
+                conflicts,
+                vec![PathConflict {
+                    tool: "node".to_string(),
+                    binary: "node".to_string(),
+                    winning: layout.system_dir.join("node"),
+                    expected: layout.tool_dir.join("node"),
+                }]
+            );
+            assert!(conflicts[0]
+                .hint()
+                .contains(&layout.system_dir.display().to_string()));
+        }
+
+        #[test]
+        fn test_correct_order_is_not_reported() {
+            let layout = layout();
+            let path_var = join_path(&[&layout.tool_dir, &layout.system_dir]);
+
+            let conflicts = find_path_conflicts(
+                &tool_paths(&layout),
+                &path_var,
+                &PathConflictsConfig::default(),
+            );
+
+            assert!(conflicts.is_empty());
+        }
+
+        #[test]
+        fn test_binary_only_provided_by_omni_is_not_reported() {
+            let layout = layout();
+            create_binary(&layout.system_dir, "other");
+            std::fs::remove_file(layout.system_dir.join("node")).expect("failed to remove");
+            let path_var = join_path(&[&layout.system_dir, &layout.tool_dir]);
+
+            let conflicts = find_path_conflicts(
+                &tool_paths(&layout),
+                &path_var,
+                &PathConflictsConfig::default(),
+            );
+
+            assert!(conflicts.is_empty());
+        }
+
+        #[test]
+        fn test_other_managed_tool_is_not_a_conflict() {
+            let layout = layout();
+            let other_tool_dir = layout.tempdir.path().join("omni/other/1.0.0/bin");
+            create_binary(&other_tool_dir, "node");
+            let path_var = join_path(&[&other_tool_dir, &layout.tool_dir]);
+
+            let mut tool_paths = tool_paths(&layout);
+            tool_paths.push(("other".to_string(), vec![other_tool_dir.clone()]));
+
+            let conflicts =
+                find_path_conflicts(&tool_paths, &path_var, &PathConflictsConfig::default());
+
+            assert!(conflicts.is_empty());
+        }
+
+        #[test]
+        fn test_silenced_tool_is_not_reported() {
+            let layout = layout();
+            let path_var = join_path(&[&layout.system_dir, &layout.tool_dir]);
+            let config = PathConflictsConfig {
+                enabled: true,
+                ignore: vec!["node".to_string()],
+            };
+
+            let conflicts = find_path_conflicts(&tool_paths(&layout), &path_var, &config);
+
+            assert!(conflicts.is_empty());
+        }
+
+        #[test]
+        fn test_disabled_check_reports_nothing() {
+            let layout = layout();
+            let path_var = join_path(&[&layout.system_dir, &layout.tool_dir]);
+            let config = PathConflictsConfig {
+                enabled: false,
+                ignore: vec![],
+            };
+
+            let conflicts = find_path_conflicts(&tool_paths(&layout), &path_var, &config);
+
+            assert!(conflicts.is_empty());
+        }
+    }
+
+    mod ambiguous_versions {
+        use super::*;
+
+        use crate::internal::testutils::run_with_env;
+
+        fn create_scoped_up_version(
+            tool: &str,
+            backend: &str,
+            version: &str,
+            dir: &str,
+        ) -> UpVersion {
+            let mut up_version = create_test_up_version(tool, backend, version, "bin", None);
+            up_version.dir = dir.to_string();
+            up_version
+        }
+
+        fn create_monorepo_environment() -> UpEnvironment {
+            create_test_environment_with_versions(vec![
+                create_scoped_up_version("go", "", "1.21.0", "services/a"),
+                create_scoped_up_version("go", "", "1.22.0", "services/b"),
+            ])
+        }
+
+        fn loaded_go_version(
+            up_env: &UpEnvironment,
+            dir: &str,
+            policy: UpAmbiguousVersionsPolicy,
+        ) -> (Option<String>, DynamicEnv) {
+            let mut dynamic_env = create_test_dynamic_env();
+            dynamic_env.ambiguous_versions_policy = Some(policy);
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions(up_env, &mut envsetter, dir);
+
+            let version = envsetter
+                .get_env_data()
+                .values
+                .get("GOVERSION")
+                .and_then(|value| value.curr.clone());
+            (version, dynamic_env)
+        }
+
+        #[test]
+        fn test_root_uses_newest_version() {
+            let up_env = create_monorepo_environment();
+
+            let (version, dynamic_env) =
+                loaded_go_version(&up_env, "", UpAmbiguousVersionsPolicy::Newest);
+
+            assert_eq!(version, Some("1.22.0".to_string()));
+            assert_eq!(dynamic_env.ambiguous_versions.len(), 1);
+
+            let message = ambiguous_versions_message(
+                &dynamic_env.ambiguous_versions[0],
+                UpAmbiguousVersionsPolicy::Newest,
+            );
+            assert!(
+                message.contains("1.21.0 in services/a, 1.22.0 in services/b"),
+                "message: {message}"
+            );
+            assert!(message.contains("using"), "message: {message}");
+        }
+
+        #[test]
+        fn test_root_requires_pin_when_configured() {
+            let up_env = create_monorepo_environment();
+
+            let (version, dynamic_env) =
+                loaded_go_version(&up_env, "", UpAmbiguousVersionsPolicy::Error);
+
+            assert_eq!(version, None);
+            assert!(dynamic_env.features.is_empty());
+            assert_eq!(dynamic_env.ambiguous_versions.len(), 1);
+
+            let message = ambiguous_versions_message(
+                &dynamic_env.ambiguous_versions[0],
+                UpAmbiguousVersionsPolicy::Error,
+            );
+            assert!(message.contains("none loaded"), "message: {message}");
+        }
+
+        #[test]
+        fn test_subdirectories_use_their_version() {
+            let up_env = create_monorepo_environment();
+
+            for policy in [
+                UpAmbiguousVersionsPolicy::Newest,
+                UpAmbiguousVersionsPolicy::Error,
+            ] {
+                for (dir, expected) in [
+                    ("services/a", "1.21.0"),
+                    ("services/a/cmd", "1.21.0"),
+                    ("services/b", "1.22.0"),
+                ] {
+                    let (version, dynamic_env) = loaded_go_version(&up_env, dir, policy);
+                    assert_eq!(version, Some(expected.to_string()), "dir: {dir}");
+                    assert!(dynamic_env.ambiguous_versions.is_empty(), "dir: {dir}");
+                }
+            }
+        }
+
+        #[test]
+        fn test_policy_applies_to_resolved_versions() {
+            let up_env = create_monorepo_environment();
+
+            let newest_env = create_test_dynamic_env();
+            let mut error_env = create_test_dynamic_env();
+            error_env.ambiguous_versions_policy = Some(UpAmbiguousVersionsPolicy::Error);
+
+            let (newest, newest_ambiguous) = newest_env.resolve_versions(&up_env, "");
+            assert_eq!(newest.len(), 1);
+            assert_eq!(newest[0].version, "1.22.0");
+            assert_eq!(newest_ambiguous.len(), 1);
+
+            let (error, error_ambiguous) = error_env.resolve_versions(&up_env, "");
+            assert!(error.is_empty());
+            assert_eq!(error_ambiguous.len(), 1);
+        }
+
+        #[test]
+        fn test_version_provides_binary() {
+            run_with_env(&[], || {
+                let a = create_scoped_up_version("tool", "ghrelease", "1.0.0", "services/a");
+                let b = create_scoped_up_version("tool", "ghrelease", "2.0.0", "services/b");
+
+                let bin_dir = github_release_tool_path("tool", "1.0.0").join("bin");
+                std::fs::create_dir_all(&bin_dir).expect("failed to create bin dir");
+                std::fs::write(bin_dir.join("tool"), "").expect("failed to create binary");
+
+                assert!(version_provides_binary(&a, "tool"));
+                assert!(!version_provides_binary(&a, "other"));
+                assert!(!version_provides_binary(&b, "tool"));
+            });
+        }
+    }
+
+    mod shell_session {
+        use super::*;
+
+        use crate::internal::config::parser::ShellSessionIdSource;
+        use crate::internal::env::workdir_or_init;
+        use crate::internal::testutils::run_with_env;
+
+        /// Runs the closure in a work directory with an up environment,
+        /// providing a function returning the id of the dynamic environment
+        /// for a given session id source and shell PPID
+        fn with_workdir_dynenv<F>(closure: F)
+        where
+            F: FnOnce(&dyn Fn(Option<ShellSessionIdSource>, &str) -> u64),
+        {
+            run_with_env(&[], || {
+                let project = PathBuf::from(std::env::var("HOME").unwrap()).join("project");
+                std::fs::create_dir_all(&project).expect("failed to create workdir");
+                let project = project.to_string_lossy().to_string();
+                workdir_or_init(&project).expect("failed to init workdir");
+
+                let up_env = create_test_environment_with_versions(vec![create_test_up_version(
+                    "go", "", "1.22.0", "bin", None,
+                )]);
+
+                let dynenv_id = |source: Option<ShellSessionIdSource>, ppid: &str| {
+                    let envs = [
+                        ("OMNI_SHELL_PPID", Some(ppid)),
+                        ("OMNI_SESSION_ID", Some("tmux-pane-1")),
+                    ];
+                    temp_env::with_vars(envs, || {
+                        let mut dynamic_env = create_test_dynamic_env()
+                            .with_path(Some(project.clone()))
+                            .with_environment(Some(&up_env));
+                        dynamic_env.shell_session = source.map(|id_source| ShellSessionConfig {
+                            id_source,
+                            ..Default::default()
+                        });
+
+                        let id = dynamic_env.id();
+                        assert_ne!(id, 0, "expected the dynamic environment to have an id");
+                        id
+                    })
+                };
+
+                closure(&dynenv_id);
+            });
+        }
+
+        #[test]
+        fn test_provided_session_id_is_stable_across_shells() {
+            with_workdir_dynenv(|dynenv_id| {
+                let source = Some(ShellSessionIdSource::Env);
+                assert_eq!(dynenv_id(source, "1234"), dynenv_id(source, "5678"));
+                assert_ne!(dynenv_id(source, "1234"), dynenv_id(None, "1234"));
+            });
+        }
+
+        #[test]
+        fn test_default_session_id_is_the_shell_ppid() {
+            with_workdir_dynenv(|dynenv_id| {
+                assert_ne!(dynenv_id(None, "1234"), dynenv_id(None, "5678"));
+                assert_eq!(dynenv_id(None, "1234"), dynenv_id(None, "1234"));
+                assert_eq!(
+                    dynenv_id(None, "1234"),
+                    dynenv_id(Some(ShellSessionIdSource::Ppid), "1234")
+                );
+            });
+        }
+    }
+
     mod apply_versions {
         use super::*;
 
@@ -92,11 +627,13 @@ mod dynamic_env {
                     name: "CUSTOM_VAR".to_string(),
                     operation: EnvOperationEnum::Set,
                     value: Some("custom_value".to_string()),
+                    command: None,
                 },
                 UpEnvVar {
                     name: "CUSTOM_PATH".to_string(),
                     operation: EnvOperationEnum::Prepend,
                     value: Some("/custom/path".to_string()),
+                    command: None,
                 },
             ];
 
@@ -258,6 +795,57 @@ mod dynamic_env {
                 .any(|p| p.value == "/custom/gem/path/bin"));
         }
 
+        fn gem_home_for_workdir(policy: UpDataPathsPolicy, wd_data_path: &str) -> String {
+            let data_path = version_data_path(
+                policy,
+                std::path::Path::new(wd_data_path),
+                "ruby",
+                "3.1.0",
+                "",
+            );
+            let versions = vec![create_test_up_version(
+                "ruby",
+                "",
+                "3.1.0",
+                "bin",
+                Some(data_path.to_string_lossy().to_string()),
+            )];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+            let env_data = envsetter.get_env_data();
+            env_data
+                .values
+                .get("GEM_HOME")
+                .unwrap()
+                .curr
+                .clone()
+                .unwrap()
+        }
+
+        #[test]
+        fn test_ruby_tool_shared_data_path() {
+            let gem_home1 = gem_home_for_workdir(UpDataPathsPolicy::Shared, "/data/wd/workdir1");
+            let gem_home2 = gem_home_for_workdir(UpDataPathsPolicy::Shared, "/data/wd/workdir2");
+
+            assert_eq!(gem_home1, gem_home2);
+            assert!(!gem_home1.starts_with("/data/wd/"));
+            assert!(gem_home1.ends_with("/shared/ruby/3.1.0"));
+        }
+
+        #[test]
+        fn test_ruby_tool_isolated_data_path() {
+            let gem_home1 = gem_home_for_workdir(UpDataPathsPolicy::Isolated, "/data/wd/workdir1");
+            let gem_home2 = gem_home_for_workdir(UpDataPathsPolicy::Isolated, "/data/wd/workdir2");
+
+            assert_ne!(gem_home1, gem_home2);
+            assert!(gem_home1.starts_with("/data/wd/workdir1/ruby/3.1.0/"));
+            assert!(gem_home2.starts_with("/data/wd/workdir2/ruby/3.1.0/"));
+        }
+
         #[test]
         fn test_rust_tool_setup() {
             std::env::set_var("RUSTUP_HOME", "invalid_value");
@@ -580,6 +1168,141 @@ mod dynamic_env {
             );
         }
 
+        #[test]
+        fn test_java_tool_setup() {
+            temp_env::with_vars(
+                [
+                    ("JAVA_HOME", Some("/old/jdk")),
+                    ("PATH", Some("/old/jdk/bin:/usr/bin")),
+                ],
+                || {
+                    let versions = vec![create_test_up_version("java", "", "21.0.2", "bin", None)];
+                    let up_env = create_test_environment_with_versions(versions);
+                    let mut dynamic_env = create_test_dynamic_env();
+                    let mut envsetter = DynamicEnvSetter::new();
+
+                    dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+                    assert!(dynamic_env.features.contains(&"java:21.0.2".to_string()));
+
+                    let java_home = mise_tool_path("java", "21.0.2");
+                    let data = envsetter.get_env_data();
+                    assert_eq!(data.env_get_var("JAVA_HOME"), Some(java_home.clone()));
+                    assert_eq!(
+                        data.env_get_var("PATH"),
+                        Some(format!("{java_home}/bin:/usr/bin"))
+                    );
+
+                    // Undoing the environment gives back the previous
+                    // JDK, and its directory in the PATH
+                    std::env::set_var("JAVA_HOME", data.env_get_var("JAVA_HOME").unwrap());
+                    std::env::set_var("PATH", data.env_get_var("PATH").unwrap());
+                    let mut data: DynamicEnvData =
+                        serde_json::from_str(&data.to_json()).expect("data should deserialize");
+                    data.prepare_undo();
+
+                    assert_eq!(data.env_get_var("JAVA_HOME"), Some("/old/jdk".to_string()));
+                    assert_eq!(
+                        data.env_get_var("PATH"),
+                        Some("/old/jdk/bin:/usr/bin".to_string())
+                    );
+                },
+            );
+        }
+
+        #[test]
+        fn test_java_tool_setup_with_custom_bin_path() {
+            temp_env::with_vars([("JAVA_HOME", None), ("PATH", Some("/usr/bin"))], || {
+                let versions = vec![create_test_up_version(
+                    "java",
+                    "",
+                    "21.0.2",
+                    "Contents/Home/bin",
+                    None,
+                )];
+                let up_env = create_test_environment_with_versions(versions);
+                let mut dynamic_env = create_test_dynamic_env();
+                let mut envsetter = DynamicEnvSetter::new();
+
+                dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+                let java_home = mise_tool_path("java", "21.0.2");
+                let data = envsetter.get_env_data();
+                assert_eq!(
+                    data.env_get_var("PATH"),
+                    Some(format!("{java_home}/Contents/Home/bin:/usr/bin"))
+                );
+                assert_eq!(
+                    data.env_get_var("JAVA_HOME"),
+                    Some(format!("{java_home}/Contents/Home"))
+                );
+            });
+        }
+
+        #[test]
+        fn test_bun_tool_setup() {
+            let versions = vec![create_test_up_version("bun", "", "1.1.0", "bin", None)];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+            assert!(dynamic_env.features.contains(&"bun:1.1.0".to_string()));
+
+            let env_data = envsetter.get_env_data();
+
+            assert!(!env_data.values.contains_key("BUN_INSTALL"));
+            assert!(!env_data.values.contains_key("BUN_INSTALL_CACHE_DIR"));
+
+            let path_additions = env_data.lists.get("PATH").unwrap();
+            assert!(path_additions
+                .iter()
+                .any(|p| p.value == format!("{}/bin", mise_tool_path("bun", "1.1.0"))));
+        }
+
+        #[test]
+        fn test_bun_tool_with_data_path() {
+            let versions = vec![create_test_up_version(
+                "bun",
+                "",
+                "1.1.0",
+                "bin",
+                Some("/custom/bun".to_string()),
+            )];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions(&up_env, &mut envsetter, "");
+
+            let env_data = envsetter.get_env_data();
+
+            assert_eq!(
+                env_data
+                    .values
+                    .get("BUN_INSTALL")
+                    .unwrap()
+                    .curr
+                    .as_ref()
+                    .unwrap(),
+                "/custom/bun"
+            );
+            assert_eq!(
+                env_data
+                    .values
+                    .get("BUN_INSTALL_CACHE_DIR")
+                    .unwrap()
+                    .curr
+                    .as_ref()
+                    .unwrap(),
+                "/custom/bun/install/cache"
+            );
+
+            let path_additions = env_data.lists.get("PATH").unwrap();
+            assert!(path_additions.iter().any(|p| p.value == "/custom/bun/bin"));
+        }
+
         #[test]
         fn test_generic_tool_setup() {
             let versions = vec![create_test_up_version(
@@ -798,4 +1521,582 @@ mod dynamic_env {
             std::env::remove_var("PATH");
         }
     }
+
+    mod apply_env_paths {
+        use super::*;
+
+        fn path_env_var(operation: EnvOperationEnum, value: &Path) -> UpEnvVar {
+            UpEnvVar {
+                name: "TEST_PATH_LIST".to_string(),
+                operation,
+                value: Some(value.to_string_lossy().to_string()),
+                command: None,
+            }
+        }
+
+        fn apply_env_vars(env_vars: Vec<UpEnvVar>) -> DynamicEnvData {
+            let up_env = UpEnvironment {
+                env_vars,
+                ..create_test_environment_with_versions(vec![])
+            };
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_env(&up_env, &mut envsetter);
+
+            envsetter.get_isolated_env_data()
+        }
+
+        fn list_values(data: &DynamicEnvData) -> Vec<String> {
+            data.lists
+                .get("TEST_PATH_LIST")
+                .map(|values| values.iter().map(|v| v.value.clone()).collect())
+                .unwrap_or_default()
+        }
+
+        #[test]
+        fn test_existing_directories_are_added() {
+            let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+            let first = tempdir.path().join("first");
+            let second = tempdir.path().join("second");
+            std::fs::create_dir_all(&first).expect("failed to create directory");
+            std::fs::create_dir_all(&second).expect("failed to create directory");
+
+            let data = apply_env_vars(vec![
+                path_env_var(EnvOperationEnum::PrependPath, &first),
+                path_env_var(EnvOperationEnum::AppendPath, &second),
+            ]);
+
+            assert_eq!(
+                data.env_get_var("TEST_PATH_LIST"),
+                Some(format!("{}:{}", first.display(), second.display()))
+            );
+        }
+
+        #[test]
+        fn test_missing_directory_is_skipped() {
+            let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+            let missing = tempdir.path().join("missing");
+
+            let data = apply_env_vars(vec![path_env_var(EnvOperationEnum::PrependPath, &missing)]);
+
+            assert!(list_values(&data).is_empty());
+            assert_eq!(data.env_get_var("TEST_PATH_LIST"), None);
+        }
+
+        #[test]
+        fn test_trailing_slash_is_not_duplicated() {
+            let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+            let dir = tempdir.path().join("bin");
+            std::fs::create_dir_all(&dir).expect("failed to create directory");
+
+            let data = apply_env_vars(vec![
+                path_env_var(EnvOperationEnum::Prepend, &dir.join("")),
+                path_env_var(EnvOperationEnum::PrependPath, &dir),
+            ]);
+
+            assert_eq!(list_values(&data).len(), 1);
+        }
+
+        #[test]
+        fn test_symlinked_directory_is_not_duplicated() {
+            let tempdir = tempfile::tempdir().expect("failed to create temp directory");
+            let dir = tempdir.path().join("bin");
+            let link = tempdir.path().join("link");
+            std::fs::create_dir_all(&dir).expect("failed to create directory");
+            std::os::unix::fs::symlink(&dir, &link).expect("failed to create symlink");
+
+            let data = apply_env_vars(vec![
+                path_env_var(EnvOperationEnum::Append, &dir),
+                path_env_var(EnvOperationEnum::AppendPath, &link),
+            ]);
+
+            assert_eq!(list_values(&data), vec![dir.to_string_lossy().to_string()]);
+        }
+    }
+
+    mod env_commands {
+        use super::*;
+
+        use crate::internal::env::workdir_or_init;
+        use crate::internal::testutils::run_with_env;
+
+        fn command_env_var(command: &str) -> UpEnvVar {
+            UpEnvVar {
+                name: "TEST_COMMAND_VAR".to_string(),
+                operation: EnvOperationEnum::Set,
+                value: None,
+                command: Some(command.to_string()),
+            }
+        }
+
+        fn apply_env_vars(
+            dynamic_env: &mut DynamicEnv,
+            env_vars: Vec<UpEnvVar>,
+        ) -> DynamicEnvSetter {
+            let up_env = UpEnvironment {
+                env_vars,
+                ..create_test_environment_with_versions(vec![])
+            };
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_env(&up_env, &mut envsetter);
+
+            envsetter
+        }
+
+        fn is_unset(envsetter: &DynamicEnvSetter) -> bool {
+            envsetter.operations.iter().any(|operation| {
+                matches!(operation, DynamicEnvOperation::UnsetValue(key) if key == "TEST_COMMAND_VAR")
+            })
+        }
+
+        #[test]
+        fn test_output_is_set_without_trailing_newlines() {
+            let mut dynamic_env = create_test_dynamic_env();
+            let envsetter = apply_env_vars(
+                &mut dynamic_env,
+                vec![command_env_var("printf 'a b\\n\\n'")],
+            );
+
+            let env_data = envsetter.get_isolated_env_data();
+            assert_eq!(
+                env_data.env.get("TEST_COMMAND_VAR"),
+                Some(&Some("a b".to_string()))
+            );
+            assert!(dynamic_env.failed_env_commands.is_empty());
+        }
+
+        #[test]
+        fn test_failing_command_unsets_the_variable() {
+            let mut dynamic_env = create_test_dynamic_env();
+            let envsetter = apply_env_vars(&mut dynamic_env, vec![command_env_var("exit 3")]);
+
+            assert!(is_unset(&envsetter));
+            assert_eq!(dynamic_env.failed_env_commands.len(), 1);
+            assert!(dynamic_env.failed_env_commands[0].contains("exited with code 3"));
+        }
+
+        #[test]
+        fn test_timed_out_command_unsets_the_variable() {
+            let mut dynamic_env = create_test_dynamic_env();
+            dynamic_env.env_command_timeout = Duration::from_millis(100);
+
+            let started = std::time::Instant::now();
+            let envsetter = apply_env_vars(&mut dynamic_env, vec![command_env_var("sleep 5")]);
+
+            assert!(started.elapsed() < Duration::from_secs(5));
+            assert!(is_unset(&envsetter));
+            assert_eq!(dynamic_env.failed_env_commands.len(), 1);
+            assert!(dynamic_env.failed_env_commands[0].contains("timed out"));
+        }
+
+        #[test]
+        fn test_id_depends_on_the_command_not_its_output() {
+            run_with_env(&[], || {
+                let project = PathBuf::from(std::env::var("HOME").unwrap()).join("project");
+                std::fs::create_dir_all(&project).expect("failed to create workdir");
+                let project = project.to_string_lossy().to_string();
+                workdir_or_init(&project).expect("failed to init workdir");
+
+                let dynenv_id = |command: &str| {
+                    let up_env = UpEnvironment {
+                        env_vars: vec![command_env_var(command)],
+                        ..create_test_environment_with_versions(vec![])
+                    };
+                    temp_env::with_var("OMNI_SHELL_PPID", Some("1234"), || {
+                        let dynamic_env = create_test_dynamic_env()
+                            .with_path(Some(project.clone()))
+                            .with_environment(Some(&up_env));
+
+                        let id = dynamic_env.id();
+                        assert_ne!(id, 0, "expected the dynamic environment to have an id");
+                        id
+                    })
+                };
+
+                // The output of the command changes each time it is run
+                let changing_output = "date +%s%N";
+                assert_eq!(dynenv_id(changing_output), dynenv_id(changing_output));
+                assert_ne!(dynenv_id(changing_output), dynenv_id("echo static"));
+            });
+        }
+    }
+
+    mod env_backends {
+        use super::*;
+        use std::collections::BTreeSet;
+
+        use crate::internal::cache::up_environments::UpVersionParams;
+        use crate::internal::config::up::utils::UpProgressHandler;
+        use crate::internal::config::up::UpOptions;
+
+        /// A backend recording the tool versions it was asked to apply
+        struct StubEnvBackend;
+
+        impl EnvBackend for StubEnvBackend {
+            fn names(&self) -> Vec<&'static str> {
+                vec!["stub-test"]
+            }
+
+            fn resolve_version(
+                &self,
+                tool: &str,
+                version: &str,
+                _options: &UpOptions,
+                _progress_handler: &UpProgressHandler,
+            ) -> Result<String, UpError> {
+                match version {
+                    "latest" => Ok("2.0.0".to_string()),
+                    _ => Err(UpError::Exec(format!("no version {version} for {tool}"))),
+                }
+            }
+
+            fn install_version(
+                &self,
+                tool: &str,
+                version: &str,
+                options: &UpOptions,
+                environment: &mut UpEnvironment,
+                progress_handler: &UpProgressHandler,
+            ) -> Result<String, UpError> {
+                let version = self.resolve_version(tool, version, options, progress_handler)?;
+                environment.add_version(UpVersionParams {
+                    backend: "stub-test",
+                    tool,
+                    plugin_name: tool,
+                    normalized_name: tool,
+                    version: &version,
+                    bin_path: "",
+                    dirs: BTreeSet::new(),
+                    env_vars: Vec::new(),
+                });
+                Ok(version)
+            }
+
+            fn is_installed(&self, toolversion: &UpVersion) -> bool {
+                toolversion.tool != "uninstalled"
+            }
+
+            fn apply_version(
+                &self,
+                toolversion: &UpVersion,
+                dynenv: &mut DynamicEnv,
+                envsetter: &mut DynamicEnvSetter,
+            ) -> bool {
+                dynenv.features.push(format!("stub:{}", toolversion.tool));
+                envsetter.set_value("STUB_TOOL_VERSION", &toolversion.version);
+                toolversion.tool != "rejected"
+            }
+        }
+
+        /// Returns a registry local to the test, holding the builtin
+        /// backends and the stub backend
+        fn stub_registry() -> EnvBackendRegistry {
+            let mut registry = EnvBackendRegistry::builtin();
+            registry.register(Arc::new(StubEnvBackend));
+            registry
+        }
+
+        #[test]
+        fn test_builtin_backends_are_registered() {
+            for name in ["", "default", "ghrelease", "cargo-install", "go-install"] {
+                assert!(env_backend(name).is_some(), "missing backend {name:?}");
+            }
+            assert!(env_backend("unknown_backend").is_none());
+        }
+
+        #[test]
+        fn test_builtin_backends_are_listed_in_order() {
+            let names = env_backends()
+                .iter()
+                .map(|backend| backend.names())
+                .take(5)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                names,
+                vec![
+                    vec!["", "default"],
+                    vec!["ghrelease"],
+                    vec!["go-install"],
+                    vec!["cargo-install"],
+                    vec!["pipx"],
+                ]
+            );
+        }
+
+        #[test]
+        fn test_registering_again_replaces_backend() {
+            let mut registry = stub_registry();
+            registry.register(Arc::new(StubEnvBackend));
+
+            let stubs = registry
+                .backends()
+                .iter()
+                .filter(|backend| backend.names() == vec!["stub-test"])
+                .count();
+            assert_eq!(stubs, 1);
+        }
+
+        #[test]
+        fn test_uninstalled_version_is_missing() {
+            let registry = stub_registry();
+
+            let versions = vec![
+                create_test_up_version("uninstalled", "stub-test", "1.0.0", "", None),
+                create_test_up_version("tool", "stub-test", "1.2.3", "", None),
+            ];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            dynamic_env.check_tool_paths = true;
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions_with_backends(&registry, &up_env, &mut envsetter, "");
+
+            assert_eq!(dynamic_env.missing_tools, vec!["uninstalled:1.0.0"]);
+            assert_eq!(dynamic_env.features, vec!["stub:tool"]);
+        }
+
+        #[test]
+        fn test_registered_backend_applies_version() {
+            let registry = stub_registry();
+
+            let env_vars = vec![UpEnvVar {
+                name: "STUB_EXTRA".to_string(),
+                operation: EnvOperationEnum::Set,
+                value: Some("extra".to_string()),
+                command: None,
+            }];
+            let versions = vec![
+                create_test_up_version_with_env("tool", "stub-test", "1.2.3", "", None, env_vars),
+                create_test_up_version("other", "unknown_backend", "1.0.0", "", None),
+            ];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions_with_backends(&registry, &up_env, &mut envsetter, "");
+
+            let env_data = envsetter.get_env_data();
+            assert_eq!(
+                env_data
+                    .values
+                    .get("STUB_TOOL_VERSION")
+                    .unwrap()
+                    .curr
+                    .as_deref(),
+                Some("1.2.3")
+            );
+            assert_eq!(
+                env_data.values.get("STUB_EXTRA").unwrap().curr.as_deref(),
+                Some("extra")
+            );
+            assert_eq!(dynamic_env.features, vec!["stub:tool".to_string()]);
+        }
+
+        #[test]
+        fn test_rejected_version_skips_env_vars() {
+            let registry = stub_registry();
+
+            let env_vars = vec![UpEnvVar {
+                name: "STUB_REJECTED".to_string(),
+                operation: EnvOperationEnum::Set,
+                value: Some("value".to_string()),
+                command: None,
+            }];
+            let versions = vec![create_test_up_version_with_env(
+                "rejected",
+                "stub-test",
+                "1.0.0",
+                "",
+                None,
+                env_vars,
+            )];
+            let up_env = create_test_environment_with_versions(versions);
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions_with_backends(&registry, &up_env, &mut envsetter, "");
+
+            let env_data = envsetter.get_env_data();
+            assert!(!env_data.values.contains_key("STUB_REJECTED"));
+        }
+
+        #[test]
+        fn test_registry_starts_empty() {
+            let registry = EnvBackendRegistry::default();
+
+            assert!(registry.get("stub-test").is_none());
+            assert!(registry.backends().is_empty());
+        }
+
+        #[test]
+        fn test_registered_backend_resolves_version() {
+            let backend = stub_registry()
+                .get("stub-test")
+                .expect("missing stub backend");
+            let progress_handler = UpProgressHandler::new(None);
+
+            let resolved =
+                backend.resolve_version("tool", "latest", &UpOptions::new(), &progress_handler);
+            assert_eq!(resolved.unwrap(), "2.0.0");
+
+            let resolved =
+                backend.resolve_version("tool", "9.9.9", &UpOptions::new(), &progress_handler);
+            assert!(resolved.is_err());
+        }
+
+        #[test]
+        fn test_installed_version_is_applied() {
+            let registry = stub_registry();
+            let backend = registry.get("stub-test").expect("missing stub backend");
+            let progress_handler = UpProgressHandler::new(None);
+            let mut up_env = create_test_environment_with_versions(Vec::new());
+
+            let installed = backend
+                .install_version(
+                    "tool",
+                    "latest",
+                    &UpOptions::new(),
+                    &mut up_env,
+                    &progress_handler,
+                )
+                .expect("failed to install the tool");
+            assert_eq!(installed, "2.0.0");
+
+            let mut dynamic_env = create_test_dynamic_env();
+            let mut envsetter = DynamicEnvSetter::new();
+
+            dynamic_env.apply_versions_with_backends(&registry, &up_env, &mut envsetter, "");
+
+            let env_data = envsetter.get_env_data();
+            assert_eq!(
+                env_data
+                    .values
+                    .get("STUB_TOOL_VERSION")
+                    .unwrap()
+                    .curr
+                    .as_deref(),
+                Some("2.0.0")
+            );
+            assert_eq!(dynamic_env.features, vec!["stub:tool".to_string()]);
+        }
+    }
+}
+
+mod update_message {
+    use super::*;
+
+    #[test]
+    fn test_colored_when_enabled() {
+        assert_eq!(
+            update_message(&ColorCapability::new(true), "dynamic environment enabled"),
+            "\x1B[96momni:\x1B[39m dynamic environment enabled",
+        );
+    }
+
+    #[test]
+    fn test_plain_when_disabled() {
+        let status = format!("dynamic environment {}", "enabled".force_light_green());
+        assert_eq!(
+            update_message(&ColorCapability::new(false), &status),
+            "omni: dynamic environment enabled",
+        );
+    }
+}
+
+mod powershell_export {
+    use super::*;
+    use crate::internal::testutils::run_with_env;
+
+    #[test]
+    fn test_list_separator() {
+        let expected = if cfg!(windows) { ';' } else { ':' };
+        assert_eq!(DynamicEnvExportMode::PowerShell.list_separator(), expected);
+        assert_eq!(DynamicEnvExportMode::Posix.list_separator(), ':');
+        assert_eq!(DynamicEnvExportMode::Fish.list_separator(), ':');
+        assert_eq!(DynamicEnvExportMode::Env.list_separator(), ':');
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(
+            powershell_quote("C:\\Program Files"),
+            "\"C:\\Program Files\""
+        );
+        assert_eq!(powershell_quote(""), "\"\"");
+        assert_eq!(
+            powershell_quote("say \"hi\" to $USER`s"),
+            "\"say `\"hi`\" to `$USER``s\""
+        );
+        assert_eq!(
+            powershell_quote("“quoted”\nnext\tline"),
+            "\"`“quoted`”`nnext`tline\""
+        );
+    }
+
+    #[test]
+    fn test_quote_dynenv_data() {
+        let mut data = DynamicEnvData::new();
+        data.set_value("TEST_PS_QUOTE_VALUE", "value");
+
+        assert_eq!(
+            powershell_quote(&format!("0000000000000001;{}", data.to_json())),
+            "\"0000000000000001;{`\"v`\":{`\"TEST_PS_QUOTE_VALUE`\":{`\"c`\":`\"value`\"}}}\""
+        );
+    }
+
+    #[test]
+    fn test_prepend_to_path_uses_platform_separator() {
+        let sep = if cfg!(windows) { ";" } else { ":" };
+        let original = ["/usr/bin", "/bin"].join(sep);
+
+        run_with_env(&[("PATH".to_string(), Some(original))], || {
+            let mut envsetter = DynamicEnvSetter::new()
+                .with_list_separator(DynamicEnvExportMode::PowerShell.list_separator());
+            envsetter.prepend_to_list("PATH", "/x/bin");
+
+            let data = envsetter.get_env_data();
+            assert_eq!(
+                data.env_get_var("PATH"),
+                Some(["/x/bin", "/usr/bin", "/bin"].join(sep))
+            );
+        });
+    }
+
+    #[test]
+    fn test_list_operations_round_trip() {
+        let key = "TEST_PS_PATH_LIST";
+
+        run_with_env(&[(key.to_string(), None)], || {
+            let sep = DynamicEnvExportMode::PowerShell
+                .list_separator()
+                .to_string();
+            let original = ["/usr/local/bin", "/opt/tools/bin", "/usr/bin"].join(&sep);
+            std::env::set_var(key, &original);
+
+            let mut envsetter = DynamicEnvSetter::new()
+                .with_list_separator(DynamicEnvExportMode::PowerShell.list_separator());
+            envsetter.prepend_to_list(key, "/omni/shims");
+            envsetter.append_to_list(key, "/srv/tools");
+            envsetter.remove_from_list(key, "/opt/tools/bin");
+
+            let data = envsetter.get_env_data();
+            let applied = data.env_get_var(key).expect("list should be set");
+            assert_eq!(
+                applied,
+                ["/omni/shims", "/usr/local/bin", "/usr/bin", "/srv/tools"].join(&sep)
+            );
+
+            // The shell now has the applied value, and gives back the
+            // serialized data through the dynamic environment variable
+            std::env::set_var(key, &applied);
+            let mut data: DynamicEnvData =
+                serde_json::from_str(&data.to_json()).expect("data should deserialize");
+            data = data.with_list_separator(DynamicEnvExportMode::PowerShell.list_separator());
+            data.prepare_undo();
+
+            assert_eq!(data.env_get_var(key), Some(original));
+        });
+    }
 }
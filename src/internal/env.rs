@@ -3,7 +3,9 @@ use std::collections::HashSet;
 use std::fs::OpenOptions;
 use std::io::IsTerminal;
 use std::io::Write;
+use std::os::unix::fs::MetadataExt;
 use std::panic::catch_unwind;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::exit;
 use std::sync::Mutex;
@@ -18,9 +20,12 @@ use lazy_static::lazy_static;
 use once_cell::sync::OnceCell;
 use time::OffsetDateTime;
 
+use crate::internal::commands::utils::canonicalize_path;
 use crate::internal::config::global_config;
+use crate::internal::config::parser::canonicalize_policy;
 use crate::internal::config::parser::PathEntryConfig;
 use crate::internal::config::up::utils::force_remove_dir_all;
+use crate::internal::config::up::utils::SyncUpdateInit;
 use crate::internal::config::up::utils::SyncUpdateListener;
 use crate::internal::config::OrgConfig;
 use crate::internal::dynenv::DynamicEnvExportMode;
@@ -29,6 +34,8 @@ use crate::internal::git::id_from_git_url;
 use crate::internal::git::safe_git_url_parse;
 use crate::internal::user_interface::StringColor;
 use crate::internal::utils::base62_encode;
+use crate::internal::utils::create_dir_all;
+use crate::internal::utils::PathKind;
 use crate::omni_error;
 use crate::omni_info;
 use crate::omni_warning;
@@ -132,8 +139,7 @@ pub fn tmpdir_cleanup_prefix(prefix: &str) -> String {
 
 pub fn git_env<T: AsRef<str>>(path: T) -> GitRepoEnv {
     let path: &str = path.as_ref();
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -143,8 +149,7 @@ pub fn git_env<T: AsRef<str>>(path: T) -> GitRepoEnv {
 
 pub fn git_env_flush_cache<T: AsRef<str>>(path: T) {
     let path: &str = path.as_ref();
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -159,8 +164,7 @@ pub fn git_env_fresh<T: AsRef<str>>(path: T) -> GitRepoEnv {
 
 pub fn workdir<T: AsRef<str>>(path: T) -> WorkDirEnv {
     let path: &str = path.as_ref();
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -170,8 +174,7 @@ pub fn workdir<T: AsRef<str>>(path: T) -> WorkDirEnv {
 
 pub fn workdir_flush_cache<T: AsRef<str>>(path: T) {
     let path: &str = path.as_ref();
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -182,8 +185,7 @@ pub fn workdir_flush_cache<T: AsRef<str>>(path: T) {
 
 pub fn workdir_or_init<T: AsRef<str>>(path: T) -> Result<WorkDirEnv, String> {
     let path: &str = path.as_ref();
-    let path = std::fs::canonicalize(path)
-        .unwrap_or(path.to_owned().into())
+    let path = canonicalize_path(path, canonicalize_policy())
         .to_str()
         .unwrap()
         .to_owned();
@@ -249,8 +251,10 @@ pub fn init_workdir<T: AsRef<str>>(
     preferred_name: Option<&str>,
 ) -> Result<WorkDirEnv, String> {
     let path_str = path.as_ref();
-    let canonical = std::fs::canonicalize(path_str).unwrap_or_else(|_| PathBuf::from(path_str));
-    let path = canonical.to_str().unwrap().to_owned();
+    let path = canonicalize_path(path_str, canonicalize_policy())
+        .to_str()
+        .unwrap()
+        .to_owned();
 
     let workdir_path = PathBuf::from(&path);
     let id_path = workdir_path.join(".omni/id");
@@ -297,6 +301,95 @@ pub fn init_workdir<T: AsRef<str>>(
     Ok(workdir(&path))
 }
 
+/// The kind of container omni is running in, if any
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerContext {
+    None,
+    Container,
+    Devcontainer,
+}
+
+impl ContainerContext {
+    /// Detects the container context from the `OMNI_CONTAINER` override,
+    /// or from the environment markers and files under `root` that are
+    /// left by the most common container runtimes
+    fn detect(root: &Path) -> Self {
+        match std::env::var("OMNI_CONTAINER") {
+            Ok(value) if !value.is_empty() => {
+                return match value.to_lowercase().as_str() {
+                    "none" | "false" | "no" | "0" => Self::None,
+                    "devcontainer" => Self::Devcontainer,
+                    _ => Self::Container,
+                };
+            }
+            _ => {}
+        }
+
+        let env_is_set = |var: &str| std::env::var_os(var).is_some_and(|value| !value.is_empty());
+
+        if ["REMOTE_CONTAINERS", "CODESPACES", "DEVCONTAINER"]
+            .iter()
+            .any(|var| env_is_set(var))
+        {
+            return Self::Devcontainer;
+        }
+
+        if env_is_set("container")
+            || root.join(".dockerenv").exists()
+            || root.join("run/.containerenv").exists()
+        {
+            return Self::Container;
+        }
+
+        match std::fs::read_to_string(root.join("proc/1/cgroup")) {
+            Ok(cgroup)
+                if ["docker", "containerd", "kubepods", "libpod", "lxc"]
+                    .iter()
+                    .any(|marker| cgroup.contains(marker)) =>
+            {
+                Self::Container
+            }
+            _ => Self::None,
+        }
+    }
+
+    /// The name of the container context, or `None` when not
+    /// running in a container
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Container => Some("container"),
+            Self::Devcontainer => Some("devcontainer"),
+        }
+    }
+
+    /// The name of the directory used by default for omni's data,
+    /// state and cache under the given XDG directory; containers use
+    /// their own directory so that a home directory shared with the
+    /// host does not mix the tools installed for different systems.
+    /// When the container was detected rather than set through
+    /// `OMNI_CONTAINER`, an existing `omni` directory keeps being
+    /// used, so that existing installations are not left behind
+    fn omni_dirname(&self, xdg_home: &str) -> &'static str {
+        if *self == Self::None {
+            return "omni";
+        }
+
+        let explicit = std::env::var("OMNI_CONTAINER").is_ok_and(|value| !value.is_empty());
+        let xdg_home = Path::new(xdg_home);
+        if !explicit && xdg_home.join("omni").is_dir() && !xdg_home.join("omni-container").is_dir()
+        {
+            return "omni";
+        }
+
+        "omni-container"
+    }
+}
+
+fn compute_container_context() -> ContainerContext {
+    ContainerContext::detect(Path::new("/"))
+}
+
 fn compute_user_home() -> String {
     std::env::var("HOME").expect("Failed to determine user's home directory")
 }
@@ -342,6 +435,10 @@ fn compute_xdg_data_home() -> String {
 }
 
 fn compute_data_home() -> String {
+    compute_data_home_in(container_context())
+}
+
+fn compute_data_home_in(context: ContainerContext) -> String {
     match std::env::var("OMNI_DATA_HOME") {
         Ok(data_home)
             if !data_home.is_empty()
@@ -354,7 +451,8 @@ fn compute_data_home() -> String {
             }
         }
         _ => {
-            format!("{}/omni", xdg_data_home())
+            let xdg_data_home = xdg_data_home();
+            format!("{}/{}", xdg_data_home, context.omni_dirname(&xdg_data_home))
         }
     }
 }
@@ -375,6 +473,10 @@ fn compute_xdg_state_home() -> String {
 }
 
 fn compute_state_home() -> String {
+    compute_state_home_in(container_context())
+}
+
+fn compute_state_home_in(context: ContainerContext) -> String {
     match std::env::var("OMNI_STATE_HOME") {
         Ok(state_home)
             if !state_home.is_empty()
@@ -387,7 +489,12 @@ fn compute_state_home() -> String {
             }
         }
         _ => {
-            format!("{}/omni", xdg_state_home())
+            let xdg_state_home = xdg_state_home();
+            format!(
+                "{}/{}",
+                xdg_state_home,
+                context.omni_dirname(&xdg_state_home)
+            )
         }
     }
 }
@@ -404,6 +511,10 @@ fn compute_xdg_cache_home() -> String {
 }
 
 fn compute_cache_home() -> String {
+    compute_cache_home_in(container_context())
+}
+
+fn compute_cache_home_in(context: ContainerContext) -> String {
     match std::env::var("OMNI_CACHE_HOME") {
         Ok(cache_home)
             if !(cache_home.is_empty()
@@ -416,7 +527,12 @@ fn compute_cache_home() -> String {
             }
         }
         _ => {
-            format!("{}/omni", xdg_cache_home())
+            let xdg_cache_home = xdg_cache_home();
+            format!(
+                "{}/{}",
+                xdg_cache_home,
+                context.omni_dirname(&xdg_cache_home)
+            )
         }
     }
 }
@@ -482,6 +598,10 @@ fn compute_omni_tmpdir() -> String {
 
 cfg_if::cfg_if! {
     if #[cfg(test)] {
+        pub fn container_context() -> ContainerContext {
+            compute_container_context()
+        }
+
         pub fn user_home() -> String {
             compute_user_home()
         }
@@ -543,6 +663,9 @@ cfg_if::cfg_if! {
         }
     } else {
         lazy_static! {
+            #[derive(Debug)]
+            static ref CONTAINER_CONTEXT: ContainerContext = compute_container_context();
+
             #[derive(Debug)]
             static ref HOME: String = compute_user_home();
 
@@ -589,6 +712,10 @@ cfg_if::cfg_if! {
             static ref OMNI_TMPDIR: String = compute_omni_tmpdir();
         }
 
+        pub fn container_context() -> ContainerContext {
+            *CONTAINER_CONTEXT
+        }
+
         pub fn user_home() -> String {
             (*HOME).to_string()
         }
@@ -942,7 +1069,7 @@ impl WorkDirEnv {
         if git.in_repo() {
             workdir_env.in_workdir = true;
             workdir_env.in_git = true;
-            workdir_env.root = git.root().map(|s| s.to_string());
+            workdir_env.root = git.root().map(|root| Self::root_reached_from(path, root));
         } else {
             // Start from `path` and go up until finding a `.omni/id` file
             let mut path = PathBuf::from(path);
@@ -964,6 +1091,29 @@ impl WorkDirEnv {
         workdir_env
     }
 
+    /// Returns the root of the repository as reached from the given path;
+    /// the root provided by git is physical, so when the path was not
+    /// resolved, this looks for the parent of the path that is the root
+    /// once resolved, so that the root goes through the same symlinks
+    fn root_reached_from(path: &str, root: &str) -> String {
+        let path = Path::new(path);
+        if path.starts_with(root) {
+            return root.to_string();
+        }
+
+        let physical_root = match std::fs::canonicalize(root) {
+            Ok(physical_root) => physical_root,
+            Err(_) => return root.to_string(),
+        };
+
+        path.ancestors()
+            .find(|ancestor| {
+                std::fs::canonicalize(ancestor).is_ok_and(|ancestor| ancestor == physical_root)
+            })
+            .map(|ancestor| ancestor.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.to_string())
+    }
+
     pub fn in_workdir(&self) -> bool {
         self.in_workdir
     }
@@ -1000,17 +1150,16 @@ impl WorkDirEnv {
 
     pub fn reldir(&self, path: &str) -> Option<String> {
         if let Some(root) = &self.root {
-            if let Ok(path) = std::fs::canonicalize(path) {
-                if let Ok(path) = path.strip_prefix(root) {
-                    let mut path = path.to_str().unwrap().to_string();
-                    while path.starts_with('/') {
-                        path = path[1..].to_string();
-                    }
-                    while path.ends_with('/') {
-                        path = path[..path.len() - 1].to_string();
-                    }
-                    return Some(path);
+            let path = canonicalize_path(path, canonicalize_policy());
+            if let Ok(path) = path.strip_prefix(root) {
+                let mut path = path.to_str().unwrap().to_string();
+                while path.starts_with('/') {
+                    path = path[1..].to_string();
+                }
+                while path.ends_with('/') {
+                    path = path[..path.len() - 1].to_string();
                 }
+                return Some(path);
             }
         }
         None
@@ -1168,12 +1317,13 @@ impl WorkDirEnv {
     pub fn lock_update(
         self,
         listener: &mut SyncUpdateListener,
+        wait: bool,
     ) -> Result<Option<std::fs::File>, SyncUpdateError> {
         // Make sure the sync directory for up operations exists
         let main_sync_dir_path = PathBuf::from(omni_tmpdir());
         let sync_dir_path = main_sync_dir_path.join("up");
         if !sync_dir_path.exists() {
-            std::fs::create_dir_all(&sync_dir_path)?;
+            create_dir_all(&sync_dir_path, PathKind::Private)?;
         }
 
         // Try to get the id and root, or raise error
@@ -1202,82 +1352,163 @@ impl WorkDirEnv {
         // The sync path is that id, in the up sync directory
         let sync_path = sync_dir_path.join(workdir_unique_id);
 
-        // Open the sync file in read/write/create mode, so it gets created if it does not exist
-        let mut file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(false)
-            .open(sync_path)?;
+        lock_sync_file(&sync_path, listener, wait)
+    }
+}
 
-        // Try to acquire an exclusive lock on the file
-        if let Ok(_file_lock) = file.try_lock_exclusive() {
-            // Now that we have the lock, truncate the file contents
-            file.set_len(0)?;
-            file.flush()?;
+/// Opens the sync file in read/write/create mode, so it gets created if
+/// it does not exist
+fn open_sync_file(sync_path: &Path) -> Result<std::fs::File, SyncUpdateError> {
+    Ok(OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(sync_path)?)
+}
 
-            return Ok(Some(file));
+/// Tries to acquire an exclusive lock on the sync file, and truncates
+/// its contents if successful. Since stale sync files are replaced when
+/// taken over, this also makes sure that the opened file is still the
+/// one at the sync path, or the lock would not exclude anyone.
+fn try_lock_sync_file(file: &std::fs::File, sync_path: &Path) -> Result<bool, SyncUpdateError> {
+    if !matches!(file.try_lock_exclusive(), Ok(true)) {
+        return Ok(false);
+    }
+
+    if !is_current_sync_file(file, sync_path) {
+        FileExt::unlock(file)?;
+        return Ok(false);
+    }
+
+    // Now that we have the lock, truncate the file contents
+    let mut file = file;
+    file.set_len(0)?;
+    file.flush()?;
+
+    Ok(true)
+}
+
+/// Returns whether the opened file is still the one at the sync path
+fn is_current_sync_file(file: &std::fs::File, sync_path: &Path) -> bool {
+    match (file.metadata(), std::fs::metadata(sync_path)) {
+        (Ok(opened), Ok(current)) => opened.dev() == current.dev() && opened.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// Tries to acquire the lock on the sync file until the timeout expires,
+/// reopening the file at each attempt in case it got replaced
+fn wait_lock_sync_file(
+    sync_path: &Path,
+    timeout: StdDuration,
+) -> Result<std::fs::File, SyncUpdateError> {
+    let start_time = StdInstant::now();
+
+    loop {
+        let file = open_sync_file(sync_path)?;
+        if try_lock_sync_file(&file, sync_path)? {
+            return Ok(file);
         }
 
-        // If we can't acquire the lock, it means that another process is updating the workdir
-        // at the same time, so we will wait on the update to finish while streaming the
-        // file contents, and return None to indicate that the update was already done
-        match listener.follow(&file) {
-            Ok(true) => Ok(None), // The update was done
-            Ok(false) => {
-                // We killed the running process, let's try and lock the file
-                // until we timeout, and error out if we can't
-                let timeout_duration =
-                    StdDuration::from_secs(global_config().up_command.attach_lock_timeout);
-                let start_time = StdInstant::now();
-
-                loop {
-                    match file.try_lock_exclusive() {
-                        Ok(_file_lock) => {
-                            // Now that we have the lock, truncate the file contents
-                            file.set_len(0)?;
-                            file.flush()?;
-
-                            return Ok(Some(file));
-                        }
-                        Err(_) => {
-                            if start_time.elapsed() > timeout_duration {
-                                return Err(SyncUpdateError::Timeout);
-                            }
+        if start_time.elapsed() > timeout {
+            return Err(SyncUpdateError::Timeout);
+        }
 
-                            std::thread::sleep(StdDuration::from_millis(100));
-                        }
-                    }
-                }
-            }
-            Err(SyncUpdateError::MismatchedInit { actual, .. }) => {
-                // If the init operation is mismatched, we need to wait for the lock to be released
-                omni_info!(format!(
-                    "waiting for running {} operation to finish",
-                    actual.name().light_yellow()
-                ));
+        std::thread::sleep(StdDuration::from_millis(100));
+    }
+}
 
-                // Grab the lock in a blocking way
-                file.lock_exclusive()?;
+/// Locks the sync file at the given path for an up or down operation.
+///
+/// If another operation is holding the lock, either attaches to it if it
+/// is the same operation, or waits for it to finish, unless `wait` is
+/// false, in which case an `AlreadyRunning` error is returned. If the
+/// process that started the other operation is not running anymore, the
+/// lock is considered stale and is taken over.
+///
+/// Returns the locked file, or `None` if the update was done by the
+/// operation we attached to.
+pub(crate) fn lock_sync_file(
+    sync_path: &Path,
+    listener: &mut SyncUpdateListener,
+    wait: bool,
+) -> Result<Option<std::fs::File>, SyncUpdateError> {
+    let mut file = open_sync_file(sync_path)?;
+    if try_lock_sync_file(&file, sync_path)? {
+        return Ok(Some(file));
+    }
+
+    // If we can't acquire the lock, it means that another process is updating
+    // the workdir at the same time; the lock can still be held after that process
+    // died if one of its children inherited the file descriptor, in which case
+    // we replace the sync file with a new one that we can lock
+    let running = SyncUpdateInit::from_sync_file(sync_path);
+    if let Some(running) = running.as_ref().filter(|running| running.is_stale()) {
+        // Processes taking over the same stale lock do it one at a time, and
+        // only replace the sync file if it is still the stale one; otherwise
+        // another process already took over, and its sync file is the one
+        // we would remove while it is holding the lock on it
+        let takeover = open_sync_file(&sync_path.with_extension("takeover"))?;
+        takeover.lock_exclusive()?;
+
+        if is_current_sync_file(&file, sync_path) {
+            omni_warning!(format!(
+                "omni {} (pid {}) is not running anymore; taking over its stale lock",
+                running.name(),
+                running.pid().unwrap_or_default(),
+            ));
 
-                // Now that we have the lock, truncate the file contents
-                file.set_len(0)?;
-                file.flush()?;
+            std::fs::remove_file(sync_path)?;
+        }
 
-                // Return the file
-                Ok(Some(file))
-            }
-            Err(err) => Err(err),
+        file = open_sync_file(sync_path)?;
+        let locked = try_lock_sync_file(&file, sync_path)?;
+        FileExt::unlock(&takeover)?;
+
+        if locked {
+            return Ok(Some(file));
+        }
+    }
+
+    let description = match running {
+        Some(running) => running.running_description(),
+        None => "another omni operation is running".to_string(),
+    };
+
+    if !wait {
+        return Err(SyncUpdateError::AlreadyRunning(description));
+    }
+
+    // Wait on the update to finish while streaming the file contents,
+    // and return None to indicate that the update was already done
+    match listener.follow(&file) {
+        Ok(true) => Ok(None), // The update was done
+        Ok(false) => {
+            // We killed the running process, let's try and lock the file
+            // until we timeout, and error out if we can't
+            let timeout = StdDuration::from_secs(global_config().up_command.attach_lock_timeout);
+            Ok(Some(wait_lock_sync_file(sync_path, timeout)?))
         }
+        Err(SyncUpdateError::MismatchedInit { .. }) => {
+            // If the init operation is mismatched, we need to wait for the lock to be released
+            omni_info!(format!("{description}, waiting for it to finish"));
+
+            let timeout = StdDuration::from_secs(global_config().up_command.wait_timeout);
+            Ok(Some(wait_lock_sync_file(sync_path, timeout)?))
+        }
+        Err(err) => Err(err),
     }
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Shell {
     Bash,
     Zsh,
     Fish,
     Posix,
+    PowerShell,
     Unknown(String),
 }
 
@@ -1293,7 +1524,13 @@ impl Shell {
     }
 
     pub fn all() -> Vec<Self> {
-        vec![Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Posix]
+        vec![
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::Posix,
+            Shell::PowerShell,
+        ]
     }
 
     pub fn from_env() -> Self {
@@ -1307,6 +1544,7 @@ impl Shell {
             "zsh" => Shell::Zsh,
             "fish" => Shell::Fish,
             "posix" => Shell::Posix,
+            "powershell" | "pwsh" => Shell::PowerShell,
             _ => Shell::Unknown(shell.to_string()),
         }
     }
@@ -1317,6 +1555,7 @@ impl Shell {
             Shell::Zsh => "zsh",
             Shell::Fish => "fish",
             Shell::Posix => "posix",
+            Shell::PowerShell => "powershell",
             Shell::Unknown(shell) => shell,
         }
     }
@@ -1325,6 +1564,7 @@ impl Shell {
         match self {
             Shell::Bash | Shell::Zsh | Shell::Posix => Some(DynamicEnvExportMode::Posix),
             Shell::Fish => Some(DynamicEnvExportMode::Fish),
+            Shell::PowerShell => Some(DynamicEnvExportMode::PowerShell),
             Shell::Unknown(_) => None,
         }
     }
@@ -1338,7 +1578,7 @@ impl Shell {
             Shell::Bash => PathBuf::from(user_home()).join(".bashrc"),
             Shell::Zsh => PathBuf::from(user_home()).join(".zshrc"),
             Shell::Fish => PathBuf::from(xdg_config_home()).join("fish/omni.fish"),
-            Shell::Posix => PathBuf::from("/dev/null"),
+            Shell::Posix | Shell::PowerShell => PathBuf::from("/dev/null"),
             Shell::Unknown(_) => PathBuf::from("/dev/null"),
         }
     }
@@ -1348,7 +1588,7 @@ impl Shell {
             Shell::Bash => "eval \"$(omni hook init bash)\"".to_string(),
             Shell::Zsh => "eval \"$(omni hook init zsh)\"".to_string(),
             Shell::Fish => "omni hook init fish | source".to_string(),
-            Shell::Posix => String::new(),
+            Shell::Posix | Shell::PowerShell => String::new(),
             Shell::Unknown(_) => String::new(),
         }
     }
@@ -1371,3 +1611,7 @@ fn determine_shell() -> String {
 
     "bash".to_string()
 }
+
+#[cfg(test)]
+#[path = "env_test.rs"]
+mod tests;
@@ -0,0 +1,531 @@
+use super::*;
+
+use std::os::unix::fs::symlink;
+
+use crate::internal::config::flush_config;
+use crate::internal::config::ConfigLoader;
+use crate::internal::config::ConfigValue;
+use crate::internal::testutils::run_with_env;
+
+/// A repository reachable both through its physical path and through
+/// a symlinked parent directory
+struct SymlinkedRepo {
+    _tempdir: tempfile::TempDir,
+    physical: PathBuf,
+    logical: PathBuf,
+}
+
+impl SymlinkedRepo {
+    fn new() -> Self {
+        let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+        let base = std::fs::canonicalize(tempdir.path()).expect("failed to canonicalize");
+
+        let physical = base.join("volume/work/repo");
+        std::fs::create_dir_all(physical.join("sub")).expect("failed to create repo");
+        let repo = Repository::init(&physical).expect("failed to init repo");
+        repo.remote("origin", "https://github.com/xaf/omni.git")
+            .expect("failed to add origin");
+
+        symlink(base.join("volume/work"), base.join("work")).expect("failed to create symlink");
+        let logical = base.join("work/repo");
+
+        Self {
+            _tempdir: tempdir,
+            physical,
+            logical,
+        }
+    }
+
+    fn physical(&self, rel: &str) -> String {
+        self.physical.join(rel).to_string_lossy().to_string()
+    }
+
+    fn logical(&self, rel: &str) -> String {
+        self.logical.join(rel).to_string_lossy().to_string()
+    }
+}
+
+fn set_canonicalize_policy(policy: &str) {
+    if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
+        *config_value = ConfigValue::from_str(&format!("paths:\n  canonicalize: {policy}"))
+            .expect("Failed to create config value");
+        true
+    }) {
+        panic!("Failed to edit main user config file: {err}");
+    }
+    flush_config("/");
+
+    // Load the global configuration, which records its policy
+    global_config();
+}
+
+mod workdir_env {
+    use super::*;
+
+    #[test]
+    fn physical_policy_resolves_symlinks() {
+        run_with_env(&[], || {
+            let repo = SymlinkedRepo::new();
+
+            let from_logical = workdir(repo.logical("sub"));
+            let from_physical = workdir(repo.physical("sub"));
+
+            assert_eq!(
+                from_logical.root(),
+                Some(repo.physical("").trim_end_matches('/'))
+            );
+            assert_eq!(from_logical.root(), from_physical.root());
+            assert_eq!(
+                from_logical.reldir(&repo.logical("sub")),
+                Some("sub".to_string())
+            );
+            assert_eq!(from_logical.id(), Some("github.com:xaf/omni".to_string()));
+            assert_eq!(from_logical.id(), from_physical.id());
+            assert_eq!(from_logical.data_path(), from_physical.data_path());
+        });
+    }
+
+    #[test]
+    fn logical_policy_keeps_symlinks() {
+        run_with_env(&[], || {
+            set_canonicalize_policy("logical");
+            let repo = SymlinkedRepo::new();
+
+            let from_logical = workdir(repo.logical("sub"));
+            let from_physical = workdir(repo.physical("sub"));
+
+            assert_eq!(
+                from_logical.root(),
+                Some(repo.logical("").trim_end_matches('/'))
+            );
+            assert_eq!(
+                from_physical.root(),
+                Some(repo.physical("").trim_end_matches('/'))
+            );
+            assert_eq!(
+                from_logical.reldir(&repo.logical("sub")),
+                Some("sub".to_string())
+            );
+            assert_eq!(
+                from_physical.reldir(&repo.physical("sub")),
+                Some("sub".to_string())
+            );
+
+            // The same repository is identified the same way
+            assert_eq!(from_logical.id(), Some("github.com:xaf/omni".to_string()));
+            assert_eq!(from_logical.id(), from_physical.id());
+            assert_eq!(from_logical.data_path(), from_physical.data_path());
+        });
+    }
+
+    #[test]
+    fn logical_policy_without_git() {
+        run_with_env(&[], || {
+            set_canonicalize_policy("logical");
+            let repo = SymlinkedRepo::new();
+            std::fs::remove_dir_all(repo.physical.join(".git")).expect("failed to remove .git");
+            init_workdir(repo.physical(""), Some("test")).expect("failed to init workdir");
+
+            let from_logical = workdir(repo.logical("sub"));
+            let from_physical = workdir(repo.physical("sub"));
+
+            assert_eq!(
+                from_logical.root(),
+                Some(repo.logical("").trim_end_matches('/'))
+            );
+            assert_eq!(
+                from_physical.root(),
+                Some(repo.physical("").trim_end_matches('/'))
+            );
+            assert!(from_logical.has_id());
+            assert_eq!(from_logical.id(), from_physical.id());
+        });
+    }
+}
+
+mod container_context {
+    use super::*;
+
+    /// Environment without any container override or marker, so that
+    /// detection only relies on the files of the given root
+    fn container_env(omni_container: Option<&str>) -> Vec<(String, Option<String>)> {
+        vec![
+            (
+                "OMNI_CONTAINER".to_string(),
+                omni_container.map(|value| value.to_string()),
+            ),
+            ("REMOTE_CONTAINERS".to_string(), None),
+            ("CODESPACES".to_string(), None),
+            ("DEVCONTAINER".to_string(), None),
+            ("container".to_string(), None),
+        ]
+    }
+
+    /// Creates a file system root with the given files
+    fn root_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let root = tempfile::tempdir().expect("failed to create temp dir");
+        for (path, contents) in files {
+            let path = root.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).expect("failed to create dir");
+            std::fs::write(path, contents).expect("failed to write file");
+        }
+        root
+    }
+
+    #[test]
+    fn no_marker() {
+        run_with_env(&container_env(None), || {
+            let root = root_with(&[("proc/1/cgroup", "0::/init.scope\n")]);
+            let context = ContainerContext::detect(root.path());
+
+            assert_eq!(context, ContainerContext::None);
+            assert_eq!(context.name(), None);
+            assert_eq!(
+                compute_data_home_in(context),
+                format!("{}/.local/share/omni", user_home())
+            );
+            assert_eq!(
+                compute_cache_home_in(context),
+                format!("{}/.cache/omni", user_home())
+            );
+        });
+    }
+
+    #[test]
+    fn dockerenv_marker() {
+        run_with_env(&container_env(None), || {
+            let root = root_with(&[(".dockerenv", "")]);
+            let context = ContainerContext::detect(root.path());
+
+            assert_eq!(context, ContainerContext::Container);
+            assert_eq!(context.name(), Some("container"));
+            assert_eq!(
+                compute_data_home_in(context),
+                format!("{}/.local/share/omni-container", user_home())
+            );
+            assert_eq!(
+                compute_cache_home_in(context),
+                format!("{}/.cache/omni-container", user_home())
+            );
+            assert_eq!(
+                compute_state_home_in(context),
+                format!("{}/.local/state/omni-container", user_home())
+            );
+        });
+    }
+
+    #[test]
+    fn detected_container_keeps_existing_dirs() {
+        run_with_env(&container_env(None), || {
+            let root = root_with(&[(".dockerenv", "")]);
+            let context = ContainerContext::detect(root.path());
+            std::fs::create_dir_all(format!("{}/.local/share/omni", user_home()))
+                .expect("failed to create data dir");
+
+            assert_eq!(
+                compute_data_home_in(context),
+                format!("{}/.local/share/omni", user_home())
+            );
+            assert_eq!(
+                compute_cache_home_in(context),
+                format!("{}/.cache/omni-container", user_home())
+            );
+
+            std::fs::create_dir_all(format!("{}/.local/share/omni-container", user_home()))
+                .expect("failed to create data dir");
+            assert_eq!(
+                compute_data_home_in(context),
+                format!("{}/.local/share/omni-container", user_home())
+            );
+        });
+    }
+
+    #[test]
+    fn explicit_container_does_not_keep_existing_dirs() {
+        run_with_env(&container_env(Some("docker")), || {
+            std::fs::create_dir_all(format!("{}/.local/share/omni", user_home()))
+                .expect("failed to create data dir");
+
+            assert_eq!(
+                compute_data_home_in(ContainerContext::Container),
+                format!("{}/.local/share/omni-container", user_home())
+            );
+        });
+    }
+
+    #[test]
+    fn cgroup_marker() {
+        run_with_env(&container_env(None), || {
+            let root = root_with(&[("proc/1/cgroup", "12:pids:/docker/abc123\n")]);
+
+            assert_eq!(
+                ContainerContext::detect(root.path()),
+                ContainerContext::Container
+            );
+        });
+    }
+
+    #[test]
+    fn devcontainer_env_marker() {
+        let mut envs = container_env(None);
+        envs.push(("REMOTE_CONTAINERS".to_string(), Some("true".to_string())));
+
+        run_with_env(&envs, || {
+            let root = root_with(&[(".dockerenv", "")]);
+            let context = ContainerContext::detect(root.path());
+
+            assert_eq!(context, ContainerContext::Devcontainer);
+            assert_eq!(context.name(), Some("devcontainer"));
+            assert_eq!(
+                compute_cache_home_in(context),
+                format!("{}/.cache/omni-container", user_home())
+            );
+        });
+    }
+
+    #[test]
+    fn override_disables_detection() {
+        run_with_env(&container_env(Some("none")), || {
+            let root = root_with(&[(".dockerenv", "")]);
+
+            assert_eq!(
+                ContainerContext::detect(root.path()),
+                ContainerContext::None
+            );
+        });
+    }
+
+    #[test]
+    fn override_forces_context() {
+        run_with_env(&container_env(Some("devcontainer")), || {
+            assert_eq!(container_context(), ContainerContext::Devcontainer);
+            assert_eq!(
+                data_home(),
+                format!("{}/.local/share/omni-container", user_home())
+            );
+        });
+
+        run_with_env(&container_env(Some("docker")), || {
+            assert_eq!(container_context(), ContainerContext::Container);
+            assert_eq!(
+                cache_home(),
+                format!("{}/.cache/omni-container", user_home())
+            );
+        });
+    }
+
+    #[test]
+    fn explicit_homes_take_precedence() {
+        let mut envs = container_env(Some("docker"));
+        envs.push(("OMNI_DATA_HOME".to_string(), Some("/data/omni".to_string())));
+        envs.push((
+            "OMNI_CACHE_HOME".to_string(),
+            Some("/cache/omni".to_string()),
+        ));
+
+        run_with_env(&envs, || {
+            assert_eq!(data_home(), "/data/omni");
+            assert_eq!(cache_home(), "/cache/omni");
+        });
+    }
+}
+
+mod lock_sync_file {
+    use super::*;
+
+    use std::collections::HashSet;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::sync::Barrier;
+
+    use crate::internal::config::up::utils::SyncUpdateOperation;
+
+    fn up_init(pid: u32) -> SyncUpdateInit {
+        SyncUpdateInit::Up {
+            commit: None,
+            options: HashSet::new(),
+            cache: true,
+            pid: Some(pid),
+            started_at: Some(OffsetDateTime::now_utc().unix_timestamp()),
+        }
+    }
+
+    fn down_init(pid: u32) -> SyncUpdateInit {
+        SyncUpdateInit::Down {
+            cache: true,
+            pid: Some(pid),
+            started_at: Some(OffsetDateTime::now_utc().unix_timestamp() - 120),
+        }
+    }
+
+    /// Locks the sync file as a running operation would, writing the
+    /// given init operation at its beginning
+    fn hold_lock(sync_path: &Path, init: SyncUpdateInit) -> std::fs::File {
+        let file = open_sync_file(sync_path).expect("failed to open sync file");
+        assert!(try_lock_sync_file(&file, sync_path).expect("failed to lock sync file"));
+        SyncUpdateOperation::Init(init)
+            .dump_to_file(&file)
+            .expect("failed to write sync file");
+        file
+    }
+
+    /// Returns the pid of a process that is not running anymore
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true")
+            .spawn()
+            .expect("failed to spawn process");
+        let pid = child.id();
+        child.wait().expect("failed to wait for process");
+        pid
+    }
+
+    #[test]
+    fn acquires_free_lock() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+        let sync_path = tempdir.path().join("sync");
+
+        let mut listener = SyncUpdateListener::new();
+        listener.expect_init(&up_init(std::process::id()));
+
+        let file = lock_sync_file(&sync_path, &mut listener, false).expect("failed to lock");
+        assert!(file.is_some());
+    }
+
+    #[test]
+    fn waits_then_proceeds() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+        let sync_path = tempdir.path().join("sync");
+
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let holder_path = sync_path.clone();
+        let holder = std::thread::spawn(move || {
+            let file = hold_lock(&holder_path, down_init(std::process::id()));
+            locked_tx.send(()).expect("failed to notify");
+            std::thread::sleep(StdDuration::from_millis(500));
+            drop(file);
+        });
+        locked_rx.recv().expect("failed to wait for the lock");
+
+        let mut listener = SyncUpdateListener::new();
+        listener.expect_init(&up_init(std::process::id()));
+
+        let start = StdInstant::now();
+        let file = lock_sync_file(&sync_path, &mut listener, true).expect("failed to lock");
+        assert!(file.is_some());
+        assert!(start.elapsed() >= StdDuration::from_millis(400));
+
+        holder.join().expect("holder panicked");
+    }
+
+    #[test]
+    fn no_wait_fails_when_running() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+        let sync_path = tempdir.path().join("sync");
+
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let holder_path = sync_path.clone();
+        let holder = std::thread::spawn(move || {
+            let _file = hold_lock(&holder_path, down_init(std::process::id()));
+            locked_tx.send(()).expect("failed to notify");
+            let _ = done_rx.recv();
+        });
+        locked_rx.recv().expect("failed to wait for the lock");
+
+        let mut listener = SyncUpdateListener::new();
+        listener.expect_init(&up_init(std::process::id()));
+
+        match lock_sync_file(&sync_path, &mut listener, false) {
+            Err(SyncUpdateError::AlreadyRunning(description)) => {
+                assert_eq!(
+                    description,
+                    format!(
+                        "another omni down is running (pid {}, started 2 minutes ago)",
+                        std::process::id()
+                    )
+                );
+            }
+            other => panic!(
+                "expected AlreadyRunning, got {:?}",
+                other.map(|f| f.is_some())
+            ),
+        }
+
+        done_tx.send(()).expect("failed to notify");
+        holder.join().expect("holder panicked");
+    }
+
+    #[test]
+    fn takes_over_stale_lock() {
+        let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+        let sync_path = tempdir.path().join("sync");
+
+        // The lock is still held, e.g. through a descriptor inherited by
+        // a child process, but the process that took it is gone
+        let stale = hold_lock(&sync_path, up_init(dead_pid()));
+
+        let mut listener = SyncUpdateListener::new();
+        listener.expect_init(&up_init(std::process::id()));
+
+        let file = lock_sync_file(&sync_path, &mut listener, false)
+            .expect("failed to lock")
+            .expect("expected the lock to be taken over");
+
+        let current = std::fs::metadata(&sync_path).expect("failed to read sync path");
+        let locked = file.metadata().expect("failed to read locked file");
+        let replaced = stale.metadata().expect("failed to read stale file");
+        assert_eq!(locked.ino(), current.ino());
+        assert_ne!(replaced.ino(), current.ino());
+
+        // Another operation cannot lock the file anymore
+        let mut listener = SyncUpdateListener::new();
+        listener.expect_init(&up_init(std::process::id()));
+        assert!(matches!(
+            lock_sync_file(&sync_path, &mut listener, false),
+            Err(SyncUpdateError::AlreadyRunning(_))
+        ));
+    }
+
+    #[test]
+    fn takes_over_stale_lock_once() {
+        for _ in 0..10 {
+            let tempdir = tempfile::tempdir().expect("failed to create temp dir");
+            let sync_path = tempdir.path().join("sync");
+
+            let _stale = hold_lock(&sync_path, up_init(dead_pid()));
+
+            // Both operations find the stale lock at the same time, but
+            // only one of them can take it over
+            let barrier = Arc::new(Barrier::new(2));
+            let contenders = (0..2)
+                .map(|_| {
+                    let barrier = barrier.clone();
+                    let sync_path = sync_path.clone();
+                    std::thread::spawn(move || {
+                        let mut listener = SyncUpdateListener::new();
+                        listener.expect_init(&up_init(std::process::id()));
+
+                        barrier.wait();
+                        lock_sync_file(&sync_path, &mut listener, false)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let locked = contenders
+                .into_iter()
+                .filter_map(
+                    |contender| match contender.join().expect("contender panicked") {
+                        Ok(file) => Some(file.expect("expected the lock to be taken over")),
+                        Err(SyncUpdateError::AlreadyRunning(_)) => None,
+                        Err(err) => panic!("unexpected error: {err:?}"),
+                    },
+                )
+                .collect::<Vec<_>>();
+
+            assert_eq!(locked.len(), 1);
+            let current = std::fs::metadata(&sync_path).expect("failed to read sync path");
+            let file = locked[0].metadata().expect("failed to read locked file");
+            assert_eq!(file.ino(), current.ino());
+        }
+    }
+}
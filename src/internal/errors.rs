@@ -22,6 +22,8 @@ pub enum SyncUpdateError {
     NoProgressHandler,
     #[error("timeout during operation")]
     Timeout,
+    #[error("{0}")]
+    AlreadyRunning(String),
 }
 
 #[derive(Error, Debug)]
@@ -43,3 +45,34 @@ pub enum GitUrlError {
     #[error("error during Git URL parsing: {0}")]
     GitUrlParse(String),
 }
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ResponseFileError {
+    #[error("{origin}: failed to read response file '{path}': {error}")]
+    Read {
+        origin: String,
+        path: String,
+        error: String,
+    },
+    #[error("{path}:{line}:{column}: unterminated {quote} quote")]
+    UnterminatedQuote {
+        path: String,
+        line: usize,
+        column: usize,
+        quote: &'static str,
+    },
+    #[error("{path}:{line}:{column}: escape character at the end of the file")]
+    DanglingEscape {
+        path: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("{origin}: response file '{path}' exceeds the maximum nesting depth of {max_depth}")]
+    TooDeep {
+        origin: String,
+        path: String,
+        max_depth: usize,
+    },
+    #[error("{origin}: response file cycle: {}", chain.join(" -> "))]
+    Cycle { origin: String, chain: Vec<String> },
+}
@@ -1,4 +1,5 @@
 mod org;
+pub(crate) use org::forget_repo_lookups;
 pub(crate) use org::Org;
 pub(crate) use org::Repo;
 pub(crate) use org::ORG_LOADER;
@@ -20,6 +21,9 @@ mod parsed_repo_url;
 pub(crate) use parsed_repo_url::strip_azure_version_prefix;
 pub(crate) use parsed_repo_url::ParsedRepoUrl;
 
+mod repo_match;
+pub(crate) use repo_match::ignore_match_memory;
+
 mod updater;
 pub(crate) use updater::auto_update_async;
 pub(crate) use updater::auto_update_on_command_not_found;
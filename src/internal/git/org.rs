@@ -1,18 +1,18 @@
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::path::Path;
 use std::path::PathBuf;
 
 use indicatif::ProgressBar;
 use indicatif::ProgressStyle;
-use itertools::Itertools;
 use lazy_static::lazy_static;
 use once_cell::sync::OnceCell;
-use strsim::normalized_damerau_levenshtein;
 use thiserror::Error;
 use url::Url;
 use walkdir::WalkDir;
 
 use crate::internal::cache::utils::Empty;
+use crate::internal::cache::RepoLookupCache;
 use crate::internal::commands::path::omnipath_entries;
 use crate::internal::config::config;
 use crate::internal::config::OrgConfig;
@@ -20,6 +20,10 @@ use crate::internal::env::omni_org_env;
 use crate::internal::env::shell_is_interactive;
 use crate::internal::git::package_path_from_handle;
 use crate::internal::git::package_root_path;
+use crate::internal::git::repo_match::match_memory_ignored;
+use crate::internal::git::repo_match::remember_choice;
+use crate::internal::git::repo_match::RepoMatchCandidate;
+use crate::internal::git::repo_match::RepoMatcher;
 use crate::internal::git::safe_git_url_parse;
 use crate::internal::git::safe_normalize_url;
 use crate::internal::git::strip_azure_version_prefix;
@@ -27,6 +31,7 @@ use crate::internal::git::utils::format_path_with_template;
 use crate::internal::git::utils::format_path_with_template_and_data;
 use crate::internal::git_env;
 use crate::internal::user_interface::colors::StringColor;
+use crate::internal::workdir::hosting_orgs;
 use crate::omni_print;
 
 lazy_static! {
@@ -34,6 +39,14 @@ lazy_static! {
         pub static ref ORG_LOADER: OrgLoader = OrgLoader::new();
 }
 
+/// Forgets the results of the previous repository lookups; this needs to
+/// be called when a repository is added to a worktree, so that a lookup
+/// that previously did not find it does not keep missing it
+pub fn forget_repo_lookups() {
+    // Failing to forget the lookups should not fail the operation
+    let _ = RepoLookupCache::get().clear();
+}
+
 #[derive(Debug, Eq, Hash, PartialEq, Clone, Copy)]
 enum SearchEntryMatchStart {
     Host,
@@ -449,7 +462,39 @@ impl OrgLoader {
         let (repo, only_worktree, only_packages) =
             Self::normalize_repo_args(repo, only_worktree, only_packages);
 
-        self.file_system_lookup(repo, only_worktree, only_packages, allow_interactive)
+        // The previous results cannot be trusted when ignoring the choices
+        // of the user, since they were ranked with those choices
+        if match_memory_ignored() {
+            return self
+                .file_system_lookup(repo, only_worktree, only_packages, allow_interactive)
+                .into_path();
+        }
+
+        // The fingerprint is computed before scanning, so that a change
+        // during the scan invalidates the result
+        let cache = RepoLookupCache::get();
+        let fingerprint = self.lookup_fingerprint(only_worktree, only_packages);
+        match cache.lookup(repo, only_worktree, only_packages, &fingerprint) {
+            Some(None) => return None,
+            Some(Some(path)) if Path::new(&path).is_dir() => return Some(PathBuf::from(path)),
+            _ => {}
+        }
+
+        match self.file_system_lookup(repo, only_worktree, only_packages, allow_interactive) {
+            FileSystemLookup::Cacheable(path) => {
+                let path_str = path.as_ref().map(|path| path.to_string_lossy().to_string());
+                // Failing to record the result should not prevent the lookup
+                let _ = cache.add(
+                    repo,
+                    only_worktree,
+                    only_packages,
+                    &fingerprint,
+                    path_str.as_deref(),
+                );
+                path
+            }
+            FileSystemLookup::Uncacheable(path) => path,
+        }
     }
 
     pub fn find_repo(
@@ -529,12 +574,22 @@ impl OrgLoader {
         only_packages: bool,
     ) -> Option<PathBuf> {
         if !only_packages {
-            for org in self.orgs.iter() {
-                if let Some(path) = org.get_repo_path(repo) {
-                    if path.is_dir() {
-                        return Some(path);
-                    }
-                }
+            // Only rank the repositories if there is an ambiguity, to
+            // avoid reading the previous choices when not needed
+            let mut candidates = self.naive_candidates(repo);
+            let best = if candidates.len() > 1 {
+                RepoMatcher::new(repo)
+                    .rank(candidates)
+                    .into_iter()
+                    .next()
+                    .map(|(_, candidate)| candidate)
+            } else {
+                candidates.pop()
+            };
+
+            if let Some(best) = best {
+                self.remember_correction(repo, &best.path);
+                return Some(best.path);
             }
         }
 
@@ -560,19 +615,166 @@ impl OrgLoader {
         None
     }
 
+    /// Returns the existing repositories that the configured organizations
+    /// resolve the repository to, in the order of the organizations
+    fn naive_candidates(&self, repo: &str) -> Vec<RepoMatchCandidate> {
+        let current_orgs = self.current_orgs();
+
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        for org in self.orgs.iter() {
+            let path = match org.get_repo_path(repo) {
+                Some(path) if path.is_dir() => path,
+                _ => continue,
+            };
+
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+
+            let relpath = path
+                .strip_prefix(org.worktree())
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+            let in_current_org = current_orgs
+                .iter()
+                .any(|current| current.config.handle == org.config.handle);
+
+            candidates.push(RepoMatchCandidate {
+                path,
+                relpath,
+                in_current_org,
+            });
+        }
+
+        candidates
+    }
+
+    /// Returns the organizations hosting the current work directory,
+    /// ignoring the default organization which hosts any repository
+    fn current_orgs(&self) -> Vec<&'static Org> {
+        hosting_orgs(".")
+            .into_iter()
+            .filter(|org| !org.is_default())
+            .collect()
+    }
+
+    /// When a repository is looked up with its owner while its name alone
+    /// resolves to multiple repositories, the user is correcting the
+    /// repository that would be picked for the name; remember that choice
+    /// so the name alone resolves to that repository next time
+    fn remember_correction(&self, repo: &str, path: &Path) {
+        let parsed = match Repo::parse(repo) {
+            Ok(parsed) if parsed.owner.is_some() => parsed,
+            _ => return,
+        };
+
+        let name_candidates = self.naive_candidates(&parsed.name);
+        if name_candidates.len() > 1 && name_candidates.iter().any(|c| c.path == path) {
+            remember_choice(&parsed.name, path);
+        }
+    }
+
+    /// Returns the directories to scan when looking up a repository
+    /// in the file system
+    fn lookup_worktrees(&self, only_worktree: bool, only_packages: bool) -> Vec<String> {
+        let mut worktrees = Vec::new();
+        let mut seen = HashSet::new();
+
+        if !only_packages {
+            for org in self.orgs.iter() {
+                if seen.insert(org.worktree()) {
+                    worktrees.push(org.worktree());
+                }
+            }
+
+            let worktree = config(".").worktree();
+            if seen.insert(worktree.clone()) {
+                worktrees.push(worktree.clone());
+            }
+        }
+
+        if !only_worktree {
+            worktrees.push(package_root_path());
+        }
+
+        worktrees
+    }
+
+    /// Returns a fingerprint of what the file system lookups depend on:
+    /// the configuration of the organizations and of the lookups, the
+    /// organizations of the current work directory, and the modification
+    /// times of the directories of the worktrees down to the owners of
+    /// the repositories, which change when a repository is added to or
+    /// removed from an owner
+    fn lookup_fingerprint(&self, only_worktree: bool, only_packages: bool) -> String {
+        let cfg = config(".");
+
+        let mut parts = vec![
+            format!("repo_path_format:{}", cfg.repo_path_format),
+            format!("cd:{}", serde_json::to_string(&cfg.cd).unwrap_or_default()),
+        ];
+
+        for org in self.orgs.iter() {
+            parts.push(format!(
+                "org:{}:{}:{}",
+                org.config.handle,
+                org.worktree(),
+                org.repo_path_format()
+            ));
+        }
+
+        for org in self.current_orgs() {
+            parts.push(format!("current_org:{}", org.config.handle));
+        }
+
+        for worktree in self.lookup_worktrees(only_worktree, only_packages) {
+            let entries = WalkDir::new(&worktree)
+                .follow_links(true)
+                .max_depth(2)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+                })
+                .flatten();
+
+            for entry in entries {
+                if !entry.file_type().is_dir() {
+                    continue;
+                }
+
+                let modified = entry
+                    .metadata()
+                    .ok()
+                    .and_then(|metadata| metadata.modified().ok())
+                    .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|modified| modified.as_nanos())
+                    .unwrap_or_default();
+
+                parts.push(format!("dir:{}:{}", entry.path().display(), modified));
+            }
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(parts.join("\0").as_bytes());
+        hasher.finalize().to_hex()[..16].to_string()
+    }
+
     fn file_system_lookup(
         &self,
         repo: &str,
         only_worktree: bool,
         only_packages: bool,
         allow_interactive: bool,
-    ) -> Option<PathBuf> {
+    ) -> FileSystemLookup {
         let interactive = allow_interactive && shell_is_interactive();
 
         let repo_url = if let Ok(repo_url) = Repo::parse(repo) {
             repo_url
         } else {
-            return None;
+            return FileSystemLookup::Uncacheable(None);
         };
         let rel_path = repo_url.rel_path();
 
@@ -594,25 +796,7 @@ impl OrgLoader {
                 });
         } else {
             // Get worktrees
-            let mut worktrees = Vec::new();
-            let mut seen = HashSet::new();
-
-            if !only_packages {
-                for org in self.orgs.iter() {
-                    if seen.insert(org.worktree()) {
-                        worktrees.push(org.worktree());
-                    }
-                }
-
-                let worktree = config(".").worktree();
-                if seen.insert(worktree.clone()) {
-                    worktrees.push(worktree.clone());
-                }
-            }
-
-            if !only_worktree {
-                worktrees.push(package_root_path());
-            }
+            let worktrees = self.lookup_worktrees(only_worktree, only_packages);
 
             // Prepare a spinner for the research
             let spinner = if interactive {
@@ -669,7 +853,7 @@ impl OrgLoader {
                                 omni_print!(format!("{} Setting up your organizations will make repository lookup much faster.", "Did you know?".bold()));
                             }
 
-                            return Some(filepath.to_path_buf());
+                            return FileSystemLookup::Cacheable(Some(filepath.to_path_buf()));
                         }
 
                         all_repos.push(PathScore {
@@ -709,39 +893,58 @@ impl OrgLoader {
             };
 
             if repo_url.matches(&found_repo) {
-                return Some(found.abspath.to_path_buf());
+                return FileSystemLookup::Cacheable(Some(found.abspath.to_path_buf()));
             }
         }
 
         // Otherwise, we need to score the results and ask the user
         // if there's a match that they want to use
-        let mut with_score = all_repos
-            .iter_mut()
-            .update(|found| {
-                // TODO: set scores related to <host>/<org>/<repo>, <org>/<repo>, and <repo> formats too
-                let absscore =
-                    normalized_damerau_levenshtein(repo, found.abspath.to_str().unwrap());
-                let relscore = normalized_damerau_levenshtein(repo, found.relpath.as_str());
-                found.score = absscore.max(relscore);
+        let matcher = RepoMatcher::new(repo);
+        let current_orgs = self.current_orgs();
+        let candidates = all_repos
+            .into_iter()
+            .map(|found| {
+                let name = found
+                    .abspath
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let in_current_org = current_orgs
+                    .iter()
+                    .any(|org| org.get_repo_path(&name).as_ref() == Some(&found.abspath));
+
+                RepoMatchCandidate {
+                    path: found.abspath,
+                    relpath: found.relpath,
+                    in_current_org,
+                }
+            })
+            .collect::<Vec<_>>();
+        let with_score = matcher
+            .rank(candidates)
+            .into_iter()
+            .filter(|(score, _)| *score > config(".").cd.path_match_min_score)
+            .map(|(score, candidate)| PathScore {
+                score,
+                abspath: candidate.path,
+                relpath: candidate.relpath,
             })
-            .filter(|found| found.score > config(".").cd.path_match_min_score)
             .collect::<Vec<_>>();
 
         if with_score.is_empty() {
-            return None;
+            return FileSystemLookup::Cacheable(None);
         }
 
-        with_score.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap());
-        with_score.reverse();
-
         if config(".").cd.path_match_skip_prompt_if.enabled
             && with_score[0].score >= config(".").cd.path_match_skip_prompt_if.first_min
             && (with_score.len() < 2
                 || with_score[1].score <= config(".").cd.path_match_skip_prompt_if.second_max)
         {
-            return Some(with_score[0].abspath.to_path_buf());
+            return FileSystemLookup::Cacheable(Some(with_score[0].abspath.to_path_buf()));
         }
 
+        // The next results depend on the answer of the user, or would if
+        // the shell was interactive, so they cannot be reused
         if interactive {
             let page_size = 7;
             let question = if with_score.len() > 1 {
@@ -777,21 +980,19 @@ impl OrgLoader {
             };
 
             match requestty::prompt_one(question) {
-                Ok(answer) => {
-                    match answer {
-                        requestty::Answer::ListItem(listitem) => {
-                            // return Some(with_score[listitem.index].abspath.to_path_buf());
-                            return Some(listitem.text.into());
-                        }
-                        requestty::Answer::Bool(confirmed) => {
-                            if confirmed {
-                                // println!("{}", format!("[✔] {}", with_score[0].abspath.to_str().unwrap()).green());
-                                return Some(with_score[0].abspath.to_path_buf());
-                            }
-                        }
-                        _ => {}
+                Ok(answer) => match answer {
+                    requestty::Answer::ListItem(listitem) => {
+                        let chosen = with_score[listitem.index].abspath.to_path_buf();
+                        remember_choice(repo, &chosen);
+                        return FileSystemLookup::Uncacheable(Some(chosen));
                     }
-                }
+                    requestty::Answer::Bool(true) => {
+                        let chosen = with_score[0].abspath.to_path_buf();
+                        remember_choice(repo, &chosen);
+                        return FileSystemLookup::Uncacheable(Some(chosen));
+                    }
+                    _ => {}
+                },
                 Err(err) => {
                     if page_size < with_score.len() {
                         print!("\x1B[1A\x1B[2K"); // This clears the line, so there's no artifact left
@@ -801,7 +1002,24 @@ impl OrgLoader {
             }
         }
 
-        None
+        FileSystemLookup::Uncacheable(None)
+    }
+}
+
+/// The result of looking up a repository in the file system; only the
+/// results that do not depend on the answer of the user can be reused
+/// for the next lookups
+#[derive(Debug)]
+enum FileSystemLookup {
+    Cacheable(Option<PathBuf>),
+    Uncacheable(Option<PathBuf>),
+}
+
+impl FileSystemLookup {
+    fn into_path(self) -> Option<PathBuf> {
+        match self {
+            Self::Cacheable(path) | Self::Uncacheable(path) => path,
+        }
     }
 }
 
@@ -7,6 +7,7 @@ fn mk_org(handle: &str) -> Org {
         trusted: true,
         worktree: None,
         repo_path_format: None,
+        up_operations: Default::default(),
     };
     Org::new(cfg).expect("Org::new should parse handle")
 }
@@ -253,3 +254,150 @@ fn get_repo_git_url_owner_mismatch() {
     );
     assert!(org.get_repo_git_url("Other/Project/Repo").is_none());
 }
+
+mod repo_lookup_cache {
+    use super::*;
+
+    use std::fs::File;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use crate::internal::testutils::run_with_env;
+    use crate::internal::ConfigLoader;
+    use crate::internal::ConfigValue;
+
+    /// Configures a worktree holding a single repository, and scanning
+    /// the worktrees when looking up a repository
+    fn setup_worktree() -> PathBuf {
+        let worktree =
+            PathBuf::from(std::env::var("HOME").expect("HOME should be set")).join("git");
+        mk_repo(&worktree, "github.com/xaf/api");
+
+        let yaml = format!(
+            "worktree: {}\ncd:\n  fast_search: false\n",
+            worktree.display()
+        );
+        if let Err(err) = ConfigLoader::edit_main_user_config_file(|config_value| {
+            *config_value = ConfigValue::from_str(&yaml).expect("Failed to create config value");
+            true
+        }) {
+            panic!("Failed to edit main user config file: {err}");
+        }
+
+        worktree
+    }
+
+    fn mk_repo(worktree: &Path, relpath: &str) -> PathBuf {
+        let path = worktree.join(relpath);
+        std::fs::create_dir_all(path.join(".git")).expect("failed to create repository");
+        path
+    }
+
+    fn modified(path: &Path) -> SystemTime {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .expect("failed to get modification time")
+    }
+
+    fn set_modified(path: &Path, modified: SystemTime) {
+        File::open(path)
+            .and_then(|file| file.set_modified(modified))
+            .expect("failed to set modification time");
+    }
+
+    /// Moves the modification time forward, so that the change is seen
+    /// even on file systems with a coarse time resolution
+    fn touch(path: &Path) {
+        set_modified(path, modified(path) + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn repeated_miss_is_served_from_cache() {
+        run_with_env(&[], || {
+            let worktree = setup_worktree();
+            let loader = OrgLoader::new();
+
+            assert_eq!(loader.find_repo_slow("zzzzzz", false, false, false), None);
+
+            let fingerprint = loader.lookup_fingerprint(false, false);
+            assert_eq!(
+                RepoLookupCache::get().lookup("zzzzzz", false, false, &fingerprint),
+                Some(None)
+            );
+
+            // A recorded miss is returned without scanning the worktree,
+            // which would otherwise find the repository
+            RepoLookupCache::get()
+                .add("api", false, false, &fingerprint, None)
+                .expect("failed to record the lookup");
+            assert_eq!(loader.find_repo_slow("api", false, false, false), None);
+
+            // A lookup limited to the worktrees is a different lookup
+            assert_eq!(
+                loader.find_repo_slow("api", true, false, false),
+                Some(worktree.join("github.com/xaf/api"))
+            );
+        });
+    }
+
+    #[test]
+    fn cached_miss_is_forgotten_on_clone() {
+        run_with_env(&[], || {
+            let worktree = setup_worktree();
+            let loader = OrgLoader::new();
+
+            assert_eq!(loader.find_repo_slow("newrepo", false, false, false), None);
+            let fingerprint = loader.lookup_fingerprint(false, false);
+
+            // Add the repository without changing the fingerprint, as if
+            // the modification time could not be relied upon
+            let owner = worktree.join("github.com/xaf");
+            let owner_modified = modified(&owner);
+            let newrepo = mk_repo(&worktree, "github.com/xaf/newrepo");
+            set_modified(&owner, owner_modified);
+            assert_eq!(loader.lookup_fingerprint(false, false), fingerprint);
+            assert_eq!(loader.find_repo_slow("newrepo", false, false, false), None);
+
+            // Cloning forgets the previous lookups
+            forget_repo_lookups();
+            assert_eq!(
+                loader.find_repo_slow("newrepo", false, false, false),
+                Some(newrepo)
+            );
+        });
+    }
+
+    #[test]
+    fn cached_results_expire_with_the_fingerprint() {
+        run_with_env(&[], || {
+            let worktree = setup_worktree();
+            let loader = OrgLoader::new();
+
+            assert_eq!(loader.find_repo_slow("newrepo", false, false, false), None);
+            let fingerprint = loader.lookup_fingerprint(false, false);
+
+            // Adding a repository changes the modification time of its
+            // owner, and thus the fingerprint
+            let owner = worktree.join("github.com/xaf");
+            let newrepo = mk_repo(&worktree, "github.com/xaf/newrepo");
+            touch(&owner);
+            assert_ne!(loader.lookup_fingerprint(false, false), fingerprint);
+            assert_eq!(
+                loader.find_repo_slow("newrepo", false, false, false),
+                Some(newrepo.clone())
+            );
+
+            // The found repository is then served from the cache, as long
+            // as it still exists
+            let fingerprint = loader.lookup_fingerprint(false, false);
+            assert_eq!(
+                RepoLookupCache::get().lookup("newrepo", false, false, &fingerprint),
+                Some(Some(newrepo.to_string_lossy().to_string()))
+            );
+
+            std::fs::remove_dir_all(&newrepo).expect("failed to remove repository");
+            touch(&owner);
+            assert_eq!(loader.find_repo_slow("newrepo", false, false, false), None);
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use strsim::normalized_damerau_levenshtein;
+
+use crate::internal::cache::RepoLookupCache;
+use crate::internal::cache::RepoMatchCache;
+
+/// Whether the repositories previously chosen by the user should be
+/// ignored when ranking the repositories matching a fragment
+static IGNORE_MATCH_MEMORY: AtomicBool = AtomicBool::new(false);
+
+/// The score of a repository whose name contains the fragment, without
+/// being exactly the fragment
+const SUBSTRING_MATCH_SCORE: f64 = 0.75;
+
+/// The boost of the repositories of the organization of the current
+/// work directory
+const CURRENT_ORG_BOOST: f64 = 0.1;
+
+/// The boost of a repository for each time it was previously chosen for
+/// the same fragment, up to `MAX_CHOICE_BOOST`
+const CHOICE_BOOST: f64 = 0.2;
+const MAX_CHOICE_BOOST: f64 = 0.6;
+
+/// Ignores the repositories previously chosen by the user for the rest of
+/// this process, both when ranking the matches and when recording choices
+pub fn ignore_match_memory() {
+    IGNORE_MATCH_MEMORY.store(true, Ordering::Relaxed);
+}
+
+pub fn match_memory_ignored() -> bool {
+    IGNORE_MATCH_MEMORY.load(Ordering::Relaxed)
+}
+
+/// Records that the repository at the given path was chosen by the user
+/// for the fragment, so that it can be preferred in the next lookups
+pub fn remember_choice(fragment: &str, path: &Path) {
+    if match_memory_ignored() {
+        return;
+    }
+
+    // Failing to record the choice should not prevent the lookup
+    let _ =
+        RepoMatchCache::get().add_choice(&normalize_fragment(fragment), &path.to_string_lossy());
+
+    // The results of the previous lookups were ranked without that choice
+    let _ = RepoLookupCache::get().clear();
+}
+
+fn normalize_fragment(fragment: &str) -> String {
+    fragment.trim().trim_end_matches('/').to_lowercase()
+}
+
+/// A repository that can match the fragment being looked up
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoMatchCandidate {
+    pub path: PathBuf,
+    /// The path of the repository relative to its worktree
+    pub relpath: String,
+    /// Whether the repository belongs to the organization of the current
+    /// work directory
+    pub in_current_org: bool,
+}
+
+/// Scores the repositories matching a fragment: an exact match of the
+/// repository path beats a substring match of the repository name, which
+/// beats a fuzzy match; the repositories of the organization of the
+/// current work directory and the ones previously chosen by the user for
+/// the same fragment are then boosted
+#[derive(Debug, Clone)]
+pub struct RepoMatcher {
+    fragment: String,
+    choices: HashMap<String, u32>,
+}
+
+impl RepoMatcher {
+    pub fn new(fragment: &str) -> Self {
+        let choices = if match_memory_ignored() {
+            HashMap::new()
+        } else {
+            RepoMatchCache::get().choices(&normalize_fragment(fragment))
+        };
+
+        Self::with_choices(fragment, choices)
+    }
+
+    pub fn with_choices(fragment: &str, choices: HashMap<String, u32>) -> Self {
+        Self {
+            fragment: normalize_fragment(fragment),
+            choices,
+        }
+    }
+
+    /// Returns the candidates along with their score, between 0.0 and 1.0,
+    /// best match first; the boosts never bring a score above the one of
+    /// an exact match, so that they cannot skip the confirmation prompt on
+    /// their own, but they still order the candidates reaching it, and
+    /// candidates with the same score keep their original order
+    pub fn rank(&self, candidates: Vec<RepoMatchCandidate>) -> Vec<(f64, RepoMatchCandidate)> {
+        let mut ranked = candidates
+            .into_iter()
+            .map(|candidate| (self.boosted_score(&candidate), candidate))
+            .collect::<Vec<_>>();
+
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        ranked
+            .into_iter()
+            .map(|(score, candidate)| (score.min(1.0), candidate))
+            .collect()
+    }
+
+    fn boosted_score(&self, candidate: &RepoMatchCandidate) -> f64 {
+        let mut score = self.match_score(candidate);
+
+        if candidate.in_current_org {
+            score += CURRENT_ORG_BOOST;
+        }
+
+        let path = candidate.path.to_string_lossy();
+        if let Some(times_chosen) = self.choices.get(path.as_ref()) {
+            score += (CHOICE_BOOST * f64::from(*times_chosen)).min(MAX_CHOICE_BOOST);
+        }
+
+        score
+    }
+
+    fn match_score(&self, candidate: &RepoMatchCandidate) -> f64 {
+        let relpath = candidate.relpath.to_lowercase();
+        if relpath == self.fragment || relpath.ends_with(&format!("/{}", self.fragment)) {
+            return 1.0;
+        }
+
+        let fuzzy_score = normalized_damerau_levenshtein(
+            &self.fragment,
+            &candidate.path.to_string_lossy().to_lowercase(),
+        )
+        .max(normalized_damerau_levenshtein(&self.fragment, &relpath));
+
+        let fragment_name = self.fragment.rsplit('/').next().unwrap_or_default();
+        let candidate_name = relpath.rsplit('/').next().unwrap_or_default();
+        if !fragment_name.is_empty() && candidate_name.contains(fragment_name) {
+            return fuzzy_score.max(SUBSTRING_MATCH_SCORE);
+        }
+
+        fuzzy_score
+    }
+}
+
+#[cfg(test)]
+#[path = "repo_match_test.rs"]
+mod tests;
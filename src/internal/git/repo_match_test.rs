@@ -0,0 +1,177 @@
+use super::*;
+
+use crate::internal::testutils::run_with_env;
+
+fn candidate(relpath: &str, in_current_org: bool) -> RepoMatchCandidate {
+    RepoMatchCandidate {
+        path: PathBuf::from("/worktree").join(relpath),
+        relpath: relpath.to_string(),
+        in_current_org,
+    }
+}
+
+/// Returns the score of the candidate, as ranked on its own
+fn score(matcher: &RepoMatcher, candidate: RepoMatchCandidate) -> f64 {
+    matcher.rank(vec![candidate])[0].0
+}
+
+fn ranked_relpaths(matcher: &RepoMatcher, candidates: Vec<RepoMatchCandidate>) -> Vec<String> {
+    matcher
+        .rank(candidates)
+        .into_iter()
+        .map(|(_, candidate)| candidate.relpath)
+        .collect()
+}
+
+mod score {
+    use super::*;
+
+    #[test]
+    fn exact_match_beats_substring() {
+        let matcher = RepoMatcher::with_choices("api", HashMap::new());
+
+        let exact = score(&matcher, candidate("github.com/org1/api", false));
+        let substring = score(&matcher, candidate("github.com/org1/api-gateway", false));
+        let fuzzy = score(&matcher, candidate("github.com/org1/app", false));
+
+        assert_eq!(exact, 1.0);
+        assert!(exact > substring, "{exact} <= {substring}");
+        assert!(substring >= SUBSTRING_MATCH_SCORE);
+        assert!(substring > fuzzy, "{substring} <= {fuzzy}");
+    }
+
+    #[test]
+    fn exact_match_with_owner() {
+        let matcher = RepoMatcher::with_choices("org2/API", HashMap::new());
+
+        assert_eq!(
+            score(&matcher, candidate("github.com/org2/api", false)),
+            1.0
+        );
+        assert!(score(&matcher, candidate("github.com/org1/api", false)) < 1.0);
+    }
+
+    #[test]
+    fn current_org_boost() {
+        let matcher = RepoMatcher::with_choices("api", HashMap::new());
+
+        let in_org = matcher.boosted_score(&candidate("github.com/org2/api", true));
+        let other = matcher.boosted_score(&candidate("github.com/org1/api", false));
+
+        assert!(in_org > other, "{in_org} <= {other}");
+    }
+
+    #[test]
+    fn choice_boost_is_capped() {
+        let path = "/worktree/github.com/org2/app".to_string();
+        let none = RepoMatcher::with_choices("api", HashMap::new());
+        let once = RepoMatcher::with_choices("api", HashMap::from([(path.clone(), 1)]));
+        let many = RepoMatcher::with_choices("api", HashMap::from([(path.clone(), 100)]));
+
+        let candidate = candidate("github.com/org2/app", false);
+        let base = score(&none, candidate.clone());
+        assert!(base + MAX_CHOICE_BOOST < 1.0, "{base}");
+        assert_eq!(score(&once, candidate.clone()), base + CHOICE_BOOST);
+        assert_eq!(score(&many, candidate), base + MAX_CHOICE_BOOST);
+    }
+
+    #[test]
+    fn boosted_score_is_clamped() {
+        let path = "/worktree/github.com/org2/api".to_string();
+        let matcher = RepoMatcher::with_choices("api", HashMap::from([(path, 100)]));
+
+        let ranked = matcher.rank(vec![
+            candidate("github.com/org1/api", false),
+            candidate("github.com/org2/api", true),
+        ]);
+
+        // Both are exact matches, the boosts of the chosen repository in
+        // the current organization only order it first
+        assert_eq!(ranked[0].0, 1.0);
+        assert_eq!(ranked[0].1.relpath, "github.com/org2/api");
+        assert_eq!(ranked[1].0, 1.0);
+        assert_eq!(ranked[1].1.relpath, "github.com/org1/api");
+    }
+}
+
+mod rank {
+    use super::*;
+
+    #[test]
+    fn scoring_order() {
+        let matcher = RepoMatcher::with_choices("api", HashMap::new());
+
+        let ranked = ranked_relpaths(
+            &matcher,
+            vec![
+                candidate("github.com/org1/app", false),
+                candidate("github.com/org1/api-gateway", false),
+                candidate("github.com/org1/api", false),
+                candidate("github.com/org2/api", true),
+            ],
+        );
+
+        assert_eq!(
+            ranked,
+            vec![
+                "github.com/org2/api",
+                "github.com/org1/api",
+                "github.com/org1/api-gateway",
+                "github.com/org1/app",
+            ]
+        );
+    }
+
+    #[test]
+    fn ties_keep_original_order() {
+        let matcher = RepoMatcher::with_choices("api", HashMap::new());
+
+        let ranked = ranked_relpaths(
+            &matcher,
+            vec![
+                candidate("github.com/org1/api", false),
+                candidate("github.com/org2/api", false),
+                candidate("github.com/org3/api", false),
+            ],
+        );
+
+        assert_eq!(
+            ranked,
+            vec![
+                "github.com/org1/api",
+                "github.com/org2/api",
+                "github.com/org3/api",
+            ]
+        );
+    }
+
+    #[test]
+    fn boost_after_recorded_choice() {
+        run_with_env(&[], || {
+            let candidates = vec![
+                candidate("github.com/org1/api", false),
+                candidate("github.com/org2/api", true),
+                candidate("github.com/org3/api", false),
+            ];
+
+            let before = ranked_relpaths(&RepoMatcher::new("api"), candidates.clone());
+            assert_eq!(before[0], "github.com/org2/api");
+
+            remember_choice("API", &PathBuf::from("/worktree/github.com/org3/api"));
+
+            let after = ranked_relpaths(&RepoMatcher::new("api"), candidates.clone());
+            assert_eq!(
+                after,
+                vec![
+                    "github.com/org3/api",
+                    "github.com/org2/api",
+                    "github.com/org1/api",
+                ]
+            );
+
+            // The choice is only remembered for the same fragment
+            let other = ranked_relpaths(&RepoMatcher::new("org1/api"), candidates);
+            assert_eq!(other[0], "github.com/org1/api");
+        });
+    }
+}
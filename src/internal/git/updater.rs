@@ -19,6 +19,7 @@ use crate::internal::cache::OmniPathCache;
 use crate::internal::commands::base::Command;
 use crate::internal::commands::path::global_omnipath_entries;
 use crate::internal::config::global_config;
+use crate::internal::config::parser::GitTransportOptions;
 use crate::internal::config::parser::PathEntryConfig;
 use crate::internal::config::parser::StringFilter;
 use crate::internal::config::up::utils::get_command_output;
@@ -418,8 +419,9 @@ pub fn update(options: &UpdateOptions) -> (HashSet<PathBuf>, HashSet<PathBuf>) {
 
                     // Check using git ls-remote
                     let mut cmd = TokioCommand::new("git");
-                    cmd.arg("ls-remote");
+                    cmd.args(updater.transport.git_args("ls-remote"));
                     cmd.arg("--heads");
+                    cmd.envs(updater.transport.env());
                     cmd.current_dir(&updater.path);
                     cmd.stdout(std::process::Stdio::piped());
                     cmd.stderr(std::process::Stdio::piped());
@@ -701,11 +703,12 @@ impl GitRepoUpdaterRefType {
         &self,
         repo_path: &str,
         ref_match: StringFilter,
+        transport: &GitTransportOptions,
         progress_handler: &dyn ProgressHandler,
     ) -> Result<bool, String> {
         match self {
-            Self::Branch => update_git_branch(repo_path, ref_match, progress_handler),
-            Self::Tag => update_git_tag(repo_path, ref_match, progress_handler),
+            Self::Branch => update_git_branch(repo_path, ref_match, transport, progress_handler),
+            Self::Tag => update_git_tag(repo_path, ref_match, transport, progress_handler),
         }
     }
 }
@@ -715,12 +718,14 @@ pub struct GitRepoUpdater {
     path: String,
     ref_type: GitRepoUpdaterRefType,
     pattern: StringFilter,
+    transport: GitTransportOptions,
 }
 
 impl GitRepoUpdater {
     pub fn from_path<T: AsRef<str>>(path: T) -> Option<Self> {
         let config = global_config();
         let prucfg = config.path_repo_updates;
+        let transport = config.git_transport;
 
         let wd = workdir(path);
         let wd_root = wd.root()?;
@@ -744,6 +749,7 @@ impl GitRepoUpdater {
                     path: wd_root.to_string(),
                     ref_type: GitRepoUpdaterRefType::from_ref_type(&value.ref_type),
                     pattern: value.ref_match.clone(),
+                    transport: transport.options_for_url(&clean_id),
                 });
             }
         }
@@ -757,6 +763,7 @@ impl GitRepoUpdater {
             path: wd_root.to_string(),
             ref_type: GitRepoUpdaterRefType::from_ref_type(&prucfg.ref_type),
             pattern: prucfg.ref_match.clone(),
+            transport: transport.options_for_url(&clean_id),
         })
     }
 
@@ -786,14 +793,19 @@ impl GitRepoUpdater {
                 Box::new(&printer)
             };
 
-        self.ref_type
-            .update(&self.path, self.pattern.clone(), *progress_handler)
+        self.ref_type.update(
+            &self.path,
+            self.pattern.clone(),
+            &self.transport,
+            *progress_handler,
+        )
     }
 }
 
 fn update_git_branch(
     repo_path: &str,
     ref_match: StringFilter,
+    transport: &GitTransportOptions,
     progress_handler: &dyn ProgressHandler,
 ) -> Result<bool, String> {
     progress_handler.progress("checking current branch".to_string());
@@ -902,9 +914,10 @@ fn update_git_branch(
         // Fetch the updates for the remote branch
         progress_handler.progress(format!("fetching updates for {remote_branch_full}"));
         let mut git_fetch_cmd = TokioCommand::new("git");
-        git_fetch_cmd.arg("fetch");
+        git_fetch_cmd.args(transport.git_args("fetch"));
         git_fetch_cmd.arg(remote_name);
         git_fetch_cmd.arg(remote_branch);
+        git_fetch_cmd.envs(transport.env());
         git_fetch_cmd.current_dir(repo_path);
         git_fetch_cmd.stdout(std::process::Stdio::piped());
         git_fetch_cmd.stderr(std::process::Stdio::piped());
@@ -1016,8 +1029,9 @@ fn update_git_branch(
         progress_handler.progress("pulling latest changes".to_string());
 
         let mut git_pull_cmd = TokioCommand::new("git");
-        git_pull_cmd.arg("pull");
+        git_pull_cmd.args(transport.git_args("pull"));
         git_pull_cmd.arg("--ff-only");
+        git_pull_cmd.envs(transport.env());
         git_pull_cmd.current_dir(repo_path);
         git_pull_cmd.stdout(std::process::Stdio::piped());
         git_pull_cmd.stderr(std::process::Stdio::piped());
@@ -1057,6 +1071,7 @@ fn update_git_branch(
 fn update_git_tag(
     repo_path: &str,
     ref_match: StringFilter,
+    transport: &GitTransportOptions,
     progress_handler: &dyn ProgressHandler,
 ) -> Result<bool, String> {
     // Check if we're currently checked out on a branch
@@ -1124,8 +1139,9 @@ fn update_git_tag(
     // Fetch all the tags for the repository
     progress_handler.progress("fetching last tags".to_string());
     let mut git_fetch_tags_cmd = TokioCommand::new("git");
-    git_fetch_tags_cmd.arg("fetch");
+    git_fetch_tags_cmd.args(transport.git_args("fetch"));
     git_fetch_tags_cmd.arg("--tags");
+    git_fetch_tags_cmd.envs(transport.env());
     git_fetch_tags_cmd.current_dir(repo_path);
     git_fetch_tags_cmd.stdout(std::process::Stdio::piped());
     git_fetch_tags_cmd.stderr(std::process::Stdio::piped());
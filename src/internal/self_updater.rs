@@ -7,11 +7,11 @@ use std::process::exit;
 use std::process::Command as ProcessCommand;
 
 use lazy_static::lazy_static;
-use semver::Prerelease;
 use semver::Version;
 use serde::Deserialize;
 use tokio::process::Command as TokioCommand;
 
+use crate::internal::build::current_omni_version;
 use crate::internal::config::config;
 use crate::internal::config::up::github_release::UpConfigGithubRelease;
 use crate::internal::config::up::utils::run_progress;
@@ -30,24 +30,6 @@ use crate::omni_error;
 use crate::omni_info;
 
 lazy_static! {
-
-    static ref CURRENT_VERSION: Version = {
-        let mut version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
-        if !version.pre.is_empty() {
-            // Check if it starts with `rc` or `beta` or `alpha`, in which case
-            // we wanna keep them, otherwise we consider we're at the version,
-            // as otherwise semver would consider `1.0.0-5-xxxx` < `1.0.0`
-            if !(version.pre.starts_with("rc")
-                || version.pre.starts_with("beta")
-                || version.pre.starts_with("alpha"))
-            {
-                // Clear prerelease
-                version.pre = Prerelease::EMPTY;
-            }
-        }
-        version
-    };
-
     static ref INSTALLED_WITH_BREW: bool = BREW_INSTALL_DETAILS.0;
 
     static ref UPDATABLE_WITH_BREW: bool = BREW_INSTALL_DETAILS.1;
@@ -176,7 +158,7 @@ impl OmniRelease {
 
     fn is_newer(&self) -> bool {
         match Version::parse(self.version.as_str()) {
-            Ok(version) => version > *CURRENT_VERSION,
+            Ok(version) => version > *current_omni_version(),
             Err(_err) => {
                 omni_error!(format!("Failed to parse release version: {}", self.version));
                 false
@@ -44,6 +44,7 @@ cfg_if::cfg_if! {
                 ("OMNI_DATA_HOME".into(), None),
                 ("OMNI_CACHE_HOME".into(), None),
                 ("OMNI_CMD_FILE".into(), None),
+                ("OMNI_CONTAINER".into(), Some("none".into())),
                 ("HOMEBREW_PREFIX".into(), None),
                 (
                     "HOME".into(),
@@ -1,7 +1,12 @@
+use std::cell::Cell;
 use std::io::IsTerminal;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
 
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::internal::env::shell_is_interactive;
 
@@ -48,18 +53,148 @@ mod color_codes {
 }
 
 lazy_static! {
-    static ref COLORS_ENABLED: bool = enable_colors();
+    // TODO: find an approach to not depend on the stderr check for the
+    //       PS1 colorization
+    static ref COLORS_TERMINAL: bool =
+        std::io::stdout().is_terminal() || std::io::stderr().is_terminal();
+}
+
+/// The color mode of the process, as set by [`ColorMode::apply`]
+static COLOR_MODE: AtomicU8 = AtomicU8::new(ColorMode::Auto as u8);
+
+thread_local! {
+    static COLORS_OVERRIDE: Cell<Option<ColorCapability>> = const { Cell::new(None) };
+}
+
+/// When the output should be colored, as set through the `color`
+/// parameter of the configuration; the `NO_COLOR` and `CLICOLOR_FORCE`
+/// environment variables take precedence over the mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ColorMode {
+    /// Colors are used if the output is a terminal
+    #[default]
+    Auto = 0,
+    /// Colors are always used
+    Always = 1,
+    /// Colors are never used
+    Never = 2,
+}
+
+impl ColorMode {
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Sets the mode of the process
+    pub fn apply(self) {
+        COLOR_MODE.store(self as u8, Ordering::Relaxed);
+    }
+
+    /// Returns the mode of the process
+    pub fn current() -> Self {
+        match COLOR_MODE.load(Ordering::Relaxed) {
+            1 => Self::Always,
+            2 => Self::Never,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// Whether the output should be colored. The capability of the process
+/// is resolved from the environment and the [`ColorMode`], and can be
+/// overridden for the current thread with [`ColorCapability::scoped`],
+/// which allows to render with a given capability without depending on
+/// the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorCapability {
+    enabled: bool,
+}
+
+impl ColorCapability {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Returns the capability of the process
+    fn from_env() -> Self {
+        Self::resolve(Self::env_override(), ColorMode::current(), *COLORS_TERMINAL)
+    }
+
+    // http://bixense.com/clicolors/
+    fn env_override() -> Option<bool> {
+        match std::env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => return Some(false),
+            _ => {}
+        };
+        match std::env::var_os("CLICOLOR_FORCE") {
+            Some(value) if !value.is_empty() => return Some(true),
+            _ => {}
+        };
+        None
+    }
+
+    /// Decides whether to use colors, from the override of the
+    /// environment if any, then from the mode, the terminal detection
+    /// only being used in [`ColorMode::Auto`]
+    fn resolve(env_override: Option<bool>, mode: ColorMode, terminal: bool) -> Self {
+        if let Some(enabled) = env_override {
+            return Self::new(enabled);
+        }
+
+        Self::new(match mode {
+            ColorMode::Auto => terminal,
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        })
+    }
+
+    /// Returns the capability in use for the current thread, which is
+    /// the one of the process unless it has been overridden
+    pub fn current() -> Self {
+        COLORS_OVERRIDE
+            .with(|colors| colors.get())
+            .unwrap_or_else(Self::from_env)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the input as-is if colors are enabled, or without its
+    /// color codes otherwise
+    pub fn strip_if_disabled<T: ToString>(&self, input: T) -> String {
+        if self.enabled {
+            input.to_string()
+        } else {
+            strip_colors(input)
+        }
+    }
+
+    /// Uses this capability for the current thread until the returned
+    /// guard is dropped, at which point the previous one is restored
+    pub fn scoped(self) -> ColorCapabilityGuard {
+        let previous = COLORS_OVERRIDE.with(|colors| colors.replace(Some(self)));
+        ColorCapabilityGuard { previous }
+    }
 }
 
-pub fn colors_enabled() -> bool {
-    *COLORS_ENABLED
+/// Restores the color capability of the current thread when dropped;
+/// see [`ColorCapability::scoped`]
+#[must_use = "the capability is only overridden until the guard is dropped"]
+pub struct ColorCapabilityGuard {
+    previous: Option<ColorCapability>,
 }
 
-pub fn strip_colors_if_needed<T: ToString>(input: T) -> String {
-    if colors_enabled() {
-        input.to_string()
-    } else {
-        strip_colors(input)
+impl Drop for ColorCapabilityGuard {
+    fn drop(&mut self) {
+        COLORS_OVERRIDE.with(|colors| colors.set(self.previous));
     }
 }
 
@@ -68,21 +203,6 @@ pub fn strip_colors<T: ToString>(input: T) -> String {
     re.replace_all(&input.to_string(), "").to_string()
 }
 
-// http://bixense.com/clicolors/
-fn enable_colors() -> bool {
-    match std::env::var_os("NO_COLOR") {
-        Some(value) if !value.is_empty() => return false,
-        _ => {}
-    };
-    match std::env::var_os("CLICOLOR_FORCE") {
-        Some(value) if !value.is_empty() => return true,
-        _ => {}
-    };
-    // TODO: find an approach to not depend on the stderr check for the
-    //       PS1 colorization
-    std::io::stdout().is_terminal() || std::io::stderr().is_terminal()
-}
-
 #[allow(dead_code)]
 pub trait StringColor {
     fn colorize(&self, color_code: &str) -> String;
@@ -150,7 +270,7 @@ pub trait StringColor {
 // Implement the extension trait for the existing type
 impl<T: ToString> StringColor for T {
     fn colorize(&self, color: &str) -> String {
-        if colors_enabled() {
+        if ColorCapability::current().enabled() {
             self.force_colorize(color)
         } else {
             self.to_string()
@@ -162,7 +282,7 @@ impl<T: ToString> StringColor for T {
     }
 
     fn noncolormodifier(&self, modifier: &str, cancel_modifier: &str) -> String {
-        if colors_enabled() {
+        if ColorCapability::current().enabled() {
             self.force_noncolormodifier(modifier, cancel_modifier)
         } else {
             self.to_string()
@@ -390,3 +510,7 @@ impl<T: ToString> StringColor for T {
         format!("\x1B[{}m{}", color_codes::RESET, self.to_string())
     }
 }
+
+#[cfg(test)]
+#[path = "colors_test.rs"]
+mod tests;
@@ -0,0 +1,191 @@
+use super::*;
+
+use std::sync::Arc;
+use std::sync::Barrier;
+
+const RENDER_ITERATIONS: usize = 1000;
+
+/// Renders a message the same way the help and error messages are
+fn render(message: &str) -> String {
+    format!("{} {}", "error:".red(), message.bold())
+}
+
+mod color_capability {
+    use super::*;
+
+    #[test]
+    fn enabled_renders_color_codes() {
+        let _colors = ColorCapability::new(true).scoped();
+
+        assert!(ColorCapability::current().enabled());
+        assert_eq!(render("oops"), "\x1B[31merror:\x1B[39m \x1B[1moops\x1B[22m");
+        assert_eq!(
+            ColorCapability::current().strip_if_disabled("\x1B[31merror:\x1B[39m"),
+            "\x1B[31merror:\x1B[39m"
+        );
+    }
+
+    #[test]
+    fn disabled_renders_plain_text() {
+        let _colors = ColorCapability::new(false).scoped();
+
+        assert!(!ColorCapability::current().enabled());
+        assert_eq!(render("oops"), "error: oops");
+        assert_eq!(
+            ColorCapability::current().strip_if_disabled("\x1B[31merror:\x1B[39m"),
+            "error:"
+        );
+    }
+
+    #[test]
+    fn forced_colors_ignore_the_capability() {
+        let _colors = ColorCapability::new(false).scoped();
+
+        assert_eq!("omni:".force_light_cyan(), "\x1B[96momni:\x1B[39m");
+    }
+
+    #[test]
+    fn explicit_capability_strips_colors() {
+        let colored = render_with(true);
+
+        assert_eq!(
+            ColorCapability::new(true).strip_if_disabled(&colored),
+            colored
+        );
+        assert_eq!(
+            ColorCapability::new(false).strip_if_disabled(&colored),
+            "error: oops"
+        );
+    }
+
+    #[test]
+    fn guard_restores_the_previous_capability() {
+        let before = ColorCapability::current();
+
+        {
+            let _outer = ColorCapability::new(true).scoped();
+            {
+                let _inner = ColorCapability::new(false).scoped();
+                assert!(!ColorCapability::current().enabled());
+            }
+            assert!(ColorCapability::current().enabled());
+        }
+
+        assert_eq!(ColorCapability::current(), before);
+    }
+
+    fn render_with(enabled: bool) -> String {
+        let _colors = ColorCapability::new(enabled).scoped();
+        render("oops")
+    }
+}
+
+mod color_mode {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(ColorMode::from_str("auto"), Some(ColorMode::Auto));
+        assert_eq!(ColorMode::from_str("Always"), Some(ColorMode::Always));
+        assert_eq!(ColorMode::from_str("NEVER"), Some(ColorMode::Never));
+        assert_eq!(ColorMode::from_str("sometimes"), None);
+    }
+
+    #[test]
+    fn auto_follows_the_terminal() {
+        assert!(ColorCapability::resolve(None, ColorMode::Auto, true).enabled());
+        assert!(!ColorCapability::resolve(None, ColorMode::Auto, false).enabled());
+    }
+
+    #[test]
+    fn always_and_never_ignore_the_terminal() {
+        assert!(ColorCapability::resolve(None, ColorMode::Always, false).enabled());
+        assert!(!ColorCapability::resolve(None, ColorMode::Never, true).enabled());
+    }
+
+    #[test]
+    fn environment_takes_precedence_over_the_mode() {
+        assert!(!ColorCapability::resolve(Some(false), ColorMode::Always, true).enabled());
+        assert!(ColorCapability::resolve(Some(true), ColorMode::Never, false).enabled());
+    }
+
+    #[test]
+    fn no_color_takes_precedence_over_clicolor_force() {
+        temp_env::with_vars(
+            [("NO_COLOR", Some("1")), ("CLICOLOR_FORCE", Some("1"))],
+            || {
+                assert_eq!(ColorCapability::env_override(), Some(false));
+            },
+        );
+    }
+
+    #[test]
+    fn empty_variables_are_ignored() {
+        temp_env::with_vars(
+            [("NO_COLOR", Some("")), ("CLICOLOR_FORCE", Some(""))],
+            || {
+                assert_eq!(ColorCapability::env_override(), None);
+            },
+        );
+    }
+
+    #[test]
+    fn scoped_capability_takes_precedence_over_the_environment() {
+        temp_env::with_var("NO_COLOR", Some("1"), || {
+            let _colors = ColorCapability::new(true).scoped();
+            assert!(ColorCapability::current().enabled());
+        });
+    }
+}
+
+/// These tests run in parallel with each other by default, and render
+/// with different capabilities without interfering with one another
+mod parallel_rendering {
+    use super::*;
+
+    fn render_repeatedly(enabled: bool, expected: &str) {
+        let _colors = ColorCapability::new(enabled).scoped();
+        for _ in 0..RENDER_ITERATIONS {
+            assert_eq!(render("oops"), expected);
+        }
+    }
+
+    #[test]
+    fn with_colors() {
+        render_repeatedly(true, "\x1B[31merror:\x1B[39m \x1B[1moops\x1B[22m");
+    }
+
+    #[test]
+    fn without_colors() {
+        render_repeatedly(false, "error: oops");
+    }
+
+    #[test]
+    fn concurrent_threads() {
+        let barrier = Arc::new(Barrier::new(2));
+
+        let handles = [true, false]
+            .into_iter()
+            .map(|enabled| {
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    let _colors = ColorCapability::new(enabled).scoped();
+                    barrier.wait();
+                    (0..RENDER_ITERATIONS)
+                        .map(|_| render("oops"))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let results = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("thread should not panic"))
+            .collect::<Vec<_>>();
+
+        assert!(results[0]
+            .iter()
+            .all(|rendered| rendered == "\x1B[31merror:\x1B[39m \x1B[1moops\x1B[22m"));
+        assert!(results[1].iter().all(|rendered| rendered == "error: oops"));
+    }
+}
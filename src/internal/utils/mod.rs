@@ -5,3 +5,16 @@ pub(crate) use base62::encode as base62_encode;
 mod libc;
 #[cfg(target_os = "linux")]
 pub(crate) use libc::detect_libc;
+
+pub(crate) mod permissions;
+pub(crate) use permissions::create_dir_all;
+pub(crate) use permissions::PathKind;
+pub(crate) use permissions::PathPermissions;
+
+pub(crate) mod secrets;
+pub(crate) use secrets::is_secret_env_var;
+pub(crate) use secrets::mask_secret;
+pub(crate) use secrets::SECRET_PLACEHOLDER;
+
+pub(crate) mod short_id;
+pub(crate) use short_id::ShortIds;
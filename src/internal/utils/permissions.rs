@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+
+use crate::internal::config::global_config;
+use crate::internal::config::parser::PathsConfig;
+
+/// The mode of the directories holding private data
+const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// The mode of the files holding private data
+const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// The kind of path being created, which defines the permissions
+/// that the path gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathKind {
+    /// Paths that can be shared with other users of the system, e.g. the
+    /// shims or the installed tools; their permissions follow the
+    /// `paths` configuration.
+    Shared,
+    /// Paths holding credential-adjacent data, e.g. the cache database,
+    /// which are never accessible to other users of the system.
+    Private,
+}
+
+/// The permissions to apply to the directories and files created by omni,
+/// so that they do not depend on the umask of the process that happened
+/// to create them first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathPermissions {
+    dir_mode: u32,
+    file_mode: u32,
+}
+
+impl PathPermissions {
+    pub fn new(config: &PathsConfig, kind: PathKind) -> Self {
+        match kind {
+            PathKind::Shared if !config.private => Self {
+                dir_mode: config.dir_mode,
+                file_mode: config.file_mode,
+            },
+            _ => Self::private(),
+        }
+    }
+
+    /// Returns the permissions for the given kind of path, following the
+    /// global configuration; private paths do not depend on the configuration,
+    /// so that they can be created while the configuration is being loaded.
+    pub fn for_kind(kind: PathKind) -> Self {
+        match kind {
+            PathKind::Shared => Self::new(&global_config().paths, kind),
+            PathKind::Private => Self::private(),
+        }
+    }
+
+    pub fn private() -> Self {
+        Self {
+            dir_mode: PRIVATE_DIR_MODE,
+            file_mode: PRIVATE_FILE_MODE,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        self.dir_mode & 0o077 == 0 && self.file_mode & 0o077 == 0
+    }
+
+    /// Create the given directory and all its missing parents, setting
+    /// the permissions of each directory that gets created. If the directory
+    /// already exists and is meant to hold private data, its permissions
+    /// are repaired if they are too open.
+    #[cfg(unix)]
+    pub fn create_dir_all(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        use std::os::unix::fs::DirBuilderExt;
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = path.as_ref();
+
+        // Identify the directories that will be created, so that
+        // we do not change the permissions of existing parents
+        let missing = path
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            if self.is_private() {
+                self.repair_dir(path)?;
+            }
+            return Ok(());
+        }
+
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(self.dir_mode)
+            .create(path)?;
+
+        // The mode given to the builder is restricted by the umask,
+        // so we set the permissions explicitly
+        for dir in missing.iter() {
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(self.dir_mode))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn create_dir_all(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    /// Repair the permissions of an existing directory if they
+    /// allow more than the expected permissions.
+    #[cfg(unix)]
+    fn repair_dir(&self, path: &Path) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(path)?.permissions().mode() & 0o777;
+        if mode & !self.dir_mode != 0 {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(self.dir_mode))?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the given file, truncating it if it already exists,
+    /// and set its permissions.
+    #[cfg(unix)]
+    pub fn create_file(&self, path: impl AsRef<Path>) -> io::Result<File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(self.file_mode)
+            .open(path)?;
+        self.apply_to_file(&file)?;
+
+        Ok(file)
+    }
+
+    #[cfg(not(unix))]
+    pub fn create_file(&self, path: impl AsRef<Path>) -> io::Result<File> {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+    }
+
+    /// Set the permissions of the given file, which might have been
+    /// created by another process or library than omni.
+    #[cfg(unix)]
+    pub fn apply_to_file(&self, file: &File) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        file.set_permissions(std::fs::Permissions::from_mode(self.file_mode))
+    }
+
+    #[cfg(not(unix))]
+    pub fn apply_to_file(&self, _file: &File) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Create the given directory and all its missing parents, with the
+/// permissions for the given kind of path.
+pub fn create_dir_all(path: impl AsRef<Path>, kind: PathKind) -> io::Result<()> {
+    PathPermissions::for_kind(kind).create_dir_all(path)
+}
+
+#[cfg(test)]
+#[path = "permissions_test.rs"]
+mod tests;
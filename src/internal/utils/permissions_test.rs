@@ -0,0 +1,150 @@
+use super::*;
+
+use std::os::unix::fs::PermissionsExt;
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path)
+        .expect("path should exist")
+        .permissions()
+        .mode()
+        & 0o777
+}
+
+fn config(dir_mode: u32, file_mode: u32, private: bool) -> PathsConfig {
+    PathsConfig {
+        dir_mode,
+        file_mode,
+        private,
+        ..PathsConfig::default()
+    }
+}
+
+mod path_permissions {
+    use super::*;
+
+    #[test]
+    fn default_config_creates_tree_with_default_modes() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let permissions = PathPermissions::new(&PathsConfig::default(), PathKind::Shared);
+
+        let dir = root.path().join("a/b/c");
+        permissions.create_dir_all(&dir).expect("create dirs");
+        let file = dir.join("file");
+        permissions.create_file(&file).expect("create file");
+
+        assert_eq!(mode(&root.path().join("a")), 0o755);
+        assert_eq!(mode(&root.path().join("a/b")), 0o755);
+        assert_eq!(mode(&dir), 0o755);
+        assert_eq!(mode(&file), 0o644);
+    }
+
+    #[test]
+    fn custom_config_creates_tree_with_custom_modes() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let permissions = PathPermissions::new(&config(0o775, 0o664, false), PathKind::Shared);
+
+        let dir = root.path().join("a/b");
+        permissions.create_dir_all(&dir).expect("create dirs");
+        let file = dir.join("file");
+        permissions.create_file(&file).expect("create file");
+
+        assert_eq!(mode(&root.path().join("a")), 0o775);
+        assert_eq!(mode(&dir), 0o775);
+        assert_eq!(mode(&file), 0o664);
+    }
+
+    #[test]
+    fn custom_modes_are_not_restricted_by_umask() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let permissions = PathPermissions::new(&config(0o777, 0o666, false), PathKind::Shared);
+
+        let dir = root.path().join("open");
+        permissions.create_dir_all(&dir).expect("create dirs");
+        let file = dir.join("file");
+        permissions.create_file(&file).expect("create file");
+
+        assert_eq!(mode(&dir), 0o777);
+        assert_eq!(mode(&file), 0o666);
+    }
+
+    #[test]
+    fn private_preset_creates_tree_with_private_modes() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let permissions = PathPermissions::new(&config(0o775, 0o664, true), PathKind::Shared);
+
+        let dir = root.path().join("a/b");
+        permissions.create_dir_all(&dir).expect("create dirs");
+        let file = dir.join("file");
+        permissions.create_file(&file).expect("create file");
+
+        assert_eq!(mode(&root.path().join("a")), 0o700);
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(&file), 0o600);
+    }
+
+    #[test]
+    fn private_kind_ignores_config() {
+        let permissions = PathPermissions::new(&config(0o777, 0o666, false), PathKind::Private);
+
+        assert_eq!(permissions, PathPermissions::private());
+    }
+
+    #[test]
+    fn existing_parents_are_left_untouched() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        std::fs::set_permissions(root.path(), std::fs::Permissions::from_mode(0o751))
+            .expect("set permissions");
+
+        PathPermissions::private()
+            .create_dir_all(root.path().join("private"))
+            .expect("create dirs");
+
+        assert_eq!(mode(root.path()), 0o751);
+        assert_eq!(mode(&root.path().join("private")), 0o700);
+    }
+
+    #[test]
+    fn existing_private_dir_too_open_is_repaired() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let dir = root.path().join("private");
+        std::fs::create_dir(&dir).expect("create dir");
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777))
+            .expect("set permissions");
+
+        PathPermissions::private()
+            .create_dir_all(&dir)
+            .expect("create dirs");
+
+        assert_eq!(mode(&dir), 0o700);
+    }
+
+    #[test]
+    fn existing_private_dir_more_restrictive_is_kept() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let dir = root.path().join("private");
+        std::fs::create_dir(&dir).expect("create dir");
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o500))
+            .expect("set permissions");
+
+        PathPermissions::private()
+            .create_dir_all(&dir)
+            .expect("create dirs");
+
+        assert_eq!(mode(&dir), 0o500);
+    }
+
+    #[test]
+    fn existing_shared_dir_is_not_repaired() {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let dir = root.path().join("shared");
+        std::fs::create_dir(&dir).expect("create dir");
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777))
+            .expect("set permissions");
+
+        PathPermissions::new(&PathsConfig::default(), PathKind::Shared)
+            .create_dir_all(&dir)
+            .expect("create dirs");
+
+        assert_eq!(mode(&dir), 0o777);
+    }
+}
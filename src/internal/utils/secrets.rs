@@ -0,0 +1,42 @@
+//! Masking of the secret values shown by omni, whether they are the
+//! values of sensitive parameters or of environment variables whose
+//! names look like secrets
+
+/// The placeholder shown instead of a secret value
+pub const SECRET_PLACEHOLDER: &str = "********";
+
+/// Patterns of the names of the environment variables whose values are
+/// considered secret; the matching is case-insensitive
+const SECRET_PATTERNS: &[&str] = &[
+    "*TOKEN*",
+    "*SECRET*",
+    "*PASSWORD*",
+    "*PASSWD*",
+    "*CREDENTIAL*",
+    "*API_KEY*",
+    "*ACCESS_KEY*",
+    "*PRIVATE_KEY*",
+];
+
+/// Whether the value of the given environment variable is considered
+/// secret, and should thus be masked when shown
+pub fn is_secret_env_var(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_PATTERNS
+        .iter()
+        .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(&name)))
+}
+
+/// Returns the value as it can be shown, i.e. replaced by the
+/// placeholder if it is secret
+pub fn mask_secret(value: &str, secret: bool) -> String {
+    if secret {
+        SECRET_PLACEHOLDER.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+#[path = "secrets_test.rs"]
+mod tests;
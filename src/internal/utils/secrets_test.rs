@@ -0,0 +1,38 @@
+use super::*;
+
+mod is_secret_env_var {
+    use super::*;
+
+    #[test]
+    fn secret_names() {
+        for name in [
+            "GITHUB_TOKEN",
+            "db_password",
+            "AWS_SECRET_ACCESS_KEY",
+            "MY_API_KEY",
+        ] {
+            assert!(is_secret_env_var(name), "{name} should be secret");
+        }
+    }
+
+    #[test]
+    fn other_names() {
+        for name in ["PATH", "HOME", "RUBY_VERSION"] {
+            assert!(!is_secret_env_var(name), "{name} should not be secret");
+        }
+    }
+}
+
+mod mask_secret {
+    use super::*;
+
+    #[test]
+    fn secret_value() {
+        assert_eq!(mask_secret("hunter2", true), SECRET_PLACEHOLDER);
+    }
+
+    #[test]
+    fn other_value() {
+        assert_eq!(mask_secret("hunter2", false), "hunter2");
+    }
+}
@@ -0,0 +1,138 @@
+use thiserror::Error;
+
+use crate::internal::utils::base62_encode;
+
+/// The minimum length of a short id; shorter inputs are never resolved
+/// as short ids, so that an id cannot be matched by chance
+pub const SHORT_ID_MIN_LEN: usize = 7;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ShortIdError {
+    #[error("unknown id: {0}")]
+    Unknown(String),
+    #[error("ambiguous id {input}, could be any of: {}", candidates.join(", "))]
+    Ambiguous {
+        input: String,
+        candidates: Vec<String>,
+    },
+}
+
+/// The short forms of a set of known ids, to show to users instead of
+/// the full ids, and to let them type ids more easily.
+///
+/// The short form of an id is a prefix of the base62-encoded hash of
+/// the id, of at least `SHORT_ID_MIN_LEN` characters; the prefix is
+/// lengthened as needed to stay unique among the known ids, so that a
+/// short form shown to the user always resolves back to its full id.
+/// Ids are compared case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct ShortIds {
+    /// The known ids, along with their hash
+    ids: Vec<(String, String)>,
+}
+
+impl ShortIds {
+    pub fn new<I, S>(ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::from_hashes(ids.into_iter().map(|id| {
+            let id = id.as_ref().to_string();
+            let hash = id_hash(&id);
+            (id, hash)
+        }))
+    }
+
+    fn from_hashes(ids: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut short_ids = Self::default();
+        for (id, hash) in ids {
+            if !short_ids.is_known(&id) {
+                short_ids.ids.push((id, hash));
+            }
+        }
+        short_ids
+    }
+
+    fn is_known(&self, id: &str) -> bool {
+        self.ids
+            .iter()
+            .any(|(known, _)| known.eq_ignore_ascii_case(id))
+    }
+
+    /// Returns the short form of the given id; the id does not need to
+    /// be known, its short form is lengthened the same way to stay
+    /// unique among the known ids
+    pub fn short(&self, id: &str) -> String {
+        let hash = self
+            .ids
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(id))
+            .map(|(_, hash)| hash.clone())
+            .unwrap_or_else(|| id_hash(id));
+
+        let len = self
+            .ids
+            .iter()
+            .filter(|(known, _)| !known.eq_ignore_ascii_case(id))
+            .map(|(_, known_hash)| common_prefix_len(&hash, known_hash) + 1)
+            .fold(SHORT_ID_MIN_LEN, usize::max)
+            .min(hash.len());
+
+        hash[..len].to_string()
+    }
+
+    /// Returns the given id followed by its short form, which is how ids
+    /// are shown to users so that they can both recognize the id and
+    /// type its short form
+    pub fn display(&self, id: &str) -> String {
+        format!("{} ({})", id, self.short(id))
+    }
+
+    /// Resolves the given input, either a full id or the short form of
+    /// an id, to the full id of a known id
+    pub fn resolve(&self, input: &str) -> Result<String, ShortIdError> {
+        if let Some((id, _)) = self
+            .ids
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(input))
+        {
+            return Ok(id.clone());
+        }
+
+        if input.len() < SHORT_ID_MIN_LEN {
+            return Err(ShortIdError::Unknown(input.to_string()));
+        }
+
+        let candidates = self
+            .ids
+            .iter()
+            .filter(|(_, hash)| hash.starts_with(input))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        match candidates.len() {
+            0 => Err(ShortIdError::Unknown(input.to_string())),
+            1 => Ok(candidates[0].clone()),
+            _ => Err(ShortIdError::Ambiguous {
+                input: input.to_string(),
+                candidates,
+            }),
+        }
+    }
+}
+
+/// Returns the base62-encoded hash of the given id, from which its
+/// short form is taken
+fn id_hash(id: &str) -> String {
+    let hash = blake3::hash(id.to_ascii_lowercase().as_bytes());
+    base62_encode(hash.as_bytes())
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count()
+}
+
+#[cfg(test)]
+#[path = "short_id_test.rs"]
+mod tests;
@@ -0,0 +1,135 @@
+use super::*;
+
+/// Creates short ids for the given ids and their artificial hashes
+fn with_hashes(ids: &[(&str, &str)]) -> ShortIds {
+    ShortIds::from_hashes(
+        ids.iter()
+            .map(|(id, hash)| (id.to_string(), hash.to_string())),
+    )
+}
+
+mod short_ids {
+    use super::*;
+
+    #[test]
+    fn short_id_round_trip() {
+        let ids = [
+            "github.com:xaf/omni",
+            "github.com:xaf/omni-docs",
+            "jolly-red-fox:1234",
+        ];
+        let short_ids = ShortIds::new(ids);
+
+        for id in ids {
+            let short = short_ids.short(id);
+            assert_eq!(short.len(), SHORT_ID_MIN_LEN);
+            assert!(short.chars().all(|c| c.is_ascii_alphanumeric()));
+            assert_eq!(short_ids.resolve(&short), Ok(id.to_string()));
+        }
+    }
+
+    #[test]
+    fn short_id_is_stable() {
+        let id = "github.com:xaf/omni";
+        let alone = ShortIds::new([id]).short(id);
+
+        assert_eq!(ShortIds::default().short(id), alone);
+        assert_eq!(ShortIds::new([id, "github.com:xaf/other"]).short(id), alone);
+        assert_eq!(ShortIds::new([id]).short("GitHub.com:xaf/omni"), alone);
+    }
+
+    #[test]
+    fn resolves_full_ids() {
+        let short_ids = ShortIds::new(["github.com:xaf/omni"]);
+
+        assert_eq!(
+            short_ids.resolve("github.com:xaf/omni"),
+            Ok("github.com:xaf/omni".to_string())
+        );
+        assert_eq!(
+            short_ids.resolve("GITHUB.COM:xaf/omni"),
+            Ok("github.com:xaf/omni".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_longer_prefixes() {
+        let short_ids = ShortIds::new(["github.com:xaf/omni"]);
+        let hash = id_hash("github.com:xaf/omni");
+
+        assert_eq!(
+            short_ids.resolve(&hash[..SHORT_ID_MIN_LEN + 3]),
+            Ok("github.com:xaf/omni".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_ids() {
+        let short_ids = ShortIds::new(["github.com:xaf/omni"]);
+        let short = short_ids.short("github.com:xaf/omni");
+
+        assert_eq!(
+            short_ids.resolve("github.com:xaf/other"),
+            Err(ShortIdError::Unknown("github.com:xaf/other".to_string()))
+        );
+        // Prefixes shorter than a short id are not resolved
+        assert_eq!(
+            short_ids.resolve(&short[..SHORT_ID_MIN_LEN - 1]),
+            Err(ShortIdError::Unknown(
+                short[..SHORT_ID_MIN_LEN - 1].to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn ambiguous_short_ids() {
+        let short_ids = with_hashes(&[
+            ("first", "abcdefghij123"),
+            ("second", "abcdefghij456"),
+            ("third", "zyxwvutsrq789"),
+        ]);
+
+        assert_eq!(
+            short_ids.resolve("abcdefg"),
+            Err(ShortIdError::Ambiguous {
+                input: "abcdefg".to_string(),
+                candidates: vec!["first".to_string(), "second".to_string()],
+            })
+        );
+        assert_eq!(short_ids.resolve("zyxwvut"), Ok("third".to_string()));
+    }
+
+    #[test]
+    fn lengthens_short_ids_on_collision() {
+        let short_ids = with_hashes(&[
+            ("first", "abcdefghij123"),
+            ("second", "abcdefghij456"),
+            ("third", "zyxwvutsrq789"),
+        ]);
+
+        assert_eq!(short_ids.short("first"), "abcdefghij1");
+        assert_eq!(short_ids.short("second"), "abcdefghij4");
+        assert_eq!(short_ids.short("third"), "zyxwvut");
+
+        for id in ["first", "second", "third"] {
+            assert_eq!(short_ids.resolve(&short_ids.short(id)), Ok(id.to_string()));
+        }
+    }
+
+    #[test]
+    fn display_shows_both_forms() {
+        let short_ids = with_hashes(&[("github.com:xaf/omni", "abcdefghij123")]);
+
+        assert_eq!(
+            short_ids.display("github.com:xaf/omni"),
+            "github.com:xaf/omni (abcdefg)"
+        );
+    }
+
+    #[test]
+    fn short_id_never_exceeds_the_hash() {
+        let short_ids = with_hashes(&[("first", "abcdefghij"), ("second", "abcdefghij")]);
+
+        assert_eq!(short_ids.short("first"), "abcdefghij");
+    }
+}
@@ -1,21 +1,44 @@
 use crate::internal::cache::WorkdirsCache;
 use crate::internal::env::shell_is_interactive;
+use crate::internal::git::Org;
 use crate::internal::git::ORG_LOADER;
 use crate::internal::git_env;
 use crate::internal::user_interface::StringColor;
+use crate::internal::utils::ShortIds;
 use crate::internal::workdir;
 use crate::omni_error;
 use crate::omni_info;
 
+/// Returns the configured organizations hosting the repository at
+/// the given path, if any
+pub fn hosting_orgs<T: AsRef<str>>(path: T) -> Vec<&'static Org> {
+    let git = git_env(path.as_ref());
+    match git.origin() {
+        Some(origin) if git.in_repo() => ORG_LOADER
+            .orgs()
+            .iter()
+            .filter(|org| org.hosts_repo(origin))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the short forms of the ids of the work directories known
+/// to the cache, which are the ids shown to users
+pub fn workdir_short_ids() -> ShortIds {
+    ShortIds::new(WorkdirsCache::get().known_ids())
+}
+
+/// Returns the given work directory id along with its short form, to
+/// show to users
+pub fn display_workdir_id(workdir_id: &str) -> String {
+    workdir_short_ids().display(workdir_id)
+}
+
 pub fn is_trusted<T: AsRef<str>>(path: T) -> bool {
     let path = path.as_ref();
-    let git = git_env(path);
-    if git.in_repo() && git.has_origin() {
-        for org in ORG_LOADER.orgs() {
-            if org.config.trusted && org.hosts_repo(git.origin().unwrap()) {
-                return true;
-            }
-        }
+    if hosting_orgs(path).iter().any(|org| org.config.trusted) {
+        return true;
     }
 
     let workdir = workdir(path);
@@ -42,7 +65,7 @@ pub fn is_trusted_or_ask(path: &str, ask: String) -> bool {
         choices.insert(0, ('a', "Yes, always (add to trusted directories)"));
         omni_info!(format!(
             "The directory {} is not in your trusted directories.",
-            workdir_id.light_blue()
+            display_workdir_id(&workdir_id).light_blue()
         ));
         omni_info!(format!(
             "{} all repositories in a trusted organization are automatically trusted.",
@@ -2,14 +2,21 @@ use std::env;
 use std::process::exit;
 
 mod internal;
+use internal::cache::CacheBatch;
 use internal::command_loader;
 use internal::commands::base::BuiltinCommand;
+use internal::commands::completion::dynamic_complete;
+use internal::commands::completion::parse_dynamic_complete_args;
 use internal::commands::loader::set_lookup_local_first;
+use internal::commands::ConfigCommand;
 use internal::commands::HelpCommand;
 use internal::commands::HookEnvCommand;
 use internal::commands::HookInitCommand;
 use internal::commands::HookUuidCommand;
+use internal::config;
+use internal::config::config_loader;
 use internal::config::ensure_bootstrap;
+use internal::config::up::utils::clear_credentials_cache;
 use internal::config::up::utils::handle_shims;
 use internal::config::up::utils::AskPassRequest;
 use internal::env::tmpdir_cleanup;
@@ -24,6 +31,7 @@ use internal::StringColor;
 struct MainArgs {
     only_check_exists: bool,
     lookup_local_commands_first: bool,
+    no_runner: bool,
     args: Vec<String>,
 }
 
@@ -67,8 +75,8 @@ impl MainArgs {
                 clap::Arg::new("askpass")
                     .long("askpass")
                     .short('A')
-                    .num_args(2..=3)
-                    .value_names(["prompt", "socket path", "prompt type"]),
+                    .num_args(2..=4)
+                    .value_names(["prompt", "socket path", "prompt type", "identity"]),
             )
             .arg(
                 clap::Arg::new("local")
@@ -76,6 +84,11 @@ impl MainArgs {
                     .short('l')
                     .action(clap::ArgAction::SetTrue),
             )
+            .arg(
+                clap::Arg::new("no-runner")
+                    .long("no-runner")
+                    .action(clap::ArgAction::SetTrue),
+            )
             .arg(
                 clap::Arg::new("args")
                     .action(clap::ArgAction::Append)
@@ -124,17 +137,14 @@ impl MainArgs {
 
         if let Some(askpass) = matches.get_many::<String>("askpass") {
             let askpass = askpass.collect::<Vec<_>>();
-            if askpass.len() < 2 || askpass.len() > 3 {
+            if askpass.len() < 2 || askpass.len() > 4 {
                 exit(1);
             }
 
             let prompt = askpass[0].as_str();
-            let prompt_type = if askpass.len() == 3 {
-                askpass[2].as_str()
-            } else {
-                ""
-            };
-            let request = AskPassRequest::new(prompt, prompt_type);
+            let prompt_type = askpass.get(2).map(|s| s.as_str()).unwrap_or("");
+            let identity = askpass.get(3).map(|s| s.as_str()).unwrap_or("");
+            let request = AskPassRequest::new(prompt, prompt_type, identity);
 
             let socket_path = askpass[1].as_str();
             match request.send(socket_path) {
@@ -174,6 +184,7 @@ impl MainArgs {
         Self {
             only_check_exists: *matches.get_one::<bool>("exists").unwrap_or(&false),
             lookup_local_commands_first: *matches.get_one::<bool>("local").unwrap_or(&false),
+            no_runner: *matches.get_one::<bool>("no-runner").unwrap_or(&false),
             args,
         }
     }
@@ -190,6 +201,15 @@ fn complete_omni_subcommand(argv: &[String]) {
     }
 }
 
+fn complete_omni_subcommand_dynamic(argv: &[String]) {
+    let (shell, words) = parse_dynamic_complete_args(argv);
+
+    match dynamic_complete(shell, words) {
+        Ok(_) => exit(0),
+        Err(_) => exit(1),
+    }
+}
+
 fn run_omni_subcommand(parsed: &MainArgs) {
     if parsed.args[0] == "hook" {
         // For hooks, we want a fast path that doesn't load all the commands;
@@ -235,11 +255,28 @@ fn run_omni_subcommand(parsed: &MainArgs) {
         env::set_var("OMNI_LOCAL_LOOKUP", "1");
     }
 
+    if parsed.no_runner {
+        // Let the configuration commands know that they should be run
+        // locally instead of through their runner
+        env::set_var("OMNI_NO_RUNNER", "1");
+    }
+
     if !parsed.only_check_exists {
         // Ensures that omni has been bootstrapped
         ensure_bootstrap();
     }
 
+    // Use the color mode from the configuration, unless the environment
+    // already decides whether to use colors
+    config(".").color.apply();
+
+    // Refuse to run commands with a configuration requiring a more recent
+    // version of omni; this is not done for the hooks, that need to keep
+    // working in the shell until omni is upgraded
+    if !parsed.only_check_exists {
+        config_loader(".").report_min_omni_version_errors();
+    }
+
     let command_loader = command_loader(".");
     if let Some((omni_cmd, called_as, argv)) = command_loader.to_serve(&parsed.args) {
         if parsed.only_check_exists {
@@ -297,6 +334,16 @@ fn run_omni_subcommand(parsed: &MainArgs) {
         parsed.args.join(" ")
     );
 
+    if let Some(name) = ConfigCommand::filtered_out(&parsed.args) {
+        eprintln!(
+            "{} {} command {} is defined but excluded by the {} configuration",
+            "omni:".light_cyan(),
+            "hint:".light_yellow(),
+            name.light_blue(),
+            "commands_filter".light_blue(),
+        );
+    }
+
     if let Some((omni_cmd, called_as, argv)) = command_loader.find_command(&parsed.args) {
         set_cleanup_handler();
         omni_cmd.exec(argv, Some(called_as));
@@ -308,6 +355,10 @@ fn run_omni_subcommand(parsed: &MainArgs) {
 
 fn set_cleanup_handler() {
     ctrlc::set_handler(move || {
+        // Keep the cache writes done so far, since the writes of a
+        // batch are only committed at checkpoints
+        let _ = CacheBatch::checkpoint();
+        clear_credentials_cache();
         tmpdir_cleanup();
 
         // Exit the process with a non-zero status code
@@ -325,6 +376,10 @@ fn main() {
         complete_omni_subcommand(&args[1..]);
     }
 
+    if !args.is_empty() && args[0] == "__complete" {
+        complete_omni_subcommand_dynamic(&args[1..]);
+    }
+
     let main_args = MainArgs::parse(args.clone());
     run_omni_subcommand(&main_args);
 }
@@ -78,6 +78,9 @@ omni_setup() {
   # Let's unset other variables that could influence the tests
   unset HOMEBREW_PREFIX
 
+  # Do not let the container omni might be running in change its paths
+  export OMNI_CONTAINER=none
+
   # Override global git configuration
   git config --global user.email "omni@potent.tool"
   git config --global user.name "omni"
@@ -291,3 +291,60 @@ test_cd_locate() {
   [ $(pwd) = "${HOME}/git/github.com/test1org/test1repo" ]
 }
 
+
+# bats test_tags=omni:cd,omni:cd:print-target
+@test "[omni_cd=15] omni cd --print-target only prints the target directory on stdout" {
+  setup_omni_config
+
+  # Call the binary directly, without the shell integration
+  target="$("${OMNI_TEST_BIN}" cd --print-target "test1org/test1repo" 2>/dev/null 3>&-)"
+  [ "$?" -eq 0 ]
+
+  echo "target is '${target}', supposed to be '${HOME}/git/github.com/test1org/test1repo'"
+  [ "${target}" = "${HOME}/git/github.com/test1org/test1repo" ]
+
+  # Relative paths are resolved by omni
+  target="$("${OMNI_TEST_BIN}" cd --print-target "git/github.com/test2org" 2>/dev/null 3>&-)"
+  [ "$?" -eq 0 ]
+
+  echo "target is '${target}', supposed to be '${HOME}/git/github.com/test2org'"
+  [ "${target}" = "${HOME}/git/github.com/test2org" ]
+}
+
+# bats test_tags=omni:cd,omni:cd:print-target
+@test "[omni_cd=16] omni cd --print-target fails with nothing on stdout if the directory is not found" {
+  setup_omni_config
+
+  run bash -c '"${OMNI_TEST_BIN}" cd --print-target "doesnotexist" 2>/dev/null 3>&-'
+  echo "STATUS: $status"
+  echo "OUTPUT: $output"
+  [ "$status" -ne 0 ]
+  [ -z "$output" ]
+
+  run bash -c '"${OMNI_TEST_BIN}" cd --print-target "doesnotexist" 2>&1 >/dev/null 3>&-'
+  echo "STDERR: $output"
+  [[ "$output" == *"No such work directory"* ]]
+}
+
+# bats test_tags=omni:cd,omni:cd:print-target
+@test "[omni_cd=17] omni cd works with the minimal cd integration" {
+  setup_omni_config
+
+  # Replace the full shell integration by the minimal one
+  unset -f omni
+  eval "$("${OMNI_TEST_BIN}" hook init --cd-only bash)"
+
+  # Check that the current directory is home
+  [ $(pwd) = "${HOME}" ]
+
+  omni cd "test1org/test1repo" 3>&-
+  [ "$?" -eq 0 ]
+
+  echo "PWD is $(pwd), supposed to be in ${HOME}/git/github.com/test1org/test1repo"
+  [ $(pwd) = "${HOME}/git/github.com/test1org/test1repo" ]
+
+  # Failing to find the directory should not change it
+  run omni cd "doesnotexist" 3>&-
+  [ "$status" -ne 0 ]
+  [ $(pwd) = "${HOME}/git/github.com/test1org/test1repo" ]
+}
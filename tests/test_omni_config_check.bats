@@ -78,3 +78,36 @@ EOF
 @test "[omni_config_check=5] omni config check fails for many issues (json)" {
   validate_test_output omni/config-check-many-issues-json.txt exit_code=1 omni config check --output json --config-file "${FIXTURES_DIR}/omni/config-check-broken-input.txt"
 }
+
+# bats test_tags=omni:config,omni:config:check
+@test "[omni_config_check=6] omni config check reports failing syntax tests of commands" {
+  cat > .omni.yaml <<EOF
+commands:
+  deploy:
+    run: echo "deploying to \$OMNI_ARG_ENV_VALUE"
+    syntax:
+      parameters:
+        - name: --env
+          type: enum(dev, staging, prod)
+    tests:
+      - args: ["--env", "prod"]
+        expect: ok
+        expect_env:
+          OMNI_ARG_ENV_VALUE: prod
+      - args: ["--env", "bogus"]
+        expect: error
+        error_contains: "possible values"
+      - args: ["--env", "bogus"]
+        expect: ok
+EOF
+
+  run omni config check --config-file .omni.yaml
+  echo "STATUS: $status"
+  echo "OUTPUT: $output"
+  [ "$status" -eq 1 ]
+
+  [[ "${output}" == *".omni.yaml:0:U203:syntax test #2 failed for command 'deploy' with args [--env bogus]: expected the arguments to be accepted, but found error: invalid value 'bogus' for '--env <env>'"* ]]
+  [[ "${output}" != *"syntax test #0"* ]]
+  [[ "${output}" != *"syntax test #1"* ]]
+  [[ "${output}" == *"syntax tests: 2 passed, 1 failed"* ]]
+}